@@ -0,0 +1,359 @@
+// crates/audio-player/src/ab_loop.rs
+// A-B repeat: a region of the loaded track plays over and over, sample
+// accurate. The first pass through the region is recorded, so following passes
+// replay it without seeking the decoder; regions too long to keep in memory,
+// or entered part way, seek back to their start instead. Boundaries are
+// crossed with a short fade to avoid clicks. The region is global and read by
+// every playing source, so changes apply immediately.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rodio::source::SeekError;
+use rodio::Source;
+use types::ui::player_details::LoopRegion;
+
+/// Shortest region looped
+pub const MIN_LOOP_SECS: f64 = 0.1;
+/// Longest region replayed from memory
+const MAX_RECORDED_SECS: u64 = 60;
+/// Length of the fades at the loop point
+const FADE_MS: u64 = 5;
+
+static REGION: Mutex<Option<LoopRegion>> = Mutex::new(None);
+/// Bumped on every change of the region
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+/// Bumped every time playback jumps back to the start of the region
+static WRAPS: AtomicU64 = AtomicU64::new(0);
+
+/// Loop `region`, or play straight through with `None`
+pub fn set_region(region: Option<LoopRegion>) {
+    if let Ok(mut current) = REGION.lock() {
+        *current = region;
+    }
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+pub fn region() -> Option<LoopRegion> {
+    REGION.lock().ok().and_then(|region| *region)
+}
+
+/// Number of times playback went back to the start of a region
+pub fn wraps() -> u64 {
+    WRAPS.load(Ordering::SeqCst)
+}
+
+/// Samples read by the looper
+trait Input {
+    fn pull(&mut self) -> Option<f32>;
+    fn seek(&mut self, pos: Duration) -> bool;
+}
+
+impl<S: Source> Input for S {
+    fn pull(&mut self) -> Option<f32> {
+        self.next()
+    }
+
+    fn seek(&mut self, pos: Duration) -> bool {
+        self.try_seek(pos).is_ok()
+    }
+}
+
+/// Loop state over interleaved samples; positions are sample indices
+struct Looper {
+    channels: u64,
+    sample_rate: u64,
+    /// Position of the next sample
+    position: u64,
+    region: Option<(u64, u64)>,
+    /// The region from its start up to `position`, while `recording`
+    recorded: Vec<f32>,
+    recording: bool,
+    /// Index into `recorded` of the next sample while replaying it
+    replay: Option<usize>,
+    /// Whether playback came to the current pass by looping
+    wrapped: bool,
+}
+
+impl Looper {
+    fn new(channels: u16, sample_rate: u32) -> Self {
+        Self {
+            channels: channels.max(1) as u64,
+            sample_rate: sample_rate.max(1) as u64,
+            position: 0,
+            region: None,
+            recorded: Vec::new(),
+            recording: false,
+            replay: None,
+            wrapped: false,
+        }
+    }
+
+    /// Sample index of the frame at `secs`
+    fn to_samples(&self, secs: f64) -> u64 {
+        (secs.max(0.0) * self.sample_rate as f64).round() as u64 * self.channels
+    }
+
+    fn fade_len(&self) -> u64 {
+        (self.sample_rate * FADE_MS / 1000).max(1) * self.channels
+    }
+
+    fn forget(&mut self) {
+        self.recorded = Vec::new();
+        self.recording = false;
+        self.wrapped = false;
+    }
+
+    /// Switch to `region`, leaving the input where playback stands
+    fn set_region(&mut self, region: Option<LoopRegion>, input: &mut impl Input) {
+        if self.replay.take().is_some() {
+            let secs = (self.position / self.channels) as f64 / self.sample_rate as f64;
+            input.seek(Duration::from_secs_f64(secs));
+        }
+        self.forget();
+        self.region = region
+            .filter(|r| r.validate().is_ok())
+            .map(|r| (self.to_samples(r.start), self.to_samples(r.end)))
+            .filter(|(start, end)| end > start);
+    }
+
+    fn seek(&mut self, pos: Duration) {
+        self.replay = None;
+        self.forget();
+        self.position = self.to_samples(pos.as_secs_f64());
+    }
+
+    /// Go back to the start of the region; false when that failed
+    fn wrap(&mut self, start: u64, input: &mut impl Input) -> bool {
+        if self.recording && !self.recorded.is_empty() {
+            self.replay = Some(0);
+        } else {
+            let secs = (start / self.channels) as f64 / self.sample_rate as f64;
+            if !input.seek(Duration::from_secs_f64(secs)) {
+                return false;
+            }
+            self.recorded.clear();
+            self.recording = true;
+        }
+        self.position = start;
+        self.wrapped = true;
+        WRAPS.fetch_add(1, Ordering::SeqCst);
+        true
+    }
+
+    /// Fade in after a wrap and out before the end of the region
+    fn gain(&self, position: u64, start: u64, end: u64) -> f32 {
+        let fade = self.fade_len();
+        let frame = |samples: u64| (samples / self.channels) as f32;
+        let mut gain = 1.0;
+        if self.wrapped && position - start < fade {
+            gain *= frame(position - start) / frame(fade);
+        }
+        if position < end && end - position <= fade {
+            gain *= frame(end - position) / frame(fade);
+        }
+        gain
+    }
+
+    fn next(&mut self, input: &mut impl Input) -> Option<f32> {
+        let Some((start, end)) = self.region else {
+            self.position += 1;
+            return input.pull();
+        };
+
+        if let Some(index) = self.replay {
+            let position = self.position;
+            let sample = self.recorded[index];
+            if index + 1 < self.recorded.len() {
+                self.replay = Some(index + 1);
+                self.position += 1;
+            } else {
+                self.replay = Some(0);
+                self.position = start;
+                WRAPS.fetch_add(1, Ordering::SeqCst);
+            }
+            return Some(sample * self.gain(position, start, end));
+        }
+
+        if self.position == end && !self.wrap(start, input) {
+            self.region = None;
+        }
+        if self.replay.is_some() {
+            return self.next(input);
+        }
+        let sample = match input.pull() {
+            Some(sample) => sample,
+            // The region runs past the end of the track
+            None if self.position > start && self.position < end && self.wrap(start, input) => {
+                return self.next(input);
+            }
+            None => return None,
+        };
+
+        let position = self.position;
+        if position == start {
+            self.recorded.clear();
+            self.recording = true;
+        }
+        if self.recording && (start..end).contains(&position) {
+            if (self.recorded.len() as u64) < MAX_RECORDED_SECS * self.sample_rate * self.channels {
+                self.recorded.push(sample);
+            } else {
+                self.forget();
+            }
+        }
+        self.position += 1;
+        Some(sample * self.gain(position, start, end))
+    }
+}
+
+/// Source looping the global A-B region; without one the input passes
+/// through untouched
+pub struct AbLoop<S> {
+    inner: S,
+    looper: Looper,
+    generation: u64,
+}
+
+impl<S: Source> AbLoop<S> {
+    pub fn new(inner: S) -> Self {
+        let looper = Looper::new(inner.channels().into(), inner.sample_rate().into());
+        Self {
+            inner,
+            looper,
+            // Pick up the region on the first sample
+            generation: u64::MAX,
+        }
+    }
+}
+
+impl<S: Source> Iterator for AbLoop<S> {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let generation = GENERATION.load(Ordering::SeqCst);
+        if generation != self.generation {
+            self.generation = generation;
+            self.looper.set_region(region(), &mut self.inner);
+        }
+        self.looper.next(&mut self.inner)
+    }
+}
+
+impl<S: Source> Source for AbLoop<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        match self.looper.replay {
+            Some(_) => None,
+            None => self.inner.current_span_len(),
+        }
+    }
+
+    fn channels(&self) -> rodio::ChannelCount {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> rodio::SampleRate {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(pos)?;
+        self.looper.seek(pos);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 1_000;
+
+    /// Mono input counting its samples, seekable
+    struct Ramp {
+        position: usize,
+        len: usize,
+        seeks: usize,
+    }
+
+    impl Input for Ramp {
+        fn pull(&mut self) -> Option<f32> {
+            (self.position < self.len).then(|| {
+                self.position += 1;
+                (self.position - 1) as f32
+            })
+        }
+
+        fn seek(&mut self, pos: Duration) -> bool {
+            self.position = (pos.as_secs_f64() * SAMPLE_RATE as f64).round() as usize;
+            self.seeks += 1;
+            true
+        }
+    }
+
+    fn ramp(seconds: usize) -> Ramp {
+        Ramp { position: 0, len: seconds * SAMPLE_RATE as usize, seeks: 0 }
+    }
+
+    fn looper(start: f64, end: f64, input: &mut Ramp) -> Looper {
+        let mut looper = Looper::new(1, SAMPLE_RATE);
+        looper.set_region(Some(LoopRegion { start, end }), input);
+        looper
+    }
+
+    #[test]
+    fn region_is_replayed_from_memory() {
+        let mut input = ramp(10);
+        let mut looper = looper(1.0, 2.0, &mut input);
+        let out: Vec<f32> = (0..4_000).map(|_| looper.next(&mut input).unwrap()).collect();
+        // Straight through to the end of the region, then the region again
+        assert_eq!(out[1_500], 1_500.0);
+        assert_eq!(out[2_500], 1_500.0);
+        assert_eq!(out[3_500], 1_500.0);
+        assert_eq!(input.seeks, 0);
+        // Faded around the loop point
+        assert!(out[1_999] < 1_999.0 * 0.25);
+        assert_eq!(out[2_000], 0.0);
+        assert!(out.iter().all(|s| (0.0..2_000.0).contains(s)));
+    }
+
+    #[test]
+    fn region_entered_part_way_seeks_back_once() {
+        let mut input = ramp(10);
+        let mut looper = Looper::new(1, SAMPLE_RATE);
+        looper.seek(Duration::from_millis(1_500));
+        input.seek(Duration::from_millis(1_500));
+        looper.set_region(Some(LoopRegion { start: 1.0, end: 2.0 }), &mut input);
+        let out: Vec<f32> = (0..2_500).map(|_| looper.next(&mut input).unwrap()).collect();
+        assert_eq!(out[0], 1_500.0);
+        assert_eq!(out[1_000], 1_500.0);
+        assert_eq!(out[2_000], 1_500.0);
+        assert_eq!(input.seeks, 2);
+    }
+
+    #[test]
+    fn clearing_the_region_plays_on_from_where_it_was() {
+        let mut input = ramp(10);
+        let mut looper = looper(1.0, 2.0, &mut input);
+        for _ in 0..2_500 {
+            looper.next(&mut input);
+        }
+        looper.set_region(None, &mut input);
+        assert_eq!(looper.next(&mut input), Some(1_500.0));
+        let rest = std::iter::from_fn(|| looper.next(&mut input)).count();
+        assert_eq!(rest, 8_499);
+    }
+
+    #[test]
+    fn region_past_the_end_loops_the_tail() {
+        let mut input = ramp(3);
+        let mut looper = looper(2.0, 5.0, &mut input);
+        let out: Vec<f32> = (0..4_000).map(|_| looper.next(&mut input).unwrap()).collect();
+        assert_eq!(out[3_500], 2_500.0);
+    }
+}
@@ -0,0 +1,193 @@
+// crates/audio-player/src/analysis.rs
+// Local audio feature extraction for mood tagging. A window of the decoded
+// audio is reduced to per-frame loudness, brightness (zero crossings) and an
+// onset envelope, from which tempo, energy, danceability and a valence proxy
+// are estimated. These are heuristics, not a trained model.
+
+use std::fs::File;
+use std::path::Path;
+
+use rodio::Source;
+use types::entities::{TrackAudioFeatures, TrackMood};
+use types::errors::{error_helpers, Result};
+
+/// Audio skipped at the start, intros being rarely representative
+const SKIP_SECONDS: u32 = 20;
+/// Length of the analyzed window
+const WINDOW_SECONDS: u32 = 60;
+/// Analysis frames per second, i.e. the rate of the onset envelope
+const FRAME_RATE: u32 = 100;
+const MIN_BPM: f64 = 60.0;
+const MAX_BPM: f64 = 180.0;
+/// About -80 dBFS, so silent frames have a finite log
+const RMS_FLOOR: f64 = 1e-4;
+/// Minimum audio analyzed, in frames
+const MIN_FRAMES: usize = 2 * FRAME_RATE as usize;
+/// Onset envelope variance below which the audio has no pulse at all
+const MIN_ONSET_VARIANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    rms: f64,
+    /// Zero crossings per sample
+    zcr: f64,
+}
+
+fn frame(samples: &[f32]) -> Frame {
+    let power = samples.iter().map(|s| (*s as f64) * (*s as f64)).sum::<f64>() / samples.len() as f64;
+    let crossings = samples
+        .windows(2)
+        .filter(|w| (w[0] >= 0.0) != (w[1] >= 0.0))
+        .count();
+    Frame {
+        rms: power.sqrt(),
+        zcr: crossings as f64 / samples.len() as f64,
+    }
+}
+
+/// Tempo in BPM and pulse clarity (0..1) from the autocorrelation of the onset
+/// envelope. The biased estimate favors the shorter of equally strong lags.
+fn estimate_tempo(onsets: &[f64]) -> (f64, f64) {
+    let n = onsets.len();
+    let mean = onsets.iter().sum::<f64>() / n as f64;
+    let centered: Vec<f64> = onsets.iter().map(|o| o - mean).collect();
+    let autocorrelation =
+        |lag: usize| centered.iter().zip(&centered[lag..]).map(|(a, b)| a * b).sum::<f64>() / n as f64;
+
+    let zero = autocorrelation(0);
+    if zero < MIN_ONSET_VARIANCE {
+        return (0.0, 0.0);
+    }
+    let min_lag = (60.0 * FRAME_RATE as f64 / MAX_BPM).round() as usize;
+    let max_lag = ((60.0 * FRAME_RATE as f64 / MIN_BPM).round() as usize).min(n - 1);
+    let mut best = (0, f64::MIN);
+    for lag in min_lag..=max_lag {
+        let value = autocorrelation(lag);
+        if value > best.1 {
+            best = (lag, value);
+        }
+    }
+    if best.0 == 0 {
+        return (0.0, 0.0);
+    }
+    (60.0 * FRAME_RATE as f64 / best.0 as f64, (best.1 / zero).clamp(0.0, 1.0))
+}
+
+/// Features of mono audio; `None` when there is too little of it
+pub fn analyze_samples(mono: &[f32], sample_rate: u32) -> Option<TrackAudioFeatures> {
+    let hop = (sample_rate / FRAME_RATE).max(1) as usize;
+    let frames: Vec<Frame> = mono.chunks_exact(hop).map(frame).collect();
+    if frames.len() < MIN_FRAMES {
+        return None;
+    }
+
+    // Loudness: -40 dBFS and below is 0, -10 dBFS and above is 1
+    let mean_rms = frames.iter().map(|f| f.rms).sum::<f64>() / frames.len() as f64;
+    let loudness = ((20.0 * mean_rms.max(RMS_FLOOR).log10() + 40.0) / 30.0).clamp(0.0, 1.0);
+
+    // Brightness: dominant frequency from the loudness-weighted zero crossing
+    // rate, 200 Hz and below is 0, 4 kHz and above is 1
+    let weight: f64 = frames.iter().map(|f| f.rms).sum();
+    let brightness = if weight > 0.0 {
+        let zcr = frames.iter().map(|f| f.zcr * f.rms).sum::<f64>() / weight;
+        let frequency = zcr * sample_rate as f64 / 2.0;
+        ((frequency.max(1.0) / 200.0).log2() / 20f64.log2()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    // Onsets: rises of the log loudness between frames
+    let onsets: Vec<f64> = frames
+        .windows(2)
+        .map(|w| (w[1].rms.max(RMS_FLOOR).ln() - w[0].rms.max(RMS_FLOOR).ln()).max(0.0))
+        .collect();
+    let (tempo, clarity) = estimate_tempo(&onsets);
+
+    // Dance music sits around 120 BPM with a clear pulse
+    let tempo_fit = if tempo > 0.0 { (1.0 - (tempo - 120.0).abs() / 60.0).clamp(0.0, 1.0) } else { 0.0 };
+    let danceability = 0.6 * clarity + 0.4 * tempo_fit;
+    let energy = 0.7 * loudness + 0.3 * brightness;
+    // Valence has no direct acoustic correlate; bright, fast, rhythmic music
+    // tends to be perceived as positive
+    let pace = ((tempo - MIN_BPM) / (MAX_BPM - MIN_BPM)).clamp(0.0, 1.0);
+    let valence = 0.4 * brightness + 0.3 * pace + 0.3 * danceability;
+
+    Some(TrackAudioFeatures {
+        energy,
+        valence,
+        danceability,
+        tempo,
+        mood: TrackMood::classify(energy, valence),
+    })
+}
+
+/// Decode a window of a local file and extract its features
+pub fn analyze_file(path: &Path) -> Result<TrackAudioFeatures> {
+    let file = File::open(path)?;
+    let decoder = rodio::Decoder::try_from(file).map_err(error_helpers::to_media_error)?;
+    let channels = u16::from(decoder.channels()).max(1) as usize;
+    let sample_rate: u32 = decoder.sample_rate().into();
+
+    // Downmix while decoding; short tracks are analyzed whole
+    let limit = ((SKIP_SECONDS + WINDOW_SECONDS) * sample_rate) as usize;
+    let mut mono = Vec::with_capacity(limit);
+    let mut sum = 0.0;
+    let mut count = 0;
+    for sample in decoder {
+        sum += sample;
+        count += 1;
+        if count == channels {
+            mono.push(sum / channels as f32);
+            sum = 0.0;
+            count = 0;
+            if mono.len() >= limit {
+                break;
+            }
+        }
+    }
+    let window = (WINDOW_SECONDS * sample_rate) as usize;
+    let start = mono.len().saturating_sub(window);
+    analyze_samples(&mono[start..], sample_rate)
+        .ok_or_else(|| format!("Not enough audio to analyze in {}", path.display()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 44_100;
+
+    #[test]
+    fn a_click_track_has_its_tempo_and_a_clear_pulse() {
+        // 50 ms of 1 kHz every half second, 30 seconds long
+        let beat = RATE as usize / 2;
+        let click = RATE as usize / 20;
+        let samples: Vec<f32> = (0..30 * RATE as usize)
+            .map(|i| {
+                let t = i % beat;
+                if t < click {
+                    0.8 * (2.0 * std::f32::consts::PI * 1000.0 * t as f32 / RATE as f32).sin()
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let features = analyze_samples(&samples, RATE).unwrap();
+        assert!((features.tempo - 120.0).abs() < 2.0, "tempo {}", features.tempo);
+        assert!(features.danceability > 0.6, "danceability {}", features.danceability);
+    }
+
+    #[test]
+    fn a_quiet_steady_hum_is_low_energy_and_negative() {
+        // Whole periods per frame, so the loudness doesn't ripple
+        let samples: Vec<f32> = (0..10 * RATE as usize)
+            .map(|i| 0.01 * (2.0 * std::f32::consts::PI * 100.0 * i as f32 / RATE as f32).sin())
+            .collect();
+        let features = analyze_samples(&samples, RATE).unwrap();
+        assert!(features.energy < 0.1, "energy {}", features.energy);
+        assert_eq!(features.tempo, 0.0);
+        assert_eq!(features.mood, TrackMood::Melancholic);
+
+        assert!(analyze_samples(&samples[..RATE as usize], RATE).is_none());
+    }
+}
@@ -0,0 +1,259 @@
+// crates/audio-player/src/buffering.rs
+// Buffering of streamed files: how much is downloaded before playback starts,
+// and how much must be buffered again once playback catches up with the
+// download. Progress is reported as `Buffering` events and each stall as a
+// `Stalled` event, for the integrator to lower the stream quality when they
+// repeat. The configuration is global and read by every new stream.
+
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::Sender;
+use types::settings::music::MusicBufferingSettings;
+use types::ui::player_details::PlayerEvents;
+
+use crate::expiry::StreamFailure;
+
+/// Bytes buffered ahead before playback resumes after a stall, by default
+pub const DEFAULT_REBUFFER_BYTES: u64 = 128 * 1024;
+/// Largest prefetch or rebuffer target accepted
+pub const MAX_BUFFER_BYTES: u64 = 16 * 1024 * 1024;
+/// Longest wait for the rebuffer target; the read then waits for any data
+const REBUFFER_TIMEOUT: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferConfig {
+    /// Bytes downloaded before playback starts, `None` keeps the defaults of
+    /// the player
+    pub prefetch_bytes: Option<u64>,
+    /// Bytes buffered ahead before playback resumes after a stall; 0 resumes
+    /// as soon as data arrives
+    pub rebuffer_bytes: u64,
+}
+
+impl Default for BufferConfig {
+    fn default() -> Self {
+        Self {
+            prefetch_bytes: None,
+            rebuffer_bytes: DEFAULT_REBUFFER_BYTES,
+        }
+    }
+}
+
+impl From<&MusicBufferingSettings> for BufferConfig {
+    fn from(settings: &MusicBufferingSettings) -> Self {
+        let kilobytes = |kb: u32| (kb as u64 * 1024).min(MAX_BUFFER_BYTES);
+        Self {
+            prefetch_bytes: settings.prefetch_kb.map(kilobytes),
+            rebuffer_bytes: settings.rebuffer_kb.map_or(DEFAULT_REBUFFER_BYTES, kilobytes),
+        }
+    }
+}
+
+static CONFIG: Mutex<BufferConfig> = Mutex::new(BufferConfig {
+    prefetch_bytes: None,
+    rebuffer_bytes: DEFAULT_REBUFFER_BYTES,
+});
+
+pub fn set_config(config: BufferConfig) {
+    *CONFIG.lock().unwrap() = config;
+}
+
+pub fn config() -> BufferConfig {
+    *CONFIG.lock().unwrap()
+}
+
+fn percent(done: u64, target: u64) -> u8 {
+    if target == 0 {
+        return 100;
+    }
+    (done.min(target) * 100 / target) as u8
+}
+
+/// Download position of a stream, updated by the downloader and read by
+/// `BufferedReader`. Reports the progress of the prefetch as it goes.
+#[derive(Debug, Clone)]
+pub struct DownloadProgress {
+    downloaded: Arc<AtomicU64>,
+    prefetch_bytes: u64,
+    /// Last prefetch percentage reported
+    reported: Arc<AtomicU8>,
+    events_tx: Sender<PlayerEvents>,
+}
+
+impl DownloadProgress {
+    pub fn new(prefetch_bytes: u64, events_tx: Sender<PlayerEvents>) -> Self {
+        Self {
+            downloaded: Arc::new(AtomicU64::new(0)),
+            prefetch_bytes,
+            reported: Arc::new(AtomicU8::new(0)),
+            events_tx,
+        }
+    }
+
+    /// The download reached `position`. Moves back when a seek restarts it.
+    pub fn update(&self, position: u64) {
+        self.downloaded.store(position, Ordering::Relaxed);
+        let percent = percent(position, self.prefetch_bytes);
+        if percent > self.reported.fetch_max(percent, Ordering::Relaxed) {
+            let _ = self.events_tx.send(PlayerEvents::Buffering(percent));
+        }
+    }
+
+    fn downloaded(&self) -> u64 {
+        self.downloaded.load(Ordering::Relaxed)
+    }
+}
+
+/// Reader of a streamed file that, when playback catches up with the
+/// download, waits until `rebuffer_bytes` are buffered ahead instead of
+/// playing every chunk as it arrives
+pub struct BufferedReader<R> {
+    inner: R,
+    progress: DownloadProgress,
+    content_length: Option<u64>,
+    position: u64,
+    rebuffer_bytes: u64,
+    /// The first wait completes the prefetch and is not a stall
+    started: bool,
+    /// Told when the download breaks off before the end of the file
+    failure: Option<StreamFailure>,
+}
+
+impl<R> BufferedReader<R> {
+    pub fn new(inner: R, progress: DownloadProgress, content_length: Option<u64>, rebuffer_bytes: u64) -> Self {
+        Self {
+            inner,
+            progress,
+            content_length,
+            position: 0,
+            rebuffer_bytes,
+            started: false,
+            failure: None,
+        }
+    }
+
+    /// Report a download breaking off before the end of the file to `failure`
+    pub fn with_failure(mut self, failure: StreamFailure) -> Self {
+        self.failure = Some(failure);
+        self
+    }
+
+    fn report_failure(&self) {
+        if let Some(failure) = &self.failure {
+            failure.report(None);
+        }
+    }
+
+    fn wait_for_data(&mut self) {
+        let remaining = self.content_length.map(|len| len.saturating_sub(self.position));
+        if self.rebuffer_bytes == 0 || remaining == Some(0) || self.progress.downloaded() > self.position {
+            return;
+        }
+        if self.started {
+            let _ = self.progress.events_tx.send(PlayerEvents::Stalled);
+        }
+        self.started = true;
+
+        let target = remaining.map_or(self.rebuffer_bytes, |remaining| remaining.min(self.rebuffer_bytes));
+        let waiting = Instant::now();
+        let mut reported = None;
+        loop {
+            let ahead = self.progress.downloaded().saturating_sub(self.position);
+            let percent = percent(ahead, target);
+            if reported != Some(percent) {
+                let _ = self.progress.events_tx.send(PlayerEvents::Buffering(percent));
+                reported = Some(percent);
+            }
+            if ahead >= target || waiting.elapsed() >= REBUFFER_TIMEOUT {
+                break;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+impl<R: Read> Read for BufferedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.wait_for_data();
+        let read = self.inner.read(buf).map_err(|e| {
+            self.report_failure();
+            e
+        })?;
+        if read == 0 && !buf.is_empty() && self.content_length.is_some_and(|len| self.position < len) {
+            self.report_failure();
+        }
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<R: Seek> Seek for BufferedReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.position = self.inner.seek(pos)?;
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn waits_for_the_rebuffer_target_and_reports_stalls() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let progress = DownloadProgress::new(0, tx);
+        progress.update(8);
+        let mut reader = BufferedReader::new(Cursor::new(vec![0u8; 64]), progress.clone(), Some(64), 16);
+        // Past the start of playback
+        reader.started = true;
+
+        let mut buf = [0u8; 8];
+        assert_eq!(reader.read(&mut buf).unwrap(), 8);
+        // Caught up with the download: the next read waits for 16 more bytes
+        let downloader = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(120));
+            progress.update(16);
+            std::thread::sleep(Duration::from_millis(120));
+            progress.update(40);
+        });
+        assert_eq!(reader.read(&mut buf).unwrap(), 8);
+        downloader.join().unwrap();
+
+        let events: Vec<String> = rx.try_iter().map(|e| format!("{:?}", e)).collect();
+        assert_eq!(events.first().map(String::as_str), Some("Buffering(100)"));
+        assert!(events.iter().any(|e| e == "Stalled"));
+        assert!(events.iter().any(|e| e == "Buffering(50)"));
+        assert_eq!(events.last().map(String::as_str), Some("Buffering(100)"));
+    }
+
+    #[test]
+    fn the_end_of_the_file_needs_no_buffer() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let progress = DownloadProgress::new(0, tx);
+        progress.update(4);
+        let mut reader = BufferedReader::new(Cursor::new(vec![0u8; 4]), progress, Some(4), 1024);
+        let mut buf = [0u8; 8];
+        assert_eq!(reader.read(&mut buf).unwrap(), 4);
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+        assert_eq!(rx.try_iter().count(), 1);
+    }
+
+    #[test]
+    fn a_download_breaking_off_is_reported() {
+        let (tx, _rx) = crossbeam_channel::unbounded();
+        let progress = DownloadProgress::new(0, tx);
+        progress.update(8);
+        let failure = StreamFailure::default();
+        let mut reader = BufferedReader::new(Cursor::new(vec![0u8; 4]), progress, Some(8), 0).with_failure(failure.clone());
+        let mut buf = [0u8; 8];
+        assert_eq!(reader.read(&mut buf).unwrap(), 4);
+        assert!(!failure.failed());
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+        assert!(failure.failed());
+    }
+}
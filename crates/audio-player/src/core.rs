@@ -1,16 +1,32 @@
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex, atomic::{AtomicUsize, Ordering}};
-use crossbeam_channel::{unbounded, Receiver};
+use std::sync::{Arc, Mutex, atomic::{AtomicBool, AtomicUsize, Ordering}};
+use std::time::Duration;
+use crossbeam_channel::{unbounded, Receiver, Sender};
 use tokio::sync::oneshot;
 use types::errors::Result;
-use types::songs::{SongType, Song};
-use types::ui::player_details::{PlayerEvents, PlayerState, PlayerMode};
+use types::tracks::{TrackType, MediaContent};
+use types::stations::is_station;
+use types::ui::player_details::{LoopRegion, PlayerEvents, PlayerState, PlayerMode, TrackGain};
 use database::database::Database;
 use crate::players::base::{BasePlayer, PlayerEventsSender};
 use crate::players::librespot::{LibrespotAdapter, LibrespotPlayer};
 use crate::players::rodio::RodioPlayer;
 use crate::store::PlayerStore;
 use crate::events::{apply_event_basic, apply_event_with_hooks, EventHooks};
+use crate::state_machine;
+use crate::media_keys::MediaKeyConfig;
+use crate::crossfade::CrossfadeConfig;
+use crate::ab_loop;
+use crate::edit_regions::EditAction;
+use crate::transport::{LoadTicket, TransportQueue};
+use crate::queue_metrics::QueueTiming;
+use crate::normalize::NormalizeConfig;
+use crate::devices::{self, DeviceEvent, OutputDevice, OutputSelection};
+use crate::interrupt::{self, InterruptAction, InterruptPolicy, InterruptSignal, InterruptState};
+use crate::mpris::RemoteAction;
+use types::settings::general::ResumeOnLaunch;
+use types::settings::music::MediaKeyAction;
+use types::ui::title_format::TitleFormatter;
 
 use ::mpris;
 
@@ -24,12 +40,50 @@ pub struct AudioPlayer {
     // Outgoing events for UI bridge
     pub(crate) events_tx: crossbeam_channel::Sender<PlayerEvents>,
     events_rx: Arc<Mutex<Receiver<PlayerEvents>>>,
+    // Resolved media key actions (gestures, next/previous) for the Tauri bridge
+    pub(crate) control_tx: crossbeam_channel::Sender<MediaKeyAction>,
+    control_rx: Arc<Mutex<Receiver<MediaKeyAction>>>,
+    // Seeks and mode changes requested by the OS media controls, for the Tauri bridge
+    pub(crate) remote_tx: Sender<RemoteAction>,
+    remote_rx: Arc<Mutex<Receiver<RemoteAction>>>,
+    // Output device selection shared with the backends, and its change notifications
+    output: Arc<OutputSelection>,
+    device_rx: Arc<Mutex<Receiver<DeviceEvent>>>,
+    // Calls and communication sessions: reports from the platform hooks and
+    // the interruption in progress
+    interrupt_tx: Sender<InterruptSignal>,
+    interrupt_rx: Arc<Mutex<Receiver<InterruptSignal>>>,
+    interrupt: Mutex<InterruptState>,
+    // Seeks and mutes due to the edit regions of the loaded track, applied by
+    // the Tauri bridge thread; whether a mute region currently silences it
+    edit_tx: Sender<EditAction>,
+    edit_rx: Arc<Mutex<Receiver<EditAction>>>,
+    edit_muted: AtomicBool,
+    // Loudness normalization settings and the analysis of the loaded track
+    normalize: Mutex<NormalizeConfig>,
+    track_gain: Mutex<Option<TrackGain>>,
+    // Highest volume (0.0 - 1.0) on the output, set by the active output profile
+    output_cap: Mutex<Option<f32>>,
+    pub(crate) media_key_config: Arc<Mutex<MediaKeyConfig>>,
+    // Display templates used for MPRIS/SMTC titles
+    pub(crate) title_formatter: Arc<Mutex<TitleFormatter>>,
+    // Crossfade between consecutive queue entries
+    crossfade: Arc<Mutex<CrossfadeConfig>>,
+    // Silence between consecutive queue entries, and a counter cancelling a pending gap
+    track_gap: Mutex<Duration>,
+    gap_generation: AtomicUsize,
+    // Track changes in request order and the seek held while a track loads;
+    // loads, plays and seeks run one at a time under `transport_serial`
+    transport: Arc<Mutex<TransportQueue>>,
+    transport_serial: tokio::sync::Mutex<()>,
+    // Position of the restored track to seek to when it is first loaded
+    launch_position: Mutex<Option<f64>>,
     // Player state and queue management
     store: Arc<Mutex<PlayerStore>>,
     // Cache dir (reserved for future use)
     _cache_dir: PathBuf,
-    // MPRIS integration
-    pub(crate) mpris_holder: Option<::mpris::MprisHolder>,
+    // OS media controls (MPRIS, SMTC, Now Playing)
+    pub(crate) mpris_holder: Option<Box<dyn crate::os_media::OsMediaControls>>,
 }
 
 impl AudioPlayer {
@@ -37,18 +91,56 @@ impl AudioPlayer {
     /// NOTE: This is an internal helper.
     fn new_base(cache_dir: PathBuf) -> Self {
         let (tx, rx) = unbounded::<PlayerEvents>();
+        let (control_tx, control_rx) = unbounded::<MediaKeyAction>();
+        let (remote_tx, remote_rx) = unbounded::<RemoteAction>();
+        let (device_tx, device_rx) = unbounded::<DeviceEvent>();
+        let output = Arc::new(OutputSelection::new(device_tx));
+        let (interrupt_tx, interrupt_rx) = unbounded::<InterruptSignal>();
+        let (edit_tx, edit_rx) = unbounded::<EditAction>();
         
         // Initialize player store (without database initially)
         let store = Arc::new(Mutex::new(PlayerStore::new(None)));
         
+        let crossfade = Arc::new(Mutex::new(CrossfadeConfig::default()));
+
         // Initialize players
-        let players = Self::initialize_players(store.clone(), tx.clone(), cache_dir.clone());
+        let players = Self::initialize_players(
+            store.clone(),
+            tx.clone(),
+            crossfade.clone(),
+            edit_tx.clone(),
+            output.clone(),
+            cache_dir.clone(),
+        );
         
         Self {
             players: std::sync::Mutex::new(players),
             active: AtomicUsize::new(0),
             events_tx: tx,
             events_rx: Arc::new(Mutex::new(rx)),
+            control_tx,
+            control_rx: Arc::new(Mutex::new(control_rx)),
+            remote_tx,
+            remote_rx: Arc::new(Mutex::new(remote_rx)),
+            output,
+            device_rx: Arc::new(Mutex::new(device_rx)),
+            interrupt_tx,
+            interrupt_rx: Arc::new(Mutex::new(interrupt_rx)),
+            interrupt: Mutex::new(InterruptState::default()),
+            edit_tx,
+            edit_rx: Arc::new(Mutex::new(edit_rx)),
+            edit_muted: AtomicBool::new(false),
+            normalize: Mutex::new(NormalizeConfig::default()),
+            track_gain: Mutex::new(None),
+            output_cap: Mutex::new(None),
+            media_key_config: Arc::new(Mutex::new(MediaKeyConfig::default())),
+            title_formatter: Arc::new(Mutex::new(TitleFormatter::default())),
+            crossfade,
+            track_gap: Mutex::new(Duration::ZERO),
+            gap_generation: AtomicUsize::new(0),
+            transport: Arc::new(Mutex::new(TransportQueue::default())),
+            transport_serial: tokio::sync::Mutex::new(()),
+            launch_position: Mutex::new(None),
             store,
             _cache_dir: cache_dir,
             mpris_holder: None,
@@ -74,6 +166,10 @@ impl AudioPlayer {
       if let Ok(mut store) = player.store.lock() {
           store.set_database(db);
       }
+
+      if !interrupt::watch_communications(player.interrupt_tx.clone()) {
+          tracing::debug!("Communication sessions are not reported on this platform");
+      }
       
       player
   }
@@ -94,14 +190,17 @@ impl AudioPlayer {
   fn initialize_players(
       store: Arc<Mutex<PlayerStore>>,
       events_tx: crossbeam_channel::Sender<PlayerEvents>,
+      crossfade: Arc<Mutex<CrossfadeConfig>>,
+      edit_tx: Sender<EditAction>,
+      output: Arc<OutputSelection>,
       cache_dir: PathBuf
   ) -> Vec<Box<dyn BasePlayer + Send + Sync>> {
-      let state_setter = Self::create_player_event_handler(store, events_tx);
+      let state_setter = Self::create_player_event_handler(store, events_tx, crossfade, edit_tx);
       
       let mut players: Vec<Box<dyn BasePlayer + Send + Sync>> = Vec::new();
       
       // Initialize Rodio player (for local files, URLs, HLS, DASH)
-      let mut rodio = RodioPlayer::new(cache_dir.clone());
+      let mut rodio = RodioPlayer::new(cache_dir.clone(), output);
       rodio.add_listeners(state_setter.clone());
       players.push(Box::new(rodio));
       
@@ -121,9 +220,12 @@ impl AudioPlayer {
   /// Create event handler for player events
   fn create_player_event_handler(
       store: Arc<Mutex<PlayerStore>>,
-      events_tx: crossbeam_channel::Sender<PlayerEvents>
+      events_tx: crossbeam_channel::Sender<PlayerEvents>,
+      crossfade: Arc<Mutex<CrossfadeConfig>>,
+      edit_tx: Sender<EditAction>,
   ) -> PlayerEventsSender {
       Arc::new(move |player_key: String, ev: PlayerEvents| {
+          let mut finished_early = false;
           // Handle player events and update store
           if let Ok(mut player_store) = store.lock() {
               if let PlayerEvents::Error(err) = &ev {
@@ -132,14 +234,62 @@ impl AudioPlayer {
               } else {
                   // Delegate to centralized basic event application
                   apply_event_basic(&mut player_store, &ev);
+                  // End position reached or crossfade due: finish the entry as if the media ended
+                  finished_early = matches!(ev, PlayerEvents::TimeUpdate(_))
+                      && Self::should_finish_early(&mut player_store, &crossfade, &edit_tx);
+                  if finished_early {
+                      apply_event_basic(&mut player_store, &PlayerEvents::Ended);
+                  }
               }
           }
           
           // Also send event to UI bridge
           let _ = events_tx.send(ev);
+          if finished_early {
+              let _ = events_tx.send(PlayerEvents::Ended);
+          }
       })
   }
 
+  /// Whether the current entry should end now: its end position was passed, a
+  /// skip region runs to its end, or the crossfade into the next entry has to
+  /// start. Other edit region actions are handed to the bridge thread.
+  fn should_finish_early(
+      store: &mut PlayerStore,
+      crossfade: &Mutex<CrossfadeConfig>,
+      edit_tx: &Sender<EditAction>,
+      loops_regions: bool,
+  ) -> bool {
+      let looping = ab_loop::region();
+      match (store.take_edit_action(), looping) {
+          // A skip region running to the end goes back to the start of the loop
+          (Some(EditAction::Finish), Some(region)) => {
+              let _ = edit_tx.send(EditAction::Seek(region.start));
+              return false;
+          }
+          (Some(EditAction::Finish), None) => return true,
+          (Some(action), _) => {
+              let _ = edit_tx.send(action);
+          }
+          (None, _) => {}
+      }
+      // The entry doesn't end while its A-B region loops, whatever the mode
+      if let Some(region) = looping {
+          if !loops_regions && store.get_current_time() >= region.end {
+              let _ = edit_tx.send(EditAction::Seek(region.start));
+          }
+          return false;
+      }
+      if store.take_end_trim() {
+          return true;
+      }
+      let lead = crossfade
+          .lock()
+          .map(|c| c.duration.as_secs_f64())
+          .unwrap_or_default();
+      store.take_crossfade_start(lead)
+  }
+
   /// Handle player error events
   fn handle_player_error(player_store: &mut PlayerStore, player_key: &str, err: &types::errors::MusicError) {
       tracing::error!("Player {} error: {:?}", player_key, err);
@@ -147,18 +297,18 @@ impl AudioPlayer {
       if player_key.starts_with("player_") {
           player_store.blacklist_player(player_key.to_string());
       }
-      player_store.set_state(PlayerState::Stopped);
+      player_store.set_state(PlayerState::Errored);
   }
 
-  fn get_player(&self, song: &mut Song) -> Result<usize> {
+  fn get_player(&self, track: &mut MediaContent) -> Result<usize> {
       let blacklist = if let Ok(store) = self.store.lock() {
           store.get_player_blacklist()
       } else {
           Vec::new()
       };
       
-      tracing::debug!("Getting players for song {:?}", song.song.title);
-      // First attempt: find player that can handle the song
+      tracing::debug!("Getting players for track {:?}", track.track.title);
+      // First attempt: find player that can handle the track
       let player_index = {
           let players = self.players_guard()?;
           players.iter().position(|p| {
@@ -166,13 +316,13 @@ impl AudioPlayer {
               tracing::debug!(
                   "Checking player capabilities {}, type: {:?}, url: {:?}",
                   player_key,
-                  song.song.type_,
-                  song.song.playback_url
+                  track.track.type_,
+                  track.track.playback_url
               );
               let res = !blacklist.contains(&player_key)
-                  && p.provides().contains(&song.song.type_)
-                  && p.can_play(song);
-              tracing::debug!("Player {} can handle song: {}", player_key, res);
+                  && p.provides().contains(&track.track.type_)
+                  && p.can_play(track);
+              tracing::debug!("Player {} can handle track: {}", player_key, res);
               res
           })
       };
@@ -184,12 +334,12 @@ impl AudioPlayer {
       
       // TODO: Second attempt with playback URL fetching (Extension support)
       // This would require provider store integration, skipping for now
-      tracing::warn!("No suitable player found for song type: {:?}", song.song.type_);
+      tracing::warn!("No suitable player found for track type: {:?}", track.track.type_);
       
       Err(types::errors::MusicError::String("Player not found".into()))
   }
 
-  fn find_player_by_type(&self, ty: SongType) -> Option<usize> {
+  fn find_player_by_type(&self, ty: TrackType) -> Option<usize> {
       self.players.lock().ok().and_then(|players| players.iter().position(|p| p.provides().contains(&ty)))
   }
 
@@ -198,11 +348,338 @@ impl AudioPlayer {
       self.events_rx.clone() 
   }
 
+  /// Expose resolved media key actions for Tauri bridge thread
+  pub fn get_control_rx(&self) -> Arc<Mutex<Receiver<MediaKeyAction>>> {
+      self.control_rx.clone()
+  }
+
+  /// Expose seek and mode requests from the OS media controls for Tauri bridge thread
+  pub fn get_remote_rx(&self) -> Arc<Mutex<Receiver<RemoteAction>>> {
+      self.remote_rx.clone()
+  }
+
+  /// Expose output device changes for Tauri bridge thread
+  pub fn get_device_rx(&self) -> Arc<Mutex<Receiver<DeviceEvent>>> {
+      self.device_rx.clone()
+  }
+
+  /// Available output devices, marking the one playback goes to
+  pub fn list_output_devices(&self) -> Result<Vec<OutputDevice>> {
+      let active = self.output.active();
+      let mut list = devices::list_output_devices()?;
+      for device in list.iter_mut() {
+          device.is_active = active.as_deref() == Some(device.name.as_str());
+      }
+      Ok(list)
+  }
+
+  /// Preferred output device, `None` when following the system default
+  pub fn get_output_device(&self) -> Option<String> {
+      self.output.preferred()
+  }
+
+  /// Move playback to `device`, or to the system default with `None`. The
+  /// choice is kept when the device disappears, and playback returns to it
+  /// once it is available again.
+  pub fn set_output_device(&self, device: Option<String>) -> Result<()> {
+      if !self.output.set_preferred(device.clone()) {
+          return Ok(());
+      }
+      for player in self.players_guard()?.iter() {
+          player.set_output_device(device.clone())?;
+      }
+      Ok(())
+  }
+
+  /// Expose call start/end reports for the Tauri bridge thread, which applies
+  /// them with `handle_interruption`
+  pub fn get_interrupt_rx(&self) -> Arc<Mutex<Receiver<InterruptSignal>>> {
+      self.interrupt_rx.clone()
+  }
+
+  /// Report a call starting or ending from a hook outside the core (e.g. the
+  /// Android audio focus)
+  pub fn report_interruption(&self, signal: InterruptSignal) {
+      let _ = self.interrupt_tx.send(signal);
+  }
+
+  /// Set how calls and communication sessions affect playback
+  pub fn set_interrupt_policy(&self, policy: InterruptPolicy) {
+      if let Ok(mut interrupt) = self.interrupt.lock() {
+          interrupt.set_policy(policy);
+      }
+  }
+
+  /// Pause or duck playback when a call starts, and undo it when it ends.
+  /// Returns whether an interruption is in progress afterwards.
+  pub async fn handle_interruption(&self, signal: InterruptSignal) -> Result<bool> {
+      let playing = self
+          .store
+          .lock()
+          .map(|store| store.get_player_state() == PlayerState::Playing)
+          .unwrap_or(false);
+      let (action, active) = {
+          let mut interrupt = self
+              .interrupt
+              .lock()
+              .map_err(|_| types::errors::MusicError::from("interrupt lock poisoned"))?;
+          let action = match signal {
+              InterruptSignal::Began => interrupt.begin(playing),
+              InterruptSignal::Ended => interrupt.end(),
+          };
+          (action, interrupt.is_active())
+      };
+      tracing::info!("Interruption {:?}: {:?}", signal, action);
+
+      match action {
+          InterruptAction::None => {}
+          InterruptAction::Pause => self.pause_active()?,
+          InterruptAction::Resume => self.audio_play(None).await?,
+          InterruptAction::Duck(_) | InterruptAction::Restore => {
+              // The store keeps the user's volume; only the backend is attenuated
+              let volume = self.audio_get_volume().await?;
+              self.apply_backend_volume(volume)?;
+          }
+      }
+      Ok(active)
+  }
+
+  /// Expose seeks and mutes due to edit regions for the Tauri bridge thread,
+  /// which applies them with `apply_edit_action`
+  pub fn get_edit_rx(&self) -> Arc<Mutex<Receiver<EditAction>>> {
+      self.edit_rx.clone()
+  }
+
+  /// Jump over a skip region or (un)mute a mute region of the loaded track
+  pub async fn apply_edit_action(&self, action: EditAction) -> Result<()> {
+      match action {
+          EditAction::Seek(raw) => {
+              let idx = self.active.load(Ordering::SeqCst);
+              let players = self.players_guard()?;
+              players[idx].seek(raw)
+          }
+          EditAction::Mute(muted) => {
+              self.edit_muted.store(muted, Ordering::SeqCst);
+              let volume = self.audio_get_volume().await?;
+              self.apply_backend_volume(volume)
+          }
+          // Handled by the player event handler
+          EditAction::Finish => Ok(()),
+      }
+  }
+
+  /// Reload the edit regions of `track_id` after they changed, when it is the
+  /// loaded track. Returns whether they were reloaded.
+  pub fn refresh_edit_regions(&self, track_id: &str) -> bool {
+      let Ok(mut store) = self.store.lock() else {
+          return false;
+      };
+      let is_current = store
+          .get_current_track()
+          .and_then(|t| t.track._id)
+          .is_some_and(|id| id == track_id);
+      if !is_current {
+          return false;
+      }
+      store.load_edit_regions(Some(track_id));
+      // The new regions are applied from the next time update, unmuted
+      if self.edit_muted.swap(false, Ordering::SeqCst) {
+          let volume = (store.get_raw_volume() / 100.0) as f32;
+          // Applying the volume reads the player mode from the store
+          drop(store);
+          if let Err(e) = self.apply_backend_volume(volume) {
+              tracing::warn!("Failed to restore the volume after editing regions: {:?}", e);
+          }
+      }
+      true
+  }
+
+  /// Set loudness normalization and apply it to the loaded track
+  pub fn set_normalization(&self, config: NormalizeConfig) {
+      if let Ok(mut current) = self.normalize.lock() {
+          *current = config;
+      }
+      self.reapply_volume();
+  }
+
+  /// Cap the volume on the output, `None` removes the cap. The stored volume is
+  /// kept, so it comes back once the cap is lifted.
+  pub fn set_output_cap(&self, cap: Option<f32>) {
+      if let Ok(mut current) = self.output_cap.lock() {
+          *current = cap.map(|cap| cap.clamp(0.0, 1.0));
+      }
+      self.reapply_volume();
+  }
+
+  /// Replace the loudness analysis of `track_id` after it was read, when it is
+  /// the loaded track. Returns whether it was applied.
+  pub fn refresh_track_gain(&self, track_id: &str, gain: Option<TrackGain>) -> bool {
+      let is_current = self
+          .store
+          .lock()
+          .ok()
+          .and_then(|store| store.get_current_track())
+          .and_then(|t| t.track._id)
+          .is_some_and(|id| id == track_id);
+      if !is_current {
+          return false;
+      }
+      if let Ok(mut current) = self.track_gain.lock() {
+          *current = gain;
+      }
+      self.reapply_volume();
+      true
+  }
+
+  /// Push the stored volume to the backend again after a gain or the volume
+  /// clamp changed
+  pub fn reapply_volume(&self) {
+      let Ok(raw) = self.store.lock().map(|store| store.get_raw_volume()) else {
+          return;
+      };
+      if let Err(e) = self.apply_backend_volume((raw / 100.0) as f32) {
+          tracing::warn!("Failed to apply the normalization gain: {:?}", e);
+      }
+  }
+
+  /// Set the backend volume, capped by the volume clamp of the track's source
+  /// and the output profile, scaled by the normalization gain of the loaded track, attenuated while
+  /// ducked for a call and silenced inside a mute region
+  fn apply_backend_volume(&self, volume: f32) -> Result<()> {
+      let mut gain = self.interrupt.lock().map(|i| i.gain()).unwrap_or(1.0);
+      let (mode, clamp) = self
+          .store
+          .lock()
+          .map(|store| (store.get_repeat(), store.get_volume_clamp()))
+          .unwrap_or_default();
+      if let (Ok(config), Ok(track_gain)) = (self.normalize.lock(), self.track_gain.lock()) {
+          gain *= config.for_player_mode(mode).factor(track_gain.as_ref());
+      }
+      if self.edit_muted.load(Ordering::SeqCst) {
+          gain = 0.0;
+      }
+      // The clamp of the source and the cap of the output profile limit the
+      // volume, not the gains applied to it
+      let mut volume = clamp.map_or(volume, |clamp| volume.min((clamp / 100.0) as f32));
+      if let Some(cap) = self.output_cap.lock().ok().and_then(|cap| *cap) {
+          volume = volume.min(cap);
+      }
+      let idx = self.active.load(Ordering::SeqCst);
+      let players = self.players_guard()?;
+      players[idx].set_volume((volume * gain) as f64)
+  }
+
+  /// The user paused or resumed during a call: don't resume when it ends
+  fn override_interruption(&self) {
+      if let Ok(mut interrupt) = self.interrupt.lock() {
+          interrupt.user_override();
+      }
+  }
+
+  /// Update timing windows and mappings used for media key gestures
+  pub fn set_media_key_config(&self, config: MediaKeyConfig) {
+      if let Ok(mut current) = self.media_key_config.lock() {
+          *current = config;
+      }
+  }
+
+  /// Replace the title templates used for system media integration
+  pub fn set_title_formatter(&self, formatter: TitleFormatter) {
+      if let Ok(mut current) = self.title_formatter.lock() {
+          *current = formatter;
+      }
+  }
+
+  /// Set crossfade duration and curve used for automatic track changes
+  pub fn set_crossfade(&self, config: CrossfadeConfig) {
+      if let Ok(mut current) = self.crossfade.lock() {
+          *current = config;
+      }
+  }
+
+  /// Current crossfade settings, `None` when crossfading is disabled
+  pub fn get_crossfade(&self) -> Option<CrossfadeConfig> {
+      self.crossfade
+          .lock()
+          .ok()
+          .map(|c| *c)
+          .filter(|c| c.is_enabled())
+  }
+
+  /// Set the silence inserted between queue entries
+  pub fn set_track_gap(&self, gap: Duration) {
+      if let Ok(mut current) = self.track_gap.lock() {
+          *current = gap;
+      }
+  }
+
+  pub fn get_track_gap(&self) -> Duration {
+      self.track_gap.lock().map(|g| *g).unwrap_or_default()
+  }
+
+  /// Transition settings to account for in queue metrics
+  pub fn queue_timing(&self) -> QueueTiming {
+      QueueTiming {
+          crossfade: self.get_crossfade().map(|c| c.duration).unwrap_or_default(),
+          gap: self.get_track_gap(),
+      }
+  }
+
+  /// Continue with `track` after the previous queue entry ended on its own:
+  /// wait for the inter-track gap, then load and play it. The wait is abandoned
+  /// when playback is paused, stopped or another track is loaded meanwhile.
+  /// Seeks within a track never go through here, so no gap is inserted there.
+  pub async fn continue_after_ended(&self, track: &mut MediaContent) -> Result<()> {
+      let ticket = self.select_track();
+      let gap = self.get_track_gap();
+      if !gap.is_zero() {
+          let generation = self.gap_generation.load(Ordering::SeqCst);
+          tracing::debug!("Waiting {:?} before the next track", gap);
+          tokio::time::sleep(gap).await;
+          if self.gap_generation.load(Ordering::SeqCst) != generation {
+              tracing::debug!("Inter-track gap interrupted");
+              return Ok(());
+          }
+      }
+      if self.load_selected(ticket, track).await? {
+          self.play_loaded().await?;
+      }
+      Ok(())
+  }
+
+  fn transport(&self) -> std::sync::MutexGuard<'_, TransportQueue> {
+      self.transport.lock().unwrap_or_else(|e| e.into_inner())
+  }
+
+  /// Record a track change; loads of earlier changes not started yet are skipped
+  fn select_track(&self) -> LoadTicket {
+      self.transport().select()
+  }
+
+  /// Load `track` unless a later track change superseded it, waiting for the
+  /// load in progress first. Returns whether it is still the selected track
+  /// once loaded, i.e. whether it should be played.
+  async fn load_selected(&self, ticket: LoadTicket, track: &mut MediaContent) -> Result<bool> {
+      let _serial = self.transport_serial.lock().await;
+      if !self.transport().is_current(ticket) {
+          tracing::debug!("Skipping load of {:?}, superseded by a later track change", track.track.title);
+          return Ok(false);
+      }
+      self.audio_load(track).await?;
+      Ok(self.transport().is_current(ticket))
+  }
+
   /// Get access to the player store
   pub fn get_store(&self) -> Arc<Mutex<PlayerStore>> { 
       self.store.clone() 
   }
 
+  /// Message for the user when the persisted queue could not be restored in
+  /// full (e.g. after downgrading the app), returned once.
+  pub fn take_restore_warning(&self) -> Option<String> {
+      self.store.lock().ok().and_then(|mut store| store.take_restore_warning())
+  }
+
   /// Load player state from database and update internal store.
   /// Intended to be called during initialization.
   pub fn load_state(&self, db: &Database) -> Result<()> {
@@ -215,6 +692,27 @@ impl AudioPlayer {
       Ok(())
   }
 
+  /// Apply the resume-on-launch policy to the restored queue. The restored
+  /// position is sought to when the current track is first played; returns
+  /// whether to start playing right away.
+  pub fn apply_resume_policy(&self, policy: ResumeOnLaunch) -> bool {
+      let resume = match self.store.lock() {
+          Ok(mut store) => store.launch_resume(policy),
+          Err(_) => return false,
+      };
+      if let Ok(mut position) = self.launch_position.lock() {
+          *position = (resume.position > 0.0).then_some(resume.position);
+      }
+      resume.play
+  }
+
+  /// Save the playback position for the next launch, e.g. when the app exits
+  pub fn save_position(&self) {
+      if let Ok(mut store) = self.store.lock() {
+          store.save_position();
+      }
+  }
+
   /// Register Spotify adapter callbacks (internal use only)
   pub fn register_spotify_adapter(&self, adapter: LibrespotAdapter) {
       // Broadcast to all players; only LibrespotPlayer will accept
@@ -227,154 +725,283 @@ impl AudioPlayer {
       }
   }
 
-  pub async fn audio_load(&self, song: &mut Song) -> Result<()> {
-      let idx = self.get_player(song)?;
+  pub async fn audio_load(&self, track: &mut MediaContent) -> Result<()> {
+      // Seeks are held from here until the backend reports the track ready
+      self.transport().begin_load();
+      let result = self.load_track(track).await;
+      if result.is_err() {
+          self.transport().finish_load();
+      }
+      result
+  }
+
+  async fn load_track(&self, track: &mut MediaContent) -> Result<()> {
+      self.gap_generation.fetch_add(1, Ordering::SeqCst);
+      let idx = self.get_player(track)?;
       self.active.store(idx, Ordering::SeqCst);
+
+      // Edit regions must be known before the first time update of the track,
+      // and its normalization gain before it becomes audible
+      let gain = match self.store.lock() {
+          Ok(mut store) => {
+              store.load_edit_regions(track.track._id.as_deref());
+              store.load_silence(track.track._id.as_deref());
+              store.read_track_gain(track.track._id.as_deref())
+          }
+          Err(_) => None,
+      };
+      if let Ok(mut current) = self.track_gain.lock() {
+          *current = gain;
+      }
+      self.edit_muted.store(false, Ordering::SeqCst);
+      // An A-B loop belongs to the track it was set on
+      if ab_loop::region().is_some() {
+          ab_loop::set_region(None);
+      }
+      let volume = self.audio_get_volume().await?;
+      self.apply_backend_volume(volume)?;
       
       // Get the actual player key from the player itself
-      let player_key = {
+      let (player_key, loops_regions) = {
           let players = self.players_guard()?;
-          players[idx].key()
+          (players[idx].key(), players[idx].loops_regions())
       };
       let store_clone = self.store.clone();
       let events_tx_clone = self.events_tx.clone();
+      let crossfade_clone = self.crossfade.clone();
+      let edit_tx_clone = self.edit_tx.clone();
+      let transport_clone = self.transport.clone();
       
-      // Use the playback_url or path from the song
-      let src = song.song.playback_url.clone().or(song.song.path.clone());
+      // Use the playback_url or path from the track
+      let src = track.track.playback_url.clone().or(track.track.path.clone());
       if src.is_none() {
           return Err(types::errors::MusicError::String("No playback URL or path available".into()));
       }
       
       let state_setter: PlayerEventsSender = Arc::new(move |_player_key: String, ev: PlayerEvents| {
           let actual_player_key = player_key.clone();
+          let mut finished_early = false;
           
           // Handle player events and update store
           if let Ok(mut player_store) = store_clone.lock() {
               if let PlayerEvents::Error(err) = &ev {
                   tracing::error!("Player {} error: {:?}", actual_player_key, err);
                   player_store.blacklist_player(actual_player_key);
-                  player_store.set_state(PlayerState::Stopped);
+                  player_store.set_state(PlayerState::Errored);
               } else {
                   let hooks = EventHooks::default();
                   apply_event_with_hooks(&mut player_store, &ev, &hooks);
+                  // End position reached or crossfade due: finish the entry as if the media ended
+                  finished_early = matches!(ev, PlayerEvents::TimeUpdate(_))
+                      && Self::should_finish_early(&mut player_store, &crossfade_clone, &edit_tx_clone, loops_regions);
+                  if finished_early {
+                      apply_event_with_hooks(&mut player_store, &PlayerEvents::Ended, &hooks);
+                  }
+              }
+
+              // The track is ready (or failed): apply the seek held while it loaded
+              if matches!(ev, PlayerEvents::Play | PlayerEvents::Pause | PlayerEvents::Error(_)) {
+                  let pending = transport_clone
+                      .lock()
+                      .map(|mut transport| transport.finish_load())
+                      .unwrap_or(None);
+                  if let Some(pos) = pending.filter(|_| !matches!(ev, PlayerEvents::Error(_))) {
+                      tracing::debug!("Applying seek to {}s requested while loading", pos);
+                      let raw = player_store.edit_regions().to_raw(pos);
+                      let _ = edit_tx_clone.send(EditAction::Seek(raw));
+                  }
               }
           }
           
           let _ = events_tx_clone.send(ev);
+          if finished_early {
+              let _ = events_tx_clone.send(PlayerEvents::Ended);
+          }
       });
       
-      tracing::debug!("Loading song with player {}: {:?}", idx, song.song.title);
+      tracing::debug!("Loading track with player {}: {:?}", idx, track.track.title);
       
+      // Crossfade only when the previous entry was finished early for it
+      let crossfade_pending = self
+          .store
+          .lock()
+          .map(|mut store| store.take_crossfade_pending())
+          .unwrap_or(false);
+      let fade = self
+          .get_crossfade()
+          .filter(|_| crossfade_pending);
+
       let (tx, rx) = oneshot::channel::<()>();
       {
           let mut players = self.players_guard()?;
           players[idx].add_listeners(state_setter);
-          players[idx].load(src.unwrap(), true, tx);
+          match fade {
+              Some(fade) => players[idx].load_crossfade(src.unwrap(), true, tx, fade),
+              None => players[idx].load(src.unwrap(), true, tx),
+          }
       }
       let _ = rx.await;
+
+      // Skip to the start offset of the queue entry being loaded, or past its
+      // leading silence
+      let start_offset = {
+          let store = self
+              .store
+              .lock()
+              .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+          let is_current = store
+              .get_current_track()
+              .and_then(|c| c.track._id)
+              .is_some_and(|id| track.track._id.as_deref() == Some(id.as_str()));
+          store
+              .get_current_trim()
+              .filter(|_| is_current)
+              .and_then(|o| o.start_offset)
+              .filter(|offset| *offset > 0.0)
+      };
+      if let Some(offset) = start_offset {
+          let players = self.players_guard()?;
+          if let Err(e) = players[idx].seek(offset) {
+              tracing::warn!("Failed to apply start offset {}s: {:?}", offset, e);
+          }
+      }
       
-      // Notify MPRIS of metadata change for the loaded song
-      self.notify_mpris_metadata(song);
+      // Notify MPRIS of metadata change for the loaded track
+      self.notify_mpris_metadata(track);
       
       Ok(())
   }
 
-  /// Play the current or provided song, loading media when necessary.
+  /// Play the current or provided track, loading media when necessary.
   ///
   /// Behavior:
-  /// - When `song` is `Some(&mut Song)`: if it is different from the current song (by `_id`),
-  ///   this function updates the store (queue/index and current song), loads the provided song
+  /// - When `track` is `Some(&mut MediaContent)`: if it is different from the current track (by `_id`),
+  ///   this function updates the store (queue/index and current track), loads the provided track
   ///   into the active backend player via `audio_load()`, and then issues `play()`.
-  ///   If it is the same as the current song, it will skip reloading and only `play()`.
-  /// - When `song` is `None`: this function implements a first-resume heuristic for app startup.
-  ///   If the store indicates `current_time == 0.0` and there is a `current_song` (i.e. the app
-  ///   persisted state but no player has loaded media yet), it will load that song once before `play()`.
+  ///   If it is the same as the current track, it will skip reloading and only `play()`.
+  /// - When `track` is `None`: this function implements a first-resume heuristic for app startup.
+  ///   If the store indicates `current_time == 0.0` and there is a `current_track` (i.e. the app
+  ///   persisted state but no player has loaded media yet), it will load that track once before `play()`.
   ///   Otherwise, it assumes the player is already loaded and only issues `play()`.
   ///
   /// Concurrency & locking:
   /// - Store access uses short-lived `Mutex` locks. The function avoids holding a lock across `await` points.
-  /// - Song loading (I/O and backend initialization) happens outside of any store lock.
+  /// - Track loading (I/O and backend initialization) happens outside of any store lock.
   ///
   /// Side effects:
-  /// - May update the store's current song and queue index via `store.play_now()` when a new song is provided.
+  /// - May update the store's current track and queue index via `store.play_now()` when a new track is provided.
   /// - May perform `audio_load()` before playing.
   /// - On successful `play()`, notifies MPRIS state as `Playing`.
   ///
   /// Errors:
   /// - Propagates errors from store locking, loading (`audio_load()`), and backend `play()`.
+  /// - Returns an error without touching the backend when the state machine forbids
+  ///   entering `Playing` (e.g. nothing is queued).
   ///
   /// Notes:
   /// - The startup heuristic (`current_time == 0.0`) is used to detect the "restoring from persisted state
   ///   but media not yet loaded" scenario on first app launch.
-  pub async fn audio_play(&self, song: Option<&mut Song>) -> Result<()> { 
+  pub async fn audio_play(&self, track: Option<&mut MediaContent>) -> Result<()> { 
       // Decide whether we need to load something before play
       enum LoadAction<'a> {
           None,
-          Provided(&'a mut Song),
-          Current(Song),
+          Provided(LoadTicket, &'a mut MediaContent),
+          Current(LoadTicket, MediaContent),
       }
 
       let mut action = LoadAction::None;
+      let mut launch_position = None;
 
-      match song {
-          Some(s) => {
-              // Compare provided song id with current song id
-              let provided_id = s.song._id.clone();
+      match track {
+          Some(t) => {
+              // Another track was chosen: the restored position doesn't apply
+              if let Ok(mut position) = self.launch_position.lock() {
+                  *position = None;
+              }
+              // Compare provided track id with current track id
+              let provided_id = t.track._id.clone();
               let is_same_as_current = {
                   let store = self
                       .store
                       .lock()
                       .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
-                  let current = store.get_current_song();
-                  match (current.and_then(|s| s.song._id), provided_id.clone()) {
+                  let current = store.get_current_track();
+                  match (current.and_then(|c| c.track._id), provided_id.clone()) {
                       (Some(cur_id), Some(prov_id)) => cur_id == prov_id,
                       _ => false,
                   }
               };
 
               if !is_same_as_current {
-                  // Update store with the new song without holding the lock across await
+                  // Update store with the new track without holding the lock across await
                   {
                       let mut store = self
                           .store
                           .lock()
                           .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
-                      store.play_now(s.clone());
+                      store.play_now(t.clone());
                   }
-                  action = LoadAction::Provided(s);
+                  action = LoadAction::Provided(self.select_track(), t);
               }
           }
           None => {
-              // First-resume heuristic: if app just started (current_time == 0.0)
-              // and there is a current song, load it before play
-              let mut current_song_opt: Option<Song> = None;
+              // First-resume heuristic: if app just started (current_time == 0.0,
+              // or a restored position pending) and there is a current track,
+              // load it before play
+              launch_position = self.launch_position.lock().ok().and_then(|mut p| p.take());
+              let mut current_track_opt: Option<MediaContent> = None;
               {
                   let store = self
                       .store
                       .lock()
                       .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
-                  if store.get_current_time() == 0.0 {
-                      current_song_opt = store.get_current_song();
+                  if store.get_current_time() == 0.0 || launch_position.is_some() {
+                      current_track_opt = store.get_current_track();
                   }
               }
-              if let Some(s) = current_song_opt {
-                  action = LoadAction::Current(s);
+              if let Some(t) = current_track_opt {
+                  action = LoadAction::Current(self.select_track(), t);
               }
           }
       }
 
-      // Execute load if required
-      match action {
-          LoadAction::None => {}
-          LoadAction::Provided(s) => {
-              self.audio_load(s).await?;
-          }
-          LoadAction::Current(mut s) => {
-              self.audio_load(&mut s).await?;
-          }
+      // Execute load if required; a later track change takes over playing
+      let loaded = match action {
+          LoadAction::None => true,
+          LoadAction::Provided(ticket, t) => self.load_selected(ticket, t).await?,
+          LoadAction::Current(ticket, mut t) => self.load_selected(ticket, &mut t).await?,
+      };
+      if !loaded {
+          return Ok(());
+      }
+      // Held by the transport until the track is ready
+      if let Some(position) = launch_position {
+          self.audio_seek(position).await?;
       }
+      self.play_loaded().await
+  }
+
+  /// Start the loaded track, after the load or seek in progress
+  async fn play_loaded(&self) -> Result<()> {
+      let _serial = self.transport_serial.lock().await;
 
-      // Play the currently loaded song
+      // Guard: refuse to start the backend when Playing would be illegal
+      {
+          let store = self
+              .store
+              .lock()
+              .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+          state_machine::check_transition(
+              store.get_player_state(),
+              PlayerState::Playing,
+              store.transition_context(),
+          )
+          .map_err(types::errors::MusicError::String)?;
+      }
+
+      self.override_interruption();
+
+      // Play the currently loaded track
       let idx = self.active.load(Ordering::SeqCst);
       let result = {
           let players = self.players_guard()?;
@@ -386,45 +1013,104 @@ impl AudioPlayer {
       result
   }
 
-  /// Advance to next song in queue: update index in store, load and play.
-  pub async fn play_next(&self) -> Result<Option<Song>> {
-      // Move index and fetch song snapshot without holding lock across await
-      let mut song_opt = None;
-      {
+  /// Advance to next track in queue: update index in store, load and play.
+  pub async fn play_next(&self) -> Result<Option<MediaContent>> {
+      // Move index and fetch track snapshot without holding lock across await
+      let (ticket, track_opt) = {
           let mut store = self
               .store
               .lock()
               .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
-          store.next_song();
-          song_opt = store.get_current_song();
-      }
-      if let Some(mut song) = song_opt.clone() {
-          // Ensure the selected song is actually loaded and then play
-          self.audio_load(&mut song).await?;
-          self.audio_play(None).await?;
-      }
-      Ok(song_opt)
+          if store.get_queue_len() == 0 {
+              return Ok(None);
+          }
+          match store.adjacent_station(true) {
+              Some(index) => store.change_index(index, true),
+              None => store.next_track(),
+          }
+          (self.select_track(), store.get_current_track())
+      };
+      self.load_and_play_selected(ticket, track_opt).await
   }
 
-  /// Go back to previous song in queue: update index in store, load and play.
-  pub async fn play_prev(&self) -> Result<Option<Song>> {
-      let mut song_opt = None;
-      {
+  /// Go back to previous track in queue: update index in store, load and play.
+  pub async fn play_prev(&self) -> Result<Option<MediaContent>> {
+      let (ticket, track_opt) = {
           let mut store = self
               .store
               .lock()
               .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
-          store.prev_song();
-          song_opt = store.get_current_song();
+          if store.get_queue_len() == 0 {
+              return Ok(None);
+          }
+          match store.adjacent_station(false) {
+              Some(index) => store.change_index(index, true),
+              None => store.prev_track(),
+          }
+          (self.select_track(), store.get_current_track())
+      };
+      self.load_and_play_selected(ticket, track_opt).await
+  }
+
+  /// Load `track` again from its (changed) source, e.g. a stream of another
+  /// quality, resuming at the same position and keeping the play/pause state
+  pub async fn reload_current(&self, track: &mut MediaContent) -> Result<()> {
+      let playing = self
+          .store
+          .lock()
+          .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?
+          .get_player_state()
+          == PlayerState::Playing;
+      self.reload_current_with(track, playing).await
+  }
+
+  /// `reload_current`, playing the track afterwards when `play` is set, e.g.
+  /// after its stream broke off and the state no longer tells
+  pub async fn reload_current_with(&self, track: &mut MediaContent, play: bool) -> Result<()> {
+      let position = self
+          .store
+          .lock()
+          .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?
+          .get_current_time();
+      let ticket = self.select_track();
+      if !self.load_selected(ticket, track).await? {
+          return Ok(());
+      }
+      if position > 0.0 {
+          self.audio_seek(position).await?;
+      }
+      if play {
+          self.play_loaded().await?;
       }
-      if let Some(mut song) = song_opt.clone() {
-          self.audio_load(&mut song).await?;
-          self.audio_play(None).await?;
+      Ok(())
+  }
+
+  /// Load and play the track a skip moved to. Skips in quick succession only
+  /// load the last target; the superseded ones return it as well.
+  async fn load_and_play_selected(&self, ticket: LoadTicket, track_opt: Option<MediaContent>) -> Result<Option<MediaContent>> {
+      let Some(mut track) = track_opt else {
+          return Ok(None);
+      };
+      if self.load_selected(ticket, &mut track).await? {
+          self.play_loaded().await?;
+          return Ok(Some(track));
       }
-      Ok(song_opt)
+      let store = self
+          .store
+          .lock()
+          .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+      Ok(store.get_current_track())
   }
 
   pub async fn audio_pause(&self) -> Result<()> { 
+      let _serial = self.transport_serial.lock().await;
+      self.override_interruption();
+      self.pause_active()
+  }
+
+  /// Pause the active backend without touching the interruption state
+  fn pause_active(&self) -> Result<()> {
+      self.gap_generation.fetch_add(1, Ordering::SeqCst);
       let idx = self.active.load(Ordering::SeqCst);
       let result = {
           let players = self.players_guard()?;
@@ -437,25 +1123,54 @@ impl AudioPlayer {
   }
 
   pub async fn audio_stop(&self) -> Result<()> { 
+      // Skips still waiting to load are dropped
+      self.select_track();
+      let _serial = self.transport_serial.lock().await;
+      self.gap_generation.fetch_add(1, Ordering::SeqCst);
       let idx = self.active.load(Ordering::SeqCst);
       let result = {
           let mut players = self.players_guard()?;
           players[idx].stop()
       };
       if result.is_ok() {
+          if let Ok(mut store) = self.store.lock() {
+              store.set_state(PlayerState::Stopped);
+          }
           self.notify_mpris_state(PlayerState::Stopped);
       }
       result
   }
 
+  /// Seek to `pos` seconds of the edited timeline (skip regions removed).
+  /// While the track is loading only the latest seek is kept, applied once it
+  /// is ready.
   pub async fn audio_seek(&self, pos: f64) -> Result<()> { 
+      let live = self
+          .store
+          .lock()
+          .map(|store| store.get_current_track().as_ref().is_some_and(is_station))
+          .unwrap_or(false);
+      if live {
+          tracing::debug!("Ignoring seek to {}s on a radio station", pos);
+          return Ok(());
+      }
+      if self.transport().defer_seek(pos) {
+          tracing::debug!("Holding seek to {}s until the track is loaded", pos);
+          return Ok(());
+      }
+      let _serial = self.transport_serial.lock().await;
+      let raw = self
+          .store
+          .lock()
+          .map(|store| store.edit_regions().to_raw(pos))
+          .unwrap_or(pos);
       let idx = self.active.load(Ordering::SeqCst);
       let result = {
           let players = self.players_guard()?;
-          players[idx].seek(pos)
+          players[idx].seek(raw)
       };
       if result.is_ok() {
-          self.notify_mpris_position(pos);
+          self.notify_mpris_seeked(pos);
       }
       result
   }
@@ -473,9 +1188,7 @@ impl AudioPlayer {
       }
 
       // Propagate to active backend player
-      let idx = self.active.load(Ordering::SeqCst);
-      let players = self.players_guard()?;
-      players[idx].set_volume(volume as f64)
+      self.apply_backend_volume(volume)
   }
 
   pub async fn audio_get_volume(&self) -> Result<f32> { 
@@ -489,4 +1202,63 @@ impl AudioPlayer {
       };
       Ok((raw / 100.0) as f32)
   }
+
+  /// Set the playback rate, clamped to 0.5 - 3.0, and return the one applied.
+  /// The pitch is kept; the librespot backend always plays at normal rate.
+  pub fn audio_set_rate(&self, rate: f32) -> f32 {
+      let rate = crate::stretch::set_rate(rate);
+      self.notify_mpris_rate(rate as f64);
+      rate
+  }
+
+  pub fn audio_get_rate(&self) -> f32 {
+      crate::stretch::rate()
+  }
+
+  /// Loop `start`..`end` of the loaded track until cleared or another track
+  /// loads. Positions are those shown, skip regions removed; the end is
+  /// clamped to the track. Returns the region applied.
+  pub fn audio_set_loop_region(&self, start: f64, end: f64) -> Result<LoopRegion> {
+      let store = self
+          .store
+          .lock()
+          .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+      let Some(track) = store.get_current_track() else {
+          return Err("No track is loaded".into());
+      };
+      if is_station(&track) {
+          return Err("A radio station can't be looped".into());
+      }
+      let duration = store.edited_duration().or(track.track.duration).filter(|d| *d > 0.0);
+      let end = duration.map_or(end, |d| end.min(d));
+      let region = LoopRegion { start, end };
+      region.validate()?;
+      if end - start < ab_loop::MIN_LOOP_SECS {
+          return Err(format!("A loop must last at least {}s", ab_loop::MIN_LOOP_SECS).into());
+      }
+      let regions = store.edit_regions();
+      ab_loop::set_region(Some(LoopRegion {
+          start: regions.to_raw(start),
+          end: regions.to_raw(end),
+      }));
+      Ok(region)
+  }
+
+  /// Stop looping; returns whether a region was looping
+  pub fn audio_clear_loop_region(&self) -> bool {
+      let looping = ab_loop::region().is_some();
+      ab_loop::set_region(None);
+      looping
+  }
+
+  /// Region looping, in positions shown
+  pub fn audio_get_loop_region(&self) -> Option<LoopRegion> {
+      let region = ab_loop::region()?;
+      let store = self.store.lock().ok()?;
+      let regions = store.edit_regions();
+      Some(LoopRegion {
+          start: regions.to_edited(region.start),
+          end: regions.to_edited(region.end),
+      })
+  }
 }
\ No newline at end of file
@@ -0,0 +1,113 @@
+// crates/audio-player/src/crossfade.rs
+// Transitions between queue entries: crossfade configuration and gain curves
+// (the rodio backend mixes the tail of the outgoing track with the head of the
+// incoming one using these gains), and the mutually exclusive silence gap.
+
+use std::time::Duration;
+
+use types::settings::music::{CrossfadeCurve, MusicPlaybackSettings};
+
+/// Longest supported crossfade
+pub const MAX_CROSSFADE: Duration = Duration::from_secs(12);
+/// Longest supported silence between two queue entries
+pub const MAX_TRACK_GAP: Duration = Duration::from_secs(30);
+
+/// Resolved crossfade settings. A zero duration disables crossfading.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CrossfadeConfig {
+    pub duration: Duration,
+    pub curve: CrossfadeCurve,
+}
+
+impl CrossfadeConfig {
+    /// Build a config, clamping the duration to `MAX_CROSSFADE`.
+    pub fn new(duration_ms: u32, curve: CrossfadeCurve) -> Self {
+        Self {
+            duration: Duration::from_millis(duration_ms as u64).min(MAX_CROSSFADE),
+            curve,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.duration.is_zero()
+    }
+
+    /// Gains of the outgoing and incoming track at `progress` (0.0..=1.0) of the fade.
+    pub fn gains(&self, progress: f32) -> (f32, f32) {
+        let p = progress.clamp(0.0, 1.0);
+        match self.curve {
+            CrossfadeCurve::Linear => (1.0 - p, p),
+            CrossfadeCurve::Logarithmic => (db_ramp(1.0 - p), db_ramp(p)),
+            CrossfadeCurve::EqualPower => {
+                let angle = p * std::f32::consts::FRAC_PI_2;
+                (angle.cos(), angle.sin())
+            }
+        }
+    }
+}
+
+impl From<&MusicPlaybackSettings> for CrossfadeConfig {
+    fn from(s: &MusicPlaybackSettings) -> Self {
+        Self::new(s.crossfade_ms.unwrap_or(0), s.crossfade_curve.unwrap_or_default())
+    }
+}
+
+/// Silence to insert between queue entries. Always zero while a crossfade is set.
+pub fn track_gap(s: &MusicPlaybackSettings) -> Duration {
+    if CrossfadeConfig::from(s).is_enabled() {
+        return Duration::ZERO;
+    }
+    Duration::from_millis(s.track_gap_ms.unwrap_or(0) as u64).min(MAX_TRACK_GAP)
+}
+
+/// Gain for a level ramping linearly in decibels from -60 dB (silent) to 0 dB.
+fn db_ramp(level: f32) -> f32 {
+    const FLOOR_DB: f32 = -60.0;
+    if level <= 0.0 {
+        return 0.0;
+    }
+    10f32.powf(FLOOR_DB * (1.0 - level) / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration_is_clamped() {
+        let config = CrossfadeConfig::new(30_000, CrossfadeCurve::Linear);
+        assert_eq!(config.duration, MAX_CROSSFADE);
+        assert!(!CrossfadeConfig::new(0, CrossfadeCurve::Linear).is_enabled());
+    }
+
+    #[test]
+    fn curves_start_and_end_at_full_scale() {
+        for curve in [CrossfadeCurve::Linear, CrossfadeCurve::Logarithmic, CrossfadeCurve::EqualPower] {
+            let config = CrossfadeConfig::new(4_000, curve);
+            let (out_start, in_start) = config.gains(0.0);
+            let (out_end, in_end) = config.gains(1.0);
+            assert!((out_start - 1.0).abs() < 1e-6 && in_start.abs() < 1e-6, "{:?}", curve);
+            assert!(out_end.abs() < 1e-6 && (in_end - 1.0).abs() < 1e-6, "{:?}", curve);
+        }
+    }
+
+    #[test]
+    fn crossfade_disables_track_gap() {
+        let mut settings = MusicPlaybackSettings {
+            track_gap_ms: Some(2_000),
+            ..Default::default()
+        };
+        assert_eq!(track_gap(&settings), Duration::from_secs(2));
+        settings.crossfade_ms = Some(3_000);
+        assert_eq!(track_gap(&settings), Duration::ZERO);
+    }
+
+    #[test]
+    fn equal_power_keeps_power_constant() {
+        let config = CrossfadeConfig::new(4_000, CrossfadeCurve::EqualPower);
+        for step in 0..=10 {
+            let (out, inc) = config.gains(step as f32 / 10.0);
+            assert!((out * out + inc * inc - 1.0).abs() < 1e-5);
+        }
+    }
+}
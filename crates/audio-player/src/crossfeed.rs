@@ -0,0 +1,158 @@
+// crates/audio-player/src/crossfeed.rs
+// Headphone crossfeed: each stereo channel receives a low-passed, attenuated
+// copy of the other, the way both ears hear both speakers. The level is
+// global and read by every playing source, so changes apply immediately.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+
+use rodio::source::SeekError;
+use rodio::Source;
+use types::settings::music::CrossfeedLevel;
+
+/// Frequencies above this are not fed to the other channel
+const CUTOFF_HZ: f32 = 700.0;
+
+static LEVEL: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_level(level: CrossfeedLevel) {
+    let value = match level {
+        CrossfeedLevel::Off => 0,
+        CrossfeedLevel::Low => 1,
+        CrossfeedLevel::Medium => 2,
+        CrossfeedLevel::High => 3,
+    };
+    LEVEL.store(value, Ordering::Relaxed);
+}
+
+pub fn level() -> CrossfeedLevel {
+    match LEVEL.load(Ordering::Relaxed) {
+        1 => CrossfeedLevel::Low,
+        2 => CrossfeedLevel::Medium,
+        3 => CrossfeedLevel::High,
+        _ => CrossfeedLevel::Off,
+    }
+}
+
+/// Share of the other channel mixed in
+fn amount(level: CrossfeedLevel) -> f32 {
+    match level {
+        CrossfeedLevel::Off => 0.0,
+        CrossfeedLevel::Low => 0.2,
+        CrossfeedLevel::Medium => 0.35,
+        CrossfeedLevel::High => 0.5,
+    }
+}
+
+/// One-pole low-pass state of both channels
+#[derive(Debug, Default, Clone, Copy)]
+struct Filter {
+    low: [f32; 2],
+}
+
+impl Filter {
+    /// Mix one frame; the output is scaled so a centered signal keeps its level
+    fn process(&mut self, left: f32, right: f32, amount: f32, alpha: f32) -> (f32, f32) {
+        self.low[0] += alpha * (left - self.low[0]);
+        self.low[1] += alpha * (right - self.low[1]);
+        let scale = 1.0 / (1.0 + amount);
+        (
+            (left + amount * self.low[1]) * scale,
+            (right + amount * self.low[0]) * scale,
+        )
+    }
+}
+
+fn smoothing(sample_rate: u32) -> f32 {
+    1.0 - (-2.0 * std::f32::consts::PI * CUTOFF_HZ / sample_rate.max(1) as f32).exp()
+}
+
+/// Source applying the crossfeed to stereo input; other channel layouts pass
+/// through unchanged
+pub struct Crossfeed<S> {
+    inner: S,
+    filter: Filter,
+    /// Right sample of the current frame, returned after the left one
+    pending: Option<f32>,
+}
+
+impl<S: Source> Crossfeed<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            filter: Filter::default(),
+            pending: None,
+        }
+    }
+}
+
+impl<S: Source> Iterator for Crossfeed<S> {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(right) = self.pending.take() {
+            return Some(right);
+        }
+        let channels: u16 = self.inner.channels().into();
+        let level = level();
+        let left = self.inner.next()?;
+        if channels != 2 || level == CrossfeedLevel::Off {
+            return Some(left);
+        }
+        let Some(right) = self.inner.next() else {
+            return Some(left);
+        };
+        let alpha = smoothing(self.inner.sample_rate().into());
+        let (left, right) = self.filter.process(left, right, amount(level), alpha);
+        self.pending = Some(right);
+        Some(left)
+    }
+}
+
+impl<S: Source> Source for Crossfeed<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        self.inner.current_span_len()
+    }
+
+    fn channels(&self) -> rodio::ChannelCount {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> rodio::SampleRate {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.pending = None;
+        self.filter = Filter::default();
+        self.inner.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hard_panned_low_frequencies_reach_the_other_channel() {
+        let mut filter = Filter::default();
+        let alpha = smoothing(44_100);
+        let mut last = (0.0, 0.0);
+        // A constant signal is all low frequency
+        for _ in 0..2_000 {
+            last = filter.process(1.0, 0.0, amount(CrossfeedLevel::Medium), alpha);
+        }
+        assert!((last.0 - 1.0 / 1.35).abs() < 1e-3);
+        assert!((last.1 - 0.35 / 1.35).abs() < 1e-3);
+
+        let mut filter = Filter::default();
+        for _ in 0..2_000 {
+            last = filter.process(0.5, 0.5, amount(CrossfeedLevel::High), alpha);
+        }
+        assert!((last.0 - 0.5).abs() < 1e-3 && (last.1 - 0.5).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,47 @@
+// crates/audio-player/src/data_usage.rs
+// Bytes the player backends fetched over the network, for the app's data
+// usage counter. The app collects them with `take_streamed_bytes`.
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+static STREAMED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Bytes streamed since the last call
+pub fn take_streamed_bytes() -> u64 {
+    STREAMED_BYTES.swap(0, Ordering::Relaxed)
+}
+
+/// Progress callback state of one stream: stream download reports the
+/// position reached, of which only the growth is new data
+#[derive(Debug, Clone, Default)]
+pub struct StreamCounter {
+    reached: Arc<AtomicU64>,
+}
+
+impl StreamCounter {
+    pub fn update(&self, position: u64) {
+        let previous = self.reached.fetch_max(position, Ordering::Relaxed);
+        if position > previous {
+            STREAMED_BYTES.fetch_add(position - previous, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_new_bytes_are_counted() {
+        take_streamed_bytes();
+        let counter = StreamCounter::default();
+        counter.update(100);
+        counter.update(60);
+        counter.update(250);
+        assert_eq!(take_streamed_bytes(), 250);
+        assert_eq!(take_streamed_bytes(), 0);
+    }
+}
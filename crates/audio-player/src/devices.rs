@@ -0,0 +1,136 @@
+// crates/audio-player/src/devices.rs
+// Audio output devices: enumeration, opening a stream on a chosen device with
+// fallback to the system default, and the selection shared between the core
+// and the backends. Devices are identified by their name, the only identifier
+// cpal exposes on every host.
+
+use std::sync::Mutex;
+
+use crossbeam_channel::Sender;
+use rodio::cpal::{
+    self,
+    traits::{DeviceTrait, HostTrait},
+};
+use rodio::{OutputStream, OutputStreamBuilder};
+use serde::Serialize;
+use types::errors::{MusicError, Result};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OutputDevice {
+    pub name: String,
+    pub is_default: bool,
+    /// The device playback currently goes to
+    pub is_active: bool,
+}
+
+/// Output device changes reported to the integrator
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "data")]
+pub enum DeviceEvent {
+    /// Playback now goes to `name`
+    Changed { name: String },
+    /// The active device disappeared (e.g. Bluetooth headphones disconnected);
+    /// playback moves to the default device
+    Lost { name: String },
+}
+
+/// Device chosen by the user and device actually opened by the backend
+#[derive(Debug)]
+pub struct OutputSelection {
+    /// `None` follows the system default
+    preferred: Mutex<Option<String>>,
+    active: Mutex<Option<String>>,
+    events_tx: Sender<DeviceEvent>,
+}
+
+impl OutputSelection {
+    pub fn new(events_tx: Sender<DeviceEvent>) -> Self {
+        Self {
+            preferred: Mutex::new(None),
+            active: Mutex::new(None),
+            events_tx,
+        }
+    }
+
+    pub fn preferred(&self) -> Option<String> {
+        self.preferred.lock().ok().and_then(|p| p.clone())
+    }
+
+    /// Remember `name` as the preferred device, returns whether it changed.
+    pub(crate) fn set_preferred(&self, name: Option<String>) -> bool {
+        let Ok(mut preferred) = self.preferred.lock() else { return false };
+        if *preferred == name {
+            return false;
+        }
+        *preferred = name;
+        true
+    }
+
+    pub fn active(&self) -> Option<String> {
+        self.active.lock().ok().and_then(|a| a.clone())
+    }
+
+    pub(crate) fn set_active(&self, name: String) {
+        if let Ok(mut active) = self.active.lock() {
+            *active = Some(name.clone());
+        }
+        let _ = self.events_tx.send(DeviceEvent::Changed { name });
+    }
+
+    pub(crate) fn report_lost(&self, name: String) {
+        let _ = self.events_tx.send(DeviceEvent::Lost { name });
+    }
+}
+
+fn default_device_name(host: &cpal::Host) -> Option<String> {
+    host.default_output_device().and_then(|d| d.name().ok())
+}
+
+/// Names of the output devices currently available
+pub fn device_names() -> Vec<String> {
+    cpal::default_host()
+        .output_devices()
+        .map(|devices| devices.filter_map(|d| d.name().ok()).collect())
+        .unwrap_or_default()
+}
+
+/// Output devices currently available, marking the default one. `is_active`
+/// is left to the caller, which knows the selection.
+pub fn list_output_devices() -> Result<Vec<OutputDevice>> {
+    let host = cpal::default_host();
+    let default_name = default_device_name(&host);
+    let devices = host
+        .output_devices()
+        .map_err(|e| MusicError::String(format!("Failed to enumerate output devices: {}", e)))?;
+    Ok(devices
+        .filter_map(|d| d.name().ok())
+        .map(|name| OutputDevice {
+            is_default: default_name.as_deref() == Some(name.as_str()),
+            is_active: false,
+            name,
+        })
+        .collect())
+}
+
+/// Open an output stream on `preferred`, or on the default device when it is
+/// `None`, missing or fails to open. Returns the stream and the name of the
+/// device it plays on.
+pub(crate) fn open_output_stream(preferred: Option<&str>) -> Result<(OutputStream, String)> {
+    let host = cpal::default_host();
+    if let Some(name) = preferred {
+        let device = host
+            .output_devices()
+            .ok()
+            .and_then(|mut devices| devices.find(|d| d.name().ok().as_deref() == Some(name)));
+        match device.map(|d| OutputStreamBuilder::from_device(d).and_then(|b| b.open_stream())) {
+            Some(Ok(stream)) => return Ok((stream, name.to_string())),
+            Some(Err(e)) => tracing::warn!("Failed to open output device {}: {}, using the default", name, e),
+            None => tracing::warn!("Output device {} not found, using the default", name),
+        }
+    }
+
+    let stream = OutputStreamBuilder::open_default_stream()
+        .map_err(|e| MusicError::String(format!("Failed to open the default output stream: {}", e)))?;
+    let name = default_device_name(&host).unwrap_or_else(|| "default".to_string());
+    Ok((stream, name))
+}
@@ -0,0 +1,210 @@
+// crates/audio-player/src/edit_regions.rs
+// Non-destructive edit regions of the loaded track: skip ranges are jumped over
+// and removed from the timeline shown to the user, mute ranges play silently.
+// Backends keep working in positions of the original audio; this maps between
+// those and the edited timeline, and decides what to do as playback crosses a
+// region. Regions are applied on time updates, so their precision is that of
+// the backend's position reports.
+
+use types::ui::player_details::{EditRegion, EditRegionKind};
+
+/// A skip region ending this close to the end of the track finishes it
+const END_TOLERANCE: f64 = 0.25;
+
+/// Change the core applies to the backend as playback crosses a region
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditAction {
+    /// Jump to this position of the original audio
+    Seek(f64),
+    Mute(bool),
+    /// A skip region runs to the end of the track: finish it instead
+    Finish,
+}
+
+/// Sorted, merged ranges of one kind
+fn ranges(regions: &[EditRegion], kind: EditRegionKind) -> Vec<(f64, f64)> {
+    let mut ranges: Vec<(f64, f64)> = regions
+        .iter()
+        .filter(|r| r.kind == kind && r.validate().is_ok())
+        .map(|r| (r.start, r.end))
+        .collect();
+    ranges.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut merged: Vec<(f64, f64)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Edit regions of a track, normalized for the timeline math
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EditRegions {
+    skips: Vec<(f64, f64)>,
+    mutes: Vec<(f64, f64)>,
+}
+
+impl EditRegions {
+    /// Overlapping regions of the same kind are merged; invalid ones are ignored.
+    pub fn new(regions: &[EditRegion]) -> Self {
+        Self {
+            skips: ranges(regions, EditRegionKind::Skip),
+            mutes: ranges(regions, EditRegionKind::Mute),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.skips.is_empty() && self.mutes.is_empty()
+    }
+
+    /// Whether the edited timeline differs from the original one
+    pub fn has_skips(&self) -> bool {
+        !self.skips.is_empty()
+    }
+
+    /// End of the skip region containing `raw`
+    pub fn skip_target(&self, raw: f64) -> Option<f64> {
+        self.skips
+            .iter()
+            .find(|(start, end)| raw >= *start && raw < *end)
+            .map(|(_, end)| *end)
+    }
+
+    pub fn is_muted(&self, raw: f64) -> bool {
+        self.mutes.iter().any(|(start, end)| raw >= *start && raw < *end)
+    }
+
+    /// Position on the edited timeline of `raw`, a position of the original audio
+    pub fn to_edited(&self, raw: f64) -> f64 {
+        let mut removed = 0.0;
+        for (start, end) in &self.skips {
+            if raw >= *end {
+                removed += end - start;
+            } else {
+                if raw > *start {
+                    removed += raw - start;
+                }
+                break;
+            }
+        }
+        raw - removed
+    }
+
+    /// Position of the original audio shown at `edited` on the edited timeline.
+    /// The start of a skip region maps to its end.
+    pub fn to_raw(&self, edited: f64) -> f64 {
+        let mut raw = edited;
+        for (start, end) in &self.skips {
+            if raw >= *start {
+                raw += end - start;
+            } else {
+                break;
+            }
+        }
+        raw
+    }
+}
+
+/// Regions of the loaded track and what was already applied to the backend
+#[derive(Debug, Default)]
+pub struct EditPlayback {
+    regions: EditRegions,
+    /// Skip target already requested, until playback leaves the region
+    pending_skip: Option<f64>,
+    muted: bool,
+}
+
+impl EditPlayback {
+    pub fn new(regions: EditRegions) -> Self {
+        Self {
+            regions,
+            ..Default::default()
+        }
+    }
+
+    pub fn regions(&self) -> &EditRegions {
+        &self.regions
+    }
+
+    /// Action due when playback of a track lasting `duration` reaches `raw`
+    pub fn on_time(&mut self, raw: f64, duration: Option<f64>) -> Option<EditAction> {
+        if let Some(target) = self.regions.skip_target(raw) {
+            if self.pending_skip == Some(target) {
+                return None;
+            }
+            self.pending_skip = Some(target);
+            if duration.is_some_and(|d| target >= d - END_TOLERANCE) {
+                return Some(EditAction::Finish);
+            }
+            return Some(EditAction::Seek(target));
+        }
+        self.pending_skip = None;
+
+        let muted = self.regions.is_muted(raw);
+        if muted != self.muted {
+            self.muted = muted;
+            return Some(EditAction::Mute(muted));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(start: f64, end: f64, kind: EditRegionKind) -> EditRegion {
+        EditRegion { start, end, kind }
+    }
+
+    #[test]
+    fn timeline_maps_both_ways() {
+        let regions = EditRegions::new(&[
+            region(30.0, 40.0, EditRegionKind::Skip),
+            region(10.0, 20.0, EditRegionKind::Skip),
+        ]);
+        assert_eq!(regions.to_edited(5.0), 5.0);
+        assert_eq!(regions.to_edited(15.0), 10.0);
+        assert_eq!(regions.to_edited(42.0), 22.0);
+        assert_eq!(regions.to_raw(10.0), 20.0);
+        assert_eq!(regions.to_raw(22.0), 42.0);
+        // Duration of a 60s track
+        assert_eq!(regions.to_edited(60.0), 40.0);
+    }
+
+    #[test]
+    fn overlapping_regions_are_merged() {
+        let regions = EditRegions::new(&[
+            region(0.0, 10.0, EditRegionKind::Skip),
+            region(5.0, 12.0, EditRegionKind::Skip),
+            region(20.0, 15.0, EditRegionKind::Skip),
+        ]);
+        assert_eq!(regions.skip_target(3.0), Some(12.0));
+        assert_eq!(regions.to_edited(30.0), 18.0);
+    }
+
+    #[test]
+    fn skips_are_requested_once() {
+        let mut playback = EditPlayback::new(EditRegions::new(&[
+            region(0.0, 8.0, EditRegionKind::Skip),
+            region(50.0, 60.0, EditRegionKind::Skip),
+        ]));
+        assert_eq!(playback.on_time(0.1, Some(60.0)), Some(EditAction::Seek(8.0)));
+        assert_eq!(playback.on_time(0.3, Some(60.0)), None);
+        assert_eq!(playback.on_time(8.2, Some(60.0)), None);
+        assert_eq!(playback.on_time(50.5, Some(60.0)), Some(EditAction::Finish));
+    }
+
+    #[test]
+    fn mute_follows_the_position() {
+        let mut playback = EditPlayback::new(EditRegions::new(&[region(10.0, 20.0, EditRegionKind::Mute)]));
+        assert_eq!(playback.on_time(5.0, None), None);
+        assert_eq!(playback.on_time(10.5, None), Some(EditAction::Mute(true)));
+        assert_eq!(playback.on_time(11.0, None), None);
+        assert_eq!(playback.on_time(20.0, None), Some(EditAction::Mute(false)));
+        // Mute regions leave the timeline alone
+        assert_eq!(playback.regions().to_edited(15.0), 15.0);
+    }
+}
@@ -44,6 +44,12 @@ pub fn apply_event_basic(store: &mut PlayerStore, ev: &PlayerEvents) {
         PlayerEvents::TimeUpdate(time) => {
             store.update_time(*time);
         }
+        PlayerEvents::StreamTitle(_) | PlayerEvents::Buffering(_) | PlayerEvents::Stalled => {
+            // Only of interest to the UI and the integrator
+        }
+        PlayerEvents::StreamExpired(_) => {
+            // The integrator resolves the URL again and reloads the track
+        }
         PlayerEvents::Error(_) => {
             // Intentionally left for caller to handle
         }
@@ -91,8 +97,9 @@ pub fn apply_event_with_hooks(store: &mut PlayerStore, ev: &PlayerEvents, hooks:
                     store.set_state(PlayerState::Playing);
                     if let Some(cb) = &hooks.on_state { cb(PlayerState::Playing); }
                 }
-                PlayerMode::Shuffle => {
-                    // Random playback: get next shuffled index
+                PlayerMode::Shuffle | PlayerMode::ShuffleAlbums => {
+                    // Random playback: get next shuffled index (by album, the next entry
+                    // of the album until it ends)
                     if let Some(next_idx) = store.get_next_shuffle_index() {
                         store.change_index(next_idx, true);
                         store.set_state(PlayerState::Playing);
@@ -128,6 +135,12 @@ pub fn apply_event_with_hooks(store: &mut PlayerStore, ev: &PlayerEvents, hooks:
             store.update_time(*time);
             if let Some(cb) = &hooks.on_position { cb(*time); }
         }
+        PlayerEvents::StreamTitle(_) | PlayerEvents::Buffering(_) | PlayerEvents::Stalled => {
+            // Only of interest to the UI and the integrator
+        }
+        PlayerEvents::StreamExpired(_) => {
+            // The integrator resolves the URL again and reloads the track
+        }
         PlayerEvents::Error(_) => {
             // Intentionally left for caller to handle
         }
@@ -151,7 +164,7 @@ fn handle_playback_ended_basic(store: &mut PlayerStore) {
             store.change_index(store.data.queue.current_index, true);
             store.set_state(PlayerState::Playing);
         }
-        PlayerMode::Shuffle => {
+        PlayerMode::Shuffle | PlayerMode::ShuffleAlbums => {
             if let Some(next_idx) = store.get_next_shuffle_index() {
                 store.change_index(next_idx, true);
                 store.set_state(PlayerState::Playing);
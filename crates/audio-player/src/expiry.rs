@@ -0,0 +1,116 @@
+// crates/audio-player/src/expiry.rs
+// Expired stream URLs. Providers sign the stream URLs they resolve for a
+// limited time; past it their servers answer 401, 403 or 410, either to the
+// first request or to a later range or segment. Players report it as a
+// `StreamExpired` event rather than an error, for the integrator to resolve
+// the URL again and resume the track where it stopped.
+
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::Arc;
+
+use types::errors::{error_helpers, MusicError};
+
+/// `StreamFailure` value of a failure whose HTTP status is not known
+const UNKNOWN_STATUS: u16 = 1;
+
+/// Whether a server answering `status` refuses a URL that expired
+pub fn is_expired_status(status: u16) -> bool {
+    matches!(status, 401 | 403 | 410)
+}
+
+#[derive(Debug)]
+pub struct StreamExpired {
+    pub status: u16,
+}
+
+impl std::fmt::Display for StreamExpired {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Stream URL expired (HTTP {})", self.status)
+    }
+}
+
+impl std::error::Error for StreamExpired {}
+
+pub fn expired(status: u16) -> MusicError {
+    error_helpers::to_network_error(StreamExpired { status })
+}
+
+/// HTTP status of `err` when it tells the stream URL expired
+pub fn expired_status(err: &MusicError) -> Option<u16> {
+    let MusicError::NetworkError(inner) = err else {
+        return None;
+    };
+    let status = if let Some(expired) = inner.downcast_ref::<StreamExpired>() {
+        expired.status
+    } else {
+        inner.downcast_ref::<reqwest::Error>()?.status()?.as_u16()
+    };
+    is_expired_status(status).then_some(status)
+}
+
+/// Status the server answers for `url` now, `None` when it can't be reached.
+/// Asks for a single byte, the stream itself is not downloaded.
+pub async fn probe_status(url: &str) -> Option<u16> {
+    let response = reqwest::Client::new()
+        .get(url)
+        .header(reqwest::header::RANGE, "bytes=0-0")
+        .send()
+        .await
+        .ok()?;
+    Some(response.status().as_u16())
+}
+
+/// Failure of a stream after it started, set by its readers and checked
+/// once playback stopped, to tell an expired URL from the end of the track
+#[derive(Debug, Clone, Default)]
+pub struct StreamFailure(Arc<AtomicU16>);
+
+impl StreamFailure {
+    /// The stream failed, with the HTTP status behind it when known
+    pub fn report(&self, status: Option<u16>) {
+        match status {
+            Some(status) => self.0.store(status, Ordering::Relaxed),
+            // Keep a status reported before
+            None => {
+                let _ = self.0.compare_exchange(0, UNKNOWN_STATUS, Ordering::Relaxed, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn failed(&self) -> bool {
+        self.0.load(Ordering::Relaxed) != 0
+    }
+
+    /// HTTP status of the failure, when known
+    pub fn status(&self) -> Option<u16> {
+        let status = self.0.load(Ordering::Relaxed);
+        (status > UNKNOWN_STATUS).then_some(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tells_expired_urls_from_other_errors() {
+        assert_eq!(expired_status(&expired(403)), Some(403));
+        assert_eq!(expired_status(&expired(404)), None);
+        assert_eq!(expired_status(&MusicError::from("Segment fetch stopped")), None);
+    }
+
+    #[test]
+    fn keeps_the_known_status_of_a_failure() {
+        let failure = StreamFailure::default();
+        assert!(!failure.failed());
+        failure.report(Some(410));
+        failure.report(None);
+        assert!(failure.failed());
+        assert_eq!(failure.status(), Some(410));
+
+        let unknown = StreamFailure::default();
+        unknown.report(None);
+        assert!(unknown.failed());
+        assert_eq!(unknown.status(), None);
+    }
+}
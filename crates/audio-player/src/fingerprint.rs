@@ -0,0 +1,150 @@
+// crates/audio-player/src/fingerprint.rs
+// Chromaprint audio fingerprints. They identify a recording whatever its file,
+// path or tags: moved files and re-rips of the same audio get fingerprints
+// that compare as similar, and AcoustID looks recordings up by them.
+
+use std::fs::File;
+use std::path::Path;
+
+use base64::Engine;
+use rodio::Source;
+use rusty_chromaprint::{Configuration, FingerprintCompressor, Fingerprinter};
+use types::errors::{error_helpers, Result};
+
+/// Audio fingerprinted from the start of the file, as AcoustID expects
+const MAX_SECONDS: u32 = 120;
+/// Largest shift between two fingerprints tried when comparing them, in items
+/// (about 10 seconds), for re-rips with more or less leading silence
+const MAX_OFFSET: usize = 80;
+/// Fewest overlapping items for two fingerprints to be compared at all
+const MIN_OVERLAP: usize = 40;
+
+/// Fingerprint and length of a decoded file
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioFingerprint {
+    pub fingerprint: Vec<u32>,
+    /// Length of the whole file in seconds
+    pub duration: f64,
+}
+
+fn configuration() -> Configuration {
+    // The default algorithm of fpcalc, the one AcoustID indexes
+    Configuration::preset_test2()
+}
+
+/// Decode the first two minutes of a local file and fingerprint them. The
+/// rest is only decoded to measure the length when the container doesn't
+/// tell it.
+pub fn fingerprint_file(path: &Path) -> Result<AudioFingerprint> {
+    let file = File::open(path)?;
+    let decoder = rodio::Decoder::try_from(file).map_err(error_helpers::to_media_error)?;
+    let channels = u16::from(decoder.channels()).max(1);
+    let sample_rate: u32 = decoder.sample_rate().into();
+    let total_duration = decoder.total_duration();
+
+    let config = configuration();
+    let mut printer = Fingerprinter::new(&config);
+    printer
+        .start(sample_rate, channels as u32)
+        .map_err(|e| format!("Can't fingerprint {}: {:?}", path.display(), e))?;
+
+    let limit = (MAX_SECONDS * sample_rate) as u64 * channels as u64;
+    let mut buffer: Vec<i16> = Vec::with_capacity(sample_rate as usize * channels as usize);
+    let mut decoded: u64 = 0;
+    let mut samples = decoder.into_iter();
+    for sample in samples.by_ref() {
+        buffer.push((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16);
+        decoded += 1;
+        if buffer.len() == buffer.capacity() {
+            printer.consume(&buffer);
+            buffer.clear();
+        }
+        if decoded >= limit {
+            break;
+        }
+    }
+    printer.consume(&buffer);
+    printer.finish();
+
+    let duration = match total_duration {
+        Some(duration) => duration.as_secs_f64(),
+        None => {
+            decoded += samples.count() as u64;
+            decoded as f64 / (sample_rate as f64 * channels as f64)
+        }
+    };
+    let fingerprint = printer.fingerprint().to_vec();
+    if fingerprint.is_empty() {
+        return Err(format!("Not enough audio to fingerprint in {}", path.display()).into());
+    }
+    Ok(AudioFingerprint { fingerprint, duration })
+}
+
+/// Share of matching bits of the best alignment of two fingerprints, from 0.5
+/// (unrelated audio) to 1.0 (same audio). 0.0 when they are too short to tell.
+pub fn similarity(a: &[u32], b: &[u32]) -> f64 {
+    let mut best = 0.0;
+    for offset in -(MAX_OFFSET as isize)..=MAX_OFFSET as isize {
+        let (a, b) = if offset >= 0 {
+            (a.get(offset as usize..).unwrap_or_default(), b)
+        } else {
+            (a, b.get(offset.unsigned_abs()..).unwrap_or_default())
+        };
+        let overlap = a.len().min(b.len());
+        if overlap < MIN_OVERLAP {
+            continue;
+        }
+        let errors: u32 = a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum();
+        let score = 1.0 - errors as f64 / (overlap as f64 * 32.0);
+        if score > best {
+            best = score;
+        }
+    }
+    best
+}
+
+/// Compressed, base64 form of a fingerprint, as sent to AcoustID
+pub fn encode_fingerprint(fingerprint: &[u32]) -> String {
+    let config = configuration();
+    let compressed = FingerprintCompressor::from(&config).compress(fingerprint);
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(compressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pseudo_random(len: usize, seed: u32) -> Vec<u32> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shifted_copies_match_and_unrelated_audio_does_not() {
+        let original = pseudo_random(900, 7);
+        assert_eq!(similarity(&original, &original), 1.0);
+
+        // A re-rip with three seconds more of leading silence
+        let mut shifted = vec![0u32; 24];
+        shifted.extend_from_slice(&original);
+        assert_eq!(similarity(&original, &shifted), 1.0);
+        assert_eq!(similarity(&shifted, &original), 1.0);
+
+        let other = pseudo_random(900, 12345);
+        let score = similarity(&original, &other);
+        assert!(score < 0.6, "similarity {}", score);
+    }
+
+    #[test]
+    fn too_short_fingerprints_are_not_compared() {
+        let short = pseudo_random(MIN_OVERLAP - 1, 3);
+        assert_eq!(similarity(&short, &short), 0.0);
+    }
+}
@@ -0,0 +1,165 @@
+// crates/audio-player/src/icy.rs
+// SHOUTcast/Icecast in-band metadata. Asked for with `Icy-MetaData: 1`, the
+// server announces `icy-metaint` and inserts a metadata block after every
+// `icy-metaint` bytes of audio: one length byte (times 16) followed by
+// `StreamTitle='…';StreamUrl='…';` padded with NULs. `IcyReader` removes the
+// blocks from the stream and reports title changes.
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Request header asking the server for in-band metadata
+pub const REQUEST_HEADER: &str = "Icy-MetaData";
+/// Response header with the audio byte count between two metadata blocks
+pub const METAINT_HEADER: &str = "icy-metaint";
+
+/// Audio bytes between two metadata blocks, from the `icy-metaint` header
+pub fn parse_metaint(value: &str) -> Option<usize> {
+    value.trim().parse().ok().filter(|&n| n > 0)
+}
+
+/// `StreamTitle` of a metadata block, `None` when absent or empty
+pub fn parse_stream_title(block: &[u8]) -> Option<String> {
+    let end = block.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+    let block = &block[..end];
+    // Stations use UTF-8 or Latin-1; every byte sequence is valid Latin-1
+    let text = match std::str::from_utf8(block) {
+        Ok(text) => text.to_string(),
+        Err(_) => block.iter().map(|&b| b as char).collect(),
+    };
+    let start = text.find("StreamTitle='")? + "StreamTitle='".len();
+    let rest = &text[start..];
+    // Titles may contain quotes themselves, the field ends at "';"
+    let title = match rest.find("';") {
+        Some(end) => &rest[..end],
+        None => rest.trim_end_matches('\''),
+    };
+    let title = title.trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+/// Audio of an ICY stream with the metadata blocks taken out. Live streams
+/// cannot seek; only the current position can be asked for.
+pub struct IcyReader<R> {
+    inner: R,
+    metaint: usize,
+    /// Audio bytes left before the next metadata block
+    until_meta: usize,
+    position: u64,
+    last_title: Option<String>,
+    on_title: Box<dyn FnMut(String) + Send + Sync>,
+}
+
+impl<R: Read> IcyReader<R> {
+    pub fn new(inner: R, metaint: usize, on_title: impl FnMut(String) + Send + Sync + 'static) -> Self {
+        Self {
+            inner,
+            metaint,
+            until_meta: metaint,
+            position: 0,
+            last_title: None,
+            on_title: Box::new(on_title),
+        }
+    }
+
+    fn read_metadata(&mut self) -> io::Result<()> {
+        let mut len = [0u8; 1];
+        match self.inner.read_exact(&mut len) {
+            Ok(()) => {}
+            // The stream ended right before a block
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e),
+        }
+        if len[0] == 0 {
+            return Ok(());
+        }
+        let mut block = vec![0u8; len[0] as usize * 16];
+        self.inner.read_exact(&mut block)?;
+        if let Some(title) = parse_stream_title(&block) {
+            if self.last_title.as_deref() != Some(title.as_str()) {
+                self.last_title = Some(title.clone());
+                (self.on_title)(title);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for IcyReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.until_meta == 0 {
+            self.read_metadata()?;
+            self.until_meta = self.metaint;
+        }
+        let len = buf.len().min(self.until_meta);
+        let read = self.inner.read(&mut buf[..len])?;
+        self.until_meta -= read;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<R: Read> Seek for IcyReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match pos {
+            SeekFrom::Current(0) => Ok(self.position),
+            _ => Err(io::Error::new(io::ErrorKind::Unsupported, "live streams cannot seek")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn block(text: &str) -> Vec<u8> {
+        let len = text.len().div_ceil(16);
+        let mut block = vec![len as u8];
+        block.extend_from_slice(text.as_bytes());
+        block.resize(1 + len * 16, 0);
+        block
+    }
+
+    #[test]
+    fn titles_are_parsed() {
+        assert_eq!(
+            parse_stream_title(b"StreamTitle='Daft Punk - Aerodynamic';StreamUrl='';\0\0\0").as_deref(),
+            Some("Daft Punk - Aerodynamic")
+        );
+        assert_eq!(
+            parse_stream_title(b"StreamTitle='Guns N' Roses - Don't Cry';").as_deref(),
+            Some("Guns N' Roses - Don't Cry")
+        );
+        assert_eq!(parse_stream_title(b"StreamTitle='';").as_deref(), None);
+        assert_eq!(parse_stream_title(b"StreamUrl='x';").as_deref(), None);
+        // Latin-1
+        assert_eq!(parse_stream_title(b"StreamTitle='Bj\xf6rk';").as_deref(), Some("Björk"));
+        assert_eq!(parse_metaint(" 16000 "), Some(16000));
+        assert_eq!(parse_metaint("0"), None);
+    }
+
+    #[test]
+    fn metadata_is_removed_and_title_changes_reported() {
+        let mut stream = b"abcd".to_vec();
+        stream.extend(block("StreamTitle='One';"));
+        stream.extend(b"efgh");
+        stream.push(0);
+        stream.extend(b"ijkl");
+        stream.extend(block("StreamTitle='One';"));
+        stream.extend(b"mn");
+
+        let titles = Arc::new(Mutex::new(Vec::new()));
+        let seen = titles.clone();
+        let mut reader = IcyReader::new(io::Cursor::new(stream), 4, move |title| seen.lock().unwrap().push(title));
+        let mut audio = Vec::new();
+        reader.read_to_end(&mut audio).unwrap();
+
+        assert_eq!(audio, b"abcdefghijklmn");
+        assert_eq!(*titles.lock().unwrap(), vec!["One".to_string()]);
+        assert_eq!(reader.stream_position().unwrap(), 14);
+        assert!(reader.seek(SeekFrom::Start(0)).is_err());
+    }
+}
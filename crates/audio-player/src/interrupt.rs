@@ -0,0 +1,320 @@
+// crates/audio-player/src/interrupt.rs
+// Interruptions by incoming calls and other communication sessions: the policy
+// chosen by the user and the bookkeeping needed to undo it once the call ends.
+// Platform hooks (Android audio focus, Windows communications ducking) only
+// report when an interruption begins and ends; the core applies the actions
+// decided here.
+
+use crossbeam_channel::Sender;
+use types::settings::music::{InterruptionMode, MusicPlaybackSettings};
+
+/// Default volume while ducked, relative to the playback volume
+const DEFAULT_DUCK_LEVEL: f32 = 0.2;
+
+/// Start or end of a call, as reported by the platform hooks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterruptSignal {
+    Began,
+    Ended,
+}
+
+/// Resolved interruption settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InterruptPolicy {
+    pub mode: InterruptionMode,
+    /// Gain applied to the playback volume while ducked (0.0..=1.0)
+    pub duck_level: f32,
+    /// Resume playback paused by the interruption once it ends
+    pub resume: bool,
+}
+
+impl Default for InterruptPolicy {
+    fn default() -> Self {
+        Self {
+            mode: InterruptionMode::Pause,
+            duck_level: DEFAULT_DUCK_LEVEL,
+            resume: true,
+        }
+    }
+}
+
+impl From<&MusicPlaybackSettings> for InterruptPolicy {
+    fn from(s: &MusicPlaybackSettings) -> Self {
+        Self {
+            mode: s.interruption_mode.unwrap_or_default(),
+            duck_level: s
+                .interruption_duck_percent
+                .map(|p| p.min(100) as f32 / 100.0)
+                .unwrap_or(DEFAULT_DUCK_LEVEL),
+            resume: s.interruption_resume.unwrap_or(true),
+        }
+    }
+}
+
+/// Change to apply to playback
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InterruptAction {
+    None,
+    Pause,
+    /// Scale the backend volume by this gain
+    Duck(f32),
+    /// Resume playback paused by the interruption
+    Resume,
+    /// Bring the backend back to the playback volume
+    Restore,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Interruption {
+    /// Playback was paused; `resume` is cleared when the user takes over
+    Paused { resume: bool },
+    Ducked { level: f32 },
+    /// Nothing was playing, or the policy ignores calls
+    Untouched,
+}
+
+/// Interruption in progress, if any, and the policy applied to new ones
+#[derive(Debug, Default)]
+pub struct InterruptState {
+    policy: InterruptPolicy,
+    active: Option<Interruption>,
+}
+
+impl InterruptState {
+    /// Replace the policy. An interruption in progress keeps the action it
+    /// started with so that ending it undoes exactly that.
+    pub fn set_policy(&mut self, policy: InterruptPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Gain to apply on top of the playback volume, below 1.0 while ducked
+    pub fn gain(&self) -> f32 {
+        match self.active {
+            Some(Interruption::Ducked { level }) => level,
+            _ => 1.0,
+        }
+    }
+
+    /// A call started while playback was `playing`. Overlapping calls are
+    /// treated as one interruption.
+    pub fn begin(&mut self, playing: bool) -> InterruptAction {
+        if self.active.is_some() {
+            return InterruptAction::None;
+        }
+        let (interruption, action) = match self.policy.mode {
+            _ if !playing => (Interruption::Untouched, InterruptAction::None),
+            InterruptionMode::Ignore => (Interruption::Untouched, InterruptAction::None),
+            InterruptionMode::Pause => (
+                Interruption::Paused { resume: self.policy.resume },
+                InterruptAction::Pause,
+            ),
+            InterruptionMode::Duck => {
+                let level = self.policy.duck_level.clamp(0.0, 1.0);
+                (Interruption::Ducked { level }, InterruptAction::Duck(level))
+            }
+        };
+        self.active = Some(interruption);
+        action
+    }
+
+    /// The call ended: undo what `begin` did.
+    pub fn end(&mut self) -> InterruptAction {
+        match self.active.take() {
+            Some(Interruption::Paused { resume: true }) => InterruptAction::Resume,
+            Some(Interruption::Ducked { .. }) => InterruptAction::Restore,
+            _ => InterruptAction::None,
+        }
+    }
+
+    /// The user played or paused during the call: their choice wins over
+    /// resuming automatically when it ends.
+    pub fn user_override(&mut self) {
+        if let Some(Interruption::Paused { resume }) = &mut self.active {
+            *resume = false;
+        }
+    }
+}
+
+/// Start listening for communication sessions where the platform reports
+/// them, sending their start and end to `tx`. Returns false when there is
+/// nothing to listen to: Android reports audio focus through the audioplayer
+/// plugin instead, PulseAudio corks media streams during calls on its own, and
+/// macOS has no public API for it.
+pub fn watch_communications(tx: Sender<InterruptSignal>) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        std::thread::spawn(move || {
+            if let Err(e) = windows_ducking::watch(tx) {
+                tracing::warn!("Failed to watch communication sessions: {}", e);
+            }
+        });
+        true
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = tx;
+        false
+    }
+}
+
+/// Windows tells the media sessions when a communications stream opens or
+/// closes (the "Communications" tab of the sound settings). The app opts out
+/// of the system attenuation so that the user's policy applies instead.
+#[cfg(target_os = "windows")]
+mod windows_ducking {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use crossbeam_channel::Sender;
+    use windows::core::{implement, Interface, Result, PCWSTR};
+    use windows::Win32::Media::Audio::{
+        eConsole, eRender, IAudioSessionControl2, IAudioSessionManager2, IAudioVolumeDuckNotification,
+        IAudioVolumeDuckNotification_Impl, IMMDeviceEnumerator, MMDeviceEnumerator,
+    };
+    use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED};
+
+    use super::InterruptSignal;
+
+    #[implement(IAudioVolumeDuckNotification)]
+    struct DuckNotification {
+        sessions: AtomicU32,
+        tx: Sender<InterruptSignal>,
+    }
+
+    #[allow(non_snake_case)]
+    impl IAudioVolumeDuckNotification_Impl for DuckNotification {
+        fn OnVolumeDuckNotification(&self, _sessionid: &PCWSTR, countcommunicationsessions: u32) -> Result<()> {
+            if self.sessions.swap(countcommunicationsessions.max(1), Ordering::SeqCst) == 0 {
+                let _ = self.tx.send(InterruptSignal::Began);
+            }
+            Ok(())
+        }
+
+        fn OnVolumeUnduckNotification(&self, _sessionid: &PCWSTR) -> Result<()> {
+            let previous = self
+                .sessions
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| Some(n.saturating_sub(1)))
+                .unwrap_or(0);
+            if previous == 1 {
+                let _ = self.tx.send(InterruptSignal::Ended);
+            }
+            Ok(())
+        }
+    }
+
+    /// Register for duck notifications on the default output device. Blocks
+    /// to keep the registration alive; notifications arrive on COM threads.
+    pub fn watch(tx: Sender<InterruptSignal>) -> Result<()> {
+        unsafe {
+            CoInitializeEx(None, COINIT_MULTITHREADED)?;
+            let enumerator: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+            let device = enumerator.GetDefaultAudioEndpoint(eRender, eConsole)?;
+            let manager: IAudioSessionManager2 = device.Activate(CLSCTX_ALL, None)?;
+
+            // Notifications for our own session, with the system attenuation turned off
+            let control = manager
+                .GetAudioSessionControl(None, 0)
+                .and_then(|c| c.cast::<IAudioSessionControl2>());
+            let session_id = match &control {
+                Ok(control) => {
+                    if let Err(e) = control.SetDuckingPreference(true) {
+                        tracing::debug!("Failed to opt out of system ducking: {}", e);
+                    }
+                    control.GetSessionInstanceIdentifier().ok()
+                }
+                Err(e) => {
+                    tracing::debug!("No audio session control: {}", e);
+                    None
+                }
+            };
+            let session_id = session_id.map(|id| PCWSTR(id.0)).unwrap_or_else(PCWSTR::null);
+
+            let notification: IAudioVolumeDuckNotification = DuckNotification {
+                sessions: AtomicU32::new(0),
+                tx,
+            }
+            .into();
+            manager.RegisterDuckNotification(session_id, &notification)?;
+            tracing::info!("Watching communication sessions");
+            loop {
+                std::thread::park();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(mode: InterruptionMode, resume: bool) -> InterruptState {
+        let mut state = InterruptState::default();
+        state.set_policy(InterruptPolicy {
+            mode,
+            duck_level: 0.3,
+            resume,
+        });
+        state
+    }
+
+    #[test]
+    fn pause_resumes_when_allowed() {
+        let mut resuming = state(InterruptionMode::Pause, true);
+        assert_eq!(resuming.begin(true), InterruptAction::Pause);
+        assert_eq!(resuming.end(), InterruptAction::Resume);
+
+        let mut staying = state(InterruptionMode::Pause, false);
+        assert_eq!(staying.begin(true), InterruptAction::Pause);
+        assert_eq!(staying.end(), InterruptAction::None);
+    }
+
+    #[test]
+    fn duck_restores_the_volume() {
+        let mut state = state(InterruptionMode::Duck, false);
+        assert_eq!(state.begin(true), InterruptAction::Duck(0.3));
+        assert_eq!(state.gain(), 0.3);
+        assert_eq!(state.end(), InterruptAction::Restore);
+        assert_eq!(state.gain(), 1.0);
+    }
+
+    #[test]
+    fn idle_playback_is_left_alone() {
+        let mut state = state(InterruptionMode::Pause, true);
+        assert_eq!(state.begin(false), InterruptAction::None);
+        assert!(state.is_active());
+        assert_eq!(state.end(), InterruptAction::None);
+        assert!(!state.is_active());
+    }
+
+    #[test]
+    fn overlapping_calls_are_one_interruption() {
+        let mut state = state(InterruptionMode::Pause, true);
+        assert_eq!(state.begin(true), InterruptAction::Pause);
+        assert_eq!(state.begin(false), InterruptAction::None);
+        assert_eq!(state.end(), InterruptAction::Resume);
+    }
+
+    #[test]
+    fn user_choice_during_a_call_wins() {
+        let mut state = state(InterruptionMode::Pause, true);
+        state.begin(true);
+        state.user_override();
+        assert_eq!(state.end(), InterruptAction::None);
+    }
+
+    #[test]
+    fn settings_are_resolved() {
+        let settings = MusicPlaybackSettings {
+            interruption_mode: Some(InterruptionMode::Duck),
+            interruption_duck_percent: Some(250),
+            ..Default::default()
+        };
+        let policy = InterruptPolicy::from(&settings);
+        assert_eq!(policy.mode, InterruptionMode::Duck);
+        assert_eq!(policy.duck_level, 1.0);
+        assert!(policy.resume);
+    }
+}
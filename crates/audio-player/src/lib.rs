@@ -3,8 +3,33 @@
 pub mod players;
 pub mod core;
 pub mod store;
+pub mod persist;
+pub mod state_machine;
 pub mod events;
 pub mod mpris;
+pub mod os_media;
+pub mod media_keys;
+pub mod crossfade;
+pub mod crossfeed;
+pub mod stretch;
+pub mod ab_loop;
+pub mod edit_regions;
+pub mod queue_metrics;
+pub mod data_usage;
+pub mod icy;
+pub mod normalize;
+pub mod devices;
+pub mod loopback;
+pub mod interrupt;
+pub mod trace;
+pub mod transport;
+pub mod analysis;
+pub mod fingerprint;
+pub mod visualizer;
+pub mod silence;
+pub mod buffering;
+pub mod segmented;
+pub mod expiry;
 
 // Public facade for backend usage
 pub use core::AudioPlayer;
\ No newline at end of file
@@ -0,0 +1,202 @@
+// crates/audio-player/src/loopback.rs
+// Samples of what the system is playing, to identify music playing in another
+// app. Windows records any output device through WASAPI loopback; elsewhere a
+// capture device has to carry the output: the "Monitor of" sources of
+// PulseAudio and PipeWire, or a virtual device such as BlackHole on macOS.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use base64::Engine;
+use rodio::cpal::{
+    self,
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    FromSample, Sample, SizedSample,
+};
+use rusty_chromaprint::{Configuration, FingerprintCompressor, Fingerprinter};
+use types::errors::{MusicError, Result};
+
+/// Names of capture devices that carry the output of the system
+#[cfg(not(target_os = "windows"))]
+const LOOPBACK_NAMES: [&str; 5] = ["monitor", "loopback", "blackhole", "soundflower", "stereo mix"];
+/// Peak below which a sample is taken as silence (-50 dBFS)
+const SILENCE_PEAK: i16 = 100;
+/// Time allowed on top of the sample length for the device to deliver it
+const CAPTURE_GRACE: Duration = Duration::from_secs(5);
+
+/// Chromaprint fingerprint of a captured sample, compressed and base64
+/// encoded as AcoustID takes it
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoopbackSample {
+    pub fingerprint: String,
+    /// Length of the sample in seconds
+    pub duration: f64,
+    /// Device the sample was recorded from
+    pub device: String,
+}
+
+fn is_loopback_name(name: &str) -> bool {
+    #[cfg(not(target_os = "windows"))]
+    {
+        let name = name.to_lowercase();
+        LOOPBACK_NAMES.iter().any(|n| name.contains(n))
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let _ = name;
+        true
+    }
+}
+
+/// Devices a sample can be recorded from, the default output first on Windows
+pub fn loopback_device_names() -> Vec<String> {
+    let host = cpal::default_host();
+    #[cfg(target_os = "windows")]
+    let devices = host.output_devices();
+    #[cfg(not(target_os = "windows"))]
+    let devices = host.input_devices();
+    devices
+        .map(|devices| devices.filter_map(|d| d.name().ok()).filter(|n| is_loopback_name(n)).collect())
+        .unwrap_or_default()
+}
+
+/// `preferred`, or else the default loopback device
+fn find_device(preferred: Option<&str>) -> Result<(cpal::Device, cpal::SupportedStreamConfig)> {
+    let host = cpal::default_host();
+    #[cfg(target_os = "windows")]
+    let (devices, default) = (host.output_devices(), host.default_output_device());
+    #[cfg(not(target_os = "windows"))]
+    let (devices, default) = (host.input_devices(), None::<cpal::Device>);
+
+    let mut candidates: Vec<cpal::Device> = devices
+        .map_err(|e| MusicError::String(format!("Failed to enumerate capture devices: {}", e)))?
+        .filter(|d| d.name().is_ok_and(|n| is_loopback_name(&n)))
+        .collect();
+    if let Some(default) = default {
+        candidates.insert(0, default);
+    }
+    let device = match preferred {
+        Some(name) => candidates
+            .into_iter()
+            .find(|d| d.name().ok().as_deref() == Some(name))
+            .ok_or_else(|| MusicError::String(format!("Loopback device {} not found", name)))?,
+        None => candidates
+            .into_iter()
+            .next()
+            .ok_or_else(|| MusicError::String("No loopback device to record the system output from".to_string()))?,
+    };
+
+    // WASAPI loopback records in the format the output device plays
+    #[cfg(target_os = "windows")]
+    let config = device.default_output_config();
+    #[cfg(not(target_os = "windows"))]
+    let config = device.default_input_config();
+    let config = config.map_err(|e| MusicError::String(format!("Loopback device has no usable format: {}", e)))?;
+    Ok((device, config))
+}
+
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    samples: Arc<Mutex<Vec<i16>>>,
+    wanted: usize,
+    done: crossbeam_channel::Sender<()>,
+) -> Result<cpal::Stream>
+where
+    T: SizedSample,
+    i16: FromSample<T>,
+{
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                let Ok(mut samples) = samples.lock() else { return };
+                if samples.len() >= wanted {
+                    return;
+                }
+                samples.extend(data.iter().map(|s| s.to_sample::<i16>()));
+                if samples.len() >= wanted {
+                    let _ = done.try_send(());
+                }
+            },
+            |e| tracing::warn!("Loopback capture error: {}", e),
+            None,
+        )
+        .map_err(|e| MusicError::String(format!("Failed to open the loopback device: {}", e)))
+}
+
+/// Fingerprint interleaved 16-bit samples the way fpcalc does
+fn fingerprint_samples(samples: &[i16], sample_rate: u32, channels: u16) -> Result<String> {
+    let config = Configuration::preset_test2();
+    let mut printer = Fingerprinter::new(&config);
+    printer
+        .start(sample_rate, channels as u32)
+        .map_err(|e| MusicError::String(format!("Can't fingerprint the sample: {:?}", e)))?;
+    printer.consume(samples);
+    printer.finish();
+    let fingerprint = printer.fingerprint();
+    if fingerprint.is_empty() {
+        return Err("Sample too short to fingerprint".into());
+    }
+    let compressed = FingerprintCompressor::from(&config).compress(fingerprint);
+    Ok(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(compressed))
+}
+
+/// Record `seconds` of the system output from `device` (the default loopback
+/// device when `None`) and fingerprint them. Blocks for the length of the
+/// sample; fails when nothing is playing.
+pub fn capture_sample(device: Option<&str>, seconds: u32) -> Result<LoopbackSample> {
+    let (device, supported) = find_device(device)?;
+    let name = device.name().unwrap_or_else(|_| "loopback".to_string());
+    let config: cpal::StreamConfig = supported.config();
+    let channels = config.channels.max(1);
+    let sample_rate = config.sample_rate.0;
+    let wanted = seconds as usize * sample_rate as usize * channels as usize;
+
+    let samples = Arc::new(Mutex::new(Vec::with_capacity(wanted)));
+    let (done_tx, done_rx) = crossbeam_channel::bounded(1);
+    let stream = match supported.sample_format() {
+        cpal::SampleFormat::F32 => build_stream::<f32>(&device, &config, samples.clone(), wanted, done_tx),
+        cpal::SampleFormat::I16 => build_stream::<i16>(&device, &config, samples.clone(), wanted, done_tx),
+        cpal::SampleFormat::U16 => build_stream::<u16>(&device, &config, samples.clone(), wanted, done_tx),
+        cpal::SampleFormat::I32 => build_stream::<i32>(&device, &config, samples.clone(), wanted, done_tx),
+        format => return Err(MusicError::String(format!("Unsupported loopback sample format {:?}", format))),
+    }?;
+    stream
+        .play()
+        .map_err(|e| MusicError::String(format!("Failed to start the loopback capture: {}", e)))?;
+    let finished = done_rx.recv_timeout(Duration::from_secs(seconds as u64) + CAPTURE_GRACE).is_ok();
+    drop(stream);
+
+    let samples = std::mem::take(&mut *samples.lock().unwrap());
+    if !finished {
+        tracing::debug!("Loopback capture delivered {} of {} samples", samples.len(), wanted);
+    }
+    if samples.iter().all(|s| s.unsigned_abs() < SILENCE_PEAK as u16) {
+        return Err("Nothing is playing on the loopback device".into());
+    }
+    let duration = samples.len() as f64 / (sample_rate as f64 * channels as f64);
+    Ok(LoopbackSample {
+        fingerprint: fingerprint_samples(&samples, sample_rate, channels)?,
+        duration,
+        device: name,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn monitor_sources_are_loopback_devices() {
+        assert!(is_loopback_name("Monitor of Built-in Audio Analog Stereo"));
+        assert!(is_loopback_name("BlackHole 2ch"));
+        assert!(!is_loopback_name("Built-in Microphone"));
+    }
+
+    #[test]
+    fn a_blip_is_too_short_to_fingerprint() {
+        assert!(fingerprint_samples(&[1000i16; 4410], 44100, 1).is_err());
+    }
+}
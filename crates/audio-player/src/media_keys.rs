@@ -0,0 +1,193 @@
+// crates/audio-player/src/media_keys.rs
+// Gesture recognition for the play/pause media key (Bluetooth headsets etc.).
+// Single press toggles playback, double/triple/long presses map to configurable actions.
+
+use std::time::{Duration, Instant};
+
+use types::settings::music::{MediaKeyAction, MusicMediaKeySettings};
+
+/// Resolved media key configuration used by the gesture detector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MediaKeyConfig {
+    /// When disabled every press resolves immediately to PlayPause
+    pub gestures_enabled: bool,
+    pub multi_press_window: Duration,
+    pub long_press: Duration,
+    pub double_press_action: MediaKeyAction,
+    pub triple_press_action: MediaKeyAction,
+    pub long_press_action: MediaKeyAction,
+}
+
+impl Default for MediaKeyConfig {
+    fn default() -> Self {
+        Self {
+            gestures_enabled: true,
+            multi_press_window: Duration::from_millis(400),
+            long_press: Duration::from_millis(800),
+            double_press_action: MediaKeyAction::Next,
+            triple_press_action: MediaKeyAction::Previous,
+            long_press_action: MediaKeyAction::Radio,
+        }
+    }
+}
+
+impl From<&MusicMediaKeySettings> for MediaKeyConfig {
+    fn from(s: &MusicMediaKeySettings) -> Self {
+        let d = Self::default();
+        Self {
+            gestures_enabled: s.gestures_enabled.unwrap_or(d.gestures_enabled),
+            multi_press_window: s
+                .multi_press_window_ms
+                .map(|ms| Duration::from_millis(ms as u64))
+                .unwrap_or(d.multi_press_window),
+            long_press: s
+                .long_press_ms
+                .map(|ms| Duration::from_millis(ms as u64))
+                .unwrap_or(d.long_press),
+            double_press_action: s.double_press.unwrap_or(d.double_press_action),
+            triple_press_action: s.triple_press.unwrap_or(d.triple_press_action),
+            long_press_action: s.long_press.unwrap_or(d.long_press_action),
+        }
+    }
+}
+
+/// Press counter for the play/pause key.
+///
+/// Sources that only report discrete presses (MPRIS/SMTC "PlayPause") call
+/// `on_press()` followed immediately by `on_release()`; long presses can only be
+/// recognized by sources that report key-down and key-up separately.
+#[derive(Debug, Default)]
+pub struct GestureDetector {
+    config: MediaKeyConfig,
+    presses: usize,
+    last_press: Option<Instant>,
+    held_since: Option<Instant>,
+    long_fired: bool,
+}
+
+impl GestureDetector {
+    pub fn new(config: MediaKeyConfig) -> Self {
+        Self { config, ..Default::default() }
+    }
+
+    pub fn set_config(&mut self, config: MediaKeyConfig) {
+        self.config = config;
+    }
+
+    /// Key went down. Returns an action when it can be resolved right away.
+    pub fn on_press(&mut self, now: Instant) -> Option<MediaKeyAction> {
+        if !self.config.gestures_enabled {
+            return Some(MediaKeyAction::PlayPause);
+        }
+        self.held_since = Some(now);
+        self.long_fired = false;
+        self.presses += 1;
+        self.last_press = Some(now);
+        if self.presses >= 3 {
+            self.reset();
+            return Some(self.config.triple_press_action);
+        }
+        None
+    }
+
+    /// Key went up.
+    pub fn on_release(&mut self, _now: Instant) {
+        self.held_since = None;
+        if self.long_fired {
+            self.long_fired = false;
+            self.reset();
+        }
+    }
+
+    /// Resolve pending gestures whose timing window has elapsed.
+    pub fn poll(&mut self, now: Instant) -> Option<MediaKeyAction> {
+        if let Some(held) = self.held_since {
+            if !self.long_fired && now.duration_since(held) >= self.config.long_press {
+                self.long_fired = true;
+                self.presses = 0;
+                self.last_press = None;
+                return Some(self.config.long_press_action);
+            }
+            return None;
+        }
+        let last = self.last_press?;
+        if now.duration_since(last) < self.config.multi_press_window {
+            return None;
+        }
+        let action = match self.presses {
+            0 => None,
+            1 => Some(MediaKeyAction::PlayPause),
+            _ => Some(self.config.double_press_action),
+        };
+        self.reset();
+        action
+    }
+
+    /// Time until the next pending gesture could resolve, if any.
+    pub fn next_deadline(&self, now: Instant) -> Option<Duration> {
+        if let Some(held) = self.held_since {
+            if !self.long_fired {
+                return Some((held + self.config.long_press).saturating_duration_since(now));
+            }
+        }
+        self.last_press
+            .map(|last| (last + self.config.multi_press_window).saturating_duration_since(now))
+    }
+
+    fn reset(&mut self) {
+        self.presses = 0;
+        self.last_press = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tap(d: &mut GestureDetector, at: Instant) -> Option<MediaKeyAction> {
+        let res = d.on_press(at);
+        d.on_release(at);
+        res
+    }
+
+    #[test]
+    fn single_double_triple_presses() {
+        let cfg = MediaKeyConfig::default();
+        let t0 = Instant::now();
+        let ms = Duration::from_millis;
+
+        let mut d = GestureDetector::new(cfg);
+        assert_eq!(tap(&mut d, t0), None);
+        assert_eq!(d.poll(t0 + ms(100)), None);
+        assert_eq!(d.poll(t0 + ms(450)), Some(MediaKeyAction::PlayPause));
+
+        let mut d = GestureDetector::new(cfg);
+        tap(&mut d, t0);
+        tap(&mut d, t0 + ms(200));
+        assert_eq!(d.poll(t0 + ms(700)), Some(MediaKeyAction::Next));
+
+        let mut d = GestureDetector::new(cfg);
+        tap(&mut d, t0);
+        tap(&mut d, t0 + ms(150));
+        assert_eq!(tap(&mut d, t0 + ms(300)), Some(MediaKeyAction::Previous));
+        assert_eq!(d.poll(t0 + ms(2000)), None);
+    }
+
+    #[test]
+    fn long_press_fires_once() {
+        let t0 = Instant::now();
+        let mut d = GestureDetector::new(MediaKeyConfig::default());
+        d.on_press(t0);
+        assert_eq!(d.poll(t0 + Duration::from_millis(900)), Some(MediaKeyAction::Radio));
+        assert_eq!(d.poll(t0 + Duration::from_millis(1200)), None);
+        d.on_release(t0 + Duration::from_millis(1300));
+        assert_eq!(d.poll(t0 + Duration::from_millis(2000)), None);
+    }
+
+    #[test]
+    fn disabled_gestures_resolve_immediately() {
+        let cfg = MediaKeyConfig { gestures_enabled: false, ..Default::default() };
+        let mut d = GestureDetector::new(cfg);
+        assert_eq!(d.on_press(Instant::now()), Some(MediaKeyAction::PlayPause));
+    }
+}
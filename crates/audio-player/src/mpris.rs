@@ -2,23 +2,69 @@
 // This module keeps all MPRIS integration and notifications in one place.
 
 use std::sync::{Arc, Mutex};
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::Instant;
 
 use types::errors::Result;
 use types::tracks::MediaContent;
-use types::ui::player_details::{PlayerEvents, PlayerState};
+use types::ui::player_details::{PlayerEvents, PlayerMode, PlayerState};
 use types::mpris::MprisPlayerDetails;
+use types::settings::display::DisplayView;
+use types::settings::music::MediaKeyAction;
 
-use ::mpris; // external crate or root module providing MprisHolder and MediaControlEvent
-
+use crate::media_keys::GestureDetector;
+use crate::os_media::{self, OsMediaEvent};
 use crate::AudioPlayer;
 
+/// Requests from the OS media controls that the Tauri bridge carries out, as
+/// they also update the UI
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RemoteAction {
+    /// Seek to this position, in seconds of the edited timeline
+    SeekTo(f64),
+    /// Seek this many seconds forward (or backward when negative)
+    SeekBy(f64),
+    SetPlayerMode(PlayerMode),
+    /// Playback rate, 1.0 being normal
+    SetRate(f64),
+}
+
+/// URL of a cover for the media controls: local paths (thumbnail directory)
+/// become file:// URLs, remote covers are passed through
+pub fn artwork_url(cover: &str) -> Option<String> {
+    let cover = cover.trim();
+    if cover.is_empty() {
+        return None;
+    }
+    if ["http://", "https://", "file://"].iter().any(|scheme| cover.starts_with(scheme)) {
+        return Some(cover.to_string());
+    }
+    let path = std::path::Path::new(cover);
+    if !path.is_absolute() {
+        return None;
+    }
+    let path = cover.replace('\\', "/");
+    let mut url = String::from("file://");
+    if !path.starts_with('/') {
+        // Windows drive letter
+        url.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => url.push(byte as char),
+            _ => url.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    Some(url)
+}
+
 impl AudioPlayer {
-    /// Initialize MPRIS integration
+    /// Initialize the OS media controls (MPRIS, SMTC, Now Playing)
     pub fn initialize_mpris(&mut self) -> Result<()> {
-        match mpris::MprisHolder::new() {
-            Ok(holder) => {
-                self.mpris_holder = Some(holder);
-                tracing::info!("MPRIS initialized successfully");
+        match os_media::create() {
+            Ok(controls) => {
+                self.mpris_holder = Some(controls);
+                tracing::info!("OS media controls initialized successfully");
                 Ok(())
             }
             Err(e) => {
@@ -39,53 +85,80 @@ impl AudioPlayer {
         }
     }
 
-    /// Start MPRIS event listener
+    /// Start MPRIS event listener.
+    /// Toggle (the headset play/pause key) goes through the gesture detector; resolved
+    /// gestures and Next/Previous are forwarded on the control channel.
     pub fn start_mpris_event_listener(&self) -> Option<std::thread::JoinHandle<()>> {
         if let Some(ref mpris) = self.mpris_holder {
-            let event_rx = mpris.event_rx.clone();
+            let event_rx = mpris.events();
             let events_tx = self.events_tx.clone();
+            let control_tx = self.control_tx.clone();
+            let remote_tx = self.remote_tx.clone();
+            let media_key_config = self.media_key_config.clone();
 
             Some(std::thread::spawn(move || {
+                let mut detector = GestureDetector::default();
                 loop {
-                    if let Ok(rx) = event_rx.lock() {
-                        match rx.recv() {
-                            Ok(event) => {
-                                tracing::debug!("Received MPRIS event: {:?}", event);
-                                match event {
-                                    mpris::MediaControlEvent::Play => {
-                                        let _ = events_tx.send(PlayerEvents::Play);
-                                    }
-                                    mpris::MediaControlEvent::Pause => {
-                                        let _ = events_tx.send(PlayerEvents::Pause);
-                                    }
-                                    mpris::MediaControlEvent::Toggle => {
-                                        tracing::debug!("MPRIS toggle event received");
-                                    }
-                                    mpris::MediaControlEvent::Stop => {
-                                        let _ = events_tx.send(PlayerEvents::Pause);
-                                    }
-                                    mpris::MediaControlEvent::Next => {
-                                        tracing::debug!("MPRIS next event received");
-                                        // TODO: Implement next track logic
-                                    }
-                                    mpris::MediaControlEvent::Previous => {
-                                        tracing::debug!("MPRIS previous event received");
-                                        // TODO: Implement previous track logic
-                                    }
-                                    mpris::MediaControlEvent::SetPosition(pos) => {
-                                        tracing::debug!("MPRIS seek event: {:?}", pos);
-                                        // TODO: Implement seek logic
-                                    }
-                                    _ => {
-                                        tracing::debug!("Unhandled MPRIS event: {:?}", event);
+                    let config = media_key_config.lock().map(|c| *c).unwrap_or_default();
+                    detector.set_config(config);
+
+                    let received = match event_rx.lock() {
+                        Ok(rx) => match detector.next_deadline(Instant::now()) {
+                            Some(wait) => rx.recv_timeout(wait),
+                            None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                        },
+                        Err(_) => break,
+                    };
+
+                    match received {
+                        Ok(event) => {
+                            tracing::debug!("Received MPRIS event: {:?}", event);
+                            match event {
+                                OsMediaEvent::Play => {
+                                    let _ = events_tx.send(PlayerEvents::Play);
+                                }
+                                OsMediaEvent::Pause | OsMediaEvent::Stop => {
+                                    let _ = events_tx.send(PlayerEvents::Pause);
+                                }
+                                OsMediaEvent::Toggle => {
+                                    let now = Instant::now();
+                                    let resolved = detector.on_press(now);
+                                    // The OS media controls report discrete presses only
+                                    detector.on_release(now);
+                                    if let Some(action) = resolved {
+                                        let _ = control_tx.send(action);
                                     }
                                 }
+                                OsMediaEvent::Next => {
+                                    let _ = control_tx.send(MediaKeyAction::Next);
+                                }
+                                OsMediaEvent::Previous => {
+                                    let _ = control_tx.send(MediaKeyAction::Previous);
+                                }
+                                OsMediaEvent::SeekTo(position) => {
+                                    let _ = remote_tx.send(RemoteAction::SeekTo(position));
+                                }
+                                OsMediaEvent::SeekBy(offset) => {
+                                    let _ = remote_tx.send(RemoteAction::SeekBy(offset));
+                                }
+                                OsMediaEvent::SetPlayerMode(mode) => {
+                                    let _ = remote_tx.send(RemoteAction::SetPlayerMode(mode));
+                                }
+                                OsMediaEvent::SetRate(rate) => {
+                                    let _ = remote_tx.send(RemoteAction::SetRate(rate));
+                                }
                             }
-                            Err(e) => {
-                                tracing::debug!("MPRIS event listener error: {:?}", e);
-                                break;
-                            }
                         }
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Err(RecvTimeoutError::Disconnected) => {
+                            tracing::debug!("MPRIS event channel disconnected");
+                            break;
+                        }
+                    }
+
+                    if let Some(action) = detector.poll(Instant::now()) {
+                        tracing::debug!("Media key gesture resolved: {:?}", action);
+                        let _ = control_tx.send(action);
                     }
                 }
                 tracing::info!("MPRIS event listener stopped");
@@ -99,9 +172,15 @@ impl AudioPlayer {
     pub fn notify_mpris_metadata(&self, track: &MediaContent) {
         // Use direct MPRIS integration if available
         if let Some(ref mpris) = self.mpris_holder {
+            let title = self
+                .title_formatter
+                .lock()
+                .map(|f| f.format(DisplayView::Mpris, track))
+                .ok()
+                .or_else(|| track.track.title.clone());
             let metadata = MprisPlayerDetails {
                 id: track.track._id.clone(),
-                title: track.track.title.clone(),
+                title,
                 artist_name: Some(
                     track.artists
                         .as_ref()
@@ -127,11 +206,7 @@ impl AudioPlayer {
                             .collect::<Vec<String>>()
                     }),
                 duration: track.track.duration,
-                thumbnail: track
-                    .track
-                    .track_cover_path_high
-                    .clone()
-                    .or_else(|| track.track.track_cover_path_low.clone()),
+                thumbnail: Self::mpris_thumbnail(track),
             };
 
             if let Err(_e) = mpris.set_metadata(metadata) {
@@ -169,4 +244,67 @@ impl AudioPlayer {
             }
         }
     }
+
+    /// Cover shown by the media controls. The mobile notification loads local
+    /// paths itself; desktop media controls expect a URL.
+    fn mpris_thumbnail(track: &MediaContent) -> Option<String> {
+        let cover = track
+            .track
+            .track_cover_path_high
+            .clone()
+            .or_else(|| track.track.track_cover_path_low.clone())
+            .or_else(|| track.album.as_ref().and_then(|a| a.album_coverpath_high.clone()));
+        if cfg!(any(target_os = "android", target_os = "ios")) {
+            return cover;
+        }
+        cover.as_deref().and_then(artwork_url)
+    }
+
+    /// Notify MPRIS that the position jumped (user seek)
+    pub fn notify_mpris_seeked(&self, position: f64) {
+        if let Some(ref mpris) = self.mpris_holder {
+            if let Err(_e) = mpris.seeked(position) {
+                tracing::debug!("MPRIS seek notification failed (expected in headless)");
+            }
+        }
+    }
+
+    /// Notify MPRIS of loop/shuffle changes
+    pub fn notify_mpris_mode(&self, mode: PlayerMode) {
+        if let Some(ref mpris) = self.mpris_holder {
+            if let Err(_e) = mpris.set_player_mode(mode) {
+                tracing::debug!("MPRIS player mode update failed (expected in headless)");
+            } else {
+                tracing::trace!("Updated MPRIS player mode: {:?}", mode);
+            }
+        }
+    }
+
+    /// Notify MPRIS of playback rate changes
+    pub fn notify_mpris_rate(&self, rate: f64) {
+        if let Some(ref mpris) = self.mpris_holder {
+            if let Err(_e) = mpris.set_rate(rate) {
+                tracing::debug!("MPRIS rate update failed (expected in headless)");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn covers_become_file_urls() {
+        assert_eq!(
+            artwork_url("/home/me/.local/share/music/thumbnails/ab cd.png").as_deref(),
+            Some("file:///home/me/.local/share/music/thumbnails/ab%20cd.png")
+        );
+        assert_eq!(
+            artwork_url("https://example.com/cover.jpg").as_deref(),
+            Some("https://example.com/cover.jpg")
+        );
+        assert_eq!(artwork_url("relative/cover.png"), None);
+        assert_eq!(artwork_url(""), None);
+    }
 }
@@ -0,0 +1,130 @@
+// crates/audio-player/src/normalize.rs
+// Loudness normalization from ReplayGain analysis. Track mode brings every
+// track to the reference level; album mode applies one gain per album so quiet
+// and loud tracks of the same album keep their relative loudness. The gain is
+// limited so the track's peak never clips, and folded into the backend volume.
+// Shuffling by album plays whole albums, so it always uses album mode.
+
+use types::settings::music::{MusicPlaybackSettings, NormalizationMode};
+use types::ui::player_details::{PlayerMode, TrackGain};
+
+/// Resolved normalization settings
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct NormalizeConfig {
+    pub enabled: bool,
+    pub mode: NormalizationMode,
+}
+
+impl From<&MusicPlaybackSettings> for NormalizeConfig {
+    fn from(s: &MusicPlaybackSettings) -> Self {
+        Self {
+            enabled: s.normalize.unwrap_or(false),
+            mode: s.normalize_mode.unwrap_or_default(),
+        }
+    }
+}
+
+impl NormalizeConfig {
+    /// Settings as applied in player `mode`
+    pub fn for_player_mode(self, mode: PlayerMode) -> Self {
+        match mode {
+            PlayerMode::ShuffleAlbums => Self { mode: NormalizationMode::Album, ..self },
+            _ => self,
+        }
+    }
+
+    /// Volume factor for a track with `gain`; 1.0 when disabled or unanalyzed
+    pub fn factor(&self, gain: Option<&TrackGain>) -> f32 {
+        if !self.enabled {
+            return 1.0;
+        }
+        gain.and_then(|g| applied_gain_db(g, self.mode))
+            .map(db_to_linear)
+            .unwrap_or(1.0)
+    }
+}
+
+/// Gain in dB applied to a track in `mode`, `None` without analysis. A
+/// missing album gain falls back to the track gain and vice versa; a positive
+/// gain is reduced so the peak stays at or below full scale.
+pub fn applied_gain_db(gain: &TrackGain, mode: NormalizationMode) -> Option<f64> {
+    let track = gain.track_gain.map(|g| (g, gain.track_peak));
+    let album = gain.album_gain.map(|g| (g, gain.album_peak.or(gain.track_peak)));
+    let (db, peak) = match mode {
+        NormalizationMode::Track => track.or(album),
+        NormalizationMode::Album => album.or(track),
+    }?;
+    if !db.is_finite() {
+        return None;
+    }
+    let limit = peak
+        .filter(|p| p.is_finite() && *p > 0.0)
+        .map(|p| -20.0 * p.log10())
+        .unwrap_or(f64::INFINITY);
+    Some(db.min(limit))
+}
+
+pub fn db_to_linear(db: f64) -> f32 {
+    10f64.powf(db / 20.0) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gain(track: f64, track_peak: f64, album: Option<f64>) -> TrackGain {
+        TrackGain {
+            track_gain: Some(track),
+            track_peak: Some(track_peak),
+            album_gain: album,
+            album_peak: album.map(|_| 0.9),
+        }
+    }
+
+    #[test]
+    fn mode_selects_gain_with_fallback() {
+        let g = gain(-6.0, 0.9, Some(-4.0));
+        assert_eq!(applied_gain_db(&g, NormalizationMode::Track), Some(-6.0));
+        assert_eq!(applied_gain_db(&g, NormalizationMode::Album), Some(-4.0));
+
+        let no_album = gain(-6.0, 0.9, None);
+        assert_eq!(applied_gain_db(&no_album, NormalizationMode::Album), Some(-6.0));
+        assert_eq!(applied_gain_db(&TrackGain::default(), NormalizationMode::Track), None);
+    }
+
+    #[test]
+    fn positive_gain_is_limited_by_peak() {
+        let g = gain(9.0, 0.5, None);
+        let applied = applied_gain_db(&g, NormalizationMode::Track).unwrap();
+        assert!((applied - 6.0206).abs() < 1e-3);
+        assert!(0.5 * db_to_linear(applied) as f64 <= 1.0 + 1e-6);
+
+        let loud = gain(3.0, 1.0, None);
+        assert_eq!(applied_gain_db(&loud, NormalizationMode::Track), Some(0.0));
+    }
+
+    #[test]
+    fn disabled_config_keeps_volume() {
+        let g = gain(-6.0, 0.9, None);
+        assert_eq!(NormalizeConfig::default().factor(Some(&g)), 1.0);
+        let config = NormalizeConfig {
+            enabled: true,
+            mode: NormalizationMode::Track,
+        };
+        assert!((config.factor(Some(&g)) - 0.501).abs() < 1e-3);
+        assert_eq!(config.factor(None), 1.0);
+    }
+
+    #[test]
+    fn album_shuffle_applies_album_gain() {
+        let g = gain(-6.0, 0.9, Some(-4.0));
+        let config = NormalizeConfig {
+            enabled: true,
+            mode: NormalizationMode::Track,
+        };
+        assert_eq!(config.for_player_mode(PlayerMode::Shuffle), config);
+        let album = config.for_player_mode(PlayerMode::ShuffleAlbums);
+        assert_eq!(album.mode, NormalizationMode::Album);
+        assert!((album.factor(Some(&g)) - db_to_linear(-4.0)).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,147 @@
+// crates/audio-player/src/os_media/mod.rs
+// OS media controls: MPRIS on Linux, the SystemMediaTransportControls on
+// Windows, Now Playing and the remote command center on macOS, and the media
+// session notification on Android. Each platform adapter implements
+// OsMediaControls so the player publishes state and receives lock screen and
+// media key commands the same way everywhere.
+
+use std::sync::{mpsc::Receiver, Arc, Mutex};
+
+use types::errors::Result;
+use types::mpris::MprisPlayerDetails;
+use types::ui::player_details::{PlayerMode, PlayerState};
+
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "ios"))))]
+mod mpris_adapter;
+#[cfg(target_os = "macos")]
+mod now_playing;
+#[cfg(target_os = "windows")]
+mod smtc;
+
+/// Command received from the OS media controls
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OsMediaEvent {
+    Play,
+    Pause,
+    /// The play/pause key; goes through the media key gesture detector
+    Toggle,
+    Stop,
+    Next,
+    Previous,
+    /// Seek to this position, in seconds
+    SeekTo(f64),
+    /// Seek this many seconds forward (or backward when negative)
+    SeekBy(f64),
+    /// Repeat or shuffle was changed from the OS surface
+    SetPlayerMode(PlayerMode),
+    /// Playback rate asked for by the OS surface
+    SetRate(f64),
+}
+
+/// Publishes the player state to the OS media surfaces and collects their
+/// commands
+pub trait OsMediaControls: Send + Sync {
+    fn set_metadata(&self, metadata: MprisPlayerDetails) -> Result<()>;
+
+    fn set_playback_state(&self, state: PlayerState) -> Result<()>;
+
+    /// Regular position update, in seconds
+    fn set_position(&self, position: f64) -> Result<()>;
+
+    /// The position jumped (user seek), in seconds
+    fn seeked(&self, position: f64) -> Result<()>;
+
+    /// Repeat and shuffle state; ignored where the platform has no such control
+    fn set_player_mode(&self, mode: PlayerMode) -> Result<()>;
+
+    /// Playback rate, 1.0 being normal; the OS extrapolates the position with it
+    fn set_rate(&self, rate: f64) -> Result<()>;
+
+    /// Commands from the OS, consumed by the player's event listener
+    fn events(&self) -> Arc<Mutex<Receiver<OsMediaEvent>>>;
+
+    /// Mobile media sessions talk to the app through the plugin handle
+    #[cfg(any(target_os = "android", target_os = "ios"))]
+    fn set_app_handle(&self, _app_handle: tauri::AppHandle) {}
+}
+
+/// Create the media controls of the current platform
+pub fn create() -> Result<Box<dyn OsMediaControls>> {
+    #[cfg(target_os = "windows")]
+    {
+        Ok(Box::new(smtc::SmtcControls::new()?))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        Ok(Box::new(now_playing::NowPlayingControls::new()?))
+    }
+    #[cfg(all(unix, not(any(target_os = "macos", target_os = "ios"))))]
+    {
+        Ok(Box::new(mpris_adapter::MprisControls::new()?))
+    }
+    #[cfg(not(any(target_os = "windows", all(unix, not(target_os = "ios")))))]
+    {
+        Err("OS media controls are not supported on this platform".into())
+    }
+}
+
+/// Repeat setting shown by OS surfaces that keep repeat and shuffle apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepeatMode {
+    Off,
+    Track,
+    List,
+}
+
+/// Repeat setting and shuffle switch for a player mode, as the MPRIS server
+/// reports them
+pub fn mode_parts(mode: PlayerMode) -> (RepeatMode, bool) {
+    match mode {
+        PlayerMode::Sequential => (RepeatMode::Off, false),
+        PlayerMode::Single => (RepeatMode::Track, false),
+        PlayerMode::ListLoop => (RepeatMode::List, false),
+        PlayerMode::Shuffle | PlayerMode::ShuffleAlbums => (RepeatMode::Off, true),
+    }
+}
+
+/// Player mode after the OS asked for another repeat setting. Turning repeat
+/// off keeps shuffling.
+pub fn mode_with_repeat(current: PlayerMode, repeat: RepeatMode) -> PlayerMode {
+    match repeat {
+        RepeatMode::Track => PlayerMode::Single,
+        RepeatMode::List => PlayerMode::ListLoop,
+        RepeatMode::Off if current.is_shuffle() => current,
+        RepeatMode::Off => PlayerMode::Sequential,
+    }
+}
+
+/// Player mode after the OS switched shuffle on or off
+pub fn mode_with_shuffle(current: PlayerMode, shuffle: bool) -> PlayerMode {
+    match (shuffle, current) {
+        (true, mode) if mode.is_shuffle() => mode,
+        (true, _) => PlayerMode::Shuffle,
+        (false, mode) if mode.is_shuffle() => PlayerMode::Sequential,
+        (false, mode) => mode,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeat_and_shuffle_requests_map_to_player_modes() {
+        for mode in [PlayerMode::Sequential, PlayerMode::Single, PlayerMode::ListLoop, PlayerMode::Shuffle] {
+            let (repeat, shuffle) = mode_parts(mode);
+            assert_eq!(mode_with_shuffle(mode_with_repeat(PlayerMode::Sequential, repeat), shuffle), mode);
+        }
+        assert_eq!(mode_with_repeat(PlayerMode::Shuffle, RepeatMode::Off), PlayerMode::Shuffle);
+        assert_eq!(mode_with_repeat(PlayerMode::Shuffle, RepeatMode::Track), PlayerMode::Single);
+        assert_eq!(mode_with_shuffle(PlayerMode::Single, false), PlayerMode::Single);
+        assert_eq!(mode_with_shuffle(PlayerMode::Shuffle, false), PlayerMode::Sequential);
+        // Shuffling by album stays so while the OS only knows shuffle on or off
+        assert_eq!(mode_with_shuffle(PlayerMode::ShuffleAlbums, true), PlayerMode::ShuffleAlbums);
+        assert_eq!(mode_with_repeat(PlayerMode::ShuffleAlbums, RepeatMode::Off), PlayerMode::ShuffleAlbums);
+        assert_eq!(mode_with_shuffle(PlayerMode::ShuffleAlbums, false), PlayerMode::Sequential);
+    }
+}
@@ -0,0 +1,107 @@
+// crates/audio-player/src/os_media/mpris_adapter.rs
+// MPRIS on the D-Bus session bus (Linux and BSDs) and the Android media
+// session, both provided by the mpris crate.
+
+use std::sync::{
+    mpsc::{self, Receiver},
+    Arc, Mutex,
+};
+
+use mpris::{MediaControlEvent, MprisHolder, SeekDirection};
+use types::errors::Result;
+use types::mpris::MprisPlayerDetails;
+use types::ui::player_details::{PlayerMode, PlayerState};
+
+use super::{OsMediaControls, OsMediaEvent};
+
+pub struct MprisControls {
+    holder: MprisHolder,
+    events: Arc<Mutex<Receiver<OsMediaEvent>>>,
+}
+
+impl MprisControls {
+    pub fn new() -> Result<Self> {
+        let holder = MprisHolder::new()?;
+        let (tx, rx) = mpsc::channel();
+        let source = holder.event_rx.clone();
+        std::thread::spawn(move || loop {
+            let event = match source.lock() {
+                Ok(rx) => match rx.recv() {
+                    Ok(event) => event,
+                    Err(_) => break,
+                },
+                Err(_) => break,
+            };
+            if let Some(event) = translate(event) {
+                if tx.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            holder,
+            events: Arc::new(Mutex::new(rx)),
+        })
+    }
+}
+
+fn translate(event: MediaControlEvent) -> Option<OsMediaEvent> {
+    let event = match event {
+        MediaControlEvent::Play => OsMediaEvent::Play,
+        MediaControlEvent::Pause => OsMediaEvent::Pause,
+        MediaControlEvent::Toggle => OsMediaEvent::Toggle,
+        MediaControlEvent::Stop => OsMediaEvent::Stop,
+        MediaControlEvent::Next => OsMediaEvent::Next,
+        MediaControlEvent::Previous => OsMediaEvent::Previous,
+        MediaControlEvent::SetPosition(position) => OsMediaEvent::SeekTo(position.0.as_secs_f64()),
+        MediaControlEvent::SeekBy(direction, amount) => OsMediaEvent::SeekBy(match direction {
+            SeekDirection::Forward => amount.as_secs_f64(),
+            SeekDirection::Backward => -amount.as_secs_f64(),
+        }),
+        // LoopStatus and Shuffle are only exposed on the D-Bus MPRIS server
+        #[cfg(not(target_os = "android"))]
+        MediaControlEvent::SetPlayerMode(mode) => OsMediaEvent::SetPlayerMode(mode),
+        #[cfg(not(target_os = "android"))]
+        MediaControlEvent::SetRate(rate) => OsMediaEvent::SetRate(rate),
+        other => {
+            tracing::debug!("Unhandled MPRIS event: {:?}", other);
+            return None;
+        }
+    };
+    Some(event)
+}
+
+impl OsMediaControls for MprisControls {
+    fn set_metadata(&self, metadata: MprisPlayerDetails) -> Result<()> {
+        self.holder.set_metadata(metadata)
+    }
+
+    fn set_playback_state(&self, state: PlayerState) -> Result<()> {
+        self.holder.set_playback_state(state)
+    }
+
+    fn set_position(&self, position: f64) -> Result<()> {
+        self.holder.set_position(position)
+    }
+
+    fn seeked(&self, position: f64) -> Result<()> {
+        self.holder.seeked(position)
+    }
+
+    fn set_player_mode(&self, mode: PlayerMode) -> Result<()> {
+        self.holder.set_player_mode(mode)
+    }
+
+    fn set_rate(&self, rate: f64) -> Result<()> {
+        self.holder.set_rate(rate)
+    }
+
+    fn events(&self) -> Arc<Mutex<Receiver<OsMediaEvent>>> {
+        self.events.clone()
+    }
+
+    #[cfg(target_os = "android")]
+    fn set_app_handle(&self, app_handle: tauri::AppHandle) {
+        self.holder.set_app_handle(app_handle);
+    }
+}
@@ -0,0 +1,272 @@
+// crates/audio-player/src/os_media/now_playing.rs
+// macOS Now Playing (Control Center, lock screen, Touch Bar) through
+// MPNowPlayingInfoCenter, and media keys through MPRemoteCommandCenter.
+// Command handlers run on the main run loop and only forward the request.
+
+use std::ptr::NonNull;
+use std::sync::{
+    mpsc::{self, Receiver, Sender},
+    Arc, Mutex,
+};
+
+use block2::RcBlock;
+use objc2::rc::Retained;
+use objc2::runtime::AnyObject;
+use objc2::AnyThread;
+use objc2_app_kit::NSImage;
+use objc2_foundation::{NSArray, NSMutableDictionary, NSNumber, NSSize, NSString, NSURL};
+use objc2_media_player::{
+    MPChangePlaybackPositionCommandEvent, MPChangeRepeatModeCommandEvent, MPChangeShuffleModeCommandEvent,
+    MPMediaItemArtwork, MPMediaItemPropertyAlbumArtist, MPMediaItemPropertyAlbumTitle, MPMediaItemPropertyArtist,
+    MPMediaItemPropertyArtwork, MPMediaItemPropertyPlaybackDuration, MPMediaItemPropertyTitle, MPNowPlayingInfoCenter,
+    MPNowPlayingInfoPropertyElapsedPlaybackTime, MPNowPlayingInfoPropertyPlaybackRate, MPNowPlayingPlaybackState,
+    MPRemoteCommand, MPRemoteCommandCenter, MPRemoteCommandEvent, MPRemoteCommandHandlerStatus, MPRepeatType,
+    MPShuffleType, MPSkipIntervalCommandEvent,
+};
+use types::errors::{MusicError, Result};
+use types::mpris::MprisPlayerDetails;
+use types::ui::player_details::{PlayerMode, PlayerState};
+
+use super::{mode_parts, mode_with_repeat, mode_with_shuffle, OsMediaControls, OsMediaEvent, RepeatMode};
+
+/// Interval offered by the skip forward/backward commands, in seconds
+const SKIP_INTERVAL: f64 = 15.0;
+
+pub struct NowPlayingControls {
+    state: Mutex<PlayerState>,
+    /// Current mode, to combine separate repeat and shuffle requests
+    mode: Arc<Mutex<PlayerMode>>,
+    /// Playback rate reported while playing
+    rate: Mutex<f64>,
+    events: Arc<Mutex<Receiver<OsMediaEvent>>>,
+}
+
+impl NowPlayingControls {
+    #[tracing::instrument(level = "debug", skip())]
+    pub fn new() -> Result<Self> {
+        let (tx, rx) = mpsc::channel();
+        let mode = Arc::new(Mutex::new(PlayerMode::default()));
+        Self::attach(tx, mode.clone());
+        Ok(Self {
+            state: Mutex::new(PlayerState::Stopped),
+            mode,
+            rate: Mutex::new(1.0),
+            events: Arc::new(Mutex::new(rx)),
+        })
+    }
+
+    /// Register the remote command handlers. They stay registered for the
+    /// lifetime of the process, like the player itself.
+    fn attach(tx: Sender<OsMediaEvent>, mode: Arc<Mutex<PlayerMode>>) {
+        unsafe {
+            let center = MPRemoteCommandCenter::sharedCommandCenter();
+
+            let simple: [(Retained<MPRemoteCommand>, OsMediaEvent); 6] = [
+                (center.playCommand(), OsMediaEvent::Play),
+                (center.pauseCommand(), OsMediaEvent::Pause),
+                (center.togglePlayPauseCommand(), OsMediaEvent::Toggle),
+                (center.stopCommand(), OsMediaEvent::Stop),
+                (center.nextTrackCommand(), OsMediaEvent::Next),
+                (center.previousTrackCommand(), OsMediaEvent::Previous),
+            ];
+            for (command, event) in simple {
+                let tx = tx.clone();
+                add_handler(&command, move |_| Some(event), tx);
+            }
+
+            add_handler(
+                &center.changePlaybackPositionCommand(),
+                |event| {
+                    let event = event.cast::<MPChangePlaybackPositionCommandEvent>();
+                    Some(OsMediaEvent::SeekTo(event.as_ref().positionTime()))
+                },
+                tx.clone(),
+            );
+
+            let intervals = NSArray::from_retained_slice(&[NSNumber::new_f64(SKIP_INTERVAL)]);
+            for (command, sign) in [(center.skipForwardCommand(), 1.0), (center.skipBackwardCommand(), -1.0)] {
+                command.setPreferredIntervals(&intervals);
+                add_handler(
+                    &command,
+                    move |event| {
+                        let event = event.cast::<MPSkipIntervalCommandEvent>();
+                        Some(OsMediaEvent::SeekBy(sign * event.as_ref().interval()))
+                    },
+                    tx.clone(),
+                );
+            }
+
+            let repeat_mode = mode.clone();
+            add_handler(
+                &center.changeRepeatModeCommand(),
+                move |event| {
+                    let event = event.cast::<MPChangeRepeatModeCommandEvent>();
+                    let repeat = match event.as_ref().repeatType() {
+                        MPRepeatType::One => RepeatMode::Track,
+                        MPRepeatType::All => RepeatMode::List,
+                        _ => RepeatMode::Off,
+                    };
+                    let current = repeat_mode.lock().map(|m| *m).unwrap_or_default();
+                    Some(OsMediaEvent::SetPlayerMode(mode_with_repeat(current, repeat)))
+                },
+                tx.clone(),
+            );
+
+            add_handler(
+                &center.changeShuffleModeCommand(),
+                move |event| {
+                    let event = event.cast::<MPChangeShuffleModeCommandEvent>();
+                    let shuffle = event.as_ref().shuffleType() != MPShuffleType::Off;
+                    let current = mode.lock().map(|m| *m).unwrap_or_default();
+                    Some(OsMediaEvent::SetPlayerMode(mode_with_shuffle(current, shuffle)))
+                },
+                tx,
+            );
+        }
+    }
+
+    /// Update the elapsed time and rate of the current Now Playing entry;
+    /// the system extrapolates the position from them.
+    fn update_progress(&self, position: Option<f64>) {
+        let playing = self.state.lock().map(|s| *s == PlayerState::Playing).unwrap_or(false);
+        let rate = self.rate.lock().map(|r| *r).unwrap_or(1.0);
+        unsafe {
+            let center = MPNowPlayingInfoCenter::defaultCenter();
+            let Some(info) = center.nowPlayingInfo() else { return };
+            let info = info.mutableCopy();
+            if let Some(position) = position {
+                info.insert(MPNowPlayingInfoPropertyElapsedPlaybackTime, &NSNumber::new_f64(position.max(0.0)));
+            }
+            info.insert(
+                MPNowPlayingInfoPropertyPlaybackRate,
+                &NSNumber::new_f64(if playing { rate } else { 0.0 }),
+            );
+            center.setNowPlayingInfo(Some(&**info));
+        }
+    }
+}
+
+/// Forward a remote command as the event returned by `map`
+unsafe fn add_handler(
+    command: &MPRemoteCommand,
+    map: impl Fn(NonNull<MPRemoteCommandEvent>) -> Option<OsMediaEvent> + 'static,
+    tx: Sender<OsMediaEvent>,
+) {
+    command.setEnabled(true);
+    let handler = RcBlock::new(move |event: NonNull<MPRemoteCommandEvent>| match map(event) {
+        Some(event) if tx.send(event).is_ok() => MPRemoteCommandHandlerStatus::Success,
+        _ => MPRemoteCommandHandlerStatus::CommandFailed,
+    });
+    command.addTargetWithHandler(&handler);
+}
+
+/// Cover for the Now Playing entry; only local files are loaded
+fn artwork(url: &str) -> Option<Retained<MPMediaItemArtwork>> {
+    unsafe {
+        let url = NSURL::URLWithString(&NSString::from_str(url))?;
+        if !url.isFileURL() {
+            return None;
+        }
+        let image = NSImage::initWithContentsOfURL(NSImage::alloc(), &url)?;
+        let size: NSSize = image.size();
+        let handler = RcBlock::new(move |_size: NSSize| NonNull::from(&*image));
+        Some(MPMediaItemArtwork::initWithBoundsSize_requestHandler(
+            MPMediaItemArtwork::alloc(),
+            size,
+            &handler,
+        ))
+    }
+}
+
+impl OsMediaControls for NowPlayingControls {
+    #[tracing::instrument(level = "debug", skip(self, metadata))]
+    fn set_metadata(&self, metadata: MprisPlayerDetails) -> Result<()> {
+        let playing = self.state.lock().map(|s| *s == PlayerState::Playing).unwrap_or(false);
+        let rate = self.rate.lock().map(|r| *r).unwrap_or(1.0);
+        unsafe {
+            let info = NSMutableDictionary::<NSString, AnyObject>::new();
+            let text = [
+                (MPMediaItemPropertyTitle, &metadata.title),
+                (MPMediaItemPropertyArtist, &metadata.artist_name),
+                (MPMediaItemPropertyAlbumTitle, &metadata.album_name),
+                (MPMediaItemPropertyAlbumArtist, &metadata.album_artist),
+            ];
+            for (key, value) in text {
+                if let Some(value) = value {
+                    info.insert(key, &NSString::from_str(value));
+                }
+            }
+            if let Some(duration) = metadata.duration {
+                info.insert(MPMediaItemPropertyPlaybackDuration, &NSNumber::new_f64(duration));
+            }
+            if let Some(artwork) = metadata.thumbnail.as_deref().and_then(artwork) {
+                info.insert(MPMediaItemPropertyArtwork, &artwork);
+            }
+            info.insert(MPNowPlayingInfoPropertyElapsedPlaybackTime, &NSNumber::new_f64(0.0));
+            info.insert(
+                MPNowPlayingInfoPropertyPlaybackRate,
+                &NSNumber::new_f64(if playing { rate } else { 0.0 }),
+            );
+            MPNowPlayingInfoCenter::defaultCenter().setNowPlayingInfo(Some(&**info));
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, state))]
+    fn set_playback_state(&self, state: PlayerState) -> Result<()> {
+        *self
+            .state
+            .lock()
+            .map_err(|_| MusicError::String("Now Playing state poisoned".into()))? = state;
+        let playback = match state {
+            PlayerState::Playing => MPNowPlayingPlaybackState::Playing,
+            PlayerState::Paused | PlayerState::Loading => MPNowPlayingPlaybackState::Paused,
+            PlayerState::Stopped | PlayerState::Idle | PlayerState::Errored => MPNowPlayingPlaybackState::Stopped,
+        };
+        unsafe {
+            MPNowPlayingInfoCenter::defaultCenter().setPlaybackState(playback);
+        }
+        self.update_progress(None);
+        Ok(())
+    }
+
+    fn set_position(&self, position: f64) -> Result<()> {
+        self.update_progress(Some(position));
+        Ok(())
+    }
+
+    fn seeked(&self, position: f64) -> Result<()> {
+        self.set_position(position)
+    }
+
+    fn set_player_mode(&self, mode: PlayerMode) -> Result<()> {
+        if let Ok(mut current) = self.mode.lock() {
+            *current = mode;
+        }
+        let (repeat, shuffle) = mode_parts(mode);
+        unsafe {
+            let center = MPRemoteCommandCenter::sharedCommandCenter();
+            center.changeRepeatModeCommand().setCurrentRepeatType(match repeat {
+                RepeatMode::Off => MPRepeatType::Off,
+                RepeatMode::Track => MPRepeatType::One,
+                RepeatMode::List => MPRepeatType::All,
+            });
+            center
+                .changeShuffleModeCommand()
+                .setCurrentShuffleType(if shuffle { MPShuffleType::Items } else { MPShuffleType::Off });
+        }
+        Ok(())
+    }
+
+    fn set_rate(&self, rate: f64) -> Result<()> {
+        if let Ok(mut current) = self.rate.lock() {
+            *current = rate;
+        }
+        self.update_progress(None);
+        Ok(())
+    }
+
+    fn events(&self) -> Arc<Mutex<Receiver<OsMediaEvent>>> {
+        self.events.clone()
+    }
+}
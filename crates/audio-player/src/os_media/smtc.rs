@@ -0,0 +1,235 @@
+// crates/audio-player/src/os_media/smtc.rs
+// Windows SystemMediaTransportControls (lock screen, volume flyout, media
+// keys). The controls come from a muted MediaPlayer whose own command handling
+// is switched off, so no window handle is needed; the app only fills in the
+// display and timeline properties and forwards the requests.
+
+use std::sync::{
+    mpsc::{self, Receiver, Sender},
+    Arc, Mutex,
+};
+
+use types::errors::{MusicError, Result};
+use types::mpris::MprisPlayerDetails;
+use types::ui::player_details::{PlayerMode, PlayerState};
+use windows::core::HSTRING;
+use windows::Foundation::{TimeSpan, TypedEventHandler, Uri};
+use windows::Media::Playback::MediaPlayer;
+use windows::Media::{
+    AutoRepeatModeChangeRequestedEventArgs, MediaPlaybackAutoRepeatMode, MediaPlaybackStatus, MediaPlaybackType,
+    PlaybackPositionChangeRequestedEventArgs, ShuffleEnabledChangeRequestedEventArgs, SystemMediaTransportControls,
+    SystemMediaTransportControlsButton, SystemMediaTransportControlsButtonPressedEventArgs,
+    SystemMediaTransportControlsTimelineProperties,
+};
+use windows::Storage::Streams::RandomAccessStreamReference;
+
+use super::{mode_parts, mode_with_repeat, mode_with_shuffle, OsMediaControls, OsMediaEvent, RepeatMode};
+
+/// TimeSpan ticks per second (100 ns units)
+const TICKS_PER_SECOND: f64 = 10_000_000.0;
+
+fn smtc_error(e: windows::core::Error) -> MusicError {
+    MusicError::String(format!("SMTC: {}", e))
+}
+
+fn timespan(seconds: f64) -> TimeSpan {
+    TimeSpan {
+        Duration: (seconds.max(0.0) * TICKS_PER_SECOND) as i64,
+    }
+}
+
+pub struct SmtcControls {
+    // Owns the controls; dropping it removes the app from the flyout
+    _player: MediaPlayer,
+    controls: SystemMediaTransportControls,
+    /// Track length in seconds, for the timeline
+    duration: Mutex<f64>,
+    /// Current mode, to combine separate repeat and shuffle requests
+    mode: Arc<Mutex<PlayerMode>>,
+    events: Arc<Mutex<Receiver<OsMediaEvent>>>,
+}
+
+impl SmtcControls {
+    #[tracing::instrument(level = "debug", skip())]
+    pub fn new() -> Result<Self> {
+        let player = MediaPlayer::new().map_err(smtc_error)?;
+        player.CommandManager().and_then(|m| m.SetIsEnabled(false)).map_err(smtc_error)?;
+        let controls = player.SystemMediaTransportControls().map_err(smtc_error)?;
+
+        controls.SetIsEnabled(true).map_err(smtc_error)?;
+        controls.SetIsPlayEnabled(true).map_err(smtc_error)?;
+        controls.SetIsPauseEnabled(true).map_err(smtc_error)?;
+        controls.SetIsStopEnabled(true).map_err(smtc_error)?;
+        controls.SetIsNextEnabled(true).map_err(smtc_error)?;
+        controls.SetIsPreviousEnabled(true).map_err(smtc_error)?;
+
+        let (tx, rx) = mpsc::channel();
+        let mode = Arc::new(Mutex::new(PlayerMode::default()));
+        Self::attach(&controls, tx, mode.clone()).map_err(smtc_error)?;
+
+        Ok(Self {
+            _player: player,
+            controls,
+            duration: Mutex::new(0.0),
+            mode,
+            events: Arc::new(Mutex::new(rx)),
+        })
+    }
+
+    fn attach(
+        controls: &SystemMediaTransportControls,
+        tx: Sender<OsMediaEvent>,
+        mode: Arc<Mutex<PlayerMode>>,
+    ) -> windows::core::Result<()> {
+        let button_tx = tx.clone();
+        controls.ButtonPressed(&TypedEventHandler::<
+            SystemMediaTransportControls,
+            SystemMediaTransportControlsButtonPressedEventArgs,
+        >::new(move |_, args| {
+            let Some(args) = args.as_ref() else { return Ok(()) };
+            let event = match args.Button()? {
+                SystemMediaTransportControlsButton::Play => OsMediaEvent::Play,
+                SystemMediaTransportControlsButton::Pause => OsMediaEvent::Pause,
+                SystemMediaTransportControlsButton::Stop => OsMediaEvent::Stop,
+                SystemMediaTransportControlsButton::Next => OsMediaEvent::Next,
+                SystemMediaTransportControlsButton::Previous => OsMediaEvent::Previous,
+                _ => return Ok(()),
+            };
+            let _ = button_tx.send(event);
+            Ok(())
+        }))?;
+
+        let seek_tx = tx.clone();
+        controls.PlaybackPositionChangeRequested(&TypedEventHandler::<
+            SystemMediaTransportControls,
+            PlaybackPositionChangeRequestedEventArgs,
+        >::new(move |_, args| {
+            let Some(args) = args.as_ref() else { return Ok(()) };
+            let position = args.RequestedPlaybackPosition()?.Duration as f64 / TICKS_PER_SECOND;
+            let _ = seek_tx.send(OsMediaEvent::SeekTo(position));
+            Ok(())
+        }))?;
+
+        let repeat_tx = tx.clone();
+        let repeat_mode = mode.clone();
+        controls.AutoRepeatModeChangeRequested(&TypedEventHandler::<
+            SystemMediaTransportControls,
+            AutoRepeatModeChangeRequestedEventArgs,
+        >::new(move |_, args| {
+            let Some(args) = args.as_ref() else { return Ok(()) };
+            let repeat = match args.RequestedAutoRepeatMode()? {
+                MediaPlaybackAutoRepeatMode::Track => RepeatMode::Track,
+                MediaPlaybackAutoRepeatMode::List => RepeatMode::List,
+                _ => RepeatMode::Off,
+            };
+            let current = repeat_mode.lock().map(|m| *m).unwrap_or_default();
+            let _ = repeat_tx.send(OsMediaEvent::SetPlayerMode(mode_with_repeat(current, repeat)));
+            Ok(())
+        }))?;
+
+        let shuffle_tx = tx;
+        controls.ShuffleEnabledChangeRequested(&TypedEventHandler::<
+            SystemMediaTransportControls,
+            ShuffleEnabledChangeRequestedEventArgs,
+        >::new(move |_, args| {
+            let Some(args) = args.as_ref() else { return Ok(()) };
+            let shuffle = args.RequestedShuffleEnabled()?;
+            let current = mode.lock().map(|m| *m).unwrap_or_default();
+            let _ = shuffle_tx.send(OsMediaEvent::SetPlayerMode(mode_with_shuffle(current, shuffle)));
+            Ok(())
+        }))?;
+
+        Ok(())
+    }
+
+    fn update_timeline(&self, position: f64) -> windows::core::Result<()> {
+        let duration = self.duration.lock().map(|d| *d).unwrap_or(0.0);
+        let timeline = SystemMediaTransportControlsTimelineProperties::new()?;
+        timeline.SetStartTime(timespan(0.0))?;
+        timeline.SetEndTime(timespan(duration))?;
+        timeline.SetMinSeekTime(timespan(0.0))?;
+        timeline.SetMaxSeekTime(timespan(duration))?;
+        let position = if duration > 0.0 { position.min(duration) } else { position };
+        timeline.SetPosition(timespan(position))?;
+        self.controls.UpdateTimelineProperties(&timeline)
+    }
+}
+
+impl OsMediaControls for SmtcControls {
+    #[tracing::instrument(level = "debug", skip(self, metadata))]
+    fn set_metadata(&self, metadata: MprisPlayerDetails) -> Result<()> {
+        if let Ok(mut duration) = self.duration.lock() {
+            *duration = metadata.duration.unwrap_or(0.0);
+        }
+
+        let updater = self.controls.DisplayUpdater().map_err(smtc_error)?;
+        updater.SetType(MediaPlaybackType::Music).map_err(smtc_error)?;
+        let music = updater.MusicProperties().map_err(smtc_error)?;
+        music
+            .SetTitle(&HSTRING::from(metadata.title.unwrap_or_default()))
+            .map_err(smtc_error)?;
+        music
+            .SetArtist(&HSTRING::from(metadata.artist_name.unwrap_or_default()))
+            .map_err(smtc_error)?;
+        music
+            .SetAlbumTitle(&HSTRING::from(metadata.album_name.unwrap_or_default()))
+            .map_err(smtc_error)?;
+        music
+            .SetAlbumArtist(&HSTRING::from(metadata.album_artist.unwrap_or_default()))
+            .map_err(smtc_error)?;
+
+        let thumbnail = metadata
+            .thumbnail
+            .as_deref()
+            .map(|url| Uri::CreateUri(&HSTRING::from(url)).and_then(|uri| RandomAccessStreamReference::CreateFromUri(&uri)));
+        match thumbnail {
+            Some(Ok(stream)) => updater.SetThumbnail(&stream).map_err(smtc_error)?,
+            Some(Err(e)) => tracing::debug!("SMTC thumbnail unavailable: {}", e),
+            None => updater.SetThumbnail(None::<&RandomAccessStreamReference>).map_err(smtc_error)?,
+        }
+        updater.Update().map_err(smtc_error)?;
+
+        self.update_timeline(0.0).map_err(smtc_error)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, state))]
+    fn set_playback_state(&self, state: PlayerState) -> Result<()> {
+        let status = match state {
+            PlayerState::Playing => MediaPlaybackStatus::Playing,
+            PlayerState::Paused => MediaPlaybackStatus::Paused,
+            PlayerState::Loading => MediaPlaybackStatus::Changing,
+            PlayerState::Stopped | PlayerState::Idle | PlayerState::Errored => MediaPlaybackStatus::Stopped,
+        };
+        self.controls.SetPlaybackStatus(status).map_err(smtc_error)
+    }
+
+    fn set_position(&self, position: f64) -> Result<()> {
+        self.update_timeline(position).map_err(smtc_error)
+    }
+
+    fn seeked(&self, position: f64) -> Result<()> {
+        self.set_position(position)
+    }
+
+    fn set_player_mode(&self, mode: PlayerMode) -> Result<()> {
+        if let Ok(mut current) = self.mode.lock() {
+            *current = mode;
+        }
+        let (repeat, shuffle) = mode_parts(mode);
+        let repeat = match repeat {
+            RepeatMode::Off => MediaPlaybackAutoRepeatMode::None,
+            RepeatMode::Track => MediaPlaybackAutoRepeatMode::Track,
+            RepeatMode::List => MediaPlaybackAutoRepeatMode::List,
+        };
+        self.controls.SetAutoRepeatMode(repeat).map_err(smtc_error)?;
+        self.controls.SetShuffleEnabled(shuffle).map_err(smtc_error)
+    }
+
+    fn set_rate(&self, rate: f64) -> Result<()> {
+        self.controls.SetPlaybackRate(rate).map_err(smtc_error)
+    }
+
+    fn events(&self) -> Arc<Mutex<Receiver<OsMediaEvent>>> {
+        self.events.clone()
+    }
+}
@@ -0,0 +1,146 @@
+// crates/audio-player/src/persist.rs
+// Schema versioning of the player state persisted in `player_store_kv`.
+//
+// Version history:
+//   1 - `track_queue` held raw track IDs; no version key was written
+//   2 - queue instance IDs (`<track_id>#<n>`), version key written
+//
+// Payloads written by a newer app (after a downgrade) are read leniently:
+// queue entries that no longer parse are skipped instead of dropping the whole
+// queue, and fields this version does not know are carried over when saving,
+// so upgrading again finds them intact.
+
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+/// Schema version written by this build
+pub const STORE_SCHEMA_VERSION: u32 = 2;
+/// Key of the schema version in `player_store_kv`
+pub const VERSION_KEY: &str = "store_schema_version";
+
+/// Schema version of the persisted state, `None` when the marker is unreadable.
+/// Stores without a marker predate versioning.
+pub fn parse_version(raw: Option<&str>) -> Option<u32> {
+    match raw {
+        None => Some(1),
+        Some(raw) => serde_json::from_str(raw).ok(),
+    }
+}
+
+/// Key a payload this version cannot read is copied to before it is replaced
+pub fn backup_key(key: &str, version: Option<u32>) -> String {
+    match version {
+        Some(version) => format!("{}.v{}.backup", key, version),
+        None => format!("{}.unknown.backup", key),
+    }
+}
+
+/// Parse a JSON object of entries one by one, skipping entries that fail.
+/// Returns `None` when `raw` is not an object at all, with the number of
+/// skipped entries otherwise.
+pub fn parse_entries<T: DeserializeOwned>(raw: &str) -> Option<(HashMap<String, T>, usize)> {
+    let Value::Object(entries) = serde_json::from_str::<Value>(raw).ok()? else {
+        return None;
+    };
+    let total = entries.len();
+    let parsed: HashMap<String, T> = entries
+        .into_iter()
+        .filter_map(|(key, value)| serde_json::from_value(value).ok().map(|v| (key, v)))
+        .collect();
+    let skipped = total - parsed.len();
+    Some((parsed, skipped))
+}
+
+/// Copy the fields of `stored` missing from `new`, descending `depth` levels
+/// into nested objects present in both. Only struct-shaped objects should be
+/// descended into: for maps, a missing key is a removed entry.
+pub fn preserve_unknown(stored: &Value, new: &mut Value, depth: usize) {
+    let (Value::Object(stored), Value::Object(new)) = (stored, new) else {
+        return;
+    };
+    for (key, stored_value) in stored {
+        match new.get_mut(key) {
+            None => {
+                new.insert(key.clone(), stored_value.clone());
+            }
+            Some(new_value) if depth > 0 => preserve_unknown(stored_value, new_value, depth - 1),
+            Some(_) => {}
+        }
+    }
+}
+
+/// `preserve_unknown` for each entry of a map present in both payloads.
+pub fn preserve_unknown_entries(stored: &Value, new: &mut Value, depth: usize) {
+    let (Value::Object(stored), Value::Object(new)) = (stored, new) else {
+        return;
+    };
+    for (key, new_entry) in new.iter_mut() {
+        if let Some(stored_entry) = stored.get(key) {
+            preserve_unknown(stored_entry, new_entry, depth);
+        }
+    }
+}
+
+/// Carry the unknown fields of `stored`, the payload of `key` written by a
+/// newer app, over to `new`.
+pub fn merge_preserved(key: &str, stored: &Value, new: &mut Value) {
+    match key {
+        "player_state" => preserve_unknown(stored, new, 0),
+        // Track fields live one level down (`track`, `album`)
+        "queue_data" => preserve_unknown_entries(stored, new, 1),
+        "queue_overrides" => preserve_unknown_entries(stored, new, 0),
+        _ => {}
+    }
+}
+
+/// Parse `raw` as a JSON value, an empty object when it is not JSON
+pub fn raw_value(raw: &str) -> Value {
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::Object(Map::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn missing_marker_is_the_legacy_version() {
+        assert_eq!(parse_version(None), Some(1));
+        assert_eq!(parse_version(Some("3")), Some(3));
+        assert_eq!(parse_version(Some("{broken")), None);
+    }
+
+    #[test]
+    fn unreadable_entries_are_skipped() {
+        let (parsed, skipped) = parse_entries::<u32>(r#"{"a": 1, "b": "x", "c": 3}"#).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(skipped, 1);
+        assert!(parse_entries::<u32>("[1, 2]").is_none());
+    }
+
+    #[test]
+    fn unknown_fields_survive_a_save() {
+        let stored = json!({
+            "a#0": { "track": { "_id": "a", "loudness": -9.5 }, "mood": "calm" },
+            "b#0": { "track": { "_id": "b" } }
+        });
+        let mut new = json!({
+            "a#0": { "track": { "_id": "a", "title": "A" } }
+        });
+        merge_preserved("queue_data", &stored, &mut new);
+        assert_eq!(
+            new,
+            json!({ "a#0": { "track": { "_id": "a", "title": "A", "loudness": -9.5 }, "mood": "calm" } })
+        );
+    }
+
+    #[test]
+    fn map_entries_are_not_resurrected() {
+        let stored = json!({ "volume_map": { "old": 0.5 }, "eq": [1, 2] });
+        let mut new = json!({ "volume_map": {} });
+        merge_preserved("player_state", &stored, &mut new);
+        assert_eq!(new, json!({ "volume_map": {}, "eq": [1, 2] }));
+    }
+}
@@ -1,10 +1,11 @@
 use std::sync::Arc;
 use types::errors::Result;
 use types::ui::player_details::PlayerEvents;
-use types::songs::{Song, SongType};
+use types::tracks::{MediaContent, TrackType};
 use tokio::sync::oneshot::Sender as OneShotSender;
 use dyn_clone::DynClone;
 use std::any::Any;
+use crate::crossfade::CrossfadeConfig;
 
 pub type PlayerEventsSender = Arc<dyn Fn(String, PlayerEvents) + Send + Sync>;
 
@@ -12,14 +13,25 @@ pub trait BasePlayer: std::fmt::Debug + DynClone + Send + Sync {
   fn initialize(&self);
   fn key(&self) -> String;
   fn load(&self, src: String, autoplay: bool, resolver: OneShotSender<()>);
+  /// Load `src` while fading out whatever is playing. Backends that cannot mix
+  /// two sources fall back to a plain `load`.
+  fn load_crossfade(&self, src: String, autoplay: bool, resolver: OneShotSender<()>, _fade: CrossfadeConfig) {
+    self.load(src, autoplay, resolver)
+  }
   fn stop(&mut self) -> Result<()>;
   fn play(&self) -> Result<()>;
   fn pause(&self) -> Result<()>;
   fn seek(&self, pos: f64) -> Result<()>;
-  fn provides(&self) -> &[SongType];
-  fn can_play(&self, song: &Song) -> bool;
+  fn provides(&self) -> &[TrackType];
+  fn can_play(&self, track: &MediaContent) -> bool;
   fn set_volume(&self, volume: f64) -> Result<()>;
   fn get_volume(&self) -> Result<f64>;
+  /// Move playback to another output device, `None` for the system default.
+  /// Backends that do not render through a local device ignore it.
+  fn set_output_device(&self, _device: Option<String>) -> Result<()> { Ok(()) }
+  /// Whether the backend loops the A-B region itself, sample accurate. Others
+  /// are sent back to its start on time updates.
+  fn loops_regions(&self) -> bool { false }
   fn add_listeners(&mut self, state_setter: PlayerEventsSender);
   fn configure(&mut self, _key: &str, _opaque: &dyn Any) { }
 }
\ No newline at end of file
@@ -3,7 +3,7 @@ use std::any::Any;
 
 use types::{
     errors::Result,
-    songs::{Song, SongType},
+    tracks::{MediaContent, TrackType},
 };
 
 use super::base::{BasePlayer, PlayerEventsSender};
@@ -65,7 +65,7 @@ impl LibrespotPlayer {
     }
 }
 
-static PROVIDES: [SongType; 1] = [SongType::SPOTIFY];
+static PROVIDES: [TrackType; 1] = [TrackType::SPOTIFY];
 
 impl BasePlayer for LibrespotPlayer {
     #[tracing::instrument(level = "debug", skip(self))]
@@ -108,10 +108,10 @@ impl BasePlayer for LibrespotPlayer {
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
-    fn provides(&self) -> &[SongType] { &PROVIDES }
+    fn provides(&self) -> &[TrackType] { &PROVIDES }
 
-    #[tracing::instrument(level = "debug", skip(self, song))]
-    fn can_play(&self, song: &Song) -> bool { song.song.type_ == SongType::SPOTIFY }
+    #[tracing::instrument(level = "debug", skip(self, track))]
+    fn can_play(&self, track: &MediaContent) -> bool { track.track.type_ == TrackType::SPOTIFY }
 
     #[tracing::instrument(level = "debug", skip(self, volume))]
     fn set_volume(&self, volume: f64) -> Result<()> {
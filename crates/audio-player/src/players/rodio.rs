@@ -1,7 +1,7 @@
 use std::{
     path::PathBuf,
     str::FromStr,
-    sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}},
+    sync::{Arc, Mutex, atomic::{AtomicBool, AtomicUsize, Ordering}},
     thread,
     time::Duration,
     fs::File,
@@ -10,13 +10,40 @@ use crossbeam_channel::{unbounded, Receiver, Sender};
 use tracing::{trace, debug, info, error};
 use types::{errors::{Result, error_helpers}, tracks::{TrackType}, ui::player_details::PlayerEvents};
 use stream_download::{StreamDownload, Settings};
+use stream_download::http::{reqwest, HttpStream};
+use stream_download::storage::bounded::BoundedStorageProvider;
+use stream_download::storage::memory::MemoryStorageProvider;
 use stream_download::storage::temp::TempStorageProvider;
 use hls_client::{config::ConfigBuilder, stream::HLSStream};
 use rodio::Sink;
 
 use super::base::{BasePlayer, PlayerEventsSender};
-
-// Supported track types for Rodio backend (DASH handled by dash backend)
+use crate::buffering::{self, BufferedReader, DownloadProgress};
+use crate::crossfade::CrossfadeConfig;
+use crate::ab_loop::{self, AbLoop};
+use crate::crossfeed::Crossfeed;
+use crate::stretch::{self, TimeStretch};
+use crate::data_usage::StreamCounter;
+use crate::expiry::{self, StreamFailure};
+use crate::devices::{self, OutputSelection};
+use crate::icy::{self, IcyReader};
+use crate::segmented::{self, Manifest, Protocol, SegmentedSource};
+use crate::visualizer::VisualizerTap;
+
+/// Interval between two gain updates while crossfading
+const FADE_STEP: Duration = Duration::from_millis(50);
+/// Bytes downloaded before an HTTP stream starts decoding, unless configured
+const HTTP_PREFETCH_BYTES: u64 = 512;
+/// Bytes of a live stream (no length) kept in memory; it never ends, so it
+/// is not written to disk
+const LIVE_BUFFER_BYTES: usize = 1024 * 1024;
+/// Bytes of a live stream buffered before it starts playing, unless configured
+const LIVE_PREFETCH_BYTES: u64 = 64 * 1024;
+/// Interval between two checks of the available output devices
+const DEVICE_POLL: Duration = Duration::from_secs(3);
+
+// Supported track types for Rodio backend; HLS and DASH manifests play as
+// URL or HLS tracks
 static PROVIDES: [TrackType; 3] = [TrackType::LOCAL, TrackType::URL, TrackType::HLS];
 
 #[derive(Debug, Clone)]
@@ -32,18 +59,21 @@ pub struct RodioPlayer {
 
 #[derive(Debug, Clone)]
 enum RodioCommand {
-    SetSrc(String),
+    /// Load a source, crossfading from the current one when a config is given
+    SetSrc(String, Option<CrossfadeConfig>),
     Play,
     Pause,
     Stop,
     SetVolume(f64),
     Seek(u64),
+    /// Move playback to another output device, `None` for the system default
+    SetDevice(Option<String>),
 }
 
 impl RodioPlayer {
 
-    #[tracing::instrument(level = "debug", skip(cache_dir))]
-    pub fn new(cache_dir: PathBuf) -> Self {
+    #[tracing::instrument(level = "debug", skip(cache_dir, output))]
+    pub fn new(cache_dir: PathBuf, output: Arc<OutputSelection>) -> Self {
         let (events_tx, events_rx) = unbounded::<PlayerEvents>();
         let cache_dir = cache_dir.join("rodio");
         if !cache_dir.exists() {
@@ -53,7 +83,8 @@ impl RodioPlayer {
         let playing = Arc::new(AtomicBool::new(false));
         let position = Arc::new(Mutex::new(0.0f64));
 
-        let tx = Self::initialize(events_tx, cache_dir, playing.clone(), position.clone());
+        let tx = Self::initialize(events_tx, cache_dir, playing.clone(), position.clone(), output.clone());
+        Self::spawn_device_monitor(tx.clone(), output);
         Self {
             tx,
             events_rx: Arc::new(Mutex::new(events_rx)),
@@ -63,72 +94,208 @@ impl RodioPlayer {
         }
     }
 
-    async fn set_src(cache_dir: PathBuf, src: String, sink: &Arc<Sink>) -> Result<()> {
-        if src.ends_with(".m3u8") || src.contains(".m3u8") {
-            Self::handle_hls_stream(cache_dir.clone(), &src, sink).await?;
+    /// Load `src` into `sink`. Streams breaking off while they play are
+    /// reported to `failure`.
+    async fn set_src(
+        cache_dir: PathBuf,
+        src: String,
+        sink: &Arc<Sink>,
+        events_tx: &Sender<PlayerEvents>,
+        failure: &StreamFailure,
+    ) -> Result<()> {
+        let started = std::time::Instant::now();
+        let (source, result) = if let Some(protocol) = segmented::protocol_of_url(&src) {
+            (protocol.as_str(), Self::handle_segmented_stream(cache_dir.clone(), &src, protocol, sink, failure).await)
         } else if src.starts_with("http") {
-            Self::handle_http_stream(cache_dir.clone(), &src, sink).await?;
+            ("http", Self::handle_http_stream(cache_dir.clone(), &src, sink, events_tx, failure).await)
         } else {
-            Self::handle_local_file(&src, sink).await?;
-        }
+            ("local", Self::handle_local_file(&src, sink).await)
+        };
+
+        crate::trace::record(
+            "decoder",
+            serde_json::json!({
+                "backend": "rodio",
+                "source": source,
+                "src": src,
+                "ok": result.is_ok(),
+                "elapsed_ms": started.elapsed().as_millis() as u64,
+                "queued_sources": sink.len(),
+            }),
+        );
+        result
+    }
 
+    /// HLS or DASH stream. Live HLS playlists go through `hls_client`; the
+    /// others through a `SegmentedSource`, which follows the bandwidth and
+    /// seeks across segments.
+    async fn handle_segmented_stream(
+        cache_dir: PathBuf,
+        src: &str,
+        protocol: Protocol,
+        sink: &Arc<Sink>,
+        failure: &StreamFailure,
+    ) -> Result<()> {
+        let presentation = match segmented::load(src, protocol).await? {
+            Manifest::Static(presentation) => presentation,
+            Manifest::Live => return Self::handle_hls_stream(cache_dir, src, sink).await,
+        };
+        crate::trace::record(
+            "buffer",
+            serde_json::json!({
+                "source": protocol.as_str(),
+                "bandwidths": presentation.bandwidths(),
+                "duration": presentation.duration(),
+            }),
+        );
+        let source = SegmentedSource::new(presentation, 0.0, failure.clone())?;
+        trace!("Segmented source created");
+        sink.append(VisualizerTap::new(TimeStretch::new(AbLoop::new(Crossfeed::new(source)))));
+        trace!("Segmented source appended");
         Ok(())
     }
 
+    /// Live HLS playlist
     async fn handle_hls_stream(cache_dir: PathBuf, src: &str, sink: &Arc<Sink>) -> Result<()> {
+        let counter = StreamCounter::default();
         let reader = StreamDownload::new::<HLSStream>(
             ConfigBuilder::new().url(src).map_err(error_helpers::to_playback_error)?.build().map_err(error_helpers::to_playback_error)?,
             TempStorageProvider::new_in(cache_dir.clone()),
-            Settings::default(),
+            Settings::default().on_progress(move |_cl, state, _c| counter.update(state.current_position)),
         )
         .await
         .map_err(error_helpers::to_playback_error)?;
 
         info!("HLS Stream content length {:?}", reader.content_length());
+        crate::trace::record(
+            "buffer",
+            serde_json::json!({ "source": "hls", "content_length": reader.content_length() }),
+        );
         trace!("Stream created");
 
         let decoder = rodio::Decoder::new(reader).map_err(error_helpers::to_playback_error)?;
         trace!("Decoder created");
-        sink.append(decoder);
+        sink.append(VisualizerTap::new(TimeStretch::new(AbLoop::new(Crossfeed::new(decoder)))));
         trace!("Decoder appended");
 
         Ok(())
     }
 
-    async fn handle_http_stream(cache_dir: PathBuf, src: &str, sink: &Arc<Sink>) -> Result<()> {
+    /// Plain HTTP file or live (SHOUTcast/Icecast) stream. Live streams are
+    /// buffered in memory, and their in-band metadata is reported as
+    /// `StreamTitle` events. Files rebuffer after a stall, see `buffering`.
+    /// A URL refused as expired fails with `expiry::StreamExpired`.
+    async fn handle_http_stream(
+        cache_dir: PathBuf,
+        src: &str,
+        sink: &Arc<Sink>,
+        events_tx: &Sender<PlayerEvents>,
+        failure: &StreamFailure,
+    ) -> Result<()> {
         trace!("Creating HTTP stream");
+        let counter = StreamCounter::default();
+
+        // Servers without in-band metadata ignore the header
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(icy::REQUEST_HEADER, reqwest::header::HeaderValue::from_static("1"));
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .map_err(error_helpers::to_playback_error)?;
+        let url = src.parse().map_err(error_helpers::to_playback_error)?;
+        let stream = match HttpStream::new(client, url).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                // The error doesn't tell the status, ask the server again
+                if let Some(status) = expiry::probe_status(src).await.filter(|s| expiry::is_expired_status(*s)) {
+                    return Err(expiry::expired(status));
+                }
+                return Err(types::errors::MusicError::from(e.to_string()));
+            }
+        };
+        // Manifests served from URLs without a telling extension
+        if let Some(protocol) = stream.header("content-type").and_then(segmented::protocol_of_type) {
+            drop(stream);
+            return Self::handle_segmented_stream(cache_dir, src, protocol, sink, failure).await;
+        }
 
-        match StreamDownload::new_http(
-            src.parse().unwrap(),
-            TempStorageProvider::new_in(cache_dir.clone()),
-            Settings::default()
-                .on_progress(move |_cl, state, _c| {
-                    tracing::debug!("Progress: {}", state.current_position)
-                })
-                .prefetch_bytes(512),
-        )
-        .await
-        {
-            Ok(reader) => {
-                trace!("Stream created");
-
-                let decoder = rodio::Decoder::new(reader).map_err(error_helpers::to_playback_error)?;
-                trace!("Decoder created");
-                sink.append(decoder);
-                trace!("Decoder appended");
+        let metaint = stream.header(icy::METAINT_HEADER).and_then(icy::parse_metaint);
+        let live = metaint.is_some() || stream.header("content-length").is_none();
+        let content_length = stream.header("content-length").and_then(|len| len.parse::<u64>().ok());
+        let config = buffering::config();
+        let prefetch_bytes = config
+            .prefetch_bytes
+            .unwrap_or(if live { LIVE_PREFETCH_BYTES } else { HTTP_PREFETCH_BYTES });
+        crate::trace::record(
+            "buffer",
+            serde_json::json!({
+                "source": "http",
+                "live": live,
+                "icy_metaint": metaint,
+                "prefetch_bytes": prefetch_bytes,
+                "rebuffer_bytes": config.rebuffer_bytes,
+            }),
+        );
+        let progress = DownloadProgress::new(prefetch_bytes, events_tx.clone());
+        let download = progress.clone();
+        let settings = Settings::default().on_progress(move |_cl, state, _c| {
+            tracing::debug!("Progress: {}", state.current_position);
+            counter.update(state.current_position);
+            download.update(state.current_position);
+        });
 
-                Ok(())
+        if live {
+            let storage = BoundedStorageProvider::new(
+                MemoryStorageProvider,
+                std::num::NonZeroUsize::new(LIVE_BUFFER_BYTES).unwrap(),
+            );
+            let reader = StreamDownload::from_stream(stream, storage, settings.prefetch_bytes(prefetch_bytes))
+                .await
+                .map_err(|e| types::errors::MusicError::from(e.to_string()))?;
+            trace!("Live stream created");
+            match metaint {
+                Some(metaint) => {
+                    let events_tx = events_tx.clone();
+                    let reader = IcyReader::new(reader, metaint, move |title| {
+                        debug!("Stream title: {}", title);
+                        let _ = events_tx.send(PlayerEvents::StreamTitle(title));
+                    });
+                    Self::append_decoder(reader, sink)
+                }
+                None => Self::append_decoder(reader, sink),
             }
-            Err(e) => Err(e.to_string().into()),
+        } else {
+            let reader = StreamDownload::from_stream(
+                stream,
+                TempStorageProvider::new_in(cache_dir.clone()),
+                settings.prefetch_bytes(prefetch_bytes),
+            )
+            .await
+            .map_err(|e| types::errors::MusicError::from(e.to_string()))?;
+            trace!("Stream created");
+            let reader = BufferedReader::new(reader, progress, content_length, config.rebuffer_bytes)
+                .with_failure(failure.clone());
+            Self::append_decoder(reader, sink)
         }
     }
 
+    fn append_decoder<R>(reader: R, sink: &Arc<Sink>) -> Result<()>
+    where
+        R: std::io::Read + std::io::Seek + Send + Sync + 'static,
+    {
+        let decoder = rodio::Decoder::new(reader).map_err(error_helpers::to_playback_error)?;
+        trace!("Decoder created");
+        sink.append(VisualizerTap::new(TimeStretch::new(AbLoop::new(Crossfeed::new(decoder)))));
+        trace!("Decoder appended");
+        Ok(())
+    }
+
     async fn handle_local_file(src: &str, sink: &Arc<Sink>) -> Result<()> {
         let path = PathBuf::from_str(src).unwrap();
         if path.exists() {
             let file = File::open(path)?;
             let decoder = rodio::Decoder::try_from(file).map_err(error_helpers::to_playback_error)?;
-            sink.append(decoder);
+            sink.append(VisualizerTap::new(TimeStretch::new(AbLoop::new(Crossfeed::new(decoder)))));
 
             trace!("Local file {} appended", src);
 
@@ -138,6 +305,51 @@ impl RodioPlayer {
         Err("Failed to read local file".into())
     }
 
+    /// Open a local file and read its container headers without playing it, so
+    /// the following load finds the file in the OS cache.
+    pub fn probe_local_file(src: &str) -> Result<()> {
+        let file = File::open(src)?;
+        rodio::Decoder::try_from(file).map_err(error_helpers::to_playback_error)?;
+        Ok(())
+    }
+
+    /// Ramp `outgoing` down and `incoming` up following the fade curve, then stop
+    /// `outgoing`. The fade only advances while `incoming` is playing and is cut
+    /// short as soon as `generation` moves on (new source, stop).
+    fn spawn_fade(
+        outgoing: Arc<Sink>,
+        incoming: Arc<Sink>,
+        fade: CrossfadeConfig,
+        volume: Arc<Mutex<f32>>,
+        generation: Arc<AtomicUsize>,
+        fading: Arc<Mutex<Option<Arc<Sink>>>>,
+    ) {
+        let fade_id = generation.fetch_add(1, Ordering::SeqCst) + 1;
+        *fading.lock().unwrap() = Some(outgoing.clone());
+        thread::spawn(move || {
+            let total = fade.duration.as_secs_f32();
+            let mut elapsed = 0f32;
+            while elapsed < total && generation.load(Ordering::SeqCst) == fade_id {
+                thread::sleep(FADE_STEP);
+                if incoming.is_paused() {
+                    continue;
+                }
+                elapsed += FADE_STEP.as_secs_f32();
+                let (out_gain, in_gain) = fade.gains(elapsed / total);
+                let volume = *volume.lock().unwrap();
+                outgoing.set_volume(volume * out_gain);
+                incoming.set_volume(volume * in_gain);
+            }
+            outgoing.stop();
+            incoming.set_volume(*volume.lock().unwrap());
+            let mut fading = fading.lock().unwrap();
+            if fading.as_ref().is_some_and(|s| Arc::ptr_eq(s, &outgoing)) {
+                *fading = None;
+            }
+            debug!("Crossfade finished after {:.1}s", elapsed);
+        });
+    }
+
     pub fn get_events_rx(&self) -> Arc<Mutex<Receiver<PlayerEvents>>> {
         self.events_rx.clone()
     }
@@ -146,18 +358,63 @@ impl RodioPlayer {
         events_tx.send(event).unwrap();
     }
 
+    /// Watch the available output devices: when the active one disappears,
+    /// fall back to the default device; when the preferred one comes back,
+    /// return to it.
+    fn spawn_device_monitor(tx: Sender<RodioCommand>, output: Arc<OutputSelection>) {
+        thread::spawn(move || loop {
+            thread::sleep(DEVICE_POLL);
+            let Some(active) = output.active() else { continue };
+            let names = devices::device_names();
+            // Enumeration failed, better keep the current stream
+            if names.is_empty() {
+                continue;
+            }
+            let preferred = output.preferred();
+            let command = if !names.contains(&active) {
+                info!("Output device {} disappeared", active);
+                output.report_lost(active);
+                RodioCommand::SetDevice(preferred)
+            } else if preferred.as_ref().is_some_and(|p| *p != active && names.contains(p)) {
+                info!("Preferred output device {:?} is back", preferred);
+                RodioCommand::SetDevice(preferred)
+            } else {
+                continue;
+            };
+            if tx.send(command).is_err() {
+                break;
+            }
+        });
+    }
+
     fn initialize(
         events_tx: Sender<PlayerEvents>,
         cache_dir: PathBuf,
         playing_flag: Arc<AtomicBool>,
         position_ref: Arc<Mutex<f64>>,
+        output: Arc<OutputSelection>,
     ) -> Sender<RodioCommand> {
         let (tx, rx) = unbounded::<RodioCommand>();
         let ret = tx.clone();
 
         thread::spawn(move || {
-            let stream_handle = rodio::OutputStreamBuilder::open_default_stream().unwrap();
-            let sink = Arc::new(rodio::Sink::connect_new(stream_handle.mixer()));
+            let (mut stream_handle, device) = match devices::open_output_stream(output.preferred().as_deref()) {
+                Ok(opened) => opened,
+                Err(e) => {
+                    crate::trace::record("device", serde_json::json!({ "event": "open_failed", "error": e.to_string() }));
+                    panic!("{}", e);
+                }
+            };
+            crate::trace::record("device", serde_json::json!({ "event": "opened", "device": device }));
+            output.set_active(device);
+            let mut mixer = stream_handle.mixer().clone();
+            let mut current_sink = Arc::new(rodio::Sink::connect_new(&mixer));
+            // Track being faded out, and a counter cancelling running fades
+            let fading: Arc<Mutex<Option<Arc<Sink>>>> = Arc::new(Mutex::new(None));
+            let fade_generation = Arc::new(AtomicUsize::new(0));
+            // Bumped on device switches, whose sink teardown must not count as an end
+            let device_generation = Arc::new(AtomicUsize::new(0));
+            let volume = Arc::new(Mutex::new(1f32));
 
             let runtime = tokio::runtime::Builder::new_multi_thread()
                 .enable_all()
@@ -173,12 +430,21 @@ impl RodioPlayer {
                 let ticker_playing = playing_flag.clone();
                 let ticker_pos = position_ref.clone();
                 thread::spawn(move || {
+                    let mut wraps = ab_loop::wraps();
                     loop {
                         thread::sleep(Duration::from_millis(500));
                         if ticker_playing.load(Ordering::SeqCst) {
-                            // increment position ~0.5s
+                            // increment position ~0.5s of media time at the playback rate
                             let mut pos = ticker_pos.lock().unwrap();
-                            *pos += 0.5;
+                            *pos += 0.5 * stretch::rate() as f64;
+                            // The A-B loop went back to its start since the last tick
+                            let wrapped = ab_loop::wraps();
+                            if wrapped != wraps {
+                                wraps = wrapped;
+                                if let Some(region) = ab_loop::region() {
+                                    *pos = region.start;
+                                }
+                            }
                             // fire event
                             RodioPlayer::send_event(
                                 ticker_events.clone(),
@@ -188,17 +454,37 @@ impl RodioPlayer {
                     }
                 });
                 while let Ok(command) = rx.recv() {
-                    let sink = sink.clone();
+                    let sink = current_sink.clone();
 
                     match command {
-                        RodioCommand::SetSrc(src) => {
+                        RodioCommand::SetSrc(src, fade) => {
                             let last_src = last_src.clone();
                             {
                                 let mut last_src = last_src.lock().unwrap();
                                 *last_src = Some(src.clone());
                             }
 
-                            sink.clear();
+                            let crossfade = fade.filter(|f| f.is_enabled() && !sink.empty() && !sink.is_paused());
+                            let sink = if let Some(fade) = crossfade {
+                                // Keep the current sink playing and load into a fresh one
+                                let incoming = Arc::new(rodio::Sink::connect_new(&mixer));
+                                incoming.pause();
+                                incoming.set_volume(0.0);
+                                current_sink = incoming.clone();
+                                Self::spawn_fade(
+                                    sink,
+                                    incoming.clone(),
+                                    fade,
+                                    volume.clone(),
+                                    fade_generation.clone(),
+                                    fading.clone(),
+                                );
+                                incoming
+                            } else {
+                                fade_generation.fetch_add(1, Ordering::SeqCst);
+                                sink.clear();
+                                sink
+                            };
                             // reset tracking state on new source
                             {
                                 let mut p = position_ref.lock().unwrap();
@@ -208,12 +494,17 @@ impl RodioPlayer {
                             Self::send_event(events_tx.clone(), PlayerEvents::TimeUpdate(0f64));
                             Self::send_event(events_tx.clone(), PlayerEvents::Loading);
 
-                            // TODO
+                            let failure = StreamFailure::default();
                             if let Err(err) =
-                                Self::set_src(cache_dir.clone(), src.clone(), &sink).await
+                                Self::set_src(cache_dir.clone(), src.clone(), &sink, &events_tx, &failure).await
                             {
-                                error!("Failed to set src: {:?}", err);
-                                Self::send_event(events_tx.clone(), PlayerEvents::Error(err))
+                                if let Some(status) = expiry::expired_status(&err) {
+                                    info!("Stream URL expired before playback (HTTP {})", status);
+                                    Self::send_event(events_tx.clone(), PlayerEvents::StreamExpired(status))
+                                } else {
+                                    error!("Failed to set src: {:?}", err);
+                                    Self::send_event(events_tx.clone(), PlayerEvents::Error(err))
+                                }
                             } else {
                                 debug!("Set src");
                                 let src_clone = src.clone();
@@ -222,27 +513,43 @@ impl RodioPlayer {
                                 let sink = sink.clone();
                                 // clone playing flag for move into thread
                                 let ended_playing_flag = playing_flag.clone();
+                                let ended_device_generation = device_generation.clone();
+                                let loaded_on = device_generation.load(Ordering::SeqCst);
+                                let runtime = tokio::runtime::Handle::current();
 
                                 // Send ended event only if track hasn't changed yet
                                 thread::spawn(move || {
                                     sink.sleep_until_end();
-                                    let last_src = last_src.clone();
-                                    let last_src = last_src.lock().unwrap();
-                                    if let Some(last_src) = last_src.clone() {
+                                    let last_src = last_src.lock().unwrap().clone();
+                                    if let Some(last_src) = last_src {
                                         info!("last src={}, current src={}", last_src, src_clone);
-                                        if last_src == src_clone {
+                                        let same_device = ended_device_generation.load(Ordering::SeqCst) == loaded_on;
+                                        if last_src == src_clone && same_device {
                                             // stop ticker when ended
                                             ended_playing_flag.store(false, Ordering::SeqCst);
-                                            Self::send_event(
-                                                events_tx.clone(),
-                                                PlayerEvents::Ended,
-                                            );
+                                            // The stream broke off: an expired URL is not the end of the track
+                                            let expired = failure
+                                                .failed()
+                                                .then(|| failure.status().or_else(|| runtime.block_on(expiry::probe_status(&src_clone))))
+                                                .flatten()
+                                                .filter(|status| expiry::is_expired_status(*status));
+                                            let event = match expired {
+                                                Some(status) => {
+                                                    info!("Stream URL expired during playback (HTTP {})", status);
+                                                    PlayerEvents::StreamExpired(status)
+                                                }
+                                                None => PlayerEvents::Ended,
+                                            };
+                                            Self::send_event(events_tx.clone(), event);
                                         }
                                     }
                                 });
                             }
                         }
                         RodioCommand::Play => {
+                            if let Some(outgoing) = fading.lock().unwrap().as_ref() {
+                                outgoing.play();
+                            }
                             if !sink.empty() {
                                 sink.play();
                                 // start ticker
@@ -251,6 +558,9 @@ impl RodioPlayer {
                             }
                         }
                         RodioCommand::Pause => {
+                            if let Some(outgoing) = fading.lock().unwrap().as_ref() {
+                                outgoing.pause();
+                            }
                             if !sink.empty() {
                                 sink.pause();
                                 playing_flag.store(false, Ordering::SeqCst);
@@ -258,6 +568,7 @@ impl RodioPlayer {
                             }
                         }
                         RodioCommand::Stop => {
+                            fade_generation.fetch_add(1, Ordering::SeqCst);
                             if !sink.empty() {
                                 sink.stop();
                                 sink.clear();
@@ -270,9 +581,47 @@ impl RodioPlayer {
                                 Self::send_event(events_tx.clone(), PlayerEvents::Pause)
                             }
                         }
-                        RodioCommand::SetVolume(volume) => {
-                            if !sink.empty() {
-                                sink.set_volume(volume as f32);
+                        RodioCommand::SetVolume(new_volume) => {
+                            *volume.lock().unwrap() = new_volume as f32;
+                            // While crossfading the fade thread applies it on its next step
+                            if !sink.empty() && fading.lock().unwrap().is_none() {
+                                sink.set_volume(new_volume as f32);
+                            }
+                        }
+                        RodioCommand::SetDevice(name) => {
+                            let (handle, device) = match devices::open_output_stream(name.as_deref()) {
+                                Ok(opened) => opened,
+                                Err(e) => {
+                                    crate::trace::record("device", serde_json::json!({ "event": "open_failed", "error": e.to_string() }));
+                                    // Not a playback error: keep playing where we are
+                                    error!("Failed to switch output device: {:?}", e);
+                                    continue;
+                                }
+                            };
+                            crate::trace::record("device", serde_json::json!({ "event": "opened", "device": device }));
+
+                            // Stopping the old sink must not report Ended
+                            device_generation.fetch_add(1, Ordering::SeqCst);
+                            let src = last_src.lock().unwrap().clone();
+                            let resume_at = *position_ref.lock().unwrap();
+                            let was_playing = playing_flag.load(Ordering::SeqCst);
+                            fade_generation.fetch_add(1, Ordering::SeqCst);
+                            if let Some(outgoing) = fading.lock().unwrap().take() {
+                                outgoing.stop();
+                            }
+                            sink.stop();
+
+                            // Sinks are bound to the mixer of their stream: rebuild and reload
+                            stream_handle = handle;
+                            mixer = stream_handle.mixer().clone();
+                            current_sink = Arc::new(rodio::Sink::connect_new(&mixer));
+                            current_sink.set_volume(*volume.lock().unwrap());
+                            output.set_active(device);
+
+                            if let Some(src) = src {
+                                let _ = tx.send(RodioCommand::SetSrc(src, None));
+                                let _ = tx.send(RodioCommand::Seek(resume_at as u64));
+                                let _ = tx.send(if was_playing { RodioCommand::Play } else { RodioCommand::Pause });
                             }
                         }
                         RodioCommand::Seek(pos) => {
@@ -294,7 +643,7 @@ impl RodioPlayer {
                                 let last_src = last_src.clone();
                                 let last_src = last_src.lock().unwrap();
                                 if let Some(last_src) = last_src.clone() {
-                                    tx.send(RodioCommand::SetSrc(last_src.clone())).unwrap();
+                                    tx.send(RodioCommand::SetSrc(last_src.clone(), None)).unwrap();
                                     tx.send(RodioCommand::Seek(pos)).unwrap();
                                     tx.send(RodioCommand::Play).unwrap();
                                 }
@@ -320,11 +669,17 @@ impl BasePlayer for RodioPlayer {
 
     #[tracing::instrument(level = "debug", skip(self, src, resolver))]
     fn load(&self, src: String, _autoplay: bool, resolver: tokio::sync::oneshot::Sender<()>) {
-        let _ = self.tx.send(RodioCommand::SetSrc(src.clone()));
+        let _ = self.tx.send(RodioCommand::SetSrc(src.clone(), None));
         // Resolve immediately to avoid blocking caller
         let _ = resolver.send(());
     }
 
+    #[tracing::instrument(level = "debug", skip(self, src, resolver))]
+    fn load_crossfade(&self, src: String, _autoplay: bool, resolver: tokio::sync::oneshot::Sender<()>, fade: CrossfadeConfig) {
+        let _ = self.tx.send(RodioCommand::SetSrc(src, Some(fade)));
+        let _ = resolver.send(());
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     fn stop(&mut self) -> types::errors::Result<()> {
         self.tx.send(RodioCommand::Stop).unwrap();
@@ -366,6 +721,15 @@ impl BasePlayer for RodioPlayer {
     #[tracing::instrument(level = "debug", skip(self))]
     fn get_volume(&self) -> types::errors::Result<f64> { Ok(0f64) }
 
+    #[tracing::instrument(level = "debug", skip(self))]
+    fn loops_regions(&self) -> bool { true }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    fn set_output_device(&self, device: Option<String>) -> types::errors::Result<()> {
+        self.tx.send(RodioCommand::SetDevice(device)).unwrap();
+        Ok(())
+    }
+
     #[tracing::instrument(level = "debug", skip(self, _state_setter))]
     fn add_listeners(&mut self, _state_setter: PlayerEventsSender) {
         // comments: start forwarding only once
@@ -0,0 +1,171 @@
+// crates/audio-player/src/queue_metrics.rs
+// Play time left in the queue, computed the way the player will actually play
+// it: entry trims, skip regions of the playing track, crossfades overlapping
+// consecutive entries and silent gaps inserted between them.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use types::tracks::MediaContent;
+use types::ui::player_details::QueueItemOverrides;
+
+/// Transition settings the metrics account for
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct QueueTiming {
+    /// Crossfade between entries of the same track type, zero when disabled
+    pub crossfade: Duration,
+    /// Silence inserted after an entry that ended on its own
+    pub gap: Duration,
+}
+
+/// Remaining play time of the queue
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueueMetrics {
+    /// Seconds until the queue ends: the rest of the playing entry and every
+    /// upcoming one, in play order
+    pub remaining_secs: f64,
+    /// Seconds until each upcoming entry starts, by queue instance ID
+    pub starts_in: HashMap<String, f64>,
+    /// Wall-clock end of the queue in milliseconds since the Unix epoch,
+    /// assuming playback runs from now on. `None` when playback does not stop
+    /// at the end (repeat and shuffle modes).
+    pub ends_at: Option<i64>,
+    /// Whether playback continues past the last upcoming entry
+    pub repeats: bool,
+    /// Upcoming entries of unknown duration, counted as zero
+    pub unknown_durations: usize,
+}
+
+/// Seconds an entry plays: its duration, shortened by its start offset and
+/// end position. `None` when the duration is unknown and no end is set.
+pub fn entry_length(track: &MediaContent, overrides: Option<&QueueItemOverrides>) -> Option<f64> {
+    let start = overrides.and_then(|o| o.start_offset).unwrap_or(0.0);
+    let end = overrides.and_then(|o| o.end_at).or(track.track.duration)?;
+    Some((end - start).max(0.0))
+}
+
+/// Entry playing now and what remains of it
+pub struct CurrentEntry<'a> {
+    pub track: &'a MediaContent,
+    /// Seconds left, `None` when unknown
+    pub remaining: Option<f64>,
+}
+
+/// Metrics of a queue playing `current` followed by `upcoming` (instance ID,
+/// track, overrides), as of `now_ms`
+pub fn compute<'a>(
+    current: Option<CurrentEntry<'a>>,
+    upcoming: impl IntoIterator<Item = (&'a str, &'a MediaContent, Option<&'a QueueItemOverrides>)>,
+    timing: &QueueTiming,
+    repeats: bool,
+    now_ms: i64,
+) -> QueueMetrics {
+    let crossfade = timing.crossfade.as_secs_f64();
+    let gap = timing.gap.as_secs_f64();
+
+    let mut metrics = QueueMetrics {
+        repeats,
+        ..Default::default()
+    };
+    let mut elapsed = 0.0;
+    // Previous entry and how long it plays, to place the transition
+    let mut previous: Option<(&MediaContent, f64)> = None;
+    if let Some(current) = current {
+        if current.remaining.is_none() {
+            metrics.unknown_durations += 1;
+        }
+        let remaining = current.remaining.unwrap_or(0.0).max(0.0);
+        elapsed += remaining;
+        previous = Some((current.track, remaining));
+    }
+
+    for (instance_id, track, overrides) in upcoming {
+        if let Some((prev, prev_length)) = previous {
+            // The crossfade starts `crossfade` seconds before the end, if the
+            // entry is long enough; otherwise the entry ends and the gap follows
+            let crossfades = crossfade > 0.0 && prev.track.type_ == track.track.type_ && prev_length > crossfade;
+            if crossfades {
+                elapsed -= crossfade;
+            } else {
+                elapsed += gap;
+            }
+        }
+        metrics.starts_in.insert(instance_id.to_string(), elapsed);
+
+        let length = entry_length(track, overrides);
+        if length.is_none() {
+            metrics.unknown_durations += 1;
+        }
+        let length = length.unwrap_or(0.0);
+        elapsed += length;
+        previous = Some((track, length));
+    }
+
+    metrics.remaining_secs = elapsed.max(0.0);
+    if !repeats {
+        metrics.ends_at = Some(now_ms + (metrics.remaining_secs * 1000.0).round() as i64);
+    }
+    metrics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use types::tracks::Tracks;
+
+    fn track(duration: Option<f64>) -> MediaContent {
+        MediaContent {
+            track: Tracks {
+                duration,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sums_remaining_time_with_trims_and_gaps() {
+        let (current, a, b) = (track(Some(200.0)), track(Some(180.0)), track(Some(240.0)));
+        let trim = QueueItemOverrides {
+            start_offset: Some(30.0),
+            end_at: Some(200.0),
+        };
+        let timing = QueueTiming {
+            crossfade: Duration::ZERO,
+            gap: Duration::from_secs(2),
+        };
+        let metrics = compute(
+            Some(CurrentEntry { track: &current, remaining: Some(50.0) }),
+            [("a#0", &a, None), ("b#0", &b, Some(&trim))],
+            &timing,
+            false,
+            1_000,
+        );
+        assert_eq!(metrics.starts_in["a#0"], 52.0);
+        assert_eq!(metrics.starts_in["b#0"], 52.0 + 180.0 + 2.0);
+        assert_eq!(metrics.remaining_secs, 52.0 + 180.0 + 2.0 + 170.0);
+        assert_eq!(metrics.ends_at, Some(1_000 + 404_000));
+    }
+
+    #[test]
+    fn crossfades_overlap_and_unknown_durations_are_counted() {
+        let (current, a, unknown) = (track(Some(200.0)), track(Some(180.0)), track(None));
+        let timing = QueueTiming {
+            crossfade: Duration::from_secs(5),
+            gap: Duration::ZERO,
+        };
+        let metrics = compute(
+            Some(CurrentEntry { track: &current, remaining: Some(60.0) }),
+            [("a#0", &a, None), ("u#0", &unknown, None)],
+            &timing,
+            true,
+            0,
+        );
+        assert_eq!(metrics.starts_in["a#0"], 55.0);
+        assert_eq!(metrics.starts_in["u#0"], 55.0 + 175.0);
+        assert_eq!(metrics.unknown_durations, 1);
+        assert_eq!(metrics.ends_at, None);
+    }
+}
@@ -0,0 +1,464 @@
+// crates/audio-player/src/segmented/dash.rs
+// DASH manifests. The audio representations of the first period become
+// renditions, their segments listed by a SegmentTemplate (numbered or with a
+// timeline), a SegmentList, or a SegmentBase whose `sidx` index is fetched.
+// Only static manifests are played.
+
+use roxmltree::Node;
+use types::errors::{error_helpers, Result};
+
+use super::{fetch, resolve, ByteRange, Manifest, Presentation, Rendition, Resource, Segment};
+
+/// Representation whose segments are listed in an index still to fetch
+struct Indexed {
+    rendition: Rendition,
+    index: Resource,
+    /// Length of the presentation, for an index that doesn't cover it
+    duration: Option<f64>,
+}
+
+pub(super) async fn load(client: &reqwest::Client, url: &str, text: &str) -> Result<Manifest> {
+    let (mut renditions, indexed) = parse(url, text)?;
+    for mut pending in indexed {
+        let index = match fetch(client, &pending.index).await {
+            Ok(index) => index,
+            Err(e) => {
+                tracing::warn!("Skipping DASH representation {}: {:?}", pending.index.url, e);
+                continue;
+            }
+        };
+        let offset = pending.index.range.map_or(0, |range| range.offset);
+        let mut start = 0.0;
+        for (range, duration) in parse_sidx(&index, offset)? {
+            pending.rendition.segments.push(Segment {
+                resource: Resource {
+                    url: pending.index.url.clone(),
+                    range: Some(range),
+                },
+                start,
+                duration,
+            });
+            start += duration;
+        }
+        if let (Some(last), Some(total)) = (pending.rendition.segments.last_mut(), pending.duration) {
+            last.duration = last.duration.max(total - last.start);
+        }
+        renditions.push(pending.rendition);
+    }
+    Presentation::new(renditions).map(Manifest::Static)
+}
+
+fn is_element(node: &Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| is_element(n, name))
+}
+
+fn children<'a, 'input: 'a>(node: Node<'a, 'input>, name: &'a str) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children().filter(move |n| is_element(n, name))
+}
+
+/// `base` with the BaseURL of `node` applied
+fn base_url(base: &str, node: Node) -> Result<String> {
+    match child(node, "BaseURL").and_then(|n| n.text()) {
+        Some(reference) => resolve(base, reference.trim()),
+        None => Ok(base.to_string()),
+    }
+}
+
+fn is_audio(node: Node) -> bool {
+    node.attribute("contentType") == Some("audio")
+        || node.attribute("mimeType").is_some_and(|mime| mime.starts_with("audio/"))
+}
+
+fn parse(url: &str, text: &str) -> Result<(Vec<Rendition>, Vec<Indexed>)> {
+    let document = roxmltree::Document::parse(text).map_err(error_helpers::to_parse_error)?;
+    let mpd = document.root_element();
+    if !is_element(&mpd, "MPD") {
+        return Err(format!("Not a DASH manifest: {}", url).into());
+    }
+    if mpd.attribute("type") == Some("dynamic") {
+        return Err("Live DASH streams are not supported".into());
+    }
+    let period = child(mpd, "Period").ok_or("DASH manifest without a period")?;
+    let duration = period
+        .attribute("duration")
+        .or_else(|| mpd.attribute("mediaPresentationDuration"))
+        .and_then(parse_duration);
+    let period_base = base_url(&base_url(url, mpd)?, period)?;
+
+    let mut renditions = Vec::new();
+    let mut indexed = Vec::new();
+    for set in children(period, "AdaptationSet") {
+        let set_base = base_url(&period_base, set)?;
+        for representation in children(set, "Representation") {
+            if !is_audio(set) && !is_audio(representation) {
+                continue;
+            }
+            let base = base_url(&set_base, representation)?;
+            let levels = [period, set, representation];
+            let info = RepresentationInfo {
+                id: representation.attribute("id").unwrap_or_default(),
+                bandwidth: representation
+                    .attribute("bandwidth")
+                    .and_then(|b| b.parse().ok())
+                    .unwrap_or(0),
+                base: &base,
+                duration,
+            };
+            let mut rendition = Rendition {
+                bandwidth: info.bandwidth,
+                codecs: representation
+                    .attribute("codecs")
+                    .or_else(|| set.attribute("codecs"))
+                    .map(str::to_string),
+                init: None,
+                segments: Vec::new(),
+            };
+
+            let template: Vec<Node> = levels.iter().filter_map(|level| child(*level, "SegmentTemplate")).collect();
+            let list = levels.iter().rev().find_map(|level| child(*level, "SegmentList"));
+            let segment_base = levels.iter().rev().find_map(|level| child(*level, "SegmentBase"));
+            if !template.is_empty() {
+                (rendition.init, rendition.segments) = info.template_segments(&template)?;
+            } else if let Some(list) = list {
+                (rendition.init, rendition.segments) = info.list_segments(list)?;
+            } else if let Some(index_range) = segment_base.and_then(|b| b.attribute("indexRange")) {
+                let init_range = segment_base
+                    .and_then(|b| child(b, "Initialization"))
+                    .and_then(|i| i.attribute("range"));
+                rendition.init = init_range
+                    .map(|range| parse_range(range).map(|range| Resource { url: base.clone(), range: Some(range) }))
+                    .transpose()?;
+                indexed.push(Indexed {
+                    rendition,
+                    index: Resource { url: base.clone(), range: Some(parse_range(index_range)?) },
+                    duration,
+                });
+                continue;
+            } else {
+                // A plain file
+                rendition.segments.push(Segment {
+                    resource: Resource { url: base.clone(), range: None },
+                    start: 0.0,
+                    duration: duration.unwrap_or(0.0),
+                });
+            }
+            renditions.push(rendition);
+        }
+    }
+    Ok((renditions, indexed))
+}
+
+struct RepresentationInfo<'a> {
+    id: &'a str,
+    bandwidth: u64,
+    base: &'a str,
+    duration: Option<f64>,
+}
+
+impl RepresentationInfo<'_> {
+    /// Segments of the SegmentTemplate elements from the period down to the
+    /// representation, the nearest inheriting what it lacks from the others
+    fn template_segments(&self, templates: &[Node]) -> Result<(Option<Resource>, Vec<Segment>)> {
+        let attribute = |name: &str| templates.iter().rev().find_map(|t| t.attribute(name));
+        let number = |name: &str, default: u64| attribute(name).and_then(|v| v.parse().ok()).unwrap_or(default);
+        let timescale = number("timescale", 1).max(1);
+        let start_number = number("startNumber", 1);
+        let time_offset = number("presentationTimeOffset", 0);
+        let media = attribute("media").ok_or("SegmentTemplate without media")?;
+        let init = attribute("initialization")
+            .map(|template| self.resource(template, 0, 0))
+            .transpose()?;
+
+        // (time, duration) of each segment, in the timescale
+        let mut times = Vec::new();
+        let timeline = templates.iter().rev().find_map(|t| child(*t, "SegmentTimeline"));
+        if let Some(timeline) = timeline {
+            let entries: Vec<Node> = children(timeline, "S").collect();
+            let mut time = 0u64;
+            for (i, entry) in entries.iter().enumerate() {
+                if let Some(t) = entry.attribute("t").and_then(|t| t.parse().ok()) {
+                    time = t;
+                }
+                let duration: u64 = entry
+                    .attribute("d")
+                    .and_then(|d| d.parse().ok())
+                    .filter(|d| *d > 0)
+                    .ok_or("SegmentTimeline entry without duration")?;
+                let repeat: i64 = entry.attribute("r").and_then(|r| r.parse().ok()).unwrap_or(0);
+                let repeat = if repeat >= 0 {
+                    repeat as u64
+                } else {
+                    // Up to the next entry or the end of the period
+                    let end = entries
+                        .get(i + 1)
+                        .and_then(|next| next.attribute("t"))
+                        .and_then(|t| t.parse::<u64>().ok())
+                        .or_else(|| self.duration.map(|d| (d * timescale as f64) as u64 + time_offset));
+                    end.map_or(0, |end| end.saturating_sub(time).div_ceil(duration).saturating_sub(1))
+                };
+                for _ in 0..=repeat {
+                    times.push((time, duration));
+                    time += duration;
+                }
+            }
+        } else {
+            let duration = number("duration", 0);
+            if duration == 0 {
+                return Err("SegmentTemplate without duration or timeline".into());
+            }
+            let total = self.duration.ok_or("SegmentTemplate of a manifest without duration")?;
+            let count = (total * timescale as f64 / duration as f64).ceil() as u64;
+            times.extend((0..count).map(|i| (time_offset + i * duration, duration)));
+        }
+
+        let segments = times
+            .into_iter()
+            .enumerate()
+            .map(|(i, (time, duration))| {
+                Ok(Segment {
+                    resource: self.resource(media, start_number + i as u64, time)?,
+                    start: time.saturating_sub(time_offset) as f64 / timescale as f64,
+                    duration: duration as f64 / timescale as f64,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok((init, segments))
+    }
+
+    fn list_segments(&self, list: Node) -> Result<(Option<Resource>, Vec<Segment>)> {
+        let timescale: u64 = list.attribute("timescale").and_then(|t| t.parse().ok()).unwrap_or(1).max(1);
+        let duration = list
+            .attribute("duration")
+            .and_then(|d| d.parse::<u64>().ok())
+            .map(|d| d as f64 / timescale as f64);
+        let init = child(list, "Initialization")
+            .map(|init| self.list_resource(init.attribute("sourceURL"), init.attribute("range")))
+            .transpose()?;
+        let urls: Vec<Node> = children(list, "SegmentURL").collect();
+        let length = match (duration, urls.len()) {
+            (Some(duration), _) => duration,
+            // A single segment lasts as long as the period
+            (None, 1) => self.duration.unwrap_or(0.0),
+            (None, _) => return Err("SegmentList without duration".into()),
+        };
+        let segments = urls
+            .iter()
+            .enumerate()
+            .map(|(i, url)| {
+                Ok(Segment {
+                    resource: self.list_resource(url.attribute("media"), url.attribute("mediaRange"))?,
+                    start: i as f64 * length,
+                    duration: length,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok((init, segments))
+    }
+
+    fn resource(&self, template: &str, number: u64, time: u64) -> Result<Resource> {
+        let url = expand(template, self.id, self.bandwidth, number, time);
+        Ok(Resource { url: resolve(self.base, &url)?, range: None })
+    }
+
+    fn list_resource(&self, url: Option<&str>, range: Option<&str>) -> Result<Resource> {
+        Ok(Resource {
+            url: match url {
+                Some(url) => resolve(self.base, url)?,
+                None => self.base.to_string(),
+            },
+            range: range.map(parse_range).transpose()?,
+        })
+    }
+}
+
+/// Fill the `$Identifier$` and `$Identifier%0Nd$` placeholders of a template
+fn expand(template: &str, id: &str, bandwidth: u64, number: u64, time: u64) -> String {
+    let mut url = String::with_capacity(template.len());
+    for (i, part) in template.split('$').enumerate() {
+        // Odd parts sit between two '$'
+        if i % 2 == 0 {
+            url.push_str(part);
+            continue;
+        }
+        let (name, format) = part.split_once('%').unwrap_or((part, ""));
+        let width: usize = format
+            .trim_start_matches('0')
+            .trim_end_matches('d')
+            .parse()
+            .unwrap_or(0);
+        let value = match name {
+            "" => "$".to_string(),
+            "RepresentationID" => id.to_string(),
+            "Number" => number.to_string(),
+            "Time" => time.to_string(),
+            "Bandwidth" => bandwidth.to_string(),
+            _ => format!("${}$", part),
+        };
+        url.push_str(&format!("{:0>width$}", value, width = width));
+    }
+    url
+}
+
+/// `first-last` byte range, both included
+fn parse_range(value: &str) -> Result<ByteRange> {
+    let invalid = || format!("Invalid byte range {:?}", value);
+    let (first, last) = value.split_once('-').ok_or_else(invalid)?;
+    let first: u64 = first.trim().parse().map_err(|_| invalid())?;
+    let last: u64 = last.trim().parse().map_err(|_| invalid())?;
+    if last < first {
+        return Err(invalid().into());
+    }
+    Ok(ByteRange { offset: first, length: last - first + 1 })
+}
+
+/// Seconds of an ISO 8601 duration such as `PT3M25.5S`
+fn parse_duration(value: &str) -> Option<f64> {
+    let mut seconds = 0.0;
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in value.trim().strip_prefix('P')?.chars() {
+        match c {
+            'T' => in_time = true,
+            '0'..='9' | '.' => number.push(c),
+            unit => {
+                let amount: f64 = number.parse().ok()?;
+                number.clear();
+                seconds += amount
+                    * match (unit, in_time) {
+                        ('D', false) => 86_400.0,
+                        ('W', false) => 7.0 * 86_400.0,
+                        ('H', true) => 3_600.0,
+                        ('M', true) => 60.0,
+                        ('S', true) => 1.0,
+                        _ => return None,
+                    };
+            }
+        }
+    }
+    number.is_empty().then_some(seconds)
+}
+
+/// Byte ranges and lengths in seconds of the segments listed by a `sidx`
+/// box read from `offset` of the file
+fn parse_sidx(data: &[u8], offset: u64) -> Result<Vec<(ByteRange, f64)>> {
+    let invalid = || types::errors::MusicError::from("Invalid sidx box in DASH index");
+    let u32_at = |at: usize| -> Result<u32> {
+        data.get(at..at + 4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(invalid)
+    };
+    let u64_at = |at: usize| -> Result<u64> { Ok((u64::from(u32_at(at)?) << 32) | u64::from(u32_at(at + 4)?)) };
+
+    if data.get(4..8) != Some(b"sidx".as_slice()) {
+        return Err(invalid());
+    }
+    let box_len = u64::from(u32_at(0)?);
+    let version = *data.get(8).ok_or_else(invalid)?;
+    let timescale = u64::from(u32_at(16)?).max(1);
+    // Earliest presentation time, then the offset of the first segment from
+    // the end of the box
+    let (first_offset, mut at) = if version == 0 {
+        (u64::from(u32_at(24)?), 28)
+    } else {
+        (u64_at(28)?, 36)
+    };
+    let count = u16::from_be_bytes([*data.get(at + 2).ok_or_else(invalid)?, *data.get(at + 3).ok_or_else(invalid)?]);
+    at += 4;
+
+    let mut position = offset + box_len + first_offset;
+    let mut segments = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let reference = u32_at(at)?;
+        if reference & 0x8000_0000 != 0 {
+            return Err("Nested DASH indexes are not supported".into());
+        }
+        let length = u64::from(reference & 0x7FFF_FFFF);
+        let duration = u32_at(at + 4)? as f64 / timescale as f64;
+        segments.push((ByteRange { offset: position, length }, duration));
+        position += length;
+        at += 12;
+    }
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "https://cdn.example.com/dash/manifest.mpd";
+
+    #[test]
+    fn template_timelines_expand_to_segments() {
+        let text = r#"<?xml version="1.0"?>
+<MPD xmlns="urn:mpeg:dash:schema:mpd:2011" type="static" mediaPresentationDuration="PT9.5S">
+  <Period>
+    <AdaptationSet contentType="video"><Representation id="v" bandwidth="900000"/></AdaptationSet>
+    <AdaptationSet mimeType="audio/mp4" codecs="mp4a.40.2">
+      <SegmentTemplate timescale="1000" initialization="$RepresentationID$/init.mp4" media="$RepresentationID$/$Number%03d$-$Time$.m4s" startNumber="5">
+        <SegmentTimeline><S t="0" d="4000" r="-1"/></SegmentTimeline>
+      </SegmentTemplate>
+      <Representation id="a128" bandwidth="128000"/>
+      <Representation id="a64" bandwidth="64000"><BaseURL>low/</BaseURL></Representation>
+    </AdaptationSet>
+  </Period>
+</MPD>"#;
+        let (renditions, indexed) = parse(BASE, text).unwrap();
+        assert!(indexed.is_empty());
+        assert_eq!(renditions.len(), 2);
+        let high = &renditions[0];
+        assert_eq!(high.bandwidth, 128_000);
+        assert_eq!(high.codecs.as_deref(), Some("mp4a.40.2"));
+        assert_eq!(high.init.as_ref().unwrap().url, "https://cdn.example.com/dash/a128/init.mp4");
+        let urls: Vec<&str> = high.segments.iter().map(|s| s.resource.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://cdn.example.com/dash/a128/005-0.m4s",
+                "https://cdn.example.com/dash/a128/006-4000.m4s",
+                "https://cdn.example.com/dash/a128/007-8000.m4s",
+            ]
+        );
+        assert_eq!(high.segments[2].start, 8.0);
+        assert_eq!(renditions[1].segments[0].resource.url, "https://cdn.example.com/dash/low/a64/005-0.m4s");
+    }
+
+    #[test]
+    fn segment_base_indexes_are_read_from_sidx() {
+        let text = r#"<MPD mediaPresentationDuration="PT1M"><Period><AdaptationSet contentType="audio">
+  <Representation id="1" bandwidth="96000"><BaseURL>track.m4a</BaseURL>
+    <SegmentBase indexRange="700-755"><Initialization range="0-699"/></SegmentBase>
+  </Representation>
+</AdaptationSet></Period></MPD>"#;
+        let (renditions, indexed) = parse(BASE, text).unwrap();
+        assert!(renditions.is_empty());
+        assert_eq!(indexed[0].index.range, Some(ByteRange { offset: 700, length: 56 }));
+        assert_eq!(indexed[0].rendition.init.as_ref().unwrap().range, Some(ByteRange { offset: 0, length: 700 }));
+
+        // Version 0 sidx of two segments at a 1000 Hz timescale
+        let mut sidx = vec![0, 0, 0, 56];
+        sidx.extend_from_slice(b"sidx");
+        sidx.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0x03, 0xE8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2]);
+        sidx.extend_from_slice(&[0, 0, 0x10, 0, 0, 0, 0x27, 0x10, 0x90, 0, 0, 0]);
+        sidx.extend_from_slice(&[0, 0, 0x08, 0, 0, 0, 0x13, 0x88, 0x90, 0, 0, 0]);
+        assert_eq!(
+            parse_sidx(&sidx, 700).unwrap(),
+            vec![
+                (ByteRange { offset: 756, length: 4096 }, 10.0),
+                (ByteRange { offset: 4852, length: 2048 }, 5.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_durations_and_templates() {
+        assert_eq!(parse_duration("PT3M25.5S"), Some(205.5));
+        assert_eq!(parse_duration("P1DT1H"), Some(90_000.0));
+        assert_eq!(parse_duration("3M"), None);
+        assert_eq!(expand("$$x$Bandwidth$/$Number%05d$", "id", 64, 42, 0), "$x64/00042");
+        assert!(parse(BASE, r#"<MPD type="dynamic"><Period/></MPD>"#).is_err());
+    }
+}
@@ -0,0 +1,357 @@
+// crates/audio-player/src/segmented/hls.rs
+// HLS playlists. A master playlist lists variants of the stream, from which
+// the audio-only ones are kept when there are any, then the audio renditions
+// of their groups, and the muxed variants last (their transport streams are
+// demuxed). Each becomes a rendition once its media playlist is fetched.
+
+use std::collections::HashMap;
+
+use types::errors::Result;
+
+use super::{fetch_text, resolve, ByteRange, Manifest, Presentation, Rendition, Resource, Segment};
+
+/// Codec prefixes of video streams, as given in the CODECS attribute
+const VIDEO_CODECS: [&str; 8] = ["avc1", "avc3", "hvc1", "hev1", "vp09", "av01", "dvh1", "mp4v"];
+
+#[derive(Debug)]
+enum Playlist {
+    Master(MasterPlaylist),
+    Media(MediaPlaylist),
+}
+
+#[derive(Debug, Default)]
+struct MasterPlaylist {
+    variants: Vec<Variant>,
+    /// Audio renditions (EXT-X-MEDIA) with their own playlist
+    audio: Vec<AudioMedia>,
+}
+
+#[derive(Debug)]
+struct Variant {
+    url: String,
+    bandwidth: u64,
+    codecs: Option<String>,
+    audio_group: Option<String>,
+}
+
+#[derive(Debug)]
+struct AudioMedia {
+    url: String,
+    group: String,
+    default: bool,
+}
+
+#[derive(Debug, Default)]
+struct MediaPlaylist {
+    init: Option<Resource>,
+    segments: Vec<Segment>,
+    /// No end tag: the playlist keeps growing
+    live: bool,
+}
+
+pub(super) async fn load(client: &reqwest::Client, url: &str, text: &str) -> Result<Manifest> {
+    let master = match parse(url, text)? {
+        Playlist::Media(media) if media.live => return Ok(Manifest::Live),
+        Playlist::Media(media) => {
+            let rendition = Rendition {
+                bandwidth: 0,
+                codecs: None,
+                init: media.init,
+                segments: media.segments,
+            };
+            return Presentation::new(vec![rendition]).map(Manifest::Static);
+        }
+        Playlist::Master(master) => master,
+    };
+
+    let mut renditions = Vec::new();
+    for (url, bandwidth, codecs) in master.streams() {
+        let media = match fetch_text(client, &url).await.and_then(|text| parse(&url, &text)) {
+            Ok(Playlist::Media(media)) => media,
+            Ok(Playlist::Master(_)) => return Err("Nested HLS master playlists are not supported".into()),
+            Err(e) => {
+                tracing::warn!("Skipping HLS variant {}: {:?}", url, e);
+                continue;
+            }
+        };
+        if media.live {
+            return Ok(Manifest::Live);
+        }
+        renditions.push(Rendition {
+            bandwidth,
+            codecs,
+            init: media.init,
+            segments: media.segments,
+        });
+    }
+    Presentation::new(renditions).map(Manifest::Static)
+}
+
+impl MasterPlaylist {
+    /// Playlists to play, with their bandwidth and codecs
+    fn streams(&self) -> Vec<(String, u64, Option<String>)> {
+        let audio_only: Vec<&Variant> = self
+            .variants
+            .iter()
+            .filter(|v| v.codecs.as_deref().is_some_and(|codecs| !has_video(codecs)))
+            .collect();
+        let mut streams: Vec<(String, u64, Option<String>)> = if !audio_only.is_empty() {
+            audio_only
+                .into_iter()
+                .map(|v| (v.url.clone(), v.bandwidth, v.codecs.clone()))
+                .collect()
+        } else if !self.audio.is_empty() {
+            // One rendition per group, ranked by the lightest variant using it
+            let mut groups: Vec<&str> = self.audio.iter().map(|m| m.group.as_str()).collect();
+            groups.dedup();
+            groups
+                .into_iter()
+                .filter_map(|group| {
+                    let media = self
+                        .audio
+                        .iter()
+                        .filter(|m| m.group == group)
+                        .max_by_key(|m| m.default)?;
+                    let bandwidth = self
+                        .variants
+                        .iter()
+                        .filter(|v| v.audio_group.as_deref() == Some(group))
+                        .map(|v| v.bandwidth)
+                        .min()
+                        .unwrap_or(0);
+                    Some((media.url.clone(), bandwidth, None))
+                })
+                .collect()
+        } else {
+            self.variants
+                .iter()
+                .map(|v| (v.url.clone(), v.bandwidth, v.codecs.clone()))
+                .collect()
+        };
+        let mut seen = Vec::new();
+        streams.retain(|(url, _, _)| {
+            let new = !seen.contains(url);
+            seen.push(url.clone());
+            new
+        });
+        streams
+    }
+}
+
+fn has_video(codecs: &str) -> bool {
+    codecs
+        .split(',')
+        .any(|codec| VIDEO_CODECS.iter().any(|video| codec.trim().starts_with(video)))
+}
+
+fn parse(url: &str, text: &str) -> Result<Playlist> {
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+    if lines.next() != Some("#EXTM3U") {
+        return Err(format!("Not an HLS playlist: {}", url).into());
+    }
+    if text.contains("#EXT-X-STREAM-INF") {
+        parse_master(url, lines).map(Playlist::Master)
+    } else {
+        parse_media(url, lines).map(Playlist::Media)
+    }
+}
+
+fn parse_master<'a>(url: &str, mut lines: impl Iterator<Item = &'a str>) -> Result<MasterPlaylist> {
+    let mut master = MasterPlaylist::default();
+    while let Some(line) = lines.next() {
+        if let Some(list) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+            let attributes = attributes(list);
+            // The playlist of a variant is on the next line
+            let Some(uri) = lines.by_ref().find(|line| !line.starts_with('#')) else { break };
+            master.variants.push(Variant {
+                url: resolve(url, uri)?,
+                bandwidth: attributes
+                    .get("AVERAGE-BANDWIDTH")
+                    .or_else(|| attributes.get("BANDWIDTH"))
+                    .and_then(|b| b.parse().ok())
+                    .unwrap_or(0),
+                codecs: attributes.get("CODECS").cloned(),
+                audio_group: attributes.get("AUDIO").cloned(),
+            });
+        } else if let Some(list) = line.strip_prefix("#EXT-X-MEDIA:") {
+            let attributes = attributes(list);
+            let (Some("AUDIO"), Some(uri), Some(group)) = (
+                attributes.get("TYPE").map(String::as_str),
+                attributes.get("URI"),
+                attributes.get("GROUP-ID"),
+            ) else {
+                continue;
+            };
+            master.audio.push(AudioMedia {
+                url: resolve(url, uri)?,
+                group: group.clone(),
+                default: attributes.get("DEFAULT").is_some_and(|d| d == "YES"),
+            });
+        }
+    }
+    Ok(master)
+}
+
+fn parse_media<'a>(url: &str, lines: impl Iterator<Item = &'a str>) -> Result<MediaPlaylist> {
+    let mut playlist = MediaPlaylist::default();
+    let mut ended = false;
+    let mut start = 0.0;
+    let mut duration = None;
+    let mut range = None;
+    // Where the previous byte range ended, for ranges without an offset
+    let mut range_end = 0;
+    for line in lines {
+        if let Some(value) = line.strip_prefix("#EXTINF:") {
+            let value = value.split(',').next().unwrap_or_default();
+            duration = Some(value.trim().parse::<f64>().map_err(|_| format!("Invalid segment duration {:?}", value))?);
+        } else if let Some(value) = line.strip_prefix("#EXT-X-BYTERANGE:") {
+            range = Some(byte_range(value, range_end)?);
+        } else if let Some(list) = line.strip_prefix("#EXT-X-MAP:") {
+            let attributes = attributes(list);
+            let uri = attributes.get("URI").ok_or("EXT-X-MAP without URI")?;
+            playlist.init = Some(Resource {
+                url: resolve(url, uri)?,
+                range: attributes.get("BYTERANGE").map(|r| byte_range(r, 0)).transpose()?,
+            });
+        } else if let Some(list) = line.strip_prefix("#EXT-X-KEY:") {
+            let method = attributes(list).get("METHOD").cloned().unwrap_or_default();
+            if method != "NONE" {
+                return Err(format!("Encrypted HLS streams ({}) are not supported", method).into());
+            }
+        } else if line == "#EXT-X-ENDLIST" || line == "#EXT-X-PLAYLIST-TYPE:VOD" {
+            ended = true;
+        } else if !line.starts_with('#') {
+            let duration = duration.take().ok_or_else(|| format!("Segment {} has no duration", line))?;
+            if let Some(range) = range {
+                range_end = range.offset + range.length;
+            }
+            playlist.segments.push(Segment {
+                resource: Resource {
+                    url: resolve(url, line)?,
+                    range: range.take(),
+                },
+                start,
+                duration,
+            });
+            start += duration;
+        }
+    }
+    playlist.live = !ended;
+    Ok(playlist)
+}
+
+/// `<length>[@<offset>]`, continuing from `previous_end` without an offset
+fn byte_range(value: &str, previous_end: u64) -> Result<ByteRange> {
+    let invalid = || format!("Invalid byte range {:?}", value);
+    let (length, offset) = match value.split_once('@') {
+        Some((length, offset)) => (length, offset.parse().map_err(|_| invalid())?),
+        None => (value, previous_end),
+    };
+    let length = length.parse().map_err(|_| invalid())?;
+    Ok(ByteRange { offset, length })
+}
+
+/// `KEY=value,KEY="quoted, value"` attribute list
+fn attributes(list: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let mut rest = list;
+    while let Some((key, value)) = rest.split_once('=') {
+        let (value, next) = match value.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                (&quoted[..end], quoted.get(end + 1..).unwrap_or_default())
+            }
+            None => value.split_at(value.find(',').unwrap_or(value.len())),
+        };
+        attributes.insert(key.trim().to_string(), value.trim().to_string());
+        rest = next.trim_start_matches(',');
+    }
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "https://cdn.example.com/track/master.m3u8";
+
+    #[test]
+    fn audio_only_variants_are_preferred() {
+        let text = "#EXTM3U
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aac\",NAME=\"Main\",DEFAULT=YES,URI=\"audio/main.m3u8\"
+#EXT-X-STREAM-INF:BANDWIDTH=2000000,CODECS=\"avc1.64001f,mp4a.40.2\",AUDIO=\"aac\"
+video/high.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=64000,CODECS=\"mp4a.40.5\"
+low.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=192000,AVERAGE-BANDWIDTH=160000,CODECS=\"mp4a.40.2\"
+high.m3u8
+";
+        let Playlist::Master(master) = parse(BASE, text).unwrap() else { panic!("not a master playlist") };
+        assert_eq!(
+            master.streams(),
+            vec![
+                ("https://cdn.example.com/track/low.m3u8".to_string(), 64000, Some("mp4a.40.5".to_string())),
+                ("https://cdn.example.com/track/high.m3u8".to_string(), 160000, Some("mp4a.40.2".to_string())),
+            ]
+        );
+
+        // Without them, the audio rendition of the group
+        let video_only = text.replace("CODECS=\"mp4a", "CODECS=\"avc1.4d401e,mp4a");
+        let Playlist::Master(master) = parse(BASE, &video_only).unwrap() else { panic!("not a master playlist") };
+        assert_eq!(
+            master.streams(),
+            vec![("https://cdn.example.com/track/audio/main.m3u8".to_string(), 2000000, None)]
+        );
+    }
+
+    #[test]
+    fn media_playlists_list_timed_segments() {
+        let text = "#EXTM3U
+#EXT-X-TARGETDURATION:10
+#EXT-X-MAP:URI=\"init.mp4\",BYTERANGE=\"720@0\"
+#EXTINF:9.5,
+#EXT-X-BYTERANGE:1000@720
+media.mp4
+#EXTINF:4.0,
+#EXT-X-BYTERANGE:500
+media.mp4
+#EXT-X-ENDLIST
+";
+        let Playlist::Media(media) = parse(BASE, text).unwrap() else { panic!("not a media playlist") };
+        assert!(!media.live);
+        let url = "https://cdn.example.com/track/media.mp4".to_string();
+        assert_eq!(
+            media.init,
+            Some(Resource {
+                url: "https://cdn.example.com/track/init.mp4".to_string(),
+                range: Some(ByteRange { offset: 0, length: 720 }),
+            })
+        );
+        assert_eq!(
+            media.segments,
+            vec![
+                Segment {
+                    resource: Resource { url: url.clone(), range: Some(ByteRange { offset: 720, length: 1000 }) },
+                    start: 0.0,
+                    duration: 9.5,
+                },
+                Segment {
+                    resource: Resource { url, range: Some(ByteRange { offset: 1720, length: 500 }) },
+                    start: 9.5,
+                    duration: 4.0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn live_and_encrypted_playlists_are_told_apart() {
+        let live = "#EXTM3U\n#EXT-X-MEDIA-SEQUENCE:42\n#EXTINF:6,\nlive/42.aac\n";
+        let Playlist::Media(media) = parse(BASE, live).unwrap() else { panic!("not a media playlist") };
+        assert!(media.live);
+
+        let encrypted = "#EXTM3U\n#EXT-X-KEY:METHOD=AES-128,URI=\"key\"\n#EXTINF:6,\na.ts\n#EXT-X-ENDLIST\n";
+        assert!(parse(BASE, encrypted).is_err());
+        assert!(parse(BASE, "<MPD/>").is_err());
+    }
+}
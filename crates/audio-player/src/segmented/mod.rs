@@ -0,0 +1,606 @@
+// crates/audio-player/src/segmented/mod.rs
+// Segmented streams: HLS playlists and DASH manifests, whose audio comes in
+// segments fetched one by one. A manifest becomes a `Presentation`, the
+// renditions of the same audio at different bandwidths, each a list of timed
+// segments. `SegmentedSource` plays it: a fetch thread downloads segments
+// ahead, taking each from the best rendition the measured bandwidth allows,
+// and a seek starts over from the segment holding the new position.
+//
+// Live HLS playlists keep growing while they play and are not handled here;
+// the player streams them with `hls_client`.
+
+mod dash;
+mod hls;
+mod ts;
+
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, Receiver};
+use rodio::decoder::DecoderBuilder;
+use rodio::source::SeekError;
+use rodio::{Decoder, Source};
+use tracing::{debug, warn};
+use types::errors::{error_helpers, Result};
+
+use crate::data_usage::StreamCounter;
+use crate::expiry::{self, StreamFailure};
+
+/// Segments fetched ahead of the one being decoded
+const SEGMENTS_AHEAD: usize = 3;
+/// Attempts at fetching a segment before the stream fails
+const FETCH_ATTEMPTS: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+/// Share of the measured bandwidth a rendition may use
+const BANDWIDTH_SAFETY: f64 = 0.8;
+/// Weight of the last segment download in the bandwidth estimate
+const SMOOTHING: f64 = 0.3;
+/// Renditions whose segments start within this of each other are switched
+/// between without a gap or overlap
+const ALIGNMENT_TOLERANCE: f64 = 0.05;
+
+/// Download rate in bits per second measured on the last segments, shared by
+/// all streams so the next one starts at a fitting rendition
+static THROUGHPUT: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    Hls,
+    Dash,
+}
+
+impl Protocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Protocol::Hls => "hls",
+            Protocol::Dash => "dash",
+        }
+    }
+}
+
+/// Protocol of a manifest from the extension of its URL
+pub fn protocol_of_url(url: &str) -> Option<Protocol> {
+    let path = url.split(['?', '#']).next().unwrap_or(url).to_ascii_lowercase();
+    if path.ends_with(".m3u8") {
+        Some(Protocol::Hls)
+    } else if path.ends_with(".mpd") {
+        Some(Protocol::Dash)
+    } else {
+        None
+    }
+}
+
+/// Protocol of a manifest from the Content-Type it was served with
+pub fn protocol_of_type(content_type: &str) -> Option<Protocol> {
+    let mime = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    match mime.as_str() {
+        "application/vnd.apple.mpegurl" | "application/x-mpegurl" | "audio/mpegurl" | "audio/x-mpegurl" => {
+            Some(Protocol::Hls)
+        }
+        "application/dash+xml" => Some(Protocol::Dash),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub offset: u64,
+    pub length: u64,
+}
+
+/// A file, or a byte range of one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resource {
+    pub url: String,
+    pub range: Option<ByteRange>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub resource: Resource,
+    /// Start and length in seconds
+    pub start: f64,
+    pub duration: f64,
+}
+
+/// One encoding of the presentation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rendition {
+    /// Bits per second, 0 when the manifest doesn't tell
+    pub bandwidth: u64,
+    pub codecs: Option<String>,
+    /// Initialization segment, in front of the first media segment decoded
+    pub init: Option<Resource>,
+    pub segments: Vec<Segment>,
+}
+
+impl Rendition {
+    fn duration(&self) -> f64 {
+        self.segments.last().map_or(0.0, |s| s.start + s.duration)
+    }
+
+    /// Index of the segment playing at `position`
+    fn segment_at(&self, position: f64) -> usize {
+        self.segments
+            .partition_point(|s| s.start + s.duration <= position)
+            .min(self.segments.len() - 1)
+    }
+
+    /// Index of the segment starting at `position`, if one does
+    fn segment_starting_at(&self, position: f64) -> Option<usize> {
+        let index = self.segment_at(position);
+        ((self.segments[index].start - position).abs() < ALIGNMENT_TOLERANCE).then_some(index)
+    }
+}
+
+#[derive(Debug)]
+pub struct Presentation {
+    /// By increasing bandwidth, none without segments
+    renditions: Vec<Rendition>,
+}
+
+impl Presentation {
+    fn new(mut renditions: Vec<Rendition>) -> Result<Self> {
+        renditions.retain(|r| !r.segments.is_empty());
+        if renditions.is_empty() {
+            return Err("Stream manifest has no playable audio".into());
+        }
+        renditions.sort_by_key(|r| r.bandwidth);
+        Ok(Self { renditions })
+    }
+
+    /// Length in seconds, `None` when the manifest doesn't tell
+    pub fn duration(&self) -> Option<f64> {
+        Some(self.renditions.iter().map(Rendition::duration).fold(0.0, f64::max)).filter(|d| *d > 0.0)
+    }
+
+    pub fn bandwidths(&self) -> Vec<u64> {
+        self.renditions.iter().map(|r| r.bandwidth).collect()
+    }
+
+    /// Highest rendition using at most a safe share of `throughput` bits per
+    /// second, the lowest when none fits
+    fn pick(&self, throughput: u64) -> usize {
+        let budget = throughput as f64 * BANDWIDTH_SAFETY;
+        self.renditions
+            .iter()
+            .rposition(|r| r.bandwidth as f64 <= budget)
+            .unwrap_or(0)
+    }
+}
+
+pub enum Manifest {
+    Static(Presentation),
+    /// Live HLS playlist
+    Live,
+}
+
+/// Fetch and parse the manifest at `url`, with the media playlists or indexes
+/// it points to
+pub async fn load(url: &str, protocol: Protocol) -> Result<Manifest> {
+    let client = reqwest::Client::new();
+    let text = fetch_text(&client, url).await?;
+    match protocol {
+        Protocol::Hls => hls::load(&client, url, &text).await,
+        Protocol::Dash => dash::load(&client, url, &text).await,
+    }
+}
+
+fn resolve(base: &str, reference: &str) -> Result<String> {
+    let base = reqwest::Url::parse(base).map_err(error_helpers::to_parse_error)?;
+    Ok(base.join(reference).map_err(error_helpers::to_parse_error)?.to_string())
+}
+
+fn request(client: &reqwest::Client, resource: &Resource) -> reqwest::RequestBuilder {
+    let request = client.get(&resource.url);
+    match resource.range {
+        Some(range) => request.header(
+            reqwest::header::RANGE,
+            format!("bytes={}-{}", range.offset, range.offset + range.length.max(1) - 1),
+        ),
+        None => request,
+    }
+}
+
+async fn fetch(client: &reqwest::Client, resource: &Resource) -> Result<Vec<u8>> {
+    let response = request(client, resource)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(error_helpers::to_network_error)?;
+    let bytes = response.bytes().await.map_err(error_helpers::to_network_error)?;
+    Ok(bytes.to_vec())
+}
+
+async fn fetch_text(client: &reqwest::Client, url: &str) -> Result<String> {
+    let resource = Resource { url: url.to_string(), range: None };
+    String::from_utf8(fetch(client, &resource).await?).map_err(|_| format!("Manifest {} is not text", url).into())
+}
+
+fn throughput() -> u64 {
+    THROUGHPUT.load(Ordering::Relaxed)
+}
+
+fn record_throughput(bytes: usize, elapsed: Duration) {
+    let sample = bytes as f64 * 8.0 / elapsed.as_secs_f64().max(0.001);
+    let previous = throughput();
+    let estimate = if previous == 0 {
+        sample
+    } else {
+        previous as f64 * (1.0 - SMOOTHING) + sample * SMOOTHING
+    };
+    THROUGHPUT.store(estimate as u64, Ordering::Relaxed);
+}
+
+/// Remove the ID3 tag HLS puts in front of packed audio segments
+fn strip_id3(data: &mut Vec<u8>) {
+    if data.len() < 10 || &data[..3] != b"ID3" {
+        return;
+    }
+    let size = data[6..10].iter().fold(0usize, |size, b| (size << 7) | (*b & 0x7F) as usize);
+    let footer = if data[5] & 0x10 != 0 { 10 } else { 0 };
+    data.drain(..(10 + size + footer).min(data.len()));
+}
+
+/// What the fetch threads of a source share
+struct Shared {
+    presentation: Presentation,
+    client: reqwest::Client,
+    runtime: tokio::runtime::Handle,
+    counter: StreamCounter,
+    fetched: AtomicU64,
+    failure: StreamFailure,
+}
+
+impl Shared {
+    fn fetch(&self, resource: &Resource) -> Result<Vec<u8>> {
+        let mut attempt = 1;
+        loop {
+            match self.runtime.block_on(fetch(&self.client, resource)) {
+                Ok(bytes) => {
+                    let total = self.fetched.fetch_add(bytes.len() as u64, Ordering::Relaxed) + bytes.len() as u64;
+                    self.counter.update(total);
+                    return Ok(bytes);
+                }
+                // An expired URL stays refused
+                Err(e) if attempt < FETCH_ATTEMPTS && expiry::expired_status(&e).is_none() => {
+                    warn!("Fetching segment {} failed (attempt {}): {:?}", resource.url, attempt, e);
+                    attempt += 1;
+                    std::thread::sleep(RETRY_DELAY);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// A media segment ready for the decoder, behind the initialization
+    /// segment of its rendition when `init` is set
+    fn fetch_segment(&self, rendition: &Rendition, segment: &Segment, init: bool) -> Result<Vec<u8>> {
+        let started = Instant::now();
+        let mut data = self.fetch(&segment.resource)?;
+        record_throughput(data.len(), started.elapsed());
+        strip_id3(&mut data);
+        if ts::is_transport_stream(&data) {
+            data = ts::extract_audio(&data)?;
+        }
+        match rendition.init.as_ref().filter(|_| init) {
+            Some(resource) => {
+                let mut with_init = self.fetch(resource)?;
+                with_init.append(&mut data);
+                Ok(with_init)
+            }
+            None => Ok(data),
+        }
+    }
+}
+
+/// Segment fetched for the decoder
+struct Fetched {
+    rendition: usize,
+    data: Vec<u8>,
+}
+
+/// Segments of one run of fetches, from a start position to the end
+struct Feed {
+    rx: Receiver<Result<Fetched>>,
+    /// First segment of another rendition, left for the next decoder
+    pending: Mutex<Option<Fetched>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Drop for Feed {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Fetch the segments from `index` of `rendition` on, switching renditions
+/// at segment boundaries as the bandwidth estimate changes
+fn spawn_fetch(shared: Arc<Shared>, mut rendition: usize, mut index: usize) -> Arc<Feed> {
+    let (tx, rx) = bounded(SEGMENTS_AHEAD);
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let feed = Arc::new(Feed {
+        rx,
+        pending: Mutex::new(None),
+        cancelled: cancelled.clone(),
+    });
+    std::thread::spawn(move || {
+        let renditions = &shared.presentation.renditions;
+        let mut switched = true;
+        while !cancelled.load(Ordering::Relaxed) {
+            let Some(segment) = renditions[rendition].segments.get(index) else { break };
+            let fetched = shared
+                .fetch_segment(&renditions[rendition], segment, switched)
+                .map(|data| Fetched { rendition, data });
+            if let Err(e) = &fetched {
+                shared.failure.report(expiry::expired_status(e));
+            }
+            let failed = fetched.is_err();
+            if tx.send(fetched).is_err() || failed {
+                break;
+            }
+
+            switched = false;
+            index += 1;
+            let wanted = shared.presentation.pick(throughput());
+            if wanted != rendition {
+                let next = segment.start + segment.duration;
+                if let Some(next_index) = renditions[wanted].segment_starting_at(next) {
+                    debug!("Switching to rendition {} at {:.1}s", renditions[wanted].bandwidth, next);
+                    rendition = wanted;
+                    index = next_index;
+                    switched = true;
+                }
+            }
+        }
+    });
+    feed
+}
+
+/// Stream of the segments of one rendition handed to a decoder. Ends where
+/// the feed moves to another rendition, which needs a decoder of its own.
+struct SegmentReader {
+    feed: Arc<Feed>,
+    rendition: usize,
+    data: Vec<u8>,
+    offset: usize,
+    position: u64,
+}
+
+impl SegmentReader {
+    fn new(feed: Arc<Feed>, first: Fetched) -> Self {
+        Self {
+            feed,
+            rendition: first.rendition,
+            data: first.data,
+            offset: 0,
+            position: 0,
+        }
+    }
+}
+
+impl Read for SegmentReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.offset == self.data.len() {
+            let fetched = match self.feed.rx.recv() {
+                Ok(fetched) => fetched.map_err(|e| std::io::Error::other(e.to_string()))?,
+                Err(_) => return Ok(0),
+            };
+            if fetched.rendition != self.rendition {
+                *self.feed.pending.lock().unwrap() = Some(fetched);
+                return Ok(0);
+            }
+            self.data = fetched.data;
+            self.offset = 0;
+        }
+        let read = buf.len().min(self.data.len() - self.offset);
+        buf[..read].copy_from_slice(&self.data[self.offset..self.offset + read]);
+        self.offset += read;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for SegmentReader {
+    /// Only tells the position: segmented streams seek by time, see
+    /// `SegmentedSource::try_seek`
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match pos {
+            SeekFrom::Current(0) => Ok(self.position),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "segmented streams seek by time",
+            )),
+        }
+    }
+}
+
+/// Source playing a presentation, one decoder per run of segments of the
+/// same rendition
+pub struct SegmentedSource {
+    shared: Arc<Shared>,
+    feed: Arc<Feed>,
+    decoder: Decoder<SegmentReader>,
+    /// Samples left to drop to reach a position inside the first segment
+    skip: u64,
+}
+
+impl SegmentedSource {
+    /// Start `presentation` at `position` seconds. Waits for the first
+    /// segment, so it must run inside a tokio runtime but off its workers.
+    /// Segments failing to download are reported to `failure`.
+    pub fn new(presentation: Presentation, position: f64, failure: StreamFailure) -> Result<Self> {
+        let shared = Arc::new(Shared {
+            presentation,
+            client: reqwest::Client::new(),
+            runtime: tokio::runtime::Handle::current(),
+            counter: StreamCounter::default(),
+            fetched: AtomicU64::new(0),
+            failure,
+        });
+        let (feed, decoder, skip) = Self::open(&shared, position)?;
+        Ok(Self {
+            shared,
+            feed,
+            decoder,
+            skip,
+        })
+    }
+
+    fn open(shared: &Arc<Shared>, position: f64) -> Result<(Arc<Feed>, Decoder<SegmentReader>, u64)> {
+        let rendition = shared.presentation.pick(throughput());
+        let segments = &shared.presentation.renditions[rendition];
+        let index = segments.segment_at(position);
+        let offset = (position - segments.segments[index].start).max(0.0);
+
+        let feed = spawn_fetch(shared.clone(), rendition, index);
+        let first = feed
+            .rx
+            .recv()
+            .map_err(|_| types::errors::MusicError::from("Segment fetch stopped"))??;
+        let decoder = Self::decoder(feed.clone(), first)?;
+        let channels = u16::from(decoder.channels()) as u64;
+        let sample_rate = u32::from(decoder.sample_rate()) as f64;
+        let skip = (offset * sample_rate) as u64 * channels;
+        Ok((feed, decoder, skip))
+    }
+
+    fn decoder(feed: Arc<Feed>, first: Fetched) -> Result<Decoder<SegmentReader>> {
+        // Not seekable: the demuxers read the segments in order instead of
+        // looking for the end of the stream
+        DecoderBuilder::new()
+            .with_data(SegmentReader::new(feed, first))
+            .with_seekable(false)
+            .build()
+            .map_err(error_helpers::to_playback_error)
+    }
+}
+
+impl Iterator for SegmentedSource {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.decoder.next() {
+                Some(_) if self.skip > 0 => self.skip -= 1,
+                Some(sample) => return Some(sample),
+                None => {
+                    // The feed moved to another rendition, or the stream ended
+                    let pending = self.feed.pending.lock().unwrap().take()?;
+                    match Self::decoder(self.feed.clone(), pending) {
+                        Ok(decoder) => self.decoder = decoder,
+                        Err(e) => {
+                            warn!("Failed to decode the next rendition: {:?}", e);
+                            return None;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Source for SegmentedSource {
+    fn current_span_len(&self) -> Option<usize> {
+        self.decoder.current_span_len()
+    }
+
+    fn channels(&self) -> rodio::ChannelCount {
+        self.decoder.channels()
+    }
+
+    fn sample_rate(&self) -> rodio::SampleRate {
+        self.decoder.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.shared.presentation.duration().map(Duration::from_secs_f64)
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> std::result::Result<(), SeekError> {
+        match Self::open(&self.shared, pos.as_secs_f64()) {
+            Ok((feed, decoder, skip)) => {
+                self.feed = feed;
+                self.decoder = decoder;
+                self.skip = skip;
+                Ok(())
+            }
+            Err(e) => {
+                warn!("Failed to seek segmented stream to {:?}: {:?}", pos, e);
+                Err(SeekError::NotSupported {
+                    underlying_source: std::any::type_name::<Self>(),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rendition(bandwidth: u64, durations: &[f64]) -> Rendition {
+        let mut start = 0.0;
+        let segments = durations
+            .iter()
+            .enumerate()
+            .map(|(i, duration)| {
+                let segment = Segment {
+                    resource: Resource { url: format!("https://cdn.example.com/{}/{}.m4s", bandwidth, i), range: None },
+                    start,
+                    duration: *duration,
+                };
+                start += duration;
+                segment
+            })
+            .collect();
+        Rendition { bandwidth, codecs: None, init: None, segments }
+    }
+
+    #[test]
+    fn picks_the_best_rendition_that_fits() {
+        let presentation = Presentation::new(vec![
+            rendition(320_000, &[10.0]),
+            rendition(64_000, &[10.0]),
+            rendition(128_000, &[10.0]),
+            rendition(256_000, &[]),
+        ])
+        .unwrap();
+        assert_eq!(presentation.bandwidths(), vec![64_000, 128_000, 320_000]);
+        assert_eq!(presentation.pick(0), 0);
+        assert_eq!(presentation.pick(200_000), 1);
+        assert_eq!(presentation.pick(1_000_000), 2);
+        assert!(Presentation::new(vec![rendition(64_000, &[])]).is_err());
+    }
+
+    #[test]
+    fn finds_segments_by_time() {
+        let low = rendition(64_000, &[4.0, 4.0, 4.0, 2.5]);
+        assert_eq!(low.duration(), 14.5);
+        assert_eq!(low.segment_at(0.0), 0);
+        assert_eq!(low.segment_at(4.0), 1);
+        assert_eq!(low.segment_at(13.0), 3);
+        assert_eq!(low.segment_at(99.0), 3);
+        assert_eq!(low.segment_starting_at(8.01), Some(2));
+        assert_eq!(low.segment_starting_at(9.0), None);
+    }
+
+    #[test]
+    fn tells_manifests_apart() {
+        assert_eq!(protocol_of_url("https://a.example/x/index.M3U8?token=1"), Some(Protocol::Hls));
+        assert_eq!(protocol_of_url("https://a.example/manifest.mpd#t=3"), Some(Protocol::Dash));
+        assert_eq!(protocol_of_url("https://a.example/song.mp3?m3u8"), None);
+        assert_eq!(protocol_of_type("application/vnd.apple.mpegURL; charset=utf-8"), Some(Protocol::Hls));
+        assert_eq!(protocol_of_type("application/dash+xml"), Some(Protocol::Dash));
+        assert_eq!(protocol_of_type("audio/mpeg"), None);
+    }
+
+    #[test]
+    fn strips_leading_id3_tags() {
+        let mut data = b"ID3\x04\x00\x00\x00\x00\x00\x02ab\xff\xf1".to_vec();
+        strip_id3(&mut data);
+        assert_eq!(data, b"\xff\xf1");
+    }
+}
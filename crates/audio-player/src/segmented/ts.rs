@@ -0,0 +1,168 @@
+// crates/audio-player/src/segmented/ts.rs
+// MPEG transport stream segments, as HLS playlists often use. Symphonia reads
+// the elementary stream inside them (ADTS AAC or MPEG audio) but not the
+// transport stream, so the audio PES payloads are taken out of each segment.
+
+use types::errors::Result;
+
+const PACKET_LEN: usize = 188;
+const SYNC: u8 = 0x47;
+const PAT_PID: u16 = 0;
+/// Stream types of the program map table symphonia can decode
+const AUDIO_STREAM_TYPES: [u8; 3] = [
+    0x03, // MPEG-1 audio
+    0x04, // MPEG-2 audio
+    0x0F, // AAC in ADTS
+];
+
+pub(super) fn is_transport_stream(data: &[u8]) -> bool {
+    data.len() >= PACKET_LEN
+        && data[0] == SYNC
+        && (data.len() < 2 * PACKET_LEN || data[PACKET_LEN] == SYNC)
+}
+
+/// Elementary stream of the first audio track of a transport stream segment
+pub(super) fn extract_audio(data: &[u8]) -> Result<Vec<u8>> {
+    let mut pmt_pid = None;
+    let mut audio_pid = None;
+    let mut audio = Vec::with_capacity(data.len());
+    let mut pes = Vec::new();
+    for packet in data.chunks_exact(PACKET_LEN) {
+        if packet[0] != SYNC {
+            return Err("Lost sync in MPEG-TS segment".into());
+        }
+        let unit_start = packet[1] & 0x40 != 0;
+        let pid = (u16::from(packet[1] & 0x1F) << 8) | u16::from(packet[2]);
+        let adaptation = (packet[3] >> 4) & 0x3;
+        let mut offset = 4;
+        if adaptation & 0x2 != 0 {
+            offset += 1 + packet[4] as usize;
+        }
+        if adaptation & 0x1 == 0 || offset >= PACKET_LEN {
+            continue;
+        }
+        let payload = &packet[offset..];
+
+        if pid == PAT_PID && unit_start {
+            pmt_pid = section(payload).and_then(parse_pat);
+        } else if Some(pid) == pmt_pid && unit_start && audio_pid.is_none() {
+            audio_pid = section(payload).and_then(parse_pmt);
+        } else if Some(pid) == audio_pid {
+            if unit_start {
+                append_pes(&pes, &mut audio);
+                pes.clear();
+            }
+            pes.extend_from_slice(payload);
+        }
+    }
+    append_pes(&pes, &mut audio);
+    if audio_pid.is_none() {
+        return Err("No supported audio track in MPEG-TS segment".into());
+    }
+    Ok(audio)
+}
+
+/// Table section of a PSI payload, after its pointer field
+fn section(payload: &[u8]) -> Option<&[u8]> {
+    let pointer = *payload.first()? as usize;
+    let section = payload.get(1 + pointer..)?;
+    let length = ((usize::from(*section.get(1)?) & 0x0F) << 8) | usize::from(*section.get(2)?);
+    // Without the 4 bytes of CRC
+    section.get(..(3 + length).checked_sub(4)?)
+}
+
+/// PID of the program map table of the first program
+fn parse_pat(section: &[u8]) -> Option<u16> {
+    section.get(8..)?.chunks_exact(4).find_map(|program| {
+        let number = u16::from_be_bytes([program[0], program[1]]);
+        // Program 0 points to the network information table
+        (number != 0).then(|| (u16::from(program[2] & 0x1F) << 8) | u16::from(program[3]))
+    })
+}
+
+/// PID of the first audio stream of a program
+fn parse_pmt(section: &[u8]) -> Option<u16> {
+    let info_len = ((usize::from(*section.get(10)?) & 0x0F) << 8) | usize::from(*section.get(11)?);
+    let mut streams = section.get(12 + info_len..)?;
+    while streams.len() >= 5 {
+        let stream_type = streams[0];
+        let pid = (u16::from(streams[1] & 0x1F) << 8) | u16::from(streams[2]);
+        if AUDIO_STREAM_TYPES.contains(&stream_type) {
+            return Some(pid);
+        }
+        let es_info_len = ((usize::from(streams[3]) & 0x0F) << 8) | usize::from(streams[4]);
+        streams = streams.get(5 + es_info_len..)?;
+    }
+    None
+}
+
+/// Add the payload of a PES packet to `audio`, skipping broken packets
+fn append_pes(pes: &[u8], audio: &mut Vec<u8>) {
+    if pes.len() < 9 || pes[..3] != [0, 0, 1] {
+        return;
+    }
+    let header_len = pes[8] as usize;
+    if let Some(payload) = pes.get(9 + header_len..) {
+        audio.extend_from_slice(payload);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(pid: u16, unit_start: bool, payload: &[u8]) -> Vec<u8> {
+        let mut packet = vec![SYNC, (pid >> 8) as u8 | if unit_start { 0x40 } else { 0 }, pid as u8];
+        // Pad short payloads with an adaptation field
+        let stuffing = PACKET_LEN - 4 - payload.len();
+        if stuffing > 0 {
+            packet.push(0x30);
+            packet.push((stuffing - 1) as u8);
+            if stuffing > 1 {
+                packet.push(0);
+                packet.extend(std::iter::repeat(0xFF).take(stuffing - 2));
+            }
+        } else {
+            packet.push(0x10);
+        }
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    fn psi(table: &[u8]) -> Vec<u8> {
+        // Pointer field, then the table with a dummy CRC
+        let mut payload = vec![0];
+        payload.extend_from_slice(table);
+        payload.extend_from_slice(&[0; 4]);
+        payload
+    }
+
+    #[test]
+    fn takes_the_audio_out_of_pes_packets() {
+        let pat = psi(&[0x00, 0xB0, 13, 0, 1, 0xC1, 0, 0, 0, 1, 0xF0, 0x00]);
+        // Program with a video stream on 0x100 and ADTS audio on 0x101
+        let pmt = psi(&[
+            0x02, 0xB0, 23, 0, 1, 0xC1, 0, 0, 0xE1, 0x00, 0xF0, 0x00, 0x1B, 0xE1, 0x00, 0xF0, 0x00, 0x0F, 0xE1,
+            0x01, 0xF0, 0x00,
+        ]);
+        let mut first = vec![0, 0, 1, 0xC0, 0, 0, 0x80, 0x80, 5, 1, 2, 3, 4, 5];
+        first.extend_from_slice(b"adts-1");
+        let mut segment = Vec::new();
+        segment.extend(packet(PAT_PID, true, &pat));
+        segment.extend(packet(0x1000, true, &pmt));
+        segment.extend(packet(0x100, true, &[0, 0, 1, 0xE0, 0, 0, 0x80, 0, 0, 9, 9]));
+        segment.extend(packet(0x101, true, &first));
+        segment.extend(packet(0x101, false, b"-more"));
+        segment.extend(packet(0x101, true, &[0, 0, 1, 0xC0, 0, 0, 0x80, 0, 0, b'!']));
+
+        assert!(is_transport_stream(&segment));
+        assert_eq!(extract_audio(&segment).unwrap(), b"adts-1-more!");
+    }
+
+    #[test]
+    fn segments_without_audio_are_rejected() {
+        assert!(!is_transport_stream(b"ID3"));
+        let pat = psi(&[0x00, 0xB0, 13, 0, 1, 0xC1, 0, 0, 0, 1, 0xF0, 0x00]);
+        assert!(extract_audio(&packet(PAT_PID, true, &pat)).is_err());
+    }
+}
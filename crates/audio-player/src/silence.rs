@@ -0,0 +1,129 @@
+// crates/audio-player/src/silence.rs
+// Silence at both ends of local files, so long lead-ins and lead-outs can be
+// trimmed at playback. Only the ends are decoded when the file can be seeked;
+// audio quieter than about -60 dBFS counts as silence.
+
+use std::fs::File;
+use std::path::Path;
+use std::time::Duration;
+
+use rodio::Source;
+use types::errors::{error_helpers, Result};
+use types::ui::player_details::TrackSilence;
+
+/// Peak sample amplitude below which a window is silent
+const THRESHOLD: f32 = 0.001;
+/// Resolution of the offsets
+const WINDOW_MS: usize = 10;
+/// Audio searched for sound at each end of the file
+const MAX_SECONDS: usize = 60;
+
+/// Loud windows of a stretch of audio and how many windows it has
+#[derive(Debug, Default, PartialEq)]
+struct Scan {
+    first_loud: Option<usize>,
+    last_loud: Option<usize>,
+    windows: usize,
+}
+
+fn scan(samples: impl Iterator<Item = f32>, window_len: usize, limit: Option<usize>) -> Scan {
+    let mut result = Scan::default();
+    let mut peak = 0.0f32;
+    let mut filled = 0;
+    for sample in samples {
+        peak = peak.max(sample.abs());
+        filled += 1;
+        if filled < window_len {
+            continue;
+        }
+        if peak > THRESHOLD {
+            result.first_loud.get_or_insert(result.windows);
+            result.last_loud = Some(result.windows);
+        }
+        result.windows += 1;
+        peak = 0.0;
+        filled = 0;
+        if limit.is_some_and(|limit| result.windows >= limit) {
+            return result;
+        }
+    }
+    // A partial last window still counts
+    if filled > 0 {
+        if peak > THRESHOLD {
+            result.first_loud.get_or_insert(result.windows);
+            result.last_loud = Some(result.windows);
+        }
+        result.windows += 1;
+    }
+    result
+}
+
+/// Decode the ends of a local file and find where its audio starts and ends
+pub fn detect_file(path: &Path) -> Result<TrackSilence> {
+    let file = File::open(path)?;
+    let mut decoder = rodio::Decoder::try_from(file).map_err(error_helpers::to_media_error)?;
+    let channels = u16::from(decoder.channels()).max(1) as usize;
+    let sample_rate: u32 = decoder.sample_rate().into();
+    let window_len = (sample_rate as usize * WINDOW_MS / 1000).max(1) * channels;
+    let window_secs = WINDOW_MS as f64 / 1000.0;
+    let limit = MAX_SECONDS * 1000 / WINDOW_MS;
+    let total = decoder.total_duration();
+
+    let head = scan(decoder.by_ref(), window_len, Some(limit));
+    // No sound in the first minute: not a lead-in worth guessing about
+    let start = head.first_loud.map_or(0.0, |first| first as f64 * window_secs);
+    if head.windows < limit {
+        // The whole file was decoded
+        let last = head
+            .last_loud
+            .ok_or_else(|| format!("No audio above the silence threshold in {}", path.display()))?;
+        return Ok(TrackSilence { start, end: (last + 1) as f64 * window_secs });
+    }
+
+    // Jump to the last minute when possible, decode the rest otherwise
+    let tail_from = total
+        .filter(|total| total.as_secs() as usize > 2 * MAX_SECONDS)
+        .map(|total| total - Duration::from_secs(MAX_SECONDS as u64))
+        .filter(|from| decoder.try_seek(*from).is_ok())
+        .map_or(head.windows as f64 * window_secs, |from| from.as_secs_f64());
+    let tail = scan(decoder, window_len, None);
+    let end = match tail.last_loud {
+        Some(last) => tail_from + (last + 1) as f64 * window_secs,
+        // The file ended with the first minute
+        None if tail.windows == 0 => head.last_loud.map_or(tail_from, |last| (last + 1) as f64 * window_secs),
+        // Silent to the end: the audio ends where the tail begins at the latest
+        None => tail_from,
+    };
+    Ok(TrackSilence { start, end })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(silent_before: usize, loud: usize, silent_after: usize) -> Vec<f32> {
+        let mut samples = vec![0.0; silent_before];
+        samples.extend((0..loud).map(|i| if i % 2 == 0 { 0.5 } else { -0.5 }));
+        samples.extend(std::iter::repeat(0.0002).take(silent_after));
+        samples
+    }
+
+    #[test]
+    fn finds_the_first_and_last_loud_windows() {
+        let result = scan(signal(300, 250, 420).into_iter(), 100, None);
+        assert_eq!(
+            result,
+            Scan {
+                first_loud: Some(3),
+                last_loud: Some(5),
+                windows: 10,
+            }
+        );
+    }
+
+    #[test]
+    fn stops_at_the_limit() {
+        let result = scan(signal(1_000, 100, 0).into_iter(), 100, Some(4));
+        assert_eq!(result, Scan { first_loud: None, last_loud: None, windows: 4 });
+    }
+}
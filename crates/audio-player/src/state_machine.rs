@@ -0,0 +1,211 @@
+// crates/audio-player/src/state_machine.rs
+// Formal playback state machine. All writes to PlayerDetails::state must go through
+// PlayerStore::transition(), which consults the rules defined here.
+
+use types::ui::player_details::PlayerState;
+
+/// Snapshot of the store facts that guard a transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransitionContext {
+    /// Whether the store currently has a resolved current track
+    pub has_track: bool,
+    /// Number of entries in the play queue
+    pub queue_len: usize,
+}
+
+/// Returns true when moving from `from` to `to` is a legal edge of the state machine.
+///
+/// Edges (self-loops are always allowed and treated as no-ops):
+/// - Idle     -> Loading | Stopped | Errored
+/// - Loading  -> Playing | Paused | Stopped | Errored | Idle
+/// - Playing  -> Paused | Loading | Stopped | Errored | Idle
+/// - Paused   -> Playing | Loading | Stopped | Errored | Idle
+/// - Stopped  -> Loading | Playing | Idle | Errored
+/// - Errored  -> Loading | Stopped | Idle
+pub fn is_legal_edge(from: PlayerState, to: PlayerState) -> bool {
+    use PlayerState::*;
+    if from == to {
+        return true;
+    }
+    matches!(
+        (from, to),
+        (Idle, Loading | Stopped | Errored)
+            | (Loading, Playing | Paused | Stopped | Errored | Idle)
+            | (Playing, Paused | Loading | Stopped | Errored | Idle)
+            | (Paused, Playing | Loading | Stopped | Errored | Idle)
+            | (Stopped, Loading | Playing | Idle | Errored)
+            | (Errored, Loading | Stopped | Idle)
+    )
+}
+
+/// Returns true when `state` is consistent with the store contents.
+///
+/// Invariants:
+/// - Loading/Playing/Paused imply a current track exists and the queue is not empty
+/// - Idle implies there is no current track
+pub fn satisfies_invariants(state: PlayerState, ctx: TransitionContext) -> bool {
+    match state {
+        PlayerState::Loading | PlayerState::Playing | PlayerState::Paused => {
+            ctx.has_track && ctx.queue_len > 0
+        }
+        PlayerState::Idle => !ctx.has_track,
+        PlayerState::Stopped | PlayerState::Errored => true,
+    }
+}
+
+/// Validate a requested transition against both edge rules and target invariants.
+pub fn check_transition(
+    from: PlayerState,
+    to: PlayerState,
+    ctx: TransitionContext,
+) -> std::result::Result<(), String> {
+    if !is_legal_edge(from, to) {
+        return Err(format!("Illegal player state transition {:?} -> {:?}", from, to));
+    }
+    if !satisfies_invariants(to, ctx) {
+        return Err(format!(
+            "Player state {:?} is not allowed with has_track={} queue_len={}",
+            to, ctx.has_track, ctx.queue_len
+        ));
+    }
+    Ok(())
+}
+
+/// Derive the state that should be in effect after the store contents changed
+/// underneath the current state (queue cleared, track removed, ...).
+pub fn reconcile(state: PlayerState, ctx: TransitionContext) -> PlayerState {
+    if satisfies_invariants(state, ctx) {
+        return state;
+    }
+    if ctx.has_track {
+        // Track exists but queue is empty or similar partial state: stop instead of
+        // keeping a playing/paused state that cannot be resumed reliably.
+        PlayerState::Stopped
+    } else {
+        PlayerState::Idle
+    }
+}
+
+/// Derive the state to restore at application startup from persisted data.
+/// No backend has media loaded yet, so active states are downgraded to Paused.
+pub fn restore_state(persisted: PlayerState, ctx: TransitionContext) -> PlayerState {
+    if !ctx.has_track || ctx.queue_len == 0 {
+        return PlayerState::Idle;
+    }
+    match persisted {
+        PlayerState::Playing | PlayerState::Loading => PlayerState::Paused,
+        PlayerState::Errored | PlayerState::Idle => PlayerState::Stopped,
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::PlayerStore;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use types::tracks::{MediaContent, Tracks};
+
+    const ALL_STATES: [PlayerState; 6] = [
+        PlayerState::Idle,
+        PlayerState::Loading,
+        PlayerState::Playing,
+        PlayerState::Paused,
+        PlayerState::Stopped,
+        PlayerState::Errored,
+    ];
+
+    fn track(id: &str) -> MediaContent {
+        MediaContent {
+            track: Tracks {
+                _id: Some(id.to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn ctx_of(store: &PlayerStore) -> TransitionContext {
+        TransitionContext {
+            has_track: store.get_current_track().is_some(),
+            queue_len: store.get_queue_len(),
+        }
+    }
+
+    #[test]
+    fn idle_cannot_jump_to_playing() {
+        assert!(!is_legal_edge(PlayerState::Idle, PlayerState::Playing));
+        assert!(!is_legal_edge(PlayerState::Idle, PlayerState::Paused));
+        assert!(is_legal_edge(PlayerState::Idle, PlayerState::Loading));
+    }
+
+    #[test]
+    fn reconcile_always_yields_consistent_state() {
+        for state in ALL_STATES {
+            for has_track in [false, true] {
+                for queue_len in [0usize, 1, 3] {
+                    let ctx = TransitionContext { has_track, queue_len };
+                    assert!(satisfies_invariants(reconcile(state, ctx), ctx));
+                    assert!(satisfies_invariants(restore_state(state, ctx), ctx));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn playing_with_empty_queue_is_rejected() {
+        let mut store = PlayerStore::new(None);
+        assert!(store.transition(PlayerState::Playing).is_err());
+        assert_eq!(store.get_player_state(), PlayerState::Idle);
+    }
+
+    #[test]
+    fn clearing_queue_drops_active_state() {
+        let mut store = PlayerStore::new(None);
+        store.add_to_queue(vec![track("a"), track("b")]);
+        store.transition(PlayerState::Loading).unwrap();
+        store.transition(PlayerState::Playing).unwrap();
+        store.clear_queue();
+        assert_eq!(store.get_player_state(), PlayerState::Idle);
+    }
+
+    /// Property: under any random sequence of store operations the state invariants hold.
+    #[test]
+    fn random_operations_preserve_invariants() {
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        for _ in 0..64 {
+            let mut store = PlayerStore::new(None);
+            for step in 0..200 {
+                match rng.gen_range(0..10) {
+                    0 => {
+                        store.add_to_queue(vec![track(&format!("t{}", rng.gen_range(0..8)))]);
+                    }
+                    1 => store.play_now(track(&format!("t{}", rng.gen_range(0..8)))),
+                    2 if store.get_queue_len() > 0 => {
+                        let idx = rng.gen_range(0..store.get_queue_len());
+                        store.remove_from_queue(idx);
+                    }
+                    3 => store.clear_queue(),
+                    4 if store.get_queue_len() > 0 => store.next_track(),
+                    5 if store.get_queue_len() > 0 => store.prev_track(),
+                    _ => {
+                        let target = ALL_STATES[rng.gen_range(0..ALL_STATES.len())];
+                        let _ = store.transition(target);
+                    }
+                }
+                let ctx = ctx_of(&store);
+                let state = store.get_player_state();
+                assert!(
+                    satisfies_invariants(state, ctx),
+                    "step {}: state {:?} violates invariants with {:?}",
+                    step,
+                    state,
+                    ctx
+                );
+                if state == PlayerState::Playing {
+                    assert!(store.get_current_track().is_some());
+                }
+            }
+        }
+    }
+}
@@ -2,24 +2,145 @@ use rand::seq::SliceRandom;
 use rand::thread_rng;
 use serde::{Serialize, Deserialize};
 use serde_json;
-use std::{cmp::min, collections::HashMap, sync::Arc};
+use std::{cmp::min, collections::{HashMap, HashSet}, sync::Arc};
 use types::{
     tracks::MediaContent,
-    ui::player_details::{PlayerState, PlayerMode, VolumeMode},
-    errors::Result,
+    stations::STATION_ID_PREFIX,
+    ui::player_details::{PlayerState, PlayerMode, QueueItemOverrides, TrackGain, TrackSilence, VolumeMode},
+    settings::{general::ResumeOnLaunch, queue::QueueDuplicatePolicy},
+    errors::{MusicError, Result},
 };
 use database::database::Database;
 
+use crate::edit_regions::{EditAction, EditPlayback, EditRegions};
+use crate::persist;
+use crate::queue_metrics::{self, CurrentEntry, QueueMetrics, QueueTiming};
+use crate::state_machine::{self, TransitionContext};
+
+/// Keys of the player state in `player_store_kv`
+const PERSISTED_KEYS: [&str; 6] = [
+    "player_state",
+    "track_queue",
+    "current_index",
+    "queue_data",
+    "queue_overrides",
+    "queue_auto_generated",
+];
+
+/// Silence at either end of a track shorter than this is left alone, being
+/// part of the music or of a gapless album
+const MIN_SILENCE_TRIM: f64 = 2.0;
+
+/// Describe a partial restore of the persisted state, `None` when it was
+/// restored in full.
+fn restore_warning(version: Option<u32>, newer: bool, unreadable: &[&str], skipped_entries: usize) -> Option<String> {
+    if !newer && unreadable.is_empty() {
+        return None;
+    }
+    let mut message = match version {
+        Some(v) if newer => format!("The play queue was saved by a newer version of the app (format {}).", v),
+        None => "The play queue was saved in an unknown format.".to_string(),
+        Some(_) => "Part of the saved play queue could not be read.".to_string(),
+    };
+    if skipped_entries > 0 {
+        message.push_str(&format!(" {} queue entries could not be restored.", skipped_entries));
+    } else if unreadable.is_empty() {
+        message.push_str(" It was restored in full.");
+    }
+    if !unreadable.is_empty() {
+        message.push_str(" The unreadable data was backed up.");
+    }
+    Some(message)
+}
+
+/// Seconds of playback between saves of the position, so it survives a crash
+/// or a shutdown without a state change
+const POSITION_SAVE_INTERVAL: f64 = 5.0;
+
+/// Where playback picks up after launch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LaunchResume {
+    /// Position in the restored track to seek to once it is loaded
+    pub position: f64,
+    /// Whether to start playing right away
+    pub play: bool,
+}
+
+/// Player state decoded from `player_store_kv`
+struct Restored {
+    data: PlayerStoreData,
+    /// Keys whose payload could not be read in full
+    unreadable: Vec<&'static str>,
+    skipped_entries: usize,
+}
+
 // No-op UI bridge hooks for backend-only usage
 // These can be wired by the integrator if needed
 fn set_position(_pos: f64) { /* noop */ }
 fn set_playback_state(_state: PlayerState) { /* noop */ }
 
+/// Play queue. `track_queue` holds queue instance IDs (`<track_id>#<n>`), so the
+/// same track can appear more than once; `data` and `overrides` are keyed by
+/// those instance IDs.
 #[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Queue {
     pub track_queue: Vec<String>,
     pub current_index: usize,
     pub data: HashMap<String, MediaContent>,
+    #[serde(default)]
+    pub overrides: HashMap<String, QueueItemOverrides>,
+    /// Instance IDs appended by radio mode rather than by the user
+    #[serde(default)]
+    pub auto_generated: HashSet<String>,
+}
+
+impl Queue {
+    /// Allocate a fresh instance ID for `track_id` that is not used by any entry.
+    fn next_instance_id(&self, track_id: &str) -> String {
+        let mut n = 0usize;
+        loop {
+            let candidate = format!("{}#{}", track_id, n);
+            if !self.data.contains_key(&candidate) {
+                return candidate;
+            }
+            n += 1;
+        }
+    }
+
+    /// Position of the first queue entry that refers to `track_id`.
+    pub fn position_of_track(&self, track_id: &str) -> Option<usize> {
+        self.track_queue.iter().position(|instance_id| {
+            self.data
+                .get(instance_id)
+                .and_then(|t| t.track._id.as_deref())
+                == Some(track_id)
+        })
+    }
+
+    /// Upgrade a queue persisted before instance IDs were introduced, where
+    /// `track_queue` held raw track IDs and `data` was keyed by track ID.
+    fn migrate_legacy_entries(&mut self) -> bool {
+        let is_legacy = self.track_queue.iter().any(|id| {
+            self.data.get(id).and_then(|t| t.track._id.as_deref()) == Some(id.as_str())
+        });
+        if !is_legacy {
+            return false;
+        }
+
+        let legacy_data = std::mem::take(&mut self.data);
+        let legacy_queue = std::mem::take(&mut self.track_queue);
+        for track_id in legacy_queue {
+            if let Some(track) = legacy_data.get(&track_id) {
+                let instance_id = self.next_instance_id(&track_id);
+                self.data.insert(instance_id.clone(), track.clone());
+                self.track_queue.push(instance_id);
+            }
+        }
+        if self.current_index >= self.track_queue.len() {
+            self.current_index = 0;
+        }
+        true
+    }
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -34,7 +155,7 @@ pub struct PlayerDetails {
     pub repeat: PlayerMode,
     old_volume: f64,
     volume: f64,
-    #[serde(skip)]
+    #[serde(default)]
     volume_mode: VolumeMode,
     volume_map: HashMap<String, f64>,
     clamp_map: HashMap<String, f64>,
@@ -52,6 +173,16 @@ pub struct PlayerStoreData {
     pub shuffle_bag: Vec<usize>,
     #[serde(skip)]
     pub shuffle_index: usize,
+    // Set once the current entry passed its end trim, cleared on track change
+    #[serde(skip)]
+    pub end_trim_reached: bool,
+    // Set when the current entry was finished early to crossfade into the next
+    // one, consumed by the next load
+    #[serde(skip)]
+    pub crossfade_pending: bool,
+    // Persisted state before the startup downgrade, i.e. at shutdown
+    #[serde(skip)]
+    pub shutdown_state: PlayerState,
 }
 
 #[derive(Debug)]
@@ -60,7 +191,22 @@ pub struct PlayerStore {
     scrobble_time: f64,
     scrobbled: bool,
     is_mobile: bool,
+    duplicate_policy: QueueDuplicatePolicy,
+    radio_mode: bool,
+    /// Scrobbling is suspended during a private session
+    private_session: bool,
     db: Option<Arc<Database>>,
+    /// Payloads written by a newer app, whose unknown fields are kept on save
+    preserved: HashMap<&'static str, serde_json::Value>,
+    restore_warning: Option<String>,
+    /// Edit regions of the loaded track, read from the database on load
+    edit: EditPlayback,
+    /// Trim long silence at the ends of tracks
+    trim_silence: bool,
+    /// Detected silence of the loaded track, by track id
+    silence: Option<(String, TrackSilence)>,
+    /// Position at the last save of the player state
+    position_saved_at: f64,
 }
 
 impl PlayerStore {
@@ -71,7 +217,16 @@ impl PlayerStore {
             scrobble_time: 0f64,
             scrobbled: false,
             is_mobile: false, // Default to false for backend usage
+            duplicate_policy: QueueDuplicatePolicy::default(),
+            radio_mode: false,
+            private_session: false,
             db,
+            preserved: HashMap::new(),
+            restore_warning: None,
+            edit: EditPlayback::default(),
+            trim_silence: false,
+            silence: None,
+            position_saved_at: 0f64,
         };
 
         // 自动从数据库加载状态
@@ -83,45 +238,142 @@ impl PlayerStore {
         player_store
     }
 
-    #[tracing::instrument(level = "debug", skip(self))]
-    fn load_from_db(&mut self) -> Result<()> {
-        if let Some(db) = &self.db {
-            let keys = vec!["player_state", "track_queue", "current_index", "queue_data"];
-            let values = db.get_player_store_values(keys)?;
-            
-            if let Some(player_state_str) = values.get("player_state") {
-                if let Ok(player_details) = serde_json::from_str::<PlayerDetails>(player_state_str) {
-                    self.data.player_details = player_details;
-                    // Reset current_time on load
-                    self.data.player_details.current_time = 0f64;
+    /// Decode persisted `player_store_kv` values written with schema `version`.
+    /// Unreadable payloads and queue entries are skipped rather than failing the
+    /// whole restore.
+    fn decode_persisted(values: &HashMap<String, String>, version: Option<u32>) -> Restored {
+        let mut data = PlayerStoreData::default();
+        let mut unreadable = Vec::new();
+        let mut skipped_entries = 0;
+
+        if let Some(player_state_str) = values.get("player_state") {
+            match serde_json::from_str::<PlayerDetails>(player_state_str) {
+                Ok(player_details) => {
+                    // The position is kept for the resume-on-launch policy
+                    data.player_details = player_details;
                 }
+                Err(_) => unreadable.push("player_state"),
             }
-            
-            if let Some(track_queue_str) = values.get("track_queue") {
-                if let Ok(track_queue) = serde_json::from_str::<Vec<String>>(track_queue_str) {
-                    self.data.queue.track_queue = track_queue;
-                }
+        }
+
+        if let Some(track_queue_str) = values.get("track_queue") {
+            match serde_json::from_str::<Vec<String>>(track_queue_str) {
+                Ok(track_queue) => data.queue.track_queue = track_queue,
+                Err(_) => unreadable.push("track_queue"),
+            }
+        }
+
+        if let Some(current_index_str) = values.get("current_index") {
+            if let Ok(current_index) = serde_json::from_str::<usize>(current_index_str) {
+                data.queue.current_index = current_index;
             }
-            
-            if let Some(current_index_str) = values.get("current_index") {
-                if let Ok(current_index) = serde_json::from_str::<usize>(current_index_str) {
-                    self.data.queue.current_index = current_index;
+        }
+
+        if let Some(queue_data_str) = values.get("queue_data") {
+            match persist::parse_entries::<MediaContent>(queue_data_str) {
+                Some((queue_data, skipped)) => {
+                    data.queue.data = queue_data;
+                    skipped_entries += skipped;
+                    if skipped > 0 {
+                        unreadable.push("queue_data");
+                    }
                 }
+                None => unreadable.push("queue_data"),
             }
-            
-            if let Some(queue_data_str) = values.get("queue_data") {
-                if let Ok(queue_data) = serde_json::from_str::<HashMap<String, MediaContent>>(queue_data_str) {
-                    self.data.queue.data = queue_data;
+        }
+
+        if let Some(overrides_str) = values.get("queue_overrides") {
+            match persist::parse_entries::<QueueItemOverrides>(overrides_str) {
+                Some((overrides, skipped)) => {
+                    data.queue.overrides = overrides;
+                    if skipped > 0 {
+                        unreadable.push("queue_overrides");
+                    }
                 }
+                None => unreadable.push("queue_overrides"),
             }
-            
-            // Update current track based on loaded data
-            if let Some(track_id) = self.data.queue.track_queue.get(self.data.queue.current_index) {
-                self.data.current_track = self.data.queue.data.get(track_id).cloned();
+        }
+
+        if let Some(auto_str) = values.get("queue_auto_generated") {
+            match serde_json::from_str::<HashSet<String>>(auto_str) {
+                Ok(auto_generated) => data.queue.auto_generated = auto_generated,
+                Err(_) => unreadable.push("queue_auto_generated"),
             }
-            
-            tracing::debug!("Loaded player store from database");
         }
+
+        // Drop entries whose data could not be read
+        let queue = &mut data.queue;
+        queue.track_queue.retain(|id| queue.data.contains_key(id));
+        if queue.current_index >= queue.track_queue.len() {
+            queue.current_index = 0;
+        }
+
+        if version.is_some_and(|v| v < 2) && data.queue.migrate_legacy_entries() {
+            tracing::info!("Migrated persisted queue to instance IDs");
+        }
+
+        // Update current track based on loaded data
+        if let Some(track_id) = data.queue.track_queue.get(data.queue.current_index) {
+            data.current_track = data.queue.data.get(track_id).cloned();
+        }
+
+        // No backend has media loaded yet, downgrade persisted active states
+        data.shutdown_state = data.player_details.state;
+        Self::restore_state(&mut data);
+
+        Restored { data, unreadable, skipped_entries }
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    fn load_from_db(&mut self) -> Result<()> {
+        let Some(db) = self.db.clone() else {
+            return Ok(());
+        };
+        let mut keys = PERSISTED_KEYS.to_vec();
+        keys.push(persist::VERSION_KEY);
+        let values = db.get_player_store_values(keys)?;
+        let version = persist::parse_version(values.get(persist::VERSION_KEY).map(String::as_str));
+        let newer = !version.is_some_and(|v| v <= persist::STORE_SCHEMA_VERSION);
+
+        let restored = Self::decode_persisted(&values, version);
+        self.data = restored.data;
+
+        if newer {
+            // Written by a newer app: keep its fields for when it is back
+            self.preserved = PERSISTED_KEYS
+                .iter()
+                .filter_map(|&key| values.get(key).map(|raw| (key, persist::raw_value(raw))))
+                .collect();
+        }
+
+        if !restored.unreadable.is_empty() {
+            // Keep what could not be read before it gets replaced
+            let backups: Vec<(String, String)> = restored
+                .unreadable
+                .iter()
+                .filter_map(|&key| values.get(key).map(|raw| (persist::backup_key(key, version), raw.clone())))
+                .collect();
+            let backup_refs: Vec<(&str, &str)> = backups.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+            if let Err(e) = db.set_player_store_values(backup_refs) {
+                tracing::warn!("Failed to back up unreadable player state: {:?}", e);
+            }
+            tracing::warn!(
+                "Unreadable player state {:?} (schema {:?}, {} queue entries skipped), backed up",
+                restored.unreadable,
+                version,
+                restored.skipped_entries
+            );
+        }
+        self.restore_warning = restore_warning(version, newer, &restored.unreadable, restored.skipped_entries);
+
+        if version != Some(persist::STORE_SCHEMA_VERSION) {
+            // Rewrite everything in this version's schema, newer fields included
+            if let Err(e) = self.save_to_db(&PERSISTED_KEYS) {
+                tracing::warn!("Failed to rewrite player state as schema {}: {:?}", persist::STORE_SCHEMA_VERSION, e);
+            }
+        }
+
+        tracing::debug!("Loaded player store from database");
         Ok(())
     }
 
@@ -129,43 +381,42 @@ impl PlayerStore {
     fn save_to_db(&self, keys: &[&str]) -> Result<()> {
         if let Some(db) = &self.db {
             let mut values = Vec::new();
-            
+
             for &key in keys {
-                match key {
-                    "player_state" => {
-                        let json = serde_json::to_string(&self.data.player_details)
-                            .map_err(|e| types::errors::MusicError::String(format!("Failed to serialize player_details: {}", e)))?;
-                        values.push(("player_state", json));
-                    },
-                    "track_queue" => {
-                        let json = serde_json::to_string(&self.data.queue.track_queue)
-                            .map_err(|e| types::errors::MusicError::String(format!("Failed to serialize track_queue: {}", e)))?;
-                        values.push(("track_queue", json));
-                    },
-                    "current_index" => {
-                        let json = serde_json::to_string(&self.data.queue.current_index)
-                            .map_err(|e| types::errors::MusicError::String(format!("Failed to serialize current_index: {}", e)))?;
-                        values.push(("current_index", json));
-                    },
-                    "queue_data" => {
-                        let json = serde_json::to_string(&self.data.queue.data)
-                            .map_err(|e| types::errors::MusicError::String(format!("Failed to serialize queue_data: {}", e)))?;
-                        values.push(("queue_data", json));
-                    },
+                let value = match key {
+                    "player_state" => serde_json::to_value(&self.data.player_details),
+                    "track_queue" => serde_json::to_value(&self.data.queue.track_queue),
+                    "current_index" => serde_json::to_value(self.data.queue.current_index),
+                    "queue_data" => serde_json::to_value(&self.data.queue.data),
+                    "queue_overrides" => serde_json::to_value(&self.data.queue.overrides),
+                    "queue_auto_generated" => serde_json::to_value(&self.data.queue.auto_generated),
                     _ => continue,
+                };
+                let mut value = value
+                    .map_err(|e| MusicError::String(format!("Failed to serialize {}: {}", key, e)))?;
+                if let Some(stored) = self.preserved.get(key) {
+                    persist::merge_preserved(key, stored, &mut value);
                 }
+                values.push((key, value.to_string()));
             }
-            
+            values.push((persist::VERSION_KEY, persist::STORE_SCHEMA_VERSION.to_string()));
+
             let values_refs: Vec<(&str, &str)> = values.iter()
                 .map(|(k, v)| (*k, v.as_str()))
                 .collect();
-            
+
             db.set_player_store_values(values_refs)?;
             tracing::debug!("Saved player store to database for keys: {:?}", keys);
         }
         Ok(())
     }
 
+    /// Message for the user when the persisted queue could not be restored
+    /// in full, cleared once taken.
+    pub fn take_restore_warning(&mut self) -> Option<String> {
+        self.restore_warning.take()
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub fn get_current_track(&self) -> Option<MediaContent> {
         self.data.current_track.clone()
@@ -216,6 +467,248 @@ impl PlayerStore {
         self.data.player_details.force_seek
     }
 
+    /// Overrides of the entry at the current queue index, if any.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_current_overrides(&self) -> Option<QueueItemOverrides> {
+        let instance_id = self.data.queue.track_queue.get(self.data.queue.current_index)?;
+        self.data.queue.overrides.get(instance_id).copied()
+    }
+
+    /// Start and end positions of the current entry: its overrides, and the
+    /// long silence at the ends of the track when trimming is on and the
+    /// overrides leave them unset
+    pub fn get_current_trim(&self) -> Option<QueueItemOverrides> {
+        let mut trim = self.get_current_overrides().unwrap_or_default();
+        let current = self.data.current_track.as_ref();
+        let silence = self
+            .silence
+            .as_ref()
+            .filter(|(track_id, _)| self.trim_silence && current.and_then(|c| c.track._id.as_ref()) == Some(track_id))
+            .map(|(_, silence)| *silence);
+        if let Some(silence) = silence {
+            if trim.start_offset.is_none() && silence.start >= MIN_SILENCE_TRIM {
+                trim.start_offset = Some(silence.start);
+            }
+            let duration = current.and_then(|c| c.track.duration);
+            if trim.end_at.is_none() && duration.is_some_and(|d| d - silence.end >= MIN_SILENCE_TRIM) {
+                trim.end_at = Some(silence.end);
+            }
+        }
+        Some(trim).filter(|trim| !trim.is_empty())
+    }
+
+    /// Attach overrides to the queue entry at `index`; empty overrides clear them.
+    /// A start offset takes effect the next time the entry is loaded, an end
+    /// position applies immediately.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_queue_item_overrides(&mut self, index: usize, overrides: QueueItemOverrides) -> Result<()> {
+        overrides.validate()?;
+        let instance_id = self
+            .data
+            .queue
+            .track_queue
+            .get(index)
+            .cloned()
+            .ok_or_else(|| MusicError::String(format!("No queue entry at index {}", index)))?;
+        if overrides.is_empty() {
+            self.data.queue.overrides.remove(&instance_id);
+        } else {
+            self.data.queue.overrides.insert(instance_id, overrides);
+        }
+        if index == self.data.queue.current_index {
+            self.data.end_trim_reached = false;
+        }
+        self.save_to_db(&["queue_overrides"])
+    }
+
+    /// Load the edit regions of `track_id`, about to be played. Tracks without
+    /// an id (or without a database) play unedited.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn load_edit_regions(&mut self, track_id: Option<&str>) {
+        let regions = match (&self.db, track_id) {
+            (Some(db), Some(track_id)) => db.get_edit_regions(track_id).unwrap_or_else(|e| {
+                tracing::warn!("Failed to read edit regions of {}: {:?}", track_id, e);
+                Vec::new()
+            }),
+            _ => Vec::new(),
+        };
+        self.edit = EditPlayback::new(EditRegions::new(&regions));
+    }
+
+    /// Load the detected silence of `track_id`, about to be played
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn load_silence(&mut self, track_id: Option<&str>) {
+        self.silence = match (&self.db, track_id) {
+            (Some(db), Some(track_id)) => db
+                .get_track_silence(track_id)
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Failed to read the silence of {}: {:?}", track_id, e);
+                    None
+                })
+                .map(|silence| (track_id.to_string(), silence)),
+            _ => None,
+        };
+    }
+
+    /// Loudness analysis of `track_id` cached in the database, `None` when it
+    /// was never read or the track has no ReplayGain tags
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn read_track_gain(&self, track_id: Option<&str>) -> Option<TrackGain> {
+        let (Some(db), Some(track_id)) = (&self.db, track_id) else {
+            return None;
+        };
+        db.get_track_gain(track_id).unwrap_or_else(|e| {
+            tracing::warn!("Failed to read the gain of {}: {:?}", track_id, e);
+            None
+        })
+    }
+
+    /// Edit regions of the loaded track
+    pub fn edit_regions(&self) -> &EditRegions {
+        self.edit.regions()
+    }
+
+    /// Skip or mute due at the current position, if any
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn take_edit_action(&mut self) -> Option<EditAction> {
+        let duration = self.data.current_track.as_ref().and_then(|t| t.track.duration);
+        self.edit.on_time(self.data.player_details.current_time, duration)
+    }
+
+    /// Duration of the current track with its skip regions removed, `None`
+    /// when it has none
+    pub fn edited_duration(&self) -> Option<f64> {
+        let regions = self.edit.regions();
+        if !regions.has_skips() {
+            return None;
+        }
+        let duration = self.data.current_track.as_ref().and_then(|t| t.track.duration)?;
+        Some(regions.to_edited(duration))
+    }
+
+    /// Returns true exactly once when playback of the current entry passes its
+    /// end position. The caller then finishes the entry as if it had ended.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn take_end_trim(&mut self) -> bool {
+        if self.data.end_trim_reached {
+            return false;
+        }
+        let end_at = self.get_current_trim().and_then(|o| o.end_at);
+        match end_at {
+            Some(end_at) if self.data.player_details.current_time >= end_at => {
+                self.data.end_trim_reached = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the current entry was cut short by its end position.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn is_end_trimmed(&self) -> bool {
+        self.data.end_trim_reached
+    }
+
+    /// Returns true exactly once when the current entry is within `lead` seconds
+    /// of its end (end position or duration) and playback continues with another
+    /// entry of the same track type. The caller then finishes the entry early and
+    /// the next load crossfades into the following one.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn take_crossfade_start(&mut self, lead: f64) -> bool {
+        if lead <= 0.0
+            || self.data.crossfade_pending
+            || self.data.end_trim_reached
+            || self.data.player_details.state != PlayerState::Playing
+        {
+            return false;
+        }
+        let Some(current) = self.data.current_track.clone() else {
+            return false;
+        };
+        let end = self
+            .get_current_trim()
+            .and_then(|o| o.end_at)
+            .or(current.track.duration)
+            .filter(|end| *end > lead);
+        let Some(end) = end else {
+            return false;
+        };
+        if self.data.player_details.current_time < end - lead {
+            return false;
+        }
+        // Different track types are played by different backends, which cannot mix
+        let same_backend = self
+            .peek_following_entry()
+            .is_some_and(|next| next.track.type_ == current.track.type_);
+        if same_backend {
+            self.data.crossfade_pending = true;
+        }
+        same_backend
+    }
+
+    /// Whether the next load should crossfade; clears the flag.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn take_crossfade_pending(&mut self) -> bool {
+        std::mem::take(&mut self.data.crossfade_pending)
+    }
+
+    /// Entry that will play when the current one ends, without advancing.
+    /// Repeating a single entry has no following entry.
+    fn peek_following_entry(&mut self) -> Option<MediaContent> {
+        let len = self.data.queue.track_queue.len();
+        let current = self.data.queue.current_index;
+        let next_index = match self.get_repeat() {
+            PlayerMode::Sequential => Some(current + 1).filter(|i| *i < len),
+            PlayerMode::ListLoop => Some((current + 1) % len.max(1)).filter(|_| len > 1),
+            PlayerMode::Shuffle | PlayerMode::ShuffleAlbums => {
+                if self.data.shuffle_index >= self.data.shuffle_bag.len() {
+                    self.rebuild_shuffle_bag();
+                }
+                self.data.shuffle_bag.get(self.data.shuffle_index).copied()
+            }
+            PlayerMode::Single => None,
+        }?;
+        let instance_id = self.data.queue.track_queue.get(next_index)?;
+        self.data.queue.data.get(instance_id).cloned()
+    }
+
+    /// Remaining play time of the queue as of `now_ms` (milliseconds since the
+    /// Unix epoch): the rest of the current entry, then the upcoming entries in
+    /// the order they will play
+    pub fn queue_metrics(&self, timing: &QueueTiming, now_ms: i64) -> QueueMetrics {
+        let queue = &self.data.queue;
+        let len = queue.track_queue.len();
+        let current_index = queue.current_index;
+
+        let current = self.data.current_track.as_ref().map(|track| {
+            let end = self.get_current_trim().and_then(|o| o.end_at).or(track.track.duration);
+            let regions = self.edit.regions();
+            let now = self.data.player_details.current_time;
+            CurrentEntry {
+                track,
+                remaining: end.map(|end| regions.to_edited(end) - regions.to_edited(now.min(end))),
+            }
+        });
+
+        // Upcoming queue indices; looping modes only count the current pass
+        let (upcoming, repeats): (Vec<usize>, bool) = match self.get_repeat() {
+            PlayerMode::Sequential => ((current_index + 1..len).collect(), false),
+            PlayerMode::ListLoop => ((current_index + 1..len).collect(), true),
+            PlayerMode::Shuffle | PlayerMode::ShuffleAlbums => (
+                self.data.shuffle_bag.get(self.data.shuffle_index..).unwrap_or_default().to_vec(),
+                true,
+            ),
+            PlayerMode::Single => (Vec::new(), true),
+        };
+        let upcoming = upcoming.into_iter().filter_map(|index| {
+            let instance_id = queue.track_queue.get(index)?;
+            let track = queue.data.get(instance_id)?;
+            Some((instance_id.as_str(), track, queue.overrides.get(instance_id)))
+        });
+
+        queue_metrics::compute(current, upcoming, timing, repeats, now_ms)
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub fn get_current_time(&self) -> f64 {
         self.data.player_details.current_time
@@ -234,6 +727,7 @@ impl PlayerStore {
 
         // TODO: Record play statistics for the last track if it played for more than 30 seconds
         self.data.player_details.current_time = 0f64;
+        self.data.end_trim_reached = false;
         set_position(self.data.player_details.current_time);
 
         if self.data.queue.current_index >= self.data.queue.track_queue.len() {
@@ -277,31 +771,191 @@ impl PlayerStore {
         self.scrobble_time = 0f64;
         self.scrobbled = false;
 
+        self.reconcile_state();
         let _ = self.save_to_db(&["current_index", "player_state"]);
     }
 
 
 
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_duplicate_policy(&self) -> QueueDuplicatePolicy {
+        self.duplicate_policy
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_duplicate_policy(&mut self, policy: QueueDuplicatePolicy) {
+        self.duplicate_policy = policy;
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn is_radio_mode(&self) -> bool {
+        self.radio_mode
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_radio_mode(&mut self, enabled: bool) {
+        self.radio_mode = enabled;
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_trim_silence(&mut self, enabled: bool) {
+        self.trim_silence = enabled;
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_private_session(&mut self, enabled: bool) {
+        self.private_session = enabled;
+    }
+
+    /// Whether radio mode should extend the queue: sequential playback stopped
+    /// after the last entry.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn needs_radio_extension(&self) -> bool {
+        let len = self.data.queue.track_queue.len();
+        self.radio_mode
+            && len > 0
+            && self.data.player_details.repeat == PlayerMode::Sequential
+            && self.data.queue.current_index + 1 >= len
+    }
+
+    /// Up to `depth` queue entries following the current one that continue its
+    /// album in track order. Empty unless playback walks the queue in order
+    /// (sequential or list loop), and cut at the first entry from another
+    /// album or out of order.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn album_lookahead(&self, depth: usize) -> Vec<MediaContent> {
+        let in_order = matches!(
+            self.data.player_details.repeat,
+            PlayerMode::Sequential | PlayerMode::ListLoop
+        );
+        let Some(current) = self.data.current_track.as_ref().filter(|_| in_order && depth > 0) else {
+            return Vec::new();
+        };
+        let album_of = |t: &MediaContent| t.album.as_ref().and_then(|a| a.album_id.clone());
+        let Some(album_id) = album_of(current) else {
+            return Vec::new();
+        };
+
+        let mut last_no = current.track.track_no;
+        self.data
+            .queue
+            .track_queue
+            .iter()
+            .skip(self.data.queue.current_index + 1)
+            .map_while(|instance_id| {
+                let entry = self.data.queue.data.get(instance_id)?;
+                if album_of(entry).as_ref() != Some(&album_id) {
+                    return None;
+                }
+                if let (Some(last), Some(no)) = (last_no, entry.track.track_no) {
+                    if no <= last {
+                        return None;
+                    }
+                }
+                last_no = entry.track.track_no.or(last_no);
+                Some(entry.clone())
+            })
+            .take(depth)
+            .collect()
+    }
+
+    /// Append tracks suggested by radio mode, skipping tracks already queued,
+    /// and mark them as auto-generated. Returns the new instance IDs.
+    #[tracing::instrument(level = "debug", skip(self, tracks))]
+    pub fn append_radio_tracks(&mut self, tracks: Vec<MediaContent>) -> Vec<String> {
+        let mut added = Vec::new();
+        for track in tracks {
+            if track.track._id.is_none() {
+                continue;
+            }
+            let index = self.data.queue.track_queue.len();
+            if self.insert_track_at_index(track, index, false, false) {
+                let instance_id = self.data.queue.track_queue[index].clone();
+                self.data.queue.auto_generated.insert(instance_id.clone());
+                added.push(instance_id);
+            }
+        }
+        if !added.is_empty() {
+            let _ = self.save_to_db(&["queue_data", "track_queue", "queue_auto_generated"]);
+        }
+        added
+    }
+
+    /// Append tracks to the queue according to the duplicate policy.
+    /// Returns the duplicates that were held back because the policy is `Ask`;
+    /// the caller can confirm them with `add_to_queue_allow_duplicates()`.
+    #[tracing::instrument(level = "debug", skip(self, tracks))]
+    pub fn add_to_queue(&mut self, tracks: Vec<MediaContent>) -> Vec<MediaContent> {
+        let pending = self.add_to_queue_at_index(tracks, self.data.queue.track_queue.len(), false);
+        self.update_current_track(false);
+        pending
+    }
+
+    /// Append tracks, inserting duplicates regardless of the configured policy.
     #[tracing::instrument(level = "debug", skip(self, tracks))]
-    pub fn add_to_queue(&mut self, tracks: Vec<MediaContent>) {
-        self.add_to_queue_at_index(tracks, self.data.queue.track_queue.len());
+    pub fn add_to_queue_allow_duplicates(&mut self, tracks: Vec<MediaContent>) {
+        self.add_to_queue_at_index(tracks, self.data.queue.track_queue.len(), true);
         self.update_current_track(false);
     }
 
+    /// Insert tracks in order at `index` (clamped to the queue) according to
+    /// the duplicate policy, unless `allow_duplicates`. Returns the duplicates
+    /// held back because the policy is `Ask`.
+    #[tracing::instrument(level = "debug", skip(self, tracks))]
+    pub fn add_to_queue_at(&mut self, tracks: Vec<MediaContent>, index: usize, allow_duplicates: bool) -> Vec<MediaContent> {
+        let had_current = self.data.current_track.is_some();
+        let pending = self.add_to_queue_at_index(tracks, index, allow_duplicates);
+        if !had_current {
+            self.update_current_track(false);
+        }
+        pending
+    }
+
     #[tracing::instrument(level = "debug", skip(self, tracks, index))]
-    fn add_to_queue_at_index(&mut self, tracks: Vec<MediaContent>, index: usize) {
+    fn add_to_queue_at_index(
+        &mut self,
+        tracks: Vec<MediaContent>,
+        index: usize,
+        force_duplicates: bool,
+    ) -> Vec<MediaContent> {
         let mut index = index;
+        let mut pending = Vec::new();
         for track in tracks {
-            self.insert_track_at_index(track, index, false);
-            index += 1;
+            let allow = force_duplicates || self.duplicate_policy == QueueDuplicatePolicy::Allow;
+            let is_duplicate = track
+                .track
+                ._id
+                .as_deref()
+                .and_then(|id| self.data.queue.position_of_track(id))
+                .is_some();
+            if is_duplicate && !allow && self.duplicate_policy == QueueDuplicatePolicy::Ask {
+                pending.push(track);
+                continue;
+            }
+            let had_entries = !self.data.queue.track_queue.is_empty();
+            if self.insert_track_at_index(track, index, false, allow) {
+                // Entries from `index` on moved one further
+                let queue = &mut self.data.queue;
+                if had_entries && index <= queue.current_index {
+                    queue.current_index += 1;
+                }
+                for bagged in self.data.shuffle_bag.iter_mut().filter(|i| **i >= index) {
+                    *bagged += 1;
+                }
+                index += 1;
+            }
         }
 
-        let _ = self.save_to_db(&["queue_data", "track_queue"]);
+        let _ = self.save_to_db(&["queue_data", "track_queue", "current_index"]);
+        pending
     }
 
     #[tracing::instrument(level = "debug", skip(self, index))]
     pub fn remove_from_queue(&mut self, index: usize) {
-        self.data.queue.track_queue.remove(index);
+        let instance_id = self.data.queue.track_queue.remove(index);
+        self.data.queue.data.remove(&instance_id);
+        self.data.queue.overrides.remove(&instance_id);
+        self.data.queue.auto_generated.remove(&instance_id);
         if self.data.queue.current_index > index {
             self.data.queue.current_index -= 1;
         }
@@ -310,56 +964,96 @@ impl PlayerStore {
             self.update_current_track(false);
         }
 
-        let _ = self.save_to_db(&["track_queue", "queue_data"]);
+        self.reconcile_state();
+        let _ = self.save_to_db(&["track_queue", "queue_data", "queue_overrides", "queue_auto_generated"]);
     }
 
+    /// Move the queue entry at `from` so it ends up at `to`. The current entry
+    /// stays current and the remaining shuffle order is kept.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn move_in_queue(&mut self, from: usize, to: usize) -> Result<()> {
+        let len = self.data.queue.track_queue.len();
+        if from >= len || to >= len {
+            return Err(MusicError::String(format!("Queue index out of range: {} -> {} of {}", from, to, len)));
+        }
+        if from == to {
+            return Ok(());
+        }
+        let instance_id = self.data.queue.track_queue.remove(from);
+        self.data.queue.track_queue.insert(to, instance_id);
+
+        // Where the entry at `index` went
+        let moved = |index: usize| {
+            if index == from {
+                to
+            } else if from < index && index <= to {
+                index - 1
+            } else if to <= index && index < from {
+                index + 1
+            } else {
+                index
+            }
+        };
+        self.data.queue.current_index = moved(self.data.queue.current_index);
+        for index in self.data.shuffle_bag.iter_mut() {
+            *index = moved(*index);
+        }
+
+        self.save_to_db(&["track_queue", "current_index"])
+    }
+
+    /// Insert a track at `index` as a new queue instance.
+    /// When `allow_duplicate` is false and the track is already queued, only its
+    /// metadata is refreshed. Returns whether a new entry was inserted.
     #[tracing::instrument(level = "debug", skip(self, track, index))]
-    fn insert_track_at_index(&mut self, track: MediaContent, index: usize, dump: bool) {
+    fn insert_track_at_index(&mut self, track: MediaContent, index: usize, dump: bool, allow_duplicate: bool) -> bool {
         let track_id = track.track._id.clone().unwrap();
-        // Update metadata in data map
-        self.data.queue.data.insert(track_id.clone(), track);
 
-        // Skip insertion if track already exists in queue (avoid duplicates)
-        if self.data.queue.track_queue.contains(&track_id) {
+        if !allow_duplicate && self.data.queue.position_of_track(&track_id).is_some() {
+            // Refresh metadata of every existing instance of this track
+            for entry in self.data.queue.data.values_mut() {
+                if entry.track._id.as_deref() == Some(track_id.as_str()) {
+                    *entry = track.clone();
+                }
+            }
             if dump {
                 // Persist metadata changes if any
                 let _ = self.save_to_db(&["queue_data"]);
             }
-            return;
+            return false;
         }
 
+        let instance_id = self.data.queue.next_instance_id(&track_id);
+        self.data.queue.data.insert(instance_id.clone(), track);
         let insertion_index = min(self.data.queue.track_queue.len(), index);
-        self.data.queue.track_queue.insert(insertion_index, track_id);
+        self.data.queue.track_queue.insert(insertion_index, instance_id);
 
         if dump {
             let _ = self.save_to_db(&["queue_data", "track_queue"]);
         }
+        true
     }
 
     #[tracing::instrument(level = "debug", skip(self, track))]
     pub fn play_now(&mut self, track: MediaContent) {
-        self.set_state(PlayerState::Playing);
         let track_id = track.track._id.clone().unwrap();
 
         // If track already exists in queue, jump to it instead of inserting duplicate
-        if let Some(existing_index) = self
-            .data
-            .queue
-            .track_queue
-            .iter()
-            .position(|id| id == &track_id)
-        {
-            self.data.queue.data.insert(track_id.clone(), track); // refresh metadata
+        if let Some(existing_index) = self.data.queue.position_of_track(&track_id) {
+            let instance_id = self.data.queue.track_queue[existing_index].clone();
+            self.data.queue.data.insert(instance_id, track); // refresh metadata
             self.data.queue.current_index = existing_index;
             self.update_current_track(true);
+            self.set_state(PlayerState::Loading);
             let _ = self.save_to_db(&["current_index", "queue_data"]);
             return;
         }
 
         // Otherwise insert after current and advance index
-        self.insert_track_at_index(track, self.data.queue.current_index + 1, true);
+        self.insert_track_at_index(track, self.data.queue.current_index + 1, true, true);
         self.data.queue.current_index += 1;
         self.update_current_track(true);
+        self.set_state(PlayerState::Loading);
     }
 
     #[tracing::instrument(level = "debug", skip(self, tracks))]
@@ -374,29 +1068,25 @@ impl PlayerStore {
         }
 
         if tracks.len() > 1 {
-            self.add_to_queue_at_index(tracks[1..].to_vec(), self.data.queue.current_index + 1);
+            self.add_to_queue_at_index(tracks[1..].to_vec(), self.data.queue.current_index + 1, false);
         }
     }
 
     #[tracing::instrument(level = "debug", skip(self, track))]
     pub fn play_next(&mut self, track: MediaContent) {
-        self.insert_track_at_index(track, self.data.queue.current_index + 1, true);
+        let allow = self.duplicate_policy == QueueDuplicatePolicy::Allow;
+        self.insert_track_at_index(track, self.data.queue.current_index + 1, true, allow);
     }
 
+    /// Insert tracks right after the current one, keeping their order. Returns
+    /// the duplicates held back because the policy is `Ask`.
     #[tracing::instrument(level = "debug", skip(self, tracks))]
-    pub fn play_next_multiple(&mut self, tracks: Vec<MediaContent>) {
-        if tracks.is_empty() {
-            return;
-        }
-
-        let first_track = tracks.first();
-        if let Some(first_track) = first_track {
-            self.play_next(first_track.clone())
-        }
-
-        if tracks.len() > 1 {
-            self.add_to_queue_at_index(tracks[1..].to_vec(), self.data.queue.current_index + 1);
-        }
+    pub fn play_next_multiple(&mut self, tracks: Vec<MediaContent>, allow_duplicates: bool) -> Vec<MediaContent> {
+        let index = match self.data.queue.track_queue.is_empty() {
+            true => 0,
+            false => self.data.queue.current_index + 1,
+        };
+        self.add_to_queue_at(tracks, index, allow_duplicates)
     }
 
     #[tracing::instrument(level = "debug", skip(self, new_index))]
@@ -409,8 +1099,12 @@ impl PlayerStore {
     pub fn update_time(&mut self, new_time: f64) {
         self.scrobble_time += 0f64.max(new_time - self.data.player_details.current_time);
         self.data.player_details.current_time = new_time;
+        if (new_time - self.position_saved_at).abs() >= POSITION_SAVE_INTERVAL {
+            self.position_saved_at = new_time;
+            let _ = self.save_to_db(&["player_state"]);
+        }
 
-        if self.scrobble_time > 20f64 && !self.scrobbled {
+        if self.scrobble_time > 20f64 && !self.scrobbled && !self.private_session {
             if let Some(_current_track) = self.get_current_track() {
                 self.scrobbled = true;
                 // send_extension_event(ExtensionExtraEvent::Scrobble([current_track]));
@@ -449,9 +1143,51 @@ impl PlayerStore {
         // send_extension_event(ExtensionExtraEvent::Seeked([new_time]))
     }
 
+    /// Facts about the store used to guard state transitions
+    pub fn transition_context(&self) -> TransitionContext {
+        TransitionContext {
+            has_track: self.data.current_track.is_some(),
+            queue_len: self.data.queue.track_queue.len(),
+        }
+    }
+
+    /// Single entry point for playback state changes.
+    /// Rejects transitions that are not edges of the state machine or that would
+    /// violate store invariants (e.g. Playing without a current track).
+    #[tracing::instrument(level = "debug", skip(self, state))]
+    pub fn transition(&mut self, state: PlayerState) -> Result<PlayerState> {
+        let from = self.data.player_details.state;
+        state_machine::check_transition(from, state, self.transition_context())
+            .map_err(types::errors::MusicError::String)?;
+        if from != state {
+            tracing::debug!("Player state {:?} -> {:?}", from, state);
+            self.apply_state(state);
+        }
+        Ok(state)
+    }
+
+    /// Lenient variant of `transition()` for event-driven callers: illegal
+    /// transitions are logged and dropped instead of being returned as errors.
     #[tracing::instrument(level = "debug", skip(self, state))]
     pub fn set_state(&mut self, state: PlayerState) {
-        tracing::debug!("Setting player state {:?}", state);
+        if let Err(e) = self.transition(state) {
+            tracing::warn!("Ignoring player state change: {}", e);
+        }
+    }
+
+    /// Force the state back into a consistent value after queue/track mutations.
+    #[tracing::instrument(level = "debug", skip(self))]
+    fn reconcile_state(&mut self) {
+        let current = self.data.player_details.state;
+        let reconciled = state_machine::reconcile(current, self.transition_context());
+        if reconciled != current {
+            tracing::debug!("Reconciled player state {:?} -> {:?}", current, reconciled);
+            self.apply_state(reconciled);
+        }
+    }
+
+    /// The only place that writes `player_details.state` after load.
+    fn apply_state(&mut self, state: PlayerState) {
         self.data.player_details.state = state;
         let _ = self.save_to_db(&["player_state"]);
 
@@ -459,6 +1195,41 @@ impl PlayerStore {
         // send_extension_event(ExtensionExtraEvent::PlayerStateChanged([state]))
     }
 
+    /// Save the playback position now rather than at the next interval, so
+    /// the next launch resumes exactly where playback stands
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn save_position(&mut self) {
+        self.position_saved_at = self.data.player_details.current_time;
+        if let Err(e) = self.save_to_db(&["player_state"]) {
+            tracing::warn!("Failed to save the playback position: {:?}", e);
+        }
+    }
+
+    /// Apply the resume-on-launch policy to the restored queue. `Never` rewinds
+    /// the current track; playing resumes only if the app was playing when it
+    /// was closed.
+    pub fn launch_resume(&mut self, policy: ResumeOnLaunch) -> LaunchResume {
+        if self.data.current_track.is_none() || policy == ResumeOnLaunch::Never {
+            self.data.player_details.current_time = 0f64;
+            return LaunchResume { position: 0f64, play: false };
+        }
+        let was_playing = matches!(self.data.shutdown_state, PlayerState::Playing | PlayerState::Loading);
+        LaunchResume {
+            position: self.data.player_details.current_time,
+            play: policy == ResumeOnLaunch::Playing && was_playing,
+        }
+    }
+
+    /// Sanitize a freshly loaded state (no backend has media loaded at startup)
+    fn restore_state(data: &mut PlayerStoreData) {
+        let ctx = TransitionContext {
+            has_track: data.current_track.is_some(),
+            queue_len: data.queue.track_queue.len(),
+        };
+        data.player_details.state =
+            state_machine::restore_state(data.player_details.state, ctx);
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     fn get_track_key(&self) -> String {
         if let Some(current_track) = &self.data.current_track {
@@ -541,6 +1312,48 @@ impl PlayerStore {
         self.data.player_details.volume
     }
 
+    pub fn get_volume_mode(&self) -> VolumeMode {
+        self.data.player_details.volume_mode
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_volume_mode(&mut self, mode: VolumeMode) {
+        self.data.player_details.volume_mode = mode;
+        let _ = self.save_to_db(&["player_state"]);
+    }
+
+    /// Source of the current track, as the per-source volumes and clamps are
+    /// keyed: its provider, or its type for local tracks. `None` when nothing
+    /// is loaded.
+    pub fn current_volume_source(&self) -> Option<String> {
+        Some(self.get_track_key()).filter(|key| !key.is_empty())
+    }
+
+    /// Cap the volume (0 - 100) of tracks from `source` in clamp mode;
+    /// `None` removes the cap.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_volume_clamp(&mut self, source: String, clamp: Option<f64>) {
+        let clamps = &mut self.data.player_details.clamp_map;
+        match clamp {
+            Some(clamp) => {
+                clamps.insert(source, clamp.clamp(0f64, 100f64));
+            }
+            None => {
+                clamps.remove(&source);
+            }
+        }
+        let _ = self.save_to_db(&["player_state"]);
+    }
+
+    /// Cap of the current source when in clamp mode
+    pub fn get_volume_clamp(&self) -> Option<f64> {
+        if self.data.player_details.volume_mode != VolumeMode::PersistClamp {
+            return None;
+        }
+        let source = self.current_volume_source()?;
+        self.data.player_details.clamp_map.get(&source).copied()
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub fn get_queue_tracks(&self) -> Vec<MediaContent> {
         self.data
@@ -577,19 +1390,36 @@ impl PlayerStore {
         self.update_current_track(false);
     }
 
+    /// Queue index of the station after (or before) the current one, wrapping
+    /// around, when a radio station is playing. Next/previous switch stations
+    /// instead of stepping through the tracks in between.
+    pub fn adjacent_station(&self, forward: bool) -> Option<usize> {
+        let queue = &self.data.queue.track_queue;
+        let current = self.data.queue.current_index;
+        let is_station = |id: &String| id.starts_with(STATION_ID_PREFIX);
+        if !queue.get(current).is_some_and(is_station) {
+            return None;
+        }
+        let len = queue.len();
+        (1..len)
+            .map(|step| if forward { (current + step) % len } else { (current + len - step) % len })
+            .find(|&index| is_station(&queue[index]))
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub fn toggle_player_mode(&mut self) {
         let new_mode = match self.data.player_details.repeat {
             PlayerMode::Sequential => PlayerMode::Single,
             PlayerMode::Single => PlayerMode::Shuffle,
-            PlayerMode::Shuffle => PlayerMode::ListLoop,
+            PlayerMode::Shuffle => PlayerMode::ShuffleAlbums,
+            PlayerMode::ShuffleAlbums => PlayerMode::ListLoop,
             PlayerMode::ListLoop => PlayerMode::Sequential,
         };
 
         self.data.player_details.repeat = new_mode;
         
         // Initialize shuffle bag when switching to shuffle mode
-        if new_mode == PlayerMode::Shuffle {
+        if new_mode.is_shuffle() {
             self.rebuild_shuffle_bag();
         }
         
@@ -603,14 +1433,16 @@ impl PlayerStore {
         self.data.player_details.repeat = mode;
         self.set_has_repeated(false);
 
-        if mode == PlayerMode::Shuffle {
+        if mode.is_shuffle() {
             self.rebuild_shuffle_bag();
         }
 
         let _ = self.save_to_db(&["player_state"]);
     }
 
-    /// Rebuild shuffle bag with all queue indices except current
+    /// Rebuild shuffle bag with all queue indices except current. Shuffling
+    /// by album, the bag holds the rest of the current album, then the other
+    /// albums in random order, each in queue order.
     #[tracing::instrument(level = "debug", skip(self))]
     pub fn rebuild_shuffle_bag(&mut self) {
         let queue_len = self.data.queue.track_queue.len();
@@ -620,14 +1452,19 @@ impl PlayerStore {
             return;
         }
 
-        // Create indices excluding current index
-        let mut indices: Vec<usize> = (0..queue_len)
-            .filter(|&i| i != self.data.queue.current_index)
-            .collect();
-        
-        // Shuffle the indices
         let mut rng = thread_rng();
-        indices.shuffle(&mut rng);
+        let indices = if self.data.player_details.repeat == PlayerMode::ShuffleAlbums {
+            self.album_shuffle_bag(&mut rng)
+        } else {
+            // Create indices excluding current index
+            let mut indices: Vec<usize> = (0..queue_len)
+                .filter(|&i| i != self.data.queue.current_index)
+                .collect();
+
+            // Shuffle the indices
+            indices.shuffle(&mut rng);
+            indices
+        };
         
         self.data.shuffle_bag = indices;
         self.data.shuffle_index = 0;
@@ -635,6 +1472,49 @@ impl PlayerStore {
         tracing::debug!("Rebuilt shuffle bag with {} indices", self.data.shuffle_bag.len());
     }
 
+    /// Queue indices grouped by album in queue order, groups in order of first
+    /// appearance. Entries without an album are a group of their own.
+    fn album_groups(&self) -> Vec<Vec<usize>> {
+        let queue = &self.data.queue;
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        let mut by_album: HashMap<String, usize> = HashMap::new();
+        for (index, instance_id) in queue.track_queue.iter().enumerate() {
+            let album = queue
+                .data
+                .get(instance_id)
+                .and_then(|t| t.album.as_ref())
+                .and_then(|a| a.album_id.clone().or_else(|| a.album_name.clone()));
+            match album.as_ref().and_then(|album| by_album.get(album)) {
+                Some(&group) => groups[group].push(index),
+                None => {
+                    if let Some(album) = album {
+                        by_album.insert(album, groups.len());
+                    }
+                    groups.push(vec![index]);
+                }
+            }
+        }
+        groups
+    }
+
+    /// Shuffle bag playing the current album out before the others. Once it
+    /// has ended, it only comes around again when it is the only album.
+    fn album_shuffle_bag(&self, rng: &mut impl rand::Rng) -> Vec<usize> {
+        let current = self.data.queue.current_index;
+        let mut groups = self.album_groups();
+        let mut bag: Vec<usize> = Vec::new();
+        if let Some(position) = groups.iter().position(|g| g.contains(&current)) {
+            let group = groups.remove(position);
+            bag.extend(group.iter().copied().skip_while(|&i| i != current).skip(1));
+            if groups.is_empty() && bag.is_empty() {
+                bag.extend(group.into_iter().filter(|&i| i != current));
+            }
+        }
+        groups.shuffle(rng);
+        bag.extend(groups.into_iter().flatten());
+        bag
+    }
+
     /// Get next index from shuffle bag, rebuild if exhausted
     #[tracing::instrument(level = "debug", skip(self))]
     pub fn get_next_shuffle_index(&mut self) -> Option<usize> {
@@ -669,29 +1549,81 @@ impl PlayerStore {
         let _ = self.save_to_db(&["current_index", "track_queue"]);
     }
 
+    /// Name shown for the queue in the history: the album when every entry
+    /// is from the same one, the first track otherwise
+    fn queue_name(tracks: &[&MediaContent]) -> String {
+        let album = |t: &MediaContent| t.album.as_ref().and_then(|a| a.album_name.clone());
+        let first = tracks.first().copied();
+        if let Some(name) = first.and_then(album) {
+            if tracks.iter().all(|t| album(t).as_deref() == Some(name.as_str())) {
+                return name;
+            }
+        }
+        let title = first.and_then(|t| t.track.title.clone()).unwrap_or_default();
+        match tracks.len() {
+            0 | 1 => title,
+            n => format!("{} and {} more", title, n - 1),
+        }
+    }
+
+    /// Keep the queue about to be replaced in the history, without the
+    /// entries radio mode appended. Nothing is kept during a private session.
+    fn archive_queue(&self) {
+        let Some(db) = &self.db else { return };
+        if self.private_session {
+            return;
+        }
+        let queue = &self.data.queue;
+        let tracks: Vec<&MediaContent> = queue
+            .track_queue
+            .iter()
+            .filter(|id| !queue.auto_generated.contains(*id))
+            .filter_map(|id| queue.data.get(id))
+            .collect();
+        let track_ids: Vec<String> = tracks.iter().filter_map(|t| t.track._id.clone()).collect();
+        if let Err(e) = db.record_queue_snapshot(&Self::queue_name(&tracks), &track_ids) {
+            tracing::warn!("Failed to keep the queue in the history: {:?}", e);
+        }
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub fn clear_queue(&mut self) {
+        self.archive_queue();
         self.data.queue.track_queue.clear();
+        self.data.queue.data.clear();
+        self.data.queue.overrides.clear();
+        self.data.queue.auto_generated.clear();
         self.data.queue.current_index = 0;
         self.update_current_track(false);
+        let _ = self.save_to_db(&["queue_auto_generated"]);
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
     pub fn clear_queue_except_current(&mut self) {
         let current_track = self.get_current_track();
+        let current_overrides = self.get_current_overrides();
 
         let only_one_track = self.get_queue().track_queue.len() == 1;
+        if !only_one_track {
+            self.archive_queue();
+        }
         self.data.queue.track_queue.clear();
+        self.data.queue.data.clear();
+        self.data.queue.overrides.clear();
+        self.data.queue.auto_generated.clear();
         self.data.queue.current_index = 0;
 
         if !only_one_track {
             if let Some(current_track) = current_track {
-                self.add_to_queue(vec![current_track]);
+                self.add_to_queue_allow_duplicates(vec![current_track]);
+                if let (Some(overrides), Some(instance_id)) = (current_overrides, self.data.queue.track_queue.first()) {
+                    self.data.queue.overrides.insert(instance_id.clone(), overrides);
+                }
             }
         }
 
         self.update_current_track(false);
-        let _ = self.save_to_db(&["queue_data", "track_queue"]);
+        let _ = self.save_to_db(&["queue_data", "track_queue", "queue_overrides", "queue_auto_generated"]);
     }
 
     #[tracing::instrument(level = "debug", skip(self, key))]
@@ -719,44 +1651,15 @@ impl PlayerStore {
 
     /// Static method to load state from database
     pub fn load_state_from_db(db: &Database) -> Option<PlayerStoreData> {
-        let keys = vec!["player_state", "track_queue", "current_index", "queue_data"];
-        
+        let mut keys = PERSISTED_KEYS.to_vec();
+        keys.push(persist::VERSION_KEY);
+
         match db.get_player_store_values(keys) {
             Ok(values) => {
-                let mut data = PlayerStoreData::default();
-                
-                if let Some(player_state_str) = values.get("player_state") {
-                    if let Ok(player_details) = serde_json::from_str::<PlayerDetails>(player_state_str) {
-                        data.player_details = player_details;
-                        data.player_details.current_time = 0f64; // Reset current_time on load
-                    }
-                }
-                
-                if let Some(track_queue_str) = values.get("track_queue") {
-                    if let Ok(track_queue) = serde_json::from_str::<Vec<String>>(track_queue_str) {
-                        data.queue.track_queue = track_queue;
-                    }
-                }
-                
-                if let Some(current_index_str) = values.get("current_index") {
-                    if let Ok(current_index) = serde_json::from_str::<usize>(current_index_str) {
-                        data.queue.current_index = current_index;
-                    }
-                }
-                
-                if let Some(queue_data_str) = values.get("queue_data") {
-                    if let Ok(queue_data) = serde_json::from_str::<HashMap<String, MediaContent>>(queue_data_str) {
-                        data.queue.data = queue_data;
-                    }
-                }
-                
-                // Update current track based on loaded data
-                if let Some(track_id) = data.queue.track_queue.get(data.queue.current_index) {
-                    data.current_track = data.queue.data.get(track_id).cloned();
-                }
-                
+                let version = persist::parse_version(values.get(persist::VERSION_KEY).map(String::as_str));
+                let restored = Self::decode_persisted(&values, version);
                 tracing::debug!("Loaded player store state from database");
-                Some(data)
+                Some(restored.data)
             }
             Err(e) => {
                 tracing::error!("Failed to load player store state from database: {:?}", e);
@@ -765,3 +1668,409 @@ impl PlayerStore {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::tracks::Tracks;
+
+    fn track(id: &str) -> MediaContent {
+        MediaContent {
+            track: Tracks {
+                _id: Some(id.to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn decode_skips_unreadable_queue_entries() {
+        let entry = serde_json::to_string(&track("a")).unwrap();
+        let values: HashMap<String, String> = [
+            ("track_queue".to_string(), r#"["a#0","b#0"]"#.to_string()),
+            ("current_index".to_string(), "1".to_string()),
+            ("queue_data".to_string(), format!(r#"{{"a#0": {}, "b#0": 42}}"#, entry)),
+        ]
+        .into_iter()
+        .collect();
+
+        let restored = PlayerStore::decode_persisted(&values, Some(3));
+        assert_eq!(restored.data.queue.track_queue, vec!["a#0".to_string()]);
+        assert_eq!(restored.data.queue.current_index, 0);
+        assert_eq!(restored.skipped_entries, 1);
+        assert_eq!(restored.unreadable, vec!["queue_data"]);
+        assert!(restore_warning(Some(3), true, &restored.unreadable, 1).is_some());
+        assert!(restore_warning(Some(2), false, &[], 0).is_none());
+    }
+
+    #[test]
+    fn launch_resume_follows_the_policy() {
+        let entry = serde_json::to_string(&track("a")).unwrap();
+        let details = PlayerDetails { current_time: 42.5, state: PlayerState::Playing, ..Default::default() };
+        let values: HashMap<String, String> = [
+            ("player_state".to_string(), serde_json::to_string(&details).unwrap()),
+            ("track_queue".to_string(), r#"["a#0"]"#.to_string()),
+            ("current_index".to_string(), "0".to_string()),
+            ("queue_data".to_string(), format!(r#"{{"a#0": {}}}"#, entry)),
+        ]
+        .into_iter()
+        .collect();
+        let restored = || {
+            let mut store = PlayerStore::new(None);
+            store.data = PlayerStore::decode_persisted(&values, Some(3)).data;
+            store
+        };
+
+        let mut store = restored();
+        assert_eq!(store.get_player_state(), PlayerState::Paused);
+        assert_eq!(
+            store.launch_resume(ResumeOnLaunch::Playing),
+            LaunchResume { position: 42.5, play: true }
+        );
+        assert_eq!(
+            restored().launch_resume(ResumeOnLaunch::Paused),
+            LaunchResume { position: 42.5, play: false }
+        );
+        let mut store = restored();
+        assert_eq!(
+            store.launch_resume(ResumeOnLaunch::Never),
+            LaunchResume { position: 0.0, play: false }
+        );
+        assert_eq!(store.get_current_time(), 0.0);
+    }
+
+    #[test]
+    fn moving_keeps_the_current_entry_and_shuffle_order() {
+        let mut store = PlayerStore::new(None);
+        store.add_to_queue(vec![track("a"), track("b"), track("c"), track("d")]);
+        store.change_index(1, false);
+        store.data.shuffle_bag = vec![3, 0, 2];
+
+        store.move_in_queue(3, 0).unwrap();
+        assert_eq!(store.get_queue().track_queue, ["d#0", "a#0", "b#0", "c#0"]);
+        assert_eq!(store.get_queue_index(), 2);
+        assert_eq!(store.data.shuffle_bag, vec![0, 1, 3]);
+
+        store.move_in_queue(2, 3).unwrap();
+        assert_eq!(store.get_queue().track_queue, ["d#0", "a#0", "c#0", "b#0"]);
+        assert_eq!(store.get_queue_index(), 3);
+        assert_eq!(store.get_current_track().and_then(|t| t.track._id), Some("b".to_string()));
+        assert_eq!(store.data.shuffle_bag, vec![0, 1, 2]);
+
+        assert!(store.move_in_queue(0, 4).is_err());
+    }
+
+    #[test]
+    fn batch_insertion_keeps_order_and_current_entry() {
+        let mut store = PlayerStore::new(None);
+        store.set_duplicate_policy(QueueDuplicatePolicy::Allow);
+        store.add_to_queue(vec![track("a"), track("b")]);
+        store.change_index(1, false);
+
+        store.play_next_multiple(vec![track("x"), track("y")], false);
+        assert_eq!(store.get_queue().track_queue, ["a#0", "b#0", "x#0", "y#0"]);
+
+        store.add_to_queue_at(vec![track("p"), track("q")], 0, false);
+        assert_eq!(store.get_queue().track_queue, ["p#0", "q#0", "a#0", "b#0", "x#0", "y#0"]);
+        assert_eq!(store.get_queue_index(), 3);
+        assert_eq!(store.get_current_track().and_then(|t| t.track._id), Some("b".to_string()));
+    }
+
+    #[test]
+    fn radio_extends_only_at_end_of_sequential_queue() {
+        let mut store = PlayerStore::new(None);
+        store.add_to_queue(vec![track("a"), track("b")]);
+        store.set_player_mode(PlayerMode::Sequential);
+        assert!(!store.needs_radio_extension());
+
+        store.set_radio_mode(true);
+        assert!(!store.needs_radio_extension());
+        store.change_index(1, false);
+        assert!(store.needs_radio_extension());
+
+        store.set_player_mode(PlayerMode::ListLoop);
+        assert!(!store.needs_radio_extension());
+    }
+
+    #[test]
+    fn radio_tracks_skip_queued_and_are_marked() {
+        let mut store = PlayerStore::new(None);
+        store.add_to_queue(vec![track("a")]);
+        let added = store.append_radio_tracks(vec![track("a"), track("b"), track("c")]);
+        assert_eq!(added.len(), 2);
+        let queue = store.get_queue();
+        assert_eq!(queue.track_queue.len(), 3);
+        assert!(!queue.auto_generated.contains(&queue.track_queue[0]));
+        assert!(added.iter().all(|id| queue.auto_generated.contains(id)));
+
+        store.remove_from_queue(2);
+        assert_eq!(store.get_queue().auto_generated.len(), 1);
+    }
+
+    #[test]
+    fn next_and_previous_switch_stations() {
+        let mut store = PlayerStore::new(None);
+        store.add_to_queue(vec![track("radio:a"), track("b"), track("radio:c"), track("d")]);
+        store.change_index(0, false);
+        assert_eq!(store.adjacent_station(true), Some(2));
+        assert_eq!(store.adjacent_station(false), Some(2));
+
+        store.change_index(2, false);
+        assert_eq!(store.adjacent_station(true), Some(0));
+
+        // Regular tracks step through the queue as before
+        store.change_index(1, false);
+        assert_eq!(store.adjacent_station(true), None);
+    }
+
+    fn album_track(id: &str, album: &str, no: f64) -> MediaContent {
+        MediaContent {
+            track: Tracks {
+                _id: Some(id.to_string()),
+                track_no: Some(no),
+                ..Default::default()
+            },
+            album: Some(types::entities::QueryableAlbum {
+                album_id: Some(album.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn album_lookahead_follows_album_order() {
+        let mut store = PlayerStore::new(None);
+        store.add_to_queue(vec![
+            album_track("a1", "a", 1.0),
+            album_track("a2", "a", 2.0),
+            album_track("a3", "a", 3.0),
+            album_track("a5", "a", 5.0),
+            album_track("b1", "b", 1.0),
+        ]);
+        store.set_player_mode(PlayerMode::Sequential);
+        store.change_index(0, true);
+
+        let ids = |tracks: Vec<MediaContent>| -> Vec<String> {
+            tracks.into_iter().filter_map(|t| t.track._id).collect()
+        };
+        assert_eq!(ids(store.album_lookahead(2)), ["a2", "a3"]);
+        assert_eq!(ids(store.album_lookahead(5)), ["a2", "a3", "a5"]);
+        assert!(store.album_lookahead(0).is_empty());
+
+        store.set_player_mode(PlayerMode::Shuffle);
+        assert!(store.album_lookahead(2).is_empty());
+
+        store.set_player_mode(PlayerMode::Sequential);
+        store.change_index(3, true);
+        assert!(store.album_lookahead(2).is_empty());
+    }
+
+    #[test]
+    fn album_shuffle_finishes_the_current_album_first() {
+        let mut store = PlayerStore::new(None);
+        store.add_to_queue(vec![
+            album_track("a1", "a", 1.0),
+            album_track("a2", "a", 2.0),
+            album_track("b1", "b", 1.0),
+            album_track("a3", "a", 3.0),
+            album_track("c1", "c", 1.0),
+            album_track("b2", "b", 2.0),
+        ]);
+        store.change_index(1, true);
+        store.set_player_mode(PlayerMode::ShuffleAlbums);
+
+        let played: Vec<usize> = (0..4).filter_map(|_| store.get_next_shuffle_index()).collect();
+        assert_eq!(played[0], 3);
+        assert!(played[1..] == [2, 5, 4] || played[1..] == [4, 2, 5], "{:?}", played);
+
+        // At the end of an album, the next pass leaves it out
+        store.change_index(3, true);
+        store.rebuild_shuffle_bag();
+        let mut bag = store.data.shuffle_bag.clone();
+        bag.sort_unstable();
+        assert_eq!(bag, vec![2, 4, 5]);
+
+        // A single album starts over
+        let mut store = PlayerStore::new(None);
+        store.add_to_queue(vec![album_track("a1", "a", 1.0), album_track("a2", "a", 2.0)]);
+        store.change_index(1, true);
+        store.set_player_mode(PlayerMode::ShuffleAlbums);
+        assert_eq!(store.get_next_shuffle_index(), Some(0));
+    }
+
+    #[test]
+    fn volume_clamp_caps_the_current_source_in_clamp_mode() {
+        let mut store = PlayerStore::new(None);
+        store.add_to_queue(vec![track("a")]);
+        store.change_index(0, true);
+        let source = store.current_volume_source().unwrap();
+
+        store.set_volume_clamp(source.clone(), Some(140.0));
+        assert_eq!(store.get_volume_clamp(), None);
+        store.set_volume_mode(VolumeMode::PersistClamp);
+        assert_eq!(store.get_volume_clamp(), Some(100.0));
+
+        // Mode and clamps are persisted with the player state
+        let json = serde_json::to_string(&store.data.player_details).unwrap();
+        let details: PlayerDetails = serde_json::from_str(&json).unwrap();
+        assert_eq!(details.volume_mode, VolumeMode::PersistClamp);
+        assert_eq!(details.clamp_map.get(&source), Some(&100.0));
+
+        store.set_volume_clamp(source, None);
+        assert_eq!(store.get_volume_clamp(), None);
+    }
+
+    #[test]
+    fn skip_policy_keeps_single_entry() {
+        let mut store = PlayerStore::new(None);
+        store.add_to_queue(vec![track("a"), track("a")]);
+        assert_eq!(store.get_queue_len(), 1);
+    }
+
+    #[test]
+    fn allow_policy_uses_distinct_instance_ids() {
+        let mut store = PlayerStore::new(None);
+        store.set_duplicate_policy(QueueDuplicatePolicy::Allow);
+        store.add_to_queue(vec![track("a"), track("a")]);
+        let queue = store.get_queue();
+        assert_eq!(queue.track_queue, vec!["a#0".to_string(), "a#1".to_string()]);
+        store.remove_from_queue(0);
+        assert_eq!(store.get_queue_tracks().len(), 1);
+    }
+
+    #[test]
+    fn ask_policy_returns_pending_duplicates() {
+        let mut store = PlayerStore::new(None);
+        store.set_duplicate_policy(QueueDuplicatePolicy::Ask);
+        let pending = store.add_to_queue(vec![track("a"), track("b"), track("a")]);
+        assert_eq!(store.get_queue_len(), 2);
+        assert_eq!(pending.len(), 1);
+        store.add_to_queue_allow_duplicates(pending);
+        assert_eq!(store.get_queue_len(), 3);
+    }
+
+    #[test]
+    fn legacy_queue_is_migrated_to_instance_ids() {
+        let mut queue = Queue {
+            track_queue: vec!["a".into(), "b".into()],
+            current_index: 1,
+            data: HashMap::from([("a".to_string(), track("a")), ("b".to_string(), track("b"))]),
+            ..Default::default()
+        };
+        assert!(queue.migrate_legacy_entries());
+        assert_eq!(queue.track_queue, vec!["a#0".to_string(), "b#0".to_string()]);
+        assert_eq!(queue.current_index, 1);
+        assert!(!queue.migrate_legacy_entries());
+    }
+
+    #[test]
+    fn overrides_follow_queue_instances() {
+        let mut store = PlayerStore::new(None);
+        store.set_duplicate_policy(QueueDuplicatePolicy::Allow);
+        store.add_to_queue(vec![track("a"), track("a")]);
+        let trim = QueueItemOverrides {
+            start_offset: Some(90.0),
+            end_at: Some(200.0),
+        };
+        store.set_queue_item_overrides(1, trim).unwrap();
+        assert_eq!(store.get_current_overrides(), None);
+
+        store.remove_from_queue(0);
+        assert_eq!(store.get_current_overrides(), Some(trim));
+        assert!(store
+            .set_queue_item_overrides(0, QueueItemOverrides { start_offset: Some(10.0), end_at: Some(5.0) })
+            .is_err());
+        assert!(store.set_queue_item_overrides(3, trim).is_err());
+    }
+
+    #[test]
+    fn long_silence_is_trimmed_unless_overridden() {
+        let mut store = PlayerStore::new(None);
+        let mut long = track("a");
+        long.track.duration = Some(300.0);
+        store.add_to_queue(vec![long]);
+        store.silence = Some(("a".to_string(), TrackSilence { start: 4.0, end: 299.0 }));
+        assert_eq!(store.get_current_trim(), None);
+
+        store.set_trim_silence(true);
+        // The second of trailing silence is too short to trim
+        assert_eq!(
+            store.get_current_trim(),
+            Some(QueueItemOverrides { start_offset: Some(4.0), end_at: None })
+        );
+        store.silence = Some(("a".to_string(), TrackSilence { start: 0.5, end: 280.0 }));
+        store
+            .set_queue_item_overrides(0, QueueItemOverrides { start_offset: None, end_at: Some(250.0) })
+            .unwrap();
+        assert_eq!(
+            store.get_current_trim(),
+            Some(QueueItemOverrides { start_offset: None, end_at: Some(250.0) })
+        );
+        store.set_queue_item_overrides(0, QueueItemOverrides::default()).unwrap();
+        assert_eq!(
+            store.get_current_trim(),
+            Some(QueueItemOverrides { start_offset: None, end_at: Some(280.0) })
+        );
+    }
+
+    #[test]
+    fn end_trim_fires_once_per_load() {
+        let mut store = PlayerStore::new(None);
+        store.add_to_queue(vec![track("a")]);
+        store
+            .set_queue_item_overrides(0, QueueItemOverrides { start_offset: None, end_at: Some(30.0) })
+            .unwrap();
+        store.update_time(29.0);
+        assert!(!store.take_end_trim());
+        store.update_time(30.5);
+        assert!(store.take_end_trim());
+        assert!(!store.take_end_trim());
+        store.change_index(0, true);
+        store.update_time(31.0);
+        assert!(store.take_end_trim());
+    }
+
+    #[test]
+    fn crossfade_starts_once_before_the_end() {
+        let mut store = PlayerStore::new(None);
+        let mut first = track("a");
+        first.track.duration = Some(100.0);
+        store.add_to_queue(vec![first, track("b")]);
+        store.data.player_details.state = PlayerState::Playing;
+
+        store.update_time(90.0);
+        assert!(!store.take_crossfade_start(5.0));
+        store.update_time(95.5);
+        assert!(store.take_crossfade_start(5.0));
+        assert!(!store.take_crossfade_start(5.0));
+        assert!(store.take_crossfade_pending());
+        assert!(!store.take_crossfade_pending());
+
+        // Last entry of a sequential queue: playback stops, nothing to fade into
+        store.change_index(1, true);
+        store.data.current_track.as_mut().unwrap().track.duration = Some(100.0);
+        store.update_time(99.0);
+        assert!(!store.take_crossfade_start(5.0));
+    }
+
+    #[test]
+    fn queue_history_names_albums_and_mixes() {
+        let from_album = |id: &str, album: &str| MediaContent {
+            album: Some(types::entities::QueryableAlbum {
+                album_name: Some(album.to_string()),
+                ..Default::default()
+            }),
+            ..track(id)
+        };
+        let mut a = from_album("a", "Kind of Blue");
+        a.track.title = Some("So What".to_string());
+        let b = from_album("b", "Kind of Blue");
+        let c = from_album("c", "Blue Train");
+
+        assert_eq!(PlayerStore::queue_name(&[&a, &b]), "Kind of Blue");
+        assert_eq!(PlayerStore::queue_name(&[&a, &b, &c]), "So What and 2 more");
+        assert_eq!(PlayerStore::queue_name(&[&track("d")]), "");
+    }
+}
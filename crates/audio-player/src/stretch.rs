@@ -0,0 +1,330 @@
+// crates/audio-player/src/stretch.rs
+// Playback rate with the pitch kept: sources are time-stretched with WSOLA
+// (overlap-add of windowed segments, each picked near its nominal position
+// where it best continues the previous one). The rate is global and read by
+// every playing source, so changes apply immediately.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use rodio::source::SeekError;
+use rodio::Source;
+
+pub const MIN_RATE: f32 = 0.5;
+pub const MAX_RATE: f32 = 3.0;
+
+/// Segment length; half of it is the output hop
+const SEGMENT_MS: usize = 40;
+/// How far from its nominal position a segment may be taken
+const SEARCH_MS: usize = 12;
+/// Candidate positions and compared frames are spaced to keep the search cheap
+const SEARCH_STEP: usize = 2;
+const COMPARE_STEP: usize = 4;
+
+/// Bits of 1.0f32
+static RATE: AtomicU32 = AtomicU32::new(0x3f80_0000);
+
+/// Rate within the supported range; 1.0 for anything not a number
+pub fn clamp_rate(rate: f32) -> f32 {
+    if rate.is_finite() {
+        rate.clamp(MIN_RATE, MAX_RATE)
+    } else {
+        1.0
+    }
+}
+
+/// Set the playback rate and return the one applied
+pub fn set_rate(rate: f32) -> f32 {
+    let rate = clamp_rate(rate);
+    RATE.store(rate.to_bits(), Ordering::Relaxed);
+    rate
+}
+
+pub fn rate() -> f32 {
+    f32::from_bits(RATE.load(Ordering::Relaxed))
+}
+
+fn is_normal(rate: f32) -> bool {
+    (rate - 1.0).abs() < 0.01
+}
+
+/// WSOLA state over interleaved samples
+struct Stretcher {
+    channels: usize,
+    /// Segment length in frames, even
+    segment: usize,
+    search: usize,
+    window: Vec<f32>,
+    /// Buffered input; positions below are frames into it
+    input: Vec<f32>,
+    /// Nominal position of the next segment
+    offset: f64,
+    /// Start of the previous segment
+    prev: Option<usize>,
+    /// Windowed second half of the previous segment, added to the next one
+    tail: Vec<f32>,
+}
+
+impl Stretcher {
+    fn new(channels: usize, sample_rate: u32) -> Self {
+        let sample_rate = sample_rate.max(1) as usize;
+        let segment = (sample_rate * SEGMENT_MS / 1000).max(16) & !1;
+        let window = (0..segment)
+            .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / segment as f32).cos())
+            .collect();
+        Self {
+            channels: channels.max(1),
+            segment,
+            search: sample_rate * SEARCH_MS / 1000,
+            window,
+            input: Vec::new(),
+            offset: 0.0,
+            prev: None,
+            tail: Vec::new(),
+        }
+    }
+
+    fn hop(&self) -> usize {
+        self.segment / 2
+    }
+
+    fn frames(&self) -> usize {
+        self.input.len() / self.channels
+    }
+
+    /// Candidate start in `lo..=hi` whose beginning best matches the input at `target`
+    fn best_match(&self, target: usize, lo: usize, hi: usize) -> usize {
+        let c = self.channels;
+        let mut best = (f32::MIN, lo);
+        for start in (lo..=hi).step_by(SEARCH_STEP) {
+            let (mut correlation, mut energy) = (0.0, 0.0);
+            for i in (0..self.hop()).step_by(COMPARE_STEP) {
+                for ch in 0..c {
+                    let candidate = self.input[(start + i) * c + ch];
+                    correlation += candidate * self.input[(target + i) * c + ch];
+                    energy += candidate * candidate;
+                }
+            }
+            let score = correlation / (energy + 1e-9).sqrt();
+            if score > best.0 {
+                best = (score, start);
+            }
+        }
+        best.1
+    }
+
+    /// Output the next hop at `rate`, pulling input as needed. Returns false
+    /// once the input ended, after flushing what was left.
+    fn step(&mut self, rate: f32, pull: &mut impl FnMut() -> Option<f32>, out: &mut VecDeque<f32>) -> bool {
+        let (c, hop) = (self.channels, self.hop());
+        let nominal = self.offset.round() as usize;
+        let needed = nominal + self.search + self.segment;
+        while self.frames() < needed {
+            match pull() {
+                Some(sample) => self.input.push(sample),
+                None => break,
+            }
+        }
+        let available = self.frames();
+        if available < nominal + self.segment {
+            self.unwind(out);
+            return false;
+        }
+
+        let start = match self.prev {
+            Some(prev) => {
+                let lo = nominal.saturating_sub(self.search);
+                let hi = (nominal + self.search).min(available - self.segment);
+                self.best_match(prev + hop, lo, hi)
+            }
+            None => nominal,
+        };
+        for i in 0..hop {
+            // The very first segment starts at full level
+            let gain = if self.prev.is_some() { self.window[i] } else { 1.0 };
+            for ch in 0..c {
+                let overlap = self.tail.get(i * c + ch).copied().unwrap_or(0.0);
+                out.push_back(self.input[(start + i) * c + ch] * gain + overlap);
+            }
+        }
+        self.tail.clear();
+        for i in hop..self.segment {
+            for ch in 0..c {
+                self.tail.push(self.input[(start + i) * c + ch] * self.window[i]);
+            }
+        }
+        self.prev = Some(start);
+        self.offset += hop as f64 * rate as f64;
+
+        // Drop input no later segment can reach
+        let keep = start.min((self.offset as usize).saturating_sub(self.search));
+        if keep > 0 {
+            self.input.drain(..keep * c);
+            self.prev = Some(start - keep);
+            self.offset -= keep as f64;
+        }
+        true
+    }
+
+    /// Output the buffered input unstretched, continuing the last segment
+    /// where its fade-in ended
+    fn unwind(&mut self, out: &mut VecDeque<f32>) {
+        let from = match self.prev {
+            Some(prev) => (prev + self.hop()) * self.channels,
+            None => (self.offset.round() as usize) * self.channels,
+        };
+        out.extend(self.input.get(from..).unwrap_or_default());
+        self.input.clear();
+        self.tail.clear();
+        self.prev = None;
+        self.offset = 0.0;
+    }
+}
+
+/// Source playing its input at the global rate without changing the pitch;
+/// at normal rate the input passes through untouched
+pub struct TimeStretch<S> {
+    inner: S,
+    stretcher: Option<Stretcher>,
+    output: VecDeque<f32>,
+}
+
+impl<S: Source> TimeStretch<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            stretcher: None,
+            output: VecDeque::new(),
+        }
+    }
+}
+
+impl<S: Source> Iterator for TimeStretch<S> {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(sample) = self.output.pop_front() {
+                return Some(sample);
+            }
+            let rate = rate();
+            if is_normal(rate) {
+                match self.stretcher.take() {
+                    Some(mut stretcher) => {
+                        stretcher.unwind(&mut self.output);
+                        continue;
+                    }
+                    None => return self.inner.next(),
+                }
+            }
+            let channels: u16 = self.inner.channels().into();
+            let sample_rate: u32 = self.inner.sample_rate().into();
+            let stretcher = self
+                .stretcher
+                .get_or_insert_with(|| Stretcher::new(channels as usize, sample_rate));
+            let inner = &mut self.inner;
+            if !stretcher.step(rate, &mut || inner.next(), &mut self.output) {
+                self.stretcher = None;
+                if self.output.is_empty() {
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+impl<S: Source> Source for TimeStretch<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        match (&self.stretcher, self.output.is_empty()) {
+            (None, true) => self.inner.current_span_len(),
+            _ => None,
+        }
+    }
+
+    fn channels(&self) -> rodio::ChannelCount {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> rodio::SampleRate {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.output.clear();
+        self.stretcher = None;
+        self.inner.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 44_100;
+
+    fn stretch(input: &[f32], channels: usize, rate: f32) -> Vec<f32> {
+        let mut stretcher = Stretcher::new(channels, SAMPLE_RATE);
+        let mut samples = input.iter().copied();
+        let mut out = VecDeque::new();
+        while stretcher.step(rate, &mut || samples.next(), &mut out) {}
+        out.into()
+    }
+
+    fn sine(frequency: f32, seconds: f32) -> Vec<f32> {
+        (0..(SAMPLE_RATE as f32 * seconds) as usize)
+            .map(|i| (2.0 * std::f32::consts::PI * frequency * i as f32 / SAMPLE_RATE as f32).sin() * 0.5)
+            .collect()
+    }
+
+    /// Frequency of a sine from its upward zero crossings
+    fn frequency(samples: &[f32]) -> f32 {
+        let crossings = samples.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count();
+        crossings as f32 * SAMPLE_RATE as f32 / samples.len() as f32
+    }
+
+    #[test]
+    fn duration_follows_the_rate_and_pitch_is_kept() {
+        let input = sine(440.0, 2.0);
+        for rate in [0.5, 1.5, 2.0, 3.0] {
+            let out = stretch(&input, 1, rate);
+            let expected = input.len() as f32 / rate;
+            // The end is flushed unstretched: up to a segment and a search range
+            let slack = expected * 0.05 + SAMPLE_RATE as f32 * (SEGMENT_MS + SEARCH_MS) as f32 / 1000.0;
+            assert!(
+                (out.len() as f32 - expected).abs() < slack,
+                "rate {}: {} samples instead of about {}",
+                rate,
+                out.len(),
+                expected
+            );
+            let middle = &out[out.len() / 4..out.len() * 3 / 4];
+            assert!((frequency(middle) - 440.0).abs() < 10.0, "rate {}: {} Hz", rate, frequency(middle));
+            // No level jumps where segments overlap
+            let peak = middle.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+            assert!(peak < 0.6, "rate {}: peak {}", rate, peak);
+        }
+    }
+
+    #[test]
+    fn channels_stay_apart() {
+        let left = sine(220.0, 1.0);
+        let stereo: Vec<f32> = left.iter().flat_map(|s| [*s, 0.0]).collect();
+        let out = stretch(&stereo, 2, 1.5);
+        assert_eq!(out.len() % 2, 0);
+        assert!(out.iter().skip(1).step_by(2).all(|s| *s == 0.0));
+        assert!(out.iter().step_by(2).any(|s| s.abs() > 0.4));
+    }
+
+    #[test]
+    fn rates_are_clamped() {
+        assert_eq!(clamp_rate(5.0), MAX_RATE);
+        assert_eq!(clamp_rate(0.1), MIN_RATE);
+        assert_eq!(clamp_rate(f32::NAN), 1.0);
+        assert_eq!(f32::from_bits(0x3f80_0000), 1.0);
+    }
+}
@@ -0,0 +1,125 @@
+// crates/audio-player/src/trace.rs
+// Opt-in playback session trace: while active, decoder choices, buffer
+// settings, resolver timings, device changes and errors are appended to a
+// JSON lines file the user can attach to a bug report.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
+use types::errors::Result;
+
+struct Recorder {
+    path: PathBuf,
+    started: Instant,
+    writer: BufWriter<File>,
+}
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+
+/// Start tracing into `path`, truncating it. A trace already running is
+/// closed first.
+pub fn start(path: PathBuf) -> Result<()> {
+    let file = File::create(&path)?;
+    let mut recorder = RECORDER.lock().unwrap();
+    if let Some(mut previous) = recorder.take() {
+        let _ = previous.writer.flush();
+    }
+    *recorder = Some(Recorder {
+        path,
+        started: Instant::now(),
+        writer: BufWriter::new(file),
+    });
+    ACTIVE.store(true, Ordering::SeqCst);
+    drop(recorder);
+
+    let unix_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    record(
+        "session_start",
+        json!({ "unix_ms": unix_ms, "version": env!("CARGO_PKG_VERSION"), "os": std::env::consts::OS }),
+    );
+    Ok(())
+}
+
+/// Stop tracing and return the path of the written file, if a trace was running.
+pub fn stop() -> Option<PathBuf> {
+    record("session_end", Value::Null);
+    ACTIVE.store(false, Ordering::SeqCst);
+    let mut recorder = RECORDER.lock().unwrap().take()?;
+    let _ = recorder.writer.flush();
+    Some(recorder.path)
+}
+
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Append an event. `data` is merged into the line when it is an object and
+/// stored under `data` otherwise. A no-op while no trace is running.
+pub fn record(kind: &str, data: Value) {
+    if !is_active() {
+        return;
+    }
+    let Ok(mut guard) = RECORDER.lock() else { return };
+    let Some(recorder) = guard.as_mut() else { return };
+
+    let mut line = json!({
+        "t_ms": recorder.started.elapsed().as_millis() as u64,
+        "kind": kind,
+    });
+    match data {
+        Value::Object(fields) => {
+            if let Value::Object(line) = &mut line {
+                line.extend(fields);
+            }
+        }
+        Value::Null => {}
+        other => line["data"] = other,
+    }
+
+    // Flush every line so the file is usable even if the app crashes
+    if writeln!(recorder.writer, "{}", line).and_then(|_| recorder.writer.flush()).is_err() {
+        tracing::warn!("Failed to write playback trace, stopping it");
+        *guard = None;
+        ACTIVE.store(false, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_only_while_active() {
+        let path = std::env::temp_dir().join(format!("playback-trace-test-{}.jsonl", std::process::id()));
+
+        record("before", json!({ "ignored": true }));
+        start(path.clone()).unwrap();
+        record("decoder", json!({ "source": "local" }));
+        record("error", json!("boom"));
+        assert_eq!(stop(), Some(path.clone()));
+        record("after", Value::Null);
+        assert_eq!(stop(), None);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<Value> = contents.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        let kinds: Vec<&str> = lines.iter().map(|l| l["kind"].as_str().unwrap()).collect();
+        assert_eq!(kinds, ["session_start", "decoder", "error", "session_end"]);
+        assert_eq!(lines[1]["source"], "local");
+        assert_eq!(lines[2]["data"], "boom");
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,87 @@
+// crates/audio-player/src/transport.rs
+// Transport commands issued while a track is loading. Track changes are
+// serialized: each one takes a ticket, and a load whose ticket was superseded
+// by a later change is skipped, so rapid next/next/next only loads the final
+// target. Seeks arriving mid-load are held until the track is ready.
+
+/// Identifies one track change request
+pub type LoadTicket = u64;
+
+#[derive(Debug, Default)]
+pub struct TransportQueue {
+    /// Bumped by every track change; only the latest ticket gets loaded
+    generation: LoadTicket,
+    loading: bool,
+    /// Latest seek requested during the load, in edited timeline seconds
+    pending_seek: Option<f64>,
+}
+
+impl TransportQueue {
+    /// A new track was selected. A seek held for the previous selection is
+    /// dropped.
+    pub fn select(&mut self) -> LoadTicket {
+        self.generation += 1;
+        self.pending_seek = None;
+        self.generation
+    }
+
+    /// Whether no track change came after the one holding `ticket`
+    pub fn is_current(&self, ticket: LoadTicket) -> bool {
+        self.generation == ticket
+    }
+
+    pub fn begin_load(&mut self) {
+        self.loading = true;
+    }
+
+    /// The load completed or failed; returns the seek to apply now
+    pub fn finish_load(&mut self) -> Option<f64> {
+        self.loading = false;
+        self.pending_seek.take()
+    }
+
+    /// Hold a seek until the load completes. Returns false when nothing is
+    /// loading and the seek is to be applied right away.
+    pub fn defer_seek(&mut self, pos: f64) -> bool {
+        if self.loading {
+            self.pending_seek = Some(pos);
+        }
+        self.loading
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_last_selection_is_current() {
+        let mut queue = TransportQueue::default();
+        let first = queue.select();
+        let second = queue.select();
+        let third = queue.select();
+        assert!(!queue.is_current(first));
+        assert!(!queue.is_current(second));
+        assert!(queue.is_current(third));
+    }
+
+    #[test]
+    fn seeks_during_load_are_held_until_it_completes() {
+        let mut queue = TransportQueue::default();
+        assert!(!queue.defer_seek(10.0));
+
+        queue.select();
+        queue.begin_load();
+        assert!(queue.defer_seek(10.0));
+        assert!(queue.defer_seek(42.0));
+        assert_eq!(queue.finish_load(), Some(42.0));
+        assert_eq!(queue.finish_load(), None);
+        assert!(!queue.defer_seek(7.0));
+
+        // Selecting another track drops the seek held for the previous one
+        queue.begin_load();
+        queue.defer_seek(5.0);
+        queue.select();
+        assert_eq!(queue.finish_load(), None);
+    }
+}
@@ -0,0 +1,306 @@
+// crates/audio-player/src/visualizer.rs
+// Spectrum data for visualizers. A tap at the end of every source's chain
+// copies a mono mix of what is played to an analysis thread, which turns it
+// into FFT band levels at a fixed rate. Both only work between `start` and
+// `stop`; otherwise the tap costs one atomic load per sample.
+//
+// Frames are `[version, 0, bands (u16 LE), level × bands]`, each level a byte
+// from 0 (-80 dBFS or quieter) to 255 (0 dBFS), bands log-spaced from 20 Hz to
+// 20 kHz (or the Nyquist frequency when lower).
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use base64::Engine;
+use crossbeam_channel::{bounded, Receiver, Sender};
+use rodio::source::SeekError;
+use rodio::Source;
+use rustfft::{num_complex::Complex, Fft, FftPlanner};
+
+/// Samples analysed per frame, about 21 Hz per bin at 44.1 kHz
+const FFT_SIZE: usize = 2048;
+/// Mono samples the tap sends at once, small enough for 60 frames a second
+const CHUNK_LEN: usize = 256;
+/// Chunks waiting for the analysis thread before the tap drops new ones
+const CHUNK_QUEUE: usize = 64;
+const MIN_HZ: f32 = 20.0;
+const MAX_HZ: f32 = 20_000.0;
+/// Levels at or below this map to 0
+const FLOOR_DB: f32 = -80.0;
+const FRAME_VERSION: u8 = 1;
+pub const MAX_BANDS: u16 = 256;
+pub const MAX_RATE_HZ: u32 = 60;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static TAP: Mutex<Option<Sender<Chunk>>> = Mutex::new(None);
+static NEXT_TAP_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Number of bands and frames per second
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VisualizerConfig {
+    pub bands: u16,
+    pub rate_hz: u32,
+}
+
+impl Default for VisualizerConfig {
+    fn default() -> Self {
+        Self { bands: 64, rate_hz: 30 }
+    }
+}
+
+impl VisualizerConfig {
+    fn clamped(self) -> Self {
+        Self {
+            bands: self.bands.clamp(1, MAX_BANDS),
+            rate_hz: self.rate_hz.clamp(1, MAX_RATE_HZ),
+        }
+    }
+}
+
+/// Mono samples from the tap of one source
+struct Chunk {
+    tap: u64,
+    sample_rate: u32,
+    samples: Vec<f32>,
+}
+
+/// Start analysing what is played, calling `on_frame` with each frame encoded
+/// in base64 (URL safe, no padding). Replaces an analysis already running.
+pub fn start<F>(config: VisualizerConfig, on_frame: F)
+where
+    F: Fn(String) + Send + 'static,
+{
+    let config = config.clamped();
+    let (tx, rx) = bounded(CHUNK_QUEUE);
+    // Dropping the previous sender ends the previous analysis thread
+    *TAP.lock().unwrap() = Some(tx);
+    ENABLED.store(true, Ordering::SeqCst);
+    std::thread::spawn(move || analyse(rx, config, on_frame));
+}
+
+/// Stop the analysis; the taps go back to passing samples through
+pub fn stop() {
+    ENABLED.store(false, Ordering::SeqCst);
+    TAP.lock().unwrap().take();
+}
+
+pub fn is_running() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+fn analyse<F: Fn(String)>(rx: Receiver<Chunk>, config: VisualizerConfig, on_frame: F) {
+    let mut analyzer = Analyzer::new();
+    let mut history: Vec<f32> = Vec::with_capacity(FFT_SIZE + CHUNK_LEN);
+    let mut tap = 0;
+    let mut since_frame = 0;
+    while let Ok(chunk) = rx.recv() {
+        // While crossfading, follow the incoming track
+        if chunk.tap < tap {
+            continue;
+        }
+        if chunk.tap > tap {
+            tap = chunk.tap;
+            history.clear();
+        }
+        since_frame += chunk.samples.len();
+        history.extend_from_slice(&chunk.samples);
+        if history.len() > FFT_SIZE {
+            history.drain(..history.len() - FFT_SIZE);
+        }
+
+        let hop = (chunk.sample_rate / config.rate_hz).max(1) as usize;
+        if since_frame >= hop && history.len() == FFT_SIZE {
+            since_frame = 0;
+            let magnitudes = analyzer.magnitudes(&history);
+            let levels = band_levels(&magnitudes, chunk.sample_rate, config.bands as usize);
+            on_frame(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(encode_frame(&levels)));
+        }
+    }
+}
+
+struct Analyzer {
+    fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    /// Scale turning a bin magnitude into the amplitude of a sine
+    norm: f32,
+    buffer: Vec<Complex<f32>>,
+}
+
+impl Analyzer {
+    fn new() -> Self {
+        let window: Vec<f32> = (0..FFT_SIZE)
+            .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / (FFT_SIZE - 1) as f32).cos())
+            .collect();
+        let norm = 2.0 / window.iter().sum::<f32>();
+        Self {
+            fft: FftPlanner::new().plan_fft_forward(FFT_SIZE),
+            window,
+            norm,
+            buffer: Vec::with_capacity(FFT_SIZE),
+        }
+    }
+
+    /// Amplitudes of the bins up to the Nyquist frequency of `samples`
+    fn magnitudes(&mut self, samples: &[f32]) -> Vec<f32> {
+        self.buffer.clear();
+        self.buffer
+            .extend(samples.iter().zip(&self.window).map(|(s, w)| Complex::new(s * w, 0.0)));
+        self.fft.process(&mut self.buffer);
+        self.buffer[..FFT_SIZE / 2].iter().map(|c| c.norm() * self.norm).collect()
+    }
+}
+
+/// Peak level of each of `bands` log-spaced bands, from the bin amplitudes of
+/// a `magnitudes.len() * 2` point FFT
+fn band_levels(magnitudes: &[f32], sample_rate: u32, bands: usize) -> Vec<u8> {
+    if magnitudes.is_empty() {
+        return vec![0; bands];
+    }
+    let bin_hz = sample_rate as f32 / (magnitudes.len() * 2) as f32;
+    let span = (MAX_HZ.min(sample_rate as f32 / 2.0) / MIN_HZ).ln();
+    let edge = |band: usize| MIN_HZ * (span * band as f32 / bands as f32).exp();
+    (0..bands)
+        .map(|band| {
+            let first = ((edge(band) / bin_hz) as usize).min(magnitudes.len() - 1);
+            // Low bands narrower than a bin still get the bin they fall in
+            let last = ((edge(band + 1) / bin_hz) as usize).clamp(first + 1, magnitudes.len());
+            level(magnitudes[first..last].iter().copied().fold(0.0, f32::max))
+        })
+        .collect()
+}
+
+fn level(amplitude: f32) -> u8 {
+    if amplitude <= 0.0 {
+        return 0;
+    }
+    let db = 20.0 * amplitude.log10();
+    ((db - FLOOR_DB) / -FLOOR_DB * 255.0).clamp(0.0, 255.0).round() as u8
+}
+
+fn encode_frame(levels: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(4 + levels.len());
+    frame.extend_from_slice(&[FRAME_VERSION, 0]);
+    frame.extend_from_slice(&(levels.len() as u16).to_le_bytes());
+    frame.extend_from_slice(levels);
+    frame
+}
+
+/// Source passing its samples through and, while the visualizer runs, copying
+/// a mono mix of them to the analysis thread
+pub struct VisualizerTap<S> {
+    inner: S,
+    id: u64,
+    /// Sum of the samples of the current frame and how many were added
+    frame_sum: f32,
+    frame_len: u16,
+    samples: Vec<f32>,
+}
+
+impl<S: Source> VisualizerTap<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            id: NEXT_TAP_ID.fetch_add(1, Ordering::Relaxed),
+            frame_sum: 0.0,
+            frame_len: 0,
+            samples: Vec::new(),
+        }
+    }
+
+    fn collect(&mut self, sample: f32) {
+        let channels: u16 = self.inner.channels().into();
+        self.frame_sum += sample;
+        self.frame_len += 1;
+        if self.frame_len < channels.max(1) {
+            return;
+        }
+        self.samples.push(self.frame_sum / self.frame_len as f32);
+        self.frame_sum = 0.0;
+        self.frame_len = 0;
+        if self.samples.len() < CHUNK_LEN {
+            return;
+        }
+        let chunk = Chunk {
+            tap: self.id,
+            sample_rate: self.inner.sample_rate().into(),
+            samples: std::mem::replace(&mut self.samples, Vec::with_capacity(CHUNK_LEN)),
+        };
+        if let Ok(tap) = TAP.lock() {
+            if let Some(tx) = tap.as_ref() {
+                // A busy analysis thread skips audio rather than delaying playback
+                let _ = tx.try_send(chunk);
+            }
+        }
+    }
+}
+
+impl<S: Source> Iterator for VisualizerTap<S> {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.inner.next()?;
+        if is_running() {
+            self.collect(sample);
+        }
+        Some(sample)
+    }
+}
+
+impl<S: Source> Source for VisualizerTap<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        self.inner.current_span_len()
+    }
+
+    fn channels(&self) -> rodio::ChannelCount {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> rodio::SampleRate {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.frame_sum = 0.0;
+        self.frame_len = 0;
+        self.samples.clear();
+        self.inner.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_sine_peaks_in_its_band() {
+        let sample_rate = 44_100;
+        let samples: Vec<f32> = (0..FFT_SIZE)
+            .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 1_000.0 * i as f32 / sample_rate as f32).sin())
+            .collect();
+        let magnitudes = Analyzer::new().magnitudes(&samples);
+        let levels = band_levels(&magnitudes, sample_rate, 32);
+
+        let peak = (0..levels.len()).max_by_key(|&b| levels[b]).unwrap();
+        let span = (MAX_HZ / MIN_HZ).ln();
+        let low = MIN_HZ * (span * peak as f32 / 32.0).exp();
+        let high = MIN_HZ * (span * (peak + 1) as f32 / 32.0).exp();
+        assert!(low <= 1_050.0 && high >= 950.0, "peak band {} Hz - {} Hz", low, high);
+        // -6 dBFS, within the scalloping loss of the window
+        let expected = level(0.5);
+        assert!(levels[peak].abs_diff(expected) <= 5, "level {} instead of {}", levels[peak], expected);
+        assert_eq!(levels[0], 0);
+    }
+
+    #[test]
+    fn frames_carry_their_band_count() {
+        let frame = encode_frame(&[0, 128, 255]);
+        assert_eq!(frame, vec![FRAME_VERSION, 0, 3, 0, 0, 128, 255]);
+        assert_eq!(level(1.0), 255);
+        assert_eq!(level(0.00001), 0);
+    }
+}
@@ -0,0 +1,6 @@
+DROP TRIGGER IF EXISTS delete_artist_romanization;
+DROP TRIGGER IF EXISTS delete_album_romanization;
+DROP TRIGGER IF EXISTS delete_track_romanization;
+DROP INDEX IF EXISTS idx_romanized_names_initials;
+DROP INDEX IF EXISTS idx_romanized_names_romanized;
+DROP TABLE IF EXISTS romanized_names;
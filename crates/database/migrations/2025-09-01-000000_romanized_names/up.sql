@@ -0,0 +1,50 @@
+-- Precomputed romanization (pinyin / romaji) for CJK library metadata.
+-- Rows only exist for names that contain Han or kana characters; values are
+-- computed in Rust (database::collation) because SQLite has no transliteration.
+--  - entity_kind: 'track' | 'album' | 'artist'
+--  - romanized:   lowercase syllables without separators, e.g. 周杰伦 -> zhoujielun
+--  - initials:    first letter of every syllable/word, e.g. 周杰伦 -> zjl
+CREATE TABLE IF NOT EXISTS romanized_names (
+  entity_id   TEXT NOT NULL,
+  entity_kind TEXT NOT NULL,
+  source      TEXT NOT NULL,
+  romanized   TEXT NOT NULL,
+  initials    TEXT NOT NULL,
+  PRIMARY KEY (entity_id, entity_kind)
+);
+
+CREATE INDEX IF NOT EXISTS idx_romanized_names_romanized ON romanized_names(romanized);
+CREATE INDEX IF NOT EXISTS idx_romanized_names_initials ON romanized_names(initials);
+
+CREATE TRIGGER IF NOT EXISTS delete_track_romanization
+AFTER
+    DELETE ON tracks BEGIN
+DELETE FROM
+    romanized_names
+WHERE
+    entity_kind = 'track'
+    AND entity_id = OLD._id;
+
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_album_romanization
+AFTER
+    DELETE ON albums BEGIN
+DELETE FROM
+    romanized_names
+WHERE
+    entity_kind = 'album'
+    AND entity_id = OLD.album_id;
+
+END;
+
+CREATE TRIGGER IF NOT EXISTS delete_artist_romanization
+AFTER
+    DELETE ON artists BEGIN
+DELETE FROM
+    romanized_names
+WHERE
+    entity_kind = 'artist'
+    AND entity_id = OLD.artist_id;
+
+END;
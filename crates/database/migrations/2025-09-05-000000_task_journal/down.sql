@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_task_journal_status;
+DROP TABLE IF EXISTS task_journal;
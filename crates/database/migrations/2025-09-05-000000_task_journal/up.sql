@@ -0,0 +1,18 @@
+-- Journal of long-running jobs (library scans, downloads) so they can resume
+-- after an app restart.
+--  - kind:       'scan' | 'download'
+--  - status:     'running' | 'completed' | 'failed' | 'cancelled'
+--  - payload:    JSON job description (what to do)
+--  - checkpoint: JSON progress (how far it got), rewritten as the job advances
+CREATE TABLE IF NOT EXISTS task_journal (
+  id         TEXT PRIMARY KEY,
+  kind       TEXT NOT NULL,
+  status     TEXT NOT NULL,
+  payload    TEXT NOT NULL,
+  checkpoint TEXT,
+  error      TEXT,
+  created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_journal_status ON task_journal(status);
@@ -0,0 +1,11 @@
+-- Tracks downloaded from online providers for offline playback.
+--  - path:   downloaded file, played instead of resolving a stream
+--  - size:   file size in bytes, summed against the download storage budget
+--  - origin: 'manual' | 'smart' (queued by the automatic download policy)
+CREATE TABLE IF NOT EXISTS downloads (
+  track_id   TEXT PRIMARY KEY,
+  path       TEXT NOT NULL,
+  size       BIGINT NOT NULL,
+  origin     TEXT NOT NULL,
+  created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
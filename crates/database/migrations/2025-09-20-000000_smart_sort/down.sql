@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS sort_presets;
+DROP TABLE IF EXISTS track_ratings;
@@ -0,0 +1,15 @@
+-- User ratings, one of the smart sort criteria.
+--  - rating: 0 (unrated) to 5
+CREATE TABLE IF NOT EXISTS track_ratings (
+  track_id   TEXT PRIMARY KEY,
+  rating     INTEGER NOT NULL CHECK (rating BETWEEN 0 AND 5),
+  updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Saved smart sort presets.
+--  - criteria: JSON array of { criterion, weight }
+CREATE TABLE IF NOT EXISTS sort_presets (
+  name       TEXT PRIMARY KEY,
+  criteria   TEXT NOT NULL,
+  updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,4 @@
+DROP TRIGGER IF EXISTS lyrics_fts_delete;
+DROP TRIGGER IF EXISTS lyrics_fts_update;
+DROP TRIGGER IF EXISTS lyrics_fts_insert;
+DROP TABLE IF EXISTS lyrics_fts;
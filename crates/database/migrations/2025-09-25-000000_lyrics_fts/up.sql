@@ -0,0 +1,35 @@
+-- Full-text index over track lyrics, kept in sync with tracks by triggers.
+-- The trigram tokenizer gives case-insensitive substring matching, which also
+-- works for CJK lyrics where there are no spaces between words.
+CREATE VIRTUAL TABLE IF NOT EXISTS lyrics_fts USING fts5(
+  track_id UNINDEXED,
+  lyrics,
+  tokenize = 'trigram'
+);
+
+INSERT INTO lyrics_fts (track_id, lyrics)
+SELECT _id, lyrics FROM tracks
+WHERE _id IS NOT NULL AND lyrics IS NOT NULL AND lyrics != '';
+
+CREATE TRIGGER lyrics_fts_insert
+AFTER INSERT ON tracks
+WHEN NEW._id IS NOT NULL AND NEW.lyrics IS NOT NULL AND NEW.lyrics != ''
+BEGIN
+  INSERT INTO lyrics_fts (track_id, lyrics) VALUES (NEW._id, NEW.lyrics);
+END;
+
+CREATE TRIGGER lyrics_fts_update
+AFTER UPDATE OF _id, lyrics ON tracks
+WHEN OLD._id IS NOT NEW._id OR OLD.lyrics IS NOT NEW.lyrics
+BEGIN
+  DELETE FROM lyrics_fts WHERE track_id = OLD._id;
+  INSERT INTO lyrics_fts (track_id, lyrics)
+  SELECT NEW._id, NEW.lyrics
+  WHERE NEW._id IS NOT NULL AND NEW.lyrics IS NOT NULL AND NEW.lyrics != '';
+END;
+
+CREATE TRIGGER lyrics_fts_delete
+AFTER DELETE ON tracks
+BEGIN
+  DELETE FROM lyrics_fts WHERE track_id = OLD._id;
+END;
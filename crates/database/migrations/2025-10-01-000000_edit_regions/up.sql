@@ -0,0 +1,8 @@
+-- Non-destructive per-track edits applied at playback.
+--  - regions: JSON array of { start, end, kind } in seconds of the original
+--             audio, kind 'skip' | 'mute'
+CREATE TABLE IF NOT EXISTS track_edit_regions (
+  track_id   TEXT PRIMARY KEY,
+  regions    TEXT NOT NULL,
+  updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,12 @@
+-- Loudness analysis read from the ReplayGain tags of local files, cached so
+-- playback and the normalization preview don't reopen the files.
+--  - *_gain: dB relative to the ReplayGain reference level
+--  - *_peak: linear sample peak, 1.0 is full scale
+CREATE TABLE IF NOT EXISTS track_gain (
+  track_id    TEXT PRIMARY KEY,
+  track_gain  DOUBLE,
+  track_peak  DOUBLE,
+  album_gain  DOUBLE,
+  album_peak  DOUBLE,
+  updated_at  DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,13 @@
+-- Snapshots of playlist membership taken on every modification, so a bad
+-- bulk removal or sync can be reverted. Bounded per playlist.
+--  - version:   increasing per playlist, starting at 1
+--  - reason:    'initial' | 'add' | 'remove' | 'restore' | 'external'
+--  - track_ids: JSON array of track ids in playlist order
+CREATE TABLE IF NOT EXISTS playlist_history (
+  playlist_id TEXT NOT NULL,
+  version     INTEGER NOT NULL,
+  reason      TEXT NOT NULL,
+  track_ids   TEXT NOT NULL,
+  created_at  DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (playlist_id, version)
+);
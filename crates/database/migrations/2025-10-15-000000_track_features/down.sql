@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS track_features_mood;
+DROP TABLE IF EXISTS track_features;
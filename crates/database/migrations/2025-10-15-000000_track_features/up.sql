@@ -0,0 +1,16 @@
+-- Audio features extracted locally from the decoded audio of tracks, and the
+-- mood bucket they fall into. Used by feature conditions and mood radio.
+--  - energy, valence, danceability: 0..1 heuristics
+--  - tempo: estimated beats per minute
+--  - mood:  'energetic' | 'tense' | 'calm' | 'melancholic'
+CREATE TABLE IF NOT EXISTS track_features (
+  track_id      TEXT PRIMARY KEY,
+  energy        DOUBLE NOT NULL,
+  valence       DOUBLE NOT NULL,
+  danceability  DOUBLE NOT NULL,
+  tempo         DOUBLE NOT NULL,
+  mood          TEXT NOT NULL,
+  updated_at    DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS track_features_mood ON track_features (mood);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS episodes_podcast;
+DROP TABLE IF EXISTS episodes;
+DROP TABLE IF EXISTS podcasts;
@@ -0,0 +1,31 @@
+-- Podcast subscriptions and their episodes. Times are unix ms.
+--  - episodes.position: resume position in seconds, 0 when not started
+CREATE TABLE IF NOT EXISTS podcasts (
+  id            TEXT PRIMARY KEY NOT NULL,
+  feed_url      TEXT NOT NULL UNIQUE,
+  title         TEXT NOT NULL,
+  author        TEXT,
+  description   TEXT,
+  image_url     TEXT,
+  link          TEXT,
+  subscribed_at BIGINT NOT NULL,
+  refreshed_at  BIGINT,
+  last_error    TEXT
+);
+
+CREATE TABLE IF NOT EXISTS episodes (
+  id            TEXT PRIMARY KEY NOT NULL,
+  podcast_id    TEXT NOT NULL REFERENCES podcasts (id) ON DELETE CASCADE,
+  guid          TEXT NOT NULL,
+  title         TEXT NOT NULL,
+  description   TEXT,
+  audio_url     TEXT NOT NULL,
+  mime_type     TEXT,
+  duration      DOUBLE,
+  published_at  BIGINT,
+  position      DOUBLE NOT NULL DEFAULT 0,
+  played        BOOLEAN NOT NULL DEFAULT 0,
+  UNIQUE (podcast_id, guid)
+);
+
+CREATE INDEX IF NOT EXISTS episodes_podcast ON episodes (podcast_id, published_at);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS track_positions;
+DROP TABLE IF EXISTS track_chapters;
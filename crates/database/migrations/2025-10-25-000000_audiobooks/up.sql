@@ -0,0 +1,17 @@
+-- Chapters read from the tags of tracks (ID3 CHAP, MP4 chpl), in seconds.
+-- end_time is NULL for a last chapter running to the end of the track.
+CREATE TABLE IF NOT EXISTS track_chapters (
+  track_id    TEXT NOT NULL,
+  idx         INTEGER NOT NULL,
+  start_time  DOUBLE NOT NULL,
+  end_time    DOUBLE,
+  title       TEXT,
+  PRIMARY KEY (track_id, idx)
+);
+
+-- Where playback of long tracks (audiobooks) left off, in seconds
+CREATE TABLE IF NOT EXISTS track_positions (
+  track_id    TEXT PRIMARY KEY,
+  position    DOUBLE NOT NULL,
+  updated_at  DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_track_bookmarks_track;
+DROP TABLE IF EXISTS track_bookmarks;
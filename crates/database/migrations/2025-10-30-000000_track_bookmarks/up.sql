@@ -0,0 +1,11 @@
+-- Positions bookmarked inside tracks (cue points of DJ mixes, songs of
+-- concerts), in seconds of the original audio
+CREATE TABLE IF NOT EXISTS track_bookmarks (
+  bookmark_id TEXT PRIMARY KEY,
+  track_id    TEXT NOT NULL,
+  position    DOUBLE NOT NULL,
+  label       TEXT,
+  created_at  DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_track_bookmarks_track ON track_bookmarks(track_id, position);
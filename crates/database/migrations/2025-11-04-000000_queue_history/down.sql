@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_queue_history_created;
+DROP TABLE IF EXISTS queue_history;
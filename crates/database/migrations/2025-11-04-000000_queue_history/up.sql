@@ -0,0 +1,12 @@
+-- Play queues replaced or cleared, so an earlier listening session can be
+-- picked up again. Bounded to the most recent ones.
+--  - name:      shown in the history (album name, first track)
+--  - track_ids: JSON array of track ids in queue order
+CREATE TABLE IF NOT EXISTS queue_history (
+  history_id TEXT PRIMARY KEY,
+  name       TEXT NOT NULL,
+  track_ids  TEXT NOT NULL,
+  created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_queue_history_created ON queue_history(created_at);
@@ -0,0 +1,9 @@
+-- Reachability of the scanned music folders. Tracks below a folder that is
+-- unreachable (network share offline, drive unmounted) are kept and reported
+-- as unavailable instead of being removed from the library.
+--  - changed_at: unix seconds of the last change of `available`
+CREATE TABLE IF NOT EXISTS library_roots (
+  root_path  TEXT PRIMARY KEY,
+  available  BOOLEAN NOT NULL DEFAULT 1,
+  changed_at BIGINT NOT NULL
+);
@@ -0,0 +1,16 @@
+-- Chromaprint fingerprints of local tracks, computed after scans when
+-- `music.fingerprint.enabled` is on. A track whose file disappears is matched
+-- against them to find where it moved, and its history, playlists and ratings
+-- follow it there.
+--  - fingerprint: JSON array of the raw 32-bit fingerprint items
+--  - duration:    seconds, to narrow the candidates of a match
+--  - acoustid:    AcoustID track id, once looked up
+CREATE TABLE IF NOT EXISTS track_fingerprints (
+  track_id     TEXT PRIMARY KEY,
+  fingerprint  TEXT NOT NULL,
+  duration     DOUBLE NOT NULL,
+  acoustid     TEXT,
+  updated_at   DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS track_fingerprints_duration ON track_fingerprints (duration);
@@ -0,0 +1,2 @@
+ALTER TABLE tracks DROP COLUMN silence_end;
+ALTER TABLE tracks DROP COLUMN silence_start;
@@ -0,0 +1,7 @@
+-- Where the audio of local tracks starts and ends, detected after scans while
+-- `music.playback.trimSilence` is on, so long lead-ins and lead-outs can be
+-- skipped. NULL until detected. Read and written with raw queries.
+--  - silence_start: seconds of silence at the start
+--  - silence_end:   position in seconds where the trailing silence begins
+ALTER TABLE tracks ADD COLUMN silence_start DOUBLE;
+ALTER TABLE tracks ADD COLUMN silence_end DOUBLE;
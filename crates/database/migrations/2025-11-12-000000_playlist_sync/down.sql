@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS playlist_sync_conflicts;
+DROP TABLE IF EXISTS playlist_sync;
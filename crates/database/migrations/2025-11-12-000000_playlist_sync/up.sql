@@ -0,0 +1,31 @@
+-- Local playlists linked to a playlist of an online provider and synced in
+-- both directions. Read and written with raw queries.
+--  - provider_id:    plugin ID of the provider
+--  - remote_id:      ID of the playlist at the provider
+--  - base_track_ids: JSON array of the track ids both sides had after the last
+--                    sync, which tells the changes of each side apart; NULL
+--                    before the first sync
+--  - last_synced_at: unix time in ms of the last sync that went through
+CREATE TABLE IF NOT EXISTS playlist_sync (
+  playlist_id    TEXT PRIMARY KEY NOT NULL,
+  provider_id    TEXT NOT NULL,
+  remote_id      TEXT NOT NULL,
+  base_track_ids TEXT,
+  last_synced_at BIGINT,
+  last_error     TEXT
+);
+
+-- Changes of one side that could not be carried to the other, for the user
+-- to settle. A settled conflict stays while the change it is about stays, so
+-- it is not raised again.
+--  - kind:       'local_addition' | 'local_removal'
+--  - resolution: NULL while open, 'local' when the local change is kept
+CREATE TABLE IF NOT EXISTS playlist_sync_conflicts (
+  id          INTEGER PRIMARY KEY AUTOINCREMENT,
+  playlist_id TEXT NOT NULL,
+  track_id    TEXT NOT NULL,
+  kind        TEXT NOT NULL,
+  resolution  TEXT,
+  detected_at BIGINT NOT NULL,
+  UNIQUE (playlist_id, track_id, kind)
+);
@@ -0,0 +1,13 @@
+-- Liked songs, one list across providers. Likes of tracks whose provider
+-- keeps favorites are mirrored to the account; the favorites of an account
+-- are imported on sign-in. Read and written with raw queries.
+--  - origin:   'local' when liked in the app, else the provider (plugin name)
+--              whose favorites the like was imported from
+--  - liked_at: unix time in ms
+CREATE TABLE IF NOT EXISTS liked_tracks (
+  track_id TEXT PRIMARY KEY NOT NULL,
+  origin   TEXT NOT NULL DEFAULT 'local',
+  liked_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS liked_tracks_liked_at ON liked_tracks (liked_at);
@@ -0,0 +1,226 @@
+// crates/database/src/collation.rs
+// Locale-aware sorting and CJK romanization used by the library search layer.
+// Romanized forms are precomputed into the `romanized_names` table on insert so
+// that queries like "zjl" can match 周杰伦 with a plain indexed LIKE.
+
+use std::cmp::Ordering;
+
+use icu_collator::{Collator, CollatorOptions, Strength};
+use icu_locid::Locale;
+use pinyin::ToPinyin;
+
+pub const KIND_TRACK: &str = "track";
+pub const KIND_ALBUM: &str = "album";
+pub const KIND_ARTIST: &str = "artist";
+
+/// Romanized representation of a name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Romanization {
+    /// Lowercase syllables without separators, e.g. "zhoujielun"
+    pub romanized: String,
+    /// First letter of every syllable/word, e.g. "zjl"
+    pub initials: String,
+}
+
+fn is_han(c: char) -> bool {
+    matches!(c as u32,
+        0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x2FA1F)
+}
+
+fn is_kana(c: char) -> bool {
+    matches!(c as u32, 0x3041..=0x3096 | 0x30A1..=0x30FA | 0x30FC)
+}
+
+/// Returns true when `s` contains characters that benefit from romanization.
+pub fn needs_romanization(s: &str) -> bool {
+    s.chars().any(|c| is_han(c) || is_kana(c))
+}
+
+/// Hepburn romaji for a single hiragana character (katakana is folded first).
+fn kana_romaji(c: char) -> Option<&'static str> {
+    // Fold katakana into the hiragana block
+    let c = match c as u32 {
+        0x30A1..=0x30F6 => char::from_u32(c as u32 - 0x60)?,
+        _ => c,
+    };
+    let r = match c {
+        'あ' => "a", 'い' => "i", 'う' => "u", 'え' => "e", 'お' => "o",
+        'か' => "ka", 'き' => "ki", 'く' => "ku", 'け' => "ke", 'こ' => "ko",
+        'が' => "ga", 'ぎ' => "gi", 'ぐ' => "gu", 'げ' => "ge", 'ご' => "go",
+        'さ' => "sa", 'し' => "shi", 'す' => "su", 'せ' => "se", 'そ' => "so",
+        'ざ' => "za", 'じ' => "ji", 'ず' => "zu", 'ぜ' => "ze", 'ぞ' => "zo",
+        'た' => "ta", 'ち' => "chi", 'つ' => "tsu", 'て' => "te", 'と' => "to",
+        'だ' => "da", 'ぢ' => "ji", 'づ' => "zu", 'で' => "de", 'ど' => "do",
+        'な' => "na", 'に' => "ni", 'ぬ' => "nu", 'ね' => "ne", 'の' => "no",
+        'は' => "ha", 'ひ' => "hi", 'ふ' => "fu", 'へ' => "he", 'ほ' => "ho",
+        'ば' => "ba", 'び' => "bi", 'ぶ' => "bu", 'べ' => "be", 'ぼ' => "bo",
+        'ぱ' => "pa", 'ぴ' => "pi", 'ぷ' => "pu", 'ぺ' => "pe", 'ぽ' => "po",
+        'ま' => "ma", 'み' => "mi", 'む' => "mu", 'め' => "me", 'も' => "mo",
+        'や' => "ya", 'ゆ' => "yu", 'よ' => "yo",
+        'ら' => "ra", 'り' => "ri", 'る' => "ru", 'れ' => "re", 'ろ' => "ro",
+        'わ' => "wa", 'ゐ' => "i", 'ゑ' => "e", 'を' => "o", 'ん' => "n",
+        'ゔ' => "vu",
+        'ぁ' => "a", 'ぃ' => "i", 'ぅ' => "u", 'ぇ' => "e", 'ぉ' => "o",
+        'ゃ' => "ya", 'ゅ' => "yu", 'ょ' => "yo", 'ゎ' => "wa",
+        _ => return None,
+    };
+    Some(r)
+}
+
+fn is_small_y(c: char) -> bool {
+    matches!(c, 'ゃ' | 'ゅ' | 'ょ' | 'ャ' | 'ュ' | 'ョ')
+}
+
+fn is_sokuon(c: char) -> bool {
+    matches!(c, 'っ' | 'ッ')
+}
+
+/// Compute pinyin (Han) / romaji (kana) for `s`.
+/// Returns None when `s` has no CJK characters; Latin names are matched directly.
+pub fn romanize(s: &str) -> Option<Romanization> {
+    if !needs_romanization(s) {
+        return None;
+    }
+
+    // Each entry is one syllable or Latin word
+    let mut syllables: Vec<String> = vec![];
+    let mut word = String::new();
+    let mut double_next = false;
+
+    let flush = |word: &mut String, syllables: &mut Vec<String>| {
+        if !word.is_empty() {
+            syllables.push(std::mem::take(word));
+        }
+    };
+
+    for (c, py) in s.chars().zip(s.to_pinyin()) {
+        if let Some(py) = py {
+            flush(&mut word, &mut syllables);
+            syllables.push(py.plain().to_string());
+        } else if is_sokuon(c) {
+            flush(&mut word, &mut syllables);
+            double_next = true;
+        } else if is_small_y(c) {
+            // きゃ -> kya, しゃ -> sha, ちゃ -> cha
+            if let (Some(prev), Some(r)) = (syllables.last_mut(), kana_romaji(c)) {
+                if prev.len() > 1 && prev.ends_with('i') {
+                    prev.pop();
+                    if prev.ends_with("sh") || prev.ends_with("ch") || prev == "j" {
+                        prev.push_str(&r[1..]);
+                    } else {
+                        prev.push_str(r);
+                    }
+                    continue;
+                }
+            }
+            if let Some(r) = kana_romaji(c) {
+                syllables.push(r.to_string());
+            }
+        } else if c == 'ー' {
+            // Long vowel mark: repeat the previous vowel
+            if let Some(v) = syllables.last().and_then(|p| p.chars().last()) {
+                if let Some(prev) = syllables.last_mut() {
+                    prev.push(v);
+                }
+            }
+        } else if let Some(r) = kana_romaji(c) {
+            flush(&mut word, &mut syllables);
+            let mut syl = r.to_string();
+            if std::mem::take(&mut double_next) {
+                if let Some(first) = syl.chars().next() {
+                    syl.insert(0, first);
+                }
+            }
+            syllables.push(syl);
+        } else if c.is_alphanumeric() {
+            word.extend(c.to_lowercase());
+        } else {
+            flush(&mut word, &mut syllables);
+        }
+    }
+    flush(&mut word, &mut syllables);
+
+    let romanized: String = syllables.concat();
+    let initials: String = syllables.iter().filter_map(|s| s.chars().next()).collect();
+    Some(Romanization { romanized, initials })
+}
+
+/// Normalize a user query for romanized matching: lowercase ASCII, no separators.
+pub fn normalize_query(term: &str) -> String {
+    term.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// ICU collator for the active UI locale.
+pub struct LibraryCollator {
+    collator: Option<Collator>,
+}
+
+impl LibraryCollator {
+    /// `locale` is a BCP-47 tag such as "zh-CN" or "ja". Unknown or empty
+    /// tags fall back to the root collation; construction never fails.
+    pub fn new(locale: Option<&str>) -> Self {
+        let locale: Locale = locale
+            .and_then(|l| l.replace('_', "-").parse().ok())
+            .unwrap_or(Locale::UND);
+        let mut options = CollatorOptions::new();
+        // Ignore case differences, keep accents significant
+        options.strength = Some(Strength::Secondary);
+        let collator = Collator::try_new(&(&locale).into(), options)
+            .map_err(|e| tracing::warn!("Failed to create collator for {}: {:?}", locale, e))
+            .ok();
+        Self { collator }
+    }
+
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match &self.collator {
+            Some(c) => c.compare(a, b),
+            None => a.to_lowercase().cmp(&b.to_lowercase()),
+        }
+    }
+
+    /// Stable sort by a string key; missing keys sort last.
+    pub fn sort_by_key<T, F>(&self, items: &mut [T], key: F)
+    where
+        F: Fn(&T) -> Option<&str>,
+    {
+        items.sort_by(|a, b| match (key(a), key(b)) {
+            (Some(a), Some(b)) => self.compare(a, b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pinyin_initials() {
+        let r = romanize("周杰伦").unwrap();
+        assert_eq!(r.romanized, "zhoujielun");
+        assert_eq!(r.initials, "zjl");
+
+        let r = romanize("Jay 周杰伦").unwrap();
+        assert_eq!(r.initials, "jzjl");
+        assert!(romanize("Jay Chou").is_none());
+    }
+
+    #[test]
+    fn kana_to_romaji() {
+        assert_eq!(romanize("さくら").unwrap().romanized, "sakura");
+        assert_eq!(romanize("きょう").unwrap().romanized, "kyou");
+        assert_eq!(romanize("ちょっと").unwrap().romanized, "chotto");
+        assert_eq!(romanize("ラーメン").unwrap().romanized, "raamen");
+    }
+
+    #[test]
+    fn collator_falls_back_on_bad_locale() {
+        let c = LibraryCollator::new(Some("not a locale"));
+        assert_eq!(c.compare("a", "B"), Ordering::Less);
+    }
+}
@@ -2,7 +2,7 @@ use std::cmp::min;
 
 use std::fmt::Write;
 use std::str::FromStr;
-use std::{path::PathBuf, vec};
+use std::{path::{Path, PathBuf}, vec};
 
 use diesel::{
     connection::SimpleConnection,
@@ -15,12 +15,18 @@ use diesel::{BoolExpressionMethods, Insertable, TextExpressionMethods};
 use diesel_logger::LoggingConnection;
 use macros::{filter_field, filter_field_like};
 use serde_json::Value;
-use tracing::{debug, info, trace, warn};
+use tracing::{debug, error, info, trace, warn};
 use uuid::Uuid;
 
 use types::common::{BridgeUtils, SearchByTerm};
-use types::entities::{EntityInfo, PlaylistBridge, PluginState};
+use types::entities::{
+    ArtworkSet, DistributionEntry, EntityInfo, FolderNode, LibraryRootStatus, LibrarySearchResult, PlaylistBridge, PlaylistDuplicate,
+    LyricsSearchHit, PlaylistInsights, PlaylistRestore, PlaylistSyncConflict, PlaylistSyncLink, PlaylistVersion, PluginState, LikedTrack, PlayHistoryEntry, QueueSnapshot, RomanizedName, SmartSortCriterion, SmartSortPreset,
+    TrackAudioFeatures, TrackFeatureFilter, TrackFingerprint, TrackMetadataEdit, TrackMood,
+};
+use types::podcasts::{Podcast, PodcastEpisode};
 use types::tracks::SearchableTrack;
+use types::ui::player_details::{EditRegion, TrackBookmark, TrackChapter, TrackGain, TrackSilence};
 use types::errors::{Result, error_helpers};
 use types::schema::playlists::dsl::playlists;
 use types::{
@@ -47,21 +53,542 @@ use types::{
     },
 };
 
-use super::migrations::run_migrations;
+use super::collation::{self, LibraryCollator, KIND_ALBUM, KIND_ARTIST, KIND_TRACK};
+use super::lyrics;
+use super::migrations::{self, migrate_database};
+
+/// Maximum number of rows per entity returned by `search_library`
+const LIBRARY_SEARCH_LIMIT: i64 = 200;
+/// Snapshots kept per playlist in `playlist_history`
+const MAX_PLAYLIST_VERSIONS: i64 = 50;
+/// Play queues kept in `queue_history`
+const MAX_QUEUE_SNAPSHOTS: i64 = 20;
+/// The trigram tokenizer of `lyrics_fts` cannot match shorter phrases
+const LYRICS_FTS_MIN_CHARS: usize = 3;
+
+#[derive(diesel::QueryableByName)]
+struct PlaylistTotalsRow {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    entries: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    unique_tracks: i64,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    total_duration: f64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    missing: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    remote: i64,
+}
+
+#[derive(diesel::QueryableByName)]
+struct LabelCountRow {
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    label: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    count: i64,
+}
+
+#[derive(diesel::QueryableByName)]
+struct PlaylistEntryRow {
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    track: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    title: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    year: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    path: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    type_: Option<String>,
+}
+
+#[derive(diesel::QueryableByName)]
+struct DownloadCandidateRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    track_id: String,
+}
+
+#[derive(diesel::QueryableByName)]
+struct DownloadSizeRow {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    total: i64,
+}
+
+#[derive(diesel::QueryableByName)]
+struct LibrarySizeRow {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    tracks: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    total: i64,
+}
+
+#[derive(diesel::QueryableByName)]
+struct RankedTrackRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    track_id: String,
+}
+
+#[derive(diesel::QueryableByName)]
+struct EditRegionsRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    regions: String,
+}
+
+#[derive(diesel::QueryableByName)]
+struct TrackGainRow {
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Double>)]
+    track_gain: Option<f64>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Double>)]
+    track_peak: Option<f64>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Double>)]
+    album_gain: Option<f64>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Double>)]
+    album_peak: Option<f64>,
+}
+
+#[derive(diesel::QueryableByName)]
+struct TrackSilenceRow {
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    silence_start: f64,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    silence_end: f64,
+}
+
+#[derive(diesel::QueryableByName)]
+struct TrackFeaturesRow {
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    energy: f64,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    valence: f64,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    danceability: f64,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    tempo: f64,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    mood: String,
+}
+
+#[derive(diesel::QueryableByName)]
+struct TrackFingerprintRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    track_id: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    fingerprint: String,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    duration: f64,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    acoustid: Option<String>,
+}
+
+#[derive(diesel::QueryableByName)]
+struct TrackChapterRow {
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    start_time: f64,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Double>)]
+    end_time: Option<f64>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    title: Option<String>,
+}
+
+#[derive(diesel::QueryableByName)]
+struct TrackPositionRow {
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    position: f64,
+}
+
+#[derive(diesel::QueryableByName)]
+struct TrackBookmarkRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    bookmark_id: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    track_id: String,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    position: f64,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    label: Option<String>,
+}
+
+impl From<TrackBookmarkRow> for TrackBookmark {
+    fn from(row: TrackBookmarkRow) -> Self {
+        Self {
+            id: row.bookmark_id,
+            track_id: row.track_id,
+            position: row.position,
+            label: row.label,
+        }
+    }
+}
+
+#[derive(diesel::QueryableByName)]
+struct QueueSnapshotRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    history_id: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    track_ids: String,
+    #[diesel(sql_type = diesel::sql_types::Timestamp)]
+    created_at: chrono::NaiveDateTime,
+}
+
+impl From<QueueSnapshotRow> for QueueSnapshot {
+    fn from(row: QueueSnapshotRow) -> Self {
+        Self {
+            id: row.history_id,
+            name: row.name,
+            track_ids: serde_json::from_str(&row.track_ids).unwrap_or_default(),
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(diesel::QueryableByName)]
+struct PlaylistVersionRow {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    version: i64,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    reason: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    track_ids: String,
+    #[diesel(sql_type = diesel::sql_types::Timestamp)]
+    created_at: chrono::NaiveDateTime,
+}
+
+#[derive(diesel::QueryableByName)]
+struct PlaylistSyncRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    playlist_id: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    provider_id: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    remote_id: String,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::BigInt>)]
+    last_synced_at: Option<i64>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    last_error: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    conflicts: i64,
+}
+
+impl From<PlaylistSyncRow> for PlaylistSyncLink {
+    fn from(row: PlaylistSyncRow) -> Self {
+        Self {
+            playlist_id: row.playlist_id,
+            provider_id: row.provider_id,
+            remote_id: row.remote_id,
+            last_synced_at: row.last_synced_at,
+            last_error: row.last_error,
+            conflicts: row.conflicts as u32,
+        }
+    }
+}
+
+const PLAYLIST_SYNC_COLUMNS: &str = "s.playlist_id, s.provider_id, s.remote_id, s.last_synced_at, s.last_error,
+     (SELECT COUNT(*) FROM playlist_sync_conflicts c
+      WHERE c.playlist_id = s.playlist_id AND c.resolution IS NULL) AS conflicts";
+
+#[derive(diesel::QueryableByName)]
+struct PlaylistSyncConflictRow {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    id: i64,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    playlist_id: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    track_id: String,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    track_title: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    kind: String,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    resolution: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    detected_at: i64,
+}
+
+impl From<PlaylistSyncConflictRow> for PlaylistSyncConflict {
+    fn from(row: PlaylistSyncConflictRow) -> Self {
+        Self {
+            id: row.id,
+            playlist_id: row.playlist_id,
+            track_id: row.track_id,
+            track_title: row.track_title,
+            kind: row.kind,
+            detected_at: row.detected_at,
+        }
+    }
+}
+
+const PLAYLIST_SYNC_CONFLICT_QUERY: &str = "SELECT c.id, c.playlist_id, c.track_id, t.title AS track_title, c.kind,
+     c.resolution, c.detected_at
+     FROM playlist_sync_conflicts c LEFT JOIN tracks t ON t._id = c.track_id";
+
+/// Track ids of a playlist in playlist order
+fn playlist_track_ids(
+    conn: &mut LoggingConnection<SqliteConnection>,
+    playlist_id: &str,
+) -> diesel::QueryResult<Vec<String>> {
+    let ids: Vec<Option<String>> = QueryDsl::filter(
+        schema::playlist_bridge::table,
+        schema::playlist_bridge::playlist.eq(playlist_id),
+    )
+    .order(schema::playlist_bridge::id.asc())
+    .select(schema::playlist_bridge::track)
+    .load(conn)?;
+    Ok(ids.into_iter().flatten().collect())
+}
+
+fn latest_playlist_version(
+    conn: &mut LoggingConnection<SqliteConnection>,
+    playlist_id: &str,
+) -> diesel::QueryResult<Option<PlaylistVersionRow>> {
+    use diesel::sql_query;
+    use diesel::sql_types::Text;
+
+    sql_query(
+        "SELECT version, reason, track_ids, created_at FROM playlist_history
+         WHERE playlist_id = ? ORDER BY version DESC LIMIT 1",
+    )
+    .bind::<Text, _>(playlist_id)
+    .get_result(conn)
+    .optional()
+}
+
+/// Snapshot the current membership of a playlist unless it equals the latest
+/// snapshot, dropping snapshots beyond `MAX_PLAYLIST_VERSIONS`. Returns the
+/// version recorded.
+fn record_playlist_version(
+    conn: &mut LoggingConnection<SqliteConnection>,
+    playlist_id: &str,
+    reason: &str,
+) -> diesel::QueryResult<Option<i64>> {
+    use diesel::sql_query;
+    use diesel::sql_types::{BigInt, Text};
+
+    let track_ids = serde_json::to_string(&playlist_track_ids(conn, playlist_id)?).unwrap_or_default();
+    let latest = latest_playlist_version(conn, playlist_id)?;
+    if latest.as_ref().is_some_and(|l| l.track_ids == track_ids) {
+        return Ok(None);
+    }
+    let version = latest.map(|l| l.version + 1).unwrap_or(1);
+    sql_query("INSERT INTO playlist_history (playlist_id, version, reason, track_ids) VALUES (?, ?, ?, ?)")
+        .bind::<Text, _>(playlist_id)
+        .bind::<BigInt, _>(version)
+        .bind::<Text, _>(reason)
+        .bind::<Text, _>(&track_ids)
+        .execute(conn)?;
+    sql_query("DELETE FROM playlist_history WHERE playlist_id = ? AND version <= ?")
+        .bind::<Text, _>(playlist_id)
+        .bind::<BigInt, _>(version - MAX_PLAYLIST_VERSIONS)
+        .execute(conn)?;
+    Ok(Some(version))
+}
+
+/// Snapshot taken before a modification, so the first one can be reverted and
+/// changes made without a snapshot (imports) are kept as their own version
+fn record_playlist_baseline(
+    conn: &mut LoggingConnection<SqliteConnection>,
+    playlist_id: &str,
+) -> diesel::QueryResult<Option<i64>> {
+    let reason = if latest_playlist_version(conn, playlist_id)?.is_some() { "external" } else { "initial" };
+    record_playlist_version(conn, playlist_id, reason)
+}
+
+/// Bounds of the paths below `dir`: from `dir/` up to, excluding, the next
+/// separator character, so a prefix match is a range scan on the path index
+fn folder_path_range(dir: &Path) -> (String, String) {
+    let separator = std::path::MAIN_SEPARATOR;
+    let dir = dir.to_string_lossy();
+    let dir = dir.trim_end_matches(separator);
+    let after = char::from_u32(separator as u32 + 1).unwrap();
+    (format!("{}{}", dir, separator), format!("{}{}", dir, after))
+}
+
+/// Folder tree of the track files `files` below `root`
+fn build_folder_tree(root: &Path, files: impl Iterator<Item = PathBuf>) -> FolderNode {
+    fn sort(node: &mut FolderNode) {
+        node.children.sort_by(|a, b| a.name.cmp(&b.name));
+        node.children.iter_mut().for_each(sort);
+    }
+
+    let mut tree = FolderNode {
+        path: root.to_string_lossy().to_string(),
+        name: root
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| root.to_string_lossy().to_string()),
+        ..Default::default()
+    };
+    for file in files {
+        let Some(folder) = file.parent().and_then(|p| p.strip_prefix(root).ok()) else {
+            continue;
+        };
+        let mut node = &mut tree;
+        node.track_count += 1;
+        for part in folder.components() {
+            let name = part.as_os_str().to_string_lossy().to_string();
+            let index = match node.children.iter().position(|c| c.name == name) {
+                Some(index) => index,
+                None => {
+                    node.children.push(FolderNode {
+                        path: Path::new(&node.path).join(&name).to_string_lossy().to_string(),
+                        name,
+                        ..Default::default()
+                    });
+                    node.children.len() - 1
+                }
+            };
+            node = &mut node.children[index];
+            node.track_count += 1;
+        }
+    }
+    sort(&mut tree);
+    tree
+}
+
+#[derive(diesel::QueryableByName)]
+struct PodcastRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    id: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    feed_url: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    title: String,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    author: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    description: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    image_url: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    link: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::BigInt>)]
+    refreshed_at: Option<i64>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    last_error: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    episode_count: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    unplayed_count: i64,
+}
+
+impl From<PodcastRow> for Podcast {
+    fn from(row: PodcastRow) -> Self {
+        Podcast {
+            id: row.id,
+            feed_url: row.feed_url,
+            title: row.title,
+            author: row.author,
+            description: row.description,
+            image_url: row.image_url,
+            link: row.link,
+            refreshed_at: row.refreshed_at,
+            last_error: row.last_error,
+            episode_count: row.episode_count,
+            unplayed_count: row.unplayed_count,
+        }
+    }
+}
+
+#[derive(diesel::QueryableByName)]
+struct EpisodeRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    id: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    podcast_id: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    guid: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    title: String,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    description: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    audio_url: String,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    mime_type: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Double>)]
+    duration: Option<f64>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::BigInt>)]
+    published_at: Option<i64>,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    position: f64,
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    played: bool,
+}
+
+impl From<EpisodeRow> for PodcastEpisode {
+    fn from(row: EpisodeRow) -> Self {
+        PodcastEpisode {
+            id: row.id,
+            podcast_id: row.podcast_id,
+            guid: row.guid,
+            title: row.title,
+            description: row.description,
+            audio_url: row.audio_url,
+            mime_type: row.mime_type,
+            duration: row.duration,
+            published_at: row.published_at,
+            position: row.position,
+            played: row.played,
+        }
+    }
+}
+
+const PODCAST_COLUMNS: &str = "p.id, p.feed_url, p.title, p.author, p.description, p.image_url, p.link,
+    p.refreshed_at, p.last_error,
+    (SELECT COUNT(*) FROM episodes e WHERE e.podcast_id = p.id) AS episode_count,
+    (SELECT COUNT(*) FROM episodes e WHERE e.podcast_id = p.id AND NOT e.played) AS unplayed_count";
+
+const EPISODE_COLUMNS: &str =
+    "id, podcast_id, guid, title, description, audio_url, mime_type, duration, published_at, position, played";
+
+#[derive(diesel::QueryableByName)]
+struct SortPresetRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    name: String,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    criteria: String,
+}
+
+/// "1994" / "1994-03-01" -> "1990s"
+fn decade_label(year: Option<&str>) -> String {
+    year.and_then(|y| y.trim().get(..4))
+        .and_then(|y| y.parse::<u32>().ok())
+        .filter(|y| *y > 0)
+        .map(|y| format!("{}s", y / 10 * 10))
+        .unwrap_or_else(|| "Unknown".to_string())
+}
 
 #[derive(Debug, Clone)]
 pub struct Database {
     pool: Pool<ConnectionManager<LoggingConnection<SqliteConnection>>>,
+    path: PathBuf,
 }
 
 impl Database {
     #[tracing::instrument(level = "debug", skip(path))]
     pub fn new(path: PathBuf) -> Self {
+        // A restored library replaces the database before anything opens it
+        match migrations::apply_staged_restore(&path) {
+            Ok(true) => info!("Restored the library from a backup"),
+            Ok(false) => {}
+            Err(e) => error!("Failed to restore the library from a backup: {:?}", e),
+        }
+
+        // Migrate before the pool opens any connection so a failed upgrade can
+        // restore the pre-migration backup in place
+        let report = migrate_database(&path, false).expect("Failed to run migrations");
+        if !report.applied.is_empty() {
+            info!(
+                "Applied migrations {:?}, affected tables: {:?}",
+                report.applied, report.affected_tables
+            );
+        }
+
         let db = Self {
-            pool: Self::connect(path),
+            pool: Self::connect(path.clone()),
+            path,
         };
 
-        run_migrations(&mut db.pool.get().expect("Failed to get connection to DB"));
         db.pool.get().unwrap().batch_execute("
             PRAGMA journal_mode = WAL;          -- better write-concurrency
             PRAGMA synchronous = NORMAL;        -- fsync only in critical moments
@@ -70,6 +597,10 @@ impl Database {
             PRAGMA busy_timeout = 250;          -- sleep if the database is busy
         ").expect("Failed to set DB options");
 
+        if let Err(e) = db.backfill_romanization() {
+            warn!("Failed to backfill romanized names: {:?}", e);
+        }
+
         info!("Created DB instance");
         db
     }
@@ -196,92 +727,105 @@ impl Database {
     pub fn insert_tracks_by_ref(&self, tracks: &mut [MediaContent]) -> Result<()> {
         let mut conn = self.pool.get().unwrap();
         trace!("Inserting tracks");
-        for track in tracks {
-            if track.track._id.is_none() {
-                // Use file hash as ID if available, otherwise generate random ID
-                if let Some(hash) = &track.track.hash {
-                    track.track._id = Some(hash.clone());
-                    tracing::debug!("Using file hash as ID: {}", hash);
-                } else {
-                    track.track._id = Some(Uuid::new_v4().to_string());
-                    tracing::debug!("Generated random ID for track");
+        // One transaction per batch, SQLite commits are the bulk of the cost
+        conn.transaction::<(), types::errors::MusicError, _>(|conn| {
+            for track in tracks {
+                if track.track._id.is_none() {
+                    // Use file hash as ID if available, otherwise generate random ID
+                    if let Some(hash) = &track.track.hash {
+                        track.track._id = Some(hash.clone());
+                        tracing::debug!("Using file hash as ID: {}", hash);
+                    } else {
+                        track.track._id = Some(Uuid::new_v4().to_string());
+                        tracing::debug!("Generated random ID for track");
+                    }
                 }
-            }
-
-            let changed = insert_into(tracks_table)
-                .values(&track.track)
-                .on_conflict(_id)
-                .do_update()
-                .set(&track.track)
-                .execute(&mut conn).map_err(error_helpers::to_database_error)?;
-
-            if changed == 0 {
-                continue;
-            }
 
-            if let Some(_album) = &mut track.album {
-                let album_id_ = self
-                    .get_albums(
-                        QueryableAlbum::search_by_term(_album.album_name.clone()),
-                        false,
-                        &mut conn,
-                    )?
-                    .first()
-                    .map(|v| v.album_id.clone().unwrap())
-                    .unwrap_or_else(|| self.insert_album(&mut conn, _album).unwrap());
+                let changed = insert_into(tracks_table)
+                    .values(&track.track)
+                    .on_conflict(_id)
+                    .do_update()
+                    .set(&track.track)
+                    .execute(conn).map_err(error_helpers::to_database_error)?;
 
-                AlbumBridge::insert_value(album_id_.clone(), track.track._id.clone().unwrap())
-                    .insert_into(album_bridge)
-                    .on_conflict_do_nothing()
-                    .execute(&mut conn).map_err(error_helpers::to_database_error)?;
+                if changed == 0 {
+                    continue;
+                }
 
-                _album.album_id = Some(album_id_);
-            }
+                self.upsert_romanization(
+                    conn,
+                    KIND_TRACK,
+                    track.track._id.as_deref().unwrap(),
+                    track.track.title.as_deref(),
+                )?;
 
-            if let Some(_artists) = &mut track.artists {
-                for mut _artist in _artists {
-                    let artist_id_ = self
-                        .get_artists(
-                            QueryableArtist::search_by_term(_artist.artist_name.clone()),
+                if let Some(_album) = &mut track.album {
+                    let album_id_ = self
+                        .get_albums(
+                            QueryableAlbum::search_by_term(_album.album_name.clone()),
                             false,
-                            &mut conn,
+                            conn,
                         )?
                         .first()
-                        .map(|v| v.artist_id.clone().unwrap())
-                        .unwrap_or_else(|| self.insert_artist(&mut conn, _artist).unwrap());
+                        .map(|v| v.album_id.clone().unwrap())
+                        .unwrap_or_else(|| self.insert_album(conn, _album).unwrap());
+                    self.upsert_romanization(conn, KIND_ALBUM, &album_id_, _album.album_name.as_deref())?;
 
-                    ArtistBridge::insert_value(artist_id_.clone(), track.track._id.clone().unwrap())
-                        .insert_into(artist_bridge)
+                    AlbumBridge::insert_value(album_id_.clone(), track.track._id.clone().unwrap())
+                        .insert_into(album_bridge)
                         .on_conflict_do_nothing()
-                        .execute(&mut conn).map_err(error_helpers::to_database_error)?;
+                        .execute(conn).map_err(error_helpers::to_database_error)?;
 
-                    _artist.artist_id = Some(artist_id_);
+                    _album.album_id = Some(album_id_);
                 }
-            }
-
-            if let Some(_genres) = &mut track.genre {
-                for mut _genre in _genres {
-                    let genre_id_ = self
-                        .get_genres(
-                            QueryableGenre::search_by_term(_genre.genre_name.clone()),
-                            false,
-                            &mut conn,
-                        )?
-                        .first()
-                        .map(|v| v.genre_id.clone().unwrap())
-                        .unwrap_or_else(|| self.insert_genre(&mut conn, _genre).unwrap());
 
-                    GenreBridge::insert_value(genre_id_.clone(), track.track._id.clone().unwrap())
-                        .insert_into(genre_bridge)
-                        .on_conflict_do_nothing()
-                        .execute(&mut conn).map_err(error_helpers::to_database_error)?;
+                if let Some(_artists) = &mut track.artists {
+                    for mut _artist in _artists {
+                        let artist_id_ = self
+                            .get_artists(
+                                QueryableArtist::search_by_term(_artist.artist_name.clone()),
+                                false,
+                                conn,
+                            )?
+                            .first()
+                            .map(|v| v.artist_id.clone().unwrap())
+                            .unwrap_or_else(|| self.insert_artist(conn, _artist).unwrap());
+                        self.upsert_romanization(conn, KIND_ARTIST, &artist_id_, _artist.artist_name.as_deref())?;
+
+                        ArtistBridge::insert_value(artist_id_.clone(), track.track._id.clone().unwrap())
+                            .insert_into(artist_bridge)
+                            .on_conflict_do_nothing()
+                            .execute(conn).map_err(error_helpers::to_database_error)?;
+
+                        _artist.artist_id = Some(artist_id_);
+                    }
+                }
 
-                    _genre.genre_id = Some(genre_id_);
+                if let Some(_genres) = &mut track.genre {
+                    for mut _genre in _genres {
+                        let genre_id_ = self
+                            .get_genres(
+                                QueryableGenre::search_by_term(_genre.genre_name.clone()),
+                                false,
+                                conn,
+                            )?
+                            .first()
+                            .map(|v| v.genre_id.clone().unwrap())
+                            .unwrap_or_else(|| self.insert_genre(conn, _genre).unwrap());
+
+                        GenreBridge::insert_value(genre_id_.clone(), track.track._id.clone().unwrap())
+                            .insert_into(genre_bridge)
+                            .on_conflict_do_nothing()
+                            .execute(conn).map_err(error_helpers::to_database_error)?;
+
+                        _genre.genre_id = Some(genre_id_);
+                    }
                 }
-            }
 
-            trace!("Inserted track, {:?}", track);
-        }
+                trace!("Inserted track, {:?}", track);
+            }
+            Ok(())
+        })?;
         info!("Inserted all tracks");
         Ok(())
     }
@@ -332,9 +876,11 @@ impl Database {
     pub fn update_track(&self, track: Tracks) -> Result<()> {
         trace!("Updating track");
         if let Some(id) = track._id.as_ref() {
+            let mut conn = self.pool.get().unwrap();
             update(tracks_table.filter(schema::tracks::_id.eq(id.clone())))
                 .set(&track)
-                .execute(&mut self.pool.get().unwrap()).map_err(error_helpers::to_database_error)?;
+                .execute(&mut conn).map_err(error_helpers::to_database_error)?;
+            self.upsert_romanization(&mut conn, KIND_TRACK, id, track.title.as_deref())?;
             debug!("Updated track");
         } else {
             debug!("MediaContent does not have an ID");
@@ -342,6 +888,107 @@ impl Database {
         Ok(())
     }
 
+    /// Apply a tag edit to a track: its own fields are updated, and a new
+    /// album, artists or genre are linked in place of the previous ones,
+    /// created when not in the library yet.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn edit_track_metadata(&self, track_id: &str, edit: &TrackMetadataEdit) -> Result<()> {
+        let mut conn = self.pool.get().unwrap();
+
+        if let Some(title) = &edit.title {
+            update(tracks_table.filter(schema::tracks::_id.eq(track_id)))
+                .set(schema::tracks::title.eq(title))
+                .execute(&mut conn).map_err(error_helpers::to_database_error)?;
+            self.upsert_romanization(&mut conn, KIND_TRACK, track_id, Some(title))?;
+        }
+        if let Some(track_no) = edit.track_no {
+            update(tracks_table.filter(schema::tracks::_id.eq(track_id)))
+                .set(schema::tracks::track_no.eq(track_no as f64))
+                .execute(&mut conn).map_err(error_helpers::to_database_error)?;
+        }
+        if let Some(year) = &edit.year {
+            update(tracks_table.filter(schema::tracks::_id.eq(track_id)))
+                .set(schema::tracks::year.eq(year))
+                .execute(&mut conn).map_err(error_helpers::to_database_error)?;
+        }
+
+        if let Some(album_name) = &edit.album {
+            delete(QueryDsl::filter(album_bridge, schema::album_bridge::track.eq(track_id)))
+                .execute(&mut conn).map_err(error_helpers::to_database_error)?;
+            let (cover_high, cover_low): (Option<String>, Option<String>) = tracks_table
+                .filter(schema::tracks::_id.eq(track_id))
+                .select((schema::tracks::track_coverpath_high, schema::tracks::track_coverpath_low))
+                .first(&mut conn)
+                .map_err(error_helpers::to_database_error)?;
+            let mut album = QueryableAlbum {
+                album_name: Some(album_name.clone()),
+                album_coverpath_high: cover_high,
+                album_coverpath_low: cover_low,
+                ..Default::default()
+            };
+            let album_id_ = match self
+                .get_albums(QueryableAlbum::search_by_term(album.album_name.clone()), false, &mut conn)?
+                .first()
+            {
+                Some(existing) => existing.album_id.clone().unwrap(),
+                None => self.insert_album(&mut conn, &mut album)?,
+            };
+            self.upsert_romanization(&mut conn, KIND_ALBUM, &album_id_, Some(album_name))?;
+            AlbumBridge::insert_value(album_id_, track_id.to_string())
+                .insert_into(album_bridge)
+                .on_conflict_do_nothing()
+                .execute(&mut conn).map_err(error_helpers::to_database_error)?;
+        }
+
+        if let Some(artist_names) = &edit.artists {
+            delete(QueryDsl::filter(artist_bridge, schema::artist_bridge::track.eq(track_id)))
+                .execute(&mut conn).map_err(error_helpers::to_database_error)?;
+            for name in artist_names.iter().map(|n| n.trim()).filter(|n| !n.is_empty()) {
+                let mut artist = QueryableArtist {
+                    artist_name: Some(name.to_string()),
+                    ..Default::default()
+                };
+                let artist_id_ = match self
+                    .get_artists(QueryableArtist::search_by_term(artist.artist_name.clone()), false, &mut conn)?
+                    .first()
+                {
+                    Some(existing) => existing.artist_id.clone().unwrap(),
+                    None => self.insert_artist(&mut conn, &mut artist)?,
+                };
+                self.upsert_romanization(&mut conn, KIND_ARTIST, &artist_id_, Some(name))?;
+                ArtistBridge::insert_value(artist_id_, track_id.to_string())
+                    .insert_into(artist_bridge)
+                    .on_conflict_do_nothing()
+                    .execute(&mut conn).map_err(error_helpers::to_database_error)?;
+            }
+        }
+
+        if let Some(genre_name) = &edit.genre {
+            delete(QueryDsl::filter(genre_bridge, schema::genre_bridge::track.eq(track_id)))
+                .execute(&mut conn).map_err(error_helpers::to_database_error)?;
+            let genre_name = genre_name.trim();
+            if !genre_name.is_empty() {
+                let mut genre = QueryableGenre {
+                    genre_name: Some(genre_name.to_string()),
+                    ..Default::default()
+                };
+                let genre_id_ = match self
+                    .get_genres(QueryableGenre::search_by_term(genre.genre_name.clone()), false, &mut conn)?
+                    .first()
+                {
+                    Some(existing) => existing.genre_id.clone().unwrap(),
+                    None => self.insert_genre(&mut conn, &mut genre)?,
+                };
+                GenreBridge::insert_value(genre_id_, track_id.to_string())
+                    .insert_into(genre_bridge)
+                    .on_conflict_do_nothing()
+                    .execute(&mut conn).map_err(error_helpers::to_database_error)?;
+            }
+        }
+        debug!("Edited track metadata");
+        Ok(())
+    }
+
     #[tracing::instrument(level = "debug", skip(self, conn))]
     fn get_albums(
         &self,
@@ -499,12 +1146,21 @@ impl Database {
         trace!("Getting entity by options");
 
         if options.album.is_some() {
-            return Ok(serde_json::to_value(self.get_albums(
-                options.album.unwrap(),
-                inclusive,
-                &mut conn,
-            )?)
-            .unwrap());
+            let found = self.get_albums(options.album.unwrap(), inclusive, &mut conn)?;
+            let mut value = serde_json::to_value(&found).unwrap();
+            // Attach artwork variants so lists can pick a size-appropriate cover
+            if let Value::Array(items) = &mut value {
+                for (item, album) in items.iter_mut().zip(found.iter()) {
+                    let set = ArtworkSet::from_cover_paths(
+                        album.album_coverpath_high.as_deref(),
+                        album.album_coverpath_low.as_deref(),
+                    );
+                    if let Value::Object(obj) = item {
+                        obj.insert("artwork".to_string(), serde_json::to_value(set).unwrap());
+                    }
+                }
+            }
+            return Ok(value);
         }
 
         if options.artist.is_some() {
@@ -773,89 +1429,493 @@ impl Database {
         Ok(ret)
     }
 
-    #[tracing::instrument(level = "debug", skip(self))]
-    pub fn files_not_in_db(
+    /// Store (or drop) the precomputed pinyin/romaji for one entity name.
+    /// Names without CJK characters have no row; they match via plain LIKE.
+    fn upsert_romanization(
         &self,
-        mut file_list: Vec<(PathBuf, f64)>,
-    ) -> Result<Vec<(PathBuf, f64)>> {
-        let mut conn = self.pool.get().unwrap();
-
-        let len = file_list.len();
-
-        let mut ret = vec![];
-
-        let exp_limit = 998;
-        for _ in 0..len / exp_limit + 1 {
-            let curr_len = min(len, exp_limit);
-            let mut query =
-                QueryDsl::select(tracks_table, (schema::tracks::path, schema::tracks::size))
-                    .into_boxed();
-            for _ in 0..curr_len {
-                let data = file_list.pop().unwrap();
-                let predicate = schema::tracks::path
-                    .eq(data.0.to_string_lossy().to_string())
-                    .and(schema::tracks::size.eq(data.1));
-                query = query.or_filter(predicate);
+        conn: &mut PooledConnection<ConnectionManager<LoggingConnection<SqliteConnection>>>,
+        kind: &str,
+        id: &str,
+        name: Option<&str>,
+    ) -> Result<()> {
+        use schema::romanized_names::dsl;
+
+        let romanization = name.and_then(|n| collation::romanize(n).map(|r| (n, r)));
+        match romanization {
+            Some((source, r)) => {
+                let row = RomanizedName {
+                    entity_id: id.to_string(),
+                    entity_kind: kind.to_string(),
+                    source: source.to_string(),
+                    romanized: r.romanized,
+                    initials: r.initials,
+                };
+                insert_into(dsl::romanized_names)
+                    .values(&row)
+                    .on_conflict((dsl::entity_id, dsl::entity_kind))
+                    .do_update()
+                    .set(&row)
+                    .execute(conn).map_err(error_helpers::to_database_error)?;
+            }
+            None => {
+                delete(
+                    dsl::romanized_names
+                        .filter(dsl::entity_id.eq(id))
+                        .filter(dsl::entity_kind.eq(kind)),
+                )
+                .execute(conn).map_err(error_helpers::to_database_error)?;
             }
-
-            let mut res = query
-                .load::<(Option<String>, Option<f64>)>(&mut conn).map_err(error_helpers::to_database_error)?
-                .iter()
-                .map(|v| {
-                    (
-                        PathBuf::from_str(v.0.as_ref().unwrap()).unwrap(),
-                        v.1.unwrap(),
-                    )
-                })
-                .collect::<Vec<_>>();
-            ret.append(&mut res);
         }
-        Ok(ret)
+        Ok(())
     }
 
+    /// Compute romanized names for rows that predate the `romanized_names` table
+    /// or whose name changed outside of the regular update paths.
     #[tracing::instrument(level = "debug", skip(self))]
-    pub fn add_to_playlist(&self, id: String, mut tracks: Vec<MediaContent>) -> Result<()> {
-        trace!("Adding to playlist");
-        tracks.iter_mut().for_each(|v| {
-            v.track.show_in_library = Some(false);
-        });
-        let res = self.insert_tracks_by_ref(tracks.as_mut_slice());
-        if let Err(e) = res {
-            // Lets hope it only fails due to unique value constrains
-            tracing::warn!(
-                "Failed to insert tracks in DB, maybe they already exist: {:?}",
-                e
-            );
-        }
+    pub fn backfill_romanization(&self) -> Result<usize> {
+        use schema::romanized_names::dsl;
 
         let mut conn = self.pool.get().unwrap();
-        for s in tracks {
-            if let Err(e) = insert_into(playlist_bridge)
-                .values((
-                    schema::playlist_bridge::playlist.eq(id.clone()),
-                    schema::playlist_bridge::track.eq(s.track._id.clone()),
-                ))
-                .execute(&mut conn)
-            {
-                warn!("Failed to add {:?} to playlist: {:?}", s, e);
+        let existing: std::collections::HashMap<(String, String), String> = dsl::romanized_names
+            .select((dsl::entity_id, dsl::entity_kind, dsl::source))
+            .load::<(String, String, String)>(&mut conn)
+            .map_err(error_helpers::to_database_error)?
+            .into_iter()
+            .map(|(id, kind, source)| ((id, kind), source))
+            .collect();
+
+        let track_names: Vec<(Option<String>, Option<String>)> = schema::tracks::table
+            .select((schema::tracks::_id, schema::tracks::title))
+            .load(&mut conn).map_err(error_helpers::to_database_error)?;
+        let album_names: Vec<(Option<String>, Option<String>)> = schema::albums::table
+            .select((schema::albums::album_id, schema::albums::album_name))
+            .load(&mut conn).map_err(error_helpers::to_database_error)?;
+        let artist_names: Vec<(Option<String>, Option<String>)> = schema::artists::table
+            .select((schema::artists::artist_id, schema::artists::artist_name))
+            .load(&mut conn).map_err(error_helpers::to_database_error)?;
+
+        let mut updated = 0;
+        for (kind, rows) in [
+            (KIND_TRACK, track_names),
+            (KIND_ALBUM, album_names),
+            (KIND_ARTIST, artist_names),
+        ] {
+            for (id, name) in rows {
+                let (Some(id), Some(name)) = (id, name) else { continue };
+                if !collation::needs_romanization(&name) {
+                    continue;
+                }
+                let key = (id.clone(), kind.to_string());
+                if existing.get(&key).map(|s| s == &name).unwrap_or(false) {
+                    continue;
+                }
+                self.upsert_romanization(&mut conn, kind, &id, Some(&name))?;
+                updated += 1;
             }
         }
-        info!("Added to playlist");
-        Ok(())
-    }
 
-    #[tracing::instrument(level = "debug", skip(self))]
-    pub fn remove_from_playlist(&self, id: String, tracks: Vec<String>) -> Result<()> {
-        trace!("Removing from playlist");
-        let mut conn = self.pool.get().unwrap();
-        for s in tracks {
-            delete(playlist_bridge)
-                .filter(schema::playlist_bridge::playlist.eq(id.clone()))
-                .filter(schema::playlist_bridge::track.eq(s.clone()))
-                .execute(&mut conn).map_err(error_helpers::to_database_error)?;
+        if updated > 0 {
+            info!("Backfilled {} romanized names", updated);
         }
-        info!("Removed from playlist");
-        Ok(())
+        Ok(updated)
+    }
+
+    /// Entity ids whose precomputed romanization matches the normalized query,
+    /// either as a prefix of the initials ("zjl") or inside the full romanization ("jielun").
+    fn romanized_matches(
+        &self,
+        conn: &mut PooledConnection<ConnectionManager<LoggingConnection<SqliteConnection>>>,
+        kind: &str,
+        query: &str,
+    ) -> Result<Vec<Option<String>>> {
+        use schema::romanized_names::dsl;
+
+        if query.is_empty() {
+            return Ok(vec![]);
+        }
+        let ids: Vec<String> = dsl::romanized_names
+            .select(dsl::entity_id)
+            .filter(dsl::entity_kind.eq(kind))
+            .filter(
+                dsl::initials
+                    .like(format!("{}%", query))
+                    .or(dsl::romanized.like(format!("%{}%", query))),
+            )
+            .limit(LIBRARY_SEARCH_LIMIT)
+            .load(conn).map_err(error_helpers::to_database_error)?;
+        Ok(ids.into_iter().map(Some).collect())
+    }
+
+    /// Search local tracks, albums and artists by name, including pinyin/romaji
+    /// matching for CJK metadata. Results are sorted with the collation of `locale`.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn search_library(&self, term: &str, locale: Option<&str>) -> Result<LibrarySearchResult> {
+        let term = term.trim();
+        if term.is_empty() {
+            return Ok(LibrarySearchResult::default());
+        }
+
+        let mut conn = self.pool.get().unwrap();
+        let pattern = format!("%{}%", term);
+        let query = collation::normalize_query(term);
+
+        let track_ids = self.romanized_matches(&mut conn, KIND_TRACK, &query)?;
+        let fetched: Vec<Tracks> = schema::tracks::table
+            .filter(
+                schema::tracks::title
+                    .like(pattern.clone())
+                    .or(schema::tracks::_id.eq_any(track_ids)),
+            )
+            .limit(LIBRARY_SEARCH_LIMIT)
+            .load(&mut conn).map_err(error_helpers::to_database_error)?;
+        let mut found_tracks = vec![];
+        for t in fetched {
+            found_tracks.push(self.get_track_from_queryable(&mut conn, t)?);
+        }
+
+        let album_ids = self.romanized_matches(&mut conn, KIND_ALBUM, &query)?;
+        let mut found_albums: Vec<QueryableAlbum> = schema::albums::table
+            .filter(
+                schema::albums::album_name
+                    .like(pattern.clone())
+                    .or(schema::albums::album_id.eq_any(album_ids)),
+            )
+            .limit(LIBRARY_SEARCH_LIMIT)
+            .load(&mut conn).map_err(error_helpers::to_database_error)?;
+
+        let artist_ids = self.romanized_matches(&mut conn, KIND_ARTIST, &query)?;
+        let mut found_artists: Vec<QueryableArtist> = schema::artists::table
+            .filter(
+                schema::artists::artist_name
+                    .like(pattern)
+                    .or(schema::artists::artist_id.eq_any(artist_ids)),
+            )
+            .limit(LIBRARY_SEARCH_LIMIT)
+            .load(&mut conn).map_err(error_helpers::to_database_error)?;
+
+        let collator = LibraryCollator::new(locale);
+        collator.sort_by_key(&mut found_tracks, |t| t.track.title.as_deref());
+        collator.sort_by_key(&mut found_albums, |a| a.album_name.as_deref());
+        collator.sort_by_key(&mut found_artists, |a| a.artist_name.as_deref());
+
+        info!(
+            "Library search matched {} tracks, {} albums, {} artists",
+            found_tracks.len(),
+            found_albums.len(),
+            found_artists.len()
+        );
+        let mut artwork = std::collections::HashMap::new();
+        for t in &found_tracks {
+            let set = ArtworkSet::from_cover_paths(
+                t.track.track_cover_path_high.as_deref(),
+                t.track.track_cover_path_low.as_deref(),
+            );
+            if let (Some(id), false) = (t.track._id.clone(), set.is_empty()) {
+                artwork.insert(id, set);
+            }
+        }
+        for a in &found_albums {
+            let set = ArtworkSet::from_cover_paths(
+                a.album_coverpath_high.as_deref(),
+                a.album_coverpath_low.as_deref(),
+            );
+            if let (Some(id), false) = (a.album_id.clone(), set.is_empty()) {
+                artwork.insert(id, set);
+            }
+        }
+
+        Ok(LibrarySearchResult {
+            tracks: found_tracks,
+            albums: found_albums,
+            artists: found_artists,
+            artwork,
+            ..Default::default()
+        })
+    }
+
+    /// Tracks whose lyrics contain `phrase` (case-insensitive), best matches
+    /// first, with the matching line as snippet and its time for LRC lyrics.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn search_lyrics(&self, phrase: &str, limit: i64) -> Result<Vec<LyricsSearchHit>> {
+        use diesel::sql_query;
+        use diesel::sql_types::{BigInt, Text};
+
+        let phrase = phrase.trim();
+        if phrase.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<RankedTrackRow> = if phrase.chars().count() >= LYRICS_FTS_MIN_CHARS {
+            // Quoted as a single FTS phrase so operators in the input are literal
+            sql_query(
+                "SELECT track_id FROM lyrics_fts WHERE lyrics_fts MATCH ? ORDER BY rank LIMIT ?",
+            )
+            .bind::<Text, _>(format!("\"{}\"", phrase.replace('"', "\"\"")))
+            .bind::<BigInt, _>(limit)
+            .load(&mut conn)
+        } else {
+            let escaped = phrase.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+            sql_query(
+                "SELECT _id AS track_id FROM tracks
+                 WHERE _id IS NOT NULL AND lyrics LIKE ? ESCAPE '\\' LIMIT ?",
+            )
+            .bind::<Text, _>(format!("%{}%", escaped))
+            .bind::<BigInt, _>(limit)
+            .load(&mut conn)
+        }
+        .map_err(error_helpers::to_database_error)?;
+
+        let ids: Vec<String> = rows.into_iter().map(|r| r.track_id).collect();
+        let mut fetched: Vec<Tracks> = schema::tracks::table
+            .filter(schema::tracks::_id.eq_any(ids.clone()))
+            .load(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        // Keep the FTS ranking
+        fetched.sort_by_key(|t| t._id.as_ref().and_then(|id| ids.iter().position(|i| i == id)));
+
+        let mut hits = vec![];
+        for t in fetched {
+            let Some(found) = t.lyrics.as_deref().and_then(|l| lyrics::find_phrase(l, phrase)) else {
+                // Matched across a line break; nothing to highlight
+                continue;
+            };
+            hits.push(LyricsSearchHit {
+                track: self.get_track_from_queryable(&mut conn, t)?,
+                snippet: found.snippet,
+                highlight_start: found.start as u32,
+                highlight_end: found.end as u32,
+                time_ms: found.time_ms,
+            });
+        }
+        info!("Lyrics search matched {} tracks", hits.len());
+        Ok(hits)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn files_not_in_db(
+        &self,
+        mut file_list: Vec<(PathBuf, f64)>,
+    ) -> Result<Vec<(PathBuf, f64)>> {
+        let mut conn = self.pool.get().unwrap();
+
+        let len = file_list.len();
+
+        let mut ret = vec![];
+
+        let exp_limit = 998;
+        for _ in 0..len / exp_limit + 1 {
+            let curr_len = min(len, exp_limit);
+            let mut query =
+                QueryDsl::select(tracks_table, (schema::tracks::path, schema::tracks::size))
+                    .into_boxed();
+            for _ in 0..curr_len {
+                let data = file_list.pop().unwrap();
+                let predicate = schema::tracks::path
+                    .eq(data.0.to_string_lossy().to_string())
+                    .and(schema::tracks::size.eq(data.1));
+                query = query.or_filter(predicate);
+            }
+
+            let mut res = query
+                .load::<(Option<String>, Option<f64>)>(&mut conn).map_err(error_helpers::to_database_error)?
+                .iter()
+                .map(|v| {
+                    (
+                        PathBuf::from_str(v.0.as_ref().unwrap()).unwrap(),
+                        v.1.unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            ret.append(&mut res);
+        }
+        Ok(ret)
+    }
+
+    /// Folders holding library tracks below each of `roots`, one tree per
+    /// root. Paths are matched as a range on the indexed `path` column.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_folder_tree(&self, roots: &[PathBuf]) -> Result<Vec<FolderNode>> {
+        let mut conn = self.pool.get().unwrap();
+        let mut trees = Vec::with_capacity(roots.len());
+        for root in roots {
+            let (lower, upper) = folder_path_range(root);
+            let paths: Vec<Option<String>> = QueryDsl::select(tracks_table, schema::tracks::path)
+                .filter(schema::tracks::path.ge(lower))
+                .filter(schema::tracks::path.lt(upper))
+                .load(&mut conn)
+                .map_err(error_helpers::to_database_error)?;
+            trees.push(build_folder_tree(root, paths.into_iter().flatten().map(PathBuf::from)));
+        }
+        Ok(trees)
+    }
+
+    /// Tracks in the folder `dir` by path, and in its subfolders when
+    /// `recursive`.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_tracks_in_folder(&self, dir: &Path, recursive: bool) -> Result<Vec<MediaContent>> {
+        let mut conn = self.pool.get().unwrap();
+        let (lower, upper) = folder_path_range(dir);
+        let found: Vec<Tracks> = schema::tracks::table
+            .filter(schema::tracks::path.ge(lower))
+            .filter(schema::tracks::path.lt(upper))
+            .order(schema::tracks::path.asc())
+            .load(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+
+        let mut ret = vec![];
+        for track in found {
+            let direct = track
+                .path
+                .as_deref()
+                .and_then(|p| Path::new(p).parent())
+                .is_some_and(|parent| parent == dir);
+            if recursive || direct {
+                ret.push(self.get_track_from_queryable(&mut conn, track)?);
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Record whether the scan root `root` is reachable. Returns whether this
+    /// changed its known state; a root first seen reachable is no change.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_root_availability(&self, root: &Path, available: bool) -> Result<bool> {
+        use schema::library_roots::dsl;
+
+        let root = root.to_string_lossy().to_string();
+        let mut conn = self.pool.get().unwrap();
+        let known: Option<bool> = QueryDsl::select(dsl::library_roots, dsl::available)
+            .filter(dsl::root_path.eq(&root))
+            .first(&mut conn)
+            .optional()
+            .map_err(error_helpers::to_database_error)?;
+        let changed = match known {
+            Some(known) => known != available,
+            None => !available,
+        };
+        if known.is_none() || changed {
+            diesel::replace_into(dsl::library_roots)
+                .values((
+                    dsl::root_path.eq(&root),
+                    dsl::available.eq(available),
+                    dsl::changed_at.eq(chrono::Utc::now().timestamp()),
+                ))
+                .execute(&mut conn)
+                .map_err(error_helpers::to_database_error)?;
+        }
+        Ok(changed)
+    }
+
+    /// Known scan roots with their reachability and track count.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_library_roots(&self) -> Result<Vec<LibraryRootStatus>> {
+        use schema::library_roots::dsl;
+
+        let mut conn = self.pool.get().unwrap();
+        let roots: Vec<(String, bool, i64)> = dsl::library_roots
+            .order(dsl::root_path.asc())
+            .load(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+
+        let mut ret = Vec::with_capacity(roots.len());
+        for (root, available, changed_at) in roots {
+            let (lower, upper) = folder_path_range(Path::new(&root));
+            let track_count: i64 = tracks_table
+                .filter(schema::tracks::path.ge(lower))
+                .filter(schema::tracks::path.lt(upper))
+                .count()
+                .get_result(&mut conn)
+                .map_err(error_helpers::to_database_error)?;
+            ret.push(LibraryRootStatus {
+                root,
+                available,
+                changed_at,
+                track_count: track_count as u32,
+            });
+        }
+        Ok(ret)
+    }
+
+    /// Ids of the tracks below unreachable scan roots.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_unavailable_track_ids(&self) -> Result<Vec<String>> {
+        use schema::library_roots::dsl;
+
+        let mut conn = self.pool.get().unwrap();
+        let roots: Vec<String> = QueryDsl::select(dsl::library_roots, dsl::root_path)
+            .filter(dsl::available.eq(false))
+            .load(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+
+        let mut ret = vec![];
+        for root in roots {
+            let (lower, upper) = folder_path_range(Path::new(&root));
+            let ids: Vec<Option<String>> = QueryDsl::select(tracks_table, _id)
+                .filter(schema::tracks::path.ge(lower))
+                .filter(schema::tracks::path.lt(upper))
+                .load(&mut conn)
+                .map_err(error_helpers::to_database_error)?;
+            ret.extend(ids.into_iter().flatten());
+        }
+        Ok(ret)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn add_to_playlist(&self, id: String, mut tracks: Vec<MediaContent>) -> Result<()> {
+        trace!("Adding to playlist");
+        tracks.iter_mut().for_each(|v| {
+            v.track.show_in_library = Some(false);
+        });
+        let res = self.insert_tracks_by_ref(tracks.as_mut_slice());
+        if let Err(e) = res {
+            // Lets hope it only fails due to unique value constrains
+            tracing::warn!(
+                "Failed to insert tracks in DB, maybe they already exist: {:?}",
+                e
+            );
+        }
+
+        let mut conn = self.pool.get().unwrap();
+        if let Err(e) = record_playlist_baseline(&mut conn, &id) {
+            warn!("Failed to snapshot playlist {}: {:?}", id, e);
+        }
+        for s in tracks {
+            if let Err(e) = insert_into(playlist_bridge)
+                .values((
+                    schema::playlist_bridge::playlist.eq(id.clone()),
+                    schema::playlist_bridge::track.eq(s.track._id.clone()),
+                ))
+                .execute(&mut conn)
+            {
+                warn!("Failed to add {:?} to playlist: {:?}", s, e);
+            }
+        }
+        if let Err(e) = record_playlist_version(&mut conn, &id, "add") {
+            warn!("Failed to snapshot playlist {}: {:?}", id, e);
+        }
+        info!("Added to playlist");
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn remove_from_playlist(&self, id: String, tracks: Vec<String>) -> Result<()> {
+        trace!("Removing from playlist");
+        let mut conn = self.pool.get().unwrap();
+        if let Err(e) = record_playlist_baseline(&mut conn, &id) {
+            warn!("Failed to snapshot playlist {}: {:?}", id, e);
+        }
+        for s in tracks {
+            delete(playlist_bridge)
+                .filter(schema::playlist_bridge::playlist.eq(id.clone()))
+                .filter(schema::playlist_bridge::track.eq(s.clone()))
+                .execute(&mut conn).map_err(error_helpers::to_database_error)?;
+        }
+        if let Err(e) = record_playlist_version(&mut conn, &id, "remove") {
+            warn!("Failed to snapshot playlist {}: {:?}", id, e);
+        }
+        info!("Removed from playlist");
+        Ok(())
     }
 
     #[tracing::instrument(level = "debug", skip(self))]
@@ -868,6 +1928,15 @@ impl Database {
         delete(playlists)
             .filter(schema::playlists::playlist_id.eq(id.clone()))
             .execute(&mut conn).map_err(error_helpers::to_database_error)?;
+        diesel::sql_query("DELETE FROM playlist_history WHERE playlist_id = ?")
+            .bind::<diesel::sql_types::Text, _>(&id)
+            .execute(&mut conn).map_err(error_helpers::to_database_error)?;
+        diesel::sql_query("DELETE FROM playlist_sync WHERE playlist_id = ?")
+            .bind::<diesel::sql_types::Text, _>(&id)
+            .execute(&mut conn).map_err(error_helpers::to_database_error)?;
+        diesel::sql_query("DELETE FROM playlist_sync_conflicts WHERE playlist_id = ?")
+            .bind::<diesel::sql_types::Text, _>(&id)
+            .execute(&mut conn).map_err(error_helpers::to_database_error)?;
 
         info!("Removed playlist");
         Ok(())
@@ -918,6 +1987,10 @@ impl Database {
 
         album.album_extra_info = self.merge_extra_info(existing_album_info, album.album_extra_info);
 
+        if let Some(id) = album.album_id.clone() {
+            self.upsert_romanization(&mut conn, KIND_ALBUM, &id, album.album_name.as_deref())?;
+        }
+
         update(albums)
             .filter(schema::albums::album_id.eq(album.album_id.clone()))
             .set(album)
@@ -947,6 +2020,10 @@ impl Database {
         artist.artist_extra_info =
             self.merge_extra_info(existing_artist_info, artist.artist_extra_info);
 
+        if let Some(id) = artist.artist_id.clone() {
+            self.upsert_romanization(&mut conn, KIND_ARTIST, &id, artist.artist_name.as_deref())?;
+        }
+
         update(artists)
             .filter(schema::artists::artist_id.eq(artist.artist_id.clone()))
             .set(artist)
@@ -982,6 +2059,9 @@ impl Database {
                     self.update_artist(a)?;
                 }
             }
+            if let Some(id) = track.track._id.as_deref() {
+                self.upsert_romanization(&mut conn, KIND_TRACK, id, track.track.title.as_deref())?;
+            }
             update(tracks_table)
                 .filter(schema::tracks::_id.eq(track.track._id.clone()))
                 .set(track.track)
@@ -1023,6 +2103,16 @@ impl Database {
         Ok(())
     }
 
+    /// Delete play history recorded at or after `since` (UTC), e.g. everything a
+    /// guest played during this app session. Returns the number of removed entries.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn purge_play_history_since(&self, since: chrono::NaiveDateTime) -> Result<usize> {
+        let mut conn = self.pool.get().unwrap();
+        delete(play_history.filter(schema::play_history::played_at.ge(since)))
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub fn clear_play_queue(&self) -> Result<()> {
         let mut conn = self.pool.get().unwrap();
@@ -1081,38 +2171,713 @@ impl Database {
         Ok(())
     }
 
+    /// Membership snapshots of a playlist, newest first. `added` and `removed`
+    /// compare each version with the previous one kept.
     #[tracing::instrument(level = "debug", skip(self))]
-    pub fn export_playlist(&self, playlist_id: String) -> Result<String> {
-        let mut conn = self.pool.get().unwrap();
+    pub fn get_playlist_history(&self, playlist_id: &str) -> Result<Vec<PlaylistVersion>> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
 
-        let binding = self.get_playlists(
-            QueryablePlaylist {
-                playlist_id: Some(playlist_id.clone()),
-                ..Default::default()
-            },
-            true,
-            &mut conn,
-        )?;
-        let playlist = binding.first();
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<PlaylistVersionRow> = sql_query(
+            "SELECT version, reason, track_ids, created_at FROM playlist_history
+             WHERE playlist_id = ? ORDER BY version ASC",
+        )
+        .bind::<Text, _>(playlist_id)
+        .load(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
 
-        if playlist.is_none() {
-            return Err("Playlist not found".into());
+        let mut previous: std::collections::HashSet<String> = std::collections::HashSet::new();
+        let mut history = Vec::with_capacity(rows.len());
+        for (index, row) in rows.into_iter().enumerate() {
+            let track_ids: Vec<String> = serde_json::from_str(&row.track_ids).unwrap_or_default();
+            let current: std::collections::HashSet<String> = track_ids.iter().cloned().collect();
+            // The oldest version kept has nothing to compare with
+            let (added, removed) = if index == 0 {
+                (0, 0)
+            } else {
+                (current.difference(&previous).count(), previous.difference(&current).count())
+            };
+            history.push(PlaylistVersion {
+                playlist_id: playlist_id.to_string(),
+                version: row.version,
+                reason: row.reason,
+                track_count: track_ids.len() as u32,
+                added: added as u32,
+                removed: removed as u32,
+                created_at: row.created_at,
+            });
+            previous = current;
         }
+        history.reverse();
+        Ok(history)
+    }
 
-        let playlist = playlist.unwrap();
+    /// Replace the membership of a playlist with a snapshot. The current
+    /// membership is snapshotted first, so the restore itself can be reverted;
+    /// tracks no longer in the library are left out.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn restore_playlist_version(&self, playlist_id: &str, version: i64) -> Result<PlaylistRestore> {
+        use diesel::sql_query;
+        use diesel::sql_types::{BigInt, Text};
 
-        let playlist_tracks = self.get_tracks_by_options(GetTrackOptions {
-            playlist: Some(QueryablePlaylist {
-                playlist_id: Some(playlist_id),
-                ..Default::default()
-            }),
-            ..Default::default()
-        })?;
+        let mut conn = self.pool.get().unwrap();
+        let row: Option<PlaylistVersionRow> = sql_query(
+            "SELECT version, reason, track_ids, created_at FROM playlist_history
+             WHERE playlist_id = ? AND version = ?",
+        )
+        .bind::<Text, _>(playlist_id)
+        .bind::<BigInt, _>(version)
+        .get_result(&mut conn)
+        .optional()
+        .map_err(error_helpers::to_database_error)?;
+        let Some(row) = row else {
+            return Err(format!("Playlist {} has no version {}", playlist_id, version).into());
+        };
+        let snapshot: Vec<String> = serde_json::from_str(&row.track_ids)
+            .map_err(|e| format!("Unreadable snapshot of playlist {}: {}", playlist_id, e))?;
+
+        conn.transaction::<PlaylistRestore, diesel::result::Error, _>(|conn| {
+            record_playlist_baseline(conn, playlist_id)?;
+            let existing: std::collections::HashSet<String> = QueryDsl::filter(tracks_table, _id.eq_any(&snapshot))
+                .select(_id)
+                .load::<Option<String>>(conn)?
+                .into_iter()
+                .flatten()
+                .collect();
 
-        let mut ret = format!("#EXTM3U\n#PLAYLIST:{}\n", playlist.playlist_name);
+            delete(playlist_bridge)
+                .filter(schema::playlist_bridge::playlist.eq(playlist_id))
+                .execute(conn)?;
+            let restored: Vec<&String> = snapshot.iter().filter(|id| existing.contains(*id)).collect();
+            for track_id in &restored {
+                insert_into(playlist_bridge)
+                    .values((
+                        schema::playlist_bridge::playlist.eq(playlist_id),
+                        schema::playlist_bridge::track.eq(*track_id),
+                    ))
+                    .execute(conn)?;
+            }
+            let new_version = match record_playlist_version(conn, playlist_id, "restore")? {
+                Some(v) => v,
+                // Membership already matched the latest snapshot
+                None => latest_playlist_version(conn, playlist_id)?.map(|l| l.version).unwrap_or(version),
+            };
+            Ok(PlaylistRestore {
+                playlist_id: playlist_id.to_string(),
+                restored_from: version,
+                version: new_version,
+                track_count: restored.len() as u32,
+                missing_tracks: (snapshot.len() - restored.len()) as u32,
+            })
+        })
+        .map_err(error_helpers::to_database_error)
+    }
 
-        for s in playlist_tracks {
-            if let Some(path) = &s.track.path {
+    /// Link a playlist to the playlist `remote_id` of `provider_id`. Linking it
+    /// to another remote playlist starts over: the sync base and conflicts of
+    /// the previous link are dropped.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn link_playlist_sync(&self, playlist_id: &str, provider_id: &str, remote_id: &str) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            let current = sql_query(format!("SELECT {} FROM playlist_sync s WHERE s.playlist_id = ?", PLAYLIST_SYNC_COLUMNS))
+                .bind::<Text, _>(playlist_id)
+                .get_result::<PlaylistSyncRow>(conn)
+                .optional()?;
+            if current.is_some_and(|c| c.provider_id == provider_id && c.remote_id == remote_id) {
+                return Ok(());
+            }
+            sql_query(
+                "INSERT INTO playlist_sync (playlist_id, provider_id, remote_id) VALUES (?, ?, ?)
+                 ON CONFLICT(playlist_id) DO UPDATE SET provider_id = excluded.provider_id,
+                 remote_id = excluded.remote_id, base_track_ids = NULL, last_synced_at = NULL, last_error = NULL",
+            )
+            .bind::<Text, _>(playlist_id)
+            .bind::<Text, _>(provider_id)
+            .bind::<Text, _>(remote_id)
+            .execute(conn)?;
+            sql_query("DELETE FROM playlist_sync_conflicts WHERE playlist_id = ?")
+                .bind::<Text, _>(playlist_id)
+                .execute(conn)?;
+            Ok(())
+        })
+        .map_err(error_helpers::to_database_error)
+    }
+
+    /// Stop syncing a playlist; its tracks stay. False when it was not linked.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn unlink_playlist_sync(&self, playlist_id: &str) -> Result<bool> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        sql_query("DELETE FROM playlist_sync_conflicts WHERE playlist_id = ?")
+            .bind::<Text, _>(playlist_id)
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        let deleted = sql_query("DELETE FROM playlist_sync WHERE playlist_id = ?")
+            .bind::<Text, _>(playlist_id)
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(deleted > 0)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_playlist_syncs(&self) -> Result<Vec<PlaylistSyncLink>> {
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<PlaylistSyncRow> = diesel::sql_query(format!(
+            "SELECT {} FROM playlist_sync s ORDER BY s.playlist_id",
+            PLAYLIST_SYNC_COLUMNS
+        ))
+        .load(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(rows.into_iter().map(PlaylistSyncLink::from).collect())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_playlist_sync(&self, playlist_id: &str) -> Result<Option<PlaylistSyncLink>> {
+        let mut conn = self.pool.get().unwrap();
+        let row: Option<PlaylistSyncRow> = diesel::sql_query(format!(
+            "SELECT {} FROM playlist_sync s WHERE s.playlist_id = ?",
+            PLAYLIST_SYNC_COLUMNS
+        ))
+        .bind::<diesel::sql_types::Text, _>(playlist_id)
+        .get_result(&mut conn)
+        .optional()
+        .map_err(error_helpers::to_database_error)?;
+        Ok(row.map(PlaylistSyncLink::from))
+    }
+
+    /// Track ids both sides of a synced playlist had after its last sync,
+    /// `None` before the first one
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_playlist_sync_base(&self, playlist_id: &str) -> Result<Option<Vec<String>>> {
+        #[derive(diesel::QueryableByName)]
+        struct BaseRow {
+            #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+            base_track_ids: Option<String>,
+        }
+
+        let mut conn = self.pool.get().unwrap();
+        let row: Option<BaseRow> = diesel::sql_query("SELECT base_track_ids FROM playlist_sync WHERE playlist_id = ?")
+            .bind::<diesel::sql_types::Text, _>(playlist_id)
+            .get_result(&mut conn)
+            .optional()
+            .map_err(error_helpers::to_database_error)?;
+        Ok(row
+            .and_then(|r| r.base_track_ids)
+            .map(|ids| serde_json::from_str(&ids).unwrap_or_default()))
+    }
+
+    /// Track ids of a playlist in playlist order
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_playlist_track_ids(&self, playlist_id: &str) -> Result<Vec<String>> {
+        let mut conn = self.pool.get().unwrap();
+        playlist_track_ids(&mut conn, playlist_id).map_err(error_helpers::to_database_error)
+    }
+
+    /// Record a sync that went through: its new base and the conflicts left.
+    /// Conflicts no longer found are dropped, settled ones stay settled while
+    /// they are found.
+    #[tracing::instrument(level = "debug", skip(self, base, conflicts))]
+    pub fn finish_playlist_sync(
+        &self,
+        playlist_id: &str,
+        base: &[String],
+        conflicts: &[(String, String)],
+        synced_at: i64,
+    ) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::{BigInt, Text};
+
+        let base = serde_json::to_string(base).unwrap_or_default();
+        let mut conn = self.pool.get().unwrap();
+        conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            sql_query(
+                "UPDATE playlist_sync SET base_track_ids = ?, last_synced_at = ?, last_error = NULL
+                 WHERE playlist_id = ?",
+            )
+            .bind::<Text, _>(&base)
+            .bind::<BigInt, _>(synced_at)
+            .bind::<Text, _>(playlist_id)
+            .execute(conn)?;
+
+            let existing: Vec<PlaylistSyncConflictRow> =
+                sql_query(format!("{} WHERE c.playlist_id = ?", PLAYLIST_SYNC_CONFLICT_QUERY))
+                    .bind::<Text, _>(playlist_id)
+                    .load(conn)?;
+            for row in existing {
+                if !conflicts.iter().any(|(track_id, kind)| *track_id == row.track_id && *kind == row.kind) {
+                    sql_query("DELETE FROM playlist_sync_conflicts WHERE id = ?")
+                        .bind::<BigInt, _>(row.id)
+                        .execute(conn)?;
+                }
+            }
+            for (track_id, kind) in conflicts {
+                sql_query(
+                    "INSERT OR IGNORE INTO playlist_sync_conflicts (playlist_id, track_id, kind, detected_at)
+                     VALUES (?, ?, ?, ?)",
+                )
+                .bind::<Text, _>(playlist_id)
+                .bind::<Text, _>(track_id)
+                .bind::<Text, _>(kind)
+                .bind::<BigInt, _>(synced_at)
+                .execute(conn)?;
+            }
+            Ok(())
+        })
+        .map_err(error_helpers::to_database_error)
+    }
+
+    /// Record why a sync failed; the base is left for the next one
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_playlist_sync_error(&self, playlist_id: &str, error: &str) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        sql_query("UPDATE playlist_sync SET last_error = ? WHERE playlist_id = ?")
+            .bind::<Text, _>(error)
+            .bind::<Text, _>(playlist_id)
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(())
+    }
+
+    /// Open conflicts of a playlist, or of all synced playlists
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_playlist_sync_conflicts(&self, playlist_id: Option<&str>) -> Result<Vec<PlaylistSyncConflict>> {
+        let mut conn = self.pool.get().unwrap();
+        let query = format!(
+            "{} WHERE c.resolution IS NULL AND (? IS NULL OR c.playlist_id = ?) ORDER BY c.detected_at, c.id",
+            PLAYLIST_SYNC_CONFLICT_QUERY
+        );
+        let rows: Vec<PlaylistSyncConflictRow> = diesel::sql_query(query)
+            .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(playlist_id)
+            .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(playlist_id)
+            .load(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(rows.into_iter().map(PlaylistSyncConflict::from).collect())
+    }
+
+    /// Conflicts of a playlist the user settled keeping the local change, as
+    /// (track id, kind); syncs leave these tracks alone
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_settled_playlist_sync_conflicts(&self, playlist_id: &str) -> Result<Vec<(String, String)>> {
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<PlaylistSyncConflictRow> = diesel::sql_query(format!(
+            "{} WHERE c.playlist_id = ? AND c.resolution IS NOT NULL",
+            PLAYLIST_SYNC_CONFLICT_QUERY
+        ))
+        .bind::<diesel::sql_types::Text, _>(playlist_id)
+        .load(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(rows.into_iter().map(|r| (r.track_id, r.kind)).collect())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_playlist_sync_conflict(&self, id: i64) -> Result<Option<PlaylistSyncConflict>> {
+        let mut conn = self.pool.get().unwrap();
+        let row: Option<PlaylistSyncConflictRow> =
+            diesel::sql_query(format!("{} WHERE c.id = ?", PLAYLIST_SYNC_CONFLICT_QUERY))
+                .bind::<diesel::sql_types::BigInt, _>(id)
+                .get_result(&mut conn)
+                .optional()
+                .map_err(error_helpers::to_database_error)?;
+        Ok(row.map(PlaylistSyncConflict::from))
+    }
+
+    /// Settle a conflict: keep the local change when `keep_local`, otherwise
+    /// the conflict is gone along with the local change the caller undid
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn settle_playlist_sync_conflict(&self, id: i64, keep_local: bool) -> Result<()> {
+        let query = if keep_local {
+            "UPDATE playlist_sync_conflicts SET resolution = 'local' WHERE id = ?"
+        } else {
+            "DELETE FROM playlist_sync_conflicts WHERE id = ?"
+        };
+        let mut conn = self.pool.get().unwrap();
+        diesel::sql_query(query)
+            .bind::<diesel::sql_types::BigInt, _>(id)
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(())
+    }
+
+    /// Add the tracks not in the library yet, hidden from it like the tracks
+    /// `add_to_playlist` adds; tracks already there are left as they are.
+    /// Returns the ids of all the tracks.
+    #[tracing::instrument(level = "debug", skip(self, tracks))]
+    pub fn insert_missing_tracks(&self, tracks: Vec<MediaContent>) -> Result<Vec<String>> {
+        let ids: Vec<String> = tracks.iter().filter_map(|t| t.track._id.clone()).collect();
+        let known: std::collections::HashSet<String> = {
+            let mut conn = self.pool.get().unwrap();
+            QueryDsl::filter(tracks_table, _id.eq_any(&ids))
+                .select(_id)
+                .load::<Option<String>>(&mut conn)
+                .map_err(error_helpers::to_database_error)?
+                .into_iter()
+                .flatten()
+                .collect()
+        };
+        let mut new_tracks: Vec<MediaContent> = tracks
+            .into_iter()
+            .filter(|t| t.track._id.as_ref().is_some_and(|id| !known.contains(id)))
+            .collect();
+        new_tracks.iter_mut().for_each(|t| t.track.show_in_library = Some(false));
+        if !new_tracks.is_empty() {
+            self.insert_tracks_by_ref(&mut new_tracks)?;
+        }
+        Ok(ids)
+    }
+
+    /// Apply the changes a sync brought to a playlist. Tracks already in the
+    /// library are linked as they are, the others are added to it hidden, like
+    /// `add_to_playlist` does. Snapshotted as a "sync" version.
+    #[tracing::instrument(level = "debug", skip(self, added, removed))]
+    pub fn apply_playlist_sync(&self, playlist_id: &str, added: Vec<MediaContent>, removed: &[String]) -> Result<()> {
+        let added_ids = self.insert_missing_tracks(added)?;
+
+        let mut conn = self.pool.get().unwrap();
+        conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            record_playlist_baseline(conn, playlist_id)?;
+            for track_id in removed {
+                delete(playlist_bridge)
+                    .filter(schema::playlist_bridge::playlist.eq(playlist_id))
+                    .filter(schema::playlist_bridge::track.eq(track_id))
+                    .execute(conn)?;
+            }
+            for track_id in &added_ids {
+                insert_into(playlist_bridge)
+                    .values((
+                        schema::playlist_bridge::playlist.eq(playlist_id),
+                        schema::playlist_bridge::track.eq(track_id),
+                    ))
+                    .execute(conn)?;
+            }
+            record_playlist_version(conn, playlist_id, "sync")?;
+            Ok(())
+        })
+        .map_err(error_helpers::to_database_error)
+    }
+
+    /// Like tracks, adding the ones not in the library yet. `origin` tags
+    /// where the likes come from; tracks liked already keep their tag. Returns
+    /// the number of tracks newly liked.
+    #[tracing::instrument(level = "debug", skip(self, tracks))]
+    pub fn like_tracks(&self, tracks: Vec<MediaContent>, origin: &str, liked_at: i64) -> Result<usize> {
+        let ids = self.insert_missing_tracks(tracks)?;
+        // Listed most recent first: keep that order within one batch
+        let likes: Vec<(String, String, i64)> = ids
+            .into_iter()
+            .enumerate()
+            .map(|(index, track_id)| (track_id, origin.to_string(), liked_at - index as i64))
+            .collect();
+        self.insert_likes(&likes)
+    }
+
+    /// Add likes given as (track id, origin, liked at), keeping the likes
+    /// already there. Returns the number of tracks newly liked.
+    #[tracing::instrument(level = "debug", skip(self, likes))]
+    pub fn insert_likes(&self, likes: &[(String, String, i64)]) -> Result<usize> {
+        use diesel::sql_query;
+        use diesel::sql_types::{BigInt, Text};
+
+        let mut conn = self.pool.get().unwrap();
+        conn.transaction::<usize, diesel::result::Error, _>(|conn| {
+            let mut liked = 0;
+            for (track_id, origin, liked_at) in likes {
+                liked += sql_query("INSERT OR IGNORE INTO liked_tracks (track_id, origin, liked_at) VALUES (?, ?, ?)")
+                    .bind::<Text, _>(track_id)
+                    .bind::<Text, _>(origin)
+                    .bind::<BigInt, _>(*liked_at)
+                    .execute(conn)?;
+            }
+            Ok(liked)
+        })
+        .map_err(error_helpers::to_database_error)
+    }
+
+    /// False when the track was not liked
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn unlike_track(&self, track_id: &str) -> Result<bool> {
+        let mut conn = self.pool.get().unwrap();
+        let deleted = diesel::sql_query("DELETE FROM liked_tracks WHERE track_id = ?")
+            .bind::<diesel::sql_types::Text, _>(track_id)
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(deleted > 0)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn is_track_liked(&self, track_id: &str) -> Result<bool> {
+        #[derive(diesel::QueryableByName)]
+        struct LikedRow {
+            #[diesel(sql_type = diesel::sql_types::BigInt)]
+            liked: i64,
+        }
+
+        let mut conn = self.pool.get().unwrap();
+        let row: LikedRow = diesel::sql_query("SELECT COUNT(*) AS liked FROM liked_tracks WHERE track_id = ?")
+            .bind::<diesel::sql_types::Text, _>(track_id)
+            .get_result(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(row.liked > 0)
+    }
+
+    /// Liked songs, most recently liked first
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_liked_tracks(&self) -> Result<Vec<LikedTrack>> {
+        #[derive(diesel::QueryableByName)]
+        struct LikeRow {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            track_id: String,
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            origin: String,
+            #[diesel(sql_type = diesel::sql_types::BigInt)]
+            liked_at: i64,
+        }
+
+        let mut conn = self.pool.get().unwrap();
+        let likes: Vec<LikeRow> =
+            diesel::sql_query("SELECT track_id, origin, liked_at FROM liked_tracks ORDER BY liked_at DESC")
+                .load(&mut conn)
+                .map_err(error_helpers::to_database_error)?;
+        let ids: Vec<&String> = likes.iter().map(|l| &l.track_id).collect();
+        let mut found: std::collections::HashMap<String, Tracks> = QueryDsl::filter(tracks_table, _id.eq_any(ids))
+            .load::<Tracks>(&mut conn)
+            .map_err(error_helpers::to_database_error)?
+            .into_iter()
+            .filter_map(|t| t._id.clone().map(|id| (id, t)))
+            .collect();
+
+        let mut ret = Vec::with_capacity(likes.len());
+        // Likes of tracks removed from the library are left out
+        for like in likes {
+            if let Some(track) = found.remove(&like.track_id) {
+                ret.push(LikedTrack {
+                    track: self.get_track_from_queryable(&mut conn, track)?,
+                    origin: like.origin,
+                    liked_at: like.liked_at,
+                });
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Every play of the listening history, oldest first
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_play_history(&self) -> Result<Vec<PlayHistoryEntry>> {
+        #[derive(diesel::QueryableByName)]
+        struct PlayRow {
+            #[diesel(sql_type = diesel::sql_types::Text)]
+            track_id: String,
+            #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Timestamp>)]
+            played_at: Option<chrono::NaiveDateTime>,
+            #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Double>)]
+            play_duration: Option<f64>,
+        }
+
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<PlayRow> = diesel::sql_query("SELECT track_id, played_at, play_duration FROM play_history ORDER BY id")
+            .load(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(rows
+            .into_iter()
+            .map(|r| PlayHistoryEntry {
+                track_id: r.track_id,
+                played_at: r.played_at,
+                play_duration: r.play_duration,
+            })
+            .collect())
+    }
+
+    /// Add plays to the listening history; a play of the same track at the
+    /// same time is kept once. Returns the number of plays added.
+    #[tracing::instrument(level = "debug", skip(self, entries))]
+    pub fn import_play_history(&self, entries: &[PlayHistoryEntry]) -> Result<usize> {
+        use diesel::sql_query;
+        use diesel::sql_types::{Double, Nullable, Text, Timestamp};
+
+        let mut conn = self.pool.get().unwrap();
+        conn.transaction::<usize, diesel::result::Error, _>(|conn| {
+            let mut added = 0;
+            for entry in entries {
+                added += sql_query(
+                    "INSERT INTO play_history (track_id, played_at, play_duration) SELECT ?, ?, ?
+                     WHERE NOT EXISTS (SELECT 1 FROM play_history WHERE track_id = ? AND played_at IS ?)",
+                )
+                .bind::<Text, _>(&entry.track_id)
+                .bind::<Nullable<Timestamp>, _>(entry.played_at)
+                .bind::<Nullable<Double>, _>(entry.play_duration)
+                .bind::<Text, _>(&entry.track_id)
+                .bind::<Nullable<Timestamp>, _>(entry.played_at)
+                .execute(conn)?;
+            }
+            Ok(added)
+        })
+        .map_err(error_helpers::to_database_error)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_all_playlists(&self) -> Result<Vec<QueryablePlaylist>> {
+        let mut conn = self.pool.get().unwrap();
+        schema::playlists::table
+            .load(&mut conn)
+            .map_err(error_helpers::to_database_error)
+    }
+
+    /// Tracks of the library with the given ids; unknown ids are left out
+    #[tracing::instrument(level = "debug", skip(self, ids))]
+    pub fn get_tracks_by_ids(&self, ids: &[String]) -> Result<Vec<MediaContent>> {
+        let mut conn = self.pool.get().unwrap();
+        let found: Vec<Tracks> = QueryDsl::filter(tracks_table, _id.eq_any(ids))
+            .load(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        let mut ret = Vec::with_capacity(found.len());
+        for track in found {
+            ret.push(self.get_track_from_queryable(&mut conn, track)?);
+        }
+        Ok(ret)
+    }
+
+    /// Statistics for a playlist: totals and distributions are computed in SQL,
+    /// decades, duplicates and file availability in a light pass over the entries.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_playlist_insights(&self, playlist_id: String) -> Result<PlaylistInsights> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+
+        let totals: PlaylistTotalsRow = sql_query(
+            "SELECT COUNT(*) AS entries,
+                    COUNT(DISTINCT pb.track) AS unique_tracks,
+                    CAST(COALESCE(SUM(t.duration), 0) AS DOUBLE) AS total_duration,
+                    COALESCE(SUM(CASE WHEN t._id IS NULL THEN 1 ELSE 0 END), 0) AS missing,
+                    COALESCE(SUM(CASE WHEN t._id IS NOT NULL AND t.type != 'LOCAL' THEN 1 ELSE 0 END), 0) AS remote
+             FROM playlist_bridge pb
+             LEFT JOIN tracks t ON t._id = pb.track
+             WHERE pb.playlist = ?",
+        )
+        .bind::<Text, _>(&playlist_id)
+        .get_result(&mut conn).map_err(error_helpers::to_database_error)?;
+
+        let genre_rows: Vec<LabelCountRow> = sql_query(
+            "SELECT g.genre_name AS label, COUNT(*) AS count
+             FROM playlist_bridge pb
+             JOIN genre_bridge gb ON gb.track = pb.track
+             JOIN genres g ON g.genre_id = gb.genre
+             WHERE pb.playlist = ?
+             GROUP BY g.genre_name
+             ORDER BY count DESC",
+        )
+        .bind::<Text, _>(&playlist_id)
+        .load(&mut conn).map_err(error_helpers::to_database_error)?;
+
+        let entries: Vec<PlaylistEntryRow> = sql_query(
+            "SELECT pb.track AS track, t.title AS title, t.year AS year, t.path AS path, t.type AS type_
+             FROM playlist_bridge pb
+             JOIN tracks t ON t._id = pb.track
+             WHERE pb.playlist = ?",
+        )
+        .bind::<Text, _>(&playlist_id)
+        .load(&mut conn).map_err(error_helpers::to_database_error)?;
+
+        let mut decades: std::collections::HashMap<String, u32> = std::collections::HashMap::new();
+        let mut occurrences: std::collections::HashMap<String, (Option<String>, u32)> =
+            std::collections::HashMap::new();
+        let mut missing_files = 0u32;
+        for entry in &entries {
+            *decades.entry(decade_label(entry.year.as_deref())).or_default() += 1;
+            if let Some(id) = &entry.track {
+                occurrences.entry(id.clone()).or_insert((entry.title.clone(), 0)).1 += 1;
+            }
+            let is_local = entry.type_.as_deref().map(|t| t == "LOCAL").unwrap_or(true);
+            if is_local {
+                if let Some(path) = &entry.path {
+                    if !PathBuf::from(path).exists() {
+                        missing_files += 1;
+                    }
+                }
+            }
+        }
+
+        let mut decade_distribution: Vec<DistributionEntry> = decades
+            .into_iter()
+            .map(|(label, count)| DistributionEntry { label, count })
+            .collect();
+        decade_distribution.sort_by(|a, b| a.label.cmp(&b.label));
+
+        let mut duplicates: Vec<PlaylistDuplicate> = occurrences
+            .into_iter()
+            .filter(|(_, (_, n))| *n > 1)
+            .map(|(track_id, (title, occurrences))| PlaylistDuplicate {
+                track_id,
+                title,
+                occurrences,
+            })
+            .collect();
+        duplicates.sort_by(|a, b| b.occurrences.cmp(&a.occurrences));
+
+        info!("Computed insights for playlist {}", playlist_id);
+        Ok(PlaylistInsights {
+            playlist_id,
+            total_entries: totals.entries as u32,
+            unique_tracks: totals.unique_tracks as u32,
+            total_duration: totals.total_duration,
+            genre_distribution: genre_rows
+                .into_iter()
+                .map(|r| DistributionEntry {
+                    label: r.label.unwrap_or_else(|| "Unknown".to_string()),
+                    count: r.count as u32,
+                })
+                .collect(),
+            decade_distribution,
+            unavailable_tracks: totals.missing as u32 + missing_files,
+            offline_unplayable_tracks: totals.remote as u32,
+            duplicates,
+            // No loudness analysis is persisted yet
+            average_loudness: None,
+        })
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn export_playlist(&self, playlist_id: String) -> Result<String> {
+        let mut conn = self.pool.get().unwrap();
+
+        let binding = self.get_playlists(
+            QueryablePlaylist {
+                playlist_id: Some(playlist_id.clone()),
+                ..Default::default()
+            },
+            true,
+            &mut conn,
+        )?;
+        let playlist = binding.first();
+
+        if playlist.is_none() {
+            return Err("Playlist not found".into());
+        }
+
+        let playlist = playlist.unwrap();
+
+        let playlist_tracks = self.get_tracks_by_options(GetTrackOptions {
+            playlist: Some(QueryablePlaylist {
+                playlist_id: Some(playlist_id),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })?;
+
+        let mut ret = format!("#EXTM3U\n#PLAYLIST:{}\n", playlist.playlist_name);
+
+        for s in playlist_tracks {
+            if let Some(path) = &s.track.path {
                 let duration = s.track.duration.unwrap_or(0f64);
                 let title = s.track.title.unwrap_or_default();
                 let album_info = s.album.as_ref().map_or(String::new(), |album| {
@@ -1185,6 +2950,123 @@ impl Database {
         Ok(ret.replace("\n\n", "\n"))
     }
 
+    /// Large/small cover paths of a track or album (`collation::KIND_*`).
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_cover_paths(&self, kind: &str, id: &str) -> Result<Option<(Option<String>, Option<String>)>> {
+        let mut conn = self.pool.get().unwrap();
+        match kind {
+            KIND_TRACK => schema::tracks::table
+                .filter(schema::tracks::_id.eq(id))
+                .select((schema::tracks::track_coverpath_high, schema::tracks::track_coverpath_low))
+                .first::<(Option<String>, Option<String>)>(&mut conn)
+                .optional()
+                .map_err(error_helpers::to_database_error),
+            KIND_ALBUM => schema::albums::table
+                .filter(schema::albums::album_id.eq(id))
+                .select((schema::albums::album_coverpath_high, schema::albums::album_coverpath_low))
+                .first::<(Option<String>, Option<String>)>(&mut conn)
+                .optional()
+                .map_err(error_helpers::to_database_error),
+            other => Err(types::errors::MusicError::String(format!(
+                "Artwork is not available for entity kind {}",
+                other
+            ))),
+        }
+    }
+
+    /// Merge `info` into the extra info of an album, filling its year when
+    /// unknown.
+    #[tracing::instrument(level = "debug", skip(self, info))]
+    pub fn merge_album_info(&self, album_id: &str, info: EntityInfo, year: Option<&str>) -> Result<()> {
+        let mut conn = self.pool.get().unwrap();
+        let existing: Option<(Option<EntityInfo>, Option<String>)> = schema::albums::table
+            .filter(schema::albums::album_id.eq(album_id))
+            .select((schema::albums::album_extra_info, schema::albums::year))
+            .first(&mut conn)
+            .optional()
+            .map_err(error_helpers::to_database_error)?;
+        let Some((old_info, old_year)) = existing else {
+            return Ok(());
+        };
+        let year = old_year
+            .filter(|y| !y.trim().is_empty())
+            .or_else(|| year.map(str::to_string));
+        update(schema::albums::table.filter(schema::albums::album_id.eq(album_id)))
+            .set((
+                schema::albums::album_extra_info.eq(self.merge_extra_info(old_info, Some(info))),
+                schema::albums::year.eq(year),
+            ))
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(())
+    }
+
+    /// Merge `info` into the extra info of an artist, filling its MusicBrainz
+    /// ID when unknown.
+    #[tracing::instrument(level = "debug", skip(self, info))]
+    pub fn merge_artist_info(&self, artist_id: &str, info: EntityInfo, mbid: Option<&str>) -> Result<()> {
+        let mut conn = self.pool.get().unwrap();
+        let existing: Option<(Option<EntityInfo>, Option<String>)> = schema::artists::table
+            .filter(schema::artists::artist_id.eq(artist_id))
+            .select((schema::artists::artist_extra_info, schema::artists::artist_mbid))
+            .first(&mut conn)
+            .optional()
+            .map_err(error_helpers::to_database_error)?;
+        let Some((old_info, old_mbid)) = existing else {
+            return Ok(());
+        };
+        let mbid = old_mbid
+            .filter(|m| !m.trim().is_empty())
+            .or_else(|| mbid.map(str::to_string));
+        update(schema::artists::table.filter(schema::artists::artist_id.eq(artist_id)))
+            .set((
+                schema::artists::artist_extra_info.eq(self.merge_extra_info(old_info, Some(info))),
+                schema::artists::artist_mbid.eq(mbid),
+            ))
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(())
+    }
+
+    /// Use a fetched cover for a track, and for its album and the tracks of
+    /// the album when they have none.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_fetched_cover(&self, track_id: &str, album_id: Option<&str>, high: &str, low: &str) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            sql_query("UPDATE tracks SET track_coverpath_high = ?, track_coverpath_low = ? WHERE _id = ?")
+                .bind::<Text, _>(high)
+                .bind::<Text, _>(low)
+                .bind::<Text, _>(track_id)
+                .execute(conn)?;
+            let Some(album_id) = album_id else {
+                return Ok(());
+            };
+            sql_query(
+                "UPDATE albums SET album_coverpath_high = ?, album_coverpath_low = ?
+                 WHERE album_id = ? AND album_coverpath_high IS NULL",
+            )
+            .bind::<Text, _>(high)
+            .bind::<Text, _>(low)
+            .bind::<Text, _>(album_id)
+            .execute(conn)?;
+            sql_query(
+                "UPDATE tracks SET track_coverpath_high = ?, track_coverpath_low = ?
+                 WHERE track_coverpath_high IS NULL
+                   AND _id IN (SELECT track FROM album_bridge WHERE album = ?)",
+            )
+            .bind::<Text, _>(high)
+            .bind::<Text, _>(low)
+            .bind::<Text, _>(album_id)
+            .execute(conn)?;
+            Ok(())
+        })
+        .map_err(error_helpers::to_database_error)
+    }
+
     // Player Store KV methods
     #[tracing::instrument(level = "debug", skip(self))]
     pub fn get_player_store_value(&self, key: &str) -> Result<Option<String>> {
@@ -1438,6 +3320,1344 @@ impl Database {
         Ok(())
     }
 
+    // Task journal methods
+    #[tracing::instrument(level = "debug", skip(self, payload))]
+    pub fn insert_task_journal(&self, task_id: &str, task_kind: &str, payload: &str) -> Result<()> {
+        use types::schema::task_journal::dsl;
+        let mut conn = self.pool.get().unwrap();
+        let now = chrono::Utc::now().naive_utc();
+        insert_into(dsl::task_journal)
+            .values(&types::entities::TaskJournalEntry {
+                id: task_id.to_string(),
+                kind: task_kind.to_string(),
+                status: "running".to_string(),
+                payload: payload.to_string(),
+                checkpoint: None,
+                error: None,
+                created_at: now,
+                updated_at: now,
+            })
+            .on_conflict(dsl::id)
+            .do_update()
+            .set((dsl::status.eq("running"), dsl::updated_at.eq(now)))
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "trace", skip(self, checkpoint))]
+    pub fn update_task_checkpoint(&self, task_id: &str, checkpoint: &str) -> Result<()> {
+        use types::schema::task_journal::dsl;
+        let mut conn = self.pool.get().unwrap();
+        update(dsl::task_journal.filter(dsl::id.eq(task_id)))
+            .set((
+                dsl::checkpoint.eq(Some(checkpoint)),
+                dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+            ))
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_task_status(&self, task_id: &str, task_status: &str, task_error: Option<&str>) -> Result<()> {
+        use types::schema::task_journal::dsl;
+        let mut conn = self.pool.get().unwrap();
+        update(dsl::task_journal.filter(dsl::id.eq(task_id)))
+            .set((
+                dsl::status.eq(task_status),
+                dsl::error.eq(task_error),
+                dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+            ))
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(())
+    }
+
+    /// Jobs still marked running, i.e. interrupted by an app exit
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_running_tasks(&self, task_kind: &str) -> Result<Vec<types::entities::TaskJournalEntry>> {
+        use types::schema::task_journal::dsl;
+        let mut conn = self.pool.get().unwrap();
+        dsl::task_journal
+            .filter(dsl::kind.eq(task_kind))
+            .filter(dsl::status.eq("running"))
+            .order(dsl::created_at.asc())
+            .load(&mut conn)
+            .map_err(error_helpers::to_database_error)
+    }
+
+    /// Drop finished journal entries older than `days`
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn prune_task_journal(&self, days: i64) -> Result<usize> {
+        use types::schema::task_journal::dsl;
+        let mut conn = self.pool.get().unwrap();
+        let cutoff = chrono::Utc::now().naive_utc() - chrono::Duration::days(days);
+        delete(
+            dsl::task_journal
+                .filter(dsl::status.ne("running"))
+                .filter(dsl::updated_at.lt(cutoff)),
+        )
+        .execute(&mut conn)
+        .map_err(error_helpers::to_database_error)
+    }
+
+    /// Online tracks that are not downloaded yet and are either saved to the
+    /// library (when `include_favorites` is set) or were played at least
+    /// `min_play_count` times (0 disables that rule). Favorites come first,
+    /// then the most played tracks.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_smart_download_candidates(
+        &self,
+        include_favorites: bool,
+        min_play_count: u32,
+        limit: i64,
+    ) -> Result<Vec<String>> {
+        use diesel::sql_query;
+        use diesel::sql_types::{BigInt, Bool};
+
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<DownloadCandidateRow> = sql_query(
+            "SELECT t._id AS track_id
+             FROM tracks t
+             LEFT JOIN play_history ph ON ph.track_id = t._id
+             LEFT JOIN downloads d ON d.track_id = t._id
+             WHERE t._id IS NOT NULL AND t.type != 'LOCAL' AND d.track_id IS NULL
+             GROUP BY t._id
+             HAVING (? AND MAX(COALESCE(t.library_item, 0)) = 1)
+                 OR (? > 0 AND COUNT(ph.id) >= ?)
+             ORDER BY MAX(COALESCE(t.library_item, 0)) DESC, COUNT(ph.id) DESC
+             LIMIT ?",
+        )
+        .bind::<Bool, _>(include_favorites)
+        .bind::<BigInt, _>(min_play_count as i64)
+        .bind::<BigInt, _>(min_play_count as i64)
+        .bind::<BigInt, _>(limit)
+        .load(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(rows.into_iter().map(|r| r.track_id).collect())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn insert_download(&self, entry: &types::entities::DownloadedTrack) -> Result<()> {
+        use types::schema::downloads::dsl;
+        let mut conn = self.pool.get().unwrap();
+        insert_into(dsl::downloads)
+            .values(entry)
+            .on_conflict(dsl::track_id)
+            .do_update()
+            .set(entry)
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub fn get_download(&self, id: &str) -> Result<Option<types::entities::DownloadedTrack>> {
+        use types::schema::downloads::dsl;
+        let mut conn = self.pool.get().unwrap();
+        dsl::downloads
+            .filter(dsl::track_id.eq(id))
+            .first(&mut conn)
+            .optional()
+            .map_err(error_helpers::to_database_error)
+    }
+
+    /// Downloaded tracks, most recent first
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_downloads(&self) -> Result<Vec<types::entities::DownloadedTrack>> {
+        use types::schema::downloads::dsl;
+        let mut conn = self.pool.get().unwrap();
+        dsl::downloads
+            .order(dsl::created_at.desc())
+            .load(&mut conn)
+            .map_err(error_helpers::to_database_error)
+    }
+
+    /// Bytes used by downloaded tracks
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_downloads_size(&self) -> Result<u64> {
+        use diesel::sql_query;
+
+        let mut conn = self.pool.get().unwrap();
+        let row: DownloadSizeRow = sql_query("SELECT CAST(COALESCE(SUM(size), 0) AS BIGINT) AS total FROM downloads")
+            .get_result(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(row.total.max(0) as u64)
+    }
+
+    /// Number and total size in bytes of the local tracks in the library.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_local_library_size(&self) -> Result<(u64, u64)> {
+        use diesel::sql_query;
+
+        let mut conn = self.pool.get().unwrap();
+        let row: LibrarySizeRow = sql_query(
+            "SELECT COUNT(*) AS tracks, CAST(COALESCE(SUM(size), 0) AS BIGINT) AS total
+             FROM tracks WHERE type = 'LOCAL'",
+        )
+        .get_result(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok((row.tracks.max(0) as u64, row.total.max(0) as u64))
+    }
+
+    /// Set the user rating (1-5) of a track; 0 clears it.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_track_rating(&self, track_id: &str, rating: u8) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::{Integer, Text};
+
+        let mut conn = self.pool.get().unwrap();
+        if rating == 0 {
+            sql_query("DELETE FROM track_ratings WHERE track_id = ?")
+                .bind::<Text, _>(track_id)
+                .execute(&mut conn)
+                .map_err(error_helpers::to_database_error)?;
+        } else {
+            sql_query(
+                "INSERT INTO track_ratings (track_id, rating) VALUES (?, ?)
+                 ON CONFLICT(track_id) DO UPDATE SET rating = excluded.rating, updated_at = CURRENT_TIMESTAMP",
+            )
+            .bind::<Text, _>(track_id)
+            .bind::<Integer, _>(rating.min(5) as i32)
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        }
+        Ok(())
+    }
+
+    /// Replace the edit regions of a track; an empty list removes them.
+    #[tracing::instrument(level = "debug", skip(self, regions))]
+    pub fn set_edit_regions(&self, track_id: &str, regions: &[EditRegion]) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        for region in regions {
+            region.validate()?;
+        }
+        let mut conn = self.pool.get().unwrap();
+        if regions.is_empty() {
+            sql_query("DELETE FROM track_edit_regions WHERE track_id = ?")
+                .bind::<Text, _>(track_id)
+                .execute(&mut conn)
+                .map_err(error_helpers::to_database_error)?;
+        } else {
+            let encoded = serde_json::to_string(regions)?;
+            sql_query(
+                "INSERT INTO track_edit_regions (track_id, regions) VALUES (?, ?)
+                 ON CONFLICT(track_id) DO UPDATE SET regions = excluded.regions, updated_at = CURRENT_TIMESTAMP",
+            )
+            .bind::<Text, _>(track_id)
+            .bind::<Text, _>(encoded)
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        }
+        Ok(())
+    }
+
+    /// Edit regions of a track, empty when it has none or they no longer parse.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_edit_regions(&self, track_id: &str) -> Result<Vec<EditRegion>> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        let row: Option<EditRegionsRow> = sql_query("SELECT regions FROM track_edit_regions WHERE track_id = ?")
+            .bind::<Text, _>(track_id)
+            .get_result(&mut conn)
+            .optional()
+            .map_err(error_helpers::to_database_error)?;
+        Ok(row
+            .and_then(|row| match serde_json::from_str(&row.regions) {
+                Ok(regions) => Some(regions),
+                Err(e) => {
+                    warn!("Ignoring invalid edit regions of {}: {}", track_id, e);
+                    None
+                }
+            })
+            .unwrap_or_default())
+    }
+
+    /// Cache the loudness analysis of tracks, replacing earlier values. Tracks
+    /// without ReplayGain tags are stored empty so they aren't read again.
+    #[tracing::instrument(level = "debug", skip(self, gains))]
+    pub fn set_track_gains(&self, gains: &[(String, TrackGain)]) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::{Double, Nullable, Text};
+
+        let mut conn = self.pool.get().unwrap();
+        conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            for (track_id, gain) in gains {
+                sql_query(
+                    "INSERT INTO track_gain (track_id, track_gain, track_peak, album_gain, album_peak)
+                     VALUES (?, ?, ?, ?, ?)
+                     ON CONFLICT(track_id) DO UPDATE SET track_gain = excluded.track_gain,
+                       track_peak = excluded.track_peak, album_gain = excluded.album_gain,
+                       album_peak = excluded.album_peak, updated_at = CURRENT_TIMESTAMP",
+                )
+                .bind::<Text, _>(track_id)
+                .bind::<Nullable<Double>, _>(gain.track_gain)
+                .bind::<Nullable<Double>, _>(gain.track_peak)
+                .bind::<Nullable<Double>, _>(gain.album_gain)
+                .bind::<Nullable<Double>, _>(gain.album_peak)
+                .execute(conn)?;
+            }
+            Ok(())
+        })
+        .map_err(error_helpers::to_database_error)
+    }
+
+    /// Cached loudness analysis of a track; `None` when it was never read,
+    /// an empty value when the track has no ReplayGain tags.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_track_gain(&self, track_id: &str) -> Result<Option<TrackGain>> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        let row: Option<TrackGainRow> = sql_query(
+            "SELECT track_gain, track_peak, album_gain, album_peak FROM track_gain WHERE track_id = ?",
+        )
+        .bind::<Text, _>(track_id)
+        .get_result(&mut conn)
+        .optional()
+        .map_err(error_helpers::to_database_error)?;
+        Ok(row.map(|row| TrackGain {
+            track_gain: row.track_gain,
+            track_peak: row.track_peak,
+            album_gain: row.album_gain,
+            album_peak: row.album_peak,
+        }))
+    }
+
+    /// Store where the audio of a track starts and ends.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_track_silence(&self, track_id: &str, silence: &TrackSilence) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::{Double, Text};
+
+        let mut conn = self.pool.get().unwrap();
+        sql_query("UPDATE tracks SET silence_start = ?, silence_end = ? WHERE _id = ?")
+            .bind::<Double, _>(silence.start)
+            .bind::<Double, _>(silence.end)
+            .bind::<Text, _>(track_id)
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(())
+    }
+
+    /// Where the audio of a track starts and ends; `None` until detected.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_track_silence(&self, track_id: &str) -> Result<Option<TrackSilence>> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        let row: Option<TrackSilenceRow> = sql_query(
+            "SELECT silence_start, silence_end FROM tracks
+             WHERE _id = ? AND silence_start IS NOT NULL AND silence_end IS NOT NULL",
+        )
+        .bind::<Text, _>(track_id)
+        .get_result(&mut conn)
+        .optional()
+        .map_err(error_helpers::to_database_error)?;
+        Ok(row.map(|row| TrackSilence {
+            start: row.silence_start,
+            end: row.silence_end,
+        }))
+    }
+
+    /// Local tracks whose silence was never detected, at most `limit`,
+    /// skipping `exclude`.
+    #[tracing::instrument(level = "debug", skip(self, exclude))]
+    pub fn get_silence_pending_track_ids(&self, limit: i64, exclude: &[String]) -> Result<Vec<String>> {
+        use diesel::sql_query;
+        use diesel::sql_types::{BigInt, Text};
+
+        let exclude = serde_json::to_string(exclude)?;
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<RankedTrackRow> = sql_query(
+            "SELECT t._id AS track_id FROM tracks t
+             WHERE t._id IS NOT NULL AND t.type = 'LOCAL' AND t.path IS NOT NULL
+               AND t.silence_start IS NULL
+               AND t._id NOT IN (SELECT value FROM json_each(?))
+             LIMIT ?",
+        )
+        .bind::<Text, _>(exclude)
+        .bind::<BigInt, _>(limit)
+        .load(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(rows.into_iter().map(|r| r.track_id).collect())
+    }
+
+    /// Store the extracted audio features of tracks, replacing earlier values.
+    #[tracing::instrument(level = "debug", skip(self, features))]
+    pub fn set_track_features(&self, features: &[(String, TrackAudioFeatures)]) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::{Double, Text};
+
+        let mut conn = self.pool.get().unwrap();
+        conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            for (track_id, f) in features {
+                sql_query(
+                    "INSERT INTO track_features (track_id, energy, valence, danceability, tempo, mood)
+                     VALUES (?, ?, ?, ?, ?, ?)
+                     ON CONFLICT(track_id) DO UPDATE SET energy = excluded.energy,
+                       valence = excluded.valence, danceability = excluded.danceability,
+                       tempo = excluded.tempo, mood = excluded.mood, updated_at = CURRENT_TIMESTAMP",
+                )
+                .bind::<Text, _>(track_id)
+                .bind::<Double, _>(f.energy)
+                .bind::<Double, _>(f.valence)
+                .bind::<Double, _>(f.danceability)
+                .bind::<Double, _>(f.tempo)
+                .bind::<Text, _>(f.mood.as_str())
+                .execute(conn)?;
+            }
+            Ok(())
+        })
+        .map_err(error_helpers::to_database_error)
+    }
+
+    /// Extracted audio features of a track; `None` when it was never analyzed.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_track_features(&self, track_id: &str) -> Result<Option<TrackAudioFeatures>> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        let row: Option<TrackFeaturesRow> = sql_query(
+            "SELECT energy, valence, danceability, tempo, mood FROM track_features WHERE track_id = ?",
+        )
+        .bind::<Text, _>(track_id)
+        .get_result(&mut conn)
+        .optional()
+        .map_err(error_helpers::to_database_error)?;
+        Ok(row.and_then(|row| {
+            let Some(mood) = TrackMood::parse(&row.mood) else {
+                warn!("Ignoring unknown mood {} of {}", row.mood, track_id);
+                return None;
+            };
+            Some(TrackAudioFeatures {
+                energy: row.energy,
+                valence: row.valence,
+                danceability: row.danceability,
+                tempo: row.tempo,
+                mood,
+            })
+        }))
+    }
+
+    /// Local tracks with a file that were never analyzed, at most `limit`.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_unanalyzed_track_ids(&self, limit: i64) -> Result<Vec<String>> {
+        use diesel::sql_query;
+        use diesel::sql_types::BigInt;
+
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<RankedTrackRow> = sql_query(
+            "SELECT t._id AS track_id FROM tracks t
+             WHERE t._id IS NOT NULL AND t.type = 'LOCAL' AND t.path IS NOT NULL
+               AND NOT EXISTS (SELECT 1 FROM track_features f WHERE f.track_id = t._id)
+             LIMIT ?",
+        )
+        .bind::<BigInt, _>(limit)
+        .load(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(rows.into_iter().map(|r| r.track_id).collect())
+    }
+
+    /// Local tracks without a cover, at most `limit`, skipping `exclude`.
+    #[tracing::instrument(level = "debug", skip(self, exclude))]
+    pub fn get_coverless_track_ids(&self, limit: i64, exclude: &[String]) -> Result<Vec<String>> {
+        use diesel::sql_query;
+        use diesel::sql_types::{BigInt, Text};
+
+        let exclude = serde_json::to_string(exclude)?;
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<RankedTrackRow> = sql_query(
+            "SELECT t._id AS track_id FROM tracks t
+             WHERE t._id IS NOT NULL AND t.type = 'LOCAL' AND t.track_coverpath_high IS NULL
+               AND t._id NOT IN (SELECT value FROM json_each(?))
+             LIMIT ?",
+        )
+        .bind::<Text, _>(exclude)
+        .bind::<BigInt, _>(limit)
+        .load(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(rows.into_iter().map(|r| r.track_id).collect())
+    }
+
+    /// IDs of the analyzed tracks matching `filter`. `scope` restricts the
+    /// result to the given tracks; `None` searches the whole library.
+    #[tracing::instrument(level = "debug", skip(self, scope))]
+    pub fn filter_track_ids_by_features(
+        &self,
+        filter: &TrackFeatureFilter,
+        scope: Option<&[String]>,
+    ) -> Result<Vec<String>> {
+        use diesel::sql_query;
+        use diesel::sql_types::{Double, Nullable, Text};
+
+        let scope = scope.map(serde_json::to_string).transpose()?;
+        let moods = (!filter.moods.is_empty())
+            .then(|| serde_json::to_string(&filter.moods))
+            .transpose()?;
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<RankedTrackRow> = sql_query(
+            "SELECT f.track_id AS track_id FROM track_features f
+             JOIN tracks t ON t._id = f.track_id
+             WHERE (? IS NULL OR f.track_id IN (SELECT value FROM json_each(?)))
+               AND (? IS NULL OR f.mood IN (SELECT value FROM json_each(?)))
+               AND (? IS NULL OR f.energy >= ?) AND (? IS NULL OR f.energy <= ?)
+               AND (? IS NULL OR f.valence >= ?) AND (? IS NULL OR f.valence <= ?)
+               AND (? IS NULL OR f.danceability >= ?) AND (? IS NULL OR f.danceability <= ?)
+               AND (? IS NULL OR f.tempo >= ?) AND (? IS NULL OR f.tempo <= ?)",
+        )
+        .bind::<Nullable<Text>, _>(scope.clone())
+        .bind::<Nullable<Text>, _>(scope)
+        .bind::<Nullable<Text>, _>(moods.clone())
+        .bind::<Nullable<Text>, _>(moods)
+        .bind::<Nullable<Double>, _>(filter.min_energy)
+        .bind::<Nullable<Double>, _>(filter.min_energy)
+        .bind::<Nullable<Double>, _>(filter.max_energy)
+        .bind::<Nullable<Double>, _>(filter.max_energy)
+        .bind::<Nullable<Double>, _>(filter.min_valence)
+        .bind::<Nullable<Double>, _>(filter.min_valence)
+        .bind::<Nullable<Double>, _>(filter.max_valence)
+        .bind::<Nullable<Double>, _>(filter.max_valence)
+        .bind::<Nullable<Double>, _>(filter.min_danceability)
+        .bind::<Nullable<Double>, _>(filter.min_danceability)
+        .bind::<Nullable<Double>, _>(filter.max_danceability)
+        .bind::<Nullable<Double>, _>(filter.max_danceability)
+        .bind::<Nullable<Double>, _>(filter.min_tempo)
+        .bind::<Nullable<Double>, _>(filter.min_tempo)
+        .bind::<Nullable<Double>, _>(filter.max_tempo)
+        .bind::<Nullable<Double>, _>(filter.max_tempo)
+        .load(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(rows.into_iter().map(|r| r.track_id).collect())
+    }
+
+    /// Run a regular library query and keep the tracks matching `filter`, in
+    /// the query's order.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_tracks_by_features(
+        &self,
+        options: GetTrackOptions,
+        filter: &TrackFeatureFilter,
+    ) -> Result<Vec<MediaContent>> {
+        let found = self.get_tracks_by_options(options)?;
+        let ids: Vec<String> = found.iter().filter_map(|t| t.track._id.clone()).collect();
+        let matching: std::collections::HashSet<String> = self
+            .filter_track_ids_by_features(filter, Some(&ids))?
+            .into_iter()
+            .collect();
+        Ok(found
+            .into_iter()
+            .filter(|t| t.track._id.as_ref().is_some_and(|id| matching.contains(id)))
+            .collect())
+    }
+
+    /// Tracks in the same mood as `seed`, closest in energy, valence,
+    /// danceability and tempo first. Empty when the seed was never analyzed.
+    #[tracing::instrument(level = "debug", skip(self, exclude))]
+    pub fn similar_mood_track_ids(&self, seed: &str, exclude: &[String], limit: i64) -> Result<Vec<String>> {
+        use diesel::sql_query;
+        use diesel::sql_types::{BigInt, Text};
+
+        let exclude = serde_json::to_string(exclude)?;
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<RankedTrackRow> = sql_query(
+            "SELECT f.track_id AS track_id FROM track_features f
+             JOIN track_features s ON s.track_id = ? AND s.mood = f.mood
+             JOIN tracks t ON t._id = f.track_id
+             WHERE f.track_id != s.track_id
+               AND f.track_id NOT IN (SELECT value FROM json_each(?))
+             ORDER BY (f.energy - s.energy) * (f.energy - s.energy)
+                    + (f.valence - s.valence) * (f.valence - s.valence)
+                    + (f.danceability - s.danceability) * (f.danceability - s.danceability)
+                    + ((f.tempo - s.tempo) / 100.0) * ((f.tempo - s.tempo) / 100.0)
+             LIMIT ?",
+        )
+        .bind::<Text, _>(seed)
+        .bind::<Text, _>(exclude)
+        .bind::<BigInt, _>(limit)
+        .load(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(rows.into_iter().map(|r| r.track_id).collect())
+    }
+
+    /// Store the fingerprint of a track, keeping its AcoustID id when the
+    /// fingerprint didn't change.
+    #[tracing::instrument(level = "debug", skip(self, fingerprint))]
+    pub fn set_track_fingerprint(&self, track_id: &str, fingerprint: &[u32], duration: f64) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::{Double, Text};
+
+        let fingerprint = serde_json::to_string(fingerprint)?;
+        let mut conn = self.pool.get().unwrap();
+        sql_query(
+            "INSERT INTO track_fingerprints (track_id, fingerprint, duration) VALUES (?, ?, ?)
+             ON CONFLICT(track_id) DO UPDATE SET
+               acoustid = CASE WHEN fingerprint = excluded.fingerprint THEN acoustid END,
+               fingerprint = excluded.fingerprint, duration = excluded.duration,
+               updated_at = CURRENT_TIMESTAMP",
+        )
+        .bind::<Text, _>(track_id)
+        .bind::<Text, _>(fingerprint)
+        .bind::<Double, _>(duration)
+        .execute(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(())
+    }
+
+    /// Stored fingerprint of a track; `None` when it was never fingerprinted.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_track_fingerprint(&self, track_id: &str) -> Result<Option<TrackFingerprint>> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        let row: Option<TrackFingerprintRow> = sql_query(
+            "SELECT track_id, fingerprint, duration, acoustid FROM track_fingerprints WHERE track_id = ?",
+        )
+        .bind::<Text, _>(track_id)
+        .get_result(&mut conn)
+        .optional()
+        .map_err(error_helpers::to_database_error)?;
+        row.map(|row| {
+            Ok(TrackFingerprint {
+                track_id: row.track_id,
+                fingerprint: serde_json::from_str(&row.fingerprint)?,
+                duration: row.duration,
+                acoustid: row.acoustid,
+            })
+        })
+        .transpose()
+    }
+
+    /// Record the AcoustID track id a fingerprint was looked up as.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_track_acoustid(&self, track_id: &str, acoustid: &str) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        sql_query("UPDATE track_fingerprints SET acoustid = ? WHERE track_id = ?")
+            .bind::<Text, _>(acoustid)
+            .bind::<Text, _>(track_id)
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(())
+    }
+
+    /// Local tracks with a file that were never fingerprinted, at most
+    /// `limit`, skipping `exclude`.
+    #[tracing::instrument(level = "debug", skip(self, exclude))]
+    pub fn get_unfingerprinted_track_ids(&self, limit: i64, exclude: &[String]) -> Result<Vec<String>> {
+        use diesel::sql_query;
+        use diesel::sql_types::{BigInt, Text};
+
+        let exclude = serde_json::to_string(exclude)?;
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<RankedTrackRow> = sql_query(
+            "SELECT t._id AS track_id FROM tracks t
+             WHERE t._id IS NOT NULL AND t.type = 'LOCAL' AND t.path IS NOT NULL
+               AND NOT EXISTS (SELECT 1 FROM track_fingerprints f WHERE f.track_id = t._id)
+               AND t._id NOT IN (SELECT value FROM json_each(?))
+             LIMIT ?",
+        )
+        .bind::<Text, _>(exclude)
+        .bind::<BigInt, _>(limit)
+        .load(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(rows.into_iter().map(|r| r.track_id).collect())
+    }
+
+    /// Local tracks other than `track_id` lasting `duration` give or take
+    /// `tolerance` seconds, closest first: the candidates for a file that
+    /// moved or was ripped again.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_fingerprint_candidates(
+        &self,
+        track_id: &str,
+        duration: f64,
+        tolerance: f64,
+        limit: i64,
+    ) -> Result<Vec<String>> {
+        use diesel::sql_query;
+        use diesel::sql_types::{BigInt, Double, Text};
+
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<RankedTrackRow> = sql_query(
+            "SELECT t._id AS track_id FROM tracks t
+             WHERE t._id IS NOT NULL AND t._id != ? AND t.type = 'LOCAL' AND t.path IS NOT NULL
+               AND t.duration BETWEEN ? AND ?
+             ORDER BY abs(t.duration - ?)
+             LIMIT ?",
+        )
+        .bind::<Text, _>(track_id)
+        .bind::<Double, _>(duration - tolerance)
+        .bind::<Double, _>(duration + tolerance)
+        .bind::<Double, _>(duration)
+        .bind::<BigInt, _>(limit)
+        .load(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(rows.into_iter().map(|r| r.track_id).collect())
+    }
+
+    /// Move the play history, queue entries, playlist entries, rating,
+    /// resume position, bookmarks and edit regions of track `from` to track
+    /// `to`, for a file that moved or was replaced by a re-rip. Per-track
+    /// values `to` already has are kept. `from` itself is left to be removed.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn relink_track(&self, from: &str, to: &str) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            for statement in [
+                "UPDATE play_history SET track_id = ? WHERE track_id = ?",
+                "UPDATE play_queue SET track_id = ? WHERE track_id = ?",
+                "UPDATE playlist_bridge SET track = ? WHERE track = ?",
+                "UPDATE track_bookmarks SET track_id = ? WHERE track_id = ?",
+                "UPDATE OR IGNORE track_ratings SET track_id = ? WHERE track_id = ?",
+                "UPDATE OR IGNORE track_positions SET track_id = ? WHERE track_id = ?",
+                "UPDATE OR IGNORE track_edit_regions SET track_id = ? WHERE track_id = ?",
+            ] {
+                sql_query(statement)
+                    .bind::<Text, _>(to)
+                    .bind::<Text, _>(from)
+                    .execute(conn)?;
+            }
+            sql_query("DELETE FROM track_fingerprints WHERE track_id = ?")
+                .bind::<Text, _>(from)
+                .execute(conn)?;
+            Ok(())
+        })
+        .map_err(error_helpers::to_database_error)
+    }
+
+    /// Add the podcast of `podcast.feed_url`, or update its details when
+    /// already subscribed. Returns the podcast ID, which stays the same.
+    #[tracing::instrument(level = "debug", skip(self, podcast))]
+    pub fn upsert_podcast(&self, podcast: &Podcast, now: i64) -> Result<String> {
+        use diesel::sql_query;
+        use diesel::sql_types::{BigInt, Nullable, Text};
+
+        let mut conn = self.pool.get().unwrap();
+        sql_query(
+            "INSERT INTO podcasts (id, feed_url, title, author, description, image_url, link, subscribed_at, refreshed_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(feed_url) DO UPDATE SET title = excluded.title, author = excluded.author,
+               description = excluded.description, image_url = excluded.image_url, link = excluded.link,
+               refreshed_at = excluded.refreshed_at, last_error = NULL",
+        )
+        .bind::<Text, _>(Uuid::new_v4().to_string())
+        .bind::<Text, _>(&podcast.feed_url)
+        .bind::<Text, _>(&podcast.title)
+        .bind::<Nullable<Text>, _>(podcast.author.as_deref())
+        .bind::<Nullable<Text>, _>(podcast.description.as_deref())
+        .bind::<Nullable<Text>, _>(podcast.image_url.as_deref())
+        .bind::<Nullable<Text>, _>(podcast.link.as_deref())
+        .bind::<BigInt, _>(now)
+        .bind::<BigInt, _>(now)
+        .execute(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+
+        let row: RankedTrackRow = sql_query("SELECT id AS track_id FROM podcasts WHERE feed_url = ?")
+            .bind::<Text, _>(&podcast.feed_url)
+            .get_result(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(row.track_id)
+    }
+
+    /// Record the error of a failed refresh
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_podcast_error(&self, podcast_id: &str, error: &str) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        sql_query("UPDATE podcasts SET last_error = ? WHERE id = ?")
+            .bind::<Text, _>(error)
+            .bind::<Text, _>(podcast_id)
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(())
+    }
+
+    /// Store the episodes of a feed. New episodes are added; known ones (by
+    /// GUID) get their details updated but keep their position and played
+    /// state. Returns the number of new episodes.
+    #[tracing::instrument(level = "debug", skip(self, episodes))]
+    pub fn upsert_episodes(&self, podcast_id: &str, episodes: &[PodcastEpisode]) -> Result<usize> {
+        use diesel::sql_query;
+        use diesel::sql_types::{BigInt, Double, Nullable, Text};
+
+        let mut conn = self.pool.get().unwrap();
+        conn.transaction::<usize, diesel::result::Error, _>(|conn| {
+            let mut added = 0;
+            for episode in episodes {
+                let known: Option<RankedTrackRow> =
+                    sql_query("SELECT id AS track_id FROM episodes WHERE podcast_id = ? AND guid = ?")
+                        .bind::<Text, _>(podcast_id)
+                        .bind::<Text, _>(&episode.guid)
+                        .get_result(conn)
+                        .optional()?;
+                if known.is_none() {
+                    added += 1;
+                }
+                sql_query(
+                    "INSERT INTO episodes (id, podcast_id, guid, title, description, audio_url, mime_type, duration, published_at)
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                     ON CONFLICT(podcast_id, guid) DO UPDATE SET title = excluded.title,
+                       description = excluded.description, audio_url = excluded.audio_url,
+                       mime_type = excluded.mime_type, duration = excluded.duration,
+                       published_at = excluded.published_at",
+                )
+                .bind::<Text, _>(Uuid::new_v4().to_string())
+                .bind::<Text, _>(podcast_id)
+                .bind::<Text, _>(&episode.guid)
+                .bind::<Text, _>(&episode.title)
+                .bind::<Nullable<Text>, _>(episode.description.as_deref())
+                .bind::<Text, _>(&episode.audio_url)
+                .bind::<Nullable<Text>, _>(episode.mime_type.as_deref())
+                .bind::<Nullable<Double>, _>(episode.duration)
+                .bind::<Nullable<BigInt>, _>(episode.published_at)
+                .execute(conn)?;
+            }
+            Ok(added)
+        })
+        .map_err(error_helpers::to_database_error)
+    }
+
+    /// Subscribed podcasts by title, with their episode counts
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_podcasts(&self) -> Result<Vec<Podcast>> {
+        use diesel::sql_query;
+
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<PodcastRow> = sql_query(format!(
+            "SELECT {} FROM podcasts p ORDER BY p.title COLLATE NOCASE",
+            PODCAST_COLUMNS
+        ))
+        .load(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(rows.into_iter().map(Podcast::from).collect())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_podcast(&self, podcast_id: &str) -> Result<Option<Podcast>> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        let row: Option<PodcastRow> = sql_query(format!("SELECT {} FROM podcasts p WHERE p.id = ?", PODCAST_COLUMNS))
+            .bind::<Text, _>(podcast_id)
+            .get_result(&mut conn)
+            .optional()
+            .map_err(error_helpers::to_database_error)?;
+        Ok(row.map(Podcast::from))
+    }
+
+    /// Unsubscribe from a podcast, dropping its episodes
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn delete_podcast(&self, podcast_id: &str) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            sql_query("DELETE FROM episodes WHERE podcast_id = ?")
+                .bind::<Text, _>(podcast_id)
+                .execute(conn)?;
+            sql_query("DELETE FROM podcasts WHERE id = ?")
+                .bind::<Text, _>(podcast_id)
+                .execute(conn)?;
+            Ok(())
+        })
+        .map_err(error_helpers::to_database_error)
+    }
+
+    /// Episodes, newest first. `podcast_id` restricts them to one podcast;
+    /// `unplayed` leaves out the played ones.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_episodes(&self, podcast_id: Option<&str>, unplayed: bool, limit: i64, offset: i64) -> Result<Vec<PodcastEpisode>> {
+        use diesel::sql_query;
+        use diesel::sql_types::{BigInt, Bool, Nullable, Text};
+
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<EpisodeRow> = sql_query(format!(
+            "SELECT {} FROM episodes
+             WHERE (? IS NULL OR podcast_id = ?) AND (NOT ? OR NOT played)
+             ORDER BY published_at IS NULL, published_at DESC, title
+             LIMIT ? OFFSET ?",
+            EPISODE_COLUMNS
+        ))
+        .bind::<Nullable<Text>, _>(podcast_id)
+        .bind::<Nullable<Text>, _>(podcast_id)
+        .bind::<Bool, _>(unplayed)
+        .bind::<BigInt, _>(limit)
+        .bind::<BigInt, _>(offset)
+        .load(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(rows.into_iter().map(PodcastEpisode::from).collect())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_episode(&self, episode_id: &str) -> Result<Option<PodcastEpisode>> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        let row: Option<EpisodeRow> = sql_query(format!("SELECT {} FROM episodes WHERE id = ?", EPISODE_COLUMNS))
+            .bind::<Text, _>(episode_id)
+            .get_result(&mut conn)
+            .optional()
+            .map_err(error_helpers::to_database_error)?;
+        Ok(row.map(PodcastEpisode::from))
+    }
+
+    /// Remember where playback of an episode stands
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_episode_progress(&self, episode_id: &str, position: f64, played: bool) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::{Bool, Double, Text};
+
+        let mut conn = self.pool.get().unwrap();
+        sql_query("UPDATE episodes SET position = ?, played = ? WHERE id = ?")
+            .bind::<Double, _>(position)
+            .bind::<Bool, _>(played)
+            .bind::<Text, _>(episode_id)
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(())
+    }
+
+    /// Replace the chapters of tracks with the ones read from their tags.
+    /// Tracks without chapters lose any stored ones.
+    #[tracing::instrument(level = "debug", skip(self, chapters))]
+    pub fn set_track_chapters(&self, chapters: &[(String, Vec<TrackChapter>)]) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::{Double, Integer, Nullable, Text};
+
+        let mut conn = self.pool.get().unwrap();
+        conn.transaction::<(), diesel::result::Error, _>(|conn| {
+            for (track_id, track_chapters) in chapters {
+                sql_query("DELETE FROM track_chapters WHERE track_id = ?")
+                    .bind::<Text, _>(track_id)
+                    .execute(conn)?;
+                for (idx, chapter) in track_chapters.iter().enumerate() {
+                    sql_query(
+                        "INSERT INTO track_chapters (track_id, idx, start_time, end_time, title)
+                         VALUES (?, ?, ?, ?, ?)",
+                    )
+                    .bind::<Text, _>(track_id)
+                    .bind::<Integer, _>(idx as i32)
+                    .bind::<Double, _>(chapter.start)
+                    .bind::<Nullable<Double>, _>(chapter.end)
+                    .bind::<Nullable<Text>, _>(chapter.title.as_deref())
+                    .execute(conn)?;
+                }
+            }
+            Ok(())
+        })
+        .map_err(error_helpers::to_database_error)
+    }
+
+    /// Chapters of a track, ordered by start; empty when it has none.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_track_chapters(&self, track_id: &str) -> Result<Vec<TrackChapter>> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<TrackChapterRow> = sql_query(
+            "SELECT start_time, end_time, title FROM track_chapters WHERE track_id = ? ORDER BY start_time",
+        )
+        .bind::<Text, _>(track_id)
+        .load(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(rows
+            .into_iter()
+            .map(|row| TrackChapter {
+                start: row.start_time,
+                end: row.end_time,
+                title: row.title,
+            })
+            .collect())
+    }
+
+    /// Saved resume position of a track in seconds, `None` when there is none.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_track_position(&self, track_id: &str) -> Result<Option<f64>> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        let row: Option<TrackPositionRow> = sql_query("SELECT position FROM track_positions WHERE track_id = ?")
+            .bind::<Text, _>(track_id)
+            .get_result(&mut conn)
+            .optional()
+            .map_err(error_helpers::to_database_error)?;
+        Ok(row.map(|row| row.position))
+    }
+
+    /// Save the resume position of a track; `None` forgets it.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_track_position(&self, track_id: &str, position: Option<f64>) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::{Double, Text};
+
+        let mut conn = self.pool.get().unwrap();
+        match position {
+            Some(position) => sql_query(
+                "INSERT INTO track_positions (track_id, position) VALUES (?, ?)
+                 ON CONFLICT(track_id) DO UPDATE SET position = excluded.position, updated_at = CURRENT_TIMESTAMP",
+            )
+            .bind::<Text, _>(track_id)
+            .bind::<Double, _>(position)
+            .execute(&mut conn),
+            None => sql_query("DELETE FROM track_positions WHERE track_id = ?")
+                .bind::<Text, _>(track_id)
+                .execute(&mut conn),
+        }
+        .map_err(error_helpers::to_database_error)?;
+        Ok(())
+    }
+
+    /// Bookmark `position` (seconds) of a track, with an optional label.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn add_track_bookmark(&self, track_id: &str, position: f64, label: Option<&str>) -> Result<TrackBookmark> {
+        use diesel::sql_query;
+        use diesel::sql_types::{Double, Nullable, Text};
+
+        if !position.is_finite() || position < 0.0 {
+            return Err("Bookmark position must be a non-negative number of seconds".into());
+        }
+        let bookmark = TrackBookmark {
+            id: Uuid::new_v4().to_string(),
+            track_id: track_id.to_string(),
+            position,
+            label: label.map(str::trim).filter(|l| !l.is_empty()).map(str::to_string),
+        };
+        let mut conn = self.pool.get().unwrap();
+        sql_query("INSERT INTO track_bookmarks (bookmark_id, track_id, position, label) VALUES (?, ?, ?, ?)")
+            .bind::<Text, _>(&bookmark.id)
+            .bind::<Text, _>(&bookmark.track_id)
+            .bind::<Double, _>(bookmark.position)
+            .bind::<Nullable<Text>, _>(bookmark.label.as_deref())
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(bookmark)
+    }
+
+    /// Bookmarks of a track, ordered by position.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_track_bookmarks(&self, track_id: &str) -> Result<Vec<TrackBookmark>> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<TrackBookmarkRow> = sql_query(
+            "SELECT bookmark_id, track_id, position, label FROM track_bookmarks
+             WHERE track_id = ? ORDER BY position, created_at",
+        )
+        .bind::<Text, _>(track_id)
+        .load(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(rows.into_iter().map(TrackBookmark::from).collect())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_track_bookmark(&self, bookmark_id: &str) -> Result<Option<TrackBookmark>> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        let row: Option<TrackBookmarkRow> = sql_query(
+            "SELECT bookmark_id, track_id, position, label FROM track_bookmarks WHERE bookmark_id = ?",
+        )
+        .bind::<Text, _>(bookmark_id)
+        .get_result(&mut conn)
+        .optional()
+        .map_err(error_helpers::to_database_error)?;
+        Ok(row.map(TrackBookmark::from))
+    }
+
+    /// Remove a bookmark; returns the track it belonged to, `None` when there
+    /// was no such bookmark.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn remove_track_bookmark(&self, bookmark_id: &str) -> Result<Option<String>> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let Some(bookmark) = self.get_track_bookmark(bookmark_id)? else {
+            return Ok(None);
+        };
+        let mut conn = self.pool.get().unwrap();
+        sql_query("DELETE FROM track_bookmarks WHERE bookmark_id = ?")
+            .bind::<Text, _>(bookmark_id)
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(Some(bookmark.track_id))
+    }
+
+    /// Keep a play queue being replaced in the history, unless it equals the
+    /// latest one kept, dropping queues beyond `MAX_QUEUE_SNAPSHOTS`.
+    #[tracing::instrument(level = "debug", skip(self, track_ids))]
+    pub fn record_queue_snapshot(&self, name: &str, track_ids: &[String]) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::{BigInt, Text};
+
+        if track_ids.is_empty() {
+            return Ok(());
+        }
+        let track_ids = serde_json::to_string(track_ids)?;
+        let mut conn = self.pool.get().unwrap();
+        let latest: Option<QueueSnapshotRow> = sql_query(
+            "SELECT history_id, name, track_ids, created_at FROM queue_history
+             ORDER BY created_at DESC, rowid DESC LIMIT 1",
+        )
+        .get_result(&mut conn)
+        .optional()
+        .map_err(error_helpers::to_database_error)?;
+        if latest.is_some_and(|l| l.track_ids == track_ids) {
+            return Ok(());
+        }
+        sql_query("INSERT INTO queue_history (history_id, name, track_ids) VALUES (?, ?, ?)")
+            .bind::<Text, _>(Uuid::new_v4().to_string())
+            .bind::<Text, _>(name)
+            .bind::<Text, _>(&track_ids)
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        sql_query(
+            "DELETE FROM queue_history WHERE rowid NOT IN
+             (SELECT rowid FROM queue_history ORDER BY created_at DESC, rowid DESC LIMIT ?)",
+        )
+        .bind::<BigInt, _>(MAX_QUEUE_SNAPSHOTS)
+        .execute(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(())
+    }
+
+    /// Play queues kept in the history, newest first.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_queue_history(&self) -> Result<Vec<QueueSnapshot>> {
+        use diesel::sql_query;
+
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<QueueSnapshotRow> = sql_query(
+            "SELECT history_id, name, track_ids, created_at FROM queue_history
+             ORDER BY created_at DESC, rowid DESC",
+        )
+        .load(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(rows.into_iter().map(QueueSnapshot::from).collect())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_queue_snapshot(&self, history_id: &str) -> Result<Option<QueueSnapshot>> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        let row: Option<QueueSnapshotRow> = sql_query(
+            "SELECT history_id, name, track_ids, created_at FROM queue_history WHERE history_id = ?",
+        )
+        .bind::<Text, _>(history_id)
+        .get_result(&mut conn)
+        .optional()
+        .map_err(error_helpers::to_database_error)?;
+        Ok(row.map(QueueSnapshot::from))
+    }
+
+    /// Save a smart sort preset, replacing the one with the same name.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn save_sort_preset(&self, preset: &SmartSortPreset) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        if preset.name.trim().is_empty() {
+            return Err("Sort preset name must not be empty".into());
+        }
+        let criteria = serde_json::to_string(&preset.criteria)?;
+        let mut conn = self.pool.get().unwrap();
+        sql_query(
+            "INSERT INTO sort_presets (name, criteria) VALUES (?, ?)
+             ON CONFLICT(name) DO UPDATE SET criteria = excluded.criteria, updated_at = CURRENT_TIMESTAMP",
+        )
+        .bind::<Text, _>(preset.name.trim())
+        .bind::<Text, _>(criteria)
+        .execute(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(())
+    }
+
+    /// Saved smart sort presets, by name. Presets whose criteria no longer
+    /// parse are skipped.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_sort_presets(&self) -> Result<Vec<SmartSortPreset>> {
+        use diesel::sql_query;
+
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<SortPresetRow> = sql_query("SELECT name, criteria FROM sort_presets ORDER BY name")
+            .load(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| match serde_json::from_str(&row.criteria) {
+                Ok(criteria) => Some(SmartSortPreset { name: row.name, criteria }),
+                Err(e) => {
+                    warn!("Skipping sort preset {} with invalid criteria: {}", row.name, e);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn delete_sort_preset(&self, name: &str) -> Result<()> {
+        use diesel::sql_query;
+        use diesel::sql_types::Text;
+
+        let mut conn = self.pool.get().unwrap();
+        sql_query("DELETE FROM sort_presets WHERE name = ?")
+            .bind::<Text, _>(name)
+            .execute(&mut conn)
+            .map_err(error_helpers::to_database_error)?;
+        Ok(())
+    }
+
+    /// Track IDs ranked by the weighted criteria of `preset`, best first.
+    ///
+    /// Each criterion is normalized to 0..1 (percentile rank for date added,
+    /// play count and title, rating / 5) and the weighted sum is ordered in SQL,
+    /// ties broken by title. `scope` restricts the ranking to the given tracks,
+    /// e.g. the result of another library query; `None` ranks the whole library.
+    #[tracing::instrument(level = "debug", skip(self, scope))]
+    pub fn smart_sort_track_ids(
+        &self,
+        preset: &SmartSortPreset,
+        scope: Option<&[String]>,
+        limit: Option<i64>,
+    ) -> Result<Vec<String>> {
+        use diesel::sql_query;
+        use diesel::sql_types::{BigInt, Double, Nullable, Text};
+
+        let scope = scope.map(serde_json::to_string).transpose()?;
+        let mut conn = self.pool.get().unwrap();
+        let rows: Vec<RankedTrackRow> = sql_query(
+            "WITH base AS (
+                 SELECT t._id AS track_id, t.title AS title,
+                        COALESCE(t.date_added, 0) AS added,
+                        (SELECT COUNT(*) FROM play_history ph WHERE ph.track_id = t._id) AS plays,
+                        COALESCE(r.rating, 0) AS rating
+                 FROM tracks t
+                 LEFT JOIN track_ratings r ON r.track_id = t._id
+                 WHERE t._id IS NOT NULL
+                   AND (? IS NULL OR t._id IN (SELECT value FROM json_each(?)))
+             ),
+             ranked AS (
+                 SELECT track_id, title,
+                        PERCENT_RANK() OVER (ORDER BY added) AS added_score,
+                        PERCENT_RANK() OVER (ORDER BY plays) AS plays_score,
+                        rating / 5.0 AS rating_score,
+                        1.0 - PERCENT_RANK() OVER (ORDER BY title COLLATE NOCASE) AS title_score
+                 FROM base
+             )
+             SELECT track_id FROM ranked
+             ORDER BY ? * added_score + ? * plays_score + ? * rating_score + ? * title_score DESC,
+                      title COLLATE NOCASE, track_id
+             LIMIT ?",
+        )
+        .bind::<Nullable<Text>, _>(scope.clone())
+        .bind::<Nullable<Text>, _>(scope)
+        .bind::<Double, _>(preset.weight_of(SmartSortCriterion::RecentlyAdded))
+        .bind::<Double, _>(preset.weight_of(SmartSortCriterion::PlayCount))
+        .bind::<Double, _>(preset.weight_of(SmartSortCriterion::Rating))
+        .bind::<Double, _>(preset.weight_of(SmartSortCriterion::Alphabetical))
+        // A negative limit means no limit in SQLite
+        .bind::<BigInt, _>(limit.unwrap_or(-1))
+        .load(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+        Ok(rows.into_iter().map(|r| r.track_id).collect())
+    }
+
+    /// Run a regular library query and order its result with `preset`.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_tracks_smart_sorted(
+        &self,
+        options: GetTrackOptions,
+        preset: &SmartSortPreset,
+    ) -> Result<Vec<MediaContent>> {
+        let found = self.get_tracks_by_options(options)?;
+        let ids: Vec<String> = found.iter().filter_map(|t| t.track._id.clone()).collect();
+        let order: std::collections::HashMap<String, usize> = self
+            .smart_sort_track_ids(preset, Some(&ids), None)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, id)| (id, i))
+            .collect();
+
+        let mut sorted = found;
+        // Stable, so tracks without an ID keep their relative order at the end
+        sorted.sort_by_key(|t| {
+            t.track
+                ._id
+                .as_ref()
+                .and_then(|id| order.get(id).copied())
+                .unwrap_or(usize::MAX)
+        });
+        Ok(sorted)
+    }
+
+    /// Schema version and pending migrations, for diagnostics.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn get_schema_version(&self) -> Result<types::entities::SchemaVersion> {
+        let mut conn = self.pool.get().unwrap();
+        migrations::schema_version(&mut conn, &self.path)
+    }
+
+    /// Report what pending migrations would change without touching the database.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn dry_run_migrations(&self) -> Result<types::entities::MigrationReport> {
+        migrate_database(&self.path, true)
+    }
+
+    /// Consistent copy of the whole database at `dest`, with the schema
+    /// version it is at.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn backup_database(&self, dest: &Path) -> Result<types::entities::SchemaVersion> {
+        migrations::snapshot_database(&self.path, dest)
+    }
+
+    /// Where a database to restore is written before `prepare_restore`.
+    pub fn staged_restore_path(&self) -> PathBuf {
+        migrations::staged_restore_path(&self.path)
+    }
+
+    /// Check and migrate the database written to `staged_restore_path`; it
+    /// replaces this one on the next start.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn prepare_restore(&self) -> Result<types::entities::MigrationReport> {
+        migrations::prepare_restore(&self.path)
+    }
+
+    /// Export tracks and play history to a standalone, de-normalized SQLite
+    /// file at `dest`. `progress` is called after every copied chunk.
+    #[tracing::instrument(level = "debug", skip(self, progress))]
+    pub fn export_library_sqlite(
+        &self,
+        dest: &Path,
+        mut progress: impl FnMut(types::entities::LibraryExportProgress),
+    ) -> Result<types::entities::LibraryExportSummary> {
+        crate::export::export_library(&self.path, dest, &mut progress)
+    }
+
     /// Get a connection from the pool for external use
     pub fn get_connection(&self) -> Result<r2d2::PooledConnection<ConnectionManager<LoggingConnection<SqliteConnection>>>> {
         self.pool.get().map_err(|e| types::errors::MusicError::String(format!("Failed to get DB connection: {}", e)))
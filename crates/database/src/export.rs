@@ -0,0 +1,262 @@
+//! Export of the library to a standalone SQLite file for analytics tools
+//! (DB Browser, Datasette, ...). The export is de-normalized: every track row
+//! carries its artists, album and genres as plain columns, and play history
+//! rows carry the track title. Internal tables (KV store, plugin states, task
+//! journal, caches) are left out.
+
+use std::path::{Path, PathBuf};
+
+use diesel::connection::SimpleConnection;
+use diesel::sql_types::BigInt;
+use diesel::{sql_query, Connection, OptionalExtension, RunQueryDsl, SqliteConnection};
+use tracing::{info, warn};
+use types::entities::{LibraryExportPhase, LibraryExportProgress, LibraryExportSummary};
+use types::errors::{error_helpers, Result};
+
+/// Rows copied per transaction, so the live database is never locked for long
+const EXPORT_CHUNK_SIZE: i64 = 500;
+
+const EXPORT_SCHEMA: &str = "
+    CREATE TABLE export.tracks (
+        id TEXT PRIMARY KEY,
+        title TEXT,
+        artists TEXT,
+        album TEXT,
+        album_artist TEXT,
+        genres TEXT,
+        year TEXT,
+        track_no INTEGER,
+        duration REAL,
+        bitrate REAL,
+        codec TEXT,
+        container TEXT,
+        sample_rate REAL,
+        size REAL,
+        source TEXT,
+        path TEXT,
+        url TEXT,
+        date_added INTEGER,
+        play_count INTEGER NOT NULL DEFAULT 0,
+        last_played TEXT
+    );
+    CREATE TABLE export.play_history (
+        id INTEGER PRIMARY KEY,
+        track_id TEXT NOT NULL,
+        title TEXT,
+        artists TEXT,
+        album TEXT,
+        played_at TEXT,
+        play_duration REAL
+    );
+";
+
+const EXPORT_INDICES: &str = "
+    CREATE INDEX export.idx_tracks_album ON tracks(album);
+    CREATE INDEX export.idx_tracks_artists ON tracks(artists);
+    CREATE INDEX export.idx_play_history_track ON play_history(track_id);
+    CREATE INDEX export.idx_play_history_played_at ON play_history(played_at);
+";
+
+const ARTISTS_OF_TRACK: &str = "(SELECT GROUP_CONCAT(a.artist_name, ', ')
+        FROM artist_bridge ab JOIN artists a ON a.artist_id = ab.artist
+        WHERE ab.track = t._id)";
+
+const ALBUM_OF_TRACK: &str = "LEFT JOIN album_bridge alb ON alb.track = t._id
+    LEFT JOIN albums al ON al.album_id = alb.album";
+
+#[derive(diesel::QueryableByName)]
+struct CountRow {
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+#[derive(diesel::QueryableByName)]
+struct RowIdRow {
+    #[diesel(sql_type = diesel::sql_types::Nullable<BigInt>)]
+    last: Option<i64>,
+}
+
+/// Write the export of the database at `db_path` to `dest`, replacing any
+/// existing file. Rows are copied in chunks; `progress` is called after each one.
+/// The file is built next to `dest` and only moved into place once complete.
+#[tracing::instrument(level = "debug", skip(progress))]
+pub fn export_library(
+    db_path: &Path,
+    dest: &Path,
+    progress: &mut dyn FnMut(LibraryExportProgress),
+) -> Result<LibraryExportSummary> {
+    let partial = PathBuf::from(format!("{}.partial", dest.to_string_lossy()));
+    if partial.exists() {
+        std::fs::remove_file(&partial).map_err(error_helpers::to_file_system_error)?;
+    }
+
+    let result = write_export(db_path, &partial, progress);
+    let (tracks, play_history) = match result {
+        Ok(counts) => counts,
+        Err(e) => {
+            if let Err(remove_err) = std::fs::remove_file(&partial) {
+                warn!("Failed to remove partial export {:?}: {}", partial, remove_err);
+            }
+            return Err(e);
+        }
+    };
+
+    std::fs::rename(&partial, dest).map_err(error_helpers::to_file_system_error)?;
+    let bytes = std::fs::metadata(dest).map(|m| m.len()).unwrap_or_default();
+    info!("Exported {} tracks and {} plays to {:?}", tracks, play_history, dest);
+    Ok(LibraryExportSummary {
+        path: dest.to_string_lossy().to_string(),
+        tracks,
+        play_history,
+        bytes,
+    })
+}
+
+fn write_export(
+    db_path: &Path,
+    target: &Path,
+    progress: &mut dyn FnMut(LibraryExportProgress),
+) -> Result<(u64, u64)> {
+    let mut conn = SqliteConnection::establish(&db_path.to_string_lossy())
+        .map_err(error_helpers::to_database_error)?;
+    conn.batch_execute(&format!(
+        "PRAGMA busy_timeout = 250; ATTACH DATABASE '{}' AS export;",
+        target.to_string_lossy().replace('\'', "''")
+    ))
+    .map_err(error_helpers::to_database_error)?;
+
+    let result = (|| {
+        conn.batch_execute(EXPORT_SCHEMA)
+            .map_err(error_helpers::to_database_error)?;
+
+        let tracks = copy_in_chunks(&mut conn, LibraryExportPhase::Tracks, "tracks", &tracks_insert(), progress)?;
+        let plays = copy_in_chunks(
+            &mut conn,
+            LibraryExportPhase::PlayHistory,
+            "play_history",
+            &play_history_insert(),
+            progress,
+        )?;
+
+        progress(LibraryExportProgress {
+            phase: LibraryExportPhase::Finalizing,
+            done: 0,
+            total: 1,
+        });
+        conn.batch_execute(EXPORT_INDICES)
+            .map_err(error_helpers::to_database_error)?;
+        Ok((tracks, plays))
+    })();
+
+    if let Err(e) = conn.batch_execute("DETACH DATABASE export;") {
+        warn!("Failed to detach export database: {}", e);
+    }
+    result
+}
+
+/// Copy `source` rows with `insert` (which must select `WHERE <alias>.rowid > ?
+/// AND <alias>.rowid <= ?`) one rowid window at a time.
+fn copy_in_chunks(
+    conn: &mut SqliteConnection,
+    phase: LibraryExportPhase,
+    source: &str,
+    insert: &str,
+    progress: &mut dyn FnMut(LibraryExportProgress),
+) -> Result<u64> {
+    let total = sql_query(format!("SELECT COUNT(*) AS count FROM {}", source))
+        .get_result::<CountRow>(conn)
+        .map_err(error_helpers::to_database_error)?
+        .count as u64;
+    progress(LibraryExportProgress { phase, done: 0, total });
+
+    let window_end = format!(
+        "SELECT MAX(rowid) AS last FROM (SELECT rowid FROM {} WHERE rowid > ? ORDER BY rowid LIMIT ?)",
+        source
+    );
+    let mut after = 0i64;
+    let mut done = 0u64;
+    loop {
+        let last = sql_query(&window_end)
+            .bind::<BigInt, _>(after)
+            .bind::<BigInt, _>(EXPORT_CHUNK_SIZE)
+            .get_result::<RowIdRow>(conn)
+            .optional()
+            .map_err(error_helpers::to_database_error)?
+            .and_then(|row| row.last);
+        let Some(last) = last else {
+            break;
+        };
+
+        let written = conn
+            .transaction::<usize, diesel::result::Error, _>(|conn| {
+                sql_query(insert)
+                    .bind::<BigInt, _>(after)
+                    .bind::<BigInt, _>(last)
+                    .execute(conn)
+            })
+            .map_err(error_helpers::to_database_error)?;
+        done += written as u64;
+        after = last;
+        // Rows added while exporting can push `done` past the initial count
+        progress(LibraryExportProgress {
+            phase,
+            done,
+            total: total.max(done),
+        });
+    }
+    Ok(done)
+}
+
+fn tracks_insert() -> String {
+    format!(
+        "INSERT OR IGNORE INTO export.tracks
+         SELECT t._id,
+                t.title,
+                {artists},
+                al.album_name,
+                al.album_artist,
+                (SELECT GROUP_CONCAT(g.genre_name, ', ')
+                   FROM genre_bridge gb JOIN genres g ON g.genre_id = gb.genre
+                  WHERE gb.track = t._id),
+                t.year,
+                CAST(t.track_no AS INTEGER),
+                t.duration,
+                t.bitrate,
+                t.codec,
+                t.container,
+                t.samplerate,
+                t.size,
+                t.type,
+                t.path,
+                t.url,
+                t.date_added,
+                (SELECT COUNT(*) FROM play_history ph WHERE ph.track_id = t._id),
+                (SELECT MAX(ph.played_at) FROM play_history ph WHERE ph.track_id = t._id)
+           FROM tracks t
+           {album}
+          WHERE t._id IS NOT NULL AND t.rowid > ? AND t.rowid <= ?
+          GROUP BY t._id",
+        artists = ARTISTS_OF_TRACK,
+        album = ALBUM_OF_TRACK,
+    )
+}
+
+fn play_history_insert() -> String {
+    format!(
+        "INSERT INTO export.play_history
+         SELECT ph.id,
+                ph.track_id,
+                t.title,
+                {artists},
+                al.album_name,
+                ph.played_at,
+                ph.play_duration
+           FROM play_history ph
+           LEFT JOIN tracks t ON t._id = ph.track_id
+           {album}
+          WHERE ph.rowid > ? AND ph.rowid <= ?
+          GROUP BY ph.id",
+        artists = ARTISTS_OF_TRACK,
+        album = ALBUM_OF_TRACK,
+    )
+}
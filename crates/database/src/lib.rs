@@ -1,5 +1,8 @@
 #![recursion_limit = "2048"]
 
 pub mod cache;
+pub mod collation;
 pub mod database;
+pub mod export;
+pub mod lyrics;
 pub mod migrations;
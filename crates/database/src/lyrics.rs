@@ -0,0 +1,206 @@
+//! Parsing stored lyrics into lines and locating a searched phrase inside
+//! them. Lyrics are either plain text or LRC, where lines start with one or
+//! more `[mm:ss.xx]` time tags.
+
+use types::entities::LyricsLine;
+
+/// Longest snippet returned for a match, in characters
+const MAX_SNIPPET_CHARS: usize = 120;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhraseMatch {
+    /// Line containing the phrase, trimmed to `MAX_SNIPPET_CHARS` around it
+    pub snippet: String,
+    /// Matched character range in `snippet`
+    pub start: usize,
+    pub end: usize,
+    /// Time tag of the line, if the lyrics are time-synced
+    pub time_ms: Option<u64>,
+}
+
+/// First line of `lyrics` containing `phrase`, compared case-insensitively.
+pub fn find_phrase(lyrics: &str, phrase: &str) -> Option<PhraseMatch> {
+    let needle: Vec<char> = phrase.trim().chars().flat_map(char::to_lowercase).collect();
+    if needle.is_empty() {
+        return None;
+    }
+
+    lyrics.lines().find_map(|raw| {
+        let (time_ms, text) = split_time_tags(raw);
+        let chars: Vec<char> = text.trim().chars().collect();
+        let start = find_chars(&chars, &needle)?;
+        let end = start + count_matched(&chars[start..], needle.len());
+        Some(snippet_around(&chars, start, end, time_ms))
+    })
+}
+
+/// Split lyrics into display lines. For LRC, metadata tags are dropped, a line
+/// with several time tags is repeated at each time and lines are sorted by time.
+pub fn parse_lines(lyrics: &str) -> Vec<LyricsLine> {
+    let synced = is_synced(lyrics);
+    let mut lines = vec![];
+    for raw in lyrics.lines() {
+        let (times, text) = all_time_tags(raw);
+        if times.is_empty() {
+            // Metadata such as [ar:Artist] only exists in LRC
+            if synced || text.trim().is_empty() {
+                continue;
+            }
+            lines.push(LyricsLine { time_ms: None, text: text.trim().to_string() });
+        } else {
+            lines.extend(times.into_iter().map(|t| LyricsLine {
+                time_ms: Some(t),
+                text: text.trim().to_string(),
+            }));
+        }
+    }
+    if synced {
+        lines.sort_by_key(|l| l.time_ms);
+    }
+    lines
+}
+
+/// Whether `lyrics` has LRC time tags
+pub fn is_synced(lyrics: &str) -> bool {
+    lyrics.lines().any(|l| split_time_tags(l).0.is_some())
+}
+
+/// Render timed lines as LRC text
+pub fn to_lrc(lines: &[LyricsLine]) -> String {
+    lines
+        .iter()
+        .map(|l| match l.time_ms {
+            Some(ms) => format!("[{:02}:{:02}.{:02}]{}", ms / 60_000, ms / 1000 % 60, ms % 1000 / 10, l.text),
+            None => l.text.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Index of the line playing at `position_ms` in time-sorted `lines`
+pub fn line_at(lines: &[LyricsLine], position_ms: u64) -> Option<usize> {
+    let next = lines.partition_point(|l| l.time_ms.is_some_and(|t| t <= position_ms));
+    next.checked_sub(1)
+}
+
+fn all_time_tags(line: &str) -> (Vec<u64>, &str) {
+    let mut times = vec![];
+    let mut rest = line.trim_start();
+    while let Some(tag) = rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+        let Some(ms) = parse_time_tag(tag.0) else { break };
+        times.push(ms);
+        rest = tag.1;
+    }
+    (times, rest)
+}
+
+/// Strip leading LRC time tags, returning the first one in milliseconds.
+/// Metadata tags such as `[ar:Artist]` are left untouched (no time).
+fn split_time_tags(line: &str) -> (Option<u64>, &str) {
+    let (times, rest) = all_time_tags(line);
+    (times.first().copied(), rest)
+}
+
+/// `mm:ss`, `mm:ss.xx` or `mm:ss.xxx`
+fn parse_time_tag(tag: &str) -> Option<u64> {
+    let (minutes, seconds) = tag.split_once(':')?;
+    let minutes: u64 = minutes.trim().parse().ok()?;
+    let (secs, fraction) = seconds.split_once(['.', ':']).unwrap_or((seconds, ""));
+    let secs: u64 = secs.trim().parse().ok()?;
+    let fraction_ms = match fraction.len() {
+        0 => 0,
+        1 => fraction.parse::<u64>().ok()? * 100,
+        2 => fraction.parse::<u64>().ok()? * 10,
+        _ => fraction.get(..3)?.parse::<u64>().ok()?,
+    };
+    Some(minutes * 60_000 + secs * 1000 + fraction_ms)
+}
+
+/// Index of the first char where the lowercased `haystack` starts with `needle`.
+fn find_chars(haystack: &[char], needle: &[char]) -> Option<usize> {
+    (0..haystack.len()).find(|&i| {
+        let lowered = haystack[i..].iter().flat_map(|c| c.to_lowercase());
+        lowered.take(needle.len()).eq(needle.iter().copied())
+    })
+}
+
+/// Number of original chars covering `needle_len` lowercased chars
+/// (a few characters lowercase to more than one char).
+fn count_matched(chars: &[char], needle_len: usize) -> usize {
+    let mut lowered = 0;
+    let mut taken = 0;
+    while lowered < needle_len && taken < chars.len() {
+        lowered += chars[taken].to_lowercase().count();
+        taken += 1;
+    }
+    taken
+}
+
+fn snippet_around(chars: &[char], start: usize, end: usize, time_ms: Option<u64>) -> PhraseMatch {
+    if chars.len() <= MAX_SNIPPET_CHARS {
+        return PhraseMatch {
+            snippet: chars.iter().collect(),
+            start,
+            end,
+            time_ms,
+        };
+    }
+
+    // Center the match, keeping the window inside the line
+    let context = MAX_SNIPPET_CHARS.saturating_sub(end - start) / 2;
+    let from = start.saturating_sub(context).min(chars.len().saturating_sub(MAX_SNIPPET_CHARS));
+    let to = (from + MAX_SNIPPET_CHARS).max(end).min(chars.len());
+    PhraseMatch {
+        snippet: chars[from..to].iter().collect(),
+        start: start - from,
+        end: end - from,
+        time_ms,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_phrase_in_lrc_with_time() {
+        let lyrics = "[ar:Someone]\n[00:12.34]First line here\n[01:02.5][02:00.00]And THE chorus goes\n";
+        let m = find_phrase(lyrics, "the chorus").unwrap();
+        assert_eq!(m.snippet, "And THE chorus goes");
+        assert_eq!((m.start, m.end), (4, 14));
+        assert_eq!(m.time_ms, Some(62_500));
+    }
+
+    #[test]
+    fn plain_and_cjk_lyrics() {
+        let m = find_phrase("天青色等烟雨\n而我在等你", "等你").unwrap();
+        assert_eq!(m.snippet, "而我在等你");
+        assert_eq!((m.start, m.end), (3, 5));
+        assert_eq!(m.time_ms, None);
+        assert!(find_phrase("nothing to see", "missing").is_none());
+    }
+
+    #[test]
+    fn parses_repeated_lrc_lines_in_time_order() {
+        let lines = parse_lines("[ti:Song]\n[00:20.00][00:05.50]Chorus\n[00:10.00]Verse\n");
+        let times: Vec<Option<u64>> = lines.iter().map(|l| l.time_ms).collect();
+        assert_eq!(times, [Some(5_500), Some(10_000), Some(20_000)]);
+        assert_eq!(lines[1].text, "Verse");
+        assert_eq!(line_at(&lines, 4_000), None);
+        assert_eq!(line_at(&lines, 12_000), Some(1));
+        assert_eq!(parse_lines(&to_lrc(&lines)), lines);
+
+        let plain = parse_lines("first\n\nsecond");
+        assert_eq!(plain.len(), 2);
+        assert!(plain.iter().all(|l| l.time_ms.is_none()));
+    }
+
+    #[test]
+    fn long_lines_are_trimmed_around_the_match() {
+        let line = format!("{}needle{}", "a".repeat(200), "b".repeat(200));
+        let m = find_phrase(&line, "NEEDLE").unwrap();
+        assert_eq!(m.snippet.chars().count(), MAX_SNIPPET_CHARS);
+        let matched: String = m.snippet.chars().skip(m.start).take(m.end - m.start).collect();
+        assert_eq!(matched, "needle");
+    }
+}
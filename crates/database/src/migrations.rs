@@ -1,9 +1,23 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use diesel::connection::SimpleConnection;
+use diesel::migration::MigrationSource;
 use diesel::sqlite::Sqlite;
+use diesel::{sql_query, Connection, RunQueryDsl, SqliteConnection};
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use tracing::{error, info, warn};
+use types::entities::{MigrationReport, SchemaVersion, TableRowChange};
+use types::errors::{error_helpers, MusicError, Result};
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
 pub const CACHE_MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations_cache");
 
+/// Directory next to the database holding pre-migration backups
+const BACKUP_DIR: &str = "migration_backups";
+/// Number of pre-migration backups kept per database
+const MAX_MIGRATION_BACKUPS: usize = 3;
+
 #[tracing::instrument(level = "debug", skip(databse))]
 pub fn run_migrations(databse: &mut impl MigrationHarness<Sqlite>) {
     databse
@@ -17,3 +31,360 @@ pub fn run_migration_cache(databse: &mut impl MigrationHarness<Sqlite>) {
         .run_pending_migrations(CACHE_MIGRATIONS)
         .expect("Failed to run migrations");
 }
+
+#[derive(diesel::QueryableByName)]
+struct TableNameRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    name: String,
+}
+
+#[derive(diesel::QueryableByName)]
+struct IntegrityRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    quick_check: String,
+}
+
+#[derive(diesel::QueryableByName)]
+struct CountRow {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    count: i64,
+}
+
+/// Apply pending migrations to the database at `db_path`.
+///
+/// The database is backed up first and the backup is restored when any
+/// migration fails, so a broken upgrade leaves the previous schema intact.
+/// With `dry_run` the migrations run against a throwaway copy and only the
+/// report (versions and per-table row changes) is returned.
+#[tracing::instrument(level = "debug")]
+pub fn migrate_database(db_path: &Path, dry_run: bool) -> Result<MigrationReport> {
+    let mut conn = establish(db_path)?;
+    let pending = pending_versions(&mut conn)?;
+    let Some(target) = pending.last().cloned() else {
+        return Ok(MigrationReport {
+            dry_run,
+            ..Default::default()
+        });
+    };
+    info!("{} pending migrations, target schema version {}", pending.len(), target);
+
+    let dir = backup_dir(db_path);
+    std::fs::create_dir_all(&dir).map_err(error_helpers::to_file_system_error)?;
+
+    if dry_run {
+        let scratch = dir.join(format!("dry-run-{}.db", uuid::Uuid::new_v4()));
+        vacuum_into(&mut conn, &scratch)?;
+        drop(conn);
+        let result = establish(&scratch).and_then(|mut scratch_conn| apply_pending(&mut scratch_conn));
+        if let Err(e) = std::fs::remove_file(&scratch) {
+            warn!("Failed to remove dry-run copy {:?}: {}", scratch, e);
+        }
+        let (applied, affected_tables) = result?;
+        return Ok(MigrationReport {
+            applied,
+            affected_tables,
+            backup_path: None,
+            dry_run: true,
+        });
+    }
+
+    // Nothing to protect on a brand-new database
+    let fresh = conn
+        .applied_migrations()
+        .map_err(MusicError::DatabaseError)?
+        .is_empty();
+    if fresh {
+        let (applied, affected_tables) = apply_pending(&mut conn)?;
+        return Ok(MigrationReport {
+            applied,
+            affected_tables,
+            backup_path: None,
+            dry_run: false,
+        });
+    }
+
+    let stem = db_stem(db_path);
+    let backup = dir.join(format!(
+        "{}-pre-{}-{}.db",
+        stem,
+        target,
+        chrono::Local::now().format("%Y%m%d%H%M%S")
+    ));
+    vacuum_into(&mut conn, &backup)?;
+    info!("Backed up database to {:?} before migrating", backup);
+
+    match apply_pending(&mut conn) {
+        Ok((applied, affected_tables)) => {
+            prune_backups(db_path);
+            Ok(MigrationReport {
+                applied,
+                affected_tables,
+                backup_path: Some(backup.to_string_lossy().to_string()),
+                dry_run: false,
+            })
+        }
+        Err(e) => {
+            error!("Migration failed, restoring backup {:?}: {}", backup, e);
+            drop(conn);
+            restore_backup(db_path, &backup)?;
+            Err(e)
+        }
+    }
+}
+
+/// Current schema version and migration state of an open connection.
+pub fn schema_version(
+    conn: &mut impl MigrationHarness<Sqlite>,
+    db_path: &Path,
+) -> Result<SchemaVersion> {
+    let applied = conn
+        .applied_migrations()
+        .map_err(MusicError::DatabaseError)?;
+    let pending = pending_versions(conn)?;
+    Ok(SchemaVersion {
+        version: applied.iter().map(|v| v.to_string()).max(),
+        applied_count: applied.len() as u32,
+        pending,
+        last_backup: list_backups(db_path)
+            .last()
+            .map(|p| p.to_string_lossy().to_string()),
+    })
+}
+
+fn establish(path: &Path) -> Result<SqliteConnection> {
+    SqliteConnection::establish(&path.to_string_lossy()).map_err(error_helpers::to_database_error)
+}
+
+fn pending_versions(conn: &mut impl MigrationHarness<Sqlite>) -> Result<Vec<String>> {
+    let pending = conn
+        .pending_migrations(MIGRATIONS)
+        .map_err(MusicError::DatabaseError)?;
+    let mut versions: Vec<String> = pending.iter().map(|m| m.name().version().to_string()).collect();
+    versions.sort();
+    Ok(versions)
+}
+
+/// Run pending migrations, returning applied versions and per-table row changes.
+fn apply_pending(conn: &mut SqliteConnection) -> Result<(Vec<String>, Vec<TableRowChange>)> {
+    let before = table_counts(conn)?;
+    let applied = conn
+        .run_pending_migrations(MIGRATIONS)
+        .map_err(MusicError::DatabaseError)?;
+    let after = table_counts(conn)?;
+    Ok((
+        applied.iter().map(|v| v.to_string()).collect(),
+        diff_counts(&before, &after),
+    ))
+}
+
+fn table_counts(conn: &mut SqliteConnection) -> Result<BTreeMap<String, i64>> {
+    let tables: Vec<TableNameRow> = sql_query(
+        "SELECT name FROM sqlite_master
+         WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name != '__diesel_schema_migrations'",
+    )
+    .load(conn)
+    .map_err(error_helpers::to_database_error)?;
+
+    let mut counts = BTreeMap::new();
+    for table in tables {
+        let row: CountRow = sql_query(format!(
+            "SELECT COUNT(*) AS count FROM \"{}\"",
+            table.name.replace('"', "\"\"")
+        ))
+        .get_result(conn)
+        .map_err(error_helpers::to_database_error)?;
+        counts.insert(table.name, row.count);
+    }
+    Ok(counts)
+}
+
+fn diff_counts(before: &BTreeMap<String, i64>, after: &BTreeMap<String, i64>) -> Vec<TableRowChange> {
+    let mut tables: Vec<&String> = before.keys().chain(after.keys()).collect();
+    tables.sort();
+    tables.dedup();
+    tables
+        .into_iter()
+        .map(|table| TableRowChange {
+            table: table.clone(),
+            before: before.get(table).copied(),
+            after: after.get(table).copied(),
+        })
+        .filter(|change| change.before != change.after)
+        .collect()
+}
+
+/// Consistent snapshot of the live database (WAL contents included).
+fn vacuum_into(conn: &mut SqliteConnection, target: &Path) -> Result<()> {
+    conn.batch_execute(&format!(
+        "VACUUM INTO '{}'",
+        target.to_string_lossy().replace('\'', "''")
+    ))
+    .map_err(error_helpers::to_database_error)
+}
+
+/// Copy `backup` over the database. All connections to it must be closed.
+fn restore_backup(db_path: &Path, backup: &Path) -> Result<()> {
+    std::fs::copy(backup, db_path).map_err(error_helpers::to_file_system_error)?;
+    // The backup already contains everything the WAL held; a stale WAL would be
+    // replayed on top of the restored file
+    for suffix in ["-wal", "-shm"] {
+        let side_file = PathBuf::from(format!("{}{}", db_path.to_string_lossy(), suffix));
+        if side_file.exists() {
+            std::fs::remove_file(&side_file).map_err(error_helpers::to_file_system_error)?;
+        }
+    }
+    info!("Restored database from {:?}", backup);
+    Ok(())
+}
+
+fn backup_dir(db_path: &Path) -> PathBuf {
+    db_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(BACKUP_DIR)
+}
+
+fn db_stem(db_path: &Path) -> String {
+    db_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "database".to_string())
+}
+
+/// Pre-migration backups of `db_path`, oldest first.
+fn list_backups(db_path: &Path) -> Vec<PathBuf> {
+    let prefix = format!("{}-pre-", db_stem(db_path));
+    let Ok(entries) = std::fs::read_dir(backup_dir(db_path)) else {
+        return vec![];
+    };
+    let mut backups: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().starts_with(&prefix))
+        .filter_map(|e| {
+            let modified = e.metadata().and_then(|m| m.modified()).ok()?;
+            Some((modified, e.path()))
+        })
+        .collect();
+    backups.sort();
+    backups.into_iter().map(|(_, path)| path).collect()
+}
+
+fn prune_backups(db_path: &Path) {
+    let backups = list_backups(db_path);
+    let excess = backups.len().saturating_sub(MAX_MIGRATION_BACKUPS);
+    for old in backups.into_iter().take(excess) {
+        if let Err(e) = std::fs::remove_file(&old) {
+            warn!("Failed to remove old migration backup {:?}: {}", old, e);
+        }
+    }
+}
+
+/// Snapshot of the database at `db_path` written to `target`, with the schema
+/// version it is at
+pub fn snapshot_database(db_path: &Path, target: &Path) -> Result<SchemaVersion> {
+    let mut conn = establish(db_path)?;
+    vacuum_into(&mut conn, target)?;
+    schema_version(&mut conn, db_path)
+}
+
+/// Where a restored database waits to replace the one at `db_path`
+pub fn staged_restore_path(db_path: &Path) -> PathBuf {
+    db_path.with_file_name(format!("{}-restore.db", db_stem(db_path)))
+}
+
+/// Applied versions no embedded migration knows, oldest first
+fn unknown_versions(applied: &[String], known: &[String]) -> Vec<String> {
+    let mut unknown: Vec<String> = applied.iter().filter(|v| !known.contains(v)).cloned().collect();
+    unknown.sort();
+    unknown
+}
+
+/// Check the database staged at `staged_restore_path(db_path)` and migrate it
+/// to the current schema. A database that fails the check, was written by a
+/// newer schema or fails to migrate is removed again.
+#[tracing::instrument(level = "debug")]
+pub fn prepare_restore(db_path: &Path) -> Result<MigrationReport> {
+    let staged = staged_restore_path(db_path);
+    let result = check_restore(&staged).and_then(|_| migrate_database(&staged, false));
+    if result.is_err() {
+        if let Err(e) = std::fs::remove_file(&staged) {
+            warn!("Failed to remove staged restore {:?}: {}", staged, e);
+        }
+    }
+    result
+}
+
+fn check_restore(staged: &Path) -> Result<()> {
+    let mut conn = establish(staged)?;
+    let check: Vec<IntegrityRow> = sql_query("PRAGMA quick_check")
+        .load(&mut conn)
+        .map_err(error_helpers::to_database_error)?;
+    if let Some(problem) = check.iter().find(|r| r.quick_check != "ok") {
+        return Err(MusicError::String(format!("Backup database is damaged: {}", problem.quick_check)));
+    }
+
+    let applied: Vec<String> = conn
+        .applied_migrations()
+        .map_err(MusicError::DatabaseError)?
+        .iter()
+        .map(|v| v.to_string())
+        .collect();
+    let known: Vec<String> = MigrationSource::<Sqlite>::migrations(&MIGRATIONS)
+        .map_err(MusicError::DatabaseError)?
+        .iter()
+        .map(|m| m.name().version().to_string())
+        .collect();
+    let unknown = unknown_versions(&applied, &known);
+    if !unknown.is_empty() {
+        return Err(MusicError::String(format!(
+            "Backup database has schema versions {:?} unknown to this version of the app",
+            unknown
+        )));
+    }
+    Ok(())
+}
+
+/// Replace the database at `db_path` with the restore staged for it, if any.
+/// No connection to the database may be open.
+#[tracing::instrument(level = "debug")]
+pub fn apply_staged_restore(db_path: &Path) -> Result<bool> {
+    let staged = staged_restore_path(db_path);
+    if !staged.exists() {
+        return Ok(false);
+    }
+    restore_backup(db_path, &staged)?;
+    std::fs::remove_file(&staged).map_err(error_helpers::to_file_system_error)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_reports_changed_created_and_dropped_tables() {
+        let before = BTreeMap::from([
+            ("tracks".to_string(), 10),
+            ("albums".to_string(), 3),
+            ("legacy".to_string(), 1),
+        ]);
+        let after = BTreeMap::from([
+            ("tracks".to_string(), 10),
+            ("albums".to_string(), 2),
+            ("romanized_names".to_string(), 7),
+        ]);
+        let changes = diff_counts(&before, &after);
+        let tables: Vec<&str> = changes.iter().map(|c| c.table.as_str()).collect();
+        assert_eq!(tables, vec!["albums", "legacy", "romanized_names"]);
+        assert_eq!(changes[1].after, None);
+        assert_eq!(changes[2].before, None);
+    }
+
+    #[test]
+    fn restore_refuses_versions_of_a_newer_schema() {
+        let known = vec!["20250101000000".to_string(), "20250201000000".to_string()];
+        let applied = vec!["20250301000000".to_string(), "20250101000000".to_string()];
+        assert_eq!(unknown_versions(&applied, &known), vec!["20250301000000"]);
+        assert!(unknown_versions(&known[..1], &known).is_empty());
+    }
+}
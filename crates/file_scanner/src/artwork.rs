@@ -0,0 +1,125 @@
+//! Artwork pipeline: covers embedded in tags or found next to the audio files
+//! (cover.jpg, folder.png, ...) are stored as small, medium and large WebP
+//! thumbnails named after the hash of the source image, so identical covers
+//! shared by the tracks of an album are stored once.
+
+use std::{
+    fs,
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+};
+
+use fast_image_resize::{self as fr, ResizeOptions};
+use image::ColorType;
+use types::{
+    entities::ArtworkSize,
+    errors::{error_helpers, Result},
+};
+
+/// Format of the thumbnails written
+const THUMBNAIL_EXTENSION: &str = "webp";
+
+/// Names of the cover images looked for next to audio files, most specific
+/// first
+const FOLDER_ART_NAMES: [&str; 5] = ["cover", "folder", "front", "album", "art"];
+const FOLDER_ART_EXTENSIONS: [&str; 4] = ["jpg", "jpeg", "png", "webp"];
+
+/// Cover image in `dir` (cover.jpg, folder.png, ...), preferring the names
+/// of `FOLDER_ART_NAMES` in order
+#[tracing::instrument(level = "debug")]
+pub fn folder_artwork(dir: &Path) -> Option<PathBuf> {
+    let rank = |path: &Path| {
+        let stem = path.file_stem()?.to_str()?.to_lowercase();
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        if !FOLDER_ART_EXTENSIONS.contains(&ext.as_str()) {
+            return None;
+        }
+        FOLDER_ART_NAMES.iter().position(|name| stem.starts_with(name))
+    };
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter_map(|path| rank(&path).map(|rank| (rank, path)))
+        .min()
+        .map(|(_, path)| path)
+}
+
+/// Store the thumbnails of the image `data` in `dir`, skipping those already
+/// there. Returns the paths of the large and small thumbnails.
+#[tracing::instrument(level = "debug", skip(dir, data))]
+pub fn store_artwork(dir: &Path, data: &[u8]) -> Result<(PathBuf, PathBuf)> {
+    if !dir.exists() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let hash = blake3::hash(data).to_hex();
+    let high_path = dir.join(format!("{}.{}", hash.as_str(), THUMBNAIL_EXTENSION));
+    for size in ArtworkSize::ALL.into_iter().rev() {
+        let path = size.variant_path(&high_path);
+        if !path.exists() {
+            generate_image(data, path, size.pixels())?;
+        }
+    }
+
+    Ok((
+        dunce::canonicalize(&high_path)?,
+        dunce::canonicalize(ArtworkSize::Small.variant_path(&high_path))?,
+    ))
+}
+
+/// Path of the `size` variant of a cover, generated from the large cover when
+/// missing. Generated files stay next to the cover and act as the cache.
+#[tracing::instrument(level = "debug")]
+pub fn artwork_variant(cover_high: &Path, size: ArtworkSize) -> Result<PathBuf> {
+    let path = size.variant_path(cover_high);
+    if !path.exists() {
+        let data = fs::read(cover_high)?;
+        generate_image(&data, path.clone(), size.pixels())?;
+    }
+    Ok(path)
+}
+
+/// Write a square `dimensions` thumbnail of the image `data` to `path`, in the
+/// format of its extension. Images that aren't square are cropped to the
+/// centre rather than stretched.
+#[tracing::instrument(level = "debug", skip(data, path, dimensions))]
+fn generate_image(data: &[u8], path: PathBuf, dimensions: u32) -> Result<()> {
+    let img = image::load_from_memory(data)
+        .map_err(error_helpers::to_media_error)?;
+
+    let (Some(width), Some(height)) = (NonZeroU32::new(img.width()), NonZeroU32::new(img.height())) else {
+        return Err("Cover image is empty".into());
+    };
+    let src_image = fr::images::Image::from_vec_u8(
+        width.into(),
+        height.into(),
+        img.to_rgba8().into_vec(),
+        fr::PixelType::U8x4,
+    ).map_err(error_helpers::to_media_error)?;
+
+    let dimensions = NonZeroU32::new(dimensions).unwrap();
+    let mut dst_image =
+        fr::images::Image::new(dimensions.into(), dimensions.into(), src_image.pixel_type());
+
+    let mut resizer = fr::Resizer::new();
+    resizer.resize(
+        &src_image,
+        &mut dst_image,
+        Some(
+            &ResizeOptions::new()
+                .resize_alg(fr::ResizeAlg::Convolution(fr::FilterType::Lanczos3))
+                .fit_into_destination(None),
+        ),
+    ).map_err(error_helpers::to_media_error)?;
+
+    image::save_buffer(
+        path,
+        dst_image.buffer(),
+        dimensions.get(),
+        dimensions.get(),
+        ColorType::Rgba8,
+    )
+    .map_err(error_helpers::to_media_error)
+}
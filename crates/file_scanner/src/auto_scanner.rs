@@ -1,26 +1,42 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
     sync::{atomic::{AtomicBool, Ordering}, Arc, RwLock},
-    time::{Duration, UNIX_EPOCH},
+    time::{Duration, Instant, UNIX_EPOCH},
 };
 
 use crossbeam_channel::unbounded as _;
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher, Event, EventKind};
 use serde::{Deserialize, Serialize};
+use threadpool::ThreadPool;
 use tokio::{sync::mpsc, time::interval};
 use tracing::{debug, error, info, warn};
 use types::{
     entities::QueryablePlaylist,
     errors::Result,
+    settings::general::ScanSymlinkPolicy,
     tracks::MediaContent,
 };
 
 use crate::{
+    exclude::ScanExcludes,
     file_cache::{FileCache, FileMetadata},
-    utils::{get_files_recursively, scan_file},
+    formats::ScanExtensions,
+    filename_tags::{fill_missing_tags, FilenamePatterns},
+    progress::{ProgressPhase, ProgressTracker, ScanProgress},
+    reachability::is_root_reachable,
+    schedule::ScanSchedule,
+    utils::{get_files_with_options, scan_cover, scan_file_metadata},
+    walk::WalkPolicy,
 };
 
+/// 定时扫描检查计划的周期
+const SCHEDULE_TICK: Duration = Duration::from_secs(60);
+/// 记录上次定时扫描时间的文件（位于缩略图目录）
+const SCHEDULE_STATE_FILE: &str = "last_scheduled_scan";
+/// 应用自身写入文件（如编辑标签）后，忽略该文件变化事件的时长
+const SELF_WRITE_WINDOW: Duration = Duration::from_secs(10);
+
 /// 扫描事件类型
 #[derive(Debug, Clone)]
 pub enum ScanEvent {
@@ -34,14 +50,143 @@ pub enum ScanEvent {
     ScheduledScan,
     /// 手动触发扫描
     ManualScan(Vec<PathBuf>),
+    /// 从检查点恢复被中断的扫描
+    Resume(ScanCheckpoint),
+    /// 外部触发的扫描任务，检查点的 `job_id` 即取消令牌
+    Start(ScanCheckpoint),
+}
+
+/// 扫描任务类型（用于检查点恢复）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScanJob {
+    /// 全量扫描所有配置路径
+    Full,
+    /// 手动扫描指定路径
+    Manual(Vec<PathBuf>),
+}
+
+/// 扫描阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScanPhase {
+    /// 遍历目录、比对缓存
+    Walking,
+    /// 解析元数据
+    Scanning,
+    /// 已完成
+    Done,
+    /// 扫描出错，不再恢复
+    Failed,
+    /// 被取消，不再恢复
+    Cancelled,
+}
+
+/// 扫描检查点：文件按路径排序处理，`last_path` 之前（含）的文件均已写出结果。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanCheckpoint {
+    pub job_id: String,
+    pub job: ScanJob,
+    pub phase: ScanPhase,
+    pub processed: usize,
+    pub total: usize,
+    pub last_path: Option<PathBuf>,
+}
+
+impl ScanCheckpoint {
+    pub fn new(job: ScanJob) -> Self {
+        Self {
+            job_id: uuid::Uuid::new_v4().to_string(),
+            job,
+            phase: ScanPhase::Walking,
+            processed: 0,
+            total: 0,
+            last_path: None,
+        }
+    }
+}
+
+/// 每处理多少个文件写出一次部分结果（一批入库）和检查点
+const CHECKPOINT_BATCH: usize = 500;
+
+/// 持续有事件时，一批最多等待多少个去抖窗口后强制处理
+const FS_BATCH_MAX_WINDOWS: u32 = 10;
+
+/// 单个路径在去抖窗口内的最终变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FsChange {
+    Upsert,
+    Deleted,
+}
+
+/// 去抖窗口内收集的文件系统事件，按路径去重，后到的事件覆盖先到的
+#[derive(Debug, Default)]
+struct FsBatch {
+    changes: HashMap<PathBuf, FsChange>,
+    started: Option<Instant>,
+}
+
+impl FsBatch {
+    fn push(&mut self, path: PathBuf, change: FsChange) {
+        self.started.get_or_insert_with(Instant::now);
+        self.changes.insert(path, change);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// 是否已超过最长等待时间，避免持续写入时一直不处理
+    fn expired(&self, debounce: Duration) -> bool {
+        self.started
+            .map(|started| started.elapsed() >= debounce * FS_BATCH_MAX_WINDOWS)
+            .unwrap_or(false)
+    }
+
+    /// 取出本批变化：(新增/修改的文件, 删除的文件)，均按路径排序
+    fn take(&mut self) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        self.started = None;
+        let mut upserts = Vec::new();
+        let mut deletes = Vec::new();
+        for (path, change) in self.changes.drain() {
+            match change {
+                FsChange::Upsert => upserts.push(path),
+                FsChange::Deleted => deletes.push(path),
+            }
+        }
+        upserts.sort();
+        deletes.sort();
+        (upserts, deletes)
+    }
+}
+
+/// 文件是否在 `SELF_WRITE_WINDOW` 内由应用自身写入
+fn is_self_write(self_writes: &RwLock<HashMap<PathBuf, Instant>>, path: &Path) -> bool {
+    let self_writes = self_writes.read().unwrap();
+    if self_writes.is_empty() {
+        return false;
+    }
+    let path = dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    self_writes
+        .get(&path)
+        .is_some_and(|at| at.elapsed() < SELF_WRITE_WINDOW)
 }
 
 /// 扫描结果
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ScanResult {
     pub tracks: Vec<MediaContent>,
     pub playlists: Vec<QueryablePlaylist>,
     pub deleted_files: Vec<PathBuf>,
+    /// 本批结果对应的扫描进度，消费者入库后据此记录检查点
+    pub checkpoint: Option<ScanCheckpoint>,
+    /// 本次全量扫描中可达的根目录
+    pub available_roots: Vec<PathBuf>,
+    /// 不可达的根目录（如离线的网络共享），其下曲目保留而不视为删除
+    pub unavailable_roots: Vec<PathBuf>,
+    /// 扫描任务的阶段进度
+    pub progress: Option<ScanProgress>,
 }
 
 /// 自动扫描器配置
@@ -49,39 +194,54 @@ pub struct ScanResult {
 pub struct AutoScannerConfig {
     /// 要扫描的路径列表
     pub scan_paths: Vec<PathBuf>,
-    /// 排除的路径列表
-    pub exclude_paths: Vec<PathBuf>,
+    /// 排除的路径与通配符模式
+    pub excludes: ScanExcludes,
     /// 扫描间隔（秒）
     pub scan_interval: u64,
+    /// 定时扫描的时间窗口与补扫策略
+    pub schedule: ScanSchedule,
     /// 是否启用文件系统监控
     pub enable_fs_watch: bool,
     /// 是否启用定时扫描
     pub enable_scheduled_scan: bool,
     /// 扫描线程数
     pub scan_threads: usize,
-    /// 缩略图目录
+    /// 缩略图目录（扫描缓存与计划状态）
     pub thumbnail_dir: PathBuf,
+    /// 封面目录
+    pub artwork_dir: PathBuf,
     /// 艺术家分隔符
     pub artist_splitter: String,
     /// 最小扫描时长过滤 ("sec30" | "min2" | "all")
     pub scan_min_duration: String,
-    /// 扫描格式过滤 ("common" | "all")
-    pub scan_formats: String,
+    /// 扫描的文件扩展名
+    pub scan_extensions: ScanExtensions,
+    /// 文件系统事件去抖窗口（毫秒），窗口内的事件合并为一批处理
+    pub fs_debounce_ms: u64,
+    /// 遍历深度与符号链接策略，可按扫描目录覆盖
+    pub walk: WalkPolicy,
+    /// 标签缺失时按文件名模式推断
+    pub filename_patterns: FilenamePatterns,
 }
 
 impl Default for AutoScannerConfig {
     fn default() -> Self {
         Self {
             scan_paths: Vec::new(),
-            exclude_paths: Vec::new(),
+            excludes: ScanExcludes::default(),
             scan_interval: 3600, // 1 hour
+            schedule: ScanSchedule::default(),
             enable_fs_watch: true,
             enable_scheduled_scan: true,
             scan_threads: num_cpus::get(),
             thumbnail_dir: PathBuf::from("thumbnails"),
+            artwork_dir: PathBuf::from("artwork"),
             artist_splitter: ";".to_string(),
             scan_min_duration: "sec30".to_string(),
-            scan_formats: "common".to_string(),
+            scan_extensions: ScanExtensions::default(),
+            fs_debounce_ms: 2000,
+            walk: WalkPolicy::default(),
+            filename_patterns: FilenamePatterns::default(),
         }
     }
 }
@@ -101,6 +261,8 @@ pub struct AutoScanner {
     state: Arc<RwLock<ScannerState>>,
     file_cache: Arc<FileCache>,
     is_running: Arc<AtomicBool>,
+    /// 事件循环线程存活标记，线程退出（包括 panic）时清除
+    loop_alive: Arc<AtomicBool>,
     
     // 事件通道
     event_tx: mpsc::UnboundedSender<ScanEvent>,
@@ -110,7 +272,91 @@ pub struct AutoScanner {
     result_tx: Option<crossbeam_channel::Sender<ScanResult>>,
     
     // 文件系统监控器
-    _watcher: Option<RecommendedWatcher>,
+    _watchers: Vec<RecommendedWatcher>,
+    /// 应用自身写入的文件及写入时间，窗口内的变化事件不触发重扫
+    self_writes: Arc<RwLock<HashMap<PathBuf, Instant>>>,
+    /// 当前扫描任务的进度与取消请求
+    progress: Arc<ProgressTracker>,
+}
+
+/// 单次扫描的结果与检查点输出。检查点随结果一起发送，保证消费者先入库再记录进度。
+struct ScanSink {
+    result_tx: Option<crossbeam_channel::Sender<ScanResult>>,
+    /// 最近一次报告的检查点，扫描结束时随最终结果发送
+    last: std::sync::Mutex<Option<ScanCheckpoint>>,
+    progress: Arc<ProgressTracker>,
+}
+
+impl ScanSink {
+    fn send(&self, result: ScanResult) {
+        self.progress.add_total(ProgressPhase::DbWrite, result.tracks.len());
+        self.deliver(result);
+    }
+
+    fn deliver(&self, result: ScanResult) {
+        if let Some(checkpoint) = &result.checkpoint {
+            *self.last.lock().unwrap() = Some(checkpoint.clone());
+        }
+        if let Some(tx) = &self.result_tx {
+            if let Err(e) = tx.send(result) {
+                error!("Failed to send scan result: {}", e);
+            }
+        }
+    }
+
+    fn checkpoint(&self, checkpoint: &ScanCheckpoint) {
+        self.send(ScanResult {
+            checkpoint: Some(checkpoint.clone()),
+            ..Default::default()
+        });
+    }
+
+    /// 开始报告任务进度
+    fn begin(&self, checkpoint: &ScanCheckpoint) {
+        let progress = self.progress.begin(&checkpoint.job_id);
+        self.report(Some(progress));
+    }
+
+    fn advance(&self, phase: ProgressPhase, file: &Path) {
+        self.report(self.progress.advance(phase, file));
+    }
+
+    fn report(&self, progress: Option<ScanProgress>) {
+        if progress.is_some() {
+            self.send(ScanResult {
+                progress,
+                ..Default::default()
+            });
+        }
+    }
+
+    /// 当前任务是否已被请求取消
+    fn cancelled(&self) -> bool {
+        self.last
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|checkpoint| self.progress.is_cancelled(&checkpoint.job_id))
+    }
+
+    /// 发送最终结果，附带 Done/Failed/Cancelled 检查点
+    fn finish(&self, mut result: ScanResult, mut phase: ScanPhase) {
+        if phase == ScanPhase::Done && self.cancelled() {
+            phase = ScanPhase::Cancelled;
+        }
+        // 入库总数需先于结束状态计入
+        self.progress.add_total(ProgressPhase::DbWrite, result.tracks.len());
+        result.progress = self.progress.finish(phase == ScanPhase::Cancelled);
+        let last = self.last.lock().unwrap().take();
+        result.checkpoint = last.map(|mut checkpoint| {
+            if phase == ScanPhase::Done {
+                checkpoint.processed = checkpoint.total;
+            }
+            checkpoint.phase = phase;
+            checkpoint
+        });
+        self.deliver(result);
+    }
 }
 
 impl AutoScanner {
@@ -136,10 +382,13 @@ impl AutoScanner {
             state: Arc::new(RwLock::new(ScannerState::Idle)),
             file_cache,
             is_running: Arc::new(AtomicBool::new(false)),
+            loop_alive: Arc::new(AtomicBool::new(false)),
             event_tx,
             event_rx: Arc::new(tokio::sync::Mutex::new(event_rx)),
             result_tx: None,
-            _watcher: None,
+            _watchers: Vec::new(),
+            self_writes: Arc::new(RwLock::new(HashMap::new())),
+            progress: Arc::new(ProgressTracker::default()),
         })
     }
 
@@ -148,6 +397,19 @@ impl AutoScanner {
         self.result_tx = Some(tx);
     }
 
+    /// 从检查点恢复被中断的扫描
+    pub fn resume_scan(&self, checkpoint: ScanCheckpoint) -> Result<()> {
+        self.event_tx
+            .send(ScanEvent::Resume(checkpoint))
+            .map_err(|e| format!("Failed to send scan event: {}", e))?;
+        Ok(())
+    }
+
+    /// 事件循环是否正常：运行中的扫描器其循环线程必须存活，未启动或已停止的视为正常
+    pub fn is_healthy(&self) -> bool {
+        !self.is_running.load(Ordering::Acquire) || self.loop_alive.load(Ordering::Acquire)
+    }
+
     /// 获取当前状态
     pub fn get_state(&self) -> ScannerState {
         self.state.read().unwrap().clone()
@@ -155,9 +417,16 @@ impl AutoScanner {
 
     /// 更新配置
     pub fn update_config(&self, config: AutoScannerConfig) -> Result<()> {
-        let old_roots = {
+        let (old_roots, rules_changed) = {
             let current = self.config.read().unwrap();
-            current.scan_paths.clone()
+            let rules_changed = current.scan_paths != config.scan_paths
+                || current.excludes != config.excludes
+                || current.scan_min_duration != config.scan_min_duration
+                || current.scan_extensions != config.scan_extensions
+                || current.artist_splitter != config.artist_splitter
+                || current.walk != config.walk
+                || current.filename_patterns != config.filename_patterns;
+            (current.scan_paths.clone(), rules_changed)
         };
 
         let new_roots = config.scan_paths.clone();
@@ -184,12 +453,14 @@ impl AutoScanner {
                         tracks: Vec::new(),
                         playlists: Vec::new(),
                         deleted_files: deleted,
+                        ..Default::default()
                     });
                 }
             }
         }
         
-        if self.is_running.load(Ordering::Acquire) {
+        // 仅调整计划等不影响结果的配置时不立即重扫，避免绕过扫描窗口
+        if rules_changed && self.is_running.load(Ordering::Acquire) {
             info!("Configuration updated, restarting scanner");
             let _ = self.event_tx.send(ScanEvent::ScheduledScan);
         }
@@ -249,85 +520,165 @@ impl AutoScanner {
         // }
     }
 
-    pub fn trigger_scan(&self, paths: Option<Vec<PathBuf>>) -> Result<()> {
-        let scan_event = if let Some(paths) = paths {
-            ScanEvent::ManualScan(paths)
+    /// 标记应用自身即将或刚刚写入的文件：`SELF_WRITE_WINDOW` 内的变化事件被忽略，
+    /// 并按当前大小与修改时间更新文件缓存，之后的全量扫描也不会重扫。写入前后各调用一次。
+    pub fn suppress_rescan(&self, paths: &[PathBuf]) {
+        let now = Instant::now();
+        let mut self_writes = self.self_writes.write().unwrap();
+        self_writes.retain(|_, at| now.duration_since(*at) < SELF_WRITE_WINDOW);
+        for path in paths {
+            self_writes.insert(path.clone(), now);
+            if let Ok(metadata) = std::fs::metadata(path) {
+                let file_meta = FileMetadata {
+                    path: path.clone(),
+                    size: metadata.len(),
+                    modified: metadata.modified().unwrap_or(UNIX_EPOCH),
+                };
+                self.file_cache.update_file(path, file_meta);
+            }
+        }
+    }
+
+    /// 触发扫描，返回任务的取消令牌
+    pub fn trigger_scan(&self, paths: Option<Vec<PathBuf>>) -> Result<String> {
+        let job = if let Some(paths) = paths {
+            ScanJob::Manual(paths)
         } else {
-            ScanEvent::ScheduledScan
+            ScanJob::Full
         };
+        let checkpoint = ScanCheckpoint::new(job);
+        let token = checkpoint.job_id.clone();
 
         self.event_tx
-            .send(scan_event)
+            .send(ScanEvent::Start(checkpoint))
             .map_err(|e| format!("Failed to send scan event: {}", e))?;
         
-        Ok(())
+        Ok(token)
+    }
+
+    /// 取消令牌 `token` 对应的扫描，为空时取消正在进行的扫描。已写出的结果保留，
+    /// 任务不再恢复。返回是否有扫描可取消
+    pub fn cancel_scan(&self, token: Option<&str>) -> bool {
+        self.progress.cancel(token)
     }
 
+    /// 最近一次扫描任务的进度
+    pub fn progress(&self) -> Option<ScanProgress> {
+        self.progress.snapshot()
+    }
+
+    /// 消费者写入了任务 `job_id` 的 `count` 首曲目，返回更新后的进度
+    pub fn record_written(&self, job_id: &str, count: usize) -> Option<ScanProgress> {
+        self.progress.record_written(job_id, count)
+    }
+
+    /// 为每个扫描目录创建监控器：按该目录的规则决定是否跟随符号链接、是否递归，
+    /// 超出深度限制的事件被丢弃
     async fn start_file_watcher(&mut self) -> Result<()> {
-        let event_tx = self.event_tx.clone();
-        let scan_paths = self.config.read().unwrap().scan_paths.clone();
-        
-        let mut watcher = RecommendedWatcher::new(
-            move |res: notify::Result<Event>| {
-                match res {
-                    Ok(event) => {
-                        match event.kind {
-                            EventKind::Create(_) => {
-                                for path in event.paths {
-                                    if Self::is_music_file(&path) {
-                                        let _ = event_tx.send(ScanEvent::FileAdded(path));
+        let (scan_paths, walk) = {
+            let config = self.config.read().unwrap();
+            (config.scan_paths.clone(), config.walk.clone())
+        };
+
+        let mut watchers = Vec::new();
+        for path in scan_paths.iter().filter(|p| p.exists()) {
+            let options = walk.options_for(path);
+            let event_tx = self.event_tx.clone();
+            let config = self.config.clone();
+            let roots = scan_paths.clone();
+            let policy = walk.clone();
+            let self_writes = self.self_writes.clone();
+
+            let mut watcher = RecommendedWatcher::new(
+                move |res: notify::Result<Event>| {
+                    match res {
+                        Ok(event) => {
+                            let paths = event.paths.into_iter().filter(|p| policy.allows(&roots, p));
+                            let is_music_file = |path: &Path| {
+                                config.read().unwrap().scan_extensions.matches(path) && !is_self_write(&self_writes, path)
+                            };
+                            match event.kind {
+                                EventKind::Create(_) => {
+                                    for path in paths {
+                                        if is_music_file(&path) {
+                                            let _ = event_tx.send(ScanEvent::FileAdded(path));
+                                        }
                                     }
                                 }
-                            }
-                            EventKind::Modify(_) => {
-                                for path in event.paths {
-                                    if Self::is_music_file(&path) {
-                                        let _ = event_tx.send(ScanEvent::FileModified(path));
+                                EventKind::Modify(_) => {
+                                    for path in paths {
+                                        if is_music_file(&path) {
+                                            let _ = event_tx.send(ScanEvent::FileModified(path));
+                                        }
                                     }
                                 }
-                            }
-                            EventKind::Remove(_) => {
-                                for path in event.paths {
-                                    let _ = event_tx.send(ScanEvent::FileDeleted(path));
+                                EventKind::Remove(_) => {
+                                    for path in paths {
+                                        let _ = event_tx.send(ScanEvent::FileDeleted(path));
+                                    }
                                 }
+                                _ => {}
                             }
-                            _ => {}
+                        }
+                        Err(e) => {
+                            error!("File watcher error: {:?}", e);
                         }
                     }
-                    Err(e) => {
-                        error!("File watcher error: {:?}", e);
-                    }
-                }
-            },
-            Config::default(),
-        ).map_err(|e| format!("Failed to create file watcher: {}", e))?;
-
-        for path in scan_paths {
-            if path.exists() {
-                watcher
-                    .watch(&path, RecursiveMode::Recursive)
-                    .map_err(|e| format!("Failed to watch path {:?}: {}", path, e))?;
-                info!("Watching path: {:?}", path);
-            }
+                },
+                Config::default().with_follow_symlinks(options.symlinks != ScanSymlinkPolicy::Never),
+            ).map_err(|e| format!("Failed to create file watcher: {}", e))?;
+
+            let mode = if options.max_depth == Some(0) {
+                RecursiveMode::NonRecursive
+            } else {
+                RecursiveMode::Recursive
+            };
+            watcher
+                .watch(path, mode)
+                .map_err(|e| format!("Failed to watch path {:?}: {}", path, e))?;
+            info!("Watching path: {:?}", path);
+            watchers.push(watcher);
         }
 
-        self._watcher = Some(watcher);
+        self._watchers = watchers;
         Ok(())
     }
 
+    /// 按计划触发定时扫描。计划每 `SCHEDULE_TICK` 按墙上时间检查一次，配置变更即时生效，
+    /// 机器休眠错过的窗口由补扫处理；手动扫描不受计划限制。
     async fn start_scheduled_scan(&self) {
         let event_tx = self.event_tx.clone();
         let is_running = self.is_running.clone();
-        let scan_interval = self.config.read().unwrap().scan_interval;
+        let config = self.config.clone();
+        let state_file = config.read().unwrap().thumbnail_dir.join(SCHEDULE_STATE_FILE);
 
         tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(scan_interval));
-            
+            let mut last_scan = std::fs::read_to_string(&state_file)
+                .ok()
+                .and_then(|s| chrono::NaiveDateTime::parse_from_str(s.trim(), "%Y-%m-%dT%H:%M:%S").ok());
+            let mut tick = interval(SCHEDULE_TICK);
+
             while is_running.load(Ordering::Acquire) {
-                interval.tick().await;
-                
-                if is_running.load(Ordering::Acquire) {
-                    let _ = event_tx.send(ScanEvent::ScheduledScan);
+                tick.tick().await;
+                if !is_running.load(Ordering::Acquire) {
+                    break;
+                }
+
+                let (schedule, scan_interval) = {
+                    let config = config.read().unwrap();
+                    (config.schedule.clone(), config.scan_interval)
+                };
+                let now = chrono::Local::now().naive_local();
+                let interval = chrono::Duration::seconds(scan_interval.max(1) as i64);
+                if !schedule.is_due(now, last_scan, interval) {
+                    continue;
+                }
+
+                debug!("Scheduled scan due at {}", now);
+                let _ = event_tx.send(ScanEvent::ScheduledScan);
+                last_scan = Some(now);
+                if let Err(e) = std::fs::write(&state_file, now.format("%Y-%m-%dT%H:%M:%S").to_string()) {
+                    warn!("Failed to record scheduled scan time: {}", e);
                 }
             }
         });
@@ -340,55 +691,170 @@ impl AutoScanner {
         let file_cache = self.file_cache.clone();
         let is_running = self.is_running.clone();
         let result_tx = self.result_tx.clone();
+        let progress = self.progress.clone();
+        self.loop_alive.store(true, Ordering::Release);
+        let alive = AliveGuard(self.loop_alive.clone());
 
         std::thread::spawn(move || {
+            let _alive = alive;
             let rt = tokio::runtime::Runtime::new().unwrap();
             rt.block_on(async {
                 let mut rx = event_rx.lock().await;
+                let mut pending = FsBatch::default();
                 
                 while is_running.load(Ordering::Acquire) {
-                    if let Some(event) = rx.recv().await {
-                        debug!("Processing scan event: {:?}", event);
-                        *state.write().unwrap() = ScannerState::Scanning;
-                        
-                        let result = match event {
-                            ScanEvent::FileAdded(path) => {
-                                Self::handle_file_added(&config, &file_cache, path).await
-                            }
-                            ScanEvent::FileModified(path) => {
-                                Self::handle_file_modified(&config, &file_cache, path).await
-                            }
-                            ScanEvent::FileDeleted(path) => {
-                                Self::handle_file_deleted(&file_cache, path).await
-                            }
-                            ScanEvent::ScheduledScan => {
-                                Self::handle_full_scan(&config, &file_cache).await
-                            }
-                            ScanEvent::ManualScan(paths) => {
-                                Self::handle_manual_scan(&config, &file_cache, paths).await
+                    let debounce = Duration::from_millis(config.read().unwrap().fs_debounce_ms);
+
+                    // 有待处理的文件系统事件时，等待去抖窗口内的后续事件
+                    let event = if pending.is_empty() {
+                        rx.recv().await
+                    } else {
+                        match tokio::time::timeout(debounce, rx.recv()).await {
+                            Ok(event) => event,
+                            Err(_) => {
+                                Self::flush_fs_batch(&config, &file_cache, &state, &result_tx, &mut pending).await;
+                                continue;
                             }
-                        };
+                        }
+                    };
 
-                        match result {
-                            Ok(scan_result) => {
-                                if let Some(tx) = &result_tx {
-                                    if let Err(e) = tx.send(scan_result) {
-                                        error!("Failed to send scan result: {}", e);
-                                    }
-                                }
+                    let Some(event) = event else {
+                        // 通道关闭前处理剩余事件
+                        Self::flush_fs_batch(&config, &file_cache, &state, &result_tx, &mut pending).await;
+                        break;
+                    };
+
+                    debug!("Processing scan event: {:?}", event);
+                    let is_fs_event = matches!(
+                        event,
+                        ScanEvent::FileAdded(_) | ScanEvent::FileModified(_) | ScanEvent::FileDeleted(_)
+                    );
+                    if !is_fs_event {
+                        // 全量/手动扫描前先处理已收集的文件事件，保证顺序
+                        Self::flush_fs_batch(&config, &file_cache, &state, &result_tx, &mut pending).await;
+                        *state.write().unwrap() = ScannerState::Scanning;
+                    }
+
+                    let sink = ScanSink {
+                        result_tx: result_tx.clone(),
+                        last: std::sync::Mutex::new(None),
+                        progress: progress.clone(),
+                    };
+                    let result = match event {
+                        ScanEvent::FileAdded(path) | ScanEvent::FileModified(path) => {
+                            pending.push(path, FsChange::Upsert);
+                            if pending.expired(debounce) {
+                                Self::flush_fs_batch(&config, &file_cache, &state, &result_tx, &mut pending).await;
                             }
-                            Err(e) => {
-                                error!("Scan error: {}", e);
+                            continue;
+                        }
+                        ScanEvent::FileDeleted(path) => {
+                            pending.push(path, FsChange::Deleted);
+                            if pending.expired(debounce) {
+                                Self::flush_fs_batch(&config, &file_cache, &state, &result_tx, &mut pending).await;
                             }
+                            continue;
+                        }
+                        ScanEvent::ScheduledScan => {
+                            let checkpoint = ScanCheckpoint::new(ScanJob::Full);
+                            Self::handle_full_scan(&config, &file_cache, &sink, checkpoint).await
+                        }
+                        ScanEvent::ManualScan(paths) => {
+                            let checkpoint = ScanCheckpoint::new(ScanJob::Manual(paths.clone()));
+                            Self::handle_manual_scan(&config, &file_cache, paths, &sink, checkpoint).await
+                        }
+                        ScanEvent::Resume(checkpoint) => {
+                            info!("Resuming scan {} after {:?}", checkpoint.job_id, checkpoint.last_path);
+                            Self::handle_job(&config, &file_cache, &sink, checkpoint).await
+                        }
+                        ScanEvent::Start(checkpoint) => {
+                            Self::handle_job(&config, &file_cache, &sink, checkpoint).await
+                        }
+                    };
+
+                    match result {
+                        Ok(scan_result) => {
+                            sink.finish(scan_result, ScanPhase::Done);
+                        }
+                        Err(e) => {
+                            error!("Scan error: {}", e);
+                            sink.finish(ScanResult::default(), ScanPhase::Failed);
                         }
-                        
-                        *state.write().unwrap() = ScannerState::Watching;
                     }
+                    
+                    *state.write().unwrap() = ScannerState::Watching;
                 }
             });
         });
     }
 
+    /// 处理去抖窗口内收集的文件事件，合并为一个扫描结果发送
+    async fn flush_fs_batch(
+        config: &Arc<RwLock<AutoScannerConfig>>,
+        file_cache: &Arc<FileCache>,
+        state: &Arc<RwLock<ScannerState>>,
+        result_tx: &Option<crossbeam_channel::Sender<ScanResult>>,
+        pending: &mut FsBatch,
+    ) {
+        if pending.is_empty() {
+            return;
+        }
+        let (upserts, deletes) = pending.take();
+        info!(
+            "Processing {} changed and {} deleted files from file system events",
+            upserts.len(),
+            deletes.len()
+        );
+        *state.write().unwrap() = ScannerState::Scanning;
+
+        let mut batch = ScanResult::default();
+        for path in upserts {
+            // 窗口内先创建后删除的临时文件等，已不存在则跳过
+            if !path.exists() {
+                continue;
+            }
+            match Self::handle_file_modified(config, file_cache, path.clone()).await {
+                Ok(mut result) => batch.tracks.append(&mut result.tracks),
+                Err(e) => warn!("Failed to scan file {:?}: {}", path, e),
+            }
+        }
+        // 共享掉线时监控可能报告整批删除，根目录不可达时忽略
+        let mut root_reachable: HashMap<PathBuf, bool> = HashMap::new();
+        let scan_paths = config.read().unwrap().scan_paths.clone();
+        for path in deletes {
+            if let Some(root) = scan_paths.iter().find(|root| path.starts_with(root)) {
+                let reachable = *root_reachable.entry(root.clone()).or_insert_with(|| {
+                    let has_cached = file_cache.get_all_files().iter().any(|f| f.path.starts_with(root));
+                    is_root_reachable(root, has_cached)
+                });
+                if !reachable {
+                    debug!("Ignoring deletion of {:?} under unreachable root {:?}", path, root);
+                    continue;
+                }
+            }
+            if let Ok(mut result) = Self::handle_file_deleted(file_cache, path).await {
+                batch.deleted_files.append(&mut result.deleted_files);
+            }
+        }
+
+        for (root, reachable) in root_reachable {
+            if reachable {
+                batch.available_roots.push(root);
+            } else {
+                batch.unavailable_roots.push(root);
+            }
+        }
+
+        if !batch.tracks.is_empty() || !batch.deleted_files.is_empty() || !batch.unavailable_roots.is_empty() {
+            if let Some(tx) = result_tx {
+                if let Err(e) = tx.send(batch) {
+                    error!("Failed to send scan result: {}", e);
+                }
+            }
+        }
+        *state.write().unwrap() = ScannerState::Watching;
+    }
+
     async fn handle_file_added(
         config: &Arc<RwLock<AutoScannerConfig>>,
         file_cache: &Arc<FileCache>,
@@ -401,16 +867,20 @@ impl AutoScanner {
                 tracks: Vec::new(),
                 playlists: Vec::new(),
                 deleted_files: Vec::new(),
+                ..Default::default()
             });
         }
 
         let config_guard = config.read().unwrap();
         let mut tracks = Self::scan_single_file(
             &path,
-            &config_guard.thumbnail_dir,
             &config_guard.artist_splitter,
-        ).await?;
+            &config_guard.filename_patterns,
+        )?;
         Self::filter_tracks_by_min_duration(&mut tracks, &config_guard.scan_min_duration);
+        for track in tracks.iter_mut() {
+            scan_cover(track, &path, &config_guard.artwork_dir);
+        }
 
         if let Ok(metadata) = std::fs::metadata(&path) {
             let file_meta = FileMetadata {
@@ -425,6 +895,7 @@ impl AutoScanner {
             tracks,
             playlists: Vec::new(),
             deleted_files: Vec::new(),
+            ..Default::default()
         })
     }
 
@@ -467,29 +938,60 @@ impl AutoScanner {
             tracks: Vec::new(),
             playlists: Vec::new(),
             deleted_files: vec![path],
+            ..Default::default()
         })
     }
 
+    async fn handle_job(
+        config: &Arc<RwLock<AutoScannerConfig>>,
+        file_cache: &Arc<FileCache>,
+        sink: &ScanSink,
+        checkpoint: ScanCheckpoint,
+    ) -> Result<ScanResult> {
+        match checkpoint.job.clone() {
+            ScanJob::Full => Self::handle_full_scan(config, file_cache, sink, checkpoint).await,
+            ScanJob::Manual(paths) => {
+                Self::handle_manual_scan(config, file_cache, paths, sink, checkpoint).await
+            }
+        }
+    }
+
     async fn handle_full_scan(
         config: &Arc<RwLock<AutoScannerConfig>>,
         file_cache: &Arc<FileCache>,
+        sink: &ScanSink,
+        checkpoint: ScanCheckpoint,
     ) -> Result<ScanResult> {
         info!("Handling full scan");
-        
+        sink.checkpoint(&checkpoint);
+        sink.begin(&checkpoint);
+
         let config_guard = config.read().unwrap();
-        let mut all_tracks = Vec::new();
-        let all_playlists = Vec::new();
+        let mut candidates = Vec::new();
         let mut deleted_files = Vec::new();
+        let mut available_roots = Vec::new();
+        let mut unavailable_roots = Vec::new();
+        sink.progress.add_total(ProgressPhase::Discovery, config_guard.scan_paths.len());
 
         for scan_path in &config_guard.scan_paths {
-            if !scan_path.exists() {
+            if sink.cancelled() {
+                info!("Scan {} cancelled", checkpoint.job_id);
+                break;
+            }
+            sink.advance(ProgressPhase::Discovery, scan_path);
+            let cached_files: HashSet<PathBuf> = file_cache.get_all_files().into_iter().map(|f| f.path).collect();
+
+            // 离线的网络共享或未挂载的挂载点：保留缓存与曲目，等待恢复
+            if !is_root_reachable(scan_path, cached_files.iter().any(|p| p.starts_with(scan_path))) {
+                warn!("Scan root {:?} is unreachable, keeping its tracks", scan_path);
+                unavailable_roots.push(scan_path.clone());
                 continue;
             }
+            available_roots.push(scan_path.clone());
 
-            let file_list = get_files_recursively(scan_path.clone())?;
+            let file_list = get_files_with_options(scan_path.clone(), config_guard.walk.options_for(scan_path))?;
             
             let current_files: HashSet<PathBuf> = file_list.file_list.iter().map(|(p, _)| p.clone()).collect();
-            let cached_files: HashSet<PathBuf> = file_cache.get_all_files().into_iter().map(|f| f.path).collect();
             
             for cached_path in &cached_files {
                 if cached_path.starts_with(scan_path) && !current_files.contains(cached_path) {
@@ -512,28 +1014,7 @@ impl AutoScanner {
                     };
 
                     if needs_scan {
-                        match Self::scan_single_file(
-                            &file_path,
-                            &config_guard.thumbnail_dir,
-                            &config_guard.artist_splitter,
-                        ).await {
-                            Ok(mut tracks) => {
-                                Self::filter_tracks_by_min_duration(&mut tracks, &config_guard.scan_min_duration);
-                                all_tracks.append(&mut tracks);
-                                
-                                if let Ok(metadata) = std::fs::metadata(&file_path) {
-                                    let file_meta = FileMetadata {
-                                        path: file_path.clone(),
-                                        size: size as u64,
-                                        modified: metadata.modified().unwrap_or(UNIX_EPOCH),
-                                    };
-                                    file_cache.update_file(&file_path, file_meta);
-                                }
-                            }
-                            Err(e) => {
-                                warn!("Failed to scan file {:?}: {}", file_path, e);
-                            }
-                        }
+                        candidates.push(file_path);
                     }
                 }
             }
@@ -544,124 +1025,203 @@ impl AutoScanner {
             // }
         }
 
-        Ok(ScanResult {
-            tracks: all_tracks,
-            playlists: all_playlists,
-            deleted_files,
-        })
+        let mut result = Self::scan_candidates(&config_guard, file_cache, candidates, sink, checkpoint).await;
+        result.deleted_files = deleted_files;
+        result.available_roots = available_roots;
+        result.unavailable_roots = unavailable_roots;
+        Ok(result)
     }
 
     async fn handle_manual_scan(
         config: &Arc<RwLock<AutoScannerConfig>>,
-        _file_cache: &Arc<FileCache>,
+        file_cache: &Arc<FileCache>,
         paths: Vec<PathBuf>,
+        sink: &ScanSink,
+        checkpoint: ScanCheckpoint,
     ) -> Result<ScanResult> {
         info!("Handling manual scan for {} paths", paths.len());
+        sink.checkpoint(&checkpoint);
+        sink.begin(&checkpoint);
         
         let config_guard = config.read().unwrap();
-        let mut all_tracks = Vec::new();
+        let mut candidates = Vec::new();
+        sink.progress.add_total(ProgressPhase::Discovery, paths.len());
         
         for path in paths {
+            if sink.cancelled() {
+                info!("Scan {} cancelled", checkpoint.job_id);
+                break;
+            }
+            sink.advance(ProgressPhase::Discovery, &path);
             if path.is_file() && Self::should_scan_file(&path, &config_guard) {
-                match Self::scan_single_file(
-                    &path,
-                    &config_guard.thumbnail_dir,
-                    &config_guard.artist_splitter,
-                ).await {
-                    Ok(mut tracks) => {
-                        Self::filter_tracks_by_min_duration(&mut tracks, &config_guard.scan_min_duration);
-                        all_tracks.append(&mut tracks);
-                    }
-                    Err(e) => {
-                        warn!("Failed to scan file {:?}: {}", path, e);
-                    }
-                }
+                candidates.push(path);
             } else if path.is_dir() {
-                let file_list = get_files_recursively(path)?;
+                let file_list = get_files_with_options(path.clone(), config_guard.walk.options_for(&path))?;
                 for (file_path, _) in file_list.file_list {
                     if Self::should_scan_file(&file_path, &config_guard) {
-                        match Self::scan_single_file(
-                            &file_path,
-                            &config_guard.thumbnail_dir,
-                            &config_guard.artist_splitter,
-                        ).await {
-                            Ok(mut tracks) => {
-                                Self::filter_tracks_by_min_duration(&mut tracks, &config_guard.scan_min_duration);
-                                all_tracks.append(&mut tracks);
-                            }
-                            Err(e) => {
-                                warn!("Failed to scan file {:?}: {}", file_path, e);
-                            }
-                        }
+                        candidates.push(file_path);
                     }
                 }
             }
         }
         
-        Ok(ScanResult {
-            tracks: all_tracks,
+        Ok(Self::scan_candidates(&config_guard, file_cache, candidates, sink, checkpoint).await)
+    }
+
+    /// 按路径顺序解析候选文件，跳过检查点之前已处理的文件。
+    /// 每 CHECKPOINT_BATCH 个文件由 `scan_threads` 个工作线程并行解析，写出部分结果和检查点，返回剩余结果。
+    async fn scan_candidates(
+        config: &AutoScannerConfig,
+        file_cache: &Arc<FileCache>,
+        mut candidates: Vec<PathBuf>,
+        sink: &ScanSink,
+        mut checkpoint: ScanCheckpoint,
+    ) -> ScanResult {
+        candidates.sort();
+        candidates.dedup();
+        if let Some(last) = checkpoint.last_path.clone() {
+            candidates.retain(|p| p > &last);
+        }
+        checkpoint.phase = ScanPhase::Scanning;
+        checkpoint.total = checkpoint.processed + candidates.len();
+        sink.checkpoint(&checkpoint);
+        sink.progress.add_total(ProgressPhase::Metadata, candidates.len());
+
+        let pool = ThreadPool::new(config.scan_threads.max(1));
+        let job = Arc::new(FileJob {
+            artwork_dir: config.artwork_dir.clone(),
+            artist_splitter: config.artist_splitter.clone(),
+            filename_patterns: config.filename_patterns.clone(),
+            scan_min_duration: config.scan_min_duration.clone(),
+        });
+
+        // 没有结果通道时，全部结果随最终结果返回
+        let mut remaining = Vec::new();
+        for chunk in candidates.chunks(CHECKPOINT_BATCH) {
+            if sink.cancelled() {
+                info!("Scan {} cancelled", checkpoint.job_id);
+                break;
+            }
+            let (tracks, complete) = Self::scan_chunk(&pool, &job, chunk, file_cache, sink, &checkpoint.job_id);
+            // 取消时跳过了部分文件，检查点不前移
+            if complete {
+                checkpoint.processed += chunk.len();
+                checkpoint.last_path = chunk.last().cloned();
+            }
+            if sink.result_tx.is_some() {
+                sink.send(ScanResult {
+                    tracks,
+                    checkpoint: complete.then(|| checkpoint.clone()),
+                    ..Default::default()
+                });
+            } else {
+                remaining.extend(tracks);
+            }
+        }
+
+        ScanResult {
+            tracks: remaining,
             playlists: Vec::new(),
             deleted_files: Vec::new(),
-        })
+            ..Default::default()
+        }
     }
 
-    async fn scan_single_file(
+    /// 在线程池中并行处理一批文件：先解析元数据，再提取封面、生成缩略图。
+    /// 按路径顺序返回曲目，以及这批文件是否都已处理（取消后未开始的文件被跳过）
+    fn scan_chunk(
+        pool: &ThreadPool,
+        job: &Arc<FileJob>,
+        chunk: &[PathBuf],
+        file_cache: &Arc<FileCache>,
+        sink: &ScanSink,
+        job_id: &str,
+    ) -> (Vec<MediaContent>, bool) {
+        let (tx, rx) = std::sync::mpsc::channel();
+        for (index, path) in chunk.iter().enumerate() {
+            let tx = tx.clone();
+            let job = job.clone();
+            let progress = sink.progress.clone();
+            let job_id = job_id.to_string();
+            let path = path.clone();
+            pool.execute(move || {
+                let result = (!progress.is_cancelled(&job_id)).then(|| job.scan_metadata(&path));
+                let _ = tx.send((index, path, result));
+            });
+        }
+        drop(tx);
+
+        let mut processed = 0;
+        let mut parsed = Vec::new();
+        for (index, path, result) in rx {
+            let Some(result) = result else {
+                continue;
+            };
+            match result {
+                Ok(tracks) => {
+                    if let Ok(metadata) = std::fs::metadata(&path) {
+                        let file_meta = FileMetadata {
+                            path: path.clone(),
+                            size: metadata.len(),
+                            modified: metadata.modified().unwrap_or(UNIX_EPOCH),
+                        };
+                        file_cache.update_file(&path, file_meta);
+                    }
+                    parsed.extend(tracks.into_iter().map(|track| (index, path.clone(), track)));
+                }
+                Err(e) => warn!("Failed to scan file {:?}: {}", path, e),
+            }
+            processed += 1;
+            sink.advance(ProgressPhase::Metadata, &path);
+        }
+
+        sink.progress.add_total(ProgressPhase::Thumbnails, parsed.len());
+        let (tx, rx) = std::sync::mpsc::channel();
+        for (index, path, mut track) in parsed {
+            let tx = tx.clone();
+            let job = job.clone();
+            pool.execute(move || {
+                scan_cover(&mut track, &path, &job.artwork_dir);
+                let _ = tx.send((index, path, track));
+            });
+        }
+        drop(tx);
+
+        let mut tracks = Vec::new();
+        for (index, path, track) in rx {
+            sink.advance(ProgressPhase::Thumbnails, &path);
+            tracks.push((index, track));
+        }
+        // 稳定排序，同一文件的多首曲目保持原顺序
+        tracks.sort_by_key(|(index, _)| *index);
+        (tracks.into_iter().map(|(_, track)| track).collect(), processed == chunk.len())
+    }
+
+    /// 解析元数据，封面由 `scan_cover` 另行提取
+    fn scan_single_file(
         path: &Path,
-        thumbnail_dir: &Path,
         artist_splitter: &str,
+        filename_patterns: &FilenamePatterns,
     ) -> Result<Vec<MediaContent>> {
         let size = std::fs::metadata(path)
             .map(|m| m.len() as f64)
             .unwrap_or(0.0);
         
-        let track = scan_file(&path.to_path_buf(), thumbnail_dir, size, false, artist_splitter)?;
-        Ok(vec![track])
-    }
-
-    fn should_scan_file(path: &Path, config: &AutoScannerConfig) -> bool {
-        for exclude_path in &config.exclude_paths {
-            if path.starts_with(exclude_path) {
-                return false;
+        let mut track = scan_file_metadata(&path.to_path_buf(), size, false, artist_splitter)?;
+        if let Some(tags) = filename_patterns.infer(path) {
+            if fill_missing_tags(&mut track, path, tags, artist_splitter) {
+                debug!("Filled missing tags of {:?} from its file name", path);
             }
         }
-
-        Self::is_supported_music_file(path, &config.scan_formats)
+        Ok(vec![track])
     }
 
-    fn is_music_file(path: &Path) -> bool {
-        if let Some(extension) = path.extension() {
-            if let Some(ext_str) = extension.to_str() {
-                let ext = ext_str.to_lowercase();
-                matches!(ext.as_str(), "flac" | "mp3" | "ogg" | "m4a" | "webm" | "wav" | "wv" | "aac" | "opus")
-            } else {
-                false
-            }
-        } else {
-            false
-        }
+    fn should_scan_file(path: &Path, config: &AutoScannerConfig) -> bool {
+        !config.excludes.is_excluded(path) && Self::is_supported_music_file(path, &config.scan_extensions)
     }
 
-    fn is_supported_music_file(path: &Path, scan_formats: &str) -> bool {
-        if let Some(extension) = path.extension() {
-            if let Some(ext_str) = extension.to_str() {
-                let ext = ext_str.to_lowercase();
-                match scan_formats {
-                    "common" => {
-                        matches!(ext.as_str(), "mp3" | "flac" | "m4a" | "ogg")
-                    }
-                    "all" => {
-                        matches!(ext.as_str(), "flac" | "mp3" | "ogg" | "m4a" | "webm" | "wav" | "wv" | "aac" | "opus")
-                    }
-                    _ => {
-                        matches!(ext.as_str(), "mp3" | "flac" | "m4a" | "ogg")
-                    }
-                }
-            } else {
-                false
-            }
-        } else {
-            false
-        }
+    fn is_supported_music_file(path: &Path, extensions: &ScanExtensions) -> bool {
+        extensions.matches(path)
     }
 
     fn filter_tracks_by_min_duration(tracks: &mut Vec<MediaContent>, scan_min_duration: &str) {
@@ -688,6 +1248,31 @@ impl AutoScanner {
     }
 }
 
+/// 工作线程解析文件所需的配置
+struct FileJob {
+    artwork_dir: PathBuf,
+    artist_splitter: String,
+    filename_patterns: FilenamePatterns,
+    scan_min_duration: String,
+}
+
+impl FileJob {
+    fn scan_metadata(&self, path: &Path) -> Result<Vec<MediaContent>> {
+        let mut tracks = AutoScanner::scan_single_file(path, &self.artist_splitter, &self.filename_patterns)?;
+        AutoScanner::filter_tracks_by_min_duration(&mut tracks, &self.scan_min_duration);
+        Ok(tracks)
+    }
+}
+
+/// 线程退出时清除存活标记，panic 展开时同样生效
+struct AliveGuard(Arc<AtomicBool>);
+
+impl Drop for AliveGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
 impl Drop for AutoScanner {
     fn drop(&mut self) {
         if self.is_running.load(Ordering::Acquire) {
@@ -0,0 +1,224 @@
+//! Chapter tags of audiobooks and long mixes: ID3v2 `CHAP` frames (MP3) and
+//! Nero `chpl` boxes (M4A/M4B). QuickTime chapter text tracks are not read.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use types::ui::player_details::TrackChapter;
+
+/// Largest tag or `moov` box read into memory
+const MAX_TAG_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Chapters of the audio file at `path`, ordered by start. Empty when the
+/// file has none or can't be read.
+#[tracing::instrument(level = "debug")]
+pub fn read_chapters(path: &Path) -> Vec<TrackChapter> {
+    let Ok(mut file) = File::open(path) else {
+        return vec![];
+    };
+    let mut magic = [0u8; 8];
+    if file.read_exact(&mut magic).is_err() || file.rewind().is_err() {
+        return vec![];
+    }
+
+    let mut chapters = if &magic[..3] == b"ID3" {
+        read_id3_chapters(&mut file)
+    } else if &magic[4..8] == b"ftyp" {
+        read_mp4_chapters(&mut file)
+    } else {
+        None
+    }
+    .unwrap_or_default();
+
+    chapters.retain(|c| c.start.is_finite() && c.start >= 0.0);
+    chapters.sort_by(|a, b| a.start.total_cmp(&b.start));
+    // A chapter runs until the next one starts unless the tag says otherwise
+    for i in 0..chapters.len() {
+        let next = chapters.get(i + 1).map(|c| c.start);
+        let chapter = &mut chapters[i];
+        match (chapter.end, next) {
+            (Some(end), _) if end > chapter.start => {}
+            (_, next) => chapter.end = next,
+        }
+    }
+    chapters
+}
+
+fn be_u32(bytes: &[u8]) -> Option<u32> {
+    Some(u32::from_be_bytes(bytes.get(..4)?.try_into().ok()?))
+}
+
+fn be_u64(bytes: &[u8]) -> Option<u64> {
+    Some(u64::from_be_bytes(bytes.get(..8)?.try_into().ok()?))
+}
+
+fn syncsafe(bytes: &[u8]) -> Option<u32> {
+    let bytes = bytes.get(..4)?;
+    Some(bytes.iter().fold(0u32, |acc, b| (acc << 7) | (*b as u32 & 0x7f)))
+}
+
+/// ID3v2 text in `encoding` (0 Latin-1, 1 UTF-16 with BOM, 2 UTF-16BE, 3 UTF-8)
+fn decode_text(encoding: u8, bytes: &[u8]) -> String {
+    let text = match encoding {
+        1 | 2 => {
+            let (big_endian, bytes) = match bytes {
+                [0xff, 0xfe, rest @ ..] => (false, rest),
+                [0xfe, 0xff, rest @ ..] => (true, rest),
+                _ => (encoding == 2, bytes),
+            };
+            let units = bytes
+                .chunks_exact(2)
+                .map(|c| if big_endian { u16::from_be_bytes([c[0], c[1]]) } else { u16::from_le_bytes([c[0], c[1]]) })
+                .collect::<Vec<_>>();
+            String::from_utf16_lossy(&units)
+        }
+        3 => String::from_utf8_lossy(bytes).to_string(),
+        _ => bytes.iter().map(|b| *b as char).collect(),
+    };
+    text.trim_end_matches('\0').trim().to_string()
+}
+
+/// Frames of an ID3v2 tag body as (id, body) pairs
+fn id3_frames(mut data: &[u8], version: u8) -> Vec<(&[u8], &[u8])> {
+    let mut frames = vec![];
+    while data.len() >= 10 && data[0] != 0 {
+        let size = match version {
+            4 => syncsafe(&data[4..8]),
+            _ => be_u32(&data[4..8]),
+        };
+        let Some(size) = size.map(|s| s as usize).filter(|s| 10 + s <= data.len()) else {
+            break;
+        };
+        frames.push((&data[..4], &data[10..10 + size]));
+        data = &data[10 + size..];
+    }
+    frames
+}
+
+fn read_id3_chapters(file: &mut File) -> Option<Vec<TrackChapter>> {
+    let mut header = [0u8; 10];
+    file.read_exact(&mut header).ok()?;
+    let version = header[3];
+    if !(3..=4).contains(&version) {
+        return None;
+    }
+    let size = syncsafe(&header[6..10])? as u64;
+    if size > MAX_TAG_SIZE {
+        return None;
+    }
+    let mut tag = vec![0u8; size as usize];
+    file.read_exact(&mut tag).ok()?;
+
+    let mut body = &tag[..];
+    if header[5] & 0x40 != 0 {
+        let skip = match version {
+            4 => syncsafe(body)? as usize,
+            _ => be_u32(body)? as usize + 4,
+        };
+        body = body.get(skip..)?;
+    }
+
+    let chapters = id3_frames(body, version)
+        .into_iter()
+        .filter(|(id, _)| *id == b"CHAP")
+        .filter_map(|(_, frame)| {
+            // Element ID, then start/end times in ms and byte offsets
+            let id_end = frame.iter().position(|b| *b == 0)?;
+            let times = frame.get(id_end + 1..id_end + 17)?;
+            let start = be_u32(times)?;
+            let end = be_u32(&times[4..])?;
+            let title = id3_frames(&frame[id_end + 17..], version)
+                .into_iter()
+                .find(|(id, _)| *id == b"TIT2")
+                .and_then(|(_, text)| {
+                    let (encoding, text) = text.split_first()?;
+                    Some(decode_text(*encoding, text))
+                })
+                .filter(|t| !t.is_empty());
+            Some(TrackChapter {
+                start: start as f64 / 1000.0,
+                end: (end != u32::MAX).then_some(end as f64 / 1000.0),
+                title,
+            })
+        })
+        .collect();
+    Some(chapters)
+}
+
+/// Child boxes of an MP4 box body as (type, body) pairs
+fn mp4_boxes(mut data: &[u8]) -> Vec<(&[u8], &[u8])> {
+    let mut boxes = vec![];
+    while let Some(size) = be_u32(data).filter(|_| data.len() >= 8) {
+        let (size, header) = match size {
+            1 => (be_u64(&data[8..]).unwrap_or(0) as usize, 16),
+            0 => (data.len(), 8),
+            size => (size as usize, 8),
+        };
+        if size < header || size > data.len() {
+            break;
+        }
+        boxes.push((&data[4..8], &data[header..size]));
+        data = &data[size..];
+    }
+    boxes
+}
+
+fn child<'a>(data: &'a [u8], kind: &[u8]) -> Option<&'a [u8]> {
+    mp4_boxes(data).into_iter().find(|(k, _)| *k == kind).map(|(_, body)| body)
+}
+
+fn read_mp4_chapters(file: &mut File) -> Option<Vec<TrackChapter>> {
+    // Find `moov` among the top-level boxes without reading `mdat`
+    let len = file.metadata().ok()?.len();
+    let mut offset = 0u64;
+    let moov = loop {
+        if offset + 8 > len {
+            return None;
+        }
+        file.seek(SeekFrom::Start(offset)).ok()?;
+        let mut header = [0u8; 16];
+        file.read_exact(&mut header[..8]).ok()?;
+        let (size, header_len) = match be_u32(&header)? {
+            1 => {
+                file.read_exact(&mut header[8..]).ok()?;
+                (be_u64(&header[8..])?, 16)
+            }
+            0 => (len - offset, 8),
+            size => (size as u64, 8),
+        };
+        if size < header_len {
+            return None;
+        }
+        if &header[4..8] == b"moov" {
+            if size > MAX_TAG_SIZE {
+                return None;
+            }
+            let mut moov = vec![0u8; (size - header_len) as usize];
+            file.read_exact(&mut moov).ok()?;
+            break moov;
+        }
+        offset += size;
+    };
+
+    // Nero chapters: version, flags, (reserved), count, then per chapter a
+    // start in 100 ns units and a length-prefixed UTF-8 title
+    let chpl = child(child(&moov, b"udta")?, b"chpl")?;
+    let mut data = chpl.get(if *chpl.first()? == 0 { 4 } else { 8 }..)?;
+    let (count, rest) = data.split_first()?;
+    data = rest;
+    let mut chapters = Vec::with_capacity(*count as usize);
+    for _ in 0..*count {
+        let start = be_u64(data)?;
+        let title_len = *data.get(8)? as usize;
+        let title = data.get(9..9 + title_len)?;
+        data = &data[9 + title_len..];
+        let title = String::from_utf8_lossy(title).trim().to_string();
+        chapters.push(TrackChapter {
+            start: start as f64 / 10_000_000.0,
+            end: None,
+            title: (!title.is_empty()).then_some(title),
+        });
+    }
+    Some(chapters)
+}
@@ -0,0 +1,97 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use types::entities::{FormatUsage, ScanEstimate};
+
+/// Extensions picked up as tracks by the scanners
+const TRACK_EXTENSIONS: &[&str] = &["flac", "mp3", "ogg", "m4a", "webm", "wav", "wv", "aac", "opus"];
+const PLAYLIST_EXTENSIONS: &[&str] = &["m3u", "m3u8"];
+
+/// Walk `paths` and count the files a scan would import, by extension.
+///
+/// Only directory metadata is read, so this is cheap enough to run before the
+/// user commits to adding folders. Symlinked directories are not followed to
+/// avoid cycles; unreadable entries are counted in `skipped`.
+#[tracing::instrument(level = "debug", skip(paths))]
+pub fn estimate_scan(paths: &[PathBuf]) -> ScanEstimate {
+    let mut estimate = ScanEstimate::default();
+    let mut formats: HashMap<String, FormatUsage> = HashMap::new();
+    let mut stack: Vec<PathBuf> = paths.to_vec();
+
+    while let Some(path) = stack.pop() {
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            estimate.skipped += 1;
+            continue;
+        };
+
+        if metadata.is_dir() {
+            match fs::read_dir(&path) {
+                Ok(entries) => stack.extend(entries.flatten().map(|e| e.path())),
+                Err(_) => estimate.skipped += 1,
+            }
+            continue;
+        }
+
+        // Resolve file symlinks, a broken link counts as skipped
+        let size = if metadata.file_type().is_symlink() {
+            match fs::metadata(&path) {
+                Ok(m) if m.is_file() => m.len(),
+                Ok(_) => continue,
+                Err(_) => {
+                    estimate.skipped += 1;
+                    continue;
+                }
+            }
+        } else {
+            metadata.len()
+        };
+
+        let Some(extension) = extension_of(&path) else { continue };
+        if TRACK_EXTENSIONS.contains(&extension.as_str()) {
+            estimate.files += 1;
+            estimate.bytes += size;
+            let usage = formats.entry(extension.clone()).or_insert_with(|| FormatUsage {
+                extension,
+                ..Default::default()
+            });
+            usage.files += 1;
+            usage.bytes += size;
+        } else if PLAYLIST_EXTENSIONS.contains(&extension.as_str()) {
+            estimate.playlists += 1;
+        }
+    }
+
+    estimate.formats = formats.into_values().collect();
+    estimate
+        .formats
+        .sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.extension.cmp(&b.extension)));
+    estimate
+}
+
+/// Total size of the regular files under `dir`, 0 if it does not exist.
+/// Symlinks are not followed.
+#[tracing::instrument(level = "debug", skip(dir))]
+pub fn dir_size(dir: &Path) -> u64 {
+    let mut total = 0;
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(path) = stack.pop() {
+        let Ok(metadata) = fs::symlink_metadata(&path) else { continue };
+        if metadata.is_dir() {
+            if let Ok(entries) = fs::read_dir(&path) {
+                stack.extend(entries.flatten().map(|e| e.path()));
+            }
+        } else if metadata.is_file() {
+            total += metadata.len();
+        }
+    }
+    total
+}
+
+fn extension_of(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+}
@@ -0,0 +1,56 @@
+use std::path::{Path, PathBuf};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use tracing::warn;
+
+/// 扫描排除规则：排除的目录，以及通配符模式（如 `**/node_modules/**`、`*.part`、
+/// `**/.*/**`）。模式同时匹配完整路径与文件名，`*` 可跨越目录分隔符。
+#[derive(Debug, Clone, Default)]
+pub struct ScanExcludes {
+    paths: Vec<PathBuf>,
+    patterns: Vec<String>,
+    globs: GlobSet,
+}
+
+impl PartialEq for ScanExcludes {
+    fn eq(&self, other: &Self) -> bool {
+        self.paths == other.paths && self.patterns == other.patterns
+    }
+}
+
+impl ScanExcludes {
+    /// 无效的模式记录警告后忽略
+    pub fn new(paths: Vec<PathBuf>, patterns: &[String]) -> Self {
+        let mut builder = GlobSetBuilder::new();
+        let mut valid = Vec::new();
+        for pattern in patterns.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            match Glob::new(pattern) {
+                Ok(glob) => {
+                    builder.add(glob);
+                    valid.push(pattern.to_string());
+                }
+                Err(e) => warn!("Ignoring invalid scan exclude pattern {:?}: {}", pattern, e),
+            }
+        }
+        let globs = builder.build().unwrap_or_else(|e| {
+            warn!("Failed to build scan exclude patterns: {}", e);
+            valid.clear();
+            GlobSet::empty()
+        });
+        Self {
+            paths,
+            patterns: valid,
+            globs,
+        }
+    }
+
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        if self.paths.iter().any(|p| path.starts_with(p)) {
+            return true;
+        }
+        if self.globs.is_empty() {
+            return false;
+        }
+        self.globs.is_match(path) || path.file_name().is_some_and(|name| self.globs.is_match(name))
+    }
+}
@@ -0,0 +1,255 @@
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use tracing::{debug, warn};
+use types::{
+    entities::{QueryableAlbum, QueryableArtist},
+    settings::general::ScanPatternOverride,
+    tracks::MediaContent,
+};
+use uuid::Uuid;
+
+/// 未配置时依次尝试的文件名模式
+pub const DEFAULT_FILENAME_PATTERNS: &[&str] = &[
+    "%track% - %artist% - %title%",
+    "%track%. %artist% - %title%",
+    "%track% - %title%",
+    "%track%. %title%",
+    "%artist% - %title%",
+];
+
+/// 从文件名推断出的标签
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InferredTags {
+    pub track_no: Option<u32>,
+    pub artist: Option<String>,
+    pub title: Option<String>,
+    pub album: Option<String>,
+}
+
+impl InferredTags {
+    fn is_empty(&self) -> bool {
+        self.track_no.is_none() && self.artist.is_none() && self.title.is_none() && self.album.is_none()
+    }
+}
+
+/// 一条文件名模式，例如 "%album%/%track% - %title%"。
+/// 占位符：%track% %artist% %title% %album%，%ignore% 匹配任意内容；
+/// "/" 匹配上一级文件夹，扩展名不参与匹配，%% 表示字面的 '%'。
+#[derive(Debug, Clone)]
+pub struct FilenamePattern {
+    source: String,
+    regex: Regex,
+}
+
+impl PartialEq for FilenamePattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl FilenamePattern {
+    pub fn parse(pattern: &str) -> Option<Self> {
+        let source = pattern.trim();
+        if source.is_empty() {
+            return None;
+        }
+
+        let mut regex = String::from("(?:^|/)");
+        let mut rest = source;
+        while let Some(start) = rest.find('%') {
+            regex.push_str(&literal(&rest[..start]));
+            let after = &rest[start + 1..];
+            let end = after.find('%')?;
+            let name = &after[..end];
+            let group = match name.to_ascii_lowercase().as_str() {
+                "" => regex::escape("%"),
+                "track" | "tracknumber" => "(?P<track>[0-9]+)".to_string(),
+                "artist" => "(?P<artist>[^/]+?)".to_string(),
+                "title" => "(?P<title>[^/]+?)".to_string(),
+                "album" => "(?P<album>[^/]+?)".to_string(),
+                "ignore" => "[^/]+?".to_string(),
+                _ => {
+                    warn!("Unknown placeholder %{}% in file name pattern {:?}", name, source);
+                    return None;
+                }
+            };
+            regex.push_str(&group);
+            rest = &after[end + 1..];
+        }
+        regex.push_str(&literal(rest));
+        regex.push('$');
+
+        match Regex::new(&regex) {
+            Ok(regex) => Some(Self {
+                source: source.to_string(),
+                regex,
+            }),
+            Err(e) => {
+                warn!("Invalid file name pattern {:?}: {}", source, e);
+                None
+            }
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// 匹配去掉扩展名、统一为 "/" 分隔的路径
+    fn infer(&self, path: &str) -> Option<InferredTags> {
+        let captures = self.regex.captures(path)?;
+        let text = |name: &str| captures.name(name).and_then(|m| clean(m.as_str()));
+        let tags = InferredTags {
+            track_no: captures.name("track").and_then(|m| m.as_str().parse().ok()),
+            artist: text("artist"),
+            title: text("title"),
+            album: text("album"),
+        };
+        (!tags.is_empty()).then_some(tags)
+    }
+}
+
+/// 模式中的字面部分：空格同时匹配下划线和多个空格
+fn literal(text: &str) -> String {
+    text.split(' ')
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join("[ _]+")
+}
+
+/// 去掉首尾空白；没有空格的值把下划线当作空格
+fn clean(value: &str) -> Option<String> {
+    let value = if value.contains(' ') {
+        value.to_string()
+    } else {
+        value.replace('_', " ")
+    };
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// 文件名推断规则：全局模式列表，以及按文件夹替换的模式列表
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilenamePatterns {
+    pub enabled: bool,
+    pub patterns: Vec<FilenamePattern>,
+    pub overrides: Vec<(PathBuf, Vec<FilenamePattern>)>,
+}
+
+impl Default for FilenamePatterns {
+    fn default() -> Self {
+        Self::new(true, None, &[])
+    }
+}
+
+impl FilenamePatterns {
+    pub fn new(enabled: bool, patterns: Option<&[String]>, overrides: &[ScanPatternOverride]) -> Self {
+        let patterns = match patterns {
+            Some(patterns) => patterns.iter().filter_map(|p| FilenamePattern::parse(p)).collect(),
+            None => DEFAULT_FILENAME_PATTERNS
+                .iter()
+                .filter_map(|p| FilenamePattern::parse(p))
+                .collect(),
+        };
+        let overrides = overrides
+            .iter()
+            .filter(|o| !o.folder.trim().is_empty())
+            .map(|o| {
+                let patterns = o.patterns.iter().filter_map(|p| FilenamePattern::parse(p)).collect();
+                (PathBuf::from(o.folder.trim()), patterns)
+            })
+            .collect();
+        Self {
+            enabled,
+            patterns,
+            overrides,
+        }
+    }
+
+    /// 适用于 `path` 的模式：包含它的最深的覆盖文件夹优先
+    fn patterns_for(&self, path: &Path) -> &[FilenamePattern] {
+        self.overrides
+            .iter()
+            .filter(|(folder, _)| path.starts_with(folder))
+            .max_by_key(|(folder, _)| folder.components().count())
+            .map(|(_, patterns)| patterns.as_slice())
+            .unwrap_or(&self.patterns)
+    }
+
+    /// 第一个匹配的模式推断出的标签
+    pub fn infer(&self, path: &Path) -> Option<InferredTags> {
+        if !self.enabled {
+            return None;
+        }
+        let normalized = path.with_extension("").to_string_lossy().replace('\\', "/");
+        self.patterns_for(path).iter().find_map(|pattern| {
+            let tags = pattern.infer(&normalized)?;
+            debug!("File name pattern {:?} matched {:?}", pattern.source(), path);
+            Some(tags)
+        })
+    }
+}
+
+/// 用推断出的标签补全缺失的字段，已有的标签不会被覆盖。标题等于文件名时视为缺失。
+/// 返回是否有字段被补全。
+pub fn fill_missing_tags(track: &mut MediaContent, path: &Path, tags: InferredTags, artist_split: &str) -> bool {
+    let mut changed = false;
+
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string());
+    let title_missing = match track.track.title.as_deref() {
+        None => true,
+        Some(title) => title.trim().is_empty() || Some(title) == file_name.as_deref(),
+    };
+    if title_missing {
+        if let Some(title) = tags.title {
+            track.track.title = Some(title);
+            changed = true;
+        }
+    }
+
+    let artists_missing = track
+        .artists
+        .as_ref()
+        .map_or(true, |artists| artists.iter().all(|a| a.artist_name.as_deref().map_or(true, |n| n.trim().is_empty())));
+    if artists_missing {
+        if let Some(artist) = tags.artist {
+            let split = if artist_split.is_empty() { ";" } else { artist_split };
+            track.artists = Some(
+                artist
+                    .split(split)
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(|name| QueryableArtist {
+                        artist_id: Some(Uuid::new_v4().to_string()),
+                        artist_name: Some(name.to_string()),
+                        ..Default::default()
+                    })
+                    .collect(),
+            );
+            changed = true;
+        }
+    }
+
+    if track.album.is_none() {
+        if let Some(album) = tags.album {
+            track.album = Some(QueryableAlbum {
+                album_id: Some(Uuid::new_v4().to_string()),
+                album_name: Some(album),
+                album_coverpath_high: track.track.track_cover_path_high.clone(),
+                album_coverpath_low: track.track.track_cover_path_low.clone(),
+                ..Default::default()
+            });
+            changed = true;
+        }
+    }
+
+    if track.track.track_no.is_none() {
+        if let Some(track_no) = tags.track_no {
+            track.track.track_no = Some(track_no as f64);
+            changed = true;
+        }
+    }
+
+    changed
+}
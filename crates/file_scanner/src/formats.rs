@@ -0,0 +1,107 @@
+use std::{collections::BTreeSet, path::Path};
+
+use types::settings::general::ScanExtension;
+
+/// 旧设置 "common" 对应的扩展名
+pub const COMMON_EXTENSIONS: &[&str] = &["mp3", "flac", "m4a", "ogg"];
+/// 旧设置 "all" 在常用格式之外追加的扩展名
+pub const EXTENDED_EXTENSIONS: &[&str] = &["webm", "wav", "wv", "aac", "opus"];
+/// 较少见的格式，默认不扫描，需要用户手动开启
+pub const RARE_EXTENSIONS: &[&str] = &["ape", "aif", "aiff", "caf", "dff", "dsf", "mka", "mp2", "mpc", "oga", "spx", "tta", "wma"];
+
+/// 扩展名最大长度
+const MAX_EXTENSION_LEN: usize = 10;
+
+/// 规范化用户输入的扩展名：去掉空白和前导点并转为小写；
+/// 非 ASCII 字母数字或过长时返回 None
+pub fn normalize_extension(raw: &str) -> Option<String> {
+    let ext = raw.trim().trim_start_matches('.').to_ascii_lowercase();
+    let valid = !ext.is_empty() && ext.len() <= MAX_EXTENSION_LEN && ext.chars().all(|c| c.is_ascii_alphanumeric());
+    valid.then_some(ext)
+}
+
+/// 校验并规范化扩展名列表，重复项保留第一个；有无效项时返回它们
+pub fn validate_extensions(entries: &[ScanExtension]) -> std::result::Result<Vec<ScanExtension>, Vec<String>> {
+    let mut seen = BTreeSet::new();
+    let mut valid = Vec::new();
+    let mut invalid = Vec::new();
+    for entry in entries {
+        match normalize_extension(&entry.extension) {
+            Some(extension) => {
+                if seen.insert(extension.clone()) {
+                    valid.push(ScanExtension {
+                        extension,
+                        enabled: entry.enabled,
+                    });
+                }
+            }
+            None => invalid.push(entry.extension.clone()),
+        }
+    }
+    if invalid.is_empty() {
+        Ok(valid)
+    } else {
+        Err(invalid)
+    }
+}
+
+/// 扫描器与文件监控接受的扩展名
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanExtensions {
+    enabled: BTreeSet<String>,
+}
+
+impl Default for ScanExtensions {
+    fn default() -> Self {
+        Self::from_legacy("common")
+    }
+}
+
+impl ScanExtensions {
+    /// 由旧设置 scan_formats（"common" | "all"）得出
+    pub fn from_legacy(scan_formats: &str) -> Self {
+        let mut enabled: BTreeSet<String> = COMMON_EXTENSIONS.iter().map(|e| e.to_string()).collect();
+        if scan_formats == "all" {
+            enabled.extend(EXTENDED_EXTENSIONS.iter().map(|e| e.to_string()));
+        }
+        Self { enabled }
+    }
+
+    /// 由用户的扩展名列表得出，忽略无效项和关闭的项
+    pub fn from_settings(entries: &[ScanExtension]) -> Self {
+        let enabled = entries
+            .iter()
+            .filter(|e| e.enabled)
+            .filter_map(|e| normalize_extension(&e.extension))
+            .collect();
+        Self { enabled }
+    }
+
+    /// 设置界面的初始列表：所有已知格式，旧设置选中的格式为开启
+    pub fn default_entries(scan_formats: &str) -> Vec<ScanExtension> {
+        let current = Self::from_legacy(scan_formats);
+        COMMON_EXTENSIONS
+            .iter()
+            .chain(EXTENDED_EXTENSIONS)
+            .chain(RARE_EXTENSIONS)
+            .map(|e| ScanExtension {
+                extension: e.to_string(),
+                enabled: current.contains(e),
+            })
+            .collect()
+    }
+
+    pub fn contains(&self, extension: &str) -> bool {
+        self.enabled.contains(&extension.to_ascii_lowercase())
+    }
+
+    pub fn matches(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| self.contains(e))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.enabled.iter().map(String::as_str)
+    }
+}
@@ -1,5 +1,14 @@
+mod artwork;
 pub mod auto_scanner;
+mod chapters;
+mod estimate;
+mod exclude;
 pub mod file_cache;
+mod filename_tags;
+mod formats;
+mod progress;
+mod reachability;
+mod schedule;
 
 #[cfg(not(any(target_os = "android", target_os = "ios")))]
 mod playlist_scanner;
@@ -12,6 +21,7 @@ mod track_scanner;
 
 mod types;
 mod utils;
+mod walk;
 
 #[cfg(test)]
 mod tests;
@@ -21,7 +31,20 @@ mod scanner_android;
 #[cfg(target_os = "android")]
 pub use scanner_android::{ScanState, ScannerHolder};
 
-pub use auto_scanner::{AutoScanner, AutoScannerConfig, ScanEvent, ScanResult, ScannerState as AutoScannerState};
+pub use auto_scanner::{
+    AutoScanner, AutoScannerConfig, ScanCheckpoint, ScanEvent, ScanJob, ScanPhase, ScanResult,
+    ScannerState as AutoScannerState,
+};
+pub use chapters::read_chapters;
+pub use estimate::{dir_size, estimate_scan};
+pub use exclude::ScanExcludes;
 pub use file_cache::{FileCache, FileMetadata, CacheStats};
-pub use utils::{get_files_recursively, scan_file};
+pub use filename_tags::{fill_missing_tags, FilenamePattern, FilenamePatterns, InferredTags, DEFAULT_FILENAME_PATTERNS};
+pub use formats::{normalize_extension, validate_extensions, ScanExtensions, COMMON_EXTENSIONS, EXTENDED_EXTENSIONS, RARE_EXTENSIONS};
+pub use progress::{PhaseProgress, ProgressPhase, ScanProgress};
+pub use reachability::{is_root_reachable, probe_root, RootStatus, REACHABILITY_TIMEOUT};
+pub use schedule::ScanSchedule;
+pub use walk::{walk_files, WalkOptions, WalkPolicy};
+pub use artwork::{artwork_variant, folder_artwork, store_artwork};
+pub use utils::{audio_extension, embed_cover, get_files_recursively, get_files_with_options, read_embedded_lyrics, read_lrc_sidecar, read_replay_gain, scan_cover, scan_file, scan_file_metadata, write_tags};
 pub use types::FileList;
@@ -226,7 +226,11 @@ impl<'a> PlaylistScanner<'a> {
 
         let mut len = 0;
 
-        for playlist in file_list.playlist_list {
+        let playlists = file_list
+            .playlist_list
+            .into_iter()
+            .filter(|path| !self.track_scanner.is_excluded(path));
+        for playlist in playlists {
             let playlist_scan_res = self.scan_playlist(&playlist);
             if playlist_scan_res.is_err() {
                 tx_playlist
@@ -0,0 +1,202 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// 进度报告的最小间隔，阶段切换与结束时立即报告
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 扫描进度阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProgressPhase {
+    /// 遍历扫描目录、比对缓存
+    Discovery,
+    /// 解析标签与音频属性
+    Metadata,
+    /// 提取封面、生成缩略图
+    Thumbnails,
+    /// 结果写入数据库，由结果的消费者报告
+    DbWrite,
+}
+
+const PHASES: [ProgressPhase; 4] = [
+    ProgressPhase::Discovery,
+    ProgressPhase::Metadata,
+    ProgressPhase::Thumbnails,
+    ProgressPhase::DbWrite,
+];
+
+/// 单个阶段的计数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseProgress {
+    pub phase: ProgressPhase,
+    pub total: usize,
+    pub processed: usize,
+}
+
+/// 扫描任务的进度。文件按批依次经过元数据、缩略图与入库阶段，各阶段计数交替推进，
+/// `phase` 为最近推进的阶段
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanProgress {
+    /// 任务 id，同时是取消令牌
+    pub job_id: String,
+    pub phase: ProgressPhase,
+    pub phases: Vec<PhaseProgress>,
+    pub current_file: Option<PathBuf>,
+    /// 预计剩余秒数，首个文件解析完成前未知
+    pub eta_secs: Option<u64>,
+    pub cancelled: bool,
+    /// 扫描结束且结果均已入库
+    pub finished: bool,
+}
+
+impl ScanProgress {
+    fn new(job_id: &str) -> Self {
+        Self {
+            job_id: job_id.to_string(),
+            phase: ProgressPhase::Discovery,
+            phases: PHASES
+                .iter()
+                .map(|&phase| PhaseProgress { phase, total: 0, processed: 0 })
+                .collect(),
+            current_file: None,
+            eta_secs: None,
+            cancelled: false,
+            finished: false,
+        }
+    }
+
+    fn counts(&mut self, phase: ProgressPhase) -> &mut PhaseProgress {
+        let index = PHASES.iter().position(|&p| p == phase).unwrap();
+        &mut self.phases[index]
+    }
+}
+
+struct TrackedJob {
+    progress: ScanProgress,
+    /// 首个文件开始解析的时间，用于估算剩余时间
+    metadata_started: Option<Instant>,
+    last_report: Instant,
+    /// 扫描器一侧已结束，剩余的只有入库
+    scan_done: bool,
+}
+
+impl TrackedJob {
+    fn update_eta(&mut self) {
+        let metadata = self.progress.counts(ProgressPhase::Metadata).clone();
+        self.progress.eta_secs = match self.metadata_started {
+            Some(started) if metadata.processed > 0 => {
+                let remaining = metadata.total.saturating_sub(metadata.processed) as f64;
+                let per_file = started.elapsed().as_secs_f64() / metadata.processed as f64;
+                Some((remaining * per_file).ceil() as u64)
+            }
+            _ => None,
+        };
+    }
+
+    fn check_finished(&mut self) {
+        let written = self.progress.counts(ProgressPhase::DbWrite);
+        if self.scan_done && written.processed >= written.total {
+            self.progress.finished = true;
+            self.progress.current_file = None;
+            self.progress.eta_secs = Some(0);
+        }
+    }
+}
+
+/// 当前扫描任务的进度与取消请求
+#[derive(Default)]
+pub(crate) struct ProgressTracker {
+    job: Mutex<Option<TrackedJob>>,
+    /// 请求取消的任务 id，任务可能尚在队列中
+    cancelled: Mutex<HashSet<String>>,
+}
+
+impl ProgressTracker {
+    /// 开始跟踪新任务，返回初始进度
+    pub fn begin(&self, job_id: &str) -> ScanProgress {
+        let progress = ScanProgress::new(job_id);
+        *self.job.lock().unwrap() = Some(TrackedJob {
+            progress: progress.clone(),
+            metadata_started: None,
+            last_report: Instant::now(),
+            scan_done: false,
+        });
+        progress
+    }
+
+    pub fn add_total(&self, phase: ProgressPhase, count: usize) {
+        if let Some(job) = self.job.lock().unwrap().as_mut() {
+            job.progress.counts(phase).total += count;
+        }
+    }
+
+    /// 阶段处理完一个文件。阶段切换或距上次报告超过间隔时返回进度
+    pub fn advance(&self, phase: ProgressPhase, file: &Path) -> Option<ScanProgress> {
+        let mut guard = self.job.lock().unwrap();
+        let job = guard.as_mut()?;
+        if phase == ProgressPhase::Metadata {
+            job.metadata_started.get_or_insert_with(Instant::now);
+        }
+        job.progress.counts(phase).processed += 1;
+        job.progress.current_file = Some(file.to_path_buf());
+        job.update_eta();
+
+        let phase_changed = job.progress.phase != phase;
+        job.progress.phase = phase;
+        if phase_changed || job.last_report.elapsed() >= PROGRESS_INTERVAL {
+            job.last_report = Instant::now();
+            return Some(job.progress.clone());
+        }
+        None
+    }
+
+    /// 扫描器一侧结束，之后只等待入库
+    pub fn finish(&self, cancelled: bool) -> Option<ScanProgress> {
+        let mut guard = self.job.lock().unwrap();
+        let job = guard.as_mut()?;
+        self.cancelled.lock().unwrap().remove(&job.progress.job_id);
+        job.scan_done = true;
+        job.progress.cancelled = cancelled;
+        job.progress.phase = ProgressPhase::DbWrite;
+        job.check_finished();
+        Some(job.progress.clone())
+    }
+
+    /// 消费者写入了任务 `job_id` 的 `count` 首曲目
+    pub fn record_written(&self, job_id: &str, count: usize) -> Option<ScanProgress> {
+        let mut guard = self.job.lock().unwrap();
+        let job = guard.as_mut().filter(|job| job.progress.job_id == job_id)?;
+        job.progress.counts(ProgressPhase::DbWrite).processed += count;
+        job.check_finished();
+        Some(job.progress.clone())
+    }
+
+    pub fn snapshot(&self) -> Option<ScanProgress> {
+        self.job.lock().unwrap().as_ref().map(|job| job.progress.clone())
+    }
+
+    /// 请求取消任务 `job_id`，为空时取消正在进行的任务。返回是否有任务可取消
+    pub fn cancel(&self, job_id: Option<&str>) -> bool {
+        let job_id = match (job_id, self.job.lock().unwrap().as_ref()) {
+            // 已结束的任务无需取消
+            (Some(job_id), Some(job)) if job.progress.job_id == job_id && job.scan_done => return false,
+            (Some(job_id), _) => job_id.to_string(),
+            (None, Some(job)) if !job.scan_done => job.progress.job_id.clone(),
+            (None, _) => return false,
+        };
+        self.cancelled.lock().unwrap().insert(job_id);
+        true
+    }
+
+    pub fn is_cancelled(&self, job_id: &str) -> bool {
+        self.cancelled.lock().unwrap().contains(job_id)
+    }
+}
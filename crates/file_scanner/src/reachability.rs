@@ -0,0 +1,63 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use tracing::warn;
+
+/// 网络共享（SMB/NFS）掉线时目录读取可能长时间阻塞，超过该时间视为不可达
+pub const REACHABILITY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 扫描根目录的探测结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootStatus {
+    /// 不存在、无法读取或读取超时
+    Unreachable,
+    /// 可读取但没有任何条目（可能是未挂载的挂载点）
+    Empty,
+    /// 可读取且有内容
+    Available,
+}
+
+/// 在独立线程中读取根目录，超时则放弃等待（阻塞的线程会在读取返回后自行退出）
+pub fn probe_root(root: &Path, timeout: Duration) -> RootStatus {
+    let (tx, rx) = mpsc::channel();
+    let dir: PathBuf = root.to_path_buf();
+    let spawned = thread::Builder::new()
+        .name("scan-root-probe".into())
+        .spawn(move || {
+            let status = match std::fs::read_dir(&dir) {
+                Ok(mut entries) => match entries.next() {
+                    Some(Ok(_)) => RootStatus::Available,
+                    Some(Err(_)) => RootStatus::Unreachable,
+                    None => RootStatus::Empty,
+                },
+                Err(_) => RootStatus::Unreachable,
+            };
+            let _ = tx.send(status);
+        });
+    if let Err(e) = spawned {
+        warn!("Failed to spawn reachability probe for {:?}: {}", root, e);
+        return RootStatus::Unreachable;
+    }
+
+    match rx.recv_timeout(timeout) {
+        Ok(status) => status,
+        Err(_) => {
+            warn!("Scan root {:?} did not respond within {:?}", root, timeout);
+            RootStatus::Unreachable
+        }
+    }
+}
+
+/// 根目录是否可作为扫描依据。空目录而缓存中仍有其下文件时，按共享未挂载处理，
+/// 避免把整个目录的曲目当作已删除
+pub fn is_root_reachable(root: &Path, has_cached_files: bool) -> bool {
+    match probe_root(root, REACHABILITY_TIMEOUT) {
+        RootStatus::Available => true,
+        RootStatus::Empty => !has_cached_files,
+        RootStatus::Unreachable => false,
+    }
+}
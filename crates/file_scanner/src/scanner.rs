@@ -9,9 +9,9 @@ use std::{
 
 use threadpool::ThreadPool;
 use types::errors::Result;
-use types::{entities::QueryablePlaylist, songs::Song};
+use types::{entities::QueryablePlaylist, tracks::MediaContent};
 
-use crate::{playlist_scanner::PlaylistScanner, song_scanner::SongScanner};
+use crate::{exclude::ScanExcludes, playlist_scanner::PlaylistScanner, track_scanner::TrackScanner};
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum ScanState {
@@ -47,6 +47,7 @@ impl ScannerHolder {
             dir,
             thumbnail_dir,
             artist_split,
+            excludes,
             scan_threads,
             song_tx,
             playlist_tx
@@ -57,8 +58,9 @@ impl ScannerHolder {
         dir: String,
         thumbnail_dir: String,
         artist_split: String,
+        excludes: ScanExcludes,
         scan_threads: f64,
-        song_tx: Sender<(Option<String>, Vec<Song>)>,
+        song_tx: Sender<(Option<String>, Vec<MediaContent>)>,
         playlist_tx: Sender<Vec<QueryablePlaylist>>,
     ) -> Result<()> {
         let mut state = self.state.lock().unwrap();
@@ -84,14 +86,15 @@ impl ScannerHolder {
         let thumbnail_dir = PathBuf::from_str(thumbnail_dir.as_str()).unwrap();
         let dir = PathBuf::from_str(dir.as_str()).unwrap();
 
-        let song_scanner = SongScanner::new(
+        let song_scanner = TrackScanner::new(
             dir.clone(),
             &mut song_pool,
             thumbnail_dir.clone(),
             artist_split,
+            excludes,
         );
 
-        let (tx_song, rx_song) = mpsc::channel::<(Option<String>, Result<Song>)>();
+        let (tx_song, rx_song) = mpsc::channel::<(Option<String>, Result<MediaContent>)>();
         let (tx_playlist, rx_playlist) = mpsc::channel::<Result<QueryablePlaylist>>();
 
         song_scanner.start(tx_song.clone())?;
@@ -0,0 +1,88 @@
+use chrono::{Datelike, Duration as ChronoDuration, NaiveDateTime, NaiveTime, Weekday};
+use tracing::debug;
+use types::settings::general::{ScanWeekday, ScanWindow};
+
+/// 定时扫描计划：扫描间隔之外，可限定只在若干时间窗口内运行
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScanSchedule {
+    /// 允许定时扫描的时间窗口，为空时任何时间均可
+    pub windows: Vec<ScanWindow>,
+    /// 错过窗口（如机器休眠）后是否尽快补扫
+    pub catch_up: bool,
+}
+
+/// 解析后的时间窗口
+struct Window {
+    days: Vec<Weekday>,
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl Window {
+    fn parse(window: &ScanWindow) -> Option<Self> {
+        let parse_time = |s: &str| NaiveTime::parse_from_str(s.trim(), "%H:%M").ok();
+        let (Some(start), Some(end)) = (parse_time(&window.start), parse_time(&window.end)) else {
+            debug!("Ignoring invalid scan window {:?}", window);
+            return None;
+        };
+        let days = window
+            .days
+            .iter()
+            .map(|day| match day {
+                ScanWeekday::Mon => Weekday::Mon,
+                ScanWeekday::Tue => Weekday::Tue,
+                ScanWeekday::Wed => Weekday::Wed,
+                ScanWeekday::Thu => Weekday::Thu,
+                ScanWeekday::Fri => Weekday::Fri,
+                ScanWeekday::Sat => Weekday::Sat,
+                ScanWeekday::Sun => Weekday::Sun,
+            })
+            .collect();
+        Some(Self { days, start, end })
+    }
+
+    /// 最近一次在 `now` 之前（含）开始的窗口区间 `[start, end)`
+    fn latest_occurrence(&self, now: NaiveDateTime) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        (0..=7).find_map(|days_back| {
+            let date = now.date() - ChronoDuration::days(days_back);
+            if !self.days.is_empty() && !self.days.contains(&date.weekday()) {
+                return None;
+            }
+            let start = date.and_time(self.start);
+            if start > now {
+                return None;
+            }
+            let mut end = date.and_time(self.end);
+            if end <= start {
+                end += ChronoDuration::days(1);
+            }
+            Some((start, end))
+        })
+    }
+}
+
+impl ScanSchedule {
+    /// `now` 时是否应执行定时扫描。
+    ///
+    /// 无窗口时仅按间隔判断；窗口内每个窗口至少扫描一次，之后按间隔重复；
+    /// 开启补扫时，若最近一个窗口已结束且其间未扫描，则立即扫描。
+    pub fn is_due(&self, now: NaiveDateTime, last_scan: Option<NaiveDateTime>, interval: ChronoDuration) -> bool {
+        let interval_elapsed = !last_scan.is_some_and(|last| now - last < interval);
+        let windows: Vec<Window> = self.windows.iter().filter_map(Window::parse).collect();
+        if windows.is_empty() {
+            return interval_elapsed;
+        }
+
+        windows
+            .iter()
+            .filter_map(|window| window.latest_occurrence(now))
+            .any(|(start, end)| {
+                if now < end {
+                    interval_elapsed || last_scan.is_some_and(|last| last < start)
+                } else {
+                    // 首次运行没有记录，不视为错过
+                    self.catch_up && last_scan.is_some_and(|last| last < start)
+                }
+            })
+    }
+}
@@ -160,3 +160,495 @@ https://example.com/lonely_track"#;
     fs::remove_dir_all(test_in_dir).unwrap();
     fs::remove_dir_all(test_out_dir).unwrap();
 }
+
+#[test]
+fn test_estimate_scan() {
+    let test_in_dir = env::temp_dir().join("music-test-in-estimate");
+    let nested = test_in_dir.join("album");
+    fs::create_dir_all(nested.clone()).unwrap();
+
+    fs::write(test_in_dir.join("a.mp3"), [0u8; 10]).unwrap();
+    fs::write(nested.join("b.FLAC"), [0u8; 100]).unwrap();
+    fs::write(nested.join("c.flac"), [0u8; 50]).unwrap();
+    fs::write(nested.join("list.m3u"), b"#EXTM3U").unwrap();
+    fs::write(nested.join("cover.jpg"), [0u8; 1000]).unwrap();
+
+    let estimate = crate::estimate_scan(&[test_in_dir.clone(), test_in_dir.join("missing")]);
+    assert_eq!(estimate.files, 3);
+    assert_eq!(estimate.bytes, 160);
+    assert_eq!(estimate.playlists, 1);
+    assert_eq!(estimate.skipped, 1);
+    let formats: Vec<_> = estimate
+        .formats
+        .iter()
+        .map(|f| (f.extension.as_str(), f.files, f.bytes))
+        .collect();
+    assert_eq!(formats, vec![("flac", 2, 150), ("mp3", 1, 10)]);
+
+    assert_eq!(crate::dir_size(&test_in_dir), 1167);
+
+    fs::remove_dir_all(test_in_dir).unwrap();
+}
+
+#[test]
+fn test_read_lrc_sidecar() {
+    let test_in_dir = env::temp_dir().join("music-test-in-lrc");
+    fs::create_dir_all(test_in_dir.clone()).unwrap();
+
+    let track = test_in_dir.join("song.mp3");
+    fs::write(&track, [0u8; 10]).unwrap();
+    assert_eq!(crate::read_lrc_sidecar(&track), None);
+
+    fs::write(test_in_dir.join("song.lrc"), "\u{feff}[00:01.00]Hello\n[00:02.50]World\n").unwrap();
+    assert_eq!(
+        crate::read_lrc_sidecar(&track).as_deref(),
+        Some("[00:01.00]Hello\n[00:02.50]World\n")
+    );
+
+    fs::remove_dir_all(test_in_dir).unwrap();
+}
+
+#[test]
+fn test_scan_schedule_windows() {
+    use chrono::{Duration, NaiveDate, NaiveDateTime};
+    use types::settings::general::{ScanWeekday, ScanWindow};
+
+    use crate::ScanSchedule;
+
+    // 2025-01-06 is a Monday
+    let at = |day: u32, h: u32, m: u32| -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 1, day).unwrap().and_hms_opt(h, m, 0).unwrap()
+    };
+    let hour = Duration::hours(1);
+
+    let nightly = ScanSchedule {
+        windows: vec![ScanWindow {
+            days: Vec::new(),
+            start: "02:00".into(),
+            end: "05:00".into(),
+        }],
+        catch_up: true,
+    };
+    assert!(!nightly.is_due(at(6, 14, 0), None, hour));
+    assert!(nightly.is_due(at(6, 2, 30), Some(at(5, 23, 0)), Duration::days(7)));
+    assert!(!nightly.is_due(at(6, 2, 30), Some(at(6, 2, 10)), hour));
+    // Asleep through the window: catch up once, then wait for the next one
+    assert!(nightly.is_due(at(6, 9, 0), Some(at(5, 3, 0)), Duration::days(7)));
+    assert!(!nightly.is_due(at(6, 9, 5), Some(at(6, 9, 0)), hour));
+    assert!(!ScanSchedule { catch_up: false, ..nightly.clone() }.is_due(at(6, 9, 0), Some(at(5, 3, 0)), hour));
+
+    let overnight = ScanSchedule {
+        windows: vec![ScanWindow {
+            days: vec![ScanWeekday::Sun],
+            start: "23:00".into(),
+            end: "01:00".into(),
+        }],
+        catch_up: false,
+    };
+    assert!(overnight.is_due(at(6, 0, 30), None, hour));
+    assert!(!overnight.is_due(at(6, 23, 30), None, hour));
+
+    let weekends = ScanSchedule {
+        windows: vec![ScanWindow {
+            days: vec![ScanWeekday::Sat, ScanWeekday::Sun],
+            start: "00:00".into(),
+            end: "00:00".into(),
+        }],
+        catch_up: false,
+    };
+    assert!(weekends.is_due(at(11, 15, 0), None, hour));
+    assert!(!weekends.is_due(at(10, 15, 0), None, hour));
+
+    let anytime = ScanSchedule::default();
+    assert!(anytime.is_due(at(6, 14, 0), Some(at(6, 12, 0)), hour));
+    assert!(!anytime.is_due(at(6, 14, 0), Some(at(6, 13, 30)), hour));
+}
+
+#[test]
+fn test_walk_depth_limit() {
+    use types::settings::general::ScanSymlinkPolicy;
+
+    use crate::{get_files_with_options, WalkOptions};
+
+    let root = tempfile::tempdir().unwrap();
+    let nested = root.path().join("a").join("b");
+    fs::create_dir_all(&nested).unwrap();
+    File::create(root.path().join("top.mp3")).unwrap();
+    File::create(root.path().join("a").join("mid.mp3")).unwrap();
+    File::create(nested.join("deep.mp3")).unwrap();
+
+    let count = |max_depth| {
+        let options = WalkOptions {
+            max_depth,
+            symlinks: ScanSymlinkPolicy::Safe,
+        };
+        get_files_with_options(root.path().to_path_buf(), options).unwrap().file_list.len()
+    };
+    assert_eq!(count(None), 3);
+    assert_eq!(count(Some(0)), 1);
+    assert_eq!(count(Some(1)), 2);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_walk_symlink_policy() {
+    use std::os::unix::fs::symlink;
+
+    use types::settings::general::{ScanRootOverride, ScanSymlinkPolicy};
+
+    use crate::{get_files_with_options, WalkOptions, WalkPolicy};
+
+    let root = tempfile::tempdir().unwrap();
+    let outside = tempfile::tempdir().unwrap();
+    let album = root.path().join("album");
+    fs::create_dir_all(&album).unwrap();
+    File::create(album.join("track.flac")).unwrap();
+    File::create(outside.path().join("linked.flac")).unwrap();
+    // A loop back to the root and a link to a folder outside it
+    symlink(root.path(), album.join("loop")).unwrap();
+    symlink(outside.path(), root.path().join("outside")).unwrap();
+    symlink(outside.path(), album.join("outside-again")).unwrap();
+
+    let files = |symlinks| {
+        let options = WalkOptions {
+            max_depth: None,
+            symlinks,
+        };
+        let mut names: Vec<String> = get_files_with_options(root.path().to_path_buf(), options)
+            .unwrap()
+            .file_list
+            .into_iter()
+            .map(|(p, _)| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    };
+    assert_eq!(files(ScanSymlinkPolicy::Never), vec!["track.flac"]);
+    assert_eq!(files(ScanSymlinkPolicy::Safe), vec!["linked.flac", "track.flac"]);
+    assert_eq!(files(ScanSymlinkPolicy::Always), vec!["linked.flac", "linked.flac", "track.flac"]);
+
+    let policy = WalkPolicy::new(
+        Some(4),
+        None,
+        &[ScanRootOverride {
+            root: album.to_string_lossy().into_owned(),
+            max_depth: Some(0),
+            symlinks: Some(ScanSymlinkPolicy::Never),
+        }],
+    );
+    assert_eq!(policy.options_for(root.path()).max_depth, Some(4));
+    assert_eq!(policy.options_for(&album.join("disc 1")).max_depth, Some(0));
+    assert_eq!(policy.options_for(&album).symlinks, ScanSymlinkPolicy::Never);
+    let roots = vec![root.path().to_path_buf(), album.clone()];
+    assert!(policy.allows(&roots, &album.join("track.flac")));
+    assert!(!policy.allows(&roots, &album.join("disc 1").join("track.flac")));
+    assert!(policy.allows(&[root.path().to_path_buf()], &album.join("disc 1").join("track.flac")));
+}
+
+#[test]
+fn test_filename_tag_inference() {
+    use std::path::Path;
+
+    use types::{
+        entities::QueryableArtist,
+        settings::general::ScanPatternOverride,
+        tracks::{MediaContent, Tracks},
+    };
+
+    use crate::{fill_missing_tags, FilenamePattern, FilenamePatterns, InferredTags};
+
+    let defaults = FilenamePatterns::default();
+    assert_eq!(
+        defaults.infer(Path::new("/music/01 - Some Artist - A Song.mp3")),
+        Some(InferredTags {
+            track_no: Some(1),
+            artist: Some("Some Artist".into()),
+            title: Some("A Song".into()),
+            album: None,
+        })
+    );
+    let numbered = defaults.infer(Path::new("/music/07. Intro.flac")).unwrap();
+    assert_eq!((numbered.track_no, numbered.title.as_deref()), (Some(7), Some("Intro")));
+    let underscored = defaults.infer(Path::new("/music/Band_Name_-_Track_Title.ogg")).unwrap();
+    assert_eq!(underscored.artist.as_deref(), Some("Band Name"));
+    assert_eq!(underscored.title.as_deref(), Some("Track Title"));
+    assert_eq!(defaults.infer(Path::new("/music/untitled.mp3")), None);
+
+    assert!(FilenamePattern::parse("%track% - %unknown%").is_none());
+    let folders = FilenamePatterns::new(
+        true,
+        Some(&["%artist% - %title%".to_string()]),
+        &[ScanPatternOverride {
+            folder: "/music/albums".into(),
+            patterns: vec!["%artist%/%album%/%track% %title%".into()],
+        }],
+    );
+    let album = folders
+        .infer(Path::new("/music/albums/Artist/Record/03 Third.mp3"))
+        .unwrap();
+    assert_eq!(album.artist.as_deref(), Some("Artist"));
+    assert_eq!(album.album.as_deref(), Some("Record"));
+    assert_eq!(album.track_no, Some(3));
+    assert_eq!(folders.infer(Path::new("/music/03 Third.mp3")), None);
+    assert_eq!(FilenamePatterns::new(false, None, &[]).infer(Path::new("/music/1 - A - B.mp3")), None);
+
+    // Only missing fields are filled
+    let path = Path::new("/music/02 - Guessed - Name.mp3");
+    let mut track = MediaContent {
+        track: Tracks {
+            title: Some("02 - Guessed - Name.mp3".into()),
+            ..Default::default()
+        },
+        album: None,
+        artists: Some(vec![QueryableArtist {
+            artist_name: Some("Tagged".into()),
+            ..Default::default()
+        }]),
+        genre: None,
+    };
+    let tags = defaults.infer(path).unwrap();
+    assert!(fill_missing_tags(&mut track, path, tags, ";"));
+    assert_eq!(track.track.title.as_deref(), Some("Name"));
+    assert_eq!(track.track.track_no, Some(2.0));
+    assert_eq!(track.artists.unwrap()[0].artist_name.as_deref(), Some("Tagged"));
+}
+
+#[test]
+fn test_scan_extensions() {
+    use std::path::Path;
+
+    use types::settings::general::ScanExtension;
+
+    use crate::{normalize_extension, validate_extensions, ScanExtensions};
+
+    let entry = |extension: &str, enabled: bool| ScanExtension {
+        extension: extension.to_string(),
+        enabled,
+    };
+
+    let common = ScanExtensions::from_legacy("common");
+    assert!(common.matches(Path::new("/music/a.FLAC")));
+    assert!(!common.matches(Path::new("/music/a.opus")));
+    assert!(ScanExtensions::from_legacy("all").matches(Path::new("/music/a.opus")));
+    assert!(!common.matches(Path::new("/music/no_extension")));
+
+    assert_eq!(normalize_extension(" .DSF "), Some("dsf".to_string()));
+    assert_eq!(normalize_extension("m p3"), None);
+    assert_eq!(normalize_extension(""), None);
+
+    let chosen = ScanExtensions::from_settings(&[entry("mp3", false), entry(".Ape", true), entry("flac", true)]);
+    assert_eq!(chosen.iter().collect::<Vec<_>>(), vec!["ape", "flac"]);
+    assert!(!chosen.matches(Path::new("/music/a.mp3")));
+
+    assert_eq!(
+        validate_extensions(&[entry("FLAC", true), entry("flac", false), entry("wma", false)]),
+        Ok(vec![entry("flac", true), entry("wma", false)])
+    );
+    assert_eq!(validate_extensions(&[entry("mp3", true), entry("*.x", true)]), Err(vec!["*.x".to_string()]));
+
+    let defaults = ScanExtensions::default_entries("common");
+    assert!(defaults.contains(&entry("ogg", true)));
+    assert!(defaults.contains(&entry("wav", false)));
+    assert!(defaults.contains(&entry("ape", false)));
+}
+
+#[test]
+fn test_read_chapters() {
+    use crate::read_chapters;
+
+    let dir = env::temp_dir().join("music-test-chapters");
+    fs::create_dir_all(&dir).unwrap();
+
+    // ID3v2.3 tag with two CHAP frames, each with a TIT2 sub-frame
+    let frame = |id: &[u8], body: &[u8]| {
+        let mut frame = id.to_vec();
+        frame.extend((body.len() as u32).to_be_bytes());
+        frame.extend([0, 0]);
+        frame.extend(body);
+        frame
+    };
+    let chap = |element: &str, start: u32, end: u32, title: &str| {
+        let mut body = element.as_bytes().to_vec();
+        body.push(0);
+        for n in [start, end, u32::MAX, u32::MAX] {
+            body.extend(n.to_be_bytes());
+        }
+        let mut text = vec![3];
+        text.extend(title.as_bytes());
+        body.extend(frame(b"TIT2", &text));
+        frame(b"CHAP", &body)
+    };
+    let mut frames = chap("ch1", 60_000, 0, "Chapter Two");
+    frames.extend(chap("ch0", 0, 60_000, "Chapter One"));
+    let size = frames.len() as u32;
+    let mut mp3 = b"ID3\x03\x00\x00".to_vec();
+    mp3.extend([(size >> 21) as u8 & 0x7f, (size >> 14) as u8 & 0x7f, (size >> 7) as u8 & 0x7f, size as u8 & 0x7f]);
+    mp3.extend(frames);
+    mp3.extend([0xff, 0xfb, 0x90, 0x00]);
+    let mp3_path = dir.join("book.mp3");
+    fs::write(&mp3_path, mp3).unwrap();
+
+    let chapters = read_chapters(&mp3_path);
+    assert_eq!(chapters.len(), 2);
+    assert_eq!(chapters[0].title.as_deref(), Some("Chapter One"));
+    assert_eq!((chapters[0].start, chapters[0].end), (0.0, Some(60.0)));
+    assert_eq!(chapters[1].title.as_deref(), Some("Chapter Two"));
+    // No end in the tag: runs to the end of the file
+    assert_eq!((chapters[1].start, chapters[1].end), (60.0, None));
+
+    // M4B with a Nero chapter list in moov/udta/chpl
+    let mp4_box = |kind: &[u8], body: &[u8]| {
+        let mut b = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        b.extend(kind);
+        b.extend(body);
+        b
+    };
+    let mut chpl = vec![1, 0, 0, 0, 0, 0, 0, 0, 2];
+    for (start, title) in [(0u64, "Intro"), (905_000_000, "Part 1")] {
+        chpl.extend(start.to_be_bytes());
+        chpl.push(title.len() as u8);
+        chpl.extend(title.as_bytes());
+    }
+    let mut m4b = mp4_box(b"ftyp", b"M4B \x00\x00\x02\x00");
+    m4b.extend(mp4_box(b"mdat", &[0; 16]));
+    m4b.extend(mp4_box(b"moov", &mp4_box(b"udta", &mp4_box(b"chpl", &chpl))));
+    let m4b_path = dir.join("book.m4b");
+    fs::write(&m4b_path, m4b).unwrap();
+
+    let chapters = read_chapters(&m4b_path);
+    assert_eq!(chapters.len(), 2);
+    assert_eq!(chapters[0].title.as_deref(), Some("Intro"));
+    assert_eq!(chapters[0].end, Some(90.5));
+    assert_eq!(chapters[1].start, 90.5);
+
+    let plain = dir.join("plain.mp3");
+    fs::write(&plain, [0xff, 0xfb, 0x90, 0x00, 0, 0, 0, 0]).unwrap();
+    assert!(read_chapters(&plain).is_empty());
+}
+
+#[test]
+fn test_folder_artwork_priority() {
+    let test_in_dir = env::temp_dir().join("music-test-in-folder-art");
+    fs::create_dir_all(test_in_dir.clone()).unwrap();
+
+    assert_eq!(crate::folder_artwork(&test_in_dir), None);
+    fs::write(test_in_dir.join("artwork.png"), [0u8; 10]).unwrap();
+    fs::write(test_in_dir.join("Folder.JPG"), [0u8; 10]).unwrap();
+    fs::write(test_in_dir.join("cover.txt"), [0u8; 10]).unwrap();
+    assert_eq!(crate::folder_artwork(&test_in_dir), Some(test_in_dir.join("Folder.JPG")));
+    fs::write(test_in_dir.join("cover.webp"), [0u8; 10]).unwrap();
+    assert_eq!(crate::folder_artwork(&test_in_dir), Some(test_in_dir.join("cover.webp")));
+
+    fs::remove_dir_all(test_in_dir).unwrap();
+}
+
+#[test]
+fn test_store_artwork_dedup() {
+    let test_out_dir = env::temp_dir().join("music-test-out-artwork");
+    let _ = fs::remove_dir_all(&test_out_dir);
+
+    let mut png = std::io::Cursor::new(Vec::new());
+    image::RgbaImage::from_pixel(300, 200, image::Rgba([200, 40, 40, 255]))
+        .write_to(&mut png, image::ImageFormat::Png)
+        .unwrap();
+    let png = png.into_inner();
+
+    let (high, low) = crate::store_artwork(&test_out_dir, &png).unwrap();
+    assert_eq!(high.extension().unwrap(), "webp");
+    assert!(low.to_string_lossy().ends_with("-low.webp"));
+    assert_eq!(image::image_dimensions(&high).unwrap(), (400, 400));
+    assert_eq!(image::image_dimensions(&low).unwrap(), (80, 80));
+
+    // The same cover again is not stored twice
+    assert_eq!(crate::store_artwork(&test_out_dir, &png).unwrap(), (high, low));
+    assert_eq!(fs::read_dir(&test_out_dir).unwrap().count(), 3);
+
+    fs::remove_dir_all(test_out_dir).unwrap();
+}
+
+#[test]
+fn test_scan_exclude_patterns() {
+    use std::path::{Path, PathBuf};
+
+    use crate::ScanExcludes;
+
+    let patterns = vec![
+        "**/node_modules/**".to_string(),
+        "*.part".to_string(),
+        "**/.*/**".to_string(),
+        "[invalid".to_string(),
+    ];
+    let excludes = ScanExcludes::new(vec![PathBuf::from("/music/skip")], &patterns);
+
+    assert!(excludes.is_excluded(Path::new("/music/skip/a.mp3")));
+    assert!(excludes.is_excluded(Path::new("/music/app/node_modules/x/a.mp3")));
+    assert!(excludes.is_excluded(Path::new("/music/album/01.flac.part")));
+    assert!(excludes.is_excluded(Path::new("/music/.trash/a.mp3")));
+    assert!(!excludes.is_excluded(Path::new("/music/album/01.flac")));
+    assert!(!excludes.is_excluded(Path::new("/music/skipped/a.mp3")));
+
+    // The invalid pattern is dropped, the others still apply
+    assert_eq!(excludes, ScanExcludes::new(vec![PathBuf::from("/music/skip")], &patterns[..3]));
+}
+
+#[test]
+fn test_scan_root_reachability() {
+    use std::time::Duration;
+
+    use crate::{is_root_reachable, probe_root, RootStatus};
+
+    let root = env::temp_dir().join("music-test-reachability");
+    let _ = fs::remove_dir_all(&root);
+    let timeout = Duration::from_secs(5);
+
+    assert_eq!(probe_root(&root, timeout), RootStatus::Unreachable);
+    assert!(!is_root_reachable(&root, false));
+
+    fs::create_dir_all(&root).unwrap();
+    assert_eq!(probe_root(&root, timeout), RootStatus::Empty);
+    // An empty root whose files are still cached looks like an unmounted share
+    assert!(!is_root_reachable(&root, true));
+    assert!(is_root_reachable(&root, false));
+
+    File::create(root.join("a.mp3")).unwrap();
+    assert_eq!(probe_root(&root, timeout), RootStatus::Available);
+    assert!(is_root_reachable(&root, true));
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_scan_progress_phases() {
+    use std::path::Path;
+
+    use crate::{progress::ProgressTracker, ProgressPhase};
+
+    let tracker = ProgressTracker::default();
+    assert!(!tracker.cancel(None));
+
+    let progress = tracker.begin("job");
+    assert_eq!(progress.phase, ProgressPhase::Discovery);
+    assert_eq!(progress.phases.len(), 4);
+
+    tracker.add_total(ProgressPhase::Metadata, 2);
+    // Moving to another phase is always reported
+    let progress = tracker.advance(ProgressPhase::Metadata, Path::new("/music/a.flac")).unwrap();
+    assert_eq!(progress.phase, ProgressPhase::Metadata);
+    assert_eq!(progress.phases[1].processed, 1);
+    assert!(progress.eta_secs.is_some());
+
+    tracker.add_total(ProgressPhase::DbWrite, 1);
+    assert!(tracker.cancel(None));
+    assert!(tracker.is_cancelled("job"));
+
+    let progress = tracker.finish(true).unwrap();
+    assert!(progress.cancelled);
+    assert!(!progress.finished);
+    assert!(!tracker.is_cancelled("job"));
+    assert!(!tracker.cancel(Some("job")));
+
+    assert!(tracker.record_written("other", 1).is_none());
+    let progress = tracker.record_written("job", 1).unwrap();
+    assert!(progress.finished);
+    assert_eq!(progress.current_file, None);
+}
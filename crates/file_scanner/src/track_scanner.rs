@@ -1,5 +1,6 @@
 use std::{path::PathBuf, sync::mpsc::Sender};
 
+use crate::exclude::ScanExcludes;
 use crate::utils::{check_directory, get_files_recursively, scan_file};
 use threadpool::ThreadPool;
 use types::errors::Result;
@@ -10,24 +11,31 @@ pub struct TrackScanner<'a> {
     pool: &'a mut ThreadPool,
     thumbnail_dir: PathBuf,
     artist_split: String,
+    excludes: ScanExcludes,
 }
 
 impl<'a> TrackScanner<'a> {
-    #[tracing::instrument(level = "debug", skip(dir, pool, thumbnail_dir, artist_split))]
+    #[tracing::instrument(level = "debug", skip(dir, pool, thumbnail_dir, artist_split, excludes))]
     pub fn new(
         dir: PathBuf,
         pool: &'a mut ThreadPool,
         thumbnail_dir: PathBuf,
         artist_split: String,
+        excludes: ScanExcludes,
     ) -> Self {
         Self {
             dir,
             pool,
             thumbnail_dir,
             artist_split,
+            excludes,
         }
     }
 
+    pub fn is_excluded(&self, path: &std::path::Path) -> bool {
+        self.excludes.is_excluded(path)
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     fn check_dirs(&self) -> Result<()> {
         check_directory(self.thumbnail_dir.clone())?;
@@ -63,7 +71,11 @@ impl<'a> TrackScanner<'a> {
 
         let file_list = get_files_recursively(self.dir.clone())?;
 
-        let track_list = file_list.file_list;
+        let track_list: Vec<(PathBuf, f64)> = file_list
+            .file_list
+            .into_iter()
+            .filter(|(path, _)| !self.excludes.is_excluded(path))
+            .collect();
 
         let len = track_list.len();
 
@@ -1,12 +1,9 @@
 use std::{
     fs,
     io::Read as _,
-    num::NonZeroU32,
     path::{Path, PathBuf},
 };
 
-use fast_image_resize::{self as fr, ResizeOptions};
-use image::ColorType;
 use lazy_static::lazy_static;
 use lofty::{
     file::{AudioFile, TaggedFileExt},
@@ -18,13 +15,16 @@ use lofty::{
 use md5;
 use regex::Regex;
 use types::{
-    entities::{QueryableAlbum, QueryableArtist, QueryableGenre},
+    entities::{QueryableAlbum, QueryableArtist, QueryableGenre, TrackMetadataEdit},
     errors::Result,
     tracks::{Tracks, MediaContent, TrackType},
+    ui::player_details::TrackGain,
 };
 use uuid::Uuid;
 
+use crate::artwork::{folder_artwork, store_artwork};
 use crate::types::FileList;
+use crate::walk::{walk_files, WalkOptions};
 
 use types::errors::error_helpers;
 
@@ -36,35 +36,14 @@ pub fn check_directory(dir: PathBuf) -> Result<()> {
     Ok(())
 }
 
-#[tracing::instrument(level = "debug", skip(thumbnail_dir, data))]
-fn store_picture_from_bytes(thumbnail_dir: &Path, data: &[u8]) -> Result<(PathBuf, PathBuf)> {
-    // Ensure target directory exists
-    if !thumbnail_dir.exists() {
-        fs::create_dir_all(thumbnail_dir)?;
-    }
-
-    let hash = blake3::hash(data).to_hex();
-    let hash_str = hash.as_str();
-
-    let low_path = thumbnail_dir.join(format!("{}-low.png", hash_str));
-    let high_path = thumbnail_dir.join(format!("{}.png", hash_str));
-
-    if !Path::new(high_path.to_str().unwrap()).exists() {
-        generate_image(data, high_path.clone(), 400)?;
-    }
-
-    if !Path::new(low_path.to_str().unwrap()).exists() {
-        generate_image(data, low_path.clone(), 80)?;
-    }
-
-    Ok((
-        dunce::canonicalize(high_path)?,
-        dunce::canonicalize(low_path)?,
-    ))
-}
-
 #[tracing::instrument(level = "debug", skip(dir))]
 pub fn get_files_recursively(dir: PathBuf) -> Result<FileList> {
+    get_files_with_options(dir, WalkOptions::default())
+}
+
+/// Collect tracks and playlists below `dir`, honouring the depth limit and
+/// symlink policy in `options`.
+pub fn get_files_with_options(dir: PathBuf, options: WalkOptions) -> Result<FileList> {
     let mut file_list: Vec<(PathBuf, f64)> = vec![];
     let mut playlist_list: Vec<PathBuf> = vec![];
 
@@ -80,38 +59,30 @@ pub fn get_files_recursively(dir: PathBuf) -> Result<FileList> {
         });
     }
 
-    if dir.is_file() {
-        if let Ok(metadata) = fs::metadata(&dir) {
-            let extension = dir
-                .extension()
-                .unwrap_or_default()
-                .to_str()
-                .unwrap_or_default();
-            if !extension.is_empty() {
-                if TRACK_RE.is_match(extension) {
-                    file_list.push((dir.clone(), metadata.len() as f64));
-                }
-
-                if PLAYLIST_RE.is_match(extension) {
-                    playlist_list.push(dir);
-                }
-            }
-            return Ok(FileList {
-                file_list,
-                playlist_list,
-            });
+    let mut classify = |path: PathBuf, size: u64| {
+        let extension = path
+            .extension()
+            .unwrap_or_default()
+            .to_str()
+            .unwrap_or_default();
+        if extension.is_empty() {
+            return;
+        }
+        if TRACK_RE.is_match(extension) {
+            file_list.push((path.clone(), size as f64));
         }
-    }
-
-    let dir_entries = fs::read_dir(dir)?;
 
-    for entry in dir_entries {
-        let Ok(entry) = entry else { continue };
-        let path = entry.path();
+        if PLAYLIST_RE.is_match(extension) {
+            playlist_list.push(path);
+        }
+    };
 
-        let res = get_files_recursively(path)?;
-        file_list.extend_from_slice(&res.file_list);
-        playlist_list.extend_from_slice(&res.playlist_list);
+    if dir.is_file() {
+        if let Ok(metadata) = fs::metadata(&dir) {
+            classify(dir, metadata.len());
+        }
+    } else {
+        walk_files(&dir, options, &mut classify)?;
     }
 
     Ok(FileList {
@@ -120,81 +91,6 @@ pub fn get_files_recursively(dir: PathBuf) -> Result<FileList> {
     })
 }
 
-#[tracing::instrument(level = "debug", skip(data, path, dimensions))]
-fn generate_image(data: &[u8], path: PathBuf, dimensions: u32) -> Result<()> {
-    let img = image::load_from_memory(data)
-        .map_err(error_helpers::to_media_error)?;
-
-    let width = NonZeroU32::new(img.width()).unwrap();
-    let height = NonZeroU32::new(img.height()).unwrap();
-    let src_image = fr::images::Image::from_vec_u8(
-        width.into(),
-        height.into(),
-        img.to_rgba8().into_vec(),
-        fr::PixelType::U8x4,
-    ).map_err(error_helpers::to_media_error)?;
-
-    // Create container for data of destination image
-    let dst_width = NonZeroU32::new(dimensions).unwrap();
-    let dst_height = NonZeroU32::new(dimensions).unwrap();
-    let mut dst_image =
-        fr::images::Image::new(dst_width.into(), dst_height.into(), src_image.pixel_type());
-
-    // Get mutable view of destination image data
-
-    // Create Resizer instance and resize source image
-    // into buffer of destination image
-    let mut resizer = fr::Resizer::new();
-    resizer.resize(
-        &src_image,
-        &mut dst_image,
-        Some(&ResizeOptions {
-            algorithm: fast_image_resize::ResizeAlg::Nearest,
-            ..Default::default()
-        }),
-    ).map_err(error_helpers::to_media_error)?;
-
-    // Save buffer and log any error explicitly to help diagnose why "saved" may not print
-    if let Err(e) = image::save_buffer(
-        path.clone(),
-        dst_image.buffer(),
-        dst_width.get(),
-        dst_height.get(),
-        ColorType::Rgba8,
-    ) {
-        return Err(error_helpers::to_media_error(e));
-    }
-    Ok(())
-}
-
-#[tracing::instrument(level = "debug", skip(thumbnail_dir, picture))]
-fn store_picture(thumbnail_dir: &Path, picture: &Picture) -> Result<(PathBuf, PathBuf)> {
-    let data = picture.data();
-    let hash = blake3::hash(data).to_hex();
-    let hash_str = hash.as_str();
-    
-    // Ensure target directory exists to avoid save failures
-    if !thumbnail_dir.exists() {
-        fs::create_dir_all(thumbnail_dir)?;
-    }
-
-    let low_path = thumbnail_dir.join(format!("{}-low.png", hash_str));
-    let high_path = thumbnail_dir.join(format!("{}.png", hash_str));
-
-    if !Path::new(high_path.to_str().unwrap()).exists() {
-        generate_image(data, high_path.clone(), 400)?;
-    }
-
-    if !Path::new(low_path.to_str().unwrap()).exists() {
-        generate_image(data, low_path.clone(), 80)?;
-    }
-
-    Ok((
-        dunce::canonicalize(high_path)?,
-        dunce::canonicalize(low_path)?,
-    ))
-}
-
 #[tracing::instrument(level = "debug", skip(path))]
 fn scan_lrc(mut path: PathBuf) -> Option<String> {
     path.set_extension("lrc");
@@ -223,6 +119,144 @@ fn scan_lrc(mut path: PathBuf) -> Option<String> {
     None
 }
 
+/// Lyrics embedded in the tags of the audio file at `path`, as stored (LRC
+/// time tags are kept).
+#[tracing::instrument(level = "debug")]
+pub fn read_embedded_lyrics(path: &Path) -> Option<String> {
+    let file = read_from_path(path).ok()?;
+    let tag = file.primary_tag().or_else(|| file.first_tag())?;
+    tag.get_string(&lofty::prelude::ItemKey::Lyrics)
+        .map(str::to_string)
+        .filter(|l| !l.trim().is_empty())
+}
+
+/// ReplayGain analysis stored in the tags of the audio file at `path`, `None`
+/// when it can't be read. Gains are written as "-6.54 dB", peaks as a linear
+/// amplitude.
+#[tracing::instrument(level = "debug")]
+pub fn read_replay_gain(path: &Path) -> Option<TrackGain> {
+    use lofty::prelude::ItemKey;
+
+    let file = read_from_path(path).ok()?;
+    let tag = file.primary_tag().or_else(|| file.first_tag())?;
+    let value = |key: &ItemKey| {
+        let number = tag.get_string(key)?.trim().trim_end_matches(|c: char| c.is_ascii_alphabetic());
+        number.trim().parse::<f64>().ok().filter(|v| v.is_finite())
+    };
+    Some(TrackGain {
+        track_gain: value(&ItemKey::ReplayGainTrackGain),
+        track_peak: value(&ItemKey::ReplayGainTrackPeak),
+        album_gain: value(&ItemKey::ReplayGainAlbumGain),
+        album_peak: value(&ItemKey::ReplayGainAlbumPeak),
+    })
+}
+
+/// Contents of the `.lrc` file next to the audio file at `path`, time tags
+/// included (unlike the lyrics stored at scan time).
+#[tracing::instrument(level = "debug")]
+pub fn read_lrc_sidecar(path: &Path) -> Option<String> {
+    let data = fs::read(path.with_extension("lrc")).ok()?;
+    let text = String::from_utf8_lossy(&data);
+    // Strip a UTF-8 BOM some editors write
+    let text = text.trim_start_matches('\u{feff}');
+    (!text.trim().is_empty()).then(|| text.to_string())
+}
+
+/// Store `image` as the front cover in the tags of the audio file at `path`,
+/// replacing any existing front cover. Creates the file's primary tag when it
+/// has none.
+#[tracing::instrument(level = "debug", skip(image))]
+pub fn embed_cover(path: &Path, image: &[u8]) -> Result<()> {
+    use lofty::config::WriteOptions;
+    use lofty::picture::PictureType;
+    use lofty::tag::{Tag, TagExt};
+
+    let mut picture = Picture::from_reader(&mut &image[..]).map_err(error_helpers::to_media_error)?;
+    picture.set_pic_type(PictureType::CoverFront);
+
+    let mut file = read_from_path(path).map_err(error_helpers::to_media_error)?;
+    if file.primary_tag().is_none() {
+        let tag_type = file.primary_tag_type();
+        file.insert_tag(Tag::new(tag_type));
+    }
+    let Some(tag) = file.primary_tag_mut() else {
+        return Ok(());
+    };
+    tag.remove_picture_type(PictureType::CoverFront);
+    tag.push_picture(picture);
+    tag.save_to_path(path, WriteOptions::default())
+        .map_err(error_helpers::to_media_error)?;
+    Ok(())
+}
+
+/// Write the fields set in `edit` to the tags of the audio file at `path`
+/// (ID3v2, Vorbis comments, MP4 atoms, ... depending on the format). Artists
+/// are joined with `artist_split` so a rescan splits them the same way.
+/// Creates the file's primary tag when it has none.
+#[tracing::instrument(level = "debug", skip(edit))]
+pub fn write_tags(path: &Path, edit: &TrackMetadataEdit, artist_split: &str) -> Result<()> {
+    use lofty::config::WriteOptions;
+    use lofty::tag::{Tag, TagExt};
+
+    let mut file = read_from_path(path).map_err(error_helpers::to_media_error)?;
+    if file.primary_tag().is_none() {
+        let tag_type = file.primary_tag_type();
+        file.insert_tag(Tag::new(tag_type));
+    }
+    let Some(tag) = file.primary_tag_mut() else {
+        return Err(format!("{:?} can't hold tags", path).into());
+    };
+
+    if let Some(title) = &edit.title {
+        tag.set_title(title.clone());
+    }
+    if let Some(artists) = &edit.artists {
+        tag.set_artist(artists.join(artist_split));
+    }
+    if let Some(album) = &edit.album {
+        tag.set_album(album.clone());
+    }
+    if let Some(genre) = &edit.genre {
+        tag.set_genre(genre.clone());
+    }
+    if let Some(track_no) = edit.track_no {
+        tag.set_track(track_no);
+    }
+    if let Some(year) = &edit.year {
+        // Tags hold the year alone, "2019-05-03" is written as 2019
+        let Some(year) = year.get(..4).and_then(|y| y.parse().ok()) else {
+            return Err(format!("Invalid year {:?}", year).into());
+        };
+        tag.set_year(year);
+    }
+    tag.save_to_path(path, WriteOptions::default())
+        .map_err(error_helpers::to_media_error)?;
+    Ok(())
+}
+
+/// Usual extension of the audio file at `path`, detected from its contents.
+#[tracing::instrument(level = "debug")]
+pub fn audio_extension(path: &Path) -> Option<&'static str> {
+    use lofty::file::FileType;
+
+    let file_type = Probe::open(path).ok()?.guess_file_type().ok()?.file_type()?;
+    Some(match file_type {
+        FileType::Aac => "aac",
+        FileType::Aiff => "aiff",
+        FileType::Ape => "ape",
+        FileType::Flac => "flac",
+        FileType::Mpeg => "mp3",
+        FileType::Mp4 => "m4a",
+        FileType::Mpc => "mpc",
+        FileType::Opus => "opus",
+        FileType::Vorbis => "ogg",
+        FileType::Speex => "spx",
+        FileType::Wav => "wav",
+        FileType::WavPack => "wv",
+        _ => return None,
+    })
+}
+
 #[tracing::instrument(level = "debug", skip(path))]
 fn calculate_file_md5(path: &PathBuf) -> Result<String> {
     let data = fs::read(path)?;
@@ -231,10 +265,66 @@ fn calculate_file_md5(path: &PathBuf) -> Result<String> {
     Ok(format!("{:x}", digest))
 }
 
-#[tracing::instrument(level = "debug", skip(path, thumbnail_dir, size, guess, artist_split))]
+/// Metadata of the audio file at `path`, its cover stored in `artwork_dir`
+#[tracing::instrument(level = "debug", skip(path, artwork_dir, size, guess, artist_split))]
 pub fn scan_file(
     path: &PathBuf,
-    thumbnail_dir: &Path,
+    artwork_dir: &Path,
+    size: f64,
+    guess: bool,
+    artist_split: &str,
+) -> Result<MediaContent> {
+    read_track(path, Some(artwork_dir), size, guess, artist_split)
+}
+
+/// Metadata of the audio file at `path` without its cover, stored later by
+/// `scan_cover`
+#[tracing::instrument(level = "debug", skip(path, size, guess, artist_split))]
+pub fn scan_file_metadata(path: &PathBuf, size: f64, guess: bool, artist_split: &str) -> Result<MediaContent> {
+    read_track(path, None, size, guess, artist_split)
+}
+
+/// Store the cover of the track scanned from `path`, embedded or a folder
+/// image, in `artwork_dir` and set it on the track and its album
+#[tracing::instrument(level = "debug", skip(track, path, artwork_dir))]
+pub fn scan_cover(track: &mut MediaContent, path: &Path, artwork_dir: &Path) {
+    let file = Probe::open(path)
+        .ok()
+        .and_then(|probe| probe.guess_file_type().ok())
+        .and_then(|probe| probe.read().ok());
+    let embedded = file
+        .as_ref()
+        .and_then(|file| file.tags().iter().find_map(|tag| tag.pictures().first()))
+        .map(|picture| picture.data());
+    store_track_cover(track, path, artwork_dir, embedded);
+    if let Some(album) = track.album.as_mut() {
+        if album.album_coverpath_high.is_none() {
+            album.album_coverpath_high = track.track.track_cover_path_high.clone();
+            album.album_coverpath_low = track.track.track_cover_path_low.clone();
+        }
+    }
+}
+
+fn store_track_cover(track: &mut MediaContent, path: &Path, artwork_dir: &Path, embedded: Option<&[u8]>) {
+    let cover = match embedded {
+        Some(data) => Some(store_artwork(artwork_dir, data)),
+        None => path.parent().and_then(folder_artwork).map(|img_path| {
+            fs::read(&img_path).map_err(Into::into).and_then(|bytes| store_artwork(artwork_dir, &bytes))
+        }),
+    };
+    match cover {
+        Some(Ok((high_path, low_path))) => {
+            track.track.track_cover_path_high = Some(high_path.to_string_lossy().to_string());
+            track.track.track_cover_path_low = Some(low_path.to_string_lossy().to_string());
+        }
+        Some(Err(e)) => tracing::error!("Error storing the cover of {:?}: {:?}", path, e),
+        None => {}
+    }
+}
+
+fn read_track(
+    path: &PathBuf,
+    artwork_dir: Option<&Path>,
     size: f64,
     guess: bool,
     artist_split: &str,
@@ -288,69 +378,14 @@ pub fn scan_file(
     track.track.sample_rate = properties.sample_rate().map(|v| v as f64);
     track.track.duration = Some(properties.duration().as_secs() as f64);
 
+    if let Some(artwork_dir) = artwork_dir {
+        let embedded = file.tags().iter().find_map(|tag| tag.pictures().first());
+        store_track_cover(&mut track, path, artwork_dir, embedded.map(|picture| picture.data()));
+    }
+
     if tags.is_some() {
         let metadata = tags.unwrap();
 
-        let mut found_picture: Option<&Picture> = None;
-        for tag in file.tags() {
-            if let Some(p) = tag.pictures().first() {
-                found_picture = Some(p);
-                break;
-            }
-        }
-
-        if let Some(picture) = found_picture.or_else(|| metadata.pictures().first()) {
-            match store_picture(thumbnail_dir, picture) {
-                Ok((high_path, low_path)) => {
-                    track.track.track_cover_path_high = Some(high_path.to_string_lossy().to_string());
-                    track.track.track_cover_path_low = Some(low_path.to_string_lossy().to_string());
-                }
-                Err(e) => {
-                    tracing::error!("Error storing picture {:?}", e);
-                }
-            }
-        } else {
-            let mut base_path = path.clone();
-            base_path.pop();
-            let files_res = base_path.read_dir();
-            if let Ok(files) = files_res {
-                let mut fallback_image: Option<PathBuf> = None;
-                for entry in files.flatten() {
-                    let p = entry.path();
-                    if !p.is_file() { continue; }
-                    let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
-                    let ext = p.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
-                    let name_match = stem.starts_with("cover")
-                        || stem.starts_with("folder")
-                        || stem.starts_with("front")
-                        || stem.starts_with("album")
-                        || stem.starts_with("art");
-                    let ext_match = matches!(ext.as_str(), "jpg" | "jpeg" | "png" | "webp");
-                    if name_match && ext_match {
-                        fallback_image = Some(p.clone());
-                        break;
-                    }
-                }
-
-                if let Some(img_path) = fallback_image {
-                    match fs::read(&img_path) {
-                        Ok(bytes) => {
-                            match store_picture_from_bytes(thumbnail_dir, &bytes) {
-                                Ok((high_path, low_path)) => {
-                                    track.track.track_cover_path_high = Some(high_path.to_string_lossy().to_string());
-                                    track.track.track_cover_path_low = Some(low_path.to_string_lossy().to_string());
-                                }
-                                Err(e) => tracing::error!("Error generating thumbnails from fallback image {:?}: {:?}", img_path, e),
-                            }
-                        }
-                        Err(e) => {
-                            tracing::error!("Error reading fallback image {:?}: {:?}", img_path, e);
-                        }
-                    }
-                }
-            }
-        }
-
         let mut lyrics = metadata
             .get_string(&lofty::prelude::ItemKey::Lyrics)
             .map(str::to_string);
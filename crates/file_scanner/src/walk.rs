@@ -0,0 +1,159 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use tracing::debug;
+use types::settings::general::{ScanRootOverride, ScanSymlinkPolicy};
+
+/// 目录遍历规则：最大深度与符号链接策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WalkOptions {
+    /// 扫描根目录之下最多进入的目录层数，None 表示不限（0 只扫描根目录本身）
+    pub max_depth: Option<usize>,
+    pub symlinks: ScanSymlinkPolicy,
+}
+
+impl WalkOptions {
+    /// 位于根目录之下 `depth` 层目录中的文件是否在深度限制之内
+    pub fn allows_depth(&self, depth: usize) -> bool {
+        self.max_depth.map_or(true, |max| depth <= max)
+    }
+}
+
+/// 全局遍历规则，以及按扫描目录的覆盖规则
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WalkPolicy {
+    pub default: WalkOptions,
+    /// 覆盖规则所在目录及合并后的规则
+    pub overrides: Vec<(PathBuf, WalkOptions)>,
+}
+
+impl WalkPolicy {
+    pub fn new(max_depth: Option<u32>, symlinks: Option<ScanSymlinkPolicy>, overrides: &[ScanRootOverride]) -> Self {
+        let default = WalkOptions {
+            max_depth: max_depth.map(|d| d as usize),
+            symlinks: symlinks.unwrap_or_default(),
+        };
+        let overrides = overrides
+            .iter()
+            .filter(|o| !o.root.trim().is_empty())
+            .map(|o| {
+                let options = WalkOptions {
+                    max_depth: o.max_depth.map(|d| d as usize).or(default.max_depth),
+                    symlinks: o.symlinks.unwrap_or(default.symlinks),
+                };
+                (PathBuf::from(o.root.trim()), options)
+            })
+            .collect();
+        Self { default, overrides }
+    }
+
+    /// 适用于 `path` 的规则：包含它的最深的覆盖目录优先
+    pub fn options_for(&self, path: &Path) -> WalkOptions {
+        self.overrides
+            .iter()
+            .filter(|(root, _)| path.starts_with(root))
+            .max_by_key(|(root, _)| root.components().count())
+            .map(|(_, options)| *options)
+            .unwrap_or(self.default)
+    }
+
+    /// 监控到的文件变化是否在规则之内：按所属扫描根目录计算文件所在目录的层数
+    pub fn allows(&self, scan_roots: &[PathBuf], path: &Path) -> bool {
+        let Some(root) = scan_roots
+            .iter()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+        else {
+            return true;
+        };
+        let options = self.options_for(root);
+        if options.symlinks == ScanSymlinkPolicy::Never && is_symlink(path) {
+            return false;
+        }
+        let depth = path
+            .strip_prefix(root)
+            .map(|rel| rel.components().count().saturating_sub(1))
+            .unwrap_or(0);
+        options.allows_depth(depth)
+    }
+}
+
+fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false)
+}
+
+/// 遍历 `root` 下的文件，回调文件路径与大小。根目录本身总会被进入（即使是链接）；
+/// 按策略跟随符号链接，并检测链接造成的循环。
+pub fn walk_files(root: &Path, options: WalkOptions, visit: &mut dyn FnMut(PathBuf, u64)) -> std::io::Result<()> {
+    let mut walker = Walker {
+        options,
+        visited: HashSet::new(),
+        ancestors: Vec::new(),
+        visit,
+    };
+    let entries = fs::read_dir(root)?;
+    let canonical = dunce::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    walker.visited.insert(canonical.clone());
+    walker.ancestors.push(canonical);
+    walker.walk_entries(entries, 0);
+    Ok(())
+}
+
+struct Walker<'a> {
+    options: WalkOptions,
+    /// Safe 策略下已进入过的目录（规范路径），每个目录只扫描一次
+    visited: HashSet<PathBuf>,
+    /// 当前路径上的目录（规范路径），用于发现指回上层的链接
+    ancestors: Vec<PathBuf>,
+    visit: &'a mut dyn FnMut(PathBuf, u64),
+}
+
+impl Walker<'_> {
+    fn walk_entries(&mut self, entries: fs::ReadDir, depth: usize) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else { continue };
+            let is_link = file_type.is_symlink();
+            if is_link && self.options.symlinks == ScanSymlinkPolicy::Never {
+                continue;
+            }
+            // 跟随链接取目标信息，失效的链接直接跳过
+            let Ok(metadata) = fs::metadata(&path) else {
+                debug!("Skipping unreadable entry {:?}", path);
+                continue;
+            };
+            if metadata.is_file() {
+                (self.visit)(path, metadata.len());
+            } else if metadata.is_dir() && self.options.allows_depth(depth + 1) {
+                self.enter(path, depth + 1);
+            }
+        }
+    }
+
+    fn enter(&mut self, dir: PathBuf, depth: usize) {
+        let canonical = dunce::canonicalize(&dir).unwrap_or_else(|_| dir.clone());
+        if self.ancestors.contains(&canonical) {
+            debug!("Skipping symlink loop at {:?}", dir);
+            return;
+        }
+        if self.options.symlinks == ScanSymlinkPolicy::Safe && !self.visited.insert(canonical.clone()) {
+            debug!("Skipping already scanned folder {:?}", dir);
+            return;
+        }
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                debug!("Failed to read {:?}: {}", dir, e);
+                return;
+            }
+        };
+        self.ancestors.push(canonical);
+        self.walk_entries(entries, depth);
+        self.ancestors.pop();
+    }
+}
@@ -4,7 +4,7 @@ macro_rules! generate_command {
         #[tracing::instrument(level = "debug", skip(db))]
         #[tauri_invoke_proc::parse_tauri_command]
         #[tauri::command(async)]
-        pub fn $method_name(db: State<$state>, $($v: $t),*) -> types::errors::Result<$ret> {
+        pub fn $method_name(db: State<$state>, $($v: $t),*) -> types::errors::CommandResult<$ret> {
             tracing::debug!("calling {}", stringify!($method_name));
             let ret = db.$method_name($($v,)*);
             if let Ok(ret) = &ret {
@@ -12,7 +12,7 @@ macro_rules! generate_command {
             } else {
                 tracing::error!("Error getting result {:?}", ret);
             }
-            ret
+            Ok(ret.into())
         }
     };
 }
@@ -24,7 +24,7 @@ macro_rules! generate_command_cached {
         #[tracing::instrument(level = "debug", skip(db, cache))]
         #[tauri_invoke_proc::parse_tauri_command]
         #[tauri::command(async)]
-        pub async fn $method_name(db: State<'_, $state>, cache: State<'_, CacheHolder>, $($v: $t,)* invalidate_cache: bool) -> types::errors::Result<$ret> {
+        pub async fn $method_name(db: State<'_, $state>, cache: State<'_, CacheHolder>, $($v: $t,)* invalidate_cache: bool) -> types::errors::CommandResult<$ret> {
             let mut cache_string = String::new();
             cache_string.push_str(stringify!($method_name));
             $(
@@ -41,7 +41,7 @@ macro_rules! generate_command_cached {
             };
 
             if cached.is_ok() {
-                return cached;
+                return Ok(cached.into());
             }
 
             let res = db.$method_name($($v,)*);
@@ -59,7 +59,7 @@ macro_rules! generate_command_cached {
                     tracing::error!("Error getting result {:?}", e);
                 }
             }
-            res
+            Ok(res.into())
         }
     };
 }
@@ -71,7 +71,7 @@ macro_rules! generate_command_async {
         #[tracing::instrument(level = "debug", skip(db))]
         #[tauri_invoke_proc::parse_tauri_command]
         #[tauri::command(async)]
-        pub async fn $method_name(db: State<'_, $state>, $($v: $t),*) -> types::errors::Result<$ret> {
+        pub async fn $method_name(db: State<'_, $state>, $($v: $t),*) -> types::errors::CommandResult<$ret> {
             tracing::debug!("calling async {}", stringify!($method_name));
             let ret = db.$method_name($($v,)*).await;
             if let Ok(ret) = &ret {
@@ -79,7 +79,7 @@ macro_rules! generate_command_async {
             } else {
                 tracing::error!("Error getting result {:?}", ret);
             }
-            ret
+            Ok(ret.into())
         }
     };
 }
@@ -91,7 +91,7 @@ macro_rules! generate_command_async_cached {
         #[tracing::instrument(level = "debug", skip(db, cache))]
         #[tauri_invoke_proc::parse_tauri_command]
         #[tauri::command(async)]
-        pub async fn $method_name(db: State<'_, $state>, cache: State<'_, CacheHolder>, $($v: $t,)* invalidate_cache: bool) -> types::errors::Result<$ret> {
+        pub async fn $method_name(db: State<'_, $state>, cache: State<'_, CacheHolder>, $($v: $t,)* invalidate_cache: bool) -> types::errors::CommandResult<$ret> {
             let mut cache_string = String::new();
             cache_string.push_str(stringify!($method_name));
             $(
@@ -110,7 +110,7 @@ macro_rules! generate_command_async_cached {
 
             if cached.is_ok() {
                 tracing::debug!("got cached data");
-                return cached;
+                return Ok(cached.into());
             }
 
             let res = db.$method_name($($v,)*).await;
@@ -128,7 +128,7 @@ macro_rules! generate_command_async_cached {
                     tracing::error!("Error getting result {:?}", e);
                 }
             }
-            res
+            Ok(res.into())
         }
     };
 }
@@ -0,0 +1,31 @@
+/// Wrap a command returning `types::errors::Result<T>` so the frontend receives a
+/// `types::errors::CommandResponse<T>` instead of a bare value or error string.
+///
+/// The original body runs in an inner function with the same arguments, so `?`
+/// and early returns keep working. Attributes (`#[tauri::command]`,
+/// `#[tracing::instrument]`, ...) are applied to the outer function.
+#[macro_export]
+macro_rules! command_envelope {
+    (
+        $(#[$meta:meta])*
+        $vis:vis async fn $name:ident($($arg:ident: $t:ty),* $(,)?) -> $result:ident<$ret:ty> $body:block
+    ) => {
+        $(#[$meta])*
+        $vis async fn $name($($arg: $t),*) -> types::errors::CommandResult<$ret> {
+            #[allow(non_snake_case)]
+            async fn inner($($arg: $t),*) -> $result<$ret> $body
+            Ok(inner($($arg),*).await.into())
+        }
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis fn $name:ident($($arg:ident: $t:ty),* $(,)?) -> $result:ident<$ret:ty> $body:block
+    ) => {
+        $(#[$meta])*
+        $vis fn $name($($arg: $t),*) -> types::errors::CommandResult<$ret> {
+            #[allow(non_snake_case)]
+            fn inner($($arg: $t),*) -> $result<$ret> $body
+            Ok(inner($($arg),*).into())
+        }
+    };
+}
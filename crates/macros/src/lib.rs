@@ -1,2 +1,3 @@
 pub mod command_macro;
 pub mod database_macro;
+pub mod envelope_macro;
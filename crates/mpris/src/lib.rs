@@ -1,11 +1,13 @@
-#[cfg(not(any(target_os = "android", target_os = "ios")))]
-mod mpris;
+// Windows and macOS media controls live in audio-player (os_media)
 
-#[cfg(not(any(target_os = "android", target_os = "ios")))]
-pub use mpris::{MediaControlEvent, MprisHolder};
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "android", target_os = "ios"))))]
+mod mpris_dbus;
+
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "android", target_os = "ios"))))]
+pub use mpris_dbus::{MediaControlEvent, MprisHolder, SeekDirection};
 
 #[cfg(target_os = "android")]
 pub mod mpris_android;
 
 #[cfg(target_os = "android")]
-pub use mpris_android::{MediaControlEvent, MprisHolder};
+pub use mpris_android::{MediaControlEvent, MprisHolder, SeekDirection};
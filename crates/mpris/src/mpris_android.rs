@@ -10,7 +10,11 @@ use std::{
 use serde_json::Value;
 use tauri::{AppHandle, Listener};
 use tauri_plugin_audioplayer::AudioplayerExt;
-use types::{errors::Result, mpris::MprisPlayerDetails, ui::player_details::PlayerState};
+use types::{
+    errors::Result,
+    mpris::MprisPlayerDetails,
+    ui::player_details::{PlayerMode, PlayerState},
+};
 
 pub struct MprisHolder {
     last_duration: Mutex<u64>,
@@ -111,6 +115,21 @@ impl MprisHolder {
         }
         Ok(())
     }
+
+    /// The position jumped (seek); reported like any other position update.
+    pub fn seeked(&self, position: f64) -> Result<()> {
+        self.set_position(position)
+    }
+
+    /// Loop and shuffle state have no counterpart in these media controls.
+    pub fn set_player_mode(&self, _mode: PlayerMode) -> Result<()> {
+        Ok(())
+    }
+
+    /// The notification shows no playback rate.
+    pub fn set_rate(&self, _rate: f64) -> Result<()> {
+        Ok(())
+    }
 }
 
 /// Events sent by the OS media controls.
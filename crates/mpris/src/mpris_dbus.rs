@@ -0,0 +1,493 @@
+// MPRIS server on the D-Bus session bus (Linux and the BSDs). souvlaki only
+// exposes the basic player state there; GNOME and KDE media controls also read
+// a live Position, CanSeek, the Seeked signal, LoopStatus/Shuffle and the
+// artwork URL, so the interfaces are served directly with dbus-crossroads.
+
+use std::sync::{
+    mpsc::{self, Receiver, Sender, TryRecvError},
+    Arc, Mutex,
+};
+use std::time::{Duration, Instant};
+
+use dbus::arg::{PropMap, RefArg, Variant};
+use dbus::blocking::stdintf::org_freedesktop_dbus::PropertiesPropertiesChanged;
+use dbus::blocking::Connection;
+use dbus::channel::{MatchingReceiver, Sender as _};
+use dbus::message::{MatchRule, SignalArgs};
+use dbus::{Message, Path};
+use dbus_crossroads::{Crossroads, IfaceBuilder, MethodErr};
+use types::{
+    errors::{MusicError, Result},
+    mpris::MprisPlayerDetails,
+    ui::player_details::{PlayerMode, PlayerState},
+};
+
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.music";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+const ROOT_IFACE: &str = "org.mpris.MediaPlayer2";
+const PLAYER_IFACE: &str = "org.mpris.MediaPlayer2.Player";
+const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+const IDENTITY: &str = "Music";
+/// Playback rates the player supports
+const MIN_RATE: f64 = 0.5;
+const MAX_RATE: f64 = 3.0;
+
+/// Events sent by the desktop media controls.
+#[derive(Clone, PartialEq, Debug)]
+pub enum MediaControlEvent {
+    Play,
+    Pause,
+    Toggle,
+    Next,
+    Previous,
+    Stop,
+
+    /// Seek forward or backward by a certain amount.
+    SeekBy(SeekDirection, Duration),
+    /// Set the position/progress of the currently playing media item.
+    SetPosition(MediaPosition),
+    /// LoopStatus or Shuffle was changed; the player mode combining both.
+    SetPlayerMode(PlayerMode),
+    /// The Rate property was set, within MinimumRate and MaximumRate.
+    SetRate(f64),
+    /// Open the URI in the media player.
+    OpenUri(String),
+
+    /// Bring the media player's user interface to the front using any appropriate mechanism available.
+    Raise,
+    /// Shut down the media player.
+    Quit,
+}
+
+/// An instant in a media item.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MediaPosition(pub Duration);
+
+/// The direction to seek in.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SeekDirection {
+    Forward,
+    Backward,
+}
+
+/// Player properties shared between the holder and the D-Bus thread
+struct PlayerProps {
+    metadata: Option<MprisPlayerDetails>,
+    /// Changes with every track so that SetPosition calls for a previous
+    /// track can be told apart
+    track_no: u64,
+    state: PlayerState,
+    mode: PlayerMode,
+    /// Seconds at `updated`; advances with the clock, at `rate`, while playing
+    position: f64,
+    updated: Instant,
+    rate: f64,
+    events: Sender<MediaControlEvent>,
+}
+
+impl PlayerProps {
+    fn send(&self, event: MediaControlEvent) {
+        let _ = self.events.send(event);
+    }
+
+    fn duration(&self) -> Option<f64> {
+        self.metadata.as_ref().and_then(|m| m.duration).filter(|d| *d > 0.0)
+    }
+
+    fn track_id(&self) -> Path<'static> {
+        match self.metadata {
+            Some(_) => Path::from(format!("{}/Track/{}", OBJECT_PATH, self.track_no)),
+            None => Path::from(NO_TRACK),
+        }
+    }
+
+    fn current_position(&self) -> f64 {
+        let mut position = self.position;
+        if self.state == PlayerState::Playing {
+            position += self.updated.elapsed().as_secs_f64() * self.rate;
+        }
+        match self.duration() {
+            Some(duration) => position.clamp(0.0, duration),
+            None => position.max(0.0),
+        }
+    }
+
+    fn set_position(&mut self, position: f64) {
+        self.position = position;
+        self.updated = Instant::now();
+    }
+
+    fn playback_status(&self) -> String {
+        let status = match self.state {
+            PlayerState::Playing => "Playing",
+            PlayerState::Paused | PlayerState::Loading => "Paused",
+            PlayerState::Stopped | PlayerState::Idle | PlayerState::Errored => "Stopped",
+        };
+        status.to_string()
+    }
+
+    fn loop_status(&self) -> String {
+        let status = match self.mode {
+            PlayerMode::Single => "Track",
+            PlayerMode::ListLoop => "Playlist",
+            PlayerMode::Sequential | PlayerMode::Shuffle | PlayerMode::ShuffleAlbums => "None",
+        };
+        status.to_string()
+    }
+
+    fn can_seek(&self) -> bool {
+        self.duration().is_some()
+    }
+
+    fn metadata_map(&self) -> PropMap {
+        fn variant<T: RefArg + 'static>(value: T) -> Variant<Box<dyn RefArg>> {
+            Variant(Box::new(value))
+        }
+
+        let mut map = PropMap::new();
+        map.insert("mpris:trackid".to_string(), variant(self.track_id()));
+        let Some(metadata) = &self.metadata else {
+            return map;
+        };
+        if let Some(title) = &metadata.title {
+            map.insert("xesam:title".to_string(), variant(title.clone()));
+        }
+        if let Some(artist) = &metadata.artist_name {
+            map.insert("xesam:artist".to_string(), variant(vec![artist.clone()]));
+        }
+        if let Some(album) = &metadata.album_name {
+            map.insert("xesam:album".to_string(), variant(album.clone()));
+        }
+        if let Some(album_artist) = &metadata.album_artist {
+            map.insert("xesam:albumArtist".to_string(), variant(vec![album_artist.clone()]));
+        }
+        if let Some(genres) = metadata.genres.clone().filter(|g| !g.is_empty()) {
+            map.insert("xesam:genre".to_string(), variant(genres));
+        }
+        if let Some(duration) = self.duration() {
+            map.insert("mpris:length".to_string(), variant(micros(duration)));
+        }
+        if let Some(art) = &metadata.thumbnail {
+            map.insert("mpris:artUrl".to_string(), variant(art.clone()));
+        }
+        map
+    }
+
+    /// Value of a Player property that notifies its changes
+    fn player_property(&self, name: &str) -> Option<Variant<Box<dyn RefArg>>> {
+        let value: Box<dyn RefArg> = match name {
+            "PlaybackStatus" => Box::new(self.playback_status()),
+            "LoopStatus" => Box::new(self.loop_status()),
+            "Shuffle" => Box::new(self.mode.is_shuffle()),
+            "Metadata" => Box::new(self.metadata_map()),
+            "CanSeek" => Box::new(self.can_seek()),
+            "Rate" => Box::new(self.rate),
+            _ => return None,
+        };
+        Some(Variant(value))
+    }
+}
+
+type Shared = Arc<Mutex<PlayerProps>>;
+
+fn micros(seconds: f64) -> i64 {
+    (seconds * 1_000_000.0) as i64
+}
+
+fn seconds(micros: i64) -> f64 {
+    micros as f64 / 1_000_000.0
+}
+
+/// Changes for the D-Bus thread to announce
+enum Notification {
+    Changed(&'static [&'static str]),
+    Seeked(f64),
+}
+
+pub struct MprisHolder {
+    props: Shared,
+    notify: Mutex<Sender<Notification>>,
+    pub event_rx: Arc<Mutex<Receiver<MediaControlEvent>>>,
+}
+
+impl MprisHolder {
+    #[tracing::instrument(level = "debug", skip())]
+    pub fn new() -> Result<MprisHolder> {
+        let conn = Connection::new_session().map_err(dbus_error)?;
+        conn.request_name(BUS_NAME, false, true, false).map_err(dbus_error)?;
+
+        let (event_tx, event_rx) = mpsc::channel();
+        let props = Arc::new(Mutex::new(PlayerProps {
+            metadata: None,
+            track_no: 0,
+            state: PlayerState::Stopped,
+            mode: PlayerMode::default(),
+            position: 0.0,
+            updated: Instant::now(),
+            rate: 1.0,
+            events: event_tx,
+        }));
+
+        let mut cr = Crossroads::new();
+        let root = cr.register(ROOT_IFACE, register_root);
+        let player = cr.register(PLAYER_IFACE, register_player);
+        cr.insert(OBJECT_PATH, &[root, player], props.clone());
+        conn.start_receive(
+            MatchRule::new_method_call(),
+            Box::new(move |msg, conn| {
+                let _ = cr.handle_message(msg, conn);
+                true
+            }),
+        );
+
+        let (notify_tx, notify_rx) = mpsc::channel();
+        let props_for_thread = props.clone();
+        std::thread::spawn(move || serve(conn, props_for_thread, notify_rx));
+
+        Ok(MprisHolder {
+            props,
+            notify: Mutex::new(notify_tx),
+            event_rx: Arc::new(Mutex::new(event_rx)),
+        })
+    }
+
+    fn update(&self, f: impl FnOnce(&mut PlayerProps), notification: Option<Notification>) -> Result<()> {
+        {
+            let mut props = self.props.lock().map_err(|_| MusicError::String("MPRIS state poisoned".into()))?;
+            f(&mut props);
+        }
+        let Some(notification) = notification else {
+            return Ok(());
+        };
+        self.notify
+            .lock()
+            .map_err(|_| MusicError::String("MPRIS state poisoned".into()))?
+            .send(notification)
+            .map_err(|_| MusicError::String("MPRIS server stopped".into()))
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, metadata))]
+    pub fn set_metadata(&self, metadata: MprisPlayerDetails) -> Result<()> {
+        self.update(
+            |props| {
+                props.metadata = Some(metadata);
+                props.track_no += 1;
+                props.set_position(0.0);
+            },
+            Some(Notification::Changed(&["Metadata", "CanSeek"])),
+        )
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, state))]
+    pub fn set_playback_state(&self, state: PlayerState) -> Result<()> {
+        self.update(
+            |props| {
+                // Freeze or restart the clock at the current position
+                let position = props.current_position();
+                props.state = state;
+                props.set_position(position);
+            },
+            Some(Notification::Changed(&["PlaybackStatus"])),
+        )
+    }
+
+    /// Resynchronize the position; clients read it on demand.
+    #[tracing::instrument(level = "debug", skip(self, position))]
+    pub fn set_position(&self, position: f64) -> Result<()> {
+        self.update(|props| props.set_position(position), None)
+    }
+
+    /// The position jumped (seek): clients are told through the Seeked signal.
+    #[tracing::instrument(level = "debug", skip(self, position))]
+    pub fn seeked(&self, position: f64) -> Result<()> {
+        self.update(|props| props.set_position(position), Some(Notification::Seeked(position)))
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_player_mode(&self, mode: PlayerMode) -> Result<()> {
+        self.update(
+            |props| props.mode = mode,
+            Some(Notification::Changed(&["LoopStatus", "Shuffle"])),
+        )
+    }
+
+    /// The position keeps advancing from where it is, at the new rate.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub fn set_rate(&self, rate: f64) -> Result<()> {
+        self.update(
+            |props| {
+                let position = props.current_position();
+                props.set_position(position);
+                props.rate = rate;
+            },
+            Some(Notification::Changed(&["Rate"])),
+        )
+    }
+}
+
+fn dbus_error(e: dbus::Error) -> MusicError {
+    MusicError::String(format!("D-Bus error: {}", e))
+}
+
+/// Dispatch method calls and announce changes until the holder is dropped
+fn serve(conn: Connection, props: Shared, notify_rx: Receiver<Notification>) {
+    let path = Path::from(OBJECT_PATH);
+    loop {
+        if let Err(e) = conn.process(Duration::from_millis(50)) {
+            tracing::warn!("MPRIS D-Bus connection failed: {}", e);
+            return;
+        }
+        loop {
+            let notification = match notify_rx.try_recv() {
+                Ok(notification) => notification,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return,
+            };
+            let Ok(current) = props.lock() else { return };
+            let message = match notification {
+                Notification::Changed(names) => {
+                    let changed_properties = names
+                        .iter()
+                        .filter_map(|name| current.player_property(name).map(|v| (name.to_string(), v)))
+                        .collect();
+                    PropertiesPropertiesChanged {
+                        interface_name: PLAYER_IFACE.to_string(),
+                        changed_properties,
+                        invalidated_properties: Vec::new(),
+                    }
+                    .to_emit_message(&path)
+                }
+                Notification::Seeked(position) => {
+                    Message::signal(&path, &PLAYER_IFACE.into(), &"Seeked".into()).append1(micros(position))
+                }
+            };
+            let _ = conn.send(message);
+        }
+    }
+}
+
+fn with_props<T>(props: &mut Shared, f: impl FnOnce(&PlayerProps) -> T) -> std::result::Result<T, MethodErr> {
+    let props = props.lock().map_err(|_| MethodErr::failed("player state unavailable"))?;
+    Ok(f(&props))
+}
+
+fn register_root(b: &mut IfaceBuilder<Shared>) {
+    b.method("Raise", (), (), |_, props, _: ()| {
+        with_props(props, |p| p.send(MediaControlEvent::Raise))
+    });
+    b.method("Quit", (), (), |_, props, _: ()| {
+        with_props(props, |p| p.send(MediaControlEvent::Quit))
+    });
+    b.property::<bool, _>("CanQuit").get(|_, _| Ok(false)).emits_changed_const();
+    b.property::<bool, _>("CanRaise").get(|_, _| Ok(false)).emits_changed_const();
+    b.property::<bool, _>("HasTrackList").get(|_, _| Ok(false)).emits_changed_const();
+    b.property::<String, _>("Identity")
+        .get(|_, _| Ok(IDENTITY.to_string()))
+        .emits_changed_const();
+    b.property::<Vec<String>, _>("SupportedUriSchemes")
+        .get(|_, _| Ok(Vec::new()))
+        .emits_changed_const();
+    b.property::<Vec<String>, _>("SupportedMimeTypes")
+        .get(|_, _| Ok(Vec::new()))
+        .emits_changed_const();
+}
+
+fn register_player(b: &mut IfaceBuilder<Shared>) {
+    b.method("Next", (), (), |_, props, _: ()| {
+        with_props(props, |p| p.send(MediaControlEvent::Next))
+    });
+    b.method("Previous", (), (), |_, props, _: ()| {
+        with_props(props, |p| p.send(MediaControlEvent::Previous))
+    });
+    b.method("Pause", (), (), |_, props, _: ()| {
+        with_props(props, |p| p.send(MediaControlEvent::Pause))
+    });
+    b.method("PlayPause", (), (), |_, props, _: ()| {
+        with_props(props, |p| p.send(MediaControlEvent::Toggle))
+    });
+    b.method("Stop", (), (), |_, props, _: ()| {
+        with_props(props, |p| p.send(MediaControlEvent::Stop))
+    });
+    b.method("Play", (), (), |_, props, _: ()| {
+        with_props(props, |p| p.send(MediaControlEvent::Play))
+    });
+    b.method("Seek", ("Offset",), (), |_, props, (offset,): (i64,)| {
+        with_props(props, |p| {
+            let direction = if offset < 0 { SeekDirection::Backward } else { SeekDirection::Forward };
+            let amount = Duration::from_micros(offset.unsigned_abs());
+            p.send(MediaControlEvent::SeekBy(direction, amount));
+        })
+    });
+    b.method(
+        "SetPosition",
+        ("TrackId", "Position"),
+        (),
+        |_, props, (track_id, position): (Path<'static>, i64)| {
+            with_props(props, |p| {
+                // Stale requests for a previous track and out of range positions are ignored
+                let in_range = p.duration().is_some_and(|d| position >= 0 && seconds(position) <= d);
+                if track_id == p.track_id() && in_range {
+                    let position = Duration::from_micros(position as u64);
+                    p.send(MediaControlEvent::SetPosition(MediaPosition(position)));
+                }
+            })
+        },
+    );
+    b.method("OpenUri", ("Uri",), (), |_, props, (uri,): (String,)| {
+        with_props(props, |p| p.send(MediaControlEvent::OpenUri(uri)))
+    });
+    b.signal::<(i64,), _>("Seeked", ("Position",));
+
+    b.property::<String, _>("PlaybackStatus")
+        .get(|_, props| with_props(props, PlayerProps::playback_status));
+    b.property::<String, _>("LoopStatus")
+        .get(|_, props| with_props(props, PlayerProps::loop_status))
+        .set(|_, props, status| {
+            with_props(props, |p| {
+                let mode = match status.as_str() {
+                    "Track" => PlayerMode::Single,
+                    "Playlist" => PlayerMode::ListLoop,
+                    // Turning looping off keeps shuffling
+                    _ if p.mode.is_shuffle() => p.mode,
+                    _ => PlayerMode::Sequential,
+                };
+                p.send(MediaControlEvent::SetPlayerMode(mode));
+            })?;
+            // Announced once the player applied the mode
+            Ok(None)
+        });
+    b.property::<bool, _>("Shuffle")
+        .get(|_, props| with_props(props, |p| p.mode.is_shuffle()))
+        .set(|_, props, shuffle| {
+            with_props(props, |p| {
+                if shuffle != p.mode.is_shuffle() {
+                    let mode = if shuffle { PlayerMode::Shuffle } else { PlayerMode::Sequential };
+                    p.send(MediaControlEvent::SetPlayerMode(mode));
+                }
+            })?;
+            Ok(None)
+        });
+    b.property::<PropMap, _>("Metadata")
+        .get(|_, props| with_props(props, PlayerProps::metadata_map));
+    b.property::<i64, _>("Position")
+        .get(|_, props| with_props(props, |p| micros(p.current_position())))
+        .emits_changed_false();
+    b.property::<f64, _>("Rate")
+        .get(|_, props| with_props(props, |p| p.rate))
+        .set(|_, props, rate| {
+            // Rates out of range are ignored; 0.0 would mean pausing
+            if (MIN_RATE..=MAX_RATE).contains(&rate) {
+                with_props(props, |p| p.send(MediaControlEvent::SetRate(rate)))?;
+            }
+            // Announced once the player applied the rate
+            Ok(None)
+        });
+    b.property::<f64, _>("MinimumRate").get(|_, _| Ok(MIN_RATE)).emits_changed_const();
+    b.property::<f64, _>("MaximumRate").get(|_, _| Ok(MAX_RATE)).emits_changed_const();
+    b.property::<bool, _>("CanSeek").get(|_, props| with_props(props, PlayerProps::can_seek));
+    b.property::<bool, _>("CanGoNext").get(|_, _| Ok(true)).emits_changed_const();
+    b.property::<bool, _>("CanGoPrevious").get(|_, _| Ok(true)).emits_changed_const();
+    b.property::<bool, _>("CanPlay").get(|_, _| Ok(true)).emits_changed_const();
+    b.property::<bool, _>("CanPause").get(|_, _| Ok(true)).emits_changed_const();
+    b.property::<bool, _>("CanControl").get(|_, _| Ok(true)).emits_changed_const();
+}
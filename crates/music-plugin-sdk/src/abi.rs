@@ -0,0 +1,113 @@
+//! Dynamic library plugin ABI
+//!
+//! Media plugins built as a `cdylib` expose a small C ABI that the host
+//! resolves with `dlopen`/`LoadLibrary`:
+//!
+//! - `plugin_abi_version() -> u32`: must equal [`PLUGIN_ABI_VERSION`]
+//! - `plugin_sdk_version() -> *const c_char`: the SDK version the plugin was
+//!   built against, NUL-terminated
+//! - `plugin_create() -> *mut PluginHandle`: a new plugin instance, null on failure
+//! - `plugin_destroy(*mut PluginHandle)`: frees an instance created above
+//!
+//! The version functions are checked before `plugin_create` is called, so a
+//! plugin built against an incompatible SDK is rejected without running any of
+//! its code. The instance behind the handle is a Rust trait object: plugin and
+//! host must be built with the same compiler and a compatible SDK. Use
+//! [`export_plugin!`](crate::export_plugin) instead of writing these by hand.
+
+use std::os::raw::c_char;
+
+use crate::traits::media::MediaPlugin;
+
+/// Version of the entry points and of [`PluginHandle`]. Bumped on any change
+/// that breaks already built plugins.
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Version of this SDK, as reported by `plugin_sdk_version`
+pub const SDK_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Same as [`SDK_VERSION`], NUL-terminated for the C ABI
+#[doc(hidden)]
+pub const SDK_VERSION_CSTR: &str = concat!(env!("CARGO_PKG_VERSION"), "\0");
+
+/// Symbol names of the entry points
+pub const ABI_VERSION_SYMBOL: &[u8] = b"plugin_abi_version\0";
+pub const SDK_VERSION_SYMBOL: &[u8] = b"plugin_sdk_version\0";
+pub const CREATE_SYMBOL: &[u8] = b"plugin_create\0";
+pub const DESTROY_SYMBOL: &[u8] = b"plugin_destroy\0";
+
+pub type AbiVersionFn = unsafe extern "C" fn() -> u32;
+pub type SdkVersionFn = unsafe extern "C" fn() -> *const c_char;
+pub type CreateFn = unsafe extern "C" fn() -> *mut PluginHandle;
+pub type DestroyFn = unsafe extern "C" fn(*mut PluginHandle);
+
+/// Plugin instance passed through the C ABI. Owned by the plugin library:
+/// release it with its `plugin_destroy`.
+pub struct PluginHandle {
+    pub plugin: Box<dyn MediaPlugin + Send + Sync>,
+}
+
+impl PluginHandle {
+    /// Box `plugin` for `plugin_create`, null when the constructor panicked
+    pub fn into_raw(create: impl FnOnce() -> Box<dyn MediaPlugin + Send + Sync>) -> *mut PluginHandle {
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(create)) {
+            Ok(plugin) => Box::into_raw(Box::new(PluginHandle { plugin })),
+            Err(_) => std::ptr::null_mut(),
+        }
+    }
+
+    /// Free a handle returned by [`PluginHandle::into_raw`]
+    ///
+    /// # Safety
+    /// `handle` must come from `into_raw` in the same library and not be used afterwards.
+    pub unsafe fn destroy(handle: *mut PluginHandle) {
+        if !handle.is_null() {
+            let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| drop(Box::from_raw(handle))));
+        }
+    }
+}
+
+/// Whether a plugin built against SDK `plugin` can be loaded by a host built
+/// against SDK `host`: same major version, and same minor version before 1.0.
+pub fn is_sdk_compatible(host: &str, plugin: &str) -> bool {
+    fn parts(version: &str) -> Option<(u64, u64)> {
+        let mut parts = version.trim().split(['.', '-', '+']);
+        Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+    }
+    match (parts(host), parts(plugin)) {
+        (Some((0, host_minor)), Some((0, plugin_minor))) => host_minor == plugin_minor,
+        (Some((host_major, _)), Some((plugin_major, _))) => host_major == plugin_major,
+        _ => false,
+    }
+}
+
+/// Export the dynamic library entry points for a media plugin type. The
+/// expression builds a new instance.
+///
+/// ```ignore
+/// music_plugin_sdk::export_plugin!(MyPlugin::new());
+/// ```
+#[macro_export]
+macro_rules! export_plugin {
+    ($create:expr) => {
+        #[no_mangle]
+        pub extern "C" fn plugin_abi_version() -> u32 {
+            $crate::abi::PLUGIN_ABI_VERSION
+        }
+
+        #[no_mangle]
+        pub extern "C" fn plugin_sdk_version() -> *const ::std::os::raw::c_char {
+            $crate::abi::SDK_VERSION_CSTR.as_ptr() as *const ::std::os::raw::c_char
+        }
+
+        #[no_mangle]
+        pub extern "C" fn plugin_create() -> *mut $crate::abi::PluginHandle {
+            $crate::abi::PluginHandle::into_raw(|| ::std::boxed::Box::new($create))
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn plugin_destroy(handle: *mut $crate::abi::PluginHandle) {
+            $crate::abi::PluginHandle::destroy(handle)
+        }
+    };
+}
@@ -10,6 +10,7 @@ pub mod errors;
 pub mod base;
 pub mod core;
 pub mod utils;
+pub mod abi;
 
 /// Prelude module containing commonly used items
 pub mod prelude {
@@ -8,7 +8,7 @@ use crate::types::base::{PluginResult, PluginContext, PluginConfig, PluginStatus
 use crate::types::media::{
     SearchQuery, SearchResult, Track, Album, Artist, Playlist, PageInput, SearchType,
     AuthMethod, AuthUserInfo, QrCodeResponse, QrCodeStatus, SmsResponse, AuthResult,
-    AudioQuality, StreamRequest, StreamSource, StreamProtocol
+    AudioQuality, StreamRequest, StreamSource, StreamProtocol, RecommendationSeed, Lyrics
 };
 use std::collections::HashMap;
 
@@ -49,6 +49,45 @@ pub trait MediaPlugin: BasePlugin {
         ))
     }
 
+    /// Get recommended tracks for `seed`, most relevant first
+    async fn get_recommendations(&self, seed: &RecommendationSeed) -> PluginResult<Vec<Track>> {
+        Err(crate::errors::PluginError::NotSupported(
+            "Recommendations not supported".to_string()
+        ))
+    }
+
+    /// Get up to `limit` tracks similar to `track_id`, most relevant first.
+    /// Used by radio mode to extend the queue once it runs out.
+    async fn get_related_tracks(&self, track_id: &str, limit: usize) -> PluginResult<Vec<Track>> {
+        self.get_recommendations(&RecommendationSeed::from_track(track_id, limit)).await
+    }
+
+    /// Get lyrics of `track_id`, `None` when the provider has none for it.
+    /// Plugins implementing this should declare `PluginCapability::Lyrics`.
+    async fn get_lyrics(&self, track_id: &str) -> PluginResult<Option<Lyrics>> {
+        Err(crate::errors::PluginError::NotSupported(
+            "Lyrics not supported".to_string()
+        ))
+    }
+
+    /// Download capability of the plugin, if any. Plugins implementing
+    /// `MediaDownloadPlugin` should return `Some(self)`.
+    fn as_download(&self) -> Option<&dyn MediaDownloadPlugin> {
+        None
+    }
+
+    /// Playlist editing capability of the plugin, if any. Plugins
+    /// implementing `MediaPlaylistWritePlugin` should return `Some(self)`.
+    fn as_playlist_write(&self) -> Option<&dyn MediaPlaylistWritePlugin> {
+        None
+    }
+
+    /// Favorites capability of the plugin, if any. Plugins implementing
+    /// `MediaFavoritesPlugin` should return `Some(self)`.
+    fn as_favorites(&self) -> Option<&dyn MediaFavoritesPlugin> {
+        None
+    }
+
 }
 
 #[async_trait]
@@ -143,3 +182,34 @@ pub trait MediaDownloadPlugin: MediaPlugin {
     /// Check if track can be downloaded
     async fn can_download(&self, track_id: &str) -> PluginResult<bool>;
 }
+
+/// Playlist editing capability trait, used to push local changes of synced
+/// playlists to the provider
+#[async_trait]
+pub trait MediaPlaylistWritePlugin: MediaPlugin {
+    /// Check if the signed-in user may change the playlist
+    async fn can_edit_playlist(&self, playlist_id: &str) -> PluginResult<bool> {
+        Ok(true)
+    }
+
+    /// Append tracks to the playlist. Returns the track IDs the provider does
+    /// not know, which were left out.
+    async fn add_playlist_tracks(&self, playlist_id: &str, track_ids: &[String]) -> PluginResult<Vec<String>>;
+
+    /// Remove every occurrence of the tracks from the playlist
+    async fn remove_playlist_tracks(&self, playlist_id: &str, track_ids: &[String]) -> PluginResult<()>;
+}
+
+/// Favorites capability trait: the tracks the signed-in user liked at the
+/// provider, mirrored with the liked songs of the app
+#[async_trait]
+pub trait MediaFavoritesPlugin: MediaPlugin {
+    /// Every track the user liked, most recent first
+    async fn get_favorite_tracks(&self) -> PluginResult<Vec<Track>>;
+
+    /// Like tracks on the user account
+    async fn add_favorite_tracks(&self, track_ids: &[String]) -> PluginResult<()>;
+
+    /// Unlike tracks on the user account
+    async fn remove_favorite_tracks(&self, track_ids: &[String]) -> PluginResult<()>;
+}
@@ -8,5 +8,5 @@ pub mod event;
 
 // Re-export all traits
 pub use base::BasePlugin;
-pub use media::{MediaPlugin, MediaAuthPlugin, MediaDownloadPlugin};
+pub use media::{MediaPlugin, MediaAuthPlugin, MediaDownloadPlugin, MediaFavoritesPlugin, MediaPlaylistWritePlugin};
 pub use event::{PluginEventHandler, PluginEvent};
\ No newline at end of file
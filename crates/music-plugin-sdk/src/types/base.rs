@@ -27,7 +27,8 @@ pub struct PluginMetadata {
     pub capabilities: Vec<PluginCapability>,
     /// Minimum SDK version required
     pub min_sdk_version: String,
-    /// Plugin configuration schema
+    /// Plugin configuration schema (JSON schema of an object), from which
+    /// the host renders the settings form; see `utils::ConfigSchemaBuilder`
     pub config_schema: Option<serde_json::Value>,
 }
 
@@ -58,6 +59,53 @@ pub struct PluginConfig {
     pub errors: Vec<String>,
 }
 
+/// Configuration key of the API endpoint a provider should use instead of its
+/// default one, e.g. a self-hosted mirror. `null` restores the default; a
+/// configuration without the key leaves the endpoint unchanged.
+pub const ENDPOINT_CONFIG_KEY: &str = "endpoint";
+
+impl PluginConfig {
+    /// Endpoint change carried by this configuration: `Some(None)` restores the
+    /// default endpoint, `None` means no change
+    pub fn endpoint(&self) -> Option<Option<&str>> {
+        self.values.get(ENDPOINT_CONFIG_KEY).map(|value| value.as_str())
+    }
+}
+
+/// Host service through which sandboxed plugins without network access make
+/// HTTP requests. The host checks the URL against the plugin's network
+/// permissions, adds the stored credential named in the request and records
+/// the request in the plugin's network audit.
+pub const HOST_FETCH_SERVICE: &str = "http.fetch";
+
+/// HTTP request made through `HOST_FETCH_SERVICE`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HostHttpRequest {
+    /// HTTP method, `GET` when empty
+    #[serde(default)]
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Text body
+    #[serde(default)]
+    pub body: Option<String>,
+    /// Name of a credential stored by the host for this plugin, added to the
+    /// request by the host. The plugin never sees its value.
+    #[serde(default)]
+    pub credential: Option<String>,
+}
+
+/// Response to a `HostHttpRequest`. Redirects are not followed; a redirect is
+/// returned as is and its target has to be requested again.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HostHttpResponse {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    /// Body decoded as UTF-8, invalid sequences replaced
+    pub body: String,
+}
+
 /// Host context provided to plugins
 #[derive(Debug, Clone)]
 pub struct PluginContext {
@@ -85,6 +133,8 @@ pub enum PluginCapability {
     Library,
     /// Can create playlists
     Playlist,
+    /// Can provide (possibly time-synced) lyrics
+    Lyrics,
     /// Requires network access
     Network,
     /// Requires file system access
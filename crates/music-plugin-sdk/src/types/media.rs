@@ -104,6 +104,7 @@ pub enum QualityPreference {
     Low,
     Medium,
     High,
+    Lossless,
     /// Provider-specific numeric quality, e.g. bilibili `qn`
     Qn(u32),
 }
@@ -400,6 +401,35 @@ pub struct SearchQuery {
     pub provider_params: HashMap<String, serde_json::Value>,
 }
 
+/// What recommendations should be similar to. Providers use the seeds they
+/// understand and ignore the rest.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct RecommendationSeed {
+    /// Seed track IDs (as returned by the provider)
+    #[serde(default)]
+    pub track_ids: Vec<String>,
+    /// Seed artist IDs
+    #[serde(default)]
+    pub artist_ids: Vec<String>,
+    /// Seed genre names
+    #[serde(default)]
+    pub genres: Vec<String>,
+    /// Maximum number of tracks to return (provider default when unset)
+    pub limit: Option<u32>,
+}
+
+impl RecommendationSeed {
+    /// Seed with a single track
+    pub fn from_track(track_id: &str, limit: usize) -> Self {
+        Self {
+            track_ids: vec![track_id.to_string()],
+            limit: Some(limit as u32),
+            ..Default::default()
+        }
+    }
+}
+
 /// Search result types
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
@@ -10,5 +10,5 @@ pub use media::{
     AuthMethod, AuthUserInfo, QrCodeResponse, QrCodeStatus, SmsResponse, AuthResult,
     AudioQuality, Image, ArtistRef, AlbumRef, StreamSource, StreamRequest, StreamProtocol, Availability, Lyrics,
     LyricLine, LyricsTranslation, AuthSession, AuthChallenge, AuthStatus, AuthProgress,
-    SearchSlice, PageInfo, SearchSort,PlaylistOwner
+    SearchSlice, PageInfo, SearchSort,PlaylistOwner, RecommendationSeed
 };
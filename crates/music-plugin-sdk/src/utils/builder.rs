@@ -23,6 +23,127 @@ impl<T> PluginBuilder<T> {
     }
 }
 
+/// Builder of the JSON schema a plugin declares in
+/// `PluginMetadata::config_schema`. The host renders a settings form from it
+/// and validates values against it before they reach `configure`.
+///
+/// ```ignore
+/// let schema = ConfigSchemaBuilder::new()
+///     .field("api_key", ConfigField::secret("API key").required())
+///     .field("region", ConfigField::choice("Region", &["us", "eu"]).default("us"))
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSchemaBuilder {
+    properties: serde_json::Map<String, serde_json::Value>,
+    required: Vec<String>,
+    order: Vec<String>,
+}
+
+impl ConfigSchemaBuilder {
+    /// Create an empty schema
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a setting, shown in the order added
+    pub fn field(mut self, key: &str, field: ConfigField) -> Self {
+        if field.required {
+            self.required.push(key.to_string());
+        }
+        if self.properties.insert(key.to_string(), serde_json::Value::Object(field.schema)).is_none() {
+            self.order.push(key.to_string());
+        }
+        self
+    }
+
+    /// Build the JSON schema
+    pub fn build(self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": self.properties,
+            "required": self.required,
+            // Object keys may be reordered; the form follows this list
+            "x-order": self.order,
+        })
+    }
+}
+
+/// One setting of a `ConfigSchemaBuilder`
+#[derive(Debug, Clone)]
+pub struct ConfigField {
+    schema: serde_json::Map<String, serde_json::Value>,
+    required: bool,
+}
+
+impl ConfigField {
+    fn new(kind: &str, title: &str) -> Self {
+        let mut schema = serde_json::Map::new();
+        schema.insert("type".to_string(), kind.into());
+        schema.insert("title".to_string(), title.into());
+        Self { schema, required: false }
+    }
+
+    /// Text setting
+    pub fn string(title: &str) -> Self {
+        Self::new("string", title)
+    }
+
+    /// Text setting whose value is masked in the form, e.g. a token
+    pub fn secret(title: &str) -> Self {
+        let mut field = Self::new("string", title);
+        field.schema.insert("format".to_string(), "password".into());
+        field
+    }
+
+    /// On/off setting
+    pub fn boolean(title: &str) -> Self {
+        Self::new("boolean", title)
+    }
+
+    /// Numeric setting
+    pub fn number(title: &str) -> Self {
+        Self::new("number", title)
+    }
+
+    /// Whole number setting
+    pub fn integer(title: &str) -> Self {
+        Self::new("integer", title)
+    }
+
+    /// Text setting limited to `options`
+    pub fn choice(title: &str, options: &[&str]) -> Self {
+        let mut field = Self::new("string", title);
+        field.schema.insert("enum".to_string(), options.into());
+        field
+    }
+
+    /// Help text shown under the setting
+    pub fn description(mut self, description: &str) -> Self {
+        self.schema.insert("description".to_string(), description.into());
+        self
+    }
+
+    /// Value used until the user sets one
+    pub fn default(mut self, value: impl Into<serde_json::Value>) -> Self {
+        self.schema.insert("default".to_string(), value.into());
+        self
+    }
+
+    /// Bounds of a numeric setting
+    pub fn range(mut self, minimum: f64, maximum: f64) -> Self {
+        self.schema.insert("minimum".to_string(), minimum.into());
+        self.schema.insert("maximum".to_string(), maximum.into());
+        self
+    }
+
+    /// The plugin cannot work without this setting
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+}
+
 /// Configuration validator
 pub struct ConfigValidator {
     schema: serde_json::Value,
@@ -34,15 +155,66 @@ impl ConfigValidator {
         Self { schema }
     }
     
-    /// Validate configuration against schema
+    /// Validate configuration against schema: required settings, value types,
+    /// choices and numeric bounds. Keys the schema does not declare are
+    /// accepted; `null` clears a setting.
     pub fn validate(&self, config: &PluginConfig) -> Result<()> {
-        // Basic validation - in real implementation use jsonschema crate
-        if config.values.is_empty() && !self.schema.is_null() {
-            return Err(PluginError::ConfigurationError(
-                "Configuration is required but empty".to_string()
-            ));
+        let errors = self.errors(&config.values);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(PluginError::ConfigurationError(errors.join("; ")))
+        }
+    }
+
+    /// Every problem of `values`, empty when they are valid
+    pub fn errors(&self, values: &HashMap<String, serde_json::Value>) -> Vec<String> {
+        let mut errors = Vec::new();
+        let present = |key: &str| values.get(key).is_some_and(|v| !v.is_null());
+
+        for key in self.schema.get("required").and_then(|r| r.as_array()).into_iter().flatten() {
+            if let Some(key) = key.as_str() {
+                if !present(key) {
+                    errors.push(format!("{} is required", key));
+                }
+            }
+        }
+
+        let Some(properties) = self.schema.get("properties").and_then(|p| p.as_object()) else {
+            return errors;
+        };
+        for (key, property) in properties {
+            let Some(value) = values.get(key).filter(|v| !v.is_null()) else {
+                continue;
+            };
+            let type_matches = match property.get("type").and_then(|t| t.as_str()) {
+                Some("string") => value.is_string(),
+                Some("boolean") => value.is_boolean(),
+                Some("number") => value.is_number(),
+                Some("integer") => value.is_i64() || value.is_u64(),
+                Some("array") => value.is_array(),
+                Some("object") => value.is_object(),
+                _ => true,
+            };
+            if !type_matches {
+                errors.push(format!("{} must be of type {}", key, property["type"]));
+                continue;
+            }
+            if let Some(options) = property.get("enum").and_then(|e| e.as_array()) {
+                if !options.contains(value) {
+                    errors.push(format!("{} must be one of {}", key, property["enum"]));
+                }
+            }
+            if let Some(number) = value.as_f64() {
+                if property.get("minimum").and_then(|m| m.as_f64()).is_some_and(|min| number < min) {
+                    errors.push(format!("{} must be at least {}", key, property["minimum"]));
+                }
+                if property.get("maximum").and_then(|m| m.as_f64()).is_some_and(|max| number > max) {
+                    errors.push(format!("{} must be at most {}", key, property["maximum"]));
+                }
+            }
         }
-        Ok(())
+        errors
     }
     
     /// Get default configuration from schema
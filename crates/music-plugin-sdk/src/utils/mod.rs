@@ -6,5 +6,5 @@ pub mod validation;
 pub mod macros;
 
 // Re-export commonly used utilities
-pub use builder::{PluginBuilder, ConfigValidator};
+pub use builder::{PluginBuilder, ConfigValidator, ConfigSchemaBuilder, ConfigField};
 pub use validation::{is_valid_url, format_duration, is_valid_plugin_id, generate_plugin_id};
\ No newline at end of file
@@ -0,0 +1,469 @@
+//! Plugin development server
+//!
+//! Loads a single media plugin and exposes its `MediaPlugin` / `MediaAuthPlugin`
+//! surface over a local HTTP port, so search, stream and auth flows can be
+//! exercised without launching the full app.
+//!
+//! Usage:
+//!   cargo run -p plugins --features dev-server --bin plugin-dev-server -- \
+//!       <plugin> [--port 7878] [--config key=value]...
+//!
+//! `<plugin>` is a built-in plugin name (e.g. `bilibili`) or the path of an
+//! external plugin file. Open http://127.0.0.1:<port>/ for the explorer.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+
+use axum::extract::{Path as UrlPath, Query, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use music_plugin_sdk::errors::PluginError;
+use music_plugin_sdk::traits::{BasePlugin, MediaAuthPlugin, MediaPlugin};
+use music_plugin_sdk::types::base::PluginConfig;
+use music_plugin_sdk::types::{PageInput, SearchQuery, SearchType, StreamRequest};
+
+const DEFAULT_PORT: u16 = 7878;
+
+type DevPlugin = Arc<Mutex<dyn MediaAuthPlugin + Send + Sync>>;
+
+struct Args {
+    plugin: String,
+    port: u16,
+    config: HashMap<String, serde_json::Value>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut plugin = None;
+    let mut port = DEFAULT_PORT;
+    let mut config = HashMap::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--port" => {
+                let value = args.next().ok_or("--port needs a value")?;
+                port = value.parse().map_err(|_| format!("Invalid port: {}", value))?;
+            }
+            "--config" => {
+                let value = args.next().ok_or("--config needs key=value")?;
+                let (key, raw) = value
+                    .split_once('=')
+                    .ok_or_else(|| format!("Invalid config entry: {}", value))?;
+                // Accept JSON literals, fall back to a plain string
+                let parsed = serde_json::from_str(raw)
+                    .unwrap_or_else(|_| serde_json::Value::String(raw.to_string()));
+                config.insert(key.to_string(), parsed);
+            }
+            "-h" | "--help" => {
+                return Err("Usage: plugin-dev-server <plugin> [--port N] [--config key=value]...".to_string())
+            }
+            _ if plugin.is_none() => plugin = Some(arg),
+            _ => return Err(format!("Unexpected argument: {}", arg)),
+        }
+    }
+
+    Ok(Args {
+        plugin: plugin.ok_or("Missing plugin name or path")?,
+        port,
+        config,
+    })
+}
+
+/// Built-in media plugins by name; external files go through the plugin loaders.
+fn load_plugin(spec: &str) -> Result<DevPlugin, String> {
+    match spec.to_lowercase().as_str() {
+        "bilibili" => Ok(Arc::new(Mutex::new(plugins::internal::BilibiliPlugin::new()))),
+        _ if Path::new(spec).is_file() => Err(format!(
+            "Loading external media plugins is not supported by the plugin loaders yet: {}",
+            spec
+        )),
+        _ => Err(format!("Unknown built-in media plugin: {}", spec)),
+    }
+}
+
+struct ApiError(PluginError);
+
+impl From<PluginError> for ApiError {
+    fn from(e: PluginError) -> Self {
+        Self(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self.0 {
+            PluginError::NotFound(_) => StatusCode::NOT_FOUND,
+            PluginError::NotSupported(_) => StatusCode::NOT_IMPLEMENTED,
+            PluginError::AuthenticationError(_) => StatusCode::UNAUTHORIZED,
+            PluginError::AuthorizationError(_) => StatusCode::FORBIDDEN,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(serde_json::json!({ "error": self.0.to_string() }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+#[derive(Deserialize)]
+struct SearchParams {
+    q: String,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+}
+
+fn parse_search_type(kind: Option<&str>) -> SearchType {
+    match kind.unwrap_or("track") {
+        "album" => SearchType::Album,
+        "artist" => SearchType::Artist,
+        "playlist" => SearchType::Playlist,
+        "all" => SearchType::All,
+        _ => SearchType::Track,
+    }
+}
+
+async fn metadata(State(plugin): State<DevPlugin>) -> Json<serde_json::Value> {
+    let plugin = plugin.lock().await;
+    Json(serde_json::json!({
+        "metadata": plugin.metadata(),
+        "status": plugin.status(),
+        "configSchema": plugin.config_schema(),
+    }))
+}
+
+async fn search_get(
+    State(plugin): State<DevPlugin>,
+    Query(params): Query<SearchParams>,
+) -> ApiResult<music_plugin_sdk::types::SearchResult> {
+    let query = SearchQuery {
+        query: params.q,
+        types: vec![parse_search_type(params.kind.as_deref())],
+        page: Some(PageInput {
+            limit: params.limit.or(Some(20)),
+            offset: params.offset,
+            cursor: None,
+        }),
+        per_type_page: None,
+        sort: None,
+        per_type_sort: None,
+        filters: HashMap::new(),
+        provider_params: HashMap::new(),
+    };
+    Ok(Json(plugin.lock().await.search(&query).await?))
+}
+
+async fn search_post(
+    State(plugin): State<DevPlugin>,
+    Json(query): Json<SearchQuery>,
+) -> ApiResult<music_plugin_sdk::types::SearchResult> {
+    Ok(Json(plugin.lock().await.search(&query).await?))
+}
+
+async fn track(
+    State(plugin): State<DevPlugin>,
+    UrlPath(id): UrlPath<String>,
+) -> ApiResult<music_plugin_sdk::types::Track> {
+    Ok(Json(plugin.lock().await.get_track(&id).await?))
+}
+
+async fn track_available(State(plugin): State<DevPlugin>, UrlPath(id): UrlPath<String>) -> ApiResult<bool> {
+    Ok(Json(plugin.lock().await.is_track_available(&id).await?))
+}
+
+async fn stream(
+    State(plugin): State<DevPlugin>,
+    UrlPath(id): UrlPath<String>,
+    body: Option<Json<StreamRequest>>,
+) -> ApiResult<music_plugin_sdk::types::StreamSource> {
+    let request = body.map(|Json(r)| r).unwrap_or_default();
+    Ok(Json(plugin.lock().await.get_media_stream(&id, &request).await?))
+}
+
+async fn album(
+    State(plugin): State<DevPlugin>,
+    UrlPath(id): UrlPath<String>,
+) -> ApiResult<music_plugin_sdk::types::Album> {
+    Ok(Json(plugin.lock().await.get_album(&id).await?))
+}
+
+async fn artist(
+    State(plugin): State<DevPlugin>,
+    UrlPath(id): UrlPath<String>,
+) -> ApiResult<music_plugin_sdk::types::Artist> {
+    Ok(Json(plugin.lock().await.get_artist(&id).await?))
+}
+
+async fn playlist(
+    State(plugin): State<DevPlugin>,
+    UrlPath(id): UrlPath<String>,
+) -> ApiResult<music_plugin_sdk::types::Playlist> {
+    Ok(Json(plugin.lock().await.get_playlist(&id).await?))
+}
+
+async fn library_tracks(State(plugin): State<DevPlugin>) -> ApiResult<Vec<music_plugin_sdk::types::Track>> {
+    Ok(Json(plugin.lock().await.get_user_library().await?))
+}
+
+async fn library_playlists(
+    State(plugin): State<DevPlugin>,
+) -> ApiResult<Vec<music_plugin_sdk::types::Playlist>> {
+    Ok(Json(plugin.lock().await.get_user_playlists().await?))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthState {
+    methods: Vec<music_plugin_sdk::types::AuthMethod>,
+    authenticated: bool,
+    user: Option<music_plugin_sdk::types::AuthUserInfo>,
+}
+
+async fn auth_state(State(plugin): State<DevPlugin>) -> Json<AuthState> {
+    let plugin = plugin.lock().await;
+    Json(AuthState {
+        methods: plugin.supported_auth_methods(),
+        authenticated: plugin.is_authenticated(),
+        user: plugin.get_user_info(),
+    })
+}
+
+async fn qrcode(State(plugin): State<DevPlugin>) -> ApiResult<music_plugin_sdk::types::QrCodeResponse> {
+    Ok(Json(plugin.lock().await.generate_qrcode().await?))
+}
+
+async fn qrcode_status(
+    State(plugin): State<DevPlugin>,
+    UrlPath(key): UrlPath<String>,
+) -> ApiResult<music_plugin_sdk::types::QrCodeStatus> {
+    Ok(Json(plugin.lock().await.check_qrcode_status(&key).await?))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SmsSend {
+    phone: String,
+    country_code: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SmsVerify {
+    phone: String,
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct PasswordLogin {
+    username: String,
+    password: String,
+}
+
+async fn sms_send(
+    State(plugin): State<DevPlugin>,
+    Json(body): Json<SmsSend>,
+) -> ApiResult<music_plugin_sdk::types::SmsResponse> {
+    let mut plugin = plugin.lock().await;
+    Ok(Json(plugin.send_sms_code(&body.phone, body.country_code.as_deref()).await?))
+}
+
+async fn sms_verify(
+    State(plugin): State<DevPlugin>,
+    Json(body): Json<SmsVerify>,
+) -> ApiResult<music_plugin_sdk::types::AuthResult> {
+    Ok(Json(plugin.lock().await.verify_sms_code(&body.phone, &body.code).await?))
+}
+
+async fn password_login(
+    State(plugin): State<DevPlugin>,
+    Json(body): Json<PasswordLogin>,
+) -> ApiResult<music_plugin_sdk::types::AuthResult> {
+    let mut plugin = plugin.lock().await;
+    Ok(Json(plugin.login_with_password(&body.username, &body.password).await?))
+}
+
+async fn logout(State(plugin): State<DevPlugin>) -> ApiResult<()> {
+    Ok(Json(plugin.lock().await.logout().await?))
+}
+
+async fn explorer() -> Html<&'static str> {
+    Html(EXPLORER_HTML)
+}
+
+fn router(plugin: DevPlugin) -> Router {
+    Router::new()
+        .route("/", get(explorer))
+        .route("/api/metadata", get(metadata))
+        .route("/api/search", get(search_get).post(search_post))
+        .route("/api/tracks/:id", get(track))
+        .route("/api/tracks/:id/available", get(track_available))
+        .route("/api/tracks/:id/stream", get(stream).post(stream))
+        .route("/api/albums/:id", get(album))
+        .route("/api/artists/:id", get(artist))
+        .route("/api/playlists/:id", get(playlist))
+        .route("/api/library/tracks", get(library_tracks))
+        .route("/api/library/playlists", get(library_playlists))
+        .route("/api/auth", get(auth_state))
+        .route("/api/auth/qrcode", post(qrcode))
+        .route("/api/auth/qrcode/:key", get(qrcode_status))
+        .route("/api/auth/sms/send", post(sms_send))
+        .route("/api/auth/sms/verify", post(sms_verify))
+        .route("/api/auth/password", post(password_login))
+        .route("/api/auth/logout", post(logout))
+        .with_state(plugin)
+}
+
+#[tokio::main]
+async fn main() {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let plugin = match load_plugin(&args.plugin) {
+        Ok(plugin) => plugin,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    {
+        let mut guard = plugin.lock().await;
+        if !args.config.is_empty() {
+            let config = PluginConfig {
+                values: args.config,
+                is_valid: true,
+                errors: vec![],
+            };
+            if let Err(e) = guard.configure(config).await {
+                eprintln!("Failed to configure plugin: {}", e);
+                std::process::exit(1);
+            }
+        }
+        if let Err(e) = guard.start().await {
+            eprintln!("Failed to start plugin: {}", e);
+            std::process::exit(1);
+        }
+        let metadata = guard.metadata();
+        println!("Loaded plugin {} v{} ({})", metadata.name, metadata.version, metadata.id);
+    }
+
+    // Local only: the server exposes the plugin's auth session
+    let addr = SocketAddr::from(([127, 0, 0, 1], args.port));
+    let listener = match tokio::net::TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Failed to bind {}: {}", addr, e);
+            std::process::exit(1);
+        }
+    };
+    println!("Plugin explorer listening on http://{}/", addr);
+
+    if let Err(e) = axum::serve(listener, router(plugin)).await {
+        eprintln!("Server error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+const EXPLORER_HTML: &str = r#"<!doctype html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Plugin explorer</title>
+<style>
+  body { font-family: system-ui, sans-serif; margin: 1.5rem; max-width: 960px; }
+  fieldset { margin-bottom: 1rem; }
+  input, select, button { margin: 0.2rem; }
+  pre { background: #f4f4f4; padding: 0.8rem; overflow: auto; max-height: 60vh; }
+</style>
+</head>
+<body>
+<h1 id="title">Plugin explorer</h1>
+
+<fieldset>
+  <legend>Search</legend>
+  <input id="q" placeholder="query">
+  <select id="type">
+    <option>track</option><option>album</option><option>artist</option>
+    <option>playlist</option><option>all</option>
+  </select>
+  <input id="limit" type="number" value="20" style="width:5em">
+  <button onclick="call('GET', `/api/search?q=${enc('q')}&type=${val('type')}&limit=${val('limit')}`)">Search</button>
+</fieldset>
+
+<fieldset>
+  <legend>Lookup</legend>
+  <input id="id" placeholder="id">
+  <button onclick="call('GET', `/api/tracks/${enc('id')}`)">Track</button>
+  <button onclick="call('GET', `/api/tracks/${enc('id')}/available`)">Available</button>
+  <button onclick="call('POST', `/api/tracks/${enc('id')}/stream`, {format:'Auto', quality:'Auto'})">Stream</button>
+  <button onclick="call('GET', `/api/albums/${enc('id')}`)">Album</button>
+  <button onclick="call('GET', `/api/artists/${enc('id')}`)">Artist</button>
+  <button onclick="call('GET', `/api/playlists/${enc('id')}`)">Playlist</button>
+</fieldset>
+
+<fieldset>
+  <legend>Auth</legend>
+  <button onclick="call('GET', '/api/auth')">State</button>
+  <button onclick="qr()">QR login</button>
+  <input id="qrkey" placeholder="qrcode key">
+  <button onclick="call('GET', `/api/auth/qrcode/${enc('qrkey')}`)">QR status</button>
+  <br>
+  <input id="phone" placeholder="phone"><input id="cc" placeholder="country code" style="width:6em">
+  <button onclick="call('POST', '/api/auth/sms/send', {phone: val('phone'), countryCode: val('cc') || null})">Send SMS</button>
+  <input id="code" placeholder="code" style="width:6em">
+  <button onclick="call('POST', '/api/auth/sms/verify', {phone: val('phone'), code: val('code')})">Verify</button>
+  <br>
+  <input id="user" placeholder="username"><input id="pass" type="password" placeholder="password">
+  <button onclick="call('POST', '/api/auth/password', {username: val('user'), password: val('pass')})">Login</button>
+  <button onclick="call('POST', '/api/auth/logout')">Logout</button>
+  <br>
+  <button onclick="call('GET', '/api/library/tracks')">Library tracks</button>
+  <button onclick="call('GET', '/api/library/playlists')">Library playlists</button>
+</fieldset>
+
+<div id="qrimg"></div>
+<pre id="out"></pre>
+
+<script>
+  const val = (id) => document.getElementById(id).value;
+  const enc = (id) => encodeURIComponent(val(id));
+  async function call(method, url, body) {
+    const init = { method, headers: {} };
+    if (body !== undefined) {
+      init.headers['content-type'] = 'application/json';
+      init.body = JSON.stringify(body);
+    }
+    const res = await fetch(url, init);
+    const text = await res.text();
+    let shown = text;
+    try { shown = JSON.stringify(JSON.parse(text), null, 2); } catch (_) {}
+    document.getElementById('out').textContent = `${method} ${url} -> ${res.status}\n\n${shown}`;
+    try { return JSON.parse(text); } catch (_) { return null; }
+  }
+  async function qr() {
+    const res = await call('POST', '/api/auth/qrcode');
+    if (res && res.qrcode_key) {
+      document.getElementById('qrkey').value = res.qrcode_key;
+      document.getElementById('qrimg').innerHTML = res.image_url
+        ? `<img src="${res.image_url}" width="200">`
+        : `<code>${res.content}</code>`;
+    }
+  }
+  fetch('/api/metadata').then(r => r.json()).then(m => {
+    document.getElementById('title').textContent = `${m.metadata.name} ${m.metadata.version}`;
+  });
+</script>
+</body>
+</html>
+"#;
@@ -1,8 +1,27 @@
 //! Dynamic Library Plugin Loader
+//!
+//! Loads media plugins built as `cdylib` against `music_plugin_sdk::abi`. The
+//! ABI and SDK versions a library reports are checked before any of its plugin
+//! code runs.
 
+use std::ffi::CStr;
 use std::fs;
-use std::path::Path;
-use libloading::Library;
+use std::path::{Path, PathBuf};
+use std::ptr::NonNull;
+use async_trait::async_trait;
+use libloading::{Library, Symbol};
+use music_plugin_sdk::abi::{
+    self, AbiVersionFn, CreateFn, DestroyFn, PluginHandle, SdkVersionFn, PLUGIN_ABI_VERSION, SDK_VERSION,
+};
+use music_plugin_sdk::traits::media::{MediaDownloadPlugin, MediaFavoritesPlugin, MediaPlaylistWritePlugin, MediaPlugin};
+use music_plugin_sdk::traits::BasePlugin;
+use music_plugin_sdk::types::base::{
+    PluginConfig, PluginContext, PluginMetadata, PluginResult as SdkResult, PluginStatus,
+};
+use music_plugin_sdk::types::media::{
+    Album, Artist, Lyrics, Playlist, RecommendationSeed, SearchQuery, SearchResult, StreamRequest, StreamSource,
+    Track,
+};
 
 use crate::system::core::*;
 use crate::system::types::*;
@@ -22,56 +41,260 @@ impl DynamicPluginLoader {
             runtime_dir,
         }
     }
-    
+
+    /// Directory dynamic library plugins are loaded from
+    pub fn runtime_dir(&self) -> &Path {
+        Path::new(&self.runtime_dir)
+    }
+
     /// Load a dynamic library plugin from file
     pub async fn load_plugin(&self, plugin_path: &Path) -> PluginResult<Box<dyn Plugin>> {
-        // Check if file exists
+        // Dynamic libraries only provide media plugins, which are registered
+        // to the media factory rather than the generic registry
+        self.check_library(plugin_path)?;
+        Err(PluginError::LoadFailed {
+            reason: "Dynamic library plugins are media plugins; load them with load_media_plugin".to_string()
+        })
+    }
+
+    /// Load a media plugin from a dynamic library exporting the SDK entry points
+    pub fn load_media_plugin(&self, plugin_path: &Path) -> PluginResult<DynamicMediaPlugin> {
+        let library = self.check_library(plugin_path)?;
+
+        let (create, destroy) = unsafe {
+            let create: Symbol<CreateFn> = library.get(abi::CREATE_SYMBOL).map_err(|e| PluginError::LoadFailed {
+                reason: format!("Missing plugin_create: {}", e)
+            })?;
+            let destroy: Symbol<DestroyFn> = library.get(abi::DESTROY_SYMBOL).map_err(|e| PluginError::LoadFailed {
+                reason: format!("Missing plugin_destroy: {}", e)
+            })?;
+            (*create, *destroy)
+        };
+
+        // plugin_create catches panics of the plugin constructor itself
+        let handle = NonNull::new(unsafe { create() }).ok_or_else(|| PluginError::InitializationFailed {
+            reason: format!("{} failed to create its plugin", plugin_path.display())
+        })?;
+        let plugin = DynamicMediaPlugin {
+            handle,
+            destroy,
+            path: plugin_path.to_path_buf(),
+            _library: library,
+        };
+        tracing::info!(
+            "Loaded dynamic plugin {} from {}",
+            plugin.metadata().name,
+            plugin_path.display()
+        );
+        Ok(plugin)
+    }
+
+    /// Open the library and verify the ABI and SDK versions it was built for
+    fn check_library(&self, plugin_path: &Path) -> PluginResult<Library> {
         if !plugin_path.exists() || !plugin_path.is_file() {
             return Err(PluginError::LoadFailed {
                 reason: "Plugin file does not exist".to_string()
             });
         }
-        
-        // Load the dynamic library
-        let _library = unsafe { Library::new(plugin_path) }
+
+        let library = unsafe { Library::new(plugin_path) }
             .map_err(|e| PluginError::LoadFailed {
                 reason: format!("Failed to load dynamic library: {}", e)
             })?;
-        
-        // Look up the plugin creation function
-        // Note: This is a simplified implementation. In a real implementation, we would:
-        // 1. Define a standard plugin creation function signature
-        // 2. Look up that function in the loaded library
-        // 3. Call the function to create a plugin instance
-        // 4. Return a wrapper that implements the Plugin trait
-        
-        // For now, we'll return an error as dynamic library plugin loading is not fully implemented
-        Err(PluginError::LoadFailed {
-            reason: "Dynamic library plugin loading not yet fully implemented".to_string()
-        })
+
+        let (abi_version, sdk_version) = unsafe {
+            let abi_version: Symbol<AbiVersionFn> = library.get(abi::ABI_VERSION_SYMBOL).map_err(|e| {
+                PluginError::InvalidManifest {
+                    reason: format!("Not a music plugin (missing plugin_abi_version): {}", e)
+                }
+            })?;
+            let sdk_version: Symbol<SdkVersionFn> = library.get(abi::SDK_VERSION_SYMBOL).map_err(|e| {
+                PluginError::InvalidManifest {
+                    reason: format!("Not a music plugin (missing plugin_sdk_version): {}", e)
+                }
+            })?;
+            let abi_version = abi_version();
+            let sdk_version = sdk_version();
+            let sdk_version = if sdk_version.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(sdk_version).to_string_lossy().into_owned()
+            };
+            (abi_version, sdk_version)
+        };
+
+        if abi_version != PLUGIN_ABI_VERSION {
+            return Err(PluginError::VersionMismatch {
+                reason: format!(
+                    "{} uses plugin ABI {}, this host supports {}",
+                    plugin_path.display(), abi_version, PLUGIN_ABI_VERSION
+                )
+            });
+        }
+        if !abi::is_sdk_compatible(SDK_VERSION, &sdk_version) {
+            return Err(PluginError::VersionMismatch {
+                reason: format!(
+                    "{} was built with SDK {:?}, this host uses {}",
+                    plugin_path.display(), sdk_version, SDK_VERSION
+                )
+            });
+        }
+        Ok(library)
     }
-    
+
     /// Validate a dynamic library plugin file
     pub fn validate_plugin(&self, plugin_path: &Path) -> PluginResult<bool> {
         // Check if file exists and is readable
         if !plugin_path.exists() || !plugin_path.is_file() {
             return Ok(false);
         }
-        
+
         // Try to open the file to check if it's readable
         if let Err(_) = fs::File::open(plugin_path) {
             return Ok(false);
         }
-        
-        // Try to load the library to check if it's a valid dynamic library
-        match unsafe { Library::new(plugin_path) } {
-            Ok(_) => {
-                // In a real implementation, we would also check:
-                // 1. If the library exports required symbols
-                // 2. If the library conforms to security requirements
-                Ok(true)
+
+        // A valid plugin exports the entry points with compatible versions
+        match self.check_library(plugin_path) {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                tracing::debug!("Rejected dynamic plugin {}: {}", plugin_path.display(), e);
+                Ok(false)
             }
-            Err(_) => Ok(false),
         }
     }
-}
\ No newline at end of file
+}
+
+/// Media plugin instance living in a loaded dynamic library. It is released
+/// with the library's own `plugin_destroy` before the library is unloaded.
+pub struct DynamicMediaPlugin {
+    handle: NonNull<PluginHandle>,
+    destroy: DestroyFn,
+    path: PathBuf,
+    // Declared last so it is dropped after the instance
+    _library: Library,
+}
+
+// The handle only holds a `Box<dyn MediaPlugin + Send + Sync>`
+unsafe impl Send for DynamicMediaPlugin {}
+unsafe impl Sync for DynamicMediaPlugin {}
+
+impl DynamicMediaPlugin {
+    /// Library file the plugin was loaded from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn inner(&self) -> &(dyn MediaPlugin + Send + Sync) {
+        unsafe { self.handle.as_ref() }.plugin.as_ref()
+    }
+
+    fn inner_mut(&mut self) -> &mut (dyn MediaPlugin + Send + Sync) {
+        unsafe { self.handle.as_mut() }.plugin.as_mut()
+    }
+}
+
+impl Drop for DynamicMediaPlugin {
+    fn drop(&mut self) {
+        unsafe { (self.destroy)(self.handle.as_ptr()) }
+    }
+}
+
+impl std::fmt::Debug for DynamicMediaPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DynamicMediaPlugin")
+            .field("path", &self.path)
+            .field("plugin", &self.inner())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl BasePlugin for DynamicMediaPlugin {
+    fn metadata(&self) -> PluginMetadata {
+        self.inner().metadata()
+    }
+
+    async fn initialize(&mut self, context: &PluginContext) -> SdkResult<()> {
+        self.inner_mut().initialize(context).await
+    }
+
+    async fn start(&mut self) -> SdkResult<()> {
+        self.inner_mut().start().await
+    }
+
+    async fn stop(&mut self) -> SdkResult<()> {
+        self.inner_mut().stop().await
+    }
+
+    fn status(&self) -> PluginStatus {
+        self.inner().status()
+    }
+
+    async fn configure(&mut self, config: PluginConfig) -> SdkResult<()> {
+        self.inner_mut().configure(config).await
+    }
+}
+
+#[async_trait]
+impl MediaPlugin for DynamicMediaPlugin {
+    async fn search(&self, query: &SearchQuery) -> SdkResult<SearchResult> {
+        self.inner().search(query).await
+    }
+
+    async fn get_track(&self, track_id: &str) -> SdkResult<Track> {
+        self.inner().get_track(track_id).await
+    }
+
+    async fn get_media_stream(&self, track_id: &str, req: &StreamRequest) -> SdkResult<StreamSource> {
+        self.inner().get_media_stream(track_id, req).await
+    }
+
+    async fn get_album(&self, album_id: &str) -> SdkResult<Album> {
+        self.inner().get_album(album_id).await
+    }
+
+    async fn get_artist(&self, artist_id: &str) -> SdkResult<Artist> {
+        self.inner().get_artist(artist_id).await
+    }
+
+    async fn get_playlist(&self, playlist_id: &str) -> SdkResult<Playlist> {
+        self.inner().get_playlist(playlist_id).await
+    }
+
+    async fn is_track_available(&self, track_id: &str) -> SdkResult<bool> {
+        self.inner().is_track_available(track_id).await
+    }
+
+    async fn get_user_library(&self) -> SdkResult<Vec<Track>> {
+        self.inner().get_user_library().await
+    }
+
+    async fn get_user_playlists(&self) -> SdkResult<Vec<Playlist>> {
+        self.inner().get_user_playlists().await
+    }
+
+    async fn get_recommendations(&self, seed: &RecommendationSeed) -> SdkResult<Vec<Track>> {
+        self.inner().get_recommendations(seed).await
+    }
+
+    async fn get_related_tracks(&self, track_id: &str, limit: usize) -> SdkResult<Vec<Track>> {
+        self.inner().get_related_tracks(track_id, limit).await
+    }
+
+    async fn get_lyrics(&self, track_id: &str) -> SdkResult<Option<Lyrics>> {
+        self.inner().get_lyrics(track_id).await
+    }
+
+    fn as_download(&self) -> Option<&dyn MediaDownloadPlugin> {
+        self.inner().as_download()
+    }
+
+    fn as_playlist_write(&self) -> Option<&dyn MediaPlaylistWritePlugin> {
+        self.inner().as_playlist_write()
+    }
+
+    fn as_favorites(&self) -> Option<&dyn MediaFavoritesPlugin> {
+        self.inner().as_favorites()
+    }
+}
@@ -2,6 +2,8 @@
 
 pub mod wasm;
 pub mod dynamic;
+mod wrapper;
 
 pub use wasm::WasmPluginLoader;
-pub use dynamic::DynamicPluginLoader;
\ No newline at end of file
+pub use dynamic::{DynamicMediaPlugin, DynamicPluginLoader};
+pub use wrapper::ExternalMediaPluginWrapper;
@@ -0,0 +1,153 @@
+//! Wrapper registering external media plugins (WASM, dynamic libraries) to the
+//! media factory. Every call into the plugin is isolated: a panic fails that
+//! call with an error instead of taking the host down.
+
+use std::any::Any;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+
+use async_trait::async_trait;
+use futures::FutureExt;
+use music_plugin_sdk::errors::PluginError as SdkError;
+use music_plugin_sdk::traits::media::{MediaDownloadPlugin, MediaFavoritesPlugin, MediaPlaylistWritePlugin, MediaPlugin};
+use music_plugin_sdk::traits::BasePlugin;
+use music_plugin_sdk::types::base::{
+    PluginConfig, PluginContext, PluginMetadata, PluginResult as SdkResult, PluginStatus,
+};
+use music_plugin_sdk::types::media::{
+    Album, Artist, Lyrics, Playlist, RecommendationSeed, SearchQuery, SearchResult, StreamRequest, StreamSource,
+    Track,
+};
+
+#[derive(Debug)]
+pub struct ExternalMediaPluginWrapper {
+    /// Read once at load, so it stays available after the plugin panicked
+    metadata: PluginMetadata,
+    inner: Box<dyn MediaPlugin + Send + Sync>,
+}
+
+impl ExternalMediaPluginWrapper {
+    pub fn new(inner: Box<dyn MediaPlugin + Send + Sync>) -> Self {
+        Self {
+            metadata: inner.metadata(),
+            inner,
+        }
+    }
+
+    /// Run a plugin call, turning a panic into an error
+    async fn guarded<T>(&self, call: &str, future: impl Future<Output = SdkResult<T>>) -> SdkResult<T> {
+        guard(&self.metadata.name, call, future).await
+    }
+}
+
+async fn guard<T>(plugin: &str, call: &str, future: impl Future<Output = SdkResult<T>>) -> SdkResult<T> {
+    match AssertUnwindSafe(future).catch_unwind().await {
+        Ok(result) => result,
+        Err(panic) => {
+            let message = panic_message(panic.as_ref());
+            tracing::error!("Plugin {} panicked in {}: {}", plugin, call, message);
+            Err(SdkError::Internal(format!("Plugin panicked in {}: {}", call, message)))
+        }
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+#[async_trait]
+impl MediaPlugin for ExternalMediaPluginWrapper {
+    async fn search(&self, query: &SearchQuery) -> SdkResult<SearchResult> {
+        self.guarded("search", self.inner.search(query)).await
+    }
+
+    async fn get_track(&self, track_id: &str) -> SdkResult<Track> {
+        self.guarded("get_track", self.inner.get_track(track_id)).await
+    }
+
+    async fn get_media_stream(&self, track_id: &str, req: &StreamRequest) -> SdkResult<StreamSource> {
+        self.guarded("get_media_stream", self.inner.get_media_stream(track_id, req)).await
+    }
+
+    async fn get_album(&self, album_id: &str) -> SdkResult<Album> {
+        self.guarded("get_album", self.inner.get_album(album_id)).await
+    }
+
+    async fn get_artist(&self, artist_id: &str) -> SdkResult<Artist> {
+        self.guarded("get_artist", self.inner.get_artist(artist_id)).await
+    }
+
+    async fn get_playlist(&self, playlist_id: &str) -> SdkResult<Playlist> {
+        self.guarded("get_playlist", self.inner.get_playlist(playlist_id)).await
+    }
+
+    async fn is_track_available(&self, track_id: &str) -> SdkResult<bool> {
+        self.guarded("is_track_available", self.inner.is_track_available(track_id)).await
+    }
+
+    async fn get_user_library(&self) -> SdkResult<Vec<Track>> {
+        self.guarded("get_user_library", self.inner.get_user_library()).await
+    }
+
+    async fn get_user_playlists(&self) -> SdkResult<Vec<Playlist>> {
+        self.guarded("get_user_playlists", self.inner.get_user_playlists()).await
+    }
+
+    async fn get_recommendations(&self, seed: &RecommendationSeed) -> SdkResult<Vec<Track>> {
+        self.guarded("get_recommendations", self.inner.get_recommendations(seed)).await
+    }
+
+    async fn get_related_tracks(&self, track_id: &str, limit: usize) -> SdkResult<Vec<Track>> {
+        self.guarded("get_related_tracks", self.inner.get_related_tracks(track_id, limit)).await
+    }
+
+    async fn get_lyrics(&self, track_id: &str) -> SdkResult<Option<Lyrics>> {
+        self.guarded("get_lyrics", self.inner.get_lyrics(track_id)).await
+    }
+
+    fn as_download(&self) -> Option<&dyn MediaDownloadPlugin> {
+        self.inner.as_download()
+    }
+
+    fn as_playlist_write(&self) -> Option<&dyn MediaPlaylistWritePlugin> {
+        self.inner.as_playlist_write()
+    }
+
+    fn as_favorites(&self) -> Option<&dyn MediaFavoritesPlugin> {
+        self.inner.as_favorites()
+    }
+}
+
+#[async_trait]
+impl BasePlugin for ExternalMediaPluginWrapper {
+    fn metadata(&self) -> PluginMetadata {
+        self.metadata.clone()
+    }
+
+    async fn initialize(&mut self, _context: &PluginContext) -> SdkResult<()> {
+        // We can't call initialize on the inner plugin since it's behind a Box
+        // External plugins should handle initialization themselves
+        Ok(())
+    }
+
+    async fn start(&mut self) -> SdkResult<()> {
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> SdkResult<()> {
+        // Lets the plugin release its resources before it is unloaded
+        guard(&self.metadata.name, "stop", self.inner.stop()).await
+    }
+
+    fn status(&self) -> PluginStatus {
+        PluginStatus::Running
+    }
+
+    async fn configure(&mut self, config: PluginConfig) -> SdkResult<()> {
+        guard(&self.metadata.name, "configure", self.inner.configure(config)).await
+    }
+}
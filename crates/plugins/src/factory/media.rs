@@ -11,6 +11,8 @@ use uuid::Uuid;
 use types::settings::music::{MusicSourceSelection, MusicSourceMode};
 use music_plugin_sdk::traits::media::MediaPlugin;
 
+use crate::system::state::{PluginStateChange, DEFAULT_PLUGIN_ENABLED};
+
 /// Audio plugin factory for true polymorphic access to media plugins
 pub struct MediaPluginFactory {
     /// Direct storage of MediaPlugin trait objects - enables true polymorphism!
@@ -53,6 +55,12 @@ impl MediaPluginFactory {
         self.enabled_plugins.insert(plugin_id, enabled);
     }
     
+    /// Remove a media plugin, returning its instance
+    pub fn unregister_media_plugin(&mut self, plugin_id: Uuid) -> Option<Arc<tokio::sync::Mutex<dyn MediaPlugin + Send + Sync>>> {
+        self.enabled_plugins.remove(&plugin_id);
+        self.media_plugins.remove(&plugin_id)
+    }
+
     /// Update media plugin status
     pub fn update_media_plugin_status(&mut self, plugin_id: Uuid, enabled: bool) {
         self.enabled_plugins.insert(plugin_id, enabled);
@@ -71,6 +79,11 @@ impl MediaPluginFactory {
         // Return MediaPlugin trait object directly - pure polymorphism!
         self.media_plugins.get(&plugin_id).cloned()
     }
+
+    /// Get a registered MediaPlugin whether or not it is enabled, e.g. to configure it
+    pub fn get_registered_media_plugin(&self, plugin_id: Uuid) -> Option<Arc<tokio::sync::Mutex<dyn MediaPlugin + Send + Sync>>> {
+        self.media_plugins.get(&plugin_id).cloned()
+    }
     
     
     /// Get MediaPlugins by music source selection
@@ -105,6 +118,25 @@ impl MediaPluginFactory {
         self.enabled_plugins.insert(plugin_id, enabled);
     }
     
+    /// Enabled flag of a registered plugin, `None` when it is not registered
+    pub fn is_plugin_enabled(&self, plugin_id: Uuid) -> Option<bool> {
+        if !self.media_plugins.contains_key(&plugin_id) {
+            return None;
+        }
+        Some(self.enabled_plugins.get(&plugin_id).copied().unwrap_or(false))
+    }
+
+    /// Mirror a persisted state change. Unknown or non-media plugins are ignored.
+    pub fn apply_state_change(&mut self, change: &PluginStateChange) {
+        let Ok(plugin_id) = Uuid::parse_str(&change.plugin_id) else {
+            return;
+        };
+        if self.media_plugins.contains_key(&plugin_id) {
+            let enabled = change.enabled.unwrap_or(DEFAULT_PLUGIN_ENABLED);
+            self.enabled_plugins.insert(plugin_id, enabled);
+        }
+    }
+
     /// Get all registered plugin IDs
     pub fn get_plugin_ids(&self) -> Vec<Uuid> {
         self.media_plugins.keys().copied().collect()
@@ -77,7 +77,9 @@ impl BilibiliPlugin {
         if let Some(body) = subtitle_content.get("body").and_then(|b| b.as_array()) {
             let lines: Vec<LyricLine> = body.iter()
                 .filter_map(|line| {
-                    let from = line.get("from").and_then(|f| f.as_u64()).unwrap_or(0) as u32;
+                    // Subtitle times are in seconds
+                    let from = line.get("from").and_then(|f| f.as_f64()).unwrap_or(0.0);
+                    let from = (from * 1000.0).round() as u32;
                     let content = line.get("content")
                         .and_then(|c| c.as_str())
                         .unwrap_or("")
@@ -149,7 +151,7 @@ impl MediaPlugin for BilibiliPlugin {
         let response = wbi_request(
             &self.http,
             reqwest::Method::GET,
-            "https://api.bilibili.com",
+            &self.api_base(),
             "/x/web-interface/wbi/search/type",
             params,
             self.session_data.as_deref(),
@@ -228,7 +230,7 @@ impl MediaPlugin for BilibiliPlugin {
         let response = wbi_request(
             &self.http,
             reqwest::Method::GET,
-            "https://api.bilibili.com",
+            &self.api_base(),
             "/x/web-interface/view",
             params,
             self.session_data.as_deref(),
@@ -317,7 +319,7 @@ impl MediaPlugin for BilibiliPlugin {
         let response = wbi_request(
             &self.http,
             reqwest::Method::GET,
-            "https://api.bilibili.com",
+            &self.api_base(),
             "/x/space/wbi/acc/info",
             params,
             self.session_data.as_deref(),
@@ -342,7 +344,7 @@ impl MediaPlugin for BilibiliPlugin {
         let response = wbi_request(
             &self.http,
             reqwest::Method::GET,
-            "https://api.bilibili.com",
+            &self.api_base(),
             "/x/v3/fav/resource/list",
             params,
             self.session_data.as_deref(),
@@ -367,7 +369,7 @@ impl MediaPlugin for BilibiliPlugin {
         let response = wbi_request(
             &self.http,
             reqwest::Method::GET,
-            "https://api.bilibili.com",
+            &self.api_base(),
             "/x/web-interface/view",
             params,
             self.session_data.as_deref(),
@@ -399,7 +401,7 @@ impl MediaPlugin for BilibiliPlugin {
         let response = wbi_request(
             &self.http,
             reqwest::Method::GET,
-            "https://api.bilibili.com",
+            &self.api_base(),
             "/x/player/wbi/playurl",
             wbi_params,
             self.session_data.as_deref(),
@@ -435,6 +437,46 @@ impl MediaPlugin for BilibiliPlugin {
         Err(PluginError::Internal("No available audio stream".to_string()))
     }
 
+    async fn get_recommendations(&self, seed: &RecommendationSeed) -> PluginResult<Vec<Track>> {
+        // Only track seeds are supported: each one contributes its related videos
+        let bvids: Vec<&str> = seed.track_ids
+            .iter()
+            .filter_map(|id| id.strip_prefix("bilibili:"))
+            .take(5)
+            .collect();
+        if bvids.is_empty() {
+            return Err(PluginError::InvalidInput("No bilibili track in recommendation seed".to_string()));
+        }
+
+        let mut lists = Vec::new();
+        for bvid in bvids {
+            let mut params = BTreeMap::new();
+            params.insert("bvid".to_string(), bvid.to_string());
+
+            let response = wbi_request(
+                &self.http,
+                reqwest::Method::GET,
+                &self.api_base(),
+                "/x/web-interface/archive/related",
+                params,
+                self.session_data.as_deref(),
+                &self.wbi_salt_cache,
+            ).await.map_err(|e| PluginError::Internal(format!("Get related videos failed: {}", e)))?;
+
+            let videos: Vec<BilibiliRelatedVideo> = serde_json::from_value(response)
+                .map_err(|e| PluginError::SerializationError(format!("Failed to parse related videos: {}", e)))?;
+            lists.push(videos.into_iter().map(convert::convert_related_video).collect());
+        }
+
+        let limit = seed.limit.unwrap_or(20) as usize;
+        Ok(convert::fuse_related(lists, &seed.track_ids, limit))
+    }
+
+    async fn get_lyrics(&self, track_id: &str) -> PluginResult<Option<Lyrics>> {
+        // Subtitles are fetched (and cached) along with the video details
+        Ok(self.get_track(track_id).await?.lyrics)
+    }
+
     async fn is_track_available(&self, track_id: &str) -> PluginResult<bool> {
         match self.get_track(track_id).await {
             Ok(_) => Ok(true),
@@ -446,7 +488,7 @@ impl MediaPlugin for BilibiliPlugin {
         let response = wbi_request(
             &self.http,
             reqwest::Method::GET,
-            "https://api.bilibili.com",
+            &self.api_base(),
             "/x/space/myinfo",
             BTreeMap::new(),
             self.session_data.as_deref(),
@@ -462,7 +504,7 @@ impl MediaPlugin for BilibiliPlugin {
         let response = wbi_request( 
             &self.http,
             reqwest::Method::GET,
-            "https://api.bilibili.com",
+            &self.api_base(),
             "/x/v3/fav/folder/created/list-all",
             params,
             self.session_data.as_deref(),
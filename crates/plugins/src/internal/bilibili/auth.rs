@@ -138,7 +138,7 @@ impl BilibiliPlugin {
         let response = super::wbi::wbi_request(
             &self.http,
             reqwest::Method::GET,
-            "https://api.bilibili.com",
+            &self.api_base(),
             "/x/space/myinfo",
             std::collections::BTreeMap::new(),
             self.session_data.as_deref(),
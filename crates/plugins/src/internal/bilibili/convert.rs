@@ -101,6 +101,66 @@ pub fn convert_track_response(track_id: &str, bvid: &str, video_details: Bilibil
 }
 
 
+/// Convert a related video entry to SDK Track format
+pub fn convert_related_video(video: BilibiliRelatedVideo) -> Track {
+    Track {
+        id: format!("bilibili:{}", video.bvid),
+        provider: Some("bilibili".to_string()),
+        provider_id: Some(video.bvid),
+        title: video.title,
+        artist: video.owner.name,
+        album: None,
+        album_ref: None,
+        disc_number: None,
+        track_number: None,
+        duration: Some(video.duration as u32 * 1000),
+        cover_url: Some(video.pic),
+        url: None,
+        quality: None,
+        preview_url: None,
+        isrc: None,
+        popularity: Some(video.stat.view as u32),
+        availability: None,
+        lyrics: None,
+        metadata: {
+            let mut meta = std::collections::HashMap::new();
+            meta.insert("pubdate".to_string(), video.pubdate.to_string());
+            meta.insert("mid".to_string(), video.owner.mid.to_string());
+            meta
+        },
+    }
+}
+
+/// Merge the related lists of several seeds into one ranking (reciprocal rank
+/// fusion): tracks related to more seeds, and ranked higher, come first.
+/// Tracks whose ID is in `exclude` (the seeds themselves) are dropped.
+pub fn fuse_related(lists: Vec<Vec<Track>>, exclude: &[String], limit: usize) -> Vec<Track> {
+    const K: f64 = 60.0;
+    let mut scored: Vec<(f64, usize, Track)> = Vec::new();
+    let mut index: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+
+    for list in lists {
+        for (rank, track) in list.into_iter().enumerate() {
+            if exclude.contains(&track.id) {
+                continue;
+            }
+            let score = 1.0 / (K + rank as f64 + 1.0);
+            match index.get(&track.id) {
+                Some(&i) => scored[i].0 += score,
+                None => {
+                    index.insert(track.id.clone(), scored.len());
+                    let order = scored.len();
+                    scored.push((score, order, track));
+                }
+            }
+        }
+    }
+
+    // Ties keep first-seen order
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored.into_iter().take(limit).map(|(_, _, track)| track).collect()
+}
+
 /// Convert Bilibili user info to SDK Artist format
 pub fn convert_artist_response(artist_id: &str, user_info: BilibiliUserInfo) -> PluginResult<Artist> {
     Ok(Artist {
@@ -231,3 +291,32 @@ pub fn parse_duration(duration_str: &str) -> u32 {
         _ => 0,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video(bvid: &str) -> Track {
+        convert_related_video(BilibiliRelatedVideo {
+            bvid: bvid.to_string(),
+            title: bvid.to_string(),
+            pic: String::new(),
+            owner: BilibiliOwner { mid: 1, name: "up".to_string(), face: String::new() },
+            stat: BilibiliRelatedStat::default(),
+            duration: 200,
+            pubdate: 0,
+        })
+    }
+
+    #[test]
+    fn fuse_related_prefers_tracks_shared_by_seeds() {
+        let lists = vec![
+            vec![video("BV1"), video("BV2"), video("BV3")],
+            vec![video("BVseed"), video("BV3"), video("BV4")],
+        ];
+        let fused = fuse_related(lists, &["bilibili:BVseed".to_string()], 3);
+        let ids: Vec<_> = fused.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["bilibili:BV3", "bilibili:BV1", "bilibili:BV2"]);
+        assert_eq!(fused[0].duration, Some(200_000));
+    }
+}
@@ -4,7 +4,7 @@ use uuid::Uuid;
 use reqwest::Client;
 use tokio::sync::RwLock;
 use std::time::{Duration, Instant};
-use std::sync::{Arc, Mutex as StdMutex};
+use std::sync::{Arc, Mutex as StdMutex, RwLock as StdRwLock};
 
 use crate::system::core::*;
 use crate::system::types::*;
@@ -12,6 +12,9 @@ use crate::PluginResult;
 use music_plugin_sdk::traits::BasePlugin;
 
 
+/// 默认 API 地址
+pub const DEFAULT_API_BASE: &str = "https://api.bilibili.com";
+
 /// 字幕缓存条目，包含内容和过期时间
 #[derive(Debug, Clone)]
 pub struct SubtitleCacheEntry {
@@ -39,6 +42,8 @@ pub struct BilibiliPlugin {
     pub max_cache_entries: usize,
     /// 缓存条目默认过期时间（24小时）
     pub default_cache_ttl: Duration,
+    /// 自建 API 镜像地址，未设置时使用默认地址
+    pub api_endpoint: Arc<StdRwLock<Option<String>>>,
 }
 
 impl BilibiliPlugin {
@@ -78,9 +83,18 @@ impl BilibiliPlugin {
             subtitle_cache: Arc::new(RwLock::new(std::collections::HashMap::new())),
             max_cache_entries: 100, // 最多缓存100个字幕
             default_cache_ttl: Duration::from_secs(24 * 60 * 60), // 24小时过期
+            api_endpoint: Arc::new(StdRwLock::new(None)),
         }
     }
 
+    /// 当前使用的 API 地址：已配置的镜像，否则为默认地址
+    pub fn api_base(&self) -> String {
+        self.api_endpoint
+            .read()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| DEFAULT_API_BASE.to_string())
+    }
 
     /// 清理过期的字幕缓存条目
     pub async fn cleanup_expired_subtitle_cache(&self) {
@@ -168,6 +182,7 @@ impl BasePlugin for BilibiliPlugin {
             capabilities: vec![
                 music_plugin_sdk::types::base::PluginCapability::Search,
                 music_plugin_sdk::types::base::PluginCapability::Playback,
+                music_plugin_sdk::types::base::PluginCapability::Lyrics,
                 music_plugin_sdk::types::base::PluginCapability::Network
             ],
             min_sdk_version: "1.0.0".to_string(),
@@ -200,8 +215,10 @@ impl BasePlugin for BilibiliPlugin {
         }
     }
 
-    async fn configure(&mut self, _config: music_plugin_sdk::types::base::PluginConfig) -> music_plugin_sdk::types::base::PluginResult<()> {
-        // Handle configuration if needed
+    async fn configure(&mut self, config: music_plugin_sdk::types::base::PluginConfig) -> music_plugin_sdk::types::base::PluginResult<()> {
+        if let Some(endpoint) = config.endpoint() {
+            *self.api_endpoint.write().unwrap() = endpoint.map(|url| url.trim_end_matches('/').to_string());
+        }
         Ok(())
     }
 }
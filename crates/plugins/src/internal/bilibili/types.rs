@@ -46,6 +46,26 @@ pub struct BilibiliVideoDetails {
     pub subtitle: Option<BilibiliSubtitleInfo>,
 }
 
+/// Entry of `/x/web-interface/archive/related`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BilibiliRelatedVideo {
+    pub bvid: String,
+    pub title: String,
+    pub pic: String,
+    pub owner: BilibiliOwner,
+    #[serde(default)]
+    pub stat: BilibiliRelatedStat,
+    pub duration: u64,
+    #[serde(default)]
+    pub pubdate: u64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct BilibiliRelatedStat {
+    #[serde(default)]
+    pub view: u64,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BilibiliOwner {
     pub mid: u64,
@@ -0,0 +1,257 @@
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+
+use music_plugin_sdk::{
+    traits::{MediaFavoritesPlugin, MediaPlaylistWritePlugin, MediaPlugin},
+    types::{*, media::{QualityPreference, StreamRequest, StreamSource, StreamProtocol}},
+    errors::PluginError
+};
+use super::plugin::SpotifyPlugin;
+use super::types::*;
+use super::convert::{self, PROVIDER};
+
+/// Largest page the Web API returns
+pub(super) const MAX_PAGE_SIZE: u32 = 50;
+/// Seeds the recommendations endpoint accepts in total
+const MAX_RECOMMENDATION_SEEDS: usize = 5;
+
+impl SpotifyPlugin {
+    /// Send a Web API request and return the response body. An access token
+    /// rejected as expired is refreshed once and the request repeated.
+    async fn api_request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<&serde_json::Value>,
+    ) -> PluginResult<String> {
+        let url = format!("{}{}", self.api_base(), path);
+        let mut retried = false;
+        loop {
+            let token = self.access_token().await?;
+            let mut request = self.http.request(method.clone(), &url)
+                .bearer_auth(&token)
+                .query(query);
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+            let resp = request
+                .send().await
+                .map_err(|e| PluginError::NetworkError(format!("Spotify request failed: {}", e)))?;
+
+            let status = resp.status();
+            if status == reqwest::StatusCode::UNAUTHORIZED && !retried {
+                retried = true;
+                self.refresh_tokens(&token).await?;
+                continue;
+            }
+            if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                let retry_after = resp.headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("?")
+                    .to_string();
+                return Err(PluginError::RateLimitExceeded(format!("Retry after {}s", retry_after)));
+            }
+            if status == reqwest::StatusCode::NOT_FOUND {
+                return Err(PluginError::NotFound(path.to_string()));
+            }
+            let text = resp.text().await
+                .map_err(|e| PluginError::NetworkError(format!("Failed to read response: {}", e)))?;
+            if !status.is_success() {
+                return Err(PluginError::NetworkError(format!("Spotify API returned {}: {}", status, text)));
+            }
+            return Ok(text);
+        }
+    }
+
+    /// GET a Web API resource
+    pub(super) async fn api_get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, String)]) -> PluginResult<T> {
+        let text = self.api_request(reqwest::Method::GET, path, query, None).await?;
+        serde_json::from_str(&text)
+            .map_err(|e| PluginError::SerializationError(format!("Failed to parse response of {}: {}", path, e)))
+    }
+
+    /// Send a change to a Web API resource, e.g. POST or DELETE with a JSON body
+    pub(super) async fn api_send(&self, method: reqwest::Method, path: &str, body: &serde_json::Value) -> PluginResult<()> {
+        self.api_request(method, path, &[], Some(body)).await.map(|_| ())
+    }
+}
+
+fn page_info<T>(page: &SpotifyPage<T>) -> PageInfo {
+    PageInfo {
+        limit: page.limit,
+        offset: page.offset,
+        next_cursor: None,
+        total: Some(page.total),
+        has_more: page.next.is_some(),
+    }
+}
+
+fn slice<T, U>(page: Option<SpotifyPage<T>>, convert: impl Fn(T) -> Option<U>) -> SearchSlice<U> {
+    match page {
+        Some(page) => {
+            let info = page_info(&page);
+            SearchSlice { items: page.into_items().filter_map(convert).collect(), page: info }
+        }
+        None => SearchSlice::default(),
+    }
+}
+
+/// `type` parameter of the search endpoint
+fn search_types(types: &[SearchType]) -> String {
+    if types.is_empty() || types.contains(&SearchType::All) {
+        return "track,album,artist,playlist".to_string();
+    }
+    types
+        .iter()
+        .map(|t| match t {
+            SearchType::Track => "track",
+            SearchType::Album => "album",
+            SearchType::Artist => "artist",
+            SearchType::Playlist => "playlist",
+            SearchType::All => unreachable!(),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Bitrate librespot streams at for a quality preference, in kbps
+fn bitrate(quality: &QualityPreference) -> u32 {
+    match quality {
+        QualityPreference::Low => 96,
+        QualityPreference::Medium => 160,
+        QualityPreference::Auto | QualityPreference::High | QualityPreference::Lossless => 320,
+        QualityPreference::Qn(kbps) => *kbps,
+    }
+}
+
+fn require_track_id(track_id: &str) -> PluginResult<&str> {
+    convert::parse_track_id(track_id)
+        .ok_or_else(|| PluginError::InvalidInput(format!("Invalid Spotify track ID: {}", track_id)))
+}
+
+#[async_trait]
+impl MediaPlugin for SpotifyPlugin {
+    async fn search(&self, query: &SearchQuery) -> PluginResult<SearchResult> {
+        let limit = query.page.as_ref().and_then(|p| p.limit).unwrap_or(20).clamp(1, MAX_PAGE_SIZE);
+        let offset = query.page.as_ref().and_then(|p| p.offset).unwrap_or(0);
+
+        let response: SpotifySearchResponse = self.api_get("/search", &[
+            ("q", query.query.clone()),
+            ("type", search_types(&query.types)),
+            ("limit", limit.to_string()),
+            ("offset", offset.to_string()),
+        ]).await?;
+
+        Ok(SearchResult {
+            provider: PROVIDER.to_string(),
+            tracks: slice(response.tracks, |t| convert::convert_track(t, None)),
+            albums: slice(response.albums, |a| Some(convert::convert_album(a))),
+            artists: slice(response.artists, |a| Some(convert::convert_artist(a))),
+            playlists: slice(response.playlists, |p| Some(convert::convert_playlist(p))),
+            genres: SearchSlice::default(),
+            suggestions: None,
+            provider_context: None,
+        })
+    }
+
+    async fn get_track(&self, track_id: &str) -> PluginResult<Track> {
+        let id = require_track_id(track_id)?;
+        let track: SpotifyTrack = self.api_get(&format!("/tracks/{}", id), &[]).await?;
+        convert::convert_track(track, None)
+            .ok_or_else(|| PluginError::NotFound(format!("Spotify track {}", id)))
+    }
+
+    /// The librespot player streams the track itself; the source carries its
+    /// URI and the bitrate matching the requested quality
+    async fn get_media_stream(&self, track_id: &str, req: &StreamRequest) -> PluginResult<StreamSource> {
+        let id = require_track_id(track_id)?;
+        if self.tokens().is_none() {
+            return Err(PluginError::AuthenticationError("Sign in to Spotify to play its tracks".to_string()));
+        }
+        Ok(StreamSource {
+            url: convert::track_uri(id),
+            mime_type: Some("audio/ogg".into()),
+            container: Some("ogg".into()),
+            codec: Some("vorbis".into()),
+            bitrate: Some(bitrate(&req.quality)),
+            sample_rate: Some(44_100),
+            channels: Some(2),
+            protocol: Some(StreamProtocol::Other("librespot".into())),
+            expires_at: None,
+            headers: None,
+            drm: None,
+        })
+    }
+
+    async fn get_album(&self, album_id: &str) -> PluginResult<Album> {
+        let album: SpotifyAlbum = self.api_get(&format!("/albums/{}", album_id), &[]).await?;
+        Ok(convert::convert_album(album))
+    }
+
+    async fn get_artist(&self, artist_id: &str) -> PluginResult<Artist> {
+        let artist: SpotifyArtist = self.api_get(&format!("/artists/{}", artist_id), &[]).await?;
+        Ok(convert::convert_artist(artist))
+    }
+
+    async fn get_playlist(&self, playlist_id: &str) -> PluginResult<Playlist> {
+        let playlist: SpotifyPlaylist = self.api_get(&format!("/playlists/{}", playlist_id), &[]).await?;
+        Ok(convert::convert_playlist(playlist))
+    }
+
+    async fn is_track_available(&self, track_id: &str) -> PluginResult<bool> {
+        let track = self.get_track(track_id).await?;
+        Ok(track.availability.map(|a| a.can_stream).unwrap_or(true))
+    }
+
+    async fn get_user_library(&self) -> PluginResult<Vec<Track>> {
+        let page: SpotifyPage<SpotifySavedTrack> = self
+            .api_get("/me/tracks", &[("limit", MAX_PAGE_SIZE.to_string())])
+            .await?;
+        Ok(convert::convert_tracks(page.into_items().map(|saved| saved.track)))
+    }
+
+    async fn get_user_playlists(&self) -> PluginResult<Vec<Playlist>> {
+        let page: SpotifyPage<SpotifyPlaylist> = self
+            .api_get("/me/playlists", &[("limit", MAX_PAGE_SIZE.to_string())])
+            .await?;
+        Ok(page.into_items().map(convert::convert_playlist).collect())
+    }
+
+    async fn get_recommendations(&self, seed: &RecommendationSeed) -> PluginResult<Vec<Track>> {
+        let tracks: Vec<&str> = seed.track_ids.iter().filter_map(|id| convert::parse_track_id(id)).collect();
+        let seeds = tracks.len() + seed.artist_ids.len() + seed.genres.len();
+        if seeds == 0 {
+            return Err(PluginError::InvalidInput("No recommendation seed Spotify understands".to_string()));
+        }
+
+        // Track seeds first, then artists, then genres, up to the seed limit
+        let mut remaining = MAX_RECOMMENDATION_SEEDS;
+        let mut take = |ids: Vec<&str>| {
+            let taken: Vec<&str> = ids.into_iter().take(remaining).collect();
+            remaining -= taken.len();
+            taken.join(",")
+        };
+        let seed_tracks = take(tracks);
+        let seed_artists = take(seed.artist_ids.iter().map(String::as_str).collect());
+        let seed_genres = take(seed.genres.iter().map(String::as_str).collect());
+
+        let mut query = vec![("limit", seed.limit.unwrap_or(20).clamp(1, 100).to_string())];
+        for (key, value) in [("seed_tracks", seed_tracks), ("seed_artists", seed_artists), ("seed_genres", seed_genres)] {
+            if !value.is_empty() {
+                query.push((key, value));
+            }
+        }
+        let response: SpotifyRecommendations = self.api_get("/recommendations", &query).await?;
+        Ok(convert::convert_tracks(response.tracks))
+    }
+
+    fn as_playlist_write(&self) -> Option<&dyn MediaPlaylistWritePlugin> {
+        Some(self)
+    }
+
+    fn as_favorites(&self) -> Option<&dyn MediaFavoritesPlugin> {
+        Some(self)
+    }
+}
@@ -0,0 +1,264 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+use music_plugin_sdk::{
+    traits::MediaAuthPlugin,
+    types::media::*,
+    errors::PluginError
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use super::plugin::SpotifyPlugin;
+use super::types::*;
+
+const DEVICE_AUTHORIZE_URL: &str = "https://accounts.spotify.com/oauth2/device/authorize";
+const TOKEN_URL: &str = "https://accounts.spotify.com/api/token";
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+/// Streaming for librespot, the rest for the library and playlists
+const SCOPES: &str = "streaming user-read-private user-read-email user-library-read user-library-modify playlist-read-private playlist-read-collaborative playlist-modify-public playlist-modify-private";
+
+/// Sign-in started with the device-code flow: the user opens
+/// `verification_uri` on any device and enters `user_code`
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceLogin {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    /// Verification address with the code filled in
+    pub verification_uri_complete: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Outcome of one token request
+enum TokenPoll {
+    Granted(SpotifyTokens),
+    Pending,
+    Expired,
+    Denied(String),
+}
+
+impl SpotifyPlugin {
+    /// POST to the token endpoint
+    async fn request_token(&self, form: &[(&str, &str)]) -> PluginResult<Result<TokenResponse, TokenError>> {
+        let resp = self.http.post(TOKEN_URL)
+            .form(form)
+            .send().await
+            .map_err(|e| PluginError::NetworkError(format!("Spotify token request failed: {}", e)))?;
+        let success = resp.status().is_success();
+        let text = resp.text().await
+            .map_err(|e| PluginError::NetworkError(format!("Failed to read response: {}", e)))?;
+        let parsed = if success {
+            serde_json::from_str(&text).map(Ok)
+        } else {
+            serde_json::from_str(&text).map(Err)
+        };
+        parsed.map_err(|e| PluginError::SerializationError(format!("Failed to parse token response: {}", e)))
+    }
+
+    /// Ask for a device code to sign in with
+    pub async fn start_device_login(&self) -> PluginResult<DeviceLogin> {
+        let client_id = self.client_id.read().unwrap().clone();
+        let resp = self.http.post(DEVICE_AUTHORIZE_URL)
+            .form(&[("client_id", client_id.as_str()), ("scope", SCOPES)])
+            .send().await
+            .map_err(|e| PluginError::NetworkError(format!("Spotify device authorization failed: {}", e)))?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            return Err(PluginError::AuthenticationError(format!("Device authorization returned {}: {}", status, text)));
+        }
+        let auth: DeviceAuthorization = resp.json().await
+            .map_err(|e| PluginError::SerializationError(format!("Failed to parse device authorization: {}", e)))?;
+        Ok(DeviceLogin {
+            device_code: auth.device_code,
+            user_code: auth.user_code,
+            verification_uri: auth.verification_uri,
+            verification_uri_complete: auth.verification_uri_complete,
+            expires_at: Utc::now() + chrono::Duration::seconds(auth.expires_in),
+        })
+    }
+
+    /// Ask whether the user approved `device_code` yet
+    async fn poll_device_login(&self, device_code: &str) -> PluginResult<TokenPoll> {
+        let client_id = self.client_id.read().unwrap().clone();
+        let result = self.request_token(&[
+            ("grant_type", DEVICE_CODE_GRANT),
+            ("device_code", device_code),
+            ("client_id", client_id.as_str()),
+        ]).await?;
+        Ok(match result {
+            Ok(token) => TokenPoll::Granted(token.into_tokens(None)),
+            // slow_down only asks for a longer polling interval
+            Err(e) if e.error == "authorization_pending" || e.error == "slow_down" => TokenPoll::Pending,
+            Err(e) if e.error == "expired_token" => TokenPoll::Expired,
+            Err(e) => TokenPoll::Denied(e.error_description.unwrap_or(e.error)),
+        })
+    }
+
+    /// Current access token, refreshed first when it expired
+    pub(super) async fn access_token(&self) -> PluginResult<String> {
+        let tokens = self.tokens()
+            .ok_or_else(|| PluginError::AuthenticationError("Not signed in to Spotify".to_string()))?;
+        if !tokens.is_expired() {
+            return Ok(tokens.access_token);
+        }
+        self.refresh_tokens(&tokens.access_token).await?;
+        self.tokens()
+            .map(|t| t.access_token)
+            .ok_or_else(|| PluginError::AuthenticationError("Not signed in to Spotify".to_string()))
+    }
+
+    /// Replace the access token `stale`. Requests failing together refresh
+    /// once: whoever comes second finds the token already replaced. A refresh
+    /// token the server rejects signs the account out.
+    pub(super) async fn refresh_tokens(&self, stale: &str) -> PluginResult<()> {
+        let _guard = self.refresh_lock.lock().await;
+        let Some(tokens) = self.tokens() else {
+            return Err(PluginError::AuthenticationError("Not signed in to Spotify".to_string()));
+        };
+        if tokens.access_token != stale && !tokens.is_expired() {
+            return Ok(());
+        }
+        let Some(refresh_token) = tokens.refresh_token.clone() else {
+            self.store_tokens(None);
+            return Err(PluginError::AuthenticationError("Spotify session expired, sign in again".to_string()));
+        };
+
+        let client_id = self.client_id.read().unwrap().clone();
+        let result = self.request_token(&[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token.as_str()),
+            ("client_id", client_id.as_str()),
+        ]).await?;
+        match result {
+            Ok(token) => {
+                self.store_tokens(Some(token.into_tokens(Some(refresh_token))));
+                Ok(())
+            }
+            Err(e) => {
+                if e.error == "invalid_grant" {
+                    self.store_tokens(None);
+                }
+                Err(PluginError::AuthenticationError(format!(
+                    "Spotify token refresh failed: {}",
+                    e.error_description.unwrap_or(e.error)
+                )))
+            }
+        }
+    }
+
+    /// Fetch and remember the profile of the signed-in account
+    async fn fetch_user(&self) -> PluginResult<AuthUserInfo> {
+        let user: SpotifyUser = self.api_get("/me", &[]).await?;
+        let mut metadata = HashMap::new();
+        if let Some(product) = user.product {
+            metadata.insert("product".to_string(), product);
+        }
+        if let Some(country) = user.country {
+            metadata.insert("country".to_string(), country);
+        }
+        let info = AuthUserInfo {
+            user_id: user.id,
+            display_name: user.display_name,
+            avatar_url: user.images.first().map(|i| i.url.clone()),
+            metadata,
+        };
+        *self.user.write().unwrap() = Some(info.clone());
+        Ok(info)
+    }
+
+    /// Profile of the signed-in account, fetched when not known yet
+    pub async fn current_user(&self) -> PluginResult<AuthUserInfo> {
+        let cached = self.user.read().unwrap().clone();
+        match cached {
+            Some(user) => Ok(user),
+            None => self.fetch_user().await,
+        }
+    }
+}
+
+fn qr_status(status: QrCodeState, user_info: Option<AuthUserInfo>, error_message: Option<String>) -> QrCodeStatus {
+    QrCodeStatus {
+        status,
+        user_info,
+        // Tokens stay with the plugin and the host's encrypted store
+        session_token: None,
+        error_message,
+    }
+}
+
+#[async_trait]
+impl MediaAuthPlugin for SpotifyPlugin {
+    fn supported_auth_methods(&self) -> Vec<AuthMethod> {
+        // The device code is offered as a QR code of the verification address
+        vec![AuthMethod::QrCode]
+    }
+
+    fn is_authenticated(&self) -> bool {
+        self.tokens.read().unwrap().is_some()
+    }
+
+    fn get_user_info(&self) -> Option<AuthUserInfo> {
+        self.user.read().unwrap().clone()
+    }
+
+    async fn logout(&mut self) -> PluginResult<()> {
+        self.store_tokens(None);
+        Ok(())
+    }
+
+    async fn refresh_auth(&mut self) -> PluginResult<()> {
+        let tokens = self.tokens()
+            .ok_or_else(|| PluginError::AuthenticationError("Not signed in to Spotify".to_string()))?;
+        self.refresh_tokens(&tokens.access_token).await
+    }
+
+    async fn generate_qrcode(&mut self) -> PluginResult<QrCodeResponse> {
+        let login = self.start_device_login().await?;
+        Ok(QrCodeResponse {
+            content: login.verification_uri_complete.unwrap_or(login.verification_uri),
+            image_url: None,
+            qrcode_key: login.device_code,
+            expires_at: Some(login.expires_at),
+        })
+    }
+
+    async fn check_qrcode_status(&self, qrcode_key: &str) -> PluginResult<QrCodeStatus> {
+        match self.poll_device_login(qrcode_key).await? {
+            TokenPoll::Granted(tokens) => {
+                self.store_tokens(Some(tokens));
+                let user = match self.fetch_user().await {
+                    Ok(user) => Some(user),
+                    Err(e) => {
+                        tracing::warn!("Signed in to Spotify but failed to fetch the profile: {}", e);
+                        None
+                    }
+                };
+                Ok(qr_status(QrCodeState::Success, user, None))
+            }
+            TokenPoll::Pending => Ok(qr_status(QrCodeState::WaitingForConfirmation, None, None)),
+            TokenPoll::Expired => Ok(qr_status(
+                QrCodeState::Expired,
+                None,
+                Some("The sign-in code expired, request a new one".to_string()),
+            )),
+            TokenPoll::Denied(reason) => Ok(qr_status(QrCodeState::Failed, None, Some(reason))),
+        }
+    }
+
+    async fn send_sms_code(&mut self, _phone: &str, _country_code: Option<&str>) -> PluginResult<SmsResponse> {
+        Err(PluginError::NotSupported("SMS authentication not supported for Spotify".to_string()))
+    }
+
+    async fn verify_sms_code(&mut self, _phone: &str, _code: &str) -> PluginResult<AuthResult> {
+        Err(PluginError::NotSupported("SMS authentication not supported for Spotify".to_string()))
+    }
+
+    async fn login_with_password(&mut self, _username: &str, _password: &str) -> PluginResult<AuthResult> {
+        Err(PluginError::NotSupported("Password authentication not supported for Spotify".to_string()))
+    }
+
+    async fn submit_verification(&mut self, _session_id: &str, _data: HashMap<String, String>) -> PluginResult<AuthResult> {
+        Err(PluginError::NotSupported("Additional verification not supported for Spotify".to_string()))
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use music_plugin_sdk::types::media::{
+    Album, AlbumRef, Artist, Availability, Image, Playlist, PlaylistOwner, Track,
+};
+
+use super::types::*;
+
+pub const PROVIDER: &str = "spotify";
+
+/// `spotify:track:<id>` URIs serve as track IDs; librespot plays them as is
+pub fn track_uri(id: &str) -> String {
+    format!("spotify:track:{}", id)
+}
+
+/// Base62 ID of a track given as URI, open.spotify.com link or bare ID
+pub fn parse_track_id(track_id: &str) -> Option<&str> {
+    let id = track_id
+        .strip_prefix("spotify:track:")
+        .or_else(|| track_id.strip_prefix("https://open.spotify.com/track/"))
+        .unwrap_or(track_id);
+    let id = id.split(['?', '/']).next().unwrap_or(id);
+    (id.len() == 22 && id.chars().all(|c| c.is_ascii_alphanumeric())).then_some(id)
+}
+
+fn images(images: &[SpotifyImage]) -> Vec<Image> {
+    images
+        .iter()
+        .map(|i| Image { url: i.url.clone(), width: i.width, height: i.height })
+        .collect()
+}
+
+/// Spotify lists images largest first
+fn largest(images: &[SpotifyImage]) -> Option<String> {
+    images.first().map(|i| i.url.clone())
+}
+
+fn smallest(images: &[SpotifyImage]) -> Option<String> {
+    images.last().map(|i| i.url.clone())
+}
+
+fn artist_names(artists: &[SpotifyArtistRef]) -> String {
+    artists.iter().map(|a| a.name.as_str()).collect::<Vec<_>>().join(", ")
+}
+
+/// Release dates have day, month or year precision
+fn parse_release_date(date: &str) -> Option<chrono::DateTime<Utc>> {
+    let padded = match date.len() {
+        4 => format!("{}-01-01", date),
+        7 => format!("{}-01", date),
+        _ => date.to_string(),
+    };
+    let day = NaiveDate::parse_from_str(&padded, "%Y-%m-%d").ok()?;
+    Some(Utc.from_utc_datetime(&day.and_hms_opt(0, 0, 0)?))
+}
+
+/// `None` for tracks without an ID (local files of the user)
+pub fn convert_track(track: SpotifyTrack, album: Option<&SpotifyAlbumRef>) -> Option<Track> {
+    let id = track.id.clone()?;
+    let album = track.album.as_ref().or(album);
+    let mut metadata = HashMap::new();
+    if track.explicit {
+        metadata.insert("explicit".to_string(), "true".to_string());
+    }
+    if let Some(artist_id) = track.artists.first().and_then(|a| a.id.clone()) {
+        metadata.insert("artist_id".to_string(), artist_id);
+    }
+    Some(Track {
+        id: track_uri(&id),
+        provider: Some(PROVIDER.to_string()),
+        provider_id: Some(id),
+        title: track.name,
+        artist: artist_names(&track.artists),
+        album: album.map(|a| a.name.clone()),
+        album_ref: album.map(|a| AlbumRef {
+            id: a.id.clone().unwrap_or_default(),
+            name: a.name.clone(),
+            images: images(&a.images),
+        }),
+        disc_number: track.disc_number,
+        track_number: track.track_number,
+        duration: track.duration_ms,
+        cover_url: album.and_then(|a| largest(&a.images)),
+        url: None,
+        quality: None,
+        preview_url: track.preview_url,
+        isrc: track.external_ids.and_then(|e| e.isrc),
+        popularity: track.popularity,
+        availability: Some(Availability {
+            markets: None,
+            blocked_markets: None,
+            requires_login: true,
+            requires_premium: true,
+            can_stream: track.is_playable.unwrap_or(true),
+            can_download: false,
+        }),
+        lyrics: None,
+        metadata,
+    })
+}
+
+pub fn convert_tracks(tracks: impl IntoIterator<Item = SpotifyTrack>) -> Vec<Track> {
+    tracks.into_iter().filter_map(|t| convert_track(t, None)).collect()
+}
+
+pub fn convert_album(album: SpotifyAlbum) -> Album {
+    let album_ref = SpotifyAlbumRef {
+        id: Some(album.id.clone()),
+        name: album.name.clone(),
+        images: album.images.clone(),
+        release_date: album.release_date.clone(),
+    };
+    // Tracks of the album endpoint come without their album
+    let tracks: Vec<Track> = album
+        .tracks
+        .map(|page| page.into_items().filter_map(|t| convert_track(t, Some(&album_ref))).collect())
+        .unwrap_or_default();
+    let mut metadata = HashMap::new();
+    if let Some(label) = album.label {
+        metadata.insert("label".to_string(), label);
+    }
+    Album {
+        id: album.id,
+        title: album.name,
+        artist: artist_names(&album.artists),
+        release_date: album.release_date.as_deref().and_then(parse_release_date),
+        year: album.release_date.as_deref().and_then(|d| d.get(..4)).map(str::to_string),
+        cover_url: largest(&album.images),
+        cover_url_low: smallest(&album.images),
+        track_count: album.total_tracks.map(f64::from).unwrap_or(tracks.len() as f64),
+        tracks,
+        metadata,
+        extra_info: None,
+    }
+}
+
+pub fn convert_artist(artist: SpotifyArtist) -> Artist {
+    let mut metadata = HashMap::new();
+    if !artist.genres.is_empty() {
+        metadata.insert("genres".to_string(), artist.genres.join(", "));
+    }
+    Artist {
+        id: artist.id,
+        sanitized_name: Some(artist.name.to_lowercase()),
+        name: artist.name,
+        mbid: None,
+        description: None,
+        avatar_url: largest(&artist.images),
+        followers: artist.followers.and_then(|f| f.total),
+        track_count: 0.0,
+        metadata,
+        extra_info: None,
+    }
+}
+
+pub fn convert_playlist(playlist: SpotifyPlaylist) -> Playlist {
+    let playlist_images = playlist.images.unwrap_or_default();
+    let (tracks, total) = match playlist.tracks {
+        Some(page) => {
+            let total = page.total;
+            let tracks = page
+                .into_items()
+                .filter_map(|item| item.track)
+                .filter_map(|t| convert_track(t, None))
+                .collect::<Vec<_>>();
+            (tracks, Some(total))
+        }
+        None => (Vec::new(), None),
+    };
+    let owner = playlist.owner;
+    let now = Utc::now();
+    let mut external_urls = HashMap::new();
+    external_urls.insert(
+        PROVIDER.to_string(),
+        format!("https://open.spotify.com/playlist/{}", playlist.id),
+    );
+    Playlist {
+        provider: Some(PROVIDER.to_string()),
+        provider_id: Some(playlist.id.clone()),
+        id: playlist.id,
+        title: playlist.name,
+        description: playlist.description.filter(|d| !d.is_empty()),
+        creator: owner
+            .as_ref()
+            .and_then(|o| o.display_name.clone().or_else(|| o.id.clone()))
+            .unwrap_or_default(),
+        owner: owner.map(|o| PlaylistOwner { id: o.id, name: o.display_name }),
+        cover_url: largest(&playlist_images),
+        images: Some(images(&playlist_images)),
+        track_count: total.map(f64::from).unwrap_or(tracks.len() as f64),
+        total_tracks: total,
+        tracks,
+        created_at: now,
+        updated_at: now,
+        is_public: playlist.public.unwrap_or(false),
+        collaborative: playlist.collaborative,
+        availability: None,
+        external_urls: Some(external_urls),
+        file_path: None,
+        extension: None,
+        icon: None,
+        library_item: None,
+        metadata: HashMap::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn track_ids_are_read_from_uris_links_and_bare_ids() {
+        let id = "4uLU6hMCjMI75M1A2tKUQC";
+        assert_eq!(parse_track_id(&track_uri(id)), Some(id));
+        assert_eq!(parse_track_id(&format!("https://open.spotify.com/track/{}?si=abc", id)), Some(id));
+        assert_eq!(parse_track_id(id), Some(id));
+        assert_eq!(parse_track_id("spotify:album:4uLU6hMCjMI75M1A2tKUQC"), None);
+        assert_eq!(parse_track_id("spotify:track:short"), None);
+    }
+
+    #[test]
+    fn release_dates_of_any_precision_are_parsed() {
+        assert_eq!(parse_release_date("1999").map(|d| d.format("%F").to_string()), Some("1999-01-01".to_string()));
+        assert_eq!(parse_release_date("1999-07").map(|d| d.format("%F").to_string()), Some("1999-07-01".to_string()));
+        assert_eq!(parse_release_date("1999-07-21").map(|d| d.format("%F").to_string()), Some("1999-07-21".to_string()));
+        assert_eq!(parse_release_date("unknown"), None);
+    }
+}
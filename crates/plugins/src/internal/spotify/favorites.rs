@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use serde_json::json;
+
+use music_plugin_sdk::{
+    traits::MediaFavoritesPlugin,
+    types::{base::PluginResult, media::Track},
+};
+use super::api::MAX_PAGE_SIZE;
+use super::plugin::SpotifyPlugin;
+use super::types::{SpotifyPage, SpotifySavedTrack};
+use super::convert;
+
+/// Pages of liked songs read at most, 10 000 tracks
+const MAX_FAVORITE_PAGES: u32 = 200;
+
+/// Spotify IDs of the tracks, leaving out those of other providers
+fn spotify_ids(track_ids: &[String]) -> Vec<&str> {
+    track_ids.iter().filter_map(|id| convert::parse_track_id(id)).collect()
+}
+
+/// Liked songs are the "Your Music" library of the account; changing it
+/// needs the `user-library-modify` scope
+#[async_trait]
+impl MediaFavoritesPlugin for SpotifyPlugin {
+    async fn get_favorite_tracks(&self) -> PluginResult<Vec<Track>> {
+        let mut tracks = Vec::new();
+        for page_index in 0..MAX_FAVORITE_PAGES {
+            let page: SpotifyPage<SpotifySavedTrack> = self
+                .api_get(
+                    "/me/tracks",
+                    &[
+                        ("limit", MAX_PAGE_SIZE.to_string()),
+                        ("offset", (page_index * MAX_PAGE_SIZE).to_string()),
+                    ],
+                )
+                .await?;
+            let last = page.next.is_none();
+            tracks.extend(convert::convert_tracks(page.into_items().map(|saved| saved.track)));
+            if last {
+                break;
+            }
+        }
+        Ok(tracks)
+    }
+
+    async fn add_favorite_tracks(&self, track_ids: &[String]) -> PluginResult<()> {
+        for chunk in spotify_ids(track_ids).chunks(MAX_PAGE_SIZE as usize) {
+            self.api_send(reqwest::Method::PUT, "/me/tracks", &json!({ "ids": chunk })).await?;
+        }
+        Ok(())
+    }
+
+    async fn remove_favorite_tracks(&self, track_ids: &[String]) -> PluginResult<()> {
+        for chunk in spotify_ids(track_ids).chunks(MAX_PAGE_SIZE as usize) {
+            self.api_send(reqwest::Method::DELETE, "/me/tracks", &json!({ "ids": chunk })).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,15 @@
+//! Spotify provider. Metadata comes from the Web API with the account signed
+//! in through the OAuth device-code flow; audio is played by the librespot
+//! player, which receives the `spotify:track:` URI as stream URL.
+
+mod plugin;
+mod api;
+mod auth;
+mod playlists;
+mod favorites;
+mod types;
+mod convert;
+
+pub use plugin::SpotifyPlugin;
+pub use auth::DeviceLogin;
+pub use types::{SpotifyTokens, TokenListener};
@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+use serde_json::json;
+
+use music_plugin_sdk::{
+    traits::MediaPlaylistWritePlugin,
+    types::base::PluginResult,
+};
+use super::plugin::SpotifyPlugin;
+use super::types::SpotifyPlaylistAccess;
+use super::convert;
+
+/// Most tracks one request adds or removes
+const MAX_TRACKS_PER_REQUEST: usize = 100;
+
+/// URIs of the tracks given as Spotify IDs, and the other IDs
+fn split_uris(track_ids: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut uris = Vec::with_capacity(track_ids.len());
+    let mut unknown = Vec::new();
+    for track_id in track_ids {
+        match convert::parse_track_id(track_id) {
+            Some(id) => uris.push(convert::track_uri(id)),
+            None => unknown.push(track_id.clone()),
+        }
+    }
+    (uris, unknown)
+}
+
+/// Changing playlists needs the `playlist-modify-*` scopes; accounts signed in
+/// before they were requested must sign in again
+#[async_trait]
+impl MediaPlaylistWritePlugin for SpotifyPlugin {
+    /// Playlists of the account and collaborative ones can be changed
+    async fn can_edit_playlist(&self, playlist_id: &str) -> PluginResult<bool> {
+        let access: SpotifyPlaylistAccess = self
+            .api_get(&format!("/playlists/{}", playlist_id), &[("fields", "owner.id,collaborative".to_string())])
+            .await?;
+        if access.collaborative.unwrap_or(false) {
+            return Ok(true);
+        }
+        let user = self.current_user().await?;
+        Ok(access.owner.and_then(|o| o.id).is_some_and(|id| id == user.user_id))
+    }
+
+    async fn add_playlist_tracks(&self, playlist_id: &str, track_ids: &[String]) -> PluginResult<Vec<String>> {
+        let (uris, unknown) = split_uris(track_ids);
+        let path = format!("/playlists/{}/tracks", playlist_id);
+        for chunk in uris.chunks(MAX_TRACKS_PER_REQUEST) {
+            self.api_send(reqwest::Method::POST, &path, &json!({ "uris": chunk })).await?;
+        }
+        Ok(unknown)
+    }
+
+    async fn remove_playlist_tracks(&self, playlist_id: &str, track_ids: &[String]) -> PluginResult<()> {
+        // Tracks of other providers can't be in the playlist
+        let (uris, _) = split_uris(track_ids);
+        let path = format!("/playlists/{}/tracks", playlist_id);
+        for chunk in uris.chunks(MAX_TRACKS_PER_REQUEST) {
+            let tracks: Vec<_> = chunk.iter().map(|uri| json!({ "uri": uri })).collect();
+            self.api_send(reqwest::Method::DELETE, &path, &json!({ "tracks": tracks })).await?;
+        }
+        Ok(())
+    }
+}
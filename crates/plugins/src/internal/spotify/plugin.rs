@@ -0,0 +1,231 @@
+use async_trait::async_trait;
+use semver::Version;
+use uuid::Uuid;
+use reqwest::Client;
+use std::sync::{Arc, RwLock as StdRwLock};
+use std::time::Duration;
+
+use crate::system::core::*;
+use crate::system::types::*;
+use crate::PluginResult;
+use music_plugin_sdk::traits::BasePlugin;
+use music_plugin_sdk::types::media::AuthUserInfo;
+use music_plugin_sdk::utils::{ConfigField, ConfigSchemaBuilder};
+
+use super::types::{SpotifyTokens, TokenListener};
+
+/// Default Web API address
+pub const DEFAULT_API_BASE: &str = "https://api.spotify.com/v1";
+/// Public client ID of librespot, used unless the user configures their own
+pub const DEFAULT_CLIENT_ID: &str = "65b708073fc0480ea92a077233ca87bd";
+/// Configuration key of the OAuth client ID
+pub const CLIENT_ID_CONFIG_KEY: &str = "client_id";
+
+#[derive(Clone)]
+pub struct SpotifyPlugin {
+    metadata: PluginMetadata,
+    status: PluginStatus,
+    context: Option<PluginContext>,
+    pub http: Client,
+    /// OAuth client ID of the device-code flow
+    pub client_id: Arc<StdRwLock<String>>,
+    /// Tokens of the signed-in account, shared by all clones of the plugin
+    pub tokens: Arc<StdRwLock<Option<SpotifyTokens>>>,
+    /// Profile of the signed-in account, fetched on sign-in
+    pub user: Arc<StdRwLock<Option<AuthUserInfo>>>,
+    /// Only one token refresh runs at a time
+    pub refresh_lock: Arc<tokio::sync::Mutex<()>>,
+    token_listener: Arc<StdRwLock<Option<TokenListener>>>,
+    /// Self-hosted Web API mirror, the default address when unset
+    pub api_endpoint: Arc<StdRwLock<Option<String>>>,
+}
+
+impl std::fmt::Debug for SpotifyPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpotifyPlugin")
+            .field("metadata", &self.metadata)
+            .field("status", &self.status)
+            .finish()
+    }
+}
+
+impl SpotifyPlugin {
+    /// Stable deterministic UUID of the builtin plugin
+    pub fn plugin_id() -> Uuid {
+        Uuid::new_v5(&Uuid::NAMESPACE_OID, b"builtin:spotify")
+    }
+
+    pub fn new() -> Self {
+        let metadata = PluginMetadata {
+            id: Self::plugin_id(),
+            name: "spotify".to_string(),
+            display_name: "Spotify Music".to_string(),
+            description: "Spotify music provider plugin".to_string(),
+            version: Version::new(1, 0, 0),
+            author: "Music Player Team".to_string(),
+            homepage: Some("https://open.spotify.com".to_string()),
+            repository: None,
+            license: Some("MIT".to_string()),
+            icon: None,
+            keywords: vec!["spotify".into(), "music".into(), "audio".into()],
+            plugin_type: PluginType::AudioProvider,
+            capabilities: vec![
+                PluginCapability::Search,
+                PluginCapability::Playlists,
+                PluginCapability::Streaming,
+                PluginCapability::Authentication,
+            ],
+            dependencies: vec![],
+            min_system_version: None,
+            max_system_version: None,
+        };
+        let http = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(5))
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_else(|_| Client::new());
+
+        Self {
+            metadata,
+            status: PluginStatus::Unloaded,
+            context: None,
+            http,
+            client_id: Arc::new(StdRwLock::new(DEFAULT_CLIENT_ID.to_string())),
+            tokens: Arc::new(StdRwLock::new(None)),
+            user: Arc::new(StdRwLock::new(None)),
+            refresh_lock: Arc::new(tokio::sync::Mutex::new(())),
+            token_listener: Arc::new(StdRwLock::new(None)),
+            api_endpoint: Arc::new(StdRwLock::new(None)),
+        }
+    }
+
+    /// Web API address in use: the configured mirror, else the default one
+    pub fn api_base(&self) -> String {
+        self.api_endpoint
+            .read()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| DEFAULT_API_BASE.to_string())
+    }
+
+    /// Tokens of the signed-in account, if any
+    pub fn tokens(&self) -> Option<SpotifyTokens> {
+        self.tokens.read().unwrap().clone()
+    }
+
+    /// Restore tokens stored by the host. The listener is not notified.
+    pub fn set_tokens(&self, tokens: Option<SpotifyTokens>) {
+        *self.tokens.write().unwrap() = tokens;
+    }
+
+    /// Be told of token changes, to persist them
+    pub fn set_token_listener(&self, listener: Option<TokenListener>) {
+        *self.token_listener.write().unwrap() = listener;
+    }
+
+    /// Replace the tokens and notify the listener
+    pub(super) fn store_tokens(&self, tokens: Option<SpotifyTokens>) {
+        *self.tokens.write().unwrap() = tokens.clone();
+        if tokens.is_none() {
+            *self.user.write().unwrap() = None;
+        }
+        let listener = self.token_listener.read().unwrap().clone();
+        if let Some(listener) = listener {
+            listener(tokens.as_ref());
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for SpotifyPlugin {
+    fn metadata(&self) -> PluginMetadata { self.metadata.clone() }
+    fn id(&self) -> Uuid { self.metadata.id }
+    fn plugin_type(&self) -> PluginType { self.metadata.plugin_type.clone() }
+    fn capabilities(&self) -> Vec<PluginCapability> { self.metadata.capabilities.clone() }
+    fn initialize(&mut self, context: &PluginContext) -> PluginResult<()> { self.context = Some(context.clone()); self.status = PluginStatus::Ready; Ok(()) }
+    fn start(&mut self) -> PluginResult<()> { self.status = PluginStatus::Running; Ok(()) }
+    fn stop(&mut self) -> PluginResult<()> { self.status = PluginStatus::Stopped; Ok(()) }
+    fn destroy(&mut self) -> PluginResult<()> { self.status = PluginStatus::Unloaded; self.context = None; Ok(()) }
+    fn status(&self) -> PluginResult<PluginStatus> { Ok(self.status.clone()) }
+    async fn handle_event(&mut self, _event: PluginEvent) -> PluginResult<Option<PluginResponse>> { Ok(None) }
+    fn health_check(&self) -> PluginResult<HealthStatus> { Ok(HealthStatus::Healthy) }
+    
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+    
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+impl Default for SpotifyPlugin { fn default() -> Self { Self::new() } }
+
+// MediaPlugin is implemented in api.rs, MediaAuthPlugin in auth.rs
+
+#[async_trait]
+impl BasePlugin for SpotifyPlugin {
+    fn metadata(&self) -> music_plugin_sdk::types::base::PluginMetadata {
+        music_plugin_sdk::types::base::PluginMetadata {
+            id: self.metadata.id,
+            name: self.metadata.name.clone(),
+            version: self.metadata.version.to_string(),
+            description: self.metadata.description.clone(),
+            author: self.metadata.author.clone(),
+            website: self.metadata.homepage.clone(),
+            icon: self.metadata.icon.clone(),
+            capabilities: vec![
+                music_plugin_sdk::types::base::PluginCapability::Search,
+                music_plugin_sdk::types::base::PluginCapability::Playback,
+                music_plugin_sdk::types::base::PluginCapability::Network,
+            ],
+            min_sdk_version: "1.0.0".to_string(),
+            config_schema: Some(
+                ConfigSchemaBuilder::new()
+                    .field(
+                        CLIENT_ID_CONFIG_KEY,
+                        ConfigField::string("Client ID")
+                            .description("OAuth client ID of your Spotify app; leave empty to use the built-in one")
+                            .default(""),
+                    )
+                    .build(),
+            ),
+        }
+    }
+
+    async fn initialize(&mut self, _context: &music_plugin_sdk::types::base::PluginContext) -> music_plugin_sdk::types::base::PluginResult<()> {
+        self.status = PluginStatus::Ready;
+        Ok(())
+    }
+
+    async fn start(&mut self) -> music_plugin_sdk::types::base::PluginResult<()> {
+        self.status = PluginStatus::Running;
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> music_plugin_sdk::types::base::PluginResult<()> {
+        self.status = PluginStatus::Stopped;
+        Ok(())
+    }
+
+    fn status(&self) -> music_plugin_sdk::types::base::PluginStatus {
+        match self.status {
+            PluginStatus::Unloaded => music_plugin_sdk::types::base::PluginStatus::Loaded,
+            PluginStatus::Ready => music_plugin_sdk::types::base::PluginStatus::Loaded,
+            PluginStatus::Running => music_plugin_sdk::types::base::PluginStatus::Running,
+            PluginStatus::Stopped => music_plugin_sdk::types::base::PluginStatus::Stopped,
+            _ => music_plugin_sdk::types::base::PluginStatus::Error("Plugin error".to_string()),
+        }
+    }
+
+    async fn configure(&mut self, config: music_plugin_sdk::types::base::PluginConfig) -> music_plugin_sdk::types::base::PluginResult<()> {
+        if let Some(endpoint) = config.endpoint() {
+            *self.api_endpoint.write().unwrap() = endpoint.map(|url| url.trim_end_matches('/').to_string());
+        }
+        if let Some(client_id) = config.values.get(CLIENT_ID_CONFIG_KEY) {
+            let client_id = client_id.as_str().map(str::trim).filter(|id| !id.is_empty());
+            *self.client_id.write().unwrap() = client_id.unwrap_or(DEFAULT_CLIENT_ID).to_string();
+        }
+        Ok(())
+    }
+}
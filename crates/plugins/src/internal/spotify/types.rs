@@ -0,0 +1,225 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// OAuth tokens of the signed-in account. The host stores them encrypted and
+/// passes them back on the next launch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpotifyTokens {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub scope: Option<String>,
+}
+
+impl SpotifyTokens {
+    /// Whether the access token expires within a minute
+    pub fn is_expired(&self) -> bool {
+        Utc::now() + Duration::seconds(60) >= self.expires_at
+    }
+}
+
+/// Called whenever the tokens change: sign-in, refresh or sign-out (`None`)
+pub type TokenListener = Arc<dyn Fn(Option<&SpotifyTokens>) + Send + Sync>;
+
+/// Response of the device authorization endpoint (RFC 8628)
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_in: i64,
+    pub scope: Option<String>,
+}
+
+impl TokenResponse {
+    /// Tokens to store; a refresh response without a new refresh token keeps
+    /// `previous_refresh`
+    pub fn into_tokens(self, previous_refresh: Option<String>) -> SpotifyTokens {
+        SpotifyTokens {
+            access_token: self.access_token,
+            refresh_token: self.refresh_token.or(previous_refresh),
+            expires_at: Utc::now() + Duration::seconds(self.expires_in),
+            scope: self.scope,
+        }
+    }
+}
+
+/// OAuth error body, e.g. `authorization_pending` while the user has not
+/// approved the device yet
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenError {
+    pub error: String,
+    pub error_description: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifyImage {
+    pub url: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifyArtistRef {
+    pub id: Option<String>,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifyAlbumRef {
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub images: Vec<SpotifyImage>,
+    pub release_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifyTrack {
+    /// `None` for local files of the user
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub artists: Vec<SpotifyArtistRef>,
+    pub album: Option<SpotifyAlbumRef>,
+    pub disc_number: Option<u32>,
+    pub track_number: Option<u32>,
+    pub duration_ms: Option<u32>,
+    pub popularity: Option<u32>,
+    pub preview_url: Option<String>,
+    pub is_playable: Option<bool>,
+    #[serde(default)]
+    pub explicit: bool,
+    pub external_ids: Option<SpotifyExternalIds>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifyExternalIds {
+    pub isrc: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifyAlbum {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub artists: Vec<SpotifyArtistRef>,
+    #[serde(default)]
+    pub images: Vec<SpotifyImage>,
+    pub release_date: Option<String>,
+    pub total_tracks: Option<u32>,
+    pub label: Option<String>,
+    /// Present on the album endpoint, not in search results
+    pub tracks: Option<SpotifyPage<SpotifyTrack>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifyArtist {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub images: Vec<SpotifyImage>,
+    pub followers: Option<SpotifyFollowers>,
+    #[serde(default)]
+    pub genres: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifyFollowers {
+    pub total: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifyPlaylistOwner {
+    pub id: Option<String>,
+    pub display_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifyPlaylist {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub owner: Option<SpotifyPlaylistOwner>,
+    /// `null` for playlists without a cover
+    pub images: Option<Vec<SpotifyImage>>,
+    pub public: Option<bool>,
+    pub collaborative: Option<bool>,
+    pub tracks: Option<SpotifyPage<SpotifyPlaylistItem>>,
+}
+
+/// Fields of a playlist telling who may change it
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifyPlaylistAccess {
+    pub owner: Option<SpotifyPlaylistOwner>,
+    pub collaborative: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifyPlaylistItem {
+    /// `None` for removed tracks
+    pub track: Option<SpotifyTrack>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifySavedTrack {
+    pub track: SpotifyTrack,
+}
+
+/// Paged collection; search results page items that may be `null`
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifyPage<T> {
+    #[serde(default = "Vec::new")]
+    pub items: Vec<Option<T>>,
+    #[serde(default)]
+    pub total: u32,
+    #[serde(default)]
+    pub limit: u32,
+    #[serde(default)]
+    pub offset: u32,
+    pub next: Option<String>,
+}
+
+impl<T> SpotifyPage<T> {
+    pub fn into_items(self) -> impl Iterator<Item = T> {
+        self.items.into_iter().flatten()
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SpotifySearchResponse {
+    pub tracks: Option<SpotifyPage<SpotifyTrack>>,
+    pub albums: Option<SpotifyPage<SpotifyAlbum>>,
+    pub artists: Option<SpotifyPage<SpotifyArtist>>,
+    pub playlists: Option<SpotifyPage<SpotifyPlaylist>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifyArtistTopTracks {
+    pub tracks: Vec<SpotifyTrack>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifyRecommendations {
+    pub tracks: Vec<SpotifyTrack>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpotifyUser {
+    pub id: String,
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub images: Vec<SpotifyImage>,
+    pub product: Option<String>,
+    pub country: Option<String>,
+}
@@ -1,299 +0,0 @@
-//! YouTube Music Plugin Implementation
-
-use async_trait::async_trait;
-use uuid::Uuid;
-use semver::Version;
-
-use crate::system::core::*;
-use crate::system::types::*;
-use crate::PluginResult;
-
-/// YouTube Music Plugin
-#[derive(Debug)]
-pub struct YoutubePlugin {
-    /// Plugin metadata
-    metadata: PluginMetadata,
-    
-    /// Plugin status
-    status: PluginStatus,
-    
-    /// Plugin context
-    context: Option<PluginContext>,
-    
-    /// YouTube API client
-    client: YoutubeClient,
-}
-
-/// YouTube API Client
-#[derive(Debug)]
-struct YoutubeClient {
-    /// Base URL for API requests
-    base_url: String,
-    
-    /// API key
-    api_key: Option<String>,
-    
-    /// OAuth2 token
-    access_token: Option<String>,
-}
-
-impl YoutubeClient {
-    /// Create a new YouTube client
-    fn new() -> Self {
-        Self {
-            base_url: "https://www.googleapis.com/youtube/v3".to_string(),
-            api_key: None,
-            access_token: None,
-        }
-    }
-    
-    /// Set API key for authentication
-    fn set_api_key(&mut self, api_key: String) {
-        self.api_key = Some(api_key);
-    }
-    
-    /// Set OAuth2 access token
-    fn set_access_token(&mut self, token: String) {
-        self.access_token = Some(token);
-    }
-    
-    /// Search for videos/music
-    async fn search_videos(&self, _query: &str) -> PluginResult<Vec<Video>> {
-        // Implementation would go here
-        // This is a placeholder for actual API calls
-        Ok(vec![])
-    }
-    
-    /// Get user playlists
-    async fn get_user_playlists(&self) -> PluginResult<Vec<Playlist>> {
-        // Implementation would go here
-        Ok(vec![])
-    }
-    
-    /// Get stream URL for a video
-    async fn get_stream_url(&self, video_id: &str) -> PluginResult<String> {
-        // Implementation would go here
-        Ok(format!("https://www.youtube.com/watch?v={}", video_id))
-    }
-}
-
-/// Video structure for YouTube
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-struct Video {
-    id: String,
-    title: String,
-    channel: String,
-    duration: u64,
-    thumbnail_url: Option<String>,
-    view_count: u64,
-}
-
-/// Playlist structure for YouTube
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-struct Playlist {
-    id: String,
-    title: String,
-    description: Option<String>,
-    thumbnail_url: Option<String>,
-    video_count: u32,
-}
-
-impl YoutubePlugin {
-    /// Create a new YouTube plugin instance
-    pub fn new() -> Self {
-        let metadata = PluginMetadata {
-            // Stable deterministic ID to avoid duplicate DB rows across runs
-            id: Uuid::new_v5(&Uuid::NAMESPACE_OID, b"builtin:youtube"),
-            name: "youtube".to_string(),
-            display_name: "YouTube Music".to_string(),
-            description: "YouTube Music provider plugin".to_string(),
-            version: Version::new(1, 0, 0),
-            author: "Music Player Team".to_string(),
-            homepage: Some("https://music.youtube.com".to_string()),
-            repository: None,
-            license: Some("MIT".to_string()),
-            icon: None,
-            keywords: vec![
-                "youtube".to_string(),
-                "music".to_string(),
-                "video".to_string(),
-                "audio".to_string(),
-            ],
-            plugin_type: PluginType::AudioProvider,
-            capabilities: vec![
-                PluginCapability::Search,
-                PluginCapability::Playlists,
-                PluginCapability::Streaming,
-                PluginCapability::Authentication,
-            ],
-            dependencies: vec![],
-            min_system_version: None,
-            max_system_version: None,
-        };
-        
-        Self {
-            metadata,
-            status: PluginStatus::Unloaded,
-            context: None,
-            client: YoutubeClient::new(),
-        }
-    }
-}
-
-#[async_trait]
-impl Plugin for YoutubePlugin {
-    fn metadata(&self) -> PluginMetadata {
-        self.metadata.clone()
-    }
-    
-    fn id(&self) -> Uuid {
-        self.metadata.id
-    }
-    
-    fn plugin_type(&self) -> PluginType {
-        PluginType::AudioProvider
-    }
-    
-    fn capabilities(&self) -> Vec<PluginCapability> {
-        self.metadata.capabilities.clone()
-    }
-    
-    fn initialize(&mut self, context: &PluginContext) -> PluginResult<()> {
-        self.context = Some(context.clone());
-        self.status = PluginStatus::Ready;
-        Ok(())
-    }
-    
-    fn start(&mut self) -> PluginResult<()> {
-        self.status = PluginStatus::Running;
-        Ok(())
-    }
-    
-    fn stop(&mut self) -> PluginResult<()> {
-        self.status = PluginStatus::Stopped;
-        Ok(())
-    }
-    
-    fn destroy(&mut self) -> PluginResult<()> {
-        self.status = PluginStatus::Unloaded;
-        self.context = None;
-        Ok(())
-    }
-    
-    fn status(&self) -> PluginResult<PluginStatus> {
-        Ok(self.status.clone())
-    }
-    
-    async fn handle_event(&mut self, event: PluginEvent) -> PluginResult<Option<PluginResponse>> {
-        match event {
-            PluginEvent::UserAction { action, parameters } => {
-                match action.as_str() {
-                    "search" => {
-                        if let Some(term) = parameters.get("term") {
-                            if let Some(search_term) = term.as_str() {
-                                match self.client.search_videos(search_term).await {
-                                    Ok(videos) => {
-                                        let response_data = serde_json::to_value(videos)
-                                            .map_err(PluginError::Serialization)?;
-                                        
-                                        Ok(Some(PluginResponse::Success {
-                                            data: Some(response_data),
-                                        }))
-                                    }
-                                    Err(e) => Ok(Some(PluginResponse::Error {
-                                        message: e.to_string(),
-                                        details: None,
-                                    }))
-                                }
-                            } else {
-                                Ok(Some(PluginResponse::Error {
-                                    message: "Invalid search term".to_string(),
-                                    details: None,
-                                }))
-                            }
-                        } else {
-                            Ok(Some(PluginResponse::Error {
-                                message: "Missing search term".to_string(),
-                                details: None,
-                            }))
-                        }
-                    }
-                    "get_playlists" => {
-                        match self.client.get_user_playlists().await {
-                            Ok(playlists) => {
-                                let response_data = serde_json::to_value(playlists)
-                                    .map_err(PluginError::Serialization)?;
-                                
-                                Ok(Some(PluginResponse::Success {
-                                    data: Some(response_data),
-                                }))
-                            }
-                            Err(e) => Ok(Some(PluginResponse::Error {
-                                message: e.to_string(),
-                                details: None,
-                            }))
-                        }
-                    }
-                    "authenticate" => {
-                        if let Some(token) = parameters.get("access_token") {
-                            if let Some(token_str) = token.as_str() {
-                                self.client.set_access_token(token_str.to_string());
-                                
-                                Ok(Some(PluginResponse::Success {
-                                    data: None,
-                                }))
-                            } else {
-                                Ok(Some(PluginResponse::Error {
-                                    message: "Invalid access token".to_string(),
-                                    details: None,
-                                }))
-                            }
-                        } else if let Some(api_key) = parameters.get("api_key") {
-                            if let Some(key_str) = api_key.as_str() {
-                                self.client.set_api_key(key_str.to_string());
-                                
-                                Ok(Some(PluginResponse::Success {
-                                    data: None,
-                                }))
-                            } else {
-                                Ok(Some(PluginResponse::Error {
-                                    message: "Invalid API key".to_string(),
-                                    details: None,
-                                }))
-                            }
-                        } else {
-                            Ok(Some(PluginResponse::Error {
-                                message: "Missing authentication credentials".to_string(),
-                                details: None,
-                            }))
-                        }
-                    }
-                    _ => Ok(Some(PluginResponse::Error {
-                        message: format!("Unknown action: {}", action),
-                        details: None,
-                    }))
-                }
-            }
-            _ => Ok(None)
-        }
-    }
-    
-    fn health_check(&self) -> PluginResult<HealthStatus> {
-        // Simple health check - in a real implementation, this would check API connectivity
-        Ok(HealthStatus::Healthy)
-    }
-    
-    fn as_any(&self) -> &dyn std::any::Any {
-        self
-    }
-    
-    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
-        self
-    }
-}
-
-impl Default for YoutubePlugin {
-    fn default() -> Self {
-        Self::new()
-    }
-}
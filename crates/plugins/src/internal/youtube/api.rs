@@ -0,0 +1,310 @@
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+
+use music_plugin_sdk::{
+    traits::MediaPlugin,
+    types::{*, media::{StreamRequest, StreamSource}},
+    errors::PluginError
+};
+use super::plugin::YoutubePlugin;
+use super::types::*;
+use super::convert::{self, PROVIDER};
+use super::link::{parse_link, ChannelRef, YoutubeLink};
+
+/// Largest page the Data API returns
+const MAX_PAGE_SIZE: u32 = 50;
+/// Pages followed when a whole playlist is requested; longer playlists are
+/// returned partially, with their full length in `total_tracks`
+const MAX_PLAYLIST_PAGES: usize = 10;
+
+fn page_size(page: &PageInput) -> u32 {
+    page.limit.unwrap_or(MAX_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+}
+
+/// The Data API pages by token only: `cursor` carries the token of the
+/// requested page and `offset` is ignored
+fn page_info<T>(page: &YoutubePage<T>, input: &PageInput) -> PageInfo {
+    PageInfo {
+        limit: page_size(input),
+        offset: input.offset.unwrap_or(0),
+        next_cursor: page.next_page_token.clone(),
+        total: page.page_info.as_ref().and_then(|p| p.total_results),
+        has_more: page.next_page_token.is_some(),
+    }
+}
+
+/// `type` parameter of the search endpoint; albums and genres have no
+/// YouTube counterpart
+fn search_types(types: &[SearchType]) -> String {
+    if types.is_empty() || types.contains(&SearchType::All) {
+        return "video,playlist,channel".to_string();
+    }
+    let types: Vec<&str> = types
+        .iter()
+        .filter_map(|t| match t {
+            SearchType::Track => Some("video"),
+            SearchType::Playlist => Some("playlist"),
+            SearchType::Artist => Some("channel"),
+            _ => None,
+        })
+        .collect();
+    types.join(",")
+}
+
+impl YoutubePlugin {
+    /// GET a Data API resource with the configured key
+    pub(super) async fn api_get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, String)]) -> PluginResult<T> {
+        let key = self
+            .api_key
+            .read()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| PluginError::AuthenticationError("Set a YouTube Data API key in the plugin settings".to_string()))?;
+        let url = format!("{}{}", self.api_base(), path);
+        let resp = self.http.get(&url)
+            .query(query)
+            .query(&[("key", key)])
+            .send().await
+            .map_err(|e| PluginError::NetworkError(format!("YouTube request failed: {}", e)))?;
+
+        let status = resp.status();
+        let text = resp.text().await
+            .map_err(|e| PluginError::NetworkError(format!("Failed to read response: {}", e)))?;
+        if !status.is_success() {
+            let error = serde_json::from_str::<YoutubeErrorResponse>(&text).ok().map(|r| r.error);
+            let reason = error.as_ref().and_then(|e| e.errors.first()).and_then(|e| e.reason.clone()).unwrap_or_default();
+            let message = error.and_then(|e| e.message).unwrap_or(text);
+            return Err(match (status.as_u16(), reason.as_str()) {
+                (403, "quotaExceeded" | "rateLimitExceeded") => PluginError::RateLimitExceeded(message),
+                (400, "keyInvalid") | (403, "forbidden" | "accessNotConfigured") => PluginError::AuthenticationError(message),
+                (404, _) => PluginError::NotFound(message),
+                (400, _) => PluginError::InvalidInput(message),
+                _ => PluginError::NetworkError(format!("YouTube API returned {}: {}", status, message)),
+            });
+        }
+        serde_json::from_str(&text)
+            .map_err(|e| PluginError::SerializationError(format!("Failed to parse response of {}: {}", path, e)))
+    }
+
+    /// Tracks of the given videos in the same order. Videos that are private,
+    /// deleted or unknown are left out.
+    async fn videos(&self, ids: &[String]) -> PluginResult<Vec<Track>> {
+        let mut tracks = Vec::with_capacity(ids.len());
+        for chunk in ids.chunks(MAX_PAGE_SIZE as usize) {
+            let page: YoutubePage<YoutubeVideo> = self.api_get("/videos", &[
+                ("part", "snippet,contentDetails".to_string()),
+                ("id", chunk.join(",")),
+                ("maxResults", MAX_PAGE_SIZE.to_string()),
+            ]).await?;
+            let mut found: std::collections::HashMap<String, YoutubeVideo> =
+                page.items.into_iter().map(|v| (v.id.clone(), v)).collect();
+            tracks.extend(chunk.iter().filter_map(|id| found.remove(id)).filter_map(convert::convert_video));
+        }
+        Ok(tracks)
+    }
+
+    async fn playlist(&self, playlist_id: &str) -> PluginResult<YoutubePlaylist> {
+        let page: YoutubePage<YoutubePlaylist> = self.api_get("/playlists", &[
+            ("part", "snippet,contentDetails".to_string()),
+            ("id", playlist_id.to_string()),
+        ]).await?;
+        page.items.into_iter().next()
+            .ok_or_else(|| PluginError::NotFound(format!("YouTube playlist {}", playlist_id)))
+    }
+
+    async fn channel(&self, channel: &ChannelRef) -> PluginResult<YoutubeChannel> {
+        let (filter, value) = match channel {
+            ChannelRef::Id(id) => ("id", id.clone()),
+            ChannelRef::Handle(handle) => ("forHandle", format!("@{}", handle)),
+            ChannelRef::Username(name) => ("forUsername", name.clone()),
+        };
+        let page: YoutubePage<YoutubeChannel> = self.api_get("/channels", &[
+            ("part", "snippet,statistics,contentDetails".to_string()),
+            (filter, value.clone()),
+        ]).await?;
+        page.items.into_iter().next()
+            .ok_or_else(|| PluginError::NotFound(format!("YouTube channel {}", value)))
+    }
+
+    /// One page of the videos of a playlist, in playlist order. Pass the
+    /// returned `next_cursor` as `cursor` for the following page.
+    pub async fn playlist_tracks(&self, playlist_id: &str, page: &PageInput) -> PluginResult<SearchSlice<Track>> {
+        let mut query = vec![
+            ("part", "contentDetails".to_string()),
+            ("playlistId", playlist_id.to_string()),
+            ("maxResults", page_size(page).to_string()),
+        ];
+        if let Some(cursor) = &page.cursor {
+            query.push(("pageToken", cursor.clone()));
+        }
+        let items: YoutubePage<YoutubePlaylistItem> = self.api_get("/playlistItems", &query).await?;
+        let ids: Vec<String> = items
+            .items
+            .iter()
+            .filter_map(|item| item.content_details.as_ref()?.video_id.clone())
+            .collect();
+        Ok(SearchSlice { items: self.videos(&ids).await?, page: page_info(&items, page) })
+    }
+
+    /// One page of the uploads of a channel, newest first. `channel_id` is a
+    /// "UC…" ID or an "@handle".
+    pub async fn channel_tracks(&self, channel_id: &str, page: &PageInput) -> PluginResult<SearchSlice<Track>> {
+        let channel = self.channel(&channel_ref(channel_id)).await?;
+        let uploads = channel
+            .content_details
+            .and_then(|d| d.related_playlists)
+            .and_then(|p| p.uploads)
+            .ok_or_else(|| PluginError::NotFound(format!("Uploads of YouTube channel {}", channel_id)))?;
+        self.playlist_tracks(&uploads, page).await
+    }
+
+    /// Tracks of a playlist, following pages up to `MAX_PLAYLIST_PAGES`
+    async fn all_playlist_tracks(&self, playlist_id: &str) -> PluginResult<Vec<Track>> {
+        let mut tracks = Vec::new();
+        let mut page = PageInput { limit: Some(MAX_PAGE_SIZE), offset: None, cursor: None };
+        for _ in 0..MAX_PLAYLIST_PAGES {
+            let slice = self.playlist_tracks(playlist_id, &page).await?;
+            tracks.extend(slice.items);
+            match slice.page.next_cursor {
+                Some(cursor) => page.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        Ok(tracks)
+    }
+
+    /// Importable content of a pasted video, playlist or channel link: the
+    /// video itself, the playlist with its tracks, or the channel with its
+    /// latest uploads. `None` when `url` is not a YouTube link.
+    pub async fn resolve_url(&self, url: &str) -> PluginResult<Option<SearchResult>> {
+        let Some(link) = parse_link(url) else {
+            return Ok(None);
+        };
+        let mut result = SearchResult { provider: PROVIDER.to_string(), ..Default::default() };
+        match link {
+            YoutubeLink::Video(id) => {
+                result.tracks.items = self.videos(&[id]).await?;
+            }
+            YoutubeLink::Playlist(id) => {
+                let playlist = self.playlist(&id).await?;
+                let tracks = self.all_playlist_tracks(&id).await?;
+                result.tracks.items = tracks.clone();
+                result.playlists.items = vec![convert::convert_playlist(playlist, tracks)];
+            }
+            YoutubeLink::Channel(channel) => {
+                let channel = self.channel(&channel).await?;
+                let page = PageInput { limit: Some(MAX_PAGE_SIZE), offset: None, cursor: None };
+                let uploads = self.channel_tracks(&channel.id, &page).await?;
+                result.tracks = uploads;
+                result.artists.items = vec![convert::convert_channel(channel)];
+            }
+        }
+        result.tracks.page.total.get_or_insert(result.tracks.items.len() as u32);
+        Ok(Some(result))
+    }
+}
+
+/// Channel IDs start with "UC"; anything else is taken as a handle
+fn channel_ref(channel_id: &str) -> ChannelRef {
+    match channel_id.strip_prefix('@') {
+        Some(handle) => ChannelRef::Handle(handle.to_string()),
+        None if channel_id.starts_with("UC") => ChannelRef::Id(channel_id.to_string()),
+        None => ChannelRef::Handle(channel_id.to_string()),
+    }
+}
+
+#[async_trait]
+impl MediaPlugin for YoutubePlugin {
+    /// A pasted link resolves to its content; anything else is searched
+    async fn search(&self, query: &SearchQuery) -> PluginResult<SearchResult> {
+        if let Some(result) = self.resolve_url(&query.query).await? {
+            return Ok(result);
+        }
+        let types = search_types(&query.types);
+        if types.is_empty() {
+            return Ok(SearchResult { provider: PROVIDER.to_string(), ..Default::default() });
+        }
+        let input = query.page.clone().unwrap_or(PageInput { limit: None, offset: None, cursor: None });
+        let mut params = vec![
+            ("part", "snippet".to_string()),
+            ("q", query.query.clone()),
+            ("type", types),
+            ("maxResults", page_size(&input).min(25).to_string()),
+        ];
+        if let Some(cursor) = &input.cursor {
+            params.push(("pageToken", cursor.clone()));
+        }
+        let response: YoutubePage<YoutubeSearchItem> = self.api_get("/search", &params).await?;
+        let info = page_info(&response, &input);
+
+        let mut video_ids = Vec::new();
+        let mut playlists = Vec::new();
+        let mut artists = Vec::new();
+        for item in response.items {
+            let YoutubeSearchItem { id, snippet } = item;
+            match id.kind.as_deref() {
+                Some("youtube#video") => video_ids.extend(id.video_id),
+                Some("youtube#playlist") => {
+                    if let Some(playlist_id) = id.playlist_id {
+                        let playlist = YoutubePlaylist { id: playlist_id, snippet, content_details: None };
+                        playlists.push(convert::convert_playlist(playlist, Vec::new()));
+                    }
+                }
+                Some("youtube#channel") => {
+                    if let Some(channel_id) = id.channel_id {
+                        let channel = YoutubeChannel { id: channel_id, snippet, statistics: None, content_details: None };
+                        artists.push(convert::convert_channel(channel));
+                    }
+                }
+                _ => {}
+            }
+        }
+        // Search results lack durations; the videos endpoint has them
+        let tracks = self.videos(&video_ids).await?;
+
+        Ok(SearchResult {
+            provider: PROVIDER.to_string(),
+            tracks: SearchSlice { items: tracks, page: info.clone() },
+            albums: SearchSlice::default(),
+            artists: SearchSlice { items: artists, page: info.clone() },
+            playlists: SearchSlice { items: playlists, page: info },
+            genres: SearchSlice::default(),
+            suggestions: None,
+            provider_context: None,
+        })
+    }
+
+    async fn get_track(&self, track_id: &str) -> PluginResult<Track> {
+        self.videos(&[track_id.to_string()]).await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| PluginError::NotFound(format!("YouTube video {}", track_id)))
+    }
+
+    async fn get_media_stream(&self, _track_id: &str, _req: &StreamRequest) -> PluginResult<StreamSource> {
+        Err(PluginError::NotSupported("Playing YouTube videos is not supported yet".to_string()))
+    }
+
+    async fn get_album(&self, _album_id: &str) -> PluginResult<Album> {
+        Err(PluginError::NotSupported("YouTube has no albums".to_string()))
+    }
+
+    /// The channel with the given "UC…" ID or "@handle". Its uploads are
+    /// listed page by page with `channel_tracks`.
+    async fn get_artist(&self, artist_id: &str) -> PluginResult<Artist> {
+        let channel = self.channel(&channel_ref(artist_id)).await?;
+        Ok(convert::convert_channel(channel))
+    }
+
+    /// The playlist with its first `MAX_PLAYLIST_PAGES` pages of videos; use
+    /// `playlist_tracks` to page through longer ones
+    async fn get_playlist(&self, playlist_id: &str) -> PluginResult<Playlist> {
+        let playlist = self.playlist(playlist_id).await?;
+        let tracks = self.all_playlist_tracks(playlist_id).await?;
+        Ok(convert::convert_playlist(playlist, tracks))
+    }
+
+    async fn is_track_available(&self, track_id: &str) -> PluginResult<bool> {
+        Ok(!self.videos(&[track_id.to_string()]).await?.is_empty())
+    }
+}
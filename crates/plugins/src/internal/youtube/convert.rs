@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use music_plugin_sdk::types::media::{Artist, Image, Playlist, PlaylistOwner, Track};
+
+use super::types::*;
+
+pub const PROVIDER: &str = "youtube";
+
+pub fn watch_url(video_id: &str) -> String {
+    format!("https://www.youtube.com/watch?v={}", video_id)
+}
+
+/// Milliseconds of an ISO 8601 duration such as "PT1H2M3S"; `None` for live
+/// streams ("P0D") and anything unparsable
+pub fn parse_duration(duration: &str) -> Option<u32> {
+    let rest = duration.strip_prefix('P')?;
+    let mut seconds = 0u64;
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in rest.chars() {
+        match c {
+            'T' => in_time = true,
+            '0'..='9' => number.push(c),
+            unit => {
+                let value: u64 = number.parse().ok()?;
+                number.clear();
+                seconds += value
+                    * match (unit, in_time) {
+                        ('W', false) => 604_800,
+                        ('D', false) => 86_400,
+                        ('H', true) => 3_600,
+                        ('M', true) => 60,
+                        ('S', true) => 1,
+                        _ => return None,
+                    };
+            }
+        }
+    }
+    if !number.is_empty() || seconds == 0 {
+        return None;
+    }
+    u32::try_from(seconds * 1000).ok()
+}
+
+fn images(thumbnails: &YoutubeThumbnails) -> Vec<Image> {
+    thumbnails
+        .all()
+        .map(|t| Image { url: t.url.clone(), width: t.width, height: t.height })
+        .collect()
+}
+
+fn largest(thumbnails: &YoutubeThumbnails) -> Option<String> {
+    thumbnails.all().last().map(|t| t.url.clone())
+}
+
+/// Auto-generated artist channels are named "<artist> - Topic"
+fn artist_name(channel_title: Option<&str>) -> String {
+    let title = channel_title.unwrap_or_default();
+    title.strip_suffix(" - Topic").unwrap_or(title).to_string()
+}
+
+/// `None` for videos returned without their snippet
+pub fn convert_video(video: YoutubeVideo) -> Option<Track> {
+    let snippet = video.snippet?;
+    let mut metadata = HashMap::new();
+    if let Some(channel_id) = snippet.channel_id.clone() {
+        metadata.insert("artist_id".to_string(), channel_id);
+    }
+    Some(Track {
+        url: Some(watch_url(&video.id)),
+        provider: Some(PROVIDER.to_string()),
+        provider_id: Some(video.id.clone()),
+        id: video.id,
+        title: snippet.title,
+        artist: artist_name(snippet.channel_title.as_deref()),
+        album: None,
+        album_ref: None,
+        disc_number: None,
+        track_number: None,
+        duration: video
+            .content_details
+            .and_then(|d| d.duration)
+            .and_then(|d| parse_duration(&d)),
+        cover_url: largest(&snippet.thumbnails),
+        quality: None,
+        preview_url: None,
+        isrc: None,
+        popularity: None,
+        availability: None,
+        lyrics: None,
+        metadata,
+    })
+}
+
+/// Playlist with the tracks listed so far; `total` is the playlist's full length
+pub fn convert_playlist(playlist: YoutubePlaylist, tracks: Vec<Track>) -> Playlist {
+    let snippet = playlist.snippet;
+    let total = playlist.content_details.and_then(|d| d.item_count);
+    let published = snippet.published_at.unwrap_or_else(Utc::now);
+    let mut external_urls = HashMap::new();
+    external_urls.insert(
+        PROVIDER.to_string(),
+        format!("https://www.youtube.com/playlist?list={}", playlist.id),
+    );
+    Playlist {
+        provider: Some(PROVIDER.to_string()),
+        provider_id: Some(playlist.id.clone()),
+        id: playlist.id,
+        title: snippet.title,
+        description: snippet.description.filter(|d| !d.is_empty()),
+        creator: artist_name(snippet.channel_title.as_deref()),
+        owner: Some(PlaylistOwner {
+            id: snippet.channel_id,
+            name: snippet.channel_title,
+        }),
+        cover_url: largest(&snippet.thumbnails),
+        images: Some(images(&snippet.thumbnails)),
+        track_count: total.map(f64::from).unwrap_or(tracks.len() as f64),
+        total_tracks: total,
+        tracks,
+        created_at: published,
+        updated_at: published,
+        is_public: true,
+        collaborative: false,
+        availability: None,
+        external_urls: Some(external_urls),
+        file_path: None,
+        extension: None,
+        icon: None,
+        library_item: None,
+        metadata: HashMap::new(),
+    }
+}
+
+/// Channels play the artist role. The uploads playlist, when known, is kept in
+/// the metadata for listing the channel's videos.
+pub fn convert_channel(channel: YoutubeChannel) -> Artist {
+    let snippet = channel.snippet;
+    let count = |value: Option<String>| value.and_then(|v| v.parse::<u64>().ok());
+    let (followers, videos) = match channel.statistics {
+        Some(s) => (count(s.subscriber_count), count(s.video_count)),
+        None => (None, None),
+    };
+    let mut metadata = HashMap::new();
+    if let Some(uploads) = channel
+        .content_details
+        .and_then(|d| d.related_playlists)
+        .and_then(|p| p.uploads)
+    {
+        metadata.insert("uploads_playlist".to_string(), uploads);
+    }
+    if let Some(handle) = snippet.custom_url {
+        metadata.insert("handle".to_string(), handle);
+    }
+    let name = artist_name(Some(&snippet.title));
+    Artist {
+        id: channel.id,
+        sanitized_name: Some(name.to_lowercase()),
+        name,
+        mbid: None,
+        description: snippet.description.filter(|d| !d.is_empty()),
+        avatar_url: largest(&snippet.thumbnails),
+        followers,
+        track_count: videos.unwrap_or(0) as f64,
+        metadata,
+        extra_info: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iso_durations_are_converted_to_milliseconds() {
+        assert_eq!(parse_duration("PT3M21S"), Some(201_000));
+        assert_eq!(parse_duration("PT1H0M5S"), Some(3_605_000));
+        assert_eq!(parse_duration("P1DT2S"), Some(86_402_000));
+        assert_eq!(parse_duration("P0D"), None);
+        assert_eq!(parse_duration("3:21"), None);
+        assert_eq!(parse_duration("PT5"), None);
+    }
+
+    #[test]
+    fn topic_channels_are_named_after_the_artist() {
+        assert_eq!(artist_name(Some("Daft Punk - Topic")), "Daft Punk");
+        assert_eq!(artist_name(Some("Some Channel")), "Some Channel");
+        assert_eq!(artist_name(None), "");
+    }
+}
@@ -0,0 +1,131 @@
+/// A channel as it appears in links
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChannelRef {
+    /// "UC…" channel ID
+    Id(String),
+    /// Handle without the "@"
+    Handle(String),
+    /// Legacy /user/ name
+    Username(String),
+}
+
+/// What a pasted YouTube link points at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum YoutubeLink {
+    Video(String),
+    /// Links to a video played within a playlist resolve to the playlist
+    Playlist(String),
+    Channel(ChannelRef),
+}
+
+const HOSTS: &[&str] = &["youtube.com", "www.youtube.com", "m.youtube.com", "music.youtube.com"];
+
+fn is_video_id(id: &str) -> bool {
+    id.len() == 11 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn is_resource_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// Mixes ("RD…") are generated per viewer and can't be listed
+fn playlist(list: Option<&str>) -> Option<YoutubeLink> {
+    list.filter(|l| is_resource_id(l) && !l.starts_with("RD"))
+        .map(|l| YoutubeLink::Playlist(l.to_string()))
+}
+
+fn video(id: Option<&str>) -> Option<YoutubeLink> {
+    id.filter(|id| is_video_id(id)).map(|id| YoutubeLink::Video(id.to_string()))
+}
+
+/// Parse a youtube.com, music.youtube.com or youtu.be link; `None` for
+/// anything else, e.g. a plain search term
+pub fn parse_link(input: &str) -> Option<YoutubeLink> {
+    let input = input.trim();
+    let rest = input
+        .strip_prefix("https://")
+        .or_else(|| input.strip_prefix("http://"))
+        .unwrap_or(input);
+    let rest = rest.split('#').next().unwrap_or(rest);
+    let (host, rest) = rest.split_once('/').unwrap_or((rest, ""));
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let list = query_param(query, "list");
+
+    if host.eq_ignore_ascii_case("youtu.be") {
+        return playlist(list).or_else(|| video(segments.first().copied()));
+    }
+    if !HOSTS.iter().any(|h| host.eq_ignore_ascii_case(h)) {
+        return None;
+    }
+    match segments.as_slice() {
+        ["watch"] => playlist(list).or_else(|| video(query_param(query, "v"))),
+        ["playlist"] => playlist(list),
+        ["shorts" | "embed" | "live", id, ..] => video(Some(*id)),
+        ["channel", id, ..] if id.starts_with("UC") => Some(YoutubeLink::Channel(ChannelRef::Id(id.to_string()))),
+        ["user", name, ..] if is_resource_id(name) => {
+            Some(YoutubeLink::Channel(ChannelRef::Username(name.to_string())))
+        }
+        // Custom /c/ names usually match the handle
+        ["c", name, ..] if is_resource_id(name) => Some(YoutubeLink::Channel(ChannelRef::Handle(name.to_string()))),
+        // YouTube Music: /browse/UC… is a channel, /browse/VL… a playlist
+        ["browse", id, ..] if id.starts_with("UC") => Some(YoutubeLink::Channel(ChannelRef::Id(id.to_string()))),
+        ["browse", id, ..] => playlist(id.strip_prefix("VL")),
+        [handle, ..] => handle
+            .strip_prefix('@')
+            .filter(|h| !h.is_empty())
+            .map(|h| YoutubeLink::Channel(ChannelRef::Handle(h.to_string()))),
+        [] => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn videos_playlists_and_channels_are_recognized() {
+        let video = Some(YoutubeLink::Video("dQw4w9WgXcQ".to_string()));
+        assert_eq!(parse_link("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42s"), video);
+        assert_eq!(parse_link("youtu.be/dQw4w9WgXcQ?si=abc"), video);
+        assert_eq!(parse_link("https://music.youtube.com/watch?v=dQw4w9WgXcQ"), video);
+        assert_eq!(parse_link("https://youtube.com/shorts/dQw4w9WgXcQ"), video);
+
+        let playlist = Some(YoutubeLink::Playlist("PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG".to_string()));
+        assert_eq!(parse_link("https://www.youtube.com/playlist?list=PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG"), playlist);
+        assert_eq!(
+            parse_link("https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG"),
+            playlist
+        );
+        assert_eq!(parse_link("https://music.youtube.com/browse/VLPLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG"), playlist);
+
+        assert_eq!(
+            parse_link("https://www.youtube.com/channel/UCuAXFkgsw1L7xaCfnd5JJOw/videos"),
+            Some(YoutubeLink::Channel(ChannelRef::Id("UCuAXFkgsw1L7xaCfnd5JJOw".to_string())))
+        );
+        assert_eq!(
+            parse_link("https://www.youtube.com/@RickAstleyYT"),
+            Some(YoutubeLink::Channel(ChannelRef::Handle("RickAstleyYT".to_string())))
+        );
+    }
+
+    #[test]
+    fn search_terms_and_mixes_are_not_links() {
+        assert_eq!(parse_link("never gonna give you up"), None);
+        assert_eq!(parse_link("https://example.com/watch?v=dQw4w9WgXcQ"), None);
+        assert_eq!(parse_link("https://www.youtube.com/watch?v=short"), None);
+        // A mix falls back to the video it was opened from
+        assert_eq!(
+            parse_link("https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=RDdQw4w9WgXcQ"),
+            Some(YoutubeLink::Video("dQw4w9WgXcQ".to_string()))
+        );
+    }
+}
@@ -0,0 +1,12 @@
+//! YouTube provider. Videos, playlists and channels come from the YouTube
+//! Data API v3 with the API key configured for the plugin. Pasting a video,
+//! playlist or channel link into the search resolves it to its tracks.
+
+mod plugin;
+mod api;
+mod types;
+mod convert;
+mod link;
+
+pub use plugin::YoutubePlugin;
+pub use link::{parse_link, ChannelRef, YoutubeLink};
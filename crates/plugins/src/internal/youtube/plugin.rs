@@ -0,0 +1,187 @@
+use async_trait::async_trait;
+use semver::Version;
+use uuid::Uuid;
+use reqwest::Client;
+use std::sync::{Arc, RwLock as StdRwLock};
+use std::time::Duration;
+
+use crate::system::core::*;
+use crate::system::types::*;
+use crate::PluginResult;
+use music_plugin_sdk::traits::BasePlugin;
+use music_plugin_sdk::utils::{ConfigField, ConfigSchemaBuilder};
+
+/// Default Data API address
+pub const DEFAULT_API_BASE: &str = "https://www.googleapis.com/youtube/v3";
+/// Configuration key of the Data API key
+pub const API_KEY_CONFIG_KEY: &str = "api_key";
+
+#[derive(Clone)]
+pub struct YoutubePlugin {
+    metadata: PluginMetadata,
+    status: PluginStatus,
+    context: Option<PluginContext>,
+    pub http: Client,
+    /// Data API key, shared by all clones of the plugin
+    pub api_key: Arc<StdRwLock<Option<String>>>,
+    /// Self-hosted Data API mirror, the default address when unset
+    pub api_endpoint: Arc<StdRwLock<Option<String>>>,
+}
+
+impl std::fmt::Debug for YoutubePlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("YoutubePlugin")
+            .field("metadata", &self.metadata)
+            .field("status", &self.status)
+            .finish()
+    }
+}
+
+impl YoutubePlugin {
+    /// Stable deterministic UUID of the builtin plugin
+    pub fn plugin_id() -> Uuid {
+        Uuid::new_v5(&Uuid::NAMESPACE_OID, b"builtin:youtube")
+    }
+
+    pub fn new() -> Self {
+        let metadata = PluginMetadata {
+            id: Self::plugin_id(),
+            name: "youtube".to_string(),
+            display_name: "YouTube Music".to_string(),
+            description: "YouTube Music provider plugin".to_string(),
+            version: Version::new(1, 0, 0),
+            author: "Music Player Team".to_string(),
+            homepage: Some("https://music.youtube.com".to_string()),
+            repository: None,
+            license: Some("MIT".to_string()),
+            icon: None,
+            keywords: vec![
+                "youtube".to_string(),
+                "music".to_string(),
+                "video".to_string(),
+                "audio".to_string(),
+            ],
+            plugin_type: PluginType::AudioProvider,
+            capabilities: vec![PluginCapability::Search, PluginCapability::Playlists],
+            dependencies: vec![],
+            min_system_version: None,
+            max_system_version: None,
+        };
+        let http = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(5))
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_else(|_| Client::new());
+
+        Self {
+            metadata,
+            status: PluginStatus::Unloaded,
+            context: None,
+            http,
+            api_key: Arc::new(StdRwLock::new(None)),
+            api_endpoint: Arc::new(StdRwLock::new(None)),
+        }
+    }
+
+    /// Data API address in use: the configured mirror, else the default one
+    pub fn api_base(&self) -> String {
+        self.api_endpoint
+            .read()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| DEFAULT_API_BASE.to_string())
+    }
+}
+
+#[async_trait]
+impl Plugin for YoutubePlugin {
+    fn metadata(&self) -> PluginMetadata { self.metadata.clone() }
+    fn id(&self) -> Uuid { self.metadata.id }
+    fn plugin_type(&self) -> PluginType { self.metadata.plugin_type.clone() }
+    fn capabilities(&self) -> Vec<PluginCapability> { self.metadata.capabilities.clone() }
+    fn initialize(&mut self, context: &PluginContext) -> PluginResult<()> { self.context = Some(context.clone()); self.status = PluginStatus::Ready; Ok(()) }
+    fn start(&mut self) -> PluginResult<()> { self.status = PluginStatus::Running; Ok(()) }
+    fn stop(&mut self) -> PluginResult<()> { self.status = PluginStatus::Stopped; Ok(()) }
+    fn destroy(&mut self) -> PluginResult<()> { self.status = PluginStatus::Unloaded; self.context = None; Ok(()) }
+    fn status(&self) -> PluginResult<PluginStatus> { Ok(self.status.clone()) }
+    async fn handle_event(&mut self, _event: PluginEvent) -> PluginResult<Option<PluginResponse>> { Ok(None) }
+    fn health_check(&self) -> PluginResult<HealthStatus> { Ok(HealthStatus::Healthy) }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+impl Default for YoutubePlugin { fn default() -> Self { Self::new() } }
+
+// MediaPlugin is implemented in api.rs
+
+#[async_trait]
+impl BasePlugin for YoutubePlugin {
+    fn metadata(&self) -> music_plugin_sdk::types::base::PluginMetadata {
+        music_plugin_sdk::types::base::PluginMetadata {
+            id: self.metadata.id,
+            name: self.metadata.name.clone(),
+            version: self.metadata.version.to_string(),
+            description: self.metadata.description.clone(),
+            author: self.metadata.author.clone(),
+            website: self.metadata.homepage.clone(),
+            icon: self.metadata.icon.clone(),
+            capabilities: vec![
+                music_plugin_sdk::types::base::PluginCapability::Search,
+                music_plugin_sdk::types::base::PluginCapability::Network,
+            ],
+            min_sdk_version: "1.0.0".to_string(),
+            config_schema: Some(
+                ConfigSchemaBuilder::new()
+                    .field(
+                        API_KEY_CONFIG_KEY,
+                        ConfigField::secret("API key")
+                            .description("YouTube Data API v3 key from the Google Cloud console")
+                            .default(""),
+                    )
+                    .build(),
+            ),
+        }
+    }
+
+    async fn initialize(&mut self, _context: &music_plugin_sdk::types::base::PluginContext) -> music_plugin_sdk::types::base::PluginResult<()> {
+        self.status = PluginStatus::Ready;
+        Ok(())
+    }
+
+    async fn start(&mut self) -> music_plugin_sdk::types::base::PluginResult<()> {
+        self.status = PluginStatus::Running;
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> music_plugin_sdk::types::base::PluginResult<()> {
+        self.status = PluginStatus::Stopped;
+        Ok(())
+    }
+
+    fn status(&self) -> music_plugin_sdk::types::base::PluginStatus {
+        match self.status {
+            PluginStatus::Unloaded => music_plugin_sdk::types::base::PluginStatus::Loaded,
+            PluginStatus::Ready => music_plugin_sdk::types::base::PluginStatus::Loaded,
+            PluginStatus::Running => music_plugin_sdk::types::base::PluginStatus::Running,
+            PluginStatus::Stopped => music_plugin_sdk::types::base::PluginStatus::Stopped,
+            _ => music_plugin_sdk::types::base::PluginStatus::Error("Plugin error".to_string()),
+        }
+    }
+
+    async fn configure(&mut self, config: music_plugin_sdk::types::base::PluginConfig) -> music_plugin_sdk::types::base::PluginResult<()> {
+        if let Some(endpoint) = config.endpoint() {
+            *self.api_endpoint.write().unwrap() = endpoint.map(|url| url.trim_end_matches('/').to_string());
+        }
+        if let Some(api_key) = config.values.get(API_KEY_CONFIG_KEY) {
+            let api_key = api_key.as_str().map(str::trim).filter(|key| !key.is_empty());
+            *self.api_key.write().unwrap() = api_key.map(str::to_string);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+/// One page of a list endpoint; the next one is requested with its token
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubePage<T> {
+    #[serde(default = "Vec::new")]
+    pub items: Vec<T>,
+    pub next_page_token: Option<String>,
+    pub page_info: Option<YoutubePageInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubePageInfo {
+    pub total_results: Option<u32>,
+    pub results_per_page: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct YoutubeThumbnail {
+    pub url: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct YoutubeThumbnails {
+    pub default: Option<YoutubeThumbnail>,
+    pub medium: Option<YoutubeThumbnail>,
+    pub high: Option<YoutubeThumbnail>,
+    pub standard: Option<YoutubeThumbnail>,
+    pub maxres: Option<YoutubeThumbnail>,
+}
+
+impl YoutubeThumbnails {
+    /// Available sizes, smallest first
+    pub fn all(&self) -> impl Iterator<Item = &YoutubeThumbnail> {
+        [&self.default, &self.medium, &self.high, &self.standard, &self.maxres]
+            .into_iter()
+            .flatten()
+    }
+}
+
+/// Kind and ID of a search result or playlist entry
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubeResourceId {
+    /// "youtube#video" | "youtube#playlist" | "youtube#channel"
+    pub kind: Option<String>,
+    pub video_id: Option<String>,
+    pub playlist_id: Option<String>,
+    pub channel_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubeSnippet {
+    #[serde(default)]
+    pub title: String,
+    pub description: Option<String>,
+    pub channel_id: Option<String>,
+    pub channel_title: Option<String>,
+    #[serde(default)]
+    pub thumbnails: YoutubeThumbnails,
+    pub published_at: Option<DateTime<Utc>>,
+    /// Channels only, e.g. "@artist"
+    pub custom_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct YoutubeSearchItem {
+    pub id: YoutubeResourceId,
+    #[serde(default)]
+    pub snippet: YoutubeSnippet,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubeVideo {
+    pub id: String,
+    pub snippet: Option<YoutubeSnippet>,
+    pub content_details: Option<YoutubeVideoDetails>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct YoutubeVideoDetails {
+    /// ISO 8601, e.g. "PT3M21S"
+    pub duration: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubePlaylist {
+    pub id: String,
+    #[serde(default)]
+    pub snippet: YoutubeSnippet,
+    pub content_details: Option<YoutubePlaylistDetails>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubePlaylistDetails {
+    pub item_count: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubePlaylistItem {
+    pub content_details: Option<YoutubePlaylistItemDetails>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubePlaylistItemDetails {
+    pub video_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubeChannel {
+    pub id: String,
+    #[serde(default)]
+    pub snippet: YoutubeSnippet,
+    pub statistics: Option<YoutubeChannelStatistics>,
+    pub content_details: Option<YoutubeChannelDetails>,
+}
+
+/// Counts come as strings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubeChannelStatistics {
+    pub subscriber_count: Option<String>,
+    pub video_count: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubeChannelDetails {
+    pub related_playlists: Option<YoutubeRelatedPlaylists>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct YoutubeRelatedPlaylists {
+    /// Playlist of all uploads of the channel
+    pub uploads: Option<String>,
+}
+
+/// Error body of the Data API
+#[derive(Debug, Clone, Deserialize)]
+pub struct YoutubeErrorResponse {
+    pub error: YoutubeError,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct YoutubeError {
+    pub message: Option<String>,
+    #[serde(default)]
+    pub errors: Vec<YoutubeErrorDetail>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct YoutubeErrorDetail {
+    /// e.g. "quotaExceeded", "keyInvalid", "playlistNotFound"
+    pub reason: Option<String>,
+}
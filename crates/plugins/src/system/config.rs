@@ -0,0 +1,77 @@
+//! User configuration of plugins
+//!
+//! A plugin declares its settings as a JSON schema (`config_schema` in its
+//! metadata). Values set by the user are validated against the schema, passed
+//! to the plugin through `configure()` and persisted as JSON in the `config`
+//! column of `plugin_states`, so they are applied again when the plugin loads.
+
+use std::collections::HashMap;
+
+use music_plugin_sdk::types::base::ENDPOINT_CONFIG_KEY;
+use music_plugin_sdk::utils::ConfigValidator;
+use serde_json::Value;
+
+use crate::system::types::PluginError;
+use crate::PluginResult;
+
+/// Values persisted in `plugin_states.config`; unreadable JSON counts as empty
+pub fn parse_stored(config: &str) -> HashMap<String, Value> {
+    serde_json::from_str(config).unwrap_or_default()
+}
+
+/// Configuration in effect: the schema defaults overlaid with the stored values
+pub fn effective(schema: Option<&Value>, stored: &HashMap<String, Value>) -> HashMap<String, Value> {
+    let mut values = schema
+        .map(|schema| ConfigValidator::new(schema.clone()).get_defaults())
+        .unwrap_or_default();
+    values.extend(stored.iter().map(|(k, v)| (k.clone(), v.clone())));
+    values
+}
+
+/// Validate user values against the schema and return the values to persist,
+/// without the cleared (`null`) ones. The endpoint is managed by the provider
+/// endpoint overrides and cannot be set here.
+pub fn prepare(schema: Option<&Value>, values: HashMap<String, Value>) -> PluginResult<HashMap<String, Value>> {
+    if values.contains_key(ENDPOINT_CONFIG_KEY) {
+        return Err(PluginError::InvalidConfig {
+            reason: format!("{} is set through the provider endpoint settings", ENDPOINT_CONFIG_KEY),
+        });
+    }
+    if let Some(schema) = schema {
+        let errors = ConfigValidator::new(schema.clone()).errors(&values);
+        if !errors.is_empty() {
+            return Err(PluginError::InvalidConfig { reason: errors.join("; ") });
+        }
+    }
+    Ok(values.into_iter().filter(|(_, v)| !v.is_null()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use music_plugin_sdk::utils::{ConfigField, ConfigSchemaBuilder};
+    use serde_json::json;
+
+    #[test]
+    fn values_are_validated_against_the_schema() {
+        let schema = ConfigSchemaBuilder::new()
+            .field("token", ConfigField::secret("Token").required())
+            .field("region", ConfigField::choice("Region", &["us", "eu"]).default("us"))
+            .field("page_size", ConfigField::integer("Page size").range(10.0, 100.0))
+            .build();
+
+        let values = HashMap::from([("token".to_string(), json!("abc")), ("page_size".to_string(), Value::Null)]);
+        let stored = prepare(Some(&schema), values).unwrap();
+        assert_eq!(stored, HashMap::from([("token".to_string(), json!("abc"))]));
+        assert_eq!(effective(Some(&schema), &stored)["region"], json!("us"));
+
+        let invalid = HashMap::from([("region".to_string(), json!("asia")), ("page_size".to_string(), json!(5))]);
+        let Err(PluginError::InvalidConfig { reason }) = prepare(Some(&schema), invalid) else {
+            panic!("invalid values were accepted");
+        };
+        assert!(reason.contains("token is required"));
+        assert!(reason.contains("region must be one of"));
+        assert!(reason.contains("page_size must be at least"));
+        assert!(prepare(None, HashMap::from([(ENDPOINT_CONFIG_KEY.to_string(), json!("x"))])).is_err());
+    }
+}
@@ -0,0 +1,233 @@
+//! Provider endpoint overrides
+//!
+//! A media provider can be pointed at a self-hosted API mirror instead of its
+//! default endpoint. The mirror reaches the plugin through `configure()` under
+//! `ENDPOINT_CONFIG_KEY`. Mirrors are health checked periodically; when the
+//! override allows it, the provider falls back to its default endpoint while
+//! its mirror is down and returns to the mirror once it recovers.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use music_plugin_sdk::traits::BasePlugin;
+use music_plugin_sdk::types::base::{PluginConfig, ENDPOINT_CONFIG_KEY};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::factory::MediaPluginFactory;
+use crate::system::types::PluginError;
+use crate::PluginResult;
+
+/// Interval between two background health checks
+pub const ENDPOINT_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Time a mirror has to answer a health check
+const ENDPOINT_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Endpoint override of one provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointOverride {
+    /// Validated base URL of the mirror
+    pub base_url: String,
+    /// Use the default endpoint while the mirror fails its health check
+    pub fallback: bool,
+}
+
+/// Health of a provider's mirror and the endpoint the provider uses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndpointStatus {
+    pub plugin_id: String,
+    pub base_url: String,
+    /// Result of the last health check
+    pub healthy: bool,
+    /// Whether the provider uses the mirror, `false` when it fell back to the default
+    pub active: bool,
+    pub error: Option<String>,
+    pub checked_at: chrono::NaiveDateTime,
+}
+
+/// Check a mirror URL and normalize it: an absolute http(s) URL with a host,
+/// without query or fragment, and without trailing slash.
+pub fn validate_endpoint(url: &str) -> PluginResult<String> {
+    let invalid = |reason: String| PluginError::InvalidConfig { reason };
+    let parsed = reqwest::Url::parse(url.trim()).map_err(|e| invalid(format!("Invalid endpoint URL {:?}: {}", url, e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(invalid(format!("Endpoint must use http or https, got {}", parsed.scheme())));
+    }
+    if parsed.host_str().map_or(true, str::is_empty) {
+        return Err(invalid(format!("Endpoint {:?} has no host", url)));
+    }
+    if parsed.query().is_some() || parsed.fragment().is_some() {
+        return Err(invalid(format!("Endpoint {:?} must not contain a query or fragment", url)));
+    }
+    Ok(parsed.as_str().trim_end_matches('/').to_string())
+}
+
+/// Applies endpoint overrides to the media plugins and keeps them on a
+/// working endpoint
+#[derive(Debug)]
+pub struct EndpointMonitor {
+    http: reqwest::Client,
+    factory: Arc<Mutex<MediaPluginFactory>>,
+    entries: Mutex<HashMap<Uuid, (EndpointOverride, EndpointStatus)>>,
+}
+
+impl EndpointMonitor {
+    pub fn new(factory: Arc<Mutex<MediaPluginFactory>>) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(ENDPOINT_CHECK_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            http,
+            factory,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Replace all overrides. Providers that lost theirs go back to the
+    /// default endpoint; new or changed mirrors are checked before use.
+    pub async fn set_overrides(&self, overrides: HashMap<Uuid, EndpointOverride>) {
+        let previous = std::mem::take(&mut *self.entries.lock().unwrap());
+        for (plugin_id, (_, status)) in &previous {
+            if !overrides.contains_key(plugin_id) && status.active {
+                self.configure(*plugin_id, None).await;
+            }
+        }
+
+        for (plugin_id, endpoint) in overrides {
+            let unchanged = previous.get(&plugin_id).filter(|(old, _)| *old == endpoint).cloned();
+            let status = match unchanged {
+                Some((_, status)) => status,
+                None => self.check(plugin_id, &endpoint, None).await,
+            };
+            self.entries.lock().unwrap().insert(plugin_id, (endpoint, status));
+        }
+    }
+
+    /// Check every mirror, switching providers between mirror and default
+    /// endpoint when a mirror goes down or recovers
+    pub async fn check_all(&self) {
+        let entries: Vec<(Uuid, EndpointOverride, bool)> = self
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, (endpoint, status))| (*id, endpoint.clone(), status.active))
+            .collect();
+        for (plugin_id, endpoint, active) in entries {
+            let status = self.check(plugin_id, &endpoint, Some(active)).await;
+            let mut guard = self.entries.lock().unwrap();
+            // Skip overrides replaced while the check was running
+            if let Some(entry) = guard.get_mut(&plugin_id).filter(|(current, _)| *current == endpoint) {
+                entry.1 = status;
+            }
+        }
+    }
+
+    /// Configure a freshly loaded instance of `plugin_id` with the endpoint it
+    /// was using
+    pub async fn reapply(&self, plugin_id: Uuid) {
+        let base_url = {
+            let guard = self.entries.lock().unwrap();
+            match guard.get(&plugin_id) {
+                Some((endpoint, status)) if status.active => Some(endpoint.base_url.clone()),
+                _ => return,
+            }
+        };
+        self.configure(plugin_id, base_url.as_deref()).await;
+    }
+
+    pub fn statuses(&self) -> Vec<EndpointStatus> {
+        let mut statuses: Vec<EndpointStatus> = self
+            .entries
+            .lock()
+            .unwrap()
+            .values()
+            .map(|(_, status)| status.clone())
+            .collect();
+        statuses.sort_by(|a, b| a.plugin_id.cmp(&b.plugin_id));
+        statuses
+    }
+
+    /// Check the mirror of `plugin_id` and configure the plugin when the
+    /// endpoint it should use differs from `active` (`None`: not configured yet)
+    async fn check(&self, plugin_id: Uuid, endpoint: &EndpointOverride, active: Option<bool>) -> EndpointStatus {
+        let error = self.probe(&endpoint.base_url).await.err();
+        let healthy = error.is_none();
+        let use_mirror = healthy || !endpoint.fallback;
+        if active != Some(use_mirror) {
+            if active.is_some() {
+                tracing::warn!(
+                    "Provider {} switches to its {} endpoint ({})",
+                    plugin_id,
+                    if use_mirror { "mirror" } else { "default" },
+                    error.as_deref().unwrap_or("mirror recovered")
+                );
+            }
+            self.configure(plugin_id, use_mirror.then_some(endpoint.base_url.as_str())).await;
+        }
+        EndpointStatus {
+            plugin_id: plugin_id.to_string(),
+            base_url: endpoint.base_url.clone(),
+            healthy,
+            active: use_mirror,
+            error,
+            checked_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    /// A mirror is healthy when it answers without a server error
+    async fn probe(&self, base_url: &str) -> Result<(), String> {
+        let response = self.http.get(base_url).send().await.map_err(|e| e.to_string())?;
+        if response.status().is_server_error() {
+            return Err(format!("Mirror answered {}", response.status()));
+        }
+        Ok(())
+    }
+
+    /// Pass `base_url` to the plugin, `None` restoring its default endpoint
+    async fn configure(&self, plugin_id: Uuid, base_url: Option<&str>) {
+        let Some(plugin) = self.factory.lock().unwrap().get_registered_media_plugin(plugin_id) else {
+            return;
+        };
+        let config = PluginConfig {
+            values: HashMap::from([(ENDPOINT_CONFIG_KEY.to_string(), serde_json::json!(base_url))]),
+            is_valid: true,
+            errors: Vec::new(),
+        };
+        if let Err(e) = plugin.lock().await.configure(config).await {
+            tracing::warn!("Failed to configure the endpoint of provider {}: {}", plugin_id, e);
+        }
+    }
+}
+
+/// Spawn the periodic mirror health checks
+pub fn spawn_endpoint_checks(monitor: Arc<EndpointMonitor>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(ENDPOINT_CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The first tick completes immediately; overrides are checked when set
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            monitor.check_all().await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoints_are_validated_and_normalized() {
+        assert_eq!(validate_endpoint(" https://mirror.example.com/api/ ").unwrap(), "https://mirror.example.com/api");
+        assert_eq!(validate_endpoint("http://192.168.1.2:3000").unwrap(), "http://192.168.1.2:3000");
+        assert!(validate_endpoint("mirror.example.com").is_err());
+        assert!(validate_endpoint("ftp://mirror.example.com").is_err());
+        assert!(validate_endpoint("https://mirror.example.com/?key=1").is_err());
+    }
+}
@@ -0,0 +1,461 @@
+//! Host-mediated HTTP for sandboxed plugins
+//!
+//! Plugins without direct network access (WASM plugins) make their requests
+//! through the `HOST_FETCH_SERVICE` host service. The proxy checks each URL
+//! against the global network restrictions and the plugin's network
+//! permissions, adds the stored credential the request names, so its value
+//! never enters plugin memory, and records every request, allowed or denied,
+//! in a bounded per-plugin audit log.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use music_plugin_sdk::types::base::{HostHttpRequest, HostHttpResponse};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, COOKIE, SET_COOKIE};
+use reqwest::{Method, Url};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::system::security::{host_matches, SecurityManager};
+use crate::system::types::PluginError;
+use crate::PluginResult;
+
+/// Audit entries kept per plugin, older ones are dropped
+pub const MAX_AUDIT_ENTRIES: usize = 200;
+
+/// Largest response body passed to a plugin, lowered by the global network
+/// restrictions
+pub const DEFAULT_MAX_RESPONSE_SIZE: u64 = 16 * 1024 * 1024;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where a credential goes in the request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialPlacement {
+    /// `Authorization: Bearer <value>`
+    Bearer,
+    /// Header `name`
+    Header,
+    /// `Cookie` header
+    Cookie,
+    /// Query parameter `name`
+    Query,
+}
+
+/// Credential stored by the host for a plugin and referenced by name in its
+/// requests
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginCredential {
+    pub placement: CredentialPlacement,
+    /// Header or query parameter name, for `Header` and `Query`
+    #[serde(default)]
+    pub name: Option<String>,
+    pub value: String,
+    /// Hosts the credential may be sent to, with the patterns of the network
+    /// permissions; any host the plugin may access when empty
+    #[serde(default)]
+    pub hosts: Vec<String>,
+}
+
+impl std::fmt::Debug for PluginCredential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PluginCredential")
+            .field("placement", &self.placement)
+            .field("name", &self.name)
+            .field("value", &"<redacted>")
+            .field("hosts", &self.hosts)
+            .finish()
+    }
+}
+
+/// One request made through the proxy. The query string is left out, it may
+/// carry credentials.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkAuditEntry {
+    pub at: chrono::NaiveDateTime,
+    pub method: String,
+    pub host: String,
+    pub path: String,
+    /// Name of the injected credential
+    pub credential: Option<String>,
+    /// Response status, `None` when the request was denied or failed
+    pub status: Option<u16>,
+    pub request_bytes: u64,
+    pub response_bytes: u64,
+    pub duration_ms: u64,
+    /// Whether the request was refused by the security checks
+    pub denied: bool,
+    pub error: Option<String>,
+}
+
+/// Fetch service shared by the plugin hosts
+#[derive(Debug)]
+pub struct HostFetchProxy {
+    security: Arc<Mutex<SecurityManager>>,
+    client: reqwest::Client,
+    credentials: Mutex<HashMap<Uuid, HashMap<String, PluginCredential>>>,
+    audit: Mutex<HashMap<Uuid, VecDeque<NetworkAuditEntry>>>,
+}
+
+impl HostFetchProxy {
+    pub fn new(security: Arc<Mutex<SecurityManager>>) -> Self {
+        // Redirects are returned to the plugin so that their targets go
+        // through the checks as well
+        let client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default();
+        Self {
+            security,
+            client,
+            credentials: Mutex::new(HashMap::new()),
+            audit: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Replace the credentials stored for a plugin
+    pub fn set_credentials(&self, plugin_id: Uuid, credentials: HashMap<String, PluginCredential>) {
+        let mut stored = self.credentials.lock().unwrap();
+        if credentials.is_empty() {
+            stored.remove(&plugin_id);
+        } else {
+            stored.insert(plugin_id, credentials);
+        }
+    }
+
+    /// Names of the credentials stored for a plugin, sorted
+    pub fn credential_names(&self, plugin_id: Uuid) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .credentials
+            .lock()
+            .unwrap()
+            .get(&plugin_id)
+            .map(|c| c.keys().cloned().collect())
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    /// Requests of a plugin, newest first
+    pub fn audit(&self, plugin_id: Uuid) -> Vec<NetworkAuditEntry> {
+        self.audit
+            .lock()
+            .unwrap()
+            .get(&plugin_id)
+            .map(|entries| entries.iter().rev().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn clear_audit(&self, plugin_id: Uuid) {
+        self.audit.lock().unwrap().remove(&plugin_id);
+    }
+
+    /// Make a request on behalf of a plugin
+    pub async fn fetch(&self, plugin_id: Uuid, request: HostHttpRequest) -> PluginResult<HostHttpResponse> {
+        let started = Instant::now();
+        let mut entry = NetworkAuditEntry {
+            at: chrono::Utc::now().naive_utc(),
+            method: request_method(&request.method),
+            host: String::new(),
+            path: String::new(),
+            credential: request.credential.clone(),
+            status: None,
+            request_bytes: request.body.as_ref().map_or(0, |b| b.len() as u64),
+            response_bytes: 0,
+            duration_ms: 0,
+            denied: false,
+            error: None,
+        };
+
+        let result = self.send(plugin_id, request, &mut entry).await;
+        entry.duration_ms = started.elapsed().as_millis() as u64;
+        if let Err(e) = &result {
+            entry.denied = matches!(e, PluginError::SecurityViolation { .. });
+            entry.error = Some(e.to_string());
+        }
+        self.record(plugin_id, entry);
+        result
+    }
+
+    async fn send(
+        &self,
+        plugin_id: Uuid,
+        request: HostHttpRequest,
+        entry: &mut NetworkAuditEntry,
+    ) -> PluginResult<HostHttpResponse> {
+        let mut url = Url::parse(&request.url).map_err(|e| PluginError::InvalidConfig {
+            reason: format!("Invalid URL: {}", e),
+        })?;
+        entry.host = url.host_str().unwrap_or_default().to_string();
+        entry.path = url.path().to_string();
+        self.authorize(plugin_id, &url, entry.request_bytes)?;
+
+        let method = Method::from_bytes(entry.method.as_bytes()).map_err(|_| PluginError::InvalidConfig {
+            reason: format!("Invalid HTTP method {:?}", request.method),
+        })?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in &request.headers {
+            let (Ok(name), Ok(value)) = (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(value)) else {
+                return Err(PluginError::InvalidConfig {
+                    reason: format!("Invalid header {:?}", name),
+                });
+            };
+            headers.insert(name, value);
+        }
+        if let Some(name) = &request.credential {
+            let credential = self.credential(plugin_id, name, &entry.host)?;
+            apply_credential(&credential, &mut url, &mut headers)?;
+        }
+
+        let mut builder = self.client.request(method, url).headers(headers);
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+        // Errors leave out the URL, its query may hold an injected credential
+        let failed = |e: reqwest::Error| PluginError::ExecutionFailed {
+            reason: format!("Request failed: {}", e.without_url()),
+        };
+        let mut response = builder.send().await.map_err(failed)?;
+        entry.status = Some(response.status().as_u16());
+
+        let max_size = self.max_response_size();
+        if response.content_length().is_some_and(|len| len > max_size) {
+            return Err(too_large(max_size));
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(failed)? {
+            body.extend_from_slice(&chunk);
+            entry.response_bytes = body.len() as u64;
+            if entry.response_bytes > max_size {
+                return Err(too_large(max_size));
+            }
+        }
+
+        let mut response_headers = HashMap::new();
+        for (name, value) in response.headers() {
+            // Cookies set in answer to a credential belong to the host
+            if request.credential.is_some() && name == SET_COOKIE {
+                continue;
+            }
+            if let Ok(value) = value.to_str() {
+                response_headers
+                    .entry(name.as_str().to_string())
+                    .and_modify(|v: &mut String| {
+                        v.push_str(", ");
+                        v.push_str(value);
+                    })
+                    .or_insert_with(|| value.to_string());
+            }
+        }
+        Ok(HostHttpResponse {
+            status: response.status().as_u16(),
+            headers: response_headers,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        })
+    }
+
+    /// Check a URL against the global restrictions and the plugin's network
+    /// permissions. Literal loopback, private and link-local addresses are
+    /// always refused.
+    fn authorize(&self, plugin_id: Uuid, url: &Url, request_size: u64) -> PluginResult<()> {
+        let denied = |reason: String| PluginError::SecurityViolation { reason };
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(denied(format!("Only http and https requests are allowed, got {}", url.scheme())));
+        }
+        let host = url.host_str().filter(|h| !h.is_empty()).ok_or_else(|| denied("URL has no host".to_string()))?;
+        let ip = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>();
+        if ip.is_ok_and(is_local) {
+            return Err(denied(format!("Access to local address {} is forbidden", host)));
+        }
+
+        let port = url.port_or_known_default().unwrap_or_default();
+        let security = self.security.lock().unwrap();
+        security.check_global_network_restrictions(host)?;
+        if !security.is_plugin_network_access_allowed(plugin_id, host, port, url.scheme()) {
+            return Err(denied(format!(
+                "Plugin {} is not allowed to access {}://{}:{}",
+                plugin_id,
+                url.scheme(),
+                host,
+                port
+            )));
+        }
+        security.validate_request_size(request_size)
+    }
+
+    fn credential(&self, plugin_id: Uuid, name: &str, host: &str) -> PluginResult<PluginCredential> {
+        let credentials = self.credentials.lock().unwrap();
+        let credential = credentials
+            .get(&plugin_id)
+            .and_then(|c| c.get(name))
+            .ok_or_else(|| PluginError::InvalidConfig {
+                reason: format!("No credential named {:?} is stored for plugin {}", name, plugin_id),
+            })?;
+        if !credential.hosts.is_empty() && !credential.hosts.iter().any(|pattern| host_matches(pattern, host)) {
+            return Err(PluginError::SecurityViolation {
+                reason: format!("Credential {:?} may not be sent to {}", name, host),
+            });
+        }
+        Ok(credential.clone())
+    }
+
+    fn max_response_size(&self) -> u64 {
+        let security = self.security.lock().unwrap();
+        security
+            .global_network_restrictions()
+            .max_response_size
+            .map_or(DEFAULT_MAX_RESPONSE_SIZE, |max| max.min(DEFAULT_MAX_RESPONSE_SIZE))
+    }
+
+    fn record(&self, plugin_id: Uuid, entry: NetworkAuditEntry) {
+        if entry.denied {
+            tracing::warn!(
+                "Plugin {} was denied {} {}{}: {}",
+                plugin_id,
+                entry.method,
+                entry.host,
+                entry.path,
+                entry.error.as_deref().unwrap_or_default()
+            );
+        } else {
+            tracing::info!(
+                "Plugin {} fetched {} {}{} -> {:?} ({} bytes in {} ms)",
+                plugin_id,
+                entry.method,
+                entry.host,
+                entry.path,
+                entry.status,
+                entry.response_bytes,
+                entry.duration_ms
+            );
+        }
+        let mut audit = self.audit.lock().unwrap();
+        let entries = audit.entry(plugin_id).or_default();
+        if entries.len() >= MAX_AUDIT_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+}
+
+fn request_method(method: &str) -> String {
+    if method.trim().is_empty() {
+        "GET".to_string()
+    } else {
+        method.trim().to_ascii_uppercase()
+    }
+}
+
+fn too_large(max_size: u64) -> PluginError {
+    PluginError::SecurityViolation {
+        reason: format!("Response exceeds the maximum size of {} bytes", max_size),
+    }
+}
+
+fn is_local(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified(),
+        IpAddr::V6(ip) => {
+            ip.is_loopback() || ip.is_unspecified() || ip.to_ipv4_mapped().is_some_and(|v4| is_local(IpAddr::V4(v4)))
+        }
+    }
+}
+
+/// Add a credential to the request, replacing what the plugin set in its place
+fn apply_credential(credential: &PluginCredential, url: &mut Url, headers: &mut HeaderMap) -> PluginResult<()> {
+    let invalid = || PluginError::InvalidConfig {
+        reason: "Stored credential is not a valid header value".to_string(),
+    };
+    let named = || {
+        credential.name.clone().filter(|n| !n.is_empty()).ok_or_else(|| PluginError::InvalidConfig {
+            reason: "Stored credential has no name".to_string(),
+        })
+    };
+    let sensitive = |value: String| -> PluginResult<HeaderValue> {
+        let mut value = HeaderValue::from_str(&value).map_err(|_| invalid())?;
+        value.set_sensitive(true);
+        Ok(value)
+    };
+
+    match credential.placement {
+        CredentialPlacement::Bearer => {
+            headers.insert(AUTHORIZATION, sensitive(format!("Bearer {}", credential.value))?);
+        }
+        CredentialPlacement::Header => {
+            let name = HeaderName::from_bytes(named()?.as_bytes()).map_err(|_| invalid())?;
+            headers.insert(name, sensitive(credential.value.clone())?);
+        }
+        CredentialPlacement::Cookie => {
+            headers.insert(COOKIE, sensitive(credential.value.clone())?);
+        }
+        CredentialPlacement::Query => {
+            let name = named()?;
+            let pairs: Vec<(String, String)> = url
+                .query_pairs()
+                .filter(|(key, _)| *key != name)
+                .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                .collect();
+            url.query_pairs_mut()
+                .clear()
+                .extend_pairs(pairs)
+                .append_pair(&name, &credential.value);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn denied_requests_are_audited_without_query() {
+        let proxy = HostFetchProxy::new(Arc::new(Mutex::new(SecurityManager::new())));
+        let plugin_id = Uuid::new_v4();
+        let request = |url: &str| HostHttpRequest {
+            url: url.to_string(),
+            ..Default::default()
+        };
+
+        // No network permissions were granted to the plugin
+        let result = proxy.fetch(plugin_id, request("https://api.example.com/v1/search?key=secret")).await;
+        assert!(matches!(result, Err(PluginError::SecurityViolation { .. })));
+        assert!(proxy.fetch(plugin_id, request("http://192.168.1.1/admin")).await.is_err());
+        assert!(proxy.fetch(plugin_id, request("file:///etc/passwd")).await.is_err());
+
+        let audit = proxy.audit(plugin_id);
+        assert_eq!(audit.len(), 3);
+        assert_eq!(audit[2].host, "api.example.com");
+        assert_eq!(audit[2].path, "/v1/search");
+        assert!(audit.iter().all(|entry| entry.denied && entry.status.is_none()));
+    }
+
+    #[test]
+    fn credentials_replace_what_the_plugin_set() {
+        let mut url = Url::parse("https://api.example.com/v1?key=fake&q=x").unwrap();
+        let mut headers = HeaderMap::new();
+        let query = PluginCredential {
+            placement: CredentialPlacement::Query,
+            name: Some("key".to_string()),
+            value: "real".to_string(),
+            hosts: vec!["*.example.com".to_string()],
+        };
+        apply_credential(&query, &mut url, &mut headers).unwrap();
+        assert_eq!(url.query(), Some("q=x&key=real"));
+
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer fake"));
+        let bearer = PluginCredential {
+            placement: CredentialPlacement::Bearer,
+            name: None,
+            value: "token".to_string(),
+            hosts: Vec::new(),
+        };
+        apply_credential(&bearer, &mut url, &mut headers).unwrap();
+        assert_eq!(headers[AUTHORIZATION], "Bearer token");
+        assert!(!format!("{:?}", bearer).contains("token"));
+    }
+}
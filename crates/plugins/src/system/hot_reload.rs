@@ -0,0 +1,188 @@
+//! Plugin hot reload
+//!
+//! Installed plugins live in `<plugin_root>/<plugin-id>/`, next to a
+//! `manifest.json` whose `entry` names the plugin library. The plugin root is
+//! watched, and a change to a manifest or a plugin file reloads the plugin of
+//! that directory: the old instance is stopped and unregistered before the new
+//! version is loaded, without restarting the app.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Weak;
+use std::time::Duration;
+
+use notify::{recommended_watcher, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::system::manager::PluginManager;
+use crate::system::types::PluginError;
+use crate::PluginResult;
+
+/// Quiet period after the last change before reloading, so a plugin being
+/// copied in several writes is loaded once, complete
+pub const RELOAD_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Manifest file of an installed plugin
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Outcome of reloading one installed plugin
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginReload {
+    /// Install directory of the plugin
+    pub plugin_dir: String,
+    /// Instance that was stopped and unregistered, if one was loaded
+    pub previous_id: Option<String>,
+    /// Instance loaded from the new version, `None` when it failed or was removed
+    pub plugin_id: Option<String>,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub error: Option<String>,
+}
+
+impl PluginReload {
+    pub fn new(plugin_dir: &Path) -> Self {
+        Self {
+            plugin_dir: plugin_dir.to_string_lossy().to_string(),
+            previous_id: None,
+            plugin_id: None,
+            name: None,
+            version: None,
+            error: None,
+        }
+    }
+}
+
+/// Whether `path` is a plugin library the host can load
+pub fn is_plugin_library(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("wasm" | "dll" | "so" | "dylib")
+    )
+}
+
+/// Plugin library declared by the manifest of an install directory. Builtin
+/// plugins have a manifest without entry.
+pub fn manifest_entry(plugin_dir: &Path) -> Option<PathBuf> {
+    let content = std::fs::read(plugin_dir.join(MANIFEST_FILE)).ok()?;
+    let manifest: serde_json::Value = serde_json::from_slice(&content).ok()?;
+    let entry = plugin_dir.join(manifest.get("entry")?.as_str()?);
+    is_plugin_library(&entry).then_some(entry)
+}
+
+/// Install directories under the plugin root that declare a plugin library
+pub fn installed_plugin_dirs(plugin_root: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(plugin_root) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir() && !is_hidden(path) && manifest_entry(path).is_some())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Hidden directories under the plugin root (e.g. the installer's staging
+/// area) never hold installed plugins
+fn is_hidden(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+/// Install directory affected by a change to `path`, if the change can
+/// concern a plugin (its manifest or a library; icons and other assets don't)
+fn affected_plugin_dir(plugin_root: &Path, path: &Path) -> Option<PathBuf> {
+    let relevant = path.file_name().is_some_and(|name| name == MANIFEST_FILE) || is_plugin_library(path);
+    if !relevant {
+        return None;
+    }
+    let first = path.strip_prefix(plugin_root).ok()?.components().next()?;
+    let dir = plugin_root.join(first);
+    (dir != path && !is_hidden(&dir)).then_some(dir)
+}
+
+/// Running hot reload: the directory watcher and the task reloading plugins.
+/// Dropping it stops both.
+pub struct HotReloadWatcher {
+    _watcher: RecommendedWatcher,
+    task: JoinHandle<()>,
+}
+
+impl std::fmt::Debug for HotReloadWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HotReloadWatcher").finish_non_exhaustive()
+    }
+}
+
+impl Drop for HotReloadWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Watch `plugin_root` and reload the plugins whose files change, reporting
+/// each reload to `on_reload`. The manager is held weakly so the watcher it
+/// owns doesn't keep it alive.
+pub fn spawn_hot_reload<F>(manager: Weak<PluginManager>, plugin_root: PathBuf, on_reload: F) -> PluginResult<HotReloadWatcher>
+where
+    F: Fn(PluginReload) + Send + Sync + 'static,
+{
+    let (tx, mut rx) = mpsc::unbounded_channel::<PathBuf>();
+    let mut watcher = recommended_watcher(move |res: notify::Result<Event>| match res {
+        Ok(event) => {
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+                for path in event.paths {
+                    let _ = tx.send(path);
+                }
+            }
+        }
+        Err(e) => tracing::warn!("Plugin directory watch error: {}", e),
+    })
+    .map_err(|e| PluginError::ExecutionFailed { reason: format!("Failed to create plugin watcher: {}", e) })?;
+    watcher
+        .watch(&plugin_root, RecursiveMode::Recursive)
+        .map_err(|e| PluginError::ExecutionFailed { reason: format!("Failed to watch {}: {}", plugin_root.display(), e) })?;
+
+    let task = tokio::spawn(async move {
+        while let Some(first) = rx.recv().await {
+            let mut changed = BTreeSet::from([first]);
+            while let Ok(Some(path)) = tokio::time::timeout(RELOAD_DEBOUNCE, rx.recv()).await {
+                changed.insert(path);
+            }
+
+            let dirs: BTreeSet<PathBuf> = changed
+                .iter()
+                .filter_map(|path| affected_plugin_dir(&plugin_root, path))
+                .collect();
+            let Some(manager) = manager.upgrade() else {
+                break;
+            };
+            for dir in dirs {
+                if let Some(reload) = manager.reload_installed_plugin(&dir).await {
+                    on_reload(reload);
+                }
+            }
+        }
+    });
+
+    tracing::info!("Watching {} for plugin changes", plugin_root.display());
+    Ok(HotReloadWatcher { _watcher: watcher, task })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_manifests_and_libraries_trigger_a_reload() {
+        let root = Path::new("/data/plugins");
+        let dir = root.join("abc");
+        assert_eq!(affected_plugin_dir(root, &dir.join("manifest.json")), Some(dir.clone()));
+        assert_eq!(affected_plugin_dir(root, &dir.join("lib/plugin.so")), Some(dir.clone()));
+        assert_eq!(affected_plugin_dir(root, &dir.join("assets/icons/icon.png")), None);
+        assert_eq!(affected_plugin_dir(root, Path::new("/elsewhere/abc/plugin.so")), None);
+        assert_eq!(affected_plugin_dir(root, &root.join("loose.so")), None);
+        assert_eq!(affected_plugin_dir(root, &root.join(".staging/abc/manifest.json")), None);
+    }
+}
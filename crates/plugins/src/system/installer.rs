@@ -0,0 +1,219 @@
+//! Plugin package installer
+//!
+//! A plugin package is a zip archive with a `manifest.json` at its root. The
+//! manifest names the plugin library (`entry`) and its SHA-256 (`sha256`);
+//! the package is unpacked into a staging directory, the library hash is
+//! verified, and only then is the plugin moved to `<plugin_root>/<id>/`.
+
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::system::hot_reload::{is_plugin_library, MANIFEST_FILE};
+use crate::system::types::PluginError;
+use crate::PluginResult;
+
+/// Largest package accepted, downloaded or unpacked
+pub const MAX_PACKAGE_SIZE: u64 = 100 * 1024 * 1024;
+
+/// Directory under the plugin root where packages are unpacked before they
+/// are verified. Hidden directories are ignored by the plugin loader.
+pub const STAGING_DIR: &str = ".staging";
+
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Manifest of a plugin package
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackageManifest {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    pub version: String,
+    /// Plugin library, relative to the package root
+    pub entry: String,
+    /// SHA-256 of the plugin library, hex encoded
+    pub sha256: String,
+    #[serde(default)]
+    pub icon: Option<String>,
+}
+
+/// Outcome of a plugin install
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginInstall {
+    pub plugin_id: String,
+    pub name: String,
+    pub version: String,
+    pub plugin_dir: String,
+    pub enabled: bool,
+    /// Whether an installed version was replaced
+    pub replaced: bool,
+}
+
+fn invalid(reason: impl Into<String>) -> PluginError {
+    PluginError::InvalidManifest { reason: reason.into() }
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Check `bytes` against a hex encoded SHA-256
+pub fn verify_sha256(bytes: &[u8], expected: &str, what: &str) -> PluginResult<()> {
+    let actual = sha256_hex(bytes);
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        return Err(PluginError::SecurityViolation {
+            reason: format!("SHA-256 mismatch for {}: expected {}, got {}", what, expected.trim(), actual),
+        });
+    }
+    Ok(())
+}
+
+/// Download a package over http(s), refusing packages over `MAX_PACKAGE_SIZE`
+pub async fn download_package(url: &str) -> PluginResult<Vec<u8>> {
+    let failed = |reason: String| PluginError::LoadFailed { reason };
+    let parsed = reqwest::Url::parse(url).map_err(|e| failed(format!("Invalid package URL {:?}: {}", url, e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(failed(format!("Package URL must use http or https, got {}", parsed.scheme())));
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .map_err(|e| failed(e.to_string()))?;
+    let mut response = client
+        .get(parsed)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| failed(format!("Failed to download plugin package: {}", e)))?;
+    if response.content_length().is_some_and(|len| len > MAX_PACKAGE_SIZE) {
+        return Err(failed("Plugin package is too large".to_string()));
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| failed(format!("Failed to download plugin package: {}", e)))?
+    {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() as u64 > MAX_PACKAGE_SIZE {
+            return Err(failed("Plugin package is too large".to_string()));
+        }
+    }
+    Ok(bytes)
+}
+
+/// Unpack a package into `dest` and verify the library its manifest declares.
+/// Entries escaping `dest` are rejected. On error `dest` may hold a partial
+/// package and should be removed.
+pub fn unpack_package(bytes: &[u8], dest: &Path) -> PluginResult<PackageManifest> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| invalid(format!("Not a zip archive: {}", e)))?;
+
+    let manifest: PackageManifest = {
+        let mut file = archive
+            .by_name(MANIFEST_FILE)
+            .map_err(|_| invalid(format!("Package has no {} at its root", MANIFEST_FILE)))?;
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)
+            .map_err(|e| invalid(format!("Failed to read {}: {}", MANIFEST_FILE, e)))?;
+        serde_json::from_slice(&content).map_err(|e| invalid(format!("Invalid {}: {}", MANIFEST_FILE, e)))?
+    };
+    let entry = safe_relative_path(&manifest.entry).ok_or_else(|| invalid(format!("Invalid entry {:?}", manifest.entry)))?;
+    if !is_plugin_library(&entry) {
+        return Err(invalid(format!("Entry {:?} is not a plugin library", manifest.entry)));
+    }
+
+    let mut unpacked: u64 = 0;
+    for index in 0..archive.len() {
+        let mut file = archive
+            .by_index(index)
+            .map_err(|e| invalid(format!("Corrupt package: {}", e)))?;
+        let Some(relative) = file.enclosed_name().map(Path::to_path_buf) else {
+            return Err(PluginError::SecurityViolation {
+                reason: format!("Package entry {:?} escapes the plugin directory", file.name()),
+            });
+        };
+        let target = dest.join(&relative);
+        let io_error = |e: std::io::Error| PluginError::ExecutionFailed {
+            reason: format!("Failed to unpack {}: {}", relative.display(), e),
+        };
+        if file.is_dir() {
+            std::fs::create_dir_all(&target).map_err(io_error)?;
+            continue;
+        }
+        unpacked += file.size();
+        if unpacked > MAX_PACKAGE_SIZE {
+            return Err(invalid("Unpacked plugin package is too large"));
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        let mut content = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut content).map_err(io_error)?;
+        std::fs::write(&target, content).map_err(io_error)?;
+    }
+
+    let library = std::fs::read(dest.join(&entry))
+        .map_err(|_| invalid(format!("Entry {:?} is missing from the package", manifest.entry)))?;
+    verify_sha256(&library, &manifest.sha256, &manifest.entry)?;
+    Ok(manifest)
+}
+
+/// `path` if it is a relative path staying inside its base directory
+fn safe_relative_path(path: &str) -> Option<PathBuf> {
+    let path = Path::new(path);
+    let safe = !path.as_os_str().is_empty()
+        && path
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_) | std::path::Component::CurDir));
+    safe.then(|| path.to_path_buf())
+}
+
+/// Fresh staging directory for one install
+pub fn staging_dir(plugin_root: &Path) -> PathBuf {
+    plugin_root.join(STAGING_DIR).join(Uuid::new_v4().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn package(entry: &str, library: &[u8], sha256: &str) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::FileOptions::default();
+        let manifest = serde_json::json!({
+            "id": "7b0d1b4e-5d8e-4c55-9a53-0f6e5a3f9d10",
+            "name": "demo",
+            "version": "1.0.0",
+            "entry": entry,
+            "sha256": sha256,
+        });
+        zip.start_file(MANIFEST_FILE, options).unwrap();
+        zip.write_all(manifest.to_string().as_bytes()).unwrap();
+        zip.start_file("lib/demo.so", options).unwrap();
+        zip.write_all(library).unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn unpacks_verified_packages_only() {
+        let library = b"\x7fELF demo";
+        let dest = std::env::temp_dir().join(format!("plugin-install-{}", Uuid::new_v4()));
+
+        let manifest = unpack_package(&package("lib/demo.so", library, &sha256_hex(library)), &dest).unwrap();
+        assert_eq!(manifest.name, "demo");
+        assert_eq!(std::fs::read(dest.join("lib/demo.so")).unwrap(), library);
+
+        let tampered = package("lib/demo.so", library, &sha256_hex(b"other"));
+        assert!(matches!(unpack_package(&tampered, &dest), Err(PluginError::SecurityViolation { .. })));
+        assert!(unpack_package(&package("../demo.so", library, &sha256_hex(library)), &dest).is_err());
+        let _ = std::fs::remove_dir_all(&dest);
+    }
+}
@@ -1,6 +1,7 @@
 //! Plugin manager for coordinating plugin operations
 
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -13,15 +14,23 @@ use crate::system::host::PluginHost;
 use crate::system::security::{SecurityManager, FsRestrictions, NetworkRestrictions};
 use crate::system::lifecycle::LifecycleManager;
 use crate::system::state::PluginStateManager;
-use crate::system::state::metadata_to_state;
+use crate::system::state::{metadata_to_state, PluginState, PluginStateChange, DEFAULT_PLUGIN_ENABLED};
+use crate::system::state_sync::{self, PluginStateReport};
+use crate::system::hot_reload::{self, HotReloadWatcher, PluginReload};
+use crate::system::installer::{self, PackageManifest, PluginInstall};
+use crate::system::endpoints::{self, EndpointMonitor, EndpointOverride, EndpointStatus};
+use crate::system::config;
 use crate::system::sandbox::{SandboxManager, ProcessIsolation, ResourceLimits};
 use crate::system::secure_host::SecurePluginHost;
+use crate::system::fetch_proxy::{HostFetchProxy, NetworkAuditEntry, PluginCredential};
 use crate::factory::MediaPluginFactory;
+use crate::external::ExternalMediaPluginWrapper;
+use crate::internal::{SpotifyPlugin, YoutubePlugin};
 use crate::PluginResult;
 use include_dir::{include_dir, Dir};
 use music_plugin_sdk::traits::media::MediaPlugin;
 use music_plugin_sdk::traits::BasePlugin;
-use async_trait::async_trait;
+use music_plugin_sdk::types::base::{PluginConfig, PluginMetadata as MediaPluginMetadata};
 // use async_trait::async_trait; // 未使用，移除
 
 
@@ -49,6 +58,20 @@ pub struct PluginManager {
     audio_factory: Arc<Mutex<MediaPluginFactory>>,
     /// Root directory for plugin installation
     plugin_root: PathBuf,
+    /// Background task keeping the factory in sync with plugin_states
+    state_sync: Mutex<Option<tokio::task::JoinHandle<()>>>,
+    /// External media plugins registered to the factory, by source file
+    external_plugins: Mutex<HashMap<PathBuf, Uuid>>,
+    /// Watcher reloading installed plugins when their files change
+    hot_reload: Mutex<Option<HotReloadWatcher>>,
+    /// Provider endpoint overrides and mirror health
+    endpoints: Arc<EndpointMonitor>,
+    /// Background task health checking the mirrors
+    endpoint_checks: Mutex<Option<tokio::task::JoinHandle<()>>>,
+    /// HTTP requests of sandboxed plugins, with their credentials and audit
+    fetch_proxy: Arc<HostFetchProxy>,
+    /// Whether the built-in Spotify plugin is loaded (prefs.spotify.enable)
+    spotify_enabled: AtomicBool,
 }
 
 // Manual Debug implementation to avoid issues with trait objects
@@ -118,9 +141,11 @@ impl PluginManager {
         
         // Create hosts
         let host: Arc<dyn crate::system::core::PluginHost> = Arc::new(PluginHost::new());
+        let fetch_proxy = Arc::new(HostFetchProxy::new(Arc::clone(&security)));
         let secure_host: Arc<dyn crate::system::core::PluginHost> = Arc::new(SecurePluginHost::new(
             Arc::clone(&security),
-            Arc::new(Mutex::new(HashMap::new()))
+            Arc::new(Mutex::new(HashMap::new())),
+            Arc::clone(&fetch_proxy),
         ));
         
         // Create lifecycle manager
@@ -137,6 +162,8 @@ impl PluginManager {
         // Create audio plugin factory
         let audio_factory = Arc::new(Mutex::new(MediaPluginFactory::new()));
         
+        let endpoints = Arc::new(EndpointMonitor::new(Arc::clone(&audio_factory)));
+
         // Ensure plugin root exists
         std::fs::create_dir_all(&plugin_root).ok();
 
@@ -151,6 +178,13 @@ impl PluginManager {
             state_manager,
             audio_factory,
             plugin_root,
+            state_sync: Mutex::new(None),
+            external_plugins: Mutex::new(HashMap::new()),
+            hot_reload: Mutex::new(None),
+            endpoints,
+            endpoint_checks: Mutex::new(None),
+            fetch_proxy,
+            spotify_enabled: AtomicBool::new(false),
         }
     }
     
@@ -194,19 +228,33 @@ impl PluginManager {
         }
         
         // Initialize all loaded plugins
-        let context = PluginContext {
-            host: Arc::clone(&self.host),
-            registry: Arc::clone(&self.registry) as Arc<dyn crate::system::core::PluginRegistry>,
-            settings: serde_json::Value::Object(serde_json::Map::new()),
-        };
-        
-        self.lifecycle.initialize_all_plugins(context).await?;
+        self.lifecycle.initialize_all_plugins(self.plugin_context()).await?;
         
         // Initialize audio plugin factory - no need to iterate!
         // Media plugins are already registered to factory during loading        
+
+        // Keep factory flags in sync with plugin_states from now on
+        let handle = state_sync::spawn_state_sync(Arc::clone(&self.state_manager), Arc::clone(&self.audio_factory));
+        if let Some(previous) = self.state_sync.lock().unwrap().replace(handle) {
+            previous.abort();
+        }
+
+        let handle = endpoints::spawn_endpoint_checks(Arc::clone(&self.endpoints));
+        if let Some(previous) = self.endpoint_checks.lock().unwrap().replace(handle) {
+            previous.abort();
+        }
         Ok(())
     }
 
+    /// Context plugins are initialized with
+    fn plugin_context(&self) -> PluginContext {
+        PluginContext {
+            host: Arc::clone(&self.host),
+            registry: Arc::clone(&self.registry) as Arc<dyn crate::system::core::PluginRegistry>,
+            settings: serde_json::Value::Object(serde_json::Map::new()),
+        }
+    }
+
     /// Ensure minimal install layout <app_data_dir>/plugins/<plugin-id>/assets/icons/icon.png
     fn ensure_install_layout(&self, metadata: &PluginMetadata) -> PluginResult<()> {
         let install_dir = self.plugin_root.join(metadata.id.to_string());
@@ -313,6 +361,7 @@ impl PluginManager {
                 Arc::new(tokio::sync::Mutex::new(plugin));
             audio_factory.register_to_media_factory(plugin_id, media_plugin, enabled);
         }
+        self.apply_stored_config(plugin_id).await;
         
         Ok(())
     }
@@ -320,10 +369,10 @@ impl PluginManager {
     pub async fn load_all_plugins(&self) -> PluginResult<()> {
         // Load built-in media plugins - directly register to media factory
         self.load_builtin_media_plugin(crate::internal::BilibiliPlugin::new()).await?;
-        
-        // TODO: Uncomment other built-in media plugins
-        // self.load_builtin_media_plugin(crate::internal::YouTubePlugin::new()).await?;
-        // self.load_builtin_media_plugin(crate::internal::SpotifyPlugin::new()).await?;
+        if self.spotify_enabled.load(Ordering::SeqCst) {
+            self.load_builtin_media_plugin(SpotifyPlugin::new()).await?;
+        }
+        self.load_builtin_media_plugin(YoutubePlugin::new()).await?;
         
         // Load external media plugins
         self.load_external_media_plugins().await?;
@@ -351,9 +400,212 @@ impl PluginManager {
                 eprintln!("Warning: Failed to load dynamic library media plugins: {}", e);
             }
         }
+
+        // Plugins installed under the plugin root
+        for dir in hot_reload::installed_plugin_dirs(&self.plugin_root) {
+            if let Some(entry) = hot_reload::manifest_entry(&dir) {
+                if let Err(e) = self.load_external_media_plugin_file(&entry).await {
+                    eprintln!("Warning: Skipping installed plugin {}: {}", entry.display(), e);
+                }
+            }
+        }
         
         Ok(())
     }
+
+    /// Load one external media plugin file and register it to the media factory
+    async fn load_external_media_plugin_file(&self, path: &Path) -> PluginResult<MediaPluginMetadata> {
+        let plugin = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("wasm") => self.load_wasm_media_plugin(path).await?,
+            _ => self.load_dynamic_media_plugin(path).await?,
+        };
+        let metadata = self.register_external_media_plugin(plugin).await?;
+        self.external_plugins.lock().unwrap().insert(path.to_path_buf(), metadata.id);
+        Ok(metadata)
+    }
+
+    /// Take an external media plugin out of the factory and stop it. The
+    /// instance is dropped (unloading its library) once calls in progress end.
+    async fn unload_external_media_plugin(&self, path: &Path, plugin_id: Uuid) {
+        self.external_plugins.lock().unwrap().remove(path);
+        let plugin = self.audio_factory.lock().unwrap().unregister_media_plugin(plugin_id);
+        if let Some(plugin) = plugin {
+            if let Err(e) = plugin.lock().await.stop().await {
+                tracing::warn!("Failed to stop plugin {} before reload: {}", plugin_id, e);
+            }
+        }
+        println!("External media plugin unloaded: {} ({})", path.display(), plugin_id);
+    }
+
+    /// Reload the plugin installed in `plugin_dir`: the instance loaded from it
+    /// is stopped and unregistered, then the version its manifest declares is
+    /// loaded. `None` when the directory neither had nor has a plugin library.
+    pub async fn reload_installed_plugin(&self, plugin_dir: &Path) -> Option<PluginReload> {
+        let previous = self.plugins_loaded_from(plugin_dir);
+        let entry = hot_reload::manifest_entry(plugin_dir).filter(|entry| entry.is_file());
+        if previous.is_empty() && entry.is_none() {
+            return None;
+        }
+
+        let mut reload = PluginReload::new(plugin_dir);
+        // The old library must be released first: loading the same path again
+        // while it is open would hand back the old code
+        for (path, plugin_id) in previous {
+            self.unload_external_media_plugin(&path, plugin_id).await;
+            reload.previous_id = Some(plugin_id.to_string());
+        }
+
+        let Some(entry) = entry else {
+            return Some(reload);
+        };
+        match self.load_external_media_plugin_file(&entry).await {
+            Ok(metadata) => {
+                // Keep the stored version current for the plugin list
+                if let Ok(Some(mut st)) = self.state_manager.get_plugin_state(&metadata.id.to_string()) {
+                    st.version = metadata.version.clone();
+                    st.last_updated = chrono::Utc::now().naive_utc();
+                    let _ = self.state_manager.save_plugin_state(&st);
+                }
+                self.endpoints.reapply(metadata.id).await;
+                reload.plugin_id = Some(metadata.id.to_string());
+                reload.name = Some(metadata.name);
+                reload.version = Some(metadata.version);
+            }
+            Err(e) => {
+                eprintln!("Warning: Failed to reload plugin {}: {}", entry.display(), e);
+                reload.error = Some(e.to_string());
+            }
+        }
+        Some(reload)
+    }
+
+    /// External plugins loaded from files under `dir`
+    fn plugins_loaded_from(&self, dir: &Path) -> Vec<(PathBuf, Uuid)> {
+        self.external_plugins
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(path, _)| path.starts_with(dir))
+            .map(|(path, id)| (path.clone(), *id))
+            .collect()
+    }
+
+    /// Install a plugin package (see `installer`): verify it against
+    /// `expected_sha256` when given and against its manifest, replace any
+    /// installed version, load it and record its state row with `enable`.
+    pub async fn install_plugin_package(
+        &self,
+        package: &[u8],
+        expected_sha256: Option<&str>,
+        enable: bool,
+    ) -> PluginResult<PluginInstall> {
+        if let Some(expected) = expected_sha256 {
+            installer::verify_sha256(package, expected, "plugin package")?;
+        }
+
+        let staging = installer::staging_dir(&self.plugin_root);
+        let manifest = match installer::unpack_package(package, &staging) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&staging);
+                return Err(e);
+            }
+        };
+
+        // Release the installed version before its files are replaced
+        let plugin_dir = self.plugin_root.join(manifest.id.to_string());
+        let previous = self.plugins_loaded_from(&plugin_dir);
+        for (path, plugin_id) in &previous {
+            self.unload_external_media_plugin(path, *plugin_id).await;
+        }
+        let replaced = plugin_dir.exists();
+        let mut moved = Ok(());
+        if replaced {
+            moved = std::fs::remove_dir_all(&plugin_dir);
+        }
+        if let Err(e) = moved.and_then(|_| std::fs::rename(&staging, &plugin_dir)) {
+            let _ = std::fs::remove_dir_all(&staging);
+            return Err(PluginError::ExecutionFailed { reason: format!("Failed to install plugin files: {}", e) });
+        }
+
+        // Recorded before loading so the plugin registers with the requested flag
+        self.save_installed_state(&manifest, &plugin_dir, enable)?;
+        let metadata = self.load_external_media_plugin_file(&plugin_dir.join(&manifest.entry)).await?;
+        if metadata.id != manifest.id {
+            self.unload_external_media_plugin(&plugin_dir.join(&manifest.entry), metadata.id).await;
+            let _ = std::fs::remove_dir_all(&plugin_dir);
+            let _ = self.state_manager.delete_plugin_state(&manifest.id.to_string());
+            return Err(PluginError::InvalidManifest {
+                reason: format!("Manifest declares plugin {} but the library is plugin {}", manifest.id, metadata.id),
+            });
+        }
+        self.endpoints.reapply(metadata.id).await;
+
+        println!("Plugin installed: {} {} ({})", manifest.name, manifest.version, manifest.id);
+        Ok(PluginInstall {
+            plugin_id: manifest.id.to_string(),
+            name: manifest.name,
+            version: manifest.version,
+            plugin_dir: plugin_dir.to_string_lossy().to_string(),
+            enabled: enable,
+            replaced,
+        })
+    }
+
+    /// Create or update the state row of an installed package
+    fn save_installed_state(&self, manifest: &PackageManifest, plugin_dir: &Path, enabled: bool) -> PluginResult<()> {
+        let now = chrono::Utc::now().naive_utc();
+        let icon = manifest
+            .icon
+            .as_deref()
+            .map(|icon| plugin_dir.join(icon))
+            .filter(|icon| icon.starts_with(plugin_dir) && icon.is_file())
+            .map(|icon| icon.to_string_lossy().to_string());
+        let display_name = manifest.display_name.clone().unwrap_or_else(|| manifest.name.clone());
+        let manifest_json = serde_json::to_string(manifest)?;
+
+        let state = match self.state_manager.get_plugin_state(&manifest.id.to_string())? {
+            Some(mut st) => {
+                st.name = manifest.name.clone();
+                st.display_name = display_name;
+                st.version = manifest.version.clone();
+                st.enabled = enabled;
+                st.installed = true;
+                st.icon = icon.or(st.icon);
+                st.manifest = Some(manifest_json);
+                st.last_updated = now;
+                st
+            }
+            None => PluginState {
+                id: manifest.id.to_string(),
+                name: manifest.name.clone(),
+                display_name,
+                version: manifest.version.clone(),
+                plugin_type: PluginType::AudioProvider.to_string(),
+                enabled,
+                installed: true,
+                builtin: false,
+                config: "{}".to_string(),
+                icon,
+                manifest: Some(manifest_json),
+                installed_at: now,
+                last_updated: now,
+                last_used: None,
+            },
+        };
+        self.state_manager.save_plugin_state(&state)
+    }
+
+    /// Watch the plugin root and reload installed plugins when their manifest
+    /// or library changes. `on_reload` is called after each reload.
+    pub fn start_hot_reload<F>(self: &Arc<Self>, on_reload: F) -> PluginResult<()>
+    where
+        F: Fn(PluginReload) + Send + Sync + 'static,
+    {
+        let watcher = hot_reload::spawn_hot_reload(Arc::downgrade(self), self.plugin_root.clone(), on_reload)?;
+        *self.hot_reload.lock().unwrap() = Some(watcher);
+        Ok(())
+    }
     
     /// Load external media plugins from directory
     async fn load_external_media_plugins_from_directory(&self, dir_path: &std::path::Path) -> PluginResult<()> {
@@ -369,13 +621,11 @@ impl PluginManager {
             if let Some(extension) = path.extension().and_then(|ext| ext.to_str()) {
                 match extension {
                     "wasm" => {
-                        if let Ok(plugin) = self.load_wasm_media_plugin(&path).await {
-                            self.register_external_media_plugin(plugin).await?;
-                        }
+                        let _ = self.load_external_media_plugin_file(&path).await;
                     },
                     "dll" | "so" | "dylib" => {
-                        if let Ok(plugin) = self.load_dynamic_media_plugin(&path).await {
-                            self.register_external_media_plugin(plugin).await?;
+                        if let Err(e) = self.load_external_media_plugin_file(&path).await {
+                            eprintln!("Warning: Skipping dynamic plugin {}: {}", path.display(), e);
                         }
                     },
                     _ => continue,
@@ -387,7 +637,7 @@ impl PluginManager {
     }
     
     /// Register external media plugin to factory
-    async fn register_external_media_plugin(&self, plugin: Box<dyn MediaPlugin + Send + Sync>) -> PluginResult<()> {
+    async fn register_external_media_plugin(&self, plugin: Box<dyn MediaPlugin + Send + Sync>) -> PluginResult<MediaPluginMetadata> {
         // Get basic information through MediaPlugin
         let plugin_metadata = plugin.metadata();
         let plugin_id = plugin_metadata.id;
@@ -395,73 +645,7 @@ impl PluginManager {
         // Get plugin status
         let enabled = self.get_plugin_enabled(plugin_id).unwrap_or(true);
         
-        // Create an external plugin wrapper
-        #[derive(Debug)]
-        struct ExternalMediaPluginWrapper {
-            inner: Box<dyn MediaPlugin + Send + Sync>,
-        }
-        
-        #[async_trait]
-        impl MediaPlugin for ExternalMediaPluginWrapper {
-            async fn search(&self, query: &music_plugin_sdk::types::SearchQuery) -> music_plugin_sdk::types::base::PluginResult<music_plugin_sdk::types::SearchResult> {
-                self.inner.search(query).await
-            }
-            
-            async fn get_track(&self, track_id: &str) -> music_plugin_sdk::types::base::PluginResult<music_plugin_sdk::types::Track> {
-                self.inner.get_track(track_id).await
-            }
-            
-            async fn get_media_stream(&self, track_id: &str, req: &music_plugin_sdk::types::StreamRequest) -> music_plugin_sdk::types::base::PluginResult<music_plugin_sdk::types::StreamSource> {
-                self.inner.get_media_stream(track_id, req).await
-            }
-            
-            async fn get_album(&self, album_id: &str) -> music_plugin_sdk::types::base::PluginResult<music_plugin_sdk::types::Album> {
-                self.inner.get_album(album_id).await
-            }
-            
-            async fn get_artist(&self, artist_id: &str) -> music_plugin_sdk::types::base::PluginResult<music_plugin_sdk::types::Artist> {
-                self.inner.get_artist(artist_id).await
-            }
-            
-            async fn get_playlist(&self, playlist_id: &str) -> music_plugin_sdk::types::base::PluginResult<music_plugin_sdk::types::Playlist> {
-                self.inner.get_playlist(playlist_id).await
-            }
-            
-            async fn is_track_available(&self, track_id: &str) -> music_plugin_sdk::types::base::PluginResult<bool> {
-                self.inner.is_track_available(track_id).await
-            }
-        }
-        
-        #[async_trait]
-        impl BasePlugin for ExternalMediaPluginWrapper {
-            fn metadata(&self) -> music_plugin_sdk::types::base::PluginMetadata {
-                self.inner.metadata()
-            }
-            
-            async fn initialize(&mut self, _context: &music_plugin_sdk::types::base::PluginContext) -> music_plugin_sdk::types::base::PluginResult<()> {
-                // We can't call initialize on the inner plugin since it's behind a Box
-                // External plugins should handle initialization themselves
-                Ok(())
-            }
-            
-            async fn start(&mut self) -> music_plugin_sdk::types::base::PluginResult<()> {
-                Ok(())
-            }
-            
-            async fn stop(&mut self) -> music_plugin_sdk::types::base::PluginResult<()> {
-                Ok(())
-            }
-            
-            fn status(&self) -> music_plugin_sdk::types::base::PluginStatus {
-                music_plugin_sdk::types::base::PluginStatus::Running
-            }
-            
-            async fn configure(&mut self, _config: music_plugin_sdk::types::base::PluginConfig) -> music_plugin_sdk::types::base::PluginResult<()> {
-                Ok(())
-            }
-        }
-        
-        let wrapper = ExternalMediaPluginWrapper { inner: plugin };
+        let wrapper = ExternalMediaPluginWrapper::new(plugin);
         let arc_plugin = Arc::new(tokio::sync::Mutex::new(wrapper));
         
         // Directly register to media factory
@@ -473,9 +657,10 @@ impl PluginManager {
                 enabled
             );
         }
+        self.apply_stored_config(plugin_id).await;
         
         println!("External media plugin loaded: {} ({})", plugin_metadata.name, plugin_id);
-        Ok(())
+        Ok(plugin_metadata)
     }
     
     /// Load WASM media plugin
@@ -485,9 +670,10 @@ impl PluginManager {
     }
     
     /// Load dynamic library media plugin
-    async fn load_dynamic_media_plugin(&self, _path: &std::path::Path) -> PluginResult<Box<dyn MediaPlugin + Send + Sync>> {
-        // TODO: Implement dynamic library plugin loading logic
-        Err(PluginError::ExecutionFailed { reason: "Dynamic library media plugin loading not implemented yet".to_string() })
+    async fn load_dynamic_media_plugin(&self, path: &std::path::Path) -> PluginResult<Box<dyn MediaPlugin + Send + Sync>> {
+        let dir = path.parent().unwrap_or(Path::new(".")).to_string_lossy().to_string();
+        let plugin = crate::external::DynamicPluginLoader::new(dir).load_media_plugin(path)?;
+        Ok(Box::new(plugin))
     }
     
     /// Load other type plugins (theme, tool, etc. non-media plugins)
@@ -545,6 +731,11 @@ impl PluginManager {
         }
         // Update DB and start runtime
         self.state_manager.enable_plugin(&pid)?;
+        // Apply right away; the state sync notification may arrive later
+        self.audio_factory.lock().unwrap().apply_state_change(&PluginStateChange {
+            plugin_id: pid.clone(),
+            enabled: Some(true),
+        });
         let _ = self.lifecycle.start_plugin(plugin_id).await;
         Ok(())
     }
@@ -572,10 +763,54 @@ impl PluginManager {
         }
         // Update DB and stop runtime
         self.state_manager.disable_plugin(&pid)?;
+        // Apply right away; the state sync notification may arrive later
+        self.audio_factory.lock().unwrap().apply_state_change(&PluginStateChange {
+            plugin_id: pid.clone(),
+            enabled: Some(false),
+        });
         let _ = self.lifecycle.stop_plugin(plugin_id).await;
         Ok(())
     }
     
+    /// Load the built-in Spotify plugin at initialization or not. Once the
+    /// plugins are loaded, a change enables or disables it right away.
+    pub async fn set_spotify_enabled(&self, enabled: bool) -> PluginResult<()> {
+        self.spotify_enabled.store(enabled, Ordering::SeqCst);
+        let plugin_id = SpotifyPlugin::plugin_id();
+        if self.registry.get_plugin(plugin_id).await?.is_some() {
+            return if enabled {
+                self.enable_plugin(plugin_id).await
+            } else {
+                self.disable_plugin(plugin_id).await
+            };
+        }
+        // Before initialization the flag is all that is needed
+        let initialized = self.state_sync.lock().unwrap().is_some();
+        if enabled && initialized {
+            let plugin = SpotifyPlugin::new();
+            let metadata = <SpotifyPlugin as Plugin>::metadata(&plugin);
+            self.load_builtin_media_plugin(plugin).await?;
+            self.lifecycle.initialize_plugin(plugin_id, self.plugin_context()).await?;
+            self.enable_plugin(plugin_id).await?;
+            let _ = self.ensure_install_layout(&metadata);
+        }
+        Ok(())
+    }
+
+    /// The built-in Spotify plugin, if loaded. Clones share the account state.
+    pub async fn spotify_plugin(&self) -> Option<SpotifyPlugin> {
+        let plugin = self.registry.get_plugin(SpotifyPlugin::plugin_id()).await.ok().flatten()?;
+        let guard = plugin.lock().unwrap();
+        guard.as_any().downcast_ref::<SpotifyPlugin>().cloned()
+    }
+
+    /// The built-in YouTube plugin, if loaded. Clones share the API key.
+    pub async fn youtube_plugin(&self) -> Option<YoutubePlugin> {
+        let plugin = self.registry.get_plugin(YoutubePlugin::plugin_id()).await.ok().flatten()?;
+        let guard = plugin.lock().unwrap();
+        guard.as_any().downcast_ref::<YoutubePlugin>().cloned()
+    }
+
     /// Get plugin status
     pub async fn get_plugin_status(&self, plugin_id: Uuid) -> PluginResult<PluginStatus> {
         self.lifecycle.get_plugin_status(plugin_id).await
@@ -587,10 +822,126 @@ impl PluginManager {
             .state_manager
             .get_plugin_state(&plugin_id.to_string())?
             .map(|st| st.enabled)
-            .unwrap_or(true);
+            .unwrap_or(DEFAULT_PLUGIN_ENABLED);
         Ok(enabled)
     }
 
+    /// Compare enabled flags in plugin_states with the media factory.
+    /// With `repair`, factory flags are overwritten with the database values.
+    pub fn plugin_state_report(&self, repair: bool) -> PluginResult<PluginStateReport> {
+        state_sync::audit_plugin_states(&self.state_manager, &self.audio_factory, repair)
+    }
+
+    /// Replace the provider endpoint overrides, keyed by plugin id
+    pub async fn set_endpoint_overrides(&self, overrides: HashMap<Uuid, EndpointOverride>) {
+        self.endpoints.set_overrides(overrides).await
+    }
+
+    /// Mirror health and endpoint in use of every provider with an override
+    pub fn endpoint_statuses(&self) -> Vec<EndpointStatus> {
+        self.endpoints.statuses()
+    }
+
+    /// Replace the credentials the fetch proxy injects into the requests of
+    /// a plugin
+    pub fn set_plugin_credentials(&self, plugin_id: Uuid, credentials: HashMap<String, PluginCredential>) {
+        self.fetch_proxy.set_credentials(plugin_id, credentials)
+    }
+
+    /// Names of the credentials stored for a plugin
+    pub fn plugin_credential_names(&self, plugin_id: Uuid) -> Vec<String> {
+        self.fetch_proxy.credential_names(plugin_id)
+    }
+
+    /// Requests a plugin made through the fetch proxy, newest first
+    pub fn plugin_network_audit(&self, plugin_id: Uuid) -> Vec<NetworkAuditEntry> {
+        self.fetch_proxy.audit(plugin_id)
+    }
+
+    pub fn clear_plugin_network_audit(&self, plugin_id: Uuid) {
+        self.fetch_proxy.clear_audit(plugin_id)
+    }
+
+    /// Media plugin registered under `plugin_id`, enabled or not
+    fn registered_media_plugin(
+        &self,
+        plugin_id: Uuid,
+    ) -> PluginResult<Arc<tokio::sync::Mutex<dyn MediaPlugin + Send + Sync>>> {
+        self.audio_factory
+            .lock()
+            .unwrap()
+            .get_registered_media_plugin(plugin_id)
+            .ok_or(PluginError::NotFound { id: plugin_id })
+    }
+
+    /// JSON schema of a plugin's settings, `None` when it declares none
+    pub async fn plugin_config_schema(&self, plugin_id: Uuid) -> PluginResult<Option<serde_json::Value>> {
+        let plugin = self.registered_media_plugin(plugin_id)?;
+        let schema = plugin.lock().await.config_schema();
+        Ok(schema)
+    }
+
+    /// Configuration in effect for a plugin: its schema defaults overlaid with
+    /// the values saved in plugin_states
+    pub async fn plugin_config(&self, plugin_id: Uuid) -> PluginResult<HashMap<String, serde_json::Value>> {
+        let schema = self.plugin_config_schema(plugin_id).await?;
+        let stored = self
+            .state_manager
+            .get_plugin_state(&plugin_id.to_string())?
+            .map(|st| config::parse_stored(&st.config))
+            .unwrap_or_default();
+        Ok(config::effective(schema.as_ref(), &stored))
+    }
+
+    /// Validate `values` against the plugin's schema, pass them to the plugin
+    /// and save them once it accepted them. `null` clears a setting. Returns
+    /// the configuration now in effect.
+    pub async fn set_plugin_config(
+        &self,
+        plugin_id: Uuid,
+        values: HashMap<String, serde_json::Value>,
+    ) -> PluginResult<HashMap<String, serde_json::Value>> {
+        let mut state = self
+            .state_manager
+            .get_plugin_state(&plugin_id.to_string())?
+            .ok_or(PluginError::NotFound { id: plugin_id })?;
+        let plugin = self.registered_media_plugin(plugin_id)?;
+        let mut plugin = plugin.lock().await;
+        let schema = plugin.config_schema();
+        let stored = config::prepare(schema.as_ref(), values)?;
+        let effective = config::effective(schema.as_ref(), &stored);
+
+        plugin
+            .configure(PluginConfig::with_values(effective.clone()))
+            .await
+            .map_err(|e| PluginError::InvalidConfig { reason: e.to_string() })?;
+        drop(plugin);
+
+        state.config = serde_json::to_string(&stored)?;
+        state.last_updated = chrono::Utc::now().naive_utc();
+        self.state_manager.save_plugin_state(&state)?;
+        Ok(effective)
+    }
+
+    /// Pass the saved configuration to a freshly loaded plugin
+    async fn apply_stored_config(&self, plugin_id: Uuid) {
+        let Ok(Some(state)) = self.state_manager.get_plugin_state(&plugin_id.to_string()) else {
+            return;
+        };
+        let stored = config::parse_stored(&state.config);
+        if stored.is_empty() {
+            return;
+        }
+        let Ok(plugin) = self.registered_media_plugin(plugin_id) else {
+            return;
+        };
+        let mut plugin = plugin.lock().await;
+        let effective = config::effective(plugin.config_schema().as_ref(), &stored);
+        if let Err(e) = plugin.configure(PluginConfig::with_values(effective)).await {
+            tracing::warn!("Failed to apply the saved configuration of plugin {}: {}", plugin_id, e);
+        }
+    }
+
     /// Get plugin icon path from the database, if any
     pub fn get_plugin_icon(&self, plugin_id: Uuid) -> PluginResult<Option<String>> {
         let icon = self
@@ -681,7 +1032,21 @@ impl PluginManager {
     pub async fn stop_plugin(&self, plugin_id: Uuid) -> PluginResult<()> {
         self.lifecycle.stop_plugin(plugin_id).await
     }
-    
+
+    /// Stop and start a plugin again, also after one of its calls panicked
+    pub async fn restart_plugin(&self, plugin_id: Uuid) -> PluginResult<()> {
+        let plugin_mutex = self.registry.get_plugin(plugin_id).await?
+            .ok_or(PluginError::NotFound { id: plugin_id })?;
+        // The panicking call left the plugin locked as poisoned
+        plugin_mutex.clear_poison();
+
+        // A failing plugin may fail to stop as well, start it regardless
+        if let Err(e) = self.lifecycle.stop_plugin(plugin_id).await {
+            tracing::warn!("Failed to stop plugin {} before restart: {}", plugin_id, e);
+        }
+        self.lifecycle.start_plugin(plugin_id).await
+    }
+
     /// Get plugin status synchronously
     pub fn get_plugin_status_sync(&self, _plugin_id: Uuid) -> PluginResult<PluginStatus> {
         // This is a placeholder implementation
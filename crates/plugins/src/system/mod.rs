@@ -9,10 +9,16 @@ pub mod manifest;
 pub mod host;
 pub mod lifecycle;
 pub mod state;
+pub mod state_sync;
+pub mod hot_reload;
+pub mod installer;
+pub mod endpoints;
+pub mod config;
 pub mod external;
 pub mod manager;
 pub mod sandbox;
 pub mod secure_host;
+pub mod fetch_proxy;
 
 pub use core::*;
 pub use types::*;
@@ -10,6 +10,8 @@ use crate::system::types::*;
 use crate::system::registry::PluginRegistry;
 use crate::system::security::{SecurityManager, FsAccessType};
 use crate::system::sandbox::PluginSandbox;
+use crate::system::fetch_proxy::HostFetchProxy;
+use music_plugin_sdk::types::base::{HostHttpRequest, HOST_FETCH_SERVICE};
 use crate::PluginResult;
 
 /// Secure plugin host implementation
@@ -29,6 +31,9 @@ pub struct SecurePluginHost {
     
     /// Resource usage tracking
     resource_usage: Arc<Mutex<std::collections::HashMap<Uuid, ResourceUsage>>>,
+    
+    /// HTTP requests made on behalf of plugins
+    fetch_proxy: Arc<HostFetchProxy>,
 }
 
 /// Resource usage tracking
@@ -52,6 +57,7 @@ impl SecurePluginHost {
     pub fn new(
         security_manager: Arc<Mutex<SecurityManager>>,
         sandboxes: Arc<Mutex<std::collections::HashMap<Uuid, Arc<Mutex<PluginSandbox>>>>>,
+        fetch_proxy: Arc<HostFetchProxy>,
     ) -> Self {
         let info = HostInfo {
             version: env!("CARGO_PKG_VERSION").to_string(),
@@ -60,6 +66,7 @@ impl SecurePluginHost {
                 "logging".to_string(),
                 "settings".to_string(),
                 "database".to_string(),
+                HOST_FETCH_SERVICE.to_string(),
             ],
         };
         
@@ -71,6 +78,7 @@ impl SecurePluginHost {
             security_manager,
             sandboxes,
             resource_usage: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            fetch_proxy,
         }
     }
     
@@ -152,6 +160,14 @@ impl SecurePluginHost {
         resource_usage.insert(plugin_id, usage);
     }
     
+    /// Count bytes a plugin sent and received through the fetch service
+    fn add_network_usage(&self, plugin_id: Uuid, sent: u64, received: u64) {
+        let mut resource_usage = self.resource_usage.lock().unwrap();
+        let usage = resource_usage.entry(plugin_id).or_default();
+        usage.network_sent += sent;
+        usage.network_received += received;
+    }
+    
     /// Check resource limits for a plugin
     fn check_resource_limits(&self, plugin_id: Uuid) -> PluginResult<()> {
         let _security_manager = self.security_manager.lock().unwrap();
@@ -238,6 +254,15 @@ impl PluginHost for SecurePluginHost {
                     self.check_network_access(plugin_id, host, port, protocol)?;
                 }
             },
+            HOST_FETCH_SERVICE => {
+                // The proxy enforces the network permissions and audits the request
+                self.check_resource_limits(plugin_id)?;
+                let request: HostHttpRequest = serde_json::from_value(data)?;
+                let sent = request.body.as_ref().map_or(0, |b| b.len() as u64);
+                let response = self.fetch_proxy.fetch(plugin_id, request).await?;
+                self.add_network_usage(plugin_id, sent, response.body.len() as u64);
+                return Ok(serde_json::to_value(response)?);
+            },
             "database" => {
                 // Database access might have specific permissions
                 // For now, we'll allow it but in a real implementation we might want to check
@@ -217,8 +217,7 @@ impl SecurityManager {
         if let Some(permissions) = self.plugin_network_permissions.get(&plugin_id) {
             // Check if host is allowed
             if !permissions.allowed_hosts.is_empty() && 
-               !permissions.allowed_hosts.contains(host) && 
-               !permissions.allowed_hosts.contains("*") {
+               !permissions.allowed_hosts.iter().any(|pattern| host_matches(pattern, host)) {
                 return false;
             }
             
@@ -354,6 +353,11 @@ impl SecurityManager {
         self.global_network_restrictions = restrictions;
     }
     
+    /// Global network restrictions
+    pub fn global_network_restrictions(&self) -> &NetworkRestrictions {
+        &self.global_network_restrictions
+    }
+    
     /// Check global file system restrictions
     pub fn check_global_fs_restrictions(&self, path: &Path, _access_type: FsAccessType) -> PluginResult<()> {
         // Check forbidden paths
@@ -455,6 +459,16 @@ impl SecurityManager {
     }
 }
 
+/// Whether `host` matches an allow-list entry: the host itself, `*` for any
+/// host, or `*.example.com` for the subdomains of example.com
+pub(crate) fn host_matches(pattern: &str, host: &str) -> bool {
+    pattern == "*"
+        || pattern.eq_ignore_ascii_case(host)
+        || pattern
+            .strip_prefix("*.")
+            .is_some_and(|domain| host.to_ascii_lowercase().ends_with(&format!(".{}", domain.to_ascii_lowercase())))
+}
+
 /// File system access types
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FsAccessType {
@@ -11,10 +11,26 @@ use database::database::Database;
 use uuid::Uuid;
 use chrono;
 use serde::{Serialize, Deserialize};
+use tokio::sync::broadcast;
 
 // RESTRICTED IMPORT: Only use PluginState for persistence
 use types::entities::PluginState as DbPluginState;
 
+/// Enabled flag assumed for plugins that have no state row yet
+pub const DEFAULT_PLUGIN_ENABLED: bool = true;
+
+/// Buffered state changes per subscriber. A subscriber that falls further
+/// behind gets `Lagged` and should fall back to a full audit.
+const STATE_CHANGE_CAPACITY: usize = 64;
+
+/// Notification sent whenever a persisted plugin state changes
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginStateChange {
+    pub plugin_id: String,
+    /// Persisted enabled flag, `None` when the state row was removed
+    pub enabled: Option<bool>,
+}
+
 /// Plugin state structure for internal plugin system use
 /// 
 /// This is a local definition to avoid broad dependency on crates/types.
@@ -151,12 +167,27 @@ fn string_to_plugin_type(plugin_type: &str) -> PluginType {
 #[derive(Debug)]
 pub struct PluginStateManager {
     database: Database,
+    changes: broadcast::Sender<PluginStateChange>,
 }
 
 impl PluginStateManager {
     /// Create a new plugin state manager
     pub fn new(database: Database) -> Self {
-        Self { database }
+        let (changes, _) = broadcast::channel(STATE_CHANGE_CAPACITY);
+        Self { database, changes }
+    }
+
+    /// Subscribe to state change notifications
+    pub fn subscribe(&self) -> broadcast::Receiver<PluginStateChange> {
+        self.changes.subscribe()
+    }
+
+    fn notify(&self, plugin_id: &str, enabled: Option<bool>) {
+        // Sending only fails when nobody is subscribed
+        let _ = self.changes.send(PluginStateChange {
+            plugin_id: plugin_id.to_string(),
+            enabled,
+        });
     }
 
     /// Get plugin state by ID
@@ -206,32 +237,43 @@ impl PluginStateManager {
                     .map_err(|e| crate::system::types::PluginError::ExecutionFailed { reason: e.to_string() })?;
             }
         }
+        self.notify(&state.id, Some(state.enabled));
         Ok(())
     }
 
     /// Delete plugin state
     pub fn delete_plugin_state(&self, plugin_id: &str) -> PluginResult<()> {
         self.database.delete_plugin_state(plugin_id)
-            .map_err(|e| crate::system::types::PluginError::ExecutionFailed { reason: e.to_string() })
+            .map_err(|e| crate::system::types::PluginError::ExecutionFailed { reason: e.to_string() })?;
+        self.notify(plugin_id, None);
+        Ok(())
     }
 
     /// Update plugin state's primary key id from old to new
     pub fn update_plugin_state_id(&self, old_id: &str, new_id: &str) -> PluginResult<()> {
         self.database
             .update_plugin_state_id(old_id, new_id)
-            .map_err(|e| crate::system::types::PluginError::ExecutionFailed { reason: e.to_string() })
+            .map_err(|e| crate::system::types::PluginError::ExecutionFailed { reason: e.to_string() })?;
+        self.notify(old_id, None);
+        let enabled = self.get_plugin_state(new_id)?.map(|st| st.enabled);
+        self.notify(new_id, enabled);
+        Ok(())
     }
 
     /// Enable plugin
     pub fn enable_plugin(&self, plugin_id: &str) -> PluginResult<()> {
         self.database.enable_plugin(plugin_id)
-            .map_err(|e| crate::system::types::PluginError::ExecutionFailed { reason: e.to_string() })
+            .map_err(|e| crate::system::types::PluginError::ExecutionFailed { reason: e.to_string() })?;
+        self.notify(plugin_id, Some(true));
+        Ok(())
     }
 
     /// Disable plugin
     pub fn disable_plugin(&self, plugin_id: &str) -> PluginResult<()> {
         self.database.disable_plugin(plugin_id)
-            .map_err(|e| crate::system::types::PluginError::ExecutionFailed { reason: e.to_string() })
+            .map_err(|e| crate::system::types::PluginError::ExecutionFailed { reason: e.to_string() })?;
+        self.notify(plugin_id, Some(false));
+        Ok(())
     }
 
     /// Update plugin last used timestamp
@@ -0,0 +1,183 @@
+//! Plugin state integrity sync
+//!
+//! The enabled flag of a media plugin lives in two places: the `plugin_states`
+//! table (source of truth) and the in-memory flags of `MediaPluginFactory`.
+//! The factory follows state change notifications from `PluginStateManager`,
+//! and a periodic audit repairs anything the notifications missed.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::factory::MediaPluginFactory;
+use crate::system::state::{PluginStateManager, DEFAULT_PLUGIN_ENABLED};
+use crate::system::types::PluginError;
+use crate::PluginResult;
+
+/// Interval between two background audits
+pub const STATE_AUDIT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Comparison of one plugin's enabled flag between the database and the factory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginStateReportEntry {
+    pub plugin_id: String,
+    pub name: Option<String>,
+    /// Enabled flag in `plugin_states`, `None` when there is no row
+    pub db_enabled: Option<bool>,
+    /// Enabled flag in the media factory, `None` when the plugin is not registered there
+    pub factory_enabled: Option<bool>,
+    pub consistent: bool,
+    /// Whether the factory flag was overwritten with the database value
+    pub repaired: bool,
+}
+
+/// Result of a plugin state audit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginStateReport {
+    pub checked_at: chrono::NaiveDateTime,
+    pub entries: Vec<PluginStateReportEntry>,
+    pub mismatches: usize,
+    pub repaired: usize,
+}
+
+/// Compare database and factory flags. Plugins missing from the factory are
+/// listed but never count as a mismatch: only media plugins are registered there.
+fn compare_states(
+    db: &HashMap<Uuid, (String, bool)>,
+    factory: &HashMap<Uuid, bool>,
+) -> Vec<PluginStateReportEntry> {
+    let ids: BTreeSet<Uuid> = db.keys().chain(factory.keys()).copied().collect();
+    ids.into_iter()
+        .map(|id| {
+            let db_entry = db.get(&id);
+            let db_enabled = db_entry.map(|(_, enabled)| *enabled);
+            let factory_enabled = factory.get(&id).copied();
+            let consistent = match factory_enabled {
+                Some(enabled) => enabled == db_enabled.unwrap_or(DEFAULT_PLUGIN_ENABLED),
+                None => true,
+            };
+            PluginStateReportEntry {
+                plugin_id: id.to_string(),
+                name: db_entry.map(|(name, _)| name.clone()),
+                db_enabled,
+                factory_enabled,
+                consistent,
+                repaired: false,
+            }
+        })
+        .collect()
+}
+
+/// Audit the factory against the database, optionally repairing mismatches.
+pub fn audit_plugin_states(
+    state_manager: &PluginStateManager,
+    factory: &Mutex<MediaPluginFactory>,
+    repair: bool,
+) -> PluginResult<PluginStateReport> {
+    let db: HashMap<Uuid, (String, bool)> = state_manager
+        .get_all_plugin_states()?
+        .into_iter()
+        .filter_map(|st| Uuid::parse_str(&st.id).ok().map(|id| (id, (st.name, st.enabled))))
+        .collect();
+
+    let mut factory = factory.lock().map_err(|_| PluginError::ExecutionFailed {
+        reason: "Failed to access media plugin factory".to_string(),
+    })?;
+    let factory_flags: HashMap<Uuid, bool> = factory
+        .get_plugin_ids()
+        .into_iter()
+        .filter_map(|id| factory.is_plugin_enabled(id).map(|enabled| (id, enabled)))
+        .collect();
+
+    let mut entries = compare_states(&db, &factory_flags);
+    let mismatches = entries.iter().filter(|e| !e.consistent).count();
+    if repair {
+        for entry in entries.iter_mut().filter(|e| !e.consistent) {
+            if let Ok(id) = Uuid::parse_str(&entry.plugin_id) {
+                let enabled = entry.db_enabled.unwrap_or(DEFAULT_PLUGIN_ENABLED);
+                tracing::warn!(
+                    "Plugin {} enabled flag drifted (factory {:?}, database {:?}), repairing",
+                    entry.plugin_id,
+                    entry.factory_enabled,
+                    entry.db_enabled
+                );
+                factory.set_plugin_enabled(id, enabled);
+                entry.repaired = true;
+            }
+        }
+    }
+    let repaired = entries.iter().filter(|e| e.repaired).count();
+
+    Ok(PluginStateReport {
+        checked_at: chrono::Utc::now().naive_utc(),
+        entries,
+        mismatches,
+        repaired,
+    })
+}
+
+/// Keep the factory in sync with persisted plugin states: apply change
+/// notifications as they arrive and run a repairing audit every
+/// `STATE_AUDIT_INTERVAL` or whenever notifications were dropped.
+pub fn spawn_state_sync(
+    state_manager: Arc<PluginStateManager>,
+    factory: Arc<Mutex<MediaPluginFactory>>,
+) -> JoinHandle<()> {
+    let mut changes = state_manager.subscribe();
+    tokio::spawn(async move {
+        let mut audit = tokio::time::interval(STATE_AUDIT_INTERVAL);
+        loop {
+            let run_audit = tokio::select! {
+                change = changes.recv() => match change {
+                    Ok(change) => {
+                        if let Ok(mut factory) = factory.lock() {
+                            factory.apply_state_change(&change);
+                        }
+                        false
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Missed {} plugin state notifications, auditing", skipped);
+                        true
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                _ = audit.tick() => true,
+            };
+            if run_audit {
+                if let Err(e) = audit_plugin_states(&state_manager, &factory, true) {
+                    tracing::warn!("Plugin state audit failed: {}", e);
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_registered_plugins_can_mismatch() {
+        let media = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let unseeded = Uuid::new_v4();
+        let db = HashMap::from([
+            (media, ("bilibili".to_string(), false)),
+            (other, ("lyrics".to_string(), true)),
+        ]);
+        let factory = HashMap::from([(media, true), (unseeded, true)]);
+
+        let entries = compare_states(&db, &factory);
+        let find = |id: Uuid| entries.iter().find(|e| e.plugin_id == id.to_string()).unwrap();
+        assert!(!find(media).consistent);
+        assert!(find(other).consistent);
+        assert_eq!(find(other).factory_enabled, None);
+        // No state row yet: the default applies
+        assert!(find(unseeded).consistent);
+    }
+}
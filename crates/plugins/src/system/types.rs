@@ -262,6 +262,10 @@ pub enum PluginError {
     /// Invalid plugin manifest
     #[error("Invalid plugin manifest: {reason}")]
     InvalidManifest { reason: String },
+
+    /// Invalid plugin configuration
+    #[error("Invalid plugin configuration: {reason}")]
+    InvalidConfig { reason: String },
     
     /// Serialization error
     #[error("Serialization error: {0}")]
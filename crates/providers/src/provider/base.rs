@@ -37,6 +37,8 @@ pub enum ProviderCapability {
     UrlMatch,
     Suggestions,
     OAuth2,
+    /// Login by importing a browser session cookie
+    CookieAuth,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -65,6 +67,8 @@ pub struct ProviderStatus {
     pub logged_in: bool,
     pub bg_color: Option<String>,
     pub account_id: Option<String>,
+    /// Paid membership (e.g. Bilibili VIP), when the provider reports it
+    pub premium: Option<bool>,
     // Expose provider capabilities to the UI for filtering/selecting providers
     pub capabilities: Vec<ProviderCapability>,
 }
@@ -111,6 +115,9 @@ pub trait BaseProvider: std::fmt::Debug + Send + Sync {
     async fn login(&self, _account_id: String) -> Result<String> { Err("Unsupported".into()) }
     async fn signout(&self, _account_id: String) -> Result<()> { Err("Unsupported".into()) }
     async fn authorize(&self, _code: String) -> Result<()> { Err("Unsupported".into()) }
+    /// Validate a browser session cookie and log in with it. Returns the secret
+    /// config to persist so the session survives a restart.
+    async fn import_cookie(&self, _cookie: String) -> Result<serde_json::Value> { Err("Unsupported".into()) }
 
     // core domain
     async fn search(&self, _term: String) -> Result<SearchResult> { Err("Unsupported".into()) }
@@ -1,15 +1,104 @@
 use super::base::*;
 use async_trait::async_trait;
 use regex::Regex;
-use types::errors::Result;
+use tokio::sync::RwLock;
+use types::errors::{error_helpers, MusicError, Result};
+
+/// Cookies kept from an import; everything else a browser sends is dropped.
+const SESSION_COOKIES: &[&str] = &["SESSDATA", "bili_jct", "DedeUserID", "DedeUserID__ckMd5", "sid", "buvid3"];
+
+const NAV_URL: &str = "https://api.bilibili.com/x/web-interface/nav";
+const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/116.0.0.0 Safari/537.36";
+
+/// Account behind a validated session cookie
+#[derive(Debug, Clone)]
+struct BilibiliAccount {
+    mid: u64,
+    name: String,
+    vip: bool,
+}
 
 #[derive(Debug)]
 pub struct BilibiliProvider {
     key: String,
+    /// Normalized `Cookie` header of the logged-in session
+    cookie: RwLock<Option<String>>,
+    account: RwLock<Option<BilibiliAccount>>,
 }
 
 impl BilibiliProvider {
-    pub fn from_config(key: String, _cfg: serde_json::Value) -> Result<Self> { Ok(Self { key }) }
+    /// `cfg.cookie` holds the session cookie persisted by `import_cookie`.
+    pub fn from_config(key: String, cfg: serde_json::Value) -> Result<Self> {
+        let cookie = cfg
+            .get("cookie")
+            .and_then(|c| c.as_str())
+            .and_then(|c| match parse_cookie_import(c) {
+                Ok(cookie) => Some(cookie),
+                Err(e) => {
+                    tracing::warn!("Ignoring stored Bilibili cookie of {}: {}", key, e);
+                    None
+                }
+            });
+        Ok(Self { key, cookie: RwLock::new(cookie), account: RwLock::new(None) })
+    }
+}
+
+/// Normalize a pasted cookie into a `Cookie` header. Accepts the header value
+/// copied from the browser dev tools (`SESSDATA=...; bili_jct=...`) or a JSON
+/// export of a cookie extension (`[{"name": "SESSDATA", "value": "..."}]`).
+pub fn parse_cookie_import(input: &str) -> Result<String> {
+    let input = input.trim();
+    let input = input.strip_prefix("Cookie:").map(str::trim).unwrap_or(input);
+
+    let pairs: Vec<(String, String)> = if input.starts_with('[') {
+        let cookies: Vec<serde_json::Value> = serde_json::from_str(input)
+            .map_err(|e| MusicError::ValidationError(format!("Invalid cookie export: {}", e).into()))?;
+        cookies
+            .iter()
+            .filter_map(|c| Some((c.get("name")?.as_str()?.to_string(), c.get("value")?.as_str()?.to_string())))
+            .collect()
+    } else {
+        input
+            .split(';')
+            .filter_map(|pair| pair.trim().split_once('='))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect()
+    };
+
+    let kept: Vec<String> = SESSION_COOKIES
+        .iter()
+        .filter_map(|name| pairs.iter().find(|(n, v)| n == name && !v.is_empty()))
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect();
+    if !kept.iter().any(|c| c.starts_with("SESSDATA=")) {
+        return Err(MusicError::ValidationError("Cookie does not contain SESSDATA".into()));
+    }
+    Ok(kept.join("; "))
+}
+
+/// Ask the nav API who the cookie belongs to; fails when it is not logged in.
+async fn fetch_account(cookie: &str) -> Result<BilibiliAccount> {
+    let response: serde_json::Value = reqwest::Client::new()
+        .get(NAV_URL)
+        .header("Cookie", cookie)
+        .header("Referer", "https://www.bilibili.com")
+        .header("User-Agent", USER_AGENT)
+        .send()
+        .await
+        .map_err(error_helpers::to_network_error)?
+        .json()
+        .await
+        .map_err(error_helpers::to_network_error)?;
+
+    let data = &response["data"];
+    if response["code"].as_i64() != Some(0) || data["isLogin"].as_bool() != Some(true) {
+        return Err(MusicError::AuthError("Bilibili cookie is invalid or expired".into()));
+    }
+    Ok(BilibiliAccount {
+        mid: data["mid"].as_u64().unwrap_or_default(),
+        name: data["uname"].as_str().unwrap_or_default().to_string(),
+        vip: data["vipStatus"].as_i64() == Some(1),
+    })
 }
 
 #[async_trait]
@@ -24,12 +113,51 @@ impl BaseProvider for BilibiliProvider {
                 ProviderCapability::Playlists,
                 ProviderCapability::StreamUrl,
                 ProviderCapability::UrlMatch,
+                ProviderCapability::CookieAuth,
             ],
             ..Default::default()
         }
     }
     fn key(&self) -> String { self.key.clone() }
 
+    async fn initialize(&self) -> Result<()> {
+        let Some(cookie) = self.cookie.read().await.clone() else {
+            return Ok(());
+        };
+        let account = fetch_account(&cookie).await?;
+        *self.account.write().await = Some(account);
+        Ok(())
+    }
+
+    async fn get_status(&self) -> Result<ProviderStatus> {
+        let account = self.account.read().await.clone();
+        Ok(ProviderStatus {
+            key: self.key(),
+            name: self.metadata().display_name,
+            logged_in: account.is_some(),
+            user_name: account.as_ref().map(|a| a.name.clone()),
+            account_id: account.as_ref().map(|a| a.mid.to_string()),
+            premium: account.as_ref().map(|a| a.vip),
+            capabilities: self.capabilities(),
+            ..Default::default()
+        })
+    }
+
+    async fn import_cookie(&self, cookie: String) -> Result<serde_json::Value> {
+        let cookie = parse_cookie_import(&cookie)?;
+        let account = fetch_account(&cookie).await?;
+        tracing::info!("Bilibili cookie login for {} as {}", self.key, account.name);
+        *self.cookie.write().await = Some(cookie.clone());
+        *self.account.write().await = Some(account);
+        Ok(serde_json::json!({ "cookie": cookie }))
+    }
+
+    async fn signout(&self, _account_id: String) -> Result<()> {
+        *self.cookie.write().await = None;
+        *self.account.write().await = None;
+        Ok(())
+    }
+
     // 简化示例：用关键词生成伪数据，真实实现应调用 B 站 API 或解析搜索页
     async fn search(&self, term: String) -> Result<SearchResult> {
         // 这里返回 3 条模拟结果，标题/作者中包含 term
@@ -26,6 +26,9 @@ use types::errors::{error_helpers, MusicError, Result};
 
 // const SCHEMA: &str = include_str!("./schema.json");
 
+/// Settings file waiting to replace `config.json` on the next start
+const RESTORE_FILE: &str = "config.restore.json";
+
 #[derive(Debug)]
 pub struct SettingsConfig {
     pub config_file: Mutex<PathBuf>,
@@ -39,11 +42,18 @@ impl SettingsConfig {
     #[tracing::instrument(level = "debug", skip(data_dir))]
     pub fn new(data_dir: PathBuf) -> Result<Self> {
         let config_file_path = data_dir.join("config.json");
+        let restore_file_path = data_dir.join(RESTORE_FILE);
 
         if !data_dir.exists() {
             fs::create_dir_all(data_dir)?;
         }
 
+        // Settings restored from a backup replace the current ones
+        if restore_file_path.exists() {
+            fs::rename(&restore_file_path, &config_file_path)?;
+            tracing::info!("Restored settings from a backup");
+        }
+
         if !config_file_path.exists() {
             let mut file = File::create(config_file_path.clone())?;
             file.write_all(b"{\"prefs\": {}}")?;
@@ -271,6 +281,22 @@ impl SettingsConfig {
         Ok(())
     }
 
+    /// Replace the settings with `config`, the contents of a settings file,
+    /// on the next start
+    #[tracing::instrument(level = "debug", skip(self, config))]
+    pub fn stage_restore(&self, config: &str) -> Result<()> {
+        let parsed: Value = serde_json::from_str(config)?;
+        if !parsed.get("prefs").is_some_and(Value::is_object) {
+            return Err(MusicError::String("Settings file has no preferences".into()));
+        }
+
+        let restore_file_path = self.config_file.lock().expect("poisoned").with_file_name(RESTORE_FILE);
+        let mut restore_file = File::create(restore_file_path)?;
+        restore_file.write_all(config.as_bytes())?;
+        restore_file.flush()?;
+        Ok(())
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     pub fn get_receiver(&self) -> Receiver<(String, Value)> {
         self.receiver.clone()
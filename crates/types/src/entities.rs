@@ -482,3 +482,761 @@ pub struct PluginState {
     #[cfg_attr(feature = "ts-rs", ts(type = "string"))]
     pub last_used: Option<chrono::NaiveDateTime>,
 }
+
+/// Precomputed pinyin/romaji for a CJK track title, album or artist name
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(
+    feature = "db",
+    derive(Insertable, Queryable, Identifiable, AsChangeset,)
+)]
+#[cfg_attr(feature = "db", diesel(table_name = crate::schema::romanized_names))]
+#[cfg_attr(feature = "db", diesel(primary_key(entity_id, entity_kind)))]
+pub struct RomanizedName {
+    pub entity_id: String,
+    /// "track" | "album" | "artist"
+    pub entity_kind: String,
+    /// Original name the romanization was computed from
+    pub source: String,
+    pub romanized: String,
+    pub initials: String,
+}
+
+/// Local library search result, sorted with the active locale's collation
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct LibrarySearchResult {
+    pub tracks: Vec<crate::tracks::MediaContent>,
+    pub albums: Vec<QueryableAlbum>,
+    pub artists: Vec<QueryableArtist>,
+    /// Track id -> track list display string (see types::ui::title_format)
+    #[serde(default)]
+    pub display: std::collections::HashMap<String, String>,
+    /// Track/album id -> available artwork variants
+    #[serde(default)]
+    pub artwork: std::collections::HashMap<String, ArtworkSet>,
+}
+
+/// Folder of the local library, as returned by `get_folder_tree`
+#[derive(Deserialize, Serialize, Default, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct FolderNode {
+    pub path: String,
+    pub name: String,
+    /// Tracks in the folder and its subfolders
+    pub track_count: u32,
+    /// Subfolders holding tracks, by name
+    pub children: Vec<FolderNode>,
+}
+
+/// Reachability of a scanned music folder. Tracks below an unavailable root
+/// (offline network share, unmounted drive) are kept but can't be played.
+#[derive(Deserialize, Serialize, Default, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct LibraryRootStatus {
+    pub root: String,
+    pub available: bool,
+    /// Unix seconds of the last change of `available`
+    pub changed_at: i64,
+    /// Library tracks below the root
+    pub track_count: u32,
+}
+
+/// Artwork size buckets produced by the thumbnail pipeline (square PNGs)
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "camelCase"))]
+pub enum ArtworkSize {
+    Small,
+    Medium,
+    Large,
+}
+
+impl ArtworkSize {
+    pub const ALL: [ArtworkSize; 3] = [ArtworkSize::Small, ArtworkSize::Medium, ArtworkSize::Large];
+
+    /// Edge length in pixels
+    pub fn pixels(self) -> u32 {
+        match self {
+            ArtworkSize::Small => 80,
+            ArtworkSize::Medium => 200,
+            ArtworkSize::Large => 400,
+        }
+    }
+
+    /// Smallest bucket covering `px` physical pixels (CSS size x device pixel ratio)
+    pub fn for_pixels(px: u32) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|size| size.pixels() >= px)
+            .unwrap_or(ArtworkSize::Large)
+    }
+
+    /// Path of this variant next to the large cover ("<hash>.webp", or
+    /// "<hash>.png" for covers stored before WebP), in the same format
+    pub fn variant_path(self, cover_high: &std::path::Path) -> std::path::PathBuf {
+        let stem = cover_high
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let ext = cover_high
+            .extension()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "png".to_string());
+        match self {
+            ArtworkSize::Small => cover_high.with_file_name(format!("{}-low.{}", stem, ext)),
+            ArtworkSize::Medium => cover_high.with_file_name(format!("{}-medium.{}", stem, ext)),
+            ArtworkSize::Large => cover_high.to_path_buf(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct ArtworkVariant {
+    pub size: ArtworkSize,
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Entity whose artwork is requested, e.g. `{ "kind": "album", "id": "..." }`
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", content = "id", rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub enum ArtworkEntity {
+    Track(String),
+    Album(String),
+}
+
+/// Artwork variants of one track or album. Variants missing on disk are None;
+/// `get_artwork` generates them on demand.
+#[derive(Deserialize, Serialize, Default, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct ArtworkSet {
+    pub small: Option<ArtworkVariant>,
+    pub medium: Option<ArtworkVariant>,
+    pub large: Option<ArtworkVariant>,
+}
+
+impl ArtworkSet {
+    pub fn from_cover_paths(high: Option<&str>, low: Option<&str>) -> Self {
+        let variant = |size: ArtworkSize, path: std::path::PathBuf| {
+            path.exists().then(|| ArtworkVariant {
+                size,
+                path: path.to_string_lossy().to_string(),
+                width: size.pixels(),
+                height: size.pixels(),
+            })
+        };
+        let high = high.map(std::path::Path::new);
+        Self {
+            small: low
+                .map(std::path::PathBuf::from)
+                .or_else(|| high.map(|h| ArtworkSize::Small.variant_path(h)))
+                .and_then(|p| variant(ArtworkSize::Small, p)),
+            medium: high.and_then(|h| variant(ArtworkSize::Medium, ArtworkSize::Medium.variant_path(h))),
+            large: high.and_then(|h| variant(ArtworkSize::Large, h.to_path_buf())),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.small.is_none() && self.medium.is_none() && self.large.is_none()
+    }
+}
+
+/// Number of playlist entries sharing a value (genre name, decade, ...)
+#[derive(Deserialize, Serialize, Default, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct DistributionEntry {
+    pub label: String,
+    pub count: u32,
+}
+
+/// A track that appears more than once in the same playlist
+#[derive(Deserialize, Serialize, Default, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct PlaylistDuplicate {
+    pub track_id: String,
+    pub title: Option<String>,
+    pub occurrences: u32,
+}
+
+/// Playlist statistics for the "health check" panel
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct PlaylistInsights {
+    pub playlist_id: String,
+    /// Number of entries, duplicates included
+    pub total_entries: u32,
+    pub unique_tracks: u32,
+    /// Sum of entry durations in seconds
+    pub total_duration: f64,
+    /// Sorted by count, descending
+    pub genre_distribution: Vec<DistributionEntry>,
+    /// Labels like "1990s"; entries without a parsable year are counted as "Unknown"
+    pub decade_distribution: Vec<DistributionEntry>,
+    /// Entries whose track row is gone or whose local file no longer exists
+    pub unavailable_tracks: u32,
+    /// Entries that need network access (non-local sources)
+    pub offline_unplayable_tracks: u32,
+    pub duplicates: Vec<PlaylistDuplicate>,
+    /// Average loudness in LUFS; None while no loudness analysis has been stored
+    pub average_loudness: Option<f64>,
+}
+
+/// Snapshot of a playlist's membership, returned by `get_playlist_history`
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct PlaylistVersion {
+    pub playlist_id: String,
+    pub version: i64,
+    /// "initial" | "add" | "remove" | "restore" | "sync" | "external" (changed
+    /// without a snapshot, e.g. by an import)
+    pub reason: String,
+    pub track_count: u32,
+    /// Tracks added and removed since the previous version kept
+    pub added: u32,
+    pub removed: u32,
+    #[cfg_attr(feature = "ts-rs", ts(type = "string"))]
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// Outcome of `restore_playlist_version`
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct PlaylistRestore {
+    pub playlist_id: String,
+    /// Version restored from
+    pub restored_from: i64,
+    /// Version recorded for the restored membership
+    pub version: i64,
+    pub track_count: u32,
+    /// Tracks of the snapshot that are no longer in the library, left out
+    pub missing_tracks: u32,
+}
+
+/// Local playlist kept in sync with a playlist of an online provider,
+/// returned by `get_playlist_syncs`
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct PlaylistSyncLink {
+    pub playlist_id: String,
+    /// Plugin ID of the provider
+    pub provider_id: String,
+    /// ID of the playlist at the provider
+    pub remote_id: String,
+    /// Unix time in ms of the last sync that went through
+    pub last_synced_at: Option<i64>,
+    /// Why the last sync failed, `None` when it went through
+    pub last_error: Option<String>,
+    /// Conflicts waiting for the user
+    pub conflicts: u32,
+}
+
+/// Change made to one side of a synced playlist that could not be carried
+/// to the other, returned by `get_playlist_sync_conflicts`
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct PlaylistSyncConflict {
+    pub id: i64,
+    pub playlist_id: String,
+    pub track_id: String,
+    pub track_title: Option<String>,
+    /// "local_addition" (added locally, the provider did not take it) |
+    /// "local_removal" (removed locally, the provider playlist is read-only)
+    pub kind: String,
+    /// Unix time in ms
+    pub detected_at: i64,
+}
+
+/// Outcome of one sync of a linked playlist
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct PlaylistSyncReport {
+    pub playlist_id: String,
+    /// Tracks added and removed locally after changes at the provider
+    pub pulled_added: u32,
+    pub pulled_removed: u32,
+    /// Local additions and removals applied at the provider
+    pub pushed_added: u32,
+    pub pushed_removed: u32,
+    /// Conflicts waiting for the user after the sync
+    pub conflicts: u32,
+    /// Why local changes could not be pushed; they are tried again next sync
+    pub error: Option<String>,
+}
+
+/// Track of the liked songs, returned by `get_liked_tracks`
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct LikedTrack {
+    pub track: crate::tracks::MediaContent,
+    /// "local" when liked in the app, else the provider whose favorites the
+    /// like was imported from
+    pub origin: String,
+    /// Unix time in ms
+    pub liked_at: i64,
+}
+
+/// One play of the listening history
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct PlayHistoryEntry {
+    pub track_id: String,
+    #[cfg_attr(feature = "ts-rs", ts(type = "string | null"))]
+    pub played_at: Option<chrono::NaiveDateTime>,
+    /// Seconds listened
+    pub play_duration: Option<f64>,
+}
+
+/// What a user data archive holds, returned by `export_user_data`; for
+/// `import_user_data`, what was added from it
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct UserDataSummary {
+    pub play_history: u32,
+    pub playlists: u32,
+    pub liked_tracks: u32,
+    /// Tracks the other entries refer to
+    pub tracks: u32,
+    pub settings: bool,
+}
+
+/// What a library backup holds, returned by `backup_library`
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct LibraryBackupContents {
+    /// Schema the database snapshot is at
+    pub schema: SchemaVersion,
+    /// Size of the database snapshot in bytes
+    pub database_size: u64,
+    pub settings: bool,
+    /// Files listed in the thumbnails manifest
+    pub thumbnails: u32,
+    pub plugin_states: u32,
+}
+
+/// Outcome of `restore_library`; the restored library is used from the next
+/// start of the app
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct LibraryRestoreReport {
+    pub contents: LibraryBackupContents,
+    /// Migrations run on the restored database to bring it to the current schema
+    pub migrations: MigrationReport,
+    /// Thumbnails of the manifest not on this machine; scans create them again
+    pub missing_thumbnails: u32,
+}
+
+/// Outcome of importing the favorites of a provider account
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct FavoritesImport {
+    pub provider_id: String,
+    /// Favorites of the account
+    pub total: u32,
+    /// Favorites that were not liked yet
+    pub imported: u32,
+}
+
+/// Outcome of `enrich_track` and `enrich_album`
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct EnrichmentResult {
+    pub track_id: String,
+    /// MusicBrainz recording matched, `None` when nothing matched closely
+    pub recording_id: Option<String>,
+    /// MusicBrainz release the album was matched to
+    pub release_id: Option<String>,
+    /// Whether a cover was fetched from the Cover Art Archive
+    pub cover_fetched: bool,
+}
+
+/// Chromaprint fingerprint stored for a track
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct TrackFingerprint {
+    pub track_id: String,
+    pub fingerprint: Vec<u32>,
+    /// Length of the file in seconds
+    pub duration: f64,
+    /// AcoustID track id, once looked up
+    pub acoustid: Option<String>,
+}
+
+/// Tags changed by `edit_track_metadata`; fields left `None` are kept
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct TrackMetadataEdit {
+    pub title: Option<String>,
+    /// Artists in credit order, written to the file joined by the artist splitter
+    pub artists: Option<Vec<String>>,
+    pub album: Option<String>,
+    pub genre: Option<String>,
+    pub track_no: Option<u32>,
+    pub year: Option<String>,
+}
+
+/// Track whose edit failed, with the reason
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct TrackEditFailure {
+    pub track_id: String,
+    pub error: String,
+}
+
+/// Outcome of `edit_track_metadata`
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct TrackEditResult {
+    /// Tracks updated in the library and in their files
+    pub updated: Vec<String>,
+    pub failed: Vec<TrackEditFailure>,
+}
+
+/// Play queue replaced or cleared, returned by `list_queue_history`
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct QueueSnapshot {
+    pub id: String,
+    /// Album name when the queue held one album, its first track otherwise
+    pub name: String,
+    /// Track ids in queue order
+    pub track_ids: Vec<String>,
+    #[cfg_attr(feature = "ts-rs", ts(type = "string"))]
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// Outcome of `restore_queue_from_history`
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct QueueRestore {
+    pub track_count: u32,
+    /// Tracks of the snapshot that are no longer in the library, left out
+    pub missing_tracks: u32,
+}
+
+/// Journaled state of a long-running job (scan, download) for resume after restart
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+#[cfg_attr(
+    feature = "db",
+    derive(Insertable, Queryable, Identifiable, AsChangeset,)
+)]
+#[cfg_attr(feature = "db", diesel(table_name = crate::schema::task_journal))]
+#[cfg_attr(feature = "db", diesel(primary_key(id)))]
+pub struct TaskJournalEntry {
+    pub id: String,
+    /// "scan" | "download"
+    pub kind: String,
+    /// "running" | "completed" | "failed" | "cancelled"
+    pub status: String,
+    /// JSON job description
+    pub payload: String,
+    /// JSON progress checkpoint
+    pub checkpoint: Option<String>,
+    pub error: Option<String>,
+    #[cfg_attr(feature = "ts-rs", ts(type = "string"))]
+    pub created_at: chrono::NaiveDateTime,
+    #[cfg_attr(feature = "ts-rs", ts(type = "string"))]
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+/// Online track downloaded for offline playback
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+#[cfg_attr(
+    feature = "db",
+    derive(Insertable, Queryable, Identifiable, AsChangeset,)
+)]
+#[cfg_attr(feature = "db", diesel(table_name = crate::schema::downloads))]
+#[cfg_attr(feature = "db", diesel(primary_key(track_id)))]
+pub struct DownloadedTrack {
+    pub track_id: String,
+    /// Downloaded file, played instead of resolving a stream
+    pub path: String,
+    /// File size in bytes
+    pub size: i64,
+    /// "manual" | "smart"
+    pub origin: String,
+    #[cfg_attr(feature = "ts-rs", ts(type = "string"))]
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// Row count of one table before and after applying pending migrations
+#[derive(Deserialize, Serialize, Default, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct TableRowChange {
+    pub table: String,
+    /// None when the table is created by the migration
+    pub before: Option<i64>,
+    /// None when the table is dropped by the migration
+    pub after: Option<i64>,
+}
+
+/// Outcome of a migration run (or dry run)
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct MigrationReport {
+    /// Versions that were (or would be) applied, oldest first
+    pub applied: Vec<String>,
+    /// Tables whose row count changed or that were created/dropped
+    pub affected_tables: Vec<TableRowChange>,
+    /// Backup taken before migrating; None for dry runs or when nothing was pending
+    pub backup_path: Option<String>,
+    pub dry_run: bool,
+}
+
+/// Schema diagnostics for the about/debug panel
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct SchemaVersion {
+    /// Latest applied migration version, e.g. "20250905000000"
+    pub version: Option<String>,
+    pub applied_count: u32,
+    /// Embedded migrations not applied yet (non-empty only for a failed upgrade)
+    pub pending: Vec<String>,
+    /// Most recent pre-migration backup, if any
+    pub last_backup: Option<String>,
+}
+
+/// Stage of a library export
+#[derive(Deserialize, Serialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+#[serde(rename_all = "snake_case")]
+pub enum LibraryExportPhase {
+    #[default]
+    Tracks,
+    PlayHistory,
+    Finalizing,
+}
+
+/// Progress of a library export, reported after every chunk
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct LibraryExportProgress {
+    pub phase: LibraryExportPhase,
+    /// Rows of the current phase written so far
+    pub done: u64,
+    pub total: u64,
+}
+
+/// Result of exporting the library to a standalone SQLite file
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct LibraryExportSummary {
+    pub path: String,
+    pub tracks: u64,
+    pub play_history: u64,
+    /// Size of the exported file
+    pub bytes: u64,
+}
+
+/// Files of one extension found by a pre-scan estimate
+#[derive(Deserialize, Serialize, Default, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct FormatUsage {
+    /// Lowercase extension without the dot, e.g. "flac"
+    pub extension: String,
+    pub files: u64,
+    pub bytes: u64,
+}
+
+/// What a scan of some folders would pick up, computed without reading tags
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct ScanEstimate {
+    /// Audio files
+    pub files: u64,
+    /// Total size of the audio files
+    pub bytes: u64,
+    pub playlists: u64,
+    /// Per-extension breakdown of the audio files, largest first
+    pub formats: Vec<FormatUsage>,
+    /// Entries that could not be read (permissions, broken links)
+    pub skipped: u64,
+}
+
+/// Disk usage of the library and of the app's own data, for the storage settings page
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct LibraryStorageReport {
+    /// Scanned local tracks, as recorded in the library
+    pub library_bytes: u64,
+    pub library_tracks: u64,
+    /// Stream and playback cache
+    pub cache_bytes: u64,
+    /// Extracted and resized artwork
+    pub artwork_bytes: u64,
+    /// Offline downloads
+    pub downloads_bytes: u64,
+}
+
+/// A signal combined into a smart sort
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "snake_case"))]
+pub enum SmartSortCriterion {
+    /// Newest additions rank highest
+    RecentlyAdded,
+    /// Most played rank highest
+    PlayCount,
+    /// Best rated rank highest, unrated tracks count as 0
+    Rating,
+    /// A to Z by title
+    Alphabetical,
+}
+
+/// One weighted term of a smart sort. Negative weights invert the criterion,
+/// e.g. -1 on `RecentlyAdded` puts the oldest tracks first.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct SmartSortWeight {
+    pub criterion: SmartSortCriterion,
+    pub weight: f64,
+}
+
+/// A named, saved combination of weighted sort criteria
+#[derive(Deserialize, Serialize, Default, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct SmartSortPreset {
+    pub name: String,
+    pub criteria: Vec<SmartSortWeight>,
+}
+
+impl SmartSortPreset {
+    /// Summed weight of `criterion`, 0 when it is not part of the preset
+    pub fn weight_of(&self, criterion: SmartSortCriterion) -> f64 {
+        self.criteria
+            .iter()
+            .filter(|c| c.criterion == criterion && c.weight.is_finite())
+            .map(|c| c.weight)
+            .sum()
+    }
+}
+
+/// Mood bucket of a track, a quadrant of the energy / valence plane
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "snake_case"))]
+pub enum TrackMood {
+    /// High energy, positive
+    Energetic,
+    /// High energy, negative
+    Tense,
+    /// Low energy, positive
+    Calm,
+    /// Low energy, negative
+    Melancholic,
+}
+
+impl TrackMood {
+    pub const ALL: [TrackMood; 4] = [Self::Energetic, Self::Tense, Self::Calm, Self::Melancholic];
+
+    /// Bucket of a track from its energy and valence, both 0..1
+    pub fn classify(energy: f64, valence: f64) -> Self {
+        match (energy >= 0.5, valence >= 0.5) {
+            (true, true) => Self::Energetic,
+            (true, false) => Self::Tense,
+            (false, true) => Self::Calm,
+            (false, false) => Self::Melancholic,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Energetic => "energetic",
+            Self::Tense => "tense",
+            Self::Calm => "calm",
+            Self::Melancholic => "melancholic",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.as_str() == value)
+    }
+}
+
+/// Audio features extracted locally from a track. Energy, valence and
+/// danceability are 0..1 heuristics, not calibrated measurements.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct TrackAudioFeatures {
+    pub energy: f64,
+    pub valence: f64,
+    pub danceability: f64,
+    /// Estimated beats per minute
+    pub tempo: f64,
+    pub mood: TrackMood,
+}
+
+/// Condition on the audio features of tracks, e.g. for a smart playlist.
+/// Unset bounds don't restrict; tracks never analyzed never match.
+#[derive(Deserialize, Serialize, Default, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct TrackFeatureFilter {
+    /// Any of these moods; empty allows all
+    #[serde(default)]
+    pub moods: Vec<TrackMood>,
+    pub min_energy: Option<f64>,
+    pub max_energy: Option<f64>,
+    pub min_valence: Option<f64>,
+    pub max_valence: Option<f64>,
+    pub min_danceability: Option<f64>,
+    pub max_danceability: Option<f64>,
+    pub min_tempo: Option<f64>,
+    pub max_tempo: Option<f64>,
+}
+
+/// A track whose lyrics contain a searched phrase
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct LyricsSearchHit {
+    pub track: crate::tracks::MediaContent,
+    /// Lyrics line containing the phrase, without LRC time tags
+    pub snippet: String,
+    /// Matched range in `snippet`, in characters (code points), end exclusive
+    pub highlight_start: u32,
+    pub highlight_end: u32,
+    /// Start of the matching line for time-synced (LRC) lyrics
+    pub time_ms: Option<u64>,
+}
+
+/// One displayed lyrics line
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct LyricsLine {
+    /// Start of the line, for time-synced lyrics
+    pub time_ms: Option<u64>,
+    pub text: String,
+}
+
+/// Lyrics of a track as served to the UI
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct TrackLyrics {
+    pub track_id: String,
+    /// "embedded" | "sidecar" | "library" (cached) | the providing plugin's id
+    pub source: String,
+    /// Whether every line carries a time, in ascending order
+    pub synced: bool,
+    /// Raw text as stored in the library (LRC when synced)
+    pub text: String,
+    pub lines: Vec<LyricsLine>,
+}
+
+/// Recording identified from a sample of the system output
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct IdentifiedRecording {
+    /// MusicBrainz recording id
+    pub recording_id: String,
+    pub title: Option<String>,
+    pub artists: Vec<String>,
+    pub album: Option<String>,
+    /// AcoustID score, 0-1
+    pub score: f64,
+    /// Tracks of the library and of the media providers with this title and
+    /// artist, to play, add to the library or like
+    pub matches: Vec<crate::tracks::MediaContent>,
+}
@@ -1,4 +1,6 @@
 
+#[cfg(all(not(feature = "extensions"), feature = "ts-rs"))]
+use ts_rs::TS;
 
 #[cfg(not(feature = "extensions"))]
 use std::{
@@ -142,6 +144,14 @@ impl From<SystemTimeError> for MusicError {
     }
 }
 
+#[cfg(feature = "db")]
+impl From<diesel::result::Error> for MusicError {
+    #[tracing::instrument(level = "debug", skip(value))]
+    fn from(value: diesel::result::Error) -> Self {
+        Self::DatabaseError(Box::new(value))
+    }
+}
+
 impl serde::Serialize for MusicError {
     #[tracing::instrument(level = "debug", skip(self, serializer))]
     fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
@@ -163,6 +173,158 @@ pub enum MusicError {
 
 pub type Result<T> = std::result::Result<T, MusicError>;
 
+/// Stable, machine-readable error category sent to the frontend
+#[cfg(not(feature = "extensions"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub enum ErrorCode {
+    Io,
+    Json,
+    Playback,
+    Database,
+    Network,
+    Auth,
+    FileSystem,
+    Media,
+    Config,
+    Parse,
+    Validation,
+    Provider,
+    Extension,
+    Cache,
+    Webview,
+    Plugin,
+    Mpris,
+    SwitchProviders,
+    InvalidatedCache,
+    Unknown,
+}
+
+#[cfg(not(feature = "extensions"))]
+impl MusicError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            #[cfg(any(feature = "db", feature = "extensions-core"))]
+            Self::IO(_) => ErrorCode::Io,
+            Self::Json(_) => ErrorCode::Json,
+            Self::PlaybackError(_) => ErrorCode::Playback,
+            Self::DatabaseError(_) => ErrorCode::Database,
+            Self::NetworkError(_) => ErrorCode::Network,
+            Self::AuthError(_) => ErrorCode::Auth,
+            Self::FileSystemError(_) => ErrorCode::FileSystem,
+            Self::MediaError(_) => ErrorCode::Media,
+            Self::ConfigError(_) => ErrorCode::Config,
+            Self::ParseError(_) => ErrorCode::Parse,
+            Self::ValidationError(_) => ErrorCode::Validation,
+            Self::ProviderError(_) => ErrorCode::Provider,
+            Self::ExtensionError(_) => ErrorCode::Extension,
+            Self::CacheError(_) => ErrorCode::Cache,
+            Self::WebviewError(_) => ErrorCode::Webview,
+            Self::PluginError(_) => ErrorCode::Plugin,
+            Self::MprisError(_) => ErrorCode::Mpris,
+            Self::String(_) => ErrorCode::Unknown,
+            #[cfg(feature = "db")]
+            Self::SwitchProviders(_) => ErrorCode::SwitchProviders,
+            Self::InvalidatedCache => ErrorCode::InvalidatedCache,
+        }
+    }
+
+    /// Whether the same call may succeed if simply repeated later
+    pub fn retryable(&self) -> bool {
+        match self {
+            Self::NetworkError(_) | Self::ProviderError(_) | Self::InvalidatedCache => true,
+            // SQLite reports lock contention as "database is locked" / "busy"
+            Self::DatabaseError(e) => {
+                let msg = e.to_string().to_lowercase();
+                msg.contains("locked") || msg.contains("busy")
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Error half of [`CommandResponse`]
+#[cfg(not(feature = "extensions"))]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct CommandError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub retryable: bool,
+}
+
+#[cfg(not(feature = "extensions"))]
+impl From<&MusicError> for CommandError {
+    fn from(value: &MusicError) -> Self {
+        Self {
+            code: value.code(),
+            message: value.to_string(),
+            retryable: value.retryable(),
+        }
+    }
+}
+
+/// Envelope returned by every Tauri command: `{ ok: true, data }` on success,
+/// `{ ok: false, error }` on failure. Built by `macros::command_envelope!`.
+#[cfg(not(feature = "extensions"))]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+pub struct CommandResponse<T> {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<CommandError>,
+}
+
+#[cfg(not(feature = "extensions"))]
+impl<T> CommandResponse<T> {
+    pub fn success(data: T) -> Self {
+        Self {
+            ok: true,
+            data: Some(data),
+            error: None,
+        }
+    }
+
+    pub fn failure(error: CommandError) -> Self {
+        Self {
+            ok: false,
+            data: None,
+            error: Some(error),
+        }
+    }
+
+    /// Back to a plain `Result`, for Rust code calling a command function directly.
+    /// Only the message of the original error is kept.
+    pub fn into_result(self) -> Result<T> {
+        match (self.data, self.error) {
+            (_, Some(error)) => Err(MusicError::String(error.message)),
+            (Some(data), None) => Ok(data),
+            (None, None) => Err(MusicError::String("Empty command response".into())),
+        }
+    }
+}
+
+#[cfg(not(feature = "extensions"))]
+impl<T> From<Result<T>> for CommandResponse<T> {
+    fn from(value: Result<T>) -> Self {
+        match value {
+            Ok(data) => Self::success(data),
+            Err(e) => {
+                tracing::error!("Command failed: {}", e);
+                Self::failure(CommandError::from(&e))
+            }
+        }
+    }
+}
+
+/// Return type of enveloped commands. Tauri requires async commands with
+/// borrowed arguments to return a `Result`; the `Err` side is never used.
+#[cfg(not(feature = "extensions"))]
+pub type CommandResult<T> = Result<CommandResponse<T>>;
+
 /// Helper functions for converting errors to MusicError variants
 /// These can be used with .map_err() directly
 #[cfg(not(feature = "extensions"))]
@@ -238,3 +400,26 @@ macro_rules! music_err {
         Err($crate::errors::MusicError::$variant(Box::new($err)))
     };
 }
+
+#[cfg(all(test, not(feature = "extensions")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn envelope_carries_code_and_retryable() {
+        let ok: CommandResponse<u32> = Ok(3).into();
+        assert_eq!(serde_json::to_value(&ok).unwrap(), serde_json::json!({ "ok": true, "data": 3 }));
+
+        let failed: CommandResponse<u32> =
+            Err(error_helpers::to_network_error(std::fmt::Error)).into();
+        let json = serde_json::to_value(&failed).unwrap();
+        assert_eq!(json["ok"], false);
+        assert_eq!(json["error"]["code"], "network");
+        assert_eq!(json["error"]["retryable"], true);
+        assert!(failed.into_result().is_err());
+
+        let invalid: CommandResponse<u32> = Err(MusicError::String("bad".into())).into();
+        assert_eq!(invalid.error.as_ref().unwrap().code, ErrorCode::Unknown);
+        assert!(!invalid.error.unwrap().retryable);
+    }
+}
@@ -3,6 +3,8 @@ pub mod settings;
 pub mod providers;
 pub mod themes;
 pub mod tracks;
+pub mod stations;
+pub mod podcasts;
 pub mod entities;
 #[cfg(feature = "db")]
 pub mod schema;
@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "ts-rs")]
+use ts_rs::TS;
+
+use crate::entities::QueryableAlbum;
+use crate::tracks::{MediaContent, TrackType, Tracks};
+
+/// ID prefix of queue entries (and downloads) that are podcast episodes,
+/// followed by the episode ID
+pub const EPISODE_ID_PREFIX: &str = "podcast:";
+
+/// A subscribed RSS/Atom podcast feed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "camelCase"))]
+pub struct Podcast {
+    pub id: String,
+    pub feed_url: String,
+    pub title: String,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    /// Website of the podcast
+    pub link: Option<String>,
+    /// Unix time in ms of the latest successful refresh
+    pub refreshed_at: Option<i64>,
+    /// Error of the latest refresh, cleared by a successful one
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub episode_count: i64,
+    #[serde(default)]
+    pub unplayed_count: i64,
+}
+
+/// An episode of a subscribed podcast, with where playback left off
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "camelCase"))]
+pub struct PodcastEpisode {
+    pub id: String,
+    pub podcast_id: String,
+    /// ID of the episode in the feed
+    pub guid: String,
+    pub title: String,
+    pub description: Option<String>,
+    /// Enclosure URL
+    pub audio_url: String,
+    pub mime_type: Option<String>,
+    /// Seconds, when the feed says
+    pub duration: Option<f64>,
+    /// Unix time in ms
+    pub published_at: Option<i64>,
+    /// Resume position in seconds, 0 when not started
+    #[serde(default)]
+    pub position: f64,
+    #[serde(default)]
+    pub played: bool,
+}
+
+impl PodcastEpisode {
+    /// Track ID of the episode in the queue and the downloads
+    pub fn track_id(&self) -> String {
+        format!("{}{}", EPISODE_ID_PREFIX, self.id)
+    }
+
+    /// The episode as a queue entry, played from `local_path` when downloaded
+    /// and streamed otherwise
+    pub fn to_media_content(&self, podcast: &Podcast, local_path: Option<String>) -> MediaContent {
+        let (type_, path, playback_url) = match local_path {
+            Some(path) => (TrackType::LOCAL, Some(path), None),
+            None => (TrackType::URL, None, Some(self.audio_url.clone())),
+        };
+        MediaContent {
+            track: Tracks {
+                _id: Some(self.track_id()),
+                title: Some(self.title.clone()),
+                duration: self.duration,
+                type_,
+                path,
+                playback_url,
+                url: podcast.link.clone(),
+                track_cover_path_high: podcast.image_url.clone(),
+                track_cover_path_low: podcast.image_url.clone(),
+                ..Default::default()
+            },
+            album: Some(QueryableAlbum {
+                album_name: Some(podcast.title.clone()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+/// Episode ID of a queue entry or download, `None` for anything else
+pub fn episode_id(track_id: &str) -> Option<&str> {
+    track_id
+        .split('#')
+        .next()
+        .and_then(|id| id.strip_prefix(EPISODE_ID_PREFIX))
+        .filter(|id| !id.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn episodes_are_recognized_by_their_id() {
+        let podcast = Podcast {
+            id: "p".to_string(),
+            title: "The Show".to_string(),
+            ..Default::default()
+        };
+        let episode = PodcastEpisode {
+            id: "e1".to_string(),
+            podcast_id: "p".to_string(),
+            audio_url: "https://example.com/e1.mp3".to_string(),
+            ..Default::default()
+        };
+
+        let streamed = episode.to_media_content(&podcast, None);
+        assert_eq!(streamed.track.type_, TrackType::URL);
+        assert_eq!(episode_id(streamed.track._id.as_deref().unwrap()), Some("e1"));
+        let local = episode.to_media_content(&podcast, Some("/tmp/e1.mp3".to_string()));
+        assert_eq!(local.track.type_, TrackType::LOCAL);
+        assert_eq!(local.track.playback_url, None);
+
+        // Queue instance IDs carry a suffix
+        assert_eq!(episode_id("podcast:e1#3"), Some("e1"));
+        assert_eq!(episode_id("radio:e1"), None);
+        assert_eq!(episode_id("podcast:"), None);
+    }
+}
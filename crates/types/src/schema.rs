@@ -73,6 +73,32 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    downloads (track_id) {
+        track_id -> Text,
+        path -> Text,
+        size -> BigInt,
+        origin -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    episodes (id) {
+        id -> Text,
+        podcast_id -> Text,
+        guid -> Text,
+        title -> Text,
+        description -> Nullable<Text>,
+        audio_url -> Text,
+        mime_type -> Nullable<Text>,
+        duration -> Nullable<Double>,
+        published_at -> Nullable<BigInt>,
+        position -> Double,
+        played -> Bool,
+    }
+}
+
 diesel::table! {
     genre_bridge (id) {
         id -> Nullable<Integer>,
@@ -89,6 +115,14 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    library_roots (root_path) {
+        root_path -> Text,
+        available -> Bool,
+        changed_at -> BigInt,
+    }
+}
+
 diesel::table! {
     playlist_bridge (id) {
         id -> Nullable<Integer>,
@@ -97,6 +131,16 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    playlist_history (playlist_id, version) {
+        playlist_id -> Text,
+        version -> BigInt,
+        reason -> Text,
+        track_ids -> Text,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     play_history (id) {
         id -> Nullable<Integer>,
@@ -129,6 +173,15 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    queue_history (history_id) {
+        history_id -> Text,
+        name -> Text,
+        track_ids -> Text,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     player_store_kv (key) {
         key -> Text,
@@ -156,6 +209,52 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    podcasts (id) {
+        id -> Text,
+        feed_url -> Text,
+        title -> Text,
+        author -> Nullable<Text>,
+        description -> Nullable<Text>,
+        image_url -> Nullable<Text>,
+        link -> Nullable<Text>,
+        subscribed_at -> BigInt,
+        refreshed_at -> Nullable<BigInt>,
+        last_error -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    romanized_names (entity_id, entity_kind) {
+        entity_id -> Text,
+        entity_kind -> Text,
+        source -> Text,
+        romanized -> Text,
+        initials -> Text,
+    }
+}
+
+diesel::table! {
+    sort_presets (name) {
+        name -> Text,
+        criteria -> Text,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    task_journal (id) {
+        id -> Text,
+        kind -> Text,
+        status -> Text,
+        payload -> Text,
+        checkpoint -> Nullable<Text>,
+        error -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     track_artists (id) {
         id -> Integer,
@@ -166,6 +265,83 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    track_positions (track_id) {
+        track_id -> Text,
+        position -> Double,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    track_ratings (track_id) {
+        track_id -> Text,
+        rating -> Integer,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    track_bookmarks (bookmark_id) {
+        bookmark_id -> Text,
+        track_id -> Text,
+        position -> Double,
+        label -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    track_chapters (track_id, idx) {
+        track_id -> Text,
+        idx -> Integer,
+        start_time -> Double,
+        end_time -> Nullable<Double>,
+        title -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    track_edit_regions (track_id) {
+        track_id -> Text,
+        regions -> Text,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    track_features (track_id) {
+        track_id -> Text,
+        energy -> Double,
+        valence -> Double,
+        danceability -> Double,
+        tempo -> Double,
+        mood -> Text,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    track_fingerprints (track_id) {
+        track_id -> Text,
+        fingerprint -> Text,
+        duration -> Double,
+        acoustid -> Nullable<Text>,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    track_gain (track_id) {
+        track_id -> Text,
+        track_gain -> Nullable<Double>,
+        track_peak -> Nullable<Double>,
+        album_gain -> Nullable<Double>,
+        album_peak -> Nullable<Double>,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     track_images (id) {
         id -> Integer,
@@ -184,14 +360,31 @@ diesel::allow_tables_to_appear_in_same_query!(
 
     artist_bridge,
     artists,
+    downloads,
+    episodes,
     genre_bridge,
     genres,
+    library_roots,
     play_history,
     play_queue,
     player_store_kv,
     plugin_states,
     playlist_bridge,
+    podcasts,
+    playlist_history,
     playlists,
+    queue_history,
+    romanized_names,
+    sort_presets,
+    task_journal,
     track_artists,
+    track_bookmarks,
+    track_chapters,
+    track_edit_regions,
+    track_features,
+    track_fingerprints,
+    track_gain,
     track_images,
+    track_positions,
+    track_ratings,
 );
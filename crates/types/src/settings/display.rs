@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "ts-rs")]
+use ts_rs::TS;
+
+// Frontend-facing typed view for the "display" settings domain.
+// Stored under prefs.display.*; templates use %field% placeholders and
+// [optional sections] that vanish when a field inside them is empty,
+// e.g. "%artist% — %title%[ (%year%)]". See types::ui::title_format.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "camelCase"))]
+pub struct DisplaySettings {
+    /// Per-view title templates; missing entries use the built-in defaults.
+    pub templates: Option<DisplayTemplates>,
+}
+
+/// Title templates for each surface that renders track titles.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "camelCase"))]
+pub struct DisplayTemplates {
+    pub track_list: Option<String>,
+    pub now_playing: Option<String>,
+    pub mpris: Option<String>,
+    pub tray: Option<String>,
+    pub notification: Option<String>,
+}
+
+/// A surface that renders track titles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "camelCase"))]
+pub enum DisplayView {
+    TrackList,
+    NowPlaying,
+    Mpris,
+    Tray,
+    Notification,
+}
+
+impl DisplayView {
+    pub const ALL: [DisplayView; 5] = [
+        DisplayView::TrackList,
+        DisplayView::NowPlaying,
+        DisplayView::Mpris,
+        DisplayView::Tray,
+        DisplayView::Notification,
+    ];
+
+    /// Built-in template used when the user has not configured one.
+    pub fn default_template(self) -> &'static str {
+        match self {
+            DisplayView::TrackList => "%title%",
+            DisplayView::NowPlaying => "%title%",
+            // MPRIS/SMTC render artist separately
+            DisplayView::Mpris => "%title%",
+            DisplayView::Tray => "%artist% — %title%",
+            DisplayView::Notification => "%artist% — %title%[ (%year%)]",
+        }
+    }
+}
+
+impl DisplayTemplates {
+    /// Template for `view`, falling back to the built-in default.
+    pub fn template_for(&self, view: DisplayView) -> &str {
+        let configured = match view {
+            DisplayView::TrackList => &self.track_list,
+            DisplayView::NowPlaying => &self.now_playing,
+            DisplayView::Mpris => &self.mpris,
+            DisplayView::Tray => &self.tray,
+            DisplayView::Notification => &self.notification,
+        };
+        configured
+            .as_deref()
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or_else(|| view.default_template())
+    }
+}
@@ -16,6 +16,8 @@ pub struct GeneralSettings {
     pub language: Option<String>,
     pub minimize_to_tray: Option<bool>,
     pub launch_at_login: Option<bool>,
+    /// What playback does when the app starts with a restored queue.
+    pub resume_on_launch: Option<ResumeOnLaunch>,
 
 
     // ===== Media Library · Auto Scan =====
@@ -25,8 +27,110 @@ pub struct GeneralSettings {
     pub scan_folders: Option<Vec<String>>,
     /// Minimal duration rule when scanning.
     pub scan_min_duration: Option<ScanMinDuration>,
-    /// File format rule when scanning.
+    /// File format rule when scanning. Superseded by `scan_extensions`, used while those are unset.
     pub scan_formats: Option<ScanFormats>,
+    /// File extensions scanned, each with its own toggle.
+    pub scan_extensions: Option<Vec<ScanExtension>>,
+    /// Quiet period in milliseconds before file system changes are scanned as one batch.
+    pub scan_debounce_ms: Option<u32>,
+    /// Times scheduled scans may run in; empty allows any time. Manual scans ignore them.
+    pub scan_windows: Option<Vec<ScanWindow>>,
+    /// Scan as soon as possible when a window was missed (e.g. the machine was asleep).
+    pub scan_catch_up: Option<bool>,
+    /// Folder levels the scanner descends below a scan folder; unset means no limit.
+    pub scan_max_depth: Option<u32>,
+    /// Whether the scanner and the folder watcher follow symbolic links.
+    pub scan_symlinks: Option<ScanSymlinkPolicy>,
+    /// Depth and symlink rules for individual scan folders.
+    pub scan_root_overrides: Option<Vec<ScanRootOverride>>,
+    /// Wildcard patterns of files and folders left out of scans, e.g. "**/node_modules/**", "*.part", "**/.*/**".
+    pub scan_exclude_patterns: Option<Vec<String>>,
+    /// Fill in missing tags from the file name and folders.
+    pub scan_filename_inference: Option<bool>,
+    /// File name patterns tried in order, e.g. "%track% - %artist% - %title%". Unset uses the built-in list.
+    pub scan_filename_patterns: Option<Vec<String>>,
+    /// File name patterns for individual folders.
+    pub scan_filename_overrides: Option<Vec<ScanPatternOverride>>,
+}
+
+/// Playback of the restored queue when the app starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "camelCase"))]
+pub enum ResumeOnLaunch {
+    /// Stay paused at the start of the current track.
+    Never,
+    /// Stay paused at the position playback stopped at.
+    #[default]
+    Paused,
+    /// Continue from that position if the app was playing when it was closed.
+    Playing,
+}
+
+/// How library scanning treats symbolic links.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "camelCase"))]
+pub enum ScanSymlinkPolicy {
+    /// Skip links to files and folders.
+    Never,
+    /// Follow links, entering each folder once so loops and duplicates are skipped.
+    #[default]
+    Safe,
+    /// Follow every link; only links back into a folder being scanned are skipped.
+    Always,
+}
+
+/// Scan rules for one scan folder, overriding the general ones.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "camelCase"))]
+pub struct ScanRootOverride {
+    /// Scan folder (or a folder inside one) the rules apply to. Absolute path.
+    pub root: String,
+    /// Unset keeps the general depth limit.
+    pub max_depth: Option<u32>,
+    /// Unset keeps the general symlink policy.
+    pub symlinks: Option<ScanSymlinkPolicy>,
+}
+
+/// File name patterns used instead of the general ones below one folder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "camelCase"))]
+pub struct ScanPatternOverride {
+    /// Folder the patterns apply to. Absolute path.
+    pub folder: String,
+    pub patterns: Vec<String>,
+}
+
+/// A recurring time window for scheduled library scans, e.g. daily from
+/// "02:00" to "05:00". A window ending at or before its start runs past
+/// midnight; equal start and end cover the whole day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "camelCase"))]
+pub struct ScanWindow {
+    /// Days the window starts on; empty means every day.
+    #[serde(default)]
+    pub days: Vec<ScanWeekday>,
+    /// Local start time, "HH:MM".
+    pub start: String,
+    /// Local end time, "HH:MM".
+    pub end: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "camelCase"))]
+pub enum ScanWeekday {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
 }
 
 /// Minimal duration rule for library scanning.
@@ -39,6 +143,16 @@ pub enum ScanMinDuration {
     All,
 }
 
+/// A file extension library scanning may pick up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "camelCase"))]
+pub struct ScanExtension {
+    /// Lowercase, without the dot, e.g. "flac".
+    pub extension: String,
+    pub enabled: bool,
+}
+
 /// File format filter for library scanning.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -1,3 +1,5 @@
+pub mod display;
 pub mod general;
 pub mod lyrics;
 pub mod music;
+pub mod queue;
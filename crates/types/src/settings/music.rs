@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "ts-rs")]
@@ -46,6 +48,73 @@ impl Default for MusicSourceSelection {
     }
 }
 
+/// Gain curve used when crossfading between two tracks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub enum CrossfadeCurve {
+    Linear,
+    /// Gain follows a decibel ramp, perceived as an even fade.
+    Logarithmic,
+    /// Sine/cosine gains keeping the summed power constant.
+    #[default]
+    EqualPower,
+}
+
+/// What to do with playback while a call or another communication app is active.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub enum InterruptionMode {
+    #[default]
+    Pause,
+    /// Keep playing at a lowered volume.
+    Duck,
+    /// Leave playback untouched.
+    Ignore,
+}
+
+/// Which ReplayGain value loudness normalization applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub enum NormalizationMode {
+    /// Every track is brought to the same loudness.
+    #[default]
+    Track,
+    /// One gain per album, preserving the loudness differences between its tracks.
+    Album,
+}
+
+/// Strength of the headphone crossfeed, which blends some of each channel into
+/// the other to soften hard-panned stereo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub enum CrossfeedLevel {
+    #[default]
+    Off,
+    Low,
+    Medium,
+    High,
+}
+
 /// Playback related preferences (kept minimal; extend as needed).
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -57,10 +126,41 @@ impl Default for MusicSourceSelection {
 pub struct MusicPlaybackSettings {
     /// Enable loudness normalization if supported by source.
     pub normalize: Option<bool>,
-    /// Crossfade duration in milliseconds.
+    /// Track or album gain; falls back to the other when missing (default track).
+    pub normalize_mode: Option<NormalizationMode>,
+    /// Crossfade duration in milliseconds (0 disables, at most 12000).
     pub crossfade_ms: Option<u32>,
+    /// Crossfade gain curve.
+    pub crossfade_curve: Option<CrossfadeCurve>,
+    /// Silence inserted between queue entries in milliseconds (at most 30000).
+    /// Ignored while a crossfade is set; the two are mutually exclusive.
+    pub track_gap_ms: Option<u32>,
     /// Prefer seamless (gapless) playback when possible.
     pub gapless: Option<bool>,
+    /// Extend a finished sequential queue with related tracks from media plugins.
+    pub radio_mode: Option<bool>,
+    /// Skip long silence at the start and end of local tracks (default false).
+    pub trim_silence: Option<bool>,
+    /// Upcoming tracks of the playing album to prepare ahead (0 disables, at most 5, default 2).
+    pub album_precache_depth: Option<u32>,
+    /// Name of the preferred audio output device; unset follows the system default.
+    pub output_device: Option<String>,
+    /// Reaction to incoming calls and communication sessions (default pause).
+    pub interruption_mode: Option<InterruptionMode>,
+    /// Volume while ducked, in percent of the playback volume (default 20).
+    pub interruption_duck_percent: Option<u32>,
+    /// Resume playback paused by a call once it ends (default true).
+    pub interruption_resume: Option<bool>,
+    /// Headphone crossfeed (default off).
+    pub crossfeed: Option<CrossfeedLevel>,
+    /// Playback position updates per second while an app window has focus (default 4).
+    pub position_update_hz: Option<f64>,
+    /// Playback position updates per second otherwise (default 0.5).
+    pub background_position_update_hz: Option<f64>,
+    /// Playback rate of music, pitch kept (0.5 - 3.0, default 1.0).
+    pub music_rate: Option<f32>,
+    /// Playback rate of podcast episodes and audiobooks (0.5 - 3.0, default 1.0).
+    pub spoken_rate: Option<f32>,
 }
 
 /// A single audio effect unit in the processing chain.
@@ -95,6 +195,361 @@ pub struct MusicEffectsSettings {
     pub chain: Vec<MusicEffectUnit>,
 }
 
+/// Action bound to a media key gesture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub enum MediaKeyAction {
+    /// Ignore the gesture.
+    None,
+    PlayPause,
+    Next,
+    Previous,
+    /// Voice/radio action, handled by the renderer.
+    Radio,
+}
+
+/// Headset/media key gesture preferences for the play/pause key.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub struct MusicMediaKeySettings {
+    /// Enable multi-press/long-press recognition (adds a short delay to single presses).
+    pub gestures_enabled: Option<bool>,
+    /// Max gap between presses counted as one gesture, in milliseconds.
+    pub multi_press_window_ms: Option<u32>,
+    /// Hold duration recognized as a long press, in milliseconds.
+    pub long_press_ms: Option<u32>,
+    pub double_press: Option<MediaKeyAction>,
+    pub triple_press: Option<MediaKeyAction>,
+    pub long_press: Option<MediaKeyAction>,
+}
+
+/// Audiobook mode: long tracks resume where they were left off and skip by
+/// fixed intervals.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub struct MusicAudiobookSettings {
+    /// Seconds skipped by skip forward (default 30).
+    pub skip_forward_secs: Option<f64>,
+    /// Seconds skipped by skip back (default 10).
+    pub skip_back_secs: Option<f64>,
+    /// Tracks at least this long, in minutes, resume where they were left off.
+    /// Tracks with chapters always do; 0 limits resuming to them (default 20).
+    pub resume_min_minutes: Option<f64>,
+}
+
+/// Identification of music playing in other apps, from a sample of the system
+/// output looked up on AcoustID.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub struct MusicIdentifySettings {
+    /// Allow recording the system output to identify it (default off).
+    pub enabled: Option<bool>,
+    /// AcoustID application key, needed for lookups.
+    pub acoustid_key: Option<String>,
+    /// Seconds recorded per identification (default 20).
+    pub sample_secs: Option<u32>,
+    /// Loopback device to record from; the default one when unset.
+    pub device: Option<String>,
+}
+
+/// Offline download preferences. The smart policy downloads online tracks the
+/// user keeps coming back to, within a storage budget.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub struct MusicDownloadSettings {
+    /// Automatically download favorites and heavy-rotation tracks.
+    pub smart_enabled: Option<bool>,
+    /// Download online tracks saved to the library.
+    pub include_favorites: Option<bool>,
+    /// Download online tracks played at least this many times (0 disables the rule).
+    pub min_play_count: Option<u32>,
+    /// Disk space downloads may use, in megabytes.
+    pub storage_budget_mb: Option<u64>,
+    /// Only download on unmetered connections.
+    pub wifi_only: Option<bool>,
+    /// Downloads running at the same time.
+    pub max_concurrent: Option<u32>,
+    /// Format and naming used for every provider without its own preferences.
+    pub defaults: Option<ProviderDownloadSettings>,
+    /// Per-provider overrides of `defaults`, keyed by plugin id.
+    pub providers: Option<HashMap<String, ProviderDownloadSettings>>,
+}
+
+/// Download format and naming preferences of a provider. Unset fields fall
+/// back to the download defaults.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub struct ProviderDownloadSettings {
+    /// Preferred container, e.g. "flac", "m4a" or "mp3".
+    pub container: Option<String>,
+    /// Preferred bitrate in kbps.
+    pub bitrate_kbps: Option<u32>,
+    /// File name relative to the download folder, without extension; `/` makes
+    /// folders. Placeholders: %artist%, %album%, %track%, %title%, %provider%, %id%.
+    pub filename_template: Option<String>,
+    /// Embed the cover art into the downloaded file.
+    pub embed_cover: Option<bool>,
+    /// Write the lyrics next to the downloaded file as `.lrc`.
+    pub export_lyrics: Option<bool>,
+}
+
+impl ProviderDownloadSettings {
+    /// `self` with unset fields taken from `defaults`
+    pub fn or(self, defaults: &ProviderDownloadSettings) -> Self {
+        Self {
+            container: self.container.or_else(|| defaults.container.clone()),
+            bitrate_kbps: self.bitrate_kbps.or(defaults.bitrate_kbps),
+            filename_template: self.filename_template.or_else(|| defaults.filename_template.clone()),
+            embed_cover: self.embed_cover.or(defaults.embed_cover),
+            export_lyrics: self.export_lyrics.or(defaults.export_lyrics),
+        }
+    }
+}
+
+/// API endpoint override of a provider, e.g. a self-hosted mirror.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub struct ProviderEndpointSettings {
+    /// Base URL used instead of the provider's default endpoint.
+    pub base_url: Option<String>,
+    /// Fall back to the default endpoint while the mirror fails its health check (default on).
+    pub fallback: Option<bool>,
+}
+
+/// Highest stream quality requested on cellular connections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub enum CellularStreamQuality {
+    Low,
+    #[default]
+    Medium,
+    High,
+    /// Same quality as on Wi-Fi.
+    Unrestricted,
+}
+
+/// Preferred stream quality; providers map it to their nearest offering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub enum StreamQuality {
+    Low,
+    Normal,
+    High,
+    Lossless,
+}
+
+/// Preferred stream quality by connection type. Unset lets the provider choose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub struct StreamQualityPolicy {
+    /// Wi-Fi, ethernet and unknown connections.
+    pub wifi: Option<StreamQuality>,
+    /// Metered connections (cellular unless reported otherwise).
+    pub metered: Option<StreamQuality>,
+}
+
+/// Stream quality preferences. A provider's own policy wins over the default
+/// one field by field; the cellular cap of `mobileData` still applies.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub struct MusicQualitySettings {
+    pub default: Option<StreamQualityPolicy>,
+    /// Per-provider policies, keyed by plugin id.
+    #[serde(default)]
+    pub providers: HashMap<String, StreamQualityPolicy>,
+}
+
+/// Buffering of streamed tracks on slow networks.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub struct MusicBufferingSettings {
+    /// Kilobytes downloaded before a stream starts playing (at most 16384);
+    /// unset keeps the defaults.
+    pub prefetch_kb: Option<u32>,
+    /// Kilobytes buffered ahead before playback resumes after a stall
+    /// (0 resumes at once, at most 16384, default 128).
+    pub rebuffer_kb: Option<u32>,
+    /// Lower the stream quality for the session when playback keeps stalling
+    /// (default on).
+    pub auto_downgrade: Option<bool>,
+    /// Stalls of one track within two minutes that lower the quality (default 3).
+    pub downgrade_after_stalls: Option<u32>,
+}
+
+/// Data saving rules applied while the connection is cellular.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub struct MobileDataSettings {
+    /// Apply the rules below on cellular connections (default on).
+    pub enabled: Option<bool>,
+    /// Stream quality cap.
+    pub stream_quality: Option<CellularStreamQuality>,
+    /// Allow downloads, including smart downloads (default off).
+    pub allow_downloads: Option<bool>,
+    /// Allow resolving upcoming streams ahead of playback (default off).
+    pub allow_prefetch: Option<bool>,
+    /// Fetch full-size artwork instead of thumbnails (default off).
+    pub high_res_artwork: Option<bool>,
+}
+
+/// Metadata enrichment from MusicBrainz and the Cover Art Archive.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub struct MusicEnrichmentSettings {
+    /// Look up covers of local tracks without one in the background after
+    /// scans (default off).
+    pub auto_enrich: Option<bool>,
+}
+
+/// Audio fingerprinting of local tracks, with Chromaprint and AcoustID.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub struct MusicFingerprintSettings {
+    /// Fingerprint local tracks after scans, so moved or re-ripped files keep
+    /// their history and playlists (default off).
+    pub enabled: Option<bool>,
+    /// AcoustID application key, needed for lookups.
+    pub acoustid_key: Option<String>,
+    /// Fill in missing titles, artists and albums from AcoustID while
+    /// fingerprinting (default off).
+    pub fix_tags: Option<bool>,
+}
+
+/// Playlists linked to playlists of online providers.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub struct MusicPlaylistSyncSettings {
+    /// Sync linked playlists in the background (default on).
+    pub enabled: Option<bool>,
+    /// Minutes between two background syncs (at least 5, default 30).
+    pub interval_minutes: Option<u32>,
+}
+
+/// Output settings switched together, e.g. "Speakers" and "Headphones".
+/// Unset fields are left as they are when the profile is applied.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub struct OutputProfile {
+    pub name: String,
+    /// Output device name; empty follows the system default.
+    pub device: Option<String>,
+    /// Playback volume from 0.0 to 1.0.
+    pub volume: Option<f32>,
+    /// Highest volume from 0.0 to 1.0 while the profile is active, e.g. for
+    /// sensitive headphones. Unlike the other fields, unset lifts the cap of
+    /// the previous profile.
+    pub volume_clamp: Option<f32>,
+    /// Crossfade duration in milliseconds (0 disables, at most 12000).
+    pub crossfade_ms: Option<u32>,
+    pub crossfade_curve: Option<CrossfadeCurve>,
+    pub crossfeed: Option<CrossfeedLevel>,
+    /// Effects chain (EQ preset) to switch to.
+    pub effects: Option<MusicEffectsSettings>,
+    /// Global shortcut applying the profile, e.g. "CmdOrCtrl+Alt+H".
+    pub hotkey: Option<String>,
+    /// Apply the profile when its device is connected (default off).
+    pub auto_switch: Option<bool>,
+}
+
+/// Named output profiles and the one last applied.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(
+    feature = "ts-rs",
+    derive(TS),
+    ts(export, export_to = "bindings.d.ts", rename_all = "camelCase")
+)]
+pub struct OutputProfileSettings {
+    #[serde(default)]
+    pub profiles: Vec<OutputProfile>,
+    pub active: Option<String>,
+}
+
 /// Root of the "music" settings domain.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -112,4 +567,28 @@ pub struct MusicSettings {
     pub playback: Option<MusicPlaybackSettings>,
     /// Effects chain configuration.
     pub effects: Option<MusicEffectsSettings>,
+    /// Media key gesture mappings.
+    pub media_keys: Option<MusicMediaKeySettings>,
+    /// Offline download preferences.
+    pub downloads: Option<MusicDownloadSettings>,
+    /// Audiobook mode: resume positions and skip intervals.
+    pub audiobook: Option<MusicAudiobookSettings>,
+    /// Identification of music playing in other apps.
+    pub identify: Option<MusicIdentifySettings>,
+    /// Provider endpoint overrides, keyed by plugin id.
+    pub endpoints: Option<HashMap<String, ProviderEndpointSettings>>,
+    /// Data saving on cellular connections.
+    pub mobile_data: Option<MobileDataSettings>,
+    /// Preferred stream quality per provider and connection type.
+    pub quality: Option<MusicQualitySettings>,
+    /// Prefetch and rebuffering of streams.
+    pub buffering: Option<MusicBufferingSettings>,
+    /// Output profiles switched by command, hotkey or device connection.
+    pub output_profiles: Option<OutputProfileSettings>,
+    /// Online metadata and artwork lookups.
+    pub enrichment: Option<MusicEnrichmentSettings>,
+    /// Audio fingerprinting and AcoustID lookups.
+    pub fingerprint: Option<MusicFingerprintSettings>,
+    /// Two-way sync of playlists linked to online providers.
+    pub playlist_sync: Option<MusicPlaylistSyncSettings>,
 }
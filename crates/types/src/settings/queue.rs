@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "ts-rs")]
+use ts_rs::TS;
+
+// Frontend-facing typed view for the "queue_settings" settings domain.
+// Stored under prefs.queue_settings.* via the settings service; the backend
+// reads individual fields with dotpath lookups and falls back to defaults.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "camelCase"))]
+pub struct QueueSettings {
+    /// How to handle a track that is already present in the play queue.
+    pub duplicate_policy: Option<QueueDuplicatePolicy>,
+    /// What double-click or Enter does on each kind of entity.
+    pub default_actions: Option<DefaultQueueActions>,
+}
+
+/// Duplicate handling when adding tracks to the play queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "camelCase"))]
+pub enum QueueDuplicatePolicy {
+    /// Always insert; the same track may appear several times.
+    Allow,
+    /// Silently keep the existing entry (legacy behavior).
+    #[default]
+    Skip,
+    /// Hold duplicates back and let the UI ask the user.
+    Ask,
+}
+
+/// What a queue action does with the tracks of an entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "camelCase"))]
+pub enum QueueAction {
+    /// Play the first track now, the others right after it.
+    #[default]
+    PlayNow,
+    /// Insert after the current track.
+    PlayNext,
+    /// Append to the end of the queue.
+    Append,
+    /// Replace the queue and keep it going with related tracks.
+    StartRadio,
+}
+
+/// Default action per entity type; unset ones play now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "camelCase"))]
+pub struct DefaultQueueActions {
+    pub track: Option<QueueAction>,
+    pub album: Option<QueueAction>,
+    pub artist: Option<QueueAction>,
+    pub playlist: Option<QueueAction>,
+}
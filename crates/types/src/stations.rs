@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "ts-rs")]
+use ts_rs::TS;
+
+use crate::tracks::{MediaContent, TrackType, Tracks};
+
+/// ID prefix of queue entries that are internet radio stations. Stations are
+/// played as endless URL (or HLS) streams: they cannot seek, and next/previous
+/// move to the neighbouring station of the queue.
+pub const STATION_ID_PREFIX: &str = "radio:";
+
+/// An internet radio station from the station directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts", rename_all = "camelCase"))]
+pub struct RadioStation {
+    /// Directory ID of the station
+    pub id: String,
+    pub name: String,
+    pub stream_url: String,
+    pub homepage: Option<String>,
+    pub favicon: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// ISO 3166-1 alpha-2
+    pub country_code: Option<String>,
+    pub language: Option<String>,
+    pub codec: Option<String>,
+    /// kbit/s, when known
+    pub bitrate: Option<u32>,
+    /// The stream is an HLS playlist rather than a plain (SHOUTcast/Icecast) stream
+    #[serde(default)]
+    pub hls: bool,
+}
+
+impl RadioStation {
+    /// The station as a queue entry
+    pub fn to_media_content(&self) -> MediaContent {
+        MediaContent {
+            track: Tracks {
+                _id: Some(format!("{}{}", STATION_ID_PREFIX, self.id)),
+                title: Some(self.name.clone()),
+                type_: if self.hls { TrackType::HLS } else { TrackType::URL },
+                playback_url: Some(self.stream_url.clone()),
+                url: self.homepage.clone(),
+                track_cover_path_high: self.favicon.clone(),
+                track_cover_path_low: self.favicon.clone(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+/// Whether the queue entry is an internet radio station
+pub fn is_station(track: &MediaContent) -> bool {
+    track
+        .track
+        ._id
+        .as_deref()
+        .is_some_and(|id| id.starts_with(STATION_ID_PREFIX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stations_are_recognized_by_their_id() {
+        let station = RadioStation {
+            id: "9617a958-0601-11e8-ae97-52543be04c81".to_string(),
+            name: "Radio Paradise".to_string(),
+            stream_url: "https://stream.radioparadise.com/mp3-192".to_string(),
+            ..Default::default()
+        };
+        let track = station.to_media_content();
+        assert!(is_station(&track));
+        assert_eq!(track.track.type_, TrackType::URL);
+        assert_eq!(track.track.playback_url.as_deref(), Some(station.stream_url.as_str()));
+
+        let hash = MediaContent {
+            track: Tracks { _id: Some("3f2a".to_string()), ..Default::default() },
+            ..Default::default()
+        };
+        assert!(!is_station(&hash));
+    }
+}
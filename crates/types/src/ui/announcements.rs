@@ -0,0 +1,166 @@
+// Announcement strings for screen readers and OS accessibility integrations.
+// The backend composes them once, in the active UI language, and attaches
+// them to the player events they describe so every window and platform
+// integration announces the same sentence.
+
+use crate::ui::player_details::PlayerMode;
+
+/// Language of the announcements, resolved from the UI language tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnnounceLocale {
+    #[default]
+    English,
+    Chinese,
+}
+
+impl AnnounceLocale {
+    /// Resolve a BCP-47 tag such as "zh-CN"; unsupported languages use English.
+    pub fn from_tag(tag: &str) -> Self {
+        match tag.split(['-', '_']).next().map(|l| l.to_ascii_lowercase()) {
+            Some(lang) if lang == "zh" => AnnounceLocale::Chinese,
+            _ => AnnounceLocale::English,
+        }
+    }
+}
+
+/// Composes announcement sentences in one locale
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Announcer {
+    locale: AnnounceLocale,
+}
+
+impl Announcer {
+    pub fn new(locale: AnnounceLocale) -> Self {
+        Self { locale }
+    }
+
+    pub fn locale(&self) -> AnnounceLocale {
+        self.locale
+    }
+
+    /// "Now playing: Title by Artist, 3 minutes 20 seconds"
+    pub fn now_playing(&self, title: &str, artist: Option<&str>, duration: Option<f64>) -> String {
+        let artist = artist.map(str::trim).filter(|a| !a.is_empty());
+        let duration = duration.filter(|d| *d >= 1.0).map(|d| self.duration(d));
+        match self.locale {
+            AnnounceLocale::English => {
+                let mut text = format!("Now playing: {}", title);
+                if let Some(artist) = artist {
+                    text.push_str(&format!(" by {}", artist));
+                }
+                if let Some(duration) = duration {
+                    text.push_str(&format!(", {}", duration));
+                }
+                text
+            }
+            AnnounceLocale::Chinese => {
+                let mut text = format!("正在播放：{}", title);
+                if let Some(artist) = artist {
+                    text.push_str(&format!("，{}", artist));
+                }
+                if let Some(duration) = duration {
+                    text.push_str(&format!("，时长 {}", duration));
+                }
+                text
+            }
+        }
+    }
+
+    pub fn playback(&self, playing: bool) -> String {
+        let text = match (self.locale, playing) {
+            (AnnounceLocale::English, true) => "Playing",
+            (AnnounceLocale::English, false) => "Paused",
+            (AnnounceLocale::Chinese, true) => "播放",
+            (AnnounceLocale::Chinese, false) => "已暂停",
+        };
+        text.to_string()
+    }
+
+    pub fn mode(&self, mode: PlayerMode) -> String {
+        let text = match (self.locale, mode) {
+            (AnnounceLocale::English, PlayerMode::Sequential) => "Play in order",
+            (AnnounceLocale::English, PlayerMode::Single) => "Repeat one",
+            (AnnounceLocale::English, PlayerMode::Shuffle) => "Shuffle",
+            (AnnounceLocale::English, PlayerMode::ListLoop) => "Repeat all",
+            (AnnounceLocale::English, PlayerMode::ShuffleAlbums) => "Shuffle albums",
+            (AnnounceLocale::Chinese, PlayerMode::Sequential) => "顺序播放",
+            (AnnounceLocale::Chinese, PlayerMode::Single) => "单曲循环",
+            (AnnounceLocale::Chinese, PlayerMode::Shuffle) => "随机播放",
+            (AnnounceLocale::Chinese, PlayerMode::ListLoop) => "列表循环",
+            (AnnounceLocale::Chinese, PlayerMode::ShuffleAlbums) => "专辑随机播放",
+        };
+        text.to_string()
+    }
+
+    /// `volume` ranges 0.0 - 1.0
+    pub fn volume(&self, volume: f32) -> String {
+        let percent = (volume.clamp(0.0, 1.0) * 100.0).round() as u32;
+        match self.locale {
+            AnnounceLocale::English => format!("Volume {}%", percent),
+            AnnounceLocale::Chinese => format!("音量 {}%", percent),
+        }
+    }
+
+    /// Spoken duration, e.g. "1 hour 3 minutes 20 seconds". Zero parts are
+    /// left out.
+    pub fn duration(&self, seconds: f64) -> String {
+        let total = seconds.max(0.0).round() as u64;
+        let (hours, minutes, secs) = (total / 3600, total / 60 % 60, total % 60);
+        let parts: Vec<String> = match self.locale {
+            AnnounceLocale::English => {
+                let unit = |n: u64, name: &str| format!("{} {}{}", n, name, if n == 1 { "" } else { "s" });
+                [(hours, "hour"), (minutes, "minute"), (secs, "second")]
+                    .iter()
+                    .filter(|(n, _)| *n > 0)
+                    .map(|(n, name)| unit(*n, name))
+                    .collect()
+            }
+            AnnounceLocale::Chinese => [(hours, "小时"), (minutes, "分"), (secs, "秒")]
+                .iter()
+                .filter(|(n, _)| *n > 0)
+                .map(|(n, name)| format!("{} {}", n, name))
+                .collect(),
+        };
+        if parts.is_empty() {
+            return match self.locale {
+                AnnounceLocale::English => "0 seconds".to_string(),
+                AnnounceLocale::Chinese => "0 秒".to_string(),
+            };
+        }
+        parts.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locale_follows_the_language_tag() {
+        assert_eq!(AnnounceLocale::from_tag("zh-CN"), AnnounceLocale::Chinese);
+        assert_eq!(AnnounceLocale::from_tag("zh_TW"), AnnounceLocale::Chinese);
+        assert_eq!(AnnounceLocale::from_tag("en-US"), AnnounceLocale::English);
+        assert_eq!(AnnounceLocale::from_tag("fr"), AnnounceLocale::English);
+    }
+
+    #[test]
+    fn now_playing_reads_title_artist_and_duration() {
+        let en = Announcer::new(AnnounceLocale::English);
+        assert_eq!(
+            en.now_playing("Song", Some("Band"), Some(200.0)),
+            "Now playing: Song by Band, 3 minutes 20 seconds"
+        );
+        assert_eq!(en.now_playing("Song", Some(" "), None), "Now playing: Song");
+
+        let zh = Announcer::new(AnnounceLocale::Chinese);
+        assert_eq!(zh.now_playing("歌", Some("乐队"), Some(61.0)), "正在播放：歌，乐队，时长 1 分 1 秒");
+    }
+
+    #[test]
+    fn durations_leave_out_zero_parts() {
+        let en = Announcer::new(AnnounceLocale::English);
+        assert_eq!(en.duration(3600.0), "1 hour");
+        assert_eq!(en.duration(3725.4), "1 hour 2 minutes 5 seconds");
+        assert_eq!(en.duration(0.2), "0 seconds");
+    }
+}
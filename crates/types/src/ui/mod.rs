@@ -1,2 +1,4 @@
+pub mod announcements;
 pub mod player_details;
+pub mod title_format;
 pub mod track_details;
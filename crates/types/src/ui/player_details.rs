@@ -9,11 +9,15 @@ use crate::errors::MusicError;
 #[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
 #[serde(rename_all = "UPPERCASE")]
 pub enum PlayerState {
+    /// Nothing is loaded (no current track)
+    #[default]
+    Idle,
     Playing,
     Paused,
-    #[default]
     Stopped,
     Loading,
+    /// The active backend failed; a new load is required to recover
+    Errored,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -23,6 +27,16 @@ pub enum PlayerEvents {
     Ended,
     Loading,
     TimeUpdate(f64),
+    /// Now playing title announced in-band by a radio stream
+    StreamTitle(String),
+    /// Progress in percent of buffering a stream, before it starts or after
+    /// a stall
+    Buffering(u8),
+    /// Playback caught up with the download of the stream
+    Stalled,
+    /// The server refused the stream URL with this HTTP status (401, 403 or
+    /// 410): it expired and must be resolved again
+    StreamExpired(u16),
 
     #[serde(
         deserialize_with = "deserialize_music_error",
@@ -39,6 +53,10 @@ impl Clone for PlayerEvents {
             PlayerEvents::Ended => PlayerEvents::Ended,
             PlayerEvents::Loading => PlayerEvents::Loading,
             PlayerEvents::TimeUpdate(time) => PlayerEvents::TimeUpdate(*time),
+            PlayerEvents::StreamTitle(title) => PlayerEvents::StreamTitle(title.clone()),
+            PlayerEvents::Buffering(percent) => PlayerEvents::Buffering(*percent),
+            PlayerEvents::Stalled => PlayerEvents::Stalled,
+            PlayerEvents::StreamExpired(status) => PlayerEvents::StreamExpired(*status),
             PlayerEvents::Error(error) => PlayerEvents::Error(error.to_string().clone().into()),
         }
     }
@@ -59,11 +77,16 @@ where
     Ok(MusicError::String(error_str))
 }
 
-#[derive(Debug, Default, Copy, Clone, Encode, Decode)]
+/// How the volume relates to the source of the playing track
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone, Encode, Decode, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
 pub enum VolumeMode {
+    /// One volume for everything
     #[default]
     Normal,
+    /// Each source remembers its own volume
     PersistSeparate,
+    /// One volume, capped per source by its clamp
     PersistClamp,
 }
 
@@ -75,4 +98,167 @@ pub enum PlayerMode {
     Single,
     Shuffle,
     ListLoop,
+    /// Whole albums in random order, each played through in queue order
+    ShuffleAlbums,
+}
+
+impl PlayerMode {
+    /// Whether the queue plays in a random order
+    pub fn is_shuffle(self) -> bool {
+        matches!(self, PlayerMode::Shuffle | PlayerMode::ShuffleAlbums)
+    }
+}
+
+/// Playback overrides attached to a single queue entry (not to the track), so
+/// the same track can be queued twice with different trims.
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+#[serde(rename_all = "camelCase")]
+pub struct QueueItemOverrides {
+    /// Seconds to skip at the start, e.g. an intro
+    pub start_offset: Option<f64>,
+    /// Position in seconds at which the entry is treated as finished
+    pub end_at: Option<f64>,
+}
+
+impl QueueItemOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.start_offset.is_none() && self.end_at.is_none()
+    }
+
+    pub fn validate(&self) -> Result<(), MusicError> {
+        if self.start_offset.is_some_and(|s| !s.is_finite() || s < 0.0) {
+            return Err(MusicError::String("Start offset must be a non-negative number of seconds".into()));
+        }
+        if let Some(end) = self.end_at {
+            if !end.is_finite() || end <= self.start_offset.unwrap_or(0.0) {
+                return Err(MusicError::String("End position must be after the start offset".into()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What happens to the audio inside an edit region
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+#[serde(rename_all = "camelCase")]
+pub enum EditRegionKind {
+    /// Jumped over; also removed from the displayed duration and timeline
+    #[default]
+    Skip,
+    /// Played silently
+    Mute,
+}
+
+/// Non-destructive edit of a track (e.g. a long spoken intro cut out),
+/// attached to the track itself and applied whenever it plays. Positions are
+/// seconds of the original audio.
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+#[serde(rename_all = "camelCase")]
+pub struct EditRegion {
+    pub start: f64,
+    pub end: f64,
+    #[serde(default)]
+    pub kind: EditRegionKind,
+}
+
+impl EditRegion {
+    pub fn validate(&self) -> Result<(), MusicError> {
+        if !self.start.is_finite() || self.start < 0.0 {
+            return Err(MusicError::String("Edit region start must be a non-negative number of seconds".into()));
+        }
+        if !self.end.is_finite() || self.end <= self.start {
+            return Err(MusicError::String("Edit region end must be after its start".into()));
+        }
+        Ok(())
+    }
+}
+
+/// Part of the loaded track played over and over (A-B repeat). Positions are
+/// seconds of the original audio.
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+#[serde(rename_all = "camelCase")]
+pub struct LoopRegion {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl LoopRegion {
+    pub fn validate(&self) -> Result<(), MusicError> {
+        if !self.start.is_finite() || self.start < 0.0 {
+            return Err(MusicError::String("Loop start must be a non-negative number of seconds".into()));
+        }
+        if !self.end.is_finite() || self.end <= self.start {
+            return Err(MusicError::String("Loop end must be after its start".into()));
+        }
+        Ok(())
+    }
+}
+
+/// Loudness analysis of a track (ReplayGain tags), in dB relative to the
+/// reference level; peaks are linear sample amplitudes (1.0 is full scale)
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+#[serde(rename_all = "camelCase")]
+pub struct TrackGain {
+    pub track_gain: Option<f64>,
+    pub track_peak: Option<f64>,
+    pub album_gain: Option<f64>,
+    pub album_peak: Option<f64>,
+}
+
+impl TrackGain {
+    pub fn is_empty(&self) -> bool {
+        self.track_gain.is_none() && self.album_gain.is_none()
+    }
+}
+
+/// Where the audio of a track starts and ends, in seconds of the original
+/// audio; what lies outside is silence
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+#[serde(rename_all = "camelCase")]
+pub struct TrackSilence {
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Gain normalization would apply to a track in each mode, shown before it is
+/// enabled. `None` when the track has no analysis.
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+#[serde(rename_all = "camelCase")]
+pub struct TrackGainPreview {
+    pub track_id: String,
+    pub album_id: Option<String>,
+    pub gain: TrackGain,
+    pub track_mode_db: Option<f64>,
+    pub album_mode_db: Option<f64>,
+}
+
+/// Chapter of a track (audiobooks, long mixes), read from its chapter tags.
+/// Times are in seconds; `end` is `None` for the last chapter when the file
+/// doesn't say.
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+#[serde(rename_all = "camelCase")]
+pub struct TrackChapter {
+    pub start: f64,
+    pub end: Option<f64>,
+    pub title: Option<String>,
+}
+
+/// Position bookmarked inside a track (a cue of a DJ mix, a song of a
+/// concert), in seconds of the original audio
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export, export_to = "bindings.d.ts"))]
+#[serde(rename_all = "camelCase")]
+pub struct TrackBookmark {
+    pub id: String,
+    pub track_id: String,
+    pub position: f64,
+    pub label: Option<String>,
 }
@@ -0,0 +1,289 @@
+// Title formatting service shared by every surface that renders track titles
+// (track lists, now playing, MPRIS/SMTC, tray, notifications).
+//
+// Template syntax:
+// - %field%      placeholder, see `Field` for the supported names
+// - [ ... ]      optional section, dropped when any placeholder inside is empty
+// - %% [[ ]]     literal '%', '[' and ']'
+
+use std::collections::HashMap;
+
+use crate::settings::display::{DisplayTemplates, DisplayView};
+use crate::tracks::MediaContent;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Title,
+    Artist,
+    Album,
+    AlbumArtist,
+    Year,
+    Genre,
+    TrackNo,
+    Duration,
+    Codec,
+    Bitrate,
+    Filename,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        let field = match name.to_ascii_lowercase().as_str() {
+            "title" => Field::Title,
+            "artist" => Field::Artist,
+            "album" => Field::Album,
+            "albumartist" | "album_artist" => Field::AlbumArtist,
+            "year" => Field::Year,
+            "genre" => Field::Genre,
+            "track" | "tracknumber" => Field::TrackNo,
+            "duration" | "length" => Field::Duration,
+            "codec" => Field::Codec,
+            "bitrate" => Field::Bitrate,
+            "filename" => Field::Filename,
+            _ => return None,
+        };
+        Some(field)
+    }
+
+    fn value(self, media: &MediaContent) -> Option<String> {
+        let track = &media.track;
+        let non_empty = |s: Option<String>| s.filter(|v| !v.trim().is_empty());
+        match self {
+            Field::Title => non_empty(track.title.clone()),
+            Field::Artist => non_empty(media.artists.as_ref().map(|artists| {
+                artists
+                    .iter()
+                    .filter_map(|a| a.artist_name.clone())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })),
+            Field::Album => non_empty(media.album.as_ref().and_then(|a| a.album_name.clone())),
+            Field::AlbumArtist => non_empty(media.album.as_ref().and_then(|a| a.album_artist.clone())),
+            Field::Year => non_empty(track.year.clone()),
+            Field::Genre => non_empty(media.genre.as_ref().map(|genres| {
+                genres
+                    .iter()
+                    .filter_map(|g| g.genre_name.clone())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })),
+            Field::TrackNo => track.track_no.filter(|n| *n > 0.0).map(|n| format!("{:02}", n as u32)),
+            Field::Duration => track.duration.filter(|d| *d > 0.0).map(|d| {
+                let secs = d.round() as u64;
+                format!("{}:{:02}", secs / 60, secs % 60)
+            }),
+            Field::Codec => non_empty(track.codec.clone()),
+            Field::Bitrate => track
+                .bitrate
+                .filter(|b| *b > 0.0)
+                .map(|b| format!("{} kbps", (b / 1000.0).round() as u64)),
+            Field::Filename => track.path.as_ref().and_then(|p| {
+                std::path::Path::new(p)
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(String),
+    Field(Field),
+    Optional(Vec<Token>),
+}
+
+/// A parsed title template. Parsing never fails: unknown placeholders and
+/// unbalanced brackets are kept as literal text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TitleTemplate {
+    tokens: Vec<Token>,
+}
+
+impl TitleTemplate {
+    pub fn parse(template: &str) -> Self {
+        let chars: Vec<char> = template.chars().collect();
+        let mut pos = 0;
+        let tokens = Self::parse_tokens(&chars, &mut pos, false);
+        Self { tokens }
+    }
+
+    fn parse_tokens(chars: &[char], pos: &mut usize, nested: bool) -> Vec<Token> {
+        let mut tokens = vec![];
+        let mut literal = String::new();
+        let flush = |literal: &mut String, tokens: &mut Vec<Token>| {
+            if !literal.is_empty() {
+                tokens.push(Token::Literal(std::mem::take(literal)));
+            }
+        };
+
+        while *pos < chars.len() {
+            let c = chars[*pos];
+            let next = chars.get(*pos + 1).copied();
+            match c {
+                '%' if next == Some('%') => {
+                    literal.push('%');
+                    *pos += 2;
+                }
+                '[' if next == Some('[') => {
+                    literal.push('[');
+                    *pos += 2;
+                }
+                ']' if next == Some(']') => {
+                    literal.push(']');
+                    *pos += 2;
+                }
+                '%' => {
+                    let end = chars[*pos + 1..].iter().position(|c| *c == '%');
+                    let field = end.and_then(|end| {
+                        let name: String = chars[*pos + 1..*pos + 1 + end].iter().collect();
+                        Field::parse(&name).map(|f| (f, end))
+                    });
+                    match field {
+                        Some((f, end)) => {
+                            flush(&mut literal, &mut tokens);
+                            tokens.push(Token::Field(f));
+                            *pos += end + 2;
+                        }
+                        None => {
+                            literal.push('%');
+                            *pos += 1;
+                        }
+                    }
+                }
+                '[' => {
+                    flush(&mut literal, &mut tokens);
+                    *pos += 1;
+                    let inner = Self::parse_tokens(chars, pos, true);
+                    tokens.push(Token::Optional(inner));
+                }
+                ']' if nested => {
+                    *pos += 1;
+                    flush(&mut literal, &mut tokens);
+                    return tokens;
+                }
+                _ => {
+                    literal.push(c);
+                    *pos += 1;
+                }
+            }
+        }
+        flush(&mut literal, &mut tokens);
+        tokens
+    }
+
+    /// Render the template for `media`. Returns an empty string only when the
+    /// template itself produces nothing.
+    pub fn render(&self, media: &MediaContent) -> String {
+        let mut out = String::new();
+        Self::render_tokens(&self.tokens, media, &mut out);
+        out.trim().to_string()
+    }
+
+    /// Returns false when a placeholder in `tokens` (outside nested optionals) was empty.
+    fn render_tokens(tokens: &[Token], media: &MediaContent, out: &mut String) -> bool {
+        let mut complete = true;
+        for token in tokens {
+            match token {
+                Token::Literal(s) => out.push_str(s),
+                Token::Field(f) => match f.value(media) {
+                    Some(v) => out.push_str(&v),
+                    None => complete = false,
+                },
+                Token::Optional(inner) => {
+                    let mut section = String::new();
+                    if Self::render_tokens(inner, media, &mut section) {
+                        out.push_str(&section);
+                    }
+                }
+            }
+        }
+        complete
+    }
+}
+
+/// Parsed templates for every view, rebuilt whenever prefs.display changes.
+#[derive(Debug, Clone)]
+pub struct TitleFormatter {
+    templates: HashMap<DisplayView, TitleTemplate>,
+}
+
+impl Default for TitleFormatter {
+    fn default() -> Self {
+        Self::new(&DisplayTemplates::default())
+    }
+}
+
+impl TitleFormatter {
+    pub fn new(templates: &DisplayTemplates) -> Self {
+        let templates = DisplayView::ALL
+            .iter()
+            .map(|view| (*view, TitleTemplate::parse(templates.template_for(*view))))
+            .collect();
+        Self { templates }
+    }
+
+    /// Display string for `media` in `view`. Falls back to the raw title (or file
+    /// name) so a misconfigured template never yields an empty label.
+    pub fn format(&self, view: DisplayView, media: &MediaContent) -> String {
+        let rendered = self
+            .templates
+            .get(&view)
+            .map(|t| t.render(media))
+            .unwrap_or_default();
+        if !rendered.is_empty() {
+            return rendered;
+        }
+        Field::Title
+            .value(media)
+            .or_else(|| Field::Filename.value(media))
+            .unwrap_or_default()
+    }
+
+    /// Display strings for every view, keyed by view.
+    pub fn format_all(&self, media: &MediaContent) -> HashMap<DisplayView, String> {
+        DisplayView::ALL
+            .iter()
+            .map(|view| (*view, self.format(*view, media)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::QueryableArtist;
+
+    fn media(title: &str, artist: &str, year: Option<&str>) -> MediaContent {
+        let mut m = MediaContent::default();
+        m.track.title = Some(title.to_string());
+        m.track.year = year.map(|y| y.to_string());
+        m.artists = Some(vec![QueryableArtist {
+            artist_name: Some(artist.to_string()),
+            ..Default::default()
+        }]);
+        m
+    }
+
+    #[test]
+    fn renders_fields_and_optional_sections() {
+        let t = TitleTemplate::parse("%artist% — %title%[ (%year%)]");
+        assert_eq!(t.render(&media("Song", "Band", Some("1999"))), "Band — Song (1999)");
+        assert_eq!(t.render(&media("Song", "Band", None)), "Band — Song");
+    }
+
+    #[test]
+    fn unknown_placeholders_and_escapes_are_literal() {
+        let t = TitleTemplate::parse("100%% %nope% [[x]]");
+        assert_eq!(t.render(&media("Song", "Band", None)), "100% %nope% [x]");
+    }
+
+    #[test]
+    fn empty_render_falls_back_to_title() {
+        let formatter = TitleFormatter::new(&DisplayTemplates {
+            tray: Some("[%year%]".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(formatter.format(DisplayView::Tray, &media("Song", "Band", None)), "Song");
+    }
+}
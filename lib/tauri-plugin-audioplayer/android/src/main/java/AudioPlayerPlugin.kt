@@ -2,11 +2,13 @@ package app.kieran.audioplayer
 
 import android.Manifest
 import android.app.Activity
+import android.os.Bundle
 import android.support.v4.media.session.MediaSessionCompat
 import android.util.Log
 import android.webkit.WebView
 import app.kieran.audioplayer.models.MetadataArgs
 import app.kieran.audioplayer.models.Track
+import app.kieran.audioplayer.services.Constants
 import app.kieran.audioplayer.services.interfaces.MediaPlayerCallbacks
 import app.tauri.annotation.Command
 import app.tauri.annotation.InvokeArg
@@ -147,6 +149,14 @@ class AudioPlayerPlugin(private val activity: Activity): Plugin(activity) {
                 ret.put("event", "onSkipToPrevious")
                 this@AudioPlayerPlugin.channel?.send(ret)
             }
+
+            override fun onCustomAction(action: String?, extras: Bundle?) {
+                if (action != Constants.ACTION_AUDIO_FOCUS_CHANGE) return
+                val ret = JSObject()
+                ret.put("event", "onAudioFocusChange")
+                ret.put("focus", extras?.getString(Constants.EXTRA_AUDIO_FOCUS))
+                this@AudioPlayerPlugin.channel?.send(ret)
+            }
         })
     }
 
@@ -4,4 +4,6 @@ object Constants {
     const val ACTION_FROM_MAIN_ACTIVITY = "from_main_activity"
     const val NOTIFICATION_CHANNEL_ID = "now_playing_media"
     const val NOTIFICATION_ID = 0xb339
+    const val ACTION_AUDIO_FOCUS_CHANGE = "audio_focus_change"
+    const val EXTRA_AUDIO_FOCUS = "focus"
 }
\ No newline at end of file
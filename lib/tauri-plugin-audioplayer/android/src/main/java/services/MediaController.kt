@@ -4,6 +4,7 @@ import android.content.Context
 import android.media.AudioAttributes
 import android.media.AudioFocusRequest
 import android.media.AudioManager
+import android.os.Bundle
 import android.support.v4.media.session.MediaSessionCompat
 import android.util.Log
 import app.kieran.audioplayer.R
@@ -60,6 +61,20 @@ class MediaController(private val mContext: Context) {
         }
     }
 
+    // Calls take the focus transiently; the backend pauses or ducks per the user's policy
+    private fun handleAudioFocusChange(change: Int) {
+        val focus = when (change) {
+            AudioManager.AUDIOFOCUS_GAIN -> "gain"
+            AudioManager.AUDIOFOCUS_LOSS -> "loss"
+            AudioManager.AUDIOFOCUS_LOSS_TRANSIENT -> "lossTransient"
+            else -> return
+        }
+        Log.d("TAG", "onAudioFocusChange: $focus")
+        val extras = Bundle()
+        extras.putString(Constants.EXTRA_AUDIO_FOCUS, focus)
+        emitInAllMediaSessionCallbacks { it.onCustomAction(Constants.ACTION_AUDIO_FOCUS_CHANGE, extras) }
+    }
+
     init {
         val audioManager = mContext.applicationContext.getSystemService(Context.AUDIO_SERVICE) as AudioManager
         val audioFocusRequest = AudioFocusRequest.Builder(AudioManager.AUDIOFOCUS_GAIN).setAudioAttributes(
@@ -67,7 +82,7 @@ class MediaController(private val mContext: Context) {
                 .setUsage(AudioAttributes.USAGE_MEDIA)
                 .setContentType(AudioAttributes.CONTENT_TYPE_MUSIC)
                 .build()
-        ).build()
+        ).setOnAudioFocusChangeListener { handleAudioFocusChange(it) }.build()
 
         val result = audioManager.requestAudioFocus(audioFocusRequest)
 
@@ -0,0 +1,142 @@
+//! Default queue actions: what double-click or Enter does on a track, album,
+//! artist or playlist, configured per entity type in
+//! `prefs.queue_settings.defaultActions` and applied here so every surface
+//! behaves the same.
+
+use audio_player::AudioPlayer;
+use database::database::Database;
+use macros::command_envelope;
+use serde::Deserialize;
+use serde_json::json;
+use settings::settings::SettingsConfig;
+use tauri::{AppHandle, Manager, State};
+use types::entities::{QueryableAlbum, QueryableArtist, QueryablePlaylist};
+use types::errors::{MusicError, Result};
+use types::settings::queue::{DefaultQueueActions, QueueAction};
+use types::tracks::{GetTrackOptions, MediaContent};
+
+/// Something the user can activate in a list
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum QueueEntity {
+    /// Library or provider track, as shown
+    Track { track: MediaContent },
+    #[serde(rename_all = "camelCase")]
+    Album { album_id: String },
+    #[serde(rename_all = "camelCase")]
+    Artist { artist_id: String },
+    #[serde(rename_all = "camelCase")]
+    Playlist { playlist_id: String },
+}
+
+fn configured_action(settings: &SettingsConfig, entity: &QueueEntity) -> QueueAction {
+    let actions = settings
+        .load_selective::<DefaultQueueActions>("queue_settings.defaultActions".to_string())
+        .unwrap_or_default();
+    match entity {
+        QueueEntity::Track { .. } => actions.track,
+        QueueEntity::Album { .. } => actions.album,
+        QueueEntity::Artist { .. } => actions.artist,
+        QueueEntity::Playlist { .. } => actions.playlist,
+    }
+    .unwrap_or_default()
+}
+
+/// Library tracks of the entity, in album, artist or playlist order
+fn entity_tracks(database: &Database, entity: QueueEntity) -> Result<Vec<MediaContent>> {
+    let options = match entity {
+        QueueEntity::Track { track } => return Ok(vec![track]),
+        QueueEntity::Album { album_id } => GetTrackOptions {
+            album: Some(QueryableAlbum { album_id: Some(album_id), ..Default::default() }),
+            ..Default::default()
+        },
+        QueueEntity::Artist { artist_id } => GetTrackOptions {
+            artist: Some(QueryableArtist { artist_id: Some(artist_id), ..Default::default() }),
+            ..Default::default()
+        },
+        QueueEntity::Playlist { playlist_id } => GetTrackOptions {
+            playlist: Some(QueryablePlaylist { playlist_id: Some(playlist_id), ..Default::default() }),
+            ..Default::default()
+        },
+    };
+    database.get_tracks_by_options(options)
+}
+
+/// Apply `action` to `tracks` in the player and notify the UI
+pub async fn apply_queue_action(app: &AppHandle, action: QueueAction, mut tracks: Vec<MediaContent>) -> Result<()> {
+    if tracks.is_empty() {
+        return Ok(());
+    }
+    let player: State<'_, AudioPlayer> = app.state();
+    let store_arc = player.get_store();
+    match action {
+        QueueAction::PlayNow | QueueAction::StartRadio => {
+            if action == QueueAction::StartRadio {
+                let mut store = store_arc
+                    .lock()
+                    .map_err(|_| MusicError::from("Failed to access player store"))?;
+                store.clear_queue();
+                store.set_radio_mode(true);
+            }
+            let mut first = tracks.remove(0);
+            player.audio_play(Some(&mut first)).await?;
+            let _ = crate::windowing::emit_audio_event(app, json!({ "type": "TrackChanged", "data": { "track": first } }));
+            let mut store = store_arc
+                .lock()
+                .map_err(|_| MusicError::from("Failed to access player store"))?;
+            // Each lands right after the current track, so go backwards
+            for track in tracks.into_iter().rev() {
+                store.play_next(track);
+            }
+            let _ = crate::windowing::emit_audio_event(app, super::queue_changed_event(&player, &store));
+            if action == QueueAction::StartRadio {
+                let _ = crate::windowing::emit_audio_event(
+                    app,
+                    json!({ "type": "RadioModeChanged", "data": { "enabled": true } }),
+                );
+            }
+        }
+        QueueAction::PlayNext => {
+            let mut store = store_arc
+                .lock()
+                .map_err(|_| MusicError::from("Failed to access player store"))?;
+            for track in tracks.into_iter().rev() {
+                store.play_next(track);
+            }
+            let _ = crate::windowing::emit_audio_event(app, super::queue_changed_event(&player, &store));
+        }
+        QueueAction::Append => {
+            let mut store = store_arc
+                .lock()
+                .map_err(|_| MusicError::from("Failed to access player store"))?;
+            let pending = store.add_to_queue(tracks);
+            let _ = crate::windowing::emit_audio_event(app, super::queue_changed_event(&player, &store));
+            if !pending.is_empty() {
+                let _ = crate::windowing::emit_audio_event(
+                    app,
+                    json!({ "type": "QueueDuplicatesPending", "data": { "tracks": pending } }),
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+command_envelope! {
+    /// Do what the user configured for activating `entity`, or `action` when
+    /// given. Returns the action performed.
+    #[tracing::instrument(level = "debug", skip(app, settings, database))]
+    #[tauri::command]
+    pub async fn perform_default_action(
+        app: AppHandle,
+        settings: State<'_, SettingsConfig>,
+        database: State<'_, Database>,
+        entity: QueueEntity,
+        action: Option<QueueAction>,
+    ) -> Result<QueueAction> {
+        let action = action.unwrap_or_else(|| configured_action(&settings, &entity));
+        let tracks = entity_tracks(&database, entity)?;
+        apply_queue_action(&app, action, tracks).await?;
+        Ok(action)
+    }
+}
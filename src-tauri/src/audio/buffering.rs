@@ -0,0 +1,90 @@
+//! Stream buffering: pushes `prefs.music.buffering` into the player and, when
+//! the playing track keeps stalling, lowers the stream quality for the session
+//! and re-resolves the track through the quality policy.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use ::settings::settings::SettingsConfig;
+use audio_player::buffering::BufferConfig;
+use audio_player::AudioPlayer;
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use types::settings::music::MusicBufferingSettings;
+
+use super::quality;
+
+/// Stalls older than this don't count towards a downgrade
+const STALL_WINDOW: Duration = Duration::from_secs(120);
+const DEFAULT_DOWNGRADE_AFTER: u32 = 3;
+
+/// Recent stalls of the playing track
+static STALLS: Mutex<Vec<Instant>> = Mutex::new(Vec::new());
+
+fn load_buffering_settings(app: &AppHandle) -> MusicBufferingSettings {
+    app.state::<SettingsConfig>()
+        .load_selective::<MusicBufferingSettings>("music.buffering".to_string())
+        .unwrap_or_default()
+}
+
+/// Push buffering preferences (prefs.music.buffering) into the player. They
+/// apply to streams opened afterwards.
+pub fn apply_buffering_settings(app: &AppHandle) {
+    audio_player::buffering::set_config(BufferConfig::from(&load_buffering_settings(app)));
+}
+
+/// A new track is loading: its stalls start over
+pub fn on_track_loading() {
+    STALLS.lock().unwrap().clear();
+}
+
+/// Playback of the current track stalled. After `downgradeAfterStalls`
+/// stalls within two minutes, the quality is lowered and the track reloaded.
+pub fn on_stall(app: &AppHandle) {
+    let settings = load_buffering_settings(app);
+    if !settings.auto_downgrade.unwrap_or(true) {
+        return;
+    }
+    let threshold = settings.downgrade_after_stalls.unwrap_or(DEFAULT_DOWNGRADE_AFTER).max(1) as usize;
+    {
+        let mut stalls = STALLS.lock().unwrap();
+        let now = Instant::now();
+        stalls.retain(|stall| now.duration_since(*stall) < STALL_WINDOW);
+        stalls.push(now);
+        if stalls.len() < threshold {
+            return;
+        }
+        stalls.clear();
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = downgrade(&app).await {
+            tracing::warn!("Failed to lower the quality of a stalling stream: {:?}", e);
+        }
+    });
+}
+
+async fn downgrade(app: &AppHandle) -> types::errors::Result<()> {
+    let track = app
+        .state::<AudioPlayer>()
+        .get_store()
+        .lock()
+        .ok()
+        .and_then(|store| store.get_current_track());
+    let Some(plugin_id) = track.and_then(|t| t.track.provider_extension) else {
+        return Ok(());
+    };
+    let Some(quality) = quality::lower_session_cap(app, &plugin_id) else {
+        tracing::info!("Stream keeps stalling at the lowest quality");
+        return Ok(());
+    };
+    tracing::info!("Stream keeps stalling, lowering the quality to {:?}", quality);
+    if quality::reload_current_stream(app, None).await? {
+        let _ = crate::windowing::emit_audio_event(
+            app,
+            json!({ "type": "QualityChanged", "data": { "quality": quality, "automatic": true } }),
+        );
+    }
+    Ok(())
+}
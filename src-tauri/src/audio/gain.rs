@@ -0,0 +1,73 @@
+//! Loudness analysis for normalization: ReplayGain tags of local files are
+//! read once and cached in the database, where the player picks them up when
+//! a track loads.
+
+use std::path::Path;
+
+use audio_player::normalize::applied_gain_db;
+use database::database::Database;
+use types::{
+    settings::music::NormalizationMode,
+    tracks::{MediaContent, TrackType},
+    ui::player_details::{TrackGain, TrackGainPreview},
+};
+
+/// ReplayGain tags of a local track; empty for streams and untagged files
+fn read_gain(track: &MediaContent) -> TrackGain {
+    if track.track.type_ != TrackType::LOCAL {
+        return TrackGain::default();
+    }
+    track
+        .track
+        .path
+        .as_deref()
+        .and_then(|path| file_scanner::read_replay_gain(Path::new(path)))
+        .unwrap_or_default()
+}
+
+/// Read and cache the analysis of freshly stored tracks
+pub fn cache_track_gains(database: &Database, tracks: &[MediaContent]) {
+    let gains: Vec<(String, TrackGain)> = tracks
+        .iter()
+        .filter_map(|t| Some((t.track._id.clone()?, read_gain(t))))
+        .collect();
+    if gains.is_empty() {
+        return;
+    }
+    if let Err(e) = database.set_track_gains(&gains) {
+        tracing::warn!("Failed to cache the gain of {} tracks: {:?}", gains.len(), e);
+    }
+}
+
+/// Cached analysis of `track`, read from its tags (and cached) the first time.
+/// The flag tells whether it was just read.
+pub fn resolve_track_gain(database: &Database, track: &MediaContent) -> (TrackGain, bool) {
+    let Some(track_id) = track.track._id.as_deref() else {
+        return (read_gain(track), false);
+    };
+    match database.get_track_gain(track_id) {
+        Ok(Some(gain)) => (gain, false),
+        Ok(None) => {
+            let gain = read_gain(track);
+            if let Err(e) = database.set_track_gains(&[(track_id.to_string(), gain)]) {
+                tracing::warn!("Failed to cache the gain of {}: {:?}", track_id, e);
+            }
+            (gain, true)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to read the cached gain of {}: {:?}", track_id, e);
+            (read_gain(track), false)
+        }
+    }
+}
+
+/// Gain applied to `track` in either normalization mode
+pub fn preview(track_id: String, track: &MediaContent, gain: TrackGain) -> TrackGainPreview {
+    TrackGainPreview {
+        track_id,
+        album_id: track.album.as_ref().and_then(|a| a.album_id.clone()),
+        track_mode_db: applied_gain_db(&gain, NormalizationMode::Track),
+        album_mode_db: applied_gain_db(&gain, NormalizationMode::Album),
+        gain,
+    }
+}
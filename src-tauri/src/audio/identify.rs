@@ -0,0 +1,281 @@
+//! Identify music playing in another app or a livestream. With
+//! `music.identify.enabled` a sample of the system output is recorded from a
+//! loopback device, fingerprinted with Chromaprint and looked up on AcoustID;
+//! each recording found comes with the library and provider tracks of the
+//! same title and artist, ready to be played, added or liked. AcoustID
+//! indexes whole recordings, so a sample taken near the start of a song
+//! matches best.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use ::settings::settings::SettingsConfig;
+use database::database::Database;
+use macros::command_envelope;
+use music_plugin_sdk::types::{SearchQuery, SearchType};
+use serde::Deserialize;
+use tauri::{AppHandle, Manager};
+use tokio::time::timeout;
+use types::entities::IdentifiedRecording;
+use types::errors::{error_helpers, MusicError, Result};
+use types::settings::music::{MusicIdentifySettings, MusicSourceSelection};
+use types::tracks::{GetTrackOptions, MediaContent, SearchableTrack};
+
+use crate::plugins::manager::PluginHandler;
+
+use super::radio::to_media_content;
+
+const ACOUSTID_URL: &str = "https://api.acoustid.org/v2/lookup";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+/// Per-provider time limit for the search of an identified recording
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(8);
+
+const DEFAULT_SAMPLE_SECS: u32 = 20;
+/// Chromaprint needs a few seconds; AcoustID gains little past a minute
+const MIN_SAMPLE_SECS: u32 = 8;
+const MAX_SAMPLE_SECS: u32 = 60;
+/// Lowest AcoustID score (0-1) reported
+const MIN_SCORE: f64 = 0.5;
+/// Recordings reported at most
+const MAX_RECORDINGS: usize = 5;
+/// Tracks searched per provider for each recording
+const MATCHES_PER_PROVIDER: usize = 3;
+
+#[derive(Debug, Clone, Deserialize)]
+struct LookupResponse {
+    #[serde(default)]
+    results: Vec<LookupResult>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct LookupResult {
+    score: f64,
+    #[serde(default)]
+    recordings: Vec<Recording>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Recording {
+    id: String,
+    title: Option<String>,
+    #[serde(default)]
+    artists: Vec<Artist>,
+    #[serde(default)]
+    releasegroups: Vec<ReleaseGroup>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Artist {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ReleaseGroup {
+    title: String,
+}
+
+fn identify_settings(app: &AppHandle) -> MusicIdentifySettings {
+    app.state::<SettingsConfig>()
+        .load_selective::<MusicIdentifySettings>("music.identify".to_string())
+        .unwrap_or_default()
+}
+
+/// Recordings of the AcoustID results, best score first, each recording once
+fn recordings(response: LookupResponse) -> Vec<IdentifiedRecording> {
+    let mut found: Vec<IdentifiedRecording> = Vec::new();
+    let mut results = response.results;
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    for result in results.into_iter().filter(|r| r.score >= MIN_SCORE) {
+        for recording in result.recordings {
+            if found.iter().any(|f| f.recording_id == recording.id) {
+                continue;
+            }
+            found.push(IdentifiedRecording {
+                recording_id: recording.id,
+                title: recording.title,
+                artists: recording.artists.into_iter().map(|a| a.name).collect(),
+                album: recording.releasegroups.into_iter().next().map(|g| g.title),
+                score: result.score,
+                matches: Vec::new(),
+            });
+        }
+    }
+    found.truncate(MAX_RECORDINGS);
+    found
+}
+
+async fn lookup(key: &str, sample: &audio_player::loopback::LoopbackSample) -> Result<Vec<IdentifiedRecording>> {
+    let duration = (sample.duration.round() as u64).to_string();
+    let body = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(error_helpers::to_network_error)?
+        .post(ACOUSTID_URL)
+        .form(&[
+            ("client", key),
+            ("meta", "recordings releasegroups"),
+            ("duration", duration.as_str()),
+            ("fingerprint", sample.fingerprint.as_str()),
+        ])
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(error_helpers::to_network_error)?
+        .bytes()
+        .await
+        .map_err(error_helpers::to_network_error)?;
+    let response: LookupResponse = serde_json::from_slice(&body).map_err(error_helpers::to_parse_error)?;
+    Ok(recordings(response))
+}
+
+fn same_text(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+/// Whether `track` is `recording`: same title, and an artist in common when
+/// both name any
+fn is_recording(track: &MediaContent, recording: &IdentifiedRecording) -> bool {
+    let (Some(title), Some(wanted)) = (track.track.title.as_deref(), recording.title.as_deref()) else {
+        return false;
+    };
+    if !same_text(title, wanted) {
+        return false;
+    }
+    let artists: Vec<&str> = track
+        .artists
+        .iter()
+        .flatten()
+        .filter_map(|a| a.artist_name.as_deref())
+        .collect();
+    artists.is_empty()
+        || recording.artists.is_empty()
+        || artists.iter().any(|a| recording.artists.iter().any(|r| same_text(a, r)))
+}
+
+fn library_matches(database: &Database, recording: &IdentifiedRecording) -> Vec<MediaContent> {
+    let Some(title) = recording.title.clone() else {
+        return Vec::new();
+    };
+    database
+        .get_tracks_by_options(GetTrackOptions {
+            track: Some(SearchableTrack {
+                title: Some(title),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|t| is_recording(t, recording))
+        .collect()
+}
+
+async fn provider_matches(app: &AppHandle, recording: &IdentifiedRecording) -> Vec<MediaContent> {
+    let Some(title) = recording.title.as_deref() else {
+        return Vec::new();
+    };
+    let query = SearchQuery {
+        query: match recording.artists.first() {
+            Some(artist) => format!("{} {}", artist, title),
+            None => title.to_string(),
+        },
+        types: vec![SearchType::Track],
+        page: None,
+        per_type_page: None,
+        sort: None,
+        per_type_sort: None,
+        filters: HashMap::new(),
+        provider_params: HashMap::new(),
+    };
+    let providers = match app
+        .state::<PluginHandler>()
+        .plugin_manager()
+        .get_audio_providers_by_selection(&MusicSourceSelection::default())
+        .await
+    {
+        Ok(providers) => providers,
+        Err(e) => {
+            tracing::warn!("Identify: failed to get audio providers: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let searches = providers.into_iter().map(|(provider_id, provider)| {
+        let query = query.clone();
+        async move {
+            let search = async {
+                let plugin = provider.lock().await;
+                plugin.search(&query).await
+            };
+            match timeout(SEARCH_TIMEOUT, search).await {
+                Ok(Ok(result)) => result.tracks.items,
+                Ok(Err(e)) => {
+                    tracing::debug!("Identify: provider {} search failed: {}", provider_id, e);
+                    Vec::new()
+                }
+                Err(_) => {
+                    tracing::debug!("Identify: provider {} timed out", provider_id);
+                    Vec::new()
+                }
+            }
+        }
+    });
+    futures::future::join_all(searches)
+        .await
+        .into_iter()
+        .flat_map(|tracks| {
+            tracks
+                .into_iter()
+                .map(to_media_content)
+                .filter(|t| is_recording(t, recording))
+                .take(MATCHES_PER_PROVIDER)
+        })
+        .collect()
+}
+
+command_envelope! {
+    /// Record `seconds` (else `music.identify.sampleSecs`, 20) of the system
+    /// output and identify the music in it. Returns the recordings AcoustID
+    /// knows it as, best match first, with the tracks they can be played
+    /// from; empty when AcoustID knows none.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command]
+    pub async fn identify_loopback_audio(app: AppHandle, seconds: Option<u32>) -> Result<Vec<IdentifiedRecording>> {
+        let settings = identify_settings(&app);
+        if !settings.enabled.unwrap_or(false) {
+            return Err(MusicError::String("Identifying the system output is turned off".to_string()));
+        }
+        let Some(key) = settings.acoustid_key.as_deref().map(str::trim).filter(|k| !k.is_empty()) else {
+            return Err(MusicError::String("An AcoustID key is needed to identify music".to_string()));
+        };
+        let seconds = seconds
+            .or(settings.sample_secs)
+            .unwrap_or(DEFAULT_SAMPLE_SECS)
+            .clamp(MIN_SAMPLE_SECS, MAX_SAMPLE_SECS);
+
+        let device = settings.device.clone();
+        let sample = tauri::async_runtime::spawn_blocking(move || {
+            audio_player::loopback::capture_sample(device.as_deref(), seconds)
+        })
+        .await
+        .map_err(|e| MusicError::String(format!("Loopback capture failed: {}", e)))??;
+        tracing::debug!("Captured {:.1}s from {}", sample.duration, sample.device);
+
+        let mut found = lookup(key, &sample).await?;
+        for recording in found.iter_mut() {
+            let mut matches = library_matches(&app.state::<Database>(), recording);
+            matches.extend(provider_matches(&app, recording).await);
+            recording.matches = matches;
+        }
+        Ok(found)
+    }
+}
+
+command_envelope! {
+    /// Devices the system output can be recorded from
+    #[tracing::instrument(level = "debug")]
+    #[tauri::command(async)]
+    pub fn list_loopback_devices() -> Result<Vec<String>> {
+        Ok(audio_player::loopback::loopback_device_names())
+    }
+}
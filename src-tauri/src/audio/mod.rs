@@ -1,13 +1,88 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
-use tauri::{AppHandle, Emitter, Manager, State};
-use types::errors::Result;
+use macros::command_envelope;
+use tauri::{AppHandle, Manager, State};
+use types::errors::{CommandResponse, Result};
 use audio_player::AudioPlayer;
+use audio_player::queue_metrics::QueueMetrics;
+use audio_player::store::PlayerStore;
+use types::ui::player_details::{PlayerEvents, VolumeMode};
+use crate::diagnostics::watchdog::Watchdog;
 use crate::playback::spotify::make_librespot_adapter;
 use database::database::Database;
+use ::settings::settings::SettingsConfig;
 use serde_json::json;
-use crate::plugins::manager::PluginHandler;
-use music_plugin_sdk::types::media::{ StreamRequest, StreamFormatPreference, QualityPreference };
+use music_plugin_sdk::types::media::{ StreamRequest, StreamSource };
+
+pub mod actions;
+pub mod buffering;
+pub mod gain;
+pub mod identify;
+pub mod mood;
+mod position;
+mod precache;
+pub mod profiles;
+pub mod quality;
+pub mod queue_history;
+pub mod rate;
+pub mod refresh;
+pub(crate) mod radio;
+pub mod resolver;
+pub mod silence;
+
+pub use position::PositionThrottle;
+pub use precache::PrecacheState;
+pub use resolver::StreamResolver;
+
+/// Set at startup when the resume-on-launch policy asks to continue playing
+static RESUME_ON_LAUNCH: AtomicBool = AtomicBool::new(false);
+
+/// Play queue with its computed metrics, as returned by `get_queue`
+#[derive(serde::Serialize)]
+pub struct QueueSnapshot {
+    #[serde(flatten)]
+    pub queue: audio_player::store::Queue,
+    pub metrics: QueueMetrics,
+}
+
+/// Queue metrics as of now
+pub(crate) fn queue_metrics(player: &AudioPlayer, store: &PlayerStore) -> QueueMetrics {
+    store.queue_metrics(&player.queue_timing(), chrono::Utc::now().timestamp_millis())
+}
+
+/// `QueueChanged` event carrying the queue metrics, for callers holding the store lock
+pub(crate) fn queue_changed_event(player: &AudioPlayer, store: &PlayerStore) -> serde_json::Value {
+    json!({ "type": "QueueChanged", "data": { "metrics": queue_metrics(player, store) } })
+}
+
+/// Emit `QueueChanged`, locking the store to compute the metrics
+fn emit_queue_changed(app: &AppHandle, player: &AudioPlayer) {
+    let event = match player.get_store().lock() {
+        Ok(store) => queue_changed_event(player, &store),
+        Err(_) => json!({ "type": "QueueChanged", "data": {} }),
+    };
+    let _ = crate::windowing::emit_audio_event(app, event);
+}
+
+/// Ask the enabled media providers for a stream of `track_id` along the
+/// failover chain, at the quality each is configured for on the current
+/// connection.
+pub(crate) async fn resolve_stream_source(app: &AppHandle, track_id: &str) -> Result<StreamSource> {
+    app.state::<StreamResolver>()
+        .resolve(app, track_id, |plugin_id| quality::stream_request(app, &plugin_id.to_string()))
+        .await
+}
+
+/// Like `resolve_stream_source`, with the same explicit format/quality hints
+/// for every provider.
+pub(crate) async fn resolve_stream_source_with(
+    app: &AppHandle,
+    track_id: &str,
+    req: &StreamRequest,
+) -> Result<StreamSource> {
+    app.state::<StreamResolver>().resolve(app, track_id, |_| req.clone()).await
+}
 
 #[tracing::instrument(level = "debug", skip(app))]
 pub fn build_audio_player(app: AppHandle) -> AudioPlayer {
@@ -25,34 +100,28 @@ pub fn build_audio_player(app: AppHandle) -> AudioPlayer {
     if let Err(e) = audio_player.load_state(&db) {
         tracing::error!("Failed to load player state from database: {:?}", e);
     }
+    apply_queue_settings(&app, &audio_player);
+    apply_media_key_settings(&app, &audio_player);
+    buffering::apply_buffering_settings(&app);
+    apply_playback_settings(&app, &audio_player);
     if let Err(e) = audio_player.initialize_mpris() {
         tracing::error!("Failed to initialize MPRIS: {:?}", e);
     }
+    if let Ok(mode) = audio_player.get_store().lock().map(|s| s.get_repeat()) {
+        audio_player.notify_mpris_mode(mode);
+    }
     
     #[cfg(any(target_os = "android", target_os = "ios"))]
     audio_player.set_mpris_app_handle(app.clone());
     
-    // Sanitize initial playback state at startup to avoid stale "PLAYING" UI
-    // If there's no current track or queue is empty, force STOPPED.
-    // Otherwise, if state persisted as PLAYING, downgrade to PAUSED until actual playback starts.
-    {
-        let store_arc = audio_player.get_store();
-        // Bind lock result to ensure its temporaries drop before store_arc
-        let lock_res = store_arc.lock();
-        if let Ok(mut store) = lock_res {
-            let q_len = store.get_queue_len();
-            let has_track = store.get_current_track().is_some();
-            let state = store.get_player_state();
-            use types::ui::player_details::PlayerState as Ps;
-            if q_len == 0 || !has_track {
-                if state != Ps::Stopped {
-                    store.set_state(Ps::Stopped);
-                }
-            } else if state == Ps::Playing {
-                store.set_state(Ps::Paused);
-            }
-        }
-    }
+    // Startup state sanitizing (stale PLAYING, tracks missing from queue) is handled by
+    // the player store state machine when persisted data is loaded. The policy
+    // decides whether the position is kept and playing continues.
+    let policy = app
+        .state::<SettingsConfig>()
+        .load_selective::<types::settings::general::ResumeOnLaunch>("general.resumeOnLaunch".to_string())
+        .unwrap_or_default();
+    RESUME_ON_LAUNCH.store(audio_player.apply_resume_policy(policy), Ordering::SeqCst);
 
     if let Some(_handle) = audio_player.start_mpris_event_listener() {
         tracing::info!("MPRIS event listener started");
@@ -62,82 +131,233 @@ pub fn build_audio_player(app: AppHandle) -> AudioPlayer {
     audio_player.register_spotify_adapter(adapter);
 
     // 注入流媒体URL解析器
-    let plugin_handler: State<'_, PluginHandler> = app.state();
     let resolver = {
-        let plugin_handler = plugin_handler.inner().clone();
         let app_for_headers = app.clone();
         Arc::new(move |track: &types::tracks::MediaContent| {
             // Clone captured handles per-call to avoid moving from the environment (Fn vs FnOnce)
-            let plugin_handler = plugin_handler.clone();
             let app_handle = app_for_headers.clone();
             let track = track.clone();
             Box::pin(async move {
                 tracing::debug!("Resolving stream URL for track: {:?}", track.track.title);
-                
-                // 获取插件管理器
-                let plugin_manager = plugin_handler.plugin_manager();
-                
-                // 使用现有的方法获取音频提供者
-                let selection = types::settings::music::MusicSourceSelection::default();
-                let audio_providers = plugin_manager
-                    .get_audio_providers_by_selection(&selection)
-                    .await
-                    .map_err(|e| types::errors::MusicError::String(format!("Failed to get audio providers: {}", e)))?;
-                
-                if audio_providers.is_empty() {
-                    return Err(types::errors::MusicError::String("No audio providers found".into()));
-                }
-                
-                // 尝试从提供者获取流媒体URL
-                for (provider_id, provider_plugin) in audio_providers {
-                    tracing::debug!("Trying provider: {}", provider_id);
-                    
-                    let track_id = track.track._id.as_ref()
-                        .ok_or_else(|| types::errors::MusicError::String("No track ID found".into()))?;
-                    
-                    // 获取流媒体描述（格式/质量由默认 StreamRequest 指示）
-                    let stream_result = {
-                        let plugin_guard = provider_plugin.lock().await;
-                        let req = StreamRequest {
-                            format: StreamFormatPreference::Auto,
-                            quality: QualityPreference::Qn(16),
-                            extra: None,
-                        };
-                        plugin_guard.get_media_stream(track_id, &req).await
-                    };
-                    
-                    match stream_result {
-                        Ok(stream) => {
-                            let stream_url = stream.url.clone();
-                            // store headers for audio player prefetch
-                            if let Some(headers) = stream.headers.clone() {
-                                let audio_state: State<'_, AudioPlayer> = app_handle.state();
-                                audio_state.set_url_headers(stream_url.clone(), headers.into_iter().collect());
-                            }
-                            tracing::info!("Successfully resolved stream URL from provider {}: {}", provider_id, stream_url);
-                            return Ok(stream_url);
-                        }
-                        Err(e) => {
-                            tracing::warn!("Provider {} failed to resolve stream URL: {}", provider_id, e);
-                            continue;
-                        }
+
+                let track_id = track.track._id.as_ref()
+                    .ok_or_else(|| types::errors::MusicError::String("No track ID found".into()))?;
+
+                // Downloaded tracks play from disk
+                let db: State<'_, Database> = app_handle.state();
+                if let Ok(Some(download)) = db.get_download(track_id) {
+                    if std::path::Path::new(&download.path).exists() {
+                        tracing::info!("Playing downloaded file for track {}", track_id);
+                        return Ok(download.path);
                     }
                 }
-                
-                Err(types::errors::MusicError::String("No provider could resolve stream URL".into()))
+
+                // Resolved ahead of time while the previous album track played
+                let precache: State<'_, PrecacheState> = app_handle.state();
+                let stream = match precache.take(track_id) {
+                    Some(stream) => stream,
+                    None => resolve_stream_source(&app_handle, track_id).await?,
+                };
+                let stream_url = stream.url.clone();
+                // store headers for audio player prefetch
+                if let Some(headers) = stream.headers {
+                    let audio_state: State<'_, AudioPlayer> = app_handle.state();
+                    audio_state.set_url_headers(stream_url.clone(), headers.into_iter().collect());
+                }
+                Ok(stream_url)
             }) as std::pin::Pin<Box<dyn std::future::Future<Output = Result<String>> + Send>>
         })
     };
     
     audio_player.set_stream_url_resolver(resolver);
     
-    let events_rx = audio_player.get_events_rx();
-    let store_arc = audio_player.get_store();
-    let app_for_thread = app.clone();
+    spawn_event_loop(app.clone(), audio_player.get_events_rx(), audio_player.get_store());
+
+    // Media key bridge: gestures and next/previous resolved by the MPRIS listener
+    let control_rx = audio_player.get_control_rx();
+    let app_for_control = app.clone();
+    thread::spawn(move || {
+        use types::settings::music::MediaKeyAction;
+        use types::ui::player_details::PlayerState;
+
+        let rx = control_rx.lock().expect("lock control rx");
+        while let Ok(action) = rx.recv() {
+            let app_clone = app_for_control.clone();
+            match action {
+                MediaKeyAction::PlayPause => {
+                    tauri::async_runtime::spawn(async move {
+                        let audio_state: State<'_, AudioPlayer> = app_clone.state();
+                        let playing = audio_state
+                            .get_store()
+                            .lock()
+                            .map(|s| s.get_player_state() == PlayerState::Playing)
+                            .unwrap_or(false);
+                        let res = if playing {
+                            audio_pause(app_clone.state()).await
+                        } else {
+                            audio_play(app_clone.clone(), app_clone.state(), None).await
+                        }
+                        .and_then(CommandResponse::into_result);
+                        if let Err(e) = res {
+                            tracing::warn!("Media key play/pause failed: {:?}", e);
+                        }
+                    });
+                }
+                MediaKeyAction::Next => {
+                    tauri::async_runtime::spawn(async move {
+                        let res = next_track(app_clone.clone(), app_clone.state())
+                            .await
+                            .and_then(CommandResponse::into_result);
+                        if let Err(e) = res {
+                            tracing::warn!("Media key next failed: {:?}", e);
+                        }
+                    });
+                }
+                MediaKeyAction::Previous => {
+                    tauri::async_runtime::spawn(async move {
+                        let res = prev_track(app_clone.clone(), app_clone.state())
+                            .await
+                            .and_then(CommandResponse::into_result);
+                        if let Err(e) = res {
+                            tracing::warn!("Media key previous failed: {:?}", e);
+                        }
+                    });
+                }
+                MediaKeyAction::Radio => {
+                    // Voice assistant / radio has no backend counterpart; let the front-end decide
+                    let _ = crate::windowing::emit_audio_event(
+                        &app_clone,
+                        json!({ "type": "MediaKeyAction", "data": { "action": action } }),
+                    );
+                }
+                MediaKeyAction::None => {}
+            }
+        }
+    });
+
+    // Media controls bridge: seeks and loop/shuffle changes requested over MPRIS
+    let remote_rx = audio_player.get_remote_rx();
+    let app_for_remote = app.clone();
+    thread::spawn(move || {
+        use audio_player::mpris::RemoteAction;
+
+        let rx = remote_rx.lock().expect("lock remote rx");
+        while let Ok(action) = rx.recv() {
+            let app_clone = app_for_remote.clone();
+            let res = match action {
+                RemoteAction::SeekTo(pos) => {
+                    tauri::async_runtime::block_on(audio_seek(app_clone.state(), app_clone.state(), pos))
+                }
+                RemoteAction::SeekBy(offset) => {
+                    let audio_state: State<'_, AudioPlayer> = app_clone.state();
+                    let (position, duration) = audio_state
+                        .get_store()
+                        .lock()
+                        .map(|s| {
+                            let duration = s
+                                .edited_duration()
+                                .or_else(|| s.get_current_track().and_then(|t| t.track.duration));
+                            (s.edit_regions().to_edited(s.get_current_time()), duration)
+                        })
+                        .unwrap_or((0.0, None));
+                    let target = position + offset;
+                    // MPRIS: seeking past the end moves to the next track
+                    if duration.is_some_and(|d| target >= d) {
+                        tauri::async_runtime::block_on(next_track(app_clone.clone(), app_clone.state()))
+                    } else {
+                        tauri::async_runtime::block_on(audio_seek(app_clone.state(), app_clone.state(), target.max(0.0)))
+                    }
+                }
+                RemoteAction::SetPlayerMode(mode) => set_player_mode(app_clone.clone(), app_clone.state(), mode),
+                RemoteAction::SetRate(rate) => {
+                    rate::set_rate(&app_clone, rate as f32).map(|_| CommandResponse::success(()))
+                }
+            }
+            .and_then(CommandResponse::into_result);
+            if let Err(e) = res {
+                tracing::warn!("Media controls request {:?} failed: {:?}", action, e);
+            }
+        }
+    });
+
+    // Output device bridge: switches and devices that disappeared
+    let device_rx = audio_player.get_device_rx();
+    let app_for_devices = app.clone();
+    thread::spawn(move || {
+        use audio_player::devices::DeviceEvent;
+
+        let rx = device_rx.lock().expect("lock device rx");
+        while let Ok(event) = rx.recv() {
+            let payload = match event {
+                DeviceEvent::Changed { name } => {
+                    profiles::on_output_device_changed(&app_for_devices, &name);
+                    json!({ "type": "OutputDeviceChanged", "data": { "name": name } })
+                }
+                DeviceEvent::Lost { name } => json!({ "type": "OutputDeviceLost", "data": { "name": name } }),
+            };
+            let _ = crate::windowing::emit_audio_event(&app_for_devices, payload);
+        }
+    });
+
+    // Calls and communication sessions: pause/duck per the policy, undo afterwards
+    let interrupt_rx = audio_player.get_interrupt_rx();
+    let app_for_interrupts = app.clone();
+    thread::spawn(move || {
+        let rx = interrupt_rx.lock().expect("lock interrupt rx");
+        while let Ok(signal) = rx.recv() {
+            // Reports can only arrive once the player is managed, but don't rely on it
+            let Some(state) = app_for_interrupts.try_state::<AudioPlayer>() else {
+                continue;
+            };
+            match tauri::async_runtime::block_on(state.handle_interruption(signal)) {
+                Ok(active) => {
+                    let _ = crate::windowing::emit_audio_event(
+                        &app_for_interrupts,
+                        json!({ "type": "InterruptionChanged", "data": { "active": active } }),
+                    );
+                }
+                Err(e) => tracing::warn!("Failed to handle interruption {:?}: {:?}", signal, e),
+            }
+        }
+    });
+
+    // Edit regions: jump over skip regions, silence mute regions
+    let edit_rx = audio_player.get_edit_rx();
+    let app_for_edits = app.clone();
+    thread::spawn(move || {
+        let rx = edit_rx.lock().expect("lock edit rx");
+        while let Ok(action) = rx.recv() {
+            let Some(state) = app_for_edits.try_state::<AudioPlayer>() else {
+                continue;
+            };
+            if let Err(e) = tauri::async_runtime::block_on(state.apply_edit_action(action)) {
+                tracing::warn!("Failed to apply edit region {:?}: {:?}", action, e);
+            }
+        }
+    });
+
+    #[cfg(target_os = "android")]
+    listen_audio_focus(&app);
+    
+    audio_player
+}
+
+/// Forward player events to the UI and act on them. The watchdog restarts
+/// the loop when it dies.
+pub(crate) fn spawn_event_loop(
+    app: AppHandle,
+    events_rx: Arc<Mutex<crossbeam_channel::Receiver<PlayerEvents>>>,
+    store_arc: Arc<Mutex<PlayerStore>>,
+) {
+    let heartbeat = app.state::<Watchdog>().audio_events.clone();
+    let running = heartbeat.start();
+    let app_for_thread = app;
     thread::spawn(move || {
         use serde::Serialize;
         use serde_json::json;
-        use types::ui::player_details::{PlayerEvents, PlayerState};
+        use types::ui::player_details::PlayerState;
+
+        let _running = running;
 
         #[derive(Serialize)]
         struct FrontendEnvelope<T: Serialize> {
@@ -146,19 +366,43 @@ pub fn build_audio_player(app: AppHandle) -> AudioPlayer {
             data: T,
         }
 
-        let rx = events_rx.lock().expect("lock events rx");
+        // A loop that panicked left the lock poisoned; its replacement takes over
+        let rx = events_rx.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
         while let Ok(ev) = rx.recv() {
+            let _busy = heartbeat.busy();
             // Helper to emit a structured envelope with arbitrary JSON data
             let emit_json = |event_type: &'static str, data: serde_json::Value| {
                 let payload = json!({
                     "type": event_type,
                     "data": data,
                 });
-                let _ = app_for_thread.emit("audio_event", payload);
+                let _ = crate::windowing::emit_audio_event(&app_for_thread, payload);
+            };
+            let throttle = app_for_thread.state::<PositionThrottle>();
+            // The UI shows the edited timeline: skip regions are removed
+            // from the position and, when there are any, the duration
+            let emit_position = |time: f64| {
+                let (position, duration) = store_arc
+                    .lock()
+                    .map(|s| (s.edit_regions().to_edited(time), s.edited_duration()))
+                    .unwrap_or((time, None));
+                if !throttle.admit(position) {
+                    return;
+                }
+                // Convert seconds(f64) to Duration-like object { secs, nanos }
+                let secs = position.trunc() as i64;
+                let nanos = ((position - secs as f64) * 1_000_000_000f64).round() as i64;
+                // The position advances `rate` seconds per second
+                let rate = audio_player::stretch::rate();
+                emit_json(
+                    "PositionChanged",
+                    json!({ "position": { "secs": secs, "nanos": nanos }, "duration": duration, "rate": rate }),
+                );
             };
 
             match ev {
                 PlayerEvents::Play => {
+                    throttle.emit_next();
                     emit_json(
                         "PlaybackStateChanged",
                         json!({ "is_playing": true, "is_paused": false }),
@@ -169,29 +413,53 @@ pub fn build_audio_player(app: AppHandle) -> AudioPlayer {
                         "PlaybackStateChanged",
                         json!({ "is_playing": false, "is_paused": true }),
                     );
+                    // No updates follow while paused, so show where it stopped
+                    throttle.emit_next();
+                    let time = store_arc.lock().map(|s| s.get_current_time()).unwrap_or(0.0);
+                    emit_position(time);
+                    crate::podcasts::on_pause(&app_for_thread);
+                    crate::audiobooks::on_pause(&app_for_thread);
                 }
                 PlayerEvents::Loading => {
                     // Do NOT modify playback state on loading; avoid UI flicker.
                     // Optionally notify front-end about buffering if it wants to show an indicator.
                     emit_json("Buffering", json!({}));
+                    buffering::on_track_loading();
+                    // The new track starts over, don't keep the old position up
+                    throttle.emit_next();
 
+                    let precache_depth = precache::lookahead_depth(&app_for_thread);
                     // Also announce current track metadata if available
                     if let Ok(store) = store_arc.lock() {
                         if let Some(track) = store.get_current_track() {
                             emit_json("TrackChanged", json!({ "track": track }));
                         }
+                        // Listening straight through an album: prepare the next tracks
+                        let upcoming = store.album_lookahead(precache_depth);
+                        precache::precache_tracks(&app_for_thread, upcoming);
                     }
+                    // Music and spoken word keep their own rate
+                    let track = store_arc.lock().ok().and_then(|s| s.get_current_track());
+                    rate::apply_track_rate(&app_for_thread, track.as_ref());
+                    // A new track drops the A-B loop of the previous one
+                    let region = app_for_thread.state::<AudioPlayer>().audio_get_loop_region();
+                    emit_json("LoopRegionChanged", json!({ "region": region }));
                 }
                 PlayerEvents::Ended => {
                     // Track finished signal
                     emit_json("TrackFinished", json!({}));
+                    crate::podcasts::on_ended(&app_for_thread);
+                    crate::audiobooks::on_ended(&app_for_thread);
                     
                     // 异步更新播放统计和存储（放入阻塞线程池，避免占用 async runtime）
-                    if let Ok(store) = store_arc.lock() {
+                    // Private sessions leave no trace in the history
+                    let private = crate::privacy::is_private(&app_for_thread);
+                    if let (Ok(store), false) = (store_arc.lock(), private) {
                         if let Some(track) = store.get_current_track() {
                             let db_state: State<'_, Database> = app_for_thread.state();
                             let db = db_state.inner().clone();
                             let track_for_storage = track.clone();
+                            let app_for_downloads = app_for_thread.clone();
                             
                             // 在阻塞线程池中执行同步 Diesel 写操作，内部用 block_on 调用现有 async API
                             tauri::async_runtime::spawn_blocking(move || {
@@ -213,6 +481,9 @@ pub fn build_audio_player(app: AppHandle) -> AudioPlayer {
                                         } else {
                                             tracing::debug!("Stored track metadata for online track: {}", track_id);
                                         }
+
+                                        // The play may have put the track into heavy rotation
+                                        crate::downloads::queue_smart_downloads(&app_for_downloads);
                                     }
                                 }
                             });
@@ -242,330 +513,1078 @@ pub fn build_audio_player(app: AppHandle) -> AudioPlayer {
                                 tauri::async_runtime::spawn(async move {
                                     // Acquire AudioPlayer state
                                     let audio_state: State<'_, AudioPlayer> = app_clone.state();
-                                    // Load the selected track and then play
-                                    let _ = audio_state.audio_load(&mut track).await;
-                                    let _ = audio_state.audio_play(None).await;
+                                    // Load the selected track and then play, after the inter-track gap
+                                    if let Err(e) = audio_state.continue_after_ended(&mut track).await {
+                                        tracing::warn!("Failed to continue with next track: {:?}", e);
+                                    }
                                 });
                             }
+                        } else {
+                            if store.is_end_trimmed() {
+                                // Cut short by an end position, the backend is still playing
+                                let app_clone = app_for_thread.clone();
+                                tauri::async_runtime::spawn(async move {
+                                    let audio_state: State<'_, AudioPlayer> = app_clone.state();
+                                    let _ = audio_state.audio_stop().await;
+                                });
+                            }
+                            // Out of tracks: radio mode looks for more
+                            if store.needs_radio_extension() {
+                                if let Some(seed) = store.get_current_track().and_then(|t| t.track._id) {
+                                    tauri::async_runtime::spawn(radio::extend_queue(app_for_thread.clone(), seed));
+                                }
+                            }
                         }
                     }
                 }
                 PlayerEvents::TimeUpdate(time) => {
-                    // Convert seconds(f64) to Duration-like object { secs, nanos }
-                    let secs = time.trunc() as i64;
-                    let nanos = ((time - secs as f64) * 1_000_000_000f64).round() as i64;
-                    emit_json(
-                        "PositionChanged",
-                        json!({ "position": { "secs": secs, "nanos": nanos } }),
-                    );
+                    emit_position(time);
+                    let track = store_arc.lock().ok().and_then(|s| s.get_current_track());
+                    let track_id = track.as_ref().and_then(|t| t.track._id.clone());
+                    crate::podcasts::on_time_update(&app_for_thread, track_id.as_deref(), time);
+                    crate::audiobooks::on_time_update(&app_for_thread, track.as_ref(), time);
+                    crate::bookmarks::on_time_update(&app_for_thread, track_id.as_deref(), time);
+                    crate::lyrics::on_time_update(&app_for_thread, track_id, time);
+                }
+                PlayerEvents::StreamTitle(title) => {
+                    emit_json("StreamTitleChanged", json!({ "title": title }));
+                }
+                // Not "Buffering", which the UI takes for a track switch
+                PlayerEvents::Buffering(percent) => {
+                    emit_json("BufferProgress", json!({ "progress": percent, "stalled": false }));
+                }
+                PlayerEvents::Stalled => {
+                    audio_player::trace::record("buffer", json!({ "event": "stalled" }));
+                    emit_json("BufferProgress", json!({ "progress": 0, "stalled": true }));
+                    buffering::on_stall(&app_for_thread);
+                }
+                // Not an error yet: the track is resolved again and resumes
+                PlayerEvents::StreamExpired(status) => {
+                    audio_player::trace::record("buffer", json!({ "event": "expired", "status": status }));
+                    refresh::on_stream_expired(&app_for_thread, status);
                 }
                 PlayerEvents::Error(err) => {
+                    audio_player::trace::record("error", json!({ "message": err.to_string() }));
                     emit_json("Error", json!({ "message": err.to_string() }));
                 }
             }
         }
     });
-    
-    audio_player
+}
+
+/// Android reports calls as a transient loss of audio focus, forwarded by the
+/// audioplayer plugin on the media session channel. A permanent loss means
+/// another app started playing: pause like the user would.
+#[cfg(target_os = "android")]
+fn listen_audio_focus(app: &AppHandle) {
+    use audio_player::interrupt::InterruptSignal;
+    use tauri::Listener;
+
+    let app_for_focus = app.clone();
+    app.listen("MediaSessionCallback", move |event| {
+        let Ok(payload) = serde_json::from_str::<serde_json::Value>(event.payload()) else {
+            return;
+        };
+        if payload["event"] != "onAudioFocusChange" {
+            return;
+        }
+        let Some(state) = app_for_focus.try_state::<AudioPlayer>() else {
+            return;
+        };
+        match payload["focus"].as_str() {
+            Some("lossTransient") => state.report_interruption(InterruptSignal::Began),
+            Some("gain") => state.report_interruption(InterruptSignal::Ended),
+            Some("loss") => {
+                let app = app_for_focus.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = app.state::<AudioPlayer>().audio_pause().await {
+                        tracing::warn!("Failed to pause after losing audio focus: {:?}", e);
+                    }
+                });
+            }
+            _ => {}
+        }
+    });
+}
+
+/// The app is exiting: keep where the queue, episodes and audiobooks stand,
+/// which are otherwise saved every few seconds
+pub fn on_exit(app: &AppHandle) {
+    if let Some(player) = app.try_state::<AudioPlayer>() {
+        player.save_position();
+    }
+    crate::podcasts::on_pause(app);
+    crate::audiobooks::on_pause(app);
+}
+
+/// Push media key gesture preferences (prefs.music.mediaKeys) into the MPRIS listener.
+#[tracing::instrument(level = "debug", skip(app, audio_player))]
+pub fn apply_media_key_settings(app: &AppHandle, audio_player: &AudioPlayer) {
+    use audio_player::media_keys::MediaKeyConfig;
+    use types::settings::music::MusicMediaKeySettings;
+    let settings: State<'_, SettingsConfig> = app.state();
+    let media_keys = settings
+        .load_selective::<MusicMediaKeySettings>("music.mediaKeys".to_string())
+        .unwrap_or_default();
+    audio_player.set_media_key_config(MediaKeyConfig::from(&media_keys));
+}
+
+/// Push playback preferences (prefs.music.playback) into the player.
+#[tracing::instrument(level = "debug", skip(app, audio_player))]
+pub fn apply_playback_settings(app: &AppHandle, audio_player: &AudioPlayer) {
+    use audio_player::crossfade::{track_gap, CrossfadeConfig};
+    use audio_player::interrupt::InterruptPolicy;
+    use audio_player::normalize::NormalizeConfig;
+    use types::settings::music::MusicPlaybackSettings;
+    let settings: State<'_, SettingsConfig> = app.state();
+    let playback = settings
+        .load_selective::<MusicPlaybackSettings>("music.playback".to_string())
+        .unwrap_or_default();
+    audio_player.set_crossfade(CrossfadeConfig::from(&playback));
+    audio_player.set_track_gap(track_gap(&playback));
+    audio_player.set_interrupt_policy(InterruptPolicy::from(&playback));
+    audio_player.set_normalization(NormalizeConfig::from(&playback));
+    audio_player::crossfeed::set_level(playback.crossfeed.unwrap_or_default());
+    app.state::<PositionThrottle>().configure(&playback);
+    if let Ok(mut store) = audio_player.get_store().lock() {
+        store.set_radio_mode(playback.radio_mode.unwrap_or(false));
+        store.set_trim_silence(playback.trim_silence.unwrap_or(false));
+    }
+    silence::spawn_auto_detect(app);
+    // The renderer stores an empty name for the system default
+    let output_device = playback.output_device.clone().filter(|name| !name.is_empty());
+    if let Err(e) = audio_player.set_output_device(output_device) {
+        tracing::warn!("Failed to apply output device: {:?}", e);
+    }
+}
+
+/// Continue playing the restored track when the resume-on-launch policy asked
+/// for it. Called once the providers are up, as streamed tracks need them.
+pub async fn resume_after_launch(app: &AppHandle) {
+    if !RESUME_ON_LAUNCH.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let state: State<'_, AudioPlayer> = app.state();
+    if let Err(e) = state.audio_play(None).await {
+        tracing::warn!("Failed to resume playback on launch: {:?}", e);
+        return;
+    }
+    if let Some(track) = state.get_store().lock().ok().and_then(|store| store.get_current_track()) {
+        let _ = crate::windowing::emit_audio_event(app, json!({ "type": "TrackChanged", "data": { "track": track } }));
+    }
+}
+
+/// Push queue related preferences (prefs.queue_settings.*) into the player store.
+#[tracing::instrument(level = "debug", skip(app, audio_player))]
+pub fn apply_queue_settings(app: &AppHandle, audio_player: &AudioPlayer) {
+    use types::settings::queue::QueueDuplicatePolicy;
+    let settings: State<'_, SettingsConfig> = app.state();
+    let policy = settings
+        .load_selective::<QueueDuplicatePolicy>("queue_settings.duplicatePolicy".to_string())
+        .unwrap_or_default();
+    if let Ok(mut store) = audio_player.get_store().lock() {
+        store.set_duplicate_policy(policy);
+    }
 }
 
 // ---------- Commands (UI only sees these) ----------
 
 
-#[tracing::instrument(level = "debug", skip_all)]
-#[tauri::command]
-pub async fn audio_play(app: AppHandle, state: State<'_, AudioPlayer>, track: Option<types::tracks::MediaContent>) -> Result<()> {
-    let mut track_ref = track;
-    let result = state.audio_play(track_ref.as_mut()).await;
-
-    // Emit events after successful play
-    if result.is_ok() {
-        // If a track was explicitly provided, use it directly to avoid any race with store updates
-        if let Some(provided_track) = track_ref {
-            // emit TrackChanged with the provided track
-            let _ = app.emit(
-                "audio_event",
-                json!({ "type": "TrackChanged", "data": { "track": provided_track } }),
-            );
-            // Optionally also notify queue changed since explicit play may update index
-            let _ = app.emit(
-                "audio_event",
-                json!({ "type": "QueueChanged", "data": {} }),
-            );
-        } else {
-            // Fallback: no track provided, emit current track from store
-            if let Ok(store) = state.get_store().lock() {
-                if let Some(track) = store.get_current_track() {
-                    let _ = app.emit(
-                        "audio_event",
-                        json!({ "type": "TrackChanged", "data": { "track": track } }),
-                    );
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip_all)]
+    #[tauri::command]
+    pub async fn audio_play(app: AppHandle, state: State<'_, AudioPlayer>, track: Option<types::tracks::MediaContent>) -> Result<()> {
+        let mut track_ref = track;
+        let result = state.audio_play(track_ref.as_mut()).await;
+
+        // Emit events after successful play
+        if result.is_ok() {
+            // If a track was explicitly provided, use it directly to avoid any race with store updates
+            if let Some(provided_track) = track_ref {
+                // emit TrackChanged with the provided track
+                let _ = crate::windowing::emit_audio_event(
+                    &app,
+                    json!({ "type": "TrackChanged", "data": { "track": provided_track } }),
+                );
+                // Optionally also notify queue changed since explicit play may update index
+                emit_queue_changed(&app, &state);
+            } else {
+                // Fallback: no track provided, emit current track from store
+                if let Ok(store) = state.get_store().lock() {
+                    if let Some(track) = store.get_current_track() {
+                        let _ = crate::windowing::emit_audio_event(
+                            &app,
+                            json!({ "type": "TrackChanged", "data": { "track": track } }),
+                        );
+                    }
                 }
             }
         }
+
+        result
     }
+}
 
-    result
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub async fn audio_pause(state: State<'_, AudioPlayer>) -> Result<()> {
+        state.audio_pause().await
+    }
 }
 
-#[tracing::instrument(level = "debug", skip(state))]
-#[tauri::command]
-pub async fn audio_pause(state: State<'_, AudioPlayer>) -> Result<()> {
-    state.audio_pause().await
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub async fn audio_stop(state: State<'_, AudioPlayer>) -> Result<()> {
+        state.audio_stop().await
+    }
 }
 
-#[tracing::instrument(level = "debug", skip(state))]
-#[tauri::command]
-pub async fn audio_stop(state: State<'_, AudioPlayer>) -> Result<()> {
-    state.audio_stop().await
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state, throttle))]
+    #[tauri::command]
+    pub async fn audio_seek(
+        state: State<'_, AudioPlayer>,
+        throttle: State<'_, PositionThrottle>,
+        pos: f64,
+    ) -> Result<()> {
+        throttle.emit_next();
+        state.audio_seek(pos).await
+    }
 }
 
-#[tracing::instrument(level = "debug", skip(state))]
-#[tauri::command]
-pub async fn audio_seek(state: State<'_, AudioPlayer>, pos: f64) -> Result<()> {
-    state.audio_seek(pos).await
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub async fn audio_set_volume(app: AppHandle, state: State<'_, AudioPlayer>, volume: f32) -> Result<()> {
+        state.audio_set_volume(volume).await?;
+        // Emit VolumeChanged event
+        let _ = crate::windowing::emit_audio_event(
+            &app,
+            json!({
+                "type": "VolumeChanged",
+                "data": { "volume": volume }
+            }),
+        );
+        Ok(())
+    }
 }
 
-#[tracing::instrument(level = "debug", skip(state))]
-#[tauri::command]
-pub async fn audio_set_volume(app: AppHandle, state: State<'_, AudioPlayer>, volume: f32) -> Result<()> {
-    state.audio_set_volume(volume).await?;
-    // Emit VolumeChanged event
-    let _ = app.emit(
-        "audio_event",
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub async fn audio_get_volume(state: State<'_, AudioPlayer>) -> Result<f32> {
+        state.audio_get_volume().await
+    }
+}
+
+/// Apply a volume clamp or mode change to the backend and tell the UI the
+/// volume and cap of the playing source.
+fn emit_volume_clamp(app: &AppHandle, state: &AudioPlayer) -> Result<()> {
+    state.reapply_volume();
+    let store_arc = state.get_store();
+    let store = store_arc
+        .lock()
+        .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+    let _ = crate::windowing::emit_audio_event(
+        app,
         json!({
             "type": "VolumeChanged",
-            "data": { "volume": volume }
+            "data": {
+                "volume": store.get_raw_volume() / 100.0,
+                "source": store.current_volume_source(),
+                "clamp": store.get_volume_clamp().map(|clamp| clamp / 100.0),
+            }
         }),
     );
     Ok(())
 }
 
+command_envelope! {
+    /// Cap the volume (0.0 - 1.0) of the tracks of a source in the
+    /// `PersistClamp` volume mode; `None` removes the cap. `source` is a
+    /// provider or "LOCAL", the source of the playing track when unset.
+    #[tracing::instrument(level = "debug", skip(app, state))]
+    #[tauri::command]
+    pub fn set_track_volume_clamp(
+        app: AppHandle,
+        state: State<'_, AudioPlayer>,
+        source: Option<String>,
+        clamp: Option<f64>,
+    ) -> Result<()> {
+        {
+            let store_arc = state.get_store();
+            let mut store = store_arc
+                .lock()
+                .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+            let source = source
+                .or_else(|| store.current_volume_source())
+                .ok_or("No source to clamp: nothing is playing")?;
+            store.set_volume_clamp(source, clamp.map(|clamp| clamp * 100.0));
+        }
+        emit_volume_clamp(&app, &state)
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub fn get_volume_mode(state: State<'_, AudioPlayer>) -> Result<VolumeMode> {
+        let store_arc = state.get_store();
+        let store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        Ok(store.get_volume_mode())
+    }
+}
 
-#[tracing::instrument(level = "debug", skip(state))]
-#[tauri::command]
-pub async fn audio_get_volume(state: State<'_, AudioPlayer>) -> Result<f32> {
-    state.audio_get_volume().await
+command_envelope! {
+    /// Switch between one volume, a volume per source and one volume capped
+    /// per source.
+    #[tracing::instrument(level = "debug", skip(app, state))]
+    #[tauri::command]
+    pub fn set_volume_mode(app: AppHandle, state: State<'_, AudioPlayer>, mode: VolumeMode) -> Result<()> {
+        {
+            let store_arc = state.get_store();
+            let mut store = store_arc
+                .lock()
+                .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+            store.set_volume_mode(mode);
+        }
+        emit_volume_clamp(&app, &state)
+    }
 }
 
 // ---------- PlayerStore Commands ----------
 
-#[tracing::instrument(level = "debug", skip(state))]
-#[tauri::command]
-pub fn get_current_track(state: State<'_, AudioPlayer>) -> Result<Option<types::tracks::MediaContent>> {
-    let store_arc = state.get_store();
-    let store = store_arc
-        .lock()
-        .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
-    // Compute current track from queue without mutating store to avoid side effects
-    let q = store.get_queue();
-    let track_opt = q
-        .track_queue
-        .get(q.current_index)
-        .and_then(|id| q.data.get(id))
-        .cloned()
-        .or_else(|| store.get_current_track());
-    Ok(track_opt)
-}
-
-#[tracing::instrument(level = "debug", skip(state))]
-#[tauri::command]
-pub fn get_queue(state: State<'_, AudioPlayer>) -> Result<audio_player::store::Queue> {
-    let store_arc = state.get_store();
-    let store = store_arc
-        .lock()
-        .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
-    Ok(store.get_queue())
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub fn get_current_track(state: State<'_, AudioPlayer>) -> Result<Option<types::tracks::MediaContent>> {
+        let store_arc = state.get_store();
+        let store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        // Compute current track from queue without mutating store to avoid side effects
+        let q = store.get_queue();
+        let track_opt = q
+            .track_queue
+            .get(q.current_index)
+            .and_then(|id| q.data.get(id))
+            .cloned()
+            .or_else(|| store.get_current_track());
+        Ok(track_opt)
+    }
 }
 
-#[tracing::instrument(level = "debug", skip(state))]
-#[tauri::command]
-pub fn get_player_state(state: State<'_, AudioPlayer>) -> Result<types::ui::player_details::PlayerState> {
-    let store_arc = state.get_store();
-    let store = store_arc
-        .lock()
-        .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
-    Ok(store.get_player_state())
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub fn get_queue(state: State<'_, AudioPlayer>) -> Result<QueueSnapshot> {
+        let store_arc = state.get_store();
+        let store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        Ok(QueueSnapshot {
+            queue: store.get_queue(),
+            metrics: queue_metrics(&state, &store),
+        })
+    }
 }
 
-#[tracing::instrument(level = "debug", skip(state, tracks))]
-#[tauri::command]
-pub fn add_to_queue(app: AppHandle, state: State<'_, AudioPlayer>, tracks: Vec<types::tracks::MediaContent>) -> Result<()> {
-    let store_arc = state.get_store();
-    let mut store = store_arc
-        .lock()
-        .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
-    store.add_to_queue(tracks);
-    // Emit QueueChanged
-    let _ = app.emit(
-        "audio_event",
-        json!({ "type": "QueueChanged", "data": {} }),
-    );
-    Ok(())
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub fn get_player_state(state: State<'_, AudioPlayer>) -> Result<types::ui::player_details::PlayerState> {
+        let store_arc = state.get_store();
+        let store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        Ok(store.get_player_state())
+    }
 }
 
-#[tracing::instrument(level = "debug", skip(state, index))]
-#[tauri::command]
-pub fn remove_from_queue(app: AppHandle, state: State<'_, AudioPlayer>, index: usize) -> Result<()> {
-    let store_arc = state.get_store();
-    let mut store = store_arc
-        .lock()
-        .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
-    store.remove_from_queue(index);
-    // Emit QueueChanged
-    let _ = app.emit(
-        "audio_event",
-        json!({ "type": "QueueChanged", "data": {} }),
-    );
-    Ok(())
+command_envelope! {
+    /// Add tracks to the queue honoring the duplicate policy from queue settings.
+    /// Returns duplicates held back by the `ask` policy; the UI confirms them by
+    /// calling again with `allow_duplicates = true`.
+    #[tracing::instrument(level = "debug", skip(state, tracks))]
+    #[tauri::command]
+    pub fn add_to_queue(
+        app: AppHandle,
+        state: State<'_, AudioPlayer>,
+        tracks: Vec<types::tracks::MediaContent>,
+        allow_duplicates: Option<bool>,
+    ) -> Result<Vec<types::tracks::MediaContent>> {
+        let store_arc = state.get_store();
+        let mut store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        let pending = if allow_duplicates.unwrap_or(false) {
+            store.add_to_queue_allow_duplicates(tracks);
+            Vec::new()
+        } else {
+            store.add_to_queue(tracks)
+        };
+        // Emit QueueChanged
+        let _ = crate::windowing::emit_audio_event(&app, queue_changed_event(&state, &store));
+        if !pending.is_empty() {
+            let _ = crate::windowing::emit_audio_event(
+                &app,
+                json!({ "type": "QueueDuplicatesPending", "data": { "tracks": pending } }),
+            );
+        }
+        Ok(pending)
+    }
 }
 
-#[tracing::instrument(level = "debug", skip(state, track))]
-#[tauri::command]
-pub fn play_now(app: AppHandle, state: State<'_, AudioPlayer>, track: types::tracks::MediaContent) -> Result<()> {
-    let store_arc = state.get_store();
-    let mut store = store_arc
-        .lock()
-        .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
-    store.play_now(track);
-    // Emit QueueChanged (now playing changed implies queue index change)
-    let _ = app.emit(
-        "audio_event",
-        json!({ "type": "QueueChanged", "data": {} }),
-    );
-    Ok(())
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state, index))]
+    #[tauri::command]
+    pub fn remove_from_queue(app: AppHandle, state: State<'_, AudioPlayer>, index: usize) -> Result<()> {
+        let store_arc = state.get_store();
+        let mut store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        store.remove_from_queue(index);
+        // Emit QueueChanged
+        let _ = crate::windowing::emit_audio_event(&app, queue_changed_event(&state, &store));
+        Ok(())
+    }
 }
 
-#[tracing::instrument(level = "debug", skip(state))]
-#[tauri::command]
-pub fn shuffle_queue(app: AppHandle, state: State<'_, AudioPlayer>) -> Result<()> {
-    let store_arc = state.get_store();
-    let mut store = store_arc
-        .lock()
-        .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
-    store.shuffle_queue();
-    // Emit QueueChanged
-    let _ = app.emit(
-        "audio_event",
-        json!({ "type": "QueueChanged", "data": {} }),
-    );
-    Ok(())
+/// Announce tracks inserted into the queue, and the duplicates held back
+fn emit_queue_inserted(app: &AppHandle, state: &AudioPlayer, store: &PlayerStore, pending: &[types::tracks::MediaContent]) {
+    let _ = crate::windowing::emit_audio_event(app, queue_changed_event(state, store));
+    if !pending.is_empty() {
+        let _ = crate::windowing::emit_audio_event(
+            app,
+            json!({ "type": "QueueDuplicatesPending", "data": { "tracks": pending } }),
+        );
+    }
 }
 
-#[tracing::instrument(level = "debug", skip(state))]
-#[tauri::command]
-pub fn clear_queue(app: AppHandle, state: State<'_, AudioPlayer>) -> Result<()> {
-    let store_arc = state.get_store();
-    let mut store = store_arc
-        .lock()
-        .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
-    store.clear_queue();
-    // Emit QueueChanged
-    let _ = app.emit(
-        "audio_event",
-        json!({ "type": "QueueChanged", "data": {} }),
-    );
-    Ok(())
+command_envelope! {
+    /// Insert a track right after the current one ("Play Next"), honoring the
+    /// duplicate policy like `add_to_queue`. Returns the duplicates held back.
+    #[tracing::instrument(level = "debug", skip(app, state, track))]
+    #[tauri::command]
+    pub fn play_next(
+        app: AppHandle,
+        state: State<'_, AudioPlayer>,
+        track: types::tracks::MediaContent,
+        allow_duplicates: Option<bool>,
+    ) -> Result<Vec<types::tracks::MediaContent>> {
+        let store_arc = state.get_store();
+        let mut store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        let pending = store.play_next_multiple(vec![track], allow_duplicates.unwrap_or(false));
+        emit_queue_inserted(&app, &state, &store, &pending);
+        Ok(pending)
+    }
 }
 
-#[tracing::instrument(level = "debug", skip(state))]
-#[tauri::command]
-pub fn toggle_player_mode(app: AppHandle, state: State<'_, AudioPlayer>) -> Result<()> {
-    let store_arc = state.get_store();
-    let mut store = store_arc
-        .lock()
-        .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
-    store.toggle_player_mode();
-    // Emit PlayerModeChanged with current mode
-    let current_mode = store.get_repeat();
-    let _ = app.emit(
-        "audio_event",
-        json!({ "type": "PlayerModeChanged", "data": { "mode": current_mode } }),
-    );
-    Ok(())
+command_envelope! {
+    /// Insert tracks (an album, a playlist) right after the current one,
+    /// keeping their order. Returns the duplicates held back.
+    #[tracing::instrument(level = "debug", skip(app, state, tracks))]
+    #[tauri::command]
+    pub fn play_next_multiple(
+        app: AppHandle,
+        state: State<'_, AudioPlayer>,
+        tracks: Vec<types::tracks::MediaContent>,
+        allow_duplicates: Option<bool>,
+    ) -> Result<Vec<types::tracks::MediaContent>> {
+        let store_arc = state.get_store();
+        let mut store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        let pending = store.play_next_multiple(tracks, allow_duplicates.unwrap_or(false));
+        emit_queue_inserted(&app, &state, &store, &pending);
+        Ok(pending)
+    }
 }
 
-#[tracing::instrument(level = "debug", skip(state))]
-#[tauri::command]
-pub fn get_player_mode(state: State<'_, AudioPlayer>) -> Result<types::ui::player_details::PlayerMode> {
-    let store_arc = state.get_store();
-    let store = store_arc
-        .lock()
-        .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
-    Ok(store.get_repeat())
+command_envelope! {
+    /// Insert tracks in order at `index` of the queue, or at its end when
+    /// beyond it. The playing entry keeps playing. Returns the duplicates held
+    /// back.
+    #[tracing::instrument(level = "debug", skip(app, state, tracks))]
+    #[tauri::command]
+    pub fn add_to_queue_at(
+        app: AppHandle,
+        state: State<'_, AudioPlayer>,
+        tracks: Vec<types::tracks::MediaContent>,
+        index: usize,
+        allow_duplicates: Option<bool>,
+    ) -> Result<Vec<types::tracks::MediaContent>> {
+        let store_arc = state.get_store();
+        let mut store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        let pending = store.add_to_queue_at(tracks, index, allow_duplicates.unwrap_or(false));
+        emit_queue_inserted(&app, &state, &store, &pending);
+        Ok(pending)
+    }
 }
 
-#[tracing::instrument(level = "debug", skip(state))]
-#[tauri::command]
-pub fn set_player_mode(app: AppHandle, state: State<'_, AudioPlayer>, mode: types::ui::player_details::PlayerMode) -> Result<()> {
-    let store_arc = state.get_store();
-    let mut store = store_arc
-        .lock()
-        .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
-    // Use public API to ensure invariants and persistence
-    store.set_player_mode(mode);
+command_envelope! {
+    /// Move the queue entry at `from` to `to` (drag and drop); the playing
+    /// entry keeps playing
+    #[tracing::instrument(level = "debug", skip(app, state))]
+    #[tauri::command]
+    pub fn move_queue_item(app: AppHandle, state: State<'_, AudioPlayer>, from: usize, to: usize) -> Result<()> {
+        let store_arc = state.get_store();
+        let mut store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        store.move_in_queue(from, to)?;
+        let _ = crate::windowing::emit_audio_event(&app, queue_changed_event(&state, &store));
+        Ok(())
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state, track))]
+    #[tauri::command]
+    pub fn play_now(app: AppHandle, state: State<'_, AudioPlayer>, track: types::tracks::MediaContent) -> Result<()> {
+        let store_arc = state.get_store();
+        let mut store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        store.play_now(track);
+        // Emit QueueChanged (now playing changed implies queue index change)
+        let _ = crate::windowing::emit_audio_event(&app, queue_changed_event(&state, &store));
+        Ok(())
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub fn shuffle_queue(app: AppHandle, state: State<'_, AudioPlayer>) -> Result<()> {
+        let store_arc = state.get_store();
+        let mut store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        store.shuffle_queue();
+        // Emit QueueChanged
+        let _ = crate::windowing::emit_audio_event(&app, queue_changed_event(&state, &store));
+        Ok(())
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub fn clear_queue(app: AppHandle, state: State<'_, AudioPlayer>) -> Result<()> {
+        let store_arc = state.get_store();
+        let mut store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        store.clear_queue();
+        // Emit QueueChanged
+        let _ = crate::windowing::emit_audio_event(&app, queue_changed_event(&state, &store));
+        Ok(())
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub fn toggle_player_mode(app: AppHandle, state: State<'_, AudioPlayer>) -> Result<()> {
+        let store_arc = state.get_store();
+        let mut store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        store.toggle_player_mode();
+        // Emit PlayerModeChanged with current mode
+        let current_mode = store.get_repeat();
+        state.notify_mpris_mode(current_mode);
+        let _ = crate::windowing::emit_audio_event(
+            &app,
+            json!({ "type": "PlayerModeChanged", "data": { "mode": current_mode } }),
+        );
+        Ok(())
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub fn get_player_mode(state: State<'_, AudioPlayer>) -> Result<types::ui::player_details::PlayerMode> {
+        let store_arc = state.get_store();
+        let store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        Ok(store.get_repeat())
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub fn set_player_mode(app: AppHandle, state: State<'_, AudioPlayer>, mode: types::ui::player_details::PlayerMode) -> Result<()> {
+        let store_arc = state.get_store();
+        let mut store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        // Use public API to ensure invariants and persistence
+        store.set_player_mode(mode);
+        state.notify_mpris_mode(mode);
     
-    // Emit PlayerModeChanged event
-    let _ = app.emit(
-        "audio_event",
-        json!({ "type": "PlayerModeChanged", "data": { "mode": mode } }),
-    );
+        // Emit PlayerModeChanged event
+        let _ = crate::windowing::emit_audio_event(
+            &app,
+            json!({ "type": "PlayerModeChanged", "data": { "mode": mode } }),
+        );
     
-    Ok(())
+        Ok(())
+    }
 }
 
-#[tracing::instrument(level = "debug", skip(state))]
-#[tauri::command]
-pub async fn next_track(app: AppHandle, state: State<'_, AudioPlayer>) -> Result<()> {
-    // Delegate to core: updates index + load + play
-    let track_opt = state.play_next().await?;
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub async fn next_track(app: AppHandle, state: State<'_, AudioPlayer>) -> Result<()> {
+        // Delegate to core: updates index + load + play
+        let track_opt = state.play_next().await?;
 
-    // Emit events for UI
-    let _ = app.emit(
-        "audio_event",
-        json!({ "type": "QueueChanged", "data": {} }),
-    );
-    if let Some(track) = track_opt {
-        let _ = app.emit(
-            "audio_event",
-            json!({ "type": "TrackChanged", "data": { "track": track } }),
+        // Emit events for UI
+        emit_queue_changed(&app, &state);
+        if let Some(track) = track_opt {
+            let _ = crate::windowing::emit_audio_event(
+                &app,
+                json!({ "type": "TrackChanged", "data": { "track": track } }),
+            );
+        }
+        Ok(())
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub async fn prev_track(app: AppHandle, state: State<'_, AudioPlayer>) -> Result<()> {
+        // Delegate to core: updates index + load + play
+        let track_opt = state.play_prev().await?;
+
+        // Emit events for UI
+        emit_queue_changed(&app, &state);
+        if let Some(track) = track_opt {
+            let _ = crate::windowing::emit_audio_event(
+                &app,
+                json!({ "type": "TrackChanged", "data": { "track": track } }),
+            );
+        }
+        Ok(())
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub fn change_index(app: AppHandle, state: State<'_, AudioPlayer>, new_index: usize, force: bool) -> Result<()> {
+        let store_arc = state.get_store();
+        let mut store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        store.change_index(new_index, force);
+        // Emit QueueChanged (explicit index change)
+        let _ = crate::windowing::emit_audio_event(&app, queue_changed_event(&state, &store));
+        Ok(())
+    }
+}
+
+command_envelope! {
+    /// Attach per-entry overrides (start offset, end position) to the queue item at `index`.
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub fn set_queue_item_overrides(
+        app: AppHandle,
+        state: State<'_, AudioPlayer>,
+        index: usize,
+        overrides: types::ui::player_details::QueueItemOverrides,
+    ) -> Result<()> {
+        let store_arc = state.get_store();
+        let mut store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        store.set_queue_item_overrides(index, overrides)?;
+        // Emit QueueChanged (entry overrides are part of the queue)
+        let _ = crate::windowing::emit_audio_event(&app, queue_changed_event(&state, &store));
+        Ok(())
+    }
+}
+
+command_envelope! {
+    /// Set the crossfade applied between consecutive queue entries and persist it
+    /// in prefs.music.playback. `duration_ms` is clamped to 12s; 0 disables it.
+    /// Enabling a crossfade clears the inter-track gap.
+    #[tracing::instrument(level = "debug", skip(state, settings))]
+    #[tauri::command]
+    pub fn audio_set_crossfade(
+        state: State<'_, AudioPlayer>,
+        settings: State<'_, SettingsConfig>,
+        duration_ms: u32,
+        curve: Option<types::settings::music::CrossfadeCurve>,
+    ) -> Result<()> {
+        use audio_player::crossfade::{CrossfadeConfig, MAX_CROSSFADE};
+        use types::settings::music::MusicPlaybackSettings;
+        let mut playback = settings
+            .load_selective::<MusicPlaybackSettings>("music.playback".to_string())
+            .unwrap_or_default();
+        let duration_ms = duration_ms.min(MAX_CROSSFADE.as_millis() as u32);
+        playback.crossfade_ms = Some(duration_ms);
+        if curve.is_some() {
+            playback.crossfade_curve = curve;
+        }
+        if duration_ms > 0 {
+            playback.track_gap_ms = Some(0);
+            state.set_track_gap(std::time::Duration::ZERO);
+        }
+        state.set_crossfade(CrossfadeConfig::from(&playback));
+        // Saving also emits settings-changed for the renderer
+        settings.save_selective("music.playback".to_string(), Some(playback))
+    }
+}
+
+command_envelope! {
+    /// Set the silence inserted between queue entries and persist it in
+    /// prefs.music.playback. `gap_ms` is clamped to 30s; 0 disables it.
+    /// Enabling a gap turns the crossfade off.
+    #[tracing::instrument(level = "debug", skip(state, settings))]
+    #[tauri::command]
+    pub fn audio_set_track_gap(
+        state: State<'_, AudioPlayer>,
+        settings: State<'_, SettingsConfig>,
+        gap_ms: u32,
+    ) -> Result<()> {
+        use audio_player::crossfade::{track_gap, CrossfadeConfig, MAX_TRACK_GAP};
+        use types::settings::music::MusicPlaybackSettings;
+        let mut playback = settings
+            .load_selective::<MusicPlaybackSettings>("music.playback".to_string())
+            .unwrap_or_default();
+        let gap_ms = gap_ms.min(MAX_TRACK_GAP.as_millis() as u32);
+        playback.track_gap_ms = Some(gap_ms);
+        if gap_ms > 0 {
+            playback.crossfade_ms = Some(0);
+        }
+        state.set_crossfade(CrossfadeConfig::from(&playback));
+        state.set_track_gap(track_gap(&playback));
+        settings.save_selective("music.playback".to_string(), Some(playback))
+    }
+}
+
+command_envelope! {
+    /// Replace the non-destructive edit regions of a track (skip or mute
+    /// ranges in seconds of the original audio); an empty list removes them.
+    /// The file is left untouched; regions apply whenever the track plays,
+    /// immediately when it is the loaded track.
+    #[tracing::instrument(level = "debug", skip(app, state, db, regions))]
+    #[tauri::command]
+    pub fn set_edit_regions(
+        app: AppHandle,
+        state: State<'_, AudioPlayer>,
+        db: State<'_, Database>,
+        track_id: String,
+        regions: Vec<types::ui::player_details::EditRegion>,
+    ) -> Result<()> {
+        db.set_edit_regions(&track_id, &regions)?;
+        let loaded = state.refresh_edit_regions(&track_id);
+        let _ = crate::windowing::emit_audio_event(
+            &app,
+            json!({
+                "type": "EditRegionsChanged",
+                "data": { "trackId": track_id, "regions": regions, "loaded": loaded },
+            }),
         );
+        Ok(())
     }
-    Ok(())
 }
 
-#[tracing::instrument(level = "debug", skip(state))]
-#[tauri::command]
-pub async fn prev_track(app: AppHandle, state: State<'_, AudioPlayer>) -> Result<()> {
-    // Delegate to core: updates index + load + play
-    let track_opt = state.play_prev().await?;
+command_envelope! {
+    /// Edit regions of a track, in seconds of the original audio
+    #[tracing::instrument(level = "debug", skip(db))]
+    #[tauri::command]
+    pub fn get_edit_regions(
+        db: State<'_, Database>,
+        track_id: String,
+    ) -> Result<Vec<types::ui::player_details::EditRegion>> {
+        db.get_edit_regions(&track_id)
+    }
+}
 
-    // Emit events for UI
-    let _ = app.emit(
-        "audio_event",
-        json!({ "type": "QueueChanged", "data": {} }),
+fn emit_loop_region(app: &AppHandle, region: Option<types::ui::player_details::LoopRegion>) {
+    let _ = crate::windowing::emit_audio_event(
+        app,
+        json!({
+            "type": "LoopRegionChanged",
+            "data": { "region": region },
+        }),
     );
-    if let Some(track) = track_opt {
-        let _ = app.emit(
-            "audio_event",
-            json!({ "type": "TrackChanged", "data": { "track": track } }),
+}
+
+command_envelope! {
+    /// Loop `start_s`..`end_s` of the loaded track (A-B repeat), in seconds as
+    /// shown, until cleared or another track loads. While it loops the entry
+    /// doesn't end, whatever the player mode. Playback outside the region
+    /// jumps to its start. Resolves to the region applied, its end clamped to
+    /// the track.
+    #[tracing::instrument(level = "debug", skip(app, state, throttle))]
+    #[tauri::command]
+    pub async fn audio_set_loop_region(
+        app: AppHandle,
+        state: State<'_, AudioPlayer>,
+        throttle: State<'_, PositionThrottle>,
+        start_s: f64,
+        end_s: f64,
+    ) -> Result<types::ui::player_details::LoopRegion> {
+        let region = state.audio_set_loop_region(start_s, end_s)?;
+        emit_loop_region(&app, Some(region));
+        let position = state
+            .get_store()
+            .lock()
+            .map(|s| s.edit_regions().to_edited(s.get_current_time()))
+            .unwrap_or_default();
+        if position < region.start || position >= region.end {
+            throttle.emit_next();
+            state.audio_seek(region.start).await?;
+        }
+        Ok(region)
+    }
+}
+
+command_envelope! {
+    /// Stop the A-B loop; playback goes on from where it is
+    #[tracing::instrument(level = "debug", skip(app, state))]
+    #[tauri::command]
+    pub fn audio_clear_loop_region(app: AppHandle, state: State<'_, AudioPlayer>) -> Result<()> {
+        if state.audio_clear_loop_region() {
+            emit_loop_region(&app, None);
+        }
+        Ok(())
+    }
+}
+
+command_envelope! {
+    /// Region of the loaded track looping, `None` without one
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub fn audio_get_loop_region(state: State<'_, AudioPlayer>) -> Result<Option<types::ui::player_details::LoopRegion>> {
+        Ok(state.audio_get_loop_region())
+    }
+}
+
+command_envelope! {
+    /// Gain loudness normalization would apply to each track, in track and
+    /// album mode, whether or not it is enabled. ReplayGain tags not cached yet
+    /// are read on the way, and take effect at once for the loaded track.
+    #[tracing::instrument(level = "debug", skip(state, db, track_ids))]
+    #[tauri::command]
+    pub fn get_normalization_preview(
+        state: State<'_, AudioPlayer>,
+        db: State<'_, Database>,
+        track_ids: Vec<String>,
+    ) -> Result<Vec<types::ui::player_details::TrackGainPreview>> {
+        use types::tracks::{GetTrackOptions, SearchableTrack};
+        let mut previews = Vec::with_capacity(track_ids.len());
+        for track_id in track_ids {
+            let Some(track) = db
+                .get_tracks_by_options(GetTrackOptions {
+                    track: Some(SearchableTrack {
+                        _id: Some(track_id.clone()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })?
+                .into_iter()
+                .next()
+            else {
+                continue;
+            };
+            let (gain, read) = gain::resolve_track_gain(&db, &track);
+            if read {
+                state.refresh_track_gain(&track_id, Some(gain));
+            }
+            previews.push(gain::preview(track_id, &track, gain));
+        }
+        Ok(previews)
+    }
+}
+
+command_envelope! {
+    /// Set how incoming calls and communication sessions affect playback and
+    /// persist it in prefs.music.playback: pause, duck to `duck_percent` of the
+    /// volume, or ignore them; `resume` restarts playback paused by a call.
+    #[tracing::instrument(level = "debug", skip(state, settings))]
+    #[tauri::command]
+    pub fn audio_set_interruption_policy(
+        state: State<'_, AudioPlayer>,
+        settings: State<'_, SettingsConfig>,
+        mode: types::settings::music::InterruptionMode,
+        duck_percent: Option<u32>,
+        resume: Option<bool>,
+    ) -> Result<()> {
+        use audio_player::interrupt::InterruptPolicy;
+        use types::settings::music::MusicPlaybackSettings;
+        let mut playback = settings
+            .load_selective::<MusicPlaybackSettings>("music.playback".to_string())
+            .unwrap_or_default();
+        playback.interruption_mode = Some(mode);
+        if let Some(percent) = duck_percent {
+            playback.interruption_duck_percent = Some(percent.min(100));
+        }
+        if resume.is_some() {
+            playback.interruption_resume = resume;
+        }
+        state.set_interrupt_policy(InterruptPolicy::from(&playback));
+        settings.save_selective("music.playback".to_string(), Some(playback))
+    }
+}
+
+command_envelope! {
+    /// Turn radio mode on or off and persist it in prefs.music.playback. While
+    /// on, a sequential queue that runs out is extended with related tracks.
+    #[tracing::instrument(level = "debug", skip(app, state, settings))]
+    #[tauri::command]
+    pub fn set_radio_mode(
+        app: AppHandle,
+        state: State<'_, AudioPlayer>,
+        settings: State<'_, SettingsConfig>,
+        enabled: bool,
+    ) -> Result<()> {
+        use types::settings::music::MusicPlaybackSettings;
+        let mut playback = settings
+            .load_selective::<MusicPlaybackSettings>("music.playback".to_string())
+            .unwrap_or_default();
+        playback.radio_mode = Some(enabled);
+        settings.save_selective("music.playback".to_string(), Some(playback))?;
+
+        let store_arc = state.get_store();
+        let mut store = store_arc
+            .lock()
+            .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+        store.set_radio_mode(enabled);
+        let _ = crate::windowing::emit_audio_event(
+            &app,
+            json!({ "type": "RadioModeChanged", "data": { "enabled": enabled } }),
         );
+        Ok(())
     }
-    Ok(())
 }
 
-#[tracing::instrument(level = "debug", skip(state))]
-#[tauri::command]
-pub fn change_index(app: AppHandle, state: State<'_, AudioPlayer>, new_index: usize, force: bool) -> Result<()> {
-    let store_arc = state.get_store();
-    let mut store = store_arc
-        .lock()
-        .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
-    store.change_index(new_index, force);
-    // Emit QueueChanged (explicit index change)
-    let _ = app.emit(
-        "audio_event",
-        json!({ "type": "QueueChanged", "data": {} }),
-    );
-    Ok(())
+command_envelope! {
+    /// Warning to show once when the saved play queue could not be restored in
+    /// full, e.g. after downgrading the app. Unreadable data is backed up.
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub fn audio_take_restore_warning(state: State<'_, AudioPlayer>) -> Result<Option<String>> {
+        Ok(state.take_restore_warning())
+    }
+}
+
+command_envelope! {
+    /// Audio output devices currently available, marking the default one and
+    /// the one playback goes to.
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub fn audio_list_output_devices(state: State<'_, AudioPlayer>) -> Result<Vec<audio_player::devices::OutputDevice>> {
+        state.list_output_devices()
+    }
+}
+
+command_envelope! {
+    /// Move playback to the output device named `device`, or back to the system
+    /// default with `None`, and persist the choice in prefs.music.playback. If
+    /// the device disappears later, playback falls back to the default and
+    /// returns once it is available again.
+    #[tracing::instrument(level = "debug", skip(state, settings))]
+    #[tauri::command]
+    pub fn audio_set_output_device(
+        state: State<'_, AudioPlayer>,
+        settings: State<'_, SettingsConfig>,
+        device: Option<String>,
+    ) -> Result<()> {
+        use types::settings::music::MusicPlaybackSettings;
+        if let Some(name) = &device {
+            if !audio_player::devices::device_names().contains(name) {
+                return Err(types::errors::MusicError::String(format!("Output device not found: {}", name)));
+            }
+        }
+        state.set_output_device(device.clone())?;
+        let mut playback = settings
+            .load_selective::<MusicPlaybackSettings>("music.playback".to_string())
+            .unwrap_or_default();
+        playback.output_device = device;
+        settings.save_selective("music.playback".to_string(), Some(playback))
+    }
+}
+
+command_envelope! {
+    /// Start recording a verbose playback trace (decoder choices, buffering,
+    /// stream resolution timings, device changes and errors) for this session.
+    /// Returns the path of the JSON lines file being written.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command]
+    pub fn start_playback_trace(app: AppHandle) -> Result<String> {
+        let dir = app
+            .path()
+            .app_log_dir()
+            .map_err(|e| types::errors::MusicError::String(e.to_string()))?;
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!(
+            "playback-trace-{}.jsonl",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        audio_player::trace::start(path.clone())?;
+        tracing::info!("Playback trace started: {}", path.display());
+        Ok(path.to_string_lossy().into_owned())
+    }
+}
+
+command_envelope! {
+    /// Stop the playback trace. Returns the path of the written file, or
+    /// nothing when no trace was running.
+    #[tracing::instrument(level = "debug")]
+    #[tauri::command]
+    pub fn stop_playback_trace() -> Result<Option<String>> {
+        Ok(audio_player::trace::stop().map(|path| path.to_string_lossy().into_owned()))
+    }
+}
+
+command_envelope! {
+    /// Stream spectrum frames of what is played on the `audio_visualizer`
+    /// event: `bands` log-spaced levels (default 64, at most 256), `rate_hz`
+    /// times a second (default 30, at most 60). Each payload is a frame of
+    /// `audio_player::visualizer` in base64. Nothing is analysed until this
+    /// is called and after `stop_audio_visualizer`.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command]
+    pub fn start_audio_visualizer(app: AppHandle, bands: Option<u16>, rate_hz: Option<u32>) -> Result<()> {
+        use audio_player::visualizer::VisualizerConfig;
+        use tauri::Emitter;
+        let defaults = VisualizerConfig::default();
+        let config = VisualizerConfig {
+            bands: bands.unwrap_or(defaults.bands),
+            rate_hz: rate_hz.unwrap_or(defaults.rate_hz),
+        };
+        audio_player::visualizer::start(config, move |frame| {
+            let _ = app.emit("audio_visualizer", frame);
+        });
+        Ok(())
+    }
+}
+
+command_envelope! {
+    /// Stop the spectrum frames started by `start_audio_visualizer`
+    #[tracing::instrument(level = "debug")]
+    #[tauri::command]
+    pub fn stop_audio_visualizer() -> Result<()> {
+        audio_player::visualizer::stop();
+        Ok(())
+    }
 }
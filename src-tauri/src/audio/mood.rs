@@ -0,0 +1,145 @@
+//! Mood tagging: energy, valence, danceability and tempo are extracted from the
+//! decoded audio of local tracks, classified into a mood bucket and stored in
+//! the database. Tracks never analyzed are picked up in the background after
+//! a scan; `reclassify` redoes given tracks on request.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use database::database::Database;
+use macros::command_envelope;
+use tauri::{AppHandle, Emitter, Manager, State};
+use types::entities::TrackAudioFeatures;
+use types::errors::{MusicError, Result};
+use types::tracks::{GetTrackOptions, MediaContent, SearchableTrack, TrackType};
+
+/// Tracks analyzed per background batch
+const BATCH: i64 = 25;
+
+/// Set while the background analysis runs, so scans don't start another one
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+fn find_track(database: &Database, track_id: &str) -> Option<MediaContent> {
+    database
+        .get_tracks_by_options(GetTrackOptions {
+            track: Some(SearchableTrack {
+                _id: Some(track_id.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .ok()?
+        .into_iter()
+        .next()
+}
+
+fn analyze(track: &MediaContent) -> Result<TrackAudioFeatures> {
+    let path = track
+        .track
+        .path
+        .as_deref()
+        .filter(|_| track.track.type_ == TrackType::LOCAL)
+        .ok_or_else(|| MusicError::String("Only local tracks can be analyzed".into()))?;
+    audio_player::analysis::analyze_file(Path::new(path))
+}
+
+/// Analyze `track_ids` and store their features. Tracks that can't be found or
+/// decoded are logged and left out of the result.
+fn classify(database: &Database, track_ids: &[String]) -> HashMap<String, TrackAudioFeatures> {
+    let mut classified = HashMap::new();
+    for track_id in track_ids {
+        let Some(track) = find_track(database, track_id) else {
+            tracing::debug!("Not classifying unknown track {}", track_id);
+            continue;
+        };
+        match analyze(&track) {
+            Ok(features) => {
+                classified.insert(track_id.clone(), features);
+            }
+            Err(e) => tracing::warn!("Failed to analyze {}: {:?}", track_id, e),
+        }
+    }
+    let rows: Vec<(String, TrackAudioFeatures)> = classified.iter().map(|(id, f)| (id.clone(), *f)).collect();
+    if let Err(e) = database.set_track_features(&rows) {
+        tracing::warn!("Failed to store the features of {} tracks: {:?}", rows.len(), e);
+    }
+    classified
+}
+
+fn emit_updated(app: &AppHandle, classified: &HashMap<String, TrackAudioFeatures>) {
+    if classified.is_empty() {
+        return;
+    }
+    if let Err(e) = app.emit("track-features-updated", classified) {
+        tracing::warn!("Failed to emit track-features-updated event: {}", e);
+    }
+}
+
+/// Analyze the local tracks never analyzed, in batches on a background
+/// thread. Does nothing when the analysis is already running.
+pub fn spawn_auto_classify(app: &AppHandle) {
+    if RUNNING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let database = app.state::<Database>();
+        // Undecodable files stay unanalyzed; don't pick them up again
+        let mut failed: HashSet<String> = HashSet::new();
+        loop {
+            let pending: Vec<String> = match database.get_unanalyzed_track_ids(BATCH + failed.len() as i64) {
+                Ok(ids) => ids.into_iter().filter(|id| !failed.contains(id)).collect(),
+                Err(e) => {
+                    tracing::warn!("Failed to list tracks to analyze: {:?}", e);
+                    break;
+                }
+            };
+            if pending.is_empty() {
+                break;
+            }
+            let classified = classify(&database, &pending);
+            failed.extend(pending.into_iter().filter(|id| !classified.contains_key(id)));
+            emit_updated(&app, &classified);
+        }
+        RUNNING.store(false, Ordering::Release);
+    });
+}
+
+/// Analyzed library tracks in the mood of `seed` and closest to it, loaded for
+/// the queue. Empty when the seed was never analyzed.
+pub fn similar_tracks(database: &Database, seed: &str, exclude: &[String], limit: usize) -> Vec<MediaContent> {
+    match database.similar_mood_track_ids(seed, exclude, limit as i64) {
+        Ok(ids) => ids.iter().filter_map(|id| find_track(database, id)).collect(),
+        Err(e) => {
+            tracing::warn!("Failed to find tracks in the mood of {}: {:?}", seed, e);
+            Vec::new()
+        }
+    }
+}
+
+command_envelope! {
+    /// Analyze the given tracks again and replace their stored features.
+    /// Returns the new features by track ID; tracks that couldn't be analyzed
+    /// (streams, missing or undecodable files) are left out.
+    #[tracing::instrument(level = "debug", skip(app, database))]
+    #[tauri::command(async)]
+    pub fn reclassify(
+        app: AppHandle,
+        database: State<'_, Database>,
+        track_ids: Vec<String>,
+    ) -> Result<HashMap<String, TrackAudioFeatures>> {
+        let classified = classify(&database, &track_ids);
+        emit_updated(&app, &classified);
+        Ok(classified)
+    }
+}
+
+command_envelope! {
+    /// Stored audio features and mood of a track, `None` until it is analyzed.
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn get_track_features(database: State<'_, Database>, track_id: String) -> Result<Option<TrackAudioFeatures>> {
+        database.get_track_features(&track_id)
+    }
+}
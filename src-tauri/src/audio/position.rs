@@ -0,0 +1,101 @@
+//! Rate of `PositionChanged` events. Backends report the position as often as
+//! they like; windows get it at most `position_update_hz` times a second while
+//! one of them has focus and `background_position_update_hz` times otherwise.
+//! Repeated positions are dropped, and seeks, play/pause changes and a window
+//! regaining focus let the next position through at once.
+
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use types::settings::music::MusicPlaybackSettings;
+
+const DEFAULT_FOREGROUND_HZ: f64 = 4.0;
+const DEFAULT_BACKGROUND_HZ: f64 = 0.5;
+const MIN_HZ: f64 = 0.1;
+const MAX_HZ: f64 = 30.0;
+
+/// Managed by Tauri, fed by the window focus events
+#[derive(Default)]
+pub struct PositionThrottle {
+    inner: Mutex<ThrottleState>,
+}
+
+struct ThrottleState {
+    foreground: Duration,
+    background: Duration,
+    /// Labels of the focused windows
+    focused: BTreeSet<String>,
+    last_emit: Option<Instant>,
+    last_position: Option<f64>,
+    /// Let the next position through regardless of the rate
+    immediate: bool,
+}
+
+impl Default for ThrottleState {
+    fn default() -> Self {
+        Self {
+            foreground: interval(None, DEFAULT_FOREGROUND_HZ),
+            background: interval(None, DEFAULT_BACKGROUND_HZ),
+            focused: BTreeSet::new(),
+            last_emit: None,
+            last_position: None,
+            immediate: true,
+        }
+    }
+}
+
+fn interval(hz: Option<f64>, default: f64) -> Duration {
+    let hz = hz.filter(|hz| hz.is_finite()).unwrap_or(default).clamp(MIN_HZ, MAX_HZ);
+    Duration::from_secs_f64(1.0 / hz)
+}
+
+impl PositionThrottle {
+    /// Rates from prefs.music.playback
+    pub fn configure(&self, playback: &MusicPlaybackSettings) {
+        let Ok(mut inner) = self.inner.lock() else { return };
+        inner.foreground = interval(playback.position_update_hz, DEFAULT_FOREGROUND_HZ);
+        inner.background = interval(playback.background_position_update_hz, DEFAULT_BACKGROUND_HZ);
+    }
+
+    /// Window `label` gained or lost focus, or closed
+    pub fn set_focused(&self, label: &str, focused: bool) {
+        let Ok(mut inner) = self.inner.lock() else { return };
+        let was_foreground = !inner.focused.is_empty();
+        if focused {
+            inner.focused.insert(label.to_string());
+        } else {
+            inner.focused.remove(label);
+        }
+        // Back in front: don't leave the progress bar behind until the next tick
+        if !was_foreground && !inner.focused.is_empty() {
+            inner.immediate = true;
+        }
+    }
+
+    /// Let the next position through at once, e.g. after a seek
+    pub fn emit_next(&self) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.immediate = true;
+        }
+    }
+
+    /// Whether to emit `position` now, recording it as emitted if so
+    pub fn admit(&self, position: f64) -> bool {
+        let Ok(mut inner) = self.inner.lock() else { return true };
+        let now = Instant::now();
+        if !inner.immediate {
+            if inner.last_position == Some(position) {
+                return false;
+            }
+            let interval = if inner.focused.is_empty() { inner.background } else { inner.foreground };
+            if inner.last_emit.is_some_and(|last| now.duration_since(last) < interval) {
+                return false;
+            }
+        }
+        inner.immediate = false;
+        inner.last_emit = Some(now);
+        inner.last_position = Some(position);
+        true
+    }
+}
@@ -0,0 +1,115 @@
+//! Album pre-caching: while an album plays straight through, open the local
+//! files of the next few tracks and resolve the streams of online ones ahead of
+//! time, so skipping to them does not wait on disk or on the media plugins.
+//! Streams are not resolved ahead on cellular connections unless allowed.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use audio_player::players::rodio::RodioPlayer;
+use database::database::Database;
+use music_plugin_sdk::types::media::StreamSource;
+use ::settings::settings::SettingsConfig;
+use tauri::{AppHandle, Manager, State};
+use tokio::time::timeout;
+use types::settings::music::MusicPlaybackSettings;
+use types::tracks::{MediaContent, TrackType};
+
+use super::resolve_stream_source;
+
+/// Upper bound of the configurable lookahead
+const MAX_DEPTH: u32 = 5;
+const DEFAULT_DEPTH: u32 = 2;
+/// Resolved streams older than this are resolved again on load
+const STREAM_TTL: Duration = Duration::from_secs(10 * 60);
+/// Time limit for resolving one upcoming stream
+const RESOLVE_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Streams resolved ahead of playback, keyed by track id
+#[derive(Default)]
+pub struct PrecacheState {
+    streams: Mutex<HashMap<String, (Instant, StreamSource)>>,
+}
+
+impl PrecacheState {
+    /// Take the pre-resolved stream of `track_id` if it is still fresh.
+    pub fn take(&self, track_id: &str) -> Option<StreamSource> {
+        let (resolved_at, stream) = self.streams.lock().ok()?.remove(track_id)?;
+        let expired = stream.expires_at.is_some_and(|at| at <= chrono::Utc::now());
+        (resolved_at.elapsed() < STREAM_TTL && !expired).then_some(stream)
+    }
+
+    fn is_fresh(&self, track_id: &str) -> bool {
+        self.streams
+            .lock()
+            .map(|s| s.get(track_id).is_some_and(|(at, _)| at.elapsed() < STREAM_TTL))
+            .unwrap_or(false)
+    }
+
+    fn insert(&self, track_id: String, stream: StreamSource) {
+        if let Ok(mut streams) = self.streams.lock() {
+            streams.retain(|_, (at, _)| at.elapsed() < STREAM_TTL);
+            streams.insert(track_id, (Instant::now(), stream));
+        }
+    }
+}
+
+/// Lookahead depth from prefs.music.playback.albumPrecacheDepth
+pub fn lookahead_depth(app: &AppHandle) -> usize {
+    let settings: State<'_, SettingsConfig> = app.state();
+    settings
+        .load_selective::<MusicPlaybackSettings>("music.playback".to_string())
+        .unwrap_or_default()
+        .album_precache_depth
+        .unwrap_or(DEFAULT_DEPTH)
+        .min(MAX_DEPTH) as usize
+}
+
+/// Prepare `upcoming` album tracks in the background: probe local and
+/// downloaded files, pre-resolve the streams of online tracks.
+pub fn precache_tracks(app: &AppHandle, upcoming: Vec<MediaContent>) {
+    if upcoming.is_empty() {
+        return;
+    }
+    // Resolving ahead costs data that may never be used
+    let allow_prefetch = crate::network::data_policy(app).allow_prefetch;
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        for track in upcoming {
+            let Some(track_id) = track.track._id.clone() else { continue };
+
+            let db: State<'_, Database> = app.state();
+            let local_path = match track.track.type_ {
+                TrackType::LOCAL => track.track.path.clone(),
+                _ => db
+                    .get_download(&track_id)
+                    .ok()
+                    .flatten()
+                    .map(|d| d.path)
+                    .filter(|p| std::path::Path::new(p).exists()),
+            };
+
+            if let Some(path) = local_path {
+                let probed = tauri::async_runtime::spawn_blocking(move || RodioPlayer::probe_local_file(&path)).await;
+                if let Ok(Err(e)) = probed {
+                    tracing::debug!("Precache: failed to probe {}: {}", track_id, e);
+                }
+                continue;
+            }
+
+            let precache: State<'_, PrecacheState> = app.state();
+            if !allow_prefetch || precache.is_fresh(&track_id) {
+                continue;
+            }
+            match timeout(RESOLVE_TIMEOUT, resolve_stream_source(&app, &track_id)).await {
+                Ok(Ok(stream)) => {
+                    tracing::debug!("Precache: resolved stream of {}", track_id);
+                    precache.insert(track_id, stream);
+                }
+                Ok(Err(e)) => tracing::debug!("Precache: failed to resolve {}: {}", track_id, e),
+                Err(_) => tracing::warn!("Precache: resolving {} timed out", track_id),
+            }
+        }
+    });
+}
@@ -0,0 +1,243 @@
+//! Output profiles: a device, volume, volume cap, crossfade, crossfeed level
+//! and effects chain (EQ included) switched together by name, from a command,
+//! a global shortcut, or when the profile's device is connected or playback
+//! moves to it. Profiles live in `prefs.music.outputProfiles`.
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use ::settings::settings::SettingsConfig;
+use audio_player::AudioPlayer;
+use macros::command_envelope;
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use types::errors::{MusicError, Result};
+use types::settings::music::{MusicPlaybackSettings, OutputProfile, OutputProfileSettings};
+
+/// How often the device list is checked for newly connected devices
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Global shortcuts currently registered, as (shortcut, profile name)
+#[derive(Default)]
+pub struct OutputProfileHotkeys {
+    registered: Mutex<Vec<(String, String)>>,
+}
+
+fn load_profiles(settings: &SettingsConfig) -> OutputProfileSettings {
+    settings
+        .load_selective::<OutputProfileSettings>("music.outputProfiles".to_string())
+        .unwrap_or_default()
+}
+
+/// The profile with `auto_switch` on whose device matches `is_device`
+fn auto_switch_profile(config: &OutputProfileSettings, is_device: impl Fn(&String) -> bool) -> Option<&OutputProfile> {
+    config.profiles.iter().find(|p| {
+        p.auto_switch.unwrap_or(false) && p.device.as_ref().is_some_and(|d| !d.is_empty() && is_device(d))
+    })
+}
+
+/// Apply the profile named `name`; fields it leaves unset keep their value,
+/// except the volume cap, which belongs to the active profile
+pub async fn apply_profile(app: &AppHandle, name: &str) -> Result<OutputProfile> {
+    apply_profile_with(app, name, true).await
+}
+
+/// Apply the profile named `name`, leaving the output device alone unless
+/// `switch_device` is set
+async fn apply_profile_with(app: &AppHandle, name: &str, switch_device: bool) -> Result<OutputProfile> {
+    use audio_player::crossfade::{CrossfadeConfig, MAX_CROSSFADE};
+
+    let settings = app.state::<SettingsConfig>();
+    let player = app.state::<AudioPlayer>();
+    let mut config = load_profiles(&settings);
+    let profile = config
+        .profiles
+        .iter()
+        .find(|p| p.name == name)
+        .cloned()
+        .ok_or_else(|| MusicError::String(format!("Output profile not found: {}", name)))?;
+
+    let mut playback = settings
+        .load_selective::<MusicPlaybackSettings>("music.playback".to_string())
+        .unwrap_or_default();
+    if let Some(device) = profile.device.as_ref().filter(|_| switch_device) {
+        // Empty follows the system default, as in the playback settings
+        let device = Some(device.clone()).filter(|d| !d.is_empty());
+        if let Some(device) = &device {
+            if !audio_player::devices::device_names().contains(device) {
+                return Err(MusicError::String(format!("Output device not found: {}", device)));
+            }
+        }
+        player.set_output_device(device)?;
+        playback.output_device = profile.device.clone();
+    }
+    if let Some(level) = profile.crossfeed {
+        audio_player::crossfeed::set_level(level);
+        playback.crossfeed = Some(level);
+    }
+    if let Some(duration_ms) = profile.crossfade_ms {
+        // Same rules as audio_set_crossfade: a crossfade turns the track gap off
+        let duration_ms = duration_ms.min(MAX_CROSSFADE.as_millis() as u32);
+        playback.crossfade_ms = Some(duration_ms);
+        if duration_ms > 0 {
+            playback.track_gap_ms = Some(0);
+            player.set_track_gap(Duration::ZERO);
+        }
+    }
+    if let Some(curve) = profile.crossfade_curve {
+        playback.crossfade_curve = Some(curve);
+    }
+    if profile.crossfade_ms.is_some() || profile.crossfade_curve.is_some() {
+        player.set_crossfade(CrossfadeConfig::from(&playback));
+    }
+    settings.save_selective("music.playback".to_string(), Some(playback))?;
+
+    player.set_output_cap(profile.volume_clamp);
+    if let Some(volume) = profile.volume {
+        let volume = volume.clamp(0.0, 1.0);
+        player.audio_set_volume(volume).await?;
+        let _ = crate::windowing::emit_audio_event(
+            app,
+            json!({ "type": "VolumeChanged", "data": { "volume": volume } }),
+        );
+    }
+    if let Some(effects) = &profile.effects {
+        settings.save_selective("music.effects".to_string(), Some(effects.clone()))?;
+    }
+
+    config.active = Some(profile.name.clone());
+    settings.save_selective("music.outputProfiles".to_string(), Some(config))?;
+    tracing::info!("Applied output profile {}", profile.name);
+    let _ = crate::windowing::emit_audio_event(
+        app,
+        json!({ "type": "OutputProfileApplied", "data": { "profile": profile } }),
+    );
+    Ok(profile)
+}
+
+fn spawn_apply(app: &AppHandle, name: String) {
+    spawn_apply_with(app, name, true);
+}
+
+fn spawn_apply_with(app: &AppHandle, name: String, switch_device: bool) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = apply_profile_with(&app, &name, switch_device).await {
+            tracing::warn!("Failed to apply output profile {}: {:?}", name, e);
+        }
+    });
+}
+
+/// Register the global shortcuts of the profiles, replacing the previous ones.
+/// Does nothing when they are unchanged.
+#[cfg(desktop)]
+pub fn register_profile_hotkeys(app: &AppHandle) {
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+    let bindings: Vec<(String, String)> = load_profiles(&app.state::<SettingsConfig>())
+        .profiles
+        .into_iter()
+        .filter_map(|p| {
+            let hotkey = p.hotkey.map(|h| h.trim().to_string()).filter(|h| !h.is_empty())?;
+            Some((hotkey, p.name))
+        })
+        .collect();
+    let state = app.state::<OutputProfileHotkeys>();
+    let mut registered = state.registered.lock().unwrap_or_else(|e| e.into_inner());
+    if *registered == bindings {
+        return;
+    }
+
+    let shortcuts = app.global_shortcut();
+    for (hotkey, _) in registered.drain(..) {
+        if let Err(e) = shortcuts.unregister(hotkey.as_str()) {
+            tracing::warn!("Failed to unregister shortcut {}: {}", hotkey, e);
+        }
+    }
+    for (hotkey, name) in bindings {
+        let profile = name.clone();
+        let result = shortcuts.on_shortcut(hotkey.as_str(), move |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                spawn_apply(app, profile.clone());
+            }
+        });
+        match result {
+            Ok(()) => registered.push((hotkey, name)),
+            Err(e) => tracing::warn!("Failed to register shortcut {} of output profile {}: {}", hotkey, name, e),
+        }
+    }
+}
+
+#[cfg(mobile)]
+pub fn register_profile_hotkeys(_app: &AppHandle) {}
+
+/// Watch for output devices being connected and apply the profile of the
+/// device, if it has one with `auto_switch` on
+pub fn start_device_watcher(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut known: HashSet<String> = audio_player::devices::device_names().into_iter().collect();
+        loop {
+            std::thread::sleep(DEVICE_POLL_INTERVAL);
+            let current: HashSet<String> = audio_player::devices::device_names().into_iter().collect();
+            // An empty list is a failed enumeration more often than no devices
+            if current.is_empty() {
+                continue;
+            }
+            let connected: Vec<&String> = current.difference(&known).collect();
+            if !connected.is_empty() {
+                let config = load_profiles(&app.state::<SettingsConfig>());
+                if let Some(profile) = auto_switch_profile(&config, |d| connected.contains(&d)) {
+                    tracing::info!("Output device {:?} connected, applying its profile", profile.device);
+                    spawn_apply(&app, profile.name.clone());
+                }
+            }
+            known = current;
+        }
+    });
+}
+
+/// Playback moved to the output device `name`: apply the profile of the
+/// device, if it has one with `auto_switch` on and it isn't already active.
+/// When playback only fell back to the device because the chosen one is gone,
+/// the choice is kept so playback still returns to it.
+pub fn on_output_device_changed(app: &AppHandle, name: &str) {
+    let config = load_profiles(&app.state::<SettingsConfig>());
+    let Some(profile) = auto_switch_profile(&config, |d| d == name) else {
+        return;
+    };
+    if config.active.as_deref() == Some(profile.name.as_str()) {
+        return;
+    }
+    // The first device is opened before the player is managed
+    let Some(player) = app.try_state::<AudioPlayer>() else {
+        return;
+    };
+    let chosen = player.get_output_device();
+    let switch_device = chosen.is_none() || chosen.as_deref() == Some(name);
+    tracing::info!("Playback moved to output device {}, applying profile {}", name, profile.name);
+    spawn_apply_with(app, profile.name.clone(), switch_device);
+}
+
+/// Put back the volume cap of the active profile, which the player doesn't
+/// keep across launches
+pub fn restore_output_cap(app: &AppHandle) {
+    let config = load_profiles(&app.state::<SettingsConfig>());
+    let cap = config
+        .active
+        .as_ref()
+        .and_then(|active| config.profiles.iter().find(|p| &p.name == active))
+        .and_then(|p| p.volume_clamp);
+    if cap.is_some() {
+        app.state::<AudioPlayer>().set_output_cap(cap);
+    }
+}
+
+command_envelope! {
+    /// Switch to the output profile named `name`. Returns the applied profile.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command]
+    pub async fn apply_output_profile(app: AppHandle, name: String) -> Result<OutputProfile> {
+        apply_profile(&app, &name).await
+    }
+}
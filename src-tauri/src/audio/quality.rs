@@ -0,0 +1,172 @@
+//! Stream quality policy. The quality requested from a provider comes from
+//! `prefs.music.quality`: the provider's own policy, else the default one,
+//! picking the Wi-Fi or metered preference by the connection reported. On
+//! cellular connections the cap of `prefs.music.mobileData` applies on top,
+//! and so does the session cap set when playback keeps stalling.
+
+use std::sync::Mutex;
+
+use ::settings::settings::SettingsConfig;
+use audio_player::AudioPlayer;
+use database::database::Database;
+use macros::command_envelope;
+use music_plugin_sdk::types::media::{QualityPreference, StreamFormatPreference, StreamRequest};
+use serde_json::json;
+use tauri::{AppHandle, Manager, State};
+use types::errors::{MusicError, Result};
+use types::settings::music::{CellularStreamQuality, MusicQualitySettings, StreamQuality, StreamQualityPolicy};
+
+use crate::network::{data_policy, is_metered};
+
+/// Cap lowered step by step while streams keep stalling, until the app
+/// restarts or the user picks a quality
+static SESSION_CAP: Mutex<Option<StreamQuality>> = Mutex::new(None);
+
+fn load_quality_settings(settings: &SettingsConfig) -> MusicQualitySettings {
+    settings
+        .load_selective::<MusicQualitySettings>("music.quality".to_string())
+        .unwrap_or_default()
+}
+
+/// Preferred quality of `plugin_id` on the current connection, `None` when
+/// neither its policy nor the default one sets it
+fn preferred_quality(config: &MusicQualitySettings, plugin_id: &str, metered: bool) -> Option<StreamQuality> {
+    let pick = |policy: &StreamQualityPolicy| if metered { policy.metered } else { policy.wifi };
+    config
+        .providers
+        .get(plugin_id)
+        .and_then(pick)
+        .or_else(|| config.default.as_ref().and_then(pick))
+}
+
+fn cap_quality(cap: CellularStreamQuality) -> Option<StreamQuality> {
+    match cap {
+        CellularStreamQuality::Low => Some(StreamQuality::Low),
+        CellularStreamQuality::Medium => Some(StreamQuality::Normal),
+        CellularStreamQuality::High => Some(StreamQuality::High),
+        CellularStreamQuality::Unrestricted => None,
+    }
+}
+
+/// Preference lowered to `cap`; an unset preference becomes the cap itself
+fn apply_cap(quality: Option<StreamQuality>, cap: Option<StreamQuality>) -> Option<StreamQuality> {
+    match (quality, cap) {
+        (Some(quality), Some(cap)) => Some(quality.min(cap)),
+        (quality, cap) => quality.or(cap),
+    }
+}
+
+fn to_preference(quality: Option<StreamQuality>) -> QualityPreference {
+    match quality {
+        Some(StreamQuality::Low) => QualityPreference::Low,
+        Some(StreamQuality::Normal) => QualityPreference::Medium,
+        Some(StreamQuality::High) => QualityPreference::High,
+        Some(StreamQuality::Lossless) => QualityPreference::Lossless,
+        None => QualityPreference::Auto,
+    }
+}
+
+/// Quality requested from `plugin_id` on the current connection, `None` when
+/// the provider chooses
+fn effective_quality(app: &AppHandle, plugin_id: &str) -> Option<StreamQuality> {
+    let config = load_quality_settings(&app.state::<SettingsConfig>());
+    let quality = preferred_quality(&config, plugin_id, is_metered(app));
+    let cap = data_policy(app).stream_quality.and_then(cap_quality);
+    let session_cap = *SESSION_CAP.lock().unwrap();
+    apply_cap(apply_cap(quality, cap), session_cap)
+}
+
+/// Stream request for playing a track from `plugin_id` on the current connection
+pub fn stream_request(app: &AppHandle, plugin_id: &str) -> StreamRequest {
+    StreamRequest {
+        format: StreamFormatPreference::Auto,
+        quality: to_preference(effective_quality(app, plugin_id)),
+        extra: None,
+    }
+}
+
+/// Lower the session cap one step below the quality now requested from
+/// `plugin_id`; a provider choosing itself is capped to normal. Returns the
+/// new cap, `None` when the quality is already the lowest.
+pub fn lower_session_cap(app: &AppHandle, plugin_id: &str) -> Option<StreamQuality> {
+    let lowered = match effective_quality(app, plugin_id) {
+        Some(StreamQuality::Lossless) => StreamQuality::High,
+        Some(StreamQuality::High) | None => StreamQuality::Normal,
+        Some(StreamQuality::Normal) => StreamQuality::Low,
+        Some(StreamQuality::Low) => return None,
+    };
+    *SESSION_CAP.lock().unwrap() = Some(lowered);
+    Some(lowered)
+}
+
+/// Resolve the streamed track playing again, at the quality now requested,
+/// and resume it where it was, playing when `play` says so and else in the
+/// state it was. Returns false when nothing needed it: no track, a local file
+/// or a download.
+pub(crate) async fn reload_current_stream(app: &AppHandle, play: Option<bool>) -> Result<bool> {
+    let state = app.state::<AudioPlayer>();
+    // Local files and downloads have a single quality
+    let current = state.get_store().lock().ok().and_then(|store| store.get_current_track());
+    let Some(mut track) = current.filter(|t| t.track.playback_url.is_some()) else {
+        return Ok(false);
+    };
+    let track_id = track
+        .track
+        ._id
+        .clone()
+        .ok_or_else(|| MusicError::String("No track ID found".into()))?;
+    if let Ok(Some(download)) = app.state::<Database>().get_download(&track_id) {
+        if std::path::Path::new(&download.path).exists() {
+            return Ok(false);
+        }
+    }
+    let stream = super::resolve_stream_source(app, &track_id).await?;
+    if let Some(headers) = stream.headers {
+        state.set_url_headers(stream.url.clone(), headers.into_iter().collect());
+    }
+    track.track.playback_url = Some(stream.url);
+    match play {
+        Some(play) => state.reload_current_with(&mut track, play).await?,
+        None => state.reload_current(&mut track).await?,
+    }
+    Ok(true)
+}
+
+command_envelope! {
+    /// Set the preferred stream quality on the current connection type, for
+    /// `plugin_id` only when given. `None` clears it. A streamed track playing
+    /// is re-resolved at the new quality and resumes where it was. Lifts the
+    /// cap set after repeated stalls.
+    #[tracing::instrument(level = "debug", skip(app, settings))]
+    #[tauri::command]
+    pub async fn audio_set_quality(
+        app: AppHandle,
+        settings: State<'_, SettingsConfig>,
+        quality: Option<StreamQuality>,
+        plugin_id: Option<String>,
+        pluginId: Option<String>,
+    ) -> Result<()> {
+        let metered = is_metered(&app);
+        let mut config = load_quality_settings(&settings);
+        let policy = match plugin_id.or(pluginId) {
+            Some(pid) => config.providers.entry(pid).or_default(),
+            None => config.default.get_or_insert_with(Default::default),
+        };
+        if metered {
+            policy.metered = quality;
+        } else {
+            policy.wifi = quality;
+        }
+        settings.save_selective("music.quality".to_string(), Some(config))?;
+        SESSION_CAP.lock().unwrap().take();
+
+        if !reload_current_stream(&app, None).await? {
+            return Ok(());
+        }
+        let _ = crate::windowing::emit_audio_event(
+            &app,
+            json!({ "type": "QualityChanged", "data": { "quality": quality } }),
+        );
+        Ok(())
+    }
+}
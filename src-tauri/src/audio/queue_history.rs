@@ -0,0 +1,74 @@
+//! Jump-back-in history: play queues replaced or cleared are kept by the
+//! player store, so an earlier listening session can be queued again.
+
+use audio_player::AudioPlayer;
+use database::database::Database;
+use macros::command_envelope;
+use tauri::{AppHandle, State};
+use types::entities::{QueueRestore, QueueSnapshot};
+use types::errors::{MusicError, Result};
+use types::tracks::{GetTrackOptions, MediaContent, SearchableTrack};
+
+/// Library tracks of a snapshot in queue order, skipping those no longer there
+fn snapshot_tracks(database: &Database, snapshot: &QueueSnapshot) -> Vec<MediaContent> {
+    snapshot
+        .track_ids
+        .iter()
+        .filter_map(|track_id| {
+            database
+                .get_tracks_by_options(GetTrackOptions {
+                    track: Some(SearchableTrack {
+                        _id: Some(track_id.clone()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+                .ok()
+                .and_then(|tracks| tracks.into_iter().next())
+        })
+        .collect()
+}
+
+command_envelope! {
+    /// Play queues replaced or cleared, newest first
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn list_queue_history(database: State<'_, Database>) -> Result<Vec<QueueSnapshot>> {
+        database.get_queue_history()
+    }
+}
+
+command_envelope! {
+    /// Replace the queue with one from `list_queue_history`. The queue
+    /// replaced is kept in the history in turn; tracks no longer in the
+    /// library are left out.
+    #[tracing::instrument(level = "debug", skip(app, database, state))]
+    #[tauri::command(async)]
+    pub fn restore_queue_from_history(
+        app: AppHandle,
+        database: State<'_, Database>,
+        state: State<'_, AudioPlayer>,
+        history_id: String,
+    ) -> Result<QueueRestore> {
+        let Some(snapshot) = database.get_queue_snapshot(&history_id)? else {
+            return Err(format!("No queue {} in the history", history_id).into());
+        };
+        let tracks = snapshot_tracks(&database, &snapshot);
+        if tracks.is_empty() {
+            return Err("None of the tracks of this queue are in the library anymore".into());
+        }
+        let restored = QueueRestore {
+            track_count: tracks.len() as u32,
+            missing_tracks: (snapshot.track_ids.len() - tracks.len()) as u32,
+        };
+
+        let store_arc = state.get_store();
+        let mut store = store_arc
+            .lock()
+            .map_err(|_| MusicError::from("Failed to access player store"))?;
+        store.clear_queue();
+        store.add_to_queue_allow_duplicates(tracks);
+        let _ = crate::windowing::emit_audio_event(&app, super::queue_changed_event(&state, &store));
+        Ok(restored)
+    }
+}
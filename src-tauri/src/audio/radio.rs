@@ -0,0 +1,132 @@
+//! Radio mode: once a sequential queue runs out, extend it with tracks the
+//! media plugins consider related to the last one and keep playing. When no
+//! plugin has any, library tracks in the same mood are used instead.
+
+use std::time::Duration;
+
+use audio_player::AudioPlayer;
+use database::database::Database;
+use music_plugin_sdk::types::Track as SdkTrack;
+use serde_json::json;
+use tauri::{AppHandle, Manager, State};
+use tokio::time::timeout;
+use types::{
+    entities::{QueryableAlbum, QueryableArtist},
+    tracks::{MediaContent, TrackType, Tracks},
+};
+
+use crate::plugins::manager::PluginHandler;
+
+/// Tracks requested per extension
+const RADIO_BATCH: usize = 10;
+/// Per-provider time limit for a related tracks lookup
+const RELATED_TIMEOUT: Duration = Duration::from_secs(8);
+
+/// Ask the enabled media plugins for tracks related to `seed`; the first
+/// provider returning any wins.
+async fn fetch_related(plugin_handler: &PluginHandler, seed: &str) -> Vec<SdkTrack> {
+    let selection = types::settings::music::MusicSourceSelection::default();
+    let providers = match plugin_handler
+        .plugin_manager()
+        .get_audio_providers_by_selection(&selection)
+        .await
+    {
+        Ok(providers) => providers,
+        Err(e) => {
+            tracing::warn!("Radio: failed to get audio providers: {}", e);
+            return Vec::new();
+        }
+    };
+
+    for (provider_id, provider) in providers {
+        let plugin = provider.lock().await;
+        match timeout(RELATED_TIMEOUT, plugin.get_related_tracks(seed, RADIO_BATCH)).await {
+            Ok(Ok(tracks)) if !tracks.is_empty() => return tracks,
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => tracing::debug!("Radio: provider {} has no related tracks: {}", provider_id, e),
+            Err(_) => tracing::warn!("Radio: provider {} timed out", provider_id),
+        }
+    }
+    Vec::new()
+}
+
+pub(crate) fn to_media_content(track: SdkTrack) -> MediaContent {
+    let cover = track
+        .cover_url
+        .clone()
+        .or_else(|| track.album_ref.as_ref().and_then(|a| a.images.first()).map(|i| i.url.clone()));
+    MediaContent {
+        track: Tracks {
+            _id: Some(track.id),
+            title: Some(track.title),
+            duration: track.duration.map(|ms| ms as f64 / 1000.0),
+            type_: TrackType::URL,
+            url: track.url,
+            track_cover_path_high: cover.clone(),
+            track_cover_path_low: cover,
+            provider_extension: track.provider,
+            ..Default::default()
+        },
+        album: track.album.map(|name| QueryableAlbum {
+            album_name: Some(name),
+            ..Default::default()
+        }),
+        artists: Some(vec![QueryableArtist {
+            artist_name: Some(track.artist),
+            ..Default::default()
+        }]),
+        ..Default::default()
+    }
+}
+
+/// Extend the queue after sequential playback stopped on its last entry and
+/// play the first added track. Emits `RadioTracksAdded` with the new queue
+/// instance IDs so the UI can mark them as auto-generated.
+#[tracing::instrument(level = "debug", skip(app))]
+pub async fn extend_queue(app: AppHandle, seed: String) {
+    let mut tracks: Vec<MediaContent> = fetch_related(app.state::<PluginHandler>().inner(), &seed)
+        .await
+        .into_iter()
+        .map(to_media_content)
+        .collect();
+    let audio_state: State<'_, AudioPlayer> = app.state();
+    if tracks.is_empty() {
+        let queued: Vec<String> = audio_state
+            .get_store()
+            .lock()
+            .map(|store| store.get_queue_tracks().into_iter().filter_map(|t| t.track._id).collect())
+            .unwrap_or_default();
+        tracks = super::mood::similar_tracks(&app.state::<Database>(), &seed, &queued, RADIO_BATCH);
+    }
+    if tracks.is_empty() {
+        tracing::info!("Radio: no related tracks for {}", seed);
+        return;
+    }
+
+    let next = {
+        let store_arc = audio_state.get_store();
+        let Ok(mut store) = store_arc.lock() else { return };
+        // The user may have queued or played something else in the meantime
+        if !store.needs_radio_extension() {
+            return;
+        }
+        let added = store.append_radio_tracks(tracks);
+        if added.is_empty() {
+            return;
+        }
+        let _ = crate::windowing::emit_audio_event(
+            &app,
+            json!({ "type": "RadioTracksAdded", "data": { "instance_ids": added } }),
+        );
+        let _ = crate::windowing::emit_audio_event(&app, super::queue_changed_event(&audio_state, &store));
+        store.next_track();
+        store.get_current_track()
+    };
+
+    if let Some(mut track) = next {
+        let _ = crate::windowing::emit_audio_event(&app, json!({ "type": "TrackChanged", "data": { "track": track } }));
+        if let Err(e) = audio_state.continue_after_ended(&mut track).await {
+            tracing::warn!("Radio: failed to play the next track: {:?}", e);
+        }
+    }
+}
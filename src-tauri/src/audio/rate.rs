@@ -0,0 +1,87 @@
+//! Playback rate, pitch kept. Music and spoken word (podcast episodes and
+//! audiobooks) each keep their own rate in `prefs.music.playback`; the rate of
+//! the kind of the current track is applied whenever a track loads. Changes
+//! are announced with `RateChanged`.
+
+use ::settings::settings::SettingsConfig;
+use audio_player::AudioPlayer;
+use macros::command_envelope;
+use serde_json::json;
+use tauri::{AppHandle, Manager, State};
+use types::errors::Result;
+use types::podcasts::episode_id;
+use types::settings::music::MusicPlaybackSettings;
+use types::tracks::MediaContent;
+
+fn load_playback_settings(settings: &SettingsConfig) -> MusicPlaybackSettings {
+    settings
+        .load_selective::<MusicPlaybackSettings>("music.playback".to_string())
+        .unwrap_or_default()
+}
+
+/// Whether `track` is a podcast episode or an audiobook
+fn is_spoken(app: &AppHandle, track: &MediaContent) -> bool {
+    let episode = track.track._id.as_deref().and_then(episode_id).is_some();
+    episode || crate::audiobooks::is_audiobook(app, track)
+}
+
+fn emit_rate(app: &AppHandle, rate: f32, spoken: bool) {
+    let _ = crate::windowing::emit_audio_event(
+        app,
+        json!({
+            "type": "RateChanged",
+            "data": { "rate": rate, "spoken": spoken }
+        }),
+    );
+}
+
+/// Apply the rate saved for the kind of `track`, the music rate without one
+pub fn apply_track_rate(app: &AppHandle, track: Option<&MediaContent>) {
+    let spoken = track.is_some_and(|t| is_spoken(app, t));
+    let playback = load_playback_settings(&app.state::<SettingsConfig>());
+    let saved = if spoken { playback.spoken_rate } else { playback.music_rate };
+    let player = app.state::<AudioPlayer>();
+    let previous = player.audio_get_rate();
+    let rate = player.audio_set_rate(saved.unwrap_or(1.0));
+    if rate != previous {
+        emit_rate(app, rate, spoken);
+    }
+}
+
+/// Set the rate of the current track and save it for its kind
+pub fn set_rate(app: &AppHandle, rate: f32) -> Result<f32> {
+    let player = app.state::<AudioPlayer>();
+    let track = player.get_store().lock().ok().and_then(|store| store.get_current_track());
+    let spoken = track.as_ref().is_some_and(|t| is_spoken(app, t));
+    let rate = player.audio_set_rate(rate);
+
+    let settings = app.state::<SettingsConfig>();
+    let mut playback = load_playback_settings(&settings);
+    if spoken {
+        playback.spoken_rate = Some(rate);
+    } else {
+        playback.music_rate = Some(rate);
+    }
+    settings.save_selective("music.playback".to_string(), Some(playback))?;
+    emit_rate(app, rate, spoken);
+    Ok(rate)
+}
+
+command_envelope! {
+    /// Set the playback rate (0.5 - 3.0, 1.0 being normal) without changing
+    /// the pitch. It is saved for music or spoken word, after the current
+    /// track, and resolves to the rate applied.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command]
+    pub fn audio_set_rate(app: AppHandle, rate: f32) -> Result<f32> {
+        set_rate(&app, rate)
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(state))]
+    #[tauri::command]
+    pub fn audio_get_rate(state: State<'_, AudioPlayer>) -> Result<f32> {
+        Ok(state.audio_get_rate())
+    }
+}
@@ -0,0 +1,86 @@
+//! Expired stream URLs. When the server refuses the stream of the current
+//! track with 401, 403 or 410, the track is resolved again through the
+//! provider chain and reloaded at the position it stopped, reported as
+//! `StreamRefreshed` instead of an error. A track whose fresh URLs keep
+//! expiring fails after `MAX_REFRESHES` within `REFRESH_WINDOW`.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use audio_player::AudioPlayer;
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use types::ui::player_details::PlayerState;
+
+use super::quality;
+
+const MAX_REFRESHES: usize = 2;
+const REFRESH_WINDOW: Duration = Duration::from_secs(60);
+
+/// Track last refreshed, with the times of its recent refreshes
+static REFRESHES: Mutex<Option<(String, Vec<Instant>)>> = Mutex::new(None);
+
+/// Count a refresh of `track_id`; false when it was refreshed too often
+fn allow_refresh(track_id: &str) -> bool {
+    let mut refreshes = REFRESHES.lock().unwrap();
+    let now = Instant::now();
+    if !matches!(refreshes.as_ref(), Some((id, _)) if id == track_id) {
+        *refreshes = Some((track_id.to_string(), Vec::new()));
+    }
+    let Some((_, times)) = refreshes.as_mut() else {
+        return false;
+    };
+    times.retain(|time| now.duration_since(*time) < REFRESH_WINDOW);
+    if times.len() >= MAX_REFRESHES {
+        return false;
+    }
+    times.push(now);
+    true
+}
+
+/// Give up on the current track: nothing plays any more, report `message`
+async fn fail(app: &AppHandle, message: String) {
+    if let Err(e) = app.state::<AudioPlayer>().audio_stop().await {
+        tracing::warn!("Failed to stop the expired stream: {:?}", e);
+    }
+    audio_player::trace::record("error", json!({ "message": message }));
+    let _ = crate::windowing::emit_audio_event(app, json!({ "type": "Error", "data": { "message": message } }));
+}
+
+/// The stream of the current track was refused with `status`: resolve it
+/// again and resume where it stopped, playing when it was playing or about
+/// to
+pub fn on_stream_expired(app: &AppHandle, status: u16) {
+    let (track, state) = match app.state::<AudioPlayer>().get_store().lock() {
+        Ok(store) => (store.get_current_track(), store.get_player_state()),
+        Err(_) => return,
+    };
+    let Some(track_id) = track.and_then(|t| t.track._id) else {
+        return;
+    };
+    let resume = matches!(state, PlayerState::Playing | PlayerState::Loading);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if !allow_refresh(&track_id) {
+            tracing::warn!("Stream of {} keeps expiring (HTTP {})", track_id, status);
+            fail(&app, format!("Stream URL expired (HTTP {})", status)).await;
+            return;
+        }
+        tracing::info!("Stream of {} expired (HTTP {}), resolving it again", track_id, status);
+        match quality::reload_current_stream(&app, Some(resume)).await {
+            Ok(true) => {
+                let _ = crate::windowing::emit_audio_event(
+                    &app,
+                    json!({ "type": "StreamRefreshed", "data": { "trackId": track_id, "status": status } }),
+                );
+            }
+            // Not a stream any more, e.g. downloaded meanwhile
+            Ok(false) => {}
+            Err(e) => {
+                tracing::warn!("Failed to refresh the stream of {}: {:?}", track_id, e);
+                fail(&app, e.to_string()).await;
+            }
+        }
+    });
+}
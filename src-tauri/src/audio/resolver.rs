@@ -0,0 +1,271 @@
+//! Stream resolution with a provider failover chain. The enabled media
+//! providers are tried in the order of `prefs.music.sourcesOrder`, the ones
+//! not listed after them. A provider failing `FAILURE_THRESHOLD` times in a
+//! row is demoted to the end of the chain for `DEMOTION_PERIOD`; one success
+//! restores it. Providers answering that they do not have the track are not
+//! counted as failing. Each resolution is reported as `resolver-status`.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use macros::command_envelope;
+use music_plugin_sdk::errors::PluginError as SdkPluginError;
+use music_plugin_sdk::types::media::{StreamRequest, StreamSource};
+use serde::Serialize;
+use serde_json::json;
+use ::settings::settings::SettingsConfig;
+use tauri::{AppHandle, Emitter, Manager, State};
+use types::errors::{MusicError, Result};
+use types::settings::music::MusicSourceSelection;
+use uuid::Uuid;
+
+use crate::plugins::manager::PluginHandler;
+
+/// Consecutive failures after which a provider is demoted
+pub const FAILURE_THRESHOLD: u32 = 3;
+/// How long a demoted provider stays at the end of the chain
+pub const DEMOTION_PERIOD: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Default)]
+struct ProviderHealth {
+    consecutive_failures: u32,
+    demoted_until: Option<Instant>,
+    last_error: Option<String>,
+    last_success_at: Option<chrono::NaiveDateTime>,
+    last_failure_at: Option<chrono::NaiveDateTime>,
+}
+
+impl ProviderHealth {
+    fn demoted_for(&self, now: Instant) -> Option<Duration> {
+        self.demoted_until.and_then(|until| until.checked_duration_since(now)).filter(|d| !d.is_zero())
+    }
+}
+
+/// Place and health of a provider in the chain
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderStatus {
+    pub plugin_id: String,
+    /// Position in the configured sources order, `None` when not listed
+    pub priority: Option<usize>,
+    pub consecutive_failures: u32,
+    pub demoted: bool,
+    /// Seconds until a demoted provider is restored
+    pub demoted_for_secs: Option<u64>,
+    pub last_error: Option<String>,
+    pub last_success_at: Option<chrono::NaiveDateTime>,
+    pub last_failure_at: Option<chrono::NaiveDateTime>,
+}
+
+/// One provider asked for a stream
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolveAttempt {
+    pub plugin_id: String,
+    pub ok: bool,
+    pub elapsed_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Provider health, managed by Tauri. Not persisted.
+#[derive(Default)]
+pub struct StreamResolver {
+    health: Mutex<HashMap<Uuid, ProviderHealth>>,
+}
+
+/// Whether an error only means the provider does not have the track
+fn is_not_available(error: &SdkPluginError) -> bool {
+    matches!(
+        error,
+        SdkPluginError::NotFound(_) | SdkPluginError::NotSupported(_) | SdkPluginError::InvalidInput(_)
+    )
+}
+
+fn sources_order(app: &AppHandle) -> Vec<String> {
+    app.state::<SettingsConfig>()
+        .load_selective::<Vec<String>>("music.sourcesOrder".to_string())
+        .unwrap_or_default()
+}
+
+impl StreamResolver {
+    /// Order providers for failover: healthy ones before demoted ones, each
+    /// group by `priority`, unlisted providers last in their given order
+    pub fn order<T>(&self, providers: Vec<(Uuid, T)>, priority: &[String]) -> Vec<(Uuid, T)> {
+        let now = Instant::now();
+        let health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        let mut keyed: Vec<_> = providers
+            .into_iter()
+            .enumerate()
+            .map(|(index, (id, provider))| {
+                let demoted = health.get(&id).is_some_and(|h| h.demoted_for(now).is_some());
+                let rank = priority.iter().position(|p| *p == id.to_string()).unwrap_or(usize::MAX);
+                ((demoted, rank, index), (id, provider))
+            })
+            .collect();
+        keyed.sort_by_key(|(key, _)| *key);
+        keyed.into_iter().map(|(_, provider)| provider).collect()
+    }
+
+    fn record_success(&self, plugin_id: Uuid) {
+        let mut health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = health.entry(plugin_id).or_default();
+        if entry.demoted_until.take().is_some() {
+            tracing::info!("Stream provider {} restored", plugin_id);
+        }
+        entry.consecutive_failures = 0;
+        entry.last_success_at = Some(chrono::Utc::now().naive_utc());
+    }
+
+    fn record_failure(&self, plugin_id: Uuid, error: String) {
+        let mut health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = health.entry(plugin_id).or_default();
+        entry.consecutive_failures += 1;
+        entry.last_error = Some(error);
+        entry.last_failure_at = Some(chrono::Utc::now().naive_utc());
+        if entry.consecutive_failures >= FAILURE_THRESHOLD && entry.demoted_for(Instant::now()).is_none() {
+            tracing::warn!(
+                "Stream provider {} demoted for {:?} after {} consecutive failures",
+                plugin_id,
+                DEMOTION_PERIOD,
+                entry.consecutive_failures
+            );
+            entry.demoted_until = Some(Instant::now() + DEMOTION_PERIOD);
+        }
+    }
+
+    /// Status of `providers`, in the given order
+    pub fn statuses(&self, providers: &[Uuid], priority: &[String]) -> Vec<ProviderStatus> {
+        let now = Instant::now();
+        let health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        providers
+            .iter()
+            .map(|id| {
+                let plugin_id = id.to_string();
+                let priority = priority.iter().position(|p| *p == plugin_id);
+                let h = health.get(id);
+                let demoted_for = h.and_then(|h| h.demoted_for(now));
+                ProviderStatus {
+                    plugin_id,
+                    priority,
+                    consecutive_failures: h.map_or(0, |h| h.consecutive_failures),
+                    demoted: demoted_for.is_some(),
+                    demoted_for_secs: demoted_for.map(|d| d.as_secs()),
+                    last_error: h.and_then(|h| h.last_error.clone()),
+                    last_success_at: h.and_then(|h| h.last_success_at),
+                    last_failure_at: h.and_then(|h| h.last_failure_at),
+                }
+            })
+            .collect()
+    }
+
+    /// Forget the health of every provider
+    pub fn reset(&self) {
+        self.health.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    /// Ask the providers for a stream of `track_id` along the failover chain,
+    /// first success wins. `request_for` gives the request sent to each provider.
+    pub async fn resolve(
+        &self,
+        app: &AppHandle,
+        track_id: &str,
+        request_for: impl Fn(&Uuid) -> StreamRequest,
+    ) -> Result<StreamSource> {
+        let plugin_handler: State<'_, PluginHandler> = app.state();
+        let providers = plugin_handler
+            .plugin_manager()
+            .get_audio_providers_by_selection(&MusicSourceSelection::default())
+            .await
+            .map_err(|e| MusicError::String(format!("Failed to get audio providers: {}", e)))?;
+        if providers.is_empty() {
+            return Err(MusicError::String("No audio providers found".into()));
+        }
+
+        let priority = sources_order(app);
+        let providers = self.order(providers, &priority);
+        let chain: Vec<Uuid> = providers.iter().map(|(id, _)| *id).collect();
+        let mut attempts = Vec::new();
+        let mut resolved = None;
+
+        for (provider_id, provider_plugin) in providers {
+            tracing::debug!("Trying provider: {}", provider_id);
+            let started = Instant::now();
+            let req = request_for(&provider_id);
+            let stream_result = {
+                let plugin_guard = provider_plugin.lock().await;
+                plugin_guard.get_media_stream(track_id, &req).await
+            };
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+            audio_player::trace::record(
+                "resolver",
+                json!({
+                    "track_id": track_id,
+                    "provider": provider_id,
+                    "ok": stream_result.is_ok(),
+                    "elapsed_ms": elapsed_ms,
+                    "error": stream_result.as_ref().err().map(|e| e.to_string()),
+                }),
+            );
+            attempts.push(ResolveAttempt {
+                plugin_id: provider_id.to_string(),
+                ok: stream_result.is_ok(),
+                elapsed_ms,
+                error: stream_result.as_ref().err().map(|e| e.to_string()),
+            });
+
+            match stream_result {
+                Ok(stream) => {
+                    tracing::info!("Successfully resolved stream URL from provider {}: {}", provider_id, stream.url);
+                    self.record_success(provider_id);
+                    resolved = Some((provider_id, stream));
+                    break;
+                }
+                Err(e) => {
+                    tracing::warn!("Provider {} failed to resolve stream URL: {}", provider_id, e);
+                    if !is_not_available(&e) {
+                        self.record_failure(provider_id, e.to_string());
+                    }
+                }
+            }
+        }
+
+        let _ = app.emit(
+            "resolver-status",
+            json!({
+                "track_id": track_id,
+                "resolved_by": resolved.as_ref().map(|(id, _)| id.to_string()),
+                "attempts": attempts,
+                "providers": self.statuses(&chain, &priority),
+            }),
+        );
+        resolved
+            .map(|(_, stream)| stream)
+            .ok_or_else(|| MusicError::String("No provider could resolve stream URL".into()))
+    }
+}
+
+command_envelope! {
+    /// Failover chain of the enabled providers in the order they are tried,
+    /// with their health. `reset` clears the health afterwards, restoring
+    /// demoted providers.
+    #[tracing::instrument(level = "debug", skip(app, resolver, plugin_handler))]
+    #[tauri::command]
+    pub async fn get_resolver_status(
+        app: AppHandle,
+        resolver: State<'_, StreamResolver>,
+        plugin_handler: State<'_, PluginHandler>,
+        reset: Option<bool>,
+    ) -> Result<Vec<ProviderStatus>> {
+        let providers = plugin_handler
+            .plugin_manager()
+            .get_audio_providers_by_selection(&MusicSourceSelection::default())
+            .await
+            .map_err(|e| MusicError::String(format!("Failed to get audio providers: {}", e)))?;
+        let priority = sources_order(&app);
+        let chain: Vec<Uuid> = resolver.order(providers, &priority).into_iter().map(|(id, _)| id).collect();
+        let statuses = resolver.statuses(&chain, &priority);
+        if reset.unwrap_or(false) {
+            resolver.reset();
+        }
+        Ok(statuses)
+    }
+}
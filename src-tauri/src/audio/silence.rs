@@ -0,0 +1,83 @@
+//! Silence trimming: where the audio of local tracks starts and ends is
+//! detected in the background after scans, while `music.playback.trimSilence`
+//! is on, and the player skips the long silence outside.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use ::settings::settings::SettingsConfig;
+use database::database::Database;
+use tauri::{AppHandle, Manager};
+use types::errors::{MusicError, Result};
+use types::settings::music::MusicPlaybackSettings;
+use types::tracks::{GetTrackOptions, SearchableTrack, TrackType};
+
+/// Tracks detected per background batch
+const BATCH: i64 = 25;
+
+/// Set while the background detection runs, so scans don't start another one
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+fn trim_enabled(app: &AppHandle) -> bool {
+    app.state::<SettingsConfig>()
+        .load_selective::<MusicPlaybackSettings>("music.playback".to_string())
+        .ok()
+        .and_then(|playback| playback.trim_silence)
+        .unwrap_or(false)
+}
+
+fn detect(database: &Database, track_id: &str) -> Result<()> {
+    let track = database
+        .get_tracks_by_options(GetTrackOptions {
+            track: Some(SearchableTrack {
+                _id: Some(track_id.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })?
+        .into_iter()
+        .next()
+        .ok_or_else(|| MusicError::String(format!("Track not found: {}", track_id)))?;
+    let path = track
+        .track
+        .path
+        .as_deref()
+        .filter(|_| track.track.type_ == TrackType::LOCAL)
+        .ok_or_else(|| MusicError::String("Only local tracks can be trimmed".into()))?;
+    let silence = audio_player::silence::detect_file(Path::new(path))?;
+    database.set_track_silence(track_id, &silence)
+}
+
+/// Detect the silence of the local tracks never checked, in batches on a
+/// background thread, when trimming is on. Does nothing when the detection
+/// already runs.
+pub fn spawn_auto_detect(app: &AppHandle) {
+    if !trim_enabled(app) || RUNNING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let database = app.state::<Database>();
+        // Undecodable files stay unchecked; don't pick them up again
+        let mut failed: Vec<String> = Vec::new();
+        while trim_enabled(&app) {
+            let pending = match database.get_silence_pending_track_ids(BATCH, &failed) {
+                Ok(ids) => ids,
+                Err(e) => {
+                    tracing::warn!("Failed to list tracks to detect silence in: {:?}", e);
+                    break;
+                }
+            };
+            if pending.is_empty() {
+                break;
+            }
+            for track_id in pending {
+                if let Err(e) = detect(&database, &track_id) {
+                    tracing::debug!("Failed to detect the silence of {}: {:?}", track_id, e);
+                    failed.push(track_id);
+                }
+            }
+        }
+        RUNNING.store(false, Ordering::Release);
+    });
+}
@@ -0,0 +1,318 @@
+//! Audiobook mode. Chapters are read from the tags of local files when they
+//! are scanned; long tracks (those with chapters, `.m4b` files, or tracks
+//! longer than the configured length) resume where they were left off, and
+//! skip forward/back move by the configured intervals. Entering a chapter is
+//! announced with a `ChapterChanged` audio event.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use ::settings::settings::SettingsConfig;
+use audio_player::AudioPlayer;
+use database::database::Database;
+use macros::command_envelope;
+use serde_json::json;
+use tauri::{AppHandle, Manager, State};
+use types::errors::Result;
+use types::podcasts::episode_id;
+use types::settings::music::MusicAudiobookSettings;
+use types::stations::is_station;
+use types::tracks::{MediaContent, TrackType};
+use types::ui::player_details::TrackChapter;
+
+use crate::audio::PositionThrottle;
+
+const DEFAULT_SKIP_FORWARD_SECS: f64 = 30.0;
+const DEFAULT_SKIP_BACK_SECS: f64 = 10.0;
+const DEFAULT_RESUME_MIN_MINUTES: f64 = 20.0;
+
+/// Interval between two saves of the position of the playing track
+const SAVE_INTERVAL: Duration = Duration::from_secs(15);
+/// Positions closer to the start are not resumed
+const MIN_RESUME_SECS: f64 = 5.0;
+/// Tracks stopped closer to their end start over next time
+const FINISHED_MARGIN_SECS: f64 = 30.0;
+/// Previous chapter restarts the current one when further into it
+const CHAPTER_RESTART_SECS: f64 = 3.0;
+
+/// Long track being played, its chapters and where it stands
+#[derive(Default)]
+struct Progress {
+    /// Queue entry being followed, long or not
+    track_id: Option<String>,
+    /// Whether the position of the entry is kept
+    resumable: bool,
+    chapters: Vec<TrackChapter>,
+    chapter: Option<usize>,
+    position: f64,
+    duration: Option<f64>,
+    last_save: Option<Instant>,
+}
+
+/// Managed by Tauri
+#[derive(Default)]
+pub struct AudiobookState {
+    progress: std::sync::Mutex<Progress>,
+}
+
+fn settings(app: &AppHandle) -> MusicAudiobookSettings {
+    app.state::<SettingsConfig>()
+        .load_selective::<MusicAudiobookSettings>("music.audiobook".to_string())
+        .unwrap_or_default()
+}
+
+/// Library ID of a queue entry; episodes and stations have their own handling
+fn library_id(track: &MediaContent) -> Option<&str> {
+    let id = track.track._id.as_deref()?;
+    if is_station(track) || episode_id(id).is_some() {
+        return None;
+    }
+    id.split('#').next()
+}
+
+/// Chapters of local tracks, read from their tags
+fn read_chapters(track: &MediaContent) -> Vec<TrackChapter> {
+    if track.track.type_ != TrackType::LOCAL {
+        return vec![];
+    }
+    track
+        .track
+        .path
+        .as_deref()
+        .map(|path| file_scanner::read_chapters(Path::new(path)))
+        .unwrap_or_default()
+}
+
+/// Read and cache the chapters of freshly stored tracks
+pub fn cache_chapters(database: &Database, tracks: &[MediaContent]) {
+    let chapters: Vec<(String, Vec<TrackChapter>)> = tracks
+        .iter()
+        .filter(|t| t.track.type_ == TrackType::LOCAL)
+        .filter_map(|t| Some((t.track._id.clone()?, read_chapters(t))))
+        .collect();
+    if chapters.is_empty() {
+        return;
+    }
+    if let Err(e) = database.set_track_chapters(&chapters) {
+        tracing::warn!("Failed to cache the chapters of {} tracks: {:?}", chapters.len(), e);
+    }
+}
+
+/// Whether the position of `track` is kept between plays
+fn is_resumable(track: &MediaContent, chapters: &[TrackChapter], settings: &MusicAudiobookSettings) -> bool {
+    let m4b = track
+        .track
+        .path
+        .as_deref()
+        .and_then(|path| Path::new(path).extension())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("m4b"));
+    let min_minutes = settings.resume_min_minutes.unwrap_or(DEFAULT_RESUME_MIN_MINUTES);
+    let long = min_minutes > 0.0 && track.track.duration.is_some_and(|d| d >= min_minutes * 60.0);
+    !chapters.is_empty() || m4b || long
+}
+
+/// Whether `track` is an audiobook: a library track kept where it was left off
+pub(crate) fn is_audiobook(app: &AppHandle, track: &MediaContent) -> bool {
+    let Some(track_id) = library_id(track) else { return false };
+    let chapters = app.state::<Database>().get_track_chapters(track_id).unwrap_or_default();
+    is_resumable(track, &chapters, &settings(app))
+}
+
+/// Index of the chapter playing at `position`
+fn chapter_at(chapters: &[TrackChapter], position: f64) -> Option<usize> {
+    chapters.iter().rposition(|c| c.start <= position)
+}
+
+fn save_position(app: &AppHandle, track_id: &str, position: f64, duration: Option<f64>) {
+    // Finished books start over
+    let finished = duration.is_some_and(|d| d > 0.0 && position >= d - FINISHED_MARGIN_SECS);
+    let position = (!finished && position >= MIN_RESUME_SECS).then_some(position);
+    if let Err(e) = app.state::<Database>().set_track_position(track_id, position) {
+        tracing::warn!("Failed to save the position of {}: {:?}", track_id, e);
+    }
+}
+
+fn emit_chapter(app: &AppHandle, track_id: &str, index: usize, chapter: &TrackChapter) {
+    let _ = crate::windowing::emit_audio_event(
+        app,
+        json!({
+            "type": "ChapterChanged",
+            "data": { "track_id": track_id, "index": index, "chapter": chapter }
+        }),
+    );
+}
+
+/// Follow playback of `track` at `position` seconds: resume a long track
+/// where it was left off, save its position every `SAVE_INTERVAL` and
+/// announce chapter changes.
+pub fn on_time_update(app: &AppHandle, track: Option<&MediaContent>, position: f64) {
+    let Some(state) = app.try_state::<AudiobookState>() else { return };
+    let Ok(mut progress) = state.progress.lock() else { return };
+    let current = track.and_then(library_id);
+
+    if progress.track_id.as_deref() != current {
+        // Switched away: keep where the previous track stopped
+        if let (Some(previous), true) = (progress.track_id.take(), progress.resumable) {
+            save_position(app, &previous, progress.position, progress.duration);
+        }
+        *progress = Progress::default();
+        let (Some(current), Some(track)) = (current, track) else { return };
+        let database = app.state::<Database>();
+        let chapters = database.get_track_chapters(current).unwrap_or_else(|e| {
+            tracing::warn!("Failed to load the chapters of {}: {:?}", current, e);
+            vec![]
+        });
+        progress.track_id = Some(current.to_string());
+        progress.resumable = is_resumable(track, &chapters, &settings(app));
+        progress.chapters = chapters;
+        progress.duration = track.track.duration;
+        progress.last_save = Some(Instant::now());
+        let saved = if progress.resumable {
+            database.get_track_position(current).ok().flatten()
+        } else {
+            None
+        };
+        let resume = saved.filter(|p| *p >= MIN_RESUME_SECS && position < MIN_RESUME_SECS);
+        if let Some(resume) = resume {
+            tracing::debug!("Resuming {} at {}s", current, resume);
+            progress.position = resume;
+            let app = app.clone();
+            let track_id = current.to_string();
+            tauri::async_runtime::spawn(async move {
+                let player: State<'_, AudioPlayer> = app.state();
+                if let Err(e) = player.audio_seek(resume).await {
+                    tracing::warn!("Failed to resume {}: {:?}", track_id, e);
+                }
+            });
+            return;
+        }
+    }
+
+    let Some(current) = current else { return };
+    progress.position = position;
+    let chapter = chapter_at(&progress.chapters, position);
+    if chapter != progress.chapter {
+        progress.chapter = chapter;
+        if let Some(index) = chapter {
+            emit_chapter(app, current, index, &progress.chapters[index]);
+        }
+    }
+    if progress.resumable && progress.last_save.map_or(true, |at| at.elapsed() >= SAVE_INTERVAL) {
+        progress.last_save = Some(Instant::now());
+        save_position(app, current, position, progress.duration);
+    }
+}
+
+/// Playback paused: save the position of the playing track now
+pub fn on_pause(app: &AppHandle) {
+    let Some(state) = app.try_state::<AudiobookState>() else { return };
+    let Ok(mut progress) = state.progress.lock() else { return };
+    if let (Some(track_id), true) = (progress.track_id.clone(), progress.resumable) {
+        progress.last_save = Some(Instant::now());
+        save_position(app, &track_id, progress.position, progress.duration);
+    }
+}
+
+/// The playing entry ended: a long track starts over next time
+pub fn on_ended(app: &AppHandle) {
+    let Some(state) = app.try_state::<AudiobookState>() else { return };
+    let Ok(mut progress) = state.progress.lock() else { return };
+    if let (Some(track_id), true) = (progress.track_id.take(), progress.resumable) {
+        if let Err(e) = app.state::<Database>().set_track_position(&track_id, None) {
+            tracing::warn!("Failed to reset the position of {}: {:?}", track_id, e);
+        }
+    }
+    *progress = Progress::default();
+}
+
+/// Current track and position of the player
+fn playing(player: &AudioPlayer) -> Option<(MediaContent, f64)> {
+    let store = player.get_store();
+    let store = store.lock().ok()?;
+    Some((store.get_current_track()?, store.get_current_time()))
+}
+
+async fn seek_by(player: &AudioPlayer, throttle: &PositionThrottle, seconds: f64) -> Result<()> {
+    let Some((track, position)) = playing(player) else {
+        return Ok(());
+    };
+    let mut target = (position + seconds).max(0.0);
+    if let Some(duration) = track.track.duration.filter(|d| *d > 0.0) {
+        target = target.min(duration);
+    }
+    throttle.emit_next();
+    player.audio_seek(target).await
+}
+
+command_envelope! {
+    /// Skip forward by `seconds`, or by the configured interval (30s by default)
+    #[tracing::instrument(level = "debug", skip(app, state, throttle))]
+    #[tauri::command]
+    pub async fn audio_skip_forward(
+        app: AppHandle,
+        state: State<'_, AudioPlayer>,
+        throttle: State<'_, PositionThrottle>,
+        seconds: Option<f64>,
+    ) -> Result<()> {
+        let seconds = seconds.or(settings(&app).skip_forward_secs).unwrap_or(DEFAULT_SKIP_FORWARD_SECS);
+        seek_by(&state, &throttle, seconds.abs()).await
+    }
+}
+
+command_envelope! {
+    /// Skip back by `seconds`, or by the configured interval (10s by default)
+    #[tracing::instrument(level = "debug", skip(app, state, throttle))]
+    #[tauri::command]
+    pub async fn audio_skip_back(
+        app: AppHandle,
+        state: State<'_, AudioPlayer>,
+        throttle: State<'_, PositionThrottle>,
+        seconds: Option<f64>,
+    ) -> Result<()> {
+        let seconds = seconds.or(settings(&app).skip_back_secs).unwrap_or(DEFAULT_SKIP_BACK_SECS);
+        seek_by(&state, &throttle, -seconds.abs()).await
+    }
+}
+
+command_envelope! {
+    /// Chapters of a library track, ordered by start; empty when it has none
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command]
+    pub fn get_track_chapters(database: State<'_, Database>, track_id: String) -> Result<Vec<TrackChapter>> {
+        database.get_track_chapters(track_id.split('#').next().unwrap_or_default())
+    }
+}
+
+command_envelope! {
+    /// Jump to the next chapter of the playing track, or the previous one.
+    /// Going back restarts the current chapter unless just into it. Does
+    /// nothing on tracks without chapters.
+    #[tracing::instrument(level = "debug", skip(database, state, throttle))]
+    #[tauri::command]
+    pub async fn audio_seek_chapter(
+        database: State<'_, Database>,
+        state: State<'_, AudioPlayer>,
+        throttle: State<'_, PositionThrottle>,
+        forward: bool,
+    ) -> Result<()> {
+        let Some((track, position)) = playing(&state) else {
+            return Ok(());
+        };
+        let Some(track_id) = library_id(&track) else {
+            return Ok(());
+        };
+        let chapters = database.get_track_chapters(track_id)?;
+        let target = match (forward, chapter_at(&chapters, position)) {
+            (true, Some(i)) => chapters.get(i + 1),
+            (true, None) => chapters.first(),
+            (false, Some(i)) if position - chapters[i].start > CHAPTER_RESTART_SECS => chapters.get(i),
+            (false, Some(i)) => chapters.get(i.saturating_sub(1)),
+            (false, None) => None,
+        };
+        let Some(target) = target else {
+            return Ok(());
+        };
+        throttle.emit_next();
+        state.audio_seek(target.start).await
+    }
+}
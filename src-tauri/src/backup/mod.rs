@@ -0,0 +1,265 @@
+//! Full backup of the library, to bring it back after a reinstall or on the
+//! same machine later. The archive is a zip file:
+//!
+//! - `manifest.json`: format, version and the schema of the database
+//! - `library.db`: snapshot of the database (`VACUUM INTO`)
+//! - `config.json`: the settings file as it is, secrets included; they can
+//!   only be read with the key of the machine that wrote them
+//! - `thumbnails.json`: files of the thumbnail directory with their sizes;
+//!   the images themselves are not kept, scans create them again
+//! - `plugin_states.json`: the plugin states, also part of the database
+//!
+//! Restoring checks and migrates the snapshot, then stages it with the
+//! settings; both replace the current ones on the next start.
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use ::settings::settings::SettingsConfig;
+use database::database::Database;
+use macros::command_envelope;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use types::entities::{LibraryBackupContents, LibraryRestoreReport, PluginState};
+use types::errors::{error_helpers, MusicError, Result};
+
+const FORMAT: &str = "music-library-backup";
+/// Version of the archive layout written; archives of later versions are refused
+const VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const DATABASE_FILE: &str = "library.db";
+const SETTINGS_FILE: &str = "config.json";
+const THUMBNAILS_FILE: &str = "thumbnails.json";
+const PLUGIN_STATES_FILE: &str = "plugin_states.json";
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    format: String,
+    version: u32,
+    app_version: String,
+    /// Unix time in ms
+    created_at: i64,
+    contents: LibraryBackupContents,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ThumbnailEntry {
+    /// Relative to the thumbnail directory
+    path: String,
+    size: u64,
+}
+
+fn thumbnail_dir(app: &AppHandle) -> Option<PathBuf> {
+    app.state::<SettingsConfig>()
+        .load_selective::<String>("thumbnail_path".to_string())
+        .ok()
+        .map(PathBuf::from)
+}
+
+/// Files below `dir`, with their sizes
+fn list_thumbnails(root: &Path, dir: &Path, entries: &mut Vec<ThumbnailEntry>) {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in read_dir.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else { continue };
+        if metadata.is_dir() {
+            list_thumbnails(root, &path, entries);
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            entries.push(ThumbnailEntry {
+                path: relative.to_string_lossy().replace('\\', "/"),
+                size: metadata.len(),
+            });
+        }
+    }
+}
+
+fn file_options() -> zip::write::FileOptions {
+    zip::write::FileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .large_file(true)
+}
+
+fn write_entry(zip: &mut zip::ZipWriter<File>, name: &str, data: &[u8]) -> Result<()> {
+    zip.start_file(name, file_options()).map_err(error_helpers::to_file_system_error)?;
+    zip.write_all(data).map_err(error_helpers::to_file_system_error)?;
+    Ok(())
+}
+
+/// Contents of `name` in the archive, `None` when it is not there
+fn read_entry(archive: &mut zip::ZipArchive<File>, name: &str) -> Result<Option<String>> {
+    let mut file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(error_helpers::to_file_system_error(e)),
+    };
+    let mut text = String::new();
+    file.read_to_string(&mut text).map_err(error_helpers::to_file_system_error)?;
+    Ok(Some(text))
+}
+
+fn backup(app: &AppHandle, dest: &Path) -> Result<LibraryBackupContents> {
+    let database = app.state::<Database>();
+    let settings = app.state::<SettingsConfig>();
+
+    let snapshot = std::env::temp_dir().join(format!("music-backup-{}.db", uuid::Uuid::new_v4()));
+    let result = write_backup(app, &database, &settings, &snapshot, dest);
+    if snapshot.exists() {
+        if let Err(e) = std::fs::remove_file(&snapshot) {
+            tracing::warn!("Failed to remove database snapshot {:?}: {}", snapshot, e);
+        }
+    }
+    result
+}
+
+fn write_backup(
+    app: &AppHandle,
+    database: &Database,
+    settings: &SettingsConfig,
+    snapshot: &Path,
+    dest: &Path,
+) -> Result<LibraryBackupContents> {
+    let schema = database.backup_database(snapshot)?;
+    let database_size = std::fs::metadata(snapshot).map_err(error_helpers::to_file_system_error)?.len();
+
+    let config_file = settings.config_file.lock().unwrap().clone();
+    let config = std::fs::read(&config_file).map_err(error_helpers::to_file_system_error)?;
+
+    let mut thumbnails = Vec::new();
+    if let Some(dir) = thumbnail_dir(app) {
+        list_thumbnails(&dir, &dir, &mut thumbnails);
+    }
+    let plugin_states: Vec<PluginState> = database.get_all_plugin_states()?;
+
+    let contents = LibraryBackupContents {
+        schema,
+        database_size,
+        settings: true,
+        thumbnails: thumbnails.len() as u32,
+        plugin_states: plugin_states.len() as u32,
+    };
+    let manifest = Manifest {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: chrono::Utc::now().timestamp_millis(),
+        contents: contents.clone(),
+    };
+
+    let file = File::create(dest).map_err(error_helpers::to_file_system_error)?;
+    let mut zip = zip::ZipWriter::new(file);
+    write_entry(&mut zip, MANIFEST_FILE, &serde_json::to_vec_pretty(&manifest)?)?;
+    zip.start_file(DATABASE_FILE, file_options()).map_err(error_helpers::to_file_system_error)?;
+    let mut snapshot_file = File::open(snapshot).map_err(error_helpers::to_file_system_error)?;
+    std::io::copy(&mut snapshot_file, &mut zip).map_err(error_helpers::to_file_system_error)?;
+    write_entry(&mut zip, SETTINGS_FILE, &config)?;
+    write_entry(&mut zip, THUMBNAILS_FILE, &serde_json::to_vec(&thumbnails)?)?;
+    write_entry(&mut zip, PLUGIN_STATES_FILE, &serde_json::to_vec_pretty(&plugin_states)?)?;
+    zip.finish().map_err(error_helpers::to_file_system_error)?;
+    Ok(contents)
+}
+
+fn restore(app: &AppHandle, src: &Path) -> Result<LibraryRestoreReport> {
+    let file = File::open(src).map_err(error_helpers::to_file_system_error)?;
+    let mut archive = zip::ZipArchive::new(file).map_err(error_helpers::to_file_system_error)?;
+    let manifest: Manifest = match read_entry(&mut archive, MANIFEST_FILE)? {
+        Some(text) => serde_json::from_str(&text)?,
+        None => return Err(MusicError::String(format!("{:?} is not a library backup", src))),
+    };
+    if manifest.format != FORMAT {
+        return Err(MusicError::String(format!("{:?} is not a library backup", src)));
+    }
+    if manifest.version > VERSION {
+        return Err(MusicError::String(format!(
+            "Backup version {} is newer than the version {} this app reads",
+            manifest.version, VERSION
+        )));
+    }
+
+    let database = app.state::<Database>();
+    let current = database.get_schema_version()?.version;
+    if manifest.contents.schema.version > current {
+        return Err(MusicError::String(format!(
+            "Backup schema {:?} is newer than the schema {:?} of this app (written by version {})",
+            manifest.contents.schema.version, current, manifest.app_version
+        )));
+    }
+
+    // The snapshot is checked and migrated where it waits for the next start
+    let staged = database.staged_restore_path();
+    {
+        let mut entry = archive
+            .by_name(DATABASE_FILE)
+            .map_err(|e| MusicError::String(format!("Backup has no database: {}", e)))?;
+        let mut staged_file = File::create(&staged).map_err(error_helpers::to_file_system_error)?;
+        if let Err(e) = std::io::copy(&mut entry, &mut staged_file) {
+            drop(staged_file);
+            let _ = std::fs::remove_file(&staged);
+            return Err(error_helpers::to_file_system_error(e));
+        }
+    }
+    let migrations = database.prepare_restore()?;
+
+    let settings = app.state::<SettingsConfig>();
+    let staged_settings = read_entry(&mut archive, SETTINGS_FILE).and_then(|config| match config {
+        Some(config) => settings.stage_restore(&config).map(|_| true),
+        None => Ok(false),
+    });
+    let restored_settings = match staged_settings {
+        Ok(restored) => restored,
+        Err(e) => {
+            // Not half a restore
+            let _ = std::fs::remove_file(&staged);
+            return Err(e);
+        }
+    };
+
+    let thumbnails: Vec<ThumbnailEntry> = match read_entry(&mut archive, THUMBNAILS_FILE)? {
+        Some(text) => serde_json::from_str(&text)?,
+        None => Vec::new(),
+    };
+    let missing_thumbnails = match thumbnail_dir(app) {
+        Some(dir) => thumbnails.iter().filter(|t| !dir.join(&t.path).exists()).count(),
+        None => thumbnails.len(),
+    };
+
+    let plugin_states = match read_entry(&mut archive, PLUGIN_STATES_FILE)? {
+        Some(text) => serde_json::from_str::<Vec<PluginState>>(&text)?.len() as u32,
+        None => 0,
+    };
+
+    Ok(LibraryRestoreReport {
+        contents: LibraryBackupContents {
+            settings: restored_settings,
+            thumbnails: thumbnails.len() as u32,
+            plugin_states,
+            ..manifest.contents
+        },
+        migrations,
+        missing_thumbnails: missing_thumbnails as u32,
+    })
+}
+
+command_envelope! {
+    /// Back up the database, settings, thumbnails manifest and plugin states
+    /// to the archive `dest`.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command(async)]
+    pub fn backup_library(app: AppHandle, dest: String) -> Result<LibraryBackupContents> {
+        backup(&app, Path::new(&dest))
+    }
+}
+
+command_envelope! {
+    /// Restore an archive written by `backup_library`. Its database is
+    /// migrated to the current schema and, with its settings, replaces the
+    /// current ones on the next start of the app.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command(async)]
+    pub fn restore_library(app: AppHandle, src: String) -> Result<LibraryRestoreReport> {
+        restore(&app, Path::new(&src))
+    }
+}
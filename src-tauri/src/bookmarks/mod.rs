@@ -0,0 +1,171 @@
+//! Bookmarks inside tracks: positions of long tracks (DJ mixes, concerts)
+//! saved with an optional label, in seconds of the original audio. Playback
+//! passing one is announced with a `BookmarkReached` audio event, for markers
+//! on the seek bar; changes to the bookmarks of a track with
+//! `BookmarksChanged`.
+
+use audio_player::AudioPlayer;
+use database::database::Database;
+use macros::command_envelope;
+use serde_json::json;
+use tauri::{AppHandle, Manager, State};
+use types::errors::Result;
+use types::ui::player_details::TrackBookmark;
+
+use crate::audio::PositionThrottle;
+
+/// Position updates further apart are seeks, which don't reach bookmarks
+/// on the way
+const MAX_STEP_SECS: f64 = 2.0;
+
+/// Bookmarks of the playing track and where it was last reported
+#[derive(Default)]
+struct Passing {
+    track_id: Option<String>,
+    bookmarks: Vec<TrackBookmark>,
+    position: Option<f64>,
+}
+
+/// Managed by Tauri
+#[derive(Default)]
+pub struct BookmarkState {
+    passing: std::sync::Mutex<Passing>,
+}
+
+/// Library ID of a queue entry
+fn track_key(track_id: &str) -> &str {
+    track_id.split('#').next().unwrap_or(track_id)
+}
+
+fn emit_reached(app: &AppHandle, bookmark: &TrackBookmark) {
+    let _ = crate::windowing::emit_audio_event(
+        app,
+        json!({
+            "type": "BookmarkReached",
+            "data": { "trackId": bookmark.track_id, "bookmark": bookmark }
+        }),
+    );
+}
+
+/// Reload the bookmarks of `track_id` after they changed and announce them
+fn bookmarks_changed(app: &AppHandle, track_id: &str) -> Result<Vec<TrackBookmark>> {
+    let bookmarks = app.state::<Database>().get_track_bookmarks(track_id)?;
+    if let Some(state) = app.try_state::<BookmarkState>() {
+        if let Ok(mut passing) = state.passing.lock() {
+            if passing.track_id.as_deref() == Some(track_id) {
+                passing.bookmarks = bookmarks.clone();
+            }
+        }
+    }
+    let _ = crate::windowing::emit_audio_event(
+        app,
+        json!({
+            "type": "BookmarksChanged",
+            "data": { "trackId": track_id, "bookmarks": bookmarks }
+        }),
+    );
+    Ok(bookmarks)
+}
+
+/// Follow playback of `track_id` at `position` seconds and announce the
+/// bookmarks it passes
+pub fn on_time_update(app: &AppHandle, track_id: Option<&str>, position: f64) {
+    let Some(state) = app.try_state::<BookmarkState>() else { return };
+    let Ok(mut passing) = state.passing.lock() else { return };
+    let current = track_id.map(track_key);
+
+    if passing.track_id.as_deref() != current {
+        *passing = Passing::default();
+        let Some(current) = current else { return };
+        passing.track_id = Some(current.to_string());
+        passing.bookmarks = app.state::<Database>().get_track_bookmarks(current).unwrap_or_else(|e| {
+            tracing::warn!("Failed to load the bookmarks of {}: {:?}", current, e);
+            vec![]
+        });
+    }
+
+    let previous = passing.position.replace(position);
+    let Some(previous) = previous.filter(|p| position > *p && position - p <= MAX_STEP_SECS) else {
+        return;
+    };
+    for bookmark in passing.bookmarks.iter() {
+        if bookmark.position > previous && bookmark.position <= position {
+            emit_reached(app, bookmark);
+        }
+    }
+}
+
+command_envelope! {
+    /// Bookmark `position` (seconds of the original audio) of a track, with an
+    /// optional label. Resolves to the bookmark saved.
+    #[tracing::instrument(level = "debug", skip(app, database))]
+    #[tauri::command]
+    pub fn add_bookmark(
+        app: AppHandle,
+        database: State<'_, Database>,
+        track_id: String,
+        position: f64,
+        label: Option<String>,
+    ) -> Result<TrackBookmark> {
+        let track_id = track_key(&track_id);
+        let bookmark = database.add_track_bookmark(track_id, position, label.as_deref())?;
+        bookmarks_changed(&app, track_id)?;
+        Ok(bookmark)
+    }
+}
+
+command_envelope! {
+    /// Bookmarks of a track, ordered by position
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command]
+    pub fn list_bookmarks(database: State<'_, Database>, track_id: String) -> Result<Vec<TrackBookmark>> {
+        database.get_track_bookmarks(track_key(&track_id))
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(app, database))]
+    #[tauri::command]
+    pub fn remove_bookmark(app: AppHandle, database: State<'_, Database>, bookmark_id: String) -> Result<()> {
+        if let Some(track_id) = database.remove_track_bookmark(&bookmark_id)? {
+            bookmarks_changed(&app, &track_id)?;
+        }
+        Ok(())
+    }
+}
+
+command_envelope! {
+    /// Seek the loaded track to one of its bookmarks, announced as reached
+    #[tracing::instrument(level = "debug", skip(app, database, state, throttle))]
+    #[tauri::command]
+    pub async fn jump_to_bookmark(
+        app: AppHandle,
+        database: State<'_, Database>,
+        state: State<'_, AudioPlayer>,
+        throttle: State<'_, PositionThrottle>,
+        bookmark_id: String,
+    ) -> Result<()> {
+        let Some(bookmark) = database.get_track_bookmark(&bookmark_id)? else {
+            return Err(format!("No bookmark {}", bookmark_id).into());
+        };
+        // Seeks are in positions shown, skip regions removed
+        let target = {
+            let store = state.get_store();
+            let store = store
+                .lock()
+                .map_err(|_| types::errors::MusicError::from("Failed to access player store"))?;
+            let loaded = store
+                .get_current_track()
+                .and_then(|t| t.track._id)
+                .is_some_and(|id| track_key(&id) == bookmark.track_id);
+            if !loaded {
+                return Err("The bookmark belongs to a track that isn't loaded".into());
+            }
+            store.edit_regions().to_edited(bookmark.position)
+        };
+        throttle.emit_next();
+        state.audio_seek(target).await?;
+        emit_reached(&app, &bookmark);
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+use database::database::Database;
+use macros::command_envelope;
+use tauri::State;
+use types::entities::{MigrationReport, SchemaVersion};
+use types::errors::Result;
+
+pub mod watchdog;
+
+command_envelope! {
+    /// Database schema version, applied/pending migrations and latest backup.
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn get_schema_version(database: State<'_, Database>) -> Result<SchemaVersion> {
+        database.get_schema_version()
+    }
+}
+
+command_envelope! {
+    /// What pending migrations would change, computed on a throwaway copy.
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn dry_run_migrations(database: State<'_, Database>) -> Result<MigrationReport> {
+        database.dry_run_migrations()
+    }
+}
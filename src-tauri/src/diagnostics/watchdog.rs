@@ -0,0 +1,351 @@
+//! Watchdog over the background subsystems: the player's event loop, the auto
+//! scanner and the plugins. It checks them every `CHECK_INTERVAL`, restarts
+//! those that died and announces it with a `subsystem-restarted` event.
+//!
+//! A thread blocked in a call cannot be taken over, so a stuck audio event
+//! loop or plugin is only reported; a stuck scanner is replaced as a whole.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Arc, Mutex, TryLockError};
+use std::time::Duration;
+
+use ::plugins::system::manager::PluginManager;
+use ::plugins::system::types::{HealthStatus, PluginStatus};
+use audio_player::AudioPlayer;
+use macros::command_envelope;
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager, State};
+use types::errors::Result;
+use uuid::Uuid;
+
+use crate::scanner::ScanTask;
+
+/// Time between two checks
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A subsystem busy with one item for longer is considered stuck
+const STUCK_AFTER_MS: i64 = 120_000;
+
+/// Restarts of one subsystem before the watchdog gives up on it
+const MAX_RESTARTS: u32 = 5;
+
+const AUDIO_EVENTS: &str = "audio_events";
+const AUTO_SCANNER: &str = "auto_scanner";
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+#[derive(Default)]
+struct Beat {
+    alive: AtomicBool,
+    /// Start of the item in progress, 0 when idle
+    busy_since: AtomicI64,
+}
+
+/// Liveness of a loop running on its own thread, shared with the watchdog
+#[derive(Clone, Default)]
+pub struct Heartbeat(Arc<Beat>);
+
+/// Marks the loop running until dropped, which a panic does too
+pub struct RunningGuard(Arc<Beat>);
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.0.alive.store(false, Ordering::Release);
+        self.0.busy_since.store(0, Ordering::Release);
+    }
+}
+
+/// Marks one item in progress until dropped
+pub struct BusyGuard(Arc<Beat>);
+
+impl Drop for BusyGuard {
+    fn drop(&mut self) {
+        self.0.busy_since.store(0, Ordering::Release);
+    }
+}
+
+impl Heartbeat {
+    /// Take before spawning the thread and move into it, so the loop counts
+    /// as running from the start
+    pub fn start(&self) -> RunningGuard {
+        self.0.alive.store(true, Ordering::Release);
+        RunningGuard(self.0.clone())
+    }
+
+    /// Hold while handling one item
+    pub fn busy(&self) -> BusyGuard {
+        self.0.busy_since.store(now_ms(), Ordering::Release);
+        BusyGuard(self.0.clone())
+    }
+
+    /// State of the loop, `what` naming it in the detail
+    pub fn probe(&self, what: &str, now: i64) -> Probe {
+        if !self.0.alive.load(Ordering::Acquire) {
+            return Probe::failed(&format!("{} exited", what));
+        }
+        let busy_since = self.0.busy_since.load(Ordering::Acquire);
+        if busy_since > 0 && now - busy_since > STUCK_AFTER_MS {
+            return Probe::stuck(&format!("{} busy for {}s", what, (now - busy_since) / 1000));
+        }
+        Probe::healthy()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthState {
+    Healthy,
+    /// Not running by configuration, e.g. auto scan turned off
+    Off,
+    /// Died or reported a failure
+    Failed,
+    /// Blocked on one item for too long
+    Stuck,
+}
+
+/// Outcome of checking one subsystem
+#[derive(Debug, Clone)]
+pub struct Probe {
+    pub state: HealthState,
+    pub detail: Option<String>,
+}
+
+impl Probe {
+    pub fn healthy() -> Self {
+        Self { state: HealthState::Healthy, detail: None }
+    }
+
+    pub fn off() -> Self {
+        Self { state: HealthState::Off, detail: None }
+    }
+
+    pub fn failed(detail: &str) -> Self {
+        Self { state: HealthState::Failed, detail: Some(detail.to_string()) }
+    }
+
+    pub fn stuck(detail: &str) -> Self {
+        Self { state: HealthState::Stuck, detail: Some(detail.to_string()) }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubsystemHealth {
+    /// `audio_events`, `auto_scanner` or `plugin:<id>`
+    pub id: String,
+    pub state: HealthState,
+    pub detail: Option<String>,
+    /// Restarts by the watchdog since launch
+    pub restarts: u32,
+    /// Unix time in ms of the latest restart
+    pub last_restart: Option<i64>,
+}
+
+/// Summary returned by `get_system_health`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SystemHealth {
+    /// Every subsystem healthy or off
+    pub healthy: bool,
+    /// Unix time in ms of the check, `None` before the first one
+    pub checked_at: Option<i64>,
+    pub subsystems: Vec<SubsystemHealth>,
+}
+
+#[derive(Default)]
+pub struct Watchdog {
+    /// The player's event loop, see `audio::spawn_event_loop`
+    pub audio_events: Heartbeat,
+    /// Held during a check, so the command and the timer never restart twice
+    checking: tokio::sync::Mutex<()>,
+    report: Mutex<SystemHealth>,
+    /// Restart count and time of the latest one, by subsystem id
+    restarts: Mutex<HashMap<String, (u32, i64)>>,
+    /// When a plugin was first found locked by a call in progress
+    plugins_locked_since: Mutex<HashMap<Uuid, i64>>,
+}
+
+impl Watchdog {
+    fn restarts_of(&self, id: &str) -> (u32, Option<i64>) {
+        match self.restarts.lock().unwrap().get(id) {
+            Some(&(count, at)) => (count, Some(at)),
+            None => (0, None),
+        }
+    }
+
+    fn record_restart(&self, id: &str, now: i64) -> u32 {
+        let mut restarts = self.restarts.lock().unwrap();
+        let entry = restarts.entry(id.to_string()).or_insert((0, now));
+        entry.0 += 1;
+        entry.1 = now;
+        entry.0
+    }
+
+    /// Health of the enabled plugins. A plugin is failing when a call
+    /// panicked, its status is an error or its health check says so.
+    async fn probe_plugins(&self, manager: &PluginManager, now: i64) -> Vec<(Uuid, Probe)> {
+        let plugins = match manager.get_all_enabled_plugins().await {
+            Ok(plugins) => plugins,
+            Err(e) => {
+                tracing::warn!("Watchdog could not list the plugins: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let mut locked_since = self.plugins_locked_since.lock().unwrap();
+        let mut probes = Vec::new();
+        for (plugin_id, plugin) in plugins {
+            let probe = match plugin.try_lock() {
+                Ok(plugin) => {
+                    locked_since.remove(&plugin_id);
+                    match (plugin.status(), plugin.health_check()) {
+                        (Ok(PluginStatus::Error(reason)), _) => Probe::failed(&reason),
+                        (_, Ok(HealthStatus::Unhealthy(reason))) => Probe::failed(&reason),
+                        (Err(e), _) | (_, Err(e)) => Probe::failed(&e.to_string()),
+                        _ => Probe::healthy(),
+                    }
+                }
+                Err(TryLockError::Poisoned(_)) => {
+                    locked_since.remove(&plugin_id);
+                    Probe::failed("a plugin call panicked")
+                }
+                Err(TryLockError::WouldBlock) => {
+                    let since = *locked_since.entry(plugin_id).or_insert(now);
+                    if now - since > STUCK_AFTER_MS {
+                        Probe::stuck(&format!("a plugin call has been running for {}s", (now - since) / 1000))
+                    } else {
+                        Probe::healthy()
+                    }
+                }
+            };
+            probes.push((plugin_id, probe));
+        }
+        probes
+    }
+}
+
+/// Restart the failed subsystem `id`, unless it used up its restarts
+async fn restart(app: &AppHandle, watchdog: &Watchdog, id: &str, probe: Probe, now: i64) -> Probe {
+    let (count, _) = watchdog.restarts_of(id);
+    if count >= MAX_RESTARTS {
+        return Probe::failed(&format!(
+            "{}; not restarted again after {} restarts",
+            probe.detail.unwrap_or_default(),
+            count
+        ));
+    }
+
+    let result: Result<()> = if id == AUDIO_EVENTS {
+        let player = app.state::<AudioPlayer>();
+        crate::audio::spawn_event_loop(app.clone(), player.get_events_rx(), player.get_store());
+        Ok(())
+    } else if id == AUTO_SCANNER {
+        let scan_task = app.state::<ScanTask>();
+        scan_task.stop_auto_scanner().await;
+        scan_task.initialize_auto_scanner(app).await
+    } else if let Some(plugin_id) = id.strip_prefix("plugin:").and_then(|id| Uuid::parse_str(id).ok()) {
+        let manager = app.state::<Arc<PluginManager>>();
+        manager
+            .restart_plugin(plugin_id)
+            .await
+            .map_err(|e| e.to_string().into())
+    } else {
+        Ok(())
+    };
+
+    let reason = probe.detail.clone().unwrap_or_default();
+    match result {
+        Ok(()) => {
+            let restarts = watchdog.record_restart(id, now);
+            tracing::warn!("Watchdog restarted {} ({}), restart {}", id, reason, restarts);
+            let _ = app.emit(
+                "subsystem-restarted",
+                json!({ "subsystem": id, "reason": reason, "restarts": restarts }),
+            );
+            Probe::healthy()
+        }
+        Err(e) => {
+            // Counts as an attempt, a subsystem that cannot start is not retried forever
+            watchdog.record_restart(id, now);
+            tracing::error!("Watchdog failed to restart {}: {:?}", id, e);
+            Probe::failed(&format!("{}; restart failed: {:?}", reason, e))
+        }
+    }
+}
+
+/// Check every subsystem once, restart the failed ones and store the report
+async fn check(app: &AppHandle) {
+    let watchdog = app.state::<Watchdog>();
+    let _checking = watchdog.checking.lock().await;
+    let now = now_ms();
+
+    let scanner = app
+        .state::<ScanTask>()
+        .auto_scanner_health(now)
+        .unwrap_or_else(Probe::off);
+    // Whether a stuck one is restarted: a stuck event loop keeps the channel,
+    // a new one could not take over
+    let mut probes: Vec<(String, Probe, bool)> = vec![
+        (AUDIO_EVENTS.to_string(), watchdog.audio_events.probe("audio event loop", now), false),
+        (AUTO_SCANNER.to_string(), scanner, true),
+    ];
+    let manager = app.state::<Arc<PluginManager>>();
+    for (plugin_id, probe) in watchdog.probe_plugins(&manager, now).await {
+        probes.push((format!("plugin:{}", plugin_id), probe, false));
+    }
+
+    let mut subsystems = Vec::new();
+    for (id, mut probe, restart_stuck) in probes {
+        let needs_restart = probe.state == HealthState::Failed || (restart_stuck && probe.state == HealthState::Stuck);
+        if needs_restart {
+            probe = restart(app, &watchdog, &id, probe, now).await;
+        } else if probe.state == HealthState::Stuck {
+            tracing::warn!("Watchdog: {} is stuck ({})", id, probe.detail.clone().unwrap_or_default());
+        }
+        let (restarts, last_restart) = watchdog.restarts_of(&id);
+        subsystems.push(SubsystemHealth {
+            id,
+            state: probe.state,
+            detail: probe.detail,
+            restarts,
+            last_restart,
+        });
+    }
+
+    let healthy = subsystems
+        .iter()
+        .all(|s| matches!(s.state, HealthState::Healthy | HealthState::Off));
+    *watchdog.report.lock().unwrap() = SystemHealth {
+        healthy,
+        checked_at: Some(now),
+        subsystems,
+    };
+}
+
+/// Check the subsystems every `CHECK_INTERVAL` for as long as the app runs
+pub fn start_watchdog(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut tick = tokio::time::interval(CHECK_INTERVAL);
+        // The first tick is immediate; the subsystems are still starting then
+        tick.tick().await;
+        loop {
+            tick.tick().await;
+            check(&app).await;
+        }
+    });
+}
+
+command_envelope! {
+    /// State of the background subsystems as of a fresh check, with what the
+    /// watchdog restarted since launch.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command]
+    pub async fn get_system_health(app: AppHandle) -> Result<SystemHealth> {
+        check(&app).await;
+        let watchdog: State<'_, Watchdog> = app.state();
+        let report = watchdog.report.lock().unwrap().clone();
+        Ok(report)
+    }
+}
@@ -0,0 +1,181 @@
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use ::settings::settings::SettingsConfig;
+use audio_player::AudioPlayer;
+use database::collation::{KIND_ALBUM, KIND_TRACK};
+use database::database::Database;
+use macros::command_envelope;
+use serde_json::Value;
+use tauri::{AppHandle, Manager, State};
+use types::entities::{ArtworkEntity, ArtworkSize, ArtworkVariant};
+use types::errors::Result;
+use types::settings::display::{DisplayTemplates, DisplayView};
+use types::tracks::{GetTrackOptions, MediaContent, SearchableTrack};
+use types::ui::announcements::{AnnounceLocale, Announcer};
+use types::ui::player_details::PlayerMode;
+use types::ui::title_format::{TitleFormatter, TitleTemplate};
+
+/// Backend-side title formatting so every surface (renderer, MPRIS, tray,
+/// notifications) renders the same strings from prefs.display.templates.
+/// Also composes the screen-reader announcements attached to player events,
+/// in the language of prefs.general.language.
+#[derive(Default)]
+pub struct DisplayService {
+    formatter: RwLock<TitleFormatter>,
+    announcer: RwLock<Announcer>,
+}
+
+impl DisplayService {
+    pub fn format(&self, view: DisplayView, track: &MediaContent) -> String {
+        match self.formatter.read() {
+            Ok(f) => f.format(view, track),
+            Err(_) => TitleFormatter::default().format(view, track),
+        }
+    }
+
+    pub fn formatter(&self) -> TitleFormatter {
+        self.formatter.read().map(|f| f.clone()).unwrap_or_default()
+    }
+
+    /// Announcement for a player event (`audio_event` type and data), for the
+    /// events worth telling a screen-reader user about.
+    pub fn announce(&self, event_type: &str, data: &Value) -> Option<String> {
+        let announcer = self.announcer.read().map(|a| *a).unwrap_or_default();
+        match event_type {
+            "TrackChanged" => {
+                let track: MediaContent = serde_json::from_value(data.get("track")?.clone()).ok()?;
+                let title = self.format(DisplayView::NowPlaying, &track);
+                let artist = TitleTemplate::parse("%artist%").render(&track);
+                Some(announcer.now_playing(&title, Some(&artist), track.track.duration))
+            }
+            "PlaybackStateChanged" => Some(announcer.playback(data.get("is_playing")?.as_bool()?)),
+            "PlayerModeChanged" => {
+                let mode: PlayerMode = serde_json::from_value(data.get("mode")?.clone()).ok()?;
+                Some(announcer.mode(mode))
+            }
+            "VolumeChanged" => Some(announcer.volume(data.get("volume")?.as_f64()? as f32)),
+            _ => None,
+        }
+    }
+}
+
+/// Reload templates from prefs.display.templates and push them to the audio player,
+/// and the announcement language from prefs.general.language.
+#[tracing::instrument(level = "debug", skip(app))]
+pub fn apply_display_settings(app: &AppHandle) {
+    let settings: State<'_, SettingsConfig> = app.state();
+    let templates = settings
+        .load_selective::<DisplayTemplates>("display.templates".to_string())
+        .unwrap_or_default();
+    let formatter = TitleFormatter::new(&templates);
+    let language = settings
+        .load_selective::<String>("general.language".to_string())
+        .unwrap_or_default();
+    let announcer = Announcer::new(AnnounceLocale::from_tag(&language));
+
+    if let Some(service) = app.try_state::<DisplayService>() {
+        if let Ok(mut current) = service.formatter.write() {
+            *current = formatter.clone();
+        }
+        if let Ok(mut current) = service.announcer.write() {
+            *current = announcer;
+        }
+    }
+    if let Some(audio_player) = app.try_state::<AudioPlayer>() {
+        audio_player.set_title_formatter(formatter);
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(service, track))]
+    #[tauri::command]
+    pub fn format_track_display(
+        service: State<'_, DisplayService>,
+        track: MediaContent,
+        view: DisplayView,
+    ) -> Result<String> {
+        Ok(service.format(view, &track))
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(service, tracks))]
+    #[tauri::command]
+    pub fn format_tracks_display(
+        service: State<'_, DisplayService>,
+        tracks: Vec<MediaContent>,
+        view: DisplayView,
+    ) -> Result<Vec<String>> {
+        let formatter = service.formatter();
+        Ok(tracks.iter().map(|t| formatter.format(view, t)).collect())
+    }
+}
+
+/// Cover paths (large, small) of an entity; tracks without a cover of their
+/// own use the cover of their album
+fn cover_paths(database: &Database, entity: &ArtworkEntity) -> Result<Option<(Option<String>, Option<String>)>> {
+    let (kind, id) = match entity {
+        ArtworkEntity::Track(id) => (KIND_TRACK, id),
+        ArtworkEntity::Album(id) => (KIND_ALBUM, id),
+    };
+    let paths = database.get_cover_paths(kind, id)?;
+    if kind == KIND_ALBUM || paths.as_ref().is_some_and(|(high, low)| high.is_some() || low.is_some()) {
+        return Ok(paths);
+    }
+    let album_id = database
+        .get_tracks_by_options(GetTrackOptions {
+            track: Some(SearchableTrack { _id: Some(id.clone()), ..Default::default() }),
+            ..Default::default()
+        })?
+        .into_iter()
+        .next()
+        .and_then(|track| track.album)
+        .and_then(|album| album.album_id);
+    match album_id {
+        Some(album_id) => database.get_cover_paths(KIND_ALBUM, &album_id),
+        None => Ok(paths),
+    }
+}
+
+command_envelope! {
+    /// Size-appropriate cover for a track or album. `size` is the rendered edge
+    /// length in physical pixels (CSS size x device pixel ratio); the matching
+    /// variant is generated from the large cover on first request and cached.
+    /// Tracks without a cover of their own get the cover of their album.
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn get_artwork(
+        database: State<'_, Database>,
+        entity: ArtworkEntity,
+        size: u32,
+    ) -> Result<Option<ArtworkVariant>> {
+        let Some((high, low)) = cover_paths(&database, &entity)? else {
+            return Ok(None);
+        };
+
+        let bucket = ArtworkSize::for_pixels(size);
+        let path = match high.as_deref().map(Path::new).filter(|p| p.exists()) {
+            Some(high) => file_scanner::artwork_variant(high, bucket)?,
+            // Only the small thumbnail survived; better than nothing
+            None => match low.map(PathBuf::from).filter(|p| p.exists()) {
+                Some(low) => {
+                    return Ok(Some(ArtworkVariant {
+                        size: ArtworkSize::Small,
+                        path: low.to_string_lossy().to_string(),
+                        width: ArtworkSize::Small.pixels(),
+                        height: ArtworkSize::Small.pixels(),
+                    }))
+                }
+                None => return Ok(None),
+            },
+        };
+
+        Ok(Some(ArtworkVariant {
+            size: bucket,
+            path: path.to_string_lossy().to_string(),
+            width: bucket.pixels(),
+            height: bucket.pixels(),
+        }))
+    }
+}
@@ -0,0 +1,1018 @@
+pub(crate) mod naming;
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use ::settings::settings::SettingsConfig;
+use database::database::Database;
+use macros::command_envelope;
+use music_plugin_sdk::traits::media::MediaPlugin;
+use music_plugin_sdk::types::media::{QualityPreference, StreamFormatPreference, StreamProtocol, StreamRequest};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Notify;
+use types::entities::DownloadedTrack;
+use types::errors::{error_helpers, MusicError, Result};
+use types::settings::music::{MusicDownloadSettings, MusicSourceSelection, ProviderDownloadSettings};
+use types::tracks::{GetTrackOptions, MediaContent, SearchableTrack};
+
+use crate::network::{self, DataCategory};
+use crate::plugins::manager::PluginHandler;
+use crate::tasks::{DownloadCheckpoint, TaskKind, TaskManager};
+
+/// Plays after which an online track counts as heavy rotation, when unset
+const DEFAULT_MIN_PLAY_COUNT: u32 = 5;
+/// Storage budget when unset
+const DEFAULT_STORAGE_BUDGET_MB: u64 = 2048;
+/// Candidates queued per policy evaluation
+const SMART_DOWNLOAD_BATCH: i64 = 20;
+/// Concurrent downloads when unset, and the most allowed
+const DEFAULT_MAX_CONCURRENT: u32 = 2;
+const MAX_CONCURRENT_LIMIT: u32 = 8;
+/// Minimum interval between two progress events of a download
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+type MediaPluginHandle = Arc<tokio::sync::Mutex<dyn MediaPlugin + Send + Sync>>;
+
+/// Who asked for a download, stored as `downloads.origin`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DownloadOrigin {
+    Manual,
+    Smart,
+    /// A podcast episode, kept out of the music library
+    Podcast,
+}
+
+impl DownloadOrigin {
+    fn as_str(self) -> &'static str {
+        match self {
+            DownloadOrigin::Manual => "manual",
+            DownloadOrigin::Smart => "smart",
+            DownloadOrigin::Podcast => "podcast",
+        }
+    }
+}
+
+/// Download format and naming, resolved from the provider preferences when
+/// the job is queued.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DownloadFormat {
+    container: Option<String>,
+    bitrate_kbps: Option<u32>,
+    filename_template: String,
+    embed_cover: bool,
+    export_lyrics: bool,
+}
+
+impl Default for DownloadFormat {
+    fn default() -> Self {
+        Self::from(ProviderDownloadSettings::default())
+    }
+}
+
+impl From<ProviderDownloadSettings> for DownloadFormat {
+    fn from(s: ProviderDownloadSettings) -> Self {
+        Self {
+            container: s.container.filter(|c| !c.trim().is_empty()),
+            bitrate_kbps: s.bitrate_kbps.filter(|b| *b > 0),
+            filename_template: s
+                .filename_template
+                .filter(|t| !t.trim().is_empty())
+                .unwrap_or_else(|| naming::DEFAULT_TEMPLATE.to_string()),
+            embed_cover: s.embed_cover.unwrap_or(false),
+            export_lyrics: s.export_lyrics.unwrap_or(false),
+        }
+    }
+}
+
+impl DownloadFormat {
+    /// Stream hints for the providers. Downloads need a single file, so
+    /// segmented formats are never preferred.
+    fn stream_request(&self) -> StreamRequest {
+        let quality = match self.bitrate_kbps {
+            None => QualityPreference::High,
+            Some(kbps) if kbps >= 320 => QualityPreference::High,
+            Some(kbps) if kbps >= 192 => QualityPreference::Medium,
+            Some(_) => QualityPreference::Low,
+        };
+        let mut extra = std::collections::HashMap::new();
+        if let Some(container) = &self.container {
+            extra.insert("container".to_string(), container.clone());
+        }
+        if let Some(kbps) = self.bitrate_kbps {
+            extra.insert("bitrate".to_string(), kbps.to_string());
+        }
+        StreamRequest {
+            format: StreamFormatPreference::Progressive,
+            quality,
+            extra: (!extra.is_empty()).then_some(extra),
+        }
+    }
+}
+
+/// Journal payload of a download task.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DownloadJob {
+    track_id: String,
+    origin: DownloadOrigin,
+    /// Jobs journaled before formats existed use the defaults
+    #[serde(default)]
+    format: DownloadFormat,
+}
+
+/// Resolved download preferences with defaults applied.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DownloadPolicy {
+    smart_enabled: bool,
+    include_favorites: bool,
+    min_play_count: u32,
+    budget_bytes: u64,
+    wifi_only: bool,
+    max_concurrent: usize,
+}
+
+impl From<&MusicDownloadSettings> for DownloadPolicy {
+    fn from(s: &MusicDownloadSettings) -> Self {
+        Self {
+            smart_enabled: s.smart_enabled.unwrap_or(false),
+            include_favorites: s.include_favorites.unwrap_or(true),
+            min_play_count: s.min_play_count.unwrap_or(DEFAULT_MIN_PLAY_COUNT),
+            budget_bytes: s.storage_budget_mb.unwrap_or(DEFAULT_STORAGE_BUDGET_MB) * 1024 * 1024,
+            wifi_only: s.wifi_only.unwrap_or(true),
+            max_concurrent: s
+                .max_concurrent
+                .unwrap_or(DEFAULT_MAX_CONCURRENT)
+                .clamp(1, MAX_CONCURRENT_LIMIT) as usize,
+        }
+    }
+}
+
+fn load_settings(app: &AppHandle) -> MusicDownloadSettings {
+    let settings: State<'_, SettingsConfig> = app.state();
+    settings
+        .load_selective::<MusicDownloadSettings>("music.downloads".to_string())
+        .unwrap_or_default()
+}
+
+fn load_policy(app: &AppHandle) -> DownloadPolicy {
+    DownloadPolicy::from(&load_settings(app))
+}
+
+fn find_track(database: &Database, track_id: &str) -> Option<MediaContent> {
+    database
+        .get_tracks_by_options(GetTrackOptions {
+            track: Some(SearchableTrack {
+                _id: Some(track_id.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .ok()?
+        .into_iter()
+        .next()
+}
+
+/// Format of a new download of `track_id`: the preferences of the provider
+/// the track comes from, completed by the download defaults.
+fn download_format(app: &AppHandle, track_id: &str) -> DownloadFormat {
+    let settings = load_settings(app);
+    let defaults = settings.defaults.unwrap_or_default();
+    let provider = find_track(&app.state::<Database>(), track_id).and_then(|t| t.track.provider_extension);
+    let preferences = provider
+        .and_then(|p| settings.providers.and_then(|mut providers| providers.remove(&p)))
+        .map(|p| p.or(&defaults))
+        .unwrap_or(defaults);
+    DownloadFormat::from(preferences)
+}
+
+/// Queue a download of `track_id` with the current format preferences;
+/// returns false if it is already queued.
+pub fn queue_download(app: &AppHandle, track_id: &str, origin: DownloadOrigin) -> bool {
+    let job = DownloadJob {
+        track_id: track_id.to_string(),
+        origin,
+        format: download_format(app, track_id),
+    };
+    app.state::<DownloadQueue>().enqueue(job)
+}
+
+/// Where a download stands, as reported by `list_downloads`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DownloadState {
+    Queued,
+    Downloading,
+    Paused,
+}
+
+/// A download that is queued, running or paused.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadEntry {
+    pub track_id: String,
+    pub origin: DownloadOrigin,
+    pub state: DownloadState,
+    pub bytes_written: u64,
+    pub total_bytes: Option<u64>,
+    /// File being written, removed when the download is cancelled
+    #[serde(skip)]
+    partial: Option<PathBuf>,
+    #[serde(skip)]
+    last_report: Option<Instant>,
+}
+
+/// Unfinished and finished downloads, returned by `list_downloads`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadList {
+    pub active: Vec<DownloadEntry>,
+    pub completed: Vec<DownloadedTrack>,
+}
+
+/// A running download and the task executing it
+struct RunningDownload {
+    job: DownloadJob,
+    handle: Option<JoinHandle<()>>,
+}
+
+/// Download manager state: pending jobs, worked off by `start_download_worker`
+/// up to the configured concurrency, plus running and paused ones.
+pub struct DownloadQueue {
+    dir: PathBuf,
+    pending: Mutex<VecDeque<DownloadJob>>,
+    running: Mutex<HashMap<String, RunningDownload>>,
+    paused: Mutex<HashMap<String, DownloadJob>>,
+    /// Every unfinished download by track id, to avoid queueing a track twice
+    entries: Mutex<HashMap<String, DownloadEntry>>,
+    wake: Notify,
+    /// Whether the current connection is metered (cellular, tethering)
+    metered: AtomicBool,
+}
+
+impl DownloadQueue {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            pending: Mutex::new(VecDeque::new()),
+            running: Mutex::new(HashMap::new()),
+            paused: Mutex::new(HashMap::new()),
+            entries: Mutex::new(HashMap::new()),
+            wake: Notify::new(),
+            metered: AtomicBool::new(false),
+        }
+    }
+
+    /// Directory downloaded files are stored in
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Queue `job`; returns false if its track is already queued.
+    fn enqueue(&self, job: DownloadJob) -> bool {
+        let Ok(mut entries) = self.entries.lock() else {
+            return false;
+        };
+        if entries.contains_key(&job.track_id) {
+            return false;
+        }
+        entries.insert(
+            job.track_id.clone(),
+            DownloadEntry {
+                track_id: job.track_id.clone(),
+                origin: job.origin,
+                state: DownloadState::Queued,
+                bytes_written: 0,
+                total_bytes: None,
+                partial: None,
+                last_report: None,
+            },
+        );
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.push_back(job);
+        }
+        self.wake.notify_one();
+        true
+    }
+
+    pub fn set_metered(&self, metered: bool) {
+        let was_metered = self.metered.swap(metered, Ordering::SeqCst);
+        if was_metered && !metered {
+            self.wake.notify_one();
+        }
+    }
+
+    pub fn is_metered(&self) -> bool {
+        self.metered.load(Ordering::SeqCst)
+    }
+
+    /// Let a worker waiting for an unmetered connection or a free slot
+    /// re-check the policy.
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+
+    fn next(&self) -> Option<DownloadJob> {
+        self.pending.lock().ok()?.pop_front()
+    }
+
+    fn requeue_front(&self, job: DownloadJob) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.push_front(job);
+        }
+    }
+
+    fn running_count(&self) -> usize {
+        self.running.lock().map(|r| r.len()).unwrap_or(0)
+    }
+
+    fn update_entry(&self, track_id: &str, update: impl FnOnce(&mut DownloadEntry)) {
+        if let Ok(mut entries) = self.entries.lock() {
+            if let Some(entry) = entries.get_mut(track_id) {
+                update(entry);
+            }
+        }
+    }
+
+    /// Mark `job` as running. The task is attached afterwards with `attach`,
+    /// so a task finishing right away never leaves a stale entry behind.
+    fn start(&self, job: DownloadJob) {
+        self.update_entry(&job.track_id, |e| e.state = DownloadState::Downloading);
+        if let Ok(mut running) = self.running.lock() {
+            running.insert(job.track_id.clone(), RunningDownload { job, handle: None });
+        }
+    }
+
+    fn attach(&self, track_id: &str, handle: JoinHandle<()>) {
+        if let Ok(mut running) = self.running.lock() {
+            if let Some(download) = running.get_mut(track_id) {
+                download.handle = Some(handle);
+            }
+        }
+    }
+
+    fn set_partial(&self, track_id: &str, partial: &Path) {
+        self.update_entry(track_id, |e| e.partial = Some(partial.to_path_buf()));
+    }
+
+    /// Record progress; returns the entry when it is time to report it.
+    fn set_progress(&self, track_id: &str, bytes_written: u64, total_bytes: Option<u64>) -> Option<DownloadEntry> {
+        let mut report = None;
+        self.update_entry(track_id, |e| {
+            e.bytes_written = bytes_written;
+            e.total_bytes = total_bytes.or(e.total_bytes);
+            if !e.last_report.is_some_and(|at| at.elapsed() < PROGRESS_INTERVAL) {
+                e.last_report = Some(Instant::now());
+                report = Some(e.clone());
+            }
+        });
+        report
+    }
+
+    fn finish(&self, track_id: &str) {
+        if let Ok(mut running) = self.running.lock() {
+            running.remove(track_id);
+        }
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.remove(track_id);
+        }
+        self.wake.notify_one();
+    }
+
+    /// Pause a queued or running download; a running one stops and keeps its
+    /// partial file to continue from. Returns false if `track_id` is neither.
+    fn pause(&self, track_id: &str) -> bool {
+        let mut job = self.pending.lock().ok().and_then(|mut pending| {
+            let index = pending.iter().position(|j| j.track_id == track_id)?;
+            pending.remove(index)
+        });
+        if job.is_none() {
+            let running = self.running.lock().ok().and_then(|mut r| r.remove(track_id));
+            if let Some(running) = running {
+                if let Some(handle) = running.handle {
+                    handle.abort();
+                }
+                job = Some(running.job);
+                self.wake.notify_one();
+            }
+        }
+        let Some(job) = job else {
+            return false;
+        };
+        self.update_entry(track_id, |e| e.state = DownloadState::Paused);
+        if let Ok(mut paused) = self.paused.lock() {
+            paused.insert(track_id.to_string(), job);
+        }
+        true
+    }
+
+    /// Put a paused download back at the front of the queue.
+    fn resume(&self, track_id: &str) -> bool {
+        let Some(job) = self.paused.lock().ok().and_then(|mut p| p.remove(track_id)) else {
+            return false;
+        };
+        self.update_entry(track_id, |e| e.state = DownloadState::Queued);
+        self.requeue_front(job);
+        self.wake.notify_one();
+        true
+    }
+
+    /// Drop a queued, running or paused download. Returns its entry, whose
+    /// partial file is left to the caller.
+    fn cancel(&self, track_id: &str) -> Option<DownloadEntry> {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.retain(|j| j.track_id != track_id);
+        }
+        if let Ok(mut paused) = self.paused.lock() {
+            paused.remove(track_id);
+        }
+        let running = self.running.lock().ok().and_then(|mut r| r.remove(track_id));
+        if let Some(handle) = running.and_then(|r| r.handle) {
+            handle.abort();
+        }
+        let entry = self.entries.lock().ok()?.remove(track_id);
+        self.wake.notify_one();
+        entry
+    }
+
+    fn list(&self) -> Vec<DownloadEntry> {
+        // Running first, then in queue order
+        let order: Vec<String> = self
+            .pending
+            .lock()
+            .map(|p| p.iter().map(|j| j.track_id.clone()).collect())
+            .unwrap_or_default();
+        let mut entries: Vec<DownloadEntry> = self
+            .entries
+            .lock()
+            .map(|e| e.values().cloned().collect())
+            .unwrap_or_default();
+        entries.sort_by_key(|e| {
+            let position = order.iter().position(|id| *id == e.track_id).unwrap_or(usize::MAX);
+            (e.state != DownloadState::Downloading, position)
+        });
+        entries
+    }
+}
+
+/// Apply the smart download policy: queue online favorites and tracks played
+/// at least `min_play_count` times that are not downloaded yet, while the
+/// storage budget has room left.
+#[tracing::instrument(level = "debug", skip(app))]
+pub fn queue_smart_downloads(app: &AppHandle) {
+    let policy = load_policy(app);
+    if !policy.smart_enabled || !network::data_policy(app).allow_downloads {
+        return;
+    }
+    let database = app.state::<Database>();
+    match database.get_downloads_size() {
+        Ok(used) if used >= policy.budget_bytes => {
+            tracing::debug!("Download storage budget exhausted ({} bytes used)", used);
+            return;
+        }
+        Err(e) => {
+            tracing::warn!("Failed to read download storage usage: {:?}", e);
+            return;
+        }
+        _ => {}
+    }
+
+    let candidates = match database.get_smart_download_candidates(
+        policy.include_favorites,
+        policy.min_play_count,
+        SMART_DOWNLOAD_BATCH,
+    ) {
+        Ok(candidates) => candidates,
+        Err(e) => {
+            tracing::warn!("Failed to select smart download candidates: {:?}", e);
+            return;
+        }
+    };
+    let queued = candidates
+        .iter()
+        .filter(|id| queue_download(app, id, DownloadOrigin::Smart))
+        .count();
+    if queued > 0 {
+        tracing::info!("Queued {} tracks for smart download", queued);
+    }
+}
+
+/// Start the background worker downloading queued tracks, after re-queueing
+/// downloads interrupted by the previous app exit.
+#[tracing::instrument(level = "debug", skip(app))]
+pub fn start_download_worker(app: AppHandle) {
+    let tasks = app.state::<TaskManager>();
+    let queue = app.state::<DownloadQueue>();
+    match tasks.interrupted(TaskKind::Download) {
+        Ok(entries) => {
+            for entry in entries {
+                match serde_json::from_str::<DownloadJob>(&entry.payload) {
+                    Ok(job) => {
+                        queue.enqueue(job);
+                    }
+                    Err(_) => {
+                        let _ = tasks.fail(&entry.id, "Unreadable download payload");
+                    }
+                }
+            }
+        }
+        Err(e) => tracing::warn!("Failed to read interrupted downloads: {:?}", e),
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let queue = app.state::<DownloadQueue>();
+        loop {
+            let policy = load_policy(&app);
+            if queue.running_count() >= policy.max_concurrent {
+                // Woken when a download finishes
+                queue.wake.notified().await;
+                continue;
+            }
+            let Some(job) = queue.next() else {
+                queue.wake.notified().await;
+                continue;
+            };
+
+            if (policy.wifi_only && queue.is_metered()) || !network::data_policy(&app).allow_downloads {
+                // Wait for an unmetered connection, or for downloads to be
+                // allowed on cellular
+                queue.requeue_front(job);
+                queue.wake.notified().await;
+                continue;
+            }
+
+            let track_id = job.track_id.clone();
+            queue.start(job.clone());
+            emit_event(&app, "started", json!({ "trackId": track_id }));
+            let task_app = app.clone();
+            let handle = tauri::async_runtime::spawn(async move {
+                run_download(&task_app, &job, policy.budget_bytes).await;
+                task_app.state::<DownloadQueue>().finish(&job.track_id);
+            });
+            queue.attach(&track_id, handle);
+        }
+    });
+}
+
+/// Emit a download manager event to the frontend as `download_event`.
+fn emit_event(app: &AppHandle, kind: &str, data: serde_json::Value) {
+    let _ = app.emit("download_event", json!({ "type": kind, "data": data }));
+}
+
+fn report_progress(app: &AppHandle, track_id: &str, bytes_written: u64, total_bytes: Option<u64>) {
+    if let Some(entry) = app.state::<DownloadQueue>().set_progress(track_id, bytes_written, total_bytes) {
+        emit_event(app, "progress", json!(entry));
+    }
+}
+
+async fn run_download(app: &AppHandle, job: &DownloadJob, budget_bytes: u64) {
+    let tasks = app.state::<TaskManager>();
+    let database = app.state::<Database>();
+    let task_id = format!("download:{}", job.track_id);
+
+    if let Err(e) = tasks.begin(&task_id, TaskKind::Download, job) {
+        tracing::warn!("Failed to journal download {}: {:?}", task_id, e);
+    }
+    let result = match database.get_downloads_size() {
+        Ok(used) if used < budget_bytes => fetch_track(app, job, &task_id, budget_bytes - used).await,
+        Ok(_) => Err(MusicError::String("Download storage budget exhausted".into())),
+        Err(e) => Err(e),
+    };
+
+    match result.and_then(|entry| database.insert_download(&entry).map(|_| entry)) {
+        Ok(entry) => {
+            tracing::info!("Downloaded track {} to {} ({} bytes)", job.track_id, entry.path, entry.size);
+            let _ = tasks.complete(&task_id);
+            if job.origin == DownloadOrigin::Manual {
+                if let Err(e) = register_in_library(app, Path::new(&entry.path)).await {
+                    tracing::warn!("Failed to add {} to the library: {:?}", entry.path, e);
+                }
+            }
+            emit_event(app, "completed", json!(entry));
+        }
+        Err(e) => {
+            tracing::warn!("Download of track {} failed: {:?}", job.track_id, e);
+            let _ = tasks.fail(&task_id, &e.to_string());
+            emit_event(app, "failed", json!({ "trackId": job.track_id, "error": e.to_string() }));
+        }
+    }
+}
+
+/// Add a manually downloaded file to the local library, scanned the same way
+/// as the files of the music folders.
+async fn register_in_library(app: &AppHandle, path: &Path) -> Result<()> {
+    let settings = app.state::<SettingsConfig>();
+    let artwork_dir = crate::scanner::artwork_dir(&settings);
+    let artist_split: String = settings
+        .load_selective("artist_splitter".to_string())
+        .unwrap_or(";".to_string());
+    let path = path.to_path_buf();
+    let track = tauri::async_runtime::spawn_blocking(move || {
+        let size = std::fs::metadata(&path).map(|m| m.len() as f64).unwrap_or(0.0);
+        file_scanner::scan_file(&path, Path::new(&artwork_dir), size, false, &artist_split)
+    })
+    .await
+    .map_err(|e| MusicError::String(e.to_string()))??;
+    app.state::<Database>().insert_tracks(vec![track])?;
+    Ok(())
+}
+
+/// First enabled provider able to download `track_id` through
+/// `MediaDownloadPlugin`.
+async fn plugin_downloader(app: &AppHandle, track_id: &str) -> Option<MediaPluginHandle> {
+    // Episodes are fetched from their feed's enclosure
+    if types::podcasts::episode_id(track_id).is_some() {
+        return None;
+    }
+    let plugin_handler = app.state::<PluginHandler>();
+    let providers = plugin_handler
+        .plugin_manager()
+        .get_audio_providers_by_selection(&MusicSourceSelection::default())
+        .await
+        .ok()?;
+    for (provider_id, plugin) in providers {
+        let can_download = {
+            let guard = plugin.lock().await;
+            match guard.as_download() {
+                Some(downloader) => downloader.can_download(track_id).await,
+                None => continue,
+            }
+        };
+        match can_download {
+            Ok(true) => return Some(plugin),
+            Ok(false) => {}
+            Err(e) => tracing::debug!("Provider {} cannot download {}: {}", provider_id, track_id, e),
+        }
+    }
+    None
+}
+
+/// Download `job` into the download directory, through the provider's
+/// downloader when it has one and by fetching its stream otherwise. Gives up
+/// once the file would exceed `budget_left` bytes.
+async fn fetch_track(app: &AppHandle, job: &DownloadJob, task_id: &str, budget_left: u64) -> Result<DownloadedTrack> {
+    let queue = app.state::<DownloadQueue>();
+    let track = find_track(&app.state::<Database>(), &job.track_id);
+    let stem = queue
+        .dir
+        .join(naming::render(&job.format.filename_template, &job.track_id, track.as_ref()));
+    if let Some(parent) = stem.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(error_helpers::to_file_system_error)?;
+    }
+
+    let dest = match plugin_downloader(app, &job.track_id).await {
+        Some(plugin) => fetch_with_plugin(app, job, plugin, &stem, budget_left).await?,
+        None => fetch_stream(app, job, task_id, &stem, budget_left).await?,
+    };
+
+    finish_file(app, job, track.as_ref(), &dest).await;
+    let size = tokio::fs::metadata(&dest)
+        .await
+        .map_err(error_helpers::to_file_system_error)?
+        .len();
+    Ok(DownloadedTrack {
+        track_id: job.track_id.clone(),
+        path: dest.to_string_lossy().to_string(),
+        size: size as i64,
+        origin: job.origin.as_str().to_string(),
+        created_at: chrono::Utc::now().naive_utc(),
+    })
+}
+
+/// Final path of a download rendered to `stem`, made unique when another
+/// track already rendered to the same name.
+async fn destination(stem: &Path, extension: &str, track_id: &str) -> PathBuf {
+    let dest = stem.with_file_name(format!("{}.{}", file_name_of(stem), extension));
+    if !tokio::fs::try_exists(&dest).await.unwrap_or(false) {
+        return dest;
+    }
+    stem.with_file_name(format!(
+        "{} [{}].{}",
+        file_name_of(stem),
+        sanitize_file_stem(track_id),
+        extension
+    ))
+}
+
+/// Let the provider's downloader write the file. Plugins report no byte
+/// counts, so progress is the size of the file as it grows. The provider is
+/// locked for the duration of the download.
+async fn fetch_with_plugin(
+    app: &AppHandle,
+    job: &DownloadJob,
+    plugin: MediaPluginHandle,
+    stem: &Path,
+    budget_left: u64,
+) -> Result<PathBuf> {
+    let queue = app.state::<DownloadQueue>();
+    let partial = stem.with_file_name(format!("{}.partial", file_name_of(stem)));
+    queue.set_partial(&job.track_id, &partial);
+
+    let download = async {
+        let guard = plugin.lock().await;
+        match guard.as_download() {
+            Some(downloader) => downloader
+                .download_track(&job.track_id, &partial)
+                .await
+                .map_err(|e| MusicError::String(format!("Plugin download failed: {}", e))),
+            None => Err(MusicError::String("Provider can no longer download".into())),
+        }
+    };
+    tokio::pin!(download);
+    let mut ticker = tokio::time::interval(PROGRESS_INTERVAL);
+    let result = loop {
+        tokio::select! {
+            result = &mut download => break result,
+            _ = ticker.tick() => {
+                if let Ok(metadata) = tokio::fs::metadata(&partial).await {
+                    report_progress(app, &job.track_id, metadata.len(), None);
+                }
+            }
+        }
+    };
+
+    let size = tokio::fs::metadata(&partial).await.map(|m| m.len()).unwrap_or(0);
+    network::record_usage(app, DataCategory::Downloads, size);
+    let result = result.and_then(|_| {
+        if size > budget_left {
+            Err(MusicError::String("Track does not fit in the download storage budget".into()))
+        } else {
+            Ok(())
+        }
+    });
+    if let Err(e) = result {
+        let _ = tokio::fs::remove_file(&partial).await;
+        return Err(e);
+    }
+
+    let extension = file_scanner::audio_extension(&partial)
+        .map(str::to_string)
+        .or_else(|| job.format.container.clone())
+        .unwrap_or_else(|| "audio".to_string());
+    let dest = destination(stem, &extension, &job.track_id).await;
+    tokio::fs::rename(&partial, &dest)
+        .await
+        .map_err(error_helpers::to_file_system_error)?;
+    Ok(dest)
+}
+
+/// Fetch the stream of `job`. A partial file left by a paused or interrupted
+/// download is continued with a Range request when the server supports it.
+async fn fetch_stream(
+    app: &AppHandle,
+    job: &DownloadJob,
+    task_id: &str,
+    stem: &Path,
+    budget_left: u64,
+) -> Result<PathBuf> {
+    let stream = match crate::podcasts::episode_stream(app, &job.track_id) {
+        Some(stream) => stream,
+        None => crate::audio::resolve_stream_source_with(app, &job.track_id, &job.format.stream_request()).await?,
+    };
+    let segmented = matches!(stream.protocol, Some(StreamProtocol::Hls) | Some(StreamProtocol::Dash))
+        || stream.url.contains(".m3u8");
+    if segmented {
+        return Err(MusicError::String("Segmented streams cannot be downloaded".into()));
+    }
+
+    let queue = app.state::<DownloadQueue>();
+    let extension = stream.container.clone().unwrap_or_else(|| "audio".to_string());
+    let dest = destination(stem, &extension, &job.track_id).await;
+    let partial = dest.with_file_name(format!("{}.partial", file_name_of(&dest)));
+    queue.set_partial(&job.track_id, &partial);
+    let existing = tokio::fs::metadata(&partial).await.map(|m| m.len()).unwrap_or(0);
+
+    let mut request = reqwest::Client::new().get(&stream.url);
+    for (name, value) in stream.headers.iter().flatten() {
+        request = request.header(name, value);
+    }
+    if existing > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", existing));
+    }
+    let mut response = request
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(error_helpers::to_network_error)?;
+    let resumed = existing > 0 && response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut written = if resumed { existing } else { 0 };
+    let total = response.content_length().map(|len| len + written);
+    if total.is_some_and(|t| t > budget_left) {
+        return Err(MusicError::String("Track does not fit in the download storage budget".into()));
+    }
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(&partial)
+        .await
+        .map_err(error_helpers::to_file_system_error)?;
+    let tasks = app.state::<TaskManager>();
+    let copied: Result<()> = async {
+        while let Some(chunk) = response.chunk().await.map_err(error_helpers::to_network_error)? {
+            written += chunk.len() as u64;
+            network::record_usage(app, DataCategory::Downloads, chunk.len() as u64);
+            if written > budget_left {
+                return Err(MusicError::String("Track does not fit in the download storage budget".into()));
+            }
+            file.write_all(&chunk).await.map_err(error_helpers::to_file_system_error)?;
+            let _ = tasks.checkpoint(
+                task_id,
+                &DownloadCheckpoint {
+                    bytes_written: written,
+                    total_bytes: total,
+                    partial_path: partial.to_string_lossy().to_string(),
+                },
+                false,
+            );
+            report_progress(app, &job.track_id, written, total);
+        }
+        file.flush().await.map_err(error_helpers::to_file_system_error)
+    }
+    .await;
+    drop(file);
+    if let Err(e) = copied {
+        let _ = tokio::fs::remove_file(&partial).await;
+        return Err(e);
+    }
+
+    tokio::fs::rename(&partial, &dest)
+        .await
+        .map_err(error_helpers::to_file_system_error)?;
+    Ok(dest)
+}
+
+fn file_name_of(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+/// Cover embedding and lyrics export. Failures only cost the extra and are
+/// logged; the download itself succeeded. On cellular the low-res cover is
+/// embedded unless high-res artwork is allowed.
+async fn finish_file(app: &AppHandle, job: &DownloadJob, track: Option<&MediaContent>, dest: &Path) {
+    let high_res = network::data_policy(app).high_res_artwork;
+    let cover = track.and_then(|t| {
+        if high_res {
+            t.track.track_cover_path_high.clone().or_else(|| t.track.track_cover_path_low.clone())
+        } else {
+            t.track.track_cover_path_low.clone().or_else(|| t.track.track_cover_path_high.clone())
+        }
+    });
+    if let (true, Some(cover)) = (job.format.embed_cover, cover) {
+        let image = if cover.starts_with("http") {
+            match reqwest::get(&cover).await.and_then(|r| r.error_for_status()) {
+                Ok(response) => response
+                    .bytes()
+                    .await
+                    .map(|b| {
+                        network::record_usage(app, DataCategory::Artwork, b.len() as u64);
+                        b.to_vec()
+                    })
+                    .map_err(error_helpers::to_network_error),
+                Err(e) => Err(error_helpers::to_network_error(e)),
+            }
+        } else {
+            tokio::fs::read(&cover).await.map_err(error_helpers::to_file_system_error)
+        };
+        let path = dest.to_path_buf();
+        let embedded = match image {
+            Ok(image) => tauri::async_runtime::spawn_blocking(move || file_scanner::embed_cover(&path, &image))
+                .await
+                .unwrap_or_else(|e| Err(MusicError::String(e.to_string()))),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = embedded {
+            tracing::warn!("Failed to embed the cover of {}: {:?}", job.track_id, e);
+        }
+    }
+
+    if job.format.export_lyrics {
+        match crate::lyrics::resolve(app, &job.track_id, false).await {
+            Ok(Some(found)) => {
+                if let Err(e) = tokio::fs::write(dest.with_extension("lrc"), found.text).await {
+                    tracing::warn!("Failed to write the lyrics of {}: {}", job.track_id, e);
+                }
+            }
+            Ok(None) => tracing::debug!("No lyrics to export for {}", job.track_id),
+            Err(e) => tracing::warn!("Failed to load the lyrics of {}: {:?}", job.track_id, e),
+        }
+    }
+}
+
+/// Provider track ids may contain characters that are not valid in file names.
+fn sanitize_file_stem(id: &str) -> String {
+    id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+command_envelope! {
+    /// Report whether the current network connection is metered. Downloads
+    /// wait for an unmetered connection when `wifiOnly` is set. Superseded by
+    /// `set_network_class`, which also sets this.
+    #[tracing::instrument(level = "debug", skip(queue))]
+    #[tauri::command]
+    pub fn set_network_metered(queue: State<'_, DownloadQueue>, metered: bool) -> Result<()> {
+        queue.set_metered(metered);
+        Ok(())
+    }
+}
+
+command_envelope! {
+    /// Queue a download of `track_id`, named and formatted after the
+    /// preferences of its provider. Once downloaded the file is also added to
+    /// the local library. Returns false if the track is already queued or
+    /// downloaded.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command]
+    pub fn download_track(app: AppHandle, track_id: String) -> Result<bool> {
+        let downloaded = app
+            .state::<Database>()
+            .get_download(&track_id)?
+            .is_some_and(|d| Path::new(&d.path).exists());
+        if downloaded || !queue_download(&app, &track_id, DownloadOrigin::Manual) {
+            return Ok(false);
+        }
+        emit_event(&app, "queued", json!({ "trackId": track_id }));
+        Ok(true)
+    }
+}
+
+command_envelope! {
+    /// Cancel a queued, running or paused download and remove its partial file.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command]
+    pub fn cancel_download(app: AppHandle, track_id: String) -> Result<()> {
+        let entry = app
+            .state::<DownloadQueue>()
+            .cancel(&track_id)
+            .ok_or_else(|| MusicError::String(format!("No download of {}", track_id)))?;
+        let _ = app.state::<TaskManager>().cancel(&format!("download:{}", track_id));
+        let cleanup_app = app.clone();
+        let cleanup_id = track_id.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Some(partial) = entry.partial {
+                let _ = tokio::fs::remove_file(partial).await;
+            }
+            // Plugin downloads may continue in the background of the provider
+            if let Some(plugin) = plugin_downloader(&cleanup_app, &cleanup_id).await {
+                if let Some(downloader) = plugin.lock().await.as_download() {
+                    let _ = downloader.cancel_download(&cleanup_id).await;
+                }
+            }
+        });
+        emit_event(&app, "cancelled", json!({ "trackId": track_id }));
+        Ok(())
+    }
+}
+
+command_envelope! {
+    /// Pause a queued or running download. Running downloads continue from
+    /// their partial file when resumed; pauses last until the app exits.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command]
+    pub fn pause_download(app: AppHandle, track_id: String) -> Result<()> {
+        if !app.state::<DownloadQueue>().pause(&track_id) {
+            return Err(MusicError::String(format!("No download of {} to pause", track_id)));
+        }
+        emit_event(&app, "paused", json!({ "trackId": track_id }));
+        Ok(())
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command]
+    pub fn resume_download(app: AppHandle, track_id: String) -> Result<()> {
+        if !app.state::<DownloadQueue>().resume(&track_id) {
+            return Err(MusicError::String(format!("No paused download of {}", track_id)));
+        }
+        emit_event(&app, "queued", json!({ "trackId": track_id }));
+        Ok(())
+    }
+}
+
+command_envelope! {
+    /// Unfinished downloads, running first then in queue order, and the
+    /// downloaded tracks, most recent first.
+    #[tracing::instrument(level = "debug", skip(queue, database))]
+    #[tauri::command]
+    pub fn list_downloads(queue: State<'_, DownloadQueue>, database: State<'_, Database>) -> Result<DownloadList> {
+        Ok(DownloadList {
+            active: queue.list(),
+            completed: database.get_downloads()?,
+        })
+    }
+}
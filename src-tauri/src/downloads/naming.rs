@@ -0,0 +1,81 @@
+//! File names of downloaded tracks, built from a template such as
+//! `%artist%/%album%/%track% - %title%`.
+
+use std::path::PathBuf;
+
+use types::tracks::MediaContent;
+
+use super::sanitize_file_stem;
+
+/// Template used when none is configured: the provider track id
+pub const DEFAULT_TEMPLATE: &str = "%id%";
+
+/// Make `value` usable as a single path component on every platform.
+pub(crate) fn sanitize_component(value: &str) -> String {
+    let cleaned: String = value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    // Windows drops trailing dots and spaces; a lone "." or ".." is a relative path
+    cleaned.trim().trim_end_matches('.').trim().to_string()
+}
+
+/// Relative path, without extension, of the download of `track_id`. Each
+/// `/`-separated part of the template becomes a folder; metadata missing from
+/// `track` leaves its placeholder empty, and parts that end up empty are
+/// dropped. Falls back to the track id when nothing is left.
+pub fn render(template: &str, track_id: &str, track: Option<&MediaContent>) -> PathBuf {
+    let artist = track
+        .and_then(|t| t.artists.as_ref())
+        .map(|artists| {
+            artists
+                .iter()
+                .filter_map(|a| a.artist_name.clone())
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .unwrap_or_default();
+    let album = track
+        .and_then(|t| t.album.as_ref())
+        .and_then(|a| a.album_name.clone())
+        .unwrap_or_default();
+    let number = track
+        .and_then(|t| t.track.track_no)
+        .map(|n| format!("{:02}", n as u32))
+        .unwrap_or_default();
+    let title = track.and_then(|t| t.track.title.clone()).unwrap_or_default();
+    let provider = track
+        .and_then(|t| t.track.provider_extension.clone())
+        .unwrap_or_default();
+    let fields = [
+        ("%artist%", artist),
+        ("%album%", album),
+        ("%track%", number),
+        ("%title%", title),
+        ("%provider%", provider),
+        ("%id%", track_id.to_string()),
+    ];
+
+    let mut path = PathBuf::new();
+    for part in template.split(['/', '\\']) {
+        let mut rendered = part.to_string();
+        for (placeholder, value) in &fields {
+            rendered = rendered.replace(placeholder, &sanitize_component(value));
+        }
+        // Separators left dangling by empty placeholders, e.g. " - Title"
+        let rendered = sanitize_component(rendered.trim_matches(|c: char| c == ' ' || c == '-' || c == '_'));
+        if !rendered.is_empty() && rendered != "." && rendered != ".." {
+            path.push(rendered);
+        }
+    }
+
+    if path.as_os_str().is_empty() {
+        PathBuf::from(sanitize_file_stem(track_id))
+    } else {
+        path
+    }
+}
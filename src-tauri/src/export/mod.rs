@@ -0,0 +1,63 @@
+pub mod playlists;
+pub mod user_data;
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use database::database::Database;
+use macros::command_envelope;
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager};
+use types::entities::LibraryExportPhase;
+use types::errors::{MusicError, Result};
+
+/// Minimum interval between two progress events of one export.
+const PROGRESS_EMIT_INTERVAL: Duration = Duration::from_millis(250);
+
+command_envelope! {
+    /// Export the library (tracks with joined artist/album/genre columns and play
+    /// history) to a standalone SQLite file at `dest`, in the background.
+    /// Progress is reported with `library-export-progress` and the outcome with
+    /// `library-export-finished`; both carry the returned export id.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command(async)]
+    pub fn export_library_sqlite(app: AppHandle, dest: String) -> Result<String> {
+        let dest = PathBuf::from(dest);
+        if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+            if !parent.is_dir() {
+                return Err(MusicError::String(format!("Directory {:?} does not exist", parent)));
+            }
+        }
+
+        let export_id = uuid::Uuid::new_v4().to_string();
+        let id = export_id.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let database = app.state::<Database>();
+            let mut last_emit: Option<Instant> = None;
+            let result = database.export_library_sqlite(&dest, |progress| {
+                // Phase changes always go out, chunk updates are throttled
+                let throttled = progress.done > 0
+                    && progress.phase != LibraryExportPhase::Finalizing
+                    && last_emit.is_some_and(|t| t.elapsed() < PROGRESS_EMIT_INTERVAL);
+                if throttled {
+                    return;
+                }
+                last_emit = Some(Instant::now());
+                let _ = app.emit("library-export-progress", json!({ "id": id, "progress": progress }));
+            });
+
+            let payload = match result {
+                Ok(summary) => json!({ "id": id, "summary": summary }),
+                Err(e) => {
+                    tracing::error!("Library export to {:?} failed: {:?}", dest, e);
+                    json!({ "id": id, "error": e.to_string() })
+                }
+            };
+            if let Err(e) = app.emit("library-export-finished", payload) {
+                tracing::warn!("Failed to emit library-export-finished event: {}", e);
+            }
+        });
+
+        Ok(export_id)
+    }
+}
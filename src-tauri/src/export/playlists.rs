@@ -0,0 +1,238 @@
+//! Archive of the playlists of a signed-in provider: every playlist is written
+//! to `dest` as an M3U file and a JSON file with the full provider metadata,
+//! its tracks are stored in the library as metadata-only entries (no audio),
+//! and `report.json` lists what was exported and what could not be.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use database::database::Database;
+use macros::command_envelope;
+use music_plugin_sdk::types::media::Playlist as SdkPlaylist;
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::time::timeout;
+use types::errors::{error_helpers, MusicError, Result};
+use types::settings::music::{MusicSourceMode, MusicSourceSelection};
+
+use crate::audio::radio::to_media_content;
+use crate::downloads::naming::sanitize_component;
+use crate::plugins::manager::PluginHandler;
+
+/// Time limit of one provider call
+const PROVIDER_TIMEOUT: Duration = Duration::from_secs(30);
+
+const REPORT_FILE: &str = "report.json";
+
+/// Outcome of one playlist, part of `PlaylistExportReport`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedPlaylist {
+    pub id: String,
+    pub title: String,
+    /// Tracks written to the archive
+    pub tracks: usize,
+    /// Track count announced by the provider
+    pub expected_tracks: usize,
+    pub m3u_path: Option<String>,
+    pub json_path: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Written as `report.json` and sent with `playlist-export-finished`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistExportReport {
+    pub provider_id: String,
+    pub dest: String,
+    /// Unix time in ms
+    pub exported_at: i64,
+    pub playlists: Vec<ExportedPlaylist>,
+    /// Tracks stored in the library
+    pub library_tracks: usize,
+}
+
+/// The playlist as an extended M3U. Entries are the provider URLs, or the
+/// provider track ids where there is none.
+fn to_m3u(playlist: &SdkPlaylist) -> String {
+    let mut m3u = format!("#EXTM3U\n#PLAYLIST:{}\n", playlist.title);
+    for track in &playlist.tracks {
+        let seconds = track.duration.map_or(-1, |ms| (ms / 1000) as i64);
+        m3u.push_str(&format!("#EXTINF:{},{} - {}\n", seconds, track.artist, track.title));
+        if let Some(album) = &track.album {
+            m3u.push_str(&format!("#EXTALB:{}\n", album));
+        }
+        m3u.push_str(track.url.as_deref().unwrap_or(&track.id));
+        m3u.push('\n');
+    }
+    m3u
+}
+
+/// Unused file stem for `title` in `dest`, numbered when taken
+fn file_stem(dest: &Path, title: &str, taken: &mut Vec<String>) -> String {
+    let base = match sanitize_component(title) {
+        stem if stem.is_empty() => "Playlist".to_string(),
+        stem => stem,
+    };
+    let mut stem = base.clone();
+    let mut n = 2;
+    while taken.contains(&stem.to_lowercase()) || dest.join(format!("{}.m3u8", stem)).exists() {
+        stem = format!("{} ({})", base, n);
+        n += 1;
+    }
+    taken.push(stem.to_lowercase());
+    stem
+}
+
+async fn write_file(path: &Path, contents: &[u8]) -> Result<String> {
+    tokio::fs::write(path, contents)
+        .await
+        .map_err(error_helpers::to_file_system_error)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Export every playlist of `provider_id`, reporting progress as it goes
+async fn export_playlists(app: &AppHandle, export_id: &str, provider_id: &str, dest: &Path) -> Result<PlaylistExportReport> {
+    let selection = MusicSourceSelection {
+        mode: MusicSourceMode::Single,
+        ids: vec![provider_id.to_string()],
+    };
+    let (_, provider) = app
+        .state::<PluginHandler>()
+        .plugin_manager()
+        .get_audio_providers_by_selection(&selection)
+        .await
+        .map_err(|e| MusicError::String(format!("Failed to get audio providers: {}", e)))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| MusicError::String(format!("Provider {} is not available", provider_id)))?;
+
+    let listed = {
+        let plugin = provider.lock().await;
+        match timeout(PROVIDER_TIMEOUT, plugin.get_user_playlists()).await {
+            Ok(res) => res.map_err(|e| MusicError::String(format!("Provider {} playlists failed: {}", provider_id, e)))?,
+            Err(_) => return Err(MusicError::String(format!("Provider {} playlists timeout", provider_id))),
+        }
+    };
+
+    tokio::fs::create_dir_all(dest)
+        .await
+        .map_err(error_helpers::to_file_system_error)?;
+    let database = app.state::<Database>();
+    let total = listed.len();
+    let mut taken = Vec::new();
+    let mut playlists = Vec::with_capacity(total);
+    let mut library_tracks = 0;
+
+    for (done, listed) in listed.into_iter().enumerate() {
+        let _ = app.emit(
+            "playlist-export-progress",
+            json!({ "id": export_id, "done": done, "total": total, "playlist": listed.title }),
+        );
+        let expected = listed.total_tracks.map_or(listed.track_count as usize, |n| n as usize);
+        let mut entry = ExportedPlaylist {
+            id: listed.id.clone(),
+            title: listed.title.clone(),
+            tracks: 0,
+            expected_tracks: expected,
+            m3u_path: None,
+            json_path: None,
+            error: None,
+        };
+
+        // Listings may carry no or only the first tracks
+        let playlist = if listed.tracks.len() >= expected {
+            listed
+        } else {
+            let plugin = provider.lock().await;
+            match timeout(PROVIDER_TIMEOUT, plugin.get_playlist(&listed.id)).await {
+                Ok(Ok(full)) => full,
+                Ok(Err(e)) => {
+                    entry.error = Some(format!("Tracks could not be fetched: {}", e));
+                    listed
+                }
+                Err(_) => {
+                    entry.error = Some("Tracks could not be fetched: timeout".to_string());
+                    listed
+                }
+            }
+        };
+        entry.tracks = playlist.tracks.len();
+
+        let stem = file_stem(dest, &playlist.title, &mut taken);
+        let written = async {
+            let m3u = write_file(&dest.join(format!("{}.m3u8", stem)), to_m3u(&playlist).as_bytes()).await?;
+            let body = serde_json::to_vec_pretty(&playlist).map_err(error_helpers::to_parse_error)?;
+            let json = write_file(&dest.join(format!("{}.json", stem)), &body).await?;
+            Ok::<_, MusicError>((m3u, json))
+        }
+        .await;
+        match written {
+            Ok((m3u, json)) => {
+                entry.m3u_path = Some(m3u);
+                entry.json_path = Some(json);
+            }
+            Err(e) => entry.error = Some(e.to_string()),
+        }
+
+        let tracks = playlist
+            .tracks
+            .into_iter()
+            .map(to_media_content)
+            .collect::<Vec<_>>();
+        match database.insert_tracks(tracks) {
+            Ok(tracks) => library_tracks += tracks.len(),
+            Err(e) => tracing::warn!("Failed to store the tracks of playlist {}: {:?}", entry.id, e),
+        }
+        playlists.push(entry);
+    }
+
+    let report = PlaylistExportReport {
+        provider_id: provider_id.to_string(),
+        dest: dest.to_string_lossy().to_string(),
+        exported_at: chrono::Utc::now().timestamp_millis(),
+        playlists,
+        library_tracks,
+    };
+    let body = serde_json::to_vec_pretty(&report).map_err(error_helpers::to_parse_error)?;
+    write_file(&dest.join(REPORT_FILE), &body).await?;
+    Ok(report)
+}
+
+command_envelope! {
+    /// Archive the playlists of the signed-in provider `provider_id` to the
+    /// folder `dest`, in the background: one M3U and one JSON file per
+    /// playlist plus `report.json`. The tracks are added to the library as
+    /// metadata-only entries. Progress is reported with
+    /// `playlist-export-progress` and the report with
+    /// `playlist-export-finished`; both carry the returned export id.
+    #[tracing::instrument(level = "debug", skip(app, plugin_handler))]
+    #[tauri::command]
+    pub async fn export_provider_playlists(
+        app: AppHandle,
+        plugin_handler: State<'_, PluginHandler>,
+        provider_id: String,
+        dest: String,
+    ) -> Result<String> {
+        if plugin_handler.get_plugin(provider_id.clone()).await.is_err() {
+            return Err(MusicError::String(format!("Unknown provider {}", provider_id)));
+        }
+        let dest = PathBuf::from(dest);
+        let export_id = uuid::Uuid::new_v4().to_string();
+        let id = export_id.clone();
+        tauri::async_runtime::spawn(async move {
+            let payload = match export_playlists(&app, &id, &provider_id, &dest).await {
+                Ok(report) => json!({ "id": id, "report": report }),
+                Err(e) => {
+                    tracing::error!("Playlist export of {} to {:?} failed: {:?}", provider_id, dest, e);
+                    json!({ "id": id, "error": e.to_string() })
+                }
+            };
+            if let Err(e) = app.emit("playlist-export-finished", payload) {
+                tracing::warn!("Failed to emit playlist-export-finished event: {}", e);
+            }
+        });
+        Ok(export_id)
+    }
+}
@@ -0,0 +1,399 @@
+//! Portable archive of the data of the user: listening history, playlists,
+//! liked songs and settings, to back them up or move them to another
+//! machine. The archive is a zip file:
+//!
+//! - `manifest.json`: format, version and what the archive holds
+//! - `play_history.csv`, `liked_tracks.csv`: one row per play and per like
+//! - `playlists.json`: playlists with their track ids in order
+//! - `tracks.json`: the tracks the other files refer to
+//! - `settings.json`: the preferences, without the secrets, which are
+//!   encrypted with a key of this machine
+//!
+//! Importing merges an archive into the current data: nothing is removed,
+//! entries already there are kept as they are.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use ::settings::settings::SettingsConfig;
+use database::database::Database;
+use macros::command_envelope;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use types::entities::{PlayHistoryEntry, QueryablePlaylist, UserDataSummary};
+use types::errors::{error_helpers, MusicError, Result};
+use types::tracks::MediaContent;
+
+const FORMAT: &str = "music-user-data";
+/// Version of the archive layout written; archives of later versions are refused
+const VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const HISTORY_FILE: &str = "play_history.csv";
+const LIKES_FILE: &str = "liked_tracks.csv";
+const PLAYLISTS_FILE: &str = "playlists.json";
+const TRACKS_FILE: &str = "tracks.json";
+const SETTINGS_FILE: &str = "settings.json";
+
+const HISTORY_HEADER: [&str; 6] = ["track_id", "title", "artist", "album", "played_at", "play_duration"];
+const LIKES_HEADER: [&str; 5] = ["track_id", "title", "artist", "origin", "liked_at"];
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    format: String,
+    version: u32,
+    app_version: String,
+    /// Unix time in ms
+    exported_at: i64,
+    contents: UserDataSummary,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExportedPlaylist {
+    playlist: QueryablePlaylist,
+    track_ids: Vec<String>,
+}
+
+/// CSV field, quoted when it has to be
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_row<S: AsRef<str>>(fields: &[S]) -> String {
+    let fields: Vec<String> = fields.iter().map(|f| csv_field(f.as_ref())).collect();
+    format!("{}\r\n", fields.join(","))
+}
+
+/// Rows of a CSV text (RFC 4180: quoted fields may hold separators, quotes
+/// and line breaks)
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => row.push(std::mem::take(&mut field)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+/// Rows of a CSV file by column name, the first row naming the columns
+fn csv_records(text: &str) -> Vec<HashMap<String, String>> {
+    let mut rows = parse_csv(text).into_iter();
+    let Some(header) = rows.next() else {
+        return Vec::new();
+    };
+    rows.map(|row| header.iter().cloned().zip(row).collect()).collect()
+}
+
+fn title(track: Option<&MediaContent>) -> String {
+    track.and_then(|t| t.track.title.clone()).unwrap_or_default()
+}
+
+fn artist(track: Option<&MediaContent>) -> String {
+    track
+        .and_then(|t| t.artists.as_ref())
+        .map(|artists| artists.iter().filter_map(|a| a.artist_name.clone()).collect::<Vec<_>>().join("; "))
+        .unwrap_or_default()
+}
+
+/// Whether `value` has the form `set_secure` stores secrets in
+fn looks_encrypted(value: &str) -> bool {
+    value
+        .split_once(':')
+        .is_some_and(|(nonce, data)| {
+            nonce.len() == 24 && !data.is_empty() && nonce.chars().chain(data.chars()).all(|c| c.is_ascii_hexdigit())
+        })
+}
+
+/// Drop the secrets below `value`, found at the preference `path`; they
+/// can't be read on another machine
+fn strip_secrets(settings: &SettingsConfig, value: &mut Value, path: &str) {
+    let Some(object) = value.as_object_mut() else {
+        return;
+    };
+    let mut secrets = Vec::new();
+    for (key, child) in object.iter_mut() {
+        let child_path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+        match child {
+            Value::String(s) if looks_encrypted(s) => {
+                if settings.get_secure::<Value>(child_path).is_ok() {
+                    secrets.push(key.clone());
+                }
+            }
+            Value::Object(_) => strip_secrets(settings, child, &child_path),
+            _ => {}
+        }
+    }
+    for key in secrets {
+        object.remove(&key);
+    }
+}
+
+/// `patch` over `base`, object by object
+fn merge(base: &mut Value, patch: Value) {
+    match (base, patch) {
+        (Value::Object(base), Value::Object(patch)) => {
+            for (key, value) in patch {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, patch) => *base = patch,
+    }
+}
+
+fn write_entry(zip: &mut zip::ZipWriter<File>, name: &str, data: &[u8]) -> Result<()> {
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    zip.start_file(name, options).map_err(error_helpers::to_file_system_error)?;
+    zip.write_all(data).map_err(error_helpers::to_file_system_error)?;
+    Ok(())
+}
+
+/// Contents of `name` in the archive, `None` when it is not there
+fn read_entry(archive: &mut zip::ZipArchive<File>, name: &str) -> Result<Option<String>> {
+    let mut file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(error_helpers::to_file_system_error(e)),
+    };
+    let mut text = String::new();
+    file.read_to_string(&mut text).map_err(error_helpers::to_file_system_error)?;
+    Ok(Some(text))
+}
+
+fn export(app: &AppHandle, dest: &Path) -> Result<UserDataSummary> {
+    let database = app.state::<Database>();
+    let history = database.get_play_history()?;
+    let likes = database.get_liked_tracks()?;
+    let mut playlists = Vec::new();
+    for playlist in database.get_all_playlists()? {
+        let Some(id) = playlist.playlist_id.clone() else { continue };
+        let track_ids = database.get_playlist_track_ids(&id)?;
+        playlists.push(ExportedPlaylist { playlist, track_ids });
+    }
+
+    let mut referenced: Vec<String> = history.iter().map(|h| h.track_id.clone()).collect();
+    referenced.extend(playlists.iter().flat_map(|p| p.track_ids.iter().cloned()));
+    let mut seen = HashSet::new();
+    referenced.retain(|id| seen.insert(id.clone()));
+    let mut tracks = database.get_tracks_by_ids(&referenced)?;
+    // Liked tracks come with their details already
+    let liked: HashSet<&str> = likes.iter().filter_map(|l| l.track.track._id.as_deref()).collect();
+    tracks.retain(|t| !t.track._id.as_deref().is_some_and(|id| liked.contains(id)));
+    tracks.extend(likes.iter().map(|l| l.track.clone()));
+    let by_id: HashMap<&str, &MediaContent> = tracks
+        .iter()
+        .filter_map(|t| t.track._id.as_deref().map(|id| (id, t)))
+        .collect();
+
+    let mut history_csv = csv_row(&HISTORY_HEADER);
+    for entry in &history {
+        let track = by_id.get(entry.track_id.as_str()).copied();
+        history_csv.push_str(&csv_row(&[
+            entry.track_id.clone(),
+            title(track),
+            artist(track),
+            track.and_then(|t| t.album.as_ref()).and_then(|a| a.album_name.clone()).unwrap_or_default(),
+            entry.played_at.map(|t| t.format(TIME_FORMAT).to_string()).unwrap_or_default(),
+            entry.play_duration.map(|d| d.to_string()).unwrap_or_default(),
+        ]));
+    }
+    let mut likes_csv = csv_row(&LIKES_HEADER);
+    for like in &likes {
+        likes_csv.push_str(&csv_row(&[
+            like.track.track._id.clone().unwrap_or_default(),
+            title(Some(&like.track)),
+            artist(Some(&like.track)),
+            like.origin.clone(),
+            like.liked_at.to_string(),
+        ]));
+    }
+
+    let settings = app.state::<SettingsConfig>();
+    let mut prefs = settings.memcache.lock().unwrap().get("prefs").cloned().unwrap_or_default();
+    strip_secrets(&settings, &mut prefs, "");
+
+    let summary = UserDataSummary {
+        play_history: history.len() as u32,
+        playlists: playlists.len() as u32,
+        liked_tracks: likes.len() as u32,
+        tracks: tracks.len() as u32,
+        settings: true,
+    };
+    let manifest = Manifest {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: chrono::Utc::now().timestamp_millis(),
+        contents: summary.clone(),
+    };
+
+    let file = File::create(dest).map_err(error_helpers::to_file_system_error)?;
+    let mut zip = zip::ZipWriter::new(file);
+    write_entry(&mut zip, MANIFEST_FILE, &serde_json::to_vec_pretty(&manifest)?)?;
+    write_entry(&mut zip, HISTORY_FILE, history_csv.as_bytes())?;
+    write_entry(&mut zip, LIKES_FILE, likes_csv.as_bytes())?;
+    write_entry(&mut zip, PLAYLISTS_FILE, &serde_json::to_vec_pretty(&playlists)?)?;
+    write_entry(&mut zip, TRACKS_FILE, &serde_json::to_vec(&tracks)?)?;
+    write_entry(&mut zip, SETTINGS_FILE, &serde_json::to_vec_pretty(&prefs)?)?;
+    zip.finish().map_err(error_helpers::to_file_system_error)?;
+    Ok(summary)
+}
+
+fn import(app: &AppHandle, src: &Path, with_settings: bool) -> Result<UserDataSummary> {
+    let file = File::open(src).map_err(error_helpers::to_file_system_error)?;
+    let mut archive = zip::ZipArchive::new(file).map_err(error_helpers::to_file_system_error)?;
+    let manifest: Manifest = match read_entry(&mut archive, MANIFEST_FILE)? {
+        Some(text) => serde_json::from_str(&text)?,
+        None => return Err(MusicError::String(format!("{:?} is not a user data archive", src))),
+    };
+    if manifest.format != FORMAT {
+        return Err(MusicError::String(format!("{:?} is not a user data archive", src)));
+    }
+    if manifest.version > VERSION {
+        return Err(MusicError::String(format!(
+            "Archive version {} is newer than the version {} this app reads",
+            manifest.version, VERSION
+        )));
+    }
+
+    let database = app.state::<Database>();
+    let mut summary = UserDataSummary::default();
+
+    let tracks: Vec<MediaContent> = match read_entry(&mut archive, TRACKS_FILE)? {
+        Some(text) => serde_json::from_str(&text)?,
+        None => Vec::new(),
+    };
+    let ids: Vec<String> = tracks.iter().filter_map(|t| t.track._id.clone()).collect();
+    let known = database.get_tracks_by_ids(&ids)?.len();
+    database.insert_missing_tracks(tracks)?;
+    summary.tracks = ids.len().saturating_sub(known) as u32;
+    let in_library: HashSet<String> = database
+        .get_tracks_by_ids(&ids)?
+        .into_iter()
+        .filter_map(|t| t.track._id)
+        .collect();
+
+    if let Some(text) = read_entry(&mut archive, PLAYLISTS_FILE)? {
+        let playlists: Vec<ExportedPlaylist> = serde_json::from_str(&text)?;
+        let existing: HashSet<String> = database
+            .get_all_playlists()?
+            .into_iter()
+            .filter_map(|p| p.playlist_id)
+            .collect();
+        for ExportedPlaylist { playlist, track_ids } in playlists {
+            let known = playlist.playlist_id.as_ref().is_some_and(|id| existing.contains(id));
+            let playlist_id = match playlist.playlist_id.clone().filter(|_| known) {
+                Some(id) => id,
+                None => {
+                    summary.playlists += 1;
+                    database.create_playlist(playlist)?
+                }
+            };
+            for track_id in track_ids.into_iter().filter(|id| in_library.contains(id)) {
+                if !database.is_track_in_playlist(playlist_id.clone(), track_id.clone())? {
+                    database.add_to_playlist_bridge(playlist_id.clone(), track_id)?;
+                }
+            }
+        }
+    }
+
+    if let Some(text) = read_entry(&mut archive, LIKES_FILE)? {
+        let likes: Vec<(String, String, i64)> = csv_records(&text)
+            .into_iter()
+            .filter_map(|mut r| {
+                let track_id = r.remove("track_id").filter(|id| in_library.contains(id))?;
+                let liked_at = r.get("liked_at")?.parse().ok()?;
+                Some((track_id, r.remove("origin").unwrap_or_else(|| "local".to_string()), liked_at))
+            })
+            .collect();
+        summary.liked_tracks = database.insert_likes(&likes)? as u32;
+    }
+
+    if let Some(text) = read_entry(&mut archive, HISTORY_FILE)? {
+        let entries: Vec<PlayHistoryEntry> = csv_records(&text)
+            .into_iter()
+            .filter_map(|mut r| {
+                Some(PlayHistoryEntry {
+                    track_id: r.remove("track_id").filter(|id| !id.is_empty())?,
+                    played_at: r
+                        .get("played_at")
+                        .and_then(|t| chrono::NaiveDateTime::parse_from_str(t, TIME_FORMAT).ok()),
+                    play_duration: r.get("play_duration").and_then(|d| d.parse().ok()),
+                })
+            })
+            .collect();
+        summary.play_history = database.import_play_history(&entries)? as u32;
+    }
+
+    if with_settings {
+        if let Some(text) = read_entry(&mut archive, SETTINGS_FILE)? {
+            let imported: Value = serde_json::from_str(&text)?;
+            let settings = app.state::<SettingsConfig>();
+            if let Value::Object(domains) = imported {
+                for (domain, value) in domains {
+                    let mut current: Value = settings.load_selective(domain.clone()).unwrap_or(Value::Null);
+                    merge(&mut current, value);
+                    settings.save_selective(domain, Some(current))?;
+                }
+                summary.settings = true;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+command_envelope! {
+    /// Write the listening history, playlists, liked songs and settings to
+    /// the archive `dest` (zip of CSV and JSON files). Secrets such as
+    /// sign-in tokens are left out.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command(async)]
+    pub fn export_user_data(app: AppHandle, dest: String) -> Result<UserDataSummary> {
+        export(&app, Path::new(&dest))
+    }
+}
+
+command_envelope! {
+    /// Merge an archive written by `export_user_data` into the current data;
+    /// its settings are applied too unless `settings` is false. Returns what
+    /// was added.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command(async)]
+    pub fn import_user_data(app: AppHandle, src: String, settings: Option<bool>) -> Result<UserDataSummary> {
+        import(&app, Path::new(&src), settings.unwrap_or(true))
+    }
+}
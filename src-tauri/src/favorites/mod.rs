@@ -0,0 +1,228 @@
+//! Liked songs, one list across providers. A like is stored locally and, when
+//! the provider of the track keeps favorites (`MediaFavoritesPlugin`), mirrored
+//! to the signed-in account. The favorites of an account are imported on
+//! sign-in, tagged with the provider they come from, and announced with
+//! `favorites-imported`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use database::database::Database;
+use macros::command_envelope;
+use music_plugin_sdk::traits::MediaPlugin;
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::time::timeout;
+use types::entities::{FavoritesImport, LikedTrack};
+use types::errors::{MusicError, Result};
+use types::settings::music::{MusicSourceMode, MusicSourceSelection};
+use types::tracks::{GetTrackOptions, MediaContent, SearchableTrack};
+
+use crate::audio::radio::to_media_content;
+use crate::plugins::manager::PluginHandler;
+
+const PROVIDER_TIMEOUT: Duration = Duration::from_secs(30);
+/// Reading every favorite of an account takes a request per page
+const IMPORT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// `origin` of the tracks liked in the app
+const LOCAL_ORIGIN: &str = "local";
+
+type MediaPluginHandle = Arc<tokio::sync::Mutex<dyn MediaPlugin + Send + Sync>>;
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn find_track(database: &Database, track_id: &str) -> Option<MediaContent> {
+    database
+        .get_tracks_by_options(GetTrackOptions {
+            track: Some(SearchableTrack {
+                _id: Some(track_id.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .ok()?
+        .into_iter()
+        .next()
+}
+
+/// Enabled provider named `provider` (the `provider_extension` of its
+/// tracks), when it keeps favorites
+async fn favorites_provider(app: &AppHandle, provider: &str) -> Option<MediaPluginHandle> {
+    let providers = app
+        .state::<PluginHandler>()
+        .plugin_manager()
+        .get_audio_providers_by_selection(&MusicSourceSelection::default())
+        .await
+        .ok()?;
+    for (_, plugin) in providers {
+        let owns = {
+            let guard = plugin.lock().await;
+            guard.metadata().name == provider && guard.as_favorites().is_some()
+        };
+        if owns {
+            return Some(plugin);
+        }
+    }
+    None
+}
+
+/// Like or unlike `track_id` on the account of `provider`. Returns false when
+/// the provider keeps no favorites.
+async fn mirror(app: &AppHandle, provider: &str, track_id: &str, liked: bool) -> Result<bool> {
+    let Some(plugin) = favorites_provider(app, provider).await else {
+        return Ok(false);
+    };
+    let guard = plugin.lock().await;
+    let Some(favorites) = guard.as_favorites() else {
+        return Ok(false);
+    };
+    let track_ids = [track_id.to_string()];
+    let res = if liked {
+        timeout(PROVIDER_TIMEOUT, favorites.add_favorite_tracks(&track_ids)).await
+    } else {
+        timeout(PROVIDER_TIMEOUT, favorites.remove_favorite_tracks(&track_ids)).await
+    };
+    match res {
+        Ok(res) => res.map(|_| true).map_err(|e| MusicError::String(format!("Provider {} favorites failed: {}", provider, e))),
+        Err(_) => Err(MusicError::String(format!("Provider {} favorites timeout", provider))),
+    }
+}
+
+/// Like every favorite of the account signed in at `provider_id`
+async fn import_favorites(app: &AppHandle, provider_id: &str) -> Result<FavoritesImport> {
+    let selection = MusicSourceSelection {
+        mode: MusicSourceMode::Single,
+        ids: vec![provider_id.to_string()],
+    };
+    let (_, provider) = app
+        .state::<PluginHandler>()
+        .plugin_manager()
+        .get_audio_providers_by_selection(&selection)
+        .await
+        .map_err(|e| MusicError::String(format!("Failed to get audio providers: {}", e)))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| MusicError::String(format!("Provider {} is not available", provider_id)))?;
+
+    let (origin, tracks) = {
+        let guard = provider.lock().await;
+        let Some(favorites) = guard.as_favorites() else {
+            return Err(MusicError::String(format!("Provider {} keeps no favorites", provider_id)));
+        };
+        let tracks = match timeout(IMPORT_TIMEOUT, favorites.get_favorite_tracks()).await {
+            Ok(res) => res.map_err(|e| MusicError::String(format!("Provider {} favorites failed: {}", provider_id, e)))?,
+            Err(_) => return Err(MusicError::String(format!("Provider {} favorites timeout", provider_id))),
+        };
+        (guard.metadata().name, tracks)
+    };
+    let total = tracks.len();
+    let tracks: Vec<MediaContent> = tracks.into_iter().map(to_media_content).collect();
+    let imported = app.state::<Database>().like_tracks(tracks, &origin, now_ms())?;
+    Ok(FavoritesImport {
+        provider_id: provider_id.to_string(),
+        total: total as u32,
+        imported: imported as u32,
+    })
+}
+
+/// Import the favorites of `provider_id` and announce the outcome with
+/// `favorites-imported`
+async fn import_and_announce(app: &AppHandle, provider_id: &str) -> Result<FavoritesImport> {
+    let res = import_favorites(app, provider_id).await;
+    let payload = match &res {
+        Ok(import) => json!({ "providerId": provider_id, "import": import }),
+        Err(e) => {
+            tracing::warn!("Import of the favorites of {} failed: {:?}", provider_id, e);
+            json!({ "providerId": provider_id, "error": e.to_string() })
+        }
+    };
+    if let Err(e) = app.emit("favorites-imported", payload) {
+        tracing::warn!("Failed to emit favorites-imported event: {}", e);
+    }
+    res
+}
+
+/// An account was signed in at `provider_id`: import its favorites in the
+/// background
+pub fn on_signed_in(app: &AppHandle, provider_id: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let _ = import_and_announce(&app, &provider_id).await;
+    });
+}
+
+command_envelope! {
+    /// Add a track to the liked songs. Returns whether the like was mirrored
+    /// to the account of its provider; a failure to mirror it keeps the like
+    /// and is logged.
+    #[tracing::instrument(level = "debug", skip(app, database, track))]
+    #[tauri::command]
+    pub async fn like_track(app: AppHandle, database: State<'_, Database>, track: MediaContent) -> Result<bool> {
+        let Some(track_id) = track.track._id.clone() else {
+            return Err(MusicError::String("Track has no id".to_string()));
+        };
+        let provider = track.track.provider_extension.clone();
+        database.like_tracks(vec![track], LOCAL_ORIGIN, now_ms())?;
+        let Some(provider) = provider else {
+            return Ok(false);
+        };
+        match mirror(&app, &provider, &track_id, true).await {
+            Ok(mirrored) => Ok(mirrored),
+            Err(e) => {
+                tracing::warn!("Failed to like {} at {}: {:?}", track_id, provider, e);
+                Ok(false)
+            }
+        }
+    }
+}
+
+command_envelope! {
+    /// Remove a track from the liked songs, and from the favorites of the
+    /// account of its provider. Returns whether the unlike was mirrored.
+    #[tracing::instrument(level = "debug", skip(app, database))]
+    #[tauri::command]
+    pub async fn unlike_track(app: AppHandle, database: State<'_, Database>, track_id: String) -> Result<bool> {
+        database.unlike_track(&track_id)?;
+        let provider = find_track(&database, &track_id).and_then(|t| t.track.provider_extension);
+        let Some(provider) = provider else {
+            return Ok(false);
+        };
+        match mirror(&app, &provider, &track_id, false).await {
+            Ok(mirrored) => Ok(mirrored),
+            Err(e) => {
+                tracing::warn!("Failed to unlike {} at {}: {:?}", track_id, provider, e);
+                Ok(false)
+            }
+        }
+    }
+}
+
+command_envelope! {
+    /// Liked songs of every provider, most recently liked first
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn get_liked_tracks(database: State<'_, Database>) -> Result<Vec<LikedTrack>> {
+        database.get_liked_tracks()
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn is_track_liked(database: State<'_, Database>, track_id: String) -> Result<bool> {
+        database.is_track_liked(&track_id)
+    }
+}
+
+command_envelope! {
+    /// Import the favorites of the account signed in at `provider_id` into
+    /// the liked songs; done on sign-in already
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command]
+    pub async fn import_provider_favorites(app: AppHandle, provider_id: String) -> Result<FavoritesImport> {
+        import_and_announce(&app, &provider_id).await
+    }
+}
@@ -25,23 +25,77 @@ use tracing_subscriber::{
 use scanner::{
   start_scan,
   get_scanner_state, ScanTask, 
-  start_auto_scanner, stop_auto_scanner, trigger_manual_scan, get_auto_scanner_status, get_local_tracks
+  start_auto_scanner, stop_auto_scanner, trigger_manual_scan, cancel_scan, get_auto_scanner_status, get_local_tracks,
+  search_local_library, search_lyrics_library, estimate_scan, get_scan_extensions, set_scan_extensions, get_library_storage_report,
+  get_failed_scan_items, retry_failed_scan_items,
 };
 use plugins::{
   get_plugins, get_plugin, enable_plugin, disable_plugin, start_plugin, stop_plugin, load_plugin,
+  get_plugin_state_report, install_plugin_from_url, install_plugin_from_archive,
+  get_plugin_config_schema, get_plugin_config, set_plugin_config,
 };
+use plugins::endpoints::{get_provider_endpoint_status, set_provider_endpoint};
+use plugins::credentials::{get_plugin_credentials, get_plugin_network_audit, set_plugin_credential};
+use plugins::spotify::{spotify_account, spotify_login_poll, spotify_login_start, spotify_logout};
+use plugins::youtube::{youtube_channel_tracks, youtube_playlist_tracks, youtube_resolve_url};
 
 use music::commands::{
-  music_search,
+  music_search, music_search_streamed, music_get_recommendations,
 };
+use music::aggregate::aggregate_search;
+
+use playlists::{get_playlist_history, get_playlist_insights, restore_playlist_version};
+use playlists::sync::{
+  get_playlist_sync_conflicts, get_playlist_syncs, link_playlist_sync, resolve_playlist_sync_conflict,
+  sync_playlist, unlink_playlist_sync,
+};
+use library::{
+  get_tracks_smart_sorted, get_sort_presets, save_sort_preset, delete_sort_preset, set_track_rating,
+  get_tracks_by_features, edit_track_metadata, get_folder_tree, get_tracks_in_folder, get_library_roots,
+  get_unavailable_tracks,
+};
+use diagnostics::{dry_run_migrations, get_schema_version};
+use diagnostics::watchdog::get_system_health;
+use export::export_library_sqlite;
+use export::playlists::export_provider_playlists;
+use export::user_data::{export_user_data, import_user_data};
+use downloads::{cancel_download, download_track, list_downloads, pause_download, resume_download, set_network_metered};
+use privacy::{get_private_session, set_private_session};
+use network::{get_data_usage, set_network_class};
+use lyrics::get_lyrics;
+use metadata::{enrich_album, enrich_track};
+use metadata::fingerprint::lookup_acoustid;
+use stations::{play_radio_station, search_radio_stations};
+use podcasts::{
+  download_episode, list_episodes, list_podcasts, play_episode, refresh_podcasts, set_episode_played,
+  subscribe_podcast, unsubscribe_podcast,
+};
+use audiobooks::{audio_seek_chapter, audio_skip_back, audio_skip_forward, get_track_chapters};
+use bookmarks::{add_bookmark, jump_to_bookmark, list_bookmarks, remove_bookmark};
+use favorites::{get_liked_tracks, import_provider_favorites, is_track_liked, like_track, unlike_track};
+use backup::{backup_library, restore_library};
+use windowing::{subscribe_player_events, unsubscribe_player_events};
+use display::{format_track_display, format_tracks_display, get_artwork, DisplayService};
 
 use audio::{
   audio_play, audio_pause, audio_stop, audio_seek, audio_set_volume, audio_get_volume,
+  set_track_volume_clamp, get_volume_mode, set_volume_mode,
   // PlayerStore commands
-  get_current_track, get_queue, get_player_state, add_to_queue, remove_from_queue,
+  get_current_track, get_queue, get_player_state, add_to_queue, play_next, play_next_multiple, add_to_queue_at, remove_from_queue, move_queue_item,
   play_now, shuffle_queue, clear_queue, toggle_player_mode, get_player_mode,
-  set_player_mode, next_track, prev_track, change_index,
+  set_player_mode, next_track, prev_track, change_index, set_queue_item_overrides,
+  audio_set_crossfade, audio_set_track_gap, set_radio_mode, audio_set_interruption_policy, set_edit_regions, get_edit_regions, audio_set_loop_region, audio_clear_loop_region, audio_get_loop_region, get_normalization_preview,
+  audio_list_output_devices, audio_set_output_device, audio_take_restore_warning,
+  start_playback_trace, stop_playback_trace, start_audio_visualizer, stop_audio_visualizer,
 };
+use audio::resolver::get_resolver_status;
+use audio::profiles::apply_output_profile;
+use audio::quality::audio_set_quality;
+use audio::rate::{audio_get_rate, audio_set_rate};
+use audio::mood::{get_track_features, reclassify};
+use audio::identify::{identify_loopback_audio, list_loopback_devices};
+use audio::actions::perform_default_action;
+use audio::queue_history::{list_queue_history, restore_queue_from_history};
 
 mod db;
 use database::database::Database;
@@ -55,6 +109,26 @@ mod audio;
 mod playback;
 mod plugins;
 mod music;
+mod display;
+mod playlists;
+mod library;
+mod privacy;
+mod lyrics;
+mod metadata;
+mod tasks;
+mod diagnostics;
+mod export;
+mod downloads;
+mod windowing;
+mod network;
+mod stations;
+mod podcasts;
+mod audiobooks;
+mod bookmarks;
+mod favorites;
+mod backup;
+#[cfg(desktop)]
+mod open_with;
 
 /// run the app
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -70,6 +144,15 @@ pub fn run() {
 
   let mut builder = tauri::Builder::default();
 
+  #[cfg(desktop)]
+  {
+    // Must come first: later launches hand their files over and exit
+    builder = builder.plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+      open_with::handle_args(app, args, std::path::Path::new(&cwd));
+    }));
+    builder = builder.plugin(tauri_plugin_global_shortcut::Builder::new().build());
+  }
+
   builder = builder
     .plugin(tauri_plugin_dialog::init())
     .invoke_handler(tauri::generate_handler![
@@ -86,8 +169,17 @@ pub fn run() {
       start_auto_scanner,
       stop_auto_scanner, 
       trigger_manual_scan,
+      cancel_scan,
       get_auto_scanner_status,
       get_local_tracks,
+      search_local_library,
+      search_lyrics_library,
+      estimate_scan,
+      get_scan_extensions,
+      set_scan_extensions,
+      get_library_storage_report,
+      get_failed_scan_items,
+      retry_failed_scan_items,
       start_scan,
       // Audio Player Commands
       audio_play,
@@ -96,12 +188,21 @@ pub fn run() {
       audio_seek,
       audio_set_volume,
       audio_get_volume,
+      set_track_volume_clamp,
+      get_volume_mode,
+      set_volume_mode,
+      audio_set_rate,
+      audio_get_rate,
       // PlayerStore Commands
       get_current_track,
       get_queue,
       get_player_state,
       add_to_queue,
+      play_next,
+      play_next_multiple,
+      add_to_queue_at,
       remove_from_queue,
+      move_queue_item,
       play_now,
       shuffle_queue,
       clear_queue,
@@ -111,6 +212,32 @@ pub fn run() {
       next_track,
       prev_track,
       change_index,
+      set_queue_item_overrides,
+      perform_default_action,
+      list_queue_history,
+      restore_queue_from_history,
+      audio_set_crossfade,
+      audio_set_track_gap,
+      set_radio_mode,
+      audio_set_interruption_policy,
+      set_edit_regions,
+      get_edit_regions,
+      audio_set_loop_region,
+      audio_clear_loop_region,
+      audio_get_loop_region,
+      get_normalization_preview,
+      subscribe_player_events,
+      unsubscribe_player_events,
+      audio_list_output_devices,
+      audio_take_restore_warning,
+      audio_set_output_device,
+      start_playback_trace,
+      stop_playback_trace,
+      start_audio_visualizer,
+      stop_audio_visualizer,
+      get_resolver_status,
+      apply_output_profile,
+      audio_set_quality,
       // Plugin management
       get_plugins,
       get_plugin,
@@ -119,8 +246,118 @@ pub fn run() {
       start_plugin,
       stop_plugin,
       load_plugin,
+      get_plugin_state_report,
+      install_plugin_from_url,
+      install_plugin_from_archive,
+      get_plugin_config_schema,
+      get_plugin_config,
+      set_plugin_config,
+      set_provider_endpoint,
+      get_provider_endpoint_status,
+      set_plugin_credential,
+      get_plugin_credentials,
+      get_plugin_network_audit,
+      spotify_login_start,
+      spotify_login_poll,
+      spotify_logout,
+      spotify_account,
+      youtube_resolve_url,
+      youtube_playlist_tracks,
+      youtube_channel_tracks,
       // Music API
-      music_search
+      music_search,
+      music_search_streamed,
+      music_get_recommendations,
+      aggregate_search,
+      // Display formatting
+      format_track_display,
+      format_tracks_display,
+      get_artwork,
+      // Playlists
+      get_playlist_insights,
+      get_playlist_history,
+      restore_playlist_version,
+      link_playlist_sync,
+      unlink_playlist_sync,
+      get_playlist_syncs,
+      sync_playlist,
+      get_playlist_sync_conflicts,
+      resolve_playlist_sync_conflict,
+      // Liked songs
+      like_track,
+      unlike_track,
+      get_liked_tracks,
+      is_track_liked,
+      import_provider_favorites,
+      // Library
+      get_tracks_smart_sorted,
+      get_sort_presets,
+      save_sort_preset,
+      delete_sort_preset,
+      set_track_rating,
+      get_tracks_by_features,
+      edit_track_metadata,
+      get_folder_tree,
+      get_tracks_in_folder,
+      get_library_roots,
+      get_unavailable_tracks,
+      get_track_features,
+      reclassify,
+      identify_loopback_audio,
+      list_loopback_devices,
+      // Diagnostics
+      get_schema_version,
+      dry_run_migrations,
+      get_system_health,
+      // Export
+      export_library_sqlite,
+      export_provider_playlists,
+      export_user_data,
+      import_user_data,
+      // Backup
+      backup_library,
+      restore_library,
+      // Network
+      set_network_class,
+      get_data_usage,
+      // Downloads
+      set_network_metered,
+      download_track,
+      cancel_download,
+      pause_download,
+      resume_download,
+      list_downloads,
+      // Privacy
+      set_private_session,
+      get_private_session,
+      // Lyrics
+      get_lyrics,
+      // Metadata
+      enrich_track,
+      enrich_album,
+      lookup_acoustid,
+      // Radio
+      search_radio_stations,
+      play_radio_station,
+      // Podcasts
+      subscribe_podcast,
+      unsubscribe_podcast,
+      list_podcasts,
+      refresh_podcasts,
+      list_episodes,
+      play_episode,
+      set_episode_played,
+      download_episode,
+      // Audiobooks
+      audio_skip_forward,
+      audio_skip_back,
+      audio_seek_chapter,
+      get_track_chapters,
+      // Bookmarks
+      add_bookmark,
+      list_bookmarks,
+      remove_bookmark,
+      jump_to_bookmark,
     ])
     .setup(|app| {
        let layer = fmt::layer()
@@ -151,18 +388,40 @@ pub fn run() {
       tracing::subscriber::set_global_default(subscriber).unwrap();
 
       let db = get_db_state(app);
+      app.manage(tasks::TaskManager::new(db.clone()));
+      app.manage(downloads::DownloadQueue::new(app.path().app_data_dir().unwrap().join("downloads")));
       app.manage(db);
+      app.manage(get_cache_state(app));
 
       let scanner_state = get_scanner_state();
       app.manage(scanner_state);
 
       let scan_task = ScanTask::default();
       app.manage(scan_task);
+      app.manage(scanner::ScanRetryQueue::load(
+          app.path().app_data_dir().unwrap().join("scan_retry_queue.json"),
+      ));
+      scanner::start_retry_worker(app.handle().clone());
 
 
       let config = get_settings_state(app)?;
       app.manage(config);
 
+      app.manage(DisplayService::default());
+      app.manage(windowing::EventRouter::default());
+      app.manage(privacy::PrivateSession::default());
+      app.manage(lyrics::LyricsFollower::default());
+      app.manage(audio::PrecacheState::default());
+      app.manage(audio::PositionThrottle::default());
+      app.manage(audio::StreamResolver::default());
+      app.manage(audio::profiles::OutputProfileHotkeys::default());
+      app.manage(network::NetworkState::default());
+      app.manage(diagnostics::watchdog::Watchdog::default());
+      app.manage(podcasts::PodcastState::default());
+      app.manage(playlists::sync::PlaylistSyncState::default());
+      app.manage(audiobooks::AudiobookState::default());
+      app.manage(bookmarks::BookmarkState::default());
+
 
       // Initialize plugin manager
       let plugins_root = app.path().app_data_dir().unwrap().join("plugins");
@@ -177,19 +436,49 @@ pub fn run() {
       // Note: This must come AFTER plugin handler is managed
       let audio_state = audio::build_audio_player(app.app_handle().clone());
       app.manage(audio_state);
+      display::apply_display_settings(app.app_handle());
+      audio::profiles::register_profile_hotkeys(app.app_handle());
+      audio::profiles::restore_output_cap(app.app_handle());
+      audio::profiles::start_device_watcher(app.handle().clone());
+      audio::mood::spawn_auto_classify(app.app_handle());
+      metadata::spawn_auto_enrich(app.app_handle());
+      metadata::fingerprint::spawn_auto_fingerprint(app.app_handle());
+      // Files this launch was opened with
+      #[cfg(desktop)]
+      open_with::handle_args(
+          app.app_handle(),
+          std::env::args().collect(),
+          &std::env::current_dir().unwrap_or_default(),
+      );
       
       // Initialize plugins (use Tauri's runtime to ensure a reactor exists)
+      let app_handle = app.handle().clone();
       tauri::async_runtime::spawn(async move {
+          // Whether the built-in Spotify plugin gets loaded
+          plugins::spotify::apply_spotify_enabled(&app_handle).await;
           if let Err(e) = plugin_manager.initialize().await {
               eprintln!("Failed to initialize plugins: {}", e);
           }
+          plugins::spotify::attach_spotify_account(&app_handle).await;
+          plugins::start_plugin_hot_reload(app_handle.clone(), &plugin_manager);
+          plugins::endpoints::apply_endpoint_settings(&app_handle);
+          plugins::credentials::apply_plugin_credentials(&app_handle);
           
           // Start plugins
           if let Err(e) = plugin_manager.start_plugins().await {
               eprintln!("Failed to start plugins: {}", e);
           }
+
+          audio::resume_after_launch(&app_handle).await;
+
+          // Downloads resolve streams through the media plugins
+          downloads::start_download_worker(app_handle.clone());
+          downloads::queue_smart_downloads(&app_handle);
       });
 
+      diagnostics::watchdog::start_watchdog(app.handle().clone());
+      podcasts::start_refresh(app.handle().clone());
+      playlists::sync::start_sync(app.handle().clone());
       initial(app);
       handle_settings_changes(app.handle().clone());
       Ok(())
@@ -198,6 +487,35 @@ pub fn run() {
 
 
   builder
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application")
+    .build(tauri::generate_context!())
+    .expect("error while building tauri application")
+    .run(|app, event| {
+      match event {
+        tauri::RunEvent::Exit => {
+          audio::on_exit(app);
+          privacy::on_exit(app);
+        }
+        tauri::RunEvent::WindowEvent {
+          label,
+          event: tauri::WindowEvent::Destroyed,
+          ..
+        } => {
+          app.state::<windowing::EventRouter>().forget(&label);
+          app.state::<audio::PositionThrottle>().set_focused(&label, false);
+        }
+        // Position updates slow down while no window has focus
+        tauri::RunEvent::WindowEvent {
+          label,
+          event: tauri::WindowEvent::Focused(focused),
+          ..
+        } => app.state::<audio::PositionThrottle>().set_focused(&label, focused),
+        // Finder's "Open With" and files dropped on the Dock icon
+        #[cfg(target_os = "macos")]
+        tauri::RunEvent::Opened { urls } => {
+          let paths = urls.into_iter().filter_map(|url| url.to_file_path().ok()).collect();
+          open_with::open_paths(app, open_with::OpenAction::Play, paths);
+        }
+        _ => {}
+      }
+    })
 }
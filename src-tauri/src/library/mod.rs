@@ -0,0 +1,193 @@
+use std::path::PathBuf;
+
+use database::database::Database;
+use macros::command_envelope;
+use settings::settings::SettingsConfig;
+use tauri::{AppHandle, Emitter, State};
+use types::entities::{FolderNode, LibraryRootStatus, SmartSortPreset, TrackEditFailure, TrackEditResult, TrackFeatureFilter, TrackMetadataEdit};
+use types::errors::Result;
+use types::tracks::{GetTrackOptions, MediaContent, SearchableTrack, TrackType};
+
+use crate::scanner::ScanTask;
+
+/// Write `edit` to the file of a local track, then to the library. The auto
+/// scanner is told to skip the file so the write isn't scanned back in.
+pub(crate) fn edit_track(
+    database: &Database,
+    scan_task: &ScanTask,
+    track_id: &str,
+    edit: &TrackMetadataEdit,
+    artist_split: &str,
+) -> Result<()> {
+    let track = database
+        .get_tracks_by_options(GetTrackOptions {
+            track: Some(SearchableTrack {
+                _id: Some(track_id.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })?
+        .into_iter()
+        .next()
+        .ok_or("Track not found")?;
+    let path = match (&track.track.type_, &track.track.path) {
+        (TrackType::LOCAL, Some(path)) => PathBuf::from(path),
+        _ => return Err("Only local files can be edited".into()),
+    };
+
+    let paths = [path];
+    scan_task.suppress_rescan(&paths);
+    let written = file_scanner::write_tags(&paths[0], edit, artist_split);
+    scan_task.suppress_rescan(&paths);
+    written?;
+    database.edit_track_metadata(track_id, edit)
+}
+
+command_envelope! {
+    /// Run a library query and order the result by the weighted criteria of
+    /// `preset` (recently added, play count, rating, alphabetical), ranked in SQL.
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn get_tracks_smart_sorted(
+        database: State<'_, Database>,
+        options: GetTrackOptions,
+        preset: SmartSortPreset,
+    ) -> Result<Vec<MediaContent>> {
+        database.get_tracks_smart_sorted(options, &preset)
+    }
+}
+
+command_envelope! {
+    /// Run a library query and keep the tracks whose audio features match
+    /// `filter` (moods, energy, valence, danceability, tempo ranges).
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn get_tracks_by_features(
+        database: State<'_, Database>,
+        options: GetTrackOptions,
+        filter: TrackFeatureFilter,
+    ) -> Result<Vec<MediaContent>> {
+        database.get_tracks_by_features(options, &filter)
+    }
+}
+
+command_envelope! {
+    /// Saved smart sort presets, by name.
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn get_sort_presets(database: State<'_, Database>) -> Result<Vec<SmartSortPreset>> {
+        database.get_sort_presets()
+    }
+}
+
+command_envelope! {
+    /// Save a smart sort preset, replacing the one with the same name.
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn save_sort_preset(database: State<'_, Database>, preset: SmartSortPreset) -> Result<()> {
+        database.save_sort_preset(&preset)
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn delete_sort_preset(database: State<'_, Database>, name: String) -> Result<()> {
+        database.delete_sort_preset(&name)
+    }
+}
+
+command_envelope! {
+    /// Change the title, artists, album, genre, track number or year of local
+    /// tracks, in their file tags and in the library. Fields left unset are
+    /// kept; a failure on one track doesn't stop the others.
+    #[tracing::instrument(level = "debug", skip(app, database, settings, scan_task))]
+    #[tauri::command(async)]
+    pub fn edit_track_metadata(
+        app: AppHandle,
+        database: State<'_, Database>,
+        settings: State<'_, SettingsConfig>,
+        scan_task: State<'_, ScanTask>,
+        track_ids: Vec<String>,
+        edit: TrackMetadataEdit,
+    ) -> Result<TrackEditResult> {
+        let artist_split: String = settings
+            .load_selective("artist_splitter".to_string())
+            .unwrap_or_else(|_| ";".to_string());
+        let mut result = TrackEditResult::default();
+        for track_id in track_ids {
+            match edit_track(&database, &scan_task, &track_id, &edit, &artist_split) {
+                Ok(()) => result.updated.push(track_id),
+                Err(e) => result.failed.push(TrackEditFailure { track_id, error: e.to_string() }),
+            }
+        }
+        if !result.updated.is_empty() {
+            if let Err(e) = app.emit("tracks-updated", &result.updated) {
+                tracing::warn!("Failed to emit tracks-updated event: {}", e);
+            }
+        }
+        Ok(result)
+    }
+}
+
+command_envelope! {
+    /// Folder tree of the local library, one root per configured music path.
+    /// Only folders holding tracks are listed.
+    #[tracing::instrument(level = "debug", skip(database, settings))]
+    #[tauri::command(async)]
+    pub fn get_folder_tree(
+        database: State<'_, Database>,
+        settings: State<'_, SettingsConfig>,
+    ) -> Result<Vec<FolderNode>> {
+        let music_paths: Vec<String> = settings.load_selective("music_paths".to_string()).unwrap_or_default();
+        // Track paths are stored canonicalized
+        let roots: Vec<PathBuf> = music_paths
+            .iter()
+            .map(|p| dunce::canonicalize(p).unwrap_or_else(|_| PathBuf::from(p)))
+            .collect();
+        database.get_folder_tree(&roots)
+    }
+}
+
+command_envelope! {
+    /// Tracks in a folder of `get_folder_tree`, by path; with `recursive`
+    /// those of its subfolders too.
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn get_tracks_in_folder(
+        database: State<'_, Database>,
+        path: String,
+        recursive: bool,
+    ) -> Result<Vec<MediaContent>> {
+        database.get_tracks_in_folder(&PathBuf::from(path), recursive)
+    }
+}
+
+command_envelope! {
+    /// Music folders seen by the scanner and whether they are reachable.
+    /// Tracks below an unreachable folder stay in the library.
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn get_library_roots(database: State<'_, Database>) -> Result<Vec<LibraryRootStatus>> {
+        database.get_library_roots()
+    }
+}
+
+command_envelope! {
+    /// Ids of the tracks that can't be played because their music folder is
+    /// unreachable.
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn get_unavailable_tracks(database: State<'_, Database>) -> Result<Vec<String>> {
+        database.get_unavailable_track_ids()
+    }
+}
+
+command_envelope! {
+    /// Rate a track from 1 to 5 stars; 0 clears the rating.
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn set_track_rating(database: State<'_, Database>, track_id: String, rating: u8) -> Result<()> {
+        database.set_track_rating(&track_id, rating)
+    }
+}
@@ -0,0 +1,240 @@
+//! Lyrics pipeline: resolve the lyrics of a track from its embedded tags, its
+//! `.lrc` sidecar file or the media plugins, cache them in `tracks.lyrics` and
+//! follow playback to announce the active line of time-synced lyrics.
+
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use database::{database::Database, lyrics};
+use macros::command_envelope;
+use music_plugin_sdk::types::Lyrics as SdkLyrics;
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use tokio::time::timeout;
+use types::entities::{LyricsLine, TrackLyrics};
+use types::errors::Result;
+use types::tracks::{GetTrackOptions, MediaContent, SearchableTrack, TrackType};
+
+use crate::plugins::manager::PluginHandler;
+
+/// Per-provider time limit for a lyrics lookup
+const PROVIDER_TIMEOUT: Duration = Duration::from_secs(8);
+
+/// Synced lyrics of the track being played, followed on time updates
+#[derive(Default)]
+pub struct LyricsFollower {
+    state: Mutex<FollowState>,
+}
+
+#[derive(Default)]
+struct FollowState {
+    /// Track the lines belong to; set as soon as loading starts
+    track_id: Option<String>,
+    lines: Vec<LyricsLine>,
+    current: Option<usize>,
+}
+
+fn find_track(database: &Database, track_id: &str) -> Option<MediaContent> {
+    database
+        .get_tracks_by_options(GetTrackOptions {
+            track: Some(SearchableTrack {
+                _id: Some(track_id.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .ok()?
+        .into_iter()
+        .next()
+}
+
+/// Embedded tags first, then the sidecar; a synced candidate beats a plain one.
+fn local_lyrics(path: &Path) -> Option<(&'static str, String)> {
+    let candidates = [
+        file_scanner::read_embedded_lyrics(path).map(|l| ("embedded", l)),
+        file_scanner::read_lrc_sidecar(path).map(|l| ("sidecar", l)),
+    ];
+    let mut found = candidates.into_iter().flatten();
+    let first = found.next()?;
+    if lyrics::is_synced(&first.1) {
+        return Some(first);
+    }
+    Some(found.find(|(_, l)| lyrics::is_synced(l)).unwrap_or(first))
+}
+
+/// Plugin lyrics as text, preferring the time-synced lines rendered as LRC
+fn provider_text(found: SdkLyrics) -> String {
+    let synced = found
+        .versions
+        .unwrap_or_default()
+        .into_iter()
+        .find(|v| v.synced && !v.lines.is_empty());
+    match synced {
+        Some(version) => {
+            let lines: Vec<LyricsLine> = version
+                .lines
+                .into_iter()
+                .map(|l| LyricsLine { time_ms: l.timestamp_ms.map(u64::from), text: l.text })
+                .collect();
+            lyrics::to_lrc(&lines)
+        }
+        None => found.text,
+    }
+}
+
+async fn provider_lyrics(plugin_handler: &PluginHandler, track_id: &str) -> Option<(String, String)> {
+    let selection = types::settings::music::MusicSourceSelection::default();
+    let providers = match plugin_handler
+        .plugin_manager()
+        .get_audio_providers_by_selection(&selection)
+        .await
+    {
+        Ok(providers) => providers,
+        Err(e) => {
+            tracing::warn!("Lyrics: failed to get providers: {}", e);
+            return None;
+        }
+    };
+
+    for (provider_id, provider) in providers {
+        let plugin = provider.lock().await;
+        match timeout(PROVIDER_TIMEOUT, plugin.get_lyrics(track_id)).await {
+            Ok(Ok(Some(found))) => {
+                let text = provider_text(found);
+                if !text.trim().is_empty() {
+                    return Some((provider_id.to_string(), text));
+                }
+            }
+            Ok(Ok(None)) => {}
+            Ok(Err(e)) => tracing::debug!("Lyrics: provider {} has none for {}: {}", provider_id, track_id, e),
+            Err(_) => tracing::warn!("Lyrics: provider {} timed out", provider_id),
+        }
+    }
+    None
+}
+
+/// Resolve the lyrics of `track_id`: cached synced lyrics, then the local file
+/// (embedded tags, `.lrc` sidecar), then cached plain lyrics, then the media
+/// plugins. New results are cached in the library.
+#[tracing::instrument(level = "debug", skip(app))]
+pub async fn resolve(app: &AppHandle, track_id: &str, refresh: bool) -> Result<Option<TrackLyrics>> {
+    let database = app.state::<Database>();
+    let track = find_track(&database, track_id);
+    let cached = track
+        .as_ref()
+        .and_then(|t| t.track.lyrics.clone())
+        .filter(|l| !l.trim().is_empty() && !refresh);
+
+    let build = |source: &str, text: String| TrackLyrics {
+        track_id: track_id.to_string(),
+        source: source.to_string(),
+        synced: lyrics::is_synced(&text),
+        lines: lyrics::parse_lines(&text),
+        text,
+    };
+
+    if let Some(text) = cached.as_ref().filter(|l| lyrics::is_synced(l)) {
+        return Ok(Some(build("library", text.clone())));
+    }
+
+    // The scanner stores LRC without its time tags, so re-read local files
+    let local_path = track
+        .as_ref()
+        .filter(|t| t.track.type_ == TrackType::LOCAL)
+        .and_then(|t| t.track.path.clone());
+    let local = match local_path {
+        Some(path) => tauri::async_runtime::spawn_blocking(move || local_lyrics(Path::new(&path)))
+            .await
+            .ok()
+            .flatten(),
+        None => None,
+    };
+
+    let (source, text) = match (local, cached) {
+        (Some(local), _) if lyrics::is_synced(&local.1) => (local.0.to_string(), local.1),
+        (_, Some(cached)) => return Ok(Some(build("library", cached))),
+        (Some(local), None) => (local.0.to_string(), local.1),
+        (None, None) => {
+            let plugin_handler = app.state::<PluginHandler>();
+            match provider_lyrics(&plugin_handler, track_id).await {
+                Some(found) => found,
+                None => return Ok(None),
+            }
+        }
+    };
+
+    if track.is_some() {
+        if let Err(e) = database.update_lyrics(track_id.to_string(), text.clone()) {
+            tracing::warn!("Failed to cache lyrics of {}: {}", track_id, e);
+        }
+    }
+    Ok(Some(build(&source, text)))
+}
+
+/// Follow playback of `track_id` at `position` seconds. Loads the lyrics of a
+/// new track in the background (emitting `LyricsLoaded`), then emits
+/// `LyricLineChanged` on the `audio_event` channel whenever the active line of
+/// synced lyrics changes.
+pub fn on_time_update(app: &AppHandle, track_id: Option<String>, position: f64) {
+    let Some(follower) = app.try_state::<LyricsFollower>() else { return };
+    let Some(track_id) = track_id else { return };
+    let Ok(mut state) = follower.state.lock() else { return };
+
+    if state.track_id.as_deref() != Some(track_id.as_str()) {
+        *state = FollowState {
+            track_id: Some(track_id.clone()),
+            ..Default::default()
+        };
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let found = match resolve(&app, &track_id, false).await {
+                Ok(found) => found,
+                Err(e) => {
+                    tracing::warn!("Failed to load lyrics of {}: {}", track_id, e);
+                    None
+                }
+            };
+            let follower = app.state::<LyricsFollower>();
+            let Ok(mut state) = follower.state.lock() else { return };
+            // Skipped to another track while loading
+            if state.track_id.as_deref() != Some(track_id.as_str()) {
+                return;
+            }
+            if let Some(found) = found.as_ref().filter(|l| l.synced) {
+                state.lines = found.lines.clone();
+            }
+            let _ = crate::windowing::emit_audio_event(
+                &app,
+                json!({ "type": "LyricsLoaded", "data": { "track_id": track_id, "lyrics": found } }),
+            );
+        });
+        return;
+    }
+
+    let index = lyrics::line_at(&state.lines, (position.max(0.0) * 1000.0) as u64);
+    if index == state.current {
+        return;
+    }
+    state.current = index;
+    if let Some(i) = index {
+        let line = &state.lines[i];
+        let _ = crate::windowing::emit_audio_event(
+            app,
+            json!({
+                "type": "LyricLineChanged",
+                "data": { "track_id": track_id, "index": i, "time_ms": line.time_ms, "text": line.text },
+            }),
+        );
+    }
+}
+
+command_envelope! {
+    /// Lyrics of a track, from its embedded tags, its `.lrc` sidecar file or the
+    /// media plugins, cached in the library. `refresh` ignores the cache.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command(async)]
+    pub async fn get_lyrics(app: AppHandle, track_id: String, refresh: Option<bool>) -> Result<Option<TrackLyrics>> {
+        resolve(&app, &track_id, refresh.unwrap_or(false)).await
+    }
+}
@@ -0,0 +1,358 @@
+//! Audio fingerprints of local tracks. With `music.fingerprint.enabled` local
+//! tracks are fingerprinted in the background after scans; when a file
+//! disappears, a track of about the same length with a matching fingerprint is
+//! taken as where it moved (or its re-rip), and its play history, playlist
+//! entries and ratings are moved there before it is removed. With an AcoustID
+//! key and `fixTags`, missing titles, artists and albums are filled in from
+//! the recording AcoustID identifies.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use database::database::Database;
+use macros::command_envelope;
+use serde::Deserialize;
+use serde_json::json;
+use settings::settings::SettingsConfig;
+use tauri::{AppHandle, Emitter, Manager};
+use types::entities::{TrackFingerprint, TrackMetadataEdit};
+use types::errors::{error_helpers, MusicError, Result};
+use types::settings::music::MusicFingerprintSettings;
+use types::tracks::{MediaContent, TrackType};
+
+use crate::network;
+use crate::scanner::ScanTask;
+
+const ACOUSTID_URL: &str = "https://api.acoustid.org/v2/lookup";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+/// AcoustID allows three requests per second
+const REQUEST_INTERVAL: Duration = Duration::from_millis(350);
+
+/// Lowest AcoustID score (0-1) taken as a match
+const MIN_ACOUSTID_SCORE: f64 = 0.9;
+/// Lowest fingerprint similarity for two files to hold the same recording
+const MIN_SIMILARITY: f64 = 0.9;
+/// Length difference allowed between a file and where it moved, in seconds
+const DURATION_TOLERANCE: f64 = 3.0;
+/// Tracks compared with a removed one at most
+const MAX_CANDIDATES: i64 = 8;
+/// Tracks fingerprinted per background batch
+const BATCH: i64 = 10;
+
+/// When the next AcoustID request may be sent
+static NEXT_REQUEST: Mutex<Option<Instant>> = Mutex::new(None);
+/// Set while the background fingerprinting runs, so scans don't start another
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Deserialize)]
+struct LookupResponse {
+    #[serde(default)]
+    results: Vec<LookupResult>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct LookupResult {
+    id: String,
+    score: f64,
+    #[serde(default)]
+    recordings: Vec<Recording>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Recording {
+    title: Option<String>,
+    #[serde(default)]
+    artists: Vec<Artist>,
+    #[serde(default)]
+    releasegroups: Vec<ReleaseGroup>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Artist {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ReleaseGroup {
+    title: String,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+}
+
+fn fingerprint_settings(app: &AppHandle) -> MusicFingerprintSettings {
+    app.state::<SettingsConfig>()
+        .load_selective::<MusicFingerprintSettings>("music.fingerprint".to_string())
+        .unwrap_or_default()
+}
+
+fn fingerprint_enabled(app: &AppHandle) -> bool {
+    fingerprint_settings(app).enabled.unwrap_or(false)
+}
+
+fn acoustid_key(settings: &MusicFingerprintSettings) -> Option<&str> {
+    settings.acoustid_key.as_deref().map(str::trim).filter(|k| !k.is_empty())
+}
+
+fn local_path(track: &MediaContent) -> Option<PathBuf> {
+    match (&track.track.type_, &track.track.path) {
+        (TrackType::LOCAL, Some(path)) => Some(PathBuf::from(path)),
+        _ => None,
+    }
+}
+
+/// Decode a local track, then store and return its fingerprint
+fn fingerprint_track(database: &Database, track_id: &str) -> Result<TrackFingerprint> {
+    let track = super::find_track(database, track_id).ok_or_else(|| format!("No track {} in the library", track_id))?;
+    let path = local_path(&track).ok_or("Only local tracks can be fingerprinted")?;
+    let audio = audio_player::fingerprint::fingerprint_file(&path)?;
+    database.set_track_fingerprint(track_id, &audio.fingerprint, audio.duration)?;
+    Ok(TrackFingerprint {
+        track_id: track_id.to_string(),
+        fingerprint: audio.fingerprint,
+        duration: audio.duration,
+        acoustid: None,
+    })
+}
+
+fn stored_or_computed(database: &Database, track_id: &str) -> Option<TrackFingerprint> {
+    match database.get_track_fingerprint(track_id) {
+        Ok(Some(stored)) => return Some(stored),
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Failed to load the fingerprint of {}: {:?}", track_id, e),
+    }
+    fingerprint_track(database, track_id)
+        .map_err(|e| tracing::debug!("Failed to fingerprint {}: {:?}", track_id, e))
+        .ok()
+}
+
+/// Before a track whose file disappeared is removed, find the track holding
+/// the same recording and move its history, playlist entries and ratings
+/// there. Returns the track relinked to; `None` when fingerprinting is off,
+/// the track was never fingerprinted or nothing matches.
+pub fn relink_removed_track(app: &AppHandle, database: &Database, track_id: &str) -> Option<String> {
+    if !fingerprint_enabled(app) {
+        return None;
+    }
+    // The file is gone, only a fingerprint taken earlier can identify it
+    let removed = database.get_track_fingerprint(track_id).ok().flatten()?;
+    let candidates = database
+        .get_fingerprint_candidates(track_id, removed.duration, DURATION_TOLERANCE, MAX_CANDIDATES)
+        .map_err(|e| tracing::warn!("Failed to list the tracks {} may have moved to: {:?}", track_id, e))
+        .ok()?;
+    let target = candidates.into_iter().find(|candidate| {
+        stored_or_computed(database, candidate).is_some_and(|c| {
+            audio_player::fingerprint::similarity(&removed.fingerprint, &c.fingerprint) >= MIN_SIMILARITY
+        })
+    })?;
+
+    if let Err(e) = database.relink_track(track_id, &target) {
+        tracing::warn!("Failed to relink {} to {}: {:?}", track_id, target, e);
+        return None;
+    }
+    tracing::info!("Relinked removed track {} to {}", track_id, target);
+    if let Err(e) = app.emit("track-relinked", json!({ "from": track_id, "to": target })) {
+        tracing::warn!("Failed to emit track-relinked event: {}", e);
+    }
+    Some(target)
+}
+
+fn client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(error_helpers::to_network_error)
+}
+
+/// Wait for the turn of the next request
+async fn throttle() {
+    let wait = {
+        let mut next = NEXT_REQUEST.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let at = next.filter(|at| *at > now).unwrap_or(now);
+        *next = Some(at + REQUEST_INTERVAL);
+        at - now
+    };
+    tokio::time::sleep(wait).await;
+}
+
+/// Best AcoustID match of a fingerprint, `None` when nothing scores
+/// `MIN_ACOUSTID_SCORE`
+async fn lookup(key: &str, fingerprint: &TrackFingerprint) -> Result<Option<LookupResult>> {
+    let encoded = audio_player::fingerprint::encode_fingerprint(&fingerprint.fingerprint);
+    let duration = (fingerprint.duration.round() as u64).to_string();
+    let url = reqwest::Url::parse_with_params(
+        ACOUSTID_URL,
+        &[
+            ("client", key),
+            ("meta", "recordings releasegroups"),
+            ("duration", duration.as_str()),
+            ("fingerprint", encoded.as_str()),
+        ],
+    )
+    .map_err(error_helpers::to_parse_error)?;
+
+    throttle().await;
+    let body = client()?
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(error_helpers::to_network_error)?
+        .bytes()
+        .await
+        .map_err(error_helpers::to_network_error)?;
+    let response: LookupResponse = serde_json::from_slice(&body).map_err(error_helpers::to_parse_error)?;
+    Ok(response
+        .results
+        .into_iter()
+        .filter(|r| r.score >= MIN_ACOUSTID_SCORE && !r.recordings.is_empty())
+        .max_by(|a, b| a.score.total_cmp(&b.score)))
+}
+
+/// Title the scanner falls back to when a file has none: its file name
+fn is_placeholder_title(title: Option<&str>, path: &Path) -> bool {
+    let Some(title) = title.map(str::trim).filter(|t| !t.is_empty()) else {
+        return true;
+    };
+    let name = path.file_name().map(|n| n.to_string_lossy());
+    let stem = path.file_stem().map(|s| s.to_string_lossy());
+    name.as_deref() == Some(title) || stem.as_deref() == Some(title)
+}
+
+/// Tags of the recording for the fields `track` is missing; `None` when it
+/// misses nothing the recording has
+fn missing_tags(track: &MediaContent, path: &Path, recording: &Recording) -> Option<TrackMetadataEdit> {
+    let mut edit = TrackMetadataEdit::default();
+    if is_placeholder_title(track.track.title.as_deref(), path) {
+        edit.title = recording.title.clone();
+    }
+    if track.artists.as_ref().map_or(true, |a| a.is_empty()) && !recording.artists.is_empty() {
+        edit.artists = Some(recording.artists.iter().map(|a| a.name.clone()).collect());
+    }
+    let has_album = track
+        .album
+        .as_ref()
+        .and_then(|a| a.album_name.as_deref())
+        .is_some_and(|name| !name.trim().is_empty());
+    if !has_album {
+        // Albums before compilations and singles
+        let group = recording
+            .releasegroups
+            .iter()
+            .find(|g| g.kind.as_deref() == Some("Album"))
+            .or_else(|| recording.releasegroups.first());
+        edit.album = group.map(|g| g.title.clone());
+    }
+    (edit.title.is_some() || edit.artists.is_some() || edit.album.is_some()).then_some(edit)
+}
+
+/// Identify a local track on AcoustID and return the tags it misses
+async fn identify(app: &AppHandle, key: &str, track_id: &str) -> Result<Option<TrackMetadataEdit>> {
+    let database = app.state::<Database>();
+    let track = super::find_track(&database, track_id).ok_or_else(|| format!("No track {} in the library", track_id))?;
+    let path = local_path(&track).ok_or("Only local tracks can be identified")?;
+    let fingerprint = match database.get_track_fingerprint(track_id)? {
+        Some(stored) => stored,
+        None => {
+            let app = app.clone();
+            let track_id = track_id.to_string();
+            tauri::async_runtime::spawn_blocking(move || fingerprint_track(&app.state::<Database>(), &track_id))
+                .await
+                .unwrap_or_else(|e| Err(MusicError::String(e.to_string())))?
+        }
+    };
+
+    let Some(result) = lookup(key, &fingerprint).await? else {
+        return Ok(None);
+    };
+    database.set_track_acoustid(track_id, &result.id)?;
+    Ok(missing_tags(&track, &path, &result.recordings[0]))
+}
+
+/// Fill in the missing tags of a track from AcoustID, in its file and in the
+/// library
+async fn fix_tags(app: &AppHandle, key: &str, track_id: &str) -> Result<()> {
+    let Some(edit) = identify(app, key, track_id).await? else {
+        return Ok(());
+    };
+    let artist_split: String = app
+        .state::<SettingsConfig>()
+        .load_selective("artist_splitter".to_string())
+        .unwrap_or_else(|_| ";".to_string());
+    crate::library::edit_track(&app.state::<Database>(), &app.state::<ScanTask>(), track_id, &edit, &artist_split)?;
+    if let Err(e) = app.emit("tracks-updated", [track_id]) {
+        tracing::warn!("Failed to emit tracks-updated event: {}", e);
+    }
+    Ok(())
+}
+
+/// Fingerprint the local tracks never fingerprinted, in batches in the
+/// background, when fingerprinting is on. With `fixTags` their missing tags
+/// are looked up on AcoustID, except on metered connections. Does nothing when
+/// fingerprinting already runs.
+pub fn spawn_auto_fingerprint(app: &AppHandle) {
+    if !fingerprint_enabled(app) || RUNNING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // Undecodable files stay unfingerprinted; don't pick them up again
+        let mut failed: Vec<String> = Vec::new();
+        let mut seen: HashSet<String> = HashSet::new();
+        while fingerprint_enabled(&app) {
+            let pending = match app.state::<Database>().get_unfingerprinted_track_ids(BATCH, &failed) {
+                Ok(ids) => ids,
+                Err(e) => {
+                    tracing::warn!("Failed to list tracks to fingerprint: {:?}", e);
+                    break;
+                }
+            };
+            if pending.is_empty() {
+                break;
+            }
+            let settings = fingerprint_settings(&app);
+            for track_id in pending {
+                let task_app = app.clone();
+                let id = track_id.clone();
+                let printed =
+                    tauri::async_runtime::spawn_blocking(move || fingerprint_track(&task_app.state::<Database>(), &id))
+                        .await
+                        .unwrap_or_else(|e| Err(MusicError::String(e.to_string())));
+                if let Err(e) = printed {
+                    tracing::debug!("Failed to fingerprint {}: {:?}", track_id, e);
+                    if seen.insert(track_id.clone()) {
+                        failed.push(track_id);
+                    }
+                    continue;
+                }
+                let Some(key) = acoustid_key(&settings).filter(|_| settings.fix_tags.unwrap_or(false)) else {
+                    continue;
+                };
+                if network::is_metered(&app) {
+                    continue;
+                }
+                if let Err(e) = fix_tags(&app, key, &track_id).await {
+                    tracing::debug!("Failed to fix the tags of {} from AcoustID: {:?}", track_id, e);
+                }
+            }
+        }
+        RUNNING.store(false, Ordering::Release);
+    });
+}
+
+command_envelope! {
+    /// Identify a local track by its fingerprint on AcoustID and return the
+    /// title, artists and album it is missing, to be reviewed and applied
+    /// with `edit_track_metadata`. `None` when AcoustID has no close match or
+    /// the track misses nothing.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command]
+    pub async fn lookup_acoustid(app: AppHandle, track_id: String) -> Result<Option<TrackMetadataEdit>> {
+        let settings = fingerprint_settings(&app);
+        let key = acoustid_key(&settings).ok_or("No AcoustID key is set")?;
+        identify(&app, key, &track_id).await
+    }
+}
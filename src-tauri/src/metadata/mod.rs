@@ -0,0 +1,378 @@
+//! Metadata enrichment from MusicBrainz and the Cover Art Archive. A track is
+//! matched to a MusicBrainz recording by title, artist and album; the cover of
+//! the release found is stored through the artwork pipeline when the track has
+//! none, and the release and artist details are merged into the extra info of
+//! the album and artists (under `musicbrainz`). MusicBrainz asks for at most
+//! one request per second; lookups are cached in the cache database. With
+//! `music.enrichment.autoEnrich` local tracks without a cover are looked up in
+//! the background after scans. Identification by audio fingerprint is in
+//! `fingerprint`.
+
+pub mod fingerprint;
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use settings::settings::SettingsConfig;
+use database::cache::CacheHolder;
+use database::database::Database;
+use macros::command_envelope;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager, State};
+use types::entities::{EnrichmentResult, EntityInfo, QueryableAlbum};
+use types::errors::{error_helpers, MusicError, Result};
+use types::settings::music::MusicEnrichmentSettings;
+use types::tracks::{GetTrackOptions, MediaContent, SearchableTrack};
+
+use crate::network::{self, DataCategory};
+
+const MUSICBRAINZ_URL: &str = "https://musicbrainz.org/ws/2";
+const COVER_ART_URL: &str = "https://coverartarchive.org";
+
+/// MusicBrainz rejects clients that don't identify themselves
+const USER_AGENT: &str = concat!("Music/", env!("CARGO_PKG_VERSION"), " ( https://github.com/lkieryan/music )");
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+/// Shortest interval between two requests to MusicBrainz or the archive
+const REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Lookups are kept this long in the cache database, misses included
+const CACHE_SECS: i32 = 30 * 24 * 3600;
+/// Lowest search score (0-100) taken as a match
+const MIN_SCORE: u32 = 90;
+/// Tracks looked up per background batch
+const BATCH: i64 = 10;
+
+/// When the next request may be sent
+static NEXT_REQUEST: Mutex<Option<Instant>> = Mutex::new(None);
+/// Set while the background lookups run, so scans don't start others
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordingSearch {
+    #[serde(default)]
+    recordings: Vec<Recording>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Recording {
+    id: String,
+    #[serde(default)]
+    score: u32,
+    #[serde(rename = "artist-credit", default)]
+    artist_credit: Vec<ArtistCredit>,
+    #[serde(default)]
+    releases: Vec<Release>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArtistCredit {
+    name: String,
+    artist: Artist,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Artist {
+    id: String,
+    #[serde(rename = "sort-name")]
+    sort_name: Option<String>,
+    disambiguation: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Release {
+    id: String,
+    title: String,
+    date: Option<String>,
+    country: Option<String>,
+    #[serde(rename = "release-group")]
+    release_group: Option<ReleaseGroup>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ReleaseGroup {
+    id: String,
+    #[serde(rename = "primary-type")]
+    primary_type: Option<String>,
+}
+
+fn client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(error_helpers::to_network_error)
+}
+
+/// Wait for the turn of the next request
+async fn throttle() {
+    let wait = {
+        let mut next = NEXT_REQUEST.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let at = next.filter(|at| *at > now).unwrap_or(now);
+        *next = Some(at + REQUEST_INTERVAL);
+        at - now
+    };
+    tokio::time::sleep(wait).await;
+}
+
+/// Quote a value for a MusicBrainz (Lucene) query
+fn phrase(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn find_track(database: &Database, track_id: &str) -> Option<MediaContent> {
+    database
+        .get_tracks_by_options(GetTrackOptions {
+            track: Some(SearchableTrack {
+                _id: Some(track_id.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .ok()?
+        .into_iter()
+        .next()
+}
+
+/// Closest MusicBrainz recording of `track`, `None` when nothing scores
+/// `MIN_SCORE`
+async fn find_recording(cache: Option<&CacheHolder>, track: &MediaContent) -> Result<Option<Recording>> {
+    let Some(title) = track.track.title.as_deref().map(str::trim).filter(|t| !t.is_empty()) else {
+        return Ok(None);
+    };
+    let mut query = format!("recording:{}", phrase(title));
+    let artist = track
+        .artists
+        .as_ref()
+        .and_then(|artists| artists.first())
+        .and_then(|a| a.artist_name.as_deref());
+    if let Some(artist) = artist {
+        query.push_str(&format!(" AND artist:{}", phrase(artist)));
+    }
+    if let Some(album) = track.album.as_ref().and_then(|a| a.album_name.as_deref()) {
+        query.push_str(&format!(" AND release:{}", phrase(album)));
+    }
+
+    let url = reqwest::Url::parse_with_params(
+        &format!("{}/recording", MUSICBRAINZ_URL),
+        &[("query", query.as_str()), ("fmt", "json"), ("limit", "5")],
+    )
+    .map_err(error_helpers::to_parse_error)?;
+    if let Some(cached) = cache.and_then(|c| c.get::<Option<Recording>>(url.as_str()).ok()) {
+        return Ok(cached);
+    }
+
+    throttle().await;
+    let body = client()?
+        .get(url.clone())
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(error_helpers::to_network_error)?
+        .bytes()
+        .await
+        .map_err(error_helpers::to_network_error)?;
+    let search: RecordingSearch = serde_json::from_slice(&body).map_err(error_helpers::to_parse_error)?;
+    let recording = search.recordings.into_iter().find(|r| r.score >= MIN_SCORE);
+    if let Some(cache) = cache {
+        if let Err(e) = cache.set(url.as_str(), &recording, CACHE_SECS) {
+            tracing::debug!("Failed to cache the MusicBrainz lookup: {:?}", e);
+        }
+    }
+    Ok(recording)
+}
+
+/// Release of `recording` on the album of the track, its first one otherwise
+fn pick_release<'a>(recording: &'a Recording, album: Option<&QueryableAlbum>) -> Option<&'a Release> {
+    let album_name = album.and_then(|a| a.album_name.as_deref()).map(str::to_lowercase);
+    recording
+        .releases
+        .iter()
+        .find(|r| Some(r.title.to_lowercase()) == album_name)
+        .or_else(|| recording.releases.first())
+}
+
+/// Front cover of a release from the Cover Art Archive, trying its release
+/// group next. `None` when the archive has none.
+async fn fetch_cover(app: &AppHandle, cache: Option<&CacheHolder>, release: &Release) -> Result<Option<Vec<u8>>> {
+    let size = if network::data_policy(app).high_res_artwork { "front-500" } else { "front-250" };
+    let mut urls = vec![format!("{}/release/{}/{}", COVER_ART_URL, release.id, size)];
+    if let Some(group) = &release.release_group {
+        urls.push(format!("{}/release-group/{}/{}", COVER_ART_URL, group.id, size));
+    }
+    for url in urls {
+        // Only misses are cached, the covers found are in the artwork directory
+        if cache.is_some_and(|c| c.get::<bool>(&url).is_ok()) {
+            continue;
+        }
+        throttle().await;
+        let response = client()?.get(&url).send().await.map_err(error_helpers::to_network_error)?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            if let Some(cache) = cache {
+                let _ = cache.set(&url, &false, CACHE_SECS);
+            }
+            continue;
+        }
+        let bytes = response
+            .error_for_status()
+            .map_err(error_helpers::to_network_error)?
+            .bytes()
+            .await
+            .map_err(error_helpers::to_network_error)?;
+        network::record_usage(app, DataCategory::Artwork, bytes.len() as u64);
+        return Ok(Some(bytes.to_vec()));
+    }
+    Ok(None)
+}
+
+/// Match `track_id` on MusicBrainz, merge what was found into its album and
+/// artists and fetch its cover when it has none
+async fn enrich(app: &AppHandle, track_id: &str) -> Result<EnrichmentResult> {
+    let database = app.state::<Database>();
+    let cache = app.try_state::<CacheHolder>();
+    let cache = cache.as_deref();
+    let Some(track) = find_track(&database, track_id) else {
+        return Err(format!("No track {} in the library", track_id).into());
+    };
+    let mut result = EnrichmentResult { track_id: track_id.to_string(), ..Default::default() };
+
+    let Some(recording) = find_recording(cache, &track).await? else {
+        return Ok(result);
+    };
+    result.recording_id = Some(recording.id.clone());
+
+    for artist in track.artists.iter().flatten() {
+        let (Some(artist_id), Some(name)) = (artist.artist_id.as_deref(), artist.artist_name.as_deref()) else {
+            continue;
+        };
+        let Some(credit) = recording.artist_credit.iter().find(|c| c.name.eq_ignore_ascii_case(name)) else {
+            continue;
+        };
+        let info = json!({ "musicbrainz": {
+            "artistId": credit.artist.id,
+            "sortName": credit.artist.sort_name,
+            "disambiguation": credit.artist.disambiguation,
+        }});
+        database.merge_artist_info(artist_id, EntityInfo(info.to_string()), Some(&credit.artist.id))?;
+    }
+
+    let Some(release) = pick_release(&recording, track.album.as_ref()) else {
+        return Ok(result);
+    };
+    result.release_id = Some(release.id.clone());
+    let album_id = track.album.as_ref().and_then(|a| a.album_id.clone());
+    if let Some(album_id) = &album_id {
+        let info = json!({ "musicbrainz": {
+            "releaseId": release.id,
+            "releaseGroupId": release.release_group.as_ref().map(|g| &g.id),
+            "type": release.release_group.as_ref().and_then(|g| g.primary_type.as_ref()),
+            "date": release.date,
+            "country": release.country,
+        }});
+        let year = release.date.as_deref().and_then(|d| d.get(..4));
+        database.merge_album_info(album_id, EntityInfo(info.to_string()), year)?;
+    }
+
+    if track.track.track_cover_path_high.is_none() {
+        if let Some(image) = fetch_cover(app, cache, release).await? {
+            let artwork_dir = PathBuf::from(crate::scanner::artwork_dir(&app.state::<SettingsConfig>()));
+            let (high, low) = tauri::async_runtime::spawn_blocking(move || file_scanner::store_artwork(&artwork_dir, &image))
+                .await
+                .unwrap_or_else(|e| Err(MusicError::String(e.to_string())))?;
+            database.set_fetched_cover(
+                track_id,
+                album_id.as_deref(),
+                &high.to_string_lossy(),
+                &low.to_string_lossy(),
+            )?;
+            result.cover_fetched = true;
+        }
+    }
+
+    if let Err(e) = app.emit("metadata-enriched", &result) {
+        tracing::warn!("Failed to emit metadata-enriched event: {}", e);
+    }
+    Ok(result)
+}
+
+fn auto_enrich_enabled(app: &AppHandle) -> bool {
+    app.state::<SettingsConfig>()
+        .load_selective::<MusicEnrichmentSettings>("music.enrichment".to_string())
+        .ok()
+        .and_then(|s| s.auto_enrich)
+        .unwrap_or(false)
+}
+
+/// Look up the covers of local tracks without one, in batches in the
+/// background, when automatic enrichment is on. Does nothing when the lookups
+/// already run; stops on metered connections.
+pub fn spawn_auto_enrich(app: &AppHandle) {
+    if !auto_enrich_enabled(app) || RUNNING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // Tracks that found no cover are not looked up again this run
+        let mut done: Vec<String> = Vec::new();
+        let mut seen: HashSet<String> = HashSet::new();
+        while !network::is_metered(&app) && auto_enrich_enabled(&app) {
+            let pending = match app.state::<Database>().get_coverless_track_ids(BATCH, &done) {
+                Ok(ids) => ids,
+                Err(e) => {
+                    tracing::warn!("Failed to list tracks to enrich: {:?}", e);
+                    break;
+                }
+            };
+            if pending.is_empty() {
+                break;
+            }
+            for track_id in pending {
+                if let Err(e) = enrich(&app, &track_id).await {
+                    tracing::debug!("Failed to enrich {}: {:?}", track_id, e);
+                }
+                if seen.insert(track_id.clone()) {
+                    done.push(track_id);
+                }
+            }
+        }
+        RUNNING.store(false, Ordering::Release);
+    });
+}
+
+command_envelope! {
+    /// Look a track up on MusicBrainz: its album and artists get the release
+    /// and artist details, and its cover is fetched when it has none.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command]
+    pub async fn enrich_track(app: AppHandle, track_id: String) -> Result<EnrichmentResult> {
+        enrich(&app, &track_id).await
+    }
+}
+
+command_envelope! {
+    /// Look an album up on MusicBrainz through its tracks, stopping at the
+    /// first one matched. `None` when no track matched.
+    #[tracing::instrument(level = "debug", skip(app, database))]
+    #[tauri::command]
+    pub async fn enrich_album(
+        app: AppHandle,
+        database: State<'_, Database>,
+        album_id: String,
+    ) -> Result<Option<EnrichmentResult>> {
+        let tracks = database.get_tracks_by_options(GetTrackOptions {
+            album: Some(QueryableAlbum { album_id: Some(album_id), ..Default::default() }),
+            ..Default::default()
+        })?;
+        for track_id in tracks.into_iter().filter_map(|t| t.track._id) {
+            let result = enrich(&app, &track_id).await?;
+            if result.release_id.is_some() {
+                return Ok(Some(result));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,209 @@
+//! Search across all enabled media providers with the results merged into one
+//! list. The same track, album or artist returned by several providers is
+//! kept once, tagged with the provider it came from first and the others that
+//! have it too.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use futures::stream::{FuturesUnordered, StreamExt};
+use macros::command_envelope;
+use music_plugin_sdk::types::{Album, Artist, Playlist, SearchQuery, SearchResult, Track};
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Emitter, State};
+use tokio::time::Duration;
+use types::errors::{MusicError, Result as MusicResult};
+use types::settings::music::MusicSourceSelection;
+
+use super::commands::{search_provider, ProviderSearchOutcome, SearchProviderError, DEFAULT_SEARCH_BUDGET};
+use crate::plugins::manager::PluginHandler;
+
+/// Largest difference between the durations of two tracks considered the same
+pub const DURATION_TOLERANCE_MS: u32 = 3_000;
+
+/// The same item as returned by another provider
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateSource {
+    pub plugin_id: String,
+    /// Provider-scoped ID of the item
+    pub id: String,
+}
+
+/// Search result item tagged with its provider
+#[derive(Debug, Clone, Serialize)]
+pub struct AggregatedItem<T> {
+    /// Plugin ID of the provider the item comes from
+    pub plugin_id: String,
+    /// Other providers that returned the same item, in the order they answered
+    pub also_on: Vec<DuplicateSource>,
+    pub item: T,
+}
+
+/// Merged results of all providers, in the order the providers answered
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AggregatedSearchResult {
+    pub tracks: Vec<AggregatedItem<Track>>,
+    pub albums: Vec<AggregatedItem<Album>>,
+    pub artists: Vec<AggregatedItem<Artist>>,
+    /// Playlists are never merged
+    pub playlists: Vec<AggregatedItem<Playlist>>,
+    pub suggestions: Vec<String>,
+    /// Outcome of each provider that has answered or timed out so far
+    pub providers: HashMap<String, ProviderSearchOutcome>,
+}
+
+impl AggregatedSearchResult {
+    fn add(&mut self, plugin_id: &str, result: SearchResult) {
+        merge_items(&mut self.tracks, plugin_id, result.tracks.items, |t| &t.id, same_track);
+        merge_items(&mut self.albums, plugin_id, result.albums.items, |a| &a.id, same_album);
+        merge_items(&mut self.artists, plugin_id, result.artists.items, |a| &a.id, same_artist);
+        self.playlists.extend(result.playlists.items.into_iter().map(|item| AggregatedItem {
+            plugin_id: plugin_id.to_string(),
+            also_on: Vec::new(),
+            item,
+        }));
+        for suggestion in result.suggestions.unwrap_or_default() {
+            if !self.suggestions.contains(&suggestion) {
+                self.suggestions.push(suggestion);
+            }
+        }
+    }
+}
+
+/// Add the items of one provider, folding those another provider already
+/// returned into the existing entry. Items of the same provider are never
+/// merged with each other.
+fn merge_items<T>(
+    merged: &mut Vec<AggregatedItem<T>>,
+    plugin_id: &str,
+    items: Vec<T>,
+    id: fn(&T) -> &String,
+    same: fn(&T, &T) -> bool,
+) {
+    let known = merged.len();
+    for item in items {
+        let existing = merged[..known]
+            .iter_mut()
+            .find(|m| !m.also_on.iter().any(|d| d.plugin_id == plugin_id) && same(&m.item, &item));
+        match existing {
+            Some(existing) => existing.also_on.push(DuplicateSource {
+                plugin_id: plugin_id.to_string(),
+                id: id(&item).clone(),
+            }),
+            None => merged.push(AggregatedItem {
+                plugin_id: plugin_id.to_string(),
+                also_on: Vec::new(),
+                item,
+            }),
+        }
+    }
+}
+
+/// Lowercase letters and digits only, so punctuation and spacing differences
+/// between providers do not matter
+fn normalize(text: &str) -> String {
+    text.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+fn same_name(a: &str, b: &str) -> bool {
+    let a = normalize(a);
+    !a.is_empty() && a == normalize(b)
+}
+
+/// Same ISRC when both have one, else same title and artist with durations
+/// within `DURATION_TOLERANCE_MS` (when both are known)
+fn same_track(a: &Track, b: &Track) -> bool {
+    let isrc = |t: &Track| t.isrc.clone().filter(|isrc| !isrc.trim().is_empty());
+    if let (Some(x), Some(y)) = (isrc(a), isrc(b)) {
+        return x.trim().eq_ignore_ascii_case(y.trim());
+    }
+    let durations_match = match (a.duration, b.duration) {
+        (Some(x), Some(y)) => x.abs_diff(y) <= DURATION_TOLERANCE_MS,
+        _ => true,
+    };
+    durations_match && same_name(&a.title, &b.title) && same_name(&a.artist, &b.artist)
+}
+
+fn same_album(a: &Album, b: &Album) -> bool {
+    same_name(&a.title, &b.title) && same_name(&a.artist, &b.artist)
+}
+
+fn same_artist(a: &Artist, b: &Artist) -> bool {
+    match (&a.mbid, &b.mbid) {
+        (Some(x), Some(y)) => x == y,
+        _ => same_name(&a.name, &b.name),
+    }
+}
+
+command_envelope! {
+    /// Search all enabled providers in parallel and merge their results. Each
+    /// time a provider answers, the merged results so far are emitted as
+    /// `aggregate-search-partial` `{search_id, provider, elapsed_ms, result}`.
+    /// A provider exceeding its budget (`budgets_ms` by plugin id, else
+    /// `default_budget_ms`, else 5s) is abandoned. Returns the final merged
+    /// results once every provider has answered or timed out. `search_id` is
+    /// chosen by the caller so it can subscribe before invoking.
+    #[tracing::instrument(level = "debug", skip(app, plugin_handler, search_query, budgets_ms))]
+    #[tauri::command]
+    pub async fn aggregate_search(
+        app: AppHandle,
+        plugin_handler: State<'_, PluginHandler>,
+        search_id: String,
+        search_query: SearchQuery,
+        default_budget_ms: Option<u64>,
+        budgets_ms: Option<HashMap<String, u64>>,
+    ) -> MusicResult<AggregatedSearchResult> {
+        let providers = plugin_handler
+            .plugin_manager()
+            .get_audio_providers_by_selection(&MusicSourceSelection::default())
+            .await
+            .map_err(|e| MusicError::String(format!("Failed to get audio providers: {}", e)))?;
+
+        let default_budget = default_budget_ms.map(Duration::from_millis).unwrap_or(DEFAULT_SEARCH_BUDGET);
+        let budgets = budgets_ms.unwrap_or_default();
+        let started = Instant::now();
+
+        let mut pending: FuturesUnordered<_> = providers
+            .into_iter()
+            .map(|(provider_id, provider_plugin)| {
+                let query = search_query.clone();
+                let budget = budgets
+                    .get(&provider_id.to_string())
+                    .copied()
+                    .map(Duration::from_millis)
+                    .unwrap_or(default_budget);
+                async move {
+                    let result = search_provider(provider_id, provider_plugin, query, budget).await;
+                    (provider_id.to_string(), result)
+                }
+            })
+            .collect();
+
+        let mut aggregated = AggregatedSearchResult::default();
+        while let Some((plugin_id, result)) = pending.next().await {
+            let outcome = match result {
+                Ok(result) => {
+                    aggregated.add(&plugin_id, result);
+                    ProviderSearchOutcome::Completed
+                }
+                Err(SearchProviderError::TimedOut) => ProviderSearchOutcome::TimedOut,
+                Err(SearchProviderError::Failed(e)) => {
+                    tracing::warn!("{}", e);
+                    ProviderSearchOutcome::Failed
+                }
+            };
+            aggregated.providers.insert(plugin_id.clone(), outcome);
+            let _ = app.emit(
+                "aggregate-search-partial",
+                json!({
+                    "search_id": search_id,
+                    "provider": plugin_id,
+                    "elapsed_ms": started.elapsed().as_millis() as u64,
+                    "result": aggregated,
+                }),
+            );
+        }
+        Ok(aggregated)
+    }
+}
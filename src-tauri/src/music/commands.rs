@@ -1,52 +1,191 @@
-use tauri::{State, AppHandle};
+use std::collections::HashMap;
+use std::time::Instant;
+
+use macros::command_envelope;
+use serde_json::json;
+use tauri::{Emitter, State, AppHandle};
 use tokio::time::{timeout, Duration};
 use uuid::Uuid;
 use crate::plugins::manager::PluginHandler;
 use types::settings::music::MusicSourceSelection;
 use music_plugin_sdk::types::{SearchResult, Track as SdkTrack, Album as SdkAlbum, Artist as SdkArtist, Playlist as SdkPlaylist, PageInfo as SdkPageInfo};
 use music_plugin_sdk::types::media::Genre as SdkGenre;
+use music_plugin_sdk::types::RecommendationSeed;
 use serde::{Serialize, Deserialize};
+use types::errors::{MusicError, Result as MusicResult};
+use types::settings::music::MusicSourceMode;
 use types::tracks::MediaContent;
 
-#[tauri::command]
-pub async fn music_search(
-    _app: AppHandle,
-    plugin_handler: State<'_, PluginHandler>,
-    search_query: music_plugin_sdk::types::SearchQuery,
-    selector: Option<serde_json::Value>,
-) -> Result<SearchResult, String> {
-    // Parse music source selection
-    let selection = parse_music_source_selection(selector)?;
+command_envelope! {
+    #[tauri::command]
+    pub async fn music_search(
+        _app: AppHandle,
+        plugin_handler: State<'_, PluginHandler>,
+        search_query: music_plugin_sdk::types::SearchQuery,
+        selector: Option<serde_json::Value>,
+    ) -> Result<SearchResult, String> {
+        // Parse music source selection
+        let selection = parse_music_source_selection(selector)?;
     
-    // Get audio providers
-    let plugin_manager = plugin_handler.plugin_manager();
-    let audio_providers = plugin_manager
-        .get_audio_providers_by_selection(&selection)
-        .await
-        .map_err(|e| format!("Failed to get audio providers: {}", e))?;
+        // Get audio providers
+        let plugin_manager = plugin_handler.plugin_manager();
+        let audio_providers = plugin_manager
+            .get_audio_providers_by_selection(&selection)
+            .await
+            .map_err(|e| format!("Failed to get audio providers: {}", e))?;
     
-    if audio_providers.is_empty() {
-        return Ok(SearchResult::default());
-    }
+        if audio_providers.is_empty() {
+            return Ok(SearchResult::default());
+        }
     
-    println!("Searching '{}' across {} providers", search_query.query, audio_providers.len());
+        println!("Searching '{}' across {} providers", search_query.query, audio_providers.len());
     
-    // Search all providers concurrently
-    let search_tasks = audio_providers.into_iter().map(|(provider_id, provider_plugin)| {
-        search_provider(provider_id, provider_plugin, search_query.clone())
-    });
+        // Search all providers concurrently
+        let search_tasks = audio_providers.into_iter().map(|(provider_id, provider_plugin)| {
+            let query = search_query.clone();
+            async move {
+                search_provider(provider_id, provider_plugin, query, DEFAULT_SEARCH_BUDGET)
+                    .await
+                    .map_err(|e| format!("Provider {}: {}", provider_id, e))
+            }
+        });
     
-    let results = futures::future::join_all(search_tasks).await;
+        let results = futures::future::join_all(search_tasks).await;
     
-    // Merge results
-    let merged_result = merge_search_results(results);
+        // Merge results
+        let merged_result = merge_search_results(results);
     
-    println!("Search completed: {} tracks, {} albums, {} artists", 
-             merged_result.tracks.items.len(), 
-             merged_result.albums.items.len(), 
-             merged_result.artists.items.len());
+        println!("Search completed: {} tracks, {} albums, {} artists", 
+                 merged_result.tracks.items.len(), 
+                 merged_result.albums.items.len(), 
+                 merged_result.artists.items.len());
     
-    Ok(merged_result)
+        Ok(merged_result)
+    }
+}
+
+command_envelope! {
+    /// Recommended tracks from one media provider for `seed`, most relevant first.
+    #[tracing::instrument(level = "debug", skip(plugin_handler, seed))]
+    #[tauri::command]
+    pub async fn music_get_recommendations(
+        plugin_handler: State<'_, PluginHandler>,
+        provider_id: String,
+        seed: RecommendationSeed,
+    ) -> MusicResult<Vec<SdkTrack>> {
+        let selection = MusicSourceSelection {
+            mode: MusicSourceMode::Single,
+            ids: vec![provider_id.clone()],
+        };
+        let (_, provider) = plugin_handler
+            .plugin_manager()
+            .get_audio_providers_by_selection(&selection)
+            .await
+            .map_err(|e| MusicError::String(format!("Failed to get audio providers: {}", e)))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| MusicError::String(format!("Provider {} is not available", provider_id)))?;
+
+        let plugin = provider.lock().await;
+        match timeout(Duration::from_secs(10), plugin.get_recommendations(&seed)).await {
+            Ok(res) => res.map_err(|e| MusicError::String(format!("Provider {} recommendations failed: {}", provider_id, e))),
+            Err(_) => Err(MusicError::String(format!("Provider {} recommendations timeout", provider_id))),
+        }
+    }
+}
+
+/// Latency budget of a provider in a streamed search unless overridden
+pub(crate) const DEFAULT_SEARCH_BUDGET: Duration = Duration::from_secs(5);
+
+/// Outcome of one provider in a streamed search, reported in `search-results-complete`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderSearchOutcome {
+    Completed,
+    TimedOut,
+    Failed,
+}
+
+command_envelope! {
+    /// Search all selected providers in parallel and stream their results.
+    /// Each provider's results are emitted as `search-results-partial`
+    /// `{search_id, provider, elapsed_ms, result}` as soon as they arrive; a
+    /// provider exceeding its latency budget (`budgets_ms` by plugin id, else
+    /// `default_budget_ms`, else 5s) is abandoned. `search-results-complete`
+    /// `{search_id, providers: {id: outcome}}` follows once every provider has
+    /// answered or timed out. `search_id` is chosen by the caller so it can
+    /// subscribe before invoking.
+    #[tracing::instrument(level = "debug", skip(app, plugin_handler, search_query, selector, budgets_ms))]
+    #[tauri::command]
+    pub async fn music_search_streamed(
+        app: AppHandle,
+        plugin_handler: State<'_, PluginHandler>,
+        search_id: String,
+        search_query: music_plugin_sdk::types::SearchQuery,
+        selector: Option<serde_json::Value>,
+        default_budget_ms: Option<u64>,
+        budgets_ms: Option<HashMap<String, u64>>,
+    ) -> MusicResult<()> {
+        let selection = parse_music_source_selection(selector).map_err(MusicError::String)?;
+        let audio_providers = plugin_handler
+            .plugin_manager()
+            .get_audio_providers_by_selection(&selection)
+            .await
+            .map_err(|e| MusicError::String(format!("Failed to get audio providers: {}", e)))?;
+
+        let default_budget = default_budget_ms.map(Duration::from_millis).unwrap_or(DEFAULT_SEARCH_BUDGET);
+        let budgets = budgets_ms.unwrap_or_default();
+        let started = Instant::now();
+
+        let tasks: Vec<_> = audio_providers.into_iter().map(|(provider_id, provider_plugin)| {
+            let app = app.clone();
+            let search_id = search_id.clone();
+            let query = search_query.clone();
+            let budget = budgets
+                .get(&provider_id.to_string())
+                .copied()
+                .map(Duration::from_millis)
+                .unwrap_or(default_budget);
+            async move {
+                let outcome = match search_provider(provider_id, provider_plugin, query, budget).await {
+                    Ok(result) => {
+                        let _ = app.emit(
+                            "search-results-partial",
+                            json!({
+                                "search_id": search_id,
+                                "provider": provider_id.to_string(),
+                                "elapsed_ms": started.elapsed().as_millis() as u64,
+                                "result": result,
+                            }),
+                        );
+                        ProviderSearchOutcome::Completed
+                    }
+                    Err(SearchProviderError::TimedOut) => ProviderSearchOutcome::TimedOut,
+                    Err(SearchProviderError::Failed(e)) => {
+                        tracing::warn!("{}", e);
+                        ProviderSearchOutcome::Failed
+                    }
+                };
+                (provider_id.to_string(), outcome)
+            }
+        }).collect();
+
+        // Report completion in the background so the command returns right away
+        let app_for_complete = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let outcomes: HashMap<String, ProviderSearchOutcome> =
+                futures::future::join_all(tasks).await.into_iter().collect();
+            let _ = app_for_complete.emit(
+                "search-results-complete",
+                json!({
+                    "search_id": search_id,
+                    "elapsed_ms": started.elapsed().as_millis() as u64,
+                    "providers": outcomes,
+                }),
+            );
+        });
+        Ok(())
+    }
 }
 
 /// Parse music source selection from frontend
@@ -60,17 +199,35 @@ fn parse_music_source_selection(selector: Option<serde_json::Value>) -> Result<M
 
 
 
-/// Search a single provider
-async fn search_provider(
+pub(crate) enum SearchProviderError {
+    TimedOut,
+    Failed(String),
+}
+
+impl std::fmt::Display for SearchProviderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchProviderError::TimedOut => write!(f, "search timeout"),
+            SearchProviderError::Failed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Search a single provider within `budget`
+pub(crate) async fn search_provider(
     provider_id: Uuid,
     provider_plugin: std::sync::Arc<tokio::sync::Mutex<dyn music_plugin_sdk::traits::MediaPlugin + Send + Sync>>,
     search_query: music_plugin_sdk::types::SearchQuery,
-) -> Result<music_plugin_sdk::types::SearchResult, String> {
-    let plugin_guard = provider_plugin.lock().await;
-    // 防止某个提供者长时间无响应导致整体卡死
-    match timeout(Duration::from_secs(5), plugin_guard.search(&search_query)).await {
-        Ok(res) => res.map_err(|e| format!("Provider {} search failed: {}", provider_id, e)),
-        Err(_) => Err(format!("Provider {} search timeout", provider_id)),
+    budget: Duration,
+) -> Result<music_plugin_sdk::types::SearchResult, SearchProviderError> {
+    // 防止某个提供者长时间无响应导致整体卡死；等待锁也计入预算
+    let search = async {
+        let plugin_guard = provider_plugin.lock().await;
+        plugin_guard.search(&search_query).await
+    };
+    match timeout(budget, search).await {
+        Ok(res) => res.map_err(|e| SearchProviderError::Failed(format!("Provider {} search failed: {}", provider_id, e))),
+        Err(_) => Err(SearchProviderError::TimedOut),
     }
 }
 
@@ -1,4 +1,6 @@
+pub mod aggregate;
 pub mod commands;
 
+pub use aggregate::*;
 pub use commands::*;
 
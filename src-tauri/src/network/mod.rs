@@ -0,0 +1,274 @@
+//! Data saving on cellular connections. The frontend reports the connection
+//! class (Wi-Fi, ethernet, cellular); while it is cellular the rules of
+//! `prefs.music.mobileData` cap the stream quality, hold back downloads and
+//! stream pre-fetching, and skip full-size artwork. Data fetched during the
+//! session is counted per connection class.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use macros::command_envelope;
+use serde::{Deserialize, Serialize};
+use ::settings::settings::SettingsConfig;
+use tauri::{AppHandle, Emitter, Manager, State};
+use types::errors::Result;
+use types::settings::music::{CellularStreamQuality, MobileDataSettings};
+
+use crate::downloads::DownloadQueue;
+
+/// Connection class reported by the frontend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum NetworkClass {
+    #[default]
+    Unknown,
+    Wifi,
+    Ethernet,
+    Cellular,
+}
+
+/// What the fetched data was used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataCategory {
+    Streaming,
+    Downloads,
+    Artwork,
+}
+
+/// Bytes fetched per category
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataUsageCounts {
+    pub streaming: u64,
+    pub downloads: u64,
+    pub artwork: u64,
+}
+
+impl DataUsageCounts {
+    fn add(&mut self, category: DataCategory, bytes: u64) {
+        let counter = match category {
+            DataCategory::Streaming => &mut self.streaming,
+            DataCategory::Downloads => &mut self.downloads,
+            DataCategory::Artwork => &mut self.artwork,
+        };
+        *counter += bytes;
+    }
+}
+
+/// Data used since the session started or the counter was reset, returned by
+/// `get_data_usage`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataUsageReport {
+    pub network_class: NetworkClass,
+    pub since: chrono::NaiveDateTime,
+    pub cellular: DataUsageCounts,
+    /// Wi-Fi, ethernet and unknown connections
+    pub other: DataUsageCounts,
+    pub policy: DataPolicy,
+}
+
+/// Restrictions in force for the current connection
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataPolicy {
+    pub restricted: bool,
+    /// Stream quality cap, `None` when unrestricted
+    pub stream_quality: Option<CellularStreamQuality>,
+    pub allow_downloads: bool,
+    pub allow_prefetch: bool,
+    pub high_res_artwork: bool,
+}
+
+impl DataPolicy {
+    const UNRESTRICTED: DataPolicy = DataPolicy {
+        restricted: false,
+        stream_quality: None,
+        allow_downloads: true,
+        allow_prefetch: true,
+        high_res_artwork: true,
+    };
+
+    fn cellular(settings: &MobileDataSettings) -> Self {
+        if !settings.enabled.unwrap_or(true) {
+            return Self::UNRESTRICTED;
+        }
+        let quality = settings.stream_quality.unwrap_or_default();
+        Self {
+            restricted: true,
+            stream_quality: (quality != CellularStreamQuality::Unrestricted).then_some(quality),
+            allow_downloads: settings.allow_downloads.unwrap_or(false),
+            allow_prefetch: settings.allow_prefetch.unwrap_or(false),
+            high_res_artwork: settings.high_res_artwork.unwrap_or(false),
+        }
+    }
+}
+
+struct Usage {
+    since: chrono::NaiveDateTime,
+    cellular: DataUsageCounts,
+    other: DataUsageCounts,
+}
+
+impl Usage {
+    fn new() -> Self {
+        Self {
+            since: chrono::Utc::now().naive_utc(),
+            cellular: DataUsageCounts::default(),
+            other: DataUsageCounts::default(),
+        }
+    }
+
+    fn add(&mut self, class: NetworkClass, category: DataCategory, bytes: u64) {
+        let counts = if class == NetworkClass::Cellular { &mut self.cellular } else { &mut self.other };
+        counts.add(category, bytes);
+    }
+}
+
+/// Connection class and session data usage, managed by Tauri. Not persisted.
+pub struct NetworkState {
+    class: Mutex<NetworkClass>,
+    metered: AtomicBool,
+    usage: Mutex<Usage>,
+}
+
+impl Default for NetworkState {
+    fn default() -> Self {
+        Self {
+            class: Mutex::new(NetworkClass::default()),
+            metered: AtomicBool::new(false),
+            usage: Mutex::new(Usage::new()),
+        }
+    }
+}
+
+impl NetworkState {
+    pub fn class(&self) -> NetworkClass {
+        self.class.lock().map(|c| *c).unwrap_or_default()
+    }
+
+    pub fn is_cellular(&self) -> bool {
+        self.class() == NetworkClass::Cellular
+    }
+
+    /// Whether the connection is metered, as last reported
+    pub fn is_metered(&self) -> bool {
+        self.metered.load(Ordering::Relaxed)
+    }
+
+    /// Count `bytes` fetched for `category` on the current connection
+    pub fn record(&self, category: DataCategory, bytes: u64) {
+        let class = self.class();
+        if let Ok(mut usage) = self.usage.lock() {
+            usage.add(class, category, bytes);
+        }
+    }
+
+    /// Collect the bytes the player streamed since the last collection. They
+    /// are attributed to the current connection, so this runs before it changes.
+    fn collect_streamed(&self) {
+        let bytes = audio_player::data_usage::take_streamed_bytes();
+        if bytes > 0 {
+            self.record(DataCategory::Streaming, bytes);
+        }
+    }
+
+    fn set_class(&self, class: NetworkClass) -> bool {
+        self.collect_streamed();
+        let Ok(mut current) = self.class.lock() else {
+            return false;
+        };
+        std::mem::replace(&mut *current, class) != class
+    }
+
+    fn report(&self, policy: DataPolicy) -> DataUsageReport {
+        self.collect_streamed();
+        let class = self.class();
+        let usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        DataUsageReport {
+            network_class: class,
+            since: usage.since,
+            cellular: usage.cellular,
+            other: usage.other,
+            policy,
+        }
+    }
+
+    fn reset(&self) {
+        audio_player::data_usage::take_streamed_bytes();
+        if let Ok(mut usage) = self.usage.lock() {
+            *usage = Usage::new();
+        }
+    }
+}
+
+/// Restrictions in force for the current connection; unrestricted before the
+/// network state is managed
+pub fn data_policy(app: &AppHandle) -> DataPolicy {
+    let cellular = app.try_state::<NetworkState>().is_some_and(|n| n.is_cellular());
+    if !cellular {
+        return DataPolicy::UNRESTRICTED;
+    }
+    let settings = app
+        .state::<SettingsConfig>()
+        .load_selective::<MobileDataSettings>("music.mobileData".to_string())
+        .unwrap_or_default();
+    DataPolicy::cellular(&settings)
+}
+
+/// Whether the connection is metered; unmetered before the network state is managed
+pub fn is_metered(app: &AppHandle) -> bool {
+    app.try_state::<NetworkState>().is_some_and(|n| n.is_metered())
+}
+
+/// Count `bytes` fetched for `category`
+pub fn record_usage(app: &AppHandle, category: DataCategory, bytes: u64) {
+    if let Some(network) = app.try_state::<NetworkState>() {
+        network.record(category, bytes);
+    }
+}
+
+/// Send the restrictions in force to the frontend as `network-policy-changed`
+/// and let waiting downloads re-check them
+pub fn notify_policy_changed(app: &AppHandle) {
+    let _ = app.emit("network-policy-changed", data_policy(app));
+    app.state::<DownloadQueue>().wake();
+}
+
+command_envelope! {
+    /// Report the connection class. `metered` (default: cellular) is passed on
+    /// to the download queue's Wi-Fi only rule. Returns the restrictions now in force.
+    #[tracing::instrument(level = "debug", skip(app, network, queue))]
+    #[tauri::command]
+    pub fn set_network_class(
+        app: AppHandle,
+        network: State<'_, NetworkState>,
+        queue: State<'_, DownloadQueue>,
+        network_class: NetworkClass,
+        metered: Option<bool>,
+    ) -> Result<DataPolicy> {
+        let changed = network.set_class(network_class);
+        let metered = metered.unwrap_or(network_class == NetworkClass::Cellular);
+        network.metered.store(metered, Ordering::Relaxed);
+        queue.set_metered(metered);
+        if changed {
+            tracing::info!("Network class changed to {:?}", network_class);
+            notify_policy_changed(&app);
+        }
+        Ok(data_policy(&app))
+    }
+}
+
+command_envelope! {
+    /// Data fetched in this session per connection class, with the
+    /// restrictions in force. `reset` starts a new count afterwards.
+    #[tracing::instrument(level = "debug", skip(app, network))]
+    #[tauri::command]
+    pub fn get_data_usage(app: AppHandle, network: State<'_, NetworkState>, reset: Option<bool>) -> Result<DataUsageReport> {
+        let report = network.report(data_policy(&app));
+        if reset.unwrap_or(false) {
+            network.reset();
+        }
+        Ok(report)
+    }
+}
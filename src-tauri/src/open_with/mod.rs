@@ -0,0 +1,180 @@
+//! Audio files and folders handed to the app by the OS: "Open with", the
+//! Explorer context menu verbs registered by the installer and files dropped
+//! on the app icon. A second launch forwards its arguments to the running
+//! instance, so the paths always end up in the one player.
+//!
+//! Files already in the library are queued as they are; any other file is
+//! scanned on the spot and queued without being imported.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use database::database::Database;
+use serde_json::json;
+use settings::settings::SettingsConfig;
+use tauri::{AppHandle, Manager};
+use types::settings::queue::QueueAction;
+use types::tracks::{GetTrackOptions, MediaContent, SearchableTrack};
+
+/// Argument of the "Add to Music queue" verb; without it the paths are played
+pub const ENQUEUE_FLAG: &str = "--enqueue";
+
+/// Explorer starts one process per selected item; paths arriving within this
+/// window are handled as one selection
+const BATCH_WINDOW: Duration = Duration::from_millis(400);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenAction {
+    /// Play the first track now, the others right after it
+    Play,
+    /// Append to the end of the queue
+    Enqueue,
+}
+
+/// Paths waiting for the end of the batch window
+static PENDING: Mutex<Vec<(OpenAction, PathBuf)>> = Mutex::new(Vec::new());
+
+/// The action and existing paths of a command line. Relative paths are taken
+/// from `cwd`, the directory of the launching process.
+pub fn parse_args(args: &[String], cwd: &Path) -> (OpenAction, Vec<PathBuf>) {
+    let mut action = OpenAction::Play;
+    let mut paths = Vec::new();
+    // The first argument is the executable
+    for arg in args.iter().skip(1) {
+        if arg == ENQUEUE_FLAG {
+            action = OpenAction::Enqueue;
+        } else if !arg.starts_with('-') {
+            let path = cwd.join(arg);
+            if path.exists() {
+                paths.push(path);
+            }
+        }
+    }
+    (action, paths)
+}
+
+/// Handle the command line of this or a later launch
+pub fn handle_args(app: &AppHandle, args: Vec<String>, cwd: &Path) {
+    let (action, paths) = parse_args(&args, cwd);
+    if !paths.is_empty() {
+        open_paths(app, action, paths);
+    }
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Play or queue `paths` once the batch window has passed
+pub fn open_paths(app: &AppHandle, action: OpenAction, paths: Vec<PathBuf>) {
+    let Ok(mut pending) = PENDING.lock() else { return };
+    let first = pending.is_empty();
+    pending.extend(paths.into_iter().map(|path| (action, path)));
+    if !first {
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(BATCH_WINDOW);
+        let batch = std::mem::take(&mut *PENDING.lock().unwrap());
+        // Play when any of the batch asked for it
+        let action = if batch.iter().any(|(a, _)| *a == OpenAction::Play) {
+            OpenAction::Play
+        } else {
+            OpenAction::Enqueue
+        };
+        let paths: Vec<PathBuf> = batch.into_iter().map(|(_, path)| path).collect();
+        let tracks = load_tracks(&app, &paths);
+        if tracks.is_empty() {
+            tracing::info!("No playable audio in {:?}", paths);
+            return;
+        }
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = queue_tracks(&app, action, tracks).await {
+                tracing::warn!("Failed to queue opened files: {:?}", e);
+            }
+        });
+    });
+}
+
+/// Tracks of the files and of the audio files below the folders, folders in
+/// path order
+fn load_tracks(app: &AppHandle, paths: &[PathBuf]) -> Vec<MediaContent> {
+    let settings = app.state::<SettingsConfig>();
+    let artwork_dir = crate::scanner::artwork_dir(&settings);
+    let artist_split: String = settings
+        .load_selective("artist_splitter".to_string())
+        .unwrap_or(";".to_string());
+    let database = app.state::<Database>();
+
+    let mut files: Vec<(PathBuf, f64)> = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            match file_scanner::get_files_recursively(path.clone()) {
+                Ok(list) => {
+                    let mut list = list.file_list;
+                    list.sort_by(|a, b| a.0.cmp(&b.0));
+                    files.extend(list);
+                }
+                Err(e) => tracing::warn!("Failed to list {}: {:?}", path.display(), e),
+            }
+        } else if file_scanner::audio_extension(path).is_some() {
+            let size = std::fs::metadata(path).map(|m| m.len() as f64).unwrap_or(0.0);
+            files.push((path.clone(), size));
+        }
+    }
+
+    files
+        .into_iter()
+        .filter_map(|(path, size)| load_track(&database, Path::new(&artwork_dir), &artist_split, &path, size))
+        .collect()
+}
+
+/// The library track of `path`, else the file scanned without storing it.
+/// Unimported tracks are identified by their content hash, the ID they get
+/// once imported.
+fn load_track(database: &Database, artwork_dir: &Path, artist_split: &str, path: &Path, size: f64) -> Option<MediaContent> {
+    let canonical = dunce::canonicalize(path).ok()?.to_string_lossy().to_string();
+    let known = database
+        .get_tracks_by_options(GetTrackOptions {
+            track: Some(SearchableTrack {
+                path: Some(canonical),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .ok()
+        .and_then(|tracks| tracks.into_iter().next());
+    if known.is_some() {
+        return known;
+    }
+
+    let path = path.to_path_buf();
+    let scanned = file_scanner::scan_file(&path, artwork_dir, size, false, artist_split)
+        .or_else(|_| file_scanner::scan_file(&path, artwork_dir, size, true, artist_split));
+    match scanned {
+        Ok(mut track) => {
+            track.track._id = track.track.hash.clone();
+            track.track._id.is_some().then_some(track)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to read {}: {:?}", path.display(), e);
+            None
+        }
+    }
+}
+
+async fn queue_tracks(app: &AppHandle, action: OpenAction, tracks: Vec<MediaContent>) -> types::errors::Result<()> {
+    let count = tracks.len();
+    let queue_action = match action {
+        OpenAction::Play => QueueAction::PlayNow,
+        OpenAction::Enqueue => QueueAction::Append,
+    };
+    crate::audio::actions::apply_queue_action(app, queue_action, tracks).await?;
+    let _ = crate::windowing::emit_audio_event(
+        app,
+        json!({ "type": "ExternalFilesOpened", "data": { "count": count, "play": action == OpenAction::Play } }),
+    );
+    Ok(())
+}
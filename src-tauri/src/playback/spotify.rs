@@ -10,6 +10,8 @@ pub(crate) struct SpotifySessionState {
     pub initialized: bool,
     pub logged_in: bool,
     pub user_name: Option<String>,
+    /// Web API access token of the account signed in through the plugin
+    pub access_token: Option<String>,
     pub volume: f32, // linear [0.0, 1.0]
 }
 
@@ -40,6 +42,15 @@ pub(crate) fn spotify_login(app: AppHandle, account_id: String) -> Result<String
     Ok(account_id)
 }
 
+/// Follow the account of the Spotify plugin; `None` when signed out
+#[tracing::instrument(level = "debug", skip(app, access_token))]
+pub(crate) fn spotify_set_access_token(app: AppHandle, access_token: Option<String>) {
+    let state = get_spotify_state(app);
+    let mut s = state.lock().unwrap_or_else(|e| e.into_inner());
+    s.logged_in = access_token.is_some();
+    s.access_token = access_token;
+}
+
 #[tracing::instrument(level = "debug", skip(app, _code))]
 pub(crate) fn spotify_authorize(app: AppHandle, _code: String) -> Result<()> {
     let state: State<'_, Arc<Mutex<SpotifySessionState>>> = app.state();
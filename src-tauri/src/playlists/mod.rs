@@ -0,0 +1,47 @@
+pub mod sync;
+
+use database::database::Database;
+use macros::command_envelope;
+use tauri::State;
+use types::entities::{PlaylistInsights, PlaylistRestore, PlaylistVersion};
+use types::errors::Result;
+
+command_envelope! {
+    /// Statistics and health report for a playlist (duration, distributions,
+    /// unavailable tracks, duplicates).
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn get_playlist_insights(
+        database: State<'_, Database>,
+        playlist_id: String,
+    ) -> Result<PlaylistInsights> {
+        database.get_playlist_insights(playlist_id)
+    }
+}
+
+command_envelope! {
+    /// Membership snapshots of a playlist, newest first. A snapshot is kept
+    /// for every modification, up to a bounded number of versions.
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn get_playlist_history(
+        database: State<'_, Database>,
+        playlist_id: String,
+    ) -> Result<Vec<PlaylistVersion>> {
+        database.get_playlist_history(&playlist_id)
+    }
+}
+
+command_envelope! {
+    /// Revert a playlist to a snapshot from `get_playlist_history`. The
+    /// membership replaced is kept as a version of its own.
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn restore_playlist_version(
+        database: State<'_, Database>,
+        playlist_id: String,
+        version: i64,
+    ) -> Result<PlaylistRestore> {
+        database.restore_playlist_version(&playlist_id, version)
+    }
+}
@@ -0,0 +1,435 @@
+//! Two-way sync of local playlists linked to a playlist of an online provider.
+//! Each sync compares both sides with the tracks they shared after the
+//! previous one (the base), so the changes of each side are told apart:
+//! changes made at the provider are pulled, local changes are pushed through
+//! `MediaPlaylistWritePlugin` when the provider has it and the playlist may be
+//! changed. Local changes that can't be pushed are recorded as conflicts for
+//! the user to settle. Linked playlists sync in the background on the
+//! `music.playlistSync` interval; every sync is announced with
+//! `playlist-sync-finished`.
+//!
+//! Providers may list only the first tracks of a playlist; the tracks past
+//! them are neither pulled nor taken as removed at the provider.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::Duration;
+
+use ::settings::settings::SettingsConfig;
+use database::database::Database;
+use macros::command_envelope;
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::time::timeout;
+use types::entities::{PlaylistSyncConflict, PlaylistSyncLink, PlaylistSyncReport};
+use types::errors::{MusicError, Result};
+use types::settings::music::{MusicPlaylistSyncSettings, MusicSourceMode, MusicSourceSelection};
+use types::tracks::{MediaContent, Tracks};
+
+use crate::audio::radio::to_media_content;
+use crate::plugins::manager::PluginHandler;
+
+const PROVIDER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Background sync interval when unset, and the shortest allowed
+const DEFAULT_INTERVAL_MINUTES: u32 = 30;
+const MIN_INTERVAL_MINUTES: u32 = 5;
+
+/// Added locally, the provider did not take it
+const LOCAL_ADDITION: &str = "local_addition";
+/// Removed locally, the provider playlist can't be changed
+const LOCAL_REMOVAL: &str = "local_removal";
+
+/// Managed by Tauri
+#[derive(Default)]
+pub struct PlaylistSyncState {
+    /// Held during a sync, so the timer and the commands never run together
+    syncing: tokio::sync::Mutex<()>,
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Changes a sync carries over, worked out from both sides and the base
+#[derive(Debug, Default, PartialEq)]
+struct SyncPlan {
+    /// Added at the provider
+    pull_add: Vec<String>,
+    /// Removed at the provider
+    pull_remove: Vec<String>,
+    /// Added locally
+    push_add: Vec<String>,
+    /// Removed locally
+    push_remove: Vec<String>,
+    /// Local changes the user chose to keep, as (track id, conflict kind)
+    held: Vec<(String, &'static str)>,
+    /// In the base and locally, past the tracks the provider listed
+    unknown: Vec<String>,
+}
+
+/// Compare the local tracks and the remote ones with the base. Without a base
+/// (first sync) both sides are merged. `remote_complete` is false when the
+/// provider listed only the first tracks, `settled` are the conflicts kept
+/// locally.
+fn plan_sync(
+    base: Option<&[String]>,
+    local: &[String],
+    remote: &[String],
+    remote_complete: bool,
+    settled: &[(String, String)],
+) -> SyncPlan {
+    let local_set: HashSet<&String> = local.iter().collect();
+    let remote_set: HashSet<&String> = remote.iter().collect();
+    let is_settled = |track_id: &String, kind: &str| settled.iter().any(|(id, k)| id == track_id && k == kind);
+    let mut plan = SyncPlan::default();
+
+    let Some(base) = base else {
+        plan.pull_add = unique(remote.iter().filter(|id| !local_set.contains(id)));
+        plan.push_add = unique(local.iter().filter(|id| !remote_set.contains(id)));
+        return plan;
+    };
+    let base_set: HashSet<&String> = base.iter().collect();
+
+    plan.pull_add = unique(remote.iter().filter(|id| !base_set.contains(id) && !local_set.contains(id)));
+    for track_id in unique(local.iter().filter(|id| !remote_set.contains(id))) {
+        if base_set.contains(&track_id) {
+            if remote_complete {
+                plan.pull_remove.push(track_id);
+            } else {
+                plan.unknown.push(track_id);
+            }
+        } else if is_settled(&track_id, LOCAL_ADDITION) {
+            plan.held.push((track_id, LOCAL_ADDITION));
+        } else {
+            plan.push_add.push(track_id);
+        }
+    }
+    // Past a cut listing, the provider may still have what was removed locally
+    for track_id in unique(base.iter().filter(|id| !local_set.contains(id))) {
+        if remote_set.contains(&track_id) || !remote_complete {
+            if is_settled(&track_id, LOCAL_REMOVAL) {
+                plan.held.push((track_id, LOCAL_REMOVAL));
+            } else {
+                plan.push_remove.push(track_id);
+            }
+        }
+    }
+    plan
+}
+
+/// Ids in order, without repeats
+fn unique<'a>(ids: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    ids.filter(|id| seen.insert(*id)).cloned().collect()
+}
+
+/// Outcome of pushing the local changes
+#[derive(Default)]
+struct Pushed {
+    added: Vec<String>,
+    removed: Vec<String>,
+    conflicts: Vec<(String, &'static str)>,
+    error: Option<String>,
+}
+
+/// Sync one linked playlist
+async fn run_sync(app: &AppHandle, link: &PlaylistSyncLink) -> Result<PlaylistSyncReport> {
+    let selection = MusicSourceSelection {
+        mode: MusicSourceMode::Single,
+        ids: vec![link.provider_id.clone()],
+    };
+    let (_, provider) = app
+        .state::<PluginHandler>()
+        .plugin_manager()
+        .get_audio_providers_by_selection(&selection)
+        .await
+        .map_err(|e| MusicError::String(format!("Failed to get audio providers: {}", e)))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| MusicError::String(format!("Provider {} is not available", link.provider_id)))?;
+
+    let remote = {
+        let plugin = provider.lock().await;
+        match timeout(PROVIDER_TIMEOUT, plugin.get_playlist(&link.remote_id)).await {
+            Ok(res) => res.map_err(|e| MusicError::String(format!("Provider {} playlist failed: {}", link.provider_id, e)))?,
+            Err(_) => return Err(MusicError::String(format!("Provider {} playlist timeout", link.provider_id))),
+        }
+    };
+    let expected = remote.total_tracks.map_or(remote.track_count as usize, |n| n as usize);
+    let remote_complete = remote.tracks.len() >= expected;
+    let remote_ids: Vec<String> = remote.tracks.iter().map(|t| t.id.clone()).collect();
+
+    let database = app.state::<Database>();
+    let local = database.get_playlist_track_ids(&link.playlist_id)?;
+    let base = database.get_playlist_sync_base(&link.playlist_id)?;
+    let settled = database.get_settled_playlist_sync_conflicts(&link.playlist_id)?;
+    let plan = plan_sync(base.as_deref(), &local, &remote_ids, remote_complete, &settled);
+
+    let mut pushed = Pushed::default();
+    if !plan.push_add.is_empty() || !plan.push_remove.is_empty() {
+        let plugin = provider.lock().await;
+        let writer = match plugin.as_playlist_write() {
+            Some(writer) => match timeout(PROVIDER_TIMEOUT, writer.can_edit_playlist(&link.remote_id)).await {
+                Ok(Ok(true)) => Some(writer),
+                Ok(Ok(false)) => None,
+                Ok(Err(e)) => return Err(MusicError::String(format!("Provider {} playlist access failed: {}", link.provider_id, e))),
+                Err(_) => return Err(MusicError::String(format!("Provider {} playlist access timeout", link.provider_id))),
+            },
+            None => None,
+        };
+        match writer {
+            Some(writer) => {
+                if !plan.push_add.is_empty() {
+                    match timeout(PROVIDER_TIMEOUT, writer.add_playlist_tracks(&link.remote_id, &plan.push_add)).await {
+                        Ok(Ok(rejected)) => {
+                            for track_id in &plan.push_add {
+                                if rejected.contains(track_id) {
+                                    pushed.conflicts.push((track_id.clone(), LOCAL_ADDITION));
+                                } else {
+                                    pushed.added.push(track_id.clone());
+                                }
+                            }
+                        }
+                        Ok(Err(e)) => pushed.error = Some(format!("Adding tracks failed: {}", e)),
+                        Err(_) => pushed.error = Some("Adding tracks timed out".to_string()),
+                    }
+                }
+                if !plan.push_remove.is_empty() {
+                    match timeout(PROVIDER_TIMEOUT, writer.remove_playlist_tracks(&link.remote_id, &plan.push_remove)).await {
+                        Ok(Ok(())) => pushed.removed = plan.push_remove.clone(),
+                        Ok(Err(e)) => pushed.error = Some(format!("Removing tracks failed: {}", e)),
+                        Err(_) => pushed.error = Some("Removing tracks timed out".to_string()),
+                    }
+                }
+            }
+            // Read-only: every local change is left to the user
+            None => {
+                pushed.conflicts.extend(plan.push_add.iter().map(|id| (id.clone(), LOCAL_ADDITION)));
+                pushed.conflicts.extend(plan.push_remove.iter().map(|id| (id.clone(), LOCAL_REMOVAL)));
+            }
+        }
+    }
+
+    if !plan.pull_add.is_empty() || !plan.pull_remove.is_empty() {
+        let mut by_id: HashMap<String, MediaContent> = HashMap::new();
+        for track in remote.tracks {
+            if plan.pull_add.contains(&track.id) && !by_id.contains_key(&track.id) {
+                by_id.insert(track.id.clone(), to_media_content(track));
+            }
+        }
+        let added = plan.pull_add.iter().filter_map(|id| by_id.remove(id)).collect();
+        database.apply_playlist_sync(&link.playlist_id, added, &plan.pull_remove)?;
+    }
+
+    // Shared by both sides now, plus what stays undecided: tracks past a cut
+    // listing and local removals the provider did not take
+    let mut local_now: HashSet<&String> = local.iter().chain(&plan.pull_add).collect();
+    plan.pull_remove.iter().for_each(|id| {
+        local_now.remove(id);
+    });
+    let mut remote_now: HashSet<&String> = remote_ids.iter().chain(&pushed.added).collect();
+    pushed.removed.iter().for_each(|id| {
+        remote_now.remove(id);
+    });
+    let mut new_base: BTreeSet<&String> = local_now.intersection(&remote_now).copied().collect();
+    new_base.extend(&plan.unknown);
+    new_base.extend(plan.push_remove.iter().filter(|id| !pushed.removed.contains(id)));
+    new_base.extend(plan.held.iter().filter(|(_, kind)| *kind == LOCAL_REMOVAL).map(|(id, _)| id));
+    let new_base: Vec<String> = new_base.into_iter().cloned().collect();
+
+    let conflicts: Vec<(String, String)> = pushed
+        .conflicts
+        .iter()
+        .chain(&plan.held)
+        .map(|(id, kind)| (id.clone(), kind.to_string()))
+        .collect();
+    database.finish_playlist_sync(&link.playlist_id, &new_base, &conflicts, now_ms())?;
+    if let Some(error) = &pushed.error {
+        tracing::warn!("Sync of playlist {} could not push its changes: {}", link.playlist_id, error);
+        database.set_playlist_sync_error(&link.playlist_id, error)?;
+    }
+
+    let open = database
+        .get_playlist_sync(&link.playlist_id)?
+        .map_or(0, |l| l.conflicts);
+    Ok(PlaylistSyncReport {
+        playlist_id: link.playlist_id.clone(),
+        pulled_added: plan.pull_add.len() as u32,
+        pulled_removed: plan.pull_remove.len() as u32,
+        pushed_added: pushed.added.len() as u32,
+        pushed_removed: pushed.removed.len() as u32,
+        conflicts: open,
+        error: pushed.error,
+    })
+}
+
+/// Sync one linked playlist, recording a failure on the link and announcing
+/// the outcome with `playlist-sync-finished`
+async fn sync_linked(app: &AppHandle, link: &PlaylistSyncLink) -> Result<PlaylistSyncReport> {
+    let state = app.state::<PlaylistSyncState>();
+    let _syncing = state.syncing.lock().await;
+    let res = run_sync(app, link).await;
+    let payload = match &res {
+        Ok(report) => json!({ "playlistId": link.playlist_id, "report": report }),
+        Err(e) => {
+            tracing::warn!("Sync of playlist {} failed: {:?}", link.playlist_id, e);
+            let _ = app.state::<Database>().set_playlist_sync_error(&link.playlist_id, &e.to_string());
+            json!({ "playlistId": link.playlist_id, "error": e.to_string() })
+        }
+    };
+    if let Err(e) = app.emit("playlist-sync-finished", payload) {
+        tracing::warn!("Failed to emit playlist-sync-finished event: {}", e);
+    }
+    res
+}
+
+fn sync_settings(app: &AppHandle) -> MusicPlaylistSyncSettings {
+    app.state::<SettingsConfig>()
+        .load_selective::<MusicPlaylistSyncSettings>("music.playlistSync".to_string())
+        .unwrap_or_default()
+}
+
+/// Sync the linked playlists on the `music.playlistSync` interval for as long
+/// as the app runs. The settings are read again after each round.
+pub fn start_sync(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let minutes = sync_settings(&app)
+                .interval_minutes
+                .unwrap_or(DEFAULT_INTERVAL_MINUTES)
+                .max(MIN_INTERVAL_MINUTES);
+            tokio::time::sleep(Duration::from_secs(minutes as u64 * 60)).await;
+            if !sync_settings(&app).enabled.unwrap_or(true) {
+                continue;
+            }
+            let links = match app.state::<Database>().get_playlist_syncs() {
+                Ok(links) => links,
+                Err(e) => {
+                    tracing::warn!("Failed to load the synced playlists: {:?}", e);
+                    continue;
+                }
+            };
+            for link in links {
+                // Failures are recorded on the link and announced
+                let _ = sync_linked(&app, &link).await;
+            }
+        }
+    });
+}
+
+fn get_link(database: &Database, playlist_id: &str) -> Result<PlaylistSyncLink> {
+    database
+        .get_playlist_sync(playlist_id)?
+        .ok_or_else(|| MusicError::String(format!("Playlist {} is not synced", playlist_id)))
+}
+
+command_envelope! {
+    /// Keep the playlist `playlist_id` in sync with the playlist `remote_id`
+    /// of the provider `provider_id`. The first sync, which merges both
+    /// sides, runs in the background and is announced with
+    /// `playlist-sync-finished`.
+    #[tracing::instrument(level = "debug", skip(app, plugin_handler, database))]
+    #[tauri::command]
+    pub async fn link_playlist_sync(
+        app: AppHandle,
+        plugin_handler: State<'_, PluginHandler>,
+        database: State<'_, Database>,
+        playlist_id: String,
+        provider_id: String,
+        remote_id: String,
+    ) -> Result<PlaylistSyncLink> {
+        if plugin_handler.get_plugin(provider_id.clone()).await.is_err() {
+            return Err(MusicError::String(format!("Unknown provider {}", provider_id)));
+        }
+        database.link_playlist_sync(&playlist_id, &provider_id, &remote_id)?;
+        let link = get_link(&database, &playlist_id)?;
+        let first = link.clone();
+        tauri::async_runtime::spawn(async move {
+            let _ = sync_linked(&app, &first).await;
+        });
+        Ok(link)
+    }
+}
+
+command_envelope! {
+    /// Stop syncing a playlist; its tracks stay as they are
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn unlink_playlist_sync(database: State<'_, Database>, playlist_id: String) -> Result<bool> {
+        database.unlink_playlist_sync(&playlist_id)
+    }
+}
+
+command_envelope! {
+    /// Playlists linked to a provider, with the state of their last sync
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn get_playlist_syncs(database: State<'_, Database>) -> Result<Vec<PlaylistSyncLink>> {
+        database.get_playlist_syncs()
+    }
+}
+
+command_envelope! {
+    /// Sync a linked playlist now
+    #[tracing::instrument(level = "debug", skip(app, database))]
+    #[tauri::command]
+    pub async fn sync_playlist(
+        app: AppHandle,
+        database: State<'_, Database>,
+        playlist_id: String,
+    ) -> Result<PlaylistSyncReport> {
+        let link = get_link(&database, &playlist_id)?;
+        sync_linked(&app, &link).await
+    }
+}
+
+command_envelope! {
+    /// Open sync conflicts of a playlist, or of every synced playlist
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command(async)]
+    pub fn get_playlist_sync_conflicts(
+        database: State<'_, Database>,
+        playlist_id: Option<String>,
+    ) -> Result<Vec<PlaylistSyncConflict>> {
+        database.get_playlist_sync_conflicts(playlist_id.as_deref())
+    }
+}
+
+command_envelope! {
+    /// Settle a sync conflict. `keep` "local" keeps the local change and the
+    /// conflict is not raised again; "remote" undoes it: a track added locally
+    /// is removed, a track removed locally is added back.
+    #[tracing::instrument(level = "debug", skip(app, database))]
+    #[tauri::command]
+    pub async fn resolve_playlist_sync_conflict(
+        app: AppHandle,
+        database: State<'_, Database>,
+        conflict_id: i64,
+        keep: String,
+    ) -> Result<()> {
+        let state = app.state::<PlaylistSyncState>();
+        let _syncing = state.syncing.lock().await;
+        let conflict = database
+            .get_playlist_sync_conflict(conflict_id)?
+            .ok_or_else(|| MusicError::String(format!("Unknown sync conflict {}", conflict_id)))?;
+        match (keep.as_str(), conflict.kind.as_str()) {
+            ("local", _) => return database.settle_playlist_sync_conflict(conflict_id, true),
+            ("remote", LOCAL_ADDITION) => {
+                database.apply_playlist_sync(&conflict.playlist_id, Vec::new(), &[conflict.track_id.clone()])?
+            }
+            ("remote", LOCAL_REMOVAL) => {
+                let track = MediaContent {
+                    track: Tracks {
+                        _id: Some(conflict.track_id.clone()),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                };
+                database.apply_playlist_sync(&conflict.playlist_id, vec![track], &[])?
+            }
+            ("remote", kind) => return Err(MusicError::String(format!("Unknown sync conflict kind {}", kind))),
+            (keep, _) => return Err(MusicError::String(format!("Unknown side {}, expected local or remote", keep))),
+        }
+        database.settle_playlist_sync_conflict(conflict_id, false)
+    }
+}
@@ -0,0 +1,109 @@
+//! Credentials of sandboxed plugins, stored encrypted in
+//! `prefs.music.pluginCredentials` and injected by the plugin fetch proxy
+//! into the requests that name them
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ::plugins::system::fetch_proxy::{NetworkAuditEntry, PluginCredential};
+use ::plugins::system::manager::PluginManager;
+use ::settings::settings::SettingsConfig;
+use macros::command_envelope;
+use tauri::{AppHandle, Manager, State};
+use types::errors::Result;
+use uuid::Uuid;
+
+const CREDENTIALS_KEY: &str = "music.pluginCredentials";
+
+/// Stored credentials by plugin ID and credential name
+fn load_credentials(settings: &SettingsConfig) -> HashMap<String, HashMap<String, PluginCredential>> {
+    settings
+        .get_secure::<HashMap<String, HashMap<String, PluginCredential>>>(CREDENTIALS_KEY.to_string())
+        .unwrap_or_default()
+}
+
+/// Pass the stored credentials to the plugin manager
+pub fn apply_plugin_credentials(app: &AppHandle) {
+    let plugin_manager = app.state::<Arc<PluginManager>>();
+    for (plugin_id, credentials) in load_credentials(&app.state::<SettingsConfig>()) {
+        match Uuid::parse_str(&plugin_id) {
+            Ok(id) => plugin_manager.set_plugin_credentials(id, credentials),
+            Err(_) => tracing::warn!("Ignoring credentials of invalid plugin ID {}", plugin_id),
+        }
+    }
+}
+
+command_envelope! {
+    /// Store or remove (no `credential`) a named credential of a plugin.
+    /// Returns the names of the plugin's credentials; values are never returned.
+    #[tauri::command]
+    pub fn set_plugin_credential(
+        settings: State<'_, SettingsConfig>,
+        plugin_manager: State<'_, Arc<PluginManager>>,
+        plugin_id: Option<String>,
+        pluginId: Option<String>,
+        name: String,
+        credential: Option<PluginCredential>,
+    ) -> Result<Vec<String>> {
+        let pid = plugin_id.or(pluginId).ok_or("missing plugin_id")?;
+        let id = Uuid::parse_str(&pid).map_err(|_| "Invalid plugin ID format".to_string())?;
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err("Credential name is empty".into());
+        }
+
+        let mut stored = load_credentials(&settings);
+        let credentials = stored.entry(pid.clone()).or_default();
+        match credential {
+            Some(credential) => {
+                credentials.insert(name, credential);
+            }
+            None => {
+                credentials.remove(&name);
+            }
+        }
+        let credentials = if credentials.is_empty() {
+            stored.remove(&pid).unwrap_or_default()
+        } else {
+            credentials.clone()
+        };
+        settings.set_secure(CREDENTIALS_KEY.to_string(), Some(stored))?;
+
+        plugin_manager.set_plugin_credentials(id, credentials);
+        Ok(plugin_manager.plugin_credential_names(id))
+    }
+}
+
+command_envelope! {
+    /// Names of the credentials stored for a plugin
+    #[tauri::command]
+    pub fn get_plugin_credentials(
+        plugin_manager: State<'_, Arc<PluginManager>>,
+        plugin_id: Option<String>,
+        pluginId: Option<String>,
+    ) -> Result<Vec<String>> {
+        let pid = plugin_id.or(pluginId).ok_or("missing plugin_id")?;
+        let id = Uuid::parse_str(&pid).map_err(|_| "Invalid plugin ID format".to_string())?;
+        Ok(plugin_manager.plugin_credential_names(id))
+    }
+}
+
+command_envelope! {
+    /// Requests a plugin made through the host fetch proxy in this session,
+    /// newest first. `clear` empties the log afterwards.
+    #[tauri::command]
+    pub fn get_plugin_network_audit(
+        plugin_manager: State<'_, Arc<PluginManager>>,
+        plugin_id: Option<String>,
+        pluginId: Option<String>,
+        clear: Option<bool>,
+    ) -> Result<Vec<NetworkAuditEntry>> {
+        let pid = plugin_id.or(pluginId).ok_or("missing plugin_id")?;
+        let id = Uuid::parse_str(&pid).map_err(|_| "Invalid plugin ID format".to_string())?;
+        let audit = plugin_manager.plugin_network_audit(id);
+        if clear.unwrap_or(false) {
+            plugin_manager.clear_plugin_network_audit(id);
+        }
+        Ok(audit)
+    }
+}
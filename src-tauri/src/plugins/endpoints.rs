@@ -0,0 +1,87 @@
+//! Provider endpoint overrides stored in `prefs.music.endpoints` and applied
+//! to the media plugins
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ::plugins::system::endpoints::{validate_endpoint, EndpointOverride, EndpointStatus};
+use ::plugins::system::manager::PluginManager;
+use ::settings::settings::SettingsConfig;
+use macros::command_envelope;
+use tauri::{AppHandle, Manager, State};
+use types::errors::Result;
+use types::settings::music::ProviderEndpointSettings;
+use uuid::Uuid;
+
+fn load_endpoints(settings: &SettingsConfig) -> HashMap<String, ProviderEndpointSettings> {
+    settings
+        .load_selective::<HashMap<String, ProviderEndpointSettings>>("music.endpoints".to_string())
+        .unwrap_or_default()
+}
+
+/// Pass the configured endpoint overrides to the plugin manager. Entries that
+/// no longer validate (e.g. edited by hand) are ignored.
+pub fn apply_endpoint_settings(app: &AppHandle) {
+    let mut overrides = HashMap::new();
+    for (plugin_id, endpoint) in load_endpoints(&app.state::<SettingsConfig>()) {
+        let (Ok(id), Some(base_url)) = (Uuid::parse_str(&plugin_id), endpoint.base_url) else {
+            continue;
+        };
+        match validate_endpoint(&base_url) {
+            Ok(base_url) => {
+                overrides.insert(id, EndpointOverride {
+                    base_url,
+                    fallback: endpoint.fallback.unwrap_or(true),
+                });
+            }
+            Err(e) => tracing::warn!("Ignoring endpoint of provider {}: {}", plugin_id, e),
+        }
+    }
+
+    let plugin_manager = app.state::<Arc<PluginManager>>().inner().clone();
+    tauri::async_runtime::spawn(async move {
+        plugin_manager.set_endpoint_overrides(overrides).await;
+    });
+}
+
+command_envelope! {
+    /// Set or clear (empty `base_url`) the endpoint override of a provider.
+    /// The URL is validated before it is saved.
+    #[tauri::command]
+    pub fn set_provider_endpoint(
+        settings: State<'_, SettingsConfig>,
+        plugin_id: Option<String>,
+        pluginId: Option<String>,
+        base_url: Option<String>,
+        fallback: Option<bool>,
+    ) -> Result<()> {
+        let pid = plugin_id.or(pluginId).ok_or("missing plugin_id")?;
+        Uuid::parse_str(&pid).map_err(|_| "Invalid plugin ID format".to_string())?;
+
+        let mut endpoints = load_endpoints(&settings);
+        match base_url.filter(|url| !url.trim().is_empty()) {
+            Some(url) => {
+                let base_url = validate_endpoint(&url).map_err(|e| e.to_string())?;
+                endpoints.insert(pid, ProviderEndpointSettings {
+                    base_url: Some(base_url),
+                    fallback,
+                });
+            }
+            None => {
+                endpoints.remove(&pid);
+            }
+        }
+        // Applied by the settings change listener
+        settings.save_selective("music.endpoints".to_string(), Some(endpoints))
+    }
+}
+
+command_envelope! {
+    /// Mirror health and the endpoint in use of every provider with an override
+    #[tauri::command]
+    pub fn get_provider_endpoint_status(
+        plugin_manager: State<'_, Arc<PluginManager>>,
+    ) -> Result<Vec<EndpointStatus>> {
+        Ok(plugin_manager.endpoint_statuses())
+    }
+}
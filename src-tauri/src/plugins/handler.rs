@@ -1,4 +1,5 @@
 // use std::sync::Arc;
+use macros::command_envelope;
 use tauri::{State, Emitter};
 use serde::Deserialize;
 use types::errors::Result;
@@ -6,97 +7,219 @@ use types::errors::Result;
 use crate::plugins::manager::PluginHandler;
 
 // #[tracing::instrument(level = "debug", skip(plugin_handler))]
-#[tauri::command]
-pub async fn get_plugins(
-    plugin_handler: State<'_, PluginHandler>,
-) -> Result<Vec<crate::plugins::manager::PluginInfo>> {
-    plugin_handler.get_plugins().await
+command_envelope! {
+    #[tauri::command]
+    pub async fn get_plugins(
+        plugin_handler: State<'_, PluginHandler>,
+    ) -> Result<Vec<crate::plugins::manager::PluginInfo>> {
+        plugin_handler.get_plugins().await
+    }
 }
 
 // #[tracing::instrument(level = "debug", skip(plugin_handler))]
 // Note: keep signatures simple and consistent with other Tauri commands.
 // Accept both snake_case and camelCase keys from the frontend for robustness.
 
-#[tauri::command]
-pub async fn get_plugin(
-    plugin_handler: State<'_, PluginHandler>,
-    plugin_id: Option<String>,
-    pluginId: Option<String>,
-) -> Result<crate::plugins::manager::PluginInfo> {
-    let pid = plugin_id.or(pluginId).ok_or("missing plugin_id")?;
-    plugin_handler.get_plugin(pid).await
+command_envelope! {
+    #[tauri::command]
+    pub async fn get_plugin(
+        plugin_handler: State<'_, PluginHandler>,
+        plugin_id: Option<String>,
+        pluginId: Option<String>,
+    ) -> Result<crate::plugins::manager::PluginInfo> {
+        let pid = plugin_id.or(pluginId).ok_or("missing plugin_id")?;
+        plugin_handler.get_plugin(pid).await
+    }
 }
 
 // #[tracing::instrument(level = "debug", skip(plugin_handler))]
-#[tauri::command]
-pub async fn enable_plugin(
-    app: tauri::AppHandle,
-    plugin_handler: State<'_, PluginHandler>,
-    plugin_id: Option<String>,
-    pluginId: Option<String>,
-) -> Result<()> {
-    let pid = plugin_id.or(pluginId).ok_or("missing plugin_id")?;
-    let res = plugin_handler.enable_plugin(pid.clone()).await;
-    if res.is_ok() {
-        let _ = app.emit("plugins-updated", pid.clone());
+command_envelope! {
+    #[tauri::command]
+    pub async fn enable_plugin(
+        app: tauri::AppHandle,
+        plugin_handler: State<'_, PluginHandler>,
+        plugin_id: Option<String>,
+        pluginId: Option<String>,
+    ) -> Result<()> {
+        let pid = plugin_id.or(pluginId).ok_or("missing plugin_id")?;
+        let res = plugin_handler.enable_plugin(pid.clone()).await;
+        if res.is_ok() {
+            let _ = app.emit("plugins-updated", pid.clone());
+        }
+        res
     }
-    res
 }
 
 // #[tracing::instrument(level = "debug", skip(plugin_handler))]
-#[tauri::command]
-pub async fn disable_plugin(
-    app: tauri::AppHandle,
-    plugin_handler: State<'_, PluginHandler>,
-    plugin_id: Option<String>,
-    pluginId: Option<String>,
-) -> Result<()> {
-    let pid = plugin_id.or(pluginId).ok_or("missing plugin_id")?;
-    let res = plugin_handler.disable_plugin(pid.clone()).await;
-    if res.is_ok() {
-        let _ = app.emit("plugins-updated", pid.clone());
+command_envelope! {
+    #[tauri::command]
+    pub async fn disable_plugin(
+        app: tauri::AppHandle,
+        plugin_handler: State<'_, PluginHandler>,
+        plugin_id: Option<String>,
+        pluginId: Option<String>,
+    ) -> Result<()> {
+        let pid = plugin_id.or(pluginId).ok_or("missing plugin_id")?;
+        let res = plugin_handler.disable_plugin(pid.clone()).await;
+        if res.is_ok() {
+            let _ = app.emit("plugins-updated", pid.clone());
+        }
+        res
     }
-    res
 }
 
 // #[tracing::instrument(level = "debug", skip(plugin_handler))]
-#[tauri::command]
-pub async fn start_plugin(
-    app: tauri::AppHandle,
-    plugin_handler: State<'_, PluginHandler>,
-    plugin_id: Option<String>,
-    pluginId: Option<String>,
-) -> Result<()> {
-    let pid = plugin_id.or(pluginId).ok_or("missing plugin_id")?;
-    let res = plugin_handler.start_plugin(pid.clone()).await;
-    if res.is_ok() { let _ = app.emit("plugins-updated", pid.clone()); }
-    res
+command_envelope! {
+    #[tauri::command]
+    pub async fn start_plugin(
+        app: tauri::AppHandle,
+        plugin_handler: State<'_, PluginHandler>,
+        plugin_id: Option<String>,
+        pluginId: Option<String>,
+    ) -> Result<()> {
+        let pid = plugin_id.or(pluginId).ok_or("missing plugin_id")?;
+        let res = plugin_handler.start_plugin(pid.clone()).await;
+        if res.is_ok() { let _ = app.emit("plugins-updated", pid.clone()); }
+        res
+    }
 }
 
 // #[tracing::instrument(level = "debug", skip(plugin_handler))]
-#[tauri::command]
-pub async fn stop_plugin(
-    app: tauri::AppHandle,
-    plugin_handler: State<'_, PluginHandler>,
-    plugin_id: Option<String>,
-    pluginId: Option<String>,
-) -> Result<()> {
-    let pid = plugin_id.or(pluginId).ok_or("missing plugin_id")?;
-    let res = plugin_handler.stop_plugin(pid.clone()).await;
-    if res.is_ok() { let _ = app.emit("plugins-updated", pid.clone()); }
-    res
+command_envelope! {
+    #[tauri::command]
+    pub async fn stop_plugin(
+        app: tauri::AppHandle,
+        plugin_handler: State<'_, PluginHandler>,
+        plugin_id: Option<String>,
+        pluginId: Option<String>,
+    ) -> Result<()> {
+        let pid = plugin_id.or(pluginId).ok_or("missing plugin_id")?;
+        let res = plugin_handler.stop_plugin(pid.clone()).await;
+        if res.is_ok() { let _ = app.emit("plugins-updated", pid.clone()); }
+        res
+    }
 }
 
 // #[tracing::instrument(level = "debug", skip(plugin_handler))]
-#[tauri::command]
-pub async fn load_plugin(
-    app: tauri::AppHandle,
-    plugin_handler: State<'_, PluginHandler>,
-    plugin_path: Option<String>,
-    pluginPath: Option<String>,
-) -> Result<()> {
-    let pp = plugin_path.or(pluginPath).ok_or("missing plugin_path")?;
-    let res = plugin_handler.load_plugin(pp).await;
-    if res.is_ok() { let _ = app.emit("plugins-updated", serde_json::Value::Null); }
-    res
+command_envelope! {
+    #[tauri::command]
+    pub async fn load_plugin(
+        app: tauri::AppHandle,
+        plugin_handler: State<'_, PluginHandler>,
+        plugin_path: Option<String>,
+        pluginPath: Option<String>,
+    ) -> Result<()> {
+        let pp = plugin_path.or(pluginPath).ok_or("missing plugin_path")?;
+        let res = plugin_handler.load_plugin(pp).await;
+        if res.is_ok() { let _ = app.emit("plugins-updated", serde_json::Value::Null); }
+        res
+    }
+}
+
+command_envelope! {
+    /// Download a plugin package (zip with manifest.json), verify it and
+    /// install it under the plugins root. `sha256` is the expected hash of the
+    /// package; `enable` defaults to false.
+    #[tauri::command]
+    pub async fn install_plugin_from_url(
+        app: tauri::AppHandle,
+        plugin_handler: State<'_, PluginHandler>,
+        url: String,
+        sha256: Option<String>,
+        enable: Option<bool>,
+    ) -> Result<::plugins::system::installer::PluginInstall> {
+        let res = plugin_handler.install_plugin_from_url(&url, sha256.as_deref(), enable.unwrap_or(false)).await;
+        if let Ok(install) = &res { let _ = app.emit("plugins-updated", install.plugin_id.clone()); }
+        res
+    }
+}
+
+command_envelope! {
+    /// Install a plugin package from a local zip archive, like `install_plugin_from_url`
+    #[tauri::command]
+    pub async fn install_plugin_from_archive(
+        app: tauri::AppHandle,
+        plugin_handler: State<'_, PluginHandler>,
+        archive_path: Option<String>,
+        archivePath: Option<String>,
+        sha256: Option<String>,
+        enable: Option<bool>,
+    ) -> Result<::plugins::system::installer::PluginInstall> {
+        let path = archive_path.or(archivePath).ok_or("missing archive_path")?;
+        let res = plugin_handler.install_plugin_from_archive(&path, sha256.as_deref(), enable.unwrap_or(false)).await;
+        if let Ok(install) = &res { let _ = app.emit("plugins-updated", install.plugin_id.clone()); }
+        res
+    }
+}
+
+command_envelope! {
+    /// JSON schema of a plugin's settings, from which the settings form is
+    /// rendered. `null` when the plugin has no settings.
+    #[tauri::command]
+    pub async fn get_plugin_config_schema(
+        plugin_handler: State<'_, PluginHandler>,
+        plugin_id: Option<String>,
+        pluginId: Option<String>,
+    ) -> Result<Option<serde_json::Value>> {
+        let pid = plugin_id.or(pluginId).ok_or("missing plugin_id")?;
+        plugin_handler.get_plugin_config_schema(pid).await
+    }
+}
+
+command_envelope! {
+    /// Configuration in effect for a plugin: schema defaults overlaid with the
+    /// saved values
+    #[tauri::command]
+    pub async fn get_plugin_config(
+        plugin_handler: State<'_, PluginHandler>,
+        plugin_id: Option<String>,
+        pluginId: Option<String>,
+    ) -> Result<std::collections::HashMap<String, serde_json::Value>> {
+        let pid = plugin_id.or(pluginId).ok_or("missing plugin_id")?;
+        plugin_handler.get_plugin_config(pid).await
+    }
+}
+
+command_envelope! {
+    /// Replace a plugin's configuration. The values are validated against its
+    /// schema and passed to the plugin, then saved; `null` clears a setting.
+    /// Returns the configuration in effect.
+    #[tauri::command]
+    pub async fn set_plugin_config(
+        app: tauri::AppHandle,
+        plugin_handler: State<'_, PluginHandler>,
+        plugin_id: Option<String>,
+        pluginId: Option<String>,
+        values: std::collections::HashMap<String, serde_json::Value>,
+    ) -> Result<std::collections::HashMap<String, serde_json::Value>> {
+        let pid = plugin_id.or(pluginId).ok_or("missing plugin_id")?;
+        let res = plugin_handler.set_plugin_config(pid.clone(), values).await;
+        if res.is_ok() {
+            let _ = app.emit("plugins-updated", pid);
+        }
+        res
+    }
+}
+
+command_envelope! {
+    /// Diagnostic: compare plugin enabled flags in the database with the media
+    /// factory. With `repair`, drifted factory flags are fixed immediately.
+    #[tauri::command]
+    pub async fn get_plugin_state_report(
+        plugin_handler: State<'_, PluginHandler>,
+        repair: Option<bool>,
+    ) -> Result<::plugins::system::state_sync::PluginStateReport> {
+        plugin_handler.get_plugin_state_report(repair.unwrap_or(false))
+    }
+}
+
+/// Reload installed plugins when their files change, sending each reload to
+/// the frontend as `plugin-reloaded`
+pub fn start_plugin_hot_reload(app: tauri::AppHandle, plugin_manager: &std::sync::Arc<::plugins::system::manager::PluginManager>) {
+    let res = plugin_manager.start_hot_reload(move |reload| {
+        let _ = app.emit("plugin-reloaded", reload);
+    });
+    if let Err(e) = res {
+        tracing::warn!("Plugin hot reload unavailable: {}", e);
+    }
 }
@@ -2,12 +2,15 @@
 //!
 //! This module provides additional management functionality for plugins.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 use serde::{Deserialize, Serialize};
 
 use plugins::system::manager::PluginManager;
 use plugins::system::types::{PluginMetadata, PluginStatus, HealthStatus};
+use plugins::system::state_sync::PluginStateReport;
+use plugins::system::installer::{self, PluginInstall, MAX_PACKAGE_SIZE};
 // use plugins::system::types::{PluginMetadata, PluginStatus, HealthStatus, PluginError};
 // use tauri::State;
 use types::errors::Result;
@@ -228,6 +231,69 @@ impl PluginHandler {
             .map_err(|e| format!("Failed to load plugin: {}", e).into())
     }
     
+    /// Download a plugin package and install it
+    pub async fn install_plugin_from_url(&self, url: &str, sha256: Option<&str>, enable: bool) -> Result<PluginInstall> {
+        let package = installer::download_package(url).await
+            .map_err(|e| format!("Failed to install plugin: {}", e))?;
+        self.install_plugin_package(&package, sha256, enable).await
+    }
+
+    /// Install a plugin package from a local archive
+    pub async fn install_plugin_from_archive(&self, archive_path: &str, sha256: Option<&str>, enable: bool) -> Result<PluginInstall> {
+        let path = std::path::Path::new(archive_path);
+        let size = std::fs::metadata(path)
+            .map_err(|e| format!("Failed to read plugin archive: {}", e))?
+            .len();
+        if size > MAX_PACKAGE_SIZE {
+            return Err("Plugin package is too large".into());
+        }
+        let package = std::fs::read(path)
+            .map_err(|e| format!("Failed to read plugin archive: {}", e))?;
+        self.install_plugin_package(&package, sha256, enable).await
+    }
+
+    async fn install_plugin_package(&self, package: &[u8], sha256: Option<&str>, enable: bool) -> Result<PluginInstall> {
+        self.plugin_manager.install_plugin_package(package, sha256, enable).await
+            .map_err(|e| format!("Failed to install plugin: {}", e).into())
+    }
+    
+    /// JSON schema of a plugin's settings, `None` when it has none
+    pub async fn get_plugin_config_schema(&self, plugin_id: String) -> Result<Option<serde_json::Value>> {
+        let uuid = Uuid::parse_str(&plugin_id)
+            .map_err(|_| "Invalid plugin ID format".to_string())?;
+
+        self.plugin_manager.plugin_config_schema(uuid).await
+            .map_err(|e| format!("Failed to get plugin config schema: {}", e).into())
+    }
+
+    /// Configuration in effect for a plugin
+    pub async fn get_plugin_config(&self, plugin_id: String) -> Result<HashMap<String, serde_json::Value>> {
+        let uuid = Uuid::parse_str(&plugin_id)
+            .map_err(|_| "Invalid plugin ID format".to_string())?;
+
+        self.plugin_manager.plugin_config(uuid).await
+            .map_err(|e| format!("Failed to get plugin config: {}", e).into())
+    }
+
+    /// Validate, apply and save a plugin's configuration
+    pub async fn set_plugin_config(
+        &self,
+        plugin_id: String,
+        values: HashMap<String, serde_json::Value>,
+    ) -> Result<HashMap<String, serde_json::Value>> {
+        let uuid = Uuid::parse_str(&plugin_id)
+            .map_err(|_| "Invalid plugin ID format".to_string())?;
+
+        self.plugin_manager.set_plugin_config(uuid, values).await
+            .map_err(|e| format!("Failed to set plugin config: {}", e).into())
+    }
+
+    /// Audit plugin enabled flags between the database and the media factory
+    pub fn get_plugin_state_report(&self, repair: bool) -> Result<PluginStateReport> {
+        self.plugin_manager.plugin_state_report(repair)
+            .map_err(|e| format!("Failed to audit plugin states: {}", e).into())
+    }
+    
     /// Get the underlying plugin manager
     pub fn plugin_manager(&self) -> Arc<PluginManager> {
         Arc::clone(&self.plugin_manager)
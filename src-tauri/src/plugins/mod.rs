@@ -8,8 +8,12 @@ use tauri::AppHandle;
 use tauri::Manager;
 use tauri::State;
 
+pub mod credentials;
+pub mod endpoints;
 pub mod handler;
 pub mod manager;
+pub mod spotify;
+pub mod youtube;
 
 // Re-export the handler functions for easier access
 pub use handler::*;
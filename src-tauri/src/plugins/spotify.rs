@@ -0,0 +1,125 @@
+//! Account of the built-in Spotify plugin. `prefs.spotify.enable` decides
+//! whether the plugin is loaded; its OAuth tokens are stored encrypted in
+//! `prefs.spotify.tokens` and handed back to it on launch.
+
+use std::sync::Arc;
+
+use ::plugins::internal::spotify::{DeviceLogin, SpotifyTokens};
+use ::plugins::internal::SpotifyPlugin;
+use ::plugins::system::manager::PluginManager;
+use ::settings::settings::SettingsConfig;
+use macros::command_envelope;
+use music_plugin_sdk::traits::MediaAuthPlugin;
+use music_plugin_sdk::types::media::{AuthUserInfo, QrCodeState, QrCodeStatus};
+use serde_json::json;
+use tauri::{AppHandle, Manager, State};
+use types::errors::{error_helpers, Result};
+
+const TOKENS_KEY: &str = "spotify.tokens";
+
+fn spotify_enabled(settings: &SettingsConfig) -> bool {
+    settings.load_selective::<bool>("spotify.enable".to_string()).unwrap_or(false)
+}
+
+async fn loaded_plugin(plugin_manager: &PluginManager) -> Result<SpotifyPlugin> {
+    plugin_manager
+        .spotify_plugin()
+        .await
+        .ok_or_else(|| "Spotify is not enabled".into())
+}
+
+/// Load the plugin or not according to `prefs.spotify.enable`. Before plugin
+/// initialization this only decides whether it gets loaded.
+pub async fn apply_spotify_enabled(app: &AppHandle) {
+    let enabled = spotify_enabled(&app.state::<SettingsConfig>());
+    let plugin_manager = app.state::<Arc<PluginManager>>();
+    if let Err(e) = plugin_manager.set_spotify_enabled(enabled).await {
+        tracing::warn!("Failed to {} Spotify: {}", if enabled { "enable" } else { "disable" }, e);
+    }
+    if enabled {
+        attach_spotify_account(app).await;
+    }
+}
+
+/// Give the loaded plugin the stored tokens and persist the ones it obtains
+/// from now on. The librespot session follows the signed-in account.
+pub async fn attach_spotify_account(app: &AppHandle) {
+    let Some(plugin) = app.state::<Arc<PluginManager>>().spotify_plugin().await else {
+        return;
+    };
+    let tokens = app
+        .state::<SettingsConfig>()
+        .get_secure::<SpotifyTokens>(TOKENS_KEY.to_string())
+        .ok();
+    crate::playback::spotify::spotify_set_access_token(app.clone(), tokens.as_ref().map(|t| t.access_token.clone()));
+    plugin.set_tokens(tokens);
+
+    let app = app.clone();
+    plugin.set_token_listener(Some(Arc::new(move |tokens: Option<&SpotifyTokens>| {
+        if let Err(e) = app.state::<SettingsConfig>().set_secure(TOKENS_KEY.to_string(), tokens.cloned()) {
+            tracing::error!("Failed to store the Spotify tokens: {:?}", e);
+        }
+        crate::playback::spotify::spotify_set_access_token(app.clone(), tokens.map(|t| t.access_token.clone()));
+        let _ = crate::windowing::emit_audio_event(
+            &app,
+            json!({ "type": "SpotifyAccountChanged", "data": { "signedIn": tokens.is_some() } }),
+        );
+    })));
+}
+
+command_envelope! {
+    /// Start signing in to Spotify: the user opens the verification address
+    /// and enters the code, then `spotify_login_poll` is called until done
+    #[tracing::instrument(level = "debug", skip(plugin_manager))]
+    #[tauri::command]
+    pub async fn spotify_login_start(plugin_manager: State<'_, Arc<PluginManager>>) -> Result<DeviceLogin> {
+        let plugin = loaded_plugin(&plugin_manager).await?;
+        plugin.start_device_login().await.map_err(error_helpers::to_auth_error)
+    }
+}
+
+command_envelope! {
+    /// Check whether the user approved the sign-in of `device_code`. On
+    /// success the tokens are stored, they are never returned, and the liked
+    /// songs of the account are imported.
+    #[tracing::instrument(level = "debug", skip(app, plugin_manager, device_code))]
+    #[tauri::command]
+    pub async fn spotify_login_poll(
+        app: AppHandle,
+        plugin_manager: State<'_, Arc<PluginManager>>,
+        device_code: String,
+    ) -> Result<QrCodeStatus> {
+        let plugin = loaded_plugin(&plugin_manager).await?;
+        let status = plugin.check_qrcode_status(&device_code).await.map_err(error_helpers::to_auth_error)?;
+        if status.status == QrCodeState::Success {
+            crate::favorites::on_signed_in(&app, SpotifyPlugin::plugin_id().to_string());
+        }
+        Ok(status)
+    }
+}
+
+command_envelope! {
+    /// Sign out of Spotify and forget the stored tokens
+    #[tracing::instrument(level = "debug", skip(plugin_manager))]
+    #[tauri::command]
+    pub async fn spotify_logout(plugin_manager: State<'_, Arc<PluginManager>>) -> Result<()> {
+        let mut plugin = loaded_plugin(&plugin_manager).await?;
+        plugin.logout().await.map_err(error_helpers::to_auth_error)
+    }
+}
+
+command_envelope! {
+    /// Profile of the signed-in Spotify account, `None` when signed out or
+    /// Spotify is disabled
+    #[tracing::instrument(level = "debug", skip(plugin_manager))]
+    #[tauri::command]
+    pub async fn spotify_account(plugin_manager: State<'_, Arc<PluginManager>>) -> Result<Option<AuthUserInfo>> {
+        let Some(plugin) = plugin_manager.spotify_plugin().await else {
+            return Ok(None);
+        };
+        if !plugin.is_authenticated() {
+            return Ok(None);
+        }
+        plugin.current_user().await.map(Some).map_err(error_helpers::to_auth_error)
+    }
+}
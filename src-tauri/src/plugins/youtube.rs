@@ -0,0 +1,69 @@
+//! Browsing of the built-in YouTube plugin: pasted links and the pages of
+//! playlists and channel uploads. Searching goes through the regular search.
+
+use std::sync::Arc;
+
+use ::plugins::internal::YoutubePlugin;
+use ::plugins::system::manager::PluginManager;
+use macros::command_envelope;
+use music_plugin_sdk::types::media::{PageInput, SearchResult, SearchSlice, Track};
+use tauri::State;
+use types::errors::{error_helpers, Result};
+
+async fn loaded_plugin(plugin_manager: &PluginManager) -> Result<YoutubePlugin> {
+    plugin_manager
+        .youtube_plugin()
+        .await
+        .ok_or_else(|| "YouTube is not loaded".into())
+}
+
+command_envelope! {
+    /// Tracks of a pasted video, playlist or channel link. `None` when `url`
+    /// is not a YouTube link.
+    #[tracing::instrument(level = "debug", skip(plugin_manager))]
+    #[tauri::command]
+    pub async fn youtube_resolve_url(
+        plugin_manager: State<'_, Arc<PluginManager>>,
+        url: String,
+    ) -> Result<Option<SearchResult>> {
+        let plugin = loaded_plugin(&plugin_manager).await?;
+        plugin.resolve_url(&url).await.map_err(error_helpers::to_provider_error)
+    }
+}
+
+command_envelope! {
+    /// One page of a playlist; pass `page.nextCursor` back as `cursor`
+    #[tracing::instrument(level = "debug", skip(plugin_manager))]
+    #[tauri::command]
+    pub async fn youtube_playlist_tracks(
+        plugin_manager: State<'_, Arc<PluginManager>>,
+        playlist_id: String,
+        page: Option<PageInput>,
+    ) -> Result<SearchSlice<Track>> {
+        let plugin = loaded_plugin(&plugin_manager).await?;
+        let page = page.unwrap_or(PageInput { limit: None, offset: None, cursor: None });
+        plugin
+            .playlist_tracks(&playlist_id, &page)
+            .await
+            .map_err(error_helpers::to_provider_error)
+    }
+}
+
+command_envelope! {
+    /// One page of a channel's uploads, newest first. `channel_id` is a
+    /// "UC…" ID or an "@handle".
+    #[tracing::instrument(level = "debug", skip(plugin_manager))]
+    #[tauri::command]
+    pub async fn youtube_channel_tracks(
+        plugin_manager: State<'_, Arc<PluginManager>>,
+        channel_id: String,
+        page: Option<PageInput>,
+    ) -> Result<SearchSlice<Track>> {
+        let plugin = loaded_plugin(&plugin_manager).await?;
+        let page = page.unwrap_or(PageInput { limit: None, offset: None, cursor: None });
+        plugin
+            .channel_tracks(&channel_id, &page)
+            .await
+            .map_err(error_helpers::to_provider_error)
+    }
+}
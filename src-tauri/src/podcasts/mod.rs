@@ -0,0 +1,414 @@
+//! Podcast subscriptions. RSS and Atom feeds are fetched on subscription and
+//! refreshed in the background on the auto scanner interval; new episodes are
+//! announced with `podcasts-updated`. Episodes are queued like tracks (see
+//! `types::podcasts`), resume where they were left off and are downloaded
+//! through the download manager.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use ::settings::settings::SettingsConfig;
+use audio_player::AudioPlayer;
+use database::database::Database;
+use macros::command_envelope;
+use music_plugin_sdk::types::media::StreamSource;
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager, State};
+use types::errors::{error_helpers, MusicError, Result};
+use types::podcasts::{episode_id, Podcast, PodcastEpisode};
+use types::settings::queue::QueueAction;
+
+use crate::downloads::{queue_download, DownloadOrigin};
+
+const USER_AGENT: &str = concat!("Music/", env!("CARGO_PKG_VERSION"));
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Refresh interval when the scan interval is unset, and the shortest allowed
+const DEFAULT_REFRESH_SECS: u64 = 3600;
+const MIN_REFRESH_SECS: u64 = 900;
+
+/// Episodes per page when unset
+const DEFAULT_PAGE: i64 = 100;
+
+/// Interval between two saves of the position of the playing episode
+const SAVE_INTERVAL: Duration = Duration::from_secs(15);
+/// Positions closer to the start are not resumed
+const MIN_RESUME_SECS: f64 = 5.0;
+/// Episodes stopped closer to their end count as played
+const PLAYED_MARGIN_SECS: f64 = 30.0;
+
+/// Episode being played and where it stands
+#[derive(Default)]
+struct Progress {
+    episode_id: Option<String>,
+    position: f64,
+    duration: Option<f64>,
+    last_save: Option<Instant>,
+}
+
+/// Managed by Tauri
+#[derive(Default)]
+pub struct PodcastState {
+    /// Held during a refresh, so the timer and the command never run together
+    refreshing: tokio::sync::Mutex<()>,
+    progress: std::sync::Mutex<Progress>,
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn text(value: Option<feed_rs::model::Text>) -> Option<String> {
+    value
+        .map(|t| t.content.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+/// Enclosure of a feed entry: its URL, MIME type and duration in seconds
+fn enclosure(entry: &feed_rs::model::Entry) -> Option<(String, Option<String>, Option<f64>)> {
+    for media in &entry.media {
+        let content = media
+            .content
+            .iter()
+            .filter(|c| c.url.is_some())
+            .find(|c| c.content_type.as_ref().map_or(true, |m| m.type_() == "audio" || m.type_() == "video"));
+        if let Some(content) = content {
+            let duration = content.duration.or(media.duration).map(|d| d.as_secs_f64());
+            return Some((
+                content.url.as_ref()?.to_string(),
+                content.content_type.as_ref().map(|m| m.to_string()),
+                duration,
+            ));
+        }
+    }
+    // Atom enclosures are links
+    entry
+        .links
+        .iter()
+        .find(|l| l.rel.as_deref() == Some("enclosure"))
+        .map(|l| (l.href.clone(), l.media_type.clone(), None))
+}
+
+/// Podcast and episodes of the feed at `feed_url`. Entries without audio are
+/// left out.
+fn parse_feed(feed_url: &str, body: &[u8]) -> Result<(Podcast, Vec<PodcastEpisode>)> {
+    let feed = feed_rs::parser::parse(body).map_err(error_helpers::to_parse_error)?;
+    let podcast = Podcast {
+        feed_url: feed_url.to_string(),
+        title: text(feed.title).unwrap_or_else(|| feed_url.to_string()),
+        author: feed.authors.first().map(|a| a.name.clone()),
+        description: text(feed.description),
+        image_url: feed.logo.or(feed.icon).map(|i| i.uri),
+        link: feed
+            .links
+            .iter()
+            .find(|l| l.rel.as_deref().map_or(true, |rel| rel == "alternate"))
+            .map(|l| l.href.clone()),
+        ..Default::default()
+    };
+    let episodes = feed
+        .entries
+        .into_iter()
+        .filter_map(|entry| {
+            let (audio_url, mime_type, duration) = enclosure(&entry)?;
+            Some(PodcastEpisode {
+                guid: entry.id.clone(),
+                title: text(entry.title.clone()).unwrap_or_else(|| entry.id.clone()),
+                description: text(entry.summary.clone()),
+                audio_url,
+                mime_type,
+                duration,
+                published_at: entry.published.or(entry.updated).map(|d| d.timestamp_millis()),
+                ..Default::default()
+            })
+        })
+        .collect();
+    Ok((podcast, episodes))
+}
+
+async fn fetch_feed(feed_url: &str) -> Result<(Podcast, Vec<PodcastEpisode>)> {
+    let body = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(error_helpers::to_network_error)?
+        .get(feed_url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(error_helpers::to_network_error)?
+        .bytes()
+        .await
+        .map_err(error_helpers::to_network_error)?;
+    parse_feed(feed_url, &body)
+}
+
+/// Fetch the feed at `feed_url` and store the podcast and its episodes.
+/// Returns the podcast ID and the number of new episodes.
+async fn update_feed(app: &AppHandle, feed_url: &str) -> Result<(String, usize)> {
+    let (podcast, episodes) = fetch_feed(feed_url).await?;
+    let database = app.state::<Database>();
+    let podcast_id = database.upsert_podcast(&podcast, now_ms())?;
+    let added = database.upsert_episodes(&podcast_id, &episodes)?;
+    Ok((podcast_id, added))
+}
+
+/// Refresh every subscription, recording the errors on the podcasts.
+/// Returns the number of new episodes.
+async fn refresh_all(app: &AppHandle) -> Result<usize> {
+    let state = app.state::<PodcastState>();
+    let _refreshing = state.refreshing.lock().await;
+    let podcasts = app.state::<Database>().get_podcasts()?;
+    let mut total = 0;
+    for podcast in podcasts {
+        match update_feed(app, &podcast.feed_url).await {
+            Ok((podcast_id, added)) => {
+                total += added;
+                if added > 0 {
+                    let _ = app.emit("podcasts-updated", json!({ "podcastId": podcast_id, "added": added }));
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Failed to refresh podcast {}: {:?}", podcast.feed_url, e);
+                let _ = app.state::<Database>().set_podcast_error(&podcast.id, &e.to_string());
+            }
+        }
+    }
+    Ok(total)
+}
+
+fn refresh_interval(app: &AppHandle) -> Duration {
+    let secs: u64 = app
+        .state::<SettingsConfig>()
+        .load_selective("scan_interval".to_string())
+        .unwrap_or(DEFAULT_REFRESH_SECS);
+    Duration::from_secs(secs.max(MIN_REFRESH_SECS))
+}
+
+/// Refresh the subscriptions on the auto scanner interval for as long as the
+/// app runs. The interval is read again after each refresh.
+pub fn start_refresh(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(refresh_interval(&app)).await;
+            if let Err(e) = refresh_all(&app).await {
+                tracing::warn!("Podcast refresh failed: {:?}", e);
+            }
+        }
+    });
+}
+
+/// Stream of a podcast episode download: the enclosure itself
+pub fn episode_stream(app: &AppHandle, track_id: &str) -> Option<StreamSource> {
+    let episode = app.state::<Database>().get_episode(episode_id(track_id)?).ok()??;
+    let container = Path::new(episode.audio_url.split(['?', '#']).next().unwrap_or_default())
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase());
+    Some(StreamSource {
+        url: episode.audio_url,
+        mime_type: episode.mime_type,
+        container,
+        codec: None,
+        bitrate: None,
+        sample_rate: None,
+        channels: None,
+        protocol: None,
+        expires_at: None,
+        headers: None,
+        drm: None,
+    })
+}
+
+fn save_progress(app: &AppHandle, episode_id: &str, position: f64, duration: Option<f64>) {
+    let played = duration.is_some_and(|d| d > 0.0 && position >= d - PLAYED_MARGIN_SECS);
+    let position = if played { 0.0 } else { position };
+    if let Err(e) = app.state::<Database>().set_episode_progress(episode_id, position, played) {
+        tracing::warn!("Failed to save the position of episode {}: {:?}", episode_id, e);
+    }
+}
+
+/// Follow playback of `track_id` at `position` seconds: resume an episode
+/// where it was left off and save its position every `SAVE_INTERVAL`.
+pub fn on_time_update(app: &AppHandle, track_id: Option<&str>, position: f64) {
+    let Some(state) = app.try_state::<PodcastState>() else { return };
+    let Ok(mut progress) = state.progress.lock() else { return };
+    let current = track_id.and_then(episode_id);
+
+    if progress.episode_id.as_deref() != current {
+        // Switched away: keep where the previous episode stopped
+        if let Some(previous) = progress.episode_id.take() {
+            save_progress(app, &previous, progress.position, progress.duration);
+        }
+        *progress = Progress::default();
+        let Some(current) = current else { return };
+        let episode = app.state::<Database>().get_episode(current).ok().flatten();
+        progress.episode_id = Some(current.to_string());
+        progress.duration = episode.as_ref().and_then(|e| e.duration);
+        progress.last_save = Some(Instant::now());
+        let resume = episode
+            .filter(|e| !e.played && e.position >= MIN_RESUME_SECS)
+            .map(|e| e.position)
+            .filter(|_| position < MIN_RESUME_SECS);
+        if let Some(resume) = resume {
+            tracing::debug!("Resuming episode {} at {}s", current, resume);
+            progress.position = resume;
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let player: State<'_, AudioPlayer> = app.state();
+                if let Err(e) = player.audio_seek(resume).await {
+                    tracing::warn!("Failed to resume episode: {:?}", e);
+                }
+            });
+            return;
+        }
+    }
+
+    let Some(current) = current else { return };
+    progress.position = position;
+    if progress.last_save.map_or(true, |at| at.elapsed() >= SAVE_INTERVAL) {
+        progress.last_save = Some(Instant::now());
+        save_progress(app, current, position, progress.duration);
+    }
+}
+
+/// Playback paused: save the position of the playing episode now
+pub fn on_pause(app: &AppHandle) {
+    let Some(state) = app.try_state::<PodcastState>() else { return };
+    let Ok(mut progress) = state.progress.lock() else { return };
+    if let Some(episode_id) = progress.episode_id.clone() {
+        progress.last_save = Some(Instant::now());
+        save_progress(app, &episode_id, progress.position, progress.duration);
+    }
+}
+
+/// The playing entry ended: an episode is played to its end
+pub fn on_ended(app: &AppHandle) {
+    let Some(state) = app.try_state::<PodcastState>() else { return };
+    let Ok(mut progress) = state.progress.lock() else { return };
+    if let Some(episode_id) = progress.episode_id.take() {
+        if let Err(e) = app.state::<Database>().set_episode_progress(&episode_id, 0.0, true) {
+            tracing::warn!("Failed to mark episode {} played: {:?}", episode_id, e);
+        }
+    }
+    *progress = Progress::default();
+}
+
+fn find_episode(database: &Database, episode_id: &str) -> Result<(PodcastEpisode, Podcast)> {
+    let episode = database
+        .get_episode(episode_id)?
+        .ok_or_else(|| MusicError::String(format!("No episode {}", episode_id)))?;
+    let podcast = database
+        .get_podcast(&episode.podcast_id)?
+        .ok_or_else(|| MusicError::String(format!("No podcast {}", episode.podcast_id)))?;
+    Ok((episode, podcast))
+}
+
+command_envelope! {
+    /// Subscribe to the RSS or Atom feed at `feed_url` and load its episodes.
+    /// Subscribing again refreshes the podcast.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command]
+    pub async fn subscribe_podcast(app: AppHandle, feed_url: String) -> Result<Podcast> {
+        let feed_url = feed_url.trim().to_string();
+        if !feed_url.starts_with("http://") && !feed_url.starts_with("https://") {
+            return Err(MusicError::String(format!("Not a feed URL: {}", feed_url)));
+        }
+        let (podcast_id, _) = update_feed(&app, &feed_url).await?;
+        app.state::<Database>()
+            .get_podcast(&podcast_id)?
+            .ok_or_else(|| MusicError::String(format!("No podcast {}", podcast_id)))
+    }
+}
+
+command_envelope! {
+    /// Unsubscribe from a podcast. Downloaded episodes are kept.
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command]
+    pub fn unsubscribe_podcast(database: State<'_, Database>, podcast_id: String) -> Result<()> {
+        database.delete_podcast(&podcast_id)
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command]
+    pub fn list_podcasts(database: State<'_, Database>) -> Result<Vec<Podcast>> {
+        database.get_podcasts()
+    }
+}
+
+command_envelope! {
+    /// Refresh every subscription now. Returns the number of new episodes.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command]
+    pub async fn refresh_podcasts(app: AppHandle) -> Result<usize> {
+        refresh_all(&app).await
+    }
+}
+
+command_envelope! {
+    /// Episodes, newest first, of one podcast or of all of them. `unplayed`
+    /// leaves out the played ones.
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command]
+    pub fn list_episodes(
+        database: State<'_, Database>,
+        podcast_id: Option<String>,
+        unplayed: Option<bool>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<PodcastEpisode>> {
+        database.get_episodes(
+            podcast_id.as_deref(),
+            unplayed.unwrap_or(false),
+            limit.unwrap_or(DEFAULT_PAGE).max(1),
+            offset.unwrap_or(0).max(0),
+        )
+    }
+}
+
+command_envelope! {
+    /// Play an episode now or queue it as `action` says, from its download
+    /// when there is one. Playback resumes where the episode was left off.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command]
+    pub async fn play_episode(app: AppHandle, episode_id: String, action: Option<QueueAction>) -> Result<()> {
+        let database = app.state::<Database>();
+        let (episode, podcast) = find_episode(&database, &episode_id)?;
+        let local_path = database
+            .get_download(&episode.track_id())?
+            .map(|d| d.path)
+            .filter(|path| Path::new(path).exists());
+        let action = match action.unwrap_or_default() {
+            // Episodes have no related tracks to continue with
+            QueueAction::StartRadio => QueueAction::PlayNow,
+            action => action,
+        };
+        crate::audio::actions::apply_queue_action(&app, action, vec![episode.to_media_content(&podcast, local_path)]).await
+    }
+}
+
+command_envelope! {
+    /// Mark an episode played or unplayed, which also forgets its position.
+    #[tracing::instrument(level = "debug", skip(database))]
+    #[tauri::command]
+    pub fn set_episode_played(database: State<'_, Database>, episode_id: String, played: bool) -> Result<()> {
+        database.set_episode_progress(&episode_id, 0.0, played)
+    }
+}
+
+command_envelope! {
+    /// Queue a download of an episode with the download manager. Returns
+    /// false if it is already queued or downloaded.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command]
+    pub fn download_episode(app: AppHandle, episode_id: String) -> Result<bool> {
+        let database = app.state::<Database>();
+        let (episode, _) = find_episode(&database, &episode_id)?;
+        let track_id = episode.track_id();
+        let downloaded = database
+            .get_download(&track_id)?
+            .is_some_and(|d| Path::new(&d.path).exists());
+        Ok(!downloaded && queue_download(&app, &track_id, DownloadOrigin::Podcast))
+    }
+}
@@ -0,0 +1,110 @@
+//! Private (guest) sessions for shared computers: while active, plays are not
+//! recorded in the history nor scrobbled, and optionally everything recorded
+//! since the app started is purged when it exits.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use audio_player::AudioPlayer;
+use database::database::Database;
+use macros::command_envelope;
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager, State};
+use types::errors::{MusicError, Result};
+
+/// Private session state, managed by Tauri. Not persisted: every launch starts
+/// a regular session.
+pub struct PrivateSession {
+    active: AtomicBool,
+    purge_on_exit: AtomicBool,
+    /// App launch, in UTC; the purge removes history recorded after it
+    started_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrivateSessionStatus {
+    pub active: bool,
+    pub purge_on_exit: bool,
+}
+
+impl Default for PrivateSession {
+    fn default() -> Self {
+        Self {
+            active: AtomicBool::new(false),
+            purge_on_exit: AtomicBool::new(false),
+            started_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+}
+
+impl PrivateSession {
+    /// Whether history (plays, searches) and scrobbles are suspended
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    pub fn status(&self) -> PrivateSessionStatus {
+        PrivateSessionStatus {
+            active: self.is_active(),
+            purge_on_exit: self.purge_on_exit.load(Ordering::SeqCst),
+        }
+    }
+}
+
+/// Whether the current session is private; false before the state is managed.
+pub fn is_private(app: &AppHandle) -> bool {
+    app.try_state::<PrivateSession>().is_some_and(|s| s.is_active())
+}
+
+/// Purge the session's play history if requested. Called once on app exit.
+#[tracing::instrument(level = "debug", skip(app))]
+pub fn on_exit(app: &AppHandle) {
+    let Some(session) = app.try_state::<PrivateSession>() else { return };
+    if !session.purge_on_exit.load(Ordering::SeqCst) {
+        return;
+    }
+    let Some(database) = app.try_state::<Database>() else { return };
+    match database.purge_play_history_since(session.started_at) {
+        Ok(removed) => tracing::info!("Purged {} play history entries of this session", removed),
+        Err(e) => tracing::error!("Failed to purge session play history: {}", e),
+    }
+}
+
+command_envelope! {
+    /// Start or end a private session. While private, play history and
+    /// scrobbling are suspended (the UI should also stop recording searches);
+    /// `purge_on_exit` additionally clears the history recorded since launch
+    /// when the app quits. Emits `private-session-changed` with the new status.
+    #[tracing::instrument(level = "debug", skip(app, session, audio))]
+    #[tauri::command]
+    pub fn set_private_session(
+        app: AppHandle,
+        session: State<'_, PrivateSession>,
+        audio: State<'_, AudioPlayer>,
+        enabled: bool,
+        purge_on_exit: Option<bool>,
+    ) -> Result<PrivateSessionStatus> {
+        session.active.store(enabled, Ordering::SeqCst);
+        if let Some(purge) = purge_on_exit {
+            session.purge_on_exit.store(purge, Ordering::SeqCst);
+        }
+        audio
+            .get_store()
+            .lock()
+            .map_err(|_| MusicError::from("Failed to access player store"))?
+            .set_private_session(enabled);
+
+        let status = session.status();
+        tracing::info!("Private session {}", if enabled { "started" } else { "ended" });
+        let _ = app.emit("private-session-changed", json!(status));
+        Ok(status)
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(session))]
+    #[tauri::command]
+    pub fn get_private_session(session: State<'_, PrivateSession>) -> Result<PrivateSessionStatus> {
+        Ok(session.status())
+    }
+}
@@ -2,6 +2,7 @@ use std::sync::Arc;
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
+use macros::command_envelope;
 use providers::{
     factory,
     provider::base::{ProviderCapability, SearchResult, Song, ProviderStatus},
@@ -9,8 +10,9 @@ use providers::{
     router::{self, ProviderSelector},
 };
 use serde::{Deserialize, Serialize};
-use types::providers::ProviderSelectorArg;
-use tauri::{AppHandle, Emitter, State};
+use settings::settings::SettingsConfig;
+use types::providers::{ProviderInstancePref, ProviderSelectorArg};
+use tauri::{AppHandle, Emitter, Manager, State};
 use types::errors::Result;
 
 #[derive(Clone)]
@@ -37,6 +39,10 @@ impl ProviderHandler {
         let key = key.unwrap_or_else(|| name.clone());
         let cfg = cfg.unwrap_or(serde_json::json!({}));
         let p = factory::create(&name, key.clone(), cfg)?;
+        // A failed initialization (e.g. an expired session) leaves the instance logged out
+        if let Err(e) = p.initialize().await {
+            tracing::warn!("Provider instance {} initialized with errors: {:?}", key, e);
+        }
        self.reg.add(key, Arc::from(p)).await;
        // emit providers-updated and provider-status-update after adding
        let _ = self.app.emit("providers-updated", serde_json::Value::Null);
@@ -75,6 +81,39 @@ impl ProviderHandler {
        self.reg.remove(key).await.is_some()
    }
 
+   /// Log `key` in with a browser session cookie and store the session in the
+   /// instance's secure settings entry, creating the entry if needed.
+   pub async fn import_cookie(&self, key: &str, cookie: String) -> Result<ProviderStatus> {
+       self.ensure_supports(key, ProviderCapability::CookieAuth).await?;
+       let provider = self.reg.get(key).await.ok_or_else(|| format!("unknown provider '{}'", key))?;
+       let secret = provider.import_cookie(cookie).await?;
+
+       let pref: State<'_, SettingsConfig> = self.app.state();
+       let mut instances = pref
+           .load_selective::<Vec<ProviderInstancePref>>("providers.instances".into())
+           .unwrap_or_default();
+       if let Some(inst) = instances.iter_mut().find(|i| i.key == key) {
+           let secure_ref = inst.secure_ref.get_or_insert_with(|| format!("providers.secure.{}", key)).clone();
+           let mut stored = pref
+               .get_secure::<serde_json::Value>(secure_ref.clone())
+               .unwrap_or_else(|_| serde_json::json!({}));
+           match (stored.as_object_mut(), secret.as_object()) {
+               (Some(stored), Some(secret)) => stored.extend(secret.clone()),
+               _ => stored = secret,
+           }
+           pref.set_secure(secure_ref, Some(stored))?;
+           pref.save_selective("providers.instances".into(), Some(instances))?;
+       } else {
+           tracing::warn!("Provider instance {} has no settings entry, cookie login is not persisted", key);
+       }
+
+       let status = provider.get_status().await?;
+       if let Ok(statuses) = self.get_all_statuses().await {
+           let _ = self.app.emit("provider-status-update", statuses);
+       }
+       Ok(status)
+   }
+
    pub async fn get_all_statuses(&self) -> Result<Vec<ProviderStatus>> {
        let mut res = Vec::new();
        for key in self.reg.keys().await {
@@ -119,31 +158,53 @@ impl ProviderHandler {
 }
 
 
-#[tauri::command(async)]
-pub async fn provider_search(
-    handler: State<'_, ProviderHandler>,
-    selector: ProviderSelectorArg,
-    term: String,
-) -> Result<SearchResult> {
-    handler.search(selector, term).await
+command_envelope! {
+    #[tauri::command(async)]
+    pub async fn provider_search(
+        handler: State<'_, ProviderHandler>,
+        selector: ProviderSelectorArg,
+        term: String,
+    ) -> Result<SearchResult> {
+        handler.search(selector, term).await
+    }
+}
+
+command_envelope! {
+    #[tauri::command(async)]
+    pub async fn provider_playback_url(
+        handler: State<'_, ProviderHandler>,
+        selector: ProviderSelectorArg,
+        song: Song,
+        player: String,
+    ) -> Result<String> {
+        handler.playback_url(selector, song, player).await
+    }
 }
 
-#[tauri::command(async)]
-pub async fn provider_playback_url(
-    handler: State<'_, ProviderHandler>,
-    selector: ProviderSelectorArg,
-    song: Song,
-    player: String,
-) -> Result<String> {
-    handler.playback_url(selector, song, player).await
+command_envelope! {
+    #[tauri::command(async)]
+    pub async fn provider_list_keys(handler: State<'_, ProviderHandler>) -> Result<Vec<String>> {
+        Ok(handler.list_keys().await)
+    }
 }
 
-#[tauri::command(async)]
-pub async fn provider_list_keys(handler: State<'_, ProviderHandler>) -> Result<Vec<String>> {
-    Ok(handler.list_keys().await)
+command_envelope! {
+    /// Log a provider instance in with a cookie pasted from the browser (a
+    /// `Cookie` header value or a cookie extension's JSON export).
+    #[tracing::instrument(level = "debug", skip(handler, cookie))]
+    #[tauri::command(async)]
+    pub async fn provider_import_cookie(
+        handler: State<'_, ProviderHandler>,
+        key: String,
+        cookie: String,
+    ) -> Result<ProviderStatus> {
+        handler.import_cookie(&key, cookie).await
+    }
 }
 
-#[tauri::command(async)]
-pub async fn provider_list_statuses(handler: State<'_, ProviderHandler>) -> Result<Vec<ProviderStatus>> {
-    handler.get_all_statuses().await
+command_envelope! {
+    #[tauri::command(async)]
+    pub async fn provider_list_statuses(handler: State<'_, ProviderHandler>) -> Result<Vec<ProviderStatus>> {
+        handler.get_all_statuses().await
+    }
 }
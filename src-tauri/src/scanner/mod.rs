@@ -7,12 +7,35 @@ use std::{
 
 // use crossbeam_channel::{Receiver, Sender};
 use database::database::Database;
-use file_scanner::{AutoScanner, AutoScannerConfig, ScanResult, ScannerHolder};
+use file_scanner::{
+    AutoScanner, AutoScannerConfig, FilenamePatterns, ScanExcludes, ScanExtensions, ScanProgress, ScanResult, ScanSchedule,
+    ScannerHolder, WalkPolicy,
+};
+use macros::command_envelope;
+use crate::diagnostics::watchdog::{Heartbeat, Probe};
+use serde::Serialize;
 use settings::settings::SettingsConfig;
 use tauri::{AppHandle, Manager, State, Emitter};
-use types::{errors::Result, tracks::MediaContent};
-use std::time::{SystemTime, UNIX_EPOCH};
-use std::sync::atomic::{AtomicU64, Ordering};
+use types::{
+    entities::{LibraryStorageReport, LyricsSearchHit, ScanEstimate},
+    errors::{CommandResponse, MusicError, Result},
+    settings::general::{ScanExtension, ScanPatternOverride, ScanRootOverride},
+    tracks::MediaContent,
+};
+
+mod retry;
+
+pub use retry::{start_retry_worker, FailedScanItem, ScanRetryQueue};
+
+/// Most lyrics search hits returned at once
+const LYRICS_SEARCH_LIMIT: u32 = 100;
+
+/// State of the auto scanner with the progress of its latest scan
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoScannerStatus {
+    pub state: String,
+    pub progress: Option<ScanProgress>,
+}
 
 #[tracing::instrument(level = "debug", skip())]
 pub fn get_scanner_state() -> ScannerHolder {
@@ -26,12 +49,96 @@ fn get_scan_paths(settings: &State<SettingsConfig>) -> Result<Vec<String>> {
     Ok(tmp)
 }
 
+/// Scheduled scan windows (general.scan_windows) and catch-up policy
+fn load_scan_schedule(settings: &SettingsConfig) -> ScanSchedule {
+    ScanSchedule {
+        windows: settings
+            .load_selective("general.scan_windows".to_string())
+            .unwrap_or_default(),
+        catch_up: settings
+            .load_selective("general.scan_catch_up".to_string())
+            .unwrap_or(true),
+    }
+}
+
+/// Scan depth limit (general.scan_max_depth), symlink policy
+/// (general.scan_symlinks) and their per-folder overrides
+fn load_walk_policy(settings: &SettingsConfig) -> WalkPolicy {
+    let max_depth: Option<u32> = settings
+        .load_selective("general.scan_max_depth".to_string())
+        .unwrap_or(None);
+    let symlinks = settings
+        .load_selective("general.scan_symlinks".to_string())
+        .unwrap_or(None);
+    let overrides: Vec<ScanRootOverride> = settings
+        .load_selective("general.scan_root_overrides".to_string())
+        .unwrap_or_default();
+    WalkPolicy::new(max_depth, symlinks, &overrides)
+}
+
+/// Excluded folders (exclude_music_paths) and wildcard patterns
+/// (general.scan_exclude_patterns)
+fn load_scan_excludes(settings: &SettingsConfig) -> ScanExcludes {
+    let paths: Vec<String> = settings
+        .load_selective("exclude_music_paths".to_string())
+        .unwrap_or_default();
+    let patterns: Vec<String> = settings
+        .load_selective("general.scan_exclude_patterns".to_string())
+        .unwrap_or_default();
+    ScanExcludes::new(paths.into_iter().map(PathBuf::from).collect(), &patterns)
+}
+
+/// Tag inference from file names (general.scan_filename_inference), the
+/// patterns to try (general.scan_filename_patterns) and per-folder patterns
+fn load_filename_patterns(settings: &SettingsConfig) -> FilenamePatterns {
+    let enabled: bool = settings
+        .load_selective("general.scan_filename_inference".to_string())
+        .unwrap_or(true);
+    let patterns: Option<Vec<String>> = settings
+        .load_selective("general.scan_filename_patterns".to_string())
+        .unwrap_or(None);
+    let overrides: Vec<ScanPatternOverride> = settings
+        .load_selective("general.scan_filename_overrides".to_string())
+        .unwrap_or_default();
+    FilenamePatterns::new(enabled, patterns.as_deref(), &overrides)
+}
+
+/// Directory the covers of scanned files are stored in (artwork_path), the
+/// thumbnail directory for preferences saved before it existed
+pub fn artwork_dir(settings: &SettingsConfig) -> String {
+    settings
+        .load_selective::<String>("artwork_path".to_string())
+        .or_else(|_| settings.load_selective::<String>("thumbnail_path".to_string()))
+        .unwrap_or_else(|_| "artwork".to_string())
+}
+
+/// Legacy format rule (general.scan_formats)
+fn load_scan_formats(settings: &SettingsConfig) -> String {
+    settings
+        .load_selective("general.scan_formats".to_string())
+        .unwrap_or_else(|_| "common".to_string())
+}
+
+/// File extensions to scan (general.scan_extensions), derived from the legacy
+/// format rule until the user edits them
+fn load_scan_extensions(settings: &SettingsConfig) -> ScanExtensions {
+    let entries: Option<Vec<ScanExtension>> = settings
+        .load_selective("general.scan_extensions".to_string())
+        .unwrap_or(None);
+    match entries {
+        Some(entries) => ScanExtensions::from_settings(&entries),
+        None => ScanExtensions::from_legacy(&load_scan_formats(settings)),
+    }
+}
+
 /// auto scanner task manager
 /// support new auto scanner and old scanner (backward compatibility)
 #[derive(Default)]
 pub struct ScanTask {
     cancellation_token: Mutex<Option<Arc<AtomicBool>>>,
     auto_scanner: Mutex<Option<AutoScanner>>,
+    /// Liveness of the thread storing the auto scanner's results
+    results: Mutex<Option<Heartbeat>>,
 }
 
 impl ScanTask {
@@ -57,9 +164,6 @@ impl ScanTask {
             let scan_paths: Vec<String> = settings
                 .load_selective("music_paths".to_string())
                 .unwrap_or_default();
-            let exclude_paths: Vec<String> = settings
-                .load_selective("exclude_music_paths".to_string())
-                .unwrap_or_default();
             let thumbnail_dir: String = settings
                 .load_selective("thumbnail_path".to_string())
                 .unwrap_or_else(|_| "thumbnails".to_string());
@@ -75,21 +179,26 @@ impl ScanTask {
             let scan_min_duration: String = settings
                 .load_selective("general.scan_min_duration".to_string())
                 .unwrap_or_else(|_| "sec30".to_string());
-            let scan_formats: String = settings
-                .load_selective("general.scan_formats".to_string())
-                .unwrap_or_else(|_| "common".to_string());
+            let fs_debounce_ms: u64 = settings
+                .load_selective("general.scan_debounce_ms".to_string())
+                .unwrap_or(2000);
 
             let cfg = AutoScannerConfig {
                 scan_paths: scan_paths.into_iter().map(PathBuf::from).collect(),
-                exclude_paths: exclude_paths.into_iter().map(PathBuf::from).collect(),
+                excludes: load_scan_excludes(&settings),
                 scan_interval,
+                schedule: load_scan_schedule(&settings),
                 enable_fs_watch: true,
                 enable_scheduled_scan: true,
                 scan_threads: if scan_threads <= 0.0 { num_cpus::get() } else { scan_threads as usize },
                 thumbnail_dir: PathBuf::from(thumbnail_dir),
+                artwork_dir: PathBuf::from(artwork_dir(&settings)),
                 artist_splitter,
                 scan_min_duration,
-                scan_formats,
+                scan_extensions: load_scan_extensions(&settings),
+                fs_debounce_ms,
+                walk: load_walk_policy(&settings),
+                filename_patterns: load_filename_patterns(&settings),
             };
 
             scanner.update_config(cfg)?;
@@ -107,10 +216,6 @@ impl ScanTask {
             .load_selective("music_paths".to_string())
             .unwrap_or_default();
             
-        let exclude_paths: Vec<String> = settings
-            .load_selective("exclude_music_paths".to_string())
-            .unwrap_or_default();
-            
         let thumbnail_dir: String = settings
             .load_selective("thumbnail_path".to_string())
             .unwrap_or_else(|_| "thumbnails".to_string());
@@ -132,15 +237,16 @@ impl ScanTask {
             .load_selective("general.scan_min_duration".to_string())
             .unwrap_or_else(|_| "sec30".to_string());
             
-        let scan_formats: String = settings
-            .load_selective("general.scan_formats".to_string())
-            .unwrap_or_else(|_| "common".to_string());
+        let fs_debounce_ms: u64 = settings
+            .load_selective("general.scan_debounce_ms".to_string())
+            .unwrap_or(2000);
 
         // create config
         let config = AutoScannerConfig {
             scan_paths: scan_paths.into_iter().map(PathBuf::from).collect(),
-            exclude_paths: exclude_paths.into_iter().map(PathBuf::from).collect(),
+            excludes: load_scan_excludes(&settings),
             scan_interval,
+            schedule: load_scan_schedule(&settings),
             enable_fs_watch: true,
             enable_scheduled_scan: true,
             scan_threads: if scan_threads <= 0.0 {
@@ -149,9 +255,13 @@ impl ScanTask {
                 scan_threads as usize
             },
             thumbnail_dir: PathBuf::from(thumbnail_dir),
+            artwork_dir: PathBuf::from(artwork_dir(&settings)),
             artist_splitter,
             scan_min_duration,
-            scan_formats,
+            scan_extensions: load_scan_extensions(&settings),
+            fs_debounce_ms,
+            walk: load_walk_policy(&settings),
+            filename_patterns: load_filename_patterns(&settings),
         };
 
         // create auto scanner
@@ -163,8 +273,13 @@ impl ScanTask {
         
         // start result handler thread
         let app_handle = app.clone();
+        let heartbeat = Heartbeat::default();
+        let running = heartbeat.start();
+        *self.results.lock().unwrap() = Some(heartbeat.clone());
         thread::spawn(move || {
+            let _running = running;
             for scan_result in result_rx {
+                let _busy = heartbeat.busy();
                 if let Err(e) = handle_scan_result(&app_handle, scan_result) {
                     tracing::error!("Failed to handle scan result: {}", e);
                 }
@@ -175,10 +290,13 @@ impl ScanTask {
         auto_scanner.start().await?;
         
         // store scanner instance
-        let mut scanner_lock = self.auto_scanner.lock().unwrap();
-        *scanner_lock = Some(auto_scanner);
+        {
+            let mut scanner_lock = self.auto_scanner.lock().unwrap();
+            *scanner_lock = Some(auto_scanner);
+        }
         
         tracing::info!("Auto scanner initialized successfully");
+        crate::tasks::resume_interrupted_scans(app);
         Ok(())
     }
 
@@ -187,6 +305,7 @@ impl ScanTask {
             let mut scanner_lock = self.auto_scanner.lock().unwrap();
             scanner_lock.take()
         };
+        *self.results.lock().unwrap() = None;
         
         if let Some(scanner) = scanner {
             scanner.stop().await;
@@ -194,11 +313,48 @@ impl ScanTask {
         tracing::info!("Auto scanner stopped");
     }
 
-    /// trigger auto scan
-    pub fn trigger_auto_scan(&self, paths: Option<Vec<PathBuf>>) -> Result<()> {
+    /// trigger auto scan, returning the token that cancels it
+    pub fn trigger_auto_scan(&self, paths: Option<Vec<PathBuf>>) -> Result<String> {
+        let scanner_lock = self.auto_scanner.lock().unwrap();
+        if let Some(scanner) = scanner_lock.as_ref() {
+            scanner.trigger_scan(paths)
+        } else {
+            Err("Auto scanner not initialized".into())
+        }
+    }
+
+    /// cancel the auto scan of `token`, or the running one
+    pub fn cancel_auto_scan(&self, token: Option<&str>) -> bool {
+        self.auto_scanner
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|scanner| scanner.cancel_scan(token))
+    }
+
+    /// progress of the latest auto scan
+    pub fn auto_scan_progress(&self) -> Option<ScanProgress> {
+        self.auto_scanner.lock().unwrap().as_ref()?.progress()
+    }
+
+    /// count tracks of the scan `job_id` as stored, returning the updated progress
+    fn record_scan_written(&self, job_id: &str, count: usize) -> Option<ScanProgress> {
+        self.auto_scanner.lock().unwrap().as_ref()?.record_written(job_id, count)
+    }
+
+    /// Keep the auto scanner from rescanning files the app writes itself
+    /// (tag edits); call before and after writing
+    pub fn suppress_rescan(&self, paths: &[PathBuf]) {
+        if let Some(scanner) = self.auto_scanner.lock().unwrap().as_ref() {
+            scanner.suppress_rescan(paths);
+        }
+    }
+
+    /// resume an interrupted scan from its journaled checkpoint
+    pub fn resume_auto_scan(&self, checkpoint: file_scanner::ScanCheckpoint) -> Result<()> {
         let scanner_lock = self.auto_scanner.lock().unwrap();
         if let Some(scanner) = scanner_lock.as_ref() {
-            scanner.trigger_scan(paths)?;
+            scanner.resume_scan(checkpoint)?;
             Ok(())
         } else {
             Err("Auto scanner not initialized".into())
@@ -215,6 +371,20 @@ impl ScanTask {
         }
     }
 
+    /// Liveness of the auto scanner for the watchdog, `None` while it is off
+    pub fn auto_scanner_health(&self, now: i64) -> Option<Probe> {
+        let scanner_lock = self.auto_scanner.lock().unwrap();
+        let scanner = scanner_lock.as_ref()?;
+        if !scanner.is_healthy() {
+            return Some(Probe::failed("scan event loop exited"));
+        }
+        let results = self.results.lock().unwrap();
+        Some(match results.as_ref() {
+            Some(heartbeat) => heartbeat.probe("scan result handler", now),
+            None => Probe::healthy(),
+        })
+    }
+
     /// spawn scan task
     pub fn spawn_scan_task(&self, app: AppHandle, scan_duration_s: u64) {
         {
@@ -237,7 +407,7 @@ impl ScanTask {
             }
 
             let app = app.clone();
-            let res = start_scan(app, None);
+            let res = start_scan(app, None).and_then(CommandResponse::into_result);
             if let Err(e) = res {
                 tracing::error!("Legacy scan failed: {:?}", e);
             }
@@ -251,32 +421,43 @@ impl ScanTask {
 /// handle scan result
 fn handle_scan_result(app: &AppHandle, result: ScanResult) -> Result<()> {
     let database = app.state::<Database>();
-    
-    // emit scan progress event（节流：<= 每 250ms 一次）
-    static LAST_PROGRESS_EMIT_MS: AtomicU64 = AtomicU64::new(0);
-    let now_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
-    let last = LAST_PROGRESS_EMIT_MS.load(Ordering::Relaxed);
-    if now_ms.saturating_sub(last) >= 250 {
-        let progress_info = serde_json::json!({
-            "tracks_count": result.tracks.len(),
-            "playlists_count": result.playlists.len(),
-            "deleted_files_count": result.deleted_files.len()
-        });
 
-        if let Err(e) = app.emit("scan-progress", progress_info) {
-            tracing::warn!("Failed to emit scan progress event: {}", e);
-        }
-        LAST_PROGRESS_EMIT_MS.store(now_ms, Ordering::Relaxed);
+    // per-phase progress of the scan, throttled by the scanner
+    if let Some(progress) = &result.progress {
+        emit_scan_progress(app, progress);
     }
     
     // handle new/modified tracks
     if !result.tracks.is_empty() {
         tracing::info!("Processing {} scanned tracks", result.tracks.len());
-        database.insert_tracks(result.tracks.clone())?;
-        
-        // emit tracks-added event
-        if let Err(e) = app.emit("tracks-added", result.tracks.len()) {
-            tracing::warn!("Failed to emit tracks-added event: {}", e);
+        match database.insert_tracks(result.tracks.clone()) {
+            Ok(inserted) => {
+                // Loudness analysis for normalization, keyed by the stored ids
+                crate::audio::gain::cache_track_gains(&database, &inserted);
+                // Chapters of audiobooks and long mixes
+                crate::audiobooks::cache_chapters(&database, &inserted);
+                // Mood tagging decodes the files, so it runs in the background
+                crate::audio::mood::spawn_auto_classify(app);
+                // Covers of the new tracks missing one, when enabled
+                crate::metadata::spawn_auto_enrich(app);
+                // Fingerprints of the new tracks, when enabled
+                crate::metadata::fingerprint::spawn_auto_fingerprint(app);
+                // Silence at the ends of the new tracks, when trimming is on
+                crate::audio::silence::spawn_auto_detect(app);
+                // emit tracks-added event
+                if let Err(e) = app.emit("tracks-added", result.tracks.len()) {
+                    tracing::warn!("Failed to emit tracks-added event: {}", e);
+                }
+            }
+            // The batch is kept for a retry, so the checkpoint below may move on
+            Err(e) => app.state::<ScanRetryQueue>().push(result.tracks.clone(), &e),
+        }
+
+        // stored or queued for a retry, the batch is done with
+        if let Some(checkpoint) = &result.checkpoint {
+            if let Some(progress) = app.state::<ScanTask>().record_scan_written(&checkpoint.job_id, result.tracks.len()) {
+                emit_scan_progress(app, &progress);
+            }
         }
     }
     
@@ -305,92 +486,305 @@ fn handle_scan_result(app: &AppHandle, result: ScanResult) -> Result<()> {
                     .filter_map(|s| s.track._id)
                     .collect();
                 
+                // A file that moved or was ripped again keeps its history
+                for track_id in &track_ids {
+                    crate::metadata::fingerprint::relink_removed_track(app, &database, track_id);
+                }
                 if !track_ids.is_empty() {
                     let _ = database.remove_tracks(track_ids);
                 }
             }
         }
     }
+
+    // network shares and drives that went offline or came back
+    update_root_availability(app, &database, &result.available_roots, &result.unavailable_roots);
+
+    // journal scan progress only after this batch has been committed
+    if let Some(checkpoint) = result.checkpoint {
+        journal_scan_checkpoint(app, &checkpoint);
+    }
     
     Ok(())
 }
 
-#[tracing::instrument(level = "debug", skip(app))]
-#[tauri_invoke_proc::parse_tauri_command]
-#[tauri::command(async)]
-pub async fn start_auto_scanner(app: AppHandle) -> Result<()> {
-    let scan_task = app.state::<ScanTask>();
-    scan_task.initialize_auto_scanner(&app).await?;
-    Ok(())
+fn emit_scan_progress(app: &AppHandle, progress: &ScanProgress) {
+    if let Err(e) = app.emit("scan-progress", progress) {
+        tracing::warn!("Failed to emit scan progress event: {}", e);
+    }
 }
 
-#[tracing::instrument(level = "debug", skip(app))]
-#[tauri_invoke_proc::parse_tauri_command]
-#[tauri::command(async)]
-pub async fn stop_auto_scanner(app: AppHandle) -> Result<()> {
-    let scan_task = app.state::<ScanTask>();
-    scan_task.stop_auto_scanner().await;
-    Ok(())
+/// Record the reachability of the scan roots. Tracks below an unreachable root
+/// are kept, and `library-availability` is emitted whenever a root changes.
+fn update_root_availability(app: &AppHandle, database: &Database, available: &[PathBuf], unavailable: &[PathBuf]) {
+    let roots = available
+        .iter()
+        .map(|root| (root, true))
+        .chain(unavailable.iter().map(|root| (root, false)));
+    for (root, available) in roots {
+        match database.set_root_availability(root, available) {
+            Ok(true) => {
+                tracing::info!("Scan root {:?} is now {}", root, if available { "available" } else { "unavailable" });
+                let payload = serde_json::json!({
+                    "root": root.to_string_lossy(),
+                    "available": available,
+                });
+                if let Err(e) = app.emit("library-availability", payload) {
+                    tracing::warn!("Failed to emit library-availability event: {}", e);
+                }
+            }
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Failed to record availability of {:?}: {:?}", root, e),
+        }
+    }
 }
 
-#[tracing::instrument(level = "debug", skip(app))]
-#[tauri_invoke_proc::parse_tauri_command]
-#[tauri::command(async)]
-pub async fn trigger_manual_scan(app: AppHandle, paths: Option<Vec<String>>) -> Result<()> {
-    let scan_task = app.state::<ScanTask>();
-    let path_bufs = paths.map(|p| p.into_iter().map(PathBuf::from).collect());
-    scan_task.trigger_auto_scan(path_bufs)?;
-    Ok(())
+fn journal_scan_checkpoint(app: &AppHandle, checkpoint: &file_scanner::ScanCheckpoint) {
+    use crate::tasks::{TaskKind, TaskManager};
+    use file_scanner::ScanPhase;
+
+    let tasks = app.state::<TaskManager>();
+    let id = checkpoint.job_id.as_str();
+    let res = match checkpoint.phase {
+        ScanPhase::Walking => tasks.begin(id, TaskKind::Scan, checkpoint),
+        ScanPhase::Scanning => tasks.checkpoint(id, checkpoint, false),
+        ScanPhase::Done => tasks.complete(id),
+        ScanPhase::Failed => tasks.fail(id, "Scan failed"),
+        ScanPhase::Cancelled => tasks.cancel(id),
+    };
+    if let Err(e) = res {
+        tracing::warn!("Failed to journal scan checkpoint: {:?}", e);
+    }
+
+    let _ = app.emit(
+        "scan-checkpoint",
+        serde_json::json!({
+            "phase": checkpoint.phase,
+            "processed": checkpoint.processed,
+            "total": checkpoint.total,
+        }),
+    );
 }
 
-#[tracing::instrument(level = "debug", skip(app))]
-#[tauri_invoke_proc::parse_tauri_command]
-#[tauri::command(async)]
-pub async fn get_auto_scanner_status(app: AppHandle) -> Result<String> {
-    let scan_task = app.state::<ScanTask>();
-    if let Some(state) = scan_task.get_auto_scanner_state() {
-        Ok(format!("{:?}", state))
-    } else {
-        Ok("Not initialized".to_string())
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri_invoke_proc::parse_tauri_command]
+    #[tauri::command(async)]
+    pub async fn start_auto_scanner(app: AppHandle) -> Result<()> {
+        let scan_task = app.state::<ScanTask>();
+        scan_task.initialize_auto_scanner(&app).await?;
+        Ok(())
     }
 }
 
-#[tracing::instrument(level = "debug", skip(app))]
-#[tauri_invoke_proc::parse_tauri_command]
-#[tauri::command(async)]
-pub async fn get_local_tracks(app: AppHandle) -> Result<Vec<MediaContent>> {
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri_invoke_proc::parse_tauri_command]
+    #[tauri::command(async)]
+    pub async fn stop_auto_scanner(app: AppHandle) -> Result<()> {
+        let scan_task = app.state::<ScanTask>();
+        scan_task.stop_auto_scanner().await;
+        Ok(())
+    }
+}
 
-    let database = match app.try_state::<Database>() {
-        Some(db) => db,
-        None => {
-            tracing::error!("database not initialized");
-            return Ok(vec![]);
-        }
-    };
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri_invoke_proc::parse_tauri_command]
+    #[tauri::command(async)]
+    pub async fn trigger_manual_scan(app: AppHandle, paths: Option<Vec<String>>) -> Result<String> {
+        let scan_task = app.state::<ScanTask>();
+        let path_bufs = paths.map(|p| p.into_iter().map(PathBuf::from).collect());
+        scan_task.trigger_auto_scan(path_bufs)
+    }
+}
+
+command_envelope! {
+    /// Cancel the scan of the token returned by `trigger_manual_scan`, or the
+    /// running scan without one. Tracks already stored are kept.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri_invoke_proc::parse_tauri_command]
+    #[tauri::command(async)]
+    pub async fn cancel_scan(app: AppHandle, token: Option<String>) -> Result<bool> {
+        let scan_task = app.state::<ScanTask>();
+        Ok(scan_task.cancel_auto_scan(token.as_deref()))
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri_invoke_proc::parse_tauri_command]
+    #[tauri::command(async)]
+    pub async fn get_auto_scanner_status(app: AppHandle) -> Result<AutoScannerStatus> {
+        let scan_task = app.state::<ScanTask>();
+        let state = if let Some(state) = scan_task.get_auto_scanner_state() {
+            format!("{:?}", state)
+        } else {
+            "Not initialized".to_string()
+        };
+        Ok(AutoScannerStatus {
+            state,
+            progress: scan_task.auto_scan_progress(),
+        })
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri_invoke_proc::parse_tauri_command]
+    #[tauri::command(async)]
+    pub async fn get_local_tracks(app: AppHandle) -> Result<Vec<MediaContent>> {
+
+        let database = match app.try_state::<Database>() {
+            Some(db) => db,
+            None => {
+                tracing::error!("database not initialized");
+                return Ok(vec![]);
+            }
+        };
     
-    match database.get_tracks_by_options(types::tracks::GetTrackOptions {
-        track: Some(types::tracks::SearchableTrack {
-            path: Some("%".to_string()),
-            type_: Some(types::tracks::TrackType::LOCAL),
+        match database.get_tracks_by_options(types::tracks::GetTrackOptions {
+            track: Some(types::tracks::SearchableTrack {
+                path: Some("%".to_string()),
+                type_: Some(types::tracks::TrackType::LOCAL),
+                ..Default::default()
+            }),
             ..Default::default()
-        }),
-        ..Default::default()
-    }) {
-        Ok(tracks) => {
-            Ok(tracks)
-        },
-        Err(e) => {
-            tracing::error!("Failed to get local tracks: {}", e);
-            Ok(vec![])
+        }) {
+            Ok(tracks) => {
+                Ok(tracks)
+            },
+            Err(e) => {
+                tracing::error!("Failed to get local tracks: {}", e);
+                Ok(vec![])
+            }
         }
     }
 }
 
-#[tracing::instrument(level = "debug", skip(app, paths))]
-#[tauri_invoke_proc::parse_tauri_command]
-#[tauri::command(async)]
-pub fn start_scan(app: AppHandle, paths: Option<Vec<String>>) -> Result<()> {
-    start_scan_inner(app, paths)
+command_envelope! {
+    /// Search the local library by title/album/artist. CJK names also match by
+    /// pinyin/romaji (full or initials, e.g. "zjl"); results follow the UI language collation.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri_invoke_proc::parse_tauri_command]
+    #[tauri::command(async)]
+    pub async fn search_local_library(app: AppHandle, term: String) -> Result<types::entities::LibrarySearchResult> {
+        let database = app.state::<Database>();
+        let locale: Option<String> = app
+            .state::<SettingsConfig>()
+            .load_selective("general.language".to_string())
+            .ok();
+        let mut result = database.search_library(&term, locale.as_deref())?;
+        let display = app.state::<crate::display::DisplayService>();
+        result.display = result
+            .tracks
+            .iter()
+            .filter_map(|t| {
+                t.track._id.clone().map(|id| {
+                    (id, display.format(types::settings::display::DisplayView::TrackList, t))
+                })
+            })
+            .collect();
+        Ok(result)
+    }
+}
+
+command_envelope! {
+    /// Find tracks by a phrase from their lyrics ("that song that goes…"). Each
+    /// hit carries the matching line with the phrase range to highlight and,
+    /// for time-synced lyrics, the line's timestamp.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri_invoke_proc::parse_tauri_command]
+    #[tauri::command(async)]
+    pub async fn search_lyrics_library(
+        app: AppHandle,
+        phrase: String,
+        limit: Option<u32>,
+    ) -> Result<Vec<LyricsSearchHit>> {
+        let database = app.state::<Database>();
+        database.search_lyrics(&phrase, limit.unwrap_or(LYRICS_SEARCH_LIMIT).min(LYRICS_SEARCH_LIMIT) as i64)
+    }
+}
+
+command_envelope! {
+    /// File extensions the scanner considers, with their toggles. Until edited
+    /// these are all known formats, enabled as the legacy format rule selects.
+    #[tracing::instrument(level = "debug", skip(settings))]
+    #[tauri::command]
+    pub fn get_scan_extensions(settings: State<'_, SettingsConfig>) -> Result<Vec<ScanExtension>> {
+        let entries: Option<Vec<ScanExtension>> = settings
+            .load_selective("general.scan_extensions".to_string())
+            .unwrap_or(None);
+        Ok(entries.unwrap_or_else(|| ScanExtensions::default_entries(&load_scan_formats(&settings))))
+    }
+}
+
+command_envelope! {
+    /// Replace the scanned file extensions. Extensions are stored lowercase
+    /// without the dot, duplicates dropped; any invalid one fails the save.
+    /// Returns the list as stored.
+    #[tracing::instrument(level = "debug", skip(settings))]
+    #[tauri::command]
+    pub fn set_scan_extensions(
+        settings: State<'_, SettingsConfig>,
+        extensions: Vec<ScanExtension>,
+    ) -> Result<Vec<ScanExtension>> {
+        let extensions = file_scanner::validate_extensions(&extensions)
+            .map_err(|invalid| MusicError::String(format!("Invalid file extensions: {}", invalid.join(", "))))?;
+        settings.save_selective("general.scanExtensions".to_string(), Some(extensions.clone()))?;
+        Ok(extensions)
+    }
+}
+
+command_envelope! {
+    /// Count the tracks and playlists under `paths` and their size per format,
+    /// without reading tags, so the user can review folders before scanning them.
+    #[tracing::instrument(level = "debug", skip(paths))]
+    #[tauri_invoke_proc::parse_tauri_command]
+    #[tauri::command(async)]
+    pub async fn estimate_scan(paths: Vec<String>) -> Result<ScanEstimate> {
+        let paths: Vec<PathBuf> = paths.into_iter().map(PathBuf::from).collect();
+        tauri::async_runtime::spawn_blocking(move || file_scanner::estimate_scan(&paths))
+            .await
+            .map_err(|e| MusicError::String(e.to_string()))
+    }
+}
+
+command_envelope! {
+    /// Disk usage of the local library, the playback cache, artwork and offline downloads.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri_invoke_proc::parse_tauri_command]
+    #[tauri::command(async)]
+    pub async fn get_library_storage_report(app: AppHandle) -> Result<LibraryStorageReport> {
+        let (library_tracks, library_bytes) = app.state::<Database>().get_local_library_size()?;
+
+        let settings = app.state::<SettingsConfig>();
+        let artwork_dirs: Vec<PathBuf> = ["thumbnail_path", "artwork_path"]
+            .into_iter()
+            .filter_map(|key| settings.load_selective::<PathBuf>(key.to_string()).ok())
+            .collect();
+        let cache_dir = app.path().app_cache_dir().ok();
+        let downloads_dir = app.state::<crate::downloads::DownloadQueue>().dir().to_path_buf();
+
+        tauri::async_runtime::spawn_blocking(move || LibraryStorageReport {
+            library_bytes,
+            library_tracks,
+            cache_bytes: cache_dir.as_deref().map(file_scanner::dir_size).unwrap_or(0),
+            artwork_bytes: artwork_dirs.iter().map(|d| file_scanner::dir_size(d)).sum(),
+            downloads_bytes: file_scanner::dir_size(&downloads_dir),
+        })
+        .await
+        .map_err(|e| MusicError::String(e.to_string()))
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(app, paths))]
+    #[tauri_invoke_proc::parse_tauri_command]
+    #[tauri::command(async)]
+    pub fn start_scan(app: AppHandle, paths: Option<Vec<String>>) -> Result<()> {
+        start_scan_inner(app, paths)
+    }
 }
 
 #[cfg(desktop)]
@@ -400,8 +794,8 @@ pub fn start_scan_inner(app: AppHandle, mut paths: Option<Vec<String>>) -> Resul
         paths = Some(get_scan_paths(&settings)?);
     }
 
-    let thumbnail_dir: String = settings.load_selective("thumbnail_path".to_string())?;
-    tracing::debug!("Got thumbnail dir {:?}", thumbnail_dir);
+    let artwork_dir = artwork_dir(&settings);
+    tracing::debug!("Got artwork dir {:?}", artwork_dir);
 
     let artist_split: String = settings
         .load_selective("artist_splitter".to_string())
@@ -411,6 +805,8 @@ pub fn start_scan_inner(app: AppHandle, mut paths: Option<Vec<String>>) -> Resul
         .load_selective("scan_threads".to_string())
         .unwrap_or(-1f64);
 
+    let excludes = load_scan_excludes(&settings);
+
     for path in paths.unwrap() {
         tracing::info!("Scanning path: {}", path);
 
@@ -428,7 +824,10 @@ pub fn start_scan_inner(app: AppHandle, mut paths: Option<Vec<String>>) -> Resul
             }
 
             for (playlist_id, tracks) in track_rx {
-                let res = database.insert_tracks(tracks);
+                let res = database.insert_tracks(tracks.clone());
+                if let Err(e) = &res {
+                    app.state::<ScanRetryQueue>().push(tracks, e);
+                }
                 if let Ok(res) = res {
                     if let Some(playlist_id) = playlist_id.as_ref() {
                         for track in res {
@@ -445,8 +844,9 @@ pub fn start_scan_inner(app: AppHandle, mut paths: Option<Vec<String>>) -> Resul
         let scanner = app.state::<ScannerHolder>();
         scanner.start_scan(
             path,
-            thumbnail_dir.clone(),
+            artwork_dir.clone(),
             artist_split.clone(),
+            excludes.clone(),
             scan_threads,
             track_tx,
             playlist_tx,
@@ -467,7 +867,29 @@ pub fn start_scan_inner(app: AppHandle, mut paths: Option<Vec<String>>) -> Resul
     tracing::debug!("Got scanned tracks {:?}", res);
 
     let database = app.state::<Database>();
-    database.insert_tracks(res)?;
+    if let Err(e) = database.insert_tracks(res.clone()) {
+        app.state::<ScanRetryQueue>().push(res, &e);
+    }
 
     Ok(())
 }
+
+command_envelope! {
+    /// Scanned tracks the library failed to store, still waiting for a retry
+    /// or given up on after repeated failures.
+    #[tracing::instrument(level = "debug", skip(queue))]
+    #[tauri::command]
+    pub fn get_failed_scan_items(queue: State<'_, ScanRetryQueue>) -> Result<Vec<FailedScanItem>> {
+        Ok(queue.items())
+    }
+}
+
+command_envelope! {
+    /// Try storing every failed scan item again now.
+    #[tracing::instrument(level = "debug", skip(queue))]
+    #[tauri::command]
+    pub fn retry_failed_scan_items(queue: State<'_, ScanRetryQueue>) -> Result<()> {
+        queue.retry_all();
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+//! Retry queue for scanned tracks the library failed to store (e.g. the
+//! database was briefly locked). Failed batches are kept in a JSON file next to
+//! the database and written again with exponential backoff; batches that keep
+//! failing stay listed by `get_failed_scan_items` instead of disappearing.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{NaiveDateTime, Utc};
+use database::database::Database;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+use types::errors::MusicError;
+use types::tracks::MediaContent;
+
+/// Attempts, the first write included, before a batch is given up on
+const MAX_ATTEMPTS: u32 = 6;
+/// Delay before the first retry, doubled after each failure
+const BASE_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_BACKOFF: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FailedBatch {
+    tracks: Vec<MediaContent>,
+    attempts: u32,
+    last_error: String,
+    /// `None` once `MAX_ATTEMPTS` is reached: only retried on request
+    next_attempt_at: Option<NaiveDateTime>,
+}
+
+/// A scanned track waiting to be stored, as reported to the UI.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedScanItem {
+    pub path: Option<String>,
+    pub title: Option<String>,
+    pub attempts: u32,
+    pub last_error: String,
+    /// Next automatic retry; `None` when retries are exhausted
+    pub next_attempt_at: Option<NaiveDateTime>,
+}
+
+fn backoff(attempts: u32) -> Duration {
+    BASE_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+        .min(MAX_BACKOFF)
+}
+
+/// When a batch written `attempts` times is retried
+fn retry_at(attempts: u32) -> NaiveDateTime {
+    Utc::now().naive_utc() + chrono::Duration::from_std(backoff(attempts)).unwrap_or_default()
+}
+
+/// Persistent queue of failed scan batches, managed by Tauri.
+pub struct ScanRetryQueue {
+    path: PathBuf,
+    batches: Mutex<Vec<FailedBatch>>,
+    wake: Notify,
+}
+
+impl ScanRetryQueue {
+    /// Load the batches left by the previous run from `path`.
+    pub fn load(path: PathBuf) -> Self {
+        let batches: Vec<FailedBatch> = std::fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+        if !batches.is_empty() {
+            tracing::info!("{} failed scan batches left to store", batches.len());
+        }
+        Self {
+            path,
+            batches: Mutex::new(batches),
+            wake: Notify::new(),
+        }
+    }
+
+    /// Keep `tracks` for a later retry after their first write failed.
+    pub fn push(&self, tracks: Vec<MediaContent>, error: &MusicError) {
+        tracing::warn!("Failed to store {} scanned tracks, will retry: {}", tracks.len(), error);
+        let batch = FailedBatch {
+            tracks,
+            attempts: 1,
+            last_error: error.to_string(),
+            next_attempt_at: Some(retry_at(1)),
+        };
+        if let Ok(mut batches) = self.batches.lock() {
+            batches.push(batch);
+            self.persist(&batches);
+        }
+        self.wake.notify_one();
+    }
+
+    pub fn items(&self) -> Vec<FailedScanItem> {
+        let Ok(batches) = self.batches.lock() else {
+            return Vec::new();
+        };
+        batches
+            .iter()
+            .flat_map(|batch| {
+                batch.tracks.iter().map(|track| FailedScanItem {
+                    path: track.track.path.clone(),
+                    title: track.track.title.clone(),
+                    attempts: batch.attempts,
+                    last_error: batch.last_error.clone(),
+                    next_attempt_at: batch.next_attempt_at,
+                })
+            })
+            .collect()
+    }
+
+    /// Retry every batch now, including those whose retries are exhausted.
+    pub fn retry_all(&self) {
+        let now = Utc::now().naive_utc();
+        if let Ok(mut batches) = self.batches.lock() {
+            for batch in batches.iter_mut() {
+                batch.next_attempt_at = Some(now);
+            }
+            self.persist(&batches);
+        }
+        self.wake.notify_one();
+    }
+
+    fn persist(&self, batches: &[FailedBatch]) {
+        let written = serde_json::to_vec(batches)
+            .map_err(|e| e.to_string())
+            .and_then(|data| std::fs::write(&self.path, data).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            tracing::warn!("Failed to save the scan retry queue: {}", e);
+        }
+    }
+
+    /// Batches due at `now`, taken out of the queue
+    fn take_due(&self, now: NaiveDateTime) -> Vec<FailedBatch> {
+        let Ok(mut batches) = self.batches.lock() else {
+            return Vec::new();
+        };
+        let (due, waiting) = batches
+            .drain(..)
+            .partition(|b| b.next_attempt_at.is_some_and(|at| at <= now));
+        *batches = waiting;
+        due
+    }
+
+    /// Put back batches that failed again, or drop the ones stored.
+    fn settle(&self, failed: Vec<FailedBatch>) {
+        if let Ok(mut batches) = self.batches.lock() {
+            batches.extend(failed);
+            self.persist(&batches);
+        }
+    }
+
+    /// Time until the next batch is due, `None` when nothing is scheduled
+    fn next_delay(&self, now: NaiveDateTime) -> Option<Duration> {
+        let batches = self.batches.lock().ok()?;
+        batches
+            .iter()
+            .filter_map(|b| b.next_attempt_at)
+            .min()
+            .map(|at| (at - now).to_std().unwrap_or(Duration::ZERO))
+    }
+}
+
+/// Start the task writing failed batches again when they are due.
+#[tracing::instrument(level = "debug", skip(app))]
+pub fn start_retry_worker(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let queue = app.state::<ScanRetryQueue>();
+        loop {
+            match queue.next_delay(Utc::now().naive_utc()) {
+                Some(delay) if !delay.is_zero() => {
+                    let _ = tokio::time::timeout(delay, queue.wake.notified()).await;
+                    continue;
+                }
+                Some(_) => {}
+                None => {
+                    queue.wake.notified().await;
+                    continue;
+                }
+            }
+
+            let database = app.state::<Database>();
+            let mut failed = Vec::new();
+            for mut batch in queue.take_due(Utc::now().naive_utc()) {
+                match database.insert_tracks(batch.tracks.clone()) {
+                    Ok(tracks) => {
+                        tracing::info!("Stored {} scanned tracks on attempt {}", tracks.len(), batch.attempts + 1);
+                        let _ = app.emit("tracks-added", tracks.len());
+                    }
+                    Err(e) => {
+                        batch.attempts += 1;
+                        batch.last_error = e.to_string();
+                        batch.next_attempt_at = (batch.attempts < MAX_ATTEMPTS)
+                            .then(|| retry_at(batch.attempts));
+                        if batch.next_attempt_at.is_none() {
+                            tracing::error!(
+                                "Giving up storing {} scanned tracks after {} attempts: {}",
+                                batch.tracks.len(),
+                                batch.attempts,
+                                e
+                            );
+                        }
+                        failed.push(batch);
+                    }
+                }
+            }
+            queue.settle(failed);
+        }
+    });
+}
+
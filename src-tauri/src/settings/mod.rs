@@ -1,12 +1,12 @@
 // use std::thread;
 
-use macros::generate_command;
+use macros::{command_envelope, generate_command};
 use ::settings::settings::SettingsConfig;
 use serde_json::{json, Value};
 use tauri::{async_runtime, App, AppHandle, Emitter, Manager, State};
 use types::errors::error_helpers;
 use std::io::Write;
-use types::errors::Result;
+use types::errors::{CommandResponse, Result};
 
 use crate::{
     scanner::{start_scan, ScanTask},
@@ -29,6 +29,13 @@ const UI_KEYS: &[&str] = &[
     "prefs.music.sources_order",
     "prefs.music.playback",
     "prefs.music.effects",
+    "prefs.music.outputProfiles",
+    "prefs.music.downloads",
+    "prefs.music.endpoints",
+    "prefs.music.mobileData",
+    "prefs.music.quality",
+    // title display templates
+    "prefs.display.templates",
 ];
 
 #[tracing::instrument(level = "debug", skip(app))]
@@ -91,6 +98,53 @@ pub fn handle_settings_changes(app: AppHandle) {
                 }
             }
 
+            if key.starts_with("prefs.queue_settings") {
+                let audio_player = app.state::<audio_player::AudioPlayer>();
+                crate::audio::apply_queue_settings(&app, audio_player.inner());
+            }
+
+            if key.starts_with("prefs.display") || key == "prefs.general.language" {
+                crate::display::apply_display_settings(&app);
+            }
+
+            if key.starts_with("prefs.music.playback") {
+                let audio_player = app.state::<audio_player::AudioPlayer>();
+                crate::audio::apply_playback_settings(&app, audio_player.inner());
+            }
+
+            if key.starts_with("prefs.music.buffering") {
+                crate::audio::buffering::apply_buffering_settings(&app);
+            }
+
+            if key.starts_with("prefs.music.outputProfiles") {
+                crate::audio::profiles::register_profile_hotkeys(&app);
+            }
+
+            if key.starts_with("prefs.music.mediaKeys") {
+                let audio_player = app.state::<audio_player::AudioPlayer>();
+                crate::audio::apply_media_key_settings(&app, audio_player.inner());
+            }
+
+            if key == "prefs.spotify.enable" {
+                let app_handle = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    crate::plugins::spotify::apply_spotify_enabled(&app_handle).await;
+                });
+            }
+
+            if key.starts_with("prefs.music.endpoints") {
+                crate::plugins::endpoints::apply_endpoint_settings(&app);
+            }
+
+            if key.starts_with("prefs.music.downloads") {
+                app.state::<crate::downloads::DownloadQueue>().wake();
+                crate::downloads::queue_smart_downloads(&app);
+            }
+
+            if key.starts_with("prefs.music.mobileData") {
+                crate::network::notify_policy_changed(&app);
+            }
+
             if key == "prefs.general.scanMinDuration" {
                 let _ = pref_config.save_selective("general.scan_min_duration".to_string(), Some(value.clone()));
                 tracing::info!("Mirrored prefs.general.scanMinDuration -> general.scan_min_duration");
@@ -101,6 +155,66 @@ pub fn handle_settings_changes(app: AppHandle) {
                 tracing::info!("Mirrored prefs.general.scanFormats -> general.scan_formats");
                 let _ = app.state::<crate::scanner::ScanTask>().update_auto_scanner_config(&app);
             }
+            if key == "prefs.general.scanExtensions" {
+                // Entries that fail validation are dropped rather than mirrored
+                let (extensions, invalid): (Vec<types::settings::general::ScanExtension>, Vec<_>) =
+                    serde_json::from_value::<Vec<types::settings::general::ScanExtension>>(value.clone())
+                        .unwrap_or_default()
+                        .into_iter()
+                        .partition(|e| file_scanner::normalize_extension(&e.extension).is_some());
+                if !invalid.is_empty() {
+                    tracing::warn!("Ignoring invalid scan extensions: {:?}", invalid);
+                }
+                let extensions = file_scanner::validate_extensions(&extensions).unwrap_or_default();
+                let _ = pref_config.save_selective("general.scan_extensions".to_string(), Some(extensions));
+                tracing::info!("Mirrored prefs.general.scanExtensions -> general.scan_extensions");
+                let _ = app.state::<crate::scanner::ScanTask>().update_auto_scanner_config(&app);
+            }
+            if key == "prefs.general.scanDebounceMs" {
+                let _ = pref_config.save_selective("general.scan_debounce_ms".to_string(), Some(value.clone()));
+                tracing::info!("Mirrored prefs.general.scanDebounceMs -> general.scan_debounce_ms");
+                let _ = app.state::<crate::scanner::ScanTask>().update_auto_scanner_config(&app);
+            }
+            if key == "prefs.general.scanWindows" {
+                let _ = pref_config.save_selective("general.scan_windows".to_string(), Some(value.clone()));
+                tracing::info!("Mirrored prefs.general.scanWindows -> general.scan_windows");
+                let _ = app.state::<crate::scanner::ScanTask>().update_auto_scanner_config(&app);
+            }
+            if key == "prefs.general.scanCatchUp" {
+                let _ = pref_config.save_selective("general.scan_catch_up".to_string(), Some(value.clone()));
+                tracing::info!("Mirrored prefs.general.scanCatchUp -> general.scan_catch_up");
+                let _ = app.state::<crate::scanner::ScanTask>().update_auto_scanner_config(&app);
+            }
+            if key == "prefs.general.scanMaxDepth" {
+                let _ = pref_config.save_selective("general.scan_max_depth".to_string(), Some(value.clone()));
+                tracing::info!("Mirrored prefs.general.scanMaxDepth -> general.scan_max_depth");
+                let _ = app.state::<crate::scanner::ScanTask>().update_auto_scanner_config(&app);
+            }
+            if key == "prefs.general.scanSymlinks" {
+                let _ = pref_config.save_selective("general.scan_symlinks".to_string(), Some(value.clone()));
+                tracing::info!("Mirrored prefs.general.scanSymlinks -> general.scan_symlinks");
+                let _ = app.state::<crate::scanner::ScanTask>().update_auto_scanner_config(&app);
+            }
+            if key == "prefs.general.scanRootOverrides" {
+                let _ = pref_config.save_selective("general.scan_root_overrides".to_string(), Some(value.clone()));
+                tracing::info!("Mirrored prefs.general.scanRootOverrides -> general.scan_root_overrides");
+                let _ = app.state::<crate::scanner::ScanTask>().update_auto_scanner_config(&app);
+            }
+            if key == "prefs.general.scanFilenameInference" {
+                let _ = pref_config.save_selective("general.scan_filename_inference".to_string(), Some(value.clone()));
+                tracing::info!("Mirrored prefs.general.scanFilenameInference -> general.scan_filename_inference");
+                let _ = app.state::<crate::scanner::ScanTask>().update_auto_scanner_config(&app);
+            }
+            if key == "prefs.general.scanFilenamePatterns" {
+                let _ = pref_config.save_selective("general.scan_filename_patterns".to_string(), Some(value.clone()));
+                tracing::info!("Mirrored prefs.general.scanFilenamePatterns -> general.scan_filename_patterns");
+                let _ = app.state::<crate::scanner::ScanTask>().update_auto_scanner_config(&app);
+            }
+            if key == "prefs.general.scanFilenameOverrides" {
+                let _ = pref_config.save_selective("general.scan_filename_overrides".to_string(), Some(value.clone()));
+                tracing::info!("Mirrored prefs.general.scanFilenameOverrides -> general.scan_filename_overrides");
+                let _ = app.state::<crate::scanner::ScanTask>().update_auto_scanner_config(&app);
+            }
 
             // if key == "prefs.general.launch_at_login" { // unified key (bool)
             //     #[cfg(not(any(target_os = "android", target_os = "ios")))]
@@ -207,7 +321,7 @@ pub fn initial(app: &mut App) {
         // Run initial legacy scan
         let handle = app.handle().clone();
         tauri::async_runtime::spawn_blocking(move || {
-            if let Err(e) = start_scan(handle, None) {
+            if let Err(e) = start_scan(handle, None).and_then(CommandResponse::into_result) {
                 tracing::error!("Failed to scan: {:?}", e);
             }
         });
@@ -220,59 +334,63 @@ generate_command!(get_secure, SettingsConfig, Value, key: String);
 generate_command!(set_secure, SettingsConfig, (), key: String, value: Option<Value>);
 generate_command!(load_selective_array, SettingsConfig, Value, key: String);
 
-#[tauri::command]
-pub fn load_domain(config: State<'_, SettingsConfig>, domain: Option<String>) -> Result<Value> {
-    let prefs_all = config.memcache.lock().unwrap().clone();
-    let root = prefs_all.get("prefs").cloned().unwrap_or_else(|| json!({}));
-    if let Some(dom) = domain {
-        if dom.is_empty() { return Ok(root); }
-        if let Some(v) = root.get(&dom) { return Ok(v.clone()); }
-        Ok(json!({}))
-    } else {
-        Ok(root)
+command_envelope! {
+    #[tauri::command]
+    pub fn load_domain(config: State<'_, SettingsConfig>, domain: Option<String>) -> Result<Value> {
+        let prefs_all = config.memcache.lock().unwrap().clone();
+        let root = prefs_all.get("prefs").cloned().unwrap_or_else(|| json!({}));
+        if let Some(dom) = domain {
+            if dom.is_empty() { return Ok(root); }
+            if let Some(v) = root.get(&dom) { return Ok(v.clone()); }
+            Ok(json!({}))
+        } else {
+            Ok(root)
+        }
     }
 }
 
-#[tauri::command]
-pub fn save_domain_partial(config: State<'_, SettingsConfig>, domain: Option<String>, patch: Value) -> Result<()> {
-    if !patch.is_object() { return Err("patch must be an object".into()); }
+command_envelope! {
+    #[tauri::command]
+    pub fn save_domain_partial(config: State<'_, SettingsConfig>, domain: Option<String>, patch: Value) -> Result<()> {
+        if !patch.is_object() { return Err("patch must be an object".into()); }
 
-    // Clone current prefs tree
-    let mut all = { config.memcache.lock().unwrap().clone() };
-    if !all.is_object() { all = json!({"prefs": {}}); }
+        // Clone current prefs tree
+        let mut all = { config.memcache.lock().unwrap().clone() };
+        if !all.is_object() { all = json!({"prefs": {}}); }
 
-    // Ensure prefs object exists
-    let mut_root = all.as_object_mut().unwrap();
-    let prefs_entry = mut_root.entry("prefs".to_string()).or_insert(json!({}));
+        // Ensure prefs object exists
+        let mut_root = all.as_object_mut().unwrap();
+        let prefs_entry = mut_root.entry("prefs".to_string()).or_insert(json!({}));
 
-    // Resolve target object: either prefs or prefs.<domain>
-    let target_obj = if let Some(dom) = domain {
-        if dom.is_empty() {
-            prefs_entry
+        // Resolve target object: either prefs or prefs.<domain>
+        let target_obj = if let Some(dom) = domain {
+            if dom.is_empty() {
+                prefs_entry
+            } else {
+                // Ensure nested domain object exists
+                if !prefs_entry.is_object() { *prefs_entry = json!({}); }
+                let obj = prefs_entry.as_object_mut().unwrap();
+                obj.entry(dom).or_insert(json!({}))
+            }
         } else {
-            // Ensure nested domain object exists
-            if !prefs_entry.is_object() { *prefs_entry = json!({}); }
-            let obj = prefs_entry.as_object_mut().unwrap();
-            obj.entry(dom).or_insert(json!({}))
-        }
-    } else {
-        prefs_entry
-    };
+            prefs_entry
+        };
 
-    // Merge patch into target object
-    if !target_obj.is_object() { *target_obj = json!({}); }
-    if let (Some(tobj), Some(pobj)) = (target_obj.as_object_mut(), patch.as_object()) {
-        for (k, v) in pobj.iter() { tobj.insert(k.clone(), v.clone()); }
-    }
+        // Merge patch into target object
+        if !target_obj.is_object() { *target_obj = json!({}); }
+        if let (Some(tobj), Some(pobj)) = (target_obj.as_object_mut(), patch.as_object()) {
+            for (k, v) in pobj.iter() { tobj.insert(k.clone(), v.clone()); }
+        }
 
-    // Write back to memcache and file
-    {
-        let mut guard = config.memcache.lock().unwrap();
-        *guard = all.clone();
+        // Write back to memcache and file
+        {
+            let mut guard = config.memcache.lock().unwrap();
+            *guard = all.clone();
+        }
+        let path = config.config_file.lock().unwrap().clone();
+        let mut f = std::fs::File::create(path)?;
+        f.write_all(&serde_json::to_vec(&all)?)?;
+        f.flush()?;
+        Ok(())
     }
-    let path = config.config_file.lock().unwrap().clone();
-    let mut f = std::fs::File::create(path)?;
-    f.write_all(&serde_json::to_vec(&all)?)?;
-    f.flush()?;
-    Ok(())
 }
@@ -0,0 +1,176 @@
+//! Internet radio station directory, backed by radio-browser.info. Stations
+//! are queued like tracks (see `types::stations`); the player reads their ICY
+//! metadata and reports title changes as `StreamTitleChanged`.
+
+use std::time::Duration;
+
+use macros::command_envelope;
+use serde::Deserialize;
+use tauri::AppHandle;
+use types::errors::{error_helpers, Result};
+use types::settings::queue::QueueAction;
+use types::stations::RadioStation;
+
+/// Load-balanced entry point of the radio-browser.info mirrors
+const DIRECTORY_URL: &str = "https://all.api.radio-browser.info/json";
+
+/// The directory asks clients to identify themselves
+const USER_AGENT: &str = concat!("Music/", env!("CARGO_PKG_VERSION"));
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Stations per search when unset, and the most allowed
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 500;
+
+/// A station as listed by radio-browser.info
+#[derive(Debug, Deserialize)]
+struct BrowserStation {
+    stationuuid: String,
+    name: String,
+    url: String,
+    /// The stream behind playlist files (.pls, .m3u), empty if unresolved
+    #[serde(default)]
+    url_resolved: String,
+    #[serde(default)]
+    homepage: String,
+    #[serde(default)]
+    favicon: String,
+    /// Comma separated
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    countrycode: String,
+    #[serde(default)]
+    language: String,
+    #[serde(default)]
+    codec: String,
+    #[serde(default)]
+    bitrate: u32,
+    #[serde(default)]
+    hls: u8,
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+impl From<BrowserStation> for RadioStation {
+    fn from(station: BrowserStation) -> Self {
+        let stream_url = if station.url_resolved.trim().is_empty() {
+            station.url
+        } else {
+            station.url_resolved
+        };
+        RadioStation {
+            id: station.stationuuid,
+            name: station.name.trim().to_string(),
+            stream_url,
+            homepage: non_empty(station.homepage),
+            favicon: non_empty(station.favicon),
+            tags: station
+                .tags
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(str::to_string)
+                .collect(),
+            country_code: non_empty(station.countrycode),
+            language: non_empty(station.language),
+            codec: non_empty(station.codec),
+            bitrate: (station.bitrate > 0).then_some(station.bitrate),
+            hls: station.hls != 0,
+        }
+    }
+}
+
+fn client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(error_helpers::to_network_error)
+}
+
+/// Tell the directory the station was played, which ranks it up. Failures
+/// don't matter to playback and are only logged.
+fn count_click(station_id: String) {
+    tauri::async_runtime::spawn(async move {
+        let result = match client() {
+            Ok(client) => client
+                .get(format!("{}/url/{}", DIRECTORY_URL, station_id))
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+                .map(|_| ())
+                .map_err(error_helpers::to_network_error),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::debug!("Failed to count the click on station {}: {:?}", station_id, e);
+        }
+    });
+}
+
+command_envelope! {
+    /// Search the station directory by name, tag and country (ISO 3166-1
+    /// alpha-2), most voted first. Broken stations are left out.
+    #[tracing::instrument(level = "debug")]
+    #[tauri::command]
+    pub async fn search_radio_stations(
+        name: Option<String>,
+        tag: Option<String>,
+        country_code: Option<String>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<RadioStation>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let mut query: Vec<(&str, String)> = vec![
+            ("limit", limit.to_string()),
+            ("offset", offset.unwrap_or(0).to_string()),
+            ("hidebroken", "true".to_string()),
+            ("order", "votes".to_string()),
+            ("reverse", "true".to_string()),
+        ];
+        for (key, value) in [("name", name), ("tag", tag), ("countrycode", country_code)] {
+            if let Some(value) = value.and_then(non_empty) {
+                query.push((key, value));
+            }
+        }
+
+        let body = client()?
+            .get(format!("{}/stations/search", DIRECTORY_URL))
+            .query(&query)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(error_helpers::to_network_error)?
+            .bytes()
+            .await
+            .map_err(error_helpers::to_network_error)?;
+        let stations: Vec<BrowserStation> = serde_json::from_slice(&body).map_err(error_helpers::to_parse_error)?;
+        Ok(stations
+            .into_iter()
+            .map(RadioStation::from)
+            .filter(|station| !station.stream_url.is_empty())
+            .collect())
+    }
+}
+
+command_envelope! {
+    /// Play `station` now or queue it as `action` says. Next and previous
+    /// switch between the stations of the queue.
+    #[tracing::instrument(level = "debug", skip(app))]
+    #[tauri::command]
+    pub async fn play_radio_station(app: AppHandle, station: RadioStation, action: Option<QueueAction>) -> Result<()> {
+        let action = match action.unwrap_or_default() {
+            // A station has no related tracks to continue with
+            QueueAction::StartRadio => QueueAction::PlayNow,
+            action => action,
+        };
+        crate::audio::actions::apply_queue_action(&app, action, vec![station.to_media_content()]).await?;
+        count_click(station.id);
+        Ok(())
+    }
+}
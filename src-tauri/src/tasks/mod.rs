@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use database::database::Database;
+use serde::{de::DeserializeOwned, Serialize};
+use tauri::{AppHandle, Manager};
+use types::entities::TaskJournalEntry;
+use types::errors::{MusicError, Result};
+
+/// Minimum interval between two checkpoint writes for the same task.
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(2);
+/// Finished journal entries are kept this long for diagnostics.
+const JOURNAL_RETENTION_DAYS: i64 = 7;
+
+/// Kind of journaled job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskKind {
+    Scan,
+    Download,
+}
+
+impl TaskKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            TaskKind::Scan => "scan",
+            TaskKind::Download => "download",
+        }
+    }
+}
+
+/// Resume information for an interrupted download. Downloaders write this as
+/// their checkpoint so a restart can continue with an HTTP Range request.
+#[derive(Debug, Clone, Default, Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadCheckpoint {
+    pub bytes_written: u64,
+    pub total_bytes: Option<u64>,
+    /// Partial file the bytes were written to
+    pub partial_path: String,
+}
+
+/// Central manager for long-running jobs. Every job is journaled in the
+/// database with its payload and latest checkpoint; jobs still marked running
+/// at startup were interrupted and are handed back to their owner to resume.
+pub struct TaskManager {
+    db: Database,
+    last_write: Mutex<HashMap<String, Instant>>,
+}
+
+impl TaskManager {
+    pub fn new(db: Database) -> Self {
+        if let Err(e) = db.prune_task_journal(JOURNAL_RETENTION_DAYS) {
+            tracing::warn!("Failed to prune task journal: {:?}", e);
+        }
+        Self {
+            db,
+            last_write: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Journal a new (or restarted) job.
+    pub fn begin<P: Serialize>(&self, id: &str, kind: TaskKind, payload: &P) -> Result<()> {
+        let payload = serde_json::to_string(payload).map_err(|e| MusicError::String(e.to_string()))?;
+        self.db.insert_task_journal(id, kind.as_str(), &payload)
+    }
+
+    /// Record progress. Writes are throttled unless `force` is set; call with
+    /// `force` after data the checkpoint refers to has been committed.
+    pub fn checkpoint<C: Serialize>(&self, id: &str, checkpoint: &C, force: bool) -> Result<()> {
+        {
+            let mut last_write = self.last_write.lock().map_err(|_| MusicError::from("Failed to access task manager"))?;
+            let now = Instant::now();
+            if !force {
+                if let Some(last) = last_write.get(id) {
+                    if now.duration_since(*last) < CHECKPOINT_INTERVAL {
+                        return Ok(());
+                    }
+                }
+            }
+            last_write.insert(id.to_string(), now);
+        }
+        let checkpoint = serde_json::to_string(checkpoint).map_err(|e| MusicError::String(e.to_string()))?;
+        self.db.update_task_checkpoint(id, &checkpoint)
+    }
+
+    pub fn complete(&self, id: &str) -> Result<()> {
+        self.forget(id);
+        self.db.set_task_status(id, "completed", None)
+    }
+
+    pub fn fail(&self, id: &str, error: &str) -> Result<()> {
+        self.forget(id);
+        self.db.set_task_status(id, "failed", Some(error))
+    }
+
+    pub fn cancel(&self, id: &str) -> Result<()> {
+        self.forget(id);
+        self.db.set_task_status(id, "cancelled", None)
+    }
+
+    /// Jobs of `kind` interrupted by the previous app exit.
+    pub fn interrupted(&self, kind: TaskKind) -> Result<Vec<TaskJournalEntry>> {
+        self.db.get_running_tasks(kind.as_str())
+    }
+
+    /// Decode the latest checkpoint of a journal entry, if any.
+    pub fn decode_checkpoint<C: DeserializeOwned>(entry: &TaskJournalEntry) -> Option<C> {
+        entry
+            .checkpoint
+            .as_deref()
+            .and_then(|c| serde_json::from_str(c).ok())
+    }
+
+    fn forget(&self, id: &str) {
+        if let Ok(mut last_write) = self.last_write.lock() {
+            last_write.remove(id);
+        }
+    }
+}
+
+/// Resume library scans interrupted by the previous app exit. Must run after the
+/// auto scanner is initialized.
+#[tracing::instrument(level = "debug", skip(app))]
+pub fn resume_interrupted_scans(app: &AppHandle) {
+    let tasks = app.state::<TaskManager>();
+    let scan_task = app.state::<crate::scanner::ScanTask>();
+    let entries = match tasks.interrupted(TaskKind::Scan) {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::warn!("Failed to read interrupted scans: {:?}", e);
+            return;
+        }
+    };
+
+    for entry in entries {
+        let checkpoint = TaskManager::decode_checkpoint::<file_scanner::ScanCheckpoint>(&entry)
+            .or_else(|| serde_json::from_str(&entry.payload).ok());
+        match checkpoint {
+            Some(checkpoint) => {
+                tracing::info!(
+                    "Resuming interrupted scan {} ({}/{} files done)",
+                    entry.id,
+                    checkpoint.processed,
+                    checkpoint.total
+                );
+                if let Err(e) = scan_task.resume_auto_scan(checkpoint) {
+                    tracing::warn!("Failed to resume scan {}: {:?}", entry.id, e);
+                }
+            }
+            None => {
+                let _ = tasks.fail(&entry.id, "Unreadable scan checkpoint");
+            }
+        }
+    }
+}
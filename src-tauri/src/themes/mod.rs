@@ -1,5 +1,6 @@
 use std::{collections::HashMap, fs, path::PathBuf, sync::Mutex};
 
+use macros::command_envelope;
 use tauri::{App, AppHandle, Emitter, State, Manager};
 use types::errors::{error_helpers, Result};
 use types::themes::ThemeDetails;
@@ -142,90 +143,104 @@ pub fn get_theme_handler_state(app: &mut App) -> ThemeHolder {
     ThemeHolder::new(root, app.app_handle().clone())
 }
 
-#[tauri::command(async)]
-pub fn save_theme(theme_holder: State<ThemeHolder>, theme: ThemeDetails) -> Result<()> {
-    theme_holder.save_theme(theme)
+command_envelope! {
+    #[tauri::command(async)]
+    pub fn save_theme(theme_holder: State<ThemeHolder>, theme: ThemeDetails) -> Result<()> {
+        theme_holder.save_theme(theme)
+    }
 }
 
-#[tauri::command(async)]
-pub fn remove_theme(theme_holder: State<ThemeHolder>, id: String) -> Result<()> {
-    theme_holder.remove_theme(id)
+command_envelope! {
+    #[tauri::command(async)]
+    pub fn remove_theme(theme_holder: State<ThemeHolder>, id: String) -> Result<()> {
+        theme_holder.remove_theme(id)
+    }
 }
 
-#[tauri::command(async)]
-pub fn load_theme(theme_holder: State<ThemeHolder>, id: String) -> Result<ThemeDetails> {
-    theme_holder.load_theme(id)
+command_envelope! {
+    #[tauri::command(async)]
+    pub fn load_theme(theme_holder: State<ThemeHolder>, id: String) -> Result<ThemeDetails> {
+        theme_holder.load_theme(id)
+    }
 }
 
-#[tauri::command(async)]
-pub fn load_all_themes(theme_holder: State<ThemeHolder>) -> Result<HashMap<String, ThemeDetails>> {
-    theme_holder.load_all_themes()
+command_envelope! {
+    #[tauri::command(async)]
+    pub fn load_all_themes(theme_holder: State<ThemeHolder>) -> Result<HashMap<String, ThemeDetails>> {
+        theme_holder.load_all_themes()
+    }
 }
 
-#[tauri::command(async)]
-pub fn get_css(theme_holder: State<ThemeHolder>, id: String) -> Result<String> {
-    theme_holder.get_css(id)
+command_envelope! {
+    #[tauri::command(async)]
+    pub fn get_css(theme_holder: State<ThemeHolder>, id: String) -> Result<String> {
+        theme_holder.get_css(id)
+    }
 }
 
-#[tauri::command(async)]
-pub fn export_theme(theme_holder: State<ThemeHolder>, id: String, dest_path: String) -> Result<()> {
-    use std::io::{Write};
-    use zip::write::FileOptions;
-    let theme = theme_holder.load_theme(id.clone())?;
-    let dir = theme_holder.theme_dir(&id);
-    let file = std::fs::File::create(&dest_path).map_err(error_helpers::to_file_system_error)?;
-    let mut zip = zip::ZipWriter::new(file);
-
-    // add config.json
-    zip.start_file("config.json", FileOptions::default()).map_err(error_helpers::to_file_system_error)?;
-    let json = serde_json::to_vec_pretty(&theme)?;
-    zip.write_all(&json).map_err(error_helpers::to_file_system_error)?;
-
-    // include custom.css if referenced
-    if let Some(css_rel) = theme.custom_css.clone() {
-        let css_path = dir.join(&css_rel);
-        if css_path.exists() {
-            zip.start_file(css_rel.replace('\\', "/"), FileOptions::default()).map_err(error_helpers::to_file_system_error)?;
-            let data = std::fs::read(css_path).map_err(error_helpers::to_file_system_error)?;
-            zip.write_all(&data).map_err(error_helpers::to_file_system_error)?;
+command_envelope! {
+    #[tauri::command(async)]
+    pub fn export_theme(theme_holder: State<ThemeHolder>, id: String, dest_path: String) -> Result<()> {
+        use std::io::{Write};
+        use zip::write::FileOptions;
+        let theme = theme_holder.load_theme(id.clone())?;
+        let dir = theme_holder.theme_dir(&id);
+        let file = std::fs::File::create(&dest_path).map_err(error_helpers::to_file_system_error)?;
+        let mut zip = zip::ZipWriter::new(file);
+
+        // add config.json
+        zip.start_file("config.json", FileOptions::default()).map_err(error_helpers::to_file_system_error)?;
+        let json = serde_json::to_vec_pretty(&theme)?;
+        zip.write_all(&json).map_err(error_helpers::to_file_system_error)?;
+
+        // include custom.css if referenced
+        if let Some(css_rel) = theme.custom_css.clone() {
+            let css_path = dir.join(&css_rel);
+            if css_path.exists() {
+                zip.start_file(css_rel.replace('\\', "/"), FileOptions::default()).map_err(error_helpers::to_file_system_error)?;
+                let data = std::fs::read(css_path).map_err(error_helpers::to_file_system_error)?;
+                zip.write_all(&data).map_err(error_helpers::to_file_system_error)?;
+            }
         }
-    }
 
-    zip.finish().map_err(error_helpers::to_file_system_error)?;
-    Ok(())
+        zip.finish().map_err(error_helpers::to_file_system_error)?;
+        Ok(())
+    }
 }
 
-#[tauri::command(async)]
-pub fn import_theme(theme_holder: State<ThemeHolder>, src_path: String) -> Result<()> {
-    use std::io::Read;
-    let file = std::fs::File::open(&src_path).map_err(error_helpers::to_file_system_error)?;
-    let mut archive = zip::ZipArchive::new(file).map_err(error_helpers::to_file_system_error)?;
-
-    // read config.json first
-    let mut cfg_file = archive.by_name("config.json").map_err(error_helpers::to_file_system_error)?;
-    let mut buf = Vec::new();
-    cfg_file.read_to_end(&mut buf).map_err(error_helpers::to_file_system_error)?;
-    let theme: ThemeDetails = serde_json::from_slice(&buf)?;
-    let id = theme.meta.id.clone();
-    let dst = theme_holder.theme_dir(&id);
-    if !dst.exists() { fs::create_dir_all(&dst).map_err(error_helpers::to_file_system_error)?; }
-
-    // reset archive to extract files
-    let file = std::fs::File::open(&src_path).map_err(error_helpers::to_file_system_error)?;
-    let mut archive = zip::ZipArchive::new(file).map_err(error_helpers::to_file_system_error)?;
-    for i in 0..archive.len() {
-        let mut f = archive.by_index(i).map_err(error_helpers::to_file_system_error)?;
-        let outpath = dst.join(f.mangled_name());
-        if f.name().ends_with('/') {
-            fs::create_dir_all(&outpath).map_err(error_helpers::to_file_system_error)?;
-        } else {
-            if let Some(p) = outpath.parent() { fs::create_dir_all(p).map_err(error_helpers::to_file_system_error)?; }
-            let mut outfile = std::fs::File::create(&outpath).map_err(error_helpers::to_file_system_error)?;
-            std::io::copy(&mut f, &mut outfile).map_err(error_helpers::to_file_system_error)?;
+command_envelope! {
+    #[tauri::command(async)]
+    pub fn import_theme(theme_holder: State<ThemeHolder>, src_path: String) -> Result<()> {
+        use std::io::Read;
+        let file = std::fs::File::open(&src_path).map_err(error_helpers::to_file_system_error)?;
+        let mut archive = zip::ZipArchive::new(file).map_err(error_helpers::to_file_system_error)?;
+
+        // read config.json first
+        let mut cfg_file = archive.by_name("config.json").map_err(error_helpers::to_file_system_error)?;
+        let mut buf = Vec::new();
+        cfg_file.read_to_end(&mut buf).map_err(error_helpers::to_file_system_error)?;
+        let theme: ThemeDetails = serde_json::from_slice(&buf)?;
+        let id = theme.meta.id.clone();
+        let dst = theme_holder.theme_dir(&id);
+        if !dst.exists() { fs::create_dir_all(&dst).map_err(error_helpers::to_file_system_error)?; }
+
+        // reset archive to extract files
+        let file = std::fs::File::open(&src_path).map_err(error_helpers::to_file_system_error)?;
+        let mut archive = zip::ZipArchive::new(file).map_err(error_helpers::to_file_system_error)?;
+        for i in 0..archive.len() {
+            let mut f = archive.by_index(i).map_err(error_helpers::to_file_system_error)?;
+            let outpath = dst.join(f.mangled_name());
+            if f.name().ends_with('/') {
+                fs::create_dir_all(&outpath).map_err(error_helpers::to_file_system_error)?;
+            } else {
+                if let Some(p) = outpath.parent() { fs::create_dir_all(p).map_err(error_helpers::to_file_system_error)?; }
+                let mut outfile = std::fs::File::create(&outpath).map_err(error_helpers::to_file_system_error)?;
+                std::io::copy(&mut f, &mut outfile).map_err(error_helpers::to_file_system_error)?;
+            }
         }
-    }
 
-    // save config.json to ensure consistency
-    theme_holder.save_theme(theme)?;
-    Ok(())
+        // save config.json to ensure consistency
+        theme_holder.save_theme(theme)?;
+        Ok(())
+    }
 }
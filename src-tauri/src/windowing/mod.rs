@@ -0,0 +1,151 @@
+//! Player events for several webview windows (main window, mini player,
+//! settings). Each window subscribes once and receives a snapshot of the player
+//! tagged with the sequence number of the last event it reflects; every
+//! `audio_event` then carries the next sequence number, so a window can drop
+//! events already covered by its snapshot and never applies one twice. Any
+//! window can issue commands: state lives in the backend, and the resulting
+//! events reach every subscribed window.
+
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+use audio_player::AudioPlayer;
+use macros::command_envelope;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, EventTarget, Manager, State, WebviewWindow};
+use types::errors::{MusicError, Result};
+use types::tracks::MediaContent;
+use types::ui::player_details::{PlayerMode, PlayerState};
+
+use crate::display::DisplayService;
+
+/// Windows subscribed to player events and the last sequence number sent,
+/// managed by Tauri.
+#[derive(Default)]
+pub struct EventRouter {
+    inner: Mutex<RouterState>,
+}
+
+#[derive(Default)]
+struct RouterState {
+    seq: u64,
+    windows: BTreeSet<String>,
+}
+
+impl EventRouter {
+    fn subscribe(&self, label: &str) -> u64 {
+        let Ok(mut inner) = self.inner.lock() else { return 0 };
+        inner.windows.insert(label.to_string());
+        inner.seq
+    }
+
+    /// Stop sending events to `label`, e.g. once the window is closed
+    pub fn forget(&self, label: &str) {
+        if let Ok(mut inner) = self.inner.lock() {
+            if inner.windows.remove(label) {
+                tracing::debug!("Window {} unsubscribed from player events", label);
+            }
+        }
+    }
+}
+
+/// Player state as seen by a window subscribing late
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerSnapshot {
+    /// Sequence number of the last event reflected in the snapshot
+    pub seq: u64,
+    pub state: PlayerState,
+    pub track: Option<MediaContent>,
+    pub queue_index: Option<usize>,
+    pub mode: PlayerMode,
+    /// Seconds on the edited timeline (skip regions removed)
+    pub position: f64,
+    /// Edited duration when the track has skip regions
+    pub duration: Option<f64>,
+    /// 0.0 - 1.0
+    pub volume: f32,
+}
+
+/// Send a player event (`{ type, data }`) to every subscribed window, tagged
+/// with the next sequence number. Before any window subscribed, the event is
+/// broadcast untagged. Events worth announcing get `data.announcement`.
+pub fn emit_audio_event(app: &AppHandle, mut payload: Value) -> tauri::Result<()> {
+    attach_announcement(app, &mut payload);
+    let Some(router) = app.try_state::<EventRouter>() else {
+        return app.emit("audio_event", payload);
+    };
+    // Held while emitting so that windows receive events in sequence order
+    let Ok(mut inner) = router.inner.lock() else {
+        return app.emit("audio_event", payload);
+    };
+    if inner.windows.is_empty() {
+        return app.emit("audio_event", payload);
+    }
+    inner.seq += 1;
+    if let Value::Object(fields) = &mut payload {
+        fields.insert("seq".to_string(), Value::from(inner.seq));
+    }
+    for label in &inner.windows {
+        app.emit_to(EventTarget::webview_window(label.clone()), "audio_event", &payload)?;
+    }
+    Ok(())
+}
+
+fn attach_announcement(app: &AppHandle, payload: &mut Value) {
+    let Some(display) = app.try_state::<DisplayService>() else { return };
+    let Value::Object(fields) = payload else { return };
+    let Some(event_type) = fields.get("type").and_then(Value::as_str) else { return };
+    let Some(data) = fields.get("data") else { return };
+    let Some(text) = display.announce(event_type, data) else { return };
+    if let Some(Value::Object(data)) = fields.get_mut("data") {
+        data.insert("announcement".to_string(), Value::from(text));
+    }
+}
+
+fn snapshot(player: &AudioPlayer, seq: u64) -> Result<PlayerSnapshot> {
+    let store_arc = player.get_store();
+    let store = store_arc
+        .lock()
+        .map_err(|_| MusicError::from("Failed to access player store"))?;
+    let track = store.get_current_track();
+    Ok(PlayerSnapshot {
+        seq,
+        state: store.get_player_state(),
+        queue_index: track.as_ref().map(|_| store.get_queue_index()),
+        track,
+        mode: store.get_repeat(),
+        position: store.edit_regions().to_edited(store.get_current_time()),
+        duration: store.edited_duration(),
+        volume: (store.get_raw_volume() / 100.0) as f32,
+    })
+}
+
+command_envelope! {
+    /// Subscribe the calling window to player events and return the player
+    /// state they continue from. Calling it again (e.g. after a reload) keeps a
+    /// single subscription.
+    #[tracing::instrument(level = "debug", skip(window, router, player))]
+    #[tauri::command]
+    pub fn subscribe_player_events(
+        window: WebviewWindow,
+        router: State<'_, EventRouter>,
+        player: State<'_, AudioPlayer>,
+    ) -> Result<PlayerSnapshot> {
+        // The sequence is read before the state: events emitted in between are
+        // applied on top of a snapshot that may already include them, which is
+        // harmless as they carry absolute values
+        let seq = router.subscribe(window.label());
+        snapshot(&player, seq)
+    }
+}
+
+command_envelope! {
+    #[tracing::instrument(level = "debug", skip(window, router))]
+    #[tauri::command]
+    pub fn unsubscribe_player_events(window: WebviewWindow, router: State<'_, EventRouter>) -> Result<()> {
+        router.forget(window.label());
+        Ok(())
+    }
+}
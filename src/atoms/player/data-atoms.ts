@@ -2,6 +2,7 @@ import { atom } from "jotai"
 import { atomWithStorage } from "jotai/utils"
 import type { LyricLine } from "@applemusic-like-lyrics/lyric"
 import type { PlayerMode } from "~/types/bindings"
+import type { QueueMetrics } from "~/services/audio-service"
 
 // ==================================================================
 //                            类型定义
@@ -110,7 +111,7 @@ export const musicPlayingPositionAtom = atom<number>(0);
 export const musicVolumeAtom = atomWithStorage<number>("player.music-volume", 0.5);
 
 /**
- * 当前播放模式：Sequential/Single/Shuffle/ListLoop
+ * 当前播放模式：Sequential/Single/Shuffle/ShuffleAlbums/ListLoop
  */
 export const playerModeAtom = atom<PlayerMode>("Sequential" as PlayerMode);
 
@@ -156,6 +157,11 @@ export const currentPlaylistAtom = atom<TrackData[]>([]);
  */
 export const currentPlaylistMusicIndexAtom = atom<number>(0);
 
+/**
+ * 后端计算的队列剩余时长与预计结束时间。
+ */
+export const queueMetricsAtom = atom<QueueMetrics | null>(null);
+
 // ==================================================================
 //                        音频可视化相关原子状态
 // ==================================================================
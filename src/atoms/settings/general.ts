@@ -17,14 +17,33 @@ export const createDefaultGeneralSettings = (): GeneralDefaults => ({
   minimizeToTray: false,
   // launch app at OS login (desktop only)
   launchAtLogin: false,
+  // Playback of the restored queue on launch: "never" | "paused" | "playing".
+  resumeOnLaunch: "paused",
   // Whether to automatically scan on app start.
   autoScanEnabled: false,
   // Folders to scan. Absolute paths.
   scanFolders: [],
   // Minimal duration rule when scanning.
   scanMinDuration: "sec30",
-  // File format rule when scanning.
+  // File format rule when scanning. Superseded by scanExtensions, which has
+  // no default: unset derives the extension list from scanFormats.
   scanFormats: "common",
+  // Time windows for scheduled scans (empty: any time).
+  scanWindows: [],
+  // Scan right away when a window was missed while the machine slept.
+  scanCatchUp: true,
+  // How symlinked files and folders are followed: "never" | "safe" | "always".
+  // scanMaxDepth has no default: unset means no depth limit.
+  scanSymlinks: "safe",
+  // Per-folder depth limit / symlink policy overrides.
+  scanRootOverrides: [],
+  // Wildcard patterns of files/folders left out of scans (e.g. "**/node_modules/**").
+  scanExcludePatterns: [],
+  // Fill in missing tags from file names. scanFilenamePatterns has no default:
+  // unset uses the scanner's built-in patterns.
+  scanFilenameInference: true,
+  // Per-folder file name patterns.
+  scanFilenameOverrides: [],
 })
 
 const {
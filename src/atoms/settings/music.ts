@@ -16,14 +16,83 @@ export const createDefaultMusicSettings = (): MusicDefaults => ({
   // Playback preferences
   playback: {
     normalize: false,
+    normalizeMode: "track",
     crossfadeMs: 0,
+    crossfadeCurve: "equalPower",
+    trackGapMs: 0,
     gapless: true,
+    radioMode: false,
+    trimSilence: false,
+    albumPrecacheDepth: 2,
+    // Empty: follow the system default output device
+    outputDevice: "",
+    // Incoming calls / communication sessions
+    interruptionMode: "pause",
+    interruptionDuckPercent: 20,
+    interruptionResume: true,
+    // Headphone crossfeed: "off" | "low" | "medium" | "high"
+    crossfeed: "off",
+    // Progress updates per second, with and without a focused window
+    positionUpdateHz: 4,
+    backgroundPositionUpdateHz: 0.5,
+    // Playback rate (pitch kept) of music and of podcasts/audiobooks
+    musicRate: 1,
+    spokenRate: 1,
   },
   // Audio effects chain configuration
   effects: {
     enabled: false,
     chain: [],
   },
+  // Offline downloads (smart policy for favorites and heavy rotation)
+  downloads: {
+    smartEnabled: false,
+    includeFavorites: true,
+    minPlayCount: 5,
+    storageBudgetMb: 2048,
+    wifiOnly: true,
+    maxConcurrent: 2,
+    // Format and naming of downloads; providers may override each field
+    defaults: {
+      container: "",
+      bitrateKbps: 0,
+      filenameTemplate: "%id%",
+      embedCover: false,
+      exportLyrics: false,
+    },
+    providers: {},
+  },
+  // Audiobook mode: skip intervals in seconds, and tracks resumed where they
+  // were left off (those with chapters, or at least this many minutes long)
+  audiobook: {
+    skipForwardSecs: 30,
+    skipBackSecs: 10,
+    resumeMinMinutes: 20,
+  },
+  // Provider endpoint overrides (self-hosted API mirrors), keyed by plugin id
+  endpoints: {},
+  // Data saving on cellular connections
+  mobileData: {
+    enabled: true,
+    streamQuality: "medium",
+    allowDownloads: false,
+    allowPrefetch: false,
+    highResArtwork: false,
+  },
+  // Preferred stream quality ("low" | "normal" | "high" | "lossless") on
+  // Wi-Fi and metered connections; providers may override, null lets them choose
+  quality: {
+    default: {
+      wifi: null,
+      metered: null,
+    },
+    providers: {},
+  },
+  // Named output profiles (device, volume, crossfeed, effects) and the active one
+  outputProfiles: {
+    profiles: [],
+    active: null,
+  },
 })
 
 const {
@@ -236,6 +236,12 @@ export const EssentialsSection: FC = () => {
       if (!mounted) return
       scheduleRefresh()
     }).then((fn) => { unlisten = fn as any }).catch(() => {})
+    // Plugins reloaded after their files changed on disk
+    let unlistenReload: (() => void) | undefined
+    listen('plugin-reloaded', () => {
+      if (!mounted) return
+      scheduleRefresh()
+    }).then((fn) => { unlistenReload = fn as any }).catch(() => {})
     // Also refresh when window regains focus (covers cross-window/state drift)
     let unlistenFocus: (() => void) | undefined
     listen('tauri://focus', () => {
@@ -245,6 +251,7 @@ export const EssentialsSection: FC = () => {
     return () => {
       mounted = false
       if (unlisten) unlisten()
+      if (unlistenReload) unlistenReload()
       if (unlistenFocus) unlistenFocus()
       if (refreshTimerRef.current) {
         clearTimeout(refreshTimerRef.current)
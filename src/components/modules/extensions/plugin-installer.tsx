@@ -0,0 +1,85 @@
+import { useState } from 'react'
+import { useTranslation } from 'react-i18next'
+import { open } from '@tauri-apps/plugin-dialog'
+import { Card } from '~/components/ui/card'
+import { Button } from '~/components/ui/button'
+import { Input } from '~/components/ui/input'
+import { Switch } from '~/components/ui/switch'
+import { pluginService, type PluginInstall } from '~/services/plugin-service'
+
+// One-click install of a plugin package from a URL or a local zip archive
+export function PluginInstaller({ onInstalled }: { onInstalled?: (install: PluginInstall) => void }) {
+  const { t } = useTranslation('app')
+  const [url, setUrl] = useState('')
+  const [sha256, setSha256] = useState('')
+  const [enable, setEnable] = useState(true)
+  const [busy, setBusy] = useState(false)
+  const [error, setError] = useState<string | null>(null)
+  const [installed, setInstalled] = useState<PluginInstall | null>(null)
+
+  const run = async (install: () => Promise<PluginInstall>) => {
+    setBusy(true)
+    setError(null)
+    setInstalled(null)
+    try {
+      const result = await install()
+      setInstalled(result)
+      setUrl('')
+      setSha256('')
+      onInstalled?.(result)
+    } catch (e) {
+      setError(String(e))
+    } finally {
+      setBusy(false)
+    }
+  }
+
+  const options = () => ({ sha256: sha256.trim() || undefined, enable })
+
+  const installFromUrl = () => run(() => pluginService.installPluginFromUrl(url.trim(), options()))
+
+  const installFromArchive = async () => {
+    const picked = await open({ multiple: false, filters: [{ name: 'Plugin package', extensions: ['zip'] }] })
+    if (typeof picked !== 'string') return
+    await run(() => pluginService.installPluginFromArchive(picked, options()))
+  }
+
+  return (
+    <Card className="p-6 text-left">
+      <h3 className="text-lg font-semibold mb-1">{t('pages.extensions.install.title')}</h3>
+      <p className="text-sm text-muted-foreground mb-4">{t('pages.extensions.install.description')}</p>
+      <div className="space-y-3">
+        <div className="flex items-center gap-2">
+          <Input
+            placeholder={t('pages.extensions.install.url-placeholder')}
+            value={url}
+            onChange={(e) => setUrl(e.target.value)}
+            className="flex-1"
+          />
+          <Button variant="outline" size="sm" disabled={busy || !url.trim()} onClick={installFromUrl}>
+            {t('pages.extensions.install.from-url')}
+          </Button>
+          <Button variant="outline" size="sm" disabled={busy} onClick={installFromArchive}>
+            {t('pages.extensions.install.from-archive')}
+          </Button>
+        </div>
+        <Input
+          placeholder={t('pages.extensions.install.sha256-placeholder')}
+          value={sha256}
+          onChange={(e) => setSha256(e.target.value)}
+        />
+        <div className="flex items-center justify-between">
+          <span className="text-sm text-muted-foreground">{t('pages.extensions.install.enable')}</span>
+          <Switch checked={enable} onCheckedChange={setEnable} />
+        </div>
+        {busy && <p className="text-sm text-muted-foreground">{t('pages.extensions.install.installing')}</p>}
+        {error && <p className="text-sm text-red-500">{error}</p>}
+        {installed && (
+          <p className="text-sm text-muted-foreground">
+            {t('pages.extensions.install.installed', { name: installed.name, version: installed.version })}
+          </p>
+        )}
+      </div>
+    </Card>
+  )
+}
@@ -0,0 +1,183 @@
+import { type ReactNode, useEffect, useState } from 'react'
+import { useTranslation } from 'react-i18next'
+import { Card } from '~/components/ui/card'
+import { Button } from '~/components/ui/button'
+import { Input } from '~/components/ui/input'
+import { Switch } from '~/components/ui/switch'
+import { ResponsiveSelect } from '~/components/ui/select/responsive'
+import {
+  pluginService,
+  type PluginConfigProperty,
+  type PluginConfigSchema,
+  type PluginConfigValues,
+  type PluginInfo,
+} from '~/services/plugin-service'
+
+interface ConfigurablePlugin {
+  plugin: PluginInfo
+  schema: PluginConfigSchema
+}
+
+// Settings forms of the plugins that declare a config schema
+export function PluginSettings() {
+  const { t } = useTranslation('app')
+  const [configurable, setConfigurable] = useState<ConfigurablePlugin[]>([])
+
+  useEffect(() => {
+    pluginService
+      .getPlugins()
+      .then((list) =>
+        Promise.all(
+          list.map((plugin) =>
+            pluginService
+              .getPluginConfigSchema(plugin.id)
+              .then((schema) => (schema?.properties ? { plugin, schema } : null))
+              .catch(() => null),
+          ),
+        ),
+      )
+      .then((found) => setConfigurable(found.filter((c): c is ConfigurablePlugin => c !== null)))
+      .catch(() => setConfigurable([]))
+  }, [])
+
+  if (configurable.length === 0) return null
+
+  return (
+    <Card className="p-6 mt-4">
+      <h3 className="text-lg font-semibold mb-1">{t('pages.extensions.config.title')}</h3>
+      <p className="text-sm text-muted-foreground mb-4">{t('pages.extensions.config.description')}</p>
+      <div className="space-y-6">
+        {configurable.map(({ plugin, schema }) => (
+          <PluginConfigForm key={plugin.id} plugin={plugin} schema={schema} />
+        ))}
+      </div>
+    </Card>
+  )
+}
+
+function PluginConfigForm({ plugin, schema }: ConfigurablePlugin) {
+  const { t } = useTranslation('app')
+  const [saved, setSaved] = useState<PluginConfigValues>({})
+  const [draft, setDraft] = useState<PluginConfigValues>({})
+  const [error, setError] = useState<string | null>(null)
+  const [saving, setSaving] = useState(false)
+
+  useEffect(() => {
+    pluginService
+      .getPluginConfig(plugin.id)
+      .then((values) => {
+        setSaved(values)
+        setDraft(values)
+      })
+      .catch((e) => setError(String(e)))
+  }, [plugin.id])
+
+  const properties = schema.properties ?? {}
+  const keys = [...(schema['x-order'] ?? []), ...Object.keys(properties)].filter(
+    (key, index, all) => properties[key] && all.indexOf(key) === index,
+  )
+  const dirty = keys.some((key) => draft[key] !== saved[key])
+
+  const save = async () => {
+    setSaving(true)
+    try {
+      // Emptied fields are cleared
+      const values = Object.fromEntries(keys.map((key) => [key, draft[key] === '' ? null : (draft[key] ?? null)]))
+      const applied = await pluginService.setPluginConfig(plugin.id, values)
+      setSaved(applied)
+      setDraft(applied)
+      setError(null)
+    } catch (e) {
+      setError(String(e))
+    } finally {
+      setSaving(false)
+    }
+  }
+
+  return (
+    <div className="space-y-3">
+      <div className="flex items-center justify-between gap-3">
+        <label className="font-medium">{plugin.display_name || plugin.name}</label>
+        <Button variant="outline" size="sm" disabled={!dirty || saving} onClick={save}>
+          {t('pages.extensions.config.save')}
+        </Button>
+      </div>
+      {keys.map((key) => (
+        <ConfigField
+          key={key}
+          name={key}
+          property={properties[key]}
+          required={schema.required?.includes(key) ?? false}
+          value={draft[key]}
+          onChange={(value) => setDraft((prev) => ({ ...prev, [key]: value }))}
+        />
+      ))}
+      {error && <p className="text-sm text-red-500">{error}</p>}
+    </div>
+  )
+}
+
+function ConfigField({
+  name,
+  property,
+  required,
+  value,
+  onChange,
+}: {
+  name: string
+  property: PluginConfigProperty
+  required: boolean
+  value: unknown
+  onChange: (value: unknown) => void
+}) {
+  const label = `${property.title ?? name}${required ? ' *' : ''}`
+
+  let control: ReactNode
+  if (property.type === 'boolean') {
+    control = <Switch checked={value === true} onCheckedChange={onChange} />
+  } else if (property.enum) {
+    control = (
+      <ResponsiveSelect
+        size="sm"
+        triggerClassName="w-48"
+        value={typeof value === 'string' ? value : ''}
+        onValueChange={onChange}
+        items={property.enum.map((option) => ({ label: option, value: option }))}
+      />
+    )
+  } else if (property.type === 'number' || property.type === 'integer') {
+    control = (
+      <Input
+        type="number"
+        className="w-48"
+        min={property.minimum}
+        max={property.maximum}
+        step={property.type === 'integer' ? 1 : 'any'}
+        value={typeof value === 'number' ? String(value) : ''}
+        onChange={(e) => {
+          const parsed = Number(e.target.value)
+          onChange(e.target.value === '' || Number.isNaN(parsed) ? null : parsed)
+        }}
+      />
+    )
+  } else {
+    control = (
+      <Input
+        type={property.format === 'password' ? 'password' : 'text'}
+        className="w-64"
+        value={typeof value === 'string' ? value : ''}
+        onChange={(e) => onChange(e.target.value)}
+      />
+    )
+  }
+
+  return (
+    <div className="space-y-1">
+      <div className="flex items-center justify-between gap-4">
+        <span className="text-sm">{label}</span>
+        {control}
+      </div>
+      {property.description && <p className="text-sm text-muted-foreground">{property.description}</p>}
+    </div>
+  )
+}
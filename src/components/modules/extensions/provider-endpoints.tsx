@@ -0,0 +1,103 @@
+import { useEffect, useState } from 'react'
+import { useTranslation } from 'react-i18next'
+import { Card } from '~/components/ui/card'
+import { Button } from '~/components/ui/button'
+import { Badge } from '~/components/ui/badge'
+import { Input } from '~/components/ui/input'
+import { Switch } from '~/components/ui/switch'
+import { useMusicSettingValue } from '~/atoms/settings/music'
+import { pluginService, type EndpointStatus, type PluginInfo } from '~/services/plugin-service'
+
+// Self-hosted API mirrors per provider, with the health of each mirror
+export function ProviderEndpoints() {
+  const { t } = useTranslation('app')
+  const { endpoints } = useMusicSettingValue()
+  const [providers, setProviders] = useState<PluginInfo[]>([])
+  const [drafts, setDrafts] = useState<Record<string, string>>({})
+  const [errors, setErrors] = useState<Record<string, string>>({})
+  const [statuses, setStatuses] = useState<Record<string, EndpointStatus>>({})
+
+  const refreshStatus = () => {
+    pluginService
+      .getProviderEndpointStatus()
+      .then((list) => setStatuses(Object.fromEntries(list.map((s) => [s.plugin_id, s]))))
+      .catch(() => {})
+  }
+
+  useEffect(() => {
+    pluginService
+      .getPlugins()
+      .then((list) =>
+        setProviders(list.filter((p) => p.plugin_type.toLowerCase().replace(/[-_\s]/g, '') === 'audioprovider')),
+      )
+      .catch(() => setProviders([]))
+    refreshStatus()
+    const timer = setInterval(refreshStatus, 30_000)
+    return () => clearInterval(timer)
+  }, [])
+
+  const save = async (id: string, baseUrl: string, fallback: boolean) => {
+    try {
+      await pluginService.setProviderEndpoint(id, baseUrl.trim(), fallback)
+      setErrors(({ [id]: _removed, ...rest }) => rest)
+      setDrafts(({ [id]: _removed, ...rest }) => rest)
+      // Overrides are applied and checked in the background
+      setTimeout(refreshStatus, 1500)
+    } catch (e) {
+      setErrors((prev) => ({ ...prev, [id]: String(e) }))
+    }
+  }
+
+  const statusBadge = (status?: EndpointStatus) => {
+    if (!status) return null
+    if (status.healthy) return <Badge variant="outline">{t('pages.extensions.endpoints.healthy')}</Badge>
+    return (
+      <Badge variant="outline" className="text-red-500" title={status.error ?? undefined}>
+        {status.active ? t('pages.extensions.endpoints.unhealthy') : t('pages.extensions.endpoints.fallback-active')}
+      </Badge>
+    )
+  }
+
+  return (
+    <Card className="p-6 mt-4">
+      <h3 className="text-lg font-semibold mb-1">{t('pages.extensions.endpoints.title')}</h3>
+      <p className="text-sm text-muted-foreground mb-4">{t('pages.extensions.endpoints.description')}</p>
+      <div className="space-y-4">
+        {providers.map((provider) => {
+          const saved = endpoints[provider.id]
+          const fallback = saved?.fallback ?? true
+          const draft = drafts[provider.id] ?? saved?.baseUrl ?? ''
+          const dirty = draft.trim() !== (saved?.baseUrl ?? '')
+          return (
+            <div key={provider.id} className="space-y-2">
+              <div className="flex items-center justify-between gap-3">
+                <label className="font-medium">{provider.display_name || provider.name}</label>
+                {saved?.baseUrl && statusBadge(statuses[provider.id])}
+              </div>
+              <div className="flex items-center gap-2">
+                <Input
+                  placeholder={t('pages.extensions.endpoints.placeholder')}
+                  value={draft}
+                  onChange={(e) => setDrafts((prev) => ({ ...prev, [provider.id]: e.target.value }))}
+                  className="flex-1"
+                />
+                <Button variant="outline" size="sm" disabled={!dirty} onClick={() => save(provider.id, draft, fallback)}>
+                  {t('pages.extensions.endpoints.save')}
+                </Button>
+              </div>
+              {errors[provider.id] && <p className="text-sm text-red-500">{errors[provider.id]}</p>}
+              <div className="flex items-center justify-between">
+                <span className="text-sm text-muted-foreground">{t('pages.extensions.endpoints.fallback')}</span>
+                <Switch
+                  checked={fallback}
+                  disabled={!saved?.baseUrl}
+                  onCheckedChange={(checked) => save(provider.id, saved?.baseUrl ?? '', checked)}
+                />
+              </div>
+            </div>
+          )
+        })}
+      </div>
+    </Card>
+  )
+}
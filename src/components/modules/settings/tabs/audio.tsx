@@ -0,0 +1,380 @@
+import { useEffect, useState } from "react"
+import { useTranslation } from "react-i18next"
+import { setMusic, setMusicSetting, useMusicSettingValue } from "~/atoms/settings/music"
+import { SettingItemGroup, SettingSectionTitle } from "../section"
+import { SettingDescription, SettingInput, SettingSwitch } from "../control"
+import { ResponsiveSelect } from "~/components/ui/select/responsive"
+import {
+  audioService,
+  type InterruptionMode,
+  type NormalizationMode,
+  type OutputDevice,
+} from "~/services/audio-service"
+
+type CrossfadeCurve = "linear" | "logarithmic" | "equalPower"
+
+const MAX_CROSSFADE_SECONDS = 12
+const MAX_TRACK_GAP_SECONDS = 30
+const MAX_PRECACHE_DEPTH = 5
+const DEFAULT_DUCK_PERCENT = 20
+const MIN_POSITION_HZ = 0.1
+const MAX_POSITION_HZ = 30
+
+export const SettingAudio = () => {
+  const { t } = useTranslation("settings")
+
+  return (
+    <div className="mt-4">
+      <SettingSectionTitle title={t("audio.output")} />
+      <OutputDeviceItem />
+      <SettingSectionTitle title={t("audio.transitions")} />
+      <CrossfadeItem />
+      <CrossfadeCurveItem />
+      <TrackGapItem />
+      <SettingSectionTitle title={t("audio.normalization")} />
+      <NormalizationItem />
+      <NormalizationModeItem />
+      <TrimSilenceItem />
+      <SettingSectionTitle title={t("audio.interruptions")} />
+      <InterruptionModeItem />
+      <InterruptionDuckItem />
+      <InterruptionResumeItem />
+      <SettingSectionTitle title={t("audio.radio")} />
+      <RadioModeItem />
+      <SettingSectionTitle title={t("audio.precache")} />
+      <AlbumPrecacheItem />
+      <SettingSectionTitle title={t("audio.position")} />
+      <PositionRateItem />
+    </div>
+  )
+}
+
+const DEFAULT_DEVICE = "__default__"
+
+// The backend persists the choice and falls back on its own when the device goes away
+const OutputDeviceItem = () => {
+  const { t } = useTranslation("settings")
+  const { playback } = useMusicSettingValue()
+  const [devices, setDevices] = useState<OutputDevice[]>([])
+
+  useEffect(() => {
+    audioService.listOutputDevices().then(setDevices).catch(() => {})
+  }, [playback.outputDevice])
+
+  const items = [
+    { label: t("audio.output.device.default"), value: DEFAULT_DEVICE },
+    ...devices.map((d) => ({ label: d.name, value: d.name })),
+  ]
+  return (
+    <SettingItemGroup>
+      <div className="mb-1 mt-2 flex items-center justify-between">
+        <span className="shrink-0 text-sm font-medium">{t("audio.output.device")}</span>
+        <ResponsiveSelect
+          size="sm"
+          triggerClassName="w-48"
+          value={playback.outputDevice || DEFAULT_DEVICE}
+          onValueChange={(value) => {
+            const outputDevice = value === DEFAULT_DEVICE ? "" : value
+            setMusicSetting("playback", { ...playback, outputDevice })
+            audioService.setOutputDevice(outputDevice || null).catch(() => {})
+          }}
+          items={items}
+        />
+      </div>
+      <SettingDescription>{t("audio.output.device.description")}</SettingDescription>
+    </SettingItemGroup>
+  )
+}
+
+// Crossfade and track gap are mutually exclusive: the backend clears one when the
+// other is enabled, mirror that locally so the form reflects the saved state.
+const CrossfadeItem = () => {
+  const { t } = useTranslation("settings")
+  const { playback } = useMusicSettingValue()
+  const seconds = (playback.crossfadeMs ?? 0) / 1000
+  return (
+    <SettingItemGroup>
+      <SettingInput
+        type="number"
+        label={t("audio.transitions.crossfade")}
+        value={String(seconds)}
+        onChange={(e) => {
+          const s = Math.max(0, Math.min(MAX_CROSSFADE_SECONDS, Number(e.target.value) || 0))
+          const crossfadeMs = Math.round(s * 1000)
+          setMusicSetting("playback", {
+            ...playback,
+            crossfadeMs,
+            trackGapMs: crossfadeMs > 0 ? 0 : playback.trackGapMs,
+          })
+          audioService.setCrossfade(crossfadeMs).catch(() => {})
+        }}
+        inputClassName="w-48"
+      />
+      <SettingDescription>{t("audio.transitions.crossfade.description")}</SettingDescription>
+    </SettingItemGroup>
+  )
+}
+
+const CrossfadeCurveItem = () => {
+  const { t } = useTranslation("settings")
+  const { playback } = useMusicSettingValue()
+  const items: { label: string; value: CrossfadeCurve }[] = [
+    { label: t("audio.transitions.crossfade_curve.equal_power"), value: "equalPower" },
+    { label: t("audio.transitions.crossfade_curve.linear"), value: "linear" },
+    { label: t("audio.transitions.crossfade_curve.logarithmic"), value: "logarithmic" },
+  ]
+  return (
+    <SettingItemGroup>
+      <div className="mb-3 flex items-center justify-between gap-4">
+        <label className="text-sm font-medium leading-none">
+          {t("audio.transitions.crossfade_curve")}
+        </label>
+        <ResponsiveSelect
+          size="sm"
+          triggerClassName="w-48"
+          value={(playback.crossfadeCurve as string) || "equalPower"}
+          onValueChange={(v) => {
+            const curve = v as CrossfadeCurve
+            setMusicSetting("playback", { ...playback, crossfadeCurve: curve })
+            audioService.setCrossfade(playback.crossfadeMs ?? 0, curve).catch(() => {})
+          }}
+          items={items}
+        />
+      </div>
+      <SettingDescription>{t("audio.transitions.crossfade_curve.description")}</SettingDescription>
+    </SettingItemGroup>
+  )
+}
+
+const TrackGapItem = () => {
+  const { t } = useTranslation("settings")
+  const { playback } = useMusicSettingValue()
+  const seconds = (playback.trackGapMs ?? 0) / 1000
+  return (
+    <SettingItemGroup>
+      <SettingInput
+        type="number"
+        label={t("audio.transitions.track_gap")}
+        value={String(seconds)}
+        onChange={(e) => {
+          const s = Math.max(0, Math.min(MAX_TRACK_GAP_SECONDS, Number(e.target.value) || 0))
+          const trackGapMs = Math.round(s * 1000)
+          setMusicSetting("playback", {
+            ...playback,
+            trackGapMs,
+            crossfadeMs: trackGapMs > 0 ? 0 : playback.crossfadeMs,
+          })
+          audioService.setTrackGap(trackGapMs).catch(() => {})
+        }}
+        inputClassName="w-48"
+      />
+      <SettingDescription>{t("audio.transitions.track_gap.description")}</SettingDescription>
+    </SettingItemGroup>
+  )
+}
+
+// Applied by the backend when prefs.music.playback changes
+const NormalizationItem = () => {
+  const { t } = useTranslation("settings")
+  const { playback } = useMusicSettingValue()
+  return (
+    <SettingItemGroup>
+      <SettingSwitch
+        label={t("audio.normalization.enabled")}
+        checked={!!playback.normalize}
+        onCheckedChange={(normalize) => setMusic("playback", { ...playback, normalize })}
+      />
+      <SettingDescription>{t("audio.normalization.enabled.description")}</SettingDescription>
+    </SettingItemGroup>
+  )
+}
+
+// Selectable before normalization is on, together with the preview in the library
+const NormalizationModeItem = () => {
+  const { t } = useTranslation("settings")
+  const { playback } = useMusicSettingValue()
+  const items: { label: string; value: NormalizationMode }[] = [
+    { label: t("audio.normalization.mode.track"), value: "track" },
+    { label: t("audio.normalization.mode.album"), value: "album" },
+  ]
+  return (
+    <SettingItemGroup>
+      <div className="mb-3 flex items-center justify-between gap-4">
+        <label className="text-sm font-medium leading-none">{t("audio.normalization.mode")}</label>
+        <ResponsiveSelect
+          size="sm"
+          triggerClassName="w-48"
+          value={(playback.normalizeMode as string) || "track"}
+          onValueChange={(v) => {
+            setMusic("playback", { ...playback, normalizeMode: v as NormalizationMode })
+          }}
+          items={items}
+        />
+      </div>
+      <SettingDescription>{t("audio.normalization.mode.description")}</SettingDescription>
+      <SettingDescription>{t("audio.normalization.preview")}</SettingDescription>
+    </SettingItemGroup>
+  )
+}
+
+// The backend persists the policy along with applying it
+const InterruptionModeItem = () => {
+  const { t } = useTranslation("settings")
+  const { playback } = useMusicSettingValue()
+  const items: { label: string; value: InterruptionMode }[] = [
+    { label: t("audio.interruptions.mode.pause"), value: "pause" },
+    { label: t("audio.interruptions.mode.duck"), value: "duck" },
+    { label: t("audio.interruptions.mode.ignore"), value: "ignore" },
+  ]
+  return (
+    <SettingItemGroup>
+      <div className="mb-3 flex items-center justify-between gap-4">
+        <label className="text-sm font-medium leading-none">{t("audio.interruptions.mode")}</label>
+        <ResponsiveSelect
+          size="sm"
+          triggerClassName="w-48"
+          value={(playback.interruptionMode as string) || "pause"}
+          onValueChange={(v) => {
+            const interruptionMode = v as InterruptionMode
+            setMusicSetting("playback", { ...playback, interruptionMode })
+            audioService.setInterruptionPolicy(interruptionMode).catch(() => {})
+          }}
+          items={items}
+        />
+      </div>
+      <SettingDescription>{t("audio.interruptions.mode.description")}</SettingDescription>
+    </SettingItemGroup>
+  )
+}
+
+const InterruptionDuckItem = () => {
+  const { t } = useTranslation("settings")
+  const { playback } = useMusicSettingValue()
+  if (playback.interruptionMode !== "duck") return null
+  return (
+    <SettingItemGroup>
+      <SettingInput
+        type="number"
+        label={t("audio.interruptions.duck_percent")}
+        value={String(playback.interruptionDuckPercent ?? DEFAULT_DUCK_PERCENT)}
+        onChange={(e) => {
+          const interruptionDuckPercent = Math.max(0, Math.min(100, Math.round(Number(e.target.value) || 0)))
+          setMusicSetting("playback", { ...playback, interruptionDuckPercent })
+          audioService.setInterruptionPolicy("duck", interruptionDuckPercent).catch(() => {})
+        }}
+        inputClassName="w-48"
+      />
+      <SettingDescription>{t("audio.interruptions.duck_percent.description")}</SettingDescription>
+    </SettingItemGroup>
+  )
+}
+
+const InterruptionResumeItem = () => {
+  const { t } = useTranslation("settings")
+  const { playback } = useMusicSettingValue()
+  if ((playback.interruptionMode ?? "pause") !== "pause") return null
+  return (
+    <SettingItemGroup>
+      <SettingSwitch
+        label={t("audio.interruptions.resume")}
+        checked={playback.interruptionResume ?? true}
+        onCheckedChange={(interruptionResume) => {
+          setMusicSetting("playback", { ...playback, interruptionResume })
+          audioService.setInterruptionPolicy("pause", undefined, interruptionResume).catch(() => {})
+        }}
+      />
+      <SettingDescription>{t("audio.interruptions.resume.description")}</SettingDescription>
+    </SettingItemGroup>
+  )
+}
+
+// Applied by the backend through settings-changed
+const TrimSilenceItem = () => {
+  const { t } = useTranslation("settings")
+  const { playback } = useMusicSettingValue()
+  return (
+    <SettingItemGroup>
+      <SettingSwitch
+        label={t("audio.trimSilence")}
+        checked={!!playback.trimSilence}
+        onCheckedChange={(trimSilence) => setMusicSetting("playback", { ...playback, trimSilence })}
+      />
+      <SettingDescription>{t("audio.trimSilence.description")}</SettingDescription>
+    </SettingItemGroup>
+  )
+}
+
+const RadioModeItem = () => {
+  const { t } = useTranslation("settings")
+  const { playback } = useMusicSettingValue()
+  return (
+    <SettingItemGroup>
+      <SettingSwitch
+        label={t("audio.radio.enabled")}
+        checked={!!playback.radioMode}
+        onCheckedChange={(radioMode) => {
+          setMusicSetting("playback", { ...playback, radioMode })
+          audioService.setRadioMode(radioMode).catch(() => {})
+        }}
+      />
+      <SettingDescription>{t("audio.radio.enabled.description")}</SettingDescription>
+    </SettingItemGroup>
+  )
+}
+
+// Read by the backend whenever a track starts loading, so persist it right away
+const AlbumPrecacheItem = () => {
+  const { t } = useTranslation("settings")
+  const { playback } = useMusicSettingValue()
+  return (
+    <SettingItemGroup>
+      <SettingInput
+        type="number"
+        label={t("audio.precache.depth")}
+        value={String(playback.albumPrecacheDepth ?? 2)}
+        onChange={(e) => {
+          const albumPrecacheDepth = Math.max(0, Math.min(MAX_PRECACHE_DEPTH, Math.round(Number(e.target.value) || 0)))
+          setMusic("playback", { ...playback, albumPrecacheDepth })
+        }}
+        inputClassName="w-48"
+      />
+      <SettingDescription>{t("audio.precache.depth.description")}</SettingDescription>
+    </SettingItemGroup>
+  )
+}
+
+const clampPositionHz = (value: string, fallback: number) => {
+  const hz = Number(value)
+  return Number.isFinite(hz) && hz > 0 ? Math.max(MIN_POSITION_HZ, Math.min(MAX_POSITION_HZ, hz)) : fallback
+}
+
+// Applied by the backend when prefs.music.playback changes
+const PositionRateItem = () => {
+  const { t } = useTranslation("settings")
+  const { playback } = useMusicSettingValue()
+  return (
+    <SettingItemGroup>
+      <SettingInput
+        type="number"
+        label={t("audio.position.foreground")}
+        value={String(playback.positionUpdateHz ?? 4)}
+        onChange={(e) => {
+          const positionUpdateHz = clampPositionHz(e.target.value, 4)
+          setMusic("playback", { ...playback, positionUpdateHz })
+        }}
+        inputClassName="w-48"
+      />
+      <SettingInput
+        type="number"
+        label={t("audio.position.background")}
+        value={String(playback.backgroundPositionUpdateHz ?? 0.5)}
+        onChange={(e) => {
+          const backgroundPositionUpdateHz = clampPositionHz(e.target.value, 0.5)
+          setMusic("playback", { ...playback, backgroundPositionUpdateHz })
+        }}
+        inputClassName="w-48"
+      />
+      <SettingDescription>{t("audio.position.description")}</SettingDescription>
+    </SettingItemGroup>
+  )
+}
@@ -1,4 +1,5 @@
 import { cn } from "~/lib/helper"
+import { useEffect, useState } from "react"
 import { open } from "@tauri-apps/plugin-dialog"
 import TrashIcon from "~/assets/icons/trash.svg?react"
 import FolderIcon from "~/assets/icons/folder.svg?react"
@@ -15,7 +16,11 @@ import { useTranslation } from "react-i18next"
 import { SettingItemGroup } from "../section"
 import { SettingDescription, SettingInput, SettingSwitch } from "../control"
 import { ResponsiveSelect } from "~/components/ui/select/responsive"
+import { TextArea } from "~/components/ui/input/text-area"
 import { currentSupportedLanguages } from "~/i18n"
+import { Input } from "~/components/ui/input"
+import scannerService from "~/services/scanner-service"
+import type { ScanExtension, ScanWindow } from "~/types/bindings"
 
 const { defineSettingItem: _defineSettingItem, SettingBuilder } = createSetting(
   useGeneralSettingValue,
@@ -38,6 +43,7 @@ export const SettingGeneral = () => {
           },
           LaunchAtLoginSetting,
           MinimizeToTraySetting,
+          ResumeOnLaunchSetting,
           LanguageSelector,
 
           {
@@ -47,17 +53,20 @@ export const SettingGeneral = () => {
           AutoScanEnabledSetting,
           ScanFoldersSetting,
           ScanRulesSetting,
+          ScanExtensionsSetting,
+          ScanTraversalSetting,
+          ScanFilenameSetting,
+          ScanScheduleSetting,
         ]}
       />
     </div>
   )
 }
 
-// Scan rules: minimal UI matching backend enums (scanMinDuration, scanFormats)
+// Scan rules: minimal UI matching backend enums (scanMinDuration)
 const ScanRulesSetting = () => {
   const { t } = useTranslation('settings')
   const scanMinDuration = (useGeneralSettingKey('scanMinDuration') as string) || 'sec30'
-  const scanFormats = (useGeneralSettingKey('scanFormats') as string) || 'common'
 
   return (
     <SettingItemGroup>
@@ -77,20 +86,236 @@ const ScanRulesSetting = () => {
       </div>
       <SettingDescription>{t('general.scan_min_duration.description')}</SettingDescription>
 
+    </SettingItemGroup>
+  )
+}
+
+// Scanned file extensions (scanExtensions); unset derives from the legacy scanFormats
+const ScanExtensionsSetting = () => {
+  const { t } = useTranslation('settings')
+  const [extensions, setExtensions] = useState<ScanExtension[]>([])
+  const [draft, setDraft] = useState('')
+  const [error, setError] = useState<string | null>(null)
+
+  useEffect(() => {
+    scannerService
+      .getScanExtensions()
+      .then(setExtensions)
+      .catch((e) => console.error('[ScanExtensionsSetting] load error:', e))
+  }, [])
+
+  const save = async (next: ScanExtension[]) => {
+    try {
+      setExtensions(await scannerService.setScanExtensions(next))
+      setError(null)
+      return true
+    } catch (e) {
+      setError(String(e))
+      return false
+    }
+  }
+
+  const toggle = (extension: string, enabled: boolean) =>
+    save(extensions.map((e) => (e.extension === extension ? { ...e, enabled } : e)))
+
+  const add = async () => {
+    const extension = draft.trim()
+    if (!extension) return
+    if (await save([...extensions, { extension, enabled: true }])) setDraft('')
+  }
+
+  return (
+    <SettingItemGroup>
+      <div className="mb-1 mt-4 flex items-center justify-between">
+        <span className="shrink-0 text-sm font-medium">{t('general.scan_extensions.label')}</span>
+      </div>
+      <SettingDescription>{t('general.scan_extensions.description')}</SettingDescription>
+      <div className="mt-2 grid grid-cols-2 gap-x-6 sm:grid-cols-3">
+        {extensions.map((e) => (
+          <SettingSwitch
+            key={e.extension}
+            checked={e.enabled}
+            onCheckedChange={(checked) => toggle(e.extension, checked)}
+            label={`.${e.extension}`}
+          />
+        ))}
+      </div>
+      <div className="mt-3 flex items-center gap-2">
+        <Input
+          className="h-8 w-48 text-sm"
+          value={draft}
+          placeholder={t('general.scan_extensions.placeholder')}
+          onChange={(e) => setDraft(e.target.value)}
+          onKeyDown={(e) => e.key === 'Enter' && add()}
+        />
+        <button
+          type="button"
+          onClick={add}
+          className="inline-flex items-center rounded-md border border-border px-2 py-1 text-xs hover:bg-accent/5"
+        >
+          <PlusIcon className="mr-1.5 h-3.5 w-3.5" />
+          <span>{t('general.scan_extensions.add')}</span>
+        </button>
+      </div>
+      {error && <p className="mt-1 text-xs text-red-500">{error}</p>}
+    </SettingItemGroup>
+  )
+}
+
+const SCAN_DEPTH_OPTIONS = ['unlimited', '0', '1', '2', '3', '5']
+
+// Folder traversal: depth limit and symlink policy (scanMaxDepth, scanSymlinks)
+const ScanTraversalSetting = () => {
+  const { t } = useTranslation('settings')
+  const scanMaxDepth = useGeneralSettingKey('scanMaxDepth' as any) as number | null | undefined
+  const scanSymlinks = (useGeneralSettingKey('scanSymlinks') as string) || 'safe'
+  const depth = scanMaxDepth == null ? 'unlimited' : String(scanMaxDepth)
+  const depthLabel = (value: string) => {
+    if (value === 'unlimited') return t('general.scan_max_depth.unlimited')
+    if (value === '0') return t('general.scan_max_depth.top')
+    return t('general.scan_max_depth.levels', { count: Number(value) })
+  }
+
+  return (
+    <SettingItemGroup>
+      <div className="mb-3 mt-4 flex items-center justify-between">
+        <span className="shrink-0 text-sm font-medium">{t('general.scan_max_depth.label')}</span>
+        <ResponsiveSelect
+          size="sm"
+          triggerClassName="w-48"
+          value={depth}
+          onValueChange={(value) =>
+            setGeneral('scanMaxDepth' as any, (value === 'unlimited' ? null : Number(value)) as any)
+          }
+          items={(SCAN_DEPTH_OPTIONS.includes(depth) ? SCAN_DEPTH_OPTIONS : [...SCAN_DEPTH_OPTIONS, depth]).map(
+            (value) => ({ label: depthLabel(value), value }),
+          )}
+        />
+      </div>
+      <SettingDescription>{t('general.scan_max_depth.description')}</SettingDescription>
+
+      <div className="mb-1 mt-4 flex items-center justify-between">
+        <span className="shrink-0 text-sm font-medium">{t('general.scan_symlinks.label')}</span>
+        <ResponsiveSelect
+          size="sm"
+          triggerClassName="w-48"
+          value={scanSymlinks}
+          onValueChange={(value) => setGeneral('scanSymlinks', value as any)}
+          items={[
+            { label: t('general.scan_symlinks.never'), value: 'never' },
+            { label: t('general.scan_symlinks.safe'), value: 'safe' },
+            { label: t('general.scan_symlinks.always'), value: 'always' },
+          ]}
+        />
+      </div>
+      <SettingDescription>{t('general.scan_symlinks.description')}</SettingDescription>
+    </SettingItemGroup>
+  )
+}
+
+// Shown as a hint only; the scanner owns the built-in list
+const DEFAULT_FILENAME_PATTERNS = [
+  '%track% - %artist% - %title%',
+  '%track%. %artist% - %title%',
+  '%track% - %title%',
+  '%track%. %title%',
+  '%artist% - %title%',
+]
+
+// Tags inferred from file names when missing (scanFilenameInference, scanFilenamePatterns)
+const ScanFilenameSetting = () => {
+  const { t } = useTranslation('settings')
+  const enabled = useGeneralSettingKey('scanFilenameInference' as any) as boolean | undefined
+  const patterns = useGeneralSettingKey('scanFilenamePatterns' as any) as string[] | null | undefined
+  const [draft, setDraft] = useState((patterns ?? []).join('\n'))
+
+  useEffect(() => {
+    setDraft((patterns ?? []).join('\n'))
+  }, [patterns])
+
+  const save = () => {
+    const lines = draft
+      .split('\n')
+      .map((line) => line.trim())
+      .filter(Boolean)
+    setGeneral('scanFilenamePatterns' as any, (lines.length > 0 ? lines : null) as any)
+  }
+
+  return (
+    <SettingItemGroup>
+      <SettingSwitch
+        checked={enabled ?? true}
+        className="mt-4"
+        onCheckedChange={(checked) => setGeneral('scanFilenameInference' as any, checked as any)}
+        label={t('general.scan_filename.label')}
+      />
+      <SettingDescription>{t('general.scan_filename.description')}</SettingDescription>
+
+      {(enabled ?? true) && (
+        <div className="mt-3">
+          <span className="text-sm font-medium">{t('general.scan_filename.patterns')}</span>
+          <TextArea
+            className="mt-2 min-h-28 font-mono text-xs"
+            rounded="md"
+            value={draft}
+            placeholder={DEFAULT_FILENAME_PATTERNS.join('\n')}
+            onChange={(e) => setDraft(e.target.value)}
+            onBlur={save}
+          />
+          <SettingDescription>{t('general.scan_filename.patterns_description')}</SettingDescription>
+        </div>
+      )}
+    </SettingItemGroup>
+  )
+}
+
+type ScanSchedulePreset = "anytime" | "nightly" | "weekends" | "custom"
+
+const SCAN_SCHEDULE_PRESETS: Record<Exclude<ScanSchedulePreset, "custom">, ScanWindow[]> = {
+  anytime: [],
+  nightly: [{ days: [], start: "02:00", end: "05:00" }],
+  weekends: [{ days: ["sat", "sun"], start: "00:00", end: "00:00" }],
+}
+
+// Scheduled scans only; "Scan now" and folder changes always scan right away
+const ScanScheduleSetting = () => {
+  const { t } = useTranslation('settings')
+  const scanWindows = (useGeneralSettingKey('scanWindows') as ScanWindow[] | undefined) || []
+  const scanCatchUp = useGeneralSettingKey('scanCatchUp') as boolean | undefined
+  const preset =
+    (Object.entries(SCAN_SCHEDULE_PRESETS).find(
+      ([, windows]) => JSON.stringify(windows) === JSON.stringify(scanWindows),
+    )?.[0] as ScanSchedulePreset | undefined) ?? 'custom'
+
+  return (
+    <SettingItemGroup>
       <div className="mb-1 mt-4 flex items-center justify-between">
-        <span className="shrink-0 text-sm font-medium">{t('general.scan_formats.label')}</span>
+        <span className="shrink-0 text-sm font-medium">{t('general.scan_schedule.label')}</span>
         <ResponsiveSelect
           size="sm"
           triggerClassName="w-48"
-          value={scanFormats}
-          onValueChange={(value) => setGeneral('scanFormats', value as any)}
+          value={preset}
+          onValueChange={(value) => {
+            if (value === 'custom') return
+            setGeneral('scanWindows', SCAN_SCHEDULE_PRESETS[value as Exclude<ScanSchedulePreset, 'custom'>])
+          }}
           items={[
-            { label: t('general.scan_formats.common'), value: 'common' },
-            { label: t('general.scan_formats.all'), value: 'all' },
+            { label: t('general.scan_schedule.anytime'), value: 'anytime' },
+            { label: t('general.scan_schedule.nightly'), value: 'nightly' },
+            { label: t('general.scan_schedule.weekends'), value: 'weekends' },
+            ...(preset === 'custom' ? [{ label: t('general.scan_schedule.custom'), value: 'custom' }] : []),
           ]}
         />
       </div>
-      <SettingDescription>{t('general.scan_formats.description')}</SettingDescription>
+      <SettingDescription>{t('general.scan_schedule.description')}</SettingDescription>
+
+      <SettingSwitch
+        checked={scanCatchUp ?? true}
+        className="mt-4"
+        onCheckedChange={(checked) => setGeneral('scanCatchUp', checked)}
+        label={t('general.scan_catch_up.label')}
+      />
+      <SettingDescription>{t('general.scan_catch_up.description')}</SettingDescription>
     </SettingItemGroup>
   )
 }
@@ -194,6 +419,31 @@ const MinimizeToTraySetting = () => {
   )
 }
 
+// Restored queue on launch (resumeOnLaunch); read by the player at startup
+const ResumeOnLaunchSetting = () => {
+  const { t } = useTranslation('settings')
+  const resumeOnLaunch = (useGeneralSettingKey('resumeOnLaunch' as any) as string) || 'paused'
+  return (
+    <SettingItemGroup>
+      <div className="mb-1 mt-4 flex items-center justify-between">
+        <span className="shrink-0 text-sm font-medium">{t('general.resume_on_launch.label')}</span>
+        <ResponsiveSelect
+          size="sm"
+          triggerClassName="w-48"
+          value={resumeOnLaunch}
+          onValueChange={(value) => setGeneral('resumeOnLaunch' as any, value as any)}
+          items={[
+            { label: t('general.resume_on_launch.never'), value: 'never' },
+            { label: t('general.resume_on_launch.paused'), value: 'paused' },
+            { label: t('general.resume_on_launch.playing'), value: 'playing' },
+          ]}
+        />
+      </div>
+      <SettingDescription>{t('general.resume_on_launch.description')}</SettingDescription>
+    </SettingItemGroup>
+  )
+}
+
 const LaunchAtLoginSetting = () => {
   const { t } = useTranslation('settings')
   const launch = useGeneralSettingKey('launchAtLogin') as boolean | undefined
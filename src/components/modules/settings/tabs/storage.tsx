@@ -0,0 +1,346 @@
+import { useEffect, useState } from "react"
+import { useTranslation } from "react-i18next"
+import { setMusic, useMusicSettingValue } from "~/atoms/settings/music"
+import { SettingItemGroup, SettingSectionTitle } from "../section"
+import { SettingDescription, SettingInput, SettingSwitch } from "../control"
+import { ResponsiveSelect } from "~/components/ui/select/responsive"
+import { Button } from "~/components/ui/button"
+import { scannerService, type LibraryStorageReport } from "~/services/scanner-service"
+import { pluginService, type PluginInfo } from "~/services/plugin-service"
+import { networkService, type DataUsageReport } from "~/services/network-service"
+
+type DownloadSettings = ReturnType<typeof useMusicSettingValue>["downloads"]
+type DownloadFormat = DownloadSettings["defaults"]
+type MobileDataSettings = ReturnType<typeof useMusicSettingValue>["mobileData"]
+
+// Scope of the format editor: the defaults, or a provider's overrides
+const DEFAULTS_SCOPE = "__defaults__"
+const CONTAINERS = ["", "flac", "m4a", "mp3", "ogg", "opus"]
+const BITRATES = [0, 128, 192, 320]
+const CELLULAR_QUALITIES = ["low", "medium", "high", "unrestricted"]
+
+const useDownloadSettings = () => {
+  const { downloads } = useMusicSettingValue()
+  const update = (patch: Partial<DownloadSettings>) => setMusic("downloads", { ...downloads, ...patch })
+  return [downloads, update] as const
+}
+
+const formatBytes = (bytes: number) => {
+  const units = ["B", "KB", "MB", "GB", "TB"]
+  let value = bytes
+  let unit = 0
+  while (value >= 1024 && unit < units.length - 1) {
+    value /= 1024
+    unit++
+  }
+  return `${unit === 0 ? value : value.toFixed(1)} ${units[unit]}`
+}
+
+export const SettingStorage = () => {
+  const { t } = useTranslation("settings")
+  const [downloads, update] = useDownloadSettings()
+
+  return (
+    <div className="mt-4">
+      <DiskUsage />
+      <SettingSectionTitle title={t("storage.smart_download")} />
+      <SettingItemGroup>
+        <SettingSwitch
+          label={t("storage.smart_download.enabled")}
+          checked={downloads.smartEnabled}
+          onCheckedChange={(smartEnabled) => update({ smartEnabled })}
+        />
+        <SettingDescription>{t("storage.smart_download.enabled.description")}</SettingDescription>
+      </SettingItemGroup>
+      <SettingItemGroup>
+        <SettingSwitch
+          label={t("storage.smart_download.include_favorites")}
+          checked={downloads.includeFavorites}
+          onCheckedChange={(includeFavorites) => update({ includeFavorites })}
+        />
+      </SettingItemGroup>
+      <SettingItemGroup>
+        <SettingInput
+          type="number"
+          label={t("storage.smart_download.min_play_count")}
+          value={String(downloads.minPlayCount)}
+          onChange={(e) => update({ minPlayCount: Math.max(0, Math.floor(Number(e.target.value) || 0)) })}
+          inputClassName="w-48"
+        />
+        <SettingDescription>{t("storage.smart_download.min_play_count.description")}</SettingDescription>
+      </SettingItemGroup>
+      <SettingItemGroup>
+        <SettingInput
+          type="number"
+          label={t("storage.smart_download.storage_budget")}
+          value={String(downloads.storageBudgetMb)}
+          onChange={(e) => update({ storageBudgetMb: Math.max(0, Math.floor(Number(e.target.value) || 0)) })}
+          inputClassName="w-48"
+        />
+        <SettingDescription>{t("storage.smart_download.storage_budget.description")}</SettingDescription>
+      </SettingItemGroup>
+      <SettingItemGroup>
+        <SettingSwitch
+          label={t("storage.smart_download.wifi_only")}
+          checked={downloads.wifiOnly}
+          onCheckedChange={(wifiOnly) => update({ wifiOnly })}
+        />
+        <SettingDescription>{t("storage.smart_download.wifi_only.description")}</SettingDescription>
+      </SettingItemGroup>
+      <SettingItemGroup>
+        <SettingInput
+          type="number"
+          label={t("storage.downloads.max_concurrent")}
+          value={String(downloads.maxConcurrent)}
+          onChange={(e) =>
+            update({ maxConcurrent: Math.min(8, Math.max(1, Math.floor(Number(e.target.value) || 1))) })
+          }
+          inputClassName="w-48"
+        />
+      </SettingItemGroup>
+      <DownloadFormatSection />
+      <MobileDataSection />
+    </div>
+  )
+}
+
+const DownloadFormatSection = () => {
+  const { t } = useTranslation("settings")
+  const [downloads, update] = useDownloadSettings()
+  const [providers, setProviders] = useState<PluginInfo[]>([])
+  const [scope, setScope] = useState(DEFAULTS_SCOPE)
+
+  useEffect(() => {
+    pluginService
+      .getPlugins()
+      .then((list) =>
+        setProviders(
+          list.filter((p) => p.enabled && p.plugin_type.toLowerCase().replace(/[-_\s]/g, "") === "audioprovider"),
+        ),
+      )
+      .catch(() => setProviders([]))
+  }, [])
+
+  const overrides = scope === DEFAULTS_SCOPE ? undefined : downloads.providers[scope]
+  const format: DownloadFormat = { ...downloads.defaults, ...overrides }
+  const setFormat = (patch: Partial<DownloadFormat>) => {
+    if (scope === DEFAULTS_SCOPE) {
+      update({ defaults: { ...downloads.defaults, ...patch } })
+    } else {
+      update({ providers: { ...downloads.providers, [scope]: { ...overrides, ...patch } } })
+    }
+  }
+  const resetProvider = () => {
+    const { [scope]: _removed, ...rest } = downloads.providers
+    update({ providers: rest })
+  }
+
+  return (
+    <>
+      <SettingSectionTitle title={t("storage.download_format")} />
+      <SettingItemGroup>
+        <div className="mb-3 flex items-center justify-between gap-4">
+          <label className="text-sm font-medium leading-none">{t("storage.download_format.scope")}</label>
+          <ResponsiveSelect
+            size="sm"
+            triggerClassName="w-48"
+            value={scope}
+            onValueChange={setScope}
+            items={[
+              { label: t("storage.download_format.scope.defaults"), value: DEFAULTS_SCOPE },
+              ...providers.map((p) => ({ label: p.display_name || p.name, value: p.id })),
+            ]}
+          />
+        </div>
+        <SettingDescription>
+          {scope === DEFAULTS_SCOPE
+            ? t("storage.download_format.scope.defaults.description")
+            : t("storage.download_format.scope.provider.description")}
+        </SettingDescription>
+        {overrides && (
+          <Button variant="outline" size="sm" className="mt-2" onClick={resetProvider}>
+            {t("storage.download_format.reset")}
+          </Button>
+        )}
+      </SettingItemGroup>
+      <SettingItemGroup>
+        <div className="mb-3 flex items-center justify-between gap-4">
+          <label className="text-sm font-medium leading-none">{t("storage.download_format.container")}</label>
+          <ResponsiveSelect
+            size="sm"
+            triggerClassName="w-48"
+            value={format.container || "auto"}
+            onValueChange={(v) => setFormat({ container: v === "auto" ? "" : v })}
+            items={CONTAINERS.map((c) => ({
+              label: c ? c.toUpperCase() : t("storage.download_format.auto"),
+              value: c || "auto",
+            }))}
+          />
+        </div>
+      </SettingItemGroup>
+      <SettingItemGroup>
+        <div className="mb-3 flex items-center justify-between gap-4">
+          <label className="text-sm font-medium leading-none">{t("storage.download_format.bitrate")}</label>
+          <ResponsiveSelect
+            size="sm"
+            triggerClassName="w-48"
+            value={String(format.bitrateKbps ?? 0)}
+            onValueChange={(v) => setFormat({ bitrateKbps: Number(v) })}
+            items={BITRATES.map((b) => ({
+              label: b ? `${b} kbps` : t("storage.download_format.auto"),
+              value: String(b),
+            }))}
+          />
+        </div>
+        <SettingDescription>{t("storage.download_format.bitrate.description")}</SettingDescription>
+      </SettingItemGroup>
+      <SettingItemGroup>
+        <SettingInput
+          type="text"
+          label={t("storage.download_format.template")}
+          value={format.filenameTemplate ?? ""}
+          onChange={(e) => setFormat({ filenameTemplate: e.target.value })}
+          inputClassName="w-64"
+        />
+        <SettingDescription>{t("storage.download_format.template.description")}</SettingDescription>
+      </SettingItemGroup>
+      <SettingItemGroup>
+        <SettingSwitch
+          label={t("storage.download_format.embed_cover")}
+          checked={!!format.embedCover}
+          onCheckedChange={(embedCover) => setFormat({ embedCover })}
+        />
+      </SettingItemGroup>
+      <SettingItemGroup>
+        <SettingSwitch
+          label={t("storage.download_format.export_lyrics")}
+          checked={!!format.exportLyrics}
+          onCheckedChange={(exportLyrics) => setFormat({ exportLyrics })}
+        />
+        <SettingDescription>{t("storage.download_format.export_lyrics.description")}</SettingDescription>
+      </SettingItemGroup>
+    </>
+  )
+}
+
+const DiskUsage = () => {
+  const { t } = useTranslation("settings")
+  const [report, setReport] = useState<LibraryStorageReport | null>(null)
+
+  useEffect(() => {
+    scannerService.getStorageReport().then(setReport).catch(() => {})
+  }, [])
+
+  const rows: { label: string; bytes?: number }[] = [
+    {
+      label: t("storage.usage.library", { count: report?.library_tracks ?? 0 }),
+      bytes: report?.library_bytes,
+    },
+    { label: t("storage.usage.downloads"), bytes: report?.downloads_bytes },
+    { label: t("storage.usage.artwork"), bytes: report?.artwork_bytes },
+    { label: t("storage.usage.cache"), bytes: report?.cache_bytes },
+  ]
+
+  return (
+    <>
+      <SettingSectionTitle title={t("storage.usage")} />
+      <SettingItemGroup>
+        {rows.map((row) => (
+          <div key={row.label} className="mb-3 flex items-center justify-between gap-4">
+            <span className="text-sm font-medium leading-none">{row.label}</span>
+            <span className="text-text-secondary text-sm tabular-nums">
+              {row.bytes === undefined ? "--" : formatBytes(row.bytes)}
+            </span>
+          </div>
+        ))}
+        <SettingDescription>{t("storage.usage.description")}</SettingDescription>
+      </SettingItemGroup>
+    </>
+  )
+}
+
+const MobileDataSection = () => {
+  const { t } = useTranslation("settings")
+  const { mobileData } = useMusicSettingValue()
+  const update = (patch: Partial<MobileDataSettings>) => setMusic("mobileData", { ...mobileData, ...patch })
+  const [usage, setUsage] = useState<DataUsageReport | null>(null)
+
+  useEffect(() => {
+    networkService.getDataUsage().then(setUsage).catch(() => {})
+  }, [])
+
+  const resetUsage = () => {
+    networkService
+      .getDataUsage(true)
+      .then(() => networkService.getDataUsage())
+      .then(setUsage)
+      .catch(() => {})
+  }
+  const total = (counts?: DataUsageReport["cellular"]) =>
+    counts ? counts.streaming + counts.downloads + counts.artwork : undefined
+
+  return (
+    <>
+      <SettingSectionTitle title={t("storage.mobile_data")} />
+      <SettingItemGroup>
+        <SettingSwitch
+          label={t("storage.mobile_data.enabled")}
+          checked={mobileData.enabled}
+          onCheckedChange={(enabled) => update({ enabled })}
+        />
+        <SettingDescription>{t("storage.mobile_data.enabled.description")}</SettingDescription>
+      </SettingItemGroup>
+      <SettingItemGroup>
+        <div className="mb-3 flex items-center justify-between gap-4">
+          <label className="text-sm font-medium leading-none">{t("storage.mobile_data.stream_quality")}</label>
+          <ResponsiveSelect
+            size="sm"
+            triggerClassName="w-48"
+            value={mobileData.streamQuality}
+            onValueChange={(streamQuality) => update({ streamQuality })}
+            items={CELLULAR_QUALITIES.map((q) => ({ label: t(`storage.mobile_data.stream_quality.${q}`), value: q }))}
+          />
+        </div>
+      </SettingItemGroup>
+      <SettingItemGroup>
+        <SettingSwitch
+          label={t("storage.mobile_data.allow_downloads")}
+          checked={mobileData.allowDownloads}
+          onCheckedChange={(allowDownloads) => update({ allowDownloads })}
+        />
+      </SettingItemGroup>
+      <SettingItemGroup>
+        <SettingSwitch
+          label={t("storage.mobile_data.allow_prefetch")}
+          checked={mobileData.allowPrefetch}
+          onCheckedChange={(allowPrefetch) => update({ allowPrefetch })}
+        />
+        <SettingDescription>{t("storage.mobile_data.allow_prefetch.description")}</SettingDescription>
+      </SettingItemGroup>
+      <SettingItemGroup>
+        <SettingSwitch
+          label={t("storage.mobile_data.high_res_artwork")}
+          checked={mobileData.highResArtwork}
+          onCheckedChange={(highResArtwork) => update({ highResArtwork })}
+        />
+      </SettingItemGroup>
+      <SettingItemGroup>
+        {[
+          { label: t("storage.mobile_data.usage.cellular"), bytes: total(usage?.cellular) },
+          { label: t("storage.mobile_data.usage.other"), bytes: total(usage?.other) },
+        ].map((row) => (
+          <div key={row.label} className="mb-3 flex items-center justify-between gap-4">
+            <span className="text-sm font-medium leading-none">{row.label}</span>
+            <span className="text-text-secondary text-sm tabular-nums">
+              {row.bytes === undefined ? "--" : formatBytes(row.bytes)}
+            </span>
+          </div>
+        ))}
+        <SettingDescription>{t("storage.mobile_data.usage.description")}</SettingDescription>
+        <Button variant="outline" size="sm" className="mt-2" onClick={resetUsage}>
+          {t("storage.mobile_data.usage.reset")}
+        </Button>
+      </SettingItemGroup>
+    </>
+  )
+}
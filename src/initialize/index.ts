@@ -16,8 +16,14 @@ export const initializeApp = async () => {
     return Promise.resolve()
   })
 
+  const unwatchNetwork = await apm("network-status", async () => {
+    const { watchNetworkStatus } = await import('~/initialize/network-status')
+    return watchNetworkStatus()
+  })
+
   // keep reference if you need to unlisten on teardown
   void unlistenSettings
+  void unwatchNetwork
 }
 
 const apm = async (label: string, fn: () => Promise<any> | any) => {
@@ -0,0 +1,40 @@
+import { setPreferLowResArtwork } from '~/lib/image'
+import { networkService, type DataPolicy, type NetworkClass } from '~/services/network-service'
+
+type NetworkInformation = EventTarget & { type?: string; saveData?: boolean }
+
+function networkClassOf(connection: NetworkInformation): NetworkClass {
+  switch (connection.type) {
+    case 'cellular':
+      return 'cellular'
+    case 'wifi':
+      return 'wifi'
+    case 'ethernet':
+      return 'ethernet'
+    default:
+      return 'unknown'
+  }
+}
+
+const applyPolicy = (policy: DataPolicy) => setPreferLowResArtwork(!policy.highResArtwork)
+
+// Report the connection class (cellular, Wi-Fi, ethernet) and whether it is metered
+// (cellular, data saver), so the backend can apply the mobile data rules and downloads
+// can wait for Wi-Fi. Where the Network Information API is missing the connection is
+// treated as unknown and unmetered.
+export function watchNetworkStatus() {
+  const unlistenPolicy = networkService.onPolicyChanged(applyPolicy)
+  const connection = (navigator as Navigator & { connection?: NetworkInformation }).connection
+  if (!connection) return () => void unlistenPolicy.then((unlisten) => unlisten())
+
+  const report = () => {
+    const metered = connection.type === 'cellular' || connection.saveData === true
+    networkService.setNetworkClass(networkClassOf(connection), metered).then(applyPolicy).catch(() => {})
+  }
+  report()
+  connection.addEventListener('change', report)
+  return () => {
+    connection.removeEventListener('change', report)
+    void unlistenPolicy.then((unlisten) => unlisten())
+  }
+}
@@ -37,17 +37,22 @@ export function resolveImageUrl(input?: string | null): string | null {
   }
 }
 
+let preferLowResArtwork = false
+
+/** Prefer low-res covers, set while on cellular without high-res artwork allowed */
+export function setPreferLowResArtwork(prefer: boolean) {
+  preferLowResArtwork = prefer
+}
+
 /**
- * Try to resolve track cover by priority: track-high -> album-high -> track-low -> album-low
+ * Try to resolve track cover by priority: track-high -> album-high -> track-low -> album-low,
+ * or low before high while low-res artwork is preferred
  */
 export function resolveTrackCoverUrl(track: MediaContent): string | null {
   if (!track) return null
-  const candidates: Array<string | undefined | null> = [
-    track.track_coverpath_high,
-    track.album?.album_coverPath_high,
-    track.track_coverpath_low,
-    track.album?.album_coverPath_low,
-  ]
+  const high = [track.track_coverpath_high, track.album?.album_coverPath_high]
+  const low = [track.track_coverpath_low, track.album?.album_coverPath_low]
+  const candidates: Array<string | undefined | null> = preferLowResArtwork ? [...low, ...high] : [...high, ...low]
 
   for (const c of candidates) {
     const url = resolveImageUrl(c ?? null)
@@ -0,0 +1,54 @@
+import { invoke as tauriInvoke, type InvokeArgs, type InvokeOptions } from "@tauri-apps/api/core"
+
+import type { CommandError, CommandResponse, ErrorCode } from "~/types/bindings"
+
+/**
+ * Rejection value of `invoke` when a backend command fails.
+ * Carries the stable error code so callers can branch without parsing messages.
+ */
+export class TauriCommandError extends Error implements CommandError {
+  code: ErrorCode
+  retryable: boolean
+
+  constructor(
+    public command: string,
+    error: CommandError,
+  ) {
+    super(error.message)
+    this.name = "TauriCommandError"
+    this.code = error.code
+    this.retryable = error.retryable
+  }
+}
+
+const isEnvelope = (value: unknown): value is CommandResponse<unknown> =>
+  typeof value === "object" &&
+  value !== null &&
+  typeof (value as CommandResponse<unknown>).ok === "boolean" &&
+  ("data" in value || "error" in value)
+
+/**
+ * Drop-in replacement for `invoke` from `@tauri-apps/api/core` that unwraps the
+ * `{ ok, data, error }` envelope returned by app commands. Failures reject with
+ * a `TauriCommandError`; values of non-enveloped commands (Tauri plugins) pass through.
+ */
+export async function invoke<T>(cmd: string, args?: InvokeArgs, options?: InvokeOptions): Promise<T> {
+  let value: unknown
+  try {
+    value = await tauriInvoke<unknown>(cmd, args, options)
+  } catch (e) {
+    // Argument deserialization and missing commands are rejected by Tauri itself
+    throw new TauriCommandError(cmd, {
+      code: "unknown",
+      message: typeof e === "string" ? e : String((e as Error)?.message ?? e),
+      retryable: false,
+    })
+  }
+
+  if (!isEnvelope(value)) return value as T
+  if (value.ok) return value.data as T
+  throw new TauriCommandError(
+    cmd,
+    value.error ?? { code: "unknown", message: `Command ${cmd} failed`, retryable: false },
+  )
+}
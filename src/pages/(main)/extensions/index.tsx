@@ -9,6 +9,9 @@ import { Input } from '~/components/ui/input'
 import { pluginService } from '~/services/plugin-service'
 import { Switch } from '~/components/ui/switch'
 import { resolveImageUrl } from '~/lib/image'
+import { ProviderEndpoints } from '~/components/modules/extensions/provider-endpoints'
+import { PluginInstaller } from '~/components/modules/extensions/plugin-installer'
+import { PluginSettings } from '~/components/modules/extensions/plugin-settings'
 import SpotifyPng from '~/assets/icons/spotify.png'
 import YoutubePng from '~/assets/icons/youtube.png'
 import BilibiliPng from '~/assets/icons/bilibili.png'
@@ -236,6 +239,7 @@ export function Component() {
 
         {/* Plugin Store */}
         <TabsContent value="store" className="mt-6">
+          <PluginInstaller onInstalled={() => fetchPlugins({ silent: true })} />
           <div className="text-center py-12">
             <ExtensionIcon className="w-12 h-12 mx-auto text-muted-foreground mb-4" />
             <h3 className="text-lg font-semibold mb-2">
@@ -291,6 +295,8 @@ export function Component() {
               </div>
             </div>
           </Card>
+          <PluginSettings />
+          <ProviderEndpoints />
         </TabsContent>
       </Tabs>
 
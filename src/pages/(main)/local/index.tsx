@@ -5,7 +5,7 @@ import { Button } from '~/components/ui/button'
 import { Input } from '~/components/ui/input'
 import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from '~/components/ui/table'
 import { Checkbox } from '~/components/ui/checkbox'
-import { audioService } from '~/services/audio-service'
+import { audioService, type TrackGainPreview } from '~/services/audio-service'
 import { scannerService } from '~/services/scanner-service'
 import type { MediaContent } from '~/types/bindings'
 import { resolveImageUrl } from '~/lib/image'
@@ -37,6 +37,10 @@ function formatBitrate(bitrate?: number): string {
   return `${Math.round(bitrate / 1000)}kbps`
 }
 
+function formatGain(db: number): string {
+  return `${db > 0 ? '+' : ''}${db.toFixed(1)} dB`
+}
+
 // removed unused getCoverImageUrl
 
 export function Component() {
@@ -48,6 +52,8 @@ export function Component() {
   const [sortBy] = useState<'title' | 'artist' | 'album' | 'duration'>('title')
   const [sortOrder] = useState<'asc' | 'desc'>('asc')
   const [scannerStatus, setScannerStatus] = useState<ScannerStatus>({ state: 'Not initialized' })
+  // Normalization gain per track id, shown whether or not normalization is enabled
+  const [gains, setGains] = useState<Map<string, TrackGainPreview>>(new Map())
   // removed unused isPlaying
 
   const loadTracks = useCallback(async () => {
@@ -63,10 +69,19 @@ export function Component() {
     }
   }, [])
 
+  useEffect(() => {
+    const ids = tracks.map((track) => track._id).filter((id): id is string => !!id)
+    if (ids.length === 0) return
+    audioService
+      .getNormalizationPreview(ids)
+      .then((previews) => setGains(new Map(previews.map((p) => [p.trackId, p]))))
+      .catch(() => {})
+  }, [tracks])
+
   const checkScannerStatus = useCallback(async () => {
     try {
       const status = await scannerService.getStatus()
-      setScannerStatus({ state: status as ScannerStatus['state'] })
+      setScannerStatus({ state: status.state as ScannerStatus['state'] })
     } catch (error) {
       console.error('Failed to get scanner status:', error)
       setScannerStatus({ state: 'Not initialized', message: t('pages.local.scanner.not_initialized') })
@@ -492,6 +507,18 @@ export function Component() {
                         {formatBitrate(track.bitrate)}
                       </div>
                     )}
+                    {(() => {
+                      const gain = track._id ? gains.get(track._id) : undefined
+                      if (gain?.trackModeDb == null) return null
+                      return (
+                        <div className="text-xs text-text-tertiary opacity-60">
+                          <div>{t('pages.local.gain.track', { gain: formatGain(gain.trackModeDb) })}</div>
+                          {gain.gain.albumGain != null && gain.albumModeDb != null && (
+                            <div>{t('pages.local.gain.album', { gain: formatGain(gain.albumModeDb) })}</div>
+                          )}
+                        </div>
+                      )
+                    })()}
                   </TableCell>
                   <TableCell>
                     <button
@@ -0,0 +1,21 @@
+import { SettingsTitle } from "~/components/modules/settings/title"
+import { defineSettingPageData } from "~/components/modules/settings/utils"
+import { SettingAudio } from "~/components/modules/settings/tabs/audio"
+
+const iconName = "i-mgc-volume-cute-re"
+const priority = 1050
+
+export const loader = defineSettingPageData({
+  icon: iconName,
+  name: "titles.audio",
+  priority,
+})
+
+export function Component() {
+  return (
+    <>
+      <SettingsTitle />
+      <SettingAudio />
+    </>
+  )
+}
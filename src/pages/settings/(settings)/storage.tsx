@@ -0,0 +1,21 @@
+import { SettingsTitle } from "~/components/modules/settings/title"
+import { defineSettingPageData } from "~/components/modules/settings/utils"
+import { SettingStorage } from "~/components/modules/settings/tabs/storage"
+
+const iconName = "i-mgc-download-2-cute-re"
+const priority = 1200
+
+export const loader = defineSettingPageData({
+  icon: iconName,
+  name: "titles.storage",
+  priority,
+})
+
+export function Component() {
+  return (
+    <>
+      <SettingsTitle />
+      <SettingStorage />
+    </>
+  )
+}
@@ -0,0 +1,28 @@
+import { type FC, useEffect, useState } from "react";
+
+import { audioService } from "~/services/audio-service";
+
+// Player events the backend attaches a screen-reader announcement to
+const ANNOUNCED_EVENTS = ["TrackChanged", "PlaybackStateChanged", "PlayerModeChanged", "VolumeChanged"];
+
+/**
+ * 通过 aria-live 区域朗读后端生成的播放器播报文本
+ */
+export const AnnouncementProvider: FC = () => {
+	const [announcement, setAnnouncement] = useState("");
+
+	useEffect(() => {
+		const unsubscribers = ANNOUNCED_EVENTS.map((eventType) =>
+			audioService.on(eventType, (data: { announcement?: string }) => {
+				if (data?.announcement) setAnnouncement(data.announcement);
+			}),
+		);
+		return () => unsubscribers.forEach((unsubscribe) => unsubscribe());
+	}, []);
+
+	return (
+		<div className="sr-only" role="status" aria-live="polite" aria-atomic="true">
+			{announcement}
+		</div>
+	);
+};
@@ -19,6 +19,7 @@ import {
     playerModeAtom,
     currentPlaylistAtom,
     currentPlaylistMusicIndexAtom,
+    queueMetricsAtom,
     onRequestNextTrackAtom,
     onRequestPrevTrackAtom,
     onPlayOrResumeAtom,
@@ -30,7 +31,7 @@ import {
 } from "~/atoms/player/index";
 
 import { audioService } from "~/services/audio-service";
-import type { QueueItem } from "~/services/audio-service";
+import type { QueueItem, QueueMetrics } from "~/services/audio-service";
 import type { MediaContent, Tracks } from "~/types/bindings";
 
 /**
@@ -286,11 +287,18 @@ export const MusicPlayerProvider: FC = () => {
 
         // Position update event
         unsubscribeEvents.push(
-            audioService.on("PositionChanged", (data: { position: { secs: number; nanos: number } }) => {
-                // Convert Rust Duration to milliseconds
-                const positionMs = data.position.secs * 1000 + Math.floor(data.position.nanos / 1_000_000);
-                store.set(musicPlayingPositionAtom, positionMs);
-            })
+            audioService.on(
+                "PositionChanged",
+                (data: { position: { secs: number; nanos: number }; duration?: number | null }) => {
+                    // Convert Rust Duration to milliseconds
+                    const positionMs = data.position.secs * 1000 + Math.floor(data.position.nanos / 1_000_000);
+                    store.set(musicPlayingPositionAtom, positionMs);
+                    // Tracks with skip regions report their edited duration
+                    if (data.duration != null) {
+                        store.set(musicDurationAtom, (data.duration * 1000) | 0);
+                    }
+                },
+            )
         );
 
         // Volume changed event
@@ -309,7 +317,10 @@ export const MusicPlayerProvider: FC = () => {
 
         // Queue changed event
         unsubscribeEvents.push(
-            audioService.on("QueueChanged", async () => {
+            audioService.on("QueueChanged", async (data: { metrics?: QueueMetrics }) => {
+                if (data?.metrics) {
+                    store.set(queueMetricsAtom, data.metrics);
+                }
                 try {
                     // Sync ordered playlist for UI
                     const queue = await audioService.getQueue();
@@ -337,9 +348,9 @@ export const MusicPlayerProvider: FC = () => {
             })
         );
 
-        // Buffer progress event
+        // Buffer progress of a stream in percent, before it starts or after a stall
         unsubscribeEvents.push(
-            audioService.on("BufferProgress", (data: { progress: number }) => {
+            audioService.on("BufferProgress", (data: { progress: number; stalled: boolean }) => {
                 // TODO: Handle buffer progress as needed
             })
         );
@@ -383,6 +394,12 @@ export const MusicPlayerProvider: FC = () => {
                 if (status.queue_index !== null) {
                     store.set(currentPlaylistMusicIndexAtom, status.queue_index);
                 }
+
+                // Queue saved by a newer app version, or partly unreadable
+                const restoreWarning = await audioService.takeRestoreWarning();
+                if (restoreWarning) {
+                    toast.warning(restoreWarning);
+                }
             } catch (error) {
                 console.error("[MusicPlayerProvider] init state failed:", error);
             }
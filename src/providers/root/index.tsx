@@ -14,6 +14,7 @@ import { MusicPlayerProvider } from '~/providers/player/music-player-provider'
 import { LyricProvider } from '~/providers/player/lyric-provider'
 import { FFTToLowPassProvider } from '~/providers/player/fft-to-low-pass-provider'
 import { MusicQualityTagProvider } from '~/providers/player/music-quality-tag-provider'
+import { AnnouncementProvider } from '~/providers/player/announcement-provider'
 
 const loadFeatures = () =>
     import('./framer-lazy-feature').then((res) => res.default)
@@ -35,6 +36,7 @@ export function RootProvider({ children }: PropsWithChildren) {
                       <LyricProvider />
                       <FFTToLowPassProvider />
                       <MusicQualityTagProvider />
+                      <AnnouncementProvider />
                       <LyricPage />
                     </ThemeProvider>
                   </AppThemeProvider>
@@ -1,5 +1,5 @@
 
-import { invoke } from "@tauri-apps/api/core";
+import { invoke } from "~/lib/tauri-command";
 import { type EventCallback, listen } from "@tauri-apps/api/event";
 import { uid } from "uid";
 
@@ -1,6 +1,6 @@
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '~/lib/tauri-command';
 import { listen } from '@tauri-apps/api/event';
-import type { MediaContent, PlayerState, PlayerMode } from '~/types/bindings';
+import type { MediaContent, OutputProfile, PlayerState, PlayerMode, StreamQuality } from '~/types/bindings';
 
 
 
@@ -9,6 +9,67 @@ export interface BackendQueue {
   track_queue: string[];
   current_index: number;
   data: Record<string, MediaContent>;
+  // Instance ids appended by radio mode
+  auto_generated?: string[];
+  metrics?: QueueMetrics;
+}
+
+// Remaining play time computed by the backend (crossfades and gaps included)
+export interface QueueMetrics {
+  remaining_secs: number;
+  // Seconds until each upcoming entry starts, by instance id
+  starts_in: Record<string, number>;
+  // End of the queue in ms since epoch, null when playback repeats
+  ends_at: number | null;
+  repeats: boolean;
+  unknown_durations: number;
+}
+
+// How the volume relates to the source of the playing track (see set_volume_mode)
+export type VolumeMode = 'Normal' | 'PersistSeparate' | 'PersistClamp';
+
+// What activating an entity does; configured per type in prefs.queue_settings.defaultActions
+export type QueueAction = 'playNow' | 'playNext' | 'append' | 'startRadio';
+
+export type QueueEntity =
+  | { type: 'track'; track: MediaContent }
+  | { type: 'album'; albumId: string }
+  | { type: 'artist'; artistId: string }
+  | { type: 'playlist'; playlistId: string };
+
+// Queue replaced or cleared, kept for jump-back-in (newest first)
+export interface QueueSnapshot {
+  id: string;
+  name: string;
+  track_ids: string[];
+  created_at: string;
+}
+
+export interface QueueRestore {
+  track_count: number;
+  // Tracks no longer in the library, left out
+  missing_tracks: number;
+}
+
+// Place and health of a provider in the stream failover chain
+export interface ResolverProviderStatus {
+  plugin_id: string;
+  // Position in sourcesOrder, null when not listed
+  priority: number | null;
+  consecutive_failures: number;
+  demoted: boolean;
+  demoted_for_secs: number | null;
+  last_error: string | null;
+  last_success_at: string | null;
+  last_failure_at: string | null;
+}
+
+// Emitted as `resolver-status` after each stream resolution
+export interface ResolverStatusEvent {
+  track_id: string;
+  resolved_by: string | null;
+  attempts: { plugin_id: string; ok: boolean; elapsed_ms: number; error: string | null }[];
+  providers: ResolverProviderStatus[];
 }
 
 // Frontend-facing structures (may be reworked gradually)
@@ -22,7 +83,80 @@ export interface QueueItem {
 export interface PlayerEventPayload {
   // NOTE: Pass-through type. Consider narrowing once Rust PlayerEvents is finalized.
   type: string;
+  // Key events carry a screen-reader sentence in data.announcement
   data: any;
+  // Set once the window subscribed to player events
+  seq?: number;
+}
+
+// Audio output device as listed by the backend
+export interface OutputDevice {
+  name: string;
+  is_default: boolean;
+  is_active: boolean;
+}
+
+// Non-destructive edit of a track, in seconds of the original audio
+export interface EditRegion {
+  start: number;
+  end: number;
+  kind: 'skip' | 'mute';
+}
+
+// A-B repeat region of the loaded track, in seconds as shown
+export interface LoopRegion {
+  start: number;
+  end: number;
+}
+
+// Reaction to incoming calls and communication sessions
+export type InterruptionMode = 'pause' | 'duck' | 'ignore';
+
+// Which ReplayGain value loudness normalization applies
+export type NormalizationMode = 'track' | 'album';
+
+// Gain (dB) normalization would apply to a track in each mode; null without analysis
+export interface TrackGainPreview {
+  trackId: string;
+  albumId: string | null;
+  gain: {
+    trackGain: number | null;
+    trackPeak: number | null;
+    albumGain: number | null;
+    albumPeak: number | null;
+  };
+  trackModeDb: number | null;
+  albumModeDb: number | null;
+}
+
+// Chapter of a track read from its tags, in seconds; end is null when unknown
+export interface TrackChapter {
+  start: number;
+  end: number | null;
+  title: string | null;
+}
+
+// Position bookmarked inside a track, in seconds of the original audio
+export interface TrackBookmark {
+  id: string;
+  trackId: string;
+  position: number;
+  label: string | null;
+}
+
+// Player state returned when a window subscribes to player events
+export interface PlayerSnapshot {
+  // Sequence number of the last event reflected in the snapshot
+  seq: number;
+  state: PlayerState;
+  track: MediaContent | null;
+  queueIndex: number | null;
+  mode: PlayerMode;
+  // Seconds on the edited timeline
+  position: number;
+  duration: number | null;
+  // 0.0 - 1.0
+  volume: number;
 }
 
 export interface AggregatedPlayerStatus {
@@ -34,28 +168,65 @@ export interface AggregatedPlayerStatus {
 
 class AudioService {
   private eventListeners: Map<string, Function[]> = new Map();
-  private isInitialized = false;
+  private initialization: Promise<void> | null = null;
+  // Events carry a sequence number once this window subscribed
+  private lastSeq = 0;
+  private snapshot: PlayerSnapshot | null = null;
 
   constructor() {
     this.initializeEventListeners();
   }
 
-  // Initialize event listeners for backend -> frontend bridge
-  private async initializeEventListeners() {
-    if (this.isInitialized) return;
-
-    try {
-      // Backend emits "audio_event" with PlayerEvents payload
-      await listen<PlayerEventPayload>('audio_event', (event) => {
-        console.log('[AudioService] 收到播放器事件:', event.payload);
-        this.emitEvent(event.payload.type, event.payload.data);
+  // Initialize event listeners for backend -> frontend bridge. Every window
+  // (main, mini player, settings) listens once and starts from a snapshot.
+  private initializeEventListeners(): Promise<void> {
+    if (!this.initialization) {
+      this.initialization = this.subscribe().catch((error) => {
+        this.initialization = null;
+        console.error('[AudioService] 初始化事件监听器失败:', error);
       });
+    }
+    return this.initialization;
+  }
 
-      this.isInitialized = true;
-      console.log('[AudioService] 事件监听器初始化完成');
-    } catch (error) {
-      console.error('[AudioService] 初始化事件监听器失败:', error);
+  private async subscribe() {
+    // Events arriving before the snapshot are replayed on top of it
+    let pending: PlayerEventPayload[] | null = [];
+
+    // Backend emits "audio_event" with PlayerEvents payload
+    await listen<PlayerEventPayload>('audio_event', (event) => {
+      if (pending) {
+        pending.push(event.payload);
+      } else {
+        this.dispatch(event.payload);
+      }
+    });
+
+    const snapshot = await invoke<PlayerSnapshot>('subscribe_player_events');
+    this.snapshot = snapshot;
+    this.lastSeq = snapshot.seq;
+    this.emitEvent('Snapshot', snapshot);
+
+    const buffered = pending;
+    pending = null;
+    buffered.forEach((payload) => this.dispatch(payload));
+    console.log('[AudioService] 事件监听器初始化完成');
+  }
+
+  // Drop events already covered by the snapshot or received before
+  private dispatch(payload: PlayerEventPayload) {
+    if (typeof payload.seq === 'number') {
+      if (payload.seq <= this.lastSeq) return;
+      this.lastSeq = payload.seq;
     }
+    console.log('[AudioService] 收到播放器事件:', payload);
+    this.emitEvent(payload.type, payload.data);
+  }
+
+  // Player state this window started from
+  async getSnapshot(): Promise<PlayerSnapshot | null> {
+    await this.initializeEventListeners();
+    return this.snapshot;
   }
 
   // Subscribe to a normalized event name
@@ -183,6 +354,389 @@ class AudioService {
     }
   }
 
+  // Cap the volume (0.0 - 1.0) of a source ("LOCAL" or a provider, the playing
+  // one when omitted) in the PersistClamp volume mode; null removes the cap
+  async setTrackVolumeClamp(clamp: number | null, source?: string): Promise<void> {
+    try {
+      await invoke('set_track_volume_clamp', { source: source ?? null, clamp });
+    } catch (error) {
+      console.error('[AudioService] 设置音量上限失败:', error);
+      throw error;
+    }
+  }
+
+  // Volume mode: "Normal", "PersistSeparate" (per source) or "PersistClamp"
+  async getVolumeMode(): Promise<VolumeMode> {
+    return invoke<VolumeMode>('get_volume_mode');
+  }
+
+  async setVolumeMode(mode: VolumeMode): Promise<void> {
+    try {
+      await invoke('set_volume_mode', { mode });
+    } catch (error) {
+      console.error('[AudioService] 设置音量模式失败:', error);
+      throw error;
+    }
+  }
+
+  // Set playback rate (0.5 - 3.0, 1.0 normal) with the pitch kept; it is saved
+  // for music or spoken word after the current track. Resolves to the applied rate
+  async setRate(rate: number): Promise<number> {
+    try {
+      return await invoke<number>('audio_set_rate', { rate });
+    } catch (error) {
+      console.error('[AudioService] 设置播放速率失败:', error);
+      throw error;
+    }
+  }
+
+  async getRate(): Promise<number> {
+    try {
+      return await invoke<number>('audio_get_rate');
+    } catch (error) {
+      console.error('[AudioService] 获取播放速率失败:', error);
+      throw error;
+    }
+  }
+
+  // Skip forward/back by `seconds`, or by the intervals of prefs.music.audiobook
+  async skipForward(seconds?: number): Promise<void> {
+    try {
+      await invoke('audio_skip_forward', { seconds });
+    } catch (error) {
+      console.error('[AudioService] 快进失败:', error);
+      throw error;
+    }
+  }
+
+  async skipBack(seconds?: number): Promise<void> {
+    try {
+      await invoke('audio_skip_back', { seconds });
+    } catch (error) {
+      console.error('[AudioService] 快退失败:', error);
+      throw error;
+    }
+  }
+
+  // Chapters of a library track; entering one emits a `ChapterChanged` event
+  async getChapters(trackId: string): Promise<TrackChapter[]> {
+    try {
+      return await invoke<TrackChapter[]>('get_track_chapters', { trackId });
+    } catch (error) {
+      console.error('[AudioService] 获取章节失败:', error);
+      throw error;
+    }
+  }
+
+  // Jump to the next chapter of the playing track, or back to the previous one
+  async seekChapter(forward: boolean): Promise<void> {
+    try {
+      await invoke('audio_seek_chapter', { forward });
+    } catch (error) {
+      console.error('[AudioService] 跳转章节失败:', error);
+      throw error;
+    }
+  }
+
+  // Bookmark a position (seconds of the original audio) of a track
+  async addBookmark(trackId: string, position: number, label?: string): Promise<TrackBookmark> {
+    try {
+      return await invoke<TrackBookmark>('add_bookmark', { trackId, position, label });
+    } catch (error) {
+      console.error('[AudioService] 添加书签失败:', error);
+      throw error;
+    }
+  }
+
+  // Bookmarks of a track by position; playback passing one emits `BookmarkReached`
+  async listBookmarks(trackId: string): Promise<TrackBookmark[]> {
+    try {
+      return await invoke<TrackBookmark[]>('list_bookmarks', { trackId });
+    } catch (error) {
+      console.error('[AudioService] 获取书签失败:', error);
+      throw error;
+    }
+  }
+
+  async removeBookmark(bookmarkId: string): Promise<void> {
+    try {
+      await invoke('remove_bookmark', { bookmarkId });
+    } catch (error) {
+      console.error('[AudioService] 删除书签失败:', error);
+      throw error;
+    }
+  }
+
+  // Seek the loaded track to one of its bookmarks
+  async jumpToBookmark(bookmarkId: string): Promise<void> {
+    try {
+      await invoke('jump_to_bookmark', { bookmarkId });
+    } catch (error) {
+      console.error('[AudioService] 跳转书签失败:', error);
+      throw error;
+    }
+  }
+
+  // Set crossfade between tracks (0 - 12000 ms, 0 disables); persisted in prefs.music.playback
+  async setCrossfade(durationMs: number, curve?: 'linear' | 'logarithmic' | 'equalPower'): Promise<void> {
+    try {
+      await invoke('audio_set_crossfade', { durationMs, curve });
+    } catch (error) {
+      console.error('[AudioService] 设置淡入淡出失败:', error);
+      throw error;
+    }
+  }
+
+  /**
+   * 设置曲目之间插入的静音时长（毫秒，0 表示关闭），开启时会关闭淡入淡出
+   */
+  async setTrackGap(gapMs: number): Promise<void> {
+    try {
+      await invoke('audio_set_track_gap', { gapMs });
+    } catch (error) {
+      console.error('[AudioService] 设置曲间静音失败:', error);
+      throw error;
+    }
+  }
+
+  /**
+   * 开关电台模式：顺序播放到队列末尾时自动追加相似曲目（RadioTracksAdded 事件）
+   */
+  async setRadioMode(enabled: boolean): Promise<void> {
+    try {
+      await invoke('set_radio_mode', { enabled });
+    } catch (error) {
+      console.error('[AudioService] 设置电台模式失败:', error);
+      throw error;
+    }
+  }
+
+  /**
+   * 设置曲目的非破坏性编辑区间（跳过/静音），空数组表示清除；不会修改音频文件
+   */
+  async setEditRegions(trackId: string, regions: EditRegion[]): Promise<void> {
+    try {
+      await invoke('set_edit_regions', { trackId, regions });
+    } catch (error) {
+      console.error('[AudioService] 设置编辑区间失败:', error);
+      throw error;
+    }
+  }
+
+  /**
+   * 获取曲目的编辑区间
+   */
+  async getEditRegions(trackId: string): Promise<EditRegion[]> {
+    try {
+      return await invoke<EditRegion[]>('get_edit_regions', { trackId });
+    } catch (error) {
+      console.error('[AudioService] 获取编辑区间失败:', error);
+      throw error;
+    }
+  }
+
+  /**
+   * A-B 循环当前曲目的一段区间，直到清除或切换曲目；返回实际应用的区间
+   */
+  async setLoopRegion(startS: number, endS: number): Promise<LoopRegion> {
+    try {
+      return await invoke<LoopRegion>('audio_set_loop_region', { startS, endS });
+    } catch (error) {
+      console.error('[AudioService] 设置循环区间失败:', error);
+      throw error;
+    }
+  }
+
+  /**
+   * 清除 A-B 循环，从当前位置继续播放
+   */
+  async clearLoopRegion(): Promise<void> {
+    try {
+      await invoke('audio_clear_loop_region');
+    } catch (error) {
+      console.error('[AudioService] 清除循环区间失败:', error);
+      throw error;
+    }
+  }
+
+  async getLoopRegion(): Promise<LoopRegion | null> {
+    try {
+      return await invoke<LoopRegion | null>('audio_get_loop_region');
+    } catch (error) {
+      console.error('[AudioService] 获取循环区间失败:', error);
+      throw error;
+    }
+  }
+
+  /**
+   * 获取响度标准化在曲目/专辑模式下对各曲目应用的增益（无论是否已开启），用于预览
+   */
+  async getNormalizationPreview(trackIds: string[]): Promise<TrackGainPreview[]> {
+    try {
+      return await invoke<TrackGainPreview[]>('get_normalization_preview', { trackIds });
+    } catch (error) {
+      console.error('[AudioService] 获取增益预览失败:', error);
+      throw error;
+    }
+  }
+
+  /**
+   * 设置来电/通信会话时的处理策略：暂停、降低音量（duckPercent 为保留的音量百分比）或忽略；
+   * resume 表示通话结束后是否自动恢复播放
+   */
+  async setInterruptionPolicy(
+    mode: InterruptionMode,
+    duckPercent?: number,
+    resume?: boolean,
+  ): Promise<void> {
+    try {
+      await invoke('audio_set_interruption_policy', { mode, duckPercent, resume });
+    } catch (error) {
+      console.error('[AudioService] 设置通话中断策略失败:', error);
+      throw error;
+    }
+  }
+
+  /**
+   * 获取播放队列恢复警告（如降级后队列无法完整恢复），仅返回一次
+   */
+  async takeRestoreWarning(): Promise<string | null> {
+    try {
+      return await invoke<string | null>('audio_take_restore_warning');
+    } catch (error) {
+      console.error('[AudioService] 获取队列恢复警告失败:', error);
+      return null;
+    }
+  }
+
+  /**
+   * 列出可用的音频输出设备（含系统默认与当前使用的设备）
+   */
+  async listOutputDevices(): Promise<OutputDevice[]> {
+    try {
+      return await invoke<OutputDevice[]>('audio_list_output_devices');
+    } catch (error) {
+      console.error('[AudioService] 获取输出设备失败:', error);
+      throw error;
+    }
+  }
+
+  /**
+   * 切换输出设备（null 跟随系统默认）；设备断开时自动回退并发出 OutputDeviceLost 事件
+   */
+  async setOutputDevice(device: string | null): Promise<void> {
+    try {
+      await invoke('audio_set_output_device', { device });
+    } catch (error) {
+      console.error('[AudioService] 切换输出设备失败:', error);
+      throw error;
+    }
+  }
+
+  /**
+   * 开始记录播放诊断日志（解码器、缓冲、解析耗时、设备与错误），返回日志文件路径
+   */
+  async startPlaybackTrace(): Promise<string> {
+    try {
+      return await invoke<string>('start_playback_trace');
+    } catch (error) {
+      console.error('[AudioService] 开始播放诊断日志失败:', error);
+      throw error;
+    }
+  }
+
+  /**
+   * 停止记录播放诊断日志，返回已写入的文件路径（未在记录时为 null）
+   */
+  async stopPlaybackTrace(): Promise<string | null> {
+    try {
+      return await invoke<string | null>('stop_playback_trace');
+    } catch (error) {
+      console.error('[AudioService] 停止播放诊断日志失败:', error);
+      throw error;
+    }
+  }
+
+  /**
+   * 开始推送频谱数据（bands 个对数分布的频段，每秒 rateHz 帧），用 onVisualizerFrame 订阅
+   */
+  async startVisualizer(bands?: number, rateHz?: number): Promise<void> {
+    try {
+      await invoke('start_audio_visualizer', { bands, rateHz });
+    } catch (error) {
+      console.error('[AudioService] 开始频谱分析失败:', error);
+      throw error;
+    }
+  }
+
+  /**
+   * 停止推送频谱数据，关闭可视化时调用，停止后不再有分析开销
+   */
+  async stopVisualizer(): Promise<void> {
+    try {
+      await invoke('stop_audio_visualizer');
+    } catch (error) {
+      console.error('[AudioService] 停止频谱分析失败:', error);
+      throw error;
+    }
+  }
+
+  /**
+   * 订阅频谱帧，每个频段 0-255（-80 dBFS 至 0 dBFS），返回取消订阅函数
+   */
+  async onVisualizerFrame(callback: (levels: Uint8Array) => void): Promise<() => void> {
+    return await listen<string>('audio_visualizer', (event) => {
+      // base64url 帧：[版本, 0, 频段数 (u16 LE), 各频段电平]
+      const binary = atob(event.payload.replace(/-/g, '+').replace(/_/g, '/'));
+      const frame = Uint8Array.from(binary, (c) => c.charCodeAt(0));
+      const bands = frame[2] | (frame[3] << 8);
+      callback(frame.subarray(4, 4 + bands));
+    });
+  }
+
+  /**
+   * 流地址解析的提供者故障转移链（按尝试顺序）及其健康状态；reset 会恢复被降级的提供者
+   */
+  async getResolverStatus(reset = false): Promise<ResolverProviderStatus[]> {
+    try {
+      return await invoke<ResolverProviderStatus[]>('get_resolver_status', { reset });
+    } catch (error) {
+      console.error('[AudioService] 获取解析器状态失败:', error);
+      throw error;
+    }
+  }
+
+  /**
+   * 订阅每次流地址解析的诊断事件，返回取消订阅函数
+   */
+  async onResolverStatus(callback: (status: ResolverStatusEvent) => void): Promise<() => void> {
+    return await listen<ResolverStatusEvent>('resolver-status', (event) => callback(event.payload));
+  }
+
+  /**
+   * 切换到指定名称的输出配置（设备、音量、音量上限、淡入淡出、交叉馈送、音效链），返回已应用的配置
+   */
+  async applyOutputProfile(name: string): Promise<OutputProfile> {
+    try {
+      return await invoke<OutputProfile>('apply_output_profile', { name });
+    } catch (error) {
+      console.error('[AudioService] 应用输出配置失败:', error);
+      throw error;
+    }
+  }
+
+  /**
+   * 设置当前网络类型下的首选音质（可仅针对某个提供者）；正在播放的流会以新音质重新解析并从当前位置继续
+   */
+  async setQuality(quality: StreamQuality | null, pluginId?: string): Promise<void> {
+    try {
+      await invoke('audio_set_quality', { quality, pluginId });
+    } catch (error) {
+      console.error('[AudioService] 设置音质失败:', error);
+      throw error;
+    }
+  }
+
   // -----------------------------
   // Queue and Store interactions
   // -----------------------------
@@ -207,6 +761,57 @@ class AudioService {
     }
   }
 
+  // "Play Next": insert tracks right after the current one, in order.
+  // Resolves to duplicates held back by the `ask` policy
+  async playNext(tracks: MediaContent[], allowDuplicates?: boolean): Promise<MediaContent[]> {
+    try {
+      return await invoke<MediaContent[]>('play_next_multiple', { tracks, allowDuplicates });
+    } catch (error) {
+      console.error('[AudioService] 下一首播放失败:', error);
+      throw error;
+    }
+  }
+
+  // Insert tracks at `index` of the queue, in order
+  async addToQueueAt(tracks: MediaContent[], index: number, allowDuplicates?: boolean): Promise<MediaContent[]> {
+    try {
+      return await invoke<MediaContent[]>('add_to_queue_at', { tracks, index, allowDuplicates });
+    } catch (error) {
+      console.error('[AudioService] 插入队列失败:', error);
+      throw error;
+    }
+  }
+
+  // Double-click / Enter on an entity: the configured default action, or `action` when given
+  async performDefaultAction(entity: QueueEntity, action?: QueueAction): Promise<QueueAction> {
+    try {
+      return await invoke<QueueAction>('perform_default_action', { entity, action });
+    } catch (error) {
+      console.error('[AudioService] 执行默认操作失败:', error);
+      throw error;
+    }
+  }
+
+  // Queues replaced or cleared, newest first
+  async listQueueHistory(): Promise<QueueSnapshot[]> {
+    try {
+      return await invoke<QueueSnapshot[]>('list_queue_history');
+    } catch (error) {
+      console.error('[AudioService] 获取队列历史失败:', error);
+      return [];
+    }
+  }
+
+  // Replace the queue with one from the history
+  async restoreQueueFromHistory(historyId: string): Promise<QueueRestore> {
+    try {
+      return await invoke<QueueRestore>('restore_queue_from_history', { historyId });
+    } catch (error) {
+      console.error('[AudioService] 恢复历史队列失败:', error);
+      throw error;
+    }
+  }
+
   // Remove by index
   async removeFromQueue(index: number): Promise<void> {
     try {
@@ -217,6 +822,16 @@ class AudioService {
     }
   }
 
+  // Move the queue entry at `from` to `to` (drag and drop)
+  async moveQueueItem(from: number, to: number): Promise<void> {
+    try {
+      await invoke('move_queue_item', { from, to });
+    } catch (error) {
+      console.error('[AudioService] 移动队列项失败:', error);
+      throw error;
+    }
+  }
+
   // Get backend queue (raw)
   async getQueueRaw(): Promise<BackendQueue> {
     try {
@@ -376,7 +991,7 @@ class AudioService {
     }
   }
 
-  // Toggle player mode (cycle through Sequential -> Single -> Shuffle -> ListLoop)
+  // Toggle player mode (cycle through Sequential -> Single -> Shuffle -> ShuffleAlbums -> ListLoop)
   async togglePlayerMode(): Promise<void> {
     try {
       await invoke('toggle_player_mode');
@@ -0,0 +1,65 @@
+import { invoke } from '~/lib/tauri-command'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+
+export type DownloadState = 'queued' | 'downloading' | 'paused'
+
+export interface DownloadEntry {
+  trackId: string
+  origin: 'manual' | 'smart' | 'podcast'
+  state: DownloadState
+  bytesWritten: number
+  totalBytes: number | null
+}
+
+export interface DownloadedTrack {
+  track_id: string
+  path: string
+  size: number
+  origin: string
+  created_at: string
+}
+
+export interface DownloadList {
+  active: DownloadEntry[]
+  completed: DownloadedTrack[]
+}
+
+export type DownloadEvent =
+  | { type: 'queued' | 'started' | 'paused' | 'cancelled'; data: { trackId: string } }
+  | { type: 'progress'; data: DownloadEntry }
+  | { type: 'completed'; data: DownloadedTrack }
+  | { type: 'failed'; data: { trackId: string; error: string } }
+
+class DownloadService {
+  /**
+   * Queue a download of an online track; the file is added to the local library once done.
+   * Resolves to false if the track is already queued or downloaded.
+   */
+  async downloadTrack(trackId: string): Promise<boolean> {
+    return invoke<boolean>('download_track', { trackId })
+  }
+
+  async cancelDownload(trackId: string): Promise<void> {
+    return invoke<void>('cancel_download', { trackId })
+  }
+
+  async pauseDownload(trackId: string): Promise<void> {
+    return invoke<void>('pause_download', { trackId })
+  }
+
+  async resumeDownload(trackId: string): Promise<void> {
+    return invoke<void>('resume_download', { trackId })
+  }
+
+  async listDownloads(): Promise<DownloadList> {
+    return invoke<DownloadList>('list_downloads')
+  }
+
+  /** Subscribe to download progress and state changes */
+  onEvent(callback: (event: DownloadEvent) => void): Promise<UnlistenFn> {
+    return listen<DownloadEvent>('download_event', (event) => callback(event.payload))
+  }
+}
+
+export const downloadService = new DownloadService()
+export default downloadService
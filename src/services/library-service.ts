@@ -0,0 +1,212 @@
+import { invoke } from '~/lib/tauri-command'
+import type { GetTrackOptions, MediaContent } from '~/types/bindings'
+
+export type SmartSortCriterion = 'recently_added' | 'play_count' | 'rating' | 'alphabetical'
+
+export interface SmartSortWeight {
+  criterion: SmartSortCriterion
+  /** Negative weights invert the criterion (e.g. oldest first) */
+  weight: number
+}
+
+export interface SmartSortPreset {
+  name: string
+  criteria: SmartSortWeight[]
+}
+
+export interface LyricsSearchHit {
+  track: MediaContent
+  /** Lyrics line containing the phrase, without LRC time tags */
+  snippet: string
+  /** Matched range in `snippet`, in code points (use Array.from(snippet)) */
+  highlight_start: number
+  highlight_end: number
+  /** Start of the matching line for time-synced lyrics */
+  time_ms: number | null
+}
+
+export interface LyricsLine {
+  time_ms: number | null
+  text: string
+}
+
+/** Track lyrics; `LyricsLoaded` / `LyricLineChanged` audio events follow the playing track */
+export interface TrackLyrics {
+  track_id: string
+  /** 'embedded' | 'sidecar' | 'library' | provider id */
+  source: string
+  synced: boolean
+  text: string
+  lines: LyricsLine[]
+}
+
+export type TrackMood = 'energetic' | 'tense' | 'calm' | 'melancholic'
+
+/** Audio features extracted from local files; energy, valence and danceability are 0..1 */
+export interface TrackAudioFeatures {
+  energy: number
+  valence: number
+  danceability: number
+  /** Estimated BPM */
+  tempo: number
+  mood: TrackMood
+}
+
+/** Condition on audio features; unset bounds don't restrict, unanalyzed tracks never match */
+export interface TrackFeatureFilter {
+  moods?: TrackMood[]
+  min_energy?: number | null
+  max_energy?: number | null
+  min_valence?: number | null
+  max_valence?: number | null
+  min_danceability?: number | null
+  max_danceability?: number | null
+  min_tempo?: number | null
+  max_tempo?: number | null
+}
+
+/** Outcome of a MusicBrainz lookup; a `metadata-enriched` event follows each match */
+export interface EnrichmentResult {
+  track_id: string
+  /** MusicBrainz recording matched, null when nothing matched closely */
+  recording_id: string | null
+  release_id: string | null
+  /** Whether a cover was fetched from the Cover Art Archive */
+  cover_fetched: boolean
+}
+
+/** Tags to change; fields left out are kept */
+export interface TrackMetadataEdit {
+  title?: string | null
+  /** Artists in credit order */
+  artists?: string[] | null
+  album?: string | null
+  genre?: string | null
+  track_no?: number | null
+  year?: string | null
+}
+
+export interface TrackEditResult {
+  /** Tracks updated in the library and in their files; a `tracks-updated` event lists them */
+  updated: string[]
+  failed: { track_id: string; error: string }[]
+}
+
+/** Library folder; `track_count` includes subfolders */
+export interface FolderNode {
+  path: string
+  name: string
+  track_count: number
+  children: FolderNode[]
+}
+
+/** Reachability of a music folder; tracks below an unavailable one are kept */
+export interface LibraryRootStatus {
+  root: string
+  available: boolean
+  changed_at: number
+  track_count: number
+}
+
+class LibraryService {
+  /** Run a library query and order the result with a smart sort preset (ranked by the backend) */
+  async getTracksSmartSorted(options: GetTrackOptions, preset: SmartSortPreset): Promise<MediaContent[]> {
+    return invoke<MediaContent[]>('get_tracks_smart_sorted', { options, preset })
+  }
+
+  async getSortPresets(): Promise<SmartSortPreset[]> {
+    try {
+      return await invoke<SmartSortPreset[]>('get_sort_presets')
+    } catch (error) {
+      console.error('[LibraryService] getSortPresets error:', error)
+      return []
+    }
+  }
+
+  /** Save a preset, replacing the one with the same name */
+  async saveSortPreset(preset: SmartSortPreset): Promise<void> {
+    await invoke('save_sort_preset', { preset })
+  }
+
+  async deleteSortPreset(name: string): Promise<void> {
+    await invoke('delete_sort_preset', { name })
+  }
+
+  /** Find tracks whose lyrics contain `phrase` */
+  async searchLyrics(phrase: string, limit?: number): Promise<LyricsSearchHit[]> {
+    return invoke<LyricsSearchHit[]>('search_lyrics_library', { phrase, limit })
+  }
+
+  /** Lyrics from tags, `.lrc` sidecar or plugins (cached in the library); `refresh` skips the cache */
+  async getLyrics(trackId: string, refresh = false): Promise<TrackLyrics | null> {
+    return invoke<TrackLyrics | null>('get_lyrics', { trackId, refresh })
+  }
+
+  /** Run a library query and keep the tracks matching an audio feature condition */
+  async getTracksByFeatures(options: GetTrackOptions, filter: TrackFeatureFilter): Promise<MediaContent[]> {
+    return invoke<MediaContent[]>('get_tracks_by_features', { options, filter })
+  }
+
+  /** Stored features and mood of a track, null until it is analyzed */
+  async getTrackFeatures(trackId: string): Promise<TrackAudioFeatures | null> {
+    return invoke<TrackAudioFeatures | null>('get_track_features', { trackId })
+  }
+
+  /** Analyze tracks again; returns the new features by track id (failures are left out) */
+  async reclassify(trackIds: string[]): Promise<Record<string, TrackAudioFeatures>> {
+    return invoke<Record<string, TrackAudioFeatures>>('reclassify', { trackIds })
+  }
+
+  /** Look a track up on MusicBrainz, merging album/artist details and fetching a missing cover */
+  async enrichTrack(trackId: string): Promise<EnrichmentResult> {
+    return invoke<EnrichmentResult>('enrich_track', { trackId })
+  }
+
+  /** Look an album up on MusicBrainz through its tracks; null when none matched */
+  async enrichAlbum(albumId: string): Promise<EnrichmentResult | null> {
+    return invoke<EnrichmentResult | null>('enrich_album', { albumId })
+  }
+
+  /** Identify a local track by its audio fingerprint on AcoustID; returns the tags it is missing, null when none */
+  async lookupAcoustid(trackId: string): Promise<TrackMetadataEdit | null> {
+    return invoke<TrackMetadataEdit | null>('lookup_acoustid', { trackId })
+  }
+
+  /** Folders holding tracks, one tree per configured music path */
+  async getFolderTree(): Promise<FolderNode[]> {
+    try {
+      return await invoke<FolderNode[]>('get_folder_tree')
+    } catch (error) {
+      console.error('[LibraryService] getFolderTree error:', error)
+      return []
+    }
+  }
+
+  /** Tracks of a folder, with those of its subfolders when `recursive` */
+  async getTracksInFolder(path: string, recursive = false): Promise<MediaContent[]> {
+    return invoke<MediaContent[]>('get_tracks_in_folder', { path, recursive })
+  }
+
+  /** Music folders and whether they are reachable (see the `library-availability` event) */
+  async getLibraryRoots(): Promise<LibraryRootStatus[]> {
+    return invoke<LibraryRootStatus[]>('get_library_roots')
+  }
+
+  /** Ids of the tracks below unreachable music folders */
+  async getUnavailableTracks(): Promise<string[]> {
+    return invoke<string[]>('get_unavailable_tracks')
+  }
+
+  /** Edit the tags of local tracks, written to the files and the library */
+  async editTrackMetadata(trackIds: string[], edit: TrackMetadataEdit): Promise<TrackEditResult> {
+    return invoke<TrackEditResult>('edit_track_metadata', { trackIds, edit })
+  }
+
+  /** Rate a track from 1 to 5; 0 clears the rating */
+  async setTrackRating(trackId: string, rating: number): Promise<void> {
+    await invoke('set_track_rating', { trackId, rating })
+  }
+}
+
+export const libraryService = new LibraryService()
+export default libraryService
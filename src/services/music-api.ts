@@ -1,4 +1,5 @@
-import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import { invoke } from '~/lib/tauri-command'
 
 // TS view of selection. Optional for callers 
 // — backend will read prefs.music.source if omitted.
@@ -63,12 +64,175 @@ export async function musicSearch(term: string, opts?: SearchOptions): Promise<T
   return invoke<TrackSearchResult>('music_search', payload)
 }
 
+export type ProviderSearchOutcome = 'completed' | 'timed_out' | 'failed'
+
+export interface SearchPartial {
+  search_id: string
+  provider: string
+  elapsed_ms: number
+  result: {
+    provider: string
+    tracks: { items: ProviderTrack[]; page: TrackPageInfo }
+  }
+}
+
+export interface SearchComplete {
+  search_id: string
+  elapsed_ms: number
+  providers: Record<string, ProviderSearchOutcome>
+}
+
+export interface StreamedSearchOptions extends SearchOptions {
+  // Latency budget per provider in ms (backend default 5000)
+  defaultBudgetMs?: number
+  // Per-provider overrides keyed by plugin id
+  budgetsMs?: Record<string, number>
+}
+
+// Search all providers in parallel; `onPartial` fires per provider as results arrive,
+// the promise resolves with the per-provider outcomes once all answered or timed out.
+export async function musicSearchStreamed(
+  term: string,
+  onPartial: (partial: SearchPartial) => void,
+  opts?: StreamedSearchOptions,
+): Promise<SearchComplete> {
+  const searchId = crypto.randomUUID()
+  const searchQuery: SearchQuery = {
+    query: term,
+    types: opts?.types || ["Track"],
+    page: opts?.page || null,
+    per_type_page: opts?.per_type_page || null,
+    sort: opts?.sort || null,
+    per_type_sort: opts?.per_type_sort || null,
+    filters: opts?.filters || {},
+    provider_params: opts?.provider_params || {}
+  }
+
+  // Subscribe before invoking so fast providers are not missed
+  const unlistenPartial = await listen<SearchPartial>('search-results-partial', (event) => {
+    if (event.payload.search_id === searchId) onPartial(event.payload)
+  })
+  let resolveComplete: (complete: SearchComplete) => void = () => {}
+  const completed = new Promise<SearchComplete>((resolve) => {
+    resolveComplete = resolve
+  })
+  const unlistenComplete = await listen<SearchComplete>('search-results-complete', (event) => {
+    if (event.payload.search_id === searchId) resolveComplete(event.payload)
+  })
+
+  try {
+    await invoke<void>('music_search_streamed', {
+      searchId,
+      searchQuery,
+      selector: opts?.selector,
+      defaultBudgetMs: opts?.defaultBudgetMs,
+      budgetsMs: opts?.budgetsMs,
+    })
+    return await completed
+  } finally {
+    unlistenPartial()
+    unlistenComplete()
+  }
+}
+
+// Same item returned by another provider
+export interface DuplicateSource {
+  plugin_id: string
+  id: string
+}
+
+// Search result item tagged with the provider (plugin id) it came from
+export interface AggregatedItem<T> {
+  plugin_id: string
+  also_on: DuplicateSource[]
+  item: T
+}
+
+export interface AggregatedSearchResult {
+  tracks: AggregatedItem<ProviderTrack>[]
+  albums: AggregatedItem<Record<string, unknown>>[]
+  artists: AggregatedItem<Record<string, unknown>>[]
+  playlists: AggregatedItem<Record<string, unknown>>[]
+  suggestions: string[]
+  providers: Record<string, ProviderSearchOutcome>
+}
+
+export interface AggregateSearchPartial {
+  search_id: string
+  provider: string
+  elapsed_ms: number
+  result: AggregatedSearchResult
+}
+
+export type AggregateSearchOptions = Omit<StreamedSearchOptions, 'selector'>
+
+// Search all enabled providers and merge duplicates across them; `onPartial` receives
+// the merged results so far each time a provider answers, the promise the final ones.
+export async function aggregateSearch(
+  term: string,
+  onPartial: (partial: AggregateSearchPartial) => void,
+  opts?: AggregateSearchOptions,
+): Promise<AggregatedSearchResult> {
+  const searchId = crypto.randomUUID()
+  const searchQuery: SearchQuery = {
+    query: term,
+    types: opts?.types || ["Track"],
+    page: opts?.page || null,
+    per_type_page: opts?.per_type_page || null,
+    sort: opts?.sort || null,
+    per_type_sort: opts?.per_type_sort || null,
+    filters: opts?.filters || {},
+    provider_params: opts?.provider_params || {}
+  }
+
+  // Subscribe before invoking so fast providers are not missed
+  const unlisten = await listen<AggregateSearchPartial>('aggregate-search-partial', (event) => {
+    if (event.payload.search_id === searchId) onPartial(event.payload)
+  })
+  try {
+    return await invoke<AggregatedSearchResult>('aggregate_search', {
+      searchId,
+      searchQuery,
+      defaultBudgetMs: opts?.defaultBudgetMs,
+      budgetsMs: opts?.budgetsMs,
+    })
+  } finally {
+    unlisten()
+  }
+}
+
 export async function musicStreamUrl(track: MediaContent, opts?: MaybeSelection): Promise<string> {
   const payload: Record<string, unknown> = { track }
   if (opts?.selector) payload.selector = opts.selector
   return invoke<string>('music_stream_url', payload)
 }
 
+// Mirrors music_plugin_sdk::types::RecommendationSeed; providers use the seeds they understand
+export interface RecommendationSeed {
+  track_ids?: string[]
+  artist_ids?: string[]
+  genres?: string[]
+  limit?: number | null
+}
+
+// Provider-side track as returned by media plugins
+export interface ProviderTrack {
+  id: string
+  provider: string | null
+  provider_id: string | null
+  title: string
+  artist: string
+  album: string | null
+  duration: number | null
+  cover_url: string | null
+  popularity: number | null
+}
+
+// Recommended tracks of a single provider (plugin id), most relevant first
+export async function musicGetRecommendations(providerId: string, seed: RecommendationSeed): Promise<ProviderTrack[]> {
+  return invoke<ProviderTrack[]>('music_get_recommendations', { providerId, seed })
+}
+
 // Convenience: build a selector from ids (runtime helper)
 export function singleSelector(id: string): MusicSelection {
   return { mode: 'single', ids: [id] }
@@ -0,0 +1,52 @@
+import { invoke } from '~/lib/tauri-command'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+
+export type NetworkClass = 'unknown' | 'wifi' | 'ethernet' | 'cellular'
+export type CellularStreamQuality = 'low' | 'medium' | 'high' | 'unrestricted'
+
+/** Restrictions in force for the current connection */
+export interface DataPolicy {
+  restricted: boolean
+  streamQuality: CellularStreamQuality | null
+  allowDownloads: boolean
+  allowPrefetch: boolean
+  highResArtwork: boolean
+}
+
+/** Bytes fetched per category */
+export interface DataUsageCounts {
+  streaming: number
+  downloads: number
+  artwork: number
+}
+
+export interface DataUsageReport {
+  networkClass: NetworkClass
+  since: string
+  cellular: DataUsageCounts
+  other: DataUsageCounts
+  policy: DataPolicy
+}
+
+class NetworkService {
+  /**
+   * Report the connection class; `metered` defaults to cellular.
+   * Returns the restrictions now in force.
+   */
+  async setNetworkClass(networkClass: NetworkClass, metered?: boolean): Promise<DataPolicy> {
+    return invoke<DataPolicy>('set_network_class', { networkClass, metered })
+  }
+
+  /** Data fetched in this session; `reset` starts a new count afterwards */
+  async getDataUsage(reset?: boolean): Promise<DataUsageReport> {
+    return invoke<DataUsageReport>('get_data_usage', { reset })
+  }
+
+  /** Subscribe to changes of the restrictions (connection or settings changed) */
+  onPolicyChanged(callback: (policy: DataPolicy) => void): Promise<UnlistenFn> {
+    return listen<DataPolicy>('network-policy-changed', (event) => callback(event.payload))
+  }
+}
+
+export const networkService = new NetworkService()
+export default networkService
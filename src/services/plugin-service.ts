@@ -1,4 +1,6 @@
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '~/lib/tauri-command';
+import type { PageInput, SearchResult, Track } from '~/types/sdk-search';
+import type { TrackPageInfo } from './music-api';
 
 // Plugin information structure
 export interface PluginInfo {
@@ -36,6 +38,104 @@ export interface PluginInfo {
   icon?: string;
 }
 
+// Health of a provider's mirror endpoint
+export interface EndpointStatus {
+  plugin_id: string;
+  base_url: string;
+  /// Result of the last health check
+  healthy: boolean;
+  /// Whether the provider uses the mirror (false: fell back to the default endpoint)
+  active: boolean;
+  error?: string | null;
+  checked_at: string;
+}
+
+export interface PluginInstall {
+  plugin_id: string;
+  name: string;
+  version: string;
+  plugin_dir: string;
+  enabled: boolean;
+  /// Whether an installed version was replaced
+  replaced: boolean;
+}
+
+// JSON schema a plugin declares for its settings (object with typed properties)
+export interface PluginConfigProperty {
+  type?: 'string' | 'boolean' | 'number' | 'integer' | 'array' | 'object';
+  title?: string;
+  description?: string;
+  default?: unknown;
+  enum?: string[];
+  /// 'password' for secrets
+  format?: string;
+  minimum?: number;
+  maximum?: number;
+}
+
+export interface PluginConfigSchema {
+  type?: 'object';
+  properties?: Record<string, PluginConfigProperty>;
+  required?: string[];
+  /// Display order of the properties
+  'x-order'?: string[];
+}
+
+export type PluginConfigValues = Record<string, unknown>;
+
+// Credential injected by the host into the requests of a sandboxed plugin
+export interface PluginCredential {
+  placement: 'bearer' | 'header' | 'cookie' | 'query';
+  /// Header or query parameter name, for 'header' and 'query'
+  name?: string;
+  value: string;
+  /// Hosts the credential may be sent to; any allowed host when empty
+  hosts?: string[];
+}
+
+// Request a plugin made through the host fetch proxy
+export interface NetworkAuditEntry {
+  at: string;
+  method: string;
+  host: string;
+  path: string;
+  credential: string | null;
+  status: number | null;
+  request_bytes: number;
+  response_bytes: number;
+  duration_ms: number;
+  denied: boolean;
+  error: string | null;
+}
+
+// Spotify sign-in in progress: open verification_uri and enter user_code
+export interface SpotifyDeviceLogin {
+  device_code: string;
+  user_code: string;
+  verification_uri: string;
+  verification_uri_complete: string | null;
+  expires_at: string;
+}
+
+export interface SpotifyLoginStatus {
+  status: 'WaitingForScan' | 'WaitingForConfirmation' | 'Success' | 'Expired' | 'Failed';
+  user_info: SpotifyAccount | null;
+  error_message: string | null;
+}
+
+export interface SpotifyAccount {
+  user_id: string;
+  display_name: string | null;
+  avatar_url: string | null;
+  metadata: Record<string, string>;
+}
+
+// One page of a YouTube playlist or channel; pass page.next_cursor as cursor for the next one
+export interface YoutubeTrackPage {
+  items: Track[];
+  page: TrackPageInfo;
+}
+
 class PluginService {
   // Get all plugins
   async getPlugins(): Promise<PluginInfo[]> {
@@ -108,6 +208,99 @@ class PluginService {
       throw error;
     }
   }
+
+  // Download, verify and install a plugin package (zip with manifest.json)
+  async installPluginFromUrl(url: string, options?: { sha256?: string; enable?: boolean }): Promise<PluginInstall> {
+    return await invoke<PluginInstall>('install_plugin_from_url', { url, sha256: options?.sha256, enable: options?.enable });
+  }
+
+  // Verify and install a plugin package from a local archive
+  async installPluginFromArchive(archivePath: string, options?: { sha256?: string; enable?: boolean }): Promise<PluginInstall> {
+    return await invoke<PluginInstall>('install_plugin_from_archive', {
+      archive_path: archivePath,
+      archivePath,
+      sha256: options?.sha256,
+      enable: options?.enable,
+    });
+  }
+
+  // Settings schema of a plugin, null when it has no settings
+  async getPluginConfigSchema(pluginId: string): Promise<PluginConfigSchema | null> {
+    return await invoke<PluginConfigSchema | null>('get_plugin_config_schema', { plugin_id: pluginId, pluginId });
+  }
+
+  // Configuration in effect: schema defaults overlaid with the saved values
+  async getPluginConfig(pluginId: string): Promise<PluginConfigValues> {
+    return await invoke<PluginConfigValues>('get_plugin_config', { plugin_id: pluginId, pluginId });
+  }
+
+  // Validate, apply and save a plugin's configuration; null clears a setting
+  async setPluginConfig(pluginId: string, values: PluginConfigValues): Promise<PluginConfigValues> {
+    return await invoke<PluginConfigValues>('set_plugin_config', { plugin_id: pluginId, pluginId, values });
+  }
+
+  // Store or remove (null) a named credential of a plugin; returns the credential names
+  async setPluginCredential(pluginId: string, name: string, credential: PluginCredential | null): Promise<string[]> {
+    return await invoke<string[]>('set_plugin_credential', { plugin_id: pluginId, pluginId, name, credential });
+  }
+
+  // Names of the credentials stored for a plugin
+  async getPluginCredentials(pluginId: string): Promise<string[]> {
+    return await invoke<string[]>('get_plugin_credentials', { plugin_id: pluginId, pluginId });
+  }
+
+  // Requests a plugin made through the host in this session, newest first
+  async getPluginNetworkAudit(pluginId: string, clear = false): Promise<NetworkAuditEntry[]> {
+    return await invoke<NetworkAuditEntry[]>('get_plugin_network_audit', { plugin_id: pluginId, pluginId, clear });
+  }
+
+  // Set or clear (empty URL) the endpoint override of a provider; rejects invalid URLs
+  async setProviderEndpoint(pluginId: string, baseUrl: string, fallback: boolean): Promise<void> {
+    await invoke('set_provider_endpoint', { plugin_id: pluginId, pluginId, baseUrl, fallback });
+  }
+
+  // Start signing in to Spotify with a device code
+  async spotifyLoginStart(): Promise<SpotifyDeviceLogin> {
+    return await invoke<SpotifyDeviceLogin>('spotify_login_start');
+  }
+
+  // Poll until the user approved the sign-in; the tokens stay in the backend
+  async spotifyLoginPoll(deviceCode: string): Promise<SpotifyLoginStatus> {
+    return await invoke<SpotifyLoginStatus>('spotify_login_poll', { deviceCode });
+  }
+
+  async spotifyLogout(): Promise<void> {
+    await invoke('spotify_logout');
+  }
+
+  // Signed-in Spotify account, null when signed out or Spotify is disabled
+  async getSpotifyAccount(): Promise<SpotifyAccount | null> {
+    return await invoke<SpotifyAccount | null>('spotify_account');
+  }
+
+  // Tracks of a pasted YouTube video, playlist or channel link; null when it isn't one
+  async youtubeResolveUrl(url: string): Promise<SearchResult | null> {
+    return await invoke<SearchResult | null>('youtube_resolve_url', { url });
+  }
+
+  async youtubePlaylistTracks(playlistId: string, page?: PageInput): Promise<YoutubeTrackPage> {
+    return await invoke<YoutubeTrackPage>('youtube_playlist_tracks', { playlistId, page });
+  }
+
+  // Uploads of a channel ("UC…" id or "@handle"), newest first
+  async youtubeChannelTracks(channelId: string, page?: PageInput): Promise<YoutubeTrackPage> {
+    return await invoke<YoutubeTrackPage>('youtube_channel_tracks', { channelId, page });
+  }
+
+  // Mirror health of every provider with an endpoint override
+  async getProviderEndpointStatus(): Promise<EndpointStatus[]> {
+    try {
+      return await invoke<EndpointStatus[]>('get_provider_endpoint_status');
+    } catch (error) {
+      console.error('[PluginService] 获取接口状态失败:', error);
+      throw error;
+    }
+  }
 }
 
 // Export singleton instance
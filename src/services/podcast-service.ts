@@ -0,0 +1,90 @@
+import { invoke } from '~/lib/tauri-command'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import type { QueueAction } from './audio-service'
+
+export interface Podcast {
+  id: string
+  feedUrl: string
+  title: string
+  author: string | null
+  description: string | null
+  imageUrl: string | null
+  link: string | null
+  /** Unix time in ms of the latest successful refresh */
+  refreshedAt: number | null
+  /** Error of the latest refresh */
+  lastError: string | null
+  episodeCount: number
+  unplayedCount: number
+}
+
+export interface PodcastEpisode {
+  id: string
+  podcastId: string
+  guid: string
+  title: string
+  description: string | null
+  audioUrl: string
+  mimeType: string | null
+  /** Seconds */
+  duration: number | null
+  /** Unix time in ms */
+  publishedAt: number | null
+  /** Resume position in seconds */
+  position: number
+  played: boolean
+}
+
+export interface EpisodeQuery {
+  podcastId?: string
+  unplayed?: boolean
+  limit?: number
+  offset?: number
+}
+
+class PodcastService {
+  /** Subscribe to an RSS/Atom feed; subscribing again refreshes it */
+  async subscribe(feedUrl: string): Promise<Podcast> {
+    return invoke<Podcast>('subscribe_podcast', { feedUrl })
+  }
+
+  async unsubscribe(podcastId: string): Promise<void> {
+    return invoke<void>('unsubscribe_podcast', { podcastId })
+  }
+
+  async listPodcasts(): Promise<Podcast[]> {
+    return invoke<Podcast[]>('list_podcasts')
+  }
+
+  /** Refresh all subscriptions now; resolves to the number of new episodes */
+  async refresh(): Promise<number> {
+    return invoke<number>('refresh_podcasts')
+  }
+
+  /** Episodes, newest first */
+  async listEpisodes(query: EpisodeQuery = {}): Promise<PodcastEpisode[]> {
+    return invoke<PodcastEpisode[]>('list_episodes', { ...query })
+  }
+
+  /** Play or queue an episode; it resumes where it was left off */
+  async playEpisode(episodeId: string, action?: QueueAction): Promise<void> {
+    return invoke<void>('play_episode', { episodeId, action })
+  }
+
+  async setPlayed(episodeId: string, played: boolean): Promise<void> {
+    return invoke<void>('set_episode_played', { episodeId, played })
+  }
+
+  /** Download through the download manager; false if already queued or downloaded */
+  async downloadEpisode(episodeId: string): Promise<boolean> {
+    return invoke<boolean>('download_episode', { episodeId })
+  }
+
+  /** New episodes found by a background refresh */
+  onUpdated(callback: (update: { podcastId: string; added: number }) => void): Promise<UnlistenFn> {
+    return listen<{ podcastId: string; added: number }>('podcasts-updated', (event) => callback(event.payload))
+  }
+}
+
+export const podcastService = new PodcastService()
+export default podcastService
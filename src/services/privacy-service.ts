@@ -0,0 +1,29 @@
+import { invoke } from '~/lib/tauri-command'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+
+export interface PrivateSessionStatus {
+  active: boolean
+  purge_on_exit: boolean
+}
+
+class PrivacyService {
+  /**
+   * Start or end a private session: play history and scrobbling are suspended while active.
+   * `purgeOnExit` clears the history recorded since launch when the app quits.
+   */
+  async setPrivateSession(enabled: boolean, purgeOnExit?: boolean): Promise<PrivateSessionStatus> {
+    return invoke<PrivateSessionStatus>('set_private_session', { enabled, purgeOnExit })
+  }
+
+  async getPrivateSession(): Promise<PrivateSessionStatus> {
+    return invoke<PrivateSessionStatus>('get_private_session')
+  }
+
+  /** Subscribe to private session changes, e.g. to show an indicator */
+  onChanged(callback: (status: PrivateSessionStatus) => void): Promise<UnlistenFn> {
+    return listen<PrivateSessionStatus>('private-session-changed', (event) => callback(event.payload))
+  }
+}
+
+export const privacyService = new PrivacyService()
+export default privacyService
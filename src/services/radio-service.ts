@@ -0,0 +1,43 @@
+import { invoke } from '~/lib/tauri-command'
+import type { QueueAction } from './audio-service'
+
+export interface RadioStation {
+  id: string
+  name: string
+  streamUrl: string
+  homepage: string | null
+  favicon: string | null
+  tags: string[]
+  countryCode: string | null
+  language: string | null
+  codec: string | null
+  bitrate: number | null
+  hls: boolean
+}
+
+export interface RadioSearch {
+  name?: string
+  tag?: string
+  /** ISO 3166-1 alpha-2 */
+  countryCode?: string
+  limit?: number
+  offset?: number
+}
+
+class RadioService {
+  /** Search the station directory, most voted first */
+  async searchStations(search: RadioSearch = {}): Promise<RadioStation[]> {
+    return invoke<RadioStation[]>('search_radio_stations', { ...search })
+  }
+
+  /**
+   * Play a station now, or queue it. Next/previous switch between the stations
+   * of the queue; the stream title arrives as a `StreamTitleChanged` audio event.
+   */
+  async playStation(station: RadioStation, action?: QueueAction): Promise<void> {
+    return invoke<void>('play_radio_station', { station, action })
+  }
+}
+
+export const radioService = new RadioService()
+export default radioService
@@ -1,6 +1,61 @@
-import { invoke } from '@tauri-apps/api/core'
+import { invoke } from '~/lib/tauri-command'
 import { listen } from '@tauri-apps/api/event'
-import type { MediaContent } from '~/types/bindings'
+import type { MediaContent, ScanExtension } from '~/types/bindings'
+
+export interface FormatUsage {
+  extension: string
+  files: number
+  bytes: number
+}
+
+export interface ScanEstimate {
+  files: number
+  bytes: number
+  playlists: number
+  formats: FormatUsage[]
+  skipped: number
+}
+
+export interface LibraryStorageReport {
+  library_bytes: number
+  library_tracks: number
+  cache_bytes: number
+  artwork_bytes: number
+  downloads_bytes: number
+}
+
+// Scanned track the library failed to store; nextAttemptAt is null once retries are exhausted
+export interface FailedScanItem {
+  path: string | null
+  title: string | null
+  attempts: number
+  lastError: string
+  nextAttemptAt: string | null
+}
+
+export type ScanProgressPhase = 'discovery' | 'metadata' | 'thumbnails' | 'dbWrite'
+
+export interface PhaseProgress {
+  phase: ScanProgressPhase
+  total: number
+  processed: number
+}
+
+// Payload of the scan-progress event; jobId is the token taken by cancelScan
+export interface ScanProgress {
+  jobId: string
+  phase: ScanProgressPhase
+  phases: PhaseProgress[]
+  currentFile: string | null
+  etaSecs: number | null
+  cancelled: boolean
+  finished: boolean
+}
+
+export interface AutoScannerStatus {
+  state: string
+  progress: ScanProgress | null
+}
 
 class ScannerService {
   private isInitialized = false
@@ -15,7 +70,7 @@ class ScannerService {
 
     try {
       // 监听后端扫描事件
-      await listen('scan-progress', (event) => {
+      await listen<ScanProgress>('scan-progress', (event) => {
         this.emitEvent('scan-progress', event.payload)
       })
 
@@ -82,10 +137,12 @@ class ScannerService {
     }
   }
 
-  async triggerManualScan(paths?: string[]): Promise<void> {
+  /** Start a scan; resolves to the token that cancels it */
+  async triggerManualScan(paths?: string[]): Promise<string> {
     try {
-      await invoke('trigger_manual_scan', paths ? { paths } : undefined)
-      this.emitEvent('scan-triggered', { paths })
+      const token = await invoke<string>('trigger_manual_scan', paths ? { paths } : undefined)
+      this.emitEvent('scan-triggered', { paths, token })
+      return token
     } catch (error) {
       console.error('[ScannerService] triggerManualScan error:', error)
       this.emitEvent('scanner-error', error)
@@ -93,13 +150,17 @@ class ScannerService {
     }
   }
 
-  async getStatus(): Promise<string> {
+  /** Cancel the scan of `token`, or the running scan; resolves to whether there was one */
+  async cancelScan(token?: string): Promise<boolean> {
+    return invoke<boolean>('cancel_scan', { token: token ?? null })
+  }
+
+  async getStatus(): Promise<AutoScannerStatus> {
     try {
-      const status = await invoke<string>('get_auto_scanner_status')
-      return status
+      return await invoke<AutoScannerStatus>('get_auto_scanner_status')
     } catch (error) {
       console.error('[ScannerService] getStatus error:', error)
-      return 'Error'
+      return { state: 'Error', progress: null }
     }
   }
 
@@ -113,6 +174,33 @@ class ScannerService {
     }
   }
 
+  /** Count the tracks under `paths` and their size per format, without scanning them */
+  async estimateScan(paths: string[]): Promise<ScanEstimate> {
+    return invoke<ScanEstimate>('estimate_scan', { paths })
+  }
+
+  async getStorageReport(): Promise<LibraryStorageReport> {
+    return invoke<LibraryStorageReport>('get_library_storage_report')
+  }
+
+  async getFailedScanItems(): Promise<FailedScanItem[]> {
+    return invoke<FailedScanItem[]>('get_failed_scan_items')
+  }
+
+  async retryFailedScanItems(): Promise<void> {
+    return invoke<void>('retry_failed_scan_items')
+  }
+
+  /** Extensions the scanner knows of and whether each is scanned */
+  async getScanExtensions(): Promise<ScanExtension[]> {
+    return invoke<ScanExtension[]>('get_scan_extensions')
+  }
+
+  /** Replace the scanned extension list; rejects when an entry is invalid. Returns the stored list */
+  async setScanExtensions(extensions: ScanExtension[]): Promise<ScanExtension[]> {
+    return invoke<ScanExtension[]>('set_scan_extensions', { extensions })
+  }
+
   async cleanup(): Promise<void> {
     try {
       this.eventListeners.clear()
@@ -1,4 +1,4 @@
-import { invoke } from '@tauri-apps/api/core'
+import { invoke } from '~/lib/tauri-command'
 import { listen as tauriListen } from '@tauri-apps/api/event'
 import { debounce } from 'es-toolkit/compat'
 
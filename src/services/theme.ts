@@ -1,4 +1,4 @@
-import { invoke } from '@tauri-apps/api/core'
+import { invoke } from '~/lib/tauri-command'
 import type { ThemeDetails } from '~/types/theme'
 
 export const ThemeCommands = {
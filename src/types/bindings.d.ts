@@ -10,10 +10,23 @@ export type BackgroundLayerBase = { opacity: number | null, blend_mode: string |
 
 export type ColorStop = { color: string, pos: number | null, };
 
+export type CommandError = { code: ErrorCode, message: string, retryable: boolean, };
+
+/**
+ * Envelope returned by every Tauri command: `{ ok: true, data }` on success,
+ * `{ ok: false, error }` on failure. Built by `macros::command_envelope!`.
+ */
+export type CommandResponse<T> = { ok: boolean, data?: T, error?: CommandError, };
+
 export type ConicLayer = { type: LayerType, angle: number | null, at: string | null, stops: Array<ColorStop>, opacity: number | null, blend_mode: string | null, position: string | null, size: string | null, repeat: string | null, };
 
 export type EntityInfo = string;
 
+/**
+ * Stable, machine-readable error category sent to the frontend
+ */
+export type ErrorCode = "io" | "json" | "playback" | "database" | "network" | "auth" | "file_system" | "media" | "config" | "parse" | "validation" | "provider" | "extension" | "cache" | "webview" | "plugin" | "mpris" | "switch_providers" | "invalidated_cache" | "unknown";
+
 export type GeneralSettings = { language: string | null, minimizeToTray: boolean | null, launchAtLogin: boolean | null, 
 /**
  * Whether to automatically scan on app start.
@@ -46,7 +59,7 @@ export type LyricsSettings = { playerImplementation: string | '', fontFamily: st
 
 export type PlayerMode = "Sequential" | "Single" | "Shuffle" | "ListLoop";
 
-export type PlayerState = "PLAYING" | "PAUSED" | "STOPPED" | "LOADING";
+export type PlayerState = "IDLE" | "PLAYING" | "PAUSED" | "STOPPED" | "LOADING" | "ERRORED";
 
 export type ProviderInstancePref = { key: string, kind: ProviderKind, enabled: boolean, cfg: Record<string, any>, secure_ref: string | null, };
 